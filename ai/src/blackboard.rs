@@ -0,0 +1,387 @@
+//! What a team's bots know between them.
+//!
+//! The manager keeps one blackboard per team. Bots stake a claim on the
+//! resource they're heading for and the station they're taking, enemies the
+//! team has seen are pinned up with the tick they were last seen, and each
+//! of the team's mechs lists what it needs. Claims lapse unless the bot
+//! keeps renewing them, so one that changes its mind or dies frees its
+//! target for the rest. Utility scoring marks down anything someone else
+//! has claimed, so two bots don't go after the same thing.
+
+use crate::{Decision, GameView, TaskAction};
+use shared::*;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// A bot's hold on a target until `expires`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Claim {
+    pub ai_id: Uuid,
+    pub expires: u64,
+}
+
+/// Where an enemy was last seen
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sighting {
+    pub position: WorldPos,
+    pub tick: u64,
+    pub is_mech: bool,
+}
+
+/// Something one of the team's mechs is waiting on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MechNeed {
+    Repair,
+    Crew { station_type: StationType },
+}
+
+/// Stations that can't be left empty without the mech suffering for it
+const CRITICAL_STATIONS: [StationType; 2] = [StationType::Pilot, StationType::Engine];
+
+#[derive(Debug, Clone, Default)]
+pub struct TeamBlackboard {
+    tick: u64,
+    resources: HashMap<Uuid, (WorldPos, Claim)>,
+    stations: HashMap<Uuid, Claim>,
+    /// The team's unmanned stations
+    open_stations: Vec<(Uuid, StationType)>,
+    enemies: HashMap<Uuid, Sighting>,
+    mech_needs: HashMap<Uuid, Vec<MechNeed>>,
+}
+
+impl TeamBlackboard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Lapse old claims and sightings and take in what `team` sees now
+    pub fn update(&mut self, game_view: &GameView, team: TeamId) {
+        let tick = game_view.tick;
+        self.tick = tick;
+        self.resources.retain(|id, (_, claim)| {
+            claim.expires > tick && game_view.resources.iter().any(|r| r.id == *id)
+        });
+        self.stations.retain(|_, claim| claim.expires > tick);
+
+        for mech in game_view.mechs.iter().filter(|m| m.team != team) {
+            self.sighted(mech.id, mech.position, true);
+        }
+        for player in game_view.players.iter().filter(|p| p.team != team) {
+            if let PlayerLocation::OutsideWorld(pos) = player.location {
+                self.sighted(player.id, pos, false);
+            }
+        }
+        self.enemies
+            .retain(|_, sighting| tick.saturating_sub(sighting.tick) <= AI_SIGHTING_TICKS);
+
+        self.open_stations.clear();
+        self.mech_needs.clear();
+        for mech in game_view.mechs.iter().filter(|m| m.team == team) {
+            let mut needs = Vec::new();
+            if (mech.health as f32) < mech.max_health as f32 * AI_REPAIR_NEED_HEALTH {
+                needs.push(MechNeed::Repair);
+            }
+            for station in mech.stations.iter().filter(|s| s.operated_by.is_none()) {
+                self.open_stations.push((station.id, station.station_type));
+                let need = MechNeed::Crew {
+                    station_type: station.station_type,
+                };
+                if CRITICAL_STATIONS.contains(&station.station_type) && !needs.contains(&need) {
+                    needs.push(need);
+                }
+            }
+            self.mech_needs.insert(mech.id, needs);
+        }
+    }
+
+    fn sighted(&mut self, id: Uuid, position: WorldPos, is_mech: bool) {
+        self.enemies.insert(
+            id,
+            Sighting {
+                position,
+                tick: self.tick,
+                is_mech,
+            },
+        );
+    }
+
+    fn claim(&self, ai_id: Uuid) -> Claim {
+        Claim {
+            ai_id,
+            expires: self.tick + AI_CLAIM_TICKS,
+        }
+    }
+
+    /// Claim a resource for `ai_id`, dropping any other it held. False if
+    /// another bot already has it.
+    pub fn claim_resource(&mut self, resource_id: Uuid, position: WorldPos, ai_id: Uuid) -> bool {
+        if self
+            .resources
+            .get(&resource_id)
+            .is_some_and(|(_, claim)| claim.ai_id != ai_id)
+        {
+            return false;
+        }
+        self.resources
+            .retain(|id, (_, claim)| claim.ai_id != ai_id || *id == resource_id);
+        let claim = self.claim(ai_id);
+        self.resources.insert(resource_id, (position, claim));
+        true
+    }
+
+    /// Claim a station for `ai_id`, dropping any other it held. False if
+    /// another bot already has it.
+    pub fn claim_station(&mut self, station_id: Uuid, ai_id: Uuid) -> bool {
+        if self.station_claimed_by_other(station_id, ai_id) {
+            return false;
+        }
+        self.stations
+            .retain(|id, claim| claim.ai_id != ai_id || *id == station_id);
+        let claim = self.claim(ai_id);
+        self.stations.insert(station_id, claim);
+        true
+    }
+
+    /// Give up everything `ai_id` has claimed
+    pub fn release(&mut self, ai_id: Uuid) {
+        self.resources.retain(|_, (_, claim)| claim.ai_id != ai_id);
+        self.stations.retain(|_, claim| claim.ai_id != ai_id);
+    }
+
+    /// Whether another bot is after the resource at `position`
+    pub fn resource_claimed_by_other(&self, position: WorldPos, ai_id: Uuid) -> bool {
+        self.resources.values().any(|(claimed_at, claim)| {
+            claim.ai_id != ai_id && claimed_at.distance_to(position) < TILE_SIZE / 2.0
+        })
+    }
+
+    pub fn station_claimed_by_other(&self, station_id: Uuid, ai_id: Uuid) -> bool {
+        self.stations
+            .get(&station_id)
+            .is_some_and(|claim| claim.ai_id != ai_id)
+    }
+
+    /// An empty station of this type nobody else has claimed
+    pub fn open_station(&self, station_type: StationType, ai_id: Uuid) -> Option<Uuid> {
+        self.open_stations
+            .iter()
+            .filter(|(_, open_type)| *open_type == station_type)
+            .map(|(id, _)| *id)
+            .find(|id| !self.station_claimed_by_other(*id, ai_id))
+    }
+
+    /// Enemies seen lately and where, by id
+    pub fn known_enemies(&self) -> impl Iterator<Item = (&Uuid, &Sighting)> {
+        self.enemies.iter()
+    }
+
+    pub fn mech_needs(&self, mech_id: Uuid) -> &[MechNeed] {
+        self.mech_needs.get(&mech_id).map_or(&[], Vec::as_slice)
+    }
+
+    /// Whether any of the team's mechs needs this
+    pub fn needs(&self, need: MechNeed) -> bool {
+        self.mech_needs.values().flatten().any(|n| *n == need)
+    }
+
+    /// Stake or renew claims on what `ai_id` just decided to go after, and
+    /// let go of what it no longer wants
+    pub fn record_decision(&mut self, game_view: &GameView, ai_id: Uuid, decision: &Decision) {
+        match &decision.chosen_action {
+            Some(TaskAction::MoveToPosition { target, .. }) => {
+                let resource = game_view
+                    .resources
+                    .iter()
+                    .find(|r| r.position.distance_to(*target) < TILE_SIZE / 2.0);
+                self.stations.retain(|_, claim| claim.ai_id != ai_id);
+                match resource {
+                    Some(resource) => {
+                        self.claim_resource(resource.id, resource.position, ai_id);
+                    }
+                    None => self.resources.retain(|_, (_, claim)| claim.ai_id != ai_id),
+                }
+            }
+            Some(TaskAction::OperateStation { station_type }) => {
+                self.resources.retain(|_, (_, claim)| claim.ai_id != ai_id);
+                let held = self
+                    .stations
+                    .iter()
+                    .find(|(_, claim)| claim.ai_id == ai_id)
+                    .map(|(id, _)| *id)
+                    .filter(|id| {
+                        self.open_stations
+                            .iter()
+                            .any(|(open, open_type)| open == id && open_type == station_type)
+                    });
+                if let Some(station_id) = held.or_else(|| self.open_station(*station_type, ai_id)) {
+                    self.claim_station(station_id, ai_id);
+                }
+            }
+            _ => self.release(ai_id),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AIConfig, AIManager, MechView, ResourceView, SimpleAI, StationView, TeamInfo};
+
+    fn view(tick: u64, resources: Vec<ResourceView>, mechs: Vec<MechView>) -> GameView {
+        GameView {
+            tick,
+            players: Vec::new(),
+            mechs,
+            resources,
+            projectiles: Vec::new(),
+            team_info: TeamInfo {
+                team_id: TeamId::Red,
+                player_count: 0,
+                mech_count: 0,
+                total_resources: HashMap::new(),
+            },
+        }
+    }
+
+    fn resource(x: f32) -> ResourceView {
+        ResourceView {
+            id: Uuid::new_v4(),
+            position: WorldPos::new(x, 0.0),
+            resource_type: ResourceType::ScrapMetal,
+        }
+    }
+
+    fn red_mech(health: u32, max_health: u32) -> MechView {
+        MechView {
+            id: Uuid::new_v4(),
+            team: TeamId::Red,
+            position: WorldPos::new(0.0, 0.0),
+            health,
+            max_health,
+            shield: 0,
+            velocity: (0.0, 0.0),
+            turret: MechTurret::default(),
+            stations: vec![StationView {
+                id: Uuid::new_v4(),
+                station_type: StationType::Engine,
+                operated_by: None,
+                position: TilePos::new(1, 1),
+                floor: 0,
+            }],
+            resource_inventory: HashMap::new(),
+            alarm: None,
+        }
+    }
+
+    #[test]
+    fn test_claims_keep_two_bots_off_the_same_target() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let (first, second) = (resource(0.0), resource(10.0 * TILE_SIZE));
+        let mut board = TeamBlackboard::new();
+        board.update(
+            &view(1, vec![first.clone(), second.clone()], Vec::new()),
+            TeamId::Red,
+        );
+
+        assert!(board.claim_resource(first.id, first.position, a));
+        assert!(!board.claim_resource(first.id, first.position, b));
+        assert!(board.resource_claimed_by_other(first.position, b));
+        assert!(!board.resource_claimed_by_other(first.position, a));
+
+        // Going after something else lets go of the first
+        assert!(board.claim_resource(second.id, second.position, a));
+        assert!(board.claim_resource(first.id, first.position, b));
+
+        let station = Uuid::new_v4();
+        assert!(board.claim_station(station, a));
+        assert!(!board.claim_station(station, b));
+        assert!(board.station_claimed_by_other(station, b));
+    }
+
+    #[test]
+    fn test_claims_and_sightings_lapse_unless_renewed() {
+        let ai = Uuid::new_v4();
+        let scrap = resource(0.0);
+        let resources = vec![scrap.clone()];
+        let mut board = TeamBlackboard::new();
+        board.update(&view(1, resources.clone(), Vec::new()), TeamId::Red);
+        board.claim_resource(scrap.id, scrap.position, ai);
+        let station = Uuid::new_v4();
+        board.claim_station(station, ai);
+
+        // Renewed just before it lapses, it holds for another stretch
+        let renewed = AI_CLAIM_TICKS;
+        board.update(&view(renewed, resources.clone(), Vec::new()), TeamId::Red);
+        assert!(board.resource_claimed_by_other(scrap.position, Uuid::new_v4()));
+        board.claim_resource(scrap.id, scrap.position, ai);
+
+        board.update(
+            &view(renewed + AI_CLAIM_TICKS, resources.clone(), Vec::new()),
+            TeamId::Red,
+        );
+        assert!(!board.resource_claimed_by_other(scrap.position, Uuid::new_v4()));
+        assert!(!board.station_claimed_by_other(station, Uuid::new_v4()));
+
+        // A claimed resource that's been picked up is let go of straight away
+        board.update(&view(200, resources, Vec::new()), TeamId::Red);
+        board.claim_resource(scrap.id, scrap.position, ai);
+        board.update(&view(201, Vec::new(), Vec::new()), TeamId::Red);
+        assert!(!board.resource_claimed_by_other(scrap.position, Uuid::new_v4()));
+
+        // Enemies stay pinned up for a while after they're out of sight
+        let mut enemy = red_mech(MECH_MAX_HEALTH, MECH_MAX_HEALTH);
+        enemy.team = TeamId::Blue;
+        board.update(&view(300, Vec::new(), vec![enemy]), TeamId::Red);
+        board.update(
+            &view(300 + AI_SIGHTING_TICKS, Vec::new(), Vec::new()),
+            TeamId::Red,
+        );
+        assert_eq!(board.known_enemies().count(), 1);
+        board.update(
+            &view(301 + AI_SIGHTING_TICKS, Vec::new(), Vec::new()),
+            TeamId::Red,
+        );
+        assert_eq!(board.known_enemies().count(), 0);
+    }
+
+    #[test]
+    fn test_removed_bots_release_their_claims() {
+        let mut manager = AIManager::new(AIConfig::default());
+        let ai = manager.add_controller(Box::new(SimpleAI::new(
+            Uuid::new_v4(),
+            crate::Personality::Balanced,
+            0.5,
+        )));
+        let scrap = resource(0.0);
+        let station = Uuid::new_v4();
+        let board = manager.blackboards.entry(TeamId::Red).or_default();
+        board.update(&view(1, vec![scrap.clone()], Vec::new()), TeamId::Red);
+        board.claim_resource(scrap.id, scrap.position, ai);
+        board.claim_station(station, ai);
+
+        manager.remove_ai(ai);
+        let other = Uuid::new_v4();
+        let board = manager.blackboard(TeamId::Red).unwrap();
+        assert!(!board.resource_claimed_by_other(scrap.position, other));
+        assert!(!board.station_claimed_by_other(station, other));
+    }
+
+    #[test]
+    fn test_repair_need_follows_the_mechs_own_max_health() {
+        let mut board = TeamBlackboard::new();
+        // A breached hull has less to lose before it needs patching
+        let breached = red_mech(50, 60);
+        board.update(&view(1, Vec::new(), vec![breached.clone()]), TeamId::Red);
+        assert!(!board.needs(MechNeed::Repair));
+        assert_eq!(
+            board.mech_needs(breached.id),
+            &[MechNeed::Crew {
+                station_type: StationType::Engine
+            }]
+        );
+
+        let hurt = red_mech(30, 60);
+        board.update(&view(2, Vec::new(), vec![hurt.clone()]), TeamId::Red);
+        assert!(board.mech_needs(hurt.id).contains(&MechNeed::Repair));
+    }
+}
@@ -0,0 +1,93 @@
+//! Answering a mech's alarm. When boarders set one of the team's mechs off,
+//! defenders outside head for it and those aboard go after the boarder,
+//! dashing across the floor to close in and swinging once in reach.
+
+use crate::{
+    AICommand, Decision, GameView, LocationRequirement, Perception, Task, TaskAction,
+    TaskRequirements,
+};
+use shared::*;
+use uuid::Uuid;
+
+/// What a defender can do about the first of the team's mechs sounding its
+/// alarm, outranking everything else a defender does
+pub fn alarm_tasks(perception: &Perception) -> Vec<Task> {
+    let Some(alarm) = perception.team_state.alarms.first() else {
+        return Vec::new();
+    };
+    vec![
+        Task {
+            name: "Answer Alarm".to_string(),
+            priority: 1.1,
+            action: TaskAction::MoveToPosition {
+                target: alarm.mech_position,
+                reason: "Boarders at one of our stations".to_string(),
+            },
+            requirements: TaskRequirements {
+                location: Some(LocationRequirement::Outside),
+                ..Default::default()
+            },
+        },
+        Task {
+            name: "Repel Boarder".to_string(),
+            priority: 1.1,
+            action: TaskAction::AttackTarget {
+                target_id: alarm.intruder,
+            },
+            requirements: TaskRequirements {
+                location: Some(LocationRequirement::InsideSpecificMech(alarm.mech_id)),
+                not_operating: true,
+                ..Default::default()
+            },
+        },
+    ]
+}
+
+/// Go for a target on the same mech floor hand to hand, in place of the
+/// weapon button attacking would otherwise press
+pub fn close_on_boarder(
+    game_view: &GameView,
+    ai_id: Uuid,
+    decision: &Decision,
+    commands: &mut Vec<AICommand>,
+) {
+    let Some(TaskAction::AttackTarget { target_id }) = decision.chosen_action else {
+        return;
+    };
+    let location = |id: Uuid| {
+        game_view
+            .players
+            .iter()
+            .find(|player| player.id == id)
+            .map(|player| player.location)
+    };
+    let (
+        Some(PlayerLocation::InsideMech { mech_id, pos }),
+        Some(PlayerLocation::InsideMech {
+            mech_id: target_mech,
+            pos: target_pos,
+        }),
+    ) = (location(ai_id), location(target_id))
+    else {
+        return;
+    };
+    if mech_id != target_mech || pos.floor != target_pos.floor {
+        return;
+    }
+
+    commands.retain(|command| !matches!(command, AICommand::PressButton { .. }));
+    if pos.tile_pos.distance_to(target_pos.tile_pos) <= MELEE_RANGE {
+        commands.push(AICommand::Melee { player_id: ai_id });
+        return;
+    }
+    let toward = (
+        (target_pos.tile_pos.x - pos.tile_pos.x) as f32,
+        (target_pos.tile_pos.y - pos.tile_pos.y) as f32,
+    );
+    commands.extend(
+        Facing::from_movement(toward).map(|direction| AICommand::Dash {
+            player_id: ai_id,
+            direction,
+        }),
+    );
+}
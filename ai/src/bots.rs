@@ -0,0 +1,99 @@
+use crate::{AIController, Personality, SimpleAI, UtilityAI};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// Builds a bot for a player id at a difficulty (0.0 = easy, 1.0 = hard)
+pub type BotFactory = fn(Uuid, f32) -> Box<dyn AIController>;
+
+/// A bot announced by the crate that defines it with [`register_bot!`].
+/// Every linked-in plugin is picked up by [`BotRegistry::new`].
+pub struct BotPlugin {
+    pub name: &'static str,
+    pub factory: BotFactory,
+}
+
+inventory::collect!(BotPlugin);
+
+/// Register a bot from its own crate, e.g.
+/// `ai::register_bot!("scavenger", Scavenger::boxed);`. The server spawns it
+/// by name once the crate is linked into the binary.
+#[macro_export]
+macro_rules! register_bot {
+    ($name:expr, $factory:expr) => {
+        $crate::inventory::submit! {
+            $crate::BotPlugin {
+                name: $name,
+                factory: $factory,
+            }
+        }
+    };
+}
+
+/// Named bot implementations the server can spawn. Comes with the built-in
+/// `simple` and `utility` AIs and every bot submitted with [`register_bot!`];
+/// add more by hand with [`BotRegistry::register`].
+pub struct BotRegistry {
+    factories: BTreeMap<String, BotFactory>,
+}
+
+impl BotRegistry {
+    pub fn new() -> Self {
+        let mut registry = Self {
+            factories: BTreeMap::new(),
+        };
+        registry.register("simple", |id, difficulty| {
+            Box::new(SimpleAI::new(id, Personality::Balanced, difficulty))
+        });
+        registry.register("utility", |id, difficulty| {
+            Box::new(UtilityAI::new(id, Personality::Balanced, difficulty))
+        });
+        for plugin in inventory::iter::<BotPlugin> {
+            registry.register(plugin.name, plugin.factory);
+        }
+        registry
+    }
+
+    /// Register a bot under `name`, replacing any bot already using it
+    pub fn register(&mut self, name: &str, factory: BotFactory) {
+        self.factories.insert(name.to_lowercase(), factory);
+    }
+
+    /// Build the bot registered under `name`, if any
+    pub fn create(&self, name: &str, id: Uuid, difficulty: f32) -> Option<Box<dyn AIController>> {
+        self.factories
+            .get(&name.to_lowercase())
+            .map(|factory| factory(id, difficulty))
+    }
+
+    /// Registered bot names in alphabetical order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.factories.keys().map(String::as_str)
+    }
+}
+
+impl Default for BotRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    register_bot!("test_plugin", |id, difficulty| {
+        Box::new(SimpleAI::new(id, Personality::Aggressive, difficulty))
+    });
+
+    #[test]
+    fn test_registered_plugins_join_the_builtin_bots() {
+        let registry = BotRegistry::new();
+        let names: Vec<&str> = registry.names().collect();
+        assert_eq!(names, vec!["simple", "test_plugin", "utility"]);
+
+        let id = Uuid::new_v4();
+        let bot = registry.create("Test_Plugin", id, 0.5).unwrap();
+        assert_eq!(bot.id(), id);
+        assert!(registry.create("missing", id, 0.5).is_none());
+    }
+}
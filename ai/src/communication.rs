@@ -1,6 +1,8 @@
+use crate::CommEvent;
 use chrono::{DateTime, Utc};
 use shared::*;
 use std::collections::VecDeque;
+use std::time::Instant;
 use uuid::Uuid;
 
 /// Message that AIs can send to each other
@@ -34,6 +36,30 @@ pub enum MessageType {
     Coordination { action: CoordinationAction },
 }
 
+impl MessageType {
+    /// Short description for observers, e.g. "Command: Retreat"
+    pub fn label(&self) -> String {
+        let (kind, detail) = match self {
+            MessageType::Command { order } => ("Command", variant_name(order)),
+            MessageType::StatusUpdate { status } => ("Status", variant_name(status)),
+            MessageType::Request { request_type } => ("Request", variant_name(request_type)),
+            MessageType::Intel { info } => ("Intel", variant_name(info)),
+            MessageType::Coordination { action } => ("Coordination", variant_name(action)),
+        };
+        format!("{kind}: {detail}")
+    }
+}
+
+/// The variant name from a derived Debug representation
+fn variant_name(value: &impl std::fmt::Debug) -> String {
+    let debug = format!("{value:?}");
+    debug
+        .split([' ', '{', '('])
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
 /// Orders that can be given (usually by captain)
 #[derive(Debug, Clone)]
 pub enum Order {
@@ -96,6 +122,12 @@ pub enum RequestType {
     NeedBackup {
         enemy_count: usize,
     },
+    /// A carrier hauling a resource home with enemies nearby
+    NeedEscort {
+        position: WorldPos,
+        resource_type: ResourceType,
+        enemy_count: usize,
+    },
     RequestRole {
         preferred_hat: String,
     },
@@ -156,6 +188,11 @@ pub struct CommunicationSystem {
     captain: Option<Uuid>,
     message_history: Vec<AIMessage>,
     max_history: usize,
+    /// Record of every message sent, for observers replaying the conversation
+    events: VecDeque<CommEvent>,
+    next_sequence: u64,
+    started: Instant,
+    tick: u64,
 }
 
 impl CommunicationSystem {
@@ -165,9 +202,18 @@ impl CommunicationSystem {
             captain: None,
             message_history: Vec::new(),
             max_history: 1000,
+            events: VecDeque::new(),
+            next_sequence: 0,
+            started: Instant::now(),
+            tick: 0,
         }
     }
 
+    /// Set the game tick that messages sent from now on are recorded under
+    pub fn set_tick(&mut self, tick: u64) {
+        self.tick = tick;
+    }
+
     /// Assign a captain
     pub fn assign_captain(&mut self, ai_id: Uuid) {
         self.captain = Some(ai_id);
@@ -201,6 +247,7 @@ impl CommunicationSystem {
             message.priority = MessagePriority::High;
         }
 
+        self.record_event(&message);
         self.messages.push_back(message.clone());
         self.message_history.push(message);
 
@@ -210,6 +257,33 @@ impl CommunicationSystem {
         }
     }
 
+    fn record_event(&mut self, message: &AIMessage) {
+        self.events.push_back(CommEvent {
+            sequence: self.next_sequence,
+            tick: self.tick,
+            time: self.started.elapsed().as_secs_f32(),
+            from: message.sender,
+            to: message.recipient,
+            from_captain: self.captain == Some(message.sender),
+            message_type: message.message_type.label(),
+            priority: format!("{:?}", message.priority),
+        });
+        self.next_sequence += 1;
+        if self.events.len() > self.max_history {
+            self.events.pop_front();
+        }
+    }
+
+    /// Recorded messages from `sequence` on, oldest first. Anything older
+    /// than the history limit is gone.
+    pub fn events_since(&self, sequence: u64) -> Vec<CommEvent> {
+        self.events
+            .iter()
+            .filter(|event| event.sequence >= sequence)
+            .cloned()
+            .collect()
+    }
+
     /// Get pending messages and clear the queue
     pub fn get_pending_messages(&mut self) -> Vec<AIMessage> {
         let now = Utc::now();
@@ -312,7 +386,9 @@ impl AIMessage {
                 Urgency::High => MessagePriority::High,
                 _ => MessagePriority::Normal,
             },
-            RequestType::NeedBackup { .. } => MessagePriority::High,
+            RequestType::NeedBackup { .. } | RequestType::NeedEscort { .. } => {
+                MessagePriority::High
+            }
             _ => MessagePriority::Normal,
         };
 
@@ -167,13 +167,13 @@ impl DifficultyModifiers {
         }
     }
 
-    /// Apply modifiers to a decision
-    pub fn apply(&self, decision: &mut Decision) {
+    /// Apply modifiers to a decision, rolling for mistakes with `rng`
+    pub fn apply(&self, decision: &mut Decision, rng: &mut impl rand::Rng) {
         // Reduce confidence based on accuracy
         decision.confidence *= self.accuracy;
 
         // Sometimes make mistakes
-        if rand::random::<f32>() < self.mistake_chance {
+        if rng.gen::<f32>() < self.mistake_chance {
             decision.confidence *= 0.5;
             decision.reasoning.push_str(" [Mistake]");
         }
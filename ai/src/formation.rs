@@ -0,0 +1,558 @@
+//! Group movement for AIs out on foot.
+//!
+//! After every AI has decided, the manager plans formations across the whole
+//! team: travelling AIs near each other fall in behind a leader in a wedge,
+//! combat AIs peel off to guard teammates hauling resources, and every
+//! movement command gets a separation push so bots don't stack up on the
+//! same tile.
+
+use crate::{AICommand, Decision, GameView, Hat, TaskAction};
+use shared::*;
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+/// An AI's part in a group this update
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FormationRole {
+    /// Picks the route; the rest of the squad keeps station on it
+    Leader,
+    /// Holds a slot in the leader's wedge
+    Follower { leader: Uuid, slot: usize },
+    /// Stays beside a resource carrier, between it and the nearest enemy
+    Escort { carrier: Uuid },
+}
+
+/// Where a grouped AI should be standing
+#[derive(Debug, Clone, Copy)]
+pub struct FormationOrder {
+    pub role: FormationRole,
+    pub target: WorldPos,
+}
+
+impl FormationOrder {
+    /// Replace the AI's own plan with its place in the group. Leaders keep
+    /// their route.
+    pub fn apply(&self, decision: &mut Decision) {
+        let reason = match self.role {
+            FormationRole::Leader => {
+                decision.reasoning.push_str(" [Leading squad]");
+                return;
+            }
+            FormationRole::Follower { slot, .. } => format!("Holding formation slot {slot}"),
+            FormationRole::Escort { carrier } => format!("Escorting carrier {carrier}"),
+        };
+        decision.reasoning.push_str(&format!(" [{reason}]"));
+        decision.chosen_action = Some(TaskAction::MoveToPosition {
+            target: self.target,
+            reason,
+        });
+    }
+}
+
+/// An AI outside the mechs, as the planner sees it
+struct Traveller {
+    id: Uuid,
+    team: TeamId,
+    position: WorldPos,
+    hat: Option<Hat>,
+    carrying: bool,
+    destination: Option<WorldPos>,
+}
+
+/// Group up every AI on foot, given what each one decided to do and which
+/// carriers have called for an escort
+pub fn plan_formations(
+    game_view: &GameView,
+    decisions: &[(Uuid, Option<Hat>, &Decision)],
+    escort_calls: &HashSet<Uuid>,
+) -> HashMap<Uuid, FormationOrder> {
+    let mut travellers: Vec<Traveller> = decisions
+        .iter()
+        .filter_map(|(id, hat, decision)| {
+            let player = game_view.players.iter().find(|p| p.id == *id)?;
+            let PlayerLocation::OutsideWorld(position) = player.location else {
+                return None;
+            };
+            let destination = match &decision.chosen_action {
+                Some(TaskAction::MoveToPosition { target, .. }) => Some(*target),
+                _ => None,
+            };
+            Some(Traveller {
+                id: *id,
+                team: player.team,
+                position,
+                hat: *hat,
+                carrying: player.carrying_resource.is_some(),
+                destination,
+            })
+        })
+        .collect();
+    // Highest ranking hat leads; ids keep the plan stable between updates
+    travellers.sort_by_key(|t| (std::cmp::Reverse(t.hat.map_or(0, |h| h.priority())), t.id));
+
+    let mut orders = HashMap::new();
+    assign_escorts(game_view, &travellers, escort_calls, &mut orders);
+    assign_squads(&travellers, &mut orders);
+    orders
+}
+
+/// One combat AI per carrier, nearest first. Carriers that called for an
+/// escort come before the rest and are answered from further away.
+fn assign_escorts(
+    game_view: &GameView,
+    travellers: &[Traveller],
+    escort_calls: &HashSet<Uuid>,
+    orders: &mut HashMap<Uuid, FormationOrder>,
+) {
+    let mut escorted = HashSet::new();
+    for escort in travellers
+        .iter()
+        .filter(|t| !t.carrying && t.hat.is_some_and(|h| h.is_combat()))
+    {
+        let carrier = travellers
+            .iter()
+            .filter(|c| c.carrying && c.team == escort.team && !escorted.contains(&c.id))
+            .map(|c| {
+                (
+                    c,
+                    escort_calls.contains(&c.id),
+                    escort.position.distance_to(c.position),
+                )
+            })
+            .filter(|(_, called, distance)| {
+                let range = if *called {
+                    AI_ESCORT_CALL_RANGE
+                } else {
+                    AI_ESCORT_RANGE
+                };
+                *distance <= range * TILE_SIZE
+            })
+            .min_by(|a, b| b.1.cmp(&a.1).then(a.2.total_cmp(&b.2)))
+            .map(|(c, ..)| c);
+        let Some(carrier) = carrier else {
+            continue;
+        };
+
+        // Stand on the side the enemy is coming from, or trail behind
+        let nearest_enemy = game_view
+            .mechs
+            .iter()
+            .filter(|m| m.team != carrier.team)
+            .map(|m| m.position)
+            .min_by(|a, b| {
+                carrier
+                    .position
+                    .distance_to(*a)
+                    .total_cmp(&carrier.position.distance_to(*b))
+            });
+        let guard_direction = match (nearest_enemy, carrier.destination) {
+            (Some(enemy), _) => direction(carrier.position, enemy),
+            (None, Some(destination)) => {
+                let (x, y) = direction(carrier.position, destination);
+                (-x, -y)
+            }
+            (None, None) => direction(carrier.position, escort.position),
+        };
+        let offset = AI_ESCORT_DISTANCE * TILE_SIZE;
+
+        escorted.insert(carrier.id);
+        orders.insert(
+            escort.id,
+            FormationOrder {
+                role: FormationRole::Escort {
+                    carrier: carrier.id,
+                },
+                target: WorldPos::new(
+                    carrier.position.x + guard_direction.0 * offset,
+                    carrier.position.y + guard_direction.1 * offset,
+                ),
+            },
+        );
+    }
+}
+
+/// Travelling AIs close to a leader fall in behind it in a wedge
+fn assign_squads(travellers: &[Traveller], orders: &mut HashMap<Uuid, FormationOrder>) {
+    let free = |t: &Traveller, orders: &HashMap<Uuid, FormationOrder>| {
+        !t.carrying && t.destination.is_some() && !orders.contains_key(&t.id)
+    };
+
+    for leader in travellers {
+        if !free(leader, orders) {
+            continue;
+        }
+        let mut followers: Vec<(&Traveller, f32)> = travellers
+            .iter()
+            .filter(|t| t.id != leader.id && t.team == leader.team && free(t, orders))
+            .map(|t| (t, leader.position.distance_to(t.position)))
+            .filter(|(_, distance)| *distance <= AI_FORMATION_JOIN_RADIUS * TILE_SIZE)
+            .collect();
+        if followers.is_empty() {
+            continue;
+        }
+        followers.sort_by(|a, b| a.1.total_cmp(&b.1));
+        followers.truncate(AI_FORMATION_MAX_SIZE - 1);
+
+        // Face where the leader is going, or away from the squad if it's
+        // standing still
+        let heading = leader
+            .destination
+            .filter(|d| d.distance_to(leader.position) > 1.0)
+            .map(|d| direction(leader.position, d))
+            .unwrap_or_else(|| {
+                let count = followers.len() as f32;
+                let centroid = WorldPos::new(
+                    followers.iter().map(|(t, _)| t.position.x).sum::<f32>() / count,
+                    followers.iter().map(|(t, _)| t.position.y).sum::<f32>() / count,
+                );
+                direction(centroid, leader.position)
+            });
+
+        orders.insert(
+            leader.id,
+            FormationOrder {
+                role: FormationRole::Leader,
+                target: leader.destination.unwrap_or(leader.position),
+            },
+        );
+        for (slot, (follower, _)) in followers.into_iter().enumerate() {
+            orders.insert(
+                follower.id,
+                FormationOrder {
+                    role: FormationRole::Follower {
+                        leader: leader.id,
+                        slot,
+                    },
+                    target: wedge_slot(leader.position, heading, slot),
+                },
+            );
+        }
+    }
+}
+
+/// Slots alternate left and right, each pair a row further back
+fn wedge_slot(leader: WorldPos, heading: (f32, f32), slot: usize) -> WorldPos {
+    let row = (slot / 2 + 1) as f32;
+    let side = if slot.is_multiple_of(2) { -1.0 } else { 1.0 };
+    let spacing = AI_FORMATION_SPACING * TILE_SIZE;
+    let (back_x, back_y) = (-heading.0 * row * spacing, -heading.1 * row * spacing);
+    let (side_x, side_y) = (
+        -heading.1 * side * row * spacing,
+        heading.0 * side * row * spacing,
+    );
+    WorldPos::new(leader.x + back_x + side_x, leader.y + back_y + side_y)
+}
+
+/// Turn an AI's movement into real steering: grouped AIs head for their
+/// spot, and everyone on foot is pushed apart from anyone standing too close
+pub fn steer_commands(
+    game_view: &GameView,
+    ai_id: Uuid,
+    order: Option<&FormationOrder>,
+    commands: &mut Vec<AICommand>,
+) {
+    let Some(PlayerLocation::OutsideWorld(position)) = game_view
+        .players
+        .iter()
+        .find(|p| p.id == ai_id)
+        .map(|p| p.location)
+    else {
+        return;
+    };
+
+    let heading = match order {
+        Some(order) if order.role != FormationRole::Leader => {
+            Some(steer_toward(position, order.target))
+        }
+        _ => None,
+    };
+    let push = separation(
+        ai_id,
+        position,
+        game_view
+            .players
+            .iter()
+            .filter(|p| p.id != ai_id)
+            .filter_map(|p| match p.location {
+                PlayerLocation::OutsideWorld(pos) => Some((p.id, pos)),
+                PlayerLocation::InsideMech { .. } => None,
+            }),
+    );
+
+    if heading.is_none() && push == (0.0, 0.0) {
+        return;
+    }
+
+    let index = match commands
+        .iter()
+        .position(|command| matches!(command, AICommand::Move { .. }))
+    {
+        Some(index) => index,
+        None => {
+            commands.push(AICommand::Move {
+                player_id: ai_id,
+                movement: (0.0, 0.0),
+            });
+            commands.len() - 1
+        }
+    };
+    let AICommand::Move { movement, .. } = &mut commands[index] else {
+        return;
+    };
+
+    let base = heading.unwrap_or(*movement);
+    let (x, y) = (base.0 + push.0, base.1 + push.1);
+    let length = (x * x + y * y).sqrt();
+    *movement = if length > 1.0 {
+        (x / length, y / length)
+    } else {
+        (x, y)
+    };
+}
+
+/// Movement toward `target`, slowing over the last slot's width so
+/// followers settle instead of overshooting
+pub fn steer_toward(from: WorldPos, target: WorldPos) -> (f32, f32) {
+    let distance = from.distance_to(target);
+    if distance < 1.0 {
+        return (0.0, 0.0);
+    }
+    let speed = (distance / (AI_FORMATION_SPACING * TILE_SIZE)).min(1.0);
+    let (x, y) = direction(from, target);
+    (x * speed, y * speed)
+}
+
+/// Push away from everyone closer than the formation spacing, harder the
+/// closer they are. Two bots on exactly the same spot split by id.
+pub fn separation(
+    my_id: Uuid,
+    position: WorldPos,
+    others: impl IntoIterator<Item = (Uuid, WorldPos)>,
+) -> (f32, f32) {
+    let spacing = AI_FORMATION_SPACING * TILE_SIZE;
+    let mut push = (0.0, 0.0);
+    for (other_id, other) in others {
+        let distance = position.distance_to(other);
+        if distance >= spacing {
+            continue;
+        }
+        let away = if distance < f32::EPSILON {
+            if my_id < other_id {
+                (-1.0, 0.0)
+            } else {
+                (1.0, 0.0)
+            }
+        } else {
+            direction(other, position)
+        };
+        let strength = 1.0 - distance / spacing;
+        push.0 += away.0 * strength;
+        push.1 += away.1 * strength;
+    }
+    push
+}
+
+/// Unit vector from one point toward another, zero if they coincide
+fn direction(from: WorldPos, to: WorldPos) -> (f32, f32) {
+    let (dx, dy) = (to.x - from.x, to.y - from.y);
+    let length = (dx * dx + dy * dy).sqrt();
+    if length < f32::EPSILON {
+        (0.0, 0.0)
+    } else {
+        (dx / length, dy / length)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MechView, PlayerView, TeamInfo};
+
+    fn on_foot(x: f32, y: f32, carrying: bool) -> PlayerView {
+        PlayerView {
+            id: Uuid::new_v4(),
+            name: "Bot".to_string(),
+            team: TeamId::Red,
+            location: PlayerLocation::OutsideWorld(WorldPos::new(x, y)),
+            carrying_resource: carrying.then_some(ResourceType::ScrapMetal),
+            operating_station: None,
+        }
+    }
+
+    fn view(players: Vec<PlayerView>, enemy_at: Option<WorldPos>) -> GameView {
+        GameView {
+            tick: 1,
+            players,
+            mechs: enemy_at
+                .map(|position| MechView {
+                    id: Uuid::new_v4(),
+                    team: TeamId::Blue,
+                    position,
+                    health: MECH_INITIAL_HEALTH,
+                    max_health: MECH_MAX_HEALTH,
+                    shield: MECH_INITIAL_SHIELD,
+                    velocity: (0.0, 0.0),
+                    turret: MechTurret::default(),
+                    stations: Vec::new(),
+                    resource_inventory: HashMap::new(),
+                    alarm: None,
+                })
+                .into_iter()
+                .collect(),
+            resources: Vec::new(),
+            projectiles: Vec::new(),
+            team_info: TeamInfo {
+                team_id: TeamId::Red,
+                player_count: 0,
+                mech_count: 0,
+                total_resources: HashMap::new(),
+            },
+        }
+    }
+
+    fn moving_to(target: WorldPos) -> Decision {
+        Decision {
+            chosen_action: Some(TaskAction::MoveToPosition {
+                target,
+                reason: "Travel".to_string(),
+            }),
+            confidence: 1.0,
+            reasoning: String::new(),
+            messages: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_squad_falls_in_behind_the_highest_ranking_hat() {
+        let leader = on_foot(320.0, 320.0, false);
+        let near = on_foot(320.0, 380.0, false);
+        let further = on_foot(320.0, 200.0, false);
+        let stray = on_foot(
+            320.0 + AI_FORMATION_JOIN_RADIUS * TILE_SIZE * 2.0,
+            320.0,
+            false,
+        );
+        let game_view = view(
+            vec![leader.clone(), near.clone(), further.clone(), stray.clone()],
+            None,
+        );
+        let east = moving_to(WorldPos::new(2000.0, 320.0));
+        let decisions = [
+            (near.id, None, &east),
+            (stray.id, None, &east),
+            (leader.id, Some(Hat::Gunner), &east),
+            (further.id, None, &east),
+        ];
+
+        let orders = plan_formations(&game_view, &decisions, &HashSet::new());
+        assert_eq!(orders[&leader.id].role, FormationRole::Leader);
+        assert!(!orders.contains_key(&stray.id));
+
+        // Nearest follower takes the first slot; the pair mirror each other
+        // behind the leader
+        let leader_pos = WorldPos::new(320.0, 320.0);
+        for (follower, slot) in [(&near, 0), (&further, 1)] {
+            let order = orders[&follower.id];
+            assert_eq!(
+                order.role,
+                FormationRole::Follower {
+                    leader: leader.id,
+                    slot
+                }
+            );
+            assert_eq!(order.target, wedge_slot(leader_pos, (1.0, 0.0), slot));
+            assert!(order.target.x < leader_pos.x);
+        }
+        let (left, right) = (orders[&near.id].target, orders[&further.id].target);
+        assert!((left.y - leader_pos.y + right.y - leader_pos.y).abs() < 0.01);
+
+        // Followers give up their own plan for their slot
+        let mut decision = east.clone();
+        orders[&near.id].apply(&mut decision);
+        assert!(matches!(
+            decision.chosen_action,
+            Some(TaskAction::MoveToPosition { target, .. }) if target == left
+        ));
+    }
+
+    #[test]
+    fn test_escort_guards_the_carrier_on_the_enemy_side() {
+        let carrier = on_foot(640.0, 640.0, true);
+        let escort = on_foot(640.0 - 5.0 * TILE_SIZE, 640.0, false);
+        let game_view = view(
+            vec![carrier.clone(), escort.clone()],
+            Some(WorldPos::new(2000.0, 640.0)),
+        );
+        let idle = Decision {
+            chosen_action: None,
+            confidence: 1.0,
+            reasoning: String::new(),
+            messages: Vec::new(),
+        };
+        let decisions = [
+            (carrier.id, None, &idle),
+            (escort.id, Some(Hat::Gunner), &idle),
+        ];
+
+        let orders = plan_formations(&game_view, &decisions, &HashSet::new());
+        let order = orders[&escort.id];
+        assert_eq!(
+            order.role,
+            FormationRole::Escort {
+                carrier: carrier.id
+            }
+        );
+        assert_eq!(
+            order.target,
+            WorldPos::new(640.0 + AI_ESCORT_DISTANCE * TILE_SIZE, 640.0)
+        );
+
+        // The escort steers toward its spot beside the carrier
+        let mut commands = Vec::new();
+        steer_commands(&game_view, escort.id, Some(&order), &mut commands);
+        let Some(AICommand::Move { movement, .. }) = commands.first() else {
+            panic!("escort should be moving, got {commands:?}");
+        };
+        assert!(movement.0 > 0.9 && movement.1.abs() < 0.01);
+    }
+
+    #[test]
+    fn test_escort_calls_reach_further_than_the_usual_range() {
+        let carrier = on_foot(640.0, 640.0, true);
+        let escort = on_foot(640.0 - 20.0 * TILE_SIZE, 640.0, false);
+        let game_view = view(vec![carrier.clone(), escort.clone()], None);
+        let idle = moving_to(WorldPos::new(640.0, 640.0));
+        let decisions = [
+            (carrier.id, None, &idle),
+            (escort.id, Some(Hat::Defender), &idle),
+        ];
+
+        let orders = plan_formations(&game_view, &decisions, &HashSet::new());
+        assert!(!matches!(
+            orders.get(&escort.id).map(|o| o.role),
+            Some(FormationRole::Escort { .. })
+        ));
+
+        let calls = HashSet::from([carrier.id]);
+        let orders = plan_formations(&game_view, &decisions, &calls);
+        assert_eq!(
+            orders[&escort.id].role,
+            FormationRole::Escort {
+                carrier: carrier.id
+            }
+        );
+    }
+
+    #[test]
+    fn test_bots_on_the_same_spot_push_apart() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let spot = WorldPos::new(100.0, 100.0);
+        let push_a = separation(a, spot, [(b, spot)]);
+        let push_b = separation(b, spot, [(a, spot)]);
+        assert_eq!(push_a.0, -push_b.0);
+        assert_eq!(push_a.0.abs(), 1.0);
+
+        let far = WorldPos::new(100.0 + AI_FORMATION_SPACING * TILE_SIZE, 100.0);
+        assert_eq!(separation(a, spot, [(b, far)]), (0.0, 0.0));
+    }
+}
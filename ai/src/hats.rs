@@ -1,4 +1,4 @@
-use crate::{HealthStatus, Perception};
+use crate::{alarm_tasks, AIMessage, HealthStatus, Perception, RequestType};
 use shared::*;
 use std::collections::HashMap;
 
@@ -10,6 +10,7 @@ pub enum Hat {
     Gunner,
     Engineer,
     Scavenger,
+    Carrier,
     Scout,
     Defender,
 
@@ -34,6 +35,7 @@ impl Hat {
             Hat::Gunner => "Gunner",
             Hat::Engineer => "Engineer",
             Hat::Scavenger => "Scavenger",
+            Hat::Carrier => "Carrier",
             Hat::Scout => "Scout",
             Hat::Defender => "Defender",
             Hat::UnderAttack => "Under Attack",
@@ -59,6 +61,21 @@ impl Hat {
         )
     }
 
+    /// The hat that does a crew role's job
+    pub fn for_role(role: CrewRole) -> Self {
+        match role {
+            CrewRole::Pilot => Hat::Pilot,
+            CrewRole::Gunner => Hat::Gunner,
+            CrewRole::Engineer => Hat::Engineer,
+            CrewRole::Runner => Hat::Carrier,
+        }
+    }
+
+    /// Fighting roles, the ones that escort teammates hauling resources
+    pub fn is_combat(&self) -> bool {
+        matches!(self, Hat::Gunner | Hat::Defender | Hat::Pursuing)
+    }
+
     /// Get priority level (higher = more important)
     pub fn priority(&self) -> u8 {
         match self {
@@ -70,6 +87,7 @@ impl Hat {
             Hat::Gunner => 5,
             Hat::ResourceRush => 5,
             Hat::Pursuing => 4,
+            Hat::Carrier => 4,
             Hat::Engineer => 3,
             Hat::Defender => 3,
             Hat::Scavenger => 2,
@@ -129,6 +147,8 @@ pub enum CarryingRequirement {
 pub struct HatManager {
     current_hat: Hat,
     reactive_hat: Option<Hat>,
+    /// Role the crew left this AI in the hangar, which it leans towards
+    assigned_hat: Option<Hat>,
     available_tasks: HashMap<Hat, Vec<Task>>,
     hat_scores: HashMap<Hat, f32>,
 }
@@ -138,6 +158,7 @@ impl HatManager {
         let mut manager = Self {
             current_hat: Hat::Idle,
             reactive_hat: None,
+            assigned_hat: None,
             available_tasks: HashMap::new(),
             hat_scores: HashMap::new(),
         };
@@ -151,6 +172,12 @@ impl HatManager {
         self.reactive_hat.unwrap_or(self.current_hat)
     }
 
+    /// Start out wearing `hat` and keep leaning towards it
+    pub fn assign(&mut self, hat: Hat) {
+        self.current_hat = hat;
+        self.assigned_hat = Some(hat);
+    }
+
     /// Update hat based on perception
     pub fn update_hat(&mut self, perception: &Perception) {
         // Check for reactive hats first
@@ -190,18 +217,27 @@ impl HatManager {
 
     /// Get tasks for current hat
     pub fn get_current_tasks(&self, perception: &Perception) -> Vec<Task> {
-        let active_hat = self.get_active_hat();
-
-        self.available_tasks
-            .get(&active_hat)
-            .map(|tasks| {
+        // Carrier errands depend on what the mech is short of right now
+        let tasks = match self.get_active_hat() {
+            Hat::Carrier => carrier_tasks(perception),
+            // Drop everything when boarders set one of our mechs off
+            Hat::Defender => {
+                let mut tasks = alarm_tasks(perception);
+                tasks.extend(
+                    self.available_tasks
+                        .get(&Hat::Defender)
+                        .into_iter()
+                        .flatten()
+                        .cloned(),
+                );
                 tasks
-                    .iter()
-                    .filter(|task| self.task_is_valid(task, perception))
-                    .cloned()
-                    .collect()
-            })
-            .unwrap_or_default()
+            }
+            hat => self.available_tasks.get(&hat).cloned().unwrap_or_default(),
+        };
+        tasks
+            .into_iter()
+            .filter(|task| self.task_is_valid(task, perception))
+            .collect()
     }
 
     /// Select a task from available tasks
@@ -229,6 +265,11 @@ impl HatManager {
 
     /// Check if reactive conditions are met
     fn check_reactive_conditions(&self, perception: &Perception) -> Option<Hat> {
+        // A loaded carrier gets its load home before anything else
+        if self.current_hat == Hat::Carrier && perception.my_state.carrying_resource.is_some() {
+            return None;
+        }
+
         // Under attack if threats are severe
         if perception.threats.iter().any(|t| t.severity > 0.7) {
             return Some(Hat::UnderAttack);
@@ -300,6 +341,19 @@ impl HatManager {
         let scavenger_score = perception.team_state.resource_status.scarcity_level;
         self.hat_scores.insert(Hat::Scavenger, scavenger_score);
 
+        // Carrier - valuable when a resource the mech is short of is known,
+        // and the job to keep while hauling one home
+        let carrier_score = if perception.my_state.carrying_resource.is_some() {
+            0.9
+        } else if wanted_resource(perception).is_some() {
+            let status = &perception.team_state.resource_status;
+            let needed = status.resource_needs.values().sum::<u32>().max(1);
+            0.4 + 0.5 * total_deficit(perception) as f32 / needed as f32
+        } else {
+            0.0
+        };
+        self.hat_scores.insert(Hat::Carrier, carrier_score);
+
         // Engineer - valuable when repairs/upgrades needed
         let engineer_score = if perception
             .team_state
@@ -322,13 +376,19 @@ impl HatManager {
             };
         self.hat_scores.insert(Hat::Scout, scout_score);
 
-        // Defender - valuable when defending
-        let defender_score = if perception.team_state.combat_readiness < 0.5 {
+        // Defender - valuable when defending, and above all when boarded
+        let defender_score = if !perception.team_state.alarms.is_empty() {
+            1.0
+        } else if perception.team_state.combat_readiness < 0.5 {
             0.6
         } else {
             0.3
         };
         self.hat_scores.insert(Hat::Defender, defender_score);
+
+        if let Some(hat) = self.assigned_hat {
+            *self.hat_scores.entry(hat).or_insert(0.0) += AI_ASSIGNED_HAT_BONUS;
+        }
     }
 
     /// Check if a task is valid given current perception
@@ -810,8 +870,266 @@ impl HatManager {
     }
 }
 
+/// How many more of a resource the mech wants than it holds
+fn deficit(perception: &Perception, resource_type: ResourceType) -> u32 {
+    let status = &perception.team_state.resource_status;
+    let need = status
+        .resource_needs
+        .get(&resource_type)
+        .copied()
+        .unwrap_or(0);
+    let held = status
+        .total_resources
+        .get(&resource_type)
+        .copied()
+        .unwrap_or(0);
+    need.saturating_sub(held)
+}
+
+fn total_deficit(perception: &Perception) -> u32 {
+    perception
+        .team_state
+        .resource_status
+        .resource_needs
+        .keys()
+        .map(|resource_type| deficit(perception, *resource_type))
+        .sum()
+}
+
+/// The nearest known resource the mech is short of that no teammate has
+/// claimed
+fn wanted_resource(perception: &Perception) -> Option<(WorldPos, ResourceType)> {
+    let PlayerLocation::OutsideWorld(position) = perception.my_state.location else {
+        return None;
+    };
+    perception
+        .environment
+        .nearby_resources
+        .iter()
+        .filter(|(_, resource_type)| deficit(perception, *resource_type) > 0)
+        .filter(|(position, _)| {
+            !perception
+                .blackboard
+                .resource_claimed_by_other(*position, perception.my_id)
+        })
+        .min_by(|a, b| {
+            position
+                .distance_to(a.0)
+                .total_cmp(&position.distance_to(b.0))
+        })
+        .copied()
+}
+
+/// Fetch what the mech needs most nearby, or take the load home. Dropping
+/// off outranks everything else a carrier could do.
+fn carrier_tasks(perception: &Perception) -> Vec<Task> {
+    if let Some(resource_type) = perception.my_state.carrying_resource {
+        return perception
+            .danger
+            .fallback
+            .map(|door| Task {
+                name: "Drop Off Load".to_string(),
+                priority: 1.2,
+                action: TaskAction::MoveToPosition {
+                    target: door,
+                    reason: format!("Hauling {resource_type:?} home"),
+                },
+                requirements: TaskRequirements {
+                    location: Some(LocationRequirement::Outside),
+                    carrying: Some(CarryingRequirement::Resource(None)),
+                    ..Default::default()
+                },
+            })
+            .into_iter()
+            .collect();
+    }
+
+    wanted_resource(perception)
+        .map(|(position, resource_type)| Task {
+            name: format!("Fetch {resource_type:?}"),
+            priority: 0.8,
+            action: TaskAction::MoveToPosition {
+                target: position,
+                reason: format!(
+                    "Mech is {} {resource_type:?} short",
+                    deficit(perception, resource_type)
+                ),
+            },
+            requirements: TaskRequirements {
+                location: Some(LocationRequirement::Outside),
+                carrying: Some(CarryingRequirement::Nothing),
+                team_needs: vec![resource_type],
+                ..Default::default()
+            },
+        })
+        .into_iter()
+        .collect()
+}
+
+/// A loaded carrier with enemies closing in asks the team for an escort
+pub fn escort_request(perception: &Perception) -> Option<AIMessage> {
+    let resource_type = perception.my_state.carrying_resource?;
+    let PlayerLocation::OutsideWorld(position) = perception.my_state.location else {
+        return None;
+    };
+    let radius = AI_CARRIER_CALL_RADIUS * TILE_SIZE;
+    let enemy_count = perception
+        .threats
+        .iter()
+        .filter(|threat| threat.distance <= radius)
+        .count();
+    (enemy_count > 0).then(|| {
+        AIMessage::request(
+            perception.my_id,
+            RequestType::NeedEscort {
+                position,
+                resource_type,
+                enemy_count,
+            },
+        )
+    })
+}
+
 impl Default for HatManager {
     fn default() -> Self {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        GameView, MechView, MessageType, PlayerView, ResourceView, TeamInfo, Threat, ThreatType,
+    };
+    use uuid::Uuid;
+
+    const HERE: WorldPos = WorldPos { x: 400.0, y: 400.0 };
+
+    /// A carrier out on foot next to its mech, which holds all the scrap it
+    /// needs and no wiring, with scrap close by and wiring further off
+    fn carrier_perception(carrying: Option<ResourceType>) -> Perception {
+        let ai_id = Uuid::new_v4();
+        let inventory = HashMap::from([(ResourceType::ScrapMetal, 5), (ResourceType::Wiring, 0)]);
+        let resource = |tiles: f32, resource_type| ResourceView {
+            id: Uuid::new_v4(),
+            position: WorldPos::new(HERE.x + tiles * TILE_SIZE, HERE.y),
+            resource_type,
+        };
+        let view = GameView {
+            tick: 1,
+            players: vec![PlayerView {
+                id: ai_id,
+                name: "Carrier".to_string(),
+                team: TeamId::Red,
+                location: PlayerLocation::OutsideWorld(HERE),
+                carrying_resource: carrying,
+                operating_station: None,
+            }],
+            mechs: vec![MechView {
+                id: Uuid::new_v4(),
+                team: TeamId::Red,
+                position: WorldPos::new(HERE.x - 20.0 * TILE_SIZE, HERE.y),
+                health: MECH_MAX_HEALTH,
+                max_health: MECH_MAX_HEALTH,
+                shield: 0,
+                velocity: (0.0, 0.0),
+                turret: MechTurret::default(),
+                stations: Vec::new(),
+                resource_inventory: inventory.clone(),
+                alarm: None,
+            }],
+            resources: vec![
+                resource(2.0, ResourceType::ScrapMetal),
+                resource(6.0, ResourceType::Wiring),
+            ],
+            projectiles: Vec::new(),
+            team_info: TeamInfo {
+                team_id: TeamId::Red,
+                player_count: 1,
+                mech_count: 1,
+                total_resources: inventory,
+            },
+        };
+        Perception::from_game_view(&view, ai_id)
+    }
+
+    fn enemy_at(perception: &mut Perception, tiles: f32) {
+        perception.threats.push(Threat {
+            threat_type: ThreatType::EnemyMech {
+                id: Uuid::new_v4(),
+                health: MECH_MAX_HEALTH,
+            },
+            position: WorldPos::new(HERE.x, HERE.y + tiles * TILE_SIZE),
+            severity: 0.9,
+            distance: tiles * TILE_SIZE,
+            time_to_impact: None,
+        });
+    }
+
+    #[test]
+    fn test_carriers_fetch_what_the_mech_is_short_of() {
+        let perception = carrier_perception(None);
+        let mut hats = HatManager::new();
+        hats.assign(Hat::Carrier);
+
+        let tasks = hats.get_current_tasks(&perception);
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].name, "Fetch Wiring");
+        let TaskAction::MoveToPosition { target, .. } = tasks[0].action else {
+            panic!("carriers walk to what they fetch");
+        };
+        assert_eq!(target, WorldPos::new(HERE.x + 6.0 * TILE_SIZE, HERE.y));
+    }
+
+    #[test]
+    fn test_a_loaded_carrier_drops_off_before_fighting() {
+        let mut loaded = carrier_perception(Some(ResourceType::Wiring));
+        enemy_at(&mut loaded, 3.0);
+        let mut hats = HatManager::new();
+        hats.assign(Hat::Carrier);
+        hats.update_hat(&loaded);
+
+        assert_eq!(hats.get_active_hat(), Hat::Carrier);
+        let tasks = hats.get_current_tasks(&loaded);
+        assert_eq!(tasks[0].name, "Drop Off Load");
+        assert!(matches!(
+            tasks[0].action,
+            TaskAction::MoveToPosition { target, .. } if Some(target) == loaded.danger.fallback
+        ));
+
+        // Empty-handed, the same threat sends it to fight
+        let mut empty = carrier_perception(None);
+        enemy_at(&mut empty, 3.0);
+        hats.update_hat(&empty);
+        assert_eq!(hats.get_active_hat(), Hat::UnderAttack);
+    }
+
+    #[test]
+    fn test_loaded_carriers_call_for_an_escort_when_enemies_close_in() {
+        let mut perception = carrier_perception(Some(ResourceType::Wiring));
+        assert!(escort_request(&perception).is_none());
+
+        enemy_at(&mut perception, AI_CARRIER_CALL_RADIUS + 1.0);
+        assert!(escort_request(&perception).is_none());
+
+        enemy_at(&mut perception, AI_CARRIER_CALL_RADIUS - 1.0);
+        let request = escort_request(&perception).unwrap();
+        assert_eq!(request.sender, perception.my_id);
+        assert!(matches!(
+            request.message_type,
+            MessageType::Request {
+                request_type: RequestType::NeedEscort {
+                    position: HERE,
+                    resource_type: ResourceType::Wiring,
+                    enemy_count: 1,
+                },
+            }
+        ));
+
+        // Nothing to protect on the way out
+        let mut empty = carrier_perception(None);
+        enemy_at(&mut empty, 1.0);
+        assert!(escort_request(&empty).is_none());
+    }
+}
@@ -1,4 +1,17 @@
-use crate::{AIDebugInfo, AIMessage, Decision, Perception};
+//! The bot SDK surface.
+//!
+//! A bot is anything implementing [`AIController`]. Each update the
+//! [`AIManager`](crate::AIManager) builds a [`Perception`] for every bot from
+//! its team's [`GameView`], passes it to [`AIController::decide`] along with
+//! team messages, and turns the returned [`Decision`] into [`AICommand`]s
+//! (see `Decision::to_commands`). Register a bot with
+//! [`register_bot!`](crate::register_bot) so the server can spawn it by
+//! name; `bots/scavenger` is a complete example.
+//!
+//! [`AICommand`]: crate::AICommand
+
+use crate::{AIDebugInfo, AIMessage, Decision, Hat, Perception, UtilityWeights};
+use serde::{Deserialize, Serialize};
 use shared::*;
 use uuid::Uuid;
 
@@ -7,8 +20,11 @@ pub trait AIController: Send + Sync {
     /// Get AI's unique ID
     fn id(&self) -> Uuid;
 
-    /// Perceive the game state
-    fn perceive(&self, game_view: &GameView) -> Perception;
+    /// Perceive the game state. The manager builds perception itself with
+    /// shared per-team caching; this is for driving a bot on its own.
+    fn perceive(&self, game_view: &GameView) -> Perception {
+        Perception::from_game_view(game_view, self.id())
+    }
 
     /// Make a decision based on perception and messages
     fn decide(
@@ -18,21 +34,50 @@ pub trait AIController: Send + Sync {
         delta_time: f32,
     ) -> Decision;
 
+    /// The role the AI is playing, if it uses hats. Combat hats escort
+    /// resource carriers and higher ranking hats lead squads.
+    fn hat(&self) -> Option<Hat> {
+        None
+    }
+
+    /// Take on the role the crew left for the AI in the hangar; ignored by
+    /// AIs without hats
+    fn assign_hat(&mut self, _hat: Hat) {}
+
     /// Get debug information about the AI's current state
-    fn get_debug_info(&self) -> AIDebugInfo;
+    fn get_debug_info(&self) -> AIDebugInfo {
+        AIDebugInfo {
+            ai_id: self.id(),
+            current_hat: String::new(),
+            personality: String::new(),
+            current_goal: None,
+            decision_history: Vec::new(),
+            state_info: String::new(),
+            last_decision: None,
+        }
+    }
 
     /// Reset AI state (useful for respawning)
-    fn reset(&mut self);
+    fn reset(&mut self) {}
+
+    /// The weights the AI scores tasks with, if it scores them
+    fn utility_weights(&self) -> Option<&UtilityWeights> {
+        None
+    }
+
+    /// Replace the AI's task weights; ignored by AIs that don't use them
+    fn set_utility_weights(&mut self, _weights: UtilityWeights) {}
 }
 
-/// View of the game state from AI's perspective
-#[derive(Debug, Clone)]
+/// View of the game state from one team's perspective, built by
+/// [`GameViewBuilder`](crate::GameViewBuilder)
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GameView {
     /// Current tick
     pub tick: u64,
-    /// All players
+    /// The team's crew, and enemies in sight
     pub players: Vec<PlayerView>,
-    /// All mechs
+    /// The team's mechs, and enemy mechs in sight
     pub mechs: Vec<MechView>,
     /// Visible resources
     pub resources: Vec<ResourceView>,
@@ -43,7 +88,7 @@ pub struct GameView {
 }
 
 /// Player information visible to AI
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlayerView {
     pub id: Uuid,
     pub name: String,
@@ -51,24 +96,28 @@ pub struct PlayerView {
     pub location: PlayerLocation,
     pub carrying_resource: Option<ResourceType>,
     pub operating_station: Option<StationType>,
-    pub is_self: bool,
 }
 
 /// Mech information visible to AI
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MechView {
     pub id: Uuid,
     pub team: TeamId,
     pub position: WorldPos,
     pub health: u32,
+    /// Full health, lowered while the hull is breached
+    pub max_health: u32,
     pub shield: u32,
     pub velocity: (f32, f32),
+    pub turret: MechTurret,
     pub stations: Vec<StationView>,
     pub resource_inventory: std::collections::HashMap<ResourceType, u32>,
+    /// Boarders at a station, only ever set for the team's own mechs
+    pub alarm: Option<SabotageAlarm>,
 }
 
 /// Station information visible to AI
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StationView {
     pub id: Uuid,
     pub station_type: StationType,
@@ -78,7 +127,7 @@ pub struct StationView {
 }
 
 /// Resource information visible to AI
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResourceView {
     pub id: Uuid,
     pub position: WorldPos,
@@ -86,7 +135,7 @@ pub struct ResourceView {
 }
 
 /// Projectile information visible to AI
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProjectileView {
     pub id: Uuid,
     pub position: WorldPos,
@@ -95,126 +144,10 @@ pub struct ProjectileView {
 }
 
 /// Team information
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TeamInfo {
     pub team_id: TeamId,
     pub player_count: usize,
     pub mech_count: usize,
     pub total_resources: std::collections::HashMap<ResourceType, u32>,
 }
-
-/// Convert server game state to AI game view
-pub fn create_game_view(
-    game_state: &ServerMessage,
-    ai_player_id: Uuid,
-    ai_team: TeamId,
-) -> Option<GameView> {
-    if let ServerMessage::GameState {
-        players,
-        mechs,
-        resources,
-        projectiles,
-    } = game_state
-    {
-        let player_views: Vec<PlayerView> = players
-            .values()
-            .map(|p| {
-                // Find the station type if player is operating a station
-                let operating_station_type = if let Some(station_id) = p.operating_station {
-                    mechs
-                        .values()
-                        .flat_map(|m| &m.stations)
-                        .find(|s| s.id == station_id)
-                        .map(|s| s.station_type)
-                } else {
-                    None
-                };
-
-                PlayerView {
-                    id: p.id,
-                    name: p.name.clone(),
-                    team: p.team,
-                    location: p.location,
-                    carrying_resource: p.carrying_resource,
-                    operating_station: operating_station_type,
-                    is_self: p.id == ai_player_id,
-                }
-            })
-            .collect();
-
-        let mech_views: Vec<MechView> = mechs
-            .values()
-            .map(|m| MechView {
-                id: m.id,
-                team: m.team,
-                position: m.world_position,
-                health: m.health,
-                shield: m.shield,
-                velocity: (0.0, 0.0), // TODO: Calculate from position changes
-                stations: m
-                    .stations
-                    .iter()
-                    .map(|s| StationView {
-                        id: s.id,
-                        station_type: s.station_type,
-                        operated_by: s.operated_by,
-                        position: s.position,
-                        floor: s.floor,
-                    })
-                    .collect(),
-                resource_inventory: m.resource_inventory.clone(),
-            })
-            .collect();
-
-        let resource_views: Vec<ResourceView> = resources
-            .iter()
-            .map(|r| ResourceView {
-                id: r.id,
-                position: r.position.to_world_pos(),
-                resource_type: r.resource_type,
-            })
-            .collect();
-
-        let projectile_views: Vec<ProjectileView> = projectiles
-            .iter()
-            .map(|p| ProjectileView {
-                id: p.id,
-                position: p.position,
-                velocity: p.velocity,
-                owner_team: mechs
-                    .values()
-                    .find(|m| m.id == p.owner_mech_id)
-                    .map(|m| m.team)
-                    .unwrap_or(TeamId::Red),
-            })
-            .collect();
-
-        // Calculate team info
-        let team_player_count = player_views.iter().filter(|p| p.team == ai_team).count();
-
-        let team_mech_count = mech_views.iter().filter(|m| m.team == ai_team).count();
-
-        let mut total_resources = std::collections::HashMap::new();
-        for mech in mech_views.iter().filter(|m| m.team == ai_team) {
-            for (resource_type, count) in &mech.resource_inventory {
-                *total_resources.entry(*resource_type).or_insert(0) += count;
-            }
-        }
-
-        Some(GameView {
-            tick: 0, // TODO: Track tick count
-            players: player_views,
-            mechs: mech_views,
-            resources: resource_views,
-            projectiles: projectile_views,
-            team_info: TeamInfo {
-                team_id: ai_team,
-                player_count: team_player_count,
-                mech_count: team_mech_count,
-                total_resources,
-            },
-        })
-    } else {
-        None
-    }
-}
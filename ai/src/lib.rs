@@ -1,24 +1,48 @@
+pub mod blackboard;
+pub mod boarding;
+pub mod bots;
 pub mod communication;
 pub mod decision;
+pub mod formation;
 pub mod hats;
 pub mod interface;
 pub mod logging;
 pub mod perception;
 pub mod personality;
+pub mod self_preservation;
+pub mod threat_map;
+pub mod tuning;
 pub mod utility;
+pub mod view;
 
+use rand::rngs::StdRng;
+use rand::{Rng, RngCore, SeedableRng};
 use shared::*;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::PathBuf;
+use std::time::Instant;
 use uuid::Uuid;
 
+pub use blackboard::*;
+pub use boarding::*;
+pub use bots::*;
 pub use communication::*;
 pub use decision::*;
+pub use formation::*;
 pub use hats::*;
 pub use interface::*;
 pub use logging::*;
 pub use perception::*;
 pub use personality::*;
+pub use self_preservation::*;
+pub use threat_map::*;
+pub use tuning::*;
 pub use utility::*;
+pub use view::*;
+
+// Used by `register_bot!` in bot crates
+#[doc(hidden)]
+pub use ::inventory;
 
 /// Configuration for AI system
 #[derive(Debug, Clone)]
@@ -52,52 +76,60 @@ impl Default for AIConfig {
 
 /// Main AI manager that coordinates all AI players
 pub struct AIManager {
-    /// All AI controllers
-    controllers: HashMap<Uuid, Box<dyn AIController>>,
+    /// All AI controllers, kept in id order so every pass walks them the
+    /// same way
+    controllers: BTreeMap<Uuid, Box<dyn AIController>>,
     /// Communication system
     comm_system: CommunicationSystem,
     /// Decision logger
     logger: DecisionLogger,
     /// Configuration
     config: AIConfig,
+    /// Static world knowledge shared by every AI
+    world_knowledge: WorldKnowledge,
+    /// What each AI remembers between updates
+    perception_memory: HashMap<Uuid, PerceptionMemory>,
+    /// Where each team's bots shouldn't walk
+    threat_maps: HashMap<TeamId, ThreatMap>,
+    /// What each team's bots have claimed and seen
+    blackboards: HashMap<TeamId, TeamBlackboard>,
+    /// Task weights every new AI starts with
+    weights: UtilityWeights,
+    /// Running decision timings for each AI
+    metrics: HashMap<Uuid, AIMetrics>,
+    /// What the last update cost, all AIs together
+    tick_metrics: AITickMetrics,
 }
 
 impl AIManager {
     /// Create a new AI manager
     pub fn new(config: AIConfig) -> Self {
         Self {
-            controllers: HashMap::new(),
+            controllers: BTreeMap::new(),
             comm_system: CommunicationSystem::new(config.enable_captain),
             logger: DecisionLogger::new(config.debug_logging),
             config,
+            world_knowledge: WorldKnowledge::default(),
+            perception_memory: HashMap::new(),
+            threat_maps: HashMap::new(),
+            blackboards: HashMap::new(),
+            weights: UtilityWeights::default(),
+            metrics: HashMap::new(),
+            tick_metrics: AITickMetrics::default(),
         }
     }
 
-    /// Initialize AI players
-    pub fn initialize_ais(&mut self) -> Vec<(String, TeamId)> {
+    /// Initialize AI players, drawing their ids and rolls from `rng`
+    pub fn initialize_ais(&mut self, rng: &mut impl RngCore) -> Vec<(String, TeamId)> {
         let mut ai_players = Vec::new();
 
         for i in 0..self.config.ai_count {
-            let ai_id = Uuid::new_v4();
+            let ai_id = uuid::Builder::from_random_bytes(rng.gen()).into_uuid();
             let personality = self.select_personality(i);
             let name = format!("AI_{}", personality.name_suffix());
 
-            // Create controller based on difficulty
-            let controller: Box<dyn AIController> = if self.config.difficulty > 0.7 {
-                Box::new(utility::UtilityAI::new(
-                    ai_id,
-                    personality,
-                    self.config.difficulty,
-                ))
-            } else {
-                Box::new(utility::SimpleAI::new(
-                    ai_id,
-                    personality,
-                    self.config.difficulty,
-                ))
-            };
-
-            self.controllers.insert(ai_id, controller);
+            let ai_rng = StdRng::seed_from_u64(rng.gen());
+            self.add_ai(ai_id, ai_rng, personality, self.config.difficulty);
             ai_players.push((name, self.config.team));
         }
 
@@ -110,43 +142,137 @@ impl AIManager {
         ai_players
     }
 
-    /// Add a single AI with specific personality and difficulty
-    pub fn add_ai(&mut self, personality: Personality, difficulty: f32) -> Uuid {
-        let ai_id = Uuid::new_v4();
+    /// Add a single AI with specific personality and difficulty. Whatever
+    /// it rolls comes from `rng`, so seed it from the match to replay it.
+    pub fn add_ai(
+        &mut self,
+        ai_id: Uuid,
+        rng: StdRng,
+        personality: Personality,
+        difficulty: f32,
+    ) -> Uuid {
+        self.add_controller(Self::build_ai(ai_id, rng, personality, difficulty))
+    }
 
+    /// The controller `add_ai` would add, for handing over later
+    pub fn build_ai(
+        ai_id: Uuid,
+        rng: StdRng,
+        personality: Personality,
+        difficulty: f32,
+    ) -> Box<dyn AIController> {
         // Create controller based on difficulty
-        let controller: Box<dyn AIController> = if difficulty > 0.7 {
-            Box::new(utility::UtilityAI::new(ai_id, personality, difficulty))
+        if difficulty > 0.7 {
+            Box::new(utility::UtilityAI::new(ai_id, personality, difficulty).with_rng(rng))
         } else {
             Box::new(utility::SimpleAI::new(ai_id, personality, difficulty))
-        };
+        }
+    }
 
+    /// Add a custom bot, returning its id
+    pub fn add_controller(&mut self, mut controller: Box<dyn AIController>) -> Uuid {
+        controller.set_utility_weights(self.weights.clone());
+        let ai_id = controller.id();
         self.controllers.insert(ai_id, controller);
         ai_id
     }
 
+    /// Start an AI off in the role the crew left it. False if there's no
+    /// such AI.
+    pub fn assign_hat(&mut self, ai_id: Uuid, hat: Hat) -> bool {
+        match self.controllers.get_mut(&ai_id) {
+            Some(controller) => {
+                controller.assign_hat(hat);
+                true
+            }
+            None => false,
+        }
+    }
+
     /// Remove an AI by ID
     pub fn remove_ai(&mut self, ai_id: Uuid) {
         self.controllers.remove(&ai_id);
+        self.perception_memory.remove(&ai_id);
+        self.metrics.remove(&ai_id);
+        for blackboard in self.blackboards.values_mut() {
+            blackboard.release(ai_id);
+        }
     }
 
-    /// Update all AIs
-    pub fn update(&mut self, game_view: &GameView, delta_time: f32) -> Vec<AICommand> {
+    /// Update all AIs, each seeing only its own team's view
+    pub fn update(&mut self, views: &[GameView], delta_time: f32) -> Vec<AICommand> {
+        let started = Instant::now();
         let mut all_commands = Vec::new();
+        let Some(first_view) = views.first() else {
+            return all_commands;
+        };
+        let mut tick_metrics = AITickMetrics {
+            tick: first_view.tick,
+            ..Default::default()
+        };
+        let view_of = |ai_id: Uuid| {
+            views
+                .iter()
+                .find(|view| view.players.iter().any(|p| p.id == ai_id))
+                .unwrap_or(first_view)
+        };
 
-        // Collect perceptions for all AIs
+        // Collect perceptions for all AIs, analyzing each team only once
+        let mut team_perceptions: HashMap<TeamId, TeamPerception> = HashMap::new();
         let mut perceptions = HashMap::new();
-        for (ai_id, controller) in &self.controllers {
-            let perception = controller.perceive(game_view);
+        for ai_id in self.controllers.keys() {
+            let game_view = view_of(*ai_id);
+            let team = ai_team(game_view, *ai_id);
+            let team_perception = team_perceptions
+                .entry(team)
+                .or_insert_with(|| TeamPerception::analyze(game_view, team, &self.world_knowledge));
+            let memory = self.perception_memory.entry(*ai_id).or_default();
+            let perception = Perception::incremental(game_view, *ai_id, team_perception, memory);
             perceptions.insert(*ai_id, perception);
         }
 
+        // Keep every team's threat map current, even with its bots all
+        // aboard, so fire lanes are remembered when they get out
+        for game_view in views {
+            let team = game_view.team_info.team_id;
+            self.threat_maps
+                .entry(team)
+                .or_default()
+                .update(game_view, team, delta_time);
+            self.blackboards
+                .entry(team)
+                .or_default()
+                .update(game_view, team);
+        }
+
         // Process communications
+        self.comm_system.set_tick(first_view.tick);
         let messages = self.comm_system.get_pending_messages();
+        let escort_calls: HashSet<Uuid> = messages
+            .iter()
+            .filter(|m| {
+                matches!(
+                    m.message_type,
+                    MessageType::Request {
+                        request_type: RequestType::NeedEscort { .. }
+                    }
+                )
+            })
+            .map(|m| m.sender)
+            .collect();
 
-        // Update each AI
+        // Let each AI decide on its own, in turn, so each sees what the
+        // ones before it claimed
+        let mut decisions = Vec::new();
         for (ai_id, controller) in &mut self.controllers {
-            if let Some(perception) = perceptions.get(ai_id) {
+            let game_view = view_of(*ai_id);
+            let blackboard = self
+                .blackboards
+                .entry(ai_team(game_view, *ai_id))
+                .or_default();
+            if let Some(perception) = perceptions.get_mut(ai_id) {
+                perception.blackboard = blackboard.clone();
+
                 // Let AI process messages
                 let relevant_messages: Vec<_> = messages
                     .iter()
@@ -154,26 +280,158 @@ impl AIManager {
                     .cloned()
                     .collect();
 
-                // Get AI decision
+                // Get AI decision, timing it
+                let decide_started = Instant::now();
                 let decision = controller.decide(perception, &relevant_messages, delta_time);
+                let decision_ms = decide_started.elapsed().as_secs_f32() * 1000.0;
+                blackboard.record_decision(game_view, *ai_id, &decision);
+                tick_metrics.record_decision(*ai_id, decision_ms);
+                self.metrics
+                    .entry(*ai_id)
+                    .or_default()
+                    .record_decision(decision_ms, decision.messages.len());
+                // Debug info is only wanted for the log, and isn't cheap
+                let debug_info = self
+                    .logger
+                    .is_logging()
+                    .then(|| controller.get_debug_info());
+                decisions.push((*ai_id, controller.hat(), decision, debug_info));
+            }
+        }
+
+        // Then group up the ones out on foot, leaving anyone retreating to run
+        let retreats: HashMap<Uuid, WorldPos> = decisions
+            .iter()
+            .filter_map(|(ai_id, _, decision, _)| {
+                self_preservation::retreat_target(decision, &perceptions[ai_id].danger)
+                    .map(|target| (*ai_id, target))
+            })
+            .collect();
+        let mut formations = HashMap::new();
+        for game_view in views {
+            let formation_input: Vec<_> = decisions
+                .iter()
+                .filter(|(ai_id, ..)| !retreats.contains_key(ai_id))
+                .filter(|(ai_id, ..)| {
+                    view_of(*ai_id).team_info.team_id == game_view.team_info.team_id
+                })
+                .map(|(ai_id, hat, decision, _)| (*ai_id, *hat, decision))
+                .collect();
+            formations.extend(formation::plan_formations(
+                game_view,
+                &formation_input,
+                &escort_calls,
+            ));
+        }
 
-                // Log decision
-                self.logger.log_decision(*ai_id, &decision);
+        for (ai_id, hat, mut decision, debug_info) in decisions {
+            let game_view = view_of(ai_id);
+            let order = formations.get(&ai_id);
+            if let Some(order) = order {
+                order.apply(&mut decision);
+            }
 
-                // Convert decision to commands
-                let commands = decision.to_commands(*ai_id);
-                all_commands.extend(commands);
+            // Log decision
+            if let Some(debug_info) = &debug_info {
+                self.logger.log_decision(
+                    ai_id,
+                    game_view.tick,
+                    &decision,
+                    &perceptions[&ai_id],
+                    debug_info,
+                );
+            }
 
-                // Handle any communications the AI wants to send
-                for msg in decision.messages {
-                    self.comm_system.send_message(*ai_id, msg);
+            // Convert decision to commands
+            let mut commands = decision.to_commands(ai_id);
+            boarding::close_on_boarder(game_view, ai_id, &decision, &mut commands);
+            let threat_map = self.threat_maps.get(&ai_team(game_view, ai_id));
+            if let Some(&target) = retreats.get(&ai_id) {
+                self_preservation::head_for(game_view, ai_id, target, &mut commands);
+            } else if let (
+                Some(Hat::Carrier),
+                Some(TaskAction::MoveToPosition { target, .. }),
+                Some(threat_map),
+            ) = (hat, &decision.chosen_action, threat_map)
+            {
+                // Carriers pick their own way around danger unless they're
+                // holding a slot in someone else's formation
+                if order.is_none_or(|order| order.role == FormationRole::Leader) {
+                    threat_map::route_carrier(threat_map, game_view, ai_id, *target, &mut commands);
                 }
             }
+            formation::steer_commands(game_view, ai_id, order, &mut commands);
+            if let Some(threat_map) = threat_map {
+                threat_map::steer_around_danger(threat_map, game_view, ai_id, &mut commands);
+            }
+            self_preservation::dodge_mechs(ai_id, &perceptions[&ai_id].danger, &mut commands);
+            for command in &commands {
+                tick_metrics.record_command(command.kind());
+            }
+            all_commands.extend(commands);
+
+            // Handle any communications the AI wants to send
+            for msg in decision.messages {
+                self.comm_system.send_message(ai_id, msg);
+            }
         }
 
+        tick_metrics.update_ms = started.elapsed().as_secs_f32() * 1000.0;
+        self.tick_metrics = tick_metrics;
         all_commands
     }
 
+    /// What the last update cost, all AIs together
+    pub fn tick_metrics(&self) -> &AITickMetrics {
+        &self.tick_metrics
+    }
+
+    /// What a team's bots have claimed and seen
+    pub fn blackboard(&self, team: TeamId) -> Option<&TeamBlackboard> {
+        self.blackboards.get(&team)
+    }
+
+    /// One AI's running decision timings
+    pub fn metrics(&self, ai_id: Uuid) -> Option<&AIMetrics> {
+        self.metrics.get(&ai_id)
+    }
+
+    /// Stream decisions as JSONL to a rotating file at `path`
+    pub fn enable_jsonl_logging(&mut self, path: PathBuf) -> std::io::Result<()> {
+        self.logger
+            .enable_jsonl(path, DEFAULT_JSONL_MAX_BYTES, DEFAULT_JSONL_MAX_FILES)
+    }
+
+    /// Messages the AIs have sent each other from `sequence` on
+    pub fn comm_events_since(&self, sequence: u64) -> Vec<CommEvent> {
+        self.comm_system.events_since(sequence)
+    }
+
+    /// Give every AI, current and future, these task weights
+    pub fn set_default_weights(&mut self, weights: UtilityWeights) {
+        for controller in self.controllers.values_mut() {
+            controller.set_utility_weights(weights.clone());
+        }
+        self.weights = weights;
+    }
+
+    /// Retune one AI's task weights. False if there's no such AI or it
+    /// doesn't score tasks by weight.
+    pub fn set_weights(&mut self, ai_id: Uuid, weights: UtilityWeights) -> bool {
+        match self.controllers.get_mut(&ai_id) {
+            Some(controller) if controller.utility_weights().is_some() => {
+                controller.set_utility_weights(weights);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// The task weights an AI is using, if it uses any
+    pub fn weights(&self, ai_id: Uuid) -> Option<UtilityWeights> {
+        self.controllers.get(&ai_id)?.utility_weights().cloned()
+    }
+
     /// Get debug info for a specific AI
     pub fn get_debug_info(&self, ai_id: Uuid) -> Option<AIDebugInfo> {
         self.controllers
@@ -210,4 +468,25 @@ pub enum AICommand {
         player_id: Uuid,
         movement: (f32, f32),
     },
+    Dash {
+        player_id: Uuid,
+        direction: Facing,
+    },
+    Melee {
+        player_id: Uuid,
+    },
+}
+
+impl AICommand {
+    /// Short name for tallying commands by kind
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AICommand::Move { .. } => "move",
+            AICommand::PressButton { .. } => "press_button",
+            AICommand::ExitMech { .. } => "exit_mech",
+            AICommand::EngineControl { .. } => "engine_control",
+            AICommand::Dash { .. } => "dash",
+            AICommand::Melee { .. } => "melee",
+        }
+    }
 }
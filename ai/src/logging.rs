@@ -1,16 +1,177 @@
-use crate::Decision;
+use crate::{AIDebugInfo, Decision, Perception};
 use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
 use std::fs::File;
-use std::io::Write;
-use std::path::PathBuf;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
 use uuid::Uuid;
 
+/// Default size at which a JSONL decision log is rotated
+pub const DEFAULT_JSONL_MAX_BYTES: u64 = 10 * 1024 * 1024;
+/// Default number of rotated JSONL files kept on disk
+pub const DEFAULT_JSONL_MAX_FILES: usize = 5;
+
 /// Logs AI decisions for debugging and analysis
 pub struct DecisionLogger {
     enabled: bool,
     log_file: Option<File>,
     log_buffer: Vec<LogEntry>,
     max_buffer_size: usize,
+    jsonl_sink: Option<JsonlSink>,
+}
+
+/// One structured decision, written as a single line of a JSONL log
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct DecisionRecord {
+    pub timestamp: String,
+    pub ai_id: Uuid,
+    pub tick: u64,
+    pub hat: String,
+    pub perception: PerceptionSummary,
+    pub scored_options: Vec<ScoredOption>,
+    pub chosen_action: Option<String>,
+    pub confidence: f32,
+    pub reasoning: String,
+    pub messages_sent: usize,
+}
+
+/// Compact view of what an AI perceived when it made a decision
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct PerceptionSummary {
+    pub location: String,
+    pub health_status: String,
+    pub carrying_resource: Option<String>,
+    pub operating_station: Option<String>,
+    pub threat_count: usize,
+    pub max_threat_severity: f32,
+    pub opportunity_count: usize,
+    pub combat_readiness: f32,
+}
+
+/// A task the AI considered along with its utility score
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ScoredOption {
+    pub name: String,
+    pub score: f32,
+}
+
+impl PerceptionSummary {
+    pub fn from_perception(perception: &Perception) -> Self {
+        Self {
+            location: format!("{:?}", perception.my_state.location),
+            health_status: format!("{:?}", perception.my_state.health_status),
+            carrying_resource: perception
+                .my_state
+                .carrying_resource
+                .map(|r| format!("{r:?}")),
+            operating_station: perception
+                .my_state
+                .operating_station
+                .map(|s| format!("{s:?}")),
+            threat_count: perception.threats.len(),
+            max_threat_severity: perception
+                .threats
+                .iter()
+                .map(|t| t.severity)
+                .fold(0.0, f32::max),
+            opportunity_count: perception.opportunities.len(),
+            combat_readiness: perception.team_state.combat_readiness,
+        }
+    }
+}
+
+/// Size-rotated JSONL file writer
+struct JsonlSink {
+    path: PathBuf,
+    file: File,
+    bytes_written: u64,
+    max_bytes: u64,
+    max_files: usize,
+}
+
+impl JsonlSink {
+    fn open(path: PathBuf, max_bytes: u64, max_files: usize) -> std::io::Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)?;
+        let bytes_written = file.metadata()?.len();
+
+        Ok(Self {
+            path,
+            file,
+            bytes_written,
+            max_bytes,
+            max_files,
+        })
+    }
+
+    fn write_record(&mut self, record: &DecisionRecord) -> std::io::Result<()> {
+        let line = serde_json::to_string(record)?;
+        if self.bytes_written + line.len() as u64 + 1 > self.max_bytes && self.bytes_written > 0 {
+            self.rotate()?;
+        }
+
+        writeln!(self.file, "{line}")?;
+        self.bytes_written += line.len() as u64 + 1;
+        Ok(())
+    }
+
+    /// Shift `log.jsonl` -> `log.1.jsonl` -> `log.2.jsonl` ..., dropping the oldest
+    fn rotate(&mut self) -> std::io::Result<()> {
+        self.file.flush()?;
+
+        // Make room before shifting, or the oldest would take the file
+        // shifted into its place with it
+        let oldest = self.rotated_path(self.max_files);
+        if oldest.exists() {
+            std::fs::remove_file(oldest)?;
+        }
+        for index in (1..self.max_files).rev() {
+            let from = self.rotated_path(index);
+            if from.exists() {
+                std::fs::rename(&from, self.rotated_path(index + 1))?;
+            }
+        }
+        if self.max_files > 0 {
+            std::fs::rename(&self.path, self.rotated_path(1))?;
+        }
+
+        self.file = File::create(&self.path)?;
+        self.bytes_written = 0;
+        Ok(())
+    }
+
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        let stem = self
+            .path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("ai_decisions");
+        self.path.with_file_name(format!("{stem}.{index}.jsonl"))
+    }
+}
+
+/// Read all decision records from a JSONL log, skipping malformed lines
+pub fn load_decision_log(path: &Path) -> std::io::Result<Vec<DecisionRecord>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut records = Vec::new();
+
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<DecisionRecord>(&line) {
+            Ok(record) => records.push(record),
+            Err(e) => log::warn!("Skipping malformed decision log line: {e}"),
+        }
+    }
+
+    Ok(records)
 }
 
 #[derive(Debug, Clone)]
@@ -56,31 +217,85 @@ impl DecisionLogger {
             log_file,
             log_buffer: Vec::new(),
             max_buffer_size: 1000,
+            jsonl_sink: None,
         }
     }
 
+    /// Stream structured decisions to a rotating JSONL file
+    pub fn enable_jsonl(
+        &mut self,
+        path: PathBuf,
+        max_bytes: u64,
+        max_files: usize,
+    ) -> std::io::Result<()> {
+        self.jsonl_sink = Some(JsonlSink::open(path, max_bytes, max_files)?);
+        Ok(())
+    }
+
+    /// Whether decisions are going anywhere, so callers can skip building
+    /// what `log_decision` would only throw away
+    pub fn is_logging(&self) -> bool {
+        self.enabled || self.jsonl_sink.is_some()
+    }
+
     /// Log a decision
-    pub fn log_decision(&mut self, ai_id: Uuid, decision: &Decision) {
+    pub fn log_decision(
+        &mut self,
+        ai_id: Uuid,
+        tick: u64,
+        decision: &Decision,
+        perception: &Perception,
+        debug_info: &AIDebugInfo,
+    ) {
+        if !self.is_logging() {
+            return;
+        }
+
+        let timestamp = Utc::now();
+        let chosen_action = decision.chosen_action.as_ref().map(|a| format!("{a:?}"));
+
+        if let Some(sink) = self.jsonl_sink.as_mut() {
+            let record = DecisionRecord {
+                timestamp: timestamp.to_rfc3339(),
+                ai_id,
+                tick,
+                hat: debug_info.current_hat.clone(),
+                perception: PerceptionSummary::from_perception(perception),
+                scored_options: debug_info
+                    .decision_history
+                    .iter()
+                    .map(|(name, score)| ScoredOption {
+                        name: name.clone(),
+                        score: *score,
+                    })
+                    .collect(),
+                chosen_action: chosen_action.clone(),
+                confidence: decision.confidence,
+                reasoning: decision.reasoning.clone(),
+                messages_sent: decision.messages.len(),
+            };
+            if let Err(e) = sink.write_record(&record) {
+                log::error!("Failed to write JSONL decision log: {e}");
+                self.jsonl_sink = None;
+            }
+        }
+
         if !self.enabled {
             return;
         }
 
         let summary = DecisionSummary {
-            action: decision
-                .chosen_action
-                .as_ref()
-                .map(|a| format!("{a:?}"))
-                .unwrap_or_else(|| "None".to_string()),
+            action: chosen_action.unwrap_or_else(|| "None".to_string()),
             confidence: decision.confidence,
             reasoning: decision.reasoning.clone(),
-            hat: "Unknown".to_string(), // Would need to pass this in
+            hat: debug_info.current_hat.clone(),
             messages_sent: decision.messages.len(),
         };
 
         let entry = LogEntry {
-            timestamp: Utc::now(),
+            timestamp,
             ai_id,
-            tick: 0, // Would need to pass game tick
+            tick,
             decision: summary,
         };
 
@@ -188,6 +403,41 @@ impl AIMetrics {
     }
 }
 
+/// What one AI update cost across every AI, for spotting a bot that's
+/// eating the frame
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct AITickMetrics {
+    pub tick: u64,
+    pub ai_count: usize,
+    /// Time spent in the AIs' own decisions, summed
+    pub total_decision_ms: f32,
+    /// Wall time of the whole update, perception and steering included
+    pub update_ms: f32,
+    pub slowest_ai: Option<Uuid>,
+    pub slowest_decision_ms: f32,
+    /// How many of each kind of command the AIs issued
+    pub commands: BTreeMap<String, u32>,
+}
+
+impl AITickMetrics {
+    pub fn record_decision(&mut self, ai_id: Uuid, decision_time_ms: f32) {
+        self.ai_count += 1;
+        self.total_decision_ms += decision_time_ms;
+        if self.slowest_ai.is_none() || decision_time_ms > self.slowest_decision_ms {
+            self.slowest_ai = Some(ai_id);
+            self.slowest_decision_ms = decision_time_ms;
+        }
+    }
+
+    pub fn record_command(&mut self, kind: &str) {
+        *self.commands.entry(kind.to_string()).or_default() += 1;
+    }
+
+    pub fn command_count(&self) -> u32 {
+        self.commands.values().sum()
+    }
+}
+
 /// Debug visualization data for egui client
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct AIVisualizationData {
@@ -244,6 +494,22 @@ pub struct CommEdge {
     pub last_message_type: String,
 }
 
+/// One AI-to-AI message, as streamed to observers for replay
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct CommEvent {
+    /// Counts up from zero, so an observer can pick up where it left off
+    pub sequence: u64,
+    pub tick: u64,
+    /// Seconds since the communication system started
+    pub time: f32,
+    pub from: Uuid,
+    /// None for a broadcast
+    pub to: Option<Uuid>,
+    pub from_captain: bool,
+    pub message_type: String,
+    pub priority: String,
+}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct DecisionEvent {
     pub timestamp: String,
@@ -257,3 +523,77 @@ impl Default for AIMetrics {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(tick: u64) -> DecisionRecord {
+        DecisionRecord {
+            timestamp: "2025-01-01T00:00:00+00:00".to_string(),
+            ai_id: Uuid::nil(),
+            tick,
+            hat: "Scout".to_string(),
+            perception: PerceptionSummary {
+                location: "Outside".to_string(),
+                health_status: "Healthy".to_string(),
+                carrying_resource: None,
+                operating_station: None,
+                threat_count: 0,
+                max_threat_severity: 0.0,
+                opportunity_count: 0,
+                combat_readiness: 1.0,
+            },
+            scored_options: Vec::new(),
+            chosen_action: None,
+            confidence: 1.0,
+            reasoning: String::new(),
+            messages_sent: 0,
+        }
+    }
+
+    fn ticks(path: &Path) -> Vec<u64> {
+        load_decision_log(path)
+            .unwrap()
+            .iter()
+            .map(|record| record.tick)
+            .collect()
+    }
+
+    #[test]
+    fn test_full_logs_rotate_and_the_oldest_is_dropped() {
+        let dir = std::env::temp_dir().join(format!("decision-log-{}", Uuid::new_v4()));
+        let path = dir.join("ai.jsonl");
+        // Room for exactly one record per file
+        let line_bytes = serde_json::to_string(&record(1)).unwrap().len() as u64 + 1;
+        let mut sink = JsonlSink::open(path.clone(), line_bytes, 2).unwrap();
+
+        for tick in 1..=4 {
+            sink.write_record(&record(tick)).unwrap();
+        }
+
+        assert_eq!(ticks(&path), vec![4]);
+        assert_eq!(ticks(&dir.join("ai.1.jsonl")), vec![3]);
+        assert_eq!(ticks(&dir.join("ai.2.jsonl")), vec![2]);
+        assert!(!dir.join("ai.3.jsonl").exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_loading_skips_lines_that_arent_records() {
+        let path = std::env::temp_dir().join(format!("decision-log-{}.jsonl", Uuid::new_v4()));
+        let line = |tick| serde_json::to_string(&record(tick)).unwrap();
+        let truncated = &line(3)[..20];
+        let contents = [
+            line(1),
+            "not json".to_string(),
+            String::new(),
+            line(2),
+            truncated.to_string(),
+        ];
+        std::fs::write(&path, contents.join("\n")).unwrap();
+
+        assert_eq!(ticks(&path), vec![1, 2]);
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{GameView, MechView, PlayerView, ProjectileView, TeamInfo};
+use crate::{Danger, GameView, MechView, PlayerView, ProjectileView, ResourceView, TeamBlackboard};
 use shared::*;
 use std::collections::HashMap;
 use uuid::Uuid;
@@ -18,6 +18,11 @@ pub struct Perception {
     pub team_state: TeamState,
     /// Environmental awareness
     pub environment: EnvironmentInfo,
+    /// How much trouble I'm in
+    pub danger: Danger,
+    /// What the rest of the team has claimed and seen. Only bots run by
+    /// the manager get one; it's empty otherwise.
+    pub blackboard: TeamBlackboard,
 }
 
 /// AI's own state
@@ -81,6 +86,15 @@ pub struct TeamState {
     pub player_roles: HashMap<Uuid, String>,    // Current "hat" each player is wearing
     pub resource_status: ResourceStatus,
     pub combat_readiness: f32, // 0.0 to 1.0
+    pub alarms: Vec<BoardingAlarm>,
+}
+
+/// One of the team's mechs with boarders at a station
+#[derive(Debug, Clone)]
+pub struct BoardingAlarm {
+    pub mech_id: Uuid,
+    pub mech_position: WorldPos,
+    pub intruder: Uuid,
 }
 
 #[derive(Debug, Clone)]
@@ -99,9 +113,135 @@ pub struct EnvironmentInfo {
     pub strategic_positions: Vec<WorldPos>,
 }
 
+/// World knowledge that never changes during a match, computed once per
+/// AI manager
+#[derive(Debug, Clone)]
+pub struct WorldKnowledge {
+    pub strategic_positions: Vec<WorldPos>,
+}
+
+impl Default for WorldKnowledge {
+    fn default() -> Self {
+        Self {
+            strategic_positions: vec![WorldPos::new(
+                ARENA_WIDTH_TILES as f32 * TILE_SIZE / 2.0,
+                ARENA_HEIGHT_TILES as f32 * TILE_SIZE / 2.0,
+            )],
+        }
+    }
+}
+
+/// Team-wide analysis computed once per update and shared by every AI on
+/// the team
+#[derive(Debug, Clone)]
+pub struct TeamPerception {
+    pub team: TeamId,
+    pub team_state: TeamState,
+    pub environment: EnvironmentInfo,
+    /// How much the team wants each resource type right now
+    resource_values: HashMap<ResourceType, f32>,
+}
+
+impl TeamPerception {
+    pub fn analyze(game_view: &GameView, team: TeamId, world: &WorldKnowledge) -> Self {
+        // The view's team info only covers the team it was built for
+        let total_resources = if team == game_view.team_info.team_id {
+            game_view.team_info.total_resources.clone()
+        } else {
+            game_view
+                .mechs
+                .iter()
+                .find(|m| m.team == team)
+                .map(|m| m.resource_inventory.clone())
+                .unwrap_or_default()
+        };
+
+        let resource_values = ResourceType::ALL
+            .into_iter()
+            .map(|resource_type| {
+                (
+                    resource_type,
+                    calculate_resource_value(resource_type, &total_resources),
+                )
+            })
+            .collect();
+
+        Self {
+            team,
+            team_state: analyze_team_state(game_view, team, total_resources),
+            environment: analyze_environment(game_view, team, world),
+            resource_values,
+        }
+    }
+}
+
+/// What an AI remembers between updates. Resources inside its perception
+/// radius are refreshed every update; ones further away keep their last
+/// known position until they go stale.
+#[derive(Debug, Default)]
+pub struct PerceptionMemory {
+    resources: HashMap<Uuid, (ResourceView, u64)>, // (last seen state, tick)
+}
+
+impl PerceptionMemory {
+    fn refresh(&mut self, game_view: &GameView, my_pos: WorldPos, radius: f32) {
+        let in_range = |pos: WorldPos| my_pos.distance_to(pos) <= radius;
+
+        // Anything we should be able to see but can't has been picked up
+        self.resources.retain(|id, (resource, last_seen)| {
+            if in_range(resource.position) {
+                game_view.resources.iter().any(|r| r.id == *id)
+            } else {
+                game_view.tick.saturating_sub(*last_seen) <= AI_MEMORY_TICKS
+            }
+        });
+        for resource in game_view.resources.iter().filter(|r| in_range(r.position)) {
+            self.resources
+                .insert(resource.id, (resource.clone(), game_view.tick));
+        }
+    }
+
+    fn resources(&self) -> impl Iterator<Item = &ResourceView> {
+        self.resources.values().map(|(resource, _)| resource)
+    }
+}
+
 impl Perception {
-    /// Create perception from game view
+    /// Create perception from game view, seeing the whole arena from scratch
     pub fn from_game_view(game_view: &GameView, ai_id: Uuid) -> Self {
+        let team = TeamPerception::analyze(
+            game_view,
+            game_view.team_info.team_id,
+            &WorldKnowledge::default(),
+        );
+        let mut memory = PerceptionMemory::default();
+        Self::build(game_view, ai_id, &team, &mut memory, f32::INFINITY)
+    }
+
+    /// Build perception from shared team analysis, refreshing only what is
+    /// within the AI's perception radius
+    pub fn incremental(
+        game_view: &GameView,
+        ai_id: Uuid,
+        team: &TeamPerception,
+        memory: &mut PerceptionMemory,
+    ) -> Self {
+        Self::build(
+            game_view,
+            ai_id,
+            team,
+            memory,
+            AI_PERCEPTION_RADIUS * TILE_SIZE,
+        )
+    }
+
+    fn build(
+        game_view: &GameView,
+        ai_id: Uuid,
+        team: &TeamPerception,
+        memory: &mut PerceptionMemory,
+        radius: f32,
+    ) -> Self {
         let my_player = game_view.players.iter().find(|p| p.id == ai_id).cloned();
 
         let my_state = if let Some(player) = my_player {
@@ -122,22 +262,48 @@ impl Perception {
             }
         };
 
-        let threats = identify_threats(game_view, ai_id, &my_state);
-        let opportunities = identify_opportunities(game_view, ai_id, &my_state);
-        let team_state = analyze_team_state(game_view, ai_id);
-        let environment = analyze_environment(game_view);
+        let my_pos = match my_state.location {
+            PlayerLocation::OutsideWorld(pos) => Some(pos),
+            PlayerLocation::InsideMech { mech_id, .. } => game_view
+                .mechs
+                .iter()
+                .find(|m| m.id == mech_id)
+                .map(|m| m.position),
+        };
+
+        let (threats, opportunities) = match my_pos {
+            Some(pos) => {
+                memory.refresh(game_view, pos, radius);
+                (
+                    identify_threats(game_view, team.team, pos, radius),
+                    identify_opportunities(game_view, team, pos, memory),
+                )
+            }
+            None => (Vec::new(), Vec::new()),
+        };
 
         Perception {
             my_id: ai_id,
             my_state,
             threats,
             opportunities,
-            team_state,
-            environment,
+            team_state: team.team_state.clone(),
+            environment: team.environment.clone(),
+            danger: Danger::assess(game_view, ai_id, team.team),
+            blackboard: TeamBlackboard::default(),
         }
     }
 }
 
+/// The team an AI plays for, falling back to the view's team if it has no body
+pub fn ai_team(game_view: &GameView, ai_id: Uuid) -> TeamId {
+    game_view
+        .players
+        .iter()
+        .find(|p| p.id == ai_id)
+        .map_or(game_view.team_info.team_id, |p| p.team)
+}
+
 /// Find nearest safe location
 fn find_nearest_safe_location(game_view: &GameView, player: &PlayerView) -> Option<WorldPos> {
     // Find friendly mech
@@ -158,58 +324,52 @@ fn find_nearest_safe_location(game_view: &GameView, player: &PlayerView) -> Opti
     }
 }
 
-/// Identify threats
-fn identify_threats(game_view: &GameView, ai_id: Uuid, my_state: &MyState) -> Vec<Threat> {
+/// Identify threats. Enemy mechs are always tracked; projectiles only
+/// within the perception radius.
+fn identify_threats(game_view: &GameView, team: TeamId, pos: WorldPos, radius: f32) -> Vec<Threat> {
     let mut threats = Vec::new();
 
-    let my_pos = match my_state.location {
-        PlayerLocation::OutsideWorld(pos) => Some(pos),
-        PlayerLocation::InsideMech { mech_id, .. } => game_view
-            .mechs
-            .iter()
-            .find(|m| m.id == mech_id)
-            .map(|m| m.position),
-    };
+    // Enemy mechs
+    for mech in &game_view.mechs {
+        if mech.team != team {
+            let distance = pos.distance_to(mech.position);
+            let severity = calculate_mech_threat_severity(mech, distance);
 
-    if let Some(pos) = my_pos {
-        // Enemy mechs
-        for mech in &game_view.mechs {
-            if mech.team != game_view.team_info.team_id {
-                let distance = pos.distance_to(mech.position);
-                let severity = calculate_mech_threat_severity(mech, distance);
-
-                threats.push(Threat {
-                    threat_type: ThreatType::EnemyMech {
-                        id: mech.id,
-                        health: mech.health,
-                    },
-                    position: mech.position,
-                    severity,
-                    distance,
-                    time_to_impact: None,
-                });
-            }
+            threats.push(Threat {
+                threat_type: ThreatType::EnemyMech {
+                    id: mech.id,
+                    health: mech.health,
+                },
+                position: mech.position,
+                severity,
+                distance,
+                time_to_impact: None,
+            });
         }
+    }
 
-        // Enemy projectiles
-        for projectile in &game_view.projectiles {
-            if projectile.owner_team != game_view.team_info.team_id {
-                let distance = pos.distance_to(projectile.position);
-                let (severity, time_to_impact) = calculate_projectile_threat(projectile, pos);
-
-                if severity > 0.0 {
-                    threats.push(Threat {
-                        threat_type: ThreatType::Projectile {
-                            id: projectile.id,
-                            velocity: projectile.velocity,
-                        },
-                        position: projectile.position,
-                        severity,
-                        distance,
-                        time_to_impact: Some(time_to_impact),
-                    });
-                }
-            }
+    // Enemy projectiles
+    for projectile in &game_view.projectiles {
+        if projectile.owner_team == team {
+            continue;
+        }
+        let distance = pos.distance_to(projectile.position);
+        if distance > radius {
+            continue;
+        }
+        let (severity, time_to_impact) = calculate_projectile_threat(projectile, pos);
+
+        if severity > 0.0 {
+            threats.push(Threat {
+                threat_type: ThreatType::Projectile {
+                    id: projectile.id,
+                    velocity: projectile.velocity,
+                },
+                position: projectile.position,
+                severity,
+                distance,
+                time_to_impact: Some(time_to_impact),
+            });
         }
     }
 
@@ -254,62 +414,48 @@ fn calculate_projectile_threat(projectile: &ProjectileView, my_pos: WorldPos) ->
     }
 }
 
-/// Identify opportunities
+/// Identify opportunities from remembered resources and our own mechs
 fn identify_opportunities(
     game_view: &GameView,
-    ai_id: Uuid,
-    my_state: &MyState,
+    team: &TeamPerception,
+    pos: WorldPos,
+    memory: &PerceptionMemory,
 ) -> Vec<Opportunity> {
     let mut opportunities = Vec::new();
 
-    let my_pos = match my_state.location {
-        PlayerLocation::OutsideWorld(pos) => Some(pos),
-        PlayerLocation::InsideMech { mech_id, .. } => game_view
-            .mechs
-            .iter()
-            .find(|m| m.id == mech_id)
-            .map(|m| m.position),
-    };
-
-    if let Some(pos) = my_pos {
-        // Resources
-        for resource in &game_view.resources {
-            let distance = pos.distance_to(resource.position);
-            let value = calculate_resource_value(resource.resource_type, &game_view.team_info);
-
-            opportunities.push(Opportunity {
-                opportunity_type: OpportunityType::Resource {
-                    resource_type: resource.resource_type,
-                },
-                position: resource.position,
-                value,
-                distance,
-                time_estimate: distance / (PLAYER_MOVE_SPEED * TILE_SIZE),
-            });
-        }
+    // Resources
+    for resource in memory.resources() {
+        let distance = pos.distance_to(resource.position);
+        let value = team.resource_values[&resource.resource_type];
+
+        opportunities.push(Opportunity {
+            opportunity_type: OpportunityType::Resource {
+                resource_type: resource.resource_type,
+            },
+            position: resource.position,
+            value,
+            distance,
+            time_estimate: distance / (PLAYER_MOVE_SPEED * TILE_SIZE),
+        });
+    }
 
-        // Unmanned stations
-        for mech in game_view
-            .mechs
-            .iter()
-            .filter(|m| m.team == game_view.team_info.team_id)
-        {
-            for station in &mech.stations {
-                if station.operated_by.is_none() {
-                    let station_world_pos = mech.position; // TODO: Calculate actual station position
-                    let distance = pos.distance_to(station_world_pos);
-                    let value = calculate_station_value(station.station_type);
-
-                    opportunities.push(Opportunity {
-                        opportunity_type: OpportunityType::UnmannedStation {
-                            station_type: station.station_type,
-                        },
-                        position: station_world_pos,
-                        value,
-                        distance,
-                        time_estimate: distance / (PLAYER_MOVE_SPEED * TILE_SIZE) + 2.0,
-                    });
-                }
+    // Unmanned stations
+    for mech in game_view.mechs.iter().filter(|m| m.team == team.team) {
+        for station in &mech.stations {
+            if station.operated_by.is_none() {
+                let station_world_pos = mech.position; // TODO: Calculate actual station position
+                let distance = pos.distance_to(station_world_pos);
+                let value = calculate_station_value(station.station_type);
+
+                opportunities.push(Opportunity {
+                    opportunity_type: OpportunityType::UnmannedStation {
+                        station_type: station.station_type,
+                    },
+                    position: station_world_pos,
+                    value,
+                    distance,
+                    time_estimate: distance / (PLAYER_MOVE_SPEED * TILE_SIZE) + 2.0,
+                });
             }
         }
     }
@@ -325,8 +471,11 @@ fn identify_opportunities(
 }
 
 /// Calculate resource value based on team needs
-fn calculate_resource_value(resource_type: ResourceType, team_info: &TeamInfo) -> f32 {
-    let current_count = team_info.total_resources.get(&resource_type).unwrap_or(&0);
+fn calculate_resource_value(
+    resource_type: ResourceType,
+    total_resources: &HashMap<ResourceType, u32>,
+) -> f32 {
+    let current_count = total_resources.get(&resource_type).unwrap_or(&0);
 
     // Higher value for resources we have less of
     match resource_type {
@@ -334,6 +483,8 @@ fn calculate_resource_value(resource_type: ResourceType, team_info: &TeamInfo) -
         ResourceType::ComputerComponents => 0.8 - (*current_count as f32 * 0.1).min(0.6),
         ResourceType::Batteries => 0.9 - (*current_count as f32 * 0.1).min(0.7),
         ResourceType::Wiring => 0.7 - (*current_count as f32 * 0.08).min(0.5),
+        // Rare, and only needed for top tiers
+        ResourceType::PowerCore => 1.0 - (*current_count as f32 * 0.2).min(0.6),
     }
 }
 
@@ -342,34 +493,42 @@ fn calculate_station_value(station_type: StationType) -> f32 {
     match station_type {
         StationType::Engine => 0.9,
         StationType::WeaponLaser | StationType::WeaponProjectile => 0.8,
+        StationType::WeaponEmp => 0.75,
+        StationType::TurretControl => 0.7,
         StationType::Shield => 0.7,
         StationType::Repair => 0.6,
         StationType::Upgrade => 0.5,
         StationType::Electrical => 0.4,
+        StationType::Radar => 0.45,
+        StationType::PointDefense => 0.5,
         StationType::Pilot => 0.85, // High value for strategic control
     }
 }
 
 /// Analyze team state
-fn analyze_team_state(game_view: &GameView, ai_id: Uuid) -> TeamState {
+fn analyze_team_state(
+    game_view: &GameView,
+    team: TeamId,
+    total_resources: HashMap<ResourceType, u32>,
+) -> TeamState {
     let mut mech_health = HashMap::new();
     let mut player_roles = HashMap::new();
+    let mut alarms = Vec::new();
 
-    // Collect mech health
-    for mech in game_view
-        .mechs
-        .iter()
-        .filter(|m| m.team == game_view.team_info.team_id)
-    {
+    // Collect mech health, and any mechs crying out for help
+    for mech in game_view.mechs.iter().filter(|m| m.team == team) {
         mech_health.insert(mech.id, (mech.health, mech.shield));
+        if let Some(alarm) = mech.alarm {
+            alarms.push(BoardingAlarm {
+                mech_id: mech.id,
+                mech_position: mech.position,
+                intruder: alarm.intruder,
+            });
+        }
     }
 
     // Guess player roles based on their actions
-    for player in game_view
-        .players
-        .iter()
-        .filter(|p| p.team == game_view.team_info.team_id)
-    {
+    for player in game_view.players.iter().filter(|p| p.team == team) {
         let role = if player.operating_station.is_some() {
             "Operator".to_string()
         } else if player.carrying_resource.is_some() {
@@ -387,13 +546,13 @@ fn analyze_team_state(game_view: &GameView, ai_id: Uuid) -> TeamState {
     resource_needs.insert(ResourceType::Batteries, 3);
     resource_needs.insert(ResourceType::Wiring, 4);
 
-    let total_resources = game_view.team_info.total_resources.values().sum::<u32>();
+    let held_resources = total_resources.values().sum::<u32>();
     let world_resources = game_view.resources.len() as u32;
-    let available_resources = total_resources + world_resources;
+    let available_resources = held_resources + world_resources;
     let scarcity_level = 1.0 - (available_resources as f32 / 20.0).min(1.0);
 
     let resource_status = ResourceStatus {
-        total_resources: game_view.team_info.total_resources.clone(),
+        total_resources,
         resource_needs,
         scarcity_level,
     };
@@ -412,18 +571,20 @@ fn analyze_team_state(game_view: &GameView, ai_id: Uuid) -> TeamState {
         player_roles,
         resource_status,
         combat_readiness,
+        alarms,
     }
 }
 
 /// Analyze environment
-fn analyze_environment(game_view: &GameView) -> EnvironmentInfo {
+fn analyze_environment(
+    game_view: &GameView,
+    team: TeamId,
+    world: &WorldKnowledge,
+) -> EnvironmentInfo {
     let mut nearby_resources = Vec::new();
     let mut safe_zones = Vec::new();
     let mut contested_areas = Vec::new();
-    let strategic_positions = vec![WorldPos::new(
-        ARENA_WIDTH_TILES as f32 * TILE_SIZE / 2.0,
-        ARENA_HEIGHT_TILES as f32 * TILE_SIZE / 2.0,
-    )];
+    let strategic_positions = world.strategic_positions.clone();
 
     // Collect resource positions
     for resource in &game_view.resources {
@@ -431,25 +592,13 @@ fn analyze_environment(game_view: &GameView) -> EnvironmentInfo {
     }
 
     // Identify safe zones (near friendly mechs)
-    for mech in game_view
-        .mechs
-        .iter()
-        .filter(|m| m.team == game_view.team_info.team_id)
-    {
+    for mech in game_view.mechs.iter().filter(|m| m.team == team) {
         safe_zones.push(mech.position);
     }
 
     // Identify contested areas (between friendly and enemy mechs)
-    for friendly in game_view
-        .mechs
-        .iter()
-        .filter(|m| m.team == game_view.team_info.team_id)
-    {
-        for enemy in game_view
-            .mechs
-            .iter()
-            .filter(|m| m.team != game_view.team_info.team_id)
-        {
+    for friendly in game_view.mechs.iter().filter(|m| m.team == team) {
+        for enemy in game_view.mechs.iter().filter(|m| m.team != team) {
             let mid_point = WorldPos::new(
                 (friendly.position.x + enemy.position.x) / 2.0,
                 (friendly.position.y + enemy.position.y) / 2.0,
@@ -465,3 +614,86 @@ fn analyze_environment(game_view: &GameView) -> EnvironmentInfo {
         strategic_positions,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TeamInfo;
+
+    const RADIUS: f32 = 10.0 * TILE_SIZE;
+
+    fn view(tick: u64, resources: Vec<ResourceView>) -> GameView {
+        GameView {
+            tick,
+            players: Vec::new(),
+            mechs: Vec::new(),
+            resources,
+            projectiles: Vec::new(),
+            team_info: TeamInfo {
+                team_id: TeamId::Red,
+                player_count: 0,
+                mech_count: 0,
+                total_resources: HashMap::new(),
+            },
+        }
+    }
+
+    fn resource(x: f32, y: f32) -> ResourceView {
+        ResourceView {
+            id: Uuid::new_v4(),
+            position: WorldPos::new(x, y),
+            resource_type: ResourceType::Wiring,
+        }
+    }
+
+    fn remembered(memory: &PerceptionMemory) -> Vec<Uuid> {
+        memory.resources().map(|r| r.id).collect()
+    }
+
+    #[test]
+    fn test_resources_in_sight_are_forgotten_once_picked_up() {
+        let here = WorldPos::new(0.0, 0.0);
+        let scrap = resource(TILE_SIZE, 0.0);
+        let mut memory = PerceptionMemory::default();
+
+        memory.refresh(&view(1, vec![scrap.clone()]), here, RADIUS);
+        assert_eq!(remembered(&memory), vec![scrap.id]);
+
+        memory.refresh(&view(2, Vec::new()), here, RADIUS);
+        assert!(remembered(&memory).is_empty());
+    }
+
+    #[test]
+    fn test_resources_out_of_sight_are_remembered_until_stale() {
+        let scrap = resource(TILE_SIZE, 0.0);
+        let mut memory = PerceptionMemory::default();
+        memory.refresh(
+            &view(1, vec![scrap.clone()]),
+            WorldPos::new(0.0, 0.0),
+            RADIUS,
+        );
+
+        // Walked away: it's gone from the view, but we can't see that
+        let away = WorldPos::new(RADIUS * 3.0, 0.0);
+        memory.refresh(&view(1 + AI_MEMORY_TICKS, Vec::new()), away, RADIUS);
+        assert_eq!(remembered(&memory), vec![scrap.id]);
+
+        memory.refresh(&view(2 + AI_MEMORY_TICKS, Vec::new()), away, RADIUS);
+        assert!(remembered(&memory).is_empty());
+    }
+
+    #[test]
+    fn test_resources_seen_again_are_refreshed() {
+        let here = WorldPos::new(0.0, 0.0);
+        let mut scrap = resource(TILE_SIZE, 0.0);
+        let mut memory = PerceptionMemory::default();
+        memory.refresh(&view(1, vec![scrap.clone()]), here, RADIUS);
+
+        // Knocked a few tiles over, and still in sight long after first seen
+        scrap.position = WorldPos::new(4.0 * TILE_SIZE, 0.0);
+        let tick = 10 + AI_MEMORY_TICKS;
+        memory.refresh(&view(tick, vec![scrap.clone()]), here, RADIUS);
+        let (seen, last_seen) = &memory.resources[&scrap.id];
+        assert_eq!((seen.position, *last_seen), (scrap.position, tick));
+    }
+}
@@ -27,7 +27,9 @@ impl Personality {
             (Personality::Aggressive, TaskAction::AttackTarget { .. }) => 1.5,
             (Personality::Aggressive, TaskAction::OperateStation { station_type }) => {
                 match station_type {
-                    shared::StationType::WeaponLaser | shared::StationType::WeaponProjectile => 1.3,
+                    shared::StationType::WeaponLaser
+                    | shared::StationType::WeaponProjectile
+                    | shared::StationType::WeaponEmp => 1.3,
                     _ => 0.8,
                 }
             }
@@ -0,0 +1,412 @@
+//! Staying alive.
+//!
+//! Every update each AI sizes up how much trouble it is in: how badly its
+//! mech is hurt, whether a mech is about to run it over, and whether the
+//! enemies on foot around it outnumber its friends. A hurt mech calls its
+//! crew home to repair it and an outnumbered bot falls back to its mech's
+//! door; both are offered as tasks that compete in the utility scoring, so
+//! braver personalities hold on longer. Getting out of a mech's way isn't
+//! up for debate and overrides whatever movement the bot chose.
+
+use crate::{
+    formation, AICommand, Decision, GameView, LocationRequirement, Perception, Personality, Task,
+    TaskAction, TaskRequirements,
+};
+use shared::*;
+use uuid::Uuid;
+
+/// How much trouble an AI is in this update
+#[derive(Debug, Clone)]
+pub struct Danger {
+    /// Our team's mech health as a fraction of full, 1.0 without a mech
+    pub mech_health: f32,
+    /// A mech about to run us over, if we're out on foot
+    pub incoming_mech: Option<IncomingMech>,
+    /// Enemies on foot nearby minus friends, ourselves included
+    pub outnumbered_by: i32,
+    /// Our mech's door, where a bot outside heads to get to safety
+    pub fallback: Option<WorldPos>,
+}
+
+impl Default for Danger {
+    fn default() -> Self {
+        Self {
+            mech_health: 1.0,
+            incoming_mech: None,
+            outnumbered_by: 0,
+            fallback: None,
+        }
+    }
+}
+
+/// A moving mech whose footprint will sweep over us
+#[derive(Debug, Clone, Copy)]
+pub struct IncomingMech {
+    pub mech_id: Uuid,
+    /// Seconds until it reaches us
+    pub time_to_contact: f32,
+    /// Unit heading out of its path
+    pub dodge: (f32, f32),
+}
+
+impl Danger {
+    pub fn assess(game_view: &GameView, ai_id: Uuid, team: TeamId) -> Self {
+        let Some(me) = game_view.players.iter().find(|p| p.id == ai_id) else {
+            return Self::default();
+        };
+        let our_mech = match me.location {
+            PlayerLocation::InsideMech { mech_id, .. } => {
+                game_view.mechs.iter().find(|m| m.id == mech_id)
+            }
+            PlayerLocation::OutsideWorld(_) => game_view.mechs.iter().find(|m| m.team == team),
+        };
+        let mech_health =
+            our_mech.map_or(1.0, |m| (m.health as f32 / MECH_MAX_HEALTH as f32).min(1.0));
+
+        let PlayerLocation::OutsideWorld(position) = me.location else {
+            return Self {
+                mech_health,
+                ..Self::default()
+            };
+        };
+
+        let radius = AI_OUTNUMBERED_RADIUS * TILE_SIZE;
+        let (friends, enemies) = game_view
+            .players
+            .iter()
+            .filter(|p| match p.location {
+                PlayerLocation::OutsideWorld(pos) => pos.distance_to(position) <= radius,
+                PlayerLocation::InsideMech { .. } => false,
+            })
+            .fold((0, 0), |(friends, enemies), p| {
+                if p.team == team {
+                    (friends + 1, enemies)
+                } else {
+                    (friends, enemies + 1)
+                }
+            });
+
+        Self {
+            mech_health,
+            incoming_mech: incoming_mech(game_view, position),
+            outnumbered_by: enemies - friends,
+            fallback: our_mech.map(|m| door_position(m.position)),
+        }
+    }
+
+    /// Whether our mech is hurt badly enough to call its crew home
+    pub fn needs_repair(&self) -> bool {
+        self.mech_health < AI_RETREAT_MECH_HEALTH
+    }
+}
+
+/// Between the two door tiles on the bottom edge of a mech at `mech_pos`
+fn door_position(mech_pos: WorldPos) -> WorldPos {
+    let doors = MechDoorPositions::from_mech_position(mech_pos.to_tile());
+    let door = doors.left_door.to_world_pos();
+    WorldPos::new(door.x + TILE_SIZE, door.y + TILE_SIZE / 2.0)
+}
+
+/// The soonest moving mech that will pass over `position`, treating each
+/// mech as a circle around its footprint moving in a straight line
+fn incoming_mech(game_view: &GameView, position: WorldPos) -> Option<IncomingMech> {
+    let half_size = MECH_SIZE_TILES as f32 * TILE_SIZE / 2.0;
+    let clearance = half_size * std::f32::consts::SQRT_2 + AI_DODGE_MARGIN * TILE_SIZE;
+
+    game_view
+        .mechs
+        .iter()
+        .filter_map(|mech| {
+            let velocity = (mech.velocity.0 * TILE_SIZE, mech.velocity.1 * TILE_SIZE);
+            let speed_sq = velocity.0 * velocity.0 + velocity.1 * velocity.1;
+            if speed_sq < (AI_MECH_MOVING_SPEED * TILE_SIZE).powi(2) {
+                return None;
+            }
+            let offset = (
+                position.x - (mech.position.x + half_size),
+                position.y - (mech.position.y + half_size),
+            );
+            let time_to_contact = (offset.0 * velocity.0 + offset.1 * velocity.1) / speed_sq;
+            if !(0.0..=AI_DODGE_LOOKAHEAD).contains(&time_to_contact) {
+                return None;
+            }
+            // Sideways distance from the mech's path, signed by which side we're on
+            let speed = speed_sq.sqrt();
+            let side = (velocity.0 * offset.1 - velocity.1 * offset.0) / speed;
+            if side.abs() > clearance {
+                return None;
+            }
+            let away = if side >= 0.0 { 1.0 } else { -1.0 };
+            Some(IncomingMech {
+                mech_id: mech.id,
+                time_to_contact,
+                dodge: (-velocity.1 / speed * away, velocity.0 / speed * away),
+            })
+        })
+        .min_by(|a, b| a.time_to_contact.total_cmp(&b.time_to_contact))
+}
+
+/// Tasks for getting out of trouble, each with its utility score. Braver
+/// personalities discount them.
+pub fn retreat_tasks(perception: &Perception, personality: Personality) -> Vec<(Task, f32)> {
+    let danger = &perception.danger;
+    let bravery = 1.0 - personality.combat_aggressiveness() * 0.5;
+    let mut tasks = Vec::new();
+
+    if danger.needs_repair() {
+        let urgency = 1.0 + (1.0 - danger.mech_health / AI_RETREAT_MECH_HEALTH);
+        let task = match (perception.my_state.location, danger.fallback) {
+            (PlayerLocation::OutsideWorld(_), Some(door)) => Some(Task {
+                name: "Return to Repair".to_string(),
+                priority: urgency,
+                action: TaskAction::MoveToPosition {
+                    target: door,
+                    reason: "Heading home to patch up the mech".to_string(),
+                },
+                requirements: TaskRequirements {
+                    location: Some(LocationRequirement::Outside),
+                    ..Default::default()
+                },
+            }),
+            (PlayerLocation::InsideMech { .. }, _)
+                if perception.my_state.operating_station != Some(StationType::Repair) =>
+            {
+                Some(Task {
+                    name: "Repair Our Mech".to_string(),
+                    priority: urgency,
+                    action: TaskAction::OperateStation {
+                        station_type: StationType::Repair,
+                    },
+                    requirements: TaskRequirements {
+                        location: Some(LocationRequirement::InsideMech),
+                        ..Default::default()
+                    },
+                })
+            }
+            _ => None,
+        };
+        tasks.extend(task.map(|task| (task, urgency * bravery)));
+    }
+
+    if let (PlayerLocation::OutsideWorld(_), Some(door)) =
+        (perception.my_state.location, danger.fallback)
+    {
+        if danger.outnumbered_by > 0 {
+            let urgency = 0.8 + 0.2 * danger.outnumbered_by as f32;
+            tasks.push((
+                Task {
+                    name: "Fall Back".to_string(),
+                    priority: urgency,
+                    action: TaskAction::MoveToPosition {
+                        target: door,
+                        reason: format!("Outnumbered by {}", danger.outnumbered_by),
+                    },
+                    requirements: TaskRequirements {
+                        location: Some(LocationRequirement::Outside),
+                        ..Default::default()
+                    },
+                },
+                urgency * bravery,
+            ));
+        }
+    }
+
+    tasks
+}
+
+/// Where the AI is retreating to, if it chose to
+pub fn retreat_target(decision: &Decision, danger: &Danger) -> Option<WorldPos> {
+    match &decision.chosen_action {
+        Some(TaskAction::MoveToPosition { target, .. }) if Some(*target) == danger.fallback => {
+            Some(*target)
+        }
+        _ => None,
+    }
+}
+
+/// Point the AI's movement straight at its retreat
+pub fn head_for(
+    game_view: &GameView,
+    ai_id: Uuid,
+    target: WorldPos,
+    commands: &mut Vec<AICommand>,
+) {
+    let Some(PlayerLocation::OutsideWorld(position)) = game_view
+        .players
+        .iter()
+        .find(|p| p.id == ai_id)
+        .map(|p| p.location)
+    else {
+        return;
+    };
+    set_movement(ai_id, formation::steer_toward(position, target), commands);
+}
+
+/// Run out of the path of a mech about to flatten us, dashing clear when
+/// it's too close to walk
+pub fn dodge_mechs(ai_id: Uuid, danger: &Danger, commands: &mut Vec<AICommand>) {
+    if let Some(incoming) = danger.incoming_mech {
+        set_movement(ai_id, incoming.dodge, commands);
+        if incoming.time_to_contact <= AI_DASH_DODGE_TIME {
+            commands.extend(Facing::from_movement(incoming.dodge).map(|direction| {
+                AICommand::Dash {
+                    player_id: ai_id,
+                    direction,
+                }
+            }));
+        }
+    }
+}
+
+/// Replace the AI's movement command, adding one if it had none
+fn set_movement(ai_id: Uuid, movement: (f32, f32), commands: &mut Vec<AICommand>) {
+    commands.retain(|command| !matches!(command, AICommand::Move { .. }));
+    commands.push(AICommand::Move {
+        player_id: ai_id,
+        movement,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MechView, PlayerView, TeamInfo};
+    use std::collections::HashMap;
+
+    const HALF_MECH: f32 = MECH_SIZE_TILES as f32 * TILE_SIZE / 2.0;
+
+    fn on_foot(team: TeamId, position: WorldPos) -> PlayerView {
+        PlayerView {
+            id: Uuid::new_v4(),
+            name: "Bot".to_string(),
+            team,
+            location: PlayerLocation::OutsideWorld(position),
+            carrying_resource: None,
+            operating_station: None,
+        }
+    }
+
+    /// A mech whose center is at `center`
+    fn mech(team: TeamId, center: WorldPos, health: u32, velocity: (f32, f32)) -> MechView {
+        MechView {
+            id: Uuid::new_v4(),
+            team,
+            position: WorldPos::new(center.x - HALF_MECH, center.y - HALF_MECH),
+            health,
+            max_health: MECH_MAX_HEALTH,
+            shield: 0,
+            velocity,
+            turret: MechTurret::default(),
+            stations: Vec::new(),
+            resource_inventory: HashMap::new(),
+            alarm: None,
+        }
+    }
+
+    fn view(players: Vec<PlayerView>, mechs: Vec<MechView>) -> GameView {
+        GameView {
+            tick: 1,
+            players,
+            mechs,
+            resources: Vec::new(),
+            projectiles: Vec::new(),
+            team_info: TeamInfo {
+                team_id: TeamId::Red,
+                player_count: 0,
+                mech_count: 0,
+                total_resources: HashMap::new(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_crews_head_home_once_the_hull_drops_below_the_threshold() {
+        let at = |mech_health| Danger {
+            mech_health,
+            ..Danger::default()
+        };
+        assert!(!at(1.0).needs_repair());
+        assert!(!at(AI_RETREAT_MECH_HEALTH).needs_repair());
+        assert!(at(AI_RETREAT_MECH_HEALTH - 0.01).needs_repair());
+
+        let me = on_foot(TeamId::Red, WorldPos::new(2000.0, 2000.0));
+        let home = WorldPos::new(640.0, 640.0);
+        let hurt = (MECH_MAX_HEALTH as f32 * (AI_RETREAT_MECH_HEALTH - 0.1)) as u32;
+        let game_view = view(
+            vec![me.clone()],
+            vec![mech(TeamId::Red, home, hurt, (0.0, 0.0))],
+        );
+        let danger = Danger::assess(&game_view, me.id, TeamId::Red);
+        assert!(danger.needs_repair());
+        assert!(danger.fallback.is_some());
+    }
+
+    #[test]
+    fn test_outnumbered_counts_only_bots_on_foot_nearby() {
+        let spot = WorldPos::new(1000.0, 1000.0);
+        let me = on_foot(TeamId::Red, spot);
+        let close = WorldPos::new(spot.x + 3.0 * TILE_SIZE, spot.y);
+        let far = WorldPos::new(spot.x + (AI_OUTNUMBERED_RADIUS + 1.0) * TILE_SIZE, spot.y);
+        let players = vec![
+            me.clone(),
+            on_foot(TeamId::Blue, close),
+            on_foot(TeamId::Blue, close),
+            on_foot(TeamId::Blue, far),
+        ];
+        let danger = Danger::assess(&view(players.clone(), Vec::new()), me.id, TeamId::Red);
+        assert_eq!(danger.outnumbered_by, 1);
+
+        let mut evened = players;
+        evened.push(on_foot(TeamId::Red, close));
+        let danger = Danger::assess(&view(evened, Vec::new()), me.id, TeamId::Red);
+        assert_eq!(danger.outnumbered_by, 0);
+    }
+
+    #[test]
+    fn test_only_mechs_about_to_arrive_are_dodged() {
+        let spot = WorldPos::new(1000.0, 1000.0);
+        let me = on_foot(TeamId::Red, spot);
+        let west = WorldPos::new(spot.x - 10.0 * TILE_SIZE, spot.y);
+        let incoming = |velocity| {
+            Danger::assess(
+                &view(
+                    vec![me.clone()],
+                    vec![mech(TeamId::Blue, west, MECH_MAX_HEALTH, velocity)],
+                ),
+                me.id,
+                TeamId::Red,
+            )
+            .incoming_mech
+        };
+
+        // Ten tiles out at ten tiles a second is a second away
+        let charging = incoming((10.0, 0.0)).expect("mech is bearing down");
+        assert!((charging.time_to_contact - 1.0).abs() < 1e-4);
+        assert_eq!(charging.dodge.0, 0.0);
+        // Too slow to arrive within the lookahead, parked, or heading away
+        assert!(incoming((3.0, 0.0)).is_none());
+        assert!(incoming((AI_MECH_MOVING_SPEED / 2.0, 0.0)).is_none());
+        assert!(incoming((-10.0, 0.0)).is_none());
+
+        // Walk aside while there's time, dash once it's close
+        let mut danger = Danger {
+            incoming_mech: Some(charging),
+            ..Danger::default()
+        };
+        let mut commands = Vec::new();
+        dodge_mechs(me.id, &danger, &mut commands);
+        assert!(!commands
+            .iter()
+            .any(|command| matches!(command, AICommand::Dash { .. })));
+
+        danger.incoming_mech = Some(IncomingMech {
+            time_to_contact: AI_DASH_DODGE_TIME,
+            ..charging
+        });
+        let mut commands = Vec::new();
+        dodge_mechs(me.id, &danger, &mut commands);
+        assert!(commands
+            .iter()
+            .any(|command| matches!(command, AICommand::Dash { .. })));
+    }
+}
@@ -0,0 +1,378 @@
+//! Where it's dangerous to stand.
+//!
+//! The manager keeps one threat map per team, a coarse grid of danger
+//! scores over the arena. Enemy shots mark the lane they're flying down and
+//! the mark fades over a few seconds, so a bot remembers a fire lane after
+//! the shot has gone; enemy turret cones and the ground around enemy mechs
+//! are rebuilt every update. Movement commands are then bent around the
+//! worst of it.
+
+use crate::{self_preservation, AICommand, GameView};
+use shared::*;
+use std::f32::consts::FRAC_PI_6;
+use uuid::Uuid;
+
+/// Danger scores for one team, one cell per `AI_THREAT_CELL_TILES` square
+#[derive(Debug, Clone)]
+pub struct ThreatMap {
+    width: i32,  // cells
+    height: i32, // cells
+    /// Danger from shots seen recently, fading over time
+    fire: Vec<f32>,
+    /// Danger from where enemies are right now
+    presence: Vec<f32>,
+}
+
+impl Default for ThreatMap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ThreatMap {
+    pub fn new() -> Self {
+        let width = (ARENA_WIDTH_TILES + AI_THREAT_CELL_TILES - 1) / AI_THREAT_CELL_TILES;
+        let height = (ARENA_HEIGHT_TILES + AI_THREAT_CELL_TILES - 1) / AI_THREAT_CELL_TILES;
+        let cells = (width * height) as usize;
+        Self {
+            width,
+            height,
+            fire: vec![0.0; cells],
+            presence: vec![0.0; cells],
+        }
+    }
+
+    /// Fade old fire and mark what `team`'s enemies are doing now
+    pub fn update(&mut self, game_view: &GameView, team: TeamId, delta_time: f32) {
+        let fade = 0.5_f32.powf(delta_time / AI_THREAT_FIRE_HALF_LIFE);
+        for danger in &mut self.fire {
+            *danger *= fade;
+        }
+        for projectile in game_view
+            .projectiles
+            .iter()
+            .filter(|p| p.owner_team != team)
+        {
+            self.mark_projectile(projectile.position, projectile.velocity);
+        }
+
+        self.presence.iter_mut().for_each(|danger| *danger = 0.0);
+        for mech in game_view.mechs.iter().filter(|m| m.team != team) {
+            let center = WorldPos::new(
+                mech.position.x + MECH_SIZE_TILES as f32 * TILE_SIZE / 2.0,
+                mech.position.y + MECH_SIZE_TILES as f32 * TILE_SIZE / 2.0,
+            );
+            self.mark_mech(center);
+            self.mark_turret_cone(center, mech.turret.aim, mech.turret.spread());
+        }
+    }
+
+    /// Danger at a point, 0.0 when nothing threatens it
+    pub fn danger_at(&self, pos: WorldPos) -> f32 {
+        self.cell_index(pos)
+            .map_or(0.0, |index| self.fire[index] + self.presence[index])
+    }
+
+    /// Average danger along a straight walk
+    pub fn danger_along(&self, from: WorldPos, to: WorldPos) -> f32 {
+        let steps = (from.distance_to(to) / TILE_SIZE).ceil().max(1.0) as usize;
+        (1..=steps)
+            .map(|step| self.danger_at(lerp(from, to, step as f32 / steps as f32)))
+            .sum::<f32>()
+            / steps as f32
+    }
+
+    /// Where to head first on the way from `from` to `to`: straight there
+    /// unless swinging out to one side past the midpoint walks through
+    /// enough less danger to be worth the extra distance
+    pub fn safe_waypoint(&self, from: WorldPos, to: WorldPos) -> WorldPos {
+        let length = from.distance_to(to);
+        if length < f32::EPSILON || self.danger_along(from, to) < AI_THREAT_IGNORE {
+            return to;
+        }
+        // Danger times tiles walked, so longer legs count for more
+        let exposure =
+            |a: WorldPos, b: WorldPos| self.danger_along(a, b) * a.distance_to(b) / TILE_SIZE;
+        let cost = |via: WorldPos| {
+            let detour = (from.distance_to(via) + via.distance_to(to) - length) / TILE_SIZE;
+            exposure(from, via) + exposure(via, to) + AI_CARRIER_DETOUR_COST * detour
+        };
+
+        let middle = lerp(from, to, 0.5);
+        let side = ((from.y - to.y) / length, (to.x - from.x) / length);
+        let mut best = (to, exposure(from, to));
+        for offset in [1.0, -1.0, 2.0, -2.0] {
+            let reach = offset * AI_CARRIER_DETOUR * TILE_SIZE;
+            let via = WorldPos::new(middle.x + side.0 * reach, middle.y + side.1 * reach);
+            let via_cost = cost(via);
+            if via_cost < best.1 {
+                best = (via, via_cost);
+            }
+        }
+        best.0
+    }
+
+    /// The shot's flight path for the next little while
+    fn mark_projectile(&mut self, position: WorldPos, velocity: (f32, f32)) {
+        let end = WorldPos::new(
+            position.x + velocity.0 * AI_THREAT_PROJECTILE_LOOKAHEAD,
+            position.y + velocity.1 * AI_THREAT_PROJECTILE_LOOKAHEAD,
+        );
+        let step = AI_THREAT_CELL_TILES as f32 * TILE_SIZE / 2.0;
+        let steps = (position.distance_to(end) / step).ceil().max(1.0) as usize;
+        for i in 0..=steps {
+            if let Some(index) = self.cell_index(lerp(position, end, i as f32 / steps as f32)) {
+                self.fire[index] = self.fire[index].max(AI_THREAT_PROJECTILE);
+            }
+        }
+    }
+
+    /// Danger falling off with distance from an enemy mech
+    fn mark_mech(&mut self, center: WorldPos) {
+        let radius = AI_THREAT_MECH_RADIUS * TILE_SIZE;
+        self.mark_cells(|cell| {
+            let distance = cell.distance_to(center);
+            (distance < radius).then(|| AI_THREAT_MECH * (1.0 - distance / radius))
+        });
+    }
+
+    /// Everything the turret could hit where it's pointing now
+    fn mark_turret_cone(&mut self, origin: WorldPos, aim: f32, spread: f32) {
+        let range = TURRET_RANGE * TILE_SIZE;
+        let half_width = spread + AI_THREAT_TURRET_MARGIN;
+        self.mark_cells(|cell| {
+            let off_aim = angle_difference(MechTurret::angle_between(origin, cell), aim);
+            (cell.distance_to(origin) <= range && off_aim <= half_width).then_some(AI_THREAT_TURRET)
+        });
+    }
+
+    /// Add `danger` at every cell center it returns something for
+    fn mark_cells(&mut self, danger: impl Fn(WorldPos) -> Option<f32>) {
+        let cell_size = AI_THREAT_CELL_TILES as f32 * TILE_SIZE;
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let center =
+                    WorldPos::new((x as f32 + 0.5) * cell_size, (y as f32 + 0.5) * cell_size);
+                if let Some(danger) = danger(center) {
+                    self.presence[(y * self.width + x) as usize] += danger;
+                }
+            }
+        }
+    }
+
+    fn cell_index(&self, pos: WorldPos) -> Option<usize> {
+        let cell_size = AI_THREAT_CELL_TILES as f32 * TILE_SIZE;
+        let (x, y) = ((pos.x / cell_size).floor(), (pos.y / cell_size).floor());
+        let inside = x >= 0.0 && y >= 0.0 && x < self.width as f32 && y < self.height as f32;
+        inside.then(|| (y as i32 * self.width + x as i32) as usize)
+    }
+}
+
+/// Bend an AI's movement away from danger. Headings up to a right angle
+/// either side of where it wants to go are tried, and the one with the
+/// least danger ahead wins, with turning further aside costing a little so
+/// bots only detour when it's worth it.
+pub fn steer_around_danger(
+    threat_map: &ThreatMap,
+    game_view: &GameView,
+    ai_id: Uuid,
+    commands: &mut [AICommand],
+) {
+    let Some(PlayerLocation::OutsideWorld(position)) = game_view
+        .players
+        .iter()
+        .find(|p| p.id == ai_id)
+        .map(|p| p.location)
+    else {
+        return;
+    };
+    for command in commands.iter_mut() {
+        if let AICommand::Move { movement, .. } = command {
+            *movement = safest_heading(threat_map, position, *movement);
+        }
+    }
+}
+
+/// Walk a carrier toward `target` by way of the safest waypoint
+pub fn route_carrier(
+    threat_map: &ThreatMap,
+    game_view: &GameView,
+    ai_id: Uuid,
+    target: WorldPos,
+    commands: &mut Vec<AICommand>,
+) {
+    let Some(PlayerLocation::OutsideWorld(position)) = game_view
+        .players
+        .iter()
+        .find(|p| p.id == ai_id)
+        .map(|p| p.location)
+    else {
+        return;
+    };
+    let waypoint = threat_map.safe_waypoint(position, target);
+    self_preservation::head_for(game_view, ai_id, waypoint, commands);
+}
+
+/// The movement to use instead of `desired` from `position`
+pub fn safest_heading(
+    threat_map: &ThreatMap,
+    position: WorldPos,
+    desired: (f32, f32),
+) -> (f32, f32) {
+    let speed = (desired.0 * desired.0 + desired.1 * desired.1).sqrt();
+    if speed < f32::EPSILON {
+        return desired;
+    }
+    let lookahead = AI_THREAT_LOOKAHEAD * TILE_SIZE;
+    let danger_ahead = |heading: (f32, f32)| {
+        threat_map.danger_along(
+            position,
+            WorldPos::new(
+                position.x + heading.0 * lookahead,
+                position.y + heading.1 * lookahead,
+            ),
+        )
+    };
+
+    let straight = (desired.0 / speed, desired.1 / speed);
+    if danger_ahead(straight) < AI_THREAT_IGNORE {
+        return desired;
+    }
+
+    let mut best = (straight, f32::INFINITY);
+    for turn in [0.0, 1.0, -1.0, 2.0, -2.0, 3.0, -3.0] {
+        let angle = turn * FRAC_PI_6;
+        let (sin, cos) = angle.sin_cos();
+        let heading = (
+            straight.0 * cos - straight.1 * sin,
+            straight.0 * sin + straight.1 * cos,
+        );
+        let cost = danger_ahead(heading) + AI_THREAT_DETOUR_COST * (1.0 - cos);
+        if cost < best.1 {
+            best = (heading, cost);
+        }
+    }
+    (best.0 .0 * speed, best.0 .1 * speed)
+}
+
+fn lerp(from: WorldPos, to: WorldPos, t: f32) -> WorldPos {
+    WorldPos::new(from.x + (to.x - from.x) * t, from.y + (to.y - from.y) * t)
+}
+
+/// Smallest angle between two headings, in radians
+fn angle_difference(a: f32, b: f32) -> f32 {
+    let diff = (a - b).rem_euclid(std::f32::consts::TAU);
+    diff.min(std::f32::consts::TAU - diff)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MechView, ProjectileView, TeamInfo};
+    use std::collections::HashMap;
+
+    fn view(mechs: Vec<MechView>, projectiles: Vec<ProjectileView>) -> GameView {
+        GameView {
+            tick: 1,
+            players: Vec::new(),
+            mechs,
+            resources: Vec::new(),
+            projectiles,
+            team_info: TeamInfo {
+                team_id: TeamId::Red,
+                player_count: 0,
+                mech_count: 0,
+                total_resources: HashMap::new(),
+            },
+        }
+    }
+
+    fn blue_shot(position: WorldPos, velocity: (f32, f32)) -> ProjectileView {
+        ProjectileView {
+            id: Uuid::new_v4(),
+            position,
+            velocity,
+            owner_team: TeamId::Blue,
+        }
+    }
+
+    #[test]
+    fn test_shot_lanes_fade_by_half_every_half_life() {
+        let start = WorldPos::new(320.0, 320.0);
+        let shots = view(Vec::new(), vec![blue_shot(start, (200.0, 0.0))]);
+        let in_lane = WorldPos::new(start.x + 150.0, start.y);
+        let beside_lane = WorldPos::new(start.x + 150.0, start.y + 10.0 * TILE_SIZE);
+
+        let mut red = ThreatMap::new();
+        red.update(&shots, TeamId::Red, 0.0);
+        assert_eq!(red.danger_at(in_lane), AI_THREAT_PROJECTILE);
+        assert_eq!(red.danger_at(beside_lane), 0.0);
+
+        // A team's own shots aren't a threat to it
+        let mut blue = ThreatMap::new();
+        blue.update(&shots, TeamId::Blue, 0.0);
+        assert_eq!(blue.danger_at(in_lane), 0.0);
+
+        // The lane is remembered after the shot is gone, fading away
+        let empty = view(Vec::new(), Vec::new());
+        red.update(&empty, TeamId::Red, AI_THREAT_FIRE_HALF_LIFE);
+        assert!((red.danger_at(in_lane) - AI_THREAT_PROJECTILE / 2.0).abs() < 1e-4);
+        red.update(&empty, TeamId::Red, AI_THREAT_FIRE_HALF_LIFE);
+        assert!((red.danger_at(in_lane) - AI_THREAT_PROJECTILE / 4.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_enemy_mechs_only_threaten_while_they_are_there() {
+        let position = WorldPos::new(1280.0, 1280.0);
+        let center = WorldPos::new(
+            position.x + MECH_SIZE_TILES as f32 * TILE_SIZE / 2.0,
+            position.y + MECH_SIZE_TILES as f32 * TILE_SIZE / 2.0,
+        );
+        let mech = MechView {
+            id: Uuid::new_v4(),
+            team: TeamId::Blue,
+            position,
+            health: MECH_INITIAL_HEALTH,
+            max_health: MECH_MAX_HEALTH,
+            shield: MECH_INITIAL_SHIELD,
+            velocity: (0.0, 0.0),
+            turret: MechTurret::default(),
+            stations: Vec::new(),
+            resource_inventory: HashMap::new(),
+            alarm: None,
+        };
+
+        let mut threat_map = ThreatMap::new();
+        threat_map.update(&view(vec![mech], Vec::new()), TeamId::Red, 0.1);
+        let near = threat_map.danger_at(center);
+        let edge = WorldPos::new(
+            center.x,
+            center.y - (AI_THREAT_MECH_RADIUS - 1.0) * TILE_SIZE,
+        );
+        assert!(near > threat_map.danger_at(edge));
+        assert!(threat_map.danger_at(edge) > 0.0);
+        // Off the arena there's nothing to look up
+        assert_eq!(threat_map.danger_at(WorldPos::new(-100.0, -100.0)), 0.0);
+
+        threat_map.update(&view(Vec::new(), Vec::new()), TeamId::Red, 0.1);
+        assert_eq!(threat_map.danger_at(center), 0.0);
+    }
+
+    #[test]
+    fn test_headings_bend_around_a_fire_lane() {
+        let start = WorldPos::new(640.0, 640.0);
+        let mut threat_map = ThreatMap::new();
+        assert_eq!(safest_heading(&threat_map, start, (1.0, 0.0)), (1.0, 0.0));
+
+        // A shot crossing just ahead, top to bottom
+        let lane = WorldPos::new(start.x + 2.0 * TILE_SIZE, start.y - 200.0);
+        threat_map.update(
+            &view(Vec::new(), vec![blue_shot(lane, (0.0, 300.0))]),
+            TeamId::Red,
+            0.0,
+        );
+        let heading = safest_heading(&threat_map, start, (1.0, 0.0));
+        assert!(heading.0 < 1.0);
+        assert!((heading.0 * heading.0 + heading.1 * heading.1 - 1.0).abs() < 1e-4);
+    }
+}
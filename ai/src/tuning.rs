@@ -0,0 +1,106 @@
+//! Weights `UtilityAI` scores tasks with. The defaults are the numbers the
+//! bots were balanced against; a TOML file can override any of them at
+//! server start, and the debug client can push new ones while a match runs.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UtilityWeights {
+    /// Updates without moving before a bot counts as stuck
+    pub stuck_threshold: f32,
+    /// Multiplier on moving somewhere while stuck
+    pub stuck_penalty: f32,
+    /// Distance in pixels at which a move is worth half as much
+    pub distance_falloff: f32,
+    /// Multiplier on taking a station while already at one
+    pub at_station_bonus: f32,
+    /// Multiplier on collecting a resource the bot knows the location of
+    pub known_resource_bonus: f32,
+    /// Multiplier on attacking for aggressive bots
+    pub aggressive_attack_bonus: f32,
+    /// Share of every score kept at difficulty 0; difficulty 1 keeps all of it
+    pub difficulty_floor: f32,
+    /// Engine need with nobody piloting, and with a pilot
+    pub engine_unmanned: f32,
+    pub engine_manned: f32,
+    /// Weapon need with threats around, and without
+    pub weapons_threatened: f32,
+    pub weapons_idle: f32,
+    /// Shield need under a serious threat, and otherwise
+    pub shield_threatened: f32,
+    pub shield_idle: f32,
+    /// Threat severity that counts as serious for the shield
+    pub shield_threat_severity: f32,
+    /// Repair need is this minus the team's average mech health (0-1)
+    pub repair_urgency: f32,
+    /// Multiplier on going after something a teammate has claimed
+    pub claimed_penalty: f32,
+    /// Multiplier on doing what one of the team's mechs is waiting on
+    pub mech_need_bonus: f32,
+}
+
+impl Default for UtilityWeights {
+    fn default() -> Self {
+        Self {
+            stuck_threshold: 3.0,
+            stuck_penalty: 0.5,
+            distance_falloff: 100.0,
+            at_station_bonus: 1.5,
+            known_resource_bonus: 1.2,
+            aggressive_attack_bonus: 1.5,
+            difficulty_floor: 0.5,
+            engine_unmanned: 2.0,
+            engine_manned: 0.5,
+            weapons_threatened: 1.5,
+            weapons_idle: 0.7,
+            shield_threatened: 1.3,
+            shield_idle: 0.6,
+            shield_threat_severity: 0.5,
+            repair_urgency: 2.0,
+            claimed_penalty: 0.2,
+            mech_need_bonus: 1.5,
+        }
+    }
+}
+
+impl UtilityWeights {
+    /// Parse weights from TOML; keys left out keep their defaults
+    pub fn from_toml(text: &str) -> Result<Self, String> {
+        toml::from_str(text).map_err(|e| e.to_string())
+    }
+
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).unwrap_or_default()
+    }
+
+    /// Read weights from the TOML file at `path`
+    pub fn load(path: &std::path::Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("can't read {}: {e}", path.display()))?;
+        Self::from_toml(&text).map_err(|e| format!("{}: {e}", path.display()))
+    }
+
+    /// Every weight by name, for editing them one at a time
+    pub fn entries_mut(&mut self) -> [(&'static str, &mut f32); 17] {
+        [
+            ("stuck_threshold", &mut self.stuck_threshold),
+            ("stuck_penalty", &mut self.stuck_penalty),
+            ("distance_falloff", &mut self.distance_falloff),
+            ("at_station_bonus", &mut self.at_station_bonus),
+            ("known_resource_bonus", &mut self.known_resource_bonus),
+            ("aggressive_attack_bonus", &mut self.aggressive_attack_bonus),
+            ("difficulty_floor", &mut self.difficulty_floor),
+            ("engine_unmanned", &mut self.engine_unmanned),
+            ("engine_manned", &mut self.engine_manned),
+            ("weapons_threatened", &mut self.weapons_threatened),
+            ("weapons_idle", &mut self.weapons_idle),
+            ("shield_threatened", &mut self.shield_threatened),
+            ("shield_idle", &mut self.shield_idle),
+            ("shield_threat_severity", &mut self.shield_threat_severity),
+            ("repair_urgency", &mut self.repair_urgency),
+            ("claimed_penalty", &mut self.claimed_penalty),
+            ("mech_need_bonus", &mut self.mech_need_bonus),
+        ]
+    }
+}
@@ -1,7 +1,10 @@
 use crate::{
-    AIController, AIDebugInfo, AIMessage, Decision, GameView, HatManager, IntelInfo, Perception,
-    Personality, Status, Task, TaskAction,
+    escort_request, self_preservation, AIController, AIDebugInfo, AIMessage, Decision, GameView,
+    Hat, HatManager, IntelInfo, MechNeed, Perception, Personality, Status, Task, TaskAction,
+    UtilityWeights,
 };
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use shared::*;
 use std::collections::HashMap;
 use uuid::Uuid;
@@ -15,6 +18,8 @@ pub struct UtilityAI {
     last_decision: Option<Decision>,
     decision_history: Vec<(String, f32)>, // (action_name, score)
     state: AIState,
+    weights: UtilityWeights,
+    rng: StdRng,
 }
 
 /// Simple AI for easier difficulties
@@ -53,11 +58,20 @@ impl UtilityAI {
                 known_resources: HashMap::new(),
                 recent_threats: Vec::new(),
             },
+            weights: UtilityWeights::default(),
+            rng: StdRng::seed_from_u64(id.as_u64_pair().0),
         }
     }
 
+    /// Roll from `rng` instead of the stream seeded from the AI's id
+    pub fn with_rng(mut self, rng: StdRng) -> Self {
+        self.rng = rng;
+        self
+    }
+
     /// Calculate utility score for a task
     fn calculate_utility(&self, task: &Task, perception: &Perception) -> f32 {
+        let w = &self.weights;
         let mut score = task.priority;
 
         // Adjust based on personality
@@ -67,25 +81,51 @@ impl UtilityAI {
         match &task.action {
             TaskAction::MoveToPosition { target, .. } => {
                 // Penalize if we're stuck
-                if self.state.stuck_counter > 3 {
-                    score *= 0.5;
+                if self.state.stuck_counter as f32 > w.stuck_threshold {
+                    score *= w.stuck_penalty;
+                }
+
+                // Leave resources a teammate is already fetching to them
+                if perception
+                    .blackboard
+                    .resource_claimed_by_other(*target, self.id)
+                {
+                    score *= w.claimed_penalty;
                 }
 
                 // Consider distance
                 if let Some(my_pos) = self.get_my_position(perception) {
                     let distance = my_pos.distance_to(*target);
-                    score *= 1.0 / (1.0 + distance / 100.0);
+                    score *= 1.0 / (1.0 + distance / w.distance_falloff.max(1.0));
                 }
             }
 
             TaskAction::OperateStation { station_type } => {
                 // Bonus if we're already at a station
                 if perception.my_state.operating_station.is_some() {
-                    score *= 1.5;
+                    score *= w.at_station_bonus;
                 }
 
                 // Consider team needs
                 score *= self.evaluate_station_need(*station_type, perception);
+
+                // Don't race a teammate to the last free station
+                let blackboard = &perception.blackboard;
+                if perception.my_state.operating_station != Some(*station_type)
+                    && blackboard.open_station(*station_type, self.id).is_none()
+                {
+                    score *= w.claimed_penalty;
+                }
+                let need = MechNeed::Crew {
+                    station_type: *station_type,
+                };
+                if blackboard.needs(need) {
+                    score *= w.mech_need_bonus;
+                }
+            }
+
+            TaskAction::RepairMech if perception.blackboard.needs(MechNeed::Repair) => {
+                score *= w.mech_need_bonus;
             }
 
             TaskAction::CollectResource { resource_type } => {
@@ -100,7 +140,7 @@ impl UtilityAI {
                         .values()
                         .any(|(_, t)| resource_type.map(|rt| rt == *t).unwrap_or(true))
                 {
-                    score *= 1.2;
+                    score *= w.known_resource_bonus;
                 }
             }
 
@@ -110,7 +150,7 @@ impl UtilityAI {
 
                 // Personality adjustment
                 if matches!(self.personality, Personality::Aggressive) {
-                    score *= 1.5;
+                    score *= w.aggressive_attack_bonus;
                 }
             }
 
@@ -118,7 +158,7 @@ impl UtilityAI {
         }
 
         // Apply difficulty modifier (higher difficulty = better decisions)
-        score *= 0.5 + (self.difficulty * 0.5);
+        score *= w.difficulty_floor + self.difficulty * (1.0 - w.difficulty_floor);
 
         score
     }
@@ -133,6 +173,7 @@ impl UtilityAI {
 
     /// Evaluate how much a station is needed
     fn evaluate_station_need(&self, station_type: StationType, perception: &Perception) -> f32 {
+        let w = &self.weights;
         match station_type {
             StationType::Engine => {
                 // Critical if no one is piloting
@@ -144,25 +185,29 @@ impl UtilityAI {
                     .count()
                     == 0
                 {
-                    2.0
+                    w.engine_unmanned
                 } else {
-                    0.5
+                    w.engine_manned
                 }
             }
-            StationType::WeaponLaser | StationType::WeaponProjectile => {
+            StationType::WeaponLaser | StationType::WeaponProjectile | StationType::WeaponEmp => {
                 // Important if threats exist
                 if !perception.threats.is_empty() {
-                    1.5
+                    w.weapons_threatened
                 } else {
-                    0.7
+                    w.weapons_idle
                 }
             }
             StationType::Shield => {
                 // Important if under attack
-                if perception.threats.iter().any(|t| t.severity > 0.5) {
-                    1.3
+                if perception
+                    .threats
+                    .iter()
+                    .any(|t| t.severity > w.shield_threat_severity)
+                {
+                    w.shield_threatened
                 } else {
-                    0.6
+                    w.shield_idle
                 }
             }
             StationType::Repair => {
@@ -174,7 +219,7 @@ impl UtilityAI {
                     .map(|(h, _)| *h as f32 / 100.0)
                     .sum::<f32>()
                     / perception.team_state.mech_health.len().max(1) as f32;
-                2.0 - avg_health
+                w.repair_urgency - avg_health
             }
             _ => 1.0,
         }
@@ -197,7 +242,7 @@ impl UtilityAI {
         // Update known resources
         for resource in &perception.environment.nearby_resources {
             // Add to known resources (would need resource ID in real implementation)
-            let fake_id = Uuid::new_v4();
+            let fake_id = uuid::Builder::from_random_bytes(self.rng.gen()).into_uuid();
             self.state.known_resources.insert(fake_id, *resource);
         }
 
@@ -240,8 +285,13 @@ impl UtilityAI {
             ));
         }
 
-        // Report hat changes
+        // Call for cover while hauling past enemies
         let current_hat = self.hat_manager.get_active_hat();
+        if current_hat == Hat::Carrier {
+            messages.extend(escort_request(perception));
+        }
+
+        // Report hat changes
         messages.push(AIMessage::status(
             self.id,
             Status::ChangingHat {
@@ -285,6 +335,10 @@ impl AIController for UtilityAI {
                 (task, score)
             })
             .collect();
+        scored_tasks.extend(self_preservation::retreat_tasks(
+            perception,
+            self.personality,
+        ));
 
         // Sort by score
         scored_tasks.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
@@ -317,6 +371,14 @@ impl AIController for UtilityAI {
         decision
     }
 
+    fn hat(&self) -> Option<Hat> {
+        Some(self.hat_manager.get_active_hat())
+    }
+
+    fn assign_hat(&mut self, hat: Hat) {
+        self.hat_manager.assign(hat);
+    }
+
     fn get_debug_info(&self) -> AIDebugInfo {
         AIDebugInfo {
             ai_id: self.id,
@@ -344,6 +406,14 @@ impl AIController for UtilityAI {
         self.last_decision = None;
         self.decision_history.clear();
     }
+
+    fn utility_weights(&self) -> Option<&UtilityWeights> {
+        Some(&self.weights)
+    }
+
+    fn set_utility_weights(&mut self, weights: UtilityWeights) {
+        self.weights = weights;
+    }
 }
 
 impl SimpleAI {
@@ -384,7 +454,15 @@ impl AIController for SimpleAI {
         self.hat_manager.update_hat(perception);
         let tasks = self.hat_manager.get_current_tasks(perception);
 
-        let selected_task = if !tasks.is_empty() {
+        // ...unless it's in trouble, then it picks the way out
+        let retreat = self_preservation::retreat_tasks(perception, self.personality)
+            .into_iter()
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(task, _)| task);
+
+        let selected_task = if retreat.is_some() {
+            retreat
+        } else if !tasks.is_empty() {
             let index = (perception.my_id.as_u128() as usize
                 + perception.team_state.mech_health.len())
                 % tasks.len();
@@ -400,10 +478,21 @@ impl AIController for SimpleAI {
                 "Simple AI - Hat: {}",
                 self.hat_manager.get_active_hat().name()
             ),
-            messages: Vec::new(),
+            messages: match self.hat_manager.get_active_hat() {
+                Hat::Carrier => escort_request(perception).into_iter().collect(),
+                _ => Vec::new(),
+            },
         }
     }
 
+    fn hat(&self) -> Option<Hat> {
+        Some(self.hat_manager.get_active_hat())
+    }
+
+    fn assign_hat(&mut self, hat: Hat) {
+        self.hat_manager.assign(hat);
+    }
+
     fn get_debug_info(&self) -> AIDebugInfo {
         AIDebugInfo {
             ai_id: self.id,
@@ -0,0 +1,186 @@
+//! The one place a [`GameView`] is made. The server's bots and the debug
+//! tooling both build views here from the same snapshot clients receive, so
+//! what an AI is told is exactly what its team could know: its own side in
+//! full, and the enemy only where the team can see.
+
+use crate::{GameView, MechView, PlayerView, ProjectileView, ResourceView, StationView, TeamInfo};
+use shared::*;
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+/// A game snapshot waiting to be cut down to each team's view
+pub struct GameViewBuilder<'a> {
+    tick: u64,
+    players: Vec<&'a PlayerState>,
+    mechs: HashMap<Uuid, &'a MechState>,
+    resources: Vec<&'a ResourceState>,
+    projectiles: Vec<&'a ProjectileState>,
+}
+
+impl<'a> GameViewBuilder<'a> {
+    pub fn new(
+        tick: u64,
+        players: impl IntoIterator<Item = &'a PlayerState>,
+        mechs: impl IntoIterator<Item = &'a MechState>,
+        resources: impl IntoIterator<Item = &'a ResourceState>,
+        projectiles: impl IntoIterator<Item = &'a ProjectileState>,
+    ) -> Self {
+        Self {
+            tick,
+            players: players.into_iter().collect(),
+            mechs: mechs.into_iter().map(|mech| (mech.id, mech)).collect(),
+            resources: resources.into_iter().collect(),
+            projectiles: projectiles.into_iter().collect(),
+        }
+    }
+
+    /// Read a `GameState` message; `None` for any other message
+    pub fn from_snapshot(snapshot: &'a ServerMessage, tick: u64) -> Option<Self> {
+        let ServerMessage::GameState {
+            players,
+            mechs,
+            resources,
+            projectiles,
+            ..
+        } = snapshot
+        else {
+            return None;
+        };
+        Some(Self::new(
+            tick,
+            players.values(),
+            mechs.values(),
+            resources,
+            projectiles,
+        ))
+    }
+
+    /// What `team` knows, seeing the tiles in `visible`. Without a visible
+    /// set the team sees the whole map.
+    pub fn build(&self, team: TeamId, visible: Option<&HashSet<TilePos>>) -> GameView {
+        let sees = |tile: TilePos| visible.is_none_or(|visible| visible.contains(&tile));
+        let mech_team = |mech_id: &Uuid| self.mechs.get(mech_id).map(|mech| mech.team);
+
+        let players = self
+            .players
+            .iter()
+            .filter(|player| {
+                player.team == team
+                    || match player.location {
+                        PlayerLocation::OutsideWorld(pos) => sees(pos.to_tile_pos()),
+                        // Boarders are spotted; crew inside their own mech aren't
+                        PlayerLocation::InsideMech { mech_id, .. } => {
+                            mech_team(&mech_id) == Some(team)
+                        }
+                    }
+            })
+            .map(|player| PlayerView {
+                id: player.id,
+                name: player.name.clone(),
+                team: player.team,
+                location: player.location,
+                carrying_resource: player.inventory.resources().next(),
+                operating_station: player.operating_station.and_then(|station_id| {
+                    self.mechs
+                        .values()
+                        .flat_map(|mech| &mech.stations)
+                        .find(|station| station.id == station_id)
+                        .map(|station| station.station_type)
+                }),
+            })
+            .collect();
+
+        let mechs: Vec<MechView> = self
+            .mechs
+            .values()
+            .filter(|mech| mech.team == team || footprint(mech.position).any(sees))
+            .map(|mech| {
+                let ours = mech.team == team;
+                MechView {
+                    id: mech.id,
+                    team: mech.team,
+                    position: mech.world_position,
+                    health: mech.health,
+                    max_health: mech.max_health,
+                    shield: mech.shield,
+                    velocity: mech.drive.velocity(),
+                    turret: mech.turret,
+                    // The enemy's layout is no secret, who's crewing it is
+                    stations: mech
+                        .stations
+                        .iter()
+                        .map(|station| StationView {
+                            id: station.id,
+                            station_type: station.station_type,
+                            operated_by: station.operated_by.filter(|_| ours),
+                            position: station.position,
+                            floor: station.floor,
+                        })
+                        .collect(),
+                    resource_inventory: if ours {
+                        mech.resource_inventory.clone()
+                    } else {
+                        HashMap::new()
+                    },
+                    alarm: mech.alarm.filter(|_| ours),
+                }
+            })
+            .collect();
+
+        let resources = self
+            .resources
+            .iter()
+            .filter(|resource| sees(resource.position))
+            .map(|resource| ResourceView {
+                id: resource.id,
+                position: resource.position.to_world_pos(),
+                resource_type: resource.resource_type,
+            })
+            .collect();
+
+        let projectiles = self
+            .projectiles
+            .iter()
+            // Interceptors only hit other shots, so they're no threat
+            .filter(|projectile| projectile.weapon_type != WeaponType::Interceptor)
+            .filter_map(|projectile| {
+                let owner_team = mech_team(&projectile.owner_mech_id)?;
+                let view = ProjectileView {
+                    id: projectile.id,
+                    position: projectile.position,
+                    velocity: projectile.velocity,
+                    owner_team,
+                };
+                (owner_team == team || sees(projectile.position.to_tile_pos())).then_some(view)
+            })
+            .collect();
+
+        let mut total_resources = HashMap::new();
+        for mech in mechs.iter().filter(|mech| mech.team == team) {
+            for (resource_type, count) in &mech.resource_inventory {
+                *total_resources.entry(*resource_type).or_insert(0) += count;
+            }
+        }
+        let team_info = TeamInfo {
+            team_id: team,
+            player_count: self.players.iter().filter(|p| p.team == team).count(),
+            mech_count: mechs.iter().filter(|mech| mech.team == team).count(),
+            total_resources,
+        };
+
+        GameView {
+            tick: self.tick,
+            players,
+            mechs,
+            resources,
+            projectiles,
+            team_info,
+        }
+    }
+}
+
+/// Every tile a mech at `position` covers
+fn footprint(position: TilePos) -> impl Iterator<Item = TilePos> {
+    (0..MECH_SIZE_TILES)
+        .flat_map(move |dy| (0..MECH_SIZE_TILES).map(move |dx| position.offset(dx, dy)))
+}
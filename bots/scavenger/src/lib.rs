@@ -0,0 +1,138 @@
+//! A minimal custom bot built on the `ai` crate's bot SDK, kept in a crate
+//! of its own the way any third-party bot would be.
+//!
+//! The scavenger ignores combat entirely: it walks to the most valuable
+//! resource it knows about and hauls it back to the nearest friendly mech.
+//! It registers itself with [`ai::register_bot!`]; build the server with
+//! `--features scavenger` and start it with `--bot scavenger` to play
+//! against it.
+
+use ai::*;
+use uuid::Uuid;
+
+pub struct Scavenger {
+    id: Uuid,
+    difficulty: f32,
+}
+
+impl Scavenger {
+    pub fn boxed(id: Uuid, difficulty: f32) -> Box<dyn AIController> {
+        Box::new(Self { id, difficulty })
+    }
+}
+
+ai::register_bot!("scavenger", Scavenger::boxed);
+
+impl AIController for Scavenger {
+    fn id(&self) -> Uuid {
+        self.id
+    }
+
+    fn decide(
+        &mut self,
+        perception: &Perception,
+        _messages: &[AIMessage],
+        _delta_time: f32,
+    ) -> Decision {
+        let (chosen_action, reasoning) = if perception.my_state.carrying_resource.is_some() {
+            match perception.my_state.nearest_safe_location {
+                Some(target) => (
+                    TaskAction::MoveToPosition {
+                        target,
+                        reason: "Deliver resource".to_string(),
+                    },
+                    "Carrying a resource, heading home",
+                ),
+                None => (TaskAction::Idle, "Carrying a resource but no mech nearby"),
+            }
+        } else {
+            // Opportunities arrive sorted by value for distance
+            let resource = perception
+                .opportunities
+                .iter()
+                .find_map(|o| match o.opportunity_type {
+                    OpportunityType::Resource { resource_type } => Some(resource_type),
+                    _ => None,
+                });
+            match resource {
+                Some(resource_type) => (
+                    TaskAction::CollectResource {
+                        resource_type: Some(resource_type),
+                    },
+                    "Going for the best resource in reach",
+                ),
+                None => (TaskAction::Idle, "Nothing to collect"),
+            }
+        };
+
+        Decision {
+            chosen_action: Some(chosen_action),
+            confidence: self.difficulty,
+            reasoning: reasoning.to_string(),
+            messages: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_scavenger_registers_itself_and_goes_for_resources() {
+        let registry = BotRegistry::new();
+        assert!(registry.names().any(|name| name == "scavenger"));
+
+        let mut manager = AIManager::new(AIConfig {
+            debug_logging: false,
+            ..Default::default()
+        });
+        let bot = registry
+            .create("scavenger", Uuid::new_v4(), 0.8)
+            .expect("scavenger is registered");
+        let bot_id = manager.add_controller(bot);
+
+        // A tiny arena: our bot outside, one resource and one friendly mech
+        let game_view = GameView {
+            tick: 1,
+            players: vec![PlayerView {
+                id: bot_id,
+                name: "Bot_scavenger".to_string(),
+                team: TeamId::Red,
+                location: PlayerLocation::OutsideWorld(WorldPos::new(640.0, 640.0)),
+                carrying_resource: None,
+                operating_station: None,
+            }],
+            mechs: vec![MechView {
+                id: Uuid::new_v4(),
+                team: TeamId::Red,
+                position: WorldPos::new(320.0, 320.0),
+                health: MECH_INITIAL_HEALTH,
+                max_health: MECH_MAX_HEALTH,
+                shield: MECH_INITIAL_SHIELD,
+                velocity: (0.0, 0.0),
+                turret: MechTurret::default(),
+                stations: Vec::new(),
+                resource_inventory: HashMap::new(),
+                alarm: None,
+            }],
+            resources: vec![ResourceView {
+                id: Uuid::new_v4(),
+                position: WorldPos::new(800.0, 640.0),
+                resource_type: ResourceType::Batteries,
+            }],
+            projectiles: Vec::new(),
+            team_info: TeamInfo {
+                team_id: TeamId::Red,
+                player_count: 1,
+                mech_count: 1,
+                total_resources: HashMap::new(),
+            },
+        };
+
+        let commands = manager.update(&[game_view], 0.1);
+        assert!(!commands.is_empty());
+    }
+}
@@ -8,6 +8,115 @@ let nextSocketId = 1;
 const messageQueues = new Map();
 const binaryMessageQueues = new Map();
 
+// In-flight and finished HTTP GETs (server browser), polled by id
+const httpRequests = new Map();
+let nextHttpRequestId = 1;
+
+// Push-to-talk voice: the microphone encoded to opus with WebCodecs, and
+// teammates decoded and scheduled back to back through WebAudio
+const VOICE_SAMPLE_RATE = 48000;
+const voice = {
+    context: null,
+    micRequested: false,
+    micReady: false,
+    capturing: false,
+    encoder: null,
+    // Encoded microphone frames waiting for the game to send them
+    frames: [],
+    // Per speaker: decoder, volume for what it decodes, and when its next sound starts
+    speakers: new Map(),
+};
+
+function voiceContext() {
+    if (!voice.context) {
+        voice.context = new AudioContext({ sampleRate: VOICE_SAMPLE_RATE });
+    }
+    return voice.context;
+}
+
+function startMicrophone() {
+    voice.micRequested = true;
+    if (typeof AudioEncoder === 'undefined') {
+        console.warn('Voice chat needs WebCodecs, which this browser lacks');
+        return;
+    }
+    navigator.mediaDevices.getUserMedia({ audio: { channelCount: 1, echoCancellation: true, noiseSuppression: true } })
+        .then(stream => {
+            const context = voiceContext();
+            voice.encoder = new AudioEncoder({
+                output: chunk => {
+                    const bytes = new Uint8Array(chunk.byteLength);
+                    chunk.copyTo(bytes);
+                    voice.frames.push(bytes);
+                },
+                error: e => console.error('Voice encoder error:', e),
+            });
+            voice.encoder.configure({
+                codec: 'opus',
+                sampleRate: context.sampleRate,
+                numberOfChannels: 1,
+                bitrate: 24000,
+                opus: { frameDuration: 20000 },
+            });
+
+            // ScriptProcessor is deprecated, but unlike a worklet it needs no extra file
+            const source = context.createMediaStreamSource(stream);
+            const processor = context.createScriptProcessor(1024, 1, 1);
+            let timestamp = 0;
+            processor.onaudioprocess = event => {
+                if (!voice.capturing) {
+                    return;
+                }
+                const samples = event.inputBuffer.getChannelData(0);
+                const data = new AudioData({
+                    format: 'f32-planar',
+                    sampleRate: context.sampleRate,
+                    numberOfFrames: samples.length,
+                    numberOfChannels: 1,
+                    timestamp,
+                    data: samples,
+                });
+                timestamp += samples.length * 1e6 / context.sampleRate;
+                voice.encoder.encode(data);
+                data.close();
+            };
+            source.connect(processor);
+            processor.connect(context.destination);
+            voice.micReady = true;
+        })
+        .catch(e => console.warn('No microphone for voice chat:', e));
+}
+
+function voiceSpeaker(key) {
+    let speaker = voice.speakers.get(key);
+    if (speaker) {
+        return speaker;
+    }
+    speaker = { volume: 1, playAt: 0, decoder: null };
+    speaker.decoder = new AudioDecoder({
+        output: audioData => {
+            const context = voiceContext();
+            const buffer = context.createBuffer(1, audioData.numberOfFrames, audioData.sampleRate);
+            audioData.copyTo(buffer.getChannelData(0), { planeIndex: 0 });
+            audioData.close();
+
+            const gain = context.createGain();
+            gain.gain.value = speaker.volume;
+            gain.connect(context.destination);
+            const source = context.createBufferSource();
+            source.buffer = buffer;
+            source.connect(gain);
+            const start = Math.max(context.currentTime, speaker.playAt);
+            source.start(start);
+            speaker.playAt = start + buffer.duration;
+        },
+        error: e => console.error('Voice decoder error:', e),
+    });
+    speaker.decoder.configure({ codec: 'opus', sampleRate: VOICE_SAMPLE_RATE, numberOfChannels: 1 });
+    voice.speakers.set(key, speaker);
+    return speaker;
+}
+
 // Register the module that will be imported by WASM
 miniquad_add_plugin({
     register_plugin: function (importObject) {
@@ -88,6 +197,12 @@ miniquad_add_plugin({
                 return (socket && socket.readyState === WebSocket.OPEN) ? 1 : 0;
             },
 
+            // Closed sockets are forgotten in onclose, so a missing one is closed too
+            js_ws_is_closed: function (socketId) {
+                const socket = sockets.get(socketId);
+                return (!socket || socket.readyState >= WebSocket.CLOSING) ? 1 : 0;
+            },
+
             js_ws_poll_message: function (socketId, bufferPtr, bufferLen) {
                 const queue = messageQueues.get(socketId);
                 if (!queue || queue.length === 0) {
@@ -128,6 +243,81 @@ miniquad_add_plugin({
 
                 return binaryMessage.length;
             },
+
+            js_voice_set_capturing: function (capturing) {
+                if (capturing && !voice.micRequested) {
+                    startMicrophone();
+                }
+                voice.capturing = capturing !== 0 && voice.micReady;
+                if (voice.capturing && voice.context.state === 'suspended') {
+                    voice.context.resume();
+                }
+                return voice.capturing ? 1 : 0;
+            },
+
+            js_voice_poll_frame: function (bufferPtr, bufferLen) {
+                const frame = voice.frames.shift();
+                if (!frame || frame.length > bufferLen) {
+                    return -1;
+                }
+                const buffer = new Uint8Array(wasm_memory.buffer, bufferPtr, bufferLen);
+                buffer.set(frame);
+                return frame.length;
+            },
+
+            js_voice_play: function (speakerPtr, dataPtr, dataLen, volume) {
+                if (typeof AudioDecoder === 'undefined') {
+                    return;
+                }
+                const speakerBytes = new Uint8Array(wasm_memory.buffer, speakerPtr, 16);
+                const key = Array.from(speakerBytes, b => b.toString(16).padStart(2, '0')).join('');
+                const speaker = voiceSpeaker(key);
+                speaker.volume = volume;
+                // Copy out of WASM memory, which may move once we return
+                const data = new Uint8Array(wasm_memory.buffer, dataPtr, dataLen).slice();
+                speaker.decoder.decode(new EncodedAudioChunk({ type: 'key', timestamp: 0, data }));
+            },
+
+            js_http_get: function (urlPtr, urlLen) {
+                const url = UTF8ToString(urlPtr, urlLen);
+                const requestId = nextHttpRequestId++;
+                httpRequests.set(requestId, { done: false });
+
+                fetch(url)
+                    .then(response => {
+                        if (!response.ok) {
+                            throw new Error(`HTTP ${response.status}`);
+                        }
+                        return response.arrayBuffer();
+                    })
+                    .then(body => httpRequests.set(requestId, { done: true, body: new Uint8Array(body) }))
+                    .catch(e => {
+                        console.error(`Request to ${url} failed:`, e);
+                        httpRequests.set(requestId, { done: true, body: null });
+                    });
+
+                return requestId;
+            },
+
+            js_http_poll: function (requestId, bufferPtr, bufferLen) {
+                const request = httpRequests.get(requestId);
+                if (!request) {
+                    return -2; // Unknown request
+                }
+                if (!request.done) {
+                    return -1; // Still in flight
+                }
+
+                httpRequests.delete(requestId);
+                if (!request.body || request.body.length > bufferLen) {
+                    return -2; // Failed or too large
+                }
+
+                const buffer = new Uint8Array(wasm_memory.buffer, bufferPtr, bufferLen);
+                buffer.set(request.body);
+
+                return request.body.length;
+            },
         };
 
 
@@ -180,6 +370,25 @@ miniquad_add_plugin({
                     console.error('Failed to check settings:', e);
                     return 0;
                 }
+            },
+
+            // Screenshot and clip downloads
+            js_download_file: function (namePtr, nameLen, dataPtr, dataLen) {
+                try {
+                    const name = UTF8ToString(namePtr, nameLen);
+                    // Copy out of WASM memory, which may move once we return
+                    const data = new Uint8Array(wasm_memory.buffer, dataPtr, dataLen).slice();
+                    const url = URL.createObjectURL(new Blob([data]));
+                    const link = document.createElement('a');
+                    link.href = url;
+                    link.download = name;
+                    link.click();
+                    setTimeout(() => URL.revokeObjectURL(url), 0);
+                    return 1;
+                } catch (e) {
+                    console.error('Failed to download file:', e);
+                    return 0;
+                }
             }
         };
 
@@ -0,0 +1,44 @@
+//! Accessibility options, saved with the rest of the settings and mirrored
+//! onto `GameState` every frame so rendering can read them. HUD text size
+//! lives in `ui_scale`, which the HUD, console and egui windows share and
+//! the camera's zoom never touches.
+
+use serde::{Deserialize, Serialize};
+use shared::render_constants::REDUCED_FLASH_INTENSITY;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AccessibilitySettings {
+    /// Team and resource colours that stay apart under colour blindness
+    pub colorblind: bool,
+    /// Dim explosions, laser beams and muzzle flashes, and stop interior
+    /// lights flickering on hits
+    pub reduced_flash: bool,
+}
+
+/// Which set of colours teams and resources are drawn in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Palette {
+    Standard,
+    /// Okabe-Ito colours, distinguishable with any common colour blindness
+    ColorblindSafe,
+}
+
+impl AccessibilitySettings {
+    pub fn palette(&self) -> Palette {
+        if self.colorblind {
+            Palette::ColorblindSafe
+        } else {
+            Palette::Standard
+        }
+    }
+
+    /// Multiplier on the brightness of flashes and beams
+    pub fn flash_intensity(&self) -> f32 {
+        if self.reduced_flash {
+            REDUCED_FLASH_INTENSITY
+        } else {
+            1.0
+        }
+    }
+}
@@ -0,0 +1,125 @@
+//! Player animation states.
+//!
+//! Each player's pose is worked out on the client from what is already
+//! synced: how far they moved between updates, whether they're carrying
+//! anything and whether they crew a station. Which way they face comes from
+//! the server's `PlayerFacing`, or straight from input for the local player.
+
+use shared::{render_constants::*, types::WorldPos};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnimationState {
+    Idle,
+    Walking,
+    /// Hands full, standing or walking
+    Carrying,
+    /// Crewing a station
+    Operating,
+}
+
+impl AnimationState {
+    /// Order of the state's block of rows in the sprite sheet
+    pub fn sheet_index(self) -> usize {
+        match self {
+            AnimationState::Idle => 0,
+            AnimationState::Walking => 1,
+            AnimationState::Carrying => 2,
+            AnimationState::Operating => 3,
+        }
+    }
+}
+
+pub struct PlayerAnimation {
+    pub state: AnimationState,
+    /// Feet moving; carrying uses it to pick between standing and walking
+    pub moving: bool,
+    /// Seconds into the current state's cycle
+    pub clock: f32,
+    last_position: Option<WorldPos>,
+    /// Seconds since the player last moved
+    still_for: f32,
+}
+
+impl PlayerAnimation {
+    pub fn new() -> Self {
+        Self {
+            state: AnimationState::Idle,
+            moving: false,
+            clock: 0.0,
+            last_position: None,
+            still_for: PLAYER_STOP_DELAY,
+        }
+    }
+
+    /// Step the state machine with where the player is now
+    pub fn update(&mut self, position: WorldPos, carrying: bool, operating: bool, delta: f32) {
+        let moved = self
+            .last_position
+            .map_or(0.0, |last| last.distance_to(position));
+        self.last_position = Some(position);
+
+        // Updates land less often than frames, so keep walking through the
+        // gaps; a jump is a teleport, not a step
+        if moved > f32::EPSILON && moved < PLAYER_TELEPORT_DISTANCE {
+            self.still_for = 0.0;
+        } else {
+            self.still_for += delta;
+        }
+        self.moving = self.still_for < PLAYER_STOP_DELAY;
+
+        let state = if operating {
+            AnimationState::Operating
+        } else if carrying {
+            AnimationState::Carrying
+        } else if self.moving {
+            AnimationState::Walking
+        } else {
+            AnimationState::Idle
+        };
+        if state != self.state {
+            self.state = state;
+            self.clock = 0.0;
+        } else {
+            self.clock += delta;
+        }
+    }
+
+    /// Which of `frames` frames to show; a carrier standing still holds the first
+    pub fn frame(&self, frames: usize) -> usize {
+        if frames == 0 || (self.state == AnimationState::Carrying && !self.moving) {
+            return 0;
+        }
+        (self.clock * PLAYER_ANIMATION_FPS) as usize % frames
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_walking_settles_to_idle_and_jobs_take_priority() {
+        let mut animation = PlayerAnimation::new();
+        let frame = 1.0 / 60.0;
+        animation.update(WorldPos::new(0.0, 0.0), false, false, frame);
+        assert_eq!(animation.state, AnimationState::Idle);
+
+        animation.update(WorldPos::new(2.0, 0.0), false, false, frame);
+        assert_eq!(animation.state, AnimationState::Walking);
+        // A frame without a position update keeps the stride going
+        animation.update(WorldPos::new(2.0, 0.0), false, false, frame);
+        assert_eq!(animation.state, AnimationState::Walking);
+        animation.update(WorldPos::new(2.0, 0.0), false, false, PLAYER_STOP_DELAY);
+        assert_eq!(animation.state, AnimationState::Idle);
+
+        // Teleports don't count as steps
+        animation.update(WorldPos::new(500.0, 0.0), false, false, frame);
+        assert_eq!(animation.state, AnimationState::Idle);
+
+        animation.update(WorldPos::new(500.0, 0.0), true, false, frame);
+        assert_eq!(animation.state, AnimationState::Carrying);
+        assert_eq!(animation.frame(4), 0, "standing carriers hold still");
+        animation.update(WorldPos::new(502.0, 0.0), true, true, frame);
+        assert_eq!(animation.state, AnimationState::Operating);
+    }
+}
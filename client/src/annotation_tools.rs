@@ -0,0 +1,174 @@
+//! The spectator's drawing toolbar. With drawing on, dragging across the
+//! map sketches a line, arrow or circle for one team or for everyone; the
+//! mark shows as a preview until the button comes up and is then sent as
+//! `Annotate`. The server echoes it back to everyone it's meant for.
+
+use crate::game_state::GameState;
+use egui::Context;
+use macroquad::prelude::{
+    is_mouse_button_down, is_mouse_button_released, mouse_position, MouseButton,
+};
+use shared::{
+    annotations::*,
+    messages::ClientMessage,
+    render_constants::ANNOTATION_MIN_DRAG,
+    types::{TeamId, WorldPos},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tool {
+    Line,
+    Arrow,
+    Circle,
+}
+
+impl Tool {
+    const ALL: [Tool; 3] = [Tool::Line, Tool::Arrow, Tool::Circle];
+
+    fn label(self) -> &'static str {
+        match self {
+            Tool::Line => "Line",
+            Tool::Arrow => "Arrow",
+            Tool::Circle => "Circle",
+        }
+    }
+
+    /// The shape dragging from `from` to `to` makes
+    fn shape(self, from: WorldPos, to: WorldPos) -> AnnotationShape {
+        match self {
+            Tool::Line => AnnotationShape::Line { from, to },
+            Tool::Arrow => AnnotationShape::Arrow { from, to },
+            Tool::Circle => AnnotationShape::Circle {
+                center: from,
+                radius: from.distance_to(to).min(ANNOTATION_MAX_RADIUS),
+            },
+        }
+    }
+}
+
+fn audience_label(audience: AnnotationAudience) -> &'static str {
+    match audience {
+        AnnotationAudience::Everyone => "Everyone",
+        AnnotationAudience::Team(TeamId::Red) => "Red team",
+        AnnotationAudience::Team(TeamId::Blue) => "Blue team",
+    }
+}
+
+pub struct AnnotationTools {
+    drawing: bool,
+    tool: Tool,
+    color: AnnotationColor,
+    audience: AnnotationAudience,
+    ttl: f32,
+    /// Where the drag in progress started
+    drag_from: Option<WorldPos>,
+}
+
+impl AnnotationTools {
+    pub fn new() -> Self {
+        Self {
+            drawing: false,
+            tool: Tool::Arrow,
+            color: AnnotationColor::Yellow,
+            audience: AnnotationAudience::Everyone,
+            ttl: 8.0,
+            drag_from: None,
+        }
+    }
+
+    /// Draw the toolbar while spectating and follow the mouse on the map.
+    /// Returns the mark to send once a drag finishes.
+    pub fn render_ui(&mut self, ctx: &Context, game: &mut GameState) -> Option<ClientMessage> {
+        if !game.spectating {
+            return None;
+        }
+        egui::Window::new("Draw")
+            .collapsible(true)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_TOP, [0.0, 8.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.drawing, "Drawing");
+                    for tool in Tool::ALL {
+                        ui.selectable_value(&mut self.tool, tool, tool.label());
+                    }
+                    for color in AnnotationColor::ALL {
+                        let (r, g, b) = color.rgb();
+                        let swatch = egui::Color32::from_rgb(
+                            (r * 255.0) as u8,
+                            (g * 255.0) as u8,
+                            (b * 255.0) as u8,
+                        );
+                        let selected = self.color == *color;
+                        let text =
+                            egui::RichText::new(if selected { "[#]" } else { " # " }).color(swatch);
+                        if ui.selectable_label(selected, text).clicked() {
+                            self.color = *color;
+                        }
+                    }
+                });
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_label("for")
+                        .selected_text(audience_label(self.audience))
+                        .show_ui(ui, |ui| {
+                            for audience in [
+                                AnnotationAudience::Everyone,
+                                AnnotationAudience::Team(TeamId::Red),
+                                AnnotationAudience::Team(TeamId::Blue),
+                            ] {
+                                ui.selectable_value(
+                                    &mut self.audience,
+                                    audience,
+                                    audience_label(audience),
+                                );
+                            }
+                        });
+                    ui.add(
+                        egui::Slider::new(&mut self.ttl, 1.0..=ANNOTATION_MAX_TTL)
+                            .suffix(" s")
+                            .text("shown for"),
+                    );
+                    if ui.button("Clear").clicked() {
+                        game.annotations.clear();
+                    }
+                });
+            });
+
+        if !self.drawing {
+            self.drag_from = None;
+            game.annotation_preview = None;
+            return None;
+        }
+        let (x, y) = mouse_position();
+        let at = game.camera.screen_to_world(x, y);
+        if self.drag_from.is_none() && is_mouse_button_down(MouseButton::Left) {
+            if ctx.wants_pointer_input() {
+                return None;
+            }
+            self.drag_from = Some(at);
+        }
+        let from = self.drag_from?;
+        let shape = self.tool.shape(from, at);
+        if !is_mouse_button_released(MouseButton::Left) {
+            // A release we never saw, e.g. off the window, drops the drag
+            game.annotation_preview =
+                is_mouse_button_down(MouseButton::Left).then_some((shape, self.color));
+            if game.annotation_preview.is_none() {
+                self.drag_from = None;
+            }
+            return None;
+        }
+
+        self.drag_from = None;
+        game.annotation_preview = None;
+        if from.distance_to(at) < ANNOTATION_MIN_DRAG || shape.problem().is_some() {
+            return None;
+        }
+        Some(ClientMessage::Annotate {
+            shape,
+            color: self.color,
+            audience: self.audience,
+            ttl: self.ttl,
+        })
+    }
+}
@@ -0,0 +1,311 @@
+//! Arena map editor.
+//!
+//! Paint world tiles, place mech spawns, resource spawners and hazards, then
+//! save to a JSON map the server loads with `--map <path>`.
+//!
+//! Usage: `cargo run --bin map_editor [path.json]`
+
+use macroquad::prelude::*;
+use shared::*;
+
+const DEFAULT_MAP_PATH: &str = "arena_map.json";
+const MIN_ZOOM: f32 = 0.1;
+const MAX_ZOOM: f32 = 2.0;
+const PAN_SPEED: f32 = 800.0; // screen pixels per second
+const HUD_HEIGHT: f32 = 70.0;
+
+const RESOURCE_CHOICES: [Option<ResourceType>; 6] = [
+    None,
+    Some(ResourceType::ScrapMetal),
+    Some(ResourceType::ComputerComponents),
+    Some(ResourceType::Wiring),
+    Some(ResourceType::Batteries),
+    Some(ResourceType::PowerCore),
+];
+
+#[derive(Clone, Copy, PartialEq)]
+enum Tool {
+    Paint(StaticTile),
+    MechSpawn(TeamId),
+    ResourceSpawner,
+    Hazard(HazardKind),
+}
+
+impl Tool {
+    fn label(&self, resource_choice: Option<ResourceType>) -> String {
+        match self {
+            Tool::Paint(tile) => format!("Paint {tile:?}"),
+            Tool::MechSpawn(team) => format!("{team:?} mech spawn"),
+            Tool::ResourceSpawner => match resource_choice {
+                Some(resource_type) => format!("Resource spawner ({resource_type:?})"),
+                None => "Resource spawner (random)".to_string(),
+            },
+            Tool::Hazard(kind) => format!("{kind:?} hazard"),
+        }
+    }
+}
+
+struct Editor {
+    map: ArenaMap,
+    path: String,
+    tool: Tool,
+    resource_choice: usize,
+    camera: Vec2, // world pixels at the top-left of the view
+    zoom: f32,
+    status: String,
+}
+
+impl Editor {
+    fn new(path: String) -> Self {
+        let (map, status) = match std::fs::read_to_string(&path) {
+            Ok(json) => match ArenaMap::from_json(&json) {
+                Ok(map) => (map, format!("Loaded {path}")),
+                Err(e) => (ArenaMap::default(), format!("Could not load {path}: {e}")),
+            },
+            Err(_) => (ArenaMap::default(), format!("New map, will save to {path}")),
+        };
+
+        Self {
+            map,
+            path,
+            tool: Tool::Paint(StaticTile::Rock),
+            resource_choice: 0,
+            camera: Vec2::ZERO,
+            zoom: 0.4,
+            status,
+        }
+    }
+
+    fn tile_size(&self) -> f32 {
+        TILE_SIZE * self.zoom
+    }
+
+    fn tile_under_mouse(&self) -> Option<TilePos> {
+        let (mx, my) = mouse_position();
+        if my < HUD_HEIGHT {
+            return None;
+        }
+        let world = self.camera + vec2(mx, my) / self.zoom;
+        let pos = TilePos::new(
+            (world.x / TILE_SIZE).floor() as i32,
+            (world.y / TILE_SIZE).floor() as i32,
+        );
+        self.map.in_bounds(pos).then_some(pos)
+    }
+
+    fn handle_input(&mut self) {
+        // Tool selection
+        const TILE_KEYS: [KeyCode; 5] = [
+            KeyCode::Key1,
+            KeyCode::Key2,
+            KeyCode::Key3,
+            KeyCode::Key4,
+            KeyCode::Key5,
+        ];
+        for (key, tile) in TILE_KEYS.iter().zip(PAINTABLE_TILES) {
+            if is_key_pressed(*key) {
+                self.tool = Tool::Paint(*tile);
+            }
+        }
+        let tool_keys = [
+            (KeyCode::R, Tool::MechSpawn(TeamId::Red)),
+            (KeyCode::B, Tool::MechSpawn(TeamId::Blue)),
+            (KeyCode::F, Tool::ResourceSpawner),
+            (KeyCode::H, Tool::Hazard(HazardKind::Radiation)),
+            (KeyCode::M, Tool::Hazard(HazardKind::Mine)),
+        ];
+        for (key, tool) in tool_keys {
+            if is_key_pressed(key) {
+                self.tool = tool;
+            }
+        }
+        if is_key_pressed(KeyCode::Tab) {
+            self.resource_choice = (self.resource_choice + 1) % RESOURCE_CHOICES.len();
+        }
+
+        // Camera
+        let delta = get_frame_time() * PAN_SPEED / self.zoom;
+        if is_key_down(KeyCode::A) || is_key_down(KeyCode::Left) {
+            self.camera.x -= delta;
+        }
+        if is_key_down(KeyCode::D) || is_key_down(KeyCode::Right) {
+            self.camera.x += delta;
+        }
+        if is_key_down(KeyCode::W) || is_key_down(KeyCode::Up) {
+            self.camera.y -= delta;
+        }
+        if is_key_down(KeyCode::S) || is_key_down(KeyCode::Down) {
+            self.camera.y += delta;
+        }
+        let (_, wheel) = mouse_wheel();
+        if wheel != 0.0 {
+            self.zoom = (self.zoom * if wheel > 0.0 { 1.1 } else { 0.9 }).clamp(MIN_ZOOM, MAX_ZOOM);
+        }
+
+        // Save with Ctrl+S; plain S pans the camera
+        let ctrl = is_key_down(KeyCode::LeftControl) || is_key_down(KeyCode::RightControl);
+        if ctrl && is_key_pressed(KeyCode::S) {
+            self.save();
+        }
+
+        // Editing
+        let Some(pos) = self.tile_under_mouse() else {
+            return;
+        };
+        if is_mouse_button_down(MouseButton::Right) {
+            self.map.clear(pos);
+        } else if is_mouse_button_down(MouseButton::Left) {
+            match self.tool {
+                Tool::Paint(tile) => self.map.set_tile(pos, tile),
+                Tool::MechSpawn(team) => self.map.set_mech_spawn(team, pos),
+                Tool::ResourceSpawner => self
+                    .map
+                    .set_resource_spawner(pos, RESOURCE_CHOICES[self.resource_choice]),
+                Tool::Hazard(kind) => self.map.set_hazard(pos, kind),
+            }
+        }
+    }
+
+    fn save(&mut self) {
+        self.status = match self.map.validate() {
+            Ok(()) => match std::fs::write(&self.path, self.map.to_json()) {
+                Ok(()) => format!("Saved {}", self.path),
+                Err(e) => format!("Failed to save {}: {e}", self.path),
+            },
+            Err(e) => format!("Not saved: {e}"),
+        };
+    }
+
+    fn to_screen(&self, pos: TilePos) -> Vec2 {
+        (vec2(pos.x as f32, pos.y as f32) * TILE_SIZE - self.camera) * self.zoom
+    }
+
+    fn draw(&self) {
+        clear_background(BLACK);
+        let size = self.tile_size();
+
+        // Ground
+        let (r, g, b) = map_tile_color(StaticTile::Grass);
+        let origin = self.to_screen(TilePos::new(0, 0));
+        draw_rectangle(
+            origin.x,
+            origin.y,
+            self.map.width as f32 * size,
+            self.map.height as f32 * size,
+            Color::new(r, g, b, 1.0),
+        );
+        for map_tile in &self.map.tiles {
+            let (r, g, b) = map_tile_color(map_tile.tile);
+            let p = self.to_screen(map_tile.position);
+            draw_rectangle(p.x, p.y, size, size, Color::new(r, g, b, 1.0));
+        }
+
+        // Grid lines once tiles are big enough to tell apart
+        if size >= 8.0 {
+            let grid = Color::new(0.0, 0.0, 0.0, 0.15);
+            for x in 0..=self.map.width {
+                let p = self.to_screen(TilePos::new(x, 0));
+                draw_line(
+                    p.x,
+                    p.y,
+                    p.x,
+                    p.y + self.map.height as f32 * size,
+                    1.0,
+                    grid,
+                );
+            }
+            for y in 0..=self.map.height {
+                let p = self.to_screen(TilePos::new(0, y));
+                draw_line(p.x, p.y, p.x + self.map.width as f32 * size, p.y, 1.0, grid);
+            }
+        }
+
+        // Hazards, spawners and mech footprints
+        for hazard in &self.map.hazards {
+            let (r, g, b) = hazard_color(hazard.kind);
+            let p = self.to_screen(hazard.position);
+            match hazard.kind {
+                HazardKind::Radiation => {
+                    draw_rectangle(p.x, p.y, size, size, Color::new(r, g, b, 0.6))
+                }
+                HazardKind::Mine => draw_circle(
+                    p.x + size / 2.0,
+                    p.y + size / 2.0,
+                    size / 3.0,
+                    Color::new(r, g, b, 1.0),
+                ),
+            }
+        }
+        for spawner in &self.map.resource_spawners {
+            let p = self.to_screen(spawner.position);
+            draw_circle(p.x + size / 2.0, p.y + size / 2.0, size / 3.0, GOLD);
+        }
+        for spawn in &self.map.mech_spawns {
+            let p = self.to_screen(spawn.position);
+            let color = match spawn.team {
+                TeamId::Red => RED,
+                TeamId::Blue => BLUE,
+            };
+            let footprint = MECH_SIZE_TILES as f32 * size;
+            draw_rectangle_lines(p.x, p.y, footprint, footprint, 3.0, color);
+            draw_text(
+                &format!("{:?}", spawn.team),
+                p.x + 4.0,
+                p.y + 20.0,
+                20.0,
+                color,
+            );
+        }
+
+        // Hover highlight
+        if let Some(pos) = self.tile_under_mouse() {
+            let p = self.to_screen(pos);
+            draw_rectangle_lines(p.x, p.y, size, size, 2.0, WHITE);
+        }
+
+        // HUD
+        draw_rectangle(
+            0.0,
+            0.0,
+            screen_width(),
+            HUD_HEIGHT,
+            Color::new(0.0, 0.0, 0.0, 0.8),
+        );
+        draw_text(
+            &format!(
+                "{} | Tool: {} | Hover: {}",
+                self.map.name,
+                self.tool.label(RESOURCE_CHOICES[self.resource_choice]),
+                self.tile_under_mouse()
+                    .map_or("-".to_string(), |p| format!("({}, {})", p.x, p.y)),
+            ),
+            10.0,
+            20.0,
+            20.0,
+            WHITE,
+        );
+        draw_text(
+            "1-5 tiles  R/B mech spawn  F resources (Tab: type)  H radiation  M mine  \
+             LMB place  RMB clear  WASD pan  wheel zoom  Ctrl+S save",
+            10.0,
+            42.0,
+            16.0,
+            LIGHTGRAY,
+        );
+        draw_text(&self.status, 10.0, 62.0, 16.0, YELLOW);
+    }
+}
+
+#[macroquad::main("Mech Arena Map Editor")]
+async fn main() {
+    let path = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| DEFAULT_MAP_PATH.to_string());
+    let mut editor = Editor::new(path);
+
+    loop {
+        editor.handle_input();
+        editor.draw();
+        next_frame().await;
+    }
+}
@@ -0,0 +1,376 @@
+//! Replay viewer.
+//!
+//! Plays back a match recorded with the server's `--record-replay <path>`
+//! through the game's own renderer, with no server needed. Recorded
+//! messages go through the same handler a live client uses, so what's on
+//! screen is what a spectator would have seen.
+//!
+//! Usage: `cargo run --bin replay-viewer <match.replay>`
+//!
+//! Space plays and pauses, Left/Right jump 5 seconds, -/= change speed and
+//! clicking the timeline seeks. WASD pans the free camera and the wheel
+//! zooms; F follows the next mech and C lets the camera go again. V cycles
+//! between seeing everything and seeing through either team's eyes.
+
+// The game's modules, shared with the client binary; whatever only the game
+// itself uses goes unused here
+#![allow(dead_code, unused_imports)]
+
+#[path = "../accessibility.rs"]
+mod accessibility;
+#[path = "../animation.rs"]
+mod animation;
+#[path = "../floor_manager.rs"]
+mod floor_manager;
+#[path = "../game_state.rs"]
+mod game_state;
+#[path = "../inspect.rs"]
+mod inspect;
+#[path = "../network_common.rs"]
+mod network_common;
+#[path = "../particles.rs"]
+mod particles;
+#[path = "../prediction.rs"]
+mod prediction;
+#[path = "../rendering/mod.rs"]
+mod rendering;
+#[path = "../vision.rs"]
+mod vision;
+
+use game_state::GameState;
+use macroquad::prelude::*;
+use rendering::{camera::FollowTarget, RenderFlags, Renderer};
+use shared::{
+    decode_replay, PlayerId, ReplayFrame, TeamId, WorldPos, ARENA_HEIGHT_TILES, ARENA_WIDTH_TILES,
+    FRAME_DELTA_SECONDS, TILE_SIZE,
+};
+use std::sync::{Arc, Mutex};
+
+const SPEEDS: [f32; 7] = [0.25, 0.5, 1.0, 2.0, 4.0, 8.0, 16.0];
+const NORMAL_SPEED: usize = 2;
+const JUMP_SECONDS: f32 = 5.0;
+const PAN_SPEED: f32 = 800.0; // screen pixels per second
+const TIMELINE_HEIGHT: f32 = 24.0;
+const TIMELINE_MARGIN: f32 = 20.0;
+
+/// Whose sight the match is drawn with
+#[derive(Clone, Copy, PartialEq)]
+enum Vision {
+    Everything,
+    Team(TeamId),
+}
+
+impl Vision {
+    fn next(self) -> Self {
+        match self {
+            Vision::Everything => Vision::Team(TeamId::Red),
+            Vision::Team(TeamId::Red) => Vision::Team(TeamId::Blue),
+            Vision::Team(TeamId::Blue) => Vision::Everything,
+        }
+    }
+
+    fn label(self) -> String {
+        match self {
+            Vision::Everything => "everything".to_string(),
+            Vision::Team(team) => format!("{team:?} team"),
+        }
+    }
+}
+
+struct Playback {
+    frames: Vec<ReplayFrame>,
+    /// Frames already handed to the game state
+    applied: usize,
+    /// Where playback is, in ticks; fractional between frames
+    tick: f64,
+    first_tick: u64,
+    last_tick: u64,
+    playing: bool,
+    speed: usize,
+    game_state: Arc<Mutex<GameState>>,
+}
+
+impl Playback {
+    fn new(frames: Vec<ReplayFrame>) -> Self {
+        let first_tick = frames.first().map_or(0, |frame| frame.tick);
+        let last_tick = frames.last().map_or(0, |frame| frame.tick);
+        let mut playback = Self {
+            frames,
+            applied: 0,
+            tick: first_tick as f64,
+            first_tick,
+            last_tick,
+            playing: true,
+            speed: NORMAL_SPEED,
+            game_state: Arc::new(Mutex::new(GameState::new())),
+        };
+        playback.seek(first_tick as f64);
+        playback
+    }
+
+    /// Move playback to `tick`. Going back means playing the match again
+    /// from the start, as messages can't be undone.
+    fn seek(&mut self, tick: f64) {
+        let tick = tick.clamp(self.first_tick as f64, self.last_tick as f64);
+        if tick < self.tick {
+            let fresh = GameState::new();
+            let mut game = self.game_state.lock().unwrap();
+            let camera = std::mem::replace(&mut *game, fresh).camera;
+            game.camera = camera;
+            self.applied = 0;
+        }
+        self.tick = tick;
+        while let Some(frame) = self.frames.get(self.applied) {
+            if frame.tick as f64 > tick {
+                break;
+            }
+            network_common::handle_server_message(frame.message.clone(), &self.game_state);
+            self.applied += 1;
+        }
+    }
+
+    /// Play on by `delta` seconds of wall time
+    fn advance(&mut self, delta: f32) {
+        if !self.playing {
+            return;
+        }
+        let ticks = (delta * SPEEDS[self.speed] / FRAME_DELTA_SECONDS) as f64;
+        self.seek(self.tick + ticks);
+        if self.tick >= self.last_tick as f64 {
+            self.playing = false;
+        }
+    }
+
+    fn seconds(&self, tick: f64) -> f32 {
+        (tick - self.first_tick as f64) as f32 * FRAME_DELTA_SECONDS
+    }
+}
+
+/// The player whose eyes `team` sees through: whoever the camera follows
+/// if they're on it, otherwise its first player
+fn viewpoint(game: &GameState, team: TeamId) -> Option<PlayerId> {
+    if let FollowTarget::Entity(id) = game.camera.follow {
+        if game
+            .players
+            .get(&id)
+            .is_some_and(|player| player.team == team)
+        {
+            return Some(id);
+        }
+    }
+    let mut crew: Vec<PlayerId> = game
+        .players
+        .iter()
+        .filter(|(_, player)| player.team == team)
+        .map(|(id, _)| *id)
+        .collect();
+    crew.sort();
+    crew.first().copied()
+}
+
+/// The arena in world pixels
+fn arena_size(game: &GameState) -> (f32, f32) {
+    let (width, height) = match &game.arena_map {
+        Some(map) => (map.width, map.height),
+        None => (ARENA_WIDTH_TILES, ARENA_HEIGHT_TILES),
+    };
+    (width as f32 * TILE_SIZE, height as f32 * TILE_SIZE)
+}
+
+fn timeline_rect() -> Rect {
+    Rect::new(
+        TIMELINE_MARGIN,
+        screen_height() - TIMELINE_MARGIN - TIMELINE_HEIGHT,
+        screen_width() - TIMELINE_MARGIN * 2.0,
+        TIMELINE_HEIGHT,
+    )
+}
+
+fn render_timeline(playback: &Playback, vision: Vision, camera: &str) {
+    let bar = timeline_rect();
+    let span = (playback.last_tick - playback.first_tick).max(1) as f64;
+    let progress = ((playback.tick - playback.first_tick as f64) / span) as f32;
+    draw_rectangle(bar.x, bar.y, bar.w, bar.h, Color::new(0.0, 0.0, 0.0, 0.6));
+    draw_rectangle(
+        bar.x,
+        bar.y,
+        bar.w * progress,
+        bar.h,
+        Color::new(0.3, 0.6, 1.0, 0.8),
+    );
+    draw_rectangle_lines(bar.x, bar.y, bar.w, bar.h, 1.0, WHITE);
+
+    let clock = |seconds: f32| format!("{}:{:02}", seconds as u32 / 60, seconds as u32 % 60);
+    let state = if playback.playing {
+        "Playing"
+    } else {
+        "Paused"
+    };
+    let status = format!(
+        "{state} {} / {}  x{}  tick {:.0}  |  Vision: {}  |  Camera: {camera}",
+        clock(playback.seconds(playback.tick)),
+        clock(playback.seconds(playback.last_tick as f64)),
+        SPEEDS[playback.speed],
+        playback.tick,
+        vision.label(),
+    );
+    draw_text(&status, bar.x, bar.y - 8.0, 20.0, WHITE);
+}
+
+/// What the viewer is looking at, apart from where in the match
+struct View {
+    vision: Vision,
+    /// Panned by hand rather than following anyone
+    free_camera: bool,
+    camera_label: String,
+}
+
+/// Move the camera and draw the match as it stands with the timeline on top
+fn draw(playback: &Playback, renderer: &mut Renderer, view: &mut View, delta: f32) {
+    let mut game = playback.game_state.lock().unwrap();
+
+    if is_key_pressed(KeyCode::F) {
+        view.free_camera = false;
+        view.camera_label = game.cycle_spectator_camera();
+    }
+    if is_key_pressed(KeyCode::C) {
+        view.free_camera = true;
+        view.camera_label = "Free".to_string();
+    }
+    let (_, wheel) = mouse_wheel();
+    if wheel != 0.0 {
+        game.camera.zoom_by(wheel.signum());
+    }
+
+    // Borrow a crew member's eyes for drawing only, so every recorded
+    // message is still handled as being about someone else
+    let (viewer, render_fog) = match view.vision {
+        Vision::Everything => (None, false),
+        Vision::Team(team) => (viewpoint(&game, team).map(|id| (id, team)), true),
+    };
+    if let Some((id, team)) = viewer {
+        game.player_id = Some(id);
+        game.player_team = Some(team);
+        if let Some(location) = game.players.get(&id).map(|player| player.location) {
+            game.player_location = location;
+        }
+    }
+
+    // The free camera stays where it's put while the game state moves on
+    let anchor = game.camera.camera.position;
+    let played = if playback.playing {
+        delta * SPEEDS[playback.speed]
+    } else {
+        0.0
+    };
+    game.update(played);
+    if view.free_camera {
+        let zoom = game.camera.camera.zoom.max(0.01);
+        let mut pan = WorldPos::new(0.0, 0.0);
+        if is_key_down(KeyCode::A) {
+            pan.x -= 1.0;
+        }
+        if is_key_down(KeyCode::D) {
+            pan.x += 1.0;
+        }
+        if is_key_down(KeyCode::W) {
+            pan.y -= 1.0;
+        }
+        if is_key_down(KeyCode::S) {
+            pan.y += 1.0;
+        }
+        let step = PAN_SPEED * delta / zoom;
+        let target_zoom = game.camera.target_zoom;
+        let (width, height) = arena_size(&game);
+        let camera = &mut game.camera.camera;
+        camera.position = WorldPos::new(anchor.x + pan.x * step, anchor.y + pan.y * step);
+        camera.set_zoom(target_zoom);
+        camera.clamp_to_arena(width, height);
+    }
+
+    clear_background(BLACK);
+    let flags = RenderFlags {
+        render_fog,
+        ..Default::default()
+    };
+    renderer.render_with_flags(&game, &flags);
+    render_timeline(playback, view.vision, &view.camera_label);
+
+    game.player_id = None;
+    game.player_team = None;
+}
+
+#[macroquad::main("Replay Viewer")]
+async fn main() {
+    let Some(path) = std::env::args().nth(1) else {
+        eprintln!("Usage: replay-viewer <match.replay>");
+        return;
+    };
+    let replay = match std::fs::read(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|bytes| decode_replay(&bytes).map_err(|e| e.to_string()))
+    {
+        Ok(replay) if !replay.frames.is_empty() => replay,
+        Ok(_) => {
+            eprintln!("{path} has nothing recorded in it");
+            return;
+        }
+        Err(e) => {
+            eprintln!("Failed to load {path}: {e}");
+            return;
+        }
+    };
+
+    println!(
+        "Recorded from seed {}; run the server with --seed to play it again",
+        replay.header.seed
+    );
+    let mut playback = Playback::new(replay.frames);
+    let mut renderer = Renderer::new();
+    renderer.load_sprites().await;
+    let mut view = View {
+        vision: Vision::Everything,
+        free_camera: true,
+        camera_label: "Free".to_string(),
+    };
+
+    loop {
+        let delta = get_frame_time();
+
+        if is_key_pressed(KeyCode::Space) {
+            if !playback.playing && playback.tick >= playback.last_tick as f64 {
+                playback.seek(playback.first_tick as f64);
+            }
+            playback.playing = !playback.playing;
+        }
+        let jump = (JUMP_SECONDS / FRAME_DELTA_SECONDS) as f64;
+        if is_key_pressed(KeyCode::Right) {
+            playback.seek(playback.tick + jump);
+        }
+        if is_key_pressed(KeyCode::Left) {
+            playback.seek(playback.tick - jump);
+        }
+        if is_key_pressed(KeyCode::Minus) {
+            playback.speed = playback.speed.saturating_sub(1);
+        }
+        if is_key_pressed(KeyCode::Equal) {
+            playback.speed = (playback.speed + 1).min(SPEEDS.len() - 1);
+        }
+        if is_key_pressed(KeyCode::V) {
+            view.vision = view.vision.next();
+        }
+        if is_mouse_button_down(MouseButton::Left) {
+            let (x, y) = mouse_position();
+            let bar = timeline_rect();
+            if bar.contains(vec2(x, y)) {
+                let share = ((x - bar.x) / bar.w) as f64;
+                let span = (playback.last_tick - playback.first_tick) as f64;
+                playback.seek(playback.first_tick as f64 + span * share);
+            }
+        }
+        playback.advance(delta);
+
+        draw(&playback, &mut renderer, &mut view, delta);
+        next_frame().await;
+    }
+}
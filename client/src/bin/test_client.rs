@@ -16,6 +16,10 @@ impl Handler for TestClient {
         let join_msg = ClientMessage::JoinGame {
             player_name: self.player_name.clone(),
             preferred_team: None,
+            protocol_version: PROTOCOL_VERSION,
+            // JSON only, so the server answers in text frames
+            features: Vec::new(),
+            password: None,
         };
 
         let json = serde_json::to_string(&join_msg).unwrap();
@@ -30,6 +34,7 @@ impl Handler for TestClient {
                         player_id,
                         team,
                         spawn_position,
+                        ..
                     } => {
                         self.player_id = Some(player_id);
                         println!(
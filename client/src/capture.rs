@@ -0,0 +1,305 @@
+//! Screenshots and short clips for bug reports. F12 saves the current frame
+//! as a PNG; holding F11 keeps a rolling buffer of recent frames that is
+//! written out as an animated GIF when the key is released. Native builds
+//! write into `CAPTURE_DIR`, the web build hands the file to the browser as
+//! a download.
+
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+use shared::render_constants::*;
+use std::collections::VecDeque;
+
+pub const SCREENSHOT_KEY: KeyCode = KeyCode::F12;
+pub const RECORD_KEY: KeyCode = KeyCode::F11;
+
+/// Clip recording options, edited in the debug overlay's settings
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(default)]
+pub struct CaptureSettings {
+    /// Seconds of footage a clip keeps
+    pub clip_seconds: f32,
+    pub clip_fps: u32,
+    /// Clip frames are scaled by this before they're buffered
+    pub clip_scale: f32,
+}
+
+impl Default for CaptureSettings {
+    fn default() -> Self {
+        Self {
+            clip_seconds: CAPTURE_CLIP_SECONDS,
+            clip_fps: CAPTURE_CLIP_FPS,
+            clip_scale: CAPTURE_CLIP_SCALE,
+        }
+    }
+}
+
+/// One downscaled frame, top row first
+struct ClipFrame {
+    width: u16,
+    height: u16,
+    rgba: Vec<u8>,
+}
+
+pub struct Capture {
+    frames: VecDeque<ClipFrame>,
+    since_last_frame: f32,
+    recording: bool,
+    recording_fps: u32,
+    /// Where the last capture went, and how long to keep saying so
+    notice: Option<(String, f32)>,
+    /// Clip being encoded in the background
+    #[cfg(not(target_arch = "wasm32"))]
+    encoding: Option<std::sync::mpsc::Receiver<Result<String, String>>>,
+}
+
+impl Capture {
+    pub fn new() -> Self {
+        Self {
+            frames: VecDeque::new(),
+            since_last_frame: 0.0,
+            recording: false,
+            recording_fps: CAPTURE_CLIP_FPS,
+            notice: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            encoding: None,
+        }
+    }
+
+    /// Handle the capture keys. Call once per frame after everything is
+    /// drawn so the grab includes the UI.
+    pub fn update(&mut self, settings: &CaptureSettings, keyboard_free: bool) {
+        let delta = get_frame_time();
+        if let Some((_, remaining)) = self.notice.as_mut() {
+            *remaining -= delta;
+            if *remaining <= 0.0 {
+                self.notice = None;
+            }
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(result) = self.encoding.as_ref().and_then(|rx| rx.try_recv().ok()) {
+            self.encoding = None;
+            self.report(result);
+        }
+
+        if keyboard_free && is_key_pressed(SCREENSHOT_KEY) {
+            self.save_screenshot();
+        }
+
+        if keyboard_free && is_key_down(RECORD_KEY) {
+            if !self.recording {
+                self.recording = true;
+                self.frames.clear();
+                self.since_last_frame = f32::INFINITY;
+            }
+            self.record_frame(settings, delta);
+        } else if self.recording {
+            self.recording = false;
+            self.save_clip();
+        }
+    }
+
+    fn record_frame(&mut self, settings: &CaptureSettings, delta: f32) {
+        let fps = settings.clip_fps.max(1);
+        self.recording_fps = fps;
+        self.since_last_frame += delta;
+        if self.since_last_frame < 1.0 / fps as f32 {
+            return;
+        }
+        self.since_last_frame = 0.0;
+
+        self.frames
+            .push_back(downscale(&get_screen_data(), settings.clip_scale));
+        let keep = (settings.clip_seconds * fps as f32).ceil().max(1.0) as usize;
+        while self.frames.len() > keep {
+            self.frames.pop_front();
+        }
+    }
+
+    fn save_clip(&mut self) {
+        let frames: Vec<ClipFrame> = self.frames.drain(..).collect();
+        if frames.is_empty() {
+            return;
+        }
+        let name = format!("clip-{}.gif", timestamp());
+        let delay = (100 / self.recording_fps) as u16;
+
+        // Quantizing every frame takes a while, so keep it off the render
+        // thread where there is one
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.notice = Some((format!("Encoding {name}..."), f32::INFINITY));
+            let (tx, rx) = std::sync::mpsc::channel();
+            self.encoding = Some(rx);
+            std::thread::spawn(move || {
+                let _ = tx.send(save_clip(&name, frames, delay));
+            });
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            let result = save_clip(&name, frames, delay);
+            self.report(result);
+        }
+    }
+
+    fn save_screenshot(&mut self) {
+        let image = get_screen_data();
+        let rgba = flip_rows(&image.bytes, image.width as usize, image.height as usize);
+        let name = format!("screenshot-{}.png", timestamp());
+        let result = encode_png(image.width as u32, image.height as u32, &rgba)
+            .and_then(|bytes| write_capture(&name, &bytes))
+            .map_err(|e| format!("Failed to save {name}: {e}"));
+        self.report(result);
+    }
+
+    fn report(&mut self, result: Result<String, String>) {
+        let message = match result {
+            Ok(location) => {
+                info!("Saved {}", location);
+                format!("Saved {location}")
+            }
+            Err(e) => {
+                warn!("{}", e);
+                e
+            }
+        };
+        self.notice = Some((message, CAPTURE_NOTICE_DURATION));
+    }
+
+    /// Recording indicator and the last capture's notice
+    pub fn draw(&self) {
+        if self.recording {
+            let seconds = self.frames.len() as f32 / self.recording_fps as f32;
+            let text = format!("REC {seconds:.1}s");
+            draw_circle(screen_width() - 110.0, 24.0, 6.0, RED);
+            draw_text(&text, screen_width() - 98.0, 30.0, UI_TEXT_FONT_SIZE, RED);
+        }
+        if let Some((notice, _)) = &self.notice {
+            draw_text(
+                notice,
+                HUD_BASE_X,
+                screen_height() - 20.0,
+                UI_TEXT_FONT_SIZE,
+                WHITE,
+            );
+        }
+    }
+}
+
+impl Default for Capture {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Seconds since the epoch, for file names
+fn timestamp() -> u64 {
+    macroquad::miniquad::date::now() as u64
+}
+
+/// Screen grabs come out bottom row first
+fn flip_rows(bytes: &[u8], width: usize, height: usize) -> Vec<u8> {
+    bytes
+        .chunks_exact(width * 4)
+        .rev()
+        .take(height)
+        .flatten()
+        .copied()
+        .collect()
+}
+
+/// Nearest-neighbour downscale of a screen grab, flipped the right way up
+fn downscale(image: &Image, scale: f32) -> ClipFrame {
+    let scale = scale.clamp(0.1, 1.0);
+    let (src_width, src_height) = (image.width as usize, image.height as usize);
+    let width = ((src_width as f32 * scale) as usize).max(1);
+    let height = ((src_height as f32 * scale) as usize).max(1);
+
+    let mut rgba = Vec::with_capacity(width * height * 4);
+    for y in 0..height {
+        let src_y = src_height - 1 - (y * src_height / height);
+        for x in 0..width {
+            let src = (src_y * src_width + x * src_width / width) * 4;
+            rgba.extend_from_slice(&image.bytes[src..src + 4]);
+        }
+    }
+    ClipFrame {
+        width: width as u16,
+        height: height as u16,
+        rgba,
+    }
+}
+
+fn save_clip(name: &str, frames: Vec<ClipFrame>, delay: u16) -> Result<String, String> {
+    encode_gif(frames, delay)
+        .and_then(|bytes| write_capture(name, &bytes))
+        .map_err(|e| format!("Failed to save {name}: {e}"))
+}
+
+fn encode_png(width: u32, height: u32, rgba: &[u8]) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    let mut encoder = png::Encoder::new(&mut bytes, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+    writer.write_image_data(rgba).map_err(|e| e.to_string())?;
+    writer.finish().map_err(|e| e.to_string())?;
+    Ok(bytes)
+}
+
+/// `delay` is in hundredths of a second per frame
+fn encode_gif(frames: Vec<ClipFrame>, delay: u16) -> Result<Vec<u8>, String> {
+    let (width, height) = (frames[0].width, frames[0].height);
+    let mut bytes = Vec::new();
+    {
+        let mut encoder =
+            gif::Encoder::new(&mut bytes, width, height, &[]).map_err(|e| e.to_string())?;
+        encoder
+            .set_repeat(gif::Repeat::Infinite)
+            .map_err(|e| e.to_string())?;
+        // A resize mid-recording changes the frame size; keep the first one
+        for mut frame in frames
+            .into_iter()
+            .filter(|f| f.width == width && f.height == height)
+        {
+            let mut gif_frame =
+                gif::Frame::from_rgba_speed(width, height, &mut frame.rgba, CAPTURE_GIF_SPEED);
+            gif_frame.delay = delay;
+            encoder.write_frame(&gif_frame).map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(bytes)
+}
+
+/// Save into the captures folder, returning where the file went
+#[cfg(not(target_arch = "wasm32"))]
+fn write_capture(name: &str, bytes: &[u8]) -> Result<String, String> {
+    let dir = std::path::Path::new(CAPTURE_DIR);
+    std::fs::create_dir_all(dir).map_err(|e| format!("{}: {e}", dir.display()))?;
+    let path = dir.join(name);
+    std::fs::write(&path, bytes).map_err(|e| format!("{}: {e}", path.display()))?;
+    Ok(path.display().to_string())
+}
+
+#[cfg(target_arch = "wasm32")]
+extern "C" {
+    fn js_download_file(
+        name_ptr: *const u8,
+        name_len: usize,
+        data_ptr: *const u8,
+        data_len: usize,
+    ) -> i32;
+}
+
+/// Hand the file to the browser as a download
+#[cfg(target_arch = "wasm32")]
+fn write_capture(name: &str, bytes: &[u8]) -> Result<String, String> {
+    let result =
+        unsafe { js_download_file(name.as_ptr(), name.len(), bytes.as_ptr(), bytes.len()) };
+    if result == 1 {
+        Ok(name.to_string())
+    } else {
+        Err("the browser refused the download".to_string())
+    }
+}
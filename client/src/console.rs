@@ -0,0 +1,476 @@
+use macroquad::prelude::*;
+use shared::{
+    ClientMessage, DevCommand, ResourceType, Validate, COMPRESSION_QUERY, CONSOLE_FONT_SIZE,
+    CONSOLE_HEIGHT_RATIO, CONSOLE_LINE_SPACING, CONSOLE_MAX_HISTORY, CONSOLE_MAX_OUTPUT_LINES,
+    CONSOLE_PADDING, UI_SCALE_MAX, UI_SCALE_MIN,
+};
+use std::collections::VecDeque;
+
+/// Render flags `toggle` accepts, by the name typed in the console
+pub const TOGGLE_FLAGS: [&str; 16] = [
+    "fog",
+    "mechs",
+    "players",
+    "resources",
+    "projectiles",
+    "effects",
+    "ui",
+    "tiles",
+    "stations",
+    "hit_feedback",
+    "lighting",
+    "spatial_debug",
+    "hitboxes",
+    "colorblind",
+    "reduced_flash",
+    "voice_mute",
+];
+
+/// Local settings `set` accepts
+const SETTINGS: [&str; 1] = ["ui_scale"];
+
+const RESOURCES: [(&str, ResourceType); 5] = [
+    ("scrap_metal", ResourceType::ScrapMetal),
+    ("computer_components", ResourceType::ComputerComponents),
+    ("wiring", ResourceType::Wiring),
+    ("batteries", ResourceType::Batteries),
+    ("power_cores", ResourceType::PowerCore),
+];
+
+struct CommandSpec {
+    name: &'static str,
+    usage: &'static str,
+    help: &'static str,
+}
+
+const COMMANDS: [CommandSpec; 13] = [
+    CommandSpec {
+        name: "connect",
+        usage: "connect <host:port | ws://url>",
+        help: "Leave the current server and join another",
+    },
+    CommandSpec {
+        name: "servers",
+        usage: "servers",
+        help: "Open the server browser",
+    },
+    CommandSpec {
+        name: "queue",
+        usage: "queue",
+        help: "Wait on this server's queue for a pick-up match",
+    },
+    CommandSpec {
+        name: "tp",
+        usage: "tp <x> <y>",
+        help: "Teleport to a tile (server must run with --dev-mode)",
+    },
+    CommandSpec {
+        name: "give",
+        usage: "give <resource> [amount]",
+        help: "Add resources to your team's mech (server must run with --dev-mode)",
+    },
+    CommandSpec {
+        name: "toggle",
+        usage: "toggle <flag>",
+        help: "Flip a render flag such as fog or tiles, or colorblind and reduced_flash",
+    },
+    CommandSpec {
+        name: "set",
+        usage: "set ui_scale <value>",
+        help: "Change a local setting",
+    },
+    CommandSpec {
+        name: "follow",
+        usage: "follow [player]",
+        help: "Point the camera at another player, or back at yourself",
+    },
+    CommandSpec {
+        name: "director",
+        usage: "director",
+        help: "While spectating, let the camera director pick what to watch again",
+    },
+    CommandSpec {
+        name: "desync",
+        usage: "desync [dump]",
+        help: "Compare our state with the server's last checksum, or log every entity's hash",
+    },
+    CommandSpec {
+        name: "help",
+        usage: "help [search]",
+        help: "List commands, or those matching a search",
+    },
+    CommandSpec {
+        name: "history",
+        usage: "history",
+        help: "Show previously entered commands",
+    },
+    CommandSpec {
+        name: "clear",
+        usage: "clear",
+        help: "Clear the console output",
+    },
+];
+
+/// What a console command asks the rest of the client to do
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConsoleAction {
+    Connect(String),
+    Server(DevCommand),
+    Toggle(&'static str),
+    SetUiScale(f32),
+    /// Camera follows the named player, or us again for `None`
+    Follow(Option<String>),
+    ServerBrowser,
+    /// Join the connected server's matchmaking queue
+    QuickMatch,
+    /// Spectator camera back on the director's picks
+    Director,
+    /// Report the last state checksum comparison; with `dump`, log every
+    /// entity's hash for diffing against the server's
+    Desync {
+        dump: bool,
+    },
+}
+
+/// Drop-down developer console, opened with the backtick key
+pub struct DevConsole {
+    open: bool,
+    input: String,
+    history: Vec<String>,
+    /// Index into history while browsing it with Up/Down
+    history_cursor: Option<usize>,
+    output: VecDeque<String>,
+}
+
+impl DevConsole {
+    pub fn new() -> Self {
+        let mut console = Self {
+            open: false,
+            input: String::new(),
+            history: Vec::new(),
+            history_cursor: None,
+            output: VecDeque::new(),
+        };
+        console.print("Developer console. Type 'help' for commands, Tab to complete.");
+        console
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn print(&mut self, line: impl Into<String>) {
+        self.output.push_back(line.into());
+        while self.output.len() > CONSOLE_MAX_OUTPUT_LINES {
+            self.output.pop_front();
+        }
+    }
+
+    /// Handle this frame's keyboard input. Returns an action when a command
+    /// was submitted that something outside the console has to carry out.
+    pub fn update(&mut self) -> Option<ConsoleAction> {
+        if is_key_pressed(KeyCode::GraveAccent) {
+            self.open = !self.open;
+        }
+        if !self.open {
+            return None;
+        }
+        if is_key_pressed(KeyCode::Escape) {
+            self.open = false;
+            return None;
+        }
+
+        while let Some(c) = get_char_pressed() {
+            if !c.is_control() && c != '`' && c != '~' {
+                self.input.push(c);
+                self.history_cursor = None;
+            }
+        }
+
+        if is_key_pressed(KeyCode::Backspace) {
+            self.input.pop();
+        }
+        if is_key_pressed(KeyCode::Tab) {
+            self.complete();
+        }
+        if is_key_pressed(KeyCode::Up) {
+            self.browse_history(-1);
+        }
+        if is_key_pressed(KeyCode::Down) {
+            self.browse_history(1);
+        }
+        if is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::KpEnter) {
+            return self.submit();
+        }
+        None
+    }
+
+    fn browse_history(&mut self, step: isize) {
+        if self.history.is_empty() {
+            return;
+        }
+        let last = self.history.len() - 1;
+        let cursor = match (self.history_cursor, step < 0) {
+            (None, true) => Some(last),
+            (None, false) => None,
+            (Some(0), true) => Some(0),
+            (Some(i), true) => Some(i - 1),
+            (Some(i), false) if i >= last => None,
+            (Some(i), false) => Some(i + 1),
+        };
+        self.history_cursor = cursor;
+        self.input = cursor.map(|i| self.history[i].clone()).unwrap_or_default();
+    }
+
+    fn submit(&mut self) -> Option<ConsoleAction> {
+        let line = std::mem::take(&mut self.input);
+        let line = line.trim();
+        self.history_cursor = None;
+        if line.is_empty() {
+            return None;
+        }
+        self.print(format!("> {line}"));
+        if self.history.last().map(String::as_str) != Some(line) {
+            self.history.push(line.to_string());
+            if self.history.len() > CONSOLE_MAX_HISTORY {
+                self.history.remove(0);
+            }
+        }
+
+        match self.execute(line) {
+            Ok(action) => action,
+            Err(message) => {
+                self.print(message);
+                None
+            }
+        }
+    }
+
+    /// Run console-local commands and parse the rest into an action
+    fn execute(&mut self, line: &str) -> Result<Option<ConsoleAction>, String> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let args = &words[1..];
+        let action = match words[0] {
+            "connect" => {
+                let [target] = args else {
+                    return Err(usage("connect"));
+                };
+                ConsoleAction::Connect(server_url(target))
+            }
+            "servers" => ConsoleAction::ServerBrowser,
+            "queue" => ConsoleAction::QuickMatch,
+            "tp" => {
+                let [x, y] = args else {
+                    return Err(usage("tp"));
+                };
+                let (Ok(x), Ok(y)) = (x.parse(), y.parse()) else {
+                    return Err("Tile coordinates must be whole numbers".to_string());
+                };
+                ConsoleAction::Server(DevCommand::Teleport { x, y })
+            }
+            "give" => {
+                let (name, amount) = match args {
+                    [name] => (name, "1"),
+                    [name, amount] => (name, *amount),
+                    _ => return Err(usage("give")),
+                };
+                let Some((_, resource_type)) = RESOURCES.iter().find(|(n, _)| n == name) else {
+                    return Err(format!("Unknown resource '{name}'"));
+                };
+                let amount = amount
+                    .parse()
+                    .map_err(|_| format!("'{amount}' is not an amount"))?;
+                ConsoleAction::Server(DevCommand::GiveResource {
+                    resource_type: *resource_type,
+                    amount,
+                })
+            }
+            "toggle" => {
+                let [flag] = args else {
+                    return Err(usage("toggle"));
+                };
+                let Some(flag) = TOGGLE_FLAGS.into_iter().find(|f| f == flag) else {
+                    return Err(format!(
+                        "Unknown flag '{flag}', try one of: {}",
+                        TOGGLE_FLAGS.join(", ")
+                    ));
+                };
+                ConsoleAction::Toggle(flag)
+            }
+            "set" => {
+                let ["ui_scale", value] = args else {
+                    return Err(usage("set"));
+                };
+                match value.parse::<f32>() {
+                    Ok(scale) if (UI_SCALE_MIN..=UI_SCALE_MAX).contains(&scale) => {
+                        ConsoleAction::SetUiScale(scale)
+                    }
+                    _ => {
+                        return Err(format!(
+                            "ui_scale must be between {UI_SCALE_MIN} and {UI_SCALE_MAX}"
+                        ))
+                    }
+                }
+            }
+            "follow" => ConsoleAction::Follow((!args.is_empty()).then(|| args.join(" "))),
+            "director" => ConsoleAction::Director,
+            "desync" => match args {
+                [] => ConsoleAction::Desync { dump: false },
+                ["dump"] => ConsoleAction::Desync { dump: true },
+                _ => return Err(usage("desync")),
+            },
+            "help" => {
+                let search = args.join(" ").to_lowercase();
+                let matches: Vec<String> = COMMANDS
+                    .iter()
+                    .filter(|c| c.name.contains(&search) || c.help.to_lowercase().contains(&search))
+                    .map(|c| format!("  {:<32} {}", c.usage, c.help))
+                    .collect();
+                if matches.is_empty() {
+                    self.print(format!("No commands match '{search}'"));
+                }
+                for line in matches {
+                    self.print(line);
+                }
+                return Ok(None);
+            }
+            "history" => {
+                let lines: Vec<String> = self
+                    .history
+                    .iter()
+                    .enumerate()
+                    .map(|(i, line)| format!("  {i:>3}  {line}"))
+                    .collect();
+                for line in lines {
+                    self.print(line);
+                }
+                return Ok(None);
+            }
+            "clear" => {
+                self.output.clear();
+                return Ok(None);
+            }
+            other => return Err(format!("Unknown command '{other}', type 'help'")),
+        };
+
+        // Catch what the server would reject before sending it
+        if let ConsoleAction::Server(command) = &action {
+            let msg = ClientMessage::DevCommand {
+                command: command.clone(),
+            };
+            msg.validate().map_err(|e| e.to_string())?;
+        }
+        Ok(Some(action))
+    }
+
+    /// Completions for the word being typed
+    fn candidates(&self) -> (usize, Vec<&'static str>) {
+        let words: Vec<&str> = self.input.split_whitespace().collect();
+        let typing_new_word = self.input.is_empty() || self.input.ends_with(' ');
+        let index = if typing_new_word {
+            words.len()
+        } else {
+            words.len() - 1
+        };
+        let partial = if typing_new_word { "" } else { words[index] };
+
+        let options: Vec<&'static str> = match (index, words.first().copied()) {
+            (0, _) => COMMANDS.iter().map(|c| c.name).collect(),
+            (1, Some("toggle")) => TOGGLE_FLAGS.to_vec(),
+            (1, Some("set")) => SETTINGS.to_vec(),
+            (1, Some("give")) => RESOURCES.iter().map(|(name, _)| *name).collect(),
+            (1, Some("desync")) => vec!["dump"],
+            _ => Vec::new(),
+        };
+        let matches = options
+            .into_iter()
+            .filter(|option| option.starts_with(partial))
+            .collect();
+        (index, matches)
+    }
+
+    /// Complete the current word, or list the options when it's ambiguous
+    fn complete(&mut self) {
+        let (index, matches) = self.candidates();
+        match matches.as_slice() {
+            [] => {}
+            [only] => {
+                let mut words: Vec<&str> = self.input.split_whitespace().take(index).collect();
+                words.push(only);
+                self.input = format!("{} ", words.join(" "));
+            }
+            _ => {
+                let line = format!("  {}", matches.join("  "));
+                self.print(line);
+            }
+        }
+    }
+
+    pub fn draw(&self, ui_scale: f32) {
+        if !self.open {
+            return;
+        }
+        let font_size = CONSOLE_FONT_SIZE * ui_scale;
+        let line_spacing = CONSOLE_LINE_SPACING * ui_scale;
+        let padding = CONSOLE_PADDING * ui_scale;
+        let height = screen_height() * CONSOLE_HEIGHT_RATIO;
+
+        draw_rectangle(
+            0.0,
+            0.0,
+            screen_width(),
+            height,
+            Color::new(0.0, 0.0, 0.0, 0.85),
+        );
+        draw_line(0.0, height, screen_width(), height, 2.0, DARKGRAY);
+
+        // Input line and live suggestions along the bottom, output above them
+        let input_y = height - padding;
+        let caret = if (get_time() * 2.0) as i64 % 2 == 0 {
+            "_"
+        } else {
+            ""
+        };
+        draw_text(
+            &format!("> {}{caret}", self.input),
+            padding,
+            input_y,
+            font_size,
+            WHITE,
+        );
+
+        let mut y = input_y - line_spacing;
+        if !self.input.is_empty() {
+            let (_, suggestions) = self.candidates();
+            if !suggestions.is_empty() {
+                draw_text(&suggestions.join("  "), padding, y, font_size, GRAY);
+                y -= line_spacing;
+            }
+        }
+
+        for line in self.output.iter().rev() {
+            if y < line_spacing {
+                break;
+            }
+            draw_text(line, padding, y, font_size, LIGHTGRAY);
+            y -= line_spacing;
+        }
+    }
+}
+
+fn usage(name: &str) -> String {
+    let usage = COMMANDS
+        .iter()
+        .find(|c| c.name == name)
+        .map_or(name, |c| c.usage);
+    format!("Usage: {usage}")
+}
+
+/// Accept a bare host:port and fill in the rest of the websocket URL
+fn server_url(target: &str) -> String {
+    if target.contains("://") {
+        target.to_string()
+    } else {
+        format!("ws://{target}/ws?{COMPRESSION_QUERY}")
+    }
+}
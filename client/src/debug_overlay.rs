@@ -1,11 +1,21 @@
+use crate::accessibility::AccessibilitySettings;
+use crate::capture::CaptureSettings;
 #[cfg(debug_assertions)]
 use crate::game_state::GameState;
 #[cfg(debug_assertions)]
-use crate::settings::{DebugSettings, SettingsManager};
+use crate::inspect::InspectTarget;
+use crate::particles::EffectsQuality;
+use crate::rendering::render_scale::GraphicsSettings;
+#[cfg(debug_assertions)]
+use crate::settings::{DebugSettings, SettingsManager, SETTINGS_VERSION};
+use crate::voice::VoiceSettings;
+#[cfg(debug_assertions)]
+use crate::voice::PUSH_TO_TALK_KEY;
 #[cfg(debug_assertions)]
 use egui::*;
 #[cfg(debug_assertions)]
-use macroquad::prelude::get_fps;
+use macroquad::prelude::{get_fps, is_mouse_button_pressed, mouse_position};
+use shared::NetConditions;
 #[cfg(debug_assertions)]
 use shared::{tile_entity::TileVisual, types::*, StationType};
 #[cfg(debug_assertions)]
@@ -23,8 +33,12 @@ pub struct DebugOverlay {
     // Smoothing for stability
     fps_smoothing_buffer: VecDeque<f32>, // Raw FPS values for smoothing
 
+    /// Share of the window's resolution the renderer drew the world at
+    pub render_scale_now: f32,
+
     // Settings persistence
     settings_manager: SettingsManager,
+    settings_error: Option<String>,
 
     // UI state
     show_performance: bool,
@@ -47,12 +61,17 @@ pub struct DebugOverlay {
     pub show_door_positions: bool,
     pub show_coordinate_grid: bool,
     pub show_floor_offsets: bool,
+    pub show_hitboxes: bool,
 
     // Spatial testing state
     test_report: String,
     show_test_report: bool,
     last_test_result: Option<String>,
 
+    // Entity inspector: clicking the world picks what to inspect
+    inspect_mode: bool,
+    inspected: Option<InspectTarget>,
+
     // Server state tracking
     last_server_message: String,
     message_history: VecDeque<String>,
@@ -68,6 +87,24 @@ pub struct DebugOverlay {
     pub render_fog: bool,
     pub render_tiles: bool,
     pub render_stations: bool,
+    pub render_hit_feedback: bool,
+    pub render_lighting: bool,
+
+    pub effects_quality: EffectsQuality,
+
+    pub graphics: GraphicsSettings,
+
+    pub ui_scale: f32,
+
+    pub accessibility: AccessibilitySettings,
+
+    pub camera_smoothing: f32,
+
+    pub capture: CaptureSettings,
+
+    pub net_conditions: NetConditions,
+
+    pub voice: VoiceSettings,
 
     // ASCII view settings
     ascii_grid_size: (usize, usize),
@@ -85,8 +122,10 @@ impl DebugOverlay {
             fps_history: VecDeque::with_capacity(120),
             elapsed_time: 0.0,
             fps_smoothing_buffer: VecDeque::with_capacity(10), // 10-frame smoothing
+            render_scale_now: 1.0,
 
             settings_manager,
+            settings_error: None,
 
             // Load settings from persistent storage
             show_performance: settings.show_performance,
@@ -108,11 +147,15 @@ impl DebugOverlay {
             show_door_positions: settings.show_door_positions,
             show_coordinate_grid: settings.show_coordinate_grid,
             show_floor_offsets: settings.show_floor_offsets,
+            show_hitboxes: settings.show_hitboxes,
 
             test_report: String::new(),
             show_test_report: false,
             last_test_result: None,
 
+            inspect_mode: false,
+            inspected: None,
+
             last_server_message: String::new(),
             message_history: VecDeque::with_capacity(20),
             message_counter: 0,
@@ -127,15 +170,39 @@ impl DebugOverlay {
             render_fog: settings.render_fog,
             render_tiles: settings.render_tiles,
             render_stations: settings.render_stations,
+            render_hit_feedback: settings.render_hit_feedback,
+            render_lighting: settings.render_lighting,
+
+            effects_quality: settings.effects_quality,
+
+            graphics: settings.graphics,
+
+            ui_scale: settings.ui_scale,
+
+            accessibility: settings.accessibility,
+
+            camera_smoothing: settings.camera_smoothing,
+
+            capture: settings.capture,
+
+            // MECH_NET_* variables win over saved settings
+            #[cfg(not(target_arch = "wasm32"))]
+            net_conditions: NetConditions::from_env().unwrap_or(settings.net_conditions),
+            #[cfg(target_arch = "wasm32")]
+            net_conditions: settings.net_conditions,
+
+            voice: settings.voice,
 
             ascii_grid_size: (40, 20),
             mini_map_zoom: 1.0,
         }
     }
 
-    // Helper method to save current settings
-    fn save_settings(&mut self) {
+    // Stage the current settings; they're written out by the Save button
+    fn stage_settings(&mut self) {
         let settings = DebugSettings {
+            version: SETTINGS_VERSION,
+
             // UI panel visibility
             show_performance: self.show_performance,
             show_server_state: self.show_server_state,
@@ -152,6 +219,7 @@ impl DebugOverlay {
             show_door_positions: self.show_door_positions,
             show_coordinate_grid: self.show_coordinate_grid,
             show_floor_offsets: self.show_floor_offsets,
+            show_hitboxes: self.show_hitboxes,
 
             // Rendering toggles
             render_mechs: self.render_mechs,
@@ -163,11 +231,257 @@ impl DebugOverlay {
             render_fog: self.render_fog,
             render_tiles: self.render_tiles,
             render_stations: self.render_stations,
+            render_hit_feedback: self.render_hit_feedback,
+            render_lighting: self.render_lighting,
+
+            effects_quality: self.effects_quality,
+
+            graphics: self.graphics,
+
+            ui_scale: self.ui_scale,
+
+            accessibility: self.accessibility,
+
+            camera_smoothing: self.camera_smoothing,
+
+            capture: self.capture,
+
+            net_conditions: self.net_conditions,
+
+            voice: self.voice.clone(),
         };
 
         self.settings_manager.update_settings(settings);
     }
 
+    // Put the overlay back to the given settings, e.g. after a revert
+    fn apply_settings(&mut self, settings: &DebugSettings) {
+        self.show_performance = settings.show_performance;
+        self.show_server_state = settings.show_server_state;
+        self.show_mini_map = settings.show_mini_map;
+        self.show_network = settings.show_network;
+        self.show_rendering_toggles = settings.show_rendering_toggles;
+        self.show_spatial_debug = settings.show_spatial_debug;
+        self.show_mech_controls = settings.show_mech_controls;
+
+        self.spatial_debug_enabled = settings.spatial_debug_enabled;
+        self.show_coordinate_transforms = settings.show_coordinate_transforms;
+        self.show_mech_bounds = settings.show_mech_bounds;
+        self.show_door_positions = settings.show_door_positions;
+        self.show_coordinate_grid = settings.show_coordinate_grid;
+        self.show_floor_offsets = settings.show_floor_offsets;
+        self.show_hitboxes = settings.show_hitboxes;
+
+        self.render_mechs = settings.render_mechs;
+        self.render_players = settings.render_players;
+        self.render_resources = settings.render_resources;
+        self.render_projectiles = settings.render_projectiles;
+        self.render_effects = settings.render_effects;
+        self.render_ui = settings.render_ui;
+        self.render_fog = settings.render_fog;
+        self.render_tiles = settings.render_tiles;
+        self.render_stations = settings.render_stations;
+        self.render_hit_feedback = settings.render_hit_feedback;
+        self.render_lighting = settings.render_lighting;
+
+        self.effects_quality = settings.effects_quality;
+
+        self.graphics = settings.graphics;
+
+        self.ui_scale = settings.ui_scale;
+
+        self.accessibility = settings.accessibility;
+
+        self.camera_smoothing = settings.camera_smoothing;
+
+        self.capture = settings.capture;
+
+        self.net_conditions = settings.net_conditions;
+
+        self.voice = settings.voice.clone();
+    }
+
+    fn render_settings_controls(&mut self, ui: &mut Ui, game_state: &GameState) {
+        let dirty = self.settings_manager.is_dirty();
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(dirty, Button::new("Save settings"))
+                .clicked()
+            {
+                self.settings_error = self.settings_manager.save().err();
+            }
+            if ui.add_enabled(dirty, Button::new("Revert")).clicked() {
+                let settings = self.settings_manager.revert().clone();
+                self.apply_settings(&settings);
+                self.settings_error = None;
+            }
+            if let Some(error) = &self.settings_error {
+                ui.colored_label(Color32::RED, format!("Save failed: {error}"));
+            } else if dirty {
+                ui.colored_label(Color32::YELLOW, "Unsaved changes");
+            }
+        });
+
+        ui.collapsing("Capture (F12 screenshot, hold F11 to record)", |ui| {
+            let capture = &mut self.capture;
+            let mut changed = false;
+            changed |= ui
+                .add(
+                    Slider::new(
+                        &mut capture.clip_seconds,
+                        1.0..=shared::CAPTURE_CLIP_MAX_SECONDS,
+                    )
+                    .text("Clip length (s)"),
+                )
+                .changed();
+            changed |= ui
+                .add(Slider::new(&mut capture.clip_fps, 5..=30).text("Clip FPS"))
+                .changed();
+            changed |= ui
+                .add(Slider::new(&mut capture.clip_scale, 0.25..=1.0).text("Clip scale"))
+                .changed();
+            if changed {
+                self.stage_settings();
+            }
+        });
+
+        ui.collapsing("Network conditions", |ui| {
+            let conditions = &mut self.net_conditions;
+            let mut changed = false;
+            changed |= ui
+                .add(Slider::new(&mut conditions.latency_ms, 0..=500).text("Latency (ms)"))
+                .changed();
+            changed |= ui
+                .add(Slider::new(&mut conditions.jitter_ms, 0..=200).text("Jitter (ms)"))
+                .changed();
+            changed |= ui
+                .add(Slider::new(&mut conditions.drop_percent, 0.0..=50.0).text("Drop %"))
+                .changed();
+            changed |= ui
+                .add(Slider::new(&mut conditions.reorder_percent, 0.0..=50.0).text("Reorder %"))
+                .changed();
+            if ui.button("Off").clicked() {
+                *conditions = NetConditions::default();
+                changed = true;
+            }
+            if changed {
+                self.stage_settings();
+            }
+        });
+
+        ui.collapsing("Graphics", |ui| {
+            let mut changed = false;
+            let label = if self.graphics.auto_render_scale {
+                "Highest render scale"
+            } else {
+                "Render scale"
+            };
+            changed |= ui
+                .add(
+                    Slider::new(
+                        &mut self.graphics.render_scale,
+                        shared::RENDER_SCALE_MIN..=shared::RENDER_SCALE_MAX,
+                    )
+                    .text(label),
+                )
+                .changed();
+            changed |= ui
+                .checkbox(
+                    &mut self.graphics.auto_render_scale,
+                    "Lower it to hold the frame rate",
+                )
+                .changed();
+            changed |= ui
+                .add_enabled(
+                    self.graphics.auto_render_scale,
+                    Slider::new(&mut self.graphics.target_fps, 20..=144).text("Target FPS"),
+                )
+                .changed();
+            ui.label(format!(
+                "Drawing the world at {:.0}%",
+                self.render_scale_now * 100.0
+            ));
+            if changed {
+                self.stage_settings();
+            }
+        });
+
+        ui.collapsing("Camera (wheel to zoom, C to follow mech)", |ui| {
+            let smoothing = Slider::new(
+                &mut self.camera_smoothing,
+                shared::CAMERA_MIN_SMOOTHING..=1.0,
+            )
+            .text("Smoothing (1 = locked)");
+            if ui.add(smoothing).changed() {
+                self.stage_settings();
+            }
+        });
+
+        ui.collapsing("Accessibility", |ui| {
+            let mut changed = false;
+            changed |= ui
+                .checkbox(
+                    &mut self.accessibility.colorblind,
+                    "Colour-blind safe palette",
+                )
+                .changed();
+            changed |= ui
+                .checkbox(&mut self.accessibility.reduced_flash, "Reduce flashing")
+                .changed();
+            changed |= ui
+                .add(
+                    Slider::new(
+                        &mut self.ui_scale,
+                        shared::UI_SCALE_MIN..=shared::UI_SCALE_MAX,
+                    )
+                    .text("UI text size"),
+                )
+                .changed();
+            ui.label("Tab and Shift+Tab move between station buttons, Enter presses one");
+            if changed {
+                self.stage_settings();
+            }
+        });
+
+        ui.collapsing(format!("Voice (hold {PUSH_TO_TALK_KEY:?} to talk)"), |ui| {
+            let mut changed = false;
+            changed |= ui
+                .checkbox(&mut self.voice.muted, "Mute voice chat")
+                .changed();
+            changed |= ui
+                .add(Slider::new(&mut self.voice.volume, 0.0..=1.0).text("Volume"))
+                .changed();
+            let mut teammates: Vec<&str> = game_state
+                .players
+                .iter()
+                .filter(|(id, player)| {
+                    Some(**id) != game_state.player_id
+                        && Some(player.team) == game_state.player_team
+                })
+                .map(|(_, player)| player.name.as_str())
+                .collect();
+            teammates.sort_unstable();
+            for name in teammates {
+                let mut volume = self.voice.player_volumes.get(name).copied().unwrap_or(1.0);
+                if ui
+                    .add(Slider::new(&mut volume, 0.0..=1.0).text(name))
+                    .changed()
+                {
+                    self.voice.player_volumes.insert(name.to_string(), volume);
+                    changed = true;
+                }
+            }
+            if changed {
+                self.stage_settings();
+            }
+        });
+    }
+
+    /// Frame rate over the last few frames, as the performance panel shows it
+    pub fn smoothed_fps(&self) -> f32 {
+        self.fps_history.back().map(|(_, fps)| *fps).unwrap_or(0.0)
+    }
+
     pub fn update(&mut self, _game_state: &GameState, frame_time: f32) {
         // Update elapsed time
         self.elapsed_time += frame_time;
@@ -207,6 +521,8 @@ impl DebugOverlay {
         game_state: &GameState,
         spatial_test_suite: &mut crate::spatial_testing::SpatialTestSuite,
     ) {
+        ctx.set_pixels_per_point(self.ui_scale);
+
         // Main debug window
         Window::new("Debug Overlay")
             .resizable(true)
@@ -223,11 +539,15 @@ impl DebugOverlay {
                     changed |= ui.toggle_value(&mut self.show_spatial_debug, "Spatial").changed();
                     changed |= ui.toggle_value(&mut self.show_mech_controls, "Mech Controls").changed();
                     
+                    ui.toggle_value(&mut self.inspect_mode, "Inspect");
+
                     if changed {
-                        self.save_settings();
+                        self.stage_settings();
                     }
                 });
 
+                self.render_settings_controls(ui, game_state);
+
                 ui.separator();
 
                 if self.show_performance {
@@ -259,6 +579,13 @@ impl DebugOverlay {
                 }
             });
 
+        if self.inspect_mode {
+            self.pick_inspected(ctx, game_state);
+        }
+        if let Some(target) = self.inspected {
+            self.render_inspector_window(ctx, game_state, target);
+        }
+
         // Separate window for test report
         if self.show_test_report && !self.test_report.is_empty() {
             let mut should_close = false;
@@ -289,12 +616,82 @@ impl DebugOverlay {
         }
     }
 
+    /// Point the inspector at whatever is under a click that egui didn't take
+    fn pick_inspected(&mut self, ctx: &Context, game_state: &GameState) {
+        if ctx.wants_pointer_input()
+            || !is_mouse_button_pressed(macroquad::prelude::MouseButton::Left)
+        {
+            return;
+        }
+        let (x, y) = mouse_position();
+        let world = game_state.camera.screen_to_world(x, y);
+        if let Some(target) = game_state.entity_at(world) {
+            self.inspected = Some(target);
+        }
+    }
+
+    fn render_inspector_window(
+        &mut self,
+        ctx: &Context,
+        game_state: &GameState,
+        target: InspectTarget,
+    ) {
+        let mut open = true;
+        Window::new("Inspector")
+            .open(&mut open)
+            .resizable(true)
+            .collapsible(true)
+            .show(ctx, |ui| {
+                let Some(inspection) = game_state.inspect(target) else {
+                    ui.label(format!("{} {} is gone", target.kind(), target.id()));
+                    return;
+                };
+
+                ui.heading(format!("{} {}", target.kind(), target.id()));
+                match inspection.last_update {
+                    Some(update) => ui.label(format!(
+                        "Last update: message #{} ({} ago)",
+                        update,
+                        game_state.messages_received - update
+                    )),
+                    None => ui.label("Last update: never"),
+                };
+                ui.separator();
+
+                Grid::new("inspector_rows").striped(true).show(ui, |ui| {
+                    for (name, value) in &inspection.rows {
+                        ui.label(*name);
+                        ui.monospace(value);
+                        ui.end_row();
+                    }
+                });
+                ui.separator();
+
+                let json = serde_json::to_string_pretty(&inspection.json).unwrap_or_default();
+                if ui.button("Copy as JSON").clicked() {
+                    ui.ctx().copy_text(json.clone());
+                }
+                CollapsingHeader::new("JSON").show(ui, |ui| {
+                    ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                        ui.monospace(json);
+                    });
+                });
+            });
+        if !open {
+            self.inspected = None;
+        }
+    }
+
     fn render_performance_panel(&mut self, ui: &mut Ui) {
         ui.heading("Performance");
         ui.indent("performance_indent", |ui| {
             // FPS display
-            let current_fps = self.fps_history.back().map(|(_, fps)| *fps).unwrap_or(0.0);
+            let current_fps = self.smoothed_fps();
             ui.label(format!("FPS: {:.1}", current_fps));
+            ui.label(format!(
+                "Render scale: {:.0}%",
+                self.render_scale_now * 100.0
+            ));
 
             // Frame time display
             let current_frame_time = self
@@ -487,6 +884,29 @@ impl DebugOverlay {
                 });
             });
 
+            if let Some(check) = &game_state.state_check {
+                ui.separator();
+                ui.strong("State Checksum:");
+                if check.mismatched.is_empty() {
+                    ui.label(format!("In sync at tick {}", check.tick));
+                } else {
+                    ui.colored_label(Color32::RED, format!("Desync at tick {}", check.tick));
+                }
+                for subsystem in shared::StateHashes::SUBSYSTEMS {
+                    let marker = if check.mismatched.contains(&subsystem) {
+                        "!="
+                    } else {
+                        "=="
+                    };
+                    let ours = check.ours.subsystem(subsystem);
+                    let server = check.server.subsystem(subsystem);
+                    ui.monospace(format!(
+                        "{subsystem:<10} {ours:016x} {marker} {server:016x}"
+                    ));
+                }
+                ui.label(format!("Desyncs so far: {}", check.desyncs));
+            }
+
             ui.separator();
 
             // ASCII tile view
@@ -543,7 +963,7 @@ impl DebugOverlay {
                     changed |= ui.checkbox(&mut self.render_resources, "Render Resources").changed();
                     
                     if changed {
-                        self.save_settings();
+                        self.stage_settings();
                     }
                 });
                 ui.separator();
@@ -552,14 +972,29 @@ impl DebugOverlay {
                     changed |= ui.checkbox(&mut self.render_projectiles, "Render Projectiles").changed();
                     changed |= ui.checkbox(&mut self.render_effects, "Render Effects").changed();
                     changed |= ui.checkbox(&mut self.render_fog, "Render Fog of War").changed();
+                    changed |= ui.checkbox(&mut self.render_lighting, "Render Lighting").changed();
                     changed |= ui.checkbox(&mut self.render_ui, "Render UI").changed();
+                    changed |= ui.checkbox(&mut self.render_hit_feedback, "Damage Numbers & Shake").changed();
                     
                     if changed {
-                        self.save_settings();
+                        self.stage_settings();
                     }
                 });
             });
 
+            ui.horizontal(|ui| {
+                ui.label("Particles:");
+                let mut changed = false;
+                for quality in EffectsQuality::ALL {
+                    changed |= ui
+                        .selectable_value(&mut self.effects_quality, quality, quality.label())
+                        .changed();
+                }
+                if changed {
+                    self.stage_settings();
+                }
+            });
+
             ui.separator();
 
             if ui.button("Enable All").clicked() {
@@ -572,7 +1007,9 @@ impl DebugOverlay {
                 self.render_effects = true;
                 self.render_fog = true;
                 self.render_ui = true;
-                self.save_settings();
+                self.render_hit_feedback = true;
+                self.render_lighting = true;
+                self.stage_settings();
             }
 
             if ui.button("Disable All").clicked() {
@@ -585,7 +1022,9 @@ impl DebugOverlay {
                 self.render_effects = false;
                 self.render_fog = false;
                 self.render_ui = false;
-                self.save_settings();
+                self.render_hit_feedback = false;
+                self.render_lighting = false;
+                self.stage_settings();
             }
         });
     }
@@ -603,7 +1042,7 @@ impl DebugOverlay {
                 &mut self.spatial_debug_enabled,
                 "Enable Spatial Debug Rendering",
             ).changed() {
-                self.save_settings();
+                self.stage_settings();
             }
 
             ui.separator();
@@ -620,7 +1059,7 @@ impl DebugOverlay {
                     changed |= ui.checkbox(&mut self.show_door_positions, "Door Positions").changed();
                     
                     if changed {
-                        self.save_settings();
+                        self.stage_settings();
                     }
                 });
                 ui.separator();
@@ -628,9 +1067,13 @@ impl DebugOverlay {
                     let mut changed = false;
                     changed |= ui.checkbox(&mut self.show_coordinate_grid, "Coordinate Grid").changed();
                     changed |= ui.checkbox(&mut self.show_floor_offsets, "Floor Offsets").changed();
+                    changed |= ui
+                        .checkbox(&mut self.show_hitboxes, "Server Hitboxes")
+                        .on_hover_text("Collision shapes as the server checks them")
+                        .changed();
                     
                     if changed {
-                        self.save_settings();
+                        self.stage_settings();
                     }
                 });
             });
@@ -763,7 +1206,8 @@ impl DebugOverlay {
                 self.show_door_positions = true;
                 self.show_coordinate_grid = true;
                 self.show_floor_offsets = true;
-                self.save_settings();
+                self.show_hitboxes = true;
+                self.stage_settings();
             }
 
             if ui.button("Hide All Debug Info").clicked() {
@@ -772,7 +1216,8 @@ impl DebugOverlay {
                 self.show_door_positions = false;
                 self.show_coordinate_grid = false;
                 self.show_floor_offsets = false;
-                self.save_settings();
+                self.show_hitboxes = false;
+                self.stage_settings();
             }
         });
     }
@@ -1055,6 +1500,7 @@ impl DebugOverlay {
             TileVisual::Station {
                 station_type,
                 active,
+                ..
             } => {
                 match station_type {
                     StationType::Pilot => {
@@ -1078,6 +1524,13 @@ impl DebugOverlay {
                             't'
                         }
                     }
+                    StationType::WeaponEmp => {
+                        if *active {
+                            'M'
+                        } else {
+                            'm'
+                        }
+                    }
                     StationType::Shield => {
                         if *active {
                             'S'
@@ -1113,6 +1566,27 @@ impl DebugOverlay {
                             'c'
                         }
                     } // 'C' for Circuit
+                    StationType::TurretControl => {
+                        if *active {
+                            'T'
+                        } else {
+                            't'
+                        }
+                    }
+                    StationType::Radar => {
+                        if *active {
+                            'D'
+                        } else {
+                            'd'
+                        }
+                    }
+                    StationType::PointDefense => {
+                        if *active {
+                            'I'
+                        } else {
+                            'i'
+                        }
+                    }
                 }
             }
             TileVisual::Turret { firing, .. } => {
@@ -1122,6 +1596,7 @@ impl DebugOverlay {
                     'x'
                 }
             }
+            TileVisual::Locker { .. } => 'L',
             TileVisual::TransitionFade { .. } => '~',
         }
     }
@@ -1148,6 +1623,17 @@ pub struct DebugOverlay {
     pub render_fog: bool,
     pub render_tiles: bool,
     pub render_stations: bool,
+    pub render_hit_feedback: bool,
+    pub render_lighting: bool,
+    pub effects_quality: EffectsQuality,
+    pub graphics: GraphicsSettings,
+    pub render_scale_now: f32,
+    pub ui_scale: f32,
+    pub accessibility: AccessibilitySettings,
+    pub camera_smoothing: f32,
+    pub capture: CaptureSettings,
+    pub net_conditions: NetConditions,
+    pub voice: VoiceSettings,
     
     // Spatial debug controls (disabled in release builds)
     pub spatial_debug_enabled: bool,
@@ -1156,6 +1642,7 @@ pub struct DebugOverlay {
     pub show_door_positions: bool,
     pub show_coordinate_grid: bool,
     pub show_floor_offsets: bool,
+    pub show_hitboxes: bool,
 }
 
 #[cfg(not(debug_assertions))]
@@ -1171,6 +1658,17 @@ impl DebugOverlay {
             render_fog: true,
             render_tiles: true,
             render_stations: true,
+            render_hit_feedback: true,
+            render_lighting: true,
+            effects_quality: EffectsQuality::default(),
+            graphics: GraphicsSettings::default(),
+            render_scale_now: 1.0,
+            ui_scale: 1.0,
+            accessibility: AccessibilitySettings::default(),
+            camera_smoothing: shared::CAMERA_SMOOTHING,
+            capture: CaptureSettings::default(),
+            net_conditions: NetConditions::default(),
+            voice: VoiceSettings::default(),
             
             // Spatial debug disabled in release builds
             spatial_debug_enabled: false,
@@ -1179,10 +1677,54 @@ impl DebugOverlay {
             show_door_positions: false,
             show_coordinate_grid: false,
             show_floor_offsets: false,
+            show_hitboxes: false,
         }
     }
 
+    pub fn smoothed_fps(&self) -> f32 {
+        macroquad::prelude::get_fps() as f32
+    }
     pub fn update(&mut self, _game_state: &crate::game_state::GameState, _frame_time: f32) {}
     pub fn render_ui(&mut self, _ctx: &egui::Context, _game_state: &crate::game_state::GameState, _spatial_test_suite: &mut crate::spatial_testing::SpatialTestSuite) {}
     pub fn log_server_message(&mut self, _message: &str) {}
+    fn stage_settings(&mut self) {}
+}
+
+impl DebugOverlay {
+    /// Flip a render flag by its developer console name, returning the new value
+    pub fn toggle_flag(&mut self, name: &str) -> Option<bool> {
+        let flag = match name {
+            "fog" => &mut self.render_fog,
+            "mechs" => &mut self.render_mechs,
+            "players" => &mut self.render_players,
+            "resources" => &mut self.render_resources,
+            "projectiles" => &mut self.render_projectiles,
+            "effects" => &mut self.render_effects,
+            "ui" => &mut self.render_ui,
+            "tiles" => &mut self.render_tiles,
+            "stations" => &mut self.render_stations,
+            "hit_feedback" => &mut self.render_hit_feedback,
+            "lighting" => &mut self.render_lighting,
+            "spatial_debug" => &mut self.spatial_debug_enabled,
+            "hitboxes" => &mut self.show_hitboxes,
+            "colorblind" => &mut self.accessibility.colorblind,
+            "reduced_flash" => &mut self.accessibility.reduced_flash,
+            "voice_mute" => &mut self.voice.muted,
+            _ => return None,
+        };
+        *flag = !*flag;
+        let value = *flag;
+        self.stage_settings();
+        Some(value)
+    }
+
+    /// Whether the server should be sending its collision shapes
+    pub fn wants_collision_shapes(&self) -> bool {
+        self.spatial_debug_enabled && self.show_hitboxes
+    }
+
+    pub fn set_ui_scale(&mut self, scale: f32) {
+        self.ui_scale = scale.clamp(shared::UI_SCALE_MIN, shared::UI_SCALE_MAX);
+        self.stage_settings();
+    }
 }
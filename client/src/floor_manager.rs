@@ -1,6 +1,6 @@
 use shared::{
     mech_layout::{MechInterior, MechStation},
-    tile_entity::{FloorMap, StaticTile},
+    tile_entity::{Barricade, FloorMap, StaticTile},
     TilePos,
 };
 use std::collections::HashMap;
@@ -36,6 +36,42 @@ impl FloorManager {
             .get_floor(floor)
     }
 
+    /// Replace a single static tile, e.g. when a wall is breached or repaired
+    pub fn set_static_tile(&mut self, mech_id: Uuid, floor: u8, position: TilePos, tile: StaticTile) {
+        if let Some(floor_map) = self
+            .mech_floors
+            .get_mut(&mech_id)
+            .and_then(|interior| interior.floors.get_mut(floor as usize))
+        {
+            floor_map.set_static_tile(position, tile);
+        }
+    }
+
+    /// Put up, update or (with None) clear the barricade on one tile
+    pub fn set_barricade(
+        &mut self,
+        mech_id: Uuid,
+        floor: u8,
+        position: TilePos,
+        barricade: Option<Barricade>,
+    ) {
+        let Some(floor_map) = self
+            .mech_floors
+            .get_mut(&mech_id)
+            .and_then(|interior| interior.floors.get_mut(floor as usize))
+        else {
+            return;
+        };
+        match barricade {
+            Some(barricade) => {
+                floor_map.barricades.insert(position, barricade);
+            }
+            None => {
+                floor_map.barricades.remove(&position);
+            }
+        }
+    }
+
     /// Get all floor data for a mech
     pub fn get_mech_interior(&self, mech_id: Uuid) -> Option<&MechInterior> {
         self.mech_floors.get(&mech_id)
@@ -1,7 +1,37 @@
-use crate::{floor_manager::FloorManager, vision::ClientVisionSystem};
+use crate::{
+    accessibility::AccessibilitySettings,
+    animation::PlayerAnimation,
+    floor_manager::FloorManager,
+    particles::ParticleSystem,
+    rendering::camera::{CameraController, FollowTarget},
+    vision::ClientVisionSystem,
+};
 use macroquad::prelude::*;
-use shared::{constants::*, network_constants::*, tile_entity::TileVisual, types::*};
-use std::collections::HashMap;
+use shared::{
+    annotations::{Annotation, AnnotationColor, AnnotationShape},
+    arena_map::ArenaMap,
+    armor::HitDirection,
+    collision::CollisionShapes,
+    components::{SabotageAlarm, StatusEffect, TeamStructure},
+    constants::*,
+    decals::DecalLayer,
+    environment::Environment,
+    game_mode::GameModeHud,
+    hangar::{CrewRole, HangarCrew},
+    inventory::Inventory,
+    messages::{FireState, GroundItemState, ServerLoad, StationButtonInfo},
+    network_constants::*,
+    pacing::{DirectorFocus, PacingZone},
+    render_constants::*,
+    spawn_tables::SpawnTables,
+    state_hash::{StateDump, StateHashes},
+    stations::StationRegistry,
+    tile_entity::{StaticTile, TileVisual},
+    tile_math::MechPositioning,
+    types::*,
+    vision::SightShape,
+};
+use std::collections::{HashMap, HashSet};
 
 pub struct GameState {
     pub player_id: Option<PlayerId>,
@@ -11,19 +41,209 @@ pub struct GameState {
     pub mechs: HashMap<MechId, MechState>,
     pub stations: HashMap<StationId, StationState>,
     pub resources: Vec<ResourceState>,
+    pub ground_items: Vec<GroundItemState>,
+    /// Scorches, burns and debris on the ground, kept as the server keeps them
+    pub decals: DecalLayer,
+    /// Team base buildings; static for the whole match
+    pub structures: Vec<TeamStructure>,
     pub projectiles: Vec<ProjectileData>,
     pub weapon_effects: Vec<WeaponEffect>,
-    pub camera_offset: (f32, f32),
+    pub camera: CameraController,
     pub ui_state: UIState,
     pub visible_tiles: HashMap<TilePos, TileVisual>,
+    /// Tiles our teammates and mech sensors can see, from the server
+    pub team_visible_tiles: HashSet<TilePos>,
+    /// The server's collision shapes, while the debug overlay asks for them
+    pub collision_shapes: Option<CollisionShapes>,
     pub vision_system: ClientVisionSystem,
+    /// How far we see around us outside, as the server told us on joining
+    pub sight: SightShape,
     pub floor_manager: FloorManager,
+    /// Active status effects per player or mech, shown as badges
+    pub status_effects: HashMap<EntityId, Vec<StatusEffect>>,
+    /// Interior fires per mech
+    pub mech_fires: HashMap<MechId, Vec<FireState>>,
+    /// Hull repair progress (0.0 - 1.0) for players currently channelling one
+    pub repair_progress: HashMap<PlayerId, f32>,
+    /// Sabotage each boarder is channelling: the station and how far along
+    pub sabotage_progress: HashMap<PlayerId, (StationId, f32)>,
+    /// Mechs sounding their boarding alarm, and where the boarders are
+    pub mech_alarms: HashMap<MechId, SabotageAlarm>,
+    /// Painted tiles and hazards of the arena we are playing on
+    pub arena_map: Option<ArenaMap>,
+    /// Zones placed by the server's match director
+    pub pacing_zones: Vec<PacingZone>,
+    /// Time of day and weather, run locally between server updates
+    pub environment: Environment,
+    /// Latest director announcement and how long it stays on screen
+    pub pacing_announcement: Option<(String, f32)>,
+    /// How much the server is giving up to keep up, shown while it's behind
+    pub server_load: ServerLoad,
+    /// Why the server is going down and seconds left until it does
+    pub server_shutdown: Option<(String, f32)>,
+    /// Why the server wouldn't let us join, when our versions don't match
+    pub join_rejected: Option<String>,
+    /// The room wants a password; true when the one we sent was wrong
+    pub password_required: Option<bool>,
+    /// Key for the voice socket, until the voice chat picks it up
+    pub voice_key: Option<uuid::Uuid>,
+    /// Token for getting our player back if the connection drops, until
+    /// the main loop picks it up
+    pub resume_token: Option<uuid::Uuid>,
+    /// Teammates talking right now, and us while push-to-talk is held
+    pub voice_speakers: Vec<PlayerId>,
+    /// Voice chat is hard muted
+    pub voice_muted: bool,
+    /// Placing barricades: the tile in front of us is previewed and a click
+    /// builds or takes down the barricade there
+    pub barricade_placement: bool,
+    /// Current tutorial instruction, its step and how many there are
+    pub tutorial: Option<(String, u32, u32)>,
+    /// Objective of the server's game mode, if the match has one
+    pub game_mode: Option<GameModeHud>,
+    /// Everyone's picks while the match waits in the hangar
+    pub hangar: Option<HangarState>,
+    /// Achievement toasts, newest last: title, detail and seconds left
+    pub toasts: Vec<(String, String, f32)>,
+    /// Buttons of the station the local player is operating
+    pub station_panel: Option<StationPanel>,
+    /// Button cooldowns still running on stations we've stepped off, so
+    /// their tooltips and panels pick up where we left them
+    pub station_cooldowns: HashMap<StationId, Vec<f32>>,
+    /// Floating damage numbers and impact flashes
+    pub damage_numbers: Vec<DamageNumber>,
+    /// Arrows toward where recent hits on our mech came from
+    pub hit_indicators: Vec<HitIndicator>,
+    pub hit_flashes: Vec<HitFlash>,
+    pub shield_ripples: Vec<ShieldRipple>,
+    pub shield_collapses: Vec<ShieldCollapse>,
+    /// Seconds until each winding-up mech's stomp lands
+    pub stomp_windups: HashMap<MechId, f32>,
+    pub shockwaves: Vec<Shockwave>,
+    /// Effects spawned by scripted map tiles
+    pub tile_effects: Vec<TileEffect>,
+    /// Coaching marks drawn on the map, with how long each has been up
+    pub annotations: Vec<(Annotation, f32)>,
+    /// The mark being dragged out in the drawing toolbar, not sent yet
+    pub annotation_preview: Option<(AnnotationShape, AnnotationColor)>,
+    /// Muzzle flashes, sparks, trails and smoke
+    pub particles: ParticleSystem,
+    pub animations: HashMap<PlayerId, PlayerAnimation>,
+    /// Seconds each mech's interior lights keep stuttering after a hit
+    pub light_flicker: HashMap<MechId, f32>,
+    /// Mirrors the hit feedback setting; off skips numbers, flashes and shake
+    pub hit_feedback_enabled: bool,
+    /// Mirrors the accessibility settings
+    pub accessibility: AccessibilitySettings,
+    /// Mirrors the UI scale setting; multiplies HUD text and panels
+    pub ui_scale: f32,
+    /// Our team's latest drop-off, highlighted in the cargo panel
+    pub last_delivery: Option<(ResourceType, f32)>,
+    /// Server replies to developer console commands, waiting to be printed
+    pub console_messages: Vec<String>,
+    /// How our mirror compared with the server's last state checksum
+    pub state_check: Option<StateCheck>,
+    /// Enemy mechs our team's last radar sweep found, and seconds until
+    /// each blip fades
+    pub radar_contacts: Vec<(WorldPos, f32)>,
+    /// Seconds until each of our mechs' radar can sweep again
+    pub radar_cooldowns: HashMap<MechId, f32>,
+    /// What our scanner last picked up, until it fades
+    pub scanner_ping: Option<ScannerPing>,
+    /// Station names and labels, matching the server's definitions
+    pub station_registry: StationRegistry,
+    /// How rare each resource is, for drawing them
+    pub spawn_tables: SpawnTables,
+    /// Watching the match without a player
+    pub spectating: bool,
+    /// The camera director's latest pick, and why
+    pub director_focus: Option<(MechId, DirectorFocus)>,
+    /// Spectators follow the director until they point the camera themselves
+    pub director_auto: bool,
+    /// Server messages handled so far, numbering each one
+    pub messages_received: u64,
+    /// Number of the last message that carried state for each entity
+    pub entity_updates: HashMap<EntityId, u64>,
 }
 
 pub struct UIState {
     pub pilot_station_open: bool,
     pub pilot_station_id: Option<StationId>,
     pub operating_mech_id: Option<MechId>,
+    /// Where our mouse points the turret we're crewing, if we are
+    pub turret_aim: Option<f32>,
+    /// Last aim we told the server about
+    pub turret_aim_sent: Option<f32>,
+}
+
+pub struct StationPanel {
+    pub station_id: StationId,
+    pub name: String,
+    pub description: String,
+    pub buttons: Vec<StationButtonInfo>,
+    /// Seconds left on each button's cooldown since we last pressed it
+    pub cooldowns: Vec<f32>,
+    /// Button Enter presses, moved with Tab and Shift+Tab
+    pub focus: usize,
+}
+
+impl StationPanel {
+    /// Start the local cooldown display for a pressed button.
+    /// Returns false if the button was still cooling down.
+    pub fn press(&mut self, index: usize) -> bool {
+        if let (Some(button), Some(cooldown)) = (self.buttons.get(index), self.cooldowns.get_mut(index)) {
+            let ready = *cooldown <= 0.0;
+            *cooldown = button.cooldown_seconds;
+            return ready;
+        }
+        false
+    }
+
+    /// Move keyboard focus forward or back, wrapping around
+    pub fn move_focus(&mut self, forward: bool) {
+        let count = self.buttons.len();
+        if count == 0 {
+            return;
+        }
+        self.focus = if forward {
+            (self.focus + 1) % count
+        } else {
+            (self.focus + count - 1) % count
+        };
+    }
+}
+
+/// Our hashes against the server's for the latest full state
+pub struct StateCheck {
+    pub tick: u64,
+    pub server: StateHashes,
+    pub ours: StateHashes,
+    /// Subsystems that disagreed, empty while in sync
+    pub mismatched: Vec<&'static str>,
+    /// Checks that have disagreed since we joined
+    pub desyncs: u32,
+}
+
+/// The hangar's roster and clock, counted down locally between updates
+pub struct HangarState {
+    pub crew: Vec<HangarCrew>,
+    pub time_left: f32,
+}
+
+impl HangarState {
+    /// Our own pick, once the server has us on the roster
+    pub fn ours(&self, player_id: Option<PlayerId>) -> Option<&HangarCrew> {
+        let player_id = player_id?;
+        self.crew.iter().find(|crew| crew.player_id == player_id)
+    }
+
+    /// Teammates on `team` who picked `role`
+    pub fn taken_by(&self, team: TeamId, role: CrewRole) -> usize {
+        self.crew
+            .iter()
+            .filter(|crew| crew.team == team && crew.role == Some(role))
+            .count()
+    }
 }
 
 pub struct PlayerData {
@@ -31,7 +251,23 @@ pub struct PlayerData {
     pub name: String,
     pub team: TeamId,
     pub location: PlayerLocation,
-    pub carrying_resource: Option<ResourceType>,
+    pub inventory: Inventory,
+    pub held_tool: Option<ToolType>,
+    pub health: u32,
+    pub facing: Facing,
+    /// Hasn't sent input in a while
+    pub afk: bool,
+}
+
+impl PlayerData {
+    /// Name to show over the player, badged while they're away
+    pub fn label(&self) -> String {
+        if self.afk {
+            format!("{} [AFK]", self.name)
+        } else {
+            self.name.clone()
+        }
+    }
 }
 
 pub struct MechState {
@@ -40,10 +276,17 @@ pub struct MechState {
     pub world_position: WorldPos,
     pub team: TeamId,
     pub health: u32,
+    pub max_health: u32,
     pub shield: u32,
     pub upgrades: shared::MechUpgrades,
+    pub drive: shared::MechDrive,
+    pub mass: shared::MechMass,
+    pub fuel: f32,
+    pub turret: shared::MechTurret,
+    pub point_defense: shared::PointDefense,
     pub floors: Vec<MechFloor>,
-    pub _resource_inventory: HashMap<ResourceType, u32>,
+    pub resource_inventory: HashMap<ResourceType, u32>,
+    pub delivery_score: u32,
 }
 
 pub struct MechFloor {
@@ -64,6 +307,19 @@ pub struct StationState {
     pub station_type: StationType,
     pub occupied: bool,
     pub operated_by: Option<PlayerId>,
+    pub health: u32,
+    pub max_health: u32,
+    pub upgrade_level: u8,
+}
+
+impl StationState {
+    /// Fraction of health left; stations from older servers count as whole
+    pub fn condition(&self) -> f32 {
+        if self.max_health == 0 {
+            return 1.0;
+        }
+        self.health as f32 / self.max_health as f32
+    }
 }
 
 pub struct ResourceState {
@@ -73,9 +329,14 @@ pub struct ResourceState {
 }
 
 pub struct ProjectileData {
+    /// Server id, or nil while the projectile is only predicted locally
     pub id: ProjectileId,
     pub position: WorldPos,
-    pub _velocity: (f32, f32),
+    pub velocity: (f32, f32), // pixels per second
+    pub owner_mech_id: MechId,
+    pub weapon_type: shared::WeaponType,
+    /// Set until the server confirms the spawn
+    pub prediction: Option<ShotPrediction>,
 }
 
 pub struct WeaponEffect {
@@ -84,6 +345,108 @@ pub struct WeaponEffect {
     pub target: TilePos,
     pub timer: f32,
     pub _projectile_id: Option<ProjectileId>,
+    /// Set until the server confirms the shot
+    pub prediction: Option<ShotPrediction>,
+}
+
+/// Damage amount rising and fading above where it landed
+pub struct DamageNumber {
+    pub position: WorldPos,
+    pub damage: u32,
+    pub age: f32,
+}
+
+impl DamageNumber {
+    pub fn alpha(&self) -> f32 {
+        (1.0 - self.age / DAMAGE_NUMBER_DURATION).clamp(0.0, 1.0)
+    }
+
+    /// Current position after rising for `age` seconds
+    pub fn current_position(&self) -> WorldPos {
+        WorldPos::new(
+            self.position.x,
+            self.position.y - self.age * DAMAGE_NUMBER_RISE_SPEED,
+        )
+    }
+}
+
+/// Brief flash where a projectile struck
+pub struct HitFlash {
+    pub position: WorldPos,
+    pub age: f32,
+}
+
+/// Which way a hit on our mech came from, fading out
+pub struct HitIndicator {
+    pub hit: HitDirection,
+    pub age: f32,
+}
+
+/// Ripple spreading from where something struck a shield bubble
+pub struct ShieldRipple {
+    pub position: WorldPos,
+    pub age: f32,
+}
+
+/// A shield bubble folding in after the hit that broke it
+pub struct ShieldCollapse {
+    pub mech_id: MechId,
+    pub age: f32,
+}
+
+/// Ring of dust spreading from a stomp that just landed
+pub struct Shockwave {
+    pub position: WorldPos,
+    pub radius: f32,
+    pub age: f32,
+}
+
+/// Resources our handheld scanner found: where the close ones are and which
+/// way the further ones lie
+pub struct ScannerPing {
+    pub origin: WorldPos,
+    pub contacts: Vec<(WorldPos, ResourceType)>,
+    pub bearings: Vec<f32>,
+    pub duration: f32,
+    pub age: f32,
+}
+
+/// One of `SCRIPT_EFFECTS` playing on a scripted tile
+pub struct TileEffect {
+    pub kind: String,
+    pub position: WorldPos,
+    pub duration: f32,
+    pub age: f32,
+}
+
+/// What clicking in barricade placement mode would do to the tile in front of us
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BarricadePreview {
+    /// Clear floor a barricade can go on
+    Buildable,
+    /// One of ours, which the click takes down
+    Ours,
+    /// A wall, station, stairway, somebody standing there or an enemy barricade
+    Blocked,
+}
+
+/// A shot drawn as soon as we fired, before the server's spawn message arrived
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ShotPrediction {
+    pub age: f32, // seconds since we fired
+}
+
+impl ShotPrediction {
+    /// Opacity of the predicted shot; fades once the server is overdue
+    pub fn alpha(&self) -> f32 {
+        let overdue = (self.age - PREDICTION_CONFIRM_TIMEOUT).max(0.0);
+        (1.0 - overdue / PREDICTION_FADE_DURATION).clamp(0.0, 1.0)
+    }
+
+    /// The server never confirmed the shot and it has fully faded out
+    pub fn is_mispredicted(&self) -> bool {
+        self.age >= PREDICTION_CONFIRM_TIMEOUT + PREDICTION_FADE_DURATION
+    }
 }
 
 impl GameState {
@@ -99,56 +462,619 @@ impl GameState {
             mechs: HashMap::new(),
             stations: HashMap::new(),
             resources: Vec::new(),
+            ground_items: Vec::new(),
+            decals: DecalLayer::new(),
+            structures: Vec::new(),
             projectiles: Vec::new(),
             weapon_effects: Vec::new(),
-            camera_offset: (0.0, 0.0),
+            camera: CameraController::new(),
             ui_state: UIState {
                 pilot_station_open: false,
                 pilot_station_id: None,
                 operating_mech_id: None,
+                turret_aim: None,
+                turret_aim_sent: None,
             },
             visible_tiles: HashMap::new(),
+            team_visible_tiles: HashSet::new(),
+            collision_shapes: None,
             vision_system: ClientVisionSystem::new(),
+            sight: SightShape::default(),
             floor_manager: FloorManager::new(),
+            status_effects: HashMap::new(),
+            mech_fires: HashMap::new(),
+            repair_progress: HashMap::new(),
+            sabotage_progress: HashMap::new(),
+            mech_alarms: HashMap::new(),
+            arena_map: None,
+            pacing_zones: Vec::new(),
+            environment: Environment::default(),
+            pacing_announcement: None,
+            server_load: ServerLoad::Normal,
+            server_shutdown: None,
+            join_rejected: None,
+            password_required: None,
+            voice_key: None,
+            resume_token: None,
+            voice_speakers: Vec::new(),
+            voice_muted: false,
+            barricade_placement: false,
+            tutorial: None,
+            game_mode: None,
+            hangar: None,
+            toasts: Vec::new(),
+            station_panel: None,
+            station_cooldowns: HashMap::new(),
+            damage_numbers: Vec::new(),
+            hit_indicators: Vec::new(),
+            hit_flashes: Vec::new(),
+            shield_ripples: Vec::new(),
+            shield_collapses: Vec::new(),
+            stomp_windups: HashMap::new(),
+            shockwaves: Vec::new(),
+            tile_effects: Vec::new(),
+            annotations: Vec::new(),
+            annotation_preview: None,
+            particles: ParticleSystem::new(),
+            animations: HashMap::new(),
+            light_flicker: HashMap::new(),
+            hit_feedback_enabled: true,
+            accessibility: AccessibilitySettings::default(),
+            ui_scale: 1.0,
+            last_delivery: None,
+            console_messages: Vec::new(),
+            state_check: None,
+            radar_contacts: Vec::new(),
+            radar_cooldowns: HashMap::new(),
+            scanner_ping: None,
+            station_registry: load_station_registry(),
+            spawn_tables: load_spawn_tables(),
+            spectating: false,
+            director_focus: None,
+            director_auto: true,
+            messages_received: 0,
+            entity_updates: HashMap::new(),
+        }
+    }
+
+    /// Start over for a new connection, keeping where the camera looks and
+    /// how it moves
+    pub fn reset_keeping_view(&mut self) {
+        let camera = std::mem::take(&mut self.camera);
+        *self = Self::new();
+        self.camera = camera;
+    }
+
+    /// Show a damage number, shaking the screen if our own mech took a big hit
+    pub fn add_damage_feedback(&mut self, mech_id: MechId, damage: u32, position: WorldPos) {
+        if !self.hit_feedback_enabled || damage == 0 {
+            return;
+        }
+        self.damage_numbers.push(DamageNumber {
+            position,
+            damage,
+            age: 0.0,
+        });
+
+        let our_mech = matches!(
+            self.player_location,
+            PlayerLocation::InsideMech { mech_id: inside, .. } if inside == mech_id
+        );
+        if our_mech && damage >= BIG_HIT_DAMAGE {
+            self.camera.shake(HIT_SHAKE_INTENSITY, HIT_SHAKE_DURATION);
+        }
+    }
+
+    /// Point the crew toward a weapon hit on our own mech
+    pub fn add_hit_indicator(&mut self, mech_id: MechId, hit: HitDirection) {
+        let our_mech = matches!(
+            self.player_location,
+            PlayerLocation::InsideMech { mech_id: inside, .. } if inside == mech_id
+        );
+        if self.hit_feedback_enabled && our_mech {
+            self.hit_indicators.push(HitIndicator { hit, age: 0.0 });
+        }
+    }
+
+    /// Rock the screen when our own mech is hit hard enough to throw the
+    /// crew around, harder the more violent the impact
+    pub fn add_impact_shake(&mut self, mech_id: MechId, severity: f32) {
+        let our_mech = matches!(
+            self.player_location,
+            PlayerLocation::InsideMech { mech_id: inside, .. } if inside == mech_id
+        );
+        if self.hit_feedback_enabled && our_mech {
+            self.camera.shake(
+                IMPACT_SHAKE_INTENSITY * (0.5 + 0.5 * severity),
+                IMPACT_SHAKE_DURATION,
+            );
+        }
+    }
+
+    /// Shake the screen for an explosion near what we're looking at
+    pub fn add_explosion_shake(&mut self, position: WorldPos) {
+        if self.hit_feedback_enabled {
+            self.camera.shake_from(
+                position,
+                EXPLOSION_SHAKE_INTENSITY,
+                EXPLOSION_SHAKE_DURATION,
+            );
+        }
+    }
+
+    /// Shake the camera for a stomp landing near it
+    pub fn add_stomp_shake(&mut self, position: WorldPos) {
+        if self.hit_feedback_enabled {
+            self.camera
+                .shake_from(position, STOMP_SHAKE_INTENSITY, STOMP_SHAKE_DURATION);
+        }
+    }
+
+    /// Shake the camera for two mechs crashing together near it
+    pub fn add_ram_shake(&mut self, position: WorldPos) {
+        if self.hit_feedback_enabled {
+            self.camera
+                .shake_from(position, RAM_SHAKE_INTENSITY, RAM_SHAKE_DURATION);
+        }
+    }
+
+    /// Switch the camera between following us and following our mech,
+    /// returning a description of the new mode
+    pub fn cycle_camera_follow(&mut self) -> &'static str {
+        match self.camera.follow {
+            FollowTarget::Player => {
+                self.camera.follow = FollowTarget::OwnMech;
+                "Following your mech"
+            }
+            FollowTarget::OwnMech | FollowTarget::Entity(_) => {
+                self.camera.follow = FollowTarget::Player;
+                "Following you"
+            }
+        }
+    }
+
+    /// Take the director's suggestion, cutting to it if we're following along
+    pub fn suggest_camera(&mut self, target: MechId, reason: DirectorFocus) {
+        let changed = self.director_focus.map(|(mech_id, _)| mech_id) != Some(target);
+        self.director_focus = Some((target, reason));
+        if !self.spectating || !self.director_auto {
+            return;
+        }
+        self.camera.follow = FollowTarget::Entity(target);
+        if changed {
+            let team = self.mechs.get(&target).map(|mech| mech.team);
+            let title = match team {
+                Some(team) => format!("{team:?} mech"),
+                None => "Director".to_string(),
+            };
+            self.push_toast(title, reason.describe().to_string());
+        }
+    }
+
+    /// Hand the camera back to the director
+    pub fn follow_director(&mut self) {
+        self.director_auto = true;
+        if let Some((target, _)) = self.director_focus {
+            self.camera.follow = FollowTarget::Entity(target);
+        }
+    }
+
+    /// Spectators step through the mechs themselves, which stops the
+    /// director cutting away; returns a description of the new view
+    pub fn cycle_spectator_camera(&mut self) -> String {
+        let mut mech_ids: Vec<MechId> = self.mechs.keys().copied().collect();
+        mech_ids.sort();
+        let current = match self.camera.follow {
+            FollowTarget::Entity(id) => mech_ids.iter().position(|mech_id| *mech_id == id),
+            _ => None,
+        };
+        let next = current.map_or(0, |index| index + 1);
+        let Some(&mech_id) = mech_ids.get(next % mech_ids.len().max(1)) else {
+            return "Nothing to watch".to_string();
+        };
+        self.director_auto = false;
+        self.camera.follow = FollowTarget::Entity(mech_id);
+        match self.mechs.get(&mech_id) {
+            Some(mech) => format!("Watching the {:?} mech", mech.team),
+            None => "Watching a mech".to_string(),
+        }
+    }
+
+    /// The mech whose turret station we're crewing, if any
+    pub fn operated_turret_mech(&self) -> Option<MechId> {
+        self.operated_station_mech(StationType::TurretControl)
+    }
+
+    /// The mech whose radar we're crewing, if any
+    pub fn operated_radar_mech(&self) -> Option<MechId> {
+        self.operated_station_mech(StationType::Radar)
+    }
+
+    fn operated_station_mech(&self, station_type: StationType) -> Option<MechId> {
+        let player_id = self.player_id?;
+        self.stations
+            .values()
+            .find(|station| {
+                station.station_type == station_type && station.operated_by == Some(player_id)
+            })
+            .map(|station| station.mech_id)
+    }
+
+    /// Hash our mirror of every player, mech and resource the way the
+    /// server hashes its own
+    pub fn state_dump(&self) -> StateDump {
+        let mut dump = StateDump::default();
+        for (id, player) in &self.players {
+            dump.add_player(*id, player.team, player.location, player.health);
+        }
+        for mech in self.mechs.values() {
+            dump.add_mech(
+                mech.id,
+                mech.team,
+                mech.position,
+                mech.health,
+                mech.shield,
+                &mech.resource_inventory,
+            );
+        }
+        for resource in &self.resources {
+            dump.add_resource(resource.id, resource.resource_type, resource.position);
+        }
+        dump
+    }
+
+    /// Queue a corner notification, dropping the oldest if too many are up
+    pub fn push_toast(&mut self, title: String, detail: String) {
+        self.toasts.push((title, detail, TOAST_DURATION));
+        if self.toasts.len() > TOAST_MAX_VISIBLE {
+            self.toasts.remove(0);
+        }
+    }
+
+    /// Flash from a mech's center toward the tile it fired at
+    pub fn add_muzzle_flash(&mut self, mech_id: MechId, target: TilePos) {
+        if let Some(mech) = self.mechs.get(&mech_id) {
+            let origin = MechPositioning::mech_center(mech.position);
+            self.particles
+                .muzzle_flash(origin, target.to_world_center());
+        }
+    }
+
+    fn update_particles(&mut self, delta: f32) {
+        self.particles.emit_trails(&self.projectiles, delta);
+        self.particles.emit_smoke(self.mechs.values(), delta);
+        let (projectiles, mechs) = (&self.projectiles, &self.mechs);
+        self.particles.retain_emitters(|id| {
+            mechs.contains_key(id) || projectiles.iter().any(|p| p.id == *id)
+        });
+        self.particles.update(delta);
+    }
+
+    pub fn add_hit_flash(&mut self, position: WorldPos) {
+        if self.hit_feedback_enabled {
+            self.hit_flashes.push(HitFlash { position, age: 0.0 });
         }
     }
 
     pub fn update(&mut self, delta: f32) {
-        // Update weapon effects
+        self.update_state(delta);
+
+        let (width, height) = match &self.arena_map {
+            Some(map) => (map.width, map.height),
+            None => (ARENA_WIDTH_TILES, ARENA_HEIGHT_TILES),
+        };
+        let arena_size = (width as f32 * TILE_SIZE, height as f32 * TILE_SIZE);
+        self.camera.update(self.camera_target(), arena_size, delta);
+    }
+
+    /// Everything `update` does but follow with the camera, which needs a
+    /// window to size itself against
+    pub fn update_state(&mut self, delta: f32) {
+        // Update weapon effects, dropping predicted shots the server never confirmed
         self.weapon_effects.retain_mut(|effect| {
             effect.timer -= delta;
+            if let Some(prediction) = effect.prediction.as_mut() {
+                prediction.age += delta;
+                if prediction.is_mispredicted() {
+                    return false;
+                }
+            }
             effect.timer > 0.0
         });
 
+        // Move projectiles between server updates
+        self.projectiles.retain_mut(|projectile| {
+            projectile.position.x += projectile.velocity.0 * delta;
+            projectile.position.y += projectile.velocity.1 * delta;
+            match projectile.prediction.as_mut() {
+                Some(prediction) => {
+                    prediction.age += delta;
+                    !prediction.is_mispredicted()
+                }
+                None => true,
+            }
+        });
+
+        // Count down status effect badges between server updates
+        for effects in self.status_effects.values_mut() {
+            effects.retain_mut(|effect| {
+                effect.remaining -= delta;
+                effect.remaining > 0.0
+            });
+        }
+        self.status_effects.retain(|_, effects| !effects.is_empty());
+
+        if let Some(panel) = self.station_panel.as_mut() {
+            for cooldown in panel.cooldowns.iter_mut() {
+                *cooldown = (*cooldown - delta).max(0.0);
+            }
+        }
+        self.station_cooldowns.retain(|_, cooldowns| {
+            for cooldown in cooldowns.iter_mut() {
+                *cooldown = (*cooldown - delta).max(0.0);
+            }
+            cooldowns.iter().any(|cooldown| *cooldown > 0.0)
+        });
+
+        // The server decides when the weather turns, so only the clock runs here
+        self.environment.advance(delta);
+
+        // Shrink and expire pacing zones locally; the server resyncs on expiry
+        self.pacing_zones.retain_mut(|zone| !zone.advance(delta));
+        if let Some((_, remaining)) = self.pacing_announcement.as_mut() {
+            *remaining -= delta;
+            if *remaining <= 0.0 {
+                self.pacing_announcement = None;
+            }
+        }
+        if let Some((_, remaining)) = self.server_shutdown.as_mut() {
+            *remaining = (*remaining - delta).max(0.0);
+        }
+        if let Some(hangar) = self.hangar.as_mut() {
+            hangar.time_left = (hangar.time_left - delta).max(0.0);
+        }
+        self.toasts.retain_mut(|(_, _, remaining)| {
+            *remaining -= delta;
+            *remaining > 0.0
+        });
+
+        self.radar_contacts.retain_mut(|(_, remaining)| {
+            *remaining -= delta;
+            *remaining > 0.0
+        });
+
+        self.light_flicker.retain(|_, remaining| {
+            *remaining -= delta;
+            *remaining > 0.0
+        });
+        self.radar_cooldowns.retain(|_, remaining| {
+            *remaining -= delta;
+            *remaining > 0.0
+        });
+        if let Some(ping) = self.scanner_ping.as_mut() {
+            ping.age += delta;
+            if ping.age >= ping.duration {
+                self.scanner_ping = None;
+            }
+        }
+
+        if let Some((_, remaining)) = self.last_delivery.as_mut() {
+            *remaining -= delta;
+            if *remaining <= 0.0 {
+                self.last_delivery = None;
+            }
+        }
+
+        // Age hit feedback
+        self.damage_numbers.retain_mut(|number| {
+            number.age += delta;
+            number.age < DAMAGE_NUMBER_DURATION
+        });
+        self.hit_flashes.retain_mut(|flash| {
+            flash.age += delta;
+            flash.age < HIT_FLASH_DURATION
+        });
+        self.hit_indicators.retain_mut(|indicator| {
+            indicator.age += delta;
+            indicator.age < HIT_INDICATOR_DURATION
+        });
+        self.shield_ripples.retain_mut(|ripple| {
+            ripple.age += delta;
+            ripple.age < SHIELD_RIPPLE_DURATION
+        });
+        self.shield_collapses.retain_mut(|collapse| {
+            collapse.age += delta;
+            collapse.age < SHIELD_COLLAPSE_DURATION
+        });
+        self.stomp_windups.retain(|_, remaining| {
+            *remaining -= delta;
+            *remaining > 0.0
+        });
+        self.shockwaves.retain_mut(|shockwave| {
+            shockwave.age += delta;
+            shockwave.age < STOMP_SHOCKWAVE_DURATION
+        });
+        self.annotations.retain_mut(|(annotation, age)| {
+            *age += delta;
+            *age < annotation.ttl
+        });
+        self.tile_effects.retain_mut(|effect| {
+            effect.age += delta;
+            effect.age < effect.duration
+        });
+        self.update_particles(delta);
+        self.update_animations(delta);
+
         // Update vision system
         self.update_vision();
+    }
 
-        // Update camera to follow player
-        match &self.player_location {
-            PlayerLocation::OutsideWorld(pos) => {
-                self.camera_offset = (pos.x - screen_width() / 2.0, pos.y - screen_height() / 2.0);
+    /// Where the camera's follow target is, once we've joined. A watched
+    /// entity that's gone falls back to our own player.
+    fn camera_target(&self) -> Option<WorldPos> {
+        let player = self
+            .player_id
+            .map(|_| self.location_world_pos(&self.player_location));
+        match self.camera.follow {
+            FollowTarget::Player => player,
+            FollowTarget::OwnMech => match self.player_location {
+                PlayerLocation::InsideMech { mech_id, .. } => self
+                    .mechs
+                    .get(&mech_id)
+                    .map(|mech| MechPositioning::mech_center(mech.position))
+                    .or(player),
+                PlayerLocation::OutsideWorld(_) => player,
+            },
+            FollowTarget::Entity(entity_id) => {
+                if let Some(other) = self.players.get(&entity_id) {
+                    Some(self.location_world_pos(&other.location))
+                } else if let Some(mech) = self.mechs.get(&entity_id) {
+                    Some(MechPositioning::mech_center(mech.position))
+                } else {
+                    player
+                }
             }
+        }
+    }
+
+    fn location_world_pos(&self, location: &PlayerLocation) -> WorldPos {
+        match location {
+            PlayerLocation::OutsideWorld(pos) => *pos,
             PlayerLocation::InsideMech { mech_id, pos } => {
                 // Get the world position by finding the mech's world position
-                let world_pos = if let Some(mech) = self.mechs.get(mech_id) {
+                if let Some(mech) = self.mechs.get(mech_id) {
                     // Use the mech's world position to convert interior position to world coordinates
                     pos.to_world_with_mech(mech.world_position)
                 } else {
                     // Fallback: use local world coordinates if mech not found yet
                     // This can happen during initial connection/sync
                     pos.to_local_world()
-                };
-                self.camera_offset = (world_pos.x - screen_width() / 2.0, world_pos.y - screen_height() / 2.0);
+                }
             }
         }
     }
 
     /// Update the vision system using the new static method pattern
+    /// The tile in front of us aboard our own mech and what a barricade click
+    /// would do there. None anywhere barricades can't be built.
+    pub fn barricade_target(&self) -> Option<(MechId, MechInteriorPos, BarricadePreview)> {
+        let PlayerLocation::InsideMech { mech_id, pos } = self.player_location else {
+            return None;
+        };
+        self.mechs
+            .get(&mech_id)
+            .filter(|mech| Some(mech.team) == self.player_team)?;
+        let facing = self.players.get(&self.player_id?)?.facing;
+        let floor_map = self.floor_manager.get_floor(mech_id, pos.floor)?;
+
+        let angle = facing.angle();
+        let target = MechInteriorPos::new(
+            pos.floor,
+            TilePos::new(
+                pos.tile_pos.x + angle.cos().round() as i32,
+                pos.tile_pos.y + angle.sin().round() as i32,
+            ),
+        );
+        let preview = match floor_map.barricades.get(&target.tile_pos) {
+            Some(barricade) if Some(barricade.team) == self.player_team => BarricadePreview::Ours,
+            Some(_) => BarricadePreview::Blocked,
+            None => {
+                let open_floor = !floor_map.entity_tiles.contains_key(&target.tile_pos)
+                    && floor_map
+                        .static_tiles
+                        .get(&target.tile_pos)
+                        .is_some_and(|tile| {
+                            tile.is_walkable() && !matches!(tile, StaticTile::TransitionZone { .. })
+                        });
+                let occupied = self.players.values().any(|player| {
+                    player.location
+                        == PlayerLocation::InsideMech {
+                            mech_id,
+                            pos: target,
+                        }
+                });
+                if open_floor && !occupied {
+                    BarricadePreview::Buildable
+                } else {
+                    BarricadePreview::Blocked
+                }
+            }
+        };
+        Some((mech_id, target, preview))
+    }
+
+    /// Turn our own player the way we're moving, ahead of the server
+    pub fn face_local_player(&mut self, movement: (f32, f32)) {
+        let Some(facing) = Facing::from_movement(movement) else {
+            return;
+        };
+        if let Some(player) = self.player_id.and_then(|id| self.players.get_mut(&id)) {
+            player.facing = facing;
+        }
+    }
+
+    /// Turn our player outside to face `target`; the new facing if we turned
+    pub fn look_at(&mut self, target: WorldPos) -> Option<Facing> {
+        let player = self.player_id.and_then(|id| self.players.get_mut(&id))?;
+        let PlayerLocation::OutsideWorld(pos) = player.location else {
+            return None;
+        };
+        let facing = Facing::from_movement((target.x - pos.x, target.y - pos.y))
+            .filter(|facing| *facing != player.facing)?;
+        player.facing = facing;
+        Some(facing)
+    }
+
+    fn update_animations(&mut self, delta: f32) {
+        self.animations
+            .retain(|id, _| self.players.contains_key(id));
+        for (id, player) in &self.players {
+            let position = match player.location {
+                PlayerLocation::OutsideWorld(pos) => pos,
+                PlayerLocation::InsideMech { pos, .. } => pos.to_local_world(),
+            };
+            let operating = self
+                .stations
+                .values()
+                .any(|station| station.operated_by == Some(*id));
+            self.animations
+                .entry(*id)
+                .or_insert_with(PlayerAnimation::new)
+                .update(position, !player.inventory.is_empty(), operating, delta);
+        }
+    }
+
     pub fn update_vision(&mut self) {
         ClientVisionSystem::force_update(self);
     }
 }
 
+/// The built-in station definitions, or on native builds the file named by
+/// MECH_STATIONS_FILE, to match a server started with `--stations`
+fn load_station_registry() -> StationRegistry {
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(path) = std::env::var_os("MECH_STATIONS_FILE") {
+        match StationRegistry::load(Some(std::path::Path::new(&path))) {
+            Ok(registry) => return registry,
+            Err(e) => error!("{e}\nUsing the built-in station definitions"),
+        }
+    }
+    StationRegistry::new()
+}
+
+/// The built-in spawn tables, or on native builds the file named by
+/// MECH_RESOURCES_FILE, to match a server started with `--resources`
+fn load_spawn_tables() -> SpawnTables {
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(path) = std::env::var_os("MECH_RESOURCES_FILE") {
+        match SpawnTables::load(Some(std::path::Path::new(&path))) {
+            Ok(tables) => return tables,
+            Err(e) => error!("{e}\nUsing the built-in spawn tables"),
+        }
+    }
+    SpawnTables::new()
+}
+
 impl MechFloor {
     pub fn new(level: u8) -> Self {
         // Add ladders
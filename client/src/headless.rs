@@ -0,0 +1,253 @@
+//! The client without a window: networking, prediction and game state, run
+//! on a plain thread so bots and integration tests can play through the
+//! same stack players do, no GPU needed.
+//!
+//! `client --headless` joins like the windowed client does (MECH_ROOM,
+//! MECH_PASSWORD and MECH_SPECTATE still apply), sends every line of JSON
+//! it reads on stdin as a `ClientMessage`, prints a JSON status line every
+//! second and quits when stdin closes. `--channels Snapshots,Match` asks
+//! the server for only those state channels. Rust callers can drive a
+//! `HeadlessClient` themselves or hand `run` a `HeadlessDriver`.
+
+use crate::game_state::GameState;
+use crate::network::NetworkClient;
+use crate::network_trait::NetworkClient as NetworkClientTrait;
+use crate::prediction;
+use macroquad::rand;
+use shared::*;
+use std::io::BufRead;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Decides what a headless client sends
+pub trait HeadlessDriver {
+    /// Messages to send this tick, looking at the state as it stands after
+    /// stepping it by `delta` seconds
+    fn tick(&mut self, game: &GameState, delta: f32) -> Vec<ClientMessage>;
+
+    /// Whether to stop the run
+    fn finished(&self) -> bool {
+        false
+    }
+}
+
+pub struct HeadlessClient {
+    game_state: Arc<Mutex<GameState>>,
+    network: NetworkClient,
+}
+
+impl HeadlessClient {
+    /// Connect to `url` and send `join`, usually a JoinGame or Spectate
+    pub fn connect(url: &str, join: ClientMessage) -> Result<Self, Box<ws::Error>> {
+        let game_state = Arc::new(Mutex::new(GameState::new()));
+        // Nobody's watching, so skip the particles and screen shake
+        game_state.lock().unwrap().hit_feedback_enabled = false;
+        let network = NetworkClient::connect(url, Arc::clone(&game_state)).map_err(Box::new)?;
+        network.send_message(join);
+        Ok(Self {
+            game_state,
+            network,
+        })
+    }
+
+    /// The client's mirror of the game, as the server last described it
+    pub fn state(&self) -> MutexGuard<'_, GameState> {
+        self.game_state.lock().unwrap()
+    }
+
+    /// Whether the server has let us in, to play or to watch
+    pub fn joined(&self) -> bool {
+        let game = self.state();
+        game.player_id.is_some() || game.spectating
+    }
+
+    /// Send a message, predicting its effects locally the way the windowed
+    /// client does for the matching key press
+    pub fn send(&self, msg: ClientMessage) {
+        {
+            let mut game = self.state();
+            match msg {
+                ClientMessage::PlayerInput { movement, .. } => game.face_local_player(movement),
+                ClientMessage::StationInput { button_index } => {
+                    let pressed = game.station_panel.as_mut().and_then(|panel| {
+                        let index = panel
+                            .buttons
+                            .iter()
+                            .position(|button| button.index == button_index)?;
+                        panel.press(index).then_some(panel.station_id)
+                    });
+                    if let Some(station_id) = pressed {
+                        prediction::predict_station_fire(&mut game, station_id, button_index);
+                    }
+                }
+                _ => {}
+            }
+        }
+        self.network.send_message(msg);
+    }
+
+    /// Advance the local simulation between server updates
+    pub fn step(&self, delta: f32) {
+        self.state().update_state(delta);
+    }
+}
+
+/// Step the client at the server's frame rate, sending whatever the driver
+/// asks for once the server has let us in, until it's finished
+pub fn run(client: &HeadlessClient, driver: &mut impl HeadlessDriver) {
+    let frame = Duration::from_millis(FRAME_DURATION_MS);
+    let mut last = Instant::now();
+    while !driver.finished() {
+        thread::sleep(frame.saturating_sub(last.elapsed()));
+        let delta = last.elapsed().as_secs_f32();
+        last = Instant::now();
+
+        client.step(delta);
+        if !client.joined() {
+            continue;
+        }
+        let messages = driver.tick(&client.state(), delta);
+        for msg in messages {
+            client.send(msg);
+        }
+    }
+}
+
+/// Drives the client from stdin and reports on stdout, for bots written in
+/// anything that can spawn a process
+pub struct StdioDriver {
+    lines: Receiver<String>,
+    closed: bool,
+    since_status: f32,
+}
+
+impl StdioDriver {
+    pub fn new() -> Self {
+        let (tx, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in std::io::stdin().lock().lines().map_while(Result::ok) {
+                if tx.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        Self {
+            lines,
+            closed: false,
+            since_status: 0.0,
+        }
+    }
+
+    fn print_status(game: &GameState) {
+        let health = game
+            .player_id
+            .and_then(|player_id| game.players.get(&player_id))
+            .map(|player| player.health);
+        let status = serde_json::json!({
+            "player_id": game.player_id,
+            "team": game.player_team,
+            "location": game.player_location,
+            "health": health,
+            "players": game.players.len(),
+            "mechs": game.mechs.len(),
+            "messages_received": game.messages_received,
+        });
+        println!("{status}");
+    }
+}
+
+impl HeadlessDriver for StdioDriver {
+    fn tick(&mut self, game: &GameState, delta: f32) -> Vec<ClientMessage> {
+        self.since_status += delta;
+        if self.since_status >= HEADLESS_STATUS_INTERVAL {
+            self.since_status = 0.0;
+            Self::print_status(game);
+        }
+
+        let mut messages = Vec::new();
+        loop {
+            match self.lines.try_recv() {
+                Ok(line) if line.trim().is_empty() => {}
+                Ok(line) => match serde_json::from_str::<ClientMessage>(&line) {
+                    Ok(msg) => messages.push(msg),
+                    Err(e) => log::warn!("Ignoring unreadable message '{line}': {e}"),
+                },
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.closed = true;
+                    break;
+                }
+            }
+        }
+        messages
+    }
+
+    fn finished(&self) -> bool {
+        self.closed
+    }
+}
+
+/// `client --headless [--name <name>] [--url <ws url>] [--channels <channel,...>]`
+pub fn main(args: &[String]) {
+    let flag = |name: &str| {
+        args.iter()
+            .position(|arg| arg == name)
+            .and_then(|index| args.get(index + 1))
+    };
+    let url = flag("--url")
+        .cloned()
+        .unwrap_or_else(crate::default_server_url);
+    let player_name = flag("--name").cloned().unwrap_or_else(|| {
+        // Several bots started together shouldn't all get the same name
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        rand::srand(now.as_nanos() as u64);
+        format!(
+            "Headless{}",
+            rand::gen_range(PLAYER_NAME_MIN_ID, PLAYER_NAME_MAX_ID)
+        )
+    });
+    let channels = match flag("--channels").map(|list| parse_channels(list)) {
+        Some(Ok(channels)) => Some(channels),
+        Some(Err(e)) => {
+            log::error!("{e}");
+            std::process::exit(1);
+        }
+        None => None,
+    };
+    let spectate = std::env::var("MECH_SPECTATE").is_ok_and(|v| v == "1");
+    let password = std::env::var("MECH_PASSWORD").ok();
+
+    log::info!("Connecting to {url} as {player_name}");
+    let join = crate::join_message(&player_name, spectate, None, password.as_deref());
+    let client = match HeadlessClient::connect(&url, join) {
+        Ok(client) => client,
+        Err(e) => {
+            log::error!("Failed to connect: {e}");
+            std::process::exit(1);
+        }
+    };
+    if let Some(channels) = channels {
+        client.send(ClientMessage::Subscribe { channels });
+    }
+    run(&client, &mut StdioDriver::new());
+}
+
+/// A comma-separated list of channel names, as `StateChannel` spells them
+fn parse_channels(list: &str) -> Result<Vec<StateChannel>, String> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(|name| {
+            serde_json::from_value(serde_json::Value::String(name.to_string())).map_err(|_| {
+                format!(
+                    "Unknown channel '{name}'; expected some of {:?}",
+                    StateChannel::ALL
+                )
+            })
+        })
+        .collect()
+}
@@ -9,6 +9,17 @@ pub struct InputState {
     pub action_pressed: bool,
     pub exit_mech_pressed: bool,
     pub floor_transition_pressed: bool,
+    pub next_slot_pressed: bool,
+    pub drop_item_pressed: bool,
+    pub use_item_pressed: bool,
+    pub attack_pressed: bool,
+    pub dash_pressed: bool,
+    pub barricade_mode_pressed: bool,
+    /// Left click, which builds while placing barricades
+    pub build_pressed: bool,
+    /// Mouse wheel notches this frame, positive to zoom in
+    pub zoom: f32,
+    pub camera_mode_pressed: bool,
 }
 
 impl InputHandler {
@@ -19,12 +30,7 @@ impl InputHandler {
     }
 
     pub fn update(&mut self) -> InputState {
-        let mut state = InputState {
-            movement: (0.0, 0.0),
-            action_pressed: false,
-            exit_mech_pressed: false,
-            floor_transition_pressed: false,
-        };
+        let mut state = InputState::idle();
 
         // Movement - combine multiple directions for diagonal movement
         let mut movement_x = 0.0;
@@ -63,11 +69,54 @@ impl InputHandler {
         // Floor transition key (E for "Enter" stairway)
         state.floor_transition_pressed = is_key_pressed(KeyCode::E);
 
+        // Inventory: cycle the active slot, drop or use what's in it
+        state.next_slot_pressed = is_key_pressed(KeyCode::Tab);
+        state.drop_item_pressed = is_key_pressed(KeyCode::G);
+        state.use_item_pressed = is_key_pressed(KeyCode::R);
+
+        // Melee attack
+        state.attack_pressed = is_key_pressed(KeyCode::F);
+
+        // Dash the way we're moving, or facing when standing still
+        state.dash_pressed =
+            is_key_pressed(KeyCode::LeftShift) || is_key_pressed(KeyCode::RightShift);
+
+        // Barricades: B toggles placement, clicking builds
+        state.barricade_mode_pressed = is_key_pressed(KeyCode::B);
+        state.build_pressed = is_mouse_button_pressed(MouseButton::Left);
+
+        // Camera: wheel zooms, C switches between following us and our mech.
+        // Wheel deltas differ wildly between platforms, so count notches.
+        let wheel = mouse_wheel().1;
+        if wheel != 0.0 {
+            state.zoom = wheel.signum();
+        }
+        state.camera_mode_pressed = is_key_pressed(KeyCode::C);
+
         state
     }
 }
 
 impl InputState {
+    /// No keys held or pressed
+    pub fn idle() -> Self {
+        Self {
+            movement: (0.0, 0.0),
+            action_pressed: false,
+            exit_mech_pressed: false,
+            floor_transition_pressed: false,
+            next_slot_pressed: false,
+            drop_item_pressed: false,
+            use_item_pressed: false,
+            attack_pressed: false,
+            dash_pressed: false,
+            barricade_mode_pressed: false,
+            build_pressed: false,
+            zoom: 0.0,
+            camera_mode_pressed: false,
+        }
+    }
+
     pub fn has_input(&self) -> bool {
         self.movement.0 != 0.0 || self.movement.1 != 0.0 || self.action_pressed
     }
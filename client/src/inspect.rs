@@ -0,0 +1,300 @@
+//! What the client knows about any one entity, gathered in one place for the
+//! debug overlay's inspector. Every server message that names an entity
+//! stamps it with the message's sequence number, so the inspector can say
+//! how stale its picture is.
+
+use crate::game_state::GameState;
+use serde_json::{json, Value};
+use shared::{
+    types::*, MechInteriorCoordinates, ServerMessage, StationUpdate, MECH_SIZE_TILES, TILE_SIZE,
+};
+use uuid::Uuid;
+
+/// Something in the world the inspector can be pointed at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InspectTarget {
+    Player(Uuid),
+    Mech(Uuid),
+    Station(Uuid),
+    Resource(Uuid),
+}
+
+impl InspectTarget {
+    pub fn id(&self) -> Uuid {
+        match *self {
+            InspectTarget::Player(id)
+            | InspectTarget::Mech(id)
+            | InspectTarget::Station(id)
+            | InspectTarget::Resource(id) => id,
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            InspectTarget::Player(_) => "player",
+            InspectTarget::Mech(_) => "mech",
+            InspectTarget::Station(_) => "station",
+            InspectTarget::Resource(_) => "resource",
+        }
+    }
+}
+
+/// Everything known about one entity: readable rows for the panel and the
+/// same state as JSON for copying out
+pub struct EntityInspection {
+    pub rows: Vec<(&'static str, String)>,
+    pub json: Value,
+    /// Sequence number of the last server message that touched it
+    pub last_update: Option<u64>,
+}
+
+impl GameState {
+    /// The entity under a world position, picking players over stations
+    /// over resources over mechs since they're drawn in that order on top
+    pub fn entity_at(&self, world: WorldPos) -> Option<InspectTarget> {
+        let tile = world.to_tile();
+        let reach = TILE_SIZE / 2.0;
+
+        let player = self.players.iter().find(|(_, player)| {
+            self.player_world_pos(player.location)
+                .is_some_and(|pos| pos.distance_to(world) <= reach)
+        });
+        if let Some((id, _)) = player {
+            return Some(InspectTarget::Player(*id));
+        }
+
+        let station = self.stations.iter().find(|(_, station)| {
+            self.mechs.get(&station.mech_id).is_some_and(|mech| {
+                MechInteriorCoordinates::interior_to_world(
+                    mech.position,
+                    station.floor,
+                    station.position,
+                ) == tile
+            })
+        });
+        if let Some((id, _)) = station {
+            return Some(InspectTarget::Station(*id));
+        }
+
+        if let Some(resource) = self.resources.iter().find(|r| r.position == tile) {
+            return Some(InspectTarget::Resource(resource.id));
+        }
+
+        self.mechs
+            .values()
+            .find(|mech| {
+                let offset = (tile.x - mech.position.x, tile.y - mech.position.y);
+                (0..MECH_SIZE_TILES).contains(&offset.0) && (0..MECH_SIZE_TILES).contains(&offset.1)
+            })
+            .map(|mech| InspectTarget::Mech(mech.id))
+    }
+
+    /// Full known state of an entity, or None once it's gone
+    pub fn inspect(&self, target: InspectTarget) -> Option<EntityInspection> {
+        let id = target.id();
+        let (rows, json) = match target {
+            InspectTarget::Player(_) => {
+                let player = self.players.get(&id)?;
+                let world = self.player_world_pos(player.location);
+                let mut rows = vec![
+                    ("name", player.label()),
+                    ("team", format!("{:?}", player.team)),
+                    ("health", player.health.to_string()),
+                    ("facing", format!("{:?}", player.facing)),
+                    ("held tool", format!("{:?}", player.held_tool)),
+                    ("location", format!("{:?}", player.location)),
+                ];
+                rows.extend(self.position_rows(world));
+                let json = json!({
+                    "name": player.name,
+                    "team": player.team,
+                    "health": player.health,
+                    "facing": player.facing,
+                    "held_tool": player.held_tool,
+                    "location": player.location,
+                    "inventory": player.inventory,
+                    "afk": player.afk,
+                    "world_position": world,
+                });
+                (rows, json)
+            }
+            InspectTarget::Mech(_) => {
+                let mech = self.mechs.get(&id)?;
+                let mut rows = vec![
+                    ("team", format!("{:?}", mech.team)),
+                    ("health", format!("{}/{}", mech.health, mech.max_health)),
+                    ("shield", mech.shield.to_string()),
+                    ("fuel", format!("{:.1}", mech.fuel)),
+                    ("tile", format!("{:?}", mech.position)),
+                ];
+                rows.extend(self.position_rows(Some(mech.world_position)));
+                let json = json!({
+                    "team": mech.team,
+                    "health": mech.health,
+                    "max_health": mech.max_health,
+                    "shield": mech.shield,
+                    "fuel": mech.fuel,
+                    "tile": mech.position,
+                    "world_position": mech.world_position,
+                    "upgrades": mech.upgrades,
+                    "drive": mech.drive,
+                    "turret": mech.turret,
+                    "point_defense": mech.point_defense,
+                    "resource_inventory": mech.resource_inventory,
+                    "delivery_score": mech.delivery_score,
+                });
+                (rows, json)
+            }
+            InspectTarget::Station(_) => {
+                let station = self.stations.get(&id)?;
+                let world_tile = self.mechs.get(&station.mech_id).map(|mech| {
+                    MechInteriorCoordinates::interior_to_world(
+                        mech.position,
+                        station.floor,
+                        station.position,
+                    )
+                });
+                let mut rows = vec![
+                    ("type", format!("{:?}", station.station_type)),
+                    ("mech", station.mech_id.to_string()),
+                    ("floor", station.floor.to_string()),
+                    ("interior tile", format!("{:?}", station.position)),
+                    ("operated by", format!("{:?}", station.operated_by)),
+                    (
+                        "health",
+                        format!("{}/{}", station.health, station.max_health),
+                    ),
+                    ("tier", station.upgrade_level.to_string()),
+                ];
+                rows.extend(self.position_rows(world_tile.map(|tile| tile.to_world_center())));
+                let json = json!({
+                    "station_type": station.station_type,
+                    "mech_id": station.mech_id,
+                    "floor": station.floor,
+                    "interior_tile": station.position,
+                    "world_tile": world_tile,
+                    "operated_by": station.operated_by,
+                    "health": station.health,
+                    "max_health": station.max_health,
+                    "upgrade_level": station.upgrade_level,
+                });
+                (rows, json)
+            }
+            InspectTarget::Resource(_) => {
+                let resource = self.resources.iter().find(|r| r.id == id)?;
+                let mut rows = vec![
+                    ("type", format!("{:?}", resource.resource_type)),
+                    ("tile", format!("{:?}", resource.position)),
+                ];
+                rows.extend(self.position_rows(Some(resource.position.to_world_center())));
+                let json = json!({
+                    "resource_type": resource.resource_type,
+                    "tile": resource.position,
+                });
+                (rows, json)
+            }
+        };
+
+        let last_update = self.entity_updates.get(&id).copied();
+        let mut json = json;
+        json["kind"] = json!(target.kind());
+        json["id"] = json!(id);
+        json["last_update"] = json!(last_update);
+        Some(EntityInspection {
+            rows,
+            json,
+            last_update,
+        })
+    }
+
+    /// Stamp every entity a server message names with its sequence number
+    pub fn record_entity_updates(&mut self, msg: &ServerMessage) {
+        self.messages_received += 1;
+        for id in touched_entities(msg) {
+            self.entity_updates.insert(id, self.messages_received);
+        }
+    }
+
+    /// Where a player stands in the world, or None if their mech is unknown
+    pub fn player_world_pos(&self, location: PlayerLocation) -> Option<WorldPos> {
+        match location {
+            PlayerLocation::OutsideWorld(pos) => Some(pos),
+            PlayerLocation::InsideMech { mech_id, pos } => {
+                let mech = self.mechs.get(&mech_id)?;
+                let tile = MechInteriorCoordinates::interior_to_world(
+                    mech.position,
+                    pos.floor(),
+                    pos.tile_pos(),
+                );
+                Some(tile.to_world_center())
+            }
+        }
+    }
+
+    /// A world position in every space the renderer works in
+    fn position_rows(&self, world: Option<WorldPos>) -> Vec<(&'static str, String)> {
+        let Some(world) = world else {
+            return Vec::new();
+        };
+        let screen = self.camera.view().world_to_screen(world);
+        vec![
+            ("world", format!("({:.1}, {:.1})", world.x, world.y)),
+            ("world tile", format!("{:?}", world.to_tile())),
+            ("screen", format!("({:.0}, {:.0})", screen.x, screen.y)),
+        ]
+    }
+}
+
+/// Entities a message carries state for
+fn touched_entities(msg: &ServerMessage) -> Vec<Uuid> {
+    match msg {
+        ServerMessage::GameState {
+            players,
+            mechs,
+            resources,
+            ..
+        } => players
+            .keys()
+            .chain(mechs.keys())
+            .copied()
+            .chain(
+                mechs
+                    .values()
+                    .flat_map(|mech| mech.stations.iter().map(|s| s.id)),
+            )
+            .chain(resources.iter().map(|r| r.id))
+            .collect(),
+        ServerMessage::PlayerMoved { player_id, .. }
+        | ServerMessage::PlayerFacing { player_id, .. }
+        | ServerMessage::PlayerAfk { player_id, .. }
+        | ServerMessage::InventoryUpdated { player_id, .. } => vec![*player_id],
+        ServerMessage::PlayerDashed { player_id, .. } => vec![*player_id],
+        ServerMessage::PlayerEnteredStation {
+            player_id,
+            station_id,
+        }
+        | ServerMessage::PlayerExitedStation {
+            player_id,
+            station_id,
+        } => vec![*player_id, *station_id],
+        ServerMessage::MechMoved { mech_id, .. }
+        | ServerMessage::MechDamaged { mech_id, .. }
+        | ServerMessage::MechShieldChanged { mech_id, .. }
+        | ServerMessage::MechFuelChanged { mech_id, .. }
+        | ServerMessage::MechUpgraded { mech_id, .. } => vec![*mech_id],
+        ServerMessage::MechInteriorUpdate {
+            station_changes, ..
+        } => station_changes
+            .iter()
+            .map(|change| match change {
+                StationUpdate::Damaged { station_id, .. }
+                | StationUpdate::Repaired { station_id, .. }
+                | StationUpdate::Upgraded { station_id, .. }
+                | StationUpdate::StatusChanged { station_id, .. } => *station_id,
+            })
+            .collect(),
+        ServerMessage::ResourceCollected { resource_id, .. }
+        | ServerMessage::ResourceDestroyed { resource_id } => vec![*resource_id],
+        _ => Vec::new(),
+    }
+}
@@ -1,4 +1,5 @@
 use macroquad::prelude::*;
+use std::f32::consts::{PI, TAU};
 use std::sync::{Arc, Mutex};
 
 use shared::*;
@@ -11,29 +12,51 @@ use wee_alloc;
 #[global_allocator]
 static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
 
+mod accessibility;
+mod animation;
+mod annotation_tools;
+mod capture;
+mod console;
 mod debug_overlay;
 mod floor_manager;
 mod game_state;
 mod input;
+mod inspect;
+mod particles;
+mod password_prompt;
+mod prediction;
+mod quick_match;
+mod reconnect;
 mod rendering;
+mod server_browser;
 mod settings;
 mod spatial_testing;
 mod tracing_profiler;
 mod vision;
+mod voice;
 
 mod network_common;
 mod network_trait;
 
+#[cfg(not(target_arch = "wasm32"))]
+mod headless;
 #[cfg(not(target_arch = "wasm32"))]
 mod network;
 #[cfg(target_arch = "wasm32")]
 mod network_web;
 
+use annotation_tools::AnnotationTools;
+use console::{ConsoleAction, DevConsole};
 use debug_overlay::DebugOverlay;
-use game_state::GameState;
-use input::InputHandler;
-use rendering::{RenderFlags, Renderer};
+use game_state::{BarricadePreview, GameState};
+use input::{InputHandler, InputState};
+use password_prompt::PasswordPrompt;
+use quick_match::QuickMatch;
+use reconnect::Reconnect;
+use rendering::{camera::FollowTarget, RenderFlags, Renderer};
+use server_browser::{ServerBrowser, SERVER_BROWSER_KEY};
 use tracing_profiler::TracingProfiler;
+use voice::{VoiceChat, PUSH_TO_TALK_KEY};
 
 #[cfg(feature = "profiling")]
 use profiling::scope;
@@ -50,8 +73,21 @@ use network::NetworkClient;
 #[cfg(target_arch = "wasm32")]
 use network_web::NetworkClient;
 
-#[macroquad::main("Mech Battle Arena")]
-async fn main() {
+fn main() {
+    // No window, for bots and tests: client --headless
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let args: Vec<String> = std::env::args().collect();
+        if args.iter().any(|arg| arg == "--headless") {
+            env_logger::init();
+            headless::main(&args);
+            return;
+        }
+    }
+    macroquad::Window::new("Mech Battle Arena", run());
+}
+
+async fn run() {
     // Initialize logging
     #[cfg(not(target_arch = "wasm32"))]
     env_logger::init();
@@ -65,15 +101,23 @@ async fn main() {
     // Initialize game state
     let game_state = Arc::new(Mutex::new(GameState::new()));
     let mut renderer = Renderer::new();
+    renderer.load_sprites().await;
     let mut input_handler = InputHandler::new();
     let mut profiler = TracingProfiler::new();
     let mut debug_overlay = DebugOverlay::new();
+    let mut dev_console = DevConsole::new();
     let mut spatial_test_suite = spatial_testing::SpatialTestSuite::new();
+    let mut capture = capture::Capture::new();
+    let mut server_browser = ServerBrowser::new();
+    let mut quick_match = QuickMatch::new();
 
     info!("Game state initialized");
 
     // Initialize network client
     let mut network_client: Option<NetworkClient>;
+    // Where the game socket points; the voice socket opens next to it
+    let mut server_url = default_server_url();
+    let mut voice_chat = VoiceChat::new();
 
     #[cfg(not(target_arch = "wasm32"))]
     {
@@ -84,8 +128,8 @@ async fn main() {
         let game_clone = Arc::clone(&game_state);
 
         // Connect to server in separate thread
+        let server_url = server_url.clone();
         thread::spawn(move || {
-            let server_url = format!("ws://127.0.0.1:{}/ws", SERVER_PORT);
             info!("Connecting to {}", server_url);
 
             match NetworkClientTrait::connect(&server_url, game_clone) {
@@ -108,8 +152,6 @@ async fn main() {
     #[cfg(target_arch = "wasm32")]
     {
         // For WASM, connect to localhost for development
-        let server_url = format!("ws://127.0.0.1:{}/ws", SERVER_PORT);
-
         info!("Connecting to {}", server_url);
 
         network_client = match NetworkClientTrait::connect(&server_url, Arc::clone(&game_state)) {
@@ -140,19 +182,46 @@ async fn main() {
         connection_wait += 1;
     }
 
-    // Send join request
+    // Generate a random player name for demo
+    let player_name = format!(
+        "Player{}",
+        rand::gen_range(PLAYER_NAME_MIN_ID, PLAYER_NAME_MAX_ID)
+    );
+
+    // Send join request, or watch instead: MECH_SPECTATE=1
+    #[cfg(not(target_arch = "wasm32"))]
+    let spectate = std::env::var("MECH_SPECTATE").is_ok_and(|v| v == "1");
+    #[cfg(target_arch = "wasm32")]
+    let spectate = false;
+    // Password for a locked room: MECH_PASSWORD=<secret>
+    #[cfg(not(target_arch = "wasm32"))]
+    let mut room_password = std::env::var("MECH_PASSWORD").ok();
+    #[cfg(target_arch = "wasm32")]
+    let mut room_password: Option<String> = None;
+    let mut password_prompt = PasswordPrompt::new();
+    let mut annotation_tools = AnnotationTools::new();
+    // Team matchmaking picked for the room we're in, asked for on joining
+    let mut preferred_team: Option<TeamId> = None;
+    // Whether we've asked the server for its collision shapes
+    let mut collision_shapes_requested = false;
     if let Some(ref client) = network_client {
-        // Generate a random player name for demo
-        let player_name = format!(
-            "Player{}",
-            rand::gen_range(PLAYER_NAME_MIN_ID, PLAYER_NAME_MAX_ID)
-        );
-        client.send_message(ClientMessage::JoinGame {
-            player_name,
-            preferred_team: None,
-        });
+        client.send_message(join_message(
+            &player_name,
+            spectate,
+            preferred_team,
+            room_password.as_deref(),
+        ));
     }
 
+    // Connection requested from the console, filled in by a background thread
+    #[cfg(not(target_arch = "wasm32"))]
+    let pending_connection: Arc<Mutex<Option<NetworkClient>>> = Arc::new(Mutex::new(None));
+
+    // Notices the server going away and counts down to getting back in,
+    // with the token the server gave us for taking our player back
+    let mut reconnect = Reconnect::new(get_time());
+    let mut resume_token: Option<uuid::Uuid> = None;
+
     info!("Starting main game loop with profiling enabled");
 
     loop {
@@ -168,15 +237,218 @@ async fn main() {
 
         // Spatial testing is now controlled through the debug overlay UI (Spatial tab)
 
-        // Handle input
+        // Developer console, servers picked in the browser and rooms
+        // matchmaking put us in
+        server_browser.update();
+        quick_match.update(&server_url);
+        if !dev_console.is_open() && is_key_pressed(SERVER_BROWSER_KEY) {
+            server_browser.toggle();
+        }
+        let mut matched_team = None;
+        let mut resuming = false;
+        let action = dev_console
+            .update()
+            .or_else(|| server_browser.take_connect().map(ConsoleAction::Connect))
+            .or_else(|| {
+                quick_match.take_match().map(|(url, team)| {
+                    matched_team = Some(team);
+                    ConsoleAction::Connect(url)
+                })
+            })
+            .or_else(|| {
+                // Back to the same server, as the same player if it kept us
+                reconnect.due(get_time()).then(|| {
+                    resuming = true;
+                    ConsoleAction::Connect(server_url.clone())
+                })
+            });
+        if let Some(action) = action {
+            match action {
+                ConsoleAction::Connect(url) => {
+                    dev_console.print(format!("Connecting to {url}..."));
+                    preferred_team = matched_team;
+                    if let Some(password) = server_browser.password() {
+                        room_password = Some(password);
+                    }
+                    // Close the old socket before the new one joins
+                    network_client = None;
+                    voice_chat.disconnect();
+                    server_url = url.clone();
+                    game_state.lock().unwrap().reset_keeping_view();
+                    reconnect.connecting(get_time());
+                    if !resuming {
+                        resume_token = None;
+                    }
+                    let url = match resume_token {
+                        Some(token) => resume_ws_url(&url, token),
+                        None => url,
+                    };
+
+                    #[cfg(not(target_arch = "wasm32"))]
+                    {
+                        let pending = Arc::clone(&pending_connection);
+                        let game_clone = Arc::clone(&game_state);
+                        std::thread::spawn(move || {
+                            match NetworkClientTrait::connect(&url, game_clone) {
+                                Ok(client) => *pending.lock().unwrap() = Some(client),
+                                Err(e) => error!("Failed to connect to {}: {}", url, e),
+                            }
+                        });
+                    }
+
+                    #[cfg(target_arch = "wasm32")]
+                    match NetworkClientTrait::connect(&url, Arc::clone(&game_state)) {
+                        Ok(client) => network_client = Some(client),
+                        Err(e) => dev_console.print(format!("Failed to connect: {e:?}")),
+                    }
+                }
+                ConsoleAction::Server(command) => match network_client {
+                    Some(ref client) => client.send_message(ClientMessage::DevCommand { command }),
+                    None => dev_console.print("Not connected to a server"),
+                },
+                ConsoleAction::Toggle(flag) => {
+                    if let Some(value) = debug_overlay.toggle_flag(flag) {
+                        dev_console.print(format!("{flag} = {value}"));
+                    }
+                }
+                ConsoleAction::SetUiScale(scale) => {
+                    debug_overlay.set_ui_scale(scale);
+                    dev_console.print(format!("ui_scale = {}", debug_overlay.ui_scale));
+                }
+                ConsoleAction::Follow(None) => {
+                    game_state.lock().unwrap().camera.follow = FollowTarget::Player;
+                    dev_console.print("Following you");
+                }
+                ConsoleAction::Follow(Some(name)) => {
+                    let mut game = game_state.lock().unwrap();
+                    // Picking someone to watch overrides the director
+                    game.director_auto = false;
+                    let player_id = game
+                        .players
+                        .iter()
+                        .find(|(_, player)| player.name.eq_ignore_ascii_case(&name))
+                        .map(|(id, _)| *id);
+                    match player_id {
+                        Some(player_id) => {
+                            game.camera.follow = FollowTarget::Entity(player_id);
+                            dev_console.print(format!("Following {name}"));
+                        }
+                        None => dev_console.print(format!("No player named '{name}'")),
+                    }
+                }
+                ConsoleAction::ServerBrowser => {
+                    if !server_browser.is_open() {
+                        server_browser.toggle();
+                    }
+                }
+                ConsoleAction::QuickMatch => quick_match.start(&server_url, &player_name),
+                ConsoleAction::Director => {
+                    let mut game = game_state.lock().unwrap();
+                    if game.spectating {
+                        game.follow_director();
+                        dev_console.print("Following the director");
+                    } else {
+                        dev_console.print("Only spectators have a director");
+                    }
+                }
+                ConsoleAction::Desync { dump } => {
+                    let game = game_state.lock().unwrap();
+                    match &game.state_check {
+                        Some(check) if check.mismatched.is_empty() => dev_console.print(format!(
+                            "In sync at tick {} ({:016x}), {} desyncs so far",
+                            check.tick,
+                            check.ours.combined(),
+                            check.desyncs
+                        )),
+                        Some(check) => dev_console.print(format!(
+                            "Out of sync at tick {}: {} differ, {} desyncs so far",
+                            check.tick,
+                            check.mismatched.join(", "),
+                            check.desyncs
+                        )),
+                        None => dev_console
+                            .print("No state checksums yet; run the server with --state-checksums"),
+                    }
+                    if dump {
+                        // Same shape as /debug/state-hash?entities=true, to diff against
+                        let dump = game.state_dump();
+                        match serde_json::to_string(&dump) {
+                            Ok(json) => info!("State dump: {json}"),
+                            Err(e) => error!("Failed to serialize state dump: {e}"),
+                        }
+                        dev_console.print(format!(
+                            "Logged hashes of {} players, {} mechs and {} resources",
+                            dump.players.len(),
+                            dump.mechs.len(),
+                            dump.resources.len()
+                        ));
+                    }
+                }
+            }
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(client) = pending_connection.lock().unwrap().take() {
+            client.send_message(join_message(
+                &player_name,
+                spectate,
+                preferred_team,
+                room_password.as_deref(),
+            ));
+            network_client = Some(client);
+            collision_shapes_requested = false;
+            dev_console.print("Connected");
+        }
+
+        // Freeze the game behind the reconnect overlay once the server's gone
+        let closed = network_client
+            .as_ref()
+            .is_some_and(|client| client.is_closed());
+        let messages_received = {
+            let mut game = game_state.lock().unwrap();
+            if let Some(token) = game.resume_token.take() {
+                resume_token = Some(token);
+            }
+            game.messages_received
+        };
+        if reconnect.watch(get_time(), messages_received, closed) {
+            dev_console.print("Lost the connection to the server");
+            voice_chat.disconnect();
+        }
+        let connection_lost = reconnect.is_lost();
+
+        for line in std::mem::take(&mut game_state.lock().unwrap().console_messages) {
+            dev_console.print(line);
+        }
+
+        // Handle input; the console swallows the keyboard while it's open
+        let console_open = dev_console.is_open();
         let input = {
             #[cfg(feature = "profiling")]
             let _input_span = info_span!("input").entered();
             #[cfg(feature = "profiling")]
             scope!("input");
-            input_handler.update()
+            if console_open {
+                InputState::idle()
+            } else {
+                input_handler.update()
+            }
         };
 
+        // Camera controls don't need the server
+        if input.zoom != 0.0 || input.camera_mode_pressed {
+            let mut game = game_state.lock().unwrap();
+            game.camera.zoom_by(input.zoom);
+            if input.camera_mode_pressed {
+                let mode = if game.spectating {
+                    game.cycle_spectator_camera()
+                } else {
+                    game.cycle_camera_follow().to_string()
+                };
+                game.push_toast("Camera".to_string(), mode);
+            }
+        }
+
         // Send input to server
         {
             #[cfg(feature = "profiling")]
@@ -184,7 +456,13 @@ async fn main() {
             #[cfg(feature = "profiling")]
             scope!("network");
 
-            if let Some(ref client) = network_client {
+            // Spectators have nobody to move, and nobody moves while the
+            // connection is lost
+            let spectating = game_state.lock().unwrap().spectating;
+            if let Some(client) = network_client
+                .as_ref()
+                .filter(|_| !spectating && !connection_lost)
+            {
                 // Check if we're operating a station
                 let (operating_engine, operating_pilot) = {
                     let game = game_state.lock().unwrap();
@@ -213,17 +491,89 @@ async fn main() {
                 } else {
                     // Normal player movement
                     if input.has_input() {
+                        game_state.lock().unwrap().face_local_player(input.movement);
                         client.send_message(ClientMessage::PlayerInput {
                             movement: input.movement,
                             action_key_pressed: input.action_pressed,
                         });
                     }
+                    // Outside, pointing the mouse turns us too
+                    if !console_open && mouse_delta_position() != Vec2::ZERO {
+                        let mut game = game_state.lock().unwrap();
+                        let (mouse_x, mouse_y) = mouse_position();
+                        let target = game.camera.screen_to_world(mouse_x, mouse_y);
+                        if let Some(facing) = game.look_at(target) {
+                            client.send_message(ClientMessage::Look { facing });
+                        }
+                    }
                 }
 
                 if input.exit_mech_pressed {
                     client.send_message(ClientMessage::ExitMech);
                 }
 
+                if input.attack_pressed {
+                    client.send_message(ClientMessage::MeleeAttack);
+                }
+
+                if input.dash_pressed && !operating_engine && !operating_pilot {
+                    let facing = {
+                        let game = game_state.lock().unwrap();
+                        game.player_id
+                            .and_then(|player_id| game.players.get(&player_id))
+                            .map(|player| player.facing)
+                    };
+                    if let Some(direction) = Facing::from_movement(input.movement).or(facing) {
+                        client.send_message(ClientMessage::Dash { direction });
+                    }
+                }
+
+                if input.barricade_mode_pressed {
+                    let mut game = game_state.lock().unwrap();
+                    game.barricade_placement =
+                        !game.barricade_placement && game.barricade_target().is_some();
+                }
+                if input.build_pressed {
+                    let target = {
+                        let game = game_state.lock().unwrap();
+                        game.barricade_target().filter(|_| game.barricade_placement)
+                    };
+                    match target {
+                        Some((_, pos, BarricadePreview::Buildable)) => {
+                            client.send_message(ClientMessage::PlaceBarricade {
+                                position: pos.tile_pos,
+                            });
+                        }
+                        Some((_, pos, BarricadePreview::Ours)) => {
+                            client.send_message(ClientMessage::RemoveBarricade {
+                                position: pos.tile_pos,
+                            });
+                        }
+                        _ => {}
+                    }
+                }
+
+                if input.next_slot_pressed {
+                    let next_slot = {
+                        let game = game_state.lock().unwrap();
+                        // At a station Tab walks its buttons instead
+                        game.player_id
+                            .filter(|_| game.station_panel.is_none())
+                            .and_then(|player_id| game.players.get(&player_id))
+                            .map(|player| (player.inventory.active_slot() + 1) % INVENTORY_SLOTS)
+                    };
+                    if let Some(slot) = next_slot {
+                        client
+                            .send_message(ClientMessage::SelectInventorySlot { slot: slot as u8 });
+                    }
+                }
+                if input.drop_item_pressed {
+                    client.send_message(ClientMessage::DropItem);
+                }
+                if input.use_item_pressed {
+                    client.send_message(ClientMessage::UseItem);
+                }
+
                 // Handle floor transitions when standing on stairway tiles
                 if input.floor_transition_pressed {
                     let game = game_state.lock().unwrap();
@@ -250,23 +600,125 @@ async fn main() {
                     }
                 }
 
-                // Handle station input (number keys 1-5)
-                for i in 1..=5 {
-                    let key = match i {
-                        1 => KeyCode::Key1,
-                        2 => KeyCode::Key2,
-                        3 => KeyCode::Key3,
-                        4 => KeyCode::Key4,
-                        5 => KeyCode::Key5,
-                        _ => continue,
-                    };
+                // Handle station input: one number key per button the station offers
+                const BUTTON_KEYS: [KeyCode; 9] = [
+                    KeyCode::Key1,
+                    KeyCode::Key2,
+                    KeyCode::Key3,
+                    KeyCode::Key4,
+                    KeyCode::Key5,
+                    KeyCode::Key6,
+                    KeyCode::Key7,
+                    KeyCode::Key8,
+                    KeyCode::Key9,
+                ];
+                let mut pressed = Vec::new();
+                {
+                    let mut game = game_state.lock().unwrap();
+                    let mut predicted = Vec::new();
+                    if let Some(panel) = game.station_panel.as_mut().filter(|_| !console_open) {
+                        // Tab and Shift+Tab walk the buttons, Enter presses the focused one
+                        if is_key_pressed(KeyCode::Tab) {
+                            panel.move_focus(
+                                !is_key_down(KeyCode::LeftShift)
+                                    && !is_key_down(KeyCode::RightShift),
+                            );
+                        }
+                        let focused = is_key_pressed(KeyCode::Enter).then_some(panel.focus);
+                        let button_count = panel.buttons.len().min(BUTTON_KEYS.len());
+                        let keyed = BUTTON_KEYS
+                            .iter()
+                            .enumerate()
+                            .take(button_count)
+                            .filter(|(_, key)| is_key_pressed(**key))
+                            .map(|(index, _)| index);
+                        for index in keyed.chain(focused).collect::<Vec<_>>() {
+                            let Some(button_index) = panel.buttons.get(index).map(|b| b.index)
+                            else {
+                                continue;
+                            };
+                            panel.focus = index;
+                            if panel.press(index) {
+                                predicted.push((panel.station_id, button_index));
+                            }
+                            pressed.push(button_index);
+                        }
+                    }
+                    // Show the shot now rather than after the server round trip
+                    for (station_id, button_index) in predicted {
+                        prediction::predict_station_fire(&mut game, station_id, button_index);
+                    }
+                }
+                for button_index in pressed {
+                    client.send_message(ClientMessage::StationInput { button_index });
+                }
+
+                // In the hangar the first number keys pick a role, pressing
+                // ours again drops it, and Enter readies up or stands down
+                let mut hangar_messages = Vec::new();
+                {
+                    let game = game_state.lock().unwrap();
+                    let ours = game
+                        .hangar
+                        .as_ref()
+                        .filter(|_| !console_open)
+                        .and_then(|hangar| hangar.ours(game.player_id));
+                    if let Some(ours) = ours {
+                        for (key, role) in BUTTON_KEYS.iter().zip(CrewRole::ALL) {
+                            if is_key_pressed(*key) {
+                                let role = (ours.role != Some(role)).then_some(role);
+                                hangar_messages.push(ClientMessage::PickRole { role });
+                            }
+                        }
+                        if is_key_pressed(KeyCode::Enter) {
+                            hangar_messages.push(ClientMessage::SetReady { ready: !ours.ready });
+                        }
+                    }
+                }
+                for message in hangar_messages {
+                    client.send_message(message);
+                }
 
-                    if is_key_pressed(key) {
-                        client.send_message(ClientMessage::StationInput {
-                            button_index: i - 1,
+                // Turret operators aim with the mouse and click to fire
+                let mut turret_messages = Vec::new();
+                {
+                    let mut game = game_state.lock().unwrap();
+                    let aim = game
+                        .operated_turret_mech()
+                        .and_then(|mech_id| game.mechs.get(&mech_id))
+                        .map(|mech| {
+                            let (mouse_x, mouse_y) = mouse_position();
+                            MechTurret::angle_between(
+                                MechPositioning::mech_center(mech.position),
+                                game.camera.screen_to_world(mouse_x, mouse_y),
+                            )
                         });
+                    game.ui_state.turret_aim = aim;
+
+                    if let Some(angle) = aim.filter(|_| !console_open) {
+                        let drifted = game.ui_state.turret_aim_sent.is_none_or(|sent| {
+                            let error = (angle - sent + PI).rem_euclid(TAU) - PI;
+                            error.abs() > TURRET_AIM_RESEND_ANGLE
+                        });
+                        if drifted {
+                            game.ui_state.turret_aim_sent = Some(angle);
+                            turret_messages.push(ClientMessage::TurretAim { angle });
+                        }
+                        if is_mouse_button_pressed(MouseButton::Left)
+                            && game
+                                .station_panel
+                                .as_mut()
+                                .is_some_and(|panel| panel.press(0))
+                        {
+                            turret_messages.push(ClientMessage::StationInput { button_index: 0 });
+                        }
+                    } else if aim.is_none() {
+                        game.ui_state.turret_aim_sent = None;
                     }
                 }
+                for message in turret_messages {
+                    client.send_message(message);
+                }
             }
 
             // Handle pilot window interactions
@@ -274,7 +726,7 @@ async fn main() {
                 let mut game = game_state.lock().unwrap();
 
                 // Handle ESC key to close pilot window
-                if is_key_pressed(KeyCode::Escape) && game.ui_state.pilot_station_open {
+                if is_key_pressed(KeyCode::Escape) && game.ui_state.pilot_station_open && !console_open {
                     game.ui_state.pilot_station_open = false;
                     game.ui_state.pilot_station_id = None;
                     game.ui_state.operating_mech_id = None;
@@ -304,6 +756,34 @@ async fn main() {
                 }
             }
 
+            if let Some(ref client) = network_client {
+                client.set_net_conditions(debug_overlay.net_conditions);
+
+                // Only have the server send collision shapes while they're drawn
+                let wants_shapes = debug_overlay.wants_collision_shapes();
+                if wants_shapes != collision_shapes_requested {
+                    client.send_message(ClientMessage::VerboseState {
+                        enabled: wants_shapes,
+                    });
+                    collision_shapes_requested = wants_shapes;
+                    if !wants_shapes {
+                        game_state.lock().unwrap().collision_shapes = None;
+                    }
+                }
+            }
+
+            // The join handed us a key for the voice socket
+            if let Some(key) = game_state.lock().unwrap().voice_key.take() {
+                voice_chat.connect(&server_url, key);
+            }
+            {
+                let talk_held = !console_open && is_key_down(PUSH_TO_TALK_KEY);
+                let mut game = game_state.lock().unwrap();
+                voice_chat.update(&debug_overlay.voice, talk_held, &game, get_time());
+                game.voice_speakers = voice_chat.speakers(&game);
+                game.voice_muted = debug_overlay.voice.muted;
+            }
+
             // Update network client (for web)
             #[cfg(target_arch = "wasm32")]
             if let Some(ref mut client) = network_client {
@@ -340,7 +820,14 @@ async fn main() {
             scope!("game_update");
 
             let mut game = game_state.lock().unwrap();
-            game.update(get_frame_time());
+            game.hit_feedback_enabled = debug_overlay.render_hit_feedback;
+            game.camera.smoothing = debug_overlay.camera_smoothing;
+            game.particles.set_quality(debug_overlay.effects_quality);
+            game.accessibility = debug_overlay.accessibility;
+            game.ui_scale = debug_overlay.ui_scale;
+            if !connection_lost {
+                game.update(get_frame_time());
+            }
         }
 
         // Update debug overlay
@@ -355,11 +842,35 @@ async fn main() {
             spatial_test_suite.auto_record_if_testing(&game);
         }
 
-        #[cfg(debug_assertions)]
+        let mut entered_password = None;
+        let mut annotation = None;
         egui_macroquad::ui(|egui_ctx| {
-            let game = game_state.lock().unwrap();
+            let mut game = game_state.lock().unwrap();
             debug_overlay.render_ui(egui_ctx, &game, &mut spatial_test_suite);
+            server_browser.render_ui(egui_ctx);
+            quick_match.render_ui(egui_ctx);
+            reconnect.render_ui(egui_ctx, get_time());
+            if let Some(wrong) = game.password_required {
+                entered_password = password_prompt.render_ui(egui_ctx, wrong);
+            }
+            annotation = annotation_tools.render_ui(egui_ctx, &mut game);
         });
+        if let (Some(annotation), Some(client)) = (annotation, network_client.as_ref()) {
+            client.send_message(annotation);
+        }
+        // Try the join again with the password the player typed
+        if let Some(password) = entered_password {
+            game_state.lock().unwrap().password_required = None;
+            if let Some(ref client) = network_client {
+                client.send_message(join_message(
+                    &player_name,
+                    spectate,
+                    preferred_team,
+                    Some(&password),
+                ));
+            }
+            room_password = Some(password);
+        }
 
         // Render
         {
@@ -369,6 +880,12 @@ async fn main() {
             scope!("render");
 
             clear_background(BLACK);
+            renderer.update_render_scale(
+                &debug_overlay.graphics,
+                debug_overlay.smoothed_fps(),
+                get_frame_time(),
+            );
+            debug_overlay.render_scale_now = renderer.render_scale();
             {
                 let game = game_state.lock().unwrap();
                 let render_flags = RenderFlags {
@@ -379,6 +896,7 @@ async fn main() {
                     render_effects: debug_overlay.render_effects,
                     render_ui: debug_overlay.render_ui,
                     render_fog: debug_overlay.render_fog,
+                    render_lighting: debug_overlay.render_lighting,
                     render_tiles: debug_overlay.render_tiles,
                     render_stations: debug_overlay.render_stations,
 
@@ -388,13 +906,22 @@ async fn main() {
                     show_door_positions: debug_overlay.show_door_positions,
                     show_coordinate_grid: debug_overlay.show_coordinate_grid,
                     show_floor_offsets: debug_overlay.show_floor_offsets,
+                    show_hitboxes: debug_overlay.show_hitboxes,
                 };
                 renderer.render_with_flags(&game, &render_flags);
             }
         }
 
         // Draw connection status
-        if network_client.is_none() {
+        if connection_lost {
+            draw_rectangle(
+                0.0,
+                0.0,
+                screen_width(),
+                screen_height(),
+                macroquad::color::Color::new(0.0, 0.0, 0.0, CONNECTION_LOST_DIM_ALPHA),
+            );
+        } else if network_client.is_none() {
             draw_text(
                 "Connecting to server...",
                 CONNECTION_MESSAGE_X,
@@ -404,6 +931,8 @@ async fn main() {
             );
         }
 
+        dev_console.draw(debug_overlay.ui_scale);
+
         // Render profiler UI overlay
         profiler.render_ui();
 
@@ -414,6 +943,44 @@ async fn main() {
 
         egui_macroquad::draw();
 
+        // Grab the finished frame before the recording indicator goes on it
+        capture.update(&debug_overlay.capture, !dev_console.is_open());
+        capture.draw();
+
         next_frame().await;
     }
 }
+
+/// The local server, in the room named by MECH_ROOM on native builds
+fn default_server_url() -> String {
+    let mut url = format!("ws://127.0.0.1:{}/ws?{}", SERVER_PORT, COMPRESSION_QUERY);
+    // Join a named room instead of the default one: MECH_ROOM=<name>
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Ok(room) = std::env::var("MECH_ROOM") {
+        url.push_str(&format!("&room={room}"));
+    }
+    url
+}
+
+/// What to say to a server we've just connected to
+fn join_message(
+    player_name: &str,
+    spectate: bool,
+    preferred_team: Option<TeamId>,
+    password: Option<&str>,
+) -> ClientMessage {
+    if spectate {
+        ClientMessage::Spectate {
+            protocol_version: PROTOCOL_VERSION,
+            features: SUPPORTED_FEATURES.to_vec(),
+        }
+    } else {
+        ClientMessage::JoinGame {
+            player_name: player_name.to_string(),
+            preferred_team,
+            protocol_version: PROTOCOL_VERSION,
+            features: SUPPORTED_FEATURES.to_vec(),
+            password: password.map(str::to_string),
+        }
+    }
+}
@@ -1,8 +1,14 @@
 #[cfg(not(target_arch = "wasm32"))]
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(not(target_arch = "wasm32"))]
 use std::sync::{Arc, Mutex};
 #[cfg(not(target_arch = "wasm32"))]
 use std::thread;
 #[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+#[cfg(not(target_arch = "wasm32"))]
+use ws::util::Token;
+#[cfg(not(target_arch = "wasm32"))]
 use ws::{connect, CloseCode, Error, Handler, Message, Result, Sender};
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -17,6 +23,58 @@ use shared::*;
 #[cfg(not(target_arch = "wasm32"))]
 pub struct NetworkClient {
     sender: Sender,
+    conditioning: Arc<Mutex<Conditioning>>,
+    /// Set by the socket thread once the connection closes or fails
+    closed: Arc<AtomicBool>,
+}
+
+/// Wakes the handler when conditioned traffic is due
+#[cfg(not(target_arch = "wasm32"))]
+const CONDITIONER_TOKEN: Token = Token(1);
+
+/// Traffic held back by the network conditioner, shared between the game
+/// thread sending and the socket thread releasing it
+#[cfg(not(target_arch = "wasm32"))]
+struct Conditioning {
+    started: Instant,
+    incoming: NetConditioner<ServerMessage>,
+    outgoing: NetConditioner<Vec<u8>>,
+    /// When the socket thread is already due to wake
+    wake_at: Option<f64>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Conditioning {
+    fn new(conditions: NetConditions) -> Self {
+        Self {
+            started: Instant::now(),
+            incoming: NetConditioner::new(conditions),
+            outgoing: NetConditioner::new(conditions),
+            wake_at: None,
+        }
+    }
+
+    fn now(&self) -> f64 {
+        self.started.elapsed().as_secs_f64()
+    }
+
+    /// Ask the socket thread to wake for the next release, unless it
+    /// already will by then
+    fn schedule(&mut self, out: &Sender) {
+        let next = [self.incoming.next_release(), self.outgoing.next_release()]
+            .into_iter()
+            .flatten()
+            .min_by(f64::total_cmp);
+        let Some(next) = next else {
+            return;
+        };
+        if self.wake_at.is_some_and(|wake_at| wake_at <= next) {
+            return;
+        }
+        self.wake_at = Some(next);
+        let delay_ms = ((next - self.now()) * 1000.0).ceil().max(0.0) as u64;
+        let _ = out.timeout(delay_ms, CONDITIONER_TOKEN);
+    }
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -27,34 +85,82 @@ impl NetworkClientTrait for NetworkClient {
         let (tx, rx) = std::sync::mpsc::channel();
 
         let url_clone = url.to_string();
+        let compression = url_requests_compression(url);
+        let conditioning = Arc::new(Mutex::new(Conditioning::new(NetConditions::default())));
+        let handler_conditioning = Arc::clone(&conditioning);
+        let closed = Arc::new(AtomicBool::new(false));
+        let handler_closed = Arc::clone(&closed);
         thread::spawn(move || {
-            connect(url_clone, |out| {
+            let result = connect(url_clone, |out| {
                 // Send the sender through the channel
-                tx.send(out.clone()).unwrap();
+                let _ = tx.send(out.clone());
 
                 ClientHandler {
                     out,
                     game_state: Arc::clone(&game_state),
+                    compression,
+                    conditioning: Arc::clone(&handler_conditioning),
+                    closed: Arc::clone(&handler_closed),
                 }
-            })
-            .unwrap();
+            });
+            if let Err(e) = result {
+                log::error!("WebSocket connection failed: {}", e);
+            }
+            handler_closed.store(true, Ordering::Relaxed);
         });
 
         // Get the sender from the connection
-        let sender = rx.recv().unwrap();
+        let sender = rx.recv().map_err(|_| {
+            Error::new(
+                ws::ErrorKind::Internal,
+                "connection ended before it was set up",
+            )
+        })?;
 
-        Ok(NetworkClient { sender })
+        Ok(NetworkClient {
+            sender,
+            conditioning,
+            closed,
+        })
     }
 
     fn send_message(&self, msg: ClientMessage) {
-        let bytes = rmp_serde::to_vec(&msg).unwrap();
-        self.sender.send(Message::Binary(bytes)).unwrap();
+        let bytes = serialize_client_message(&msg).unwrap();
+        let mut conditioning = self.conditioning.lock().unwrap();
+        if !conditioning.outgoing.conditions().is_active() && conditioning.outgoing.is_empty() {
+            drop(conditioning);
+            self.sender.send(Message::Binary(bytes)).unwrap();
+            return;
+        }
+        let now = conditioning.now();
+        conditioning.outgoing.push(now, bytes);
+        conditioning.schedule(&self.sender);
     }
 
     fn is_connected(&self) -> bool {
-        // For native ws, we assume connected if we have a sender
-        // In a real implementation, we might want to track connection state
-        true
+        // For native ws, we assume connected if we have a sender until
+        // the socket thread says otherwise
+        !self.is_closed()
+    }
+
+    fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Relaxed)
+    }
+
+    fn set_net_conditions(&self, conditions: NetConditions) {
+        let mut conditioning = self.conditioning.lock().unwrap();
+        if conditioning.incoming.conditions() != conditions {
+            log::info!("Network conditions: {conditions}");
+            conditioning.incoming.set_conditions(conditions);
+            conditioning.outgoing.set_conditions(conditions);
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Drop for NetworkClient {
+    fn drop(&mut self) {
+        let _ = self.sender.close(CloseCode::Normal);
     }
 }
 
@@ -62,19 +168,26 @@ impl NetworkClientTrait for NetworkClient {
 struct ClientHandler {
     out: Sender,
     game_state: Arc<Mutex<GameState>>,
+    /// Server frames carry a compression header: asked for in the URL, then
+    /// settled by the join handshake
+    compression: bool,
+    conditioning: Arc<Mutex<Conditioning>>,
+    closed: Arc<AtomicBool>,
 }
 
 #[cfg(not(target_arch = "wasm32"))]
 impl Handler for ClientHandler {
     fn on_message(&mut self, msg: Message) -> Result<()> {
         match msg {
-            Message::Binary(bytes) => {
-                if let Ok(server_msg) = rmp_serde::from_slice::<ServerMessage>(&bytes) {
-                    self.handle_server_message(server_msg);
-                } else {
-                    log::warn!("Failed to parse binary message from server");
+            Message::Binary(bytes) => match decode_server_frame(&bytes, self.compression) {
+                Ok(server_msg) => {
+                    if let Some(compression) = compression_after(&server_msg) {
+                        self.compression = compression;
+                    }
+                    self.handle_server_message(server_msg)
                 }
-            }
+                Err(e) => log::warn!("Failed to parse binary message from server: {}", e),
+            },
             Message::Text(text) => {
                 // Legacy JSON support during migration
                 if let Ok(server_msg) = serde_json::from_str::<ServerMessage>(&text) {
@@ -90,18 +203,59 @@ impl Handler for ClientHandler {
         Ok(())
     }
 
+    fn on_timeout(&mut self, event: Token) -> Result<()> {
+        if event == CONDITIONER_TOKEN {
+            self.conditioning.lock().unwrap().wake_at = None;
+            self.release_conditioned()?;
+        }
+        Ok(())
+    }
+
     fn on_close(&mut self, code: CloseCode, reason: &str) {
         log::info!("Connection closed: {:?} - {}", code, reason);
+        self.closed.store(true, Ordering::Relaxed);
     }
 
     fn on_error(&mut self, err: Error) {
         log::error!("WebSocket error: {}", err);
+        self.closed.store(true, Ordering::Relaxed);
     }
 }
 
 #[cfg(not(target_arch = "wasm32"))]
 impl ClientHandler {
     fn handle_server_message(&mut self, msg: ServerMessage) {
-        handle_server_message(msg, &self.game_state);
+        let mut conditioning = self.conditioning.lock().unwrap();
+        if !conditioning.incoming.conditions().is_active() && conditioning.incoming.is_empty() {
+            drop(conditioning);
+            handle_server_message(msg, &self.game_state);
+            return;
+        }
+        let now = conditioning.now();
+        conditioning.incoming.push(now, msg);
+        drop(conditioning);
+        let _ = self.release_conditioned();
+    }
+
+    /// Deliver whatever the simulated network has let through in both
+    /// directions, then sleep until the next message is due
+    fn release_conditioned(&mut self) -> Result<()> {
+        let (incoming, outgoing) = {
+            let mut conditioning = self.conditioning.lock().unwrap();
+            let now = conditioning.now();
+            let released = (
+                conditioning.incoming.pop_ready(now),
+                conditioning.outgoing.pop_ready(now),
+            );
+            conditioning.schedule(&self.out);
+            released
+        };
+        for msg in incoming {
+            handle_server_message(msg, &self.game_state);
+        }
+        for bytes in outgoing {
+            self.out.send(Message::Binary(bytes))?;
+        }
+        Ok(())
     }
 }
@@ -1,4 +1,4 @@
-use crate::game_state::GameState;
+use crate::game_state::{GameState, HangarState, ScannerPing, StateCheck};
 use macroquad::prelude::*;
 use shared::*;
 use std::sync::{Arc, Mutex};
@@ -9,14 +9,18 @@ pub fn handle_server_message(msg: ServerMessage, game_state: &Arc<Mutex<GameStat
 
     // Debug print the type of the message
     info!("Server message type: {}", msg.type_name());
+    game.record_entity_updates(&msg);
 
     match msg {
         ServerMessage::JoinedGame {
             player_id,
             team,
             spawn_position,
+            features: _,
+            sight,
         } => {
             game.player_id = Some(player_id);
+            game.sight = sight;
             game.player_team = Some(team);
             game.player_location = PlayerLocation::OutsideWorld(spawn_position.to_world_pos());
             #[cfg(not(target_arch = "wasm32"))]
@@ -30,6 +34,11 @@ pub fn handle_server_message(msg: ServerMessage, game_state: &Arc<Mutex<GameStat
             mechs,
             resources,
             projectiles,
+            pacing_zones,
+            ground_items,
+            structures,
+            game_mode,
+            environment,
         } => {
             // Update full game state
             game.players.clear();
@@ -41,23 +50,44 @@ pub fn handle_server_message(msg: ServerMessage, game_state: &Arc<Mutex<GameStat
                         name: player.name,
                         team: player.team,
                         location: player.location,
-                        carrying_resource: player.carrying_resource,
+                        inventory: player.inventory,
+                        held_tool: player.held_tool,
+                        health: player.health,
+                        facing: player.facing,
+                        afk: player.afk,
                     },
                 );
             }
 
             game.mechs.clear();
             for (id, mech) in mechs {
+                if mech.fires.is_empty() {
+                    game.mech_fires.remove(&mech.id);
+                } else {
+                    game.mech_fires.insert(mech.id, mech.fires.clone());
+                }
+                match mech.alarm {
+                    Some(alarm) => game.mech_alarms.insert(mech.id, alarm),
+                    None => game.mech_alarms.remove(&mech.id),
+                };
+
                 let mut mech_state = crate::game_state::MechState {
                     id: mech.id,
                     position: mech.position,
                     world_position: mech.world_position,
                     team: mech.team,
                     health: mech.health,
+                    max_health: mech.max_health,
                     shield: mech.shield,
                     upgrades: mech.upgrades,
+                    drive: mech.drive,
+                    mass: mech.mass,
+                    fuel: mech.fuel,
+                    turret: mech.turret,
+                    point_defense: mech.point_defense,
                     floors: vec![],
-                    _resource_inventory: mech.resource_inventory,
+                    resource_inventory: mech.resource_inventory,
+                    delivery_score: mech.delivery_score,
                 };
 
                 // Build floor layouts
@@ -79,6 +109,9 @@ pub fn handle_server_message(msg: ServerMessage, game_state: &Arc<Mutex<GameStat
                             station_type: station.station_type,
                             occupied: station.operated_by.is_some(),
                             operated_by: station.operated_by,
+                            health: station.health,
+                            max_health: station.max_health,
+                            upgrade_level: station.upgrade_level,
                         },
                     );
                 }
@@ -86,6 +119,9 @@ pub fn handle_server_message(msg: ServerMessage, game_state: &Arc<Mutex<GameStat
                 game.mechs.insert(id, mech_state);
             }
 
+            game.ground_items = ground_items;
+            game.structures = structures;
+
             game.resources.clear();
             for resource in resources {
                 game.resources.push(crate::game_state::ResourceState {
@@ -95,14 +131,22 @@ pub fn handle_server_message(msg: ServerMessage, game_state: &Arc<Mutex<GameStat
                 });
             }
 
-            game.projectiles.clear();
+            // Keep shots we predicted that the server hasn't confirmed yet
+            game.projectiles.retain(|p| p.prediction.is_some());
             for proj in projectiles {
                 game.projectiles.push(crate::game_state::ProjectileData {
                     id: proj.id,
                     position: proj.position,
-                    _velocity: proj.velocity,
+                    velocity: proj.velocity,
+                    owner_mech_id: proj.owner_mech_id,
+                    weapon_type: proj.weapon_type,
+                    prediction: None,
                 });
             }
+
+            game.pacing_zones = pacing_zones;
+            game.game_mode = game_mode;
+            game.environment = environment;
         }
 
         ServerMessage::PlayerMoved {
@@ -118,26 +162,76 @@ pub fn handle_server_message(msg: ServerMessage, game_state: &Arc<Mutex<GameStat
             }
         }
 
-        ServerMessage::PlayerPickedUpResource {
-            player_id,
-            resource_type,
-            resource_id,
-        } => {
-            if let Some(player) = game.players.get_mut(&player_id) {
-                player.carrying_resource = Some(resource_type);
+        ServerMessage::PlayerFacing { player_id, facing } => {
+            // We turn as soon as we press a key, not a round trip later
+            if player_id != game.player_id.unwrap_or(Uuid::nil()) {
+                if let Some(player) = game.players.get_mut(&player_id) {
+                    player.facing = facing;
+                }
             }
+        }
+
+        ServerMessage::PlayerPickedUpResource { resource_id, .. } => {
             game.resources.retain(|r| r.id != resource_id);
         }
 
         ServerMessage::PlayerDroppedResource {
-            player_id,
+            player_id: _,
             resource_type: _,
             position: _,
+        } => {
+            // Could add visual effect here
+        }
+
+        ServerMessage::InventoryUpdated {
+            player_id,
+            inventory,
         } => {
             if let Some(player) = game.players.get_mut(&player_id) {
-                player.carrying_resource = None;
+                player.inventory = inventory;
             }
-            // Could add visual effect here
+        }
+
+        ServerMessage::ItemDropped {
+            item_id,
+            item,
+            position,
+            ..
+        } => {
+            game.ground_items.push(shared::GroundItemState {
+                id: item_id,
+                item,
+                position,
+            });
+        }
+
+        ServerMessage::ItemPickedUp { item_id, .. } => {
+            game.ground_items.retain(|item| item.id != item_id);
+        }
+
+        ServerMessage::ResourceDelivered {
+            player_id: _,
+            mech_id,
+            resource_type,
+            delivery_score,
+        } => {
+            let our_team = game.player_team;
+            if let Some(mech) = game.mechs.get_mut(&mech_id) {
+                mech.delivery_score = delivery_score;
+                if Some(mech.team) == our_team {
+                    game.last_delivery = Some((resource_type, DELIVERY_HIGHLIGHT_DURATION));
+                }
+            }
+        }
+
+        ServerMessage::MechInventoryChanged { mech_id, inventory } => {
+            if let Some(mech) = game.mechs.get_mut(&mech_id) {
+                mech.resource_inventory = inventory;
+            }
+        }
+
+        ServerMessage::ResourceDestroyed { resource_id } => {
+            game.resources.retain(|r| r.id != resource_id);
         }
 
         ServerMessage::ResourceCollected {
@@ -190,6 +284,12 @@ pub fn handle_server_message(msg: ServerMessage, game_state: &Arc<Mutex<GameStat
                     game.ui_state.pilot_station_id = None;
                     game.ui_state.operating_mech_id = None;
                 }
+                if let Some(panel) = game
+                    .station_panel
+                    .take_if(|panel| panel.station_id == station_id)
+                {
+                    game.station_cooldowns.insert(station_id, panel.cooldowns);
+                }
             }
             // Update station state
             if let Some(station) = game.stations.get_mut(&station_id) {
@@ -198,25 +298,63 @@ pub fn handle_server_message(msg: ServerMessage, game_state: &Arc<Mutex<GameStat
             }
         }
 
+        ServerMessage::StationLayout {
+            station_id,
+            name,
+            description,
+            buttons,
+        } => {
+            let cooldowns = game
+                .station_cooldowns
+                .remove(&station_id)
+                .filter(|cooldowns| cooldowns.len() == buttons.len())
+                .unwrap_or_else(|| vec![0.0; buttons.len()]);
+            game.station_panel = Some(crate::game_state::StationPanel {
+                station_id,
+                name,
+                description,
+                cooldowns,
+                buttons,
+                focus: 0,
+            });
+        }
+
         ServerMessage::MechMoved {
             mech_id,
             position,
             world_position,
+            drive,
         } => {
             if let Some(mech) = game.mechs.get_mut(&mech_id) {
                 mech.position = position;
                 mech.world_position = world_position;
+                mech.drive = drive;
             }
         }
 
         ServerMessage::MechDamaged {
             mech_id,
-            damage: _,
+            damage,
             health_remaining,
+            position,
+            hit,
         } => {
             if let Some(mech) = game.mechs.get_mut(&mech_id) {
                 mech.health = health_remaining;
             }
+            game.add_damage_feedback(mech_id, damage, position);
+            if let Some(hit) = hit {
+                game.add_hit_indicator(mech_id, hit);
+            }
+            if !game.accessibility.reduced_flash {
+                game.light_flicker.insert(mech_id, LIGHT_FLICKER_DURATION);
+            }
+        }
+
+        ServerMessage::InteriorImpact {
+            mech_id, severity, ..
+        } => {
+            game.add_impact_shake(mech_id, severity);
         }
 
         ServerMessage::MechShieldChanged { mech_id, shield } => {
@@ -225,31 +363,98 @@ pub fn handle_server_message(msg: ServerMessage, game_state: &Arc<Mutex<GameStat
             }
         }
 
+        ServerMessage::MechFuelChanged { mech_id, fuel } => {
+            if let Some(mech) = game.mechs.get_mut(&mech_id) {
+                mech.fuel = fuel;
+            }
+        }
+
         ServerMessage::WeaponFired {
             mech_id,
             weapon_type,
             target_position,
             projectile_id,
         } => {
-            // Add visual effect
-            game.weapon_effects.push(crate::game_state::WeaponEffect {
+            // Confirm our predicted shot, or add the visual effect
+            crate::prediction::reconcile_weapon_fired(
+                &mut game,
                 mech_id,
                 weapon_type,
-                target: target_position,
-                timer: WEAPON_EFFECT_DURATION,
-                _projectile_id: projectile_id,
+                target_position,
+                projectile_id,
+            );
+        }
+
+        ServerMessage::MechStompWindup { mech_id, windup } => {
+            game.stomp_windups.insert(mech_id, windup);
+        }
+
+        ServerMessage::MechStomped {
+            mech_id,
+            position,
+            radius,
+        } => {
+            game.stomp_windups.remove(&mech_id);
+            game.shockwaves.push(crate::game_state::Shockwave {
+                position,
+                radius,
+                age: 0.0,
             });
+            game.add_stomp_shake(position);
+        }
+
+        ServerMessage::MechsCollided { position, .. } => {
+            game.particles.impact_sparks(position);
+            game.add_ram_shake(position);
+        }
+
+        ServerMessage::PlayerRunOver { position, .. } => {
+            game.particles.gore_spatter(position);
         }
 
-        ServerMessage::ProjectileHit { projectile_id, .. } => {
+        ServerMessage::ProjectileHit {
+            projectile_id,
+            position,
+            ..
+        } => {
             game.projectiles.retain(|p| p.id != projectile_id);
-            // Could add explosion effect
+            game.add_hit_flash(position);
+            game.particles.impact_sparks(position);
+            game.add_explosion_shake(position);
+        }
+
+        ServerMessage::ShieldImpact {
+            mech_id,
+            position,
+            shield_remaining,
+            collapsed,
+            ..
+        } => {
+            if let Some(mech) = game.mechs.get_mut(&mech_id) {
+                mech.shield = shield_remaining;
+            }
+            game.shield_ripples
+                .push(crate::game_state::ShieldRipple { position, age: 0.0 });
+            if collapsed {
+                game.shield_collapses
+                    .push(crate::game_state::ShieldCollapse { mech_id, age: 0.0 });
+            }
         }
 
         ServerMessage::ProjectileExpired { projectile_id } => {
             game.projectiles.retain(|p| p.id != projectile_id);
         }
 
+        ServerMessage::ProjectileIntercepted {
+            projectile_id,
+            interceptor_id,
+            position,
+        } => {
+            game.projectiles
+                .retain(|p| p.id != projectile_id && p.id != interceptor_id);
+            game.particles.impact_sparks(position);
+        }
+
         ServerMessage::ResourceSpawned {
             resource_id,
             position,
@@ -266,6 +471,12 @@ pub fn handle_server_message(msg: ServerMessage, game_state: &Arc<Mutex<GameStat
             game.players.remove(&player_id);
         }
 
+        ServerMessage::PlayerAfk { player_id, afk } => {
+            if let Some(player) = game.players.get_mut(&player_id) {
+                player.afk = afk;
+            }
+        }
+
         ServerMessage::MechUpgraded {
             mech_id,
             upgrade_type,
@@ -279,6 +490,7 @@ pub fn handle_server_message(msg: ServerMessage, game_state: &Arc<Mutex<GameStat
                     }
                     shared::UpgradeType::Shield => mech.upgrades.shield_level = new_level,
                     shared::UpgradeType::Engine => mech.upgrades.engine_level = new_level,
+                    shared::UpgradeType::Ecm => mech.upgrades.ecm_level = new_level,
                 }
             }
             // Could add visual effect for upgrade completion
@@ -295,6 +507,57 @@ pub fn handle_server_message(msg: ServerMessage, game_state: &Arc<Mutex<GameStat
             // Could add visual effect for repair
         }
 
+        ServerMessage::TurretAimed { mech_id, turret } => {
+            if let Some(mech) = game.mechs.get_mut(&mech_id) {
+                mech.turret = turret;
+            }
+        }
+
+        ServerMessage::PointDefenseChanged {
+            mech_id,
+            point_defense,
+        } => {
+            if let Some(mech) = game.mechs.get_mut(&mech_id) {
+                mech.point_defense = point_defense;
+            }
+        }
+
+        ServerMessage::RadarSweep {
+            mech_id,
+            team,
+            blips,
+            duration,
+        } => {
+            // A new sweep replaces the old picture; the enemy only learns they were swept
+            if Some(team) == game.player_team {
+                game.radar_contacts = blips.into_iter().map(|blip| (blip, duration)).collect();
+                game.radar_cooldowns.insert(mech_id, RADAR_SWEEP_COOLDOWN);
+            } else {
+                game.push_toast(
+                    "Radar sweep detected".to_string(),
+                    "The enemy knows roughly where our mech is".to_string(),
+                );
+            }
+        }
+
+        ServerMessage::ScannerPing {
+            player_id,
+            origin,
+            contacts,
+            bearings,
+            duration,
+        } => {
+            if Some(player_id) == game.player_id {
+                game.scanner_ping = Some(ScannerPing {
+                    origin,
+                    contacts,
+                    bearings,
+                    duration,
+                    age: 0.0,
+                });
+            }
+        }
+
         ServerMessage::PlayerKilled {
             player_id,
             killer: _,
@@ -306,8 +569,10 @@ pub fn handle_server_message(msg: ServerMessage, game_state: &Arc<Mutex<GameStat
             }
             if let Some(player) = game.players.get_mut(&player_id) {
                 player.location = PlayerLocation::OutsideWorld(respawn_position);
-                player.carrying_resource = None;
+                player.inventory.clear();
+                player.health = PLAYER_MAX_HEALTH;
             }
+            game.sabotage_progress.remove(&player_id);
         }
 
         ServerMessage::TileUpdate { position, visual } => {
@@ -330,6 +595,14 @@ pub fn handle_server_message(msg: ServerMessage, game_state: &Arc<Mutex<GameStat
             }
         }
 
+        ServerMessage::TeamVisionUpdate { visible_tiles } => {
+            game.team_visible_tiles = visible_tiles.into_iter().collect();
+        }
+
+        ServerMessage::CollisionShapes { shapes } => {
+            game.collision_shapes = Some(shapes);
+        }
+
         ServerMessage::MechFloorData {
             mech_id,
             interior,
@@ -385,20 +658,363 @@ pub fn handle_server_message(msg: ServerMessage, game_state: &Arc<Mutex<GameStat
             }
         }
 
-        ServerMessage::Error { message } => {
+        ServerMessage::StatusEffectsChanged { entity_id, effects } => {
+            if effects.is_empty() {
+                game.status_effects.remove(&entity_id);
+            } else {
+                game.status_effects.insert(entity_id, effects);
+            }
+        }
+
+        ServerMessage::MechFiresUpdated { mech_id, fires } => {
+            if fires.is_empty() {
+                game.mech_fires.remove(&mech_id);
+            } else {
+                game.mech_fires.insert(mech_id, fires);
+            }
+        }
+
+        ServerMessage::HullBreachUpdated {
+            mech_id,
+            floor,
+            position,
+            breached,
+            max_health,
+        } => {
+            let tile = if breached {
+                tile_entity::StaticTile::DamagedWall
+            } else {
+                tile_entity::StaticTile::MetalWall
+            };
+            game.floor_manager.set_static_tile(mech_id, floor, position, tile);
+            if let Some(mech) = game.mechs.get_mut(&mech_id) {
+                mech.max_health = max_health;
+                mech.health = mech.health.min(max_health);
+            }
+        }
+
+        ServerMessage::BarricadeUpdated {
+            mech_id,
+            floor,
+            position,
+            barricade,
+        } => {
+            game.floor_manager
+                .set_barricade(mech_id, floor, position, barricade);
+        }
+
+        ServerMessage::PlayerToolChanged { player_id, tool } => {
+            if let Some(player) = game.players.get_mut(&player_id) {
+                player.held_tool = tool;
+            }
+        }
+
+        ServerMessage::RepairProgress {
+            player_id,
+            mech_id: _,
+            progress,
+        } => match progress {
+            Some(progress) => {
+                game.repair_progress.insert(player_id, progress);
+            }
+            None => {
+                game.repair_progress.remove(&player_id);
+            }
+        },
+
+        ServerMessage::PlayerDashed {
+            player_id,
+            from,
+            to,
+        } => {
+            if player_id == game.player_id.unwrap_or(Uuid::nil()) {
+                game.player_location = to;
+            }
+            if let Some(player) = game.players.get_mut(&player_id) {
+                player.location = to;
+            }
+            if let (Some(from), Some(to)) = (game.player_world_pos(from), game.player_world_pos(to))
+            {
+                game.particles.dash_dust(from, to);
+            }
+        }
+
+        ServerMessage::PlayerAttacked {
+            attacker: _,
+            target,
+            damage: _,
+            health,
+        } => {
+            if let Some(player) = game.players.get_mut(&target) {
+                player.health = health;
+            }
+        }
+
+        ServerMessage::SabotageProgress {
+            player_id,
+            station_id,
+            progress,
+        } => match progress {
+            Some(progress) => {
+                game.sabotage_progress
+                    .insert(player_id, (station_id, progress));
+            }
+            None => {
+                game.sabotage_progress.remove(&player_id);
+            }
+        },
+
+        ServerMessage::MechAlarm { mech_id, alarm } => match alarm {
+            Some(alarm) => {
+                let ours = game
+                    .mechs
+                    .get(&mech_id)
+                    .is_some_and(|mech| Some(mech.team) == game.player_team);
+                if ours {
+                    let name = game
+                        .stations
+                        .get(&alarm.station_id)
+                        .and_then(|station| {
+                            game.station_registry.get_definition(station.station_type)
+                        })
+                        .map_or("a station".to_string(), |definition| {
+                            definition.name.clone()
+                        });
+                    game.push_toast("Boarders!".to_string(), format!("Sabotage at {name}"));
+                }
+                game.mech_alarms.insert(mech_id, alarm);
+            }
+            None => {
+                game.mech_alarms.remove(&mech_id);
+            }
+        },
+
+        ServerMessage::AchievementUnlocked {
+            player_id,
+            achievement,
+        } => {
+            // Our own get the full toast; everyone else's is a shout-out
+            if Some(player_id) == game.player_id {
+                game.push_toast(
+                    format!("Achievement: {}", achievement.title()),
+                    achievement.description().to_string(),
+                );
+            } else if let Some(player) = game.players.get(&player_id) {
+                let title = format!("{} earned {}", player.name, achievement.title());
+                game.push_toast(title, achievement.description().to_string());
+            }
+        }
+
+        ServerMessage::TutorialInstruction {
+            player_id,
+            text,
+            step,
+            total,
+        } => {
+            if Some(player_id) == game.player_id {
+                game.tutorial = Some((text, step, total));
+            }
+        }
+
+        ServerMessage::TutorialComplete { player_id } => {
+            if Some(player_id) == game.player_id {
+                game.tutorial = None;
+                game.push_toast(
+                    "Tutorial complete".to_string(),
+                    "You're on your own now".to_string(),
+                );
+            }
+        }
+
+        ServerMessage::HangarUpdated { crew, time_left } => {
+            game.hangar = Some(HangarState { crew, time_left });
+        }
+
+        ServerMessage::MatchStarted => {
+            let role = game
+                .hangar
+                .take()
+                .and_then(|hangar| hangar.ours(game.player_id).and_then(|crew| crew.role));
+            let detail = match role {
+                Some(role) => format!("You're crewing as {}", role.name()),
+                None => "Find yourself a job aboard".to_string(),
+            };
+            game.push_toast("Match started".to_string(), detail);
+        }
+
+        ServerMessage::GameModeUpdated { hud } => {
+            let just_ended = hud.result.is_some()
+                && game
+                    .game_mode
+                    .as_ref()
+                    .is_none_or(|old| old.result.is_none());
+            if let (true, Some(result)) = (just_ended, &hud.result) {
+                let title = match result.winner {
+                    Some(team) if Some(team) == game.player_team => "Victory!".to_string(),
+                    Some(team) => format!("{team:?} team wins"),
+                    None => "Draw".to_string(),
+                };
+                game.push_toast(title, result.reason.clone());
+            }
+            game.game_mode = Some(hud);
+        }
+
+        ServerMessage::PacingEventStarted { announcement, zone } => {
+            game.pacing_zones.retain(|existing| existing.id != zone.id);
+            game.pacing_zones.push(zone);
+            game.pacing_announcement = Some((announcement, PACING_ANNOUNCEMENT_DURATION));
+        }
+
+        ServerMessage::PacingZonesUpdated { zones } => {
+            game.pacing_zones = zones;
+        }
+
+        ServerMessage::WeatherChanged { environment } => {
+            let detail = match environment.weather {
+                Weather::Clear => "The skies clear",
+                Weather::Fog => "Fog rolls in, cutting sight outside",
+                Weather::Rain => "Rain sets in, slowing crew on foot",
+            };
+            game.push_toast(environment.weather.name().to_string(), detail.to_string());
+            game.environment = environment;
+        }
+
+        ServerMessage::ArenaMapData { map } => {
+            game.arena_map = Some(map);
+        }
+
+        ServerMessage::HazardsUpdated { hazards } => {
+            if let Some(map) = game.arena_map.as_mut() {
+                map.hazards = hazards;
+            }
+        }
+
+        ServerMessage::DecalLayer { decals } => {
+            game.decals = DecalLayer::from_decals(decals);
+        }
+
+        ServerMessage::DecalAdded { position, kind } => {
+            game.decals.add(Decal { position, kind });
+        }
+
+        ServerMessage::SpectatingGame { features: _ } => {
+            game.spectating = true;
+            game.player_id = None;
+            game.player_team = None;
+            game.follow_director();
+        }
+
+        ServerMessage::CameraSuggestion { target, reason } => {
+            game.suggest_camera(target, reason);
+        }
+
+        ServerMessage::JoinRejected {
+            reason,
+            server_version,
+        } => {
             #[cfg(not(target_arch = "wasm32"))]
-            error!("Server error: {}", message);
+            error!(
+                "Server (protocol {}) rejected join: {}",
+                server_version, reason
+            );
             #[cfg(target_arch = "wasm32")]
-            error!("Server error: {}", message);
+            error!(
+                "Server (protocol {}) rejected join: {}",
+                server_version, reason
+            );
+            game.join_rejected = Some(reason);
+        }
+
+        ServerMessage::PasswordRequired { wrong } => {
+            game.password_required = Some(wrong);
+        }
+
+        ServerMessage::VoiceChannel { key } => {
+            game.voice_key = Some(key);
+        }
+
+        ServerMessage::SessionResume { token } => {
+            game.resume_token = Some(token);
+        }
+
+        ServerMessage::Subscribed { channels } => {
+            log::info!("Now getting only {channels:?} from the server");
+        }
+
+        ServerMessage::ServerLoad { level } => {
+            game.server_load = level;
+        }
+
+        ServerMessage::ServerShutdown { reason, seconds } => {
+            game.server_shutdown = Some((reason, seconds as f32));
+        }
+
+        ServerMessage::StateChecksum { tick, hashes } => {
+            let ours = game.state_dump().hashes();
+            let mismatched = hashes.mismatches(&ours);
+            let (was_mismatched, desyncs) = game
+                .state_check
+                .take()
+                .map_or((Vec::new(), 0), |check| (check.mismatched, check.desyncs));
+            // Report when a desync starts or spreads, not every second it lasts
+            if !mismatched.is_empty() && mismatched != was_mismatched {
+                let line = format!(
+                    "Desync at tick {tick}: {} differ from the server",
+                    mismatched.join(", ")
+                );
+                #[cfg(not(target_arch = "wasm32"))]
+                log::warn!("{line}");
+                #[cfg(target_arch = "wasm32")]
+                warn!("{line}");
+                game.console_messages.push(line);
+            }
+            game.state_check = Some(StateCheck {
+                tick,
+                server: hashes,
+                ours,
+                desyncs: desyncs + u32::from(!mismatched.is_empty()),
+                mismatched,
+            });
+        }
+
+        ServerMessage::DevCommandResult { success, message } => {
+            let status = if success { "ok" } else { "error" };
+            game.console_messages.push(format!("[{status}] {message}"));
+        }
+
+        ServerMessage::Error { code, message } => {
+            #[cfg(not(target_arch = "wasm32"))]
+            error!("Server error ({:?}): {}", code, message);
+            #[cfg(target_arch = "wasm32")]
+            error!("Server error ({:?}): {}", code, message);
+
+            // Tell the player about things they can fix; a repeat of one
+            // already showing just keeps it up longer
+            if code.is_actionable() {
+                let title = code.title().to_string();
+                match game
+                    .toasts
+                    .iter_mut()
+                    .find(|(t, detail, _)| *t == title && *detail == message)
+                {
+                    Some((_, _, remaining)) => *remaining = TOAST_DURATION,
+                    None => game.push_toast(title, message),
+                }
+            }
         }
 
         ServerMessage::EffectCreated {
             effect_id: _,
-            effect_type: _,
-            position: _,
-            duration: _,
+            effect_type,
+            position,
+            duration,
         } => {
-            // Could add visual effects in the future
+            game.tile_effects.push(crate::game_state::TileEffect {
+                kind: effect_type,
+                position,
+                duration,
+                age: 0.0,
+            });
         }
 
         ServerMessage::EffectExpired { effect_id: _ } => {
@@ -418,13 +1034,27 @@ pub fn handle_server_message(msg: ServerMessage, game_state: &Arc<Mutex<GameStat
             info!("Chat from {}: {}", player_name, message);
         }
 
+        ServerMessage::AnnotationDrawn { annotation } => {
+            game.annotations.push((annotation, 0.0));
+        }
+
         ServerMessage::MechInteriorUpdate {
             mech_id: _,
             floor: _,
             tile_updates: _,
-            station_changes: _,
+            station_changes,
         } => {
-            // Future scope - not implemented yet
+            for change in station_changes {
+                if let StationUpdate::Upgraded {
+                    station_id,
+                    new_level,
+                } = change
+                {
+                    if let Some(station) = game.stations.get_mut(&station_id) {
+                        station.upgrade_level = new_level;
+                    }
+                }
+            }
         }
     }
 }
\ No newline at end of file
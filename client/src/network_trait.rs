@@ -1,4 +1,4 @@
-use shared::ClientMessage;
+use shared::{ClientMessage, NetConditions};
 use crate::game_state::GameState;
 use std::sync::{Arc, Mutex};
 
@@ -15,6 +15,12 @@ pub trait NetworkClient {
     
     /// Check if the connection is established
     fn is_connected(&self) -> bool;
+
+    /// Whether the socket has closed or failed, so nothing more will arrive
+    fn is_closed(&self) -> bool;
+
+    /// Simulate a bad connection on traffic in both directions
+    fn set_net_conditions(&self, conditions: NetConditions);
 }
 
 /// Web-specific trait for polling-based updates
@@ -12,6 +12,8 @@ use macroquad::prelude::*;
 #[cfg(target_arch = "wasm32")]
 use shared::*;
 #[cfg(target_arch = "wasm32")]
+use std::cell::RefCell;
+#[cfg(target_arch = "wasm32")]
 use std::sync::{Arc, Mutex};
 
 // JavaScript bindings for WebSocket using macroquad's sapp_jsutils
@@ -23,6 +25,7 @@ extern "C" {
     fn js_ws_send_binary(socket_id: u32, data_ptr: *const u8, data_len: usize);
     fn js_ws_close(socket_id: u32);
     fn js_ws_is_connected(socket_id: u32) -> u32;
+    fn js_ws_is_closed(socket_id: u32) -> u32;
     fn js_ws_poll_message(socket_id: u32, buffer_ptr: *mut u8, buffer_len: usize) -> i32;
     fn js_ws_poll_binary_message(socket_id: u32, buffer_ptr: *mut u8, buffer_len: usize) -> i32;
 }
@@ -32,6 +35,12 @@ pub struct NetworkClient {
     socket_id: u32,
     game_state: Arc<Mutex<GameState>>,
     message_buffer: Vec<u8>,
+    /// Server frames carry a compression header: asked for in the URL, then
+    /// settled by the join handshake
+    compression: bool,
+    /// Traffic held back by the network conditioner
+    incoming: RefCell<NetConditioner<ServerMessage>>,
+    outgoing: RefCell<NetConditioner<Vec<u8>>>,
 }
 
 #[cfg(target_arch = "wasm32")]
@@ -50,14 +59,20 @@ impl NetworkClientTrait for NetworkClient {
         Ok(NetworkClient {
             socket_id,
             game_state,
-            message_buffer: vec![0u8; 65536], // 64KB buffer for messages
+            message_buffer: vec![0u8; MESSAGE_BUFFER_SIZE],
+            compression: url_requests_compression(url),
+            incoming: RefCell::new(NetConditioner::new(NetConditions::default())),
+            outgoing: RefCell::new(NetConditioner::new(NetConditions::default())),
         })
     }
 
     fn send_message(&self, msg: ClientMessage) {
-        if let Ok(bytes) = rmp_serde::to_vec(&msg) {
-            unsafe {
-                js_ws_send_binary(self.socket_id, bytes.as_ptr(), bytes.len());
+        if let Ok(bytes) = serialize_client_message(&msg) {
+            let mut outgoing = self.outgoing.borrow_mut();
+            if outgoing.conditions().is_active() || !outgoing.is_empty() {
+                outgoing.push(get_time(), bytes);
+            } else {
+                self.send_bytes(&bytes);
             }
         }
     }
@@ -65,6 +80,38 @@ impl NetworkClientTrait for NetworkClient {
     fn is_connected(&self) -> bool {
         unsafe { js_ws_is_connected(self.socket_id) != 0 }
     }
+
+    fn is_closed(&self) -> bool {
+        unsafe { js_ws_is_closed(self.socket_id) != 0 }
+    }
+
+    fn set_net_conditions(&self, conditions: NetConditions) {
+        if self.incoming.borrow().conditions() != conditions {
+            info!("Network conditions: {}", conditions);
+            self.incoming.borrow_mut().set_conditions(conditions);
+            self.outgoing.borrow_mut().set_conditions(conditions);
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl NetworkClient {
+    fn send_bytes(&self, bytes: &[u8]) {
+        unsafe {
+            js_ws_send_binary(self.socket_id, bytes.as_ptr(), bytes.len());
+        }
+    }
+
+    /// Pass a message on, or hold it until the simulated network delivers it
+    fn receive(&self, msg: ServerMessage) {
+        let mut incoming = self.incoming.borrow_mut();
+        if incoming.conditions().is_active() || !incoming.is_empty() {
+            incoming.push(get_time(), msg);
+        } else {
+            drop(incoming);
+            handle_server_message(msg, &self.game_state);
+        }
+    }
 }
 
 #[cfg(target_arch = "wasm32")]
@@ -84,11 +131,15 @@ impl WebNetworkClient for NetworkClient {
                 break; // No more binary messages
             }
 
-            // Parse the binary message
-            if let Ok(server_msg) = rmp_serde::from_slice::<ServerMessage>(&self.message_buffer[0..msg_len as usize]) {
-                handle_server_message(server_msg, &self.game_state);
-            } else {
-                error!("Failed to parse binary server message, length: {}", msg_len);
+            // Parse the binary message, inflating it if the server compressed it
+            match decode_server_frame(&self.message_buffer[0..msg_len as usize], self.compression) {
+                Ok(server_msg) => {
+                    if let Some(compression) = compression_after(&server_msg) {
+                        self.compression = compression;
+                    }
+                    self.receive(server_msg)
+                }
+                Err(e) => error!("Failed to parse binary server message, length {}: {}", msg_len, e),
             }
         }
 
@@ -110,12 +161,23 @@ impl WebNetworkClient for NetworkClient {
             if let Ok(message_str) = std::str::from_utf8(&self.message_buffer[0..msg_len as usize])
             {
                 if let Ok(server_msg) = serde_json::from_str::<ServerMessage>(message_str) {
-                    handle_server_message(server_msg, &self.game_state);
+                    self.receive(server_msg);
                 } else {
                     error!("Failed to parse JSON server message: {}", message_str);
                 }
             }
         }
+
+        // Release whatever the simulated network has delivered by now
+        let now = get_time();
+        let incoming = self.incoming.borrow_mut().pop_ready(now);
+        for server_msg in incoming {
+            handle_server_message(server_msg, &self.game_state);
+        }
+        let outgoing = self.outgoing.borrow_mut().pop_ready(now);
+        for bytes in outgoing {
+            self.send_bytes(&bytes);
+        }
     }
 }
 
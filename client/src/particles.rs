@@ -0,0 +1,418 @@
+//! Short-lived particles for weapon fire and battle damage.
+//!
+//! Particles live in a fixed pool: dead slots are reused before the pool
+//! grows, and nothing new spawns once the effects quality budget is full.
+//! One-off bursts (muzzle flashes, impact sparks) are spawned from network
+//! messages; trails and smoke are emitted every frame from what is on screen.
+
+use crate::game_state::{MechState, ProjectileData};
+use macroquad::rand::gen_range;
+use serde::{Deserialize, Serialize};
+use shared::{render_constants::*, tile_math::MechPositioning, types::WorldPos};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// How many particles the client is willing to draw
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum EffectsQuality {
+    Off,
+    Low,
+    Medium,
+    #[default]
+    High,
+}
+
+impl EffectsQuality {
+    pub const ALL: [EffectsQuality; 4] = [
+        EffectsQuality::Off,
+        EffectsQuality::Low,
+        EffectsQuality::Medium,
+        EffectsQuality::High,
+    ];
+
+    /// Most particles alive at once
+    pub fn budget(self) -> usize {
+        match self {
+            EffectsQuality::Off => 0,
+            EffectsQuality::Low => PARTICLE_BUDGET_LOW,
+            EffectsQuality::Medium => PARTICLE_BUDGET_MEDIUM,
+            EffectsQuality::High => PARTICLE_BUDGET_HIGH,
+        }
+    }
+
+    /// Multiplier on how many particles each burst and emitter produces
+    pub fn density(self) -> f32 {
+        match self {
+            EffectsQuality::Off => 0.0,
+            EffectsQuality::Low => 0.35,
+            EffectsQuality::Medium => 0.65,
+            EffectsQuality::High => 1.0,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            EffectsQuality::Off => "Off",
+            EffectsQuality::Low => "Low",
+            EffectsQuality::Medium => "Medium",
+            EffectsQuality::High => "High",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParticleKind {
+    /// Glow left behind a projectile
+    Trail,
+    /// Bright, fast flash out of a firing mech
+    Muzzle,
+    /// Hot fragments thrown off where a shot lands
+    Spark,
+    /// Rising smoke from a badly damaged mech
+    Smoke,
+    /// Puffs kicked up along a player's dash
+    Dust,
+    /// Spatter from a player crushed under a mech
+    Gore,
+}
+
+impl ParticleKind {
+    /// Colour at birth and death, faded between by age
+    pub fn colors(self) -> ((f32, f32, f32), (f32, f32, f32)) {
+        match self {
+            ParticleKind::Trail => ((1.0, 0.8, 0.4), (0.6, 0.3, 0.1)),
+            ParticleKind::Muzzle => ((1.0, 1.0, 0.8), (1.0, 0.5, 0.1)),
+            ParticleKind::Spark => ((1.0, 0.95, 0.6), (0.9, 0.3, 0.0)),
+            ParticleKind::Smoke => ((0.35, 0.35, 0.35), (0.2, 0.2, 0.2)),
+            ParticleKind::Dust => ((0.75, 0.7, 0.6), (0.5, 0.45, 0.4)),
+            ParticleKind::Gore => ((0.7, 0.05, 0.05), (0.3, 0.0, 0.0)),
+        }
+    }
+
+    /// Fraction of speed kept per second
+    fn drag(self) -> f32 {
+        match self {
+            ParticleKind::Trail => 0.2,
+            ParticleKind::Muzzle => 0.05,
+            ParticleKind::Spark => 0.1,
+            ParticleKind::Smoke => 0.5,
+            ParticleKind::Dust => 0.1,
+            ParticleKind::Gore => 0.05,
+        }
+    }
+
+    /// Smoke and dust billow out; everything else burns down
+    fn grows(self) -> bool {
+        matches!(self, ParticleKind::Smoke | ParticleKind::Dust)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Particle {
+    pub kind: ParticleKind,
+    pub position: WorldPos,
+    pub velocity: (f32, f32), // pixels per second
+    pub size: f32,            // pixels
+    pub age: f32,
+    pub lifetime: f32,
+}
+
+impl Particle {
+    pub fn is_alive(&self) -> bool {
+        self.age < self.lifetime
+    }
+
+    /// 0.0 when spawned, 1.0 when it dies
+    pub fn progress(&self) -> f32 {
+        (self.age / self.lifetime).clamp(0.0, 1.0)
+    }
+
+    pub fn current_size(&self) -> f32 {
+        if self.kind.grows() {
+            self.size * (1.0 + self.progress() * 1.5)
+        } else {
+            self.size * (1.0 - self.progress() * 0.7)
+        }
+    }
+}
+
+/// Shape of a one-off spray of particles
+struct Burst {
+    kind: ParticleKind,
+    count: usize,
+    spread: f32,          // radians either side of the burst's direction
+    speed: (f32, f32),    // pixels per second
+    lifetime: (f32, f32), // seconds
+    size: f32,            // pixels
+}
+
+const MUZZLE_FLASH: Burst = Burst {
+    kind: ParticleKind::Muzzle,
+    count: MUZZLE_FLASH_PARTICLES,
+    spread: 0.35,
+    speed: (120.0, 260.0),
+    lifetime: (0.06, 0.14),
+    size: 5.0,
+};
+
+const IMPACT_SPARKS: Burst = Burst {
+    kind: ParticleKind::Spark,
+    count: IMPACT_SPARK_PARTICLES,
+    spread: std::f32::consts::PI,
+    speed: (60.0, 220.0),
+    lifetime: (0.2, 0.45),
+    size: 2.5,
+};
+
+const GORE_SPATTER: Burst = Burst {
+    kind: ParticleKind::Gore,
+    count: GORE_PARTICLES,
+    spread: std::f32::consts::PI,
+    speed: (40.0, 180.0),
+    lifetime: (0.3, 0.7),
+    size: 3.0,
+};
+
+pub struct ParticleSystem {
+    /// Live and dead particles; dead slots are listed in `free`
+    particles: Vec<Particle>,
+    free: Vec<usize>,
+    quality: EffectsQuality,
+    /// Fractional particles owed to each continuous emitter
+    emitters: HashMap<Uuid, f32>,
+}
+
+impl ParticleSystem {
+    pub fn new() -> Self {
+        Self {
+            particles: Vec::with_capacity(PARTICLE_BUDGET_MEDIUM),
+            free: Vec::new(),
+            quality: EffectsQuality::default(),
+            emitters: HashMap::new(),
+        }
+    }
+
+    /// Change the budget; lowering it below what's alive clears the pool
+    pub fn set_quality(&mut self, quality: EffectsQuality) {
+        self.quality = quality;
+        if self.live_count() > quality.budget() {
+            self.clear();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.particles.clear();
+        self.free.clear();
+        self.emitters.clear();
+    }
+
+    pub fn live_count(&self) -> usize {
+        self.particles.len() - self.free.len()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Particle> {
+        self.particles.iter().filter(|p| p.is_alive())
+    }
+
+    /// Put a particle in a dead slot, or a new one while under budget
+    fn spawn(&mut self, particle: Particle) -> bool {
+        if self.live_count() >= self.quality.budget() {
+            return false;
+        }
+        match self.free.pop() {
+            Some(index) => self.particles[index] = particle,
+            None => self.particles.push(particle),
+        }
+        true
+    }
+
+    /// Scale a burst's particle count by quality, keeping at least one
+    fn scaled(&self, count: usize) -> usize {
+        if self.quality == EffectsQuality::Off {
+            return 0;
+        }
+        ((count as f32 * self.quality.density()).round() as usize).max(1)
+    }
+
+    /// Throw a burst of particles from `position` around `direction` (radians)
+    fn burst(&mut self, burst: &Burst, position: WorldPos, direction: f32) {
+        for _ in 0..self.scaled(burst.count) {
+            let angle = direction + gen_range(-burst.spread, burst.spread);
+            let speed = gen_range(burst.speed.0, burst.speed.1);
+            let spawned = self.spawn(Particle {
+                kind: burst.kind,
+                position,
+                velocity: (angle.cos() * speed, angle.sin() * speed),
+                size: burst.size * gen_range(0.7, 1.3),
+                age: 0.0,
+                lifetime: gen_range(burst.lifetime.0, burst.lifetime.1),
+            });
+            if !spawned {
+                break;
+            }
+        }
+    }
+
+    /// Flash out of a mech's barrel toward what it fired at
+    pub fn muzzle_flash(&mut self, origin: WorldPos, target: WorldPos) {
+        let direction = (target.y - origin.y).atan2(target.x - origin.x);
+        self.burst(&MUZZLE_FLASH, origin, direction);
+    }
+
+    /// Sparks spraying every way from where a shot landed
+    pub fn impact_sparks(&mut self, position: WorldPos) {
+        self.burst(&IMPACT_SPARKS, position, 0.0);
+    }
+
+    /// Spatter every way from where a player was run over
+    pub fn gore_spatter(&mut self, position: WorldPos) {
+        self.burst(&GORE_SPATTER, position, 0.0);
+    }
+
+    /// Dust strung along a dash's path, drifting back the way the player came
+    pub fn dash_dust(&mut self, from: WorldPos, to: WorldPos) {
+        let back = (from.y - to.y).atan2(from.x - to.x);
+        let count = self.scaled(DASH_DUST_PARTICLES);
+        for i in 0..count {
+            let along = (i as f32 + gen_range(0.0, 1.0)) / count as f32;
+            let angle = back + gen_range(-0.6, 0.6);
+            let speed = gen_range(15.0, 45.0);
+            let spawned = self.spawn(Particle {
+                kind: ParticleKind::Dust,
+                position: WorldPos::new(
+                    from.x + (to.x - from.x) * along,
+                    from.y + (to.y - from.y) * along,
+                ),
+                velocity: (angle.cos() * speed, angle.sin() * speed),
+                size: gen_range(3.0, 5.0),
+                age: 0.0,
+                lifetime: gen_range(0.3, 0.6),
+            });
+            if !spawned {
+                break;
+            }
+        }
+    }
+
+    /// Spend each emitter's share of `rate` particles per second, carrying
+    /// fractions over to the next frame
+    fn emit(&mut self, id: Uuid, rate: f32, delta: f32) -> usize {
+        let owed = self.emitters.entry(id).or_insert(0.0);
+        *owed += rate * self.quality.density() * delta;
+        let whole = owed.floor();
+        *owed -= whole;
+        whole as usize
+    }
+
+    /// Leave a trail behind every projectile in flight
+    pub fn emit_trails(&mut self, projectiles: &[ProjectileData], delta: f32) {
+        for projectile in projectiles {
+            let count = self.emit(projectile.id, PROJECTILE_TRAIL_RATE, delta);
+            for _ in 0..count {
+                let jitter = (gen_range(-10.0, 10.0), gen_range(-10.0, 10.0));
+                self.spawn(Particle {
+                    kind: ParticleKind::Trail,
+                    position: projectile.position,
+                    velocity: (
+                        -projectile.velocity.0 * 0.1 + jitter.0,
+                        -projectile.velocity.1 * 0.1 + jitter.1,
+                    ),
+                    size: 3.0,
+                    age: 0.0,
+                    lifetime: gen_range(0.15, 0.3),
+                });
+            }
+        }
+    }
+
+    /// Smoke pouring off mechs below `MECH_SMOKE_HEALTH`, thicker the worse
+    /// the damage
+    pub fn emit_smoke<'a>(&mut self, mechs: impl Iterator<Item = &'a MechState>, delta: f32) {
+        for mech in mechs {
+            if mech.max_health == 0 {
+                continue;
+            }
+            let health = mech.health as f32 / mech.max_health as f32;
+            if health >= MECH_SMOKE_HEALTH || mech.health == 0 {
+                continue;
+            }
+            let damage = 1.0 - health / MECH_SMOKE_HEALTH;
+            let rate = MECH_SMOKE_RATE * (0.3 + 0.7 * damage);
+            let center = MechPositioning::mech_center(mech.position);
+            for _ in 0..self.emit(mech.id, rate, delta) {
+                let offset = MECH_SMOKE_SPREAD;
+                self.spawn(Particle {
+                    kind: ParticleKind::Smoke,
+                    position: WorldPos::new(
+                        center.x + gen_range(-offset, offset),
+                        center.y + gen_range(-offset, offset),
+                    ),
+                    velocity: (gen_range(-8.0, 8.0), -gen_range(15.0, 35.0)),
+                    size: gen_range(5.0, 9.0),
+                    age: 0.0,
+                    lifetime: gen_range(1.0, 2.0),
+                });
+            }
+        }
+    }
+
+    /// Age and move every particle, freeing the slots of ones that died
+    pub fn update(&mut self, delta: f32) {
+        for (index, particle) in self.particles.iter_mut().enumerate() {
+            if !particle.is_alive() {
+                continue;
+            }
+            particle.age += delta;
+            if !particle.is_alive() {
+                self.free.push(index);
+                continue;
+            }
+            let drag = particle.kind.drag().powf(delta);
+            particle.velocity.0 *= drag;
+            particle.velocity.1 *= drag;
+            particle.position.x += particle.velocity.0 * delta;
+            particle.position.y += particle.velocity.1 * delta;
+        }
+    }
+
+    /// Forget emitters that are no longer in the world
+    pub fn retain_emitters(&mut self, mut alive: impl FnMut(&Uuid) -> bool) {
+        self.emitters.retain(|id, _| alive(id));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spark() -> Particle {
+        Particle {
+            kind: ParticleKind::Spark,
+            position: WorldPos::new(0.0, 0.0),
+            velocity: (0.0, 0.0),
+            size: 2.0,
+            age: 0.0,
+            lifetime: 0.5,
+        }
+    }
+
+    #[test]
+    fn test_pool_reuses_dead_slots_and_respects_budget() {
+        let mut particles = ParticleSystem::new();
+        particles.set_quality(EffectsQuality::Low);
+        for _ in 0..PARTICLE_BUDGET_LOW + 10 {
+            particles.spawn(spark());
+        }
+        assert_eq!(particles.live_count(), PARTICLE_BUDGET_LOW);
+
+        particles.update(1.0);
+        assert_eq!(particles.live_count(), 0);
+        particles.spawn(spark());
+        assert_eq!(particles.particles.len(), PARTICLE_BUDGET_LOW);
+        assert_eq!(particles.live_count(), 1);
+
+        particles.set_quality(EffectsQuality::Off);
+        particles.impact_sparks(WorldPos::new(0.0, 0.0));
+        assert_eq!(particles.live_count(), 0);
+    }
+}
@@ -0,0 +1,52 @@
+//! Asks for a room's password after the server turned our join away for
+//! lacking one. Submitting hands the password to the main loop, which
+//! remembers it and sends `JoinGame` again over the same connection.
+
+use egui::Context;
+
+pub struct PasswordPrompt {
+    input: String,
+    /// Put the cursor in the box on the next frame
+    focus: bool,
+}
+
+impl PasswordPrompt {
+    pub fn new() -> Self {
+        Self {
+            input: String::new(),
+            focus: true,
+        }
+    }
+
+    /// Draw the prompt; `wrong` when the last password didn't match.
+    /// Returns the password once the player submits one.
+    pub fn render_ui(&mut self, ctx: &Context, wrong: bool) -> Option<String> {
+        let mut submitted = false;
+        egui::Window::new("Password required")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label("This room is locked. Enter its password to join.");
+                if wrong {
+                    ui.colored_label(egui::Color32::LIGHT_RED, "That password was wrong");
+                }
+                ui.horizontal(|ui| {
+                    ui.label("Password:");
+                    let field = ui.add(egui::TextEdit::singleline(&mut self.input).password(true));
+                    if std::mem::take(&mut self.focus) {
+                        field.request_focus();
+                    }
+                    submitted = field.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                });
+                submitted |= ui
+                    .add_enabled(!self.input.is_empty(), egui::Button::new("Join"))
+                    .clicked();
+            });
+        if !submitted || self.input.is_empty() {
+            return None;
+        }
+        self.focus = true;
+        Some(std::mem::take(&mut self.input))
+    }
+}
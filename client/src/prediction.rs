@@ -0,0 +1,277 @@
+use crate::game_state::{GameState, ProjectileData, ShotPrediction, WeaponEffect};
+use shared::{
+    balance::{EMP_PROJECTILE_SPEED, PROJECTILE_BASE_SPEED},
+    render_constants::WEAPON_EFFECT_DURATION,
+    types::*,
+    WeaponType,
+};
+use uuid::Uuid;
+
+/// Draw the shot a weapon button will fire right away instead of waiting a
+/// round trip for the server. Mirrors the server's targeting: the nearest
+/// enemy mech, with projectiles launched from our mech's corner tile.
+pub fn predict_station_fire(game: &mut GameState, station_id: StationId, button_index: u8) {
+    let Some(station) = game.stations.get(&station_id) else {
+        return;
+    };
+    let (mech_id, weapon_type) = (station.mech_id, station.station_type);
+    if button_index != 0
+        || !matches!(
+            weapon_type,
+            StationType::WeaponLaser | StationType::WeaponProjectile | StationType::WeaponEmp
+        )
+    {
+        return;
+    }
+
+    let Some(our_mech) = game.mechs.get(&mech_id) else {
+        return;
+    };
+    let (our_team, our_pos) = (our_mech.team, our_mech.position);
+    let Some(target_pos) = game
+        .mechs
+        .values()
+        .filter(|m| m.team != our_team)
+        .map(|m| m.position)
+        .min_by(|a, b| {
+            a.distance_to(our_pos)
+                .partial_cmp(&b.distance_to(our_pos))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })
+    else {
+        return;
+    };
+
+    game.weapon_effects.push(WeaponEffect {
+        mech_id,
+        weapon_type,
+        target: target_pos,
+        timer: WEAPON_EFFECT_DURATION,
+        _projectile_id: None,
+        prediction: Some(ShotPrediction::default()),
+    });
+    game.add_muzzle_flash(mech_id, target_pos);
+
+    if weapon_type != StationType::WeaponLaser {
+        let speed = if weapon_type == StationType::WeaponEmp {
+            EMP_PROJECTILE_SPEED
+        } else {
+            PROJECTILE_BASE_SPEED
+        };
+        let start = our_pos.to_world_pos();
+        let target = target_pos.to_world_pos();
+        let (dx, dy) = (target.x - start.x, target.y - start.y);
+        let dist = (dx * dx + dy * dy).sqrt();
+        let velocity = if dist > 0.0 {
+            (dx / dist * speed, dy / dist * speed)
+        } else {
+            (0.0, 0.0)
+        };
+
+        game.projectiles.push(ProjectileData {
+            id: Uuid::nil(),
+            position: start,
+            velocity,
+            owner_mech_id: mech_id,
+            weapon_type: if weapon_type == StationType::WeaponEmp {
+                WeaponType::Emp
+            } else {
+                WeaponType::Projectile
+            },
+            prediction: Some(ShotPrediction::default()),
+        });
+    }
+}
+
+/// Match an authoritative `WeaponFired` to the oldest shot we predicted for
+/// that mech. The predicted projectile keeps flying under the server's id;
+/// shots nobody predicted are added as before.
+pub fn reconcile_weapon_fired(
+    game: &mut GameState,
+    mech_id: MechId,
+    weapon_type: StationType,
+    target: TilePos,
+    projectile_id: Option<ProjectileId>,
+) {
+    match game
+        .weapon_effects
+        .iter_mut()
+        .find(|e| e.mech_id == mech_id && e.weapon_type == weapon_type && e.prediction.is_some())
+    {
+        Some(effect) => {
+            effect.target = target;
+            effect._projectile_id = projectile_id;
+            effect.prediction = None;
+        }
+        None => {
+            game.weapon_effects.push(WeaponEffect {
+                mech_id,
+                weapon_type,
+                target,
+                timer: WEAPON_EFFECT_DURATION,
+                _projectile_id: projectile_id,
+                prediction: None,
+            });
+            game.add_muzzle_flash(mech_id, target);
+        }
+    }
+
+    let Some(projectile_id) = projectile_id else {
+        return;
+    };
+    if let Some(projectile) = game
+        .projectiles
+        .iter_mut()
+        .find(|p| p.owner_mech_id == mech_id && p.prediction.is_some())
+    {
+        projectile.id = projectile_id;
+        projectile.prediction = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_state::{MechState, StationState};
+    use shared::{
+        balance::MECH_MAX_HEALTH,
+        render_constants::{PREDICTION_CONFIRM_TIMEOUT, PREDICTION_FADE_DURATION},
+        MechUpgrades, TeamId,
+    };
+    use std::collections::HashMap;
+
+    fn add_mech(game: &mut GameState, team: TeamId, position: TilePos) -> MechId {
+        let id = Uuid::new_v4();
+        game.mechs.insert(
+            id,
+            MechState {
+                id,
+                position,
+                world_position: position.to_world_pos(),
+                team,
+                health: MECH_MAX_HEALTH,
+                max_health: MECH_MAX_HEALTH,
+                shield: 0,
+                upgrades: MechUpgrades {
+                    laser_level: 1,
+                    projectile_level: 1,
+                    engine_level: 1,
+                    shield_level: 1,
+                    ecm_level: 0,
+                },
+                drive: Default::default(),
+                mass: Default::default(),
+                fuel: 0.0,
+                turret: Default::default(),
+                point_defense: Default::default(),
+                floors: Vec::new(),
+                resource_inventory: HashMap::new(),
+                delivery_score: 0,
+            },
+        );
+        id
+    }
+
+    fn add_station(game: &mut GameState, mech_id: MechId, station_type: StationType) -> StationId {
+        let id = Uuid::new_v4();
+        game.stations.insert(
+            id,
+            StationState {
+                _id: id,
+                mech_id,
+                floor: 0,
+                position: TilePos::new(0, 0),
+                station_type,
+                occupied: true,
+                operated_by: None,
+                health: 100,
+                max_health: 100,
+                upgrade_level: 1,
+            },
+        );
+        id
+    }
+
+    /// Our mech with a laser and a cannon, and an enemy to aim them at
+    fn armed_game() -> (GameState, MechId, StationId, StationId, TilePos) {
+        let mut game = GameState::new();
+        let ours = add_mech(&mut game, TeamId::Red, TilePos::new(10, 10));
+        let enemy_at = TilePos::new(40, 10);
+        add_mech(&mut game, TeamId::Blue, enemy_at);
+        let laser = add_station(&mut game, ours, StationType::WeaponLaser);
+        let cannon = add_station(&mut game, ours, StationType::WeaponProjectile);
+        (game, ours, laser, cannon, enemy_at)
+    }
+
+    #[test]
+    fn test_the_servers_shot_confirms_the_prediction_for_that_mech_and_weapon() {
+        let (mut game, ours, laser, cannon, enemy_at) = armed_game();
+        predict_station_fire(&mut game, laser, 0);
+        predict_station_fire(&mut game, cannon, 0);
+        assert_eq!(game.weapon_effects.len(), 2);
+        assert_eq!(game.projectiles.len(), 1);
+
+        let projectile_id = Uuid::new_v4();
+        let landed = TilePos::new(41, 11);
+        reconcile_weapon_fired(
+            &mut game,
+            ours,
+            StationType::WeaponProjectile,
+            landed,
+            Some(projectile_id),
+        );
+
+        // The cannon shot is the server's now; the laser is still waiting
+        assert_eq!(game.weapon_effects.len(), 2);
+        let effect = |weapon_type| {
+            game.weapon_effects
+                .iter()
+                .find(|e| e.weapon_type == weapon_type)
+                .unwrap()
+        };
+        let cannon_shot = effect(StationType::WeaponProjectile);
+        assert!(cannon_shot.prediction.is_none());
+        assert_eq!(cannon_shot.target, landed);
+        assert_eq!(cannon_shot._projectile_id, Some(projectile_id));
+        assert!(effect(StationType::WeaponLaser).prediction.is_some());
+        assert_eq!(effect(StationType::WeaponLaser).target, enemy_at);
+        assert_eq!(game.projectiles[0].id, projectile_id);
+        assert!(game.projectiles[0].prediction.is_none());
+
+        // Another mech's shot was never predicted, so it's added as is
+        let other = add_mech(&mut game, TeamId::Red, TilePos::new(10, 30));
+        reconcile_weapon_fired(&mut game, other, StationType::WeaponLaser, enemy_at, None);
+        let lasers: Vec<_> = game
+            .weapon_effects
+            .iter()
+            .filter(|e| e.weapon_type == StationType::WeaponLaser)
+            .collect();
+        assert_eq!(lasers.len(), 2);
+        assert!(lasers
+            .iter()
+            .any(|e| e.mech_id == ours && e.prediction.is_some()));
+        assert!(lasers
+            .iter()
+            .any(|e| e.mech_id == other && e.prediction.is_none()));
+    }
+
+    #[test]
+    fn test_unconfirmed_predictions_fade_out() {
+        let (mut game, _, _, cannon, _) = armed_game();
+        predict_station_fire(&mut game, cannon, 0);
+
+        game.update_state(PREDICTION_CONFIRM_TIMEOUT);
+        let prediction = game.weapon_effects[0].prediction.unwrap();
+        assert_eq!(prediction.alpha(), 1.0);
+
+        game.update_state(PREDICTION_FADE_DURATION / 2.0);
+        let alpha = game.weapon_effects[0].prediction.unwrap().alpha();
+        assert!(alpha > 0.0 && alpha < 1.0, "alpha {alpha} should be fading");
+        assert_eq!(game.projectiles.len(), 1);
+
+        // The server never answered, so the shot and its projectile go
+        game.update_state(PREDICTION_FADE_DURATION);
+        assert!(game.weapon_effects.is_empty());
+        assert!(game.projectiles.is_empty());
+    }
+}
@@ -0,0 +1,212 @@
+//! Quick match, opened with the console's `queue`. Queues us on the
+//! server we're connected to and shows our place in line; once the server
+//! has put a match together, the room's URL and the team picked for us go
+//! to the main loop through `take_match`, which connects like `connect`.
+//!
+//! Queueing takes POST and DELETE requests, which the browser bindings
+//! can't make, so web builds only say so.
+
+use egui::Context;
+use shared::{matchmaking_url, room_ws_url, QueueStatus, TeamId};
+
+type QueueUpdate = Result<QueueStatus, String>;
+
+pub struct QuickMatch {
+    open: bool,
+    status: String,
+    pending: Option<PendingQueue>,
+    /// Room URL and team, once matched
+    matched: Option<(String, TeamId)>,
+}
+
+impl QuickMatch {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            status: String::new(),
+            pending: None,
+            matched: None,
+        }
+    }
+
+    /// Join the queue of the server behind `server_url`, unless we're in it
+    pub fn start(&mut self, server_url: &str, player_name: &str) {
+        self.open = true;
+        if self.pending.is_some() {
+            return;
+        }
+        let Some(url) = matchmaking_url(server_url) else {
+            self.status = "This server can't be queued on".to_string();
+            return;
+        };
+        self.status = "Joining the queue...".to_string();
+        self.pending = PendingQueue::start(url, player_name.to_string())
+            .map_err(|e| self.status = e)
+            .ok();
+    }
+
+    /// The room we were matched into and the team to ask for, once
+    pub fn take_match(&mut self) -> Option<(String, TeamId)> {
+        self.matched.take()
+    }
+
+    /// Pick up news from the queue; call once a frame. `server_url` is
+    /// where matched rooms live.
+    pub fn update(&mut self, server_url: &str) {
+        let Some(pending) = self.pending.as_mut() else {
+            return;
+        };
+        let Some(update) = pending.poll() else {
+            return;
+        };
+        match update {
+            Ok(QueueStatus::Waiting { position, queued }) => {
+                self.status = format!("Waiting for players: {position} of {queued} in line");
+            }
+            Ok(QueueStatus::Matched { room, team }) => {
+                self.status = format!("Matched on the {team:?} team");
+                self.matched = Some((room_ws_url(server_url, &room), team));
+                self.pending = None;
+                self.open = false;
+            }
+            Err(e) => {
+                self.status = e;
+                self.pending = None;
+            }
+        }
+    }
+
+    fn leave(&mut self) {
+        if let Some(pending) = self.pending.take() {
+            pending.leave();
+        }
+        self.status = "Left the queue".to_string();
+    }
+
+    pub fn render_ui(&mut self, ctx: &Context) {
+        if !self.open {
+            return;
+        }
+        let mut open = true;
+        egui::Window::new("Quick Match")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(&self.status);
+                if self.pending.is_some() && ui.button("Leave queue").clicked() {
+                    self.leave();
+                }
+            });
+        if !open {
+            // Closing the window gives up our place too
+            self.leave();
+            self.open = false;
+        }
+    }
+}
+
+/// A ticket being polled on a background thread
+#[cfg(not(target_arch = "wasm32"))]
+struct PendingQueue {
+    updates: std::sync::mpsc::Receiver<QueueUpdate>,
+    leave: std::sync::Arc<std::sync::atomic::AtomicBool>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl PendingQueue {
+    fn start(url: String, player_name: String) -> Result<Self, String> {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let leave = std::sync::Arc::default();
+        let leave_flag = std::sync::Arc::clone(&leave);
+        std::thread::spawn(move || native::wait_for_match(&url, player_name, &tx, &leave_flag));
+        Ok(Self { updates: rx, leave })
+    }
+
+    /// The latest news, skipping any we were too slow to show
+    fn poll(&mut self) -> Option<QueueUpdate> {
+        use std::sync::mpsc::TryRecvError;
+        let mut latest = None;
+        loop {
+            match self.updates.try_recv() {
+                Ok(update) => latest = Some(update),
+                Err(TryRecvError::Empty) => return latest,
+                Err(TryRecvError::Disconnected) => {
+                    return latest.or_else(|| Some(Err("Lost track of the queue".to_string())))
+                }
+            }
+        }
+    }
+
+    /// Give up the ticket; the thread tells the server on its next poll
+    fn leave(self) {
+        self.leave.store(true, std::sync::atomic::Ordering::Relaxed);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod native {
+    use super::QueueUpdate;
+    use shared::{http_request, QueueRequest, QueueStatus, QueueTicket, MATCHMAKING_POLL_MS};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::mpsc::Sender;
+    use std::time::Duration;
+
+    /// Take a ticket and ask after it until we're matched, something goes
+    /// wrong or we're told to leave
+    pub fn wait_for_match(
+        url: &str,
+        player_name: String,
+        tx: &Sender<QueueUpdate>,
+        leave: &AtomicBool,
+    ) {
+        let request =
+            serde_json::to_string(&QueueRequest { player_name }).expect("queue requests serialize");
+        let ticket: QueueTicket = match http_request("POST", url, Some(&request))
+            .and_then(|body| serde_json::from_str(&body).map_err(|e| e.to_string()))
+        {
+            Ok(ticket) => ticket,
+            Err(e) => {
+                let _ = tx.send(Err(format!("Couldn't join the queue: {e}")));
+                return;
+            }
+        };
+        let ticket_url = format!("{url}/{}", ticket.ticket);
+
+        loop {
+            if leave.load(Ordering::Relaxed) {
+                if let Err(e) = http_request("DELETE", &ticket_url, None) {
+                    log::warn!("Failed to leave the queue: {e}");
+                }
+                return;
+            }
+            let status = http_request("GET", &ticket_url, None)
+                .and_then(|body| {
+                    serde_json::from_str::<QueueStatus>(&body).map_err(|e| e.to_string())
+                })
+                .map_err(|e| format!("Lost our place in the queue: {e}"));
+            let done = !matches!(status, Ok(QueueStatus::Waiting { .. }));
+            if tx.send(status).is_err() || done {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(MATCHMAKING_POLL_MS));
+        }
+    }
+}
+
+/// Stands in for the native queue so the window can say why nothing happens
+#[cfg(target_arch = "wasm32")]
+struct PendingQueue;
+
+#[cfg(target_arch = "wasm32")]
+impl PendingQueue {
+    fn start(_url: String, _player_name: String) -> Result<Self, String> {
+        Err("Quick match needs the desktop client".to_string())
+    }
+
+    fn poll(&mut self) -> Option<QueueUpdate> {
+        None
+    }
+
+    fn leave(self) {}
+}
@@ -0,0 +1,118 @@
+//! Noticing the server has gone away and getting back in. The socket
+//! closing, or the server going quiet for `CONNECTION_TIMEOUT_SECS`, counts
+//! as losing the connection: the main loop freezes the game behind an
+//! overlay counting down to the next attempt, which it makes through the
+//! same path as `connect`, with the resume token the server gave us so we
+//! get our player back. Each failed attempt doubles the wait.
+
+use egui::Context;
+use shared::{CONNECTION_TIMEOUT_SECS, RECONNECT_DELAY_SECS, RECONNECT_MAX_DELAY_SECS};
+
+pub struct Reconnect {
+    /// Server messages handled so far, and when that last went up
+    heard: u64,
+    heard_at: f64,
+    /// Why the connection was lost, while it is
+    lost: Option<String>,
+    /// Attempts made since the connection was lost
+    attempts: u32,
+    /// Whether the latest attempt is still waiting to hear back
+    attempting: bool,
+    next_attempt: f64,
+}
+
+impl Reconnect {
+    pub fn new(now: f64) -> Self {
+        Self {
+            heard: 0,
+            heard_at: now,
+            lost: None,
+            attempts: 0,
+            attempting: false,
+            next_attempt: now,
+        }
+    }
+
+    pub fn is_lost(&self) -> bool {
+        self.lost.is_some()
+    }
+
+    /// A new connection is being opened; it gets the whole timeout to be
+    /// heard from, and counts as an attempt if we were cut off
+    pub fn connecting(&mut self, now: f64) {
+        self.heard = 0;
+        self.heard_at = now;
+        if self.lost.is_some() {
+            self.attempting = true;
+            self.attempts += 1;
+        }
+    }
+
+    /// Check on the connection once a frame, given how many server messages
+    /// have been handled and whether the socket has `closed`. True when the
+    /// connection was lost just now.
+    pub fn watch(&mut self, now: f64, messages_received: u64, closed: bool) -> bool {
+        if messages_received != self.heard {
+            self.heard = messages_received;
+            self.heard_at = now;
+            if self.lost.take().is_some() {
+                self.attempts = 0;
+                self.attempting = false;
+            }
+            return false;
+        }
+        let reason = if closed {
+            "The server closed the connection"
+        } else if now - self.heard_at > CONNECTION_TIMEOUT_SECS {
+            "The server stopped responding"
+        } else {
+            return false;
+        };
+
+        if self.lost.is_none() {
+            self.lost = Some(reason.to_string());
+            self.next_attempt = now + RECONNECT_DELAY_SECS;
+            return true;
+        }
+        if self.attempting {
+            self.attempting = false;
+            self.lost = Some(reason.to_string());
+            let backoff = 2f64.powi(self.attempts.min(8) as i32);
+            self.next_attempt =
+                now + (RECONNECT_DELAY_SECS * backoff).min(RECONNECT_MAX_DELAY_SECS);
+        }
+        false
+    }
+
+    /// Whether it's time for the next attempt
+    pub fn due(&self, now: f64) -> bool {
+        self.lost.is_some() && !self.attempting && now >= self.next_attempt
+    }
+
+    /// Draw the overlay while the connection is lost
+    pub fn render_ui(&mut self, ctx: &Context, now: f64) {
+        let Some(reason) = &self.lost else {
+            return;
+        };
+        let mut retry = false;
+        egui::Window::new("Connection lost")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(reason.as_str());
+                if self.attempting {
+                    ui.label(format!("Reconnecting (attempt {})...", self.attempts));
+                } else {
+                    let left = (self.next_attempt - now).max(0.0).ceil();
+                    ui.label(format!("Reconnecting in {left:.0}s"));
+                }
+                retry = ui
+                    .add_enabled(!self.attempting, egui::Button::new("Retry now"))
+                    .clicked();
+            });
+        if retry {
+            self.next_attempt = now;
+        }
+    }
+}
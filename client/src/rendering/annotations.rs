@@ -0,0 +1,50 @@
+use crate::game_state::GameState;
+use macroquad::prelude::*;
+use shared::{annotations::AnnotationShape, render_constants::*};
+
+/// Coaching marks in world space, fading out over their last second, and
+/// the one being dragged out in the drawing toolbar. Like radar blips these
+/// show through fog.
+pub fn render_annotations(game_state: &GameState, cam_x: f32, cam_y: f32) {
+    for (annotation, age) in &game_state.annotations {
+        let alpha = ((annotation.ttl - age) / ANNOTATION_FADE_TIME).clamp(0.0, 1.0);
+        let (r, g, b) = annotation.color.rgb();
+        draw_shape(&annotation.shape, Color::new(r, g, b, alpha), cam_x, cam_y);
+    }
+    if let Some((shape, color)) = &game_state.annotation_preview {
+        let (r, g, b) = color.rgb();
+        let color = Color::new(r, g, b, ANNOTATION_PREVIEW_ALPHA);
+        draw_shape(shape, color, cam_x, cam_y);
+    }
+}
+
+fn draw_shape(shape: &AnnotationShape, color: Color, cam_x: f32, cam_y: f32) {
+    match *shape {
+        AnnotationShape::Line { from, to } => {
+            let (from, to) = (
+                vec2(cam_x + from.x, cam_y + from.y),
+                vec2(cam_x + to.x, cam_y + to.y),
+            );
+            draw_line(from.x, from.y, to.x, to.y, ANNOTATION_LINE_WIDTH, color);
+        }
+        AnnotationShape::Arrow { from, to } => {
+            let (from, to) = (
+                vec2(cam_x + from.x, cam_y + from.y),
+                vec2(cam_x + to.x, cam_y + to.y),
+            );
+            draw_line(from.x, from.y, to.x, to.y, ANNOTATION_LINE_WIDTH, color);
+            let back = (from - to).normalize_or_zero() * ANNOTATION_ARROW_HEAD;
+            let side = vec2(-back.y, back.x) * 0.5;
+            draw_triangle(to, to + back + side, to + back - side, color);
+        }
+        AnnotationShape::Circle { center, radius } => {
+            draw_circle_lines(
+                cam_x + center.x,
+                cam_y + center.y,
+                radius,
+                ANNOTATION_LINE_WIDTH,
+                color,
+            );
+        }
+    }
+}
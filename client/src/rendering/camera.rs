@@ -2,6 +2,8 @@ use macroquad::prelude::*;
 use shared::{
     constants::{ARENA_HEIGHT_TILES, ARENA_WIDTH_TILES, TILE_SIZE},
     coordinates::{ScreenPos, TilePos, TileRegion, WorldPos},
+    render_constants::*,
+    types::EntityId,
 };
 
 /// Camera transformation utilities for converting between coordinate spaces
@@ -75,22 +77,32 @@ impl Camera {
 
     /// Clamp the camera position to stay within world bounds
     pub fn clamp_to_world(&mut self) {
+        self.clamp_to_arena(
+            ARENA_WIDTH_TILES as f32 * TILE_SIZE,
+            ARENA_HEIGHT_TILES as f32 * TILE_SIZE,
+        );
+    }
+
+    /// Keep the view inside an arena of the given size in world pixels,
+    /// centering on any axis where the arena is smaller than the view
+    pub fn clamp_to_arena(&mut self, world_width: f32, world_height: f32) {
         let half_screen_width = screen_width() / 2.0 / self.zoom;
         let half_screen_height = screen_height() / 2.0 / self.zoom;
 
-        let world_width = ARENA_WIDTH_TILES as f32 * TILE_SIZE;
-        let world_height = ARENA_HEIGHT_TILES as f32 * TILE_SIZE;
-
-        self.position.x = self
-            .position
-            .x
-            .max(half_screen_width)
-            .min(world_width - half_screen_width);
-        self.position.y = self
-            .position
-            .y
-            .max(half_screen_height)
-            .min(world_height - half_screen_height);
+        self.position.x = if world_width <= half_screen_width * 2.0 {
+            world_width / 2.0
+        } else {
+            self.position
+                .x
+                .clamp(half_screen_width, world_width - half_screen_width)
+        };
+        self.position.y = if world_height <= half_screen_height * 2.0 {
+            world_height / 2.0
+        } else {
+            self.position
+                .y
+                .clamp(half_screen_height, world_height - half_screen_height)
+        };
     }
 
     /// Set the zoom level (clamped to reasonable values)
@@ -344,6 +356,16 @@ impl CameraShake {
         camera.position = camera.position + self.offset;
     }
 
+    /// Current shake offset in world pixels
+    pub fn offset(&self) -> WorldPos {
+        self.offset
+    }
+
+    /// How hard the shake is right now, in pixels
+    pub fn current_intensity(&self) -> f32 {
+        self.intensity * (self.remaining_time / self.duration).max(0.0)
+    }
+
     /// Check if shake is still active
     pub fn is_active(&self) -> bool {
         self.remaining_time > 0.0
@@ -357,6 +379,153 @@ impl CameraShake {
     }
 }
 
+/// What the game camera keeps centered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FollowTarget {
+    /// Our own player, wherever they are
+    Player,
+    /// The mech we're inside, so the view holds still while we walk between
+    /// stations. Outside a mech this follows the player.
+    OwnMech,
+    /// Another player or a mech, for watching someone else
+    Entity(EntityId),
+}
+
+/// The game view's camera: follows a target with smoothing, eases toward
+/// the zoom picked with the mouse wheel, shakes for hits and explosions and
+/// stays inside the arena.
+///
+/// World renderers keep drawing at `world offset + world position`; the
+/// zoom is applied on top by drawing them through `world_camera`.
+pub struct CameraController {
+    /// Where the view is centered, without shake
+    pub camera: Camera,
+    /// Zoom the camera is easing toward
+    pub target_zoom: f32,
+    pub follow: FollowTarget,
+    /// Share of the way to the target covered each 60th of a second; 1.0
+    /// locks the view to the target
+    pub smoothing: f32,
+    shake: Option<CameraShake>,
+    /// False until the first target arrives, which the camera jumps to
+    placed: bool,
+}
+
+impl Default for CameraController {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CameraController {
+    pub fn new() -> Self {
+        Self {
+            camera: Camera::new(WorldPos::new(0.0, 0.0)),
+            target_zoom: 1.0,
+            follow: FollowTarget::Player,
+            smoothing: CAMERA_SMOOTHING,
+            shake: None,
+            placed: false,
+        }
+    }
+
+    /// Move toward `target`, ease the zoom and run any shake. The arena
+    /// size is in world pixels.
+    pub fn update(&mut self, target: Option<WorldPos>, arena_size: (f32, f32), delta_time: f32) {
+        // Per-frame rates scaled so the feel doesn't change with frame rate
+        let ease = |rate: f32| 1.0 - (1.0 - rate.clamp(0.0, 1.0)).powf(delta_time * 60.0);
+
+        let zoom_step = (self.target_zoom - self.camera.zoom) * ease(CAMERA_ZOOM_SMOOTHING);
+        self.camera.set_zoom(self.camera.zoom + zoom_step);
+        if let Some(target) = target {
+            self.camera.position = if self.placed {
+                self.camera.position.lerp(target, ease(self.smoothing))
+            } else {
+                target
+            };
+            self.placed = true;
+        }
+        self.camera.clamp_to_arena(arena_size.0, arena_size.1);
+
+        if let Some(shake) = self.shake.as_mut() {
+            shake.update(delta_time);
+            if !shake.is_active() {
+                self.shake = None;
+            }
+        }
+    }
+
+    /// Zoom in for positive mouse wheel notches, out for negative
+    pub fn zoom_by(&mut self, notches: f32) {
+        self.target_zoom = (self.target_zoom * CAMERA_ZOOM_STEP.powf(notches))
+            .clamp(CAMERA_MIN_ZOOM, CAMERA_MAX_ZOOM);
+    }
+
+    /// Start shaking, unless a stronger shake is already running
+    pub fn shake(&mut self, intensity: f32, duration: f32) {
+        let stronger_running = self
+            .shake
+            .as_ref()
+            .is_some_and(|shake| shake.current_intensity() >= intensity);
+        if !stronger_running {
+            self.shake = Some(CameraShake::new(intensity, duration));
+        }
+    }
+
+    /// Shake for an explosion, weaker the further it is from the view
+    pub fn shake_from(&mut self, position: WorldPos, intensity: f32, duration: f32) {
+        let range = EXPLOSION_SHAKE_RANGE * TILE_SIZE;
+        let falloff = 1.0 - self.camera.position.distance_to(position) / range;
+        if falloff > 0.0 {
+            self.shake(intensity * falloff, duration);
+        }
+    }
+
+    /// The camera as drawn this frame, shake included
+    pub fn view(&self) -> Camera {
+        let shake = self
+            .shake
+            .as_ref()
+            .map_or(WorldPos::new(0.0, 0.0), |shake| shake.offset());
+        Camera {
+            position: self.camera.position + shake,
+            zoom: self.camera.zoom,
+        }
+    }
+
+    /// Where the world origin lands on screen before zoom; world renderers
+    /// add this to world positions
+    pub fn world_offset(&self) -> (f32, f32) {
+        let view = self.view();
+        (
+            screen_width() / 2.0 - view.position.x,
+            screen_height() / 2.0 - view.position.y,
+        )
+    }
+
+    /// Scales what's drawn at `world_offset` by the zoom, around the screen
+    /// center. Set it for world rendering and go back to the default camera
+    /// for the HUD.
+    pub fn world_camera(&self) -> Camera2D {
+        let zoom = self.camera.zoom;
+        Camera2D {
+            target: vec2(screen_width() / 2.0, screen_height() / 2.0),
+            zoom: vec2(2.0 * zoom / screen_width(), -2.0 * zoom / screen_height()),
+            ..Default::default()
+        }
+    }
+
+    /// World position under a point on the screen, e.g. the mouse
+    pub fn screen_to_world(&self, x: f32, y: f32) -> WorldPos {
+        self.view().screen_to_world(ScreenPos::new(x, y))
+    }
+
+    /// Top-left and bottom-right corners of the world on screen
+    pub fn visible_world_bounds(&self) -> (WorldPos, WorldPos) {
+        ViewportCalculations::get_visible_world_bounds(&self.view())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -424,4 +593,26 @@ mod tests {
         assert!(!shake.is_active());
         // assert_eq!(shake.offset.magnitude(), 0.0);
     }
+
+    #[test]
+    fn test_wheel_zoom_stays_in_range() {
+        let mut controller = CameraController::new();
+        controller.zoom_by(1.0);
+        assert_eq!(controller.target_zoom, CAMERA_ZOOM_STEP);
+        controller.zoom_by(-100.0);
+        assert_eq!(controller.target_zoom, CAMERA_MIN_ZOOM);
+        controller.zoom_by(100.0);
+        assert_eq!(controller.target_zoom, CAMERA_MAX_ZOOM);
+    }
+
+    #[test]
+    fn test_weaker_shake_does_not_cut_a_stronger_one_short() {
+        let mut controller = CameraController::new();
+        controller.shake(10.0, 1.0);
+        controller.shake(2.0, 1.0);
+        assert_eq!(controller.shake.as_ref().unwrap().current_intensity(), 10.0);
+
+        controller.shake_from(WorldPos::new(0.0, 0.0), 20.0, 1.0);
+        assert_eq!(controller.shake.as_ref().unwrap().current_intensity(), 20.0);
+    }
 }
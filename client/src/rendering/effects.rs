@@ -1,15 +1,285 @@
 use super::utils::*;
 use crate::game_state::*;
+use crate::particles::ParticleKind;
 use macroquad::prelude::*;
 use shared::{
+    balance::{MECH_MAX_SHIELD, SHIELD_BUBBLE_RADIUS_TILES, STOMP_RADIUS, STOMP_WINDUP},
+    components::StatusEffectKind,
     constants::*,
     coordinates::{TilePos, ViewportCalculations, WorldPos},
+    render_constants::*,
     types::*,
+    MechInteriorCoordinates,
 };
 
 pub fn render_effects(game_state: &GameState, cam_x: f32, cam_y: f32) {
     render_weapon_effects(game_state, cam_x, cam_y);
+    render_particles(game_state, cam_x, cam_y);
+    render_shields(game_state, cam_x, cam_y);
     render_oxygen_tethers(game_state, cam_x, cam_y);
+    render_hit_feedback(game_state, cam_x, cam_y);
+    render_emp_sparks(game_state, cam_x, cam_y);
+    render_stomps(game_state, cam_x, cam_y);
+    render_tile_effects(game_state, cam_x, cam_y);
+}
+
+/// Trails, flashes, sparks, smoke and dust, each blending toward its dying colour
+fn render_particles(game_state: &GameState, cam_x: f32, cam_y: f32) {
+    let flash = game_state.accessibility.flash_intensity();
+    for particle in game_state.particles.iter() {
+        let t = particle.progress();
+        let (birth, death) = particle.kind.colors();
+        let alpha = match particle.kind {
+            ParticleKind::Smoke | ParticleKind::Dust => 0.5 * (1.0 - t),
+            _ => (1.0 - t * t) * flash,
+        };
+        draw_circle(
+            cam_x + particle.position.x,
+            cam_y + particle.position.y,
+            particle.current_size(),
+            Color::new(
+                birth.0 + (death.0 - birth.0) * t,
+                birth.1 + (death.1 - birth.1) * t,
+                birth.2 + (death.2 - birth.2) * t,
+                alpha,
+            ),
+        );
+    }
+}
+
+/// Effects from scripted map tiles, each fading out over its duration
+fn render_tile_effects(game_state: &GameState, cam_x: f32, cam_y: f32) {
+    for effect in &game_state.tile_effects {
+        let fade = 1.0 - (effect.age / effect.duration.max(f32::EPSILON)).min(1.0);
+        let (x, y) = (cam_x + effect.position.x, cam_y + effect.position.y);
+        match effect.kind.as_str() {
+            "sparks" => {
+                let time = get_time() as f32 * 20.0;
+                for i in 0..6 {
+                    let angle = i as f32 * std::f32::consts::TAU / 6.0 + time;
+                    let reach = TILE_SIZE * 0.5 * (1.0 - fade * 0.5);
+                    draw_line(
+                        x,
+                        y,
+                        x + angle.cos() * reach,
+                        y + angle.sin() * reach,
+                        1.5,
+                        Color::new(1.0, 0.9, 0.3, fade),
+                    );
+                }
+            }
+            "smoke" => {
+                let rise = effect.age * TILE_SIZE * 0.5;
+                draw_circle(
+                    x,
+                    y - rise,
+                    TILE_SIZE * (0.3 + effect.age * 0.2),
+                    Color::new(0.5, 0.5, 0.5, 0.5 * fade),
+                );
+            }
+            _ => {
+                draw_circle(x, y, TILE_SIZE * 0.6, Color::new(0.4, 0.9, 1.0, 0.3 * fade));
+            }
+        }
+    }
+}
+
+/// A throbbing warning ring over the ground a stomp is about to hit, then
+/// the shockwave rolling out once it lands
+fn render_stomps(game_state: &GameState, cam_x: f32, cam_y: f32) {
+    let radius = STOMP_RADIUS * TILE_SIZE;
+
+    for (mech_id, remaining) in &game_state.stomp_windups {
+        let Some(mech) = game_state.mechs.get(mech_id) else {
+            continue;
+        };
+        let center = get_mech_center(mech);
+        let progress = (1.0 - remaining / STOMP_WINDUP).clamp(0.0, 1.0);
+        let throb = 0.5 + 0.5 * (get_time() as f32 * STOMP_WARNING_PULSE).sin();
+        draw_circle(
+            cam_x + center.x,
+            cam_y + center.y,
+            radius,
+            Color::new(1.0, 0.3, 0.1, 0.08 + 0.12 * progress),
+        );
+        draw_circle_lines(
+            cam_x + center.x,
+            cam_y + center.y,
+            radius,
+            2.0 + 2.0 * throb,
+            Color::new(1.0, 0.4, 0.1, 0.4 + 0.5 * progress),
+        );
+        // Closes in on the hull as the foot comes down
+        draw_circle_lines(
+            cam_x + center.x,
+            cam_y + center.y,
+            radius * (1.0 - progress),
+            1.5,
+            Color::new(1.0, 0.8, 0.3, 0.6),
+        );
+    }
+
+    for shockwave in &game_state.shockwaves {
+        let t = shockwave.age / STOMP_SHOCKWAVE_DURATION;
+        let (x, y) = (cam_x + shockwave.position.x, cam_y + shockwave.position.y);
+        // Eases out so the ring hits hard and then drifts
+        let spread = shockwave.radius * (1.0 - (1.0 - t) * (1.0 - t));
+        draw_circle_lines(
+            x,
+            y,
+            spread,
+            6.0 * (1.0 - t) + 1.0,
+            Color::new(0.8, 0.7, 0.5, 1.0 - t),
+        );
+        draw_circle_lines(
+            x,
+            y,
+            spread * 0.8,
+            3.0,
+            Color::new(0.6, 0.5, 0.35, 0.6 * (1.0 - t)),
+        );
+    }
+}
+
+/// Bubble around every mech with its shield up, ripples where it was struck,
+/// and the bubble folding in once it breaks
+fn render_shields(game_state: &GameState, cam_x: f32, cam_y: f32) {
+    let radius = SHIELD_BUBBLE_RADIUS_TILES * TILE_SIZE;
+
+    for mech in game_state.mechs.values() {
+        if mech.shield == 0 {
+            continue;
+        }
+        let center = get_mech_center(mech);
+        let strength = (mech.shield as f32 / MECH_MAX_SHIELD as f32).clamp(0.0, 1.0);
+        draw_circle(
+            cam_x + center.x,
+            cam_y + center.y,
+            radius,
+            Color::new(0.3, 0.7, 1.0, SHIELD_BUBBLE_ALPHA * strength),
+        );
+        draw_circle_lines(
+            cam_x + center.x,
+            cam_y + center.y,
+            radius,
+            2.0,
+            Color::new(0.5, 0.85, 1.0, 0.3 + 0.4 * strength),
+        );
+    }
+
+    for ripple in &game_state.shield_ripples {
+        let t = ripple.age / SHIELD_RIPPLE_DURATION;
+        let (x, y) = (cam_x + ripple.position.x, cam_y + ripple.position.y);
+        draw_circle_lines(
+            x,
+            y,
+            SHIELD_RIPPLE_RADIUS * (0.3 + t),
+            2.0,
+            Color::new(0.7, 0.9, 1.0, 1.0 - t),
+        );
+        draw_circle(x, y, 6.0 * (1.0 - t), Color::new(1.0, 1.0, 1.0, 1.0 - t));
+    }
+
+    for collapse in &game_state.shield_collapses {
+        let Some(mech) = game_state.mechs.get(&collapse.mech_id) else {
+            continue;
+        };
+        let t = collapse.age / SHIELD_COLLAPSE_DURATION;
+        let center = get_mech_center(mech);
+        // Flickers as it shrinks in on the hull
+        let flicker = if (collapse.age * 30.0) as i32 % 2 == 0 {
+            1.0
+        } else {
+            0.4
+        };
+        draw_circle_lines(
+            cam_x + center.x,
+            cam_y + center.y,
+            radius * (1.0 - t * t),
+            3.0,
+            Color::new(0.5, 0.85, 1.0, (1.0 - t) * flicker),
+        );
+    }
+}
+
+/// Crackle over every station of a mech knocked out by an EMP
+fn render_emp_sparks(game_state: &GameState, cam_x: f32, cam_y: f32) {
+    use macroquad::rand::gen_range;
+
+    for station in game_state.stations.values() {
+        let disabled = game_state
+            .status_effects
+            .get(&station.mech_id)
+            .is_some_and(|effects| effects.iter().any(|e| e.kind == StatusEffectKind::Emp));
+        let Some(mech) = game_state.mechs.get(&station.mech_id) else {
+            continue;
+        };
+        if !disabled {
+            continue;
+        }
+
+        let world = MechInteriorCoordinates::interior_to_world(
+            mech.position,
+            station.floor,
+            station.position,
+        )
+        .to_world();
+        let center_x = cam_x + world.x + TILE_SIZE / 2.0;
+        let center_y = cam_y + world.y + TILE_SIZE / 2.0;
+
+        for _ in 0..EMP_SPARKS_PER_STATION {
+            let (mut x, mut y) = (center_x, center_y);
+            for _ in 0..3 {
+                let nx = x + gen_range(-EMP_SPARK_LENGTH, EMP_SPARK_LENGTH);
+                let ny = y + gen_range(-EMP_SPARK_LENGTH, EMP_SPARK_LENGTH);
+                let color = if gen_range(0, 2) == 0 {
+                    Color::new(0.6, 0.3, 1.0, 0.9)
+                } else {
+                    Color::new(0.9, 0.9, 1.0, 0.9)
+                };
+                draw_line(x, y, nx, ny, 1.5, color);
+                (x, y) = (nx, ny);
+            }
+        }
+    }
+}
+
+fn render_hit_feedback(game_state: &GameState, cam_x: f32, cam_y: f32) {
+    let intensity = game_state.accessibility.flash_intensity();
+    for flash in &game_state.hit_flashes {
+        let t = flash.age / HIT_FLASH_DURATION;
+        draw_circle(
+            cam_x + flash.position.x,
+            cam_y + flash.position.y,
+            HIT_FLASH_RADIUS * (1.0 + t),
+            Color::new(1.0, 1.0, 0.8, (1.0 - t) * intensity),
+        );
+    }
+
+    for number in &game_state.damage_numbers {
+        let pos = number.current_position();
+        let text = number.damage.to_string();
+        let size = measure_text(&text, None, DAMAGE_NUMBER_FONT_SIZE as u16, 1.0);
+        let alpha = number.alpha();
+        let x = cam_x + pos.x - size.width / 2.0;
+        let y = cam_y + pos.y;
+
+        // Dark outline keeps numbers readable over any tile
+        draw_text(
+            &text,
+            x + 1.0,
+            y + 1.0,
+            DAMAGE_NUMBER_FONT_SIZE,
+            Color::new(0.0, 0.0, 0.0, alpha),
+        );
+        draw_text(
+            &text,
+            x,
+            y,
+            DAMAGE_NUMBER_FONT_SIZE,
+            Color::new(1.0, 0.85, 0.2, alpha),
+        );
+    }
 }
 
 fn render_weapon_effects(game_state: &GameState, cam_x: f32, cam_y: f32) {
@@ -25,13 +295,17 @@ fn render_weapon_effects(game_state: &GameState, cam_x: f32, cam_y: f32) {
                     WorldPos::new(cam_x, cam_y),
                 );
 
+                // Predicted beams fade out if the server never confirms them
+                let alpha = effect.timer
+                    * effect.prediction.map_or(1.0, |p| p.alpha())
+                    * game_state.accessibility.flash_intensity();
                 draw_line(
                     start_x,
                     start_y,
                     end_x,
                     end_y,
                     3.0 * effect.timer,
-                    Color::new(1.0, 0.0, 0.0, effect.timer),
+                    Color::new(1.0, 0.0, 0.0, alpha),
                 );
             }
         }
@@ -46,7 +320,7 @@ fn render_oxygen_tethers(game_state: &GameState, cam_x: f32, cam_y: f32) {
 
                 // Calculate tether properties
                 let (color, width) =
-                    calculate_tether_properties(distance, player.carrying_resource.is_some());
+                    calculate_tether_properties(distance, player.inventory.has_resource());
 
                 // Draw tether line
                 draw_line(
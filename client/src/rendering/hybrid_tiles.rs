@@ -1,8 +1,9 @@
 use super::primitives::{ArrowRenderer, ArrowStyle};
+use super::utils::{draw_station_tier_badge, get_station_tier_color};
 use macroquad::prelude::*;
 use shared::{
     coordinates::{TilePos, ViewportCalculations, WorldPos},
-    Direction, Material, StationType, TileVisual, TILE_SIZE,
+    Direction, Material, StationType, TileVisual, ToolType, TILE_SIZE,
 };
 
 /// Render a tile using the hybrid tile visual system
@@ -83,6 +84,7 @@ pub fn render_tile_visual(tile: &TileVisual, x: f32, y: f32, size: f32) {
         TileVisual::Station {
             station_type,
             active,
+            level,
         } => {
             // Draw floor first
             draw_rectangle(x, y, size, size, Color::from_rgba(100, 100, 110, 255));
@@ -108,12 +110,16 @@ pub fn render_tile_visual(tile: &TileVisual, x: f32, y: f32, size: f32) {
             let text = match station_type {
                 StationType::WeaponLaser => "L",
                 StationType::WeaponProjectile => "P",
+                StationType::WeaponEmp => "M",
                 StationType::Engine => "E",
                 StationType::Shield => "S",
                 StationType::Repair => "R",
                 StationType::Electrical => "⚡",
                 StationType::Upgrade => "U",
                 StationType::Pilot => "◎",
+                StationType::TurretControl => "T",
+                StationType::Radar => "D",
+                StationType::PointDefense => "I",
             };
 
             let text_size = size * 0.4;
@@ -124,6 +130,13 @@ pub fn render_tile_visual(tile: &TileVisual, x: f32, y: f32, size: f32) {
                 text_size,
                 BLACK,
             );
+
+            // Upgraded stations get a tier-colored rim and a pip per tier
+            if *level > 1 {
+                let tier_color = get_station_tier_color(*level);
+                draw_rectangle_lines(x, y, size, size, 2.0, tier_color);
+                draw_station_tier_badge(x, y, size, *level, tier_color);
+            }
         }
 
         TileVisual::Turret { facing, firing } => {
@@ -162,6 +175,25 @@ pub fn render_tile_visual(tile: &TileVisual, x: f32, y: f32, size: f32) {
             );
         }
 
+        TileVisual::Locker { tool } => {
+            draw_rectangle(x, y, size, size, Color::from_rgba(70, 70, 80, 255));
+
+            // Door panel tinted by the tool stored inside
+            let panel_color = match tool {
+                ToolType::Extinguisher => Color::from_rgba(200, 50, 50, 255),
+                ToolType::RepairTool => Color::from_rgba(220, 180, 40, 255),
+            };
+            let inset = size * 0.15;
+            draw_rectangle(
+                x + inset,
+                y + inset,
+                size - inset * 2.0,
+                size - inset * 2.0,
+                panel_color,
+            );
+            draw_rectangle_lines(x, y, size, size, 2.0, Color::from_rgba(40, 40, 50, 255));
+        }
+
         TileVisual::TransitionFade { progress } => {
             // Draw fade effect
             let alpha = (255.0 * (1.0 - progress)) as u8;
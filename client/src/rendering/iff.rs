@@ -0,0 +1,192 @@
+use super::utils::*;
+use crate::accessibility::Palette;
+use crate::game_state::{GameState, MechState};
+use crate::vision::ClientVisionSystem;
+use macroquad::prelude::*;
+use shared::{
+    render_constants::*, MechInteriorCoordinates, PlayerId, PlayerLocation, TeamId, WorldPos,
+    MECH_SIZE_TILES, TILE_SIZE,
+};
+
+/// How someone on screen stands with us
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Allegiance {
+    Own,
+    Friendly,
+    Enemy,
+    /// We're spectating, so nobody is friend or foe
+    Unaligned,
+}
+
+impl Allegiance {
+    /// For a player; mechs pass no id
+    pub fn of(game_state: &GameState, id: Option<PlayerId>, team: TeamId) -> Self {
+        match game_state.player_team {
+            None => Allegiance::Unaligned,
+            Some(_) if id.is_some() && id == game_state.player_id => Allegiance::Own,
+            Some(ours) if ours == team => Allegiance::Friendly,
+            Some(_) => Allegiance::Enemy,
+        }
+    }
+}
+
+fn enemy_color(palette: Palette) -> Color {
+    let (r, g, b) = match palette {
+        Palette::Standard => IFF_ENEMY_COLOR,
+        Palette::ColorblindSafe => COLORBLIND_IFF_ENEMY_COLOR,
+    };
+    Color::new(r, g, b, 1.0)
+}
+
+fn faded(color: Color, alpha: f32) -> Color {
+    Color {
+        a: color.a * alpha,
+        ..color
+    }
+}
+
+/// Ring around a player centered on (x, y): a halo of two white rings for
+/// us, a faint team-coloured one for teammates, and for enemies a red ring
+/// with sight ticks so they read as hostile by shape as well as colour.
+/// `visibility` fades it with the fog like the player under it.
+pub fn draw_player_mark(
+    game_state: &GameState,
+    player_id: PlayerId,
+    team: TeamId,
+    x: f32,
+    y: f32,
+    radius: f32,
+    visibility: f32,
+) {
+    let palette = game_state.accessibility.palette();
+    let ring = radius + IFF_RING_GAP;
+    match Allegiance::of(game_state, Some(player_id), team) {
+        Allegiance::Own => {
+            draw_circle_lines(x, y, ring, 2.0, faded(WHITE, visibility));
+            draw_circle_lines(x, y, ring + IFF_RING_GAP, 1.0, faded(WHITE, 0.4));
+        }
+        Allegiance::Friendly | Allegiance::Unaligned => {
+            let color = get_team_color(team, palette);
+            draw_circle_lines(
+                x,
+                y,
+                ring,
+                1.5,
+                faded(color, IFF_FRIENDLY_ALPHA * visibility),
+            );
+        }
+        Allegiance::Enemy => {
+            let color = faded(enemy_color(palette), visibility);
+            draw_circle_lines(x, y, ring, 2.5, color);
+            for (dx, dy) in [(1.0, 0.0), (-1.0, 0.0), (0.0, 1.0), (0.0, -1.0)] {
+                draw_line(
+                    x + dx * ring,
+                    y + dy * ring,
+                    x + dx * (ring + IFF_ENEMY_TICK_LENGTH),
+                    y + dy * (ring + IFF_ENEMY_TICK_LENGTH),
+                    2.0,
+                    color,
+                );
+            }
+        }
+    }
+}
+
+/// Colour of a mech's outline: its team's for ours, red for the enemy's
+pub fn mech_outline_color(game_state: &GameState, team: TeamId) -> Color {
+    let palette = game_state.accessibility.palette();
+    match Allegiance::of(game_state, None, team) {
+        Allegiance::Enemy => enemy_color(palette),
+        _ => get_team_color(team, palette),
+    }
+}
+
+/// Corner brackets just outside an enemy mech's outline at (x, y)
+pub fn draw_enemy_mech_brackets(x: f32, y: f32, size: f32, color: Color) {
+    let (gap, arm) = (IFF_RING_GAP * 2.0, size * IFF_MECH_BRACKET_LENGTH);
+    let (left, top, right, bottom) = (x - gap, y - gap, x + size + gap, y + size + gap);
+    for (cx, cy, sx, sy) in [
+        (left, top, 1.0, 1.0),
+        (right, top, -1.0, 1.0),
+        (left, bottom, 1.0, -1.0),
+        (right, bottom, -1.0, -1.0),
+    ] {
+        draw_line(cx, cy, cx + sx * arm, cy, 3.0, color);
+        draw_line(cx, cy, cx, cy + sy * arm, 3.0, color);
+    }
+}
+
+fn mech_center(mech: &MechState) -> WorldPos {
+    let half = MECH_SIZE_TILES as f32 * TILE_SIZE / 2.0;
+    WorldPos::new(mech.world_position.x + half, mech.world_position.y + half)
+}
+
+/// Enemies we can see, wherever the camera is. With no fog everyone counts
+/// as seen.
+fn enemies_in_sight(game_state: &GameState, vision: Option<&ClientVisionSystem>) -> Vec<WorldPos> {
+    let Some(our_team) = game_state.player_team else {
+        return Vec::new();
+    };
+    let mut enemies: Vec<WorldPos> = game_state
+        .mechs
+        .values()
+        .filter(|mech| mech.team != our_team)
+        .map(mech_center)
+        .filter(|center| vision.is_none_or(|vision| vision.is_visible(center.to_tile())))
+        .collect();
+    for player in game_state.players.values() {
+        if player.team == our_team {
+            continue;
+        }
+        let seen = match player.location {
+            PlayerLocation::OutsideWorld(pos) => vision
+                .is_none_or(|vision| vision.is_visible(pos.to_tile()))
+                .then_some(pos),
+            PlayerLocation::InsideMech { mech_id, pos } => {
+                game_state.mechs.get(&mech_id).and_then(|mech| {
+                    let visible = vision.is_none_or(|vision| {
+                        vision.is_interior_visible(mech_id, pos.floor(), pos.tile_pos())
+                    });
+                    visible.then(|| {
+                        MechInteriorCoordinates::interior_to_world(
+                            mech.position,
+                            pos.floor(),
+                            pos.tile_pos(),
+                        )
+                        .to_world_center()
+                    })
+                })
+            }
+        };
+        enemies.extend(seen);
+    }
+    enemies
+}
+
+/// "Enemy spotted" arrows along the screen's edge, pointing at each enemy
+/// in sight that the camera has left off screen. Drawn over the HUD.
+pub fn render_offscreen_enemies(game_state: &GameState, vision: Option<&ClientVisionSystem>) {
+    let view = game_state.camera.view();
+    let (width, height) = (screen_width(), screen_height());
+    let center = vec2(width / 2.0, height / 2.0);
+    let color = enemy_color(game_state.accessibility.palette());
+
+    for enemy in enemies_in_sight(game_state, vision) {
+        let screen = view.world_to_screen(enemy);
+        let offset = vec2(screen.x, screen.y) - center;
+        let on_screen =
+            screen.x >= 0.0 && screen.x <= width && screen.y >= 0.0 && screen.y <= height;
+        if on_screen || offset.length() < f32::EPSILON {
+            continue;
+        }
+        // Walk out from the middle of the screen until we hit the margin
+        let reach_x = (width / 2.0 - IFF_ARROW_MARGIN) / offset.x.abs().max(f32::EPSILON);
+        let reach_y = (height / 2.0 - IFF_ARROW_MARGIN) / offset.y.abs().max(f32::EPSILON);
+        let direction = offset.normalize();
+        let tip = center + offset * reach_x.min(reach_y);
+        let base = tip - direction * IFF_ARROW_SIZE;
+        let side = vec2(-direction.y, direction.x) * IFF_ARROW_SIZE * 0.5;
+        draw_triangle(tip, base + side, base - side, color);
+        draw_triangle_lines(tip, base + side, base - side, 1.0, BLACK);
+    }
+}
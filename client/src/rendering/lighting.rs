@@ -0,0 +1,423 @@
+//! Light and darkness inside mechs.
+//!
+//! Each mech's interior sits in ambient darkness, lit by its stations, by
+//! daylight through its windows and, when its power is failing, by pulsing
+//! emergency lights. The lights stutter for a moment whenever the mech is
+//! hit. Native builds draw the darkness into a render target, add radial
+//! light sprites onto it and multiply it over the world; WASM builds shade
+//! interior tiles one by one instead.
+
+use super::render_scale;
+use super::utils::get_station_color;
+use crate::game_state::*;
+use macroquad::miniquad::{BlendFactor, BlendState, BlendValue, Equation, PipelineParams};
+use macroquad::prelude::*;
+use shared::{
+    components::StatusEffectKind, tile_entity::StaticTile, types::*, MechInteriorCoordinates,
+    LIGHT_ALARM_FLASH, LIGHT_AMBIENT, LIGHT_DAYLIGHT_COLOR, LIGHT_EMERGENCY_COLOR,
+    LIGHT_EMERGENCY_PULSE, LIGHT_EMERGENCY_RADIUS, LIGHT_FLICKER_DURATION, LIGHT_LOW_POWER_DIM,
+    LIGHT_LOW_POWER_HEALTH, LIGHT_STATION_RADIUS, LIGHT_TEXTURE_SIZE, LIGHT_WINDOW_RADIUS,
+    MECH_SIZE_TILES, TILE_SIZE,
+};
+use std::collections::HashMap;
+
+/// A round light in world space
+#[derive(Debug, Clone, Copy)]
+pub struct LightSource {
+    pub position: WorldPos,
+    /// Pixels to where it fades out
+    pub radius: f32,
+    /// Color at full brightness, already scaled by the light's strength
+    pub color: Color,
+}
+
+impl LightSource {
+    /// How strongly this light reaches `pos`: 1 at its center, 0 at its radius
+    fn falloff(&self, pos: WorldPos) -> f32 {
+        let distance = self.position.distance_to(pos) / self.radius;
+        (1.0 - distance).max(0.0).powi(2)
+    }
+}
+
+/// How lit a spot is, from the ambient level to fully lit
+pub fn light_level(lights: &[LightSource], pos: WorldPos) -> f32 {
+    let lit: f32 = lights
+        .iter()
+        .map(|light| light.falloff(pos) * (light.color.r + light.color.g + light.color.b) / 3.0)
+        .sum();
+    (LIGHT_AMBIENT + lit).min(1.0)
+}
+
+/// Whether a mech's main power is out or failing
+fn low_power(game_state: &GameState, mech: &MechState) -> bool {
+    let emp = game_state
+        .status_effects
+        .get(&mech.id)
+        .is_some_and(|effects| effects.iter().any(|e| e.kind == StatusEffectKind::Emp));
+    emp || (mech.health as f32) < mech.max_health as f32 * LIGHT_LOW_POWER_HEALTH
+}
+
+/// Brightness of a mech's powered lights right now, stuttering after a hit
+fn flicker(game_state: &GameState, mech_id: MechId, time: f64) -> f32 {
+    let Some(&remaining) = game_state.light_flicker.get(&mech_id) else {
+        return 1.0;
+    };
+    let settle = 1.0 - remaining / LIGHT_FLICKER_DURATION;
+    let noise = ((time * 53.0).sin() * (time * 31.0).sin()).abs() as f32;
+    (settle + (1.0 - settle) * noise).clamp(0.0, 1.0)
+}
+
+/// Every light on the ground floor of `mech`, where it is in the world now
+pub fn mech_lights(game_state: &GameState, mech: &MechState, time: f64) -> Vec<LightSource> {
+    let Some(floor_map) = game_state.floor_manager.get_floor(mech.id, 0) else {
+        return Vec::new();
+    };
+    // Interior tiles follow the mech's smooth position, not its tile
+    let offset_x = mech.world_position.x - mech.position.to_world().x;
+    let offset_y = mech.world_position.y - mech.position.to_world().y;
+    let tile_center = |interior_pos: TilePos| {
+        let world = MechInteriorCoordinates::interior_to_world(mech.position, 0, interior_pos)
+            .to_world_center();
+        WorldPos::new(world.x + offset_x, world.y + offset_y)
+    };
+
+    let low_power = low_power(game_state, mech);
+    let power =
+        flicker(game_state, mech.id, time) * if low_power { LIGHT_LOW_POWER_DIM } else { 1.0 };
+    let mut lights = Vec::new();
+
+    // One light per station, at the middle of the tiles it covers
+    let mut stations: HashMap<_, (f32, f32, f32)> = HashMap::new();
+    for (interior_pos, station_id) in &floor_map.multi_tile_stations {
+        let center = tile_center(*interior_pos);
+        let sum = stations.entry(*station_id).or_default();
+        *sum = (sum.0 + center.x, sum.1 + center.y, sum.2 + 1.0);
+    }
+    let mech_stations = game_state.floor_manager.get_mech_stations(mech.id);
+    for (station_id, (x, y, count)) in stations {
+        let Some(station) = mech_stations.and_then(|stations| stations.get(&station_id)) else {
+            continue;
+        };
+        let color = get_station_color(station.station_type);
+        lights.push(LightSource {
+            position: WorldPos::new(x / count, y / count),
+            radius: LIGHT_STATION_RADIUS * TILE_SIZE,
+            color: Color::new(color.r * power, color.g * power, color.b * power, 1.0),
+        });
+    }
+
+    let sun = game_state.environment.daylight();
+    if sun > 0.0 {
+        let (r, g, b) = LIGHT_DAYLIGHT_COLOR;
+        for (interior_pos, tile) in &floor_map.static_tiles {
+            if matches!(
+                tile,
+                StaticTile::Window { .. } | StaticTile::ReinforcedWindow { .. }
+            ) {
+                lights.push(LightSource {
+                    position: tile_center(*interior_pos),
+                    radius: LIGHT_WINDOW_RADIUS * TILE_SIZE * sun.sqrt(),
+                    color: Color::new(r * sun, g * sun, b * sun, 1.0),
+                });
+            }
+        }
+    }
+
+    let alarm = game_state.mech_alarms.contains_key(&mech.id);
+    if low_power || alarm {
+        // Battery lamps in the corners, which don't care about hits, and
+        // strobe while boarders are aboard
+        let pulse = if alarm {
+            if (time * LIGHT_ALARM_FLASH as f64).fract() < 0.5 {
+                1.0
+            } else {
+                0.0
+            }
+        } else {
+            0.6 + 0.4 * (time as f32 * LIGHT_EMERGENCY_PULSE * std::f32::consts::TAU).sin()
+        };
+        let (r, g, b) = LIGHT_EMERGENCY_COLOR;
+        let size = MECH_SIZE_TILES as f32 * TILE_SIZE;
+        let inset = TILE_SIZE * 1.5;
+        for (x, y) in [
+            (inset, inset),
+            (size - inset, inset),
+            (inset, size - inset),
+            (size - inset, size - inset),
+        ] {
+            lights.push(LightSource {
+                position: WorldPos::new(mech.world_position.x + x, mech.world_position.y + y),
+                radius: LIGHT_EMERGENCY_RADIUS * TILE_SIZE,
+                color: Color::new(r * pulse, g * pulse, b * pulse, 1.0),
+            });
+        }
+    }
+
+    lights
+}
+
+const LIGHT_VERTEX: &str = r#"#version 100
+attribute vec3 position;
+attribute vec2 texcoord;
+attribute vec4 color0;
+
+varying lowp vec2 uv;
+varying lowp vec4 color;
+
+uniform mat4 Model;
+uniform mat4 Projection;
+
+void main() {
+    gl_Position = Projection * Model * vec4(position, 1);
+    color = color0 / 255.0;
+    uv = texcoord;
+}"#;
+
+const LIGHT_FRAGMENT: &str = r#"#version 100
+varying lowp vec4 color;
+varying lowp vec2 uv;
+
+uniform sampler2D Texture;
+
+void main() {
+    gl_FragColor = color * texture2D(Texture, uv);
+}"#;
+
+/// The render target the darkness is drawn into, with the blend modes for
+/// adding lights onto it and multiplying it over the world
+struct LightMap {
+    target: RenderTarget,
+    size: (u32, u32),
+    additive: Material,
+    multiply: Material,
+    light_texture: Texture2D,
+}
+
+impl LightMap {
+    fn new() -> Option<Self> {
+        let material = |color_blend| {
+            load_material(
+                ShaderSource::Glsl {
+                    vertex: LIGHT_VERTEX,
+                    fragment: LIGHT_FRAGMENT,
+                },
+                MaterialParams {
+                    pipeline_params: PipelineParams {
+                        color_blend: Some(color_blend),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+            )
+        };
+        let additive = material(BlendState::new(
+            Equation::Add,
+            BlendFactor::One,
+            BlendFactor::One,
+        ));
+        let multiply = material(BlendState::new(
+            Equation::Add,
+            BlendFactor::Zero,
+            BlendFactor::Value(BlendValue::SourceColor),
+        ));
+        let (additive, multiply) = match (additive, multiply) {
+            (Ok(additive), Ok(multiply)) => (additive, multiply),
+            (Err(e), _) | (_, Err(e)) => {
+                warn!("Falling back to low quality lighting: {}", e);
+                return None;
+            }
+        };
+
+        // White in the middle fading to black at the edge; the additive
+        // blend ignores alpha, so the falloff lives in the color
+        let size = LIGHT_TEXTURE_SIZE;
+        let mut image = Image::gen_image_color(size, size, BLACK);
+        let half = size as f32 / 2.0;
+        for y in 0..size as u32 {
+            for x in 0..size as u32 {
+                let distance = Vec2::new(x as f32 + 0.5 - half, y as f32 + 0.5 - half).length();
+                let value = (1.0 - distance / half).max(0.0).powi(2);
+                image.set_pixel(x, y, Color::new(value, value, value, 1.0));
+            }
+        }
+        let light_texture = Texture2D::from_image(&image);
+        light_texture.set_filter(FilterMode::Linear);
+
+        let size = (screen_width() as u32, screen_height() as u32);
+        Some(Self {
+            target: Self::target(size),
+            size,
+            additive,
+            multiply,
+            light_texture,
+        })
+    }
+
+    fn target((width, height): (u32, u32)) -> RenderTarget {
+        let target = render_target(width.max(1), height.max(1));
+        target.texture.set_filter(FilterMode::Linear);
+        target
+    }
+
+    fn render(
+        &mut self,
+        game_state: &GameState,
+        world_camera: &Camera2D,
+        cam_x: f32,
+        cam_y: f32,
+        time: f64,
+    ) {
+        // Match whatever the world is drawn into, so a lowered render scale
+        // lowers the light map's too
+        let size = match &world_camera.render_target {
+            Some(scene) => (scene.texture.width() as u32, scene.texture.height() as u32),
+            None => (screen_width() as u32, screen_height() as u32),
+        };
+        if size != self.size {
+            self.target = Self::target(size);
+            self.size = size;
+        }
+
+        set_camera(&render_scale::onto(
+            game_state.camera.world_camera(),
+            Some(self.target.clone()),
+        ));
+        // White leaves the world outside mechs as it is
+        clear_background(WHITE);
+
+        let mech_size = MECH_SIZE_TILES as f32 * TILE_SIZE;
+        let ambient = Color::new(LIGHT_AMBIENT, LIGHT_AMBIENT, LIGHT_AMBIENT, 1.0);
+        let mut lights = Vec::new();
+        for mech in game_state.mechs.values() {
+            if game_state.floor_manager.get_floor(mech.id, 0).is_none() {
+                continue;
+            }
+            draw_rectangle(
+                cam_x + mech.world_position.x,
+                cam_y + mech.world_position.y,
+                mech_size,
+                mech_size,
+                ambient,
+            );
+            lights.extend(mech_lights(game_state, mech, time));
+        }
+
+        gl_use_material(&self.additive);
+        for light in &lights {
+            draw_texture_ex(
+                &self.light_texture,
+                cam_x + light.position.x - light.radius,
+                cam_y + light.position.y - light.radius,
+                light.color,
+                DrawTextureParams {
+                    dest_size: Some(vec2(light.radius * 2.0, light.radius * 2.0)),
+                    ..Default::default()
+                },
+            );
+        }
+
+        render_scale::set_screen_camera(world_camera.render_target.clone());
+        gl_use_material(&self.multiply);
+        draw_texture_ex(
+            &self.target.texture,
+            0.0,
+            0.0,
+            WHITE,
+            DrawTextureParams {
+                dest_size: Some(vec2(screen_width(), screen_height())),
+                ..Default::default()
+            },
+        );
+        gl_use_default_material();
+        set_camera(world_camera);
+    }
+}
+
+/// Shade each interior tile by how lit its center is, without render
+/// targets or custom blending
+fn render_per_tile(game_state: &GameState, cam_x: f32, cam_y: f32, time: f64) {
+    for mech in game_state.mechs.values() {
+        let Some(floor_map) = game_state.floor_manager.get_floor(mech.id, 0) else {
+            continue;
+        };
+        let lights = mech_lights(game_state, mech, time);
+        let offset_x = mech.world_position.x - mech.position.to_world().x;
+        let offset_y = mech.world_position.y - mech.position.to_world().y;
+
+        for interior_pos in floor_map.static_tiles.keys() {
+            let world = MechInteriorCoordinates::interior_to_world(mech.position, 0, *interior_pos)
+                .to_world();
+            let x = world.x + offset_x;
+            let y = world.y + offset_y;
+            let center = WorldPos::new(x + TILE_SIZE / 2.0, y + TILE_SIZE / 2.0);
+            let darkness = 1.0 - light_level(&lights, center);
+            if darkness > 0.01 {
+                draw_rectangle(
+                    cam_x + x,
+                    cam_y + y,
+                    TILE_SIZE,
+                    TILE_SIZE,
+                    Color::new(0.0, 0.0, 0.0, darkness),
+                );
+            }
+        }
+    }
+}
+
+/// Draws the darkness and light over mech interiors; call with the world
+/// camera set, after the world itself, and pass that camera in
+pub struct LightingRenderer {
+    /// None on WASM, or if the shaders wouldn't build
+    light_map: Option<LightMap>,
+    tried_light_map: bool,
+}
+
+impl LightingRenderer {
+    pub fn new() -> Self {
+        Self {
+            light_map: None,
+            tried_light_map: cfg!(target_arch = "wasm32"),
+        }
+    }
+
+    pub fn render(
+        &mut self,
+        game_state: &GameState,
+        world_camera: &Camera2D,
+        cam_x: f32,
+        cam_y: f32,
+    ) {
+        // Materials need a live GL context, so build them on first use
+        if !self.tried_light_map {
+            self.tried_light_map = true;
+            self.light_map = LightMap::new();
+        }
+
+        let time = get_time();
+        match self.light_map.as_mut() {
+            Some(light_map) => light_map.render(game_state, world_camera, cam_x, cam_y, time),
+            None => render_per_tile(game_state, cam_x, cam_y, time),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_light_level_fades_to_ambient() {
+        let light = LightSource {
+            position: WorldPos::new(0.0, 0.0),
+            radius: 100.0,
+            color: WHITE,
+        };
+        assert_eq!(light_level(&[light], WorldPos::new(0.0, 0.0)), 1.0);
+        let halfway = light_level(&[light], WorldPos::new(50.0, 0.0));
+        assert!(halfway > LIGHT_AMBIENT && halfway < 1.0);
+        assert_eq!(
+            light_level(&[light], WorldPos::new(200.0, 0.0)),
+            LIGHT_AMBIENT
+        );
+    }
+}
@@ -1,8 +1,10 @@
+use super::iff;
+use super::sprites::{draw_player, SpriteSheet};
 use super::utils::*;
 use crate::game_state::*;
 use crate::vision::{ClientVisionSystem, FogOfWarRenderer};
 use macroquad::prelude::*;
-use shared::{constants::*, types::*, MechInteriorCoordinates};
+use shared::{balance::BARRICADE_MAX_HEALTH, constants::*, types::*, MechInteriorCoordinates};
 use uuid::Uuid;
 
 #[cfg(feature = "profiling")]
@@ -109,15 +111,24 @@ pub fn render_mech_interior_with_vision(
 
                     draw_rectangle(tile_x, tile_y, TILE_SIZE, TILE_SIZE, final_color);
 
-                    // Draw station border to indicate it's interactive
-                    let mut border_color = WHITE;
+                    // Draw station border to indicate it's interactive, colored by tier
+                    let level = game_state
+                        .stations
+                        .get(station_id)
+                        .map_or(1, |station| station.upgrade_level);
+                    let mut border_color = get_station_tier_color(level);
                     if let Some(_vision) = vision_system {
-                        border_color = FogOfWarRenderer::apply_fog_to_color(WHITE, visibility);
+                        border_color =
+                            FogOfWarRenderer::apply_fog_to_color(border_color, visibility);
                     }
                     draw_rectangle_lines(tile_x, tile_y, TILE_SIZE, TILE_SIZE, 2.0, border_color);
+                    draw_station_tier_badge(tile_x, tile_y, TILE_SIZE, level, border_color);
                 }
             }
         }
+
+        render_fires_on_floor(game_state, mech, current_floor, cam_x, cam_y, vision_system);
+        render_barricades_on_floor(game_state, mech, current_floor, cam_x, cam_y, vision_system);
     }
 
     // Check for interior tiles from server data (hybrid system)
@@ -151,6 +162,100 @@ pub fn render_mech_interior_with_vision(
     }
 }
 
+/// Draw flickering fires on one floor of a mech; `origin_x/y` is the screen
+/// offset already applied to the floor's tiles
+pub fn render_fires_on_floor(
+    game_state: &GameState,
+    mech: &MechState,
+    floor: u8,
+    origin_x: f32,
+    origin_y: f32,
+    vision_system: Option<&ClientVisionSystem>,
+) {
+    let Some(fires) = game_state.mech_fires.get(&mech.id) else {
+        return;
+    };
+    let time = get_time() as f32;
+
+    for fire in fires.iter().filter(|fire| fire.floor == floor) {
+        let mut visibility = 1.0;
+        if let Some(vision) = vision_system {
+            visibility = vision.get_interior_visibility(mech.id, floor, fire.position);
+            if visibility < 0.05 {
+                continue;
+            }
+        }
+
+        let world_coords =
+            MechInteriorCoordinates::interior_to_world(mech.position, floor, fire.position)
+                .to_world();
+        let center_x = origin_x + world_coords.x + TILE_SIZE / 2.0;
+        let center_y = origin_y + world_coords.y + TILE_SIZE / 2.0;
+
+        // Offset each tile's flicker so a spreading fire doesn't pulse in unison
+        let phase = (fire.position.x * 7 + fire.position.y * 13) as f32;
+        let flicker = 0.85 + 0.15 * (time * 12.0 + phase).sin();
+        let radius = TILE_SIZE * 0.45 * (0.5 + 0.5 * fire.intensity) * flicker;
+
+        let outer = Color::new(1.0, 0.35, 0.05, 0.75 * visibility);
+        let inner = Color::new(1.0, 0.85, 0.2, 0.9 * visibility);
+        draw_circle(center_x, center_y, radius, outer);
+        draw_circle(center_x, center_y - radius * 0.2, radius * 0.5, inner);
+    }
+}
+
+/// Draw the barricades on one floor of a mech as planked crates edged in
+/// their team's colour, with a health bar once they've been hit
+pub fn render_barricades_on_floor(
+    game_state: &GameState,
+    mech: &MechState,
+    floor: u8,
+    origin_x: f32,
+    origin_y: f32,
+    vision_system: Option<&ClientVisionSystem>,
+) {
+    let Some(floor_map) = game_state.floor_manager.get_floor(mech.id, floor) else {
+        return;
+    };
+
+    for (position, barricade) in &floor_map.barricades {
+        let mut visibility = 1.0;
+        if let Some(vision) = vision_system {
+            visibility = vision.get_interior_visibility(mech.id, floor, *position);
+            if visibility < 0.05 {
+                continue;
+            }
+        }
+
+        let world_coords =
+            MechInteriorCoordinates::interior_to_world(mech.position, floor, *position).to_world();
+        let inset = TILE_SIZE * 0.1;
+        let size = TILE_SIZE - inset * 2.0;
+        let left = origin_x + world_coords.x + inset;
+        let top = origin_y + world_coords.y + inset;
+        let (right, bottom) = (left + size, top + size);
+
+        let wood =
+            FogOfWarRenderer::apply_fog_to_color(Color::new(0.45, 0.3, 0.15, 1.0), visibility);
+        let plank =
+            FogOfWarRenderer::apply_fog_to_color(Color::new(0.3, 0.2, 0.1, 1.0), visibility);
+        let edge = FogOfWarRenderer::apply_fog_to_color(
+            get_player_color(barricade.team, game_state.accessibility.palette()),
+            visibility,
+        );
+        draw_rectangle(left, top, size, size, wood);
+        draw_line(left, top, right, bottom, 3.0, plank);
+        draw_line(right, top, left, bottom, 3.0, plank);
+        draw_rectangle_lines(left, top, size, size, 2.0, edge);
+
+        if barricade.health < BARRICADE_MAX_HEALTH {
+            let share = barricade.health as f32 / BARRICADE_MAX_HEALTH as f32;
+            draw_rectangle(left, top - inset, size, 3.0, DARKGRAY);
+            draw_rectangle(left, top - inset, size * share, 3.0, ORANGE);
+        }
+    }
+}
+
 pub fn render_stations_on_floor(game_state: &GameState, mech_id: Uuid, floor: u8) {
     render_stations_on_floor_with_vision(game_state, mech_id, floor, 0.0, 0.0, None);
 }
@@ -188,26 +293,16 @@ pub fn render_stations_on_floor_with_vision(
                     text_color = FogOfWarRenderer::apply_fog_to_color(text_color, visibility);
                 }
 
-                let label = get_station_label(station.station_type);
+                let label = game_state
+                    .station_registry
+                    .get_definition(station.station_type)
+                    .map_or("?", |definition| definition.short_name.as_str());
                 draw_text(label, x, y, 16.0, text_color);
             }
         }
     }
 }
 
-fn get_station_label(station_type: StationType) -> &'static str {
-    match station_type {
-        StationType::WeaponLaser => "LASER",
-        StationType::WeaponProjectile => "GUN",
-        StationType::Engine => "ENGINE",
-        StationType::Shield => "SHIELD",
-        StationType::Repair => "REPAIR",
-        StationType::Electrical => "ELEC",
-        StationType::Upgrade => "UPGRADE",
-        StationType::Pilot => "PILOT",
-    }
-}
-
 pub fn render_players_on_floor(
     game_state: &GameState,
     mech_id: Uuid,
@@ -215,7 +310,7 @@ pub fn render_players_on_floor(
     cam_x: f32,
     cam_y: f32,
 ) {
-    render_players_on_floor_with_vision(game_state, mech_id, floor, cam_x, cam_y, None);
+    render_players_on_floor_with_vision(game_state, mech_id, floor, cam_x, cam_y, None, None);
 }
 
 pub fn render_players_on_floor_with_vision(
@@ -225,6 +320,7 @@ pub fn render_players_on_floor_with_vision(
     cam_x: f32,
     cam_y: f32,
     vision_system: Option<&ClientVisionSystem>,
+    sprites: Option<&SpriteSheet>,
 ) {
     for player in game_state.players.values() {
         if let PlayerLocation::InsideMech {
@@ -244,13 +340,14 @@ pub fn render_players_on_floor_with_vision(
                     );
                     let world_coords = world_pos.to_world();
 
-                    let mut color = get_player_color(player.team);
+                    let mut color =
+                        get_player_color(player.team, game_state.accessibility.palette());
                     let mut text_color = WHITE;
+                    let mut visibility = 1.0;
 
                     // Check visibility
                     if let Some(vision) = vision_system {
-                        let visibility =
-                            vision.get_interior_visibility(mech_id, floor, interior_pos);
+                        visibility = vision.get_interior_visibility(mech_id, floor, interior_pos);
                         if visibility < 0.1 {
                             continue; // Don't render invisible players
                         }
@@ -258,16 +355,28 @@ pub fn render_players_on_floor_with_vision(
                         text_color = FogOfWarRenderer::apply_fog_to_color(text_color, visibility);
                     }
 
-                    draw_circle(
+                    draw_player(
+                        sprites,
+                        game_state.animations.get(&player._id),
+                        player.facing,
                         cam_x + world_coords.x + TILE_SIZE / 2.0,
                         cam_y + world_coords.y + TILE_SIZE / 2.0,
                         TILE_SIZE / 2.5,
                         color,
                     );
+                    iff::draw_player_mark(
+                        game_state,
+                        player._id,
+                        player.team,
+                        cam_x + world_coords.x + TILE_SIZE / 2.0,
+                        cam_y + world_coords.y + TILE_SIZE / 2.0,
+                        TILE_SIZE / 2.5,
+                        visibility,
+                    );
 
                     // Draw player name
                     draw_text(
-                        &player.name,
+                        &player.label(),
                         cam_x + world_coords.x - 20.0,
                         cam_y + world_coords.y - 5.0,
                         14.0,
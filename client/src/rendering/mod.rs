@@ -1,15 +1,26 @@
+mod annotations;
 pub mod camera;
 mod effects;
+mod lighting;
 pub mod hybrid_tiles;
+mod iff;
 mod mech_interior;
 mod pilot_station;
 pub mod primitives;
+mod radar_scope;
+pub mod render_scale;
+mod scanner;
+mod schematic;
 pub mod spatial_debug;
+mod sprites;
+mod turret_station;
 mod ui;
 mod utils;
+mod weather;
 mod world;
 
 use crate::game_state::*;
+use macroquad::prelude::{set_camera, set_default_camera};
 use shared::types::*;
 
 #[derive(Clone)]
@@ -21,6 +32,7 @@ pub struct RenderFlags {
     pub render_effects: bool,
     pub render_ui: bool,
     pub render_fog: bool,
+    pub render_lighting: bool,
     pub render_tiles: bool,
     pub render_stations: bool,
 
@@ -31,6 +43,7 @@ pub struct RenderFlags {
     pub show_door_positions: bool,
     pub show_coordinate_grid: bool,
     pub show_floor_offsets: bool,
+    pub show_hitboxes: bool,
 }
 
 impl Default for RenderFlags {
@@ -43,6 +56,7 @@ impl Default for RenderFlags {
             render_effects: true,
             render_ui: true,
             render_fog: true,
+            render_lighting: true,
             render_tiles: true,
             render_stations: true,
 
@@ -52,6 +66,7 @@ impl Default for RenderFlags {
             show_door_positions: false,
             show_coordinate_grid: false,
             show_floor_offsets: false,
+            show_hitboxes: false,
         }
     }
 }
@@ -67,24 +82,57 @@ pub use pilot_station::{is_pilot_window_clicked, PilotWindowClick};
 pub struct Renderer {
     // Could store textures and other rendering resources here
     pub spatial_debug: spatial_debug::SpatialDebugRenderer,
+    lighting: lighting::LightingRenderer,
+    player_sprites: Option<sprites::SpriteSheet>,
+    resolution: render_scale::DynamicResolution,
+    scene: render_scale::SceneTarget,
 }
 
 impl Renderer {
     pub fn new() -> Self {
         Self {
             spatial_debug: spatial_debug::SpatialDebugRenderer::new(),
+            lighting: lighting::LightingRenderer::new(),
+            player_sprites: None,
+            resolution: render_scale::DynamicResolution::new(),
+            scene: render_scale::SceneTarget::new(),
         }
     }
 
-    pub fn render_with_flags(&self, game_state: &GameState, flags: &RenderFlags) {
+    /// Pick this frame's render scale from the graphics settings and, in
+    /// auto mode, the measured frame rate
+    pub fn update_render_scale(
+        &mut self,
+        settings: &render_scale::GraphicsSettings,
+        fps: f32,
+        frame_time: f32,
+    ) {
+        self.resolution.update(settings, fps, frame_time);
+    }
+
+    /// Share of the window's resolution the world is drawn at
+    pub fn render_scale(&self) -> f32 {
+        self.resolution.scale()
+    }
+
+    /// Load the player sprite sheet; players are drawn as shapes without it
+    pub async fn load_sprites(&mut self) {
+        self.player_sprites = sprites::SpriteSheet::load(shared::PLAYER_SPRITE_SHEET).await;
+    }
+
+    pub fn render_with_flags(&mut self, game_state: &GameState, flags: &RenderFlags) {
         #[cfg(feature = "profiling")]
         let _renderer_span = info_span!("renderer").entered();
         #[cfg(feature = "profiling")]
         scope!("renderer");
 
-        // Apply camera transform
-        let cam_x = -game_state.camera_offset.0;
-        let cam_y = -game_state.camera_offset.1;
+        // World layers draw at the camera's offset and get zoomed by its
+        // macroquad camera, into the scene target below full render scale;
+        // the HUD goes on top with the default camera
+        let (cam_x, cam_y) = game_state.camera.world_offset();
+        let scene = self.scene.begin(self.resolution.scale());
+        let world_camera = render_scale::onto(game_state.camera.world_camera(), scene.clone());
+        set_camera(&world_camera);
 
         // Only use vision system if fog of war is enabled
         let vision_system = if flags.render_fog {
@@ -108,9 +156,18 @@ impl Renderer {
                     cam_y,
                     vision_system,
                     flags,
+                    self.player_sprites.as_ref(),
                 );
             }
 
+            // Darken mech interiors around their lights
+            if flags.render_lighting && flags.render_tiles {
+                #[cfg(feature = "profiling")]
+                scope!("lighting");
+                self.lighting
+                    .render(game_state, &world_camera, cam_x, cam_y);
+            }
+
             // // ALWAYS render all mech interiors in their world positions
             // if flags.render_tiles || flags.render_stations || flags.render_players {
             //     for mech in game_state.mechs.values() {
@@ -166,7 +223,26 @@ impl Renderer {
                 scope!("effects");
                 effects::render_effects(game_state, cam_x, cam_y);
             }
+
+            // Turret sight cone, radar contacts and scanner pings mark things
+            // out in the world
+            if flags.render_ui {
+                turret_station::render_turret_overlay(game_state, cam_x, cam_y);
+                radar_scope::render_radar_contacts(game_state, cam_x, cam_y);
+                scanner::render_scanner_ping(game_state, cam_x, cam_y);
+                annotations::render_annotations(game_state, cam_x, cam_y);
+            }
+        }
+        render_scale::set_screen_camera(scene);
+
+        // Night and weather cover the world but not the HUD
+        if flags.render_lighting {
+            #[cfg(feature = "profiling")]
+            scope!("weather");
+
+            weather::render_environment(game_state);
         }
+        self.scene.present();
 
         // Render UI overlay
         if flags.render_ui {
@@ -174,6 +250,7 @@ impl Renderer {
             scope!("ui");
 
             ui::render_ui(game_state);
+            iff::render_offscreen_enemies(game_state, vision_system);
         }
 
         // Render pilot station window if open
@@ -184,11 +261,30 @@ impl Renderer {
             pilot_station::render_pilot_station_window(game_state);
         }
 
+        // Render the turret crosshair while we crew a turret
+        if flags.render_ui {
+            #[cfg(feature = "profiling")]
+            scope!("turret_station");
+
+            turret_station::render_turret_hud(game_state);
+        }
+
+        // Render the radar scope while we crew the radar
+        if flags.render_ui {
+            #[cfg(feature = "profiling")]
+            scope!("radar_scope");
+
+            radar_scope::render_radar_scope(game_state);
+        }
+
         // Render spatial debug overlays (if enabled in debug overlay)
         if flags.spatial_debug_enabled {
             #[cfg(feature = "profiling")]
             scope!("spatial_debug");
 
+            // These mark world positions, so they share the world's zoom
+            set_camera(&game_state.camera.world_camera());
+
             if flags.show_coordinate_grid {
                 self.spatial_debug.render_coordinate_grid(cam_x, cam_y);
             }
@@ -208,6 +304,10 @@ impl Renderer {
                     .render_floor_offsets(game_state, cam_x, cam_y);
             }
 
+            if flags.show_hitboxes {
+                spatial_debug::render_collision_shapes(game_state, cam_x, cam_y);
+            }
+
             // Render coordinate mapping if player is inside a mech and coordinate transforms are enabled
             if flags.show_coordinate_transforms {
                 if let PlayerLocation::InsideMech {
@@ -221,6 +321,7 @@ impl Renderer {
                         .render_coordinate_mapping(game_state, mech_id, world_pos, pos.floor, cam_x, cam_y);
                 }
             }
+            set_default_camera();
         }
     }
 }
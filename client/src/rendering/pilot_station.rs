@@ -1,11 +1,22 @@
 use super::utils::*;
 use crate::game_state::GameState;
 use macroquad::prelude::*;
-use shared::{constants::*, types::*};
+use shared::{
+    balance::{MECH_FUEL_CAPACITY, MECH_HULL_MASS, MECH_REVERSE_THROTTLE},
+    components::StructureKind,
+    constants::*,
+    render_constants::FUEL_LOW_SHARE,
+    types::*,
+    MechDrive, MechMass, MechPerformance,
+};
 
 const PILOT_WINDOW_WIDTH: f32 = 800.0;
 const PILOT_WINDOW_HEIGHT: f32 = 600.0;
 const MAP_ZOOM: f32 = 0.25; // Show mechs at 1/4 scale
+const GAUGE_PANEL_WIDTH: f32 = 200.0;
+const GAUGE_PANEL_HEIGHT: f32 = 120.0;
+const LOADOUT_PANEL_WIDTH: f32 = 170.0;
+const MOMENTUM_LOOKAHEAD: f32 = 3.0; // seconds of travel the momentum line projects
 
 pub fn render_pilot_station_window(game_state: &GameState) {
     if !game_state.ui_state.pilot_station_open {
@@ -82,10 +93,27 @@ pub fn render_pilot_station_window(game_state: &GameState) {
     // Render area view
     render_area_view(game_state, map_x, map_y, map_width, map_height);
 
+    if let Some(mech) = game_state
+        .ui_state
+        .operating_mech_id
+        .and_then(|id| game_state.mechs.get(&id))
+    {
+        let performance = MechPerformance::new(mech.upgrades.engine_level, mech.mass.total());
+        let gauges_x = map_x + map_width - GAUGE_PANEL_WIDTH - 10.0;
+        let gauges_y = map_y + map_height - GAUGE_PANEL_HEIGHT - 10.0;
+        render_drive_gauges(&mech.drive, &performance, mech.fuel, gauges_x, gauges_y);
+        render_loadout_panel(
+            &mech.mass,
+            &performance,
+            gauges_x - LOADOUT_PANEL_WIDTH - 10.0,
+            gauges_y,
+        );
+    }
+
     // Draw control instructions at bottom
     let instruction_y = window_y + PILOT_WINDOW_HEIGHT - 50.0;
     draw_text(
-        "WASD - Move Mech | ESC - Exit Pilot Mode",
+        "W/S - Throttle | A/D - Steer | 1 - Stomp | 2 - Brace | ESC - Exit Pilot Mode",
         window_x + 10.0,
         instruction_y,
         16.0,
@@ -123,6 +151,35 @@ fn render_area_view(
             map_x, map_y, map_width, map_height, world_left, world_top, MAP_ZOOM,
         );
 
+        // Base structures: an outlined bay to park in and a small depot crate
+        for structure in &game_state.structures {
+            let world = structure.position.to_world_pos();
+            let screen_x = map_x + (world.x - world_left) * MAP_ZOOM;
+            let screen_y = map_y + (world.y - world_top) * MAP_ZOOM;
+            let width = structure.width as f32 * TILE_SIZE * MAP_ZOOM;
+            let height = structure.height as f32 * TILE_SIZE * MAP_ZOOM;
+
+            // Skip if outside visible area
+            if screen_x + width < map_x
+                || screen_x > map_x + map_width
+                || screen_y + height < map_y
+                || screen_y > map_y + map_height
+            {
+                continue;
+            }
+
+            let color = get_team_color(structure.team, game_state.accessibility.palette());
+            match structure.kind {
+                StructureKind::RepairBay => {
+                    draw_rectangle_lines(screen_x, screen_y, width, height, 1.0, color);
+                    draw_text("+", screen_x + 2.0, screen_y + 10.0, 14.0, color);
+                }
+                StructureKind::ResupplyDepot => {
+                    draw_rectangle(screen_x, screen_y, width.max(3.0), height.max(3.0), color);
+                }
+            }
+        }
+
         // Draw all mechs
         for (mech_id, other_mech) in &game_state.mechs {
             let screen_x = map_x + (other_mech.world_position.x - world_left) * MAP_ZOOM;
@@ -143,7 +200,7 @@ fn render_area_view(
                 Color::new(0.0, 1.0, 0.0, 0.8)
             } else {
                 // Other mechs
-                get_team_color(other_mech.team)
+                get_team_color(other_mech.team, game_state.accessibility.palette())
             };
 
             // Draw mech
@@ -166,6 +223,33 @@ fn render_area_view(
                 16.0,
                 WHITE,
             );
+
+            // Heading arrow and where our momentum carries us
+            if mech_id == &mech.id {
+                let center = vec2(screen_x + mech_size / 2.0, screen_y + mech_size / 2.0);
+                let facing = vec2(
+                    other_mech.drive.heading.cos(),
+                    other_mech.drive.heading.sin(),
+                );
+                let (vx, vy) = other_mech.drive.velocity();
+                let momentum = vec2(vx, vy) * TILE_SIZE * MAP_ZOOM * MOMENTUM_LOOKAHEAD;
+                draw_line(
+                    center.x,
+                    center.y,
+                    center.x + momentum.x,
+                    center.y + momentum.y,
+                    2.0,
+                    YELLOW,
+                );
+                let tip = center + facing * mech_size * 0.75;
+                let side = vec2(-facing.y, facing.x) * 5.0;
+                draw_triangle(
+                    tip,
+                    tip - facing * 10.0 + side,
+                    tip - facing * 10.0 - side,
+                    WHITE,
+                );
+            }
         }
 
         // Draw players in the world
@@ -183,7 +267,8 @@ fn render_area_view(
                     continue;
                 }
 
-                let player_color = get_player_color(player.team);
+                let player_color =
+                    get_player_color(player.team, game_state.accessibility.palette());
                 draw_circle(screen_x, screen_y, 3.0, player_color);
             }
         }
@@ -203,12 +288,188 @@ fn render_area_view(
                 continue;
             }
 
-            let resource_color = get_resource_color(resource.resource_type);
+            let resource_color =
+                get_resource_color(resource.resource_type, game_state.accessibility.palette());
             draw_circle(screen_x, screen_y, 2.0, resource_color);
         }
     }
 }
 
+/// Throttle lever, speed, fuel and compass for the mech being driven
+fn render_drive_gauges(
+    drive: &MechDrive,
+    performance: &MechPerformance,
+    fuel: f32,
+    x: f32,
+    y: f32,
+) {
+    draw_rectangle(
+        x,
+        y,
+        GAUGE_PANEL_WIDTH,
+        GAUGE_PANEL_HEIGHT,
+        Color::new(0.0, 0.0, 0.0, 0.75),
+    );
+    draw_rectangle_lines(x, y, GAUGE_PANEL_WIDTH, GAUGE_PANEL_HEIGHT, 1.0, DARKGREEN);
+
+    // Throttle lever: forward fills up from the zero line, reverse fills down
+    let bar_x = x + 12.0;
+    let bar_top = y + 12.0;
+    let bar_width = 14.0;
+    let bar_height = GAUGE_PANEL_HEIGHT - 24.0;
+    let scale = bar_height / (1.0 + MECH_REVERSE_THROTTLE);
+    let zero_y = bar_top + scale;
+    let throttle_y = zero_y - drive.throttle * scale;
+    let throttle_color = if drive.throttle >= 0.0 { GREEN } else { ORANGE };
+    draw_rectangle(
+        bar_x,
+        throttle_y.min(zero_y),
+        bar_width,
+        (zero_y - throttle_y).abs(),
+        throttle_color,
+    );
+    draw_rectangle_lines(bar_x, bar_top, bar_width, bar_height, 1.0, GRAY);
+    draw_line(
+        bar_x - 3.0,
+        zero_y,
+        bar_x + bar_width + 3.0,
+        zero_y,
+        1.0,
+        WHITE,
+    );
+
+    // Speed marker lags behind the lever while the mech builds momentum
+    let speed_y = zero_y - drive.speed / performance.max_speed * scale;
+    let marker_x = bar_x + bar_width + 2.0;
+    draw_triangle(
+        vec2(marker_x, speed_y),
+        vec2(marker_x + 7.0, speed_y - 5.0),
+        vec2(marker_x + 7.0, speed_y + 5.0),
+        YELLOW,
+    );
+
+    // Fuel tank, draining from the top
+    let fuel_x = x + GAUGE_PANEL_WIDTH - 18.0;
+    let fuel_share = (fuel / MECH_FUEL_CAPACITY).clamp(0.0, 1.0);
+    let fuel_color = if fuel_share > FUEL_LOW_SHARE {
+        GREEN
+    } else if fuel > 0.0 {
+        ORANGE
+    } else {
+        RED
+    };
+    draw_rectangle(
+        fuel_x,
+        bar_top + bar_height * (1.0 - fuel_share),
+        8.0,
+        bar_height * fuel_share,
+        fuel_color,
+    );
+    draw_rectangle_lines(fuel_x, bar_top, 8.0, bar_height, 1.0, GRAY);
+
+    // Compass with north up
+    let compass = vec2(x + 80.0, y + GAUGE_PANEL_HEIGHT / 2.0);
+    let radius = 32.0;
+    draw_circle_lines(compass.x, compass.y, radius, 1.0, GRAY);
+    draw_text(
+        "N",
+        compass.x - 4.0,
+        compass.y - radius + 12.0,
+        14.0,
+        LIGHTGRAY,
+    );
+    let facing = vec2(drive.heading.cos(), drive.heading.sin());
+    let needle = compass + facing * (radius - 4.0);
+    draw_line(compass.x, compass.y, needle.x, needle.y, 3.0, GREEN);
+    draw_circle(compass.x, compass.y, 3.0, GREEN);
+
+    let bearing = (drive.heading.to_degrees() + 90.0).rem_euclid(360.0);
+    let text_x = x + 120.0;
+    draw_text(
+        &format!("THR {:>4.0}%", drive.throttle * 100.0),
+        text_x,
+        y + 35.0,
+        14.0,
+        throttle_color,
+    );
+    draw_text(
+        &format!("SPD {:.1}", drive.speed),
+        text_x,
+        y + 55.0,
+        14.0,
+        YELLOW,
+    );
+    let (max_text, max_color) = if fuel > 0.0 {
+        (format!("MAX {:.1}", performance.max_speed), LIGHTGRAY)
+    } else {
+        ("NO FUEL".to_string(), fuel_color)
+    };
+    draw_text(&max_text, text_x, y + 75.0, 14.0, max_color);
+    draw_text(
+        &format!("HDG {bearing:03.0}"),
+        text_x,
+        y + 95.0,
+        14.0,
+        GREEN,
+    );
+}
+
+/// What the mech weighs, where the weight comes from, and what that leaves
+/// the engine able to do
+fn render_loadout_panel(mass: &MechMass, performance: &MechPerformance, x: f32, y: f32) {
+    draw_rectangle(
+        x,
+        y,
+        LOADOUT_PANEL_WIDTH,
+        GAUGE_PANEL_HEIGHT,
+        Color::new(0.0, 0.0, 0.0, 0.75),
+    );
+    draw_rectangle_lines(
+        x,
+        y,
+        LOADOUT_PANEL_WIDTH,
+        GAUGE_PANEL_HEIGHT,
+        1.0,
+        DARKGREEN,
+    );
+
+    draw_text(
+        &format!("MASS {:.1}t", mass.total()),
+        x + 10.0,
+        y + 20.0,
+        16.0,
+        WHITE,
+    );
+    let parts = [
+        ("Hull", MECH_HULL_MASS),
+        ("Stations", mass.stations),
+        ("Upgrades", mass.upgrades),
+        ("Cargo", mass.cargo),
+        ("Crew", mass.crew),
+    ];
+    for (row, (label, tonnes)) in parts.iter().enumerate() {
+        let row_y = y + 38.0 + row as f32 * 14.0;
+        draw_text(label, x + 10.0, row_y, 14.0, LIGHTGRAY);
+        draw_text(
+            &format!("{tonnes:>5.1}t"),
+            x + 100.0,
+            row_y,
+            14.0,
+            LIGHTGRAY,
+        );
+    }
+    draw_text(
+        &format!(
+            "ACC {:.1}  BRK {:.1}",
+            performance.acceleration, performance.braking
+        ),
+        x + 10.0,
+        y + GAUGE_PANEL_HEIGHT - 10.0,
+        14.0,
+        YELLOW,
+    );
+}
+
 fn draw_grid(
     map_x: f32,
     map_y: f32,
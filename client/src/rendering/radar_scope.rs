@@ -0,0 +1,98 @@
+use super::utils::*;
+use crate::game_state::GameState;
+use macroquad::prelude::*;
+use shared::{render_constants::*, RADAR_RANGE, TILE_SIZE};
+
+/// Radar contacts from our team's last sweep: a ring over each enemy mech it
+/// found. Blips only say something is there, so they are drawn whether or
+/// not the tile is visible.
+pub fn render_radar_contacts(game_state: &GameState, cam_x: f32, cam_y: f32) {
+    for (position, remaining) in &game_state.radar_contacts {
+        let alpha = (remaining / RADAR_BLIP_FADE_TIME).min(1.0);
+        let pulse = 0.8 + 0.2 * (get_time() as f32 * 4.0).sin();
+        draw_circle_lines(
+            cam_x + position.x,
+            cam_y + position.y,
+            RADAR_WORLD_BLIP_RADIUS * pulse,
+            2.0,
+            Color::new(0.2, 1.0, 0.4, alpha),
+        );
+        draw_text(
+            "?",
+            cam_x + position.x - 5.0,
+            cam_y + position.y + 6.0,
+            24.0,
+            Color::new(0.2, 1.0, 0.4, alpha),
+        );
+    }
+}
+
+/// The scope, for whoever is crewing the radar
+pub fn render_radar_scope(game_state: &GameState) {
+    if let Some(mech_id) = game_state.operated_radar_mech() {
+        render_scope(game_state, mech_id);
+    }
+}
+
+/// Circular scope centered on our mech, with the sweep line, range rings,
+/// contacts and how long until the radar can sweep again
+fn render_scope(game_state: &GameState, mech_id: shared::MechId) {
+    let Some(mech) = game_state.mechs.get(&mech_id) else {
+        return;
+    };
+    let radius = RADAR_SIZE / 2.0;
+    let cx = screen_width() - RADAR_OFFSET_X - radius;
+    let cy = RESOURCE_PANEL_OFFSET_Y + RESOURCE_PANEL_HEIGHT + RADAR_OFFSET_Y + radius;
+    let green = Color::new(0.2, 1.0, 0.4, 1.0);
+
+    draw_circle(cx, cy, radius, Color::new(0.0, 0.1, 0.0, 0.8));
+    for ring in 1..=RADAR_GRID_SIZE {
+        let ring_radius = radius * ring as f32 / RADAR_GRID_SIZE as f32;
+        draw_circle_lines(cx, cy, ring_radius, 1.0, Color::new(0.2, 1.0, 0.4, 0.25));
+    }
+    let sweep = get_time() as f32 * RADAR_SWEEP_SPEED;
+    draw_line(
+        cx,
+        cy,
+        cx + sweep.cos() * radius,
+        cy + sweep.sin() * radius,
+        2.0,
+        Color::new(0.2, 1.0, 0.4, 0.6),
+    );
+    draw_circle(cx, cy, 2.0, green);
+
+    // Contacts past the scope's edge sit on the rim
+    let origin = get_mech_center(mech);
+    let scale = radius / (RADAR_RANGE * TILE_SIZE);
+    for (position, remaining) in &game_state.radar_contacts {
+        let (mut dx, mut dy) = (
+            (position.x - origin.x) * scale,
+            (position.y - origin.y) * scale,
+        );
+        let distance = (dx * dx + dy * dy).sqrt();
+        if distance > radius {
+            dx *= radius / distance;
+            dy *= radius / distance;
+        }
+        let alpha = (remaining / RADAR_BLIP_FADE_TIME).min(1.0);
+        draw_circle(
+            cx + dx,
+            cy + dy,
+            RADAR_SCOPE_BLIP_RADIUS,
+            Color::new(1.0, 0.3, 0.3, alpha),
+        );
+    }
+
+    let status = match game_state.radar_cooldowns.get(&mech_id) {
+        Some(remaining) => format!("RADAR - recharging {remaining:.0}s"),
+        None => "RADAR - [1] Sweep".to_string(),
+    };
+    let text_width = measure_text(&status, None, UI_TEXT_FONT_SIZE as u16, 1.0).width;
+    draw_text(
+        &status,
+        cx - text_width / 2.0,
+        cy + radius + UI_TEXT_FONT_SIZE,
+        UI_TEXT_FONT_SIZE,
+        green,
+    );
+}
@@ -0,0 +1,161 @@
+//! Drawing the world at less than the window's resolution.
+//!
+//! Below full scale the world, its lighting, effects and weather are drawn
+//! into an offscreen target a fraction of the window's size, then stretched
+//! over the window before the HUD goes on top, so text and panels stay
+//! sharp. In auto mode the scale steps down while the frame rate is under
+//! the target and creeps back up once there's room to spare.
+
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+use shared::{
+    RENDER_SCALE_ADJUST_INTERVAL, RENDER_SCALE_HEADROOM, RENDER_SCALE_MAX, RENDER_SCALE_MIN,
+    RENDER_SCALE_STEP, RENDER_TARGET_FPS,
+};
+
+/// Graphics options, edited in the debug overlay's settings
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(default)]
+pub struct GraphicsSettings {
+    /// Share of the window's resolution the world is drawn at; in auto mode
+    /// the most it's allowed to reach
+    pub render_scale: f32,
+    pub auto_render_scale: bool,
+    /// Frame rate auto mode tries to hold
+    pub target_fps: u32,
+}
+
+impl Default for GraphicsSettings {
+    fn default() -> Self {
+        Self {
+            render_scale: RENDER_SCALE_MAX,
+            auto_render_scale: false,
+            target_fps: RENDER_TARGET_FPS,
+        }
+    }
+}
+
+/// The scale the world is drawn at this frame
+pub struct DynamicResolution {
+    scale: f32,
+    since_adjust: f32,
+}
+
+impl DynamicResolution {
+    pub fn new() -> Self {
+        Self {
+            scale: RENDER_SCALE_MAX,
+            since_adjust: 0.0,
+        }
+    }
+
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    /// Follow the settings, or in auto mode step towards what `fps` allows
+    pub fn update(&mut self, settings: &GraphicsSettings, fps: f32, frame_time: f32) {
+        let ceiling = settings
+            .render_scale
+            .clamp(RENDER_SCALE_MIN, RENDER_SCALE_MAX);
+        if !settings.auto_render_scale {
+            self.scale = ceiling;
+            self.since_adjust = 0.0;
+            return;
+        }
+
+        self.since_adjust += frame_time;
+        if self.since_adjust < RENDER_SCALE_ADJUST_INTERVAL {
+            self.scale = self.scale.min(ceiling);
+            return;
+        }
+        self.since_adjust = 0.0;
+        let target = settings.target_fps as f32;
+        if fps < target {
+            self.scale -= RENDER_SCALE_STEP;
+        } else if fps > target * RENDER_SCALE_HEADROOM {
+            self.scale += RENDER_SCALE_STEP;
+        }
+        self.scale = self.scale.clamp(RENDER_SCALE_MIN, ceiling);
+    }
+}
+
+/// Go back to drawing in screen space, into `render_target` or onto the
+/// window when there's none
+pub fn set_screen_camera(render_target: Option<RenderTarget>) {
+    match render_target {
+        Some(target) => {
+            let screen = Rect::new(0.0, 0.0, screen_width(), screen_height());
+            set_camera(&onto(Camera2D::from_display_rect(screen), Some(target)));
+        }
+        None => set_default_camera(),
+    }
+}
+
+/// The same view drawn into `render_target` instead, flipped because
+/// render targets are stored bottom row first
+pub fn onto(camera: Camera2D, render_target: Option<RenderTarget>) -> Camera2D {
+    match render_target {
+        Some(target) => Camera2D {
+            zoom: vec2(camera.zoom.x, -camera.zoom.y),
+            render_target: Some(target),
+            ..camera
+        },
+        None => camera,
+    }
+}
+
+/// The offscreen target the world is drawn into below full scale
+pub struct SceneTarget {
+    target: Option<RenderTarget>,
+    size: (u32, u32),
+}
+
+impl SceneTarget {
+    pub fn new() -> Self {
+        Self {
+            target: None,
+            size: (0, 0),
+        }
+    }
+
+    /// Where to draw the world this frame: a target sized for `scale`,
+    /// cleared, or the window itself at full scale
+    pub fn begin(&mut self, scale: f32) -> Option<RenderTarget> {
+        if scale >= RENDER_SCALE_MAX {
+            self.target = None;
+            return None;
+        }
+        let size = (
+            ((screen_width() * scale) as u32).max(1),
+            ((screen_height() * scale) as u32).max(1),
+        );
+        if self.target.is_none() || size != self.size {
+            let target = render_target(size.0, size.1);
+            target.texture.set_filter(FilterMode::Linear);
+            self.target = Some(target);
+            self.size = size;
+        }
+        set_screen_camera(self.target.clone());
+        clear_background(BLACK);
+        self.target.clone()
+    }
+
+    /// Stretch what was drawn over the window and go back to the default
+    /// camera
+    pub fn present(&self) {
+        set_default_camera();
+        if let Some(target) = &self.target {
+            draw_texture_ex(
+                &target.texture,
+                0.0,
+                0.0,
+                WHITE,
+                DrawTextureParams {
+                    dest_size: Some(vec2(screen_width(), screen_height())),
+                    ..Default::default()
+                },
+            );
+        }
+    }
+}
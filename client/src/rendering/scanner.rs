@@ -0,0 +1,71 @@
+use super::utils::*;
+use crate::game_state::GameState;
+use macroquad::prelude::*;
+use shared::{render_constants::*, SCANNER_RANGE, TILE_SIZE};
+
+/// Our scanner's last ping: a ring sweeping out from where we scanned, each
+/// resource it found lighting up as the ring passes, and an arrow on the
+/// ring for every one further out. Like radar blips these show through fog.
+pub fn render_scanner_ping(game_state: &GameState, cam_x: f32, cam_y: f32) {
+    let Some(ping) = &game_state.scanner_ping else {
+        return;
+    };
+    let (r, g, b) = SCANNER_COLOR;
+    let fade = ((ping.duration - ping.age) / RADAR_BLIP_FADE_TIME).clamp(0.0, 1.0);
+    let range = SCANNER_RANGE * TILE_SIZE;
+    let swept = (ping.age / SCANNER_SWEEP_TIME).min(1.0);
+    let (ox, oy) = (cam_x + ping.origin.x, cam_y + ping.origin.y);
+
+    if swept < 1.0 {
+        draw_circle(ox, oy, range * swept, Color::new(r, g, b, 0.08));
+        draw_circle_lines(ox, oy, range * swept, 3.0, Color::new(r, g, b, 1.0 - swept));
+    } else {
+        draw_circle_lines(ox, oy, range, 1.0, Color::new(r, g, b, 0.3 * fade));
+    }
+
+    let palette = game_state.accessibility.palette();
+    for (position, resource_type) in &ping.contacts {
+        if position.distance_to(ping.origin) > range * swept {
+            continue;
+        }
+        let (x, y) = (cam_x + position.x, cam_y + position.y);
+        let color = get_resource_color(*resource_type, palette);
+        // Four-sided polygons start at +x, so these stand on a corner
+        draw_poly(
+            x,
+            y,
+            4,
+            SCANNER_CONTACT_SIZE,
+            0.0,
+            Color { a: fade, ..color },
+        );
+        draw_poly_lines(
+            x,
+            y,
+            4,
+            SCANNER_CONTACT_SIZE,
+            0.0,
+            2.0,
+            Color::new(r, g, b, fade),
+        );
+    }
+
+    // Bearings only show once the ring has reached its edge
+    if swept < 1.0 {
+        return;
+    }
+    let color = Color::new(r, g, b, fade);
+    for bearing in &ping.bearings {
+        let (dx, dy) = (bearing.cos(), bearing.sin());
+        let base = vec2(ox + dx * range, oy + dy * range);
+        let tip = base + vec2(dx, dy) * SCANNER_BEARING_LENGTH;
+        let side = vec2(-dy, dx) * SCANNER_BEARING_LENGTH * 0.3;
+        draw_line(base.x, base.y, tip.x, tip.y, 2.0, color);
+        draw_triangle(
+            tip,
+            tip - vec2(dx, dy) * 8.0 + side,
+            tip - vec2(dx, dy) * 8.0 - side,
+            color,
+        );
+    }
+}
@@ -0,0 +1,431 @@
+use super::utils::{get_player_color, get_station_color};
+use crate::game_state::*;
+use macroquad::prelude::*;
+use shared::{
+    balance::{MECH_FUEL_CAPACITY, MECH_MAX_HEALTH, MECH_MAX_SHIELD},
+    components::StatusEffectKind,
+    constants::*,
+    render_constants::*,
+    tile_entity::{FloorMap, StaticTile},
+    types::*,
+};
+
+/// What's wrong with a station, worst first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum StationTrouble {
+    Boarded,
+    Sabotaged,
+    NoPower,
+    Burning,
+    Damaged,
+}
+
+impl StationTrouble {
+    fn label(self) -> &'static str {
+        match self {
+            StationTrouble::Boarded => "boarders",
+            StationTrouble::Sabotaged => "offline",
+            StationTrouble::NoPower => "no power",
+            StationTrouble::Burning => "on fire",
+            StationTrouble::Damaged => "damaged",
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            StationTrouble::Boarded => MAGENTA,
+            StationTrouble::Sabotaged => RED,
+            StationTrouble::NoPower => PURPLE,
+            StationTrouble::Burning => ORANGE,
+            StationTrouble::Damaged => GOLD,
+        }
+    }
+}
+
+fn has_effect(game_state: &GameState, entity: EntityId, kind: StatusEffectKind) -> bool {
+    game_state
+        .status_effects
+        .get(&entity)
+        .is_some_and(|effects| effects.iter().any(|effect| effect.kind == kind))
+}
+
+/// Whether the alarm has boarders at this station
+fn alarm_at(game_state: &GameState, station: &StationState) -> bool {
+    game_state
+        .mech_alarms
+        .get(&station.mech_id)
+        .is_some_and(|alarm| alarm.station_id == station._id)
+}
+
+fn station_trouble(game_state: &GameState, station: &StationState) -> Option<StationTrouble> {
+    if alarm_at(game_state, station) {
+        return Some(StationTrouble::Boarded);
+    }
+    if has_effect(game_state, station._id, StatusEffectKind::Sabotaged) {
+        return Some(StationTrouble::Sabotaged);
+    }
+    if has_effect(game_state, station.mech_id, StatusEffectKind::Emp) {
+        return Some(StationTrouble::NoPower);
+    }
+    let burning = game_state
+        .mech_fires
+        .get(&station.mech_id)
+        .is_some_and(|fires| {
+            fires
+                .iter()
+                .any(|fire| fire.floor == station.floor && fire.position == station.position)
+        });
+    if burning {
+        return Some(StationTrouble::Burning);
+    }
+    (station.condition() < 1.0).then_some(StationTrouble::Damaged)
+}
+
+/// Breached and intact wall tiles on one floor
+fn hull_section(game_state: &GameState, mech_id: MechId, floor: u8) -> (usize, usize) {
+    let Some(floor_map) = game_state.floor_manager.get_floor(mech_id, floor) else {
+        return (0, 0);
+    };
+    floor_map
+        .static_tiles
+        .values()
+        .fold((0, 0), |(breached, intact), tile| match tile {
+            StaticTile::DamagedWall => (breached + 1, intact),
+            StaticTile::MetalWall => (breached, intact + 1),
+            _ => (breached, intact),
+        })
+}
+
+/// The callouts under the cutaway: what to repair or man first
+fn mech_needs(game_state: &GameState, mech: &MechState) -> Vec<(String, Color)> {
+    let mut needs = Vec::new();
+
+    let mut troubled: Vec<(StationTrouble, &StationState)> = game_state
+        .stations
+        .values()
+        .filter(|station| station.mech_id == mech.id)
+        .filter_map(|station| station_trouble(game_state, station).map(|t| (t, station)))
+        .collect();
+    troubled.sort_by_key(|(trouble, station)| (*trouble, station.floor));
+    for (trouble, station) in troubled {
+        let name = game_state
+            .station_registry
+            .get_definition(station.station_type)
+            .map_or("Station", |definition| definition.short_name.as_str());
+        needs.push((
+            format!("{name} {} (F{})", trouble.label(), station.floor + 1),
+            trouble.color(),
+        ));
+    }
+
+    for floor in 0..MECH_FLOORS as u8 {
+        let (breached, _) = hull_section(game_state, mech.id, floor);
+        if breached > 0 {
+            let walls = if breached == 1 { "breach" } else { "breaches" };
+            needs.push((format!("{breached} hull {walls} (F{})", floor + 1), RED));
+        }
+    }
+
+    let fires = game_state.mech_fires.get(&mech.id).map_or(0, Vec::len);
+    if fires > 0 {
+        let aboard = if fires == 1 { "fire" } else { "fires" };
+        needs.push((format!("{fires} {aboard} aboard"), ORANGE));
+    }
+
+    let pilot_manned = game_state.stations.values().any(|station| {
+        station.mech_id == mech.id
+            && station.station_type == StationType::Pilot
+            && station.operated_by.is_some()
+    });
+    if !pilot_manned {
+        needs.push(("Pilot station unmanned".to_string(), YELLOW));
+    }
+
+    if mech.fuel <= 0.0 {
+        needs.push(("Out of fuel: deliver batteries".to_string(), RED));
+    } else if mech.fuel / MECH_FUEL_CAPACITY <= FUEL_LOW_SHARE {
+        needs.push(("Fuel low: deliver batteries".to_string(), ORANGE));
+    }
+
+    needs.truncate(SCHEMATIC_MAX_NEEDS);
+    needs
+}
+
+/// Cutaway of the mech we're crewing, shown while aboard: hull and shield,
+/// each floor's walls, stations, fires and crew, and a short list of what
+/// needs repair or manning. Boarders don't get a readout of the enemy's mech.
+pub fn render_mech_schematic(game_state: &GameState) {
+    let PlayerLocation::InsideMech { mech_id, .. } = game_state.player_location else {
+        return;
+    };
+    let Some(mech) = game_state
+        .mechs
+        .get(&mech_id)
+        .filter(|mech| Some(mech.team) == game_state.player_team)
+    else {
+        return;
+    };
+
+    let floor_width = FLOOR_WIDTH_TILES as f32 * SCHEMATIC_TILE_SIZE;
+    let floor_height = FLOOR_HEIGHT_TILES as f32 * SCHEMATIC_TILE_SIZE;
+    let needs = mech_needs(game_state, mech);
+    let floors_height = MECH_FLOORS as f32 * (floor_height + SCHEMATIC_FLOOR_GAP);
+    let height = SCHEMATIC_HEADER_HEIGHT
+        + floors_height
+        + needs.len().max(1) as f32 * SCHEMATIC_LINE_HEIGHT
+        + 8.0;
+    let x = screen_width() - SCHEMATIC_WIDTH - RESOURCE_PANEL_OFFSET_X;
+    let y = RESOURCE_PANEL_OFFSET_Y + RESOURCE_PANEL_HEIGHT + 10.0;
+
+    draw_rectangle(
+        x,
+        y,
+        SCHEMATIC_WIDTH,
+        height,
+        Color::new(0.0, 0.0, 0.0, 0.6),
+    );
+    draw_text("Mech Status", x + 10.0, y + 18.0, 18.0, WHITE);
+    if has_effect(game_state, mech.id, StatusEffectKind::Emp) {
+        draw_text("EMP", x + SCHEMATIC_WIDTH - 40.0, y + 18.0, 16.0, PURPLE);
+    }
+    let flash = (get_time() * LIGHT_ALARM_FLASH as f64).fract() < 0.5;
+    if game_state.mech_alarms.contains_key(&mech.id) && flash {
+        draw_text("ALARM", x + SCHEMATIC_WIDTH - 95.0, y + 18.0, 16.0, MAGENTA);
+    }
+
+    // Hull, with the capacity breaches have taken off the end
+    let bar_x = x + 60.0;
+    let bar_width = SCHEMATIC_WIDTH - 70.0;
+    draw_text("Hull", x + 10.0, y + 33.0, 14.0, LIGHTGRAY);
+    draw_rectangle(bar_x, y + 25.0, bar_width, 8.0, DARKGRAY);
+    draw_rectangle(
+        bar_x,
+        y + 25.0,
+        bar_width * (mech.health as f32 / MECH_MAX_HEALTH as f32).min(1.0),
+        8.0,
+        GREEN,
+    );
+    let lost = 1.0 - mech.max_health as f32 / MECH_MAX_HEALTH as f32;
+    if lost > 0.0 {
+        draw_rectangle(
+            bar_x + bar_width * (1.0 - lost),
+            y + 25.0,
+            bar_width * lost,
+            8.0,
+            Color::new(0.4, 0.1, 0.1, 1.0),
+        );
+    }
+    draw_text("Shield", x + 10.0, y + 47.0, 14.0, LIGHTGRAY);
+    draw_rectangle(bar_x, y + 39.0, bar_width, 8.0, DARKGRAY);
+    draw_rectangle(
+        bar_x,
+        y + 39.0,
+        bar_width * (mech.shield as f32 / MECH_MAX_SHIELD as f32).min(1.0),
+        8.0,
+        SKYBLUE,
+    );
+    let fuel_share = (mech.fuel / MECH_FUEL_CAPACITY).clamp(0.0, 1.0);
+    let fuel_color = if fuel_share > FUEL_LOW_SHARE {
+        GOLD
+    } else {
+        ORANGE
+    };
+    draw_text("Fuel", x + 10.0, y + 61.0, 14.0, LIGHTGRAY);
+    draw_rectangle(bar_x, y + 53.0, bar_width, 8.0, DARKGRAY);
+    draw_rectangle(bar_x, y + 53.0, bar_width * fuel_share, 8.0, fuel_color);
+
+    // Top floor on top, like the mech itself
+    for floor in 0..MECH_FLOORS as u8 {
+        let row = (MECH_FLOORS as u8 - 1 - floor) as f32;
+        let floor_y = y + SCHEMATIC_HEADER_HEIGHT + row * (floor_height + SCHEMATIC_FLOOR_GAP);
+        render_floor(game_state, mech, floor, x + 10.0, floor_y);
+
+        let (breached, intact) = hull_section(game_state, mech.id, floor);
+        let integrity = (intact * 100).checked_div(breached + intact).unwrap_or(100);
+        let text_x = x + 20.0 + floor_width;
+        let (integrity_color, label) = if breached > 0 {
+            (RED, format!("Hull {integrity}%"))
+        } else {
+            (LIGHTGRAY, "Hull OK".to_string())
+        };
+        draw_text(
+            &format!("Floor {}", floor + 1),
+            text_x,
+            floor_y + 12.0,
+            14.0,
+            WHITE,
+        );
+        draw_text(&label, text_x, floor_y + 28.0, 14.0, integrity_color);
+        let crew = game_state
+            .players
+            .values()
+            .filter(|player| {
+                matches!(player.location, PlayerLocation::InsideMech { mech_id: id, pos }
+                    if id == mech.id && pos.floor() == floor && player.team == mech.team)
+            })
+            .count();
+        draw_text(
+            &format!("Crew {crew}"),
+            text_x,
+            floor_y + 44.0,
+            14.0,
+            LIGHTGRAY,
+        );
+    }
+
+    let needs_y = y + SCHEMATIC_HEADER_HEIGHT + floors_height + SCHEMATIC_LINE_HEIGHT - 4.0;
+    if needs.is_empty() {
+        draw_text("All systems nominal", x + 10.0, needs_y, 14.0, GREEN);
+    }
+    for (i, (text, color)) in needs.iter().enumerate() {
+        let line_y = needs_y + i as f32 * SCHEMATIC_LINE_HEIGHT;
+        draw_text(text, x + 10.0, line_y, 14.0, *color);
+    }
+}
+
+/// One floor of the cutaway with its top-left corner at (x, y)
+fn render_floor(game_state: &GameState, mech: &MechState, floor: u8, x: f32, y: f32) {
+    let tile = SCHEMATIC_TILE_SIZE;
+    let at = |pos: TilePos| (x + pos.x as f32 * tile, y + pos.y as f32 * tile);
+    draw_rectangle(
+        x,
+        y,
+        FLOOR_WIDTH_TILES as f32 * tile,
+        FLOOR_HEIGHT_TILES as f32 * tile,
+        Color::new(0.12, 0.12, 0.15, 1.0),
+    );
+
+    // Breaches blink so they stand out from the rest of the wall
+    let blink = (get_time() * 4.0).sin() > 0.0;
+    let floor_map = game_state.floor_manager.get_floor(mech.id, floor);
+    if let Some(floor_map) = floor_map {
+        for (pos, static_tile) in &floor_map.static_tiles {
+            let color = match static_tile {
+                StaticTile::MetalWall => Color::new(0.45, 0.45, 0.5, 1.0),
+                StaticTile::DamagedWall if blink => RED,
+                StaticTile::DamagedWall => Color::new(0.5, 0.1, 0.1, 1.0),
+                _ => continue,
+            };
+            let (tile_x, tile_y) = at(*pos);
+            draw_rectangle(tile_x, tile_y, tile, tile, color);
+        }
+        for (pos, barricade) in &floor_map.barricades {
+            let (tile_x, tile_y) = at(*pos);
+            draw_rectangle(tile_x, tile_y, tile, tile, Color::new(0.45, 0.3, 0.15, 1.0));
+            draw_rectangle_lines(
+                tile_x,
+                tile_y,
+                tile,
+                tile,
+                1.0,
+                get_player_color(barricade.team, game_state.accessibility.palette()),
+            );
+        }
+    }
+
+    // Where a click would build or take down a barricade
+    if let Some((_, target, preview)) = game_state.barricade_target().filter(|(id, target, _)| {
+        game_state.barricade_placement && *id == mech.id && target.floor == floor
+    }) {
+        let color = match preview {
+            BarricadePreview::Buildable => GREEN,
+            BarricadePreview::Ours => YELLOW,
+            BarricadePreview::Blocked => RED,
+        };
+        let (tile_x, tile_y) = at(target.tile_pos);
+        draw_rectangle_lines(tile_x, tile_y, tile, tile, 2.0, color);
+    }
+
+    for fire in game_state
+        .mech_fires
+        .get(&mech.id)
+        .into_iter()
+        .flatten()
+        .filter(|fire| fire.floor == floor)
+    {
+        let (fire_x, fire_y) = at(fire.position);
+        draw_circle(
+            fire_x + tile / 2.0,
+            fire_y + tile / 2.0,
+            tile * (0.3 + 0.3 * fire.intensity),
+            ORANGE,
+        );
+    }
+
+    for station in game_state
+        .stations
+        .values()
+        .filter(|station| station.mech_id == mech.id && station.floor == floor)
+    {
+        let (station_x, station_y) = at(station.position);
+        let fill = match station_trouble(game_state, station) {
+            Some(trouble) => trouble.color(),
+            None => get_station_color(station.station_type),
+        };
+        draw_rectangle(station_x, station_y, tile, tile, fill);
+        // Outlined while someone's on it
+        let outline = if station.operated_by.is_some() {
+            WHITE
+        } else {
+            DARKGRAY
+        };
+        draw_rectangle_lines(station_x, station_y, tile, tile, 1.0, outline);
+        // Rings ping out from wherever the boarders are
+        if alarm_at(game_state, station) {
+            let ping = (get_time() * LIGHT_ALARM_FLASH as f64).fract() as f32;
+            draw_circle_lines(
+                station_x + tile / 2.0,
+                station_y + tile / 2.0,
+                tile * (0.5 + 1.5 * ping),
+                1.0,
+                Color::new(1.0, 0.0, 1.0, 1.0 - ping),
+            );
+        }
+    }
+
+    for player in game_state.players.values() {
+        let PlayerLocation::InsideMech { mech_id, pos } = player.location else {
+            continue;
+        };
+        if mech_id != mech.id || pos.floor() != floor {
+            continue;
+        }
+        // Boarders only show up where some of the crew on that floor can see them
+        if player.team != mech.team && !crew_can_see(game_state, floor_map, mech, pos) {
+            continue;
+        }
+        let (player_x, player_y) = at(pos.tile_pos());
+        let (center_x, center_y) = (player_x + tile / 2.0, player_y + tile / 2.0);
+        draw_circle(
+            center_x,
+            center_y,
+            tile * 0.4,
+            get_player_color(player.team, game_state.accessibility.palette()),
+        );
+        if Some(player._id) == game_state.player_id {
+            draw_circle_lines(center_x, center_y, tile * 0.6, 1.0, WHITE);
+        } else if player.team != mech.team && blink {
+            draw_circle_lines(center_x, center_y, tile * 0.7, 1.0, RED);
+        }
+    }
+}
+
+/// Whether any of the mech's crew on the same floor has a line of sight to
+/// `pos` past the walls and barricades
+fn crew_can_see(
+    game_state: &GameState,
+    floor_map: Option<&FloorMap>,
+    mech: &MechState,
+    pos: MechInteriorPos,
+) -> bool {
+    let Some(floor_map) = floor_map else {
+        return true;
+    };
+    game_state.players.values().any(|crew| {
+        crew.team == mech.team
+            && matches!(crew.location, PlayerLocation::InsideMech { mech_id, pos: at }
+                if mech_id == mech.id
+                    && at.floor == pos.floor
+                    && floor_map.has_line_of_sight(at.tile_pos, pos.tile_pos))
+    })
+}
@@ -1,8 +1,8 @@
 use crate::game_state::GameState;
 use macroquad::prelude::*;
 use shared::{
-    MechInteriorCoordinates, TilePos, WorldPos, FLOOR_HEIGHT_TILES, FLOOR_WIDTH_TILES, MECH_FLOORS,
-    TILE_SIZE,
+    MechInteriorCoordinates, PlayerLocation, TilePos, WorldPos, AABB, FLOOR_HEIGHT_TILES,
+    FLOOR_WIDTH_TILES, MECH_FLOORS, TILE_SIZE,
 };
 use uuid::Uuid;
 
@@ -503,3 +503,75 @@ impl SpatialDebugRenderer {
         draw_text("F4 - Show test report", panel_x + 10.0, y, 12.0, WHITE);
     }
 }
+
+/// Draw the collision shapes the server last sent: player radii, mech hulls
+/// and shield bubbles, projectiles with the path behind them and where
+/// stations can be taken over from. Shapes aboard a mech only show for the
+/// floor we're on.
+pub fn render_collision_shapes(game_state: &GameState, cam_x: f32, cam_y: f32) {
+    let Some(shapes) = &game_state.collision_shapes else {
+        return;
+    };
+    let our_floor = match game_state.player_location {
+        PlayerLocation::InsideMech { mech_id, pos } => Some((mech_id, pos.floor)),
+        PlayerLocation::OutsideWorld(_) => None,
+    };
+    let rect = |aabb: &AABB, color: Color| {
+        let (width, height) = aabb.size();
+        draw_rectangle_lines(
+            cam_x + aabb.min.x,
+            cam_y + aabb.min.y,
+            width,
+            height,
+            2.0,
+            color,
+        );
+    };
+
+    for mech in &shapes.mechs {
+        rect(&mech.hull, RED);
+        if let Some(shield) = mech.shield {
+            let (x, y) = (cam_x + shield.center.x, cam_y + shield.center.y);
+            draw_circle_lines(x, y, shield.radius, 2.0, SKYBLUE);
+        }
+    }
+
+    for station in shapes
+        .stations
+        .iter()
+        .filter(|station| our_floor == Some((station.mech_id, station.floor)))
+    {
+        rect(&station.area, Color::new(1.0, 0.8, 0.2, 0.8));
+    }
+
+    for player in shapes
+        .players
+        .iter()
+        .filter(|player| player.aboard.is_none() || player.aboard == our_floor)
+    {
+        let (x, y) = (cam_x + player.center.x, cam_y + player.center.y);
+        draw_circle_lines(x, y, player.radius, 1.5, GREEN);
+    }
+
+    for projectile in &shapes.projectiles {
+        // Older stretches of the trail fade out
+        let steps = projectile.trail.len().max(1) as f32;
+        let path = projectile.trail.iter().chain([&projectile.position]);
+        for (i, (from, to)) in path.clone().zip(path.skip(1)).enumerate() {
+            let color = Color::new(1.0, 0.5, 0.0, (i + 1) as f32 / steps);
+            draw_line(
+                cam_x + from.x,
+                cam_y + from.y,
+                cam_x + to.x,
+                cam_y + to.y,
+                1.5,
+                color,
+            );
+        }
+        let (x, y) = (cam_x + projectile.position.x, cam_y + projectile.position.y);
+        draw_circle_lines(x, y, projectile.radius, 1.5, ORANGE);
+        // and where it'll be a tenth of a second from now
+        let (vx, vy) = projectile.velocity;
+        draw_line(x, y, x + vx * 0.1, y + vy * 0.1, 1.0, YELLOW);
+    }
+}
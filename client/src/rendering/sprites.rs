@@ -0,0 +1,162 @@
+use crate::animation::{AnimationState, PlayerAnimation};
+use macroquad::prelude::*;
+use shared::{render_constants::*, types::Facing};
+use std::f32::consts::TAU;
+
+/// Player sprite sheet of square `PLAYER_SPRITE_FRAME_SIZE` frames. Columns
+/// are the frames of a cycle; rows come in a block per animation state
+/// (idle, walking, carrying, operating), one row per direction in `Facing`
+/// order, or east, south, west and north for a four-direction sheet.
+pub struct SpriteSheet {
+    texture: Texture2D,
+    frames: usize,
+    directions: usize,
+}
+
+impl SpriteSheet {
+    pub async fn load(path: &str) -> Option<Self> {
+        let texture = match load_texture(path).await {
+            Ok(texture) => texture,
+            Err(e) => {
+                info!("No player sprites at {}, drawing shapes: {}", path, e);
+                return None;
+            }
+        };
+        texture.set_filter(FilterMode::Nearest);
+        let frames = (texture.width() / PLAYER_SPRITE_FRAME_SIZE) as usize;
+        let rows = (texture.height() / PLAYER_SPRITE_FRAME_SIZE) as usize;
+        let directions = rows / 4;
+        if frames == 0 || rows != directions * 4 || !matches!(directions, 4 | 8) {
+            warn!(
+                "Player sprite sheet {} is {}x{} frames, expected 4 or 8 rows per state",
+                path, frames, rows
+            );
+            return None;
+        }
+        Some(Self {
+            texture,
+            frames,
+            directions,
+        })
+    }
+
+    fn row(&self, state: AnimationState, facing: Facing) -> usize {
+        let direction = if self.directions == 8 {
+            facing as usize
+        } else {
+            facing.to_four() as usize / 2
+        };
+        state.sheet_index() * self.directions + direction
+    }
+
+    fn draw(
+        &self,
+        animation: &PlayerAnimation,
+        facing: Facing,
+        x: f32,
+        y: f32,
+        size: f32,
+        tint: Color,
+    ) {
+        let source = Rect::new(
+            animation.frame(self.frames) as f32 * PLAYER_SPRITE_FRAME_SIZE,
+            self.row(animation.state, facing) as f32 * PLAYER_SPRITE_FRAME_SIZE,
+            PLAYER_SPRITE_FRAME_SIZE,
+            PLAYER_SPRITE_FRAME_SIZE,
+        );
+        draw_texture_ex(
+            &self.texture,
+            x - size / 2.0,
+            y - size / 2.0,
+            tint,
+            DrawTextureParams {
+                dest_size: Some(vec2(size, size)),
+                source: Some(source),
+                ..Default::default()
+            },
+        );
+    }
+}
+
+/// Draw a player centered on (x, y), from the sprite sheet if there is one
+/// and as team-colored shapes otherwise
+pub fn draw_player(
+    sprites: Option<&SpriteSheet>,
+    animation: Option<&PlayerAnimation>,
+    facing: Facing,
+    x: f32,
+    y: f32,
+    radius: f32,
+    color: Color,
+) {
+    let Some(animation) = animation else {
+        draw_circle(x, y, radius, color);
+        return;
+    };
+    if let Some(sheet) = sprites {
+        // Tint only by fog, keeping the sprite's own colors
+        let tint = Color::new(1.0, 1.0, 1.0, color.a);
+        sheet.draw(animation, facing, x, y, radius * 2.0, tint);
+        return;
+    }
+
+    // Bob along with the stride
+    let cycle = animation.clock * PLAYER_ANIMATION_FPS / 4.0 * TAU;
+    let bob = if animation.moving {
+        cycle.sin().abs() * radius * 0.15
+    } else {
+        0.0
+    };
+    let y = y - bob;
+    let (dx, dy) = (facing.angle().cos(), facing.angle().sin());
+    let shade = Color::new(color.r * 0.6, color.g * 0.6, color.b * 0.6, color.a);
+
+    // Feet stepping out to either side of the way we're facing
+    if animation.moving {
+        let stride = cycle.sin() * radius * 0.4;
+        for side in [-1.0, 1.0] {
+            let (fx, fy) = (-dy * side * radius * 0.45, dx * side * radius * 0.45);
+            let step = stride * side;
+            draw_circle(
+                x + fx + dx * step,
+                y + bob + fy + dy * step,
+                radius * 0.25,
+                shade,
+            );
+        }
+    }
+
+    draw_circle(x, y, radius, color);
+    // Visor on the facing side
+    draw_circle(
+        x + dx * radius * 0.55,
+        y + dy * radius * 0.55,
+        radius * 0.3,
+        shade,
+    );
+
+    match animation.state {
+        AnimationState::Carrying => {
+            let size = radius * 0.7;
+            draw_rectangle(
+                x + dx * radius - size / 2.0,
+                y + dy * radius - size / 2.0,
+                size,
+                size,
+                Color::new(0.6, 0.45, 0.25, color.a),
+            );
+        }
+        AnimationState::Operating => {
+            // Hands busy at the console
+            let pulse = 0.5 + 0.5 * (animation.clock * PLAYER_ANIMATION_FPS).sin();
+            draw_circle_lines(
+                x,
+                y,
+                radius * 1.3,
+                1.5,
+                Color::new(0.4, 0.9, 1.0, pulse * color.a),
+            );
+        }
+        AnimationState::Idle | AnimationState::Walking => {}
+    }
+}
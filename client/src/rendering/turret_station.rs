@@ -0,0 +1,88 @@
+use super::utils::*;
+use crate::game_state::GameState;
+use macroquad::prelude::*;
+use shared::{render_constants::*, MechTurret};
+
+/// Targeting overlay for a turret operator: what the turret sees out of its
+/// window, the spread its shots land in, and a lock marker on any enemy mech
+/// inside the view. Drawn in the world, under the zoom.
+pub fn render_turret_overlay(game_state: &GameState, cam_x: f32, cam_y: f32) {
+    let (Some(mech_id), Some(aim)) = (
+        game_state.operated_turret_mech(),
+        game_state.ui_state.turret_aim,
+    ) else {
+        return;
+    };
+    let Some(mech) = game_state.mechs.get(&mech_id) else {
+        return;
+    };
+
+    // Draw from our own aim rather than the server's so the view tracks the mouse
+    let turret = MechTurret {
+        aim,
+        manual: true,
+        ..mech.turret
+    };
+    let origin = get_mech_center(mech);
+    let cone = turret.vision_cone(origin);
+    let (ox, oy) = (cam_x + origin.x, cam_y + origin.y);
+    let ray = |angle: f32| vec2(ox + angle.cos() * cone.range, oy + angle.sin() * cone.range);
+
+    // Sight cone as a fan of thin triangles
+    let half_width = (cone.width / 2.0).to_radians();
+    let step = half_width * 2.0 / TURRET_CONE_SEGMENTS as f32;
+    for i in 0..TURRET_CONE_SEGMENTS {
+        let start = aim - half_width + step * i as f32;
+        draw_triangle(
+            vec2(ox, oy),
+            ray(start),
+            ray(start + step),
+            Color::new(1.0, 1.0, 0.6, TURRET_CONE_ALPHA),
+        );
+    }
+    for edge in [aim - half_width, aim + half_width] {
+        let end = ray(edge);
+        draw_line(ox, oy, end.x, end.y, 1.0, Color::new(1.0, 1.0, 0.6, 0.5));
+    }
+
+    // Shots land somewhere between these
+    for edge in [aim - turret.spread(), aim + turret.spread()] {
+        let end = ray(edge);
+        draw_line(ox, oy, end.x, end.y, 1.0, ORANGE);
+    }
+
+    for enemy in game_state.mechs.values().filter(|m| m.team != mech.team) {
+        let center = get_mech_center(enemy);
+        if cone.contains(center) {
+            draw_circle_lines(
+                cam_x + center.x,
+                cam_y + center.y,
+                TURRET_LOCK_MARKER_RADIUS,
+                2.0,
+                RED,
+            );
+        }
+    }
+}
+
+/// The operator's crosshair and controls hint, drawn over the HUD
+pub fn render_turret_hud(game_state: &GameState) {
+    // Only set while we crew a turret
+    if game_state.ui_state.turret_aim.is_none() {
+        return;
+    }
+
+    let (mouse_x, mouse_y) = mouse_position();
+    let size = TURRET_CROSSHAIR_SIZE;
+    draw_circle_lines(mouse_x, mouse_y, size * 0.6, 1.5, WHITE);
+    draw_line(mouse_x - size, mouse_y, mouse_x + size, mouse_y, 1.0, WHITE);
+    draw_line(mouse_x, mouse_y - size, mouse_x, mouse_y + size, 1.0, WHITE);
+
+    draw_text(
+        "TURRET - MANUAL | Mouse: Aim | Click: Fire",
+        HUD_BASE_X,
+        screen_height() - TURRET_HINT_BOTTOM_OFFSET,
+        UI_TEXT_FONT_SIZE,
+        YELLOW,
+    );
+}
@@ -1,12 +1,35 @@
+use super::utils::{draw_item_icon, get_item_color, get_player_color};
 use crate::game_state::*;
 use macroquad::prelude::*;
+use shared::coordinates::MechDoorPositions;
+use shared::render_constants::*;
 use shared::types::*;
+use shared::{
+    ArmorArc, ServerLoad, BARRICADE_SCRAP_COST, MECH_MAX_HEALTH, MECH_MAX_SHIELD, PLAYER_MAX_HEALTH,
+};
+use std::collections::HashMap;
 
 pub fn render_ui(game_state: &GameState) {
     // Team and location info moved to debug overlay to avoid overlap
     // render_team_and_location_info(game_state);
     render_mech_status_bars(game_state);
+    render_voice_speakers(game_state);
     render_control_hints(game_state);
+    render_boarding_status(game_state);
+    render_hit_indicators(game_state);
+    render_station_panel(game_state);
+    render_station_tooltip(game_state);
+    render_team_cargo(game_state);
+    super::schematic::render_mech_schematic(game_state);
+    render_inventory_strip(game_state);
+    render_game_mode(game_state);
+    render_hangar(game_state);
+    render_pacing_announcement(game_state);
+    render_tutorial(game_state);
+    render_shutdown_warning(game_state);
+    render_server_load(game_state);
+    render_join_rejected(game_state);
+    render_toasts(game_state);
 }
 
 fn render_team_and_location_info(game_state: &GameState) {
@@ -39,10 +62,7 @@ fn render_mech_status_bars(game_state: &GameState) {
     let mut y_offset = 80.0;
 
     for mech in game_state.mechs.values() {
-        let team_color = match mech.team {
-            TeamId::Red => RED,
-            TeamId::Blue => BLUE,
-        };
+        let team_color = get_player_color(mech.team, game_state.accessibility.palette());
 
         draw_text(
             &format!("{:?} Mech", mech.team),
@@ -58,17 +78,30 @@ fn render_mech_status_bars(game_state: &GameState) {
             y_offset + 5.0,
             200.0,
             10.0,
-            mech.health as f32 / 100.0,
+            mech.health as f32 / MECH_MAX_HEALTH as f32,
             GREEN,
         );
 
+        // Capacity lost to unrepaired hull breaches
+        let lost_capacity = 1.0 - mech.max_health as f32 / MECH_MAX_HEALTH as f32;
+        if lost_capacity > 0.0 {
+            let lost_width = 200.0 * lost_capacity;
+            draw_rectangle(
+                10.0 + 200.0 - lost_width,
+                y_offset + 5.0,
+                lost_width,
+                10.0,
+                Color::new(0.4, 0.1, 0.1, 1.0),
+            );
+        }
+
         // Shield bar
         render_status_bar(
             10.0,
             y_offset + 17.0,
             200.0,
             10.0,
-            mech.shield as f32 / 50.0,
+            mech.shield as f32 / MECH_MAX_SHIELD as f32,
             SKYBLUE,
         );
 
@@ -76,23 +109,61 @@ fn render_mech_status_bars(game_state: &GameState) {
     }
 }
 
+/// Who is talking on voice chat, listed under the mech status bars
+fn render_voice_speakers(game_state: &GameState) {
+    let scale = game_state.ui_scale;
+    let (font_size, line) = (16.0 * scale, 20.0 * scale);
+    let x = 10.0;
+    let mut y = 80.0 + 40.0 * game_state.mechs.len() as f32 + line;
+
+    if game_state.voice_muted {
+        draw_text("Voice muted", x, y, font_size, GRAY);
+        return;
+    }
+    let pulse = 0.6 + 0.4 * (get_time() as f32 * 8.0).sin().abs();
+    for speaker in &game_state.voice_speakers {
+        let Some(player) = game_state.players.get(speaker) else {
+            continue;
+        };
+        let color = get_player_color(player.team, game_state.accessibility.palette());
+        let radius = 5.0 * scale;
+        draw_circle(
+            x + radius,
+            y - radius,
+            radius,
+            Color::new(color.r, color.g, color.b, pulse),
+        );
+        draw_circle_lines(x + radius, y - radius, radius + 3.0 * scale, 1.5, color);
+        draw_text(
+            &player.name,
+            x + radius * 2.0 + 8.0 * scale,
+            y,
+            font_size,
+            color,
+        );
+        y += line;
+    }
+}
+
 fn render_status_bar(x: f32, y: f32, width: f32, height: f32, fill_ratio: f32, color: Color) {
     draw_rectangle(x, y, width, height, DARKGRAY);
     draw_rectangle(x, y, width * fill_ratio, height, color);
 }
 
 fn render_control_hints(game_state: &GameState) {
+    let (font_size, line) = (16.0 * game_state.ui_scale, 20.0 * game_state.ui_scale);
+
     // Basic controls
     draw_text(
-        "WASD: Move | Space: Action | Q: Exit Mech",
+        "WASD: Move | Space: Action | F: Attack | Shift: Dash | Q: Exit Mech | Tab: Switch Slot | G: Drop | B: Barricade | V: Talk | Wheel: Zoom | C: Camera",
         10.0,
-        screen_height() - 20.0,
-        16.0,
+        screen_height() - line,
+        font_size,
         WHITE,
     );
 
     // Context-specific hints
-    if let PlayerLocation::InsideMech { pos, .. } = game_state.player_location {
+    if let PlayerLocation::InsideMech { mech_id, pos } = game_state.player_location {
         let floor = pos.floor();
         draw_text(
             &format!(
@@ -100,24 +171,778 @@ fn render_control_hints(game_state: &GameState) {
                 floor + 1
             ),
             10.0,
-            screen_height() - 40.0,
-            16.0,
+            screen_height() - line * 2.0,
+            font_size,
             WHITE,
         );
 
+        render_damage_control_status(game_state);
+
+        if let Some((_, _, preview)) = game_state
+            .barricade_target()
+            .filter(|_| game_state.barricade_placement)
+        {
+            let (text, color) = match preview {
+                BarricadePreview::Buildable => (
+                    format!("Click: Build barricade ({BARRICADE_SCRAP_COST} scrap) | B: Done"),
+                    GREEN,
+                ),
+                BarricadePreview::Ours => {
+                    ("Click: Take down barricade | B: Done".to_string(), YELLOW)
+                }
+                BarricadePreview::Blocked => ("Can't build here | B: Done".to_string(), RED),
+            };
+            draw_text(&text, 10.0, screen_height() - line * 4.0, font_size, color);
+        }
+
         // Station controls hint
-        if is_player_at_station(game_state) {
+        if let Some(panel) = game_state
+            .station_panel
+            .as_ref()
+            .filter(|panel| !panel.buttons.is_empty() && is_player_at_station(game_state))
+        {
+            draw_text(
+                &format!(
+                    "Station Controls: Press 1-{} to operate, or Tab to pick and Enter to press",
+                    panel.buttons.len()
+                ),
+                10.0,
+                screen_height() - line * 3.0,
+                font_size,
+                YELLOW,
+            );
+        } else if let Some(definition) = station_in_reach(game_state, mech_id, pos)
+            .filter(|_| !is_player_at_station(game_state))
+            .and_then(|station| {
+                game_state
+                    .station_registry
+                    .get_definition(station.station_type)
+            })
+        {
             draw_text(
-                "Station Controls: Press 1-5 to operate",
+                &format!("Space: Operate {}", definition.name),
                 10.0,
-                screen_height() - 60.0,
-                16.0,
+                screen_height() - line * 3.0,
+                font_size,
                 YELLOW,
             );
         }
     }
 }
 
+/// A free station next to where we stand, which the action key would take
+fn station_in_reach(
+    game_state: &GameState,
+    mech_id: MechId,
+    pos: MechInteriorPos,
+) -> Option<&StationState> {
+    game_state.stations.values().find(|station| {
+        station.mech_id == mech_id
+            && station.floor == pos.floor
+            && station.operated_by.is_none()
+            && (station.position.x - pos.tile_pos.x).abs() <= 1
+            && (station.position.y - pos.tile_pos.y).abs() <= 1
+    })
+}
+
+fn render_damage_control_status(game_state: &GameState) {
+    let Some(player_id) = game_state.player_id else {
+        return;
+    };
+
+    if let Some(tool) = game_state.players.get(&player_id).and_then(|p| p.held_tool) {
+        let (label, color) = match tool {
+            ToolType::Extinguisher => ("Holding: Extinguisher (Space near fire)", RED),
+            ToolType::RepairTool => ("Holding: Repair Tool (Space near breach)", GOLD),
+        };
+        draw_text(label, 10.0, screen_height() - 80.0, 16.0, color);
+    }
+
+    if let Some(progress) = game_state.repair_progress.get(&player_id) {
+        draw_text("Repairing hull...", 10.0, screen_height() - 100.0, 16.0, WHITE);
+        render_status_bar(140.0, screen_height() - 110.0, 120.0, 10.0, *progress, GOLD);
+    }
+}
+
+/// List the buttons of the station we're operating, as sent by the server
+/// Our own health, and the prompts for fighting aboard mechs: broken enemy
+/// doors we could walk through, sabotage while we're aboard theirs, and an
+/// alert when they're aboard ours
+fn render_boarding_status(game_state: &GameState) {
+    let (Some(player_id), Some(team)) = (game_state.player_id, game_state.player_team) else {
+        return;
+    };
+
+    if let Some(player) = game_state
+        .players
+        .get(&player_id)
+        .filter(|p| p.health < PLAYER_MAX_HEALTH)
+    {
+        draw_text("Health", 10.0, 60.0, 16.0, WHITE);
+        render_status_bar(
+            60.0,
+            50.0,
+            150.0,
+            10.0,
+            player.health as f32 / PLAYER_MAX_HEALTH as f32,
+            RED,
+        );
+    }
+
+    match game_state.player_location {
+        PlayerLocation::OutsideWorld(pos) => {
+            let tile = pos.to_tile();
+            let breached_door_nearby = game_state.mechs.values().any(|mech| {
+                mech.team != team
+                    && MechDoorPositions::breached(mech.health, mech.max_health)
+                    && MechDoorPositions::from_mech_position(mech.position)
+                        .door_tiles()
+                        .iter()
+                        .any(|door| door.distance_to(tile) <= BOARDING_PROMPT_RANGE)
+            });
+            if breached_door_nearby {
+                draw_text(
+                    "Enemy doors breached - walk in to board",
+                    10.0,
+                    screen_height() - 40.0,
+                    16.0,
+                    ORANGE,
+                );
+            }
+        }
+        PlayerLocation::InsideMech { mech_id, .. } => {
+            let Some(mech) = game_state.mechs.get(&mech_id) else {
+                return;
+            };
+            if mech.team != team {
+                draw_text(
+                    "BOARDING | Space: Sabotage station | F: Attack | Q: Leave",
+                    10.0,
+                    screen_height() - 60.0,
+                    16.0,
+                    ORANGE,
+                );
+                if let Some((_, progress)) = game_state.sabotage_progress.get(&player_id) {
+                    draw_text("Sabotaging...", 10.0, screen_height() - 130.0, 16.0, WHITE);
+                    render_status_bar(140.0, screen_height() - 140.0, 120.0, 10.0, *progress, RED);
+                }
+                return;
+            }
+
+            let intruders = game_state
+                .players
+                .values()
+                .filter(|p| p.team != team)
+                .filter(|p| {
+                    matches!(p.location, PlayerLocation::InsideMech { mech_id: id, .. } if id == mech_id)
+                })
+                .count();
+            if intruders > 0 {
+                let text = format!("INTRUDER ALERT: {intruders} enemy aboard - F to attack");
+                let font_size = 24.0 * game_state.ui_scale;
+                let dimensions = measure_text(&text, None, font_size as u16, 1.0);
+                let x = (screen_width() - dimensions.width) / 2.0;
+                let y = 160.0;
+                draw_rectangle(
+                    x - 10.0,
+                    y - dimensions.height - 8.0,
+                    dimensions.width + 20.0,
+                    dimensions.height + 16.0,
+                    Color::new(0.0, 0.0, 0.0, 0.6),
+                );
+                draw_text(&text, x, y, font_size, RED);
+            }
+        }
+    }
+}
+
+fn render_station_panel(game_state: &GameState) {
+    let Some(panel) = &game_state.station_panel else {
+        return;
+    };
+    if panel.buttons.is_empty() {
+        return;
+    }
+
+    let scale = game_state.ui_scale;
+    let line_height = 18.0 * scale;
+    let width = 340.0 * scale;
+    let status = station_status(game_state, panel.station_id);
+    let header = if status.is_some() { 68.0 } else { 50.0 } * scale;
+    let height = header + panel.buttons.len() as f32 * line_height * 2.0;
+    let x = screen_width() - width - 10.0;
+    let y = screen_height() - height - 10.0;
+
+    draw_rectangle(x, y, width, height, Color::new(0.0, 0.0, 0.0, 0.7));
+    draw_rectangle_lines(x, y, width, height, 2.0, GRAY);
+    draw_text(&panel.name, x + 10.0, y + 22.0 * scale, 20.0 * scale, WHITE);
+    draw_text(
+        &panel.description,
+        x + 10.0,
+        y + 40.0 * scale,
+        14.0 * scale,
+        LIGHTGRAY,
+    );
+    if let Some((text, color)) = status {
+        draw_text(&text, x + 10.0, y + 58.0 * scale, 16.0 * scale, color);
+    }
+
+    for (i, button) in panel.buttons.iter().enumerate() {
+        let row_y = y + header + i as f32 * line_height * 2.0 + line_height;
+        if i == panel.focus {
+            draw_rectangle_lines(
+                x + 4.0,
+                row_y - line_height + 2.0,
+                width - 8.0,
+                line_height * 2.0,
+                2.0,
+                SKYBLUE,
+            );
+        }
+        let cooldown = panel.cooldowns.get(i).copied().unwrap_or(0.0);
+        let (label, color) = if cooldown > 0.0 {
+            (
+                format!("[{}] {} ({cooldown:.1}s)", i + 1, button.label),
+                GRAY,
+            )
+        } else {
+            (format!("[{}] {}", i + 1, button.label), YELLOW)
+        };
+        draw_text(&label, x + 10.0, row_y, 18.0 * scale, color);
+
+        let mut detail = button.description.clone();
+        if !button.resource_cost.is_empty() {
+            detail.push_str(&format!(" - costs {}", cost_text(&button.resource_cost)));
+        }
+        draw_text(
+            &detail,
+            x + 24.0 * scale,
+            row_y + line_height - 2.0,
+            14.0 * scale,
+            LIGHTGRAY,
+        );
+    }
+}
+
+/// Arrows around the middle of the screen pointing where recent hits on our
+/// mech came from, redder the thinner the armor they struck
+fn render_hit_indicators(game_state: &GameState) {
+    let (center_x, center_y) = (screen_width() / 2.0, screen_height() / 2.0);
+    let radius = center_x.min(center_y) * 0.6;
+    let size = HIT_INDICATOR_SIZE * game_state.ui_scale;
+    for indicator in &game_state.hit_indicators {
+        let alpha = 1.0 - indicator.age / HIT_INDICATOR_DURATION;
+        let color = match indicator.hit.arc {
+            ArmorArc::Front => YELLOW,
+            ArmorArc::Side => ORANGE,
+            ArmorArc::Rear => RED,
+        };
+        let color = Color { a: alpha, ..color };
+        let (dx, dy) = (indicator.hit.from.cos(), indicator.hit.from.sin());
+        let tip = vec2(center_x + dx * radius, center_y + dy * radius);
+        let base = tip - vec2(dx, dy) * size;
+        let across = vec2(-dy, dx) * size * 0.6;
+        draw_triangle(tip, base + across, base - across, color);
+        let label = format!("{} hit", indicator.hit.arc.name());
+        let font_size = 14.0 * game_state.ui_scale;
+        let width = measure_text(&label, None, font_size as u16, 1.0).width;
+        let at = base - vec2(dx, dy) * size;
+        draw_text(&label, at.x - width / 2.0, at.y, font_size, color);
+    }
+}
+
+/// What a station does, shown while we stand next to one we could take:
+/// its buttons, what they cost and how long until each is ready again
+fn render_station_tooltip(game_state: &GameState) {
+    let PlayerLocation::InsideMech { mech_id, pos } = game_state.player_location else {
+        return;
+    };
+    if game_state.station_panel.is_some() || is_player_at_station(game_state) {
+        return;
+    }
+    let Some((station_id, definition)) =
+        station_in_reach(game_state, mech_id, pos).and_then(|station| {
+            let definition = game_state
+                .station_registry
+                .get_definition(station.station_type)?;
+            Some((station._id, definition))
+        })
+    else {
+        return;
+    };
+    let cooldowns = game_state.station_cooldowns.get(&station_id);
+
+    let mut summary = vec![format!("{}x{} tiles", definition.size.0, definition.size.1)];
+    if definition.cooldown_seconds > 0.0 {
+        summary.push(format!("{:.1}s between uses", definition.cooldown_seconds));
+    }
+    if !definition.resource_requirements.is_empty() {
+        summary.push(format!(
+            "needs {}",
+            cost_text(&definition.resource_requirements)
+        ));
+    }
+
+    let scale = game_state.ui_scale;
+    let line_height = 18.0 * scale;
+    let width = 380.0 * scale;
+    let header = 68.0 * scale;
+    let height = header + definition.button_definitions.len() as f32 * line_height * 2.0;
+    let x = screen_width() - width - 10.0;
+    let y = screen_height() - height - 10.0;
+
+    draw_rectangle(x, y, width, height, Color::new(0.0, 0.0, 0.0, 0.7));
+    draw_rectangle_lines(x, y, width, height, 2.0, SKYBLUE);
+    draw_text(
+        &definition.name,
+        x + 10.0,
+        y + 22.0 * scale,
+        20.0 * scale,
+        WHITE,
+    );
+    draw_text(
+        &definition.description,
+        x + 10.0,
+        y + 40.0 * scale,
+        14.0 * scale,
+        LIGHTGRAY,
+    );
+    draw_text(
+        &summary.join(" | "),
+        x + 10.0,
+        y + 58.0 * scale,
+        14.0 * scale,
+        GOLD,
+    );
+
+    for (i, button) in definition.button_definitions.iter().enumerate() {
+        let row_y = y + header + i as f32 * line_height * 2.0 + line_height;
+        let remaining = cooldowns
+            .and_then(|cooldowns| cooldowns.get(i))
+            .copied()
+            .unwrap_or(0.0);
+        let (label, color) = if remaining > 0.0 {
+            (format!("{} - ready in {remaining:.1}s", button.label), GRAY)
+        } else {
+            (
+                format!(
+                    "{} - {:.1}s cooldown",
+                    button.label, button.cooldown_seconds
+                ),
+                YELLOW,
+            )
+        };
+        draw_text(&label, x + 10.0, row_y, 16.0 * scale, color);
+
+        let mut detail = button.description.clone();
+        if !button.resource_cost.is_empty() {
+            detail.push_str(&format!(" - costs {}", cost_text(&button.resource_cost)));
+        }
+        draw_text(
+            &detail,
+            x + 24.0 * scale,
+            row_y + line_height - 2.0,
+            14.0 * scale,
+            LIGHTGRAY,
+        );
+    }
+}
+
+/// Resource amounts like "2 Wiring, 1 ScrapMetal", in a steady order
+fn cost_text(costs: &HashMap<ResourceType, u32>) -> String {
+    let mut costs: Vec<String> = costs
+        .iter()
+        .map(|(resource, amount)| format!("{amount} {resource:?}"))
+        .collect();
+    costs.sort();
+    costs.join(", ")
+}
+
+/// A live readout under the station's description, for stations with one
+fn station_status(game_state: &GameState, station_id: StationId) -> Option<(String, Color)> {
+    let station = game_state.stations.get(&station_id)?;
+    if station.station_type != StationType::PointDefense {
+        return None;
+    }
+    let battery = game_state.mechs.get(&station.mech_id)?.point_defense;
+    let mode = if battery.auto { "auto" } else { "manual" };
+    let color = if battery.ammo == 0 { RED } else { GOLD };
+    Some((
+        format!(
+            "Interceptors {}/{} - {mode}",
+            battery.ammo,
+            shared::POINT_DEFENSE_MAGAZINE
+        ),
+        color,
+    ))
+}
+
+/// Our mech's resource totals, so the crew knows what to gather next
+fn render_team_cargo(game_state: &GameState) {
+    let Some(mech) = game_state
+        .mechs
+        .values()
+        .find(|mech| Some(mech.team) == game_state.player_team)
+    else {
+        return;
+    };
+
+    let x = screen_width() - RESOURCE_PANEL_WIDTH - RESOURCE_PANEL_OFFSET_X;
+    let y = RESOURCE_PANEL_OFFSET_Y;
+    draw_rectangle(
+        x,
+        y,
+        RESOURCE_PANEL_WIDTH,
+        RESOURCE_PANEL_HEIGHT,
+        Color::new(0.0, 0.0, 0.0, 0.6),
+    );
+    draw_text(
+        "Team Cargo",
+        x + 10.0,
+        y + RESOURCE_TEXT_SPACING,
+        18.0,
+        WHITE,
+    );
+
+    let resources = [
+        (ResourceType::ScrapMetal, "Scrap Metal"),
+        (ResourceType::ComputerComponents, "Components"),
+        (ResourceType::Wiring, "Wiring"),
+        (ResourceType::Batteries, "Batteries"),
+        (ResourceType::PowerCore, "Power Cores"),
+    ];
+    let delivered = game_state
+        .last_delivery
+        .map(|(resource_type, _)| resource_type);
+    for (i, (resource_type, name)) in resources.iter().enumerate() {
+        let count = mech.resource_inventory.get(resource_type).unwrap_or(&0);
+        let (text, color) = if delivered == Some(*resource_type) {
+            (format!("{name}: {count} (+1)"), GOLD)
+        } else {
+            (format!("{name}: {count}"), LIGHTGRAY)
+        };
+        let row_y = y + RESOURCE_TEXT_SPACING * (i as f32 + 2.0);
+        draw_text(&text, x + 10.0, row_y, 16.0, color);
+    }
+
+    draw_text(
+        &format!("Delivery score: {}", mech.delivery_score),
+        x + 10.0,
+        y + RESOURCE_PANEL_HEIGHT - 6.0,
+        16.0,
+        YELLOW,
+    );
+}
+
+/// Our carrying slots along the bottom of the screen, the active one outlined
+fn render_inventory_strip(game_state: &GameState) {
+    let Some(player) = game_state
+        .player_id
+        .and_then(|player_id| game_state.players.get(&player_id))
+    else {
+        return;
+    };
+
+    let slots = player.inventory.slots();
+    let stride = INVENTORY_SLOT_SIZE + INVENTORY_SLOT_GAP;
+    let x = (screen_width() - (slots.len() as f32 * stride - INVENTORY_SLOT_GAP)) / 2.0;
+    let y = screen_height() - INVENTORY_STRIP_BOTTOM_OFFSET - INVENTORY_SLOT_SIZE;
+
+    for (i, slot) in slots.iter().enumerate() {
+        let slot_x = x + i as f32 * stride;
+        draw_rectangle(
+            slot_x,
+            y,
+            INVENTORY_SLOT_SIZE,
+            INVENTORY_SLOT_SIZE,
+            Color::new(0.0, 0.0, 0.0, 0.6),
+        );
+        let (outline, thickness) = if i == player.inventory.active_slot() {
+            (YELLOW, 3.0)
+        } else {
+            (GRAY, 1.0)
+        };
+        draw_rectangle_lines(
+            slot_x,
+            y,
+            INVENTORY_SLOT_SIZE,
+            INVENTORY_SLOT_SIZE,
+            thickness,
+            outline,
+        );
+        if let Some(item) = slot {
+            draw_item_icon(
+                *item,
+                slot_x + INVENTORY_SLOT_SIZE / 2.0,
+                y + INVENTORY_SLOT_SIZE / 2.0,
+                INVENTORY_SLOT_SIZE * 0.5,
+                get_item_color(*item, game_state.accessibility.palette()),
+            );
+        }
+    }
+
+    if let Some(item) = player.inventory.active_item() {
+        draw_text(item.label(), x, y - 6.0, 16.0, WHITE);
+    }
+}
+
+/// Objective panel along the top: one progress bar per team, the clock,
+/// and the result once the match is over
+fn render_game_mode(game_state: &GameState) {
+    let Some(hud) = &game_state.game_mode else {
+        return;
+    };
+
+    let scale = game_state.ui_scale;
+    let width = 420.0 * scale;
+    let x = (screen_width() - width) / 2.0;
+    let y = 8.0;
+    let height = (42.0 + hud.teams.len() as f32 * 18.0) * scale;
+    draw_rectangle(x, y, width, height, Color::new(0.0, 0.0, 0.0, 0.6));
+
+    let heading = match hud.time_left {
+        Some(seconds) => format!(
+            "{} - {}:{:02}",
+            hud.mode.title(),
+            seconds / 60,
+            seconds % 60
+        ),
+        None => hud.mode.title().to_string(),
+    };
+    draw_text(&heading, x + 10.0, y + 18.0 * scale, 18.0 * scale, GOLD);
+    draw_text(
+        &hud.objective,
+        x + 10.0,
+        y + 36.0 * scale,
+        16.0 * scale,
+        WHITE,
+    );
+
+    for (i, objective) in hud.teams.iter().enumerate() {
+        let row_y = y + (44.0 + i as f32 * 18.0) * scale;
+        let color = get_player_color(objective.team, game_state.accessibility.palette());
+        let fraction = if objective.target > 0 {
+            (objective.value as f32 / objective.target as f32).min(1.0)
+        } else {
+            0.0
+        };
+        let (bar_width, bar_height) = (160.0 * scale, 12.0 * scale);
+        draw_rectangle(x + 10.0, row_y, bar_width, bar_height, DARKGRAY);
+        draw_rectangle(x + 10.0, row_y, bar_width * fraction, bar_height, color);
+        let label = format!(
+            "{} {}/{}",
+            objective.label, objective.value, objective.target
+        );
+        let label_y = row_y + 11.0 * scale;
+        draw_text(&label, x + bar_width + 20.0, label_y, 16.0 * scale, WHITE);
+    }
+
+    if let Some(result) = &hud.result {
+        let text = match result.winner {
+            Some(team) => format!("{team:?} wins - {}", result.reason),
+            None => format!("Draw - {}", result.reason),
+        };
+        let font_size = 32.0 * game_state.ui_scale;
+        let dimensions = measure_text(&text, None, font_size as u16, 1.0);
+        let text_x = (screen_width() - dimensions.width) / 2.0;
+        let text_y = screen_height() / 3.0;
+        draw_rectangle(
+            text_x - 14.0,
+            text_y - dimensions.height - 10.0,
+            dimensions.width + 28.0,
+            dimensions.height + 20.0,
+            Color::new(0.0, 0.0, 0.0, 0.75),
+        );
+        draw_text(&text, text_x, text_y, font_size, GOLD);
+    }
+}
+
+/// Roles to pick from, who on our team has picked what and who's ready,
+/// while the match waits in the hangar
+fn render_hangar(game_state: &GameState) {
+    let Some(hangar) = &game_state.hangar else {
+        return;
+    };
+    let Some(team) = game_state.player_team else {
+        return;
+    };
+    let ours = hangar.ours(game_state.player_id);
+    let crew: Vec<_> = hangar
+        .crew
+        .iter()
+        .filter(|crew| crew.team == team)
+        .collect();
+
+    let scale = game_state.ui_scale;
+    let line = 20.0 * scale;
+    let width = 460.0 * scale;
+    let rows = shared::CrewRole::ALL.len().max(crew.len()) as f32;
+    let height = (70.0 + rows * 20.0) * scale;
+    let x = (screen_width() - width) / 2.0;
+    let y = (screen_height() - height) / 2.0;
+    draw_rectangle(x, y, width, height, Color::new(0.0, 0.0, 0.0, 0.75));
+    draw_rectangle_lines(x, y, width, height, 2.0, GOLD);
+
+    let seconds = hangar.time_left.ceil() as u32;
+    let heading = format!(
+        "Hangar - match starts in {}:{:02}",
+        seconds / 60,
+        seconds % 60
+    );
+    draw_text(&heading, x + 10.0, y + 22.0 * scale, 22.0 * scale, GOLD);
+
+    let top = y + 48.0 * scale;
+    for (i, role) in shared::CrewRole::ALL.into_iter().enumerate() {
+        let picked = ours.is_some_and(|crew| crew.role == Some(role));
+        let text = format!(
+            "{} {} ({})",
+            i + 1,
+            role.name(),
+            hangar.taken_by(team, role)
+        );
+        let color = if picked { GOLD } else { WHITE };
+        draw_text(&text, x + 10.0, top + i as f32 * line, 18.0 * scale, color);
+    }
+
+    let column = x + width / 2.0;
+    for (i, crew) in crew.iter().enumerate() {
+        let role = crew.role.map_or("-", |role| role.name());
+        let mark = if crew.ready { "+" } else { " " };
+        let text = format!("{mark} {}: {role}", crew.name);
+        let color = if crew.ready { GREEN } else { LIGHTGRAY };
+        draw_text(&text, column, top + i as f32 * line, 18.0 * scale, color);
+    }
+
+    let hint = match ours {
+        Some(crew) if crew.ready => "Enter to stand down",
+        _ => "1-4 pick a role, Enter to ready up",
+    };
+    let hint_y = y + height - 8.0 * scale;
+    draw_text(hint, x + 10.0, hint_y, 16.0 * scale, SKYBLUE);
+}
+
+fn render_pacing_announcement(game_state: &GameState) {
+    let Some((announcement, _)) = &game_state.pacing_announcement else {
+        return;
+    };
+
+    let font_size = 24.0 * game_state.ui_scale;
+    let dimensions = measure_text(announcement, None, font_size as u16, 1.0);
+    let x = (screen_width() - dimensions.width) / 2.0;
+    let y = 80.0;
+    draw_rectangle(
+        x - 10.0,
+        y - dimensions.height - 8.0,
+        dimensions.width + 20.0,
+        dimensions.height + 16.0,
+        Color::new(0.0, 0.0, 0.0, 0.6),
+    );
+    draw_text(announcement, x, y, font_size, ORANGE);
+}
+
+fn render_tutorial(game_state: &GameState) {
+    let Some((text, step, total)) = &game_state.tutorial else {
+        return;
+    };
+
+    let heading = format!("Tutorial {step}/{total}");
+    let scale = game_state.ui_scale;
+    let font_size = 22.0 * scale;
+    let dimensions = measure_text(text, None, font_size as u16, 1.0);
+    let width = dimensions.width.max(160.0) + 20.0;
+    let height = 54.0 * scale;
+    let x = (screen_width() - width) / 2.0;
+    let y = screen_height() - 96.0 - height;
+    draw_rectangle(x, y, width, height, Color::new(0.0, 0.0, 0.0, 0.7));
+    draw_rectangle_lines(x, y, width, height, 2.0, SKYBLUE);
+    draw_text(&heading, x + 10.0, y + 18.0 * scale, 16.0 * scale, SKYBLUE);
+    draw_text(text, x + 10.0, y + 42.0 * scale, font_size, WHITE);
+}
+
+fn render_shutdown_warning(game_state: &GameState) {
+    let Some((reason, remaining)) = &game_state.server_shutdown else {
+        return;
+    };
+
+    let text = if *remaining > 0.0 {
+        format!("Server shutting down in {}s: {reason}", remaining.ceil())
+    } else {
+        format!("Server shutting down: {reason}")
+    };
+    let font_size = 24.0 * game_state.ui_scale;
+    let dimensions = measure_text(&text, None, font_size as u16, 1.0);
+    let x = (screen_width() - dimensions.width) / 2.0;
+    let y = 120.0;
+    draw_rectangle(
+        x - 10.0,
+        y - dimensions.height - 8.0,
+        dimensions.width + 20.0,
+        dimensions.height + 16.0,
+        Color::new(0.0, 0.0, 0.0, 0.6),
+    );
+    draw_text(&text, x, y, font_size, RED);
+}
+
+/// A quiet note under the top bar while the server sheds load, so missing
+/// effects and choppier updates don't look like the client's fault
+fn render_server_load(game_state: &GameState) {
+    if game_state.server_load == ServerLoad::Normal {
+        return;
+    }
+
+    let text = game_state.server_load.description();
+    let font_size = 18.0 * game_state.ui_scale;
+    let dimensions = measure_text(text, None, font_size as u16, 1.0);
+    let x = (screen_width() - dimensions.width) / 2.0;
+    let y = 150.0;
+    draw_rectangle(
+        x - 8.0,
+        y - dimensions.height - 6.0,
+        dimensions.width + 16.0,
+        dimensions.height + 12.0,
+        Color::new(0.0, 0.0, 0.0, 0.5),
+    );
+    draw_text(text, x, y, font_size, ORANGE);
+}
+
+fn render_join_rejected(game_state: &GameState) {
+    let Some(reason) = &game_state.join_rejected else {
+        return;
+    };
+
+    let font_size = 24.0 * game_state.ui_scale;
+    let dimensions = measure_text(reason, None, font_size as u16, 1.0);
+    let x = (screen_width() - dimensions.width) / 2.0;
+    let y = screen_height() / 2.0;
+    draw_rectangle(
+        x - 10.0,
+        y - dimensions.height - 8.0,
+        dimensions.width + 20.0,
+        dimensions.height + 16.0,
+        Color::new(0.0, 0.0, 0.0, 0.8),
+    );
+    draw_text(reason, x, y, font_size, RED);
+}
+
+/// Achievement toasts stacked up from the bottom-right corner, newest lowest
+fn render_toasts(game_state: &GameState) {
+    let scale = game_state.ui_scale;
+    let (width, height) = (TOAST_WIDTH * scale, TOAST_HEIGHT * scale);
+    let x = screen_width() - width - HUD_BASE_X;
+    let mut y = screen_height() - height - HUD_BASE_X;
+
+    for (title, detail, remaining) in game_state.toasts.iter().rev() {
+        let alpha = (remaining / TOAST_FADE_TIME).min(1.0);
+        draw_rectangle(x, y, width, height, Color::new(0.0, 0.0, 0.0, 0.7 * alpha));
+        draw_rectangle(x, y, 4.0, height, Color::new(1.0, 0.84, 0.0, alpha));
+        let gold = Color::new(1.0, 0.84, 0.0, alpha);
+        draw_text(title, x + 12.0, y + 18.0 * scale, 18.0 * scale, gold);
+        let grey = Color::new(0.8, 0.8, 0.8, alpha);
+        draw_text(detail, x + 12.0, y + 36.0 * scale, 14.0 * scale, grey);
+        y -= height + TOAST_GAP;
+    }
+}
+
 fn is_player_at_station(game_state: &GameState) -> bool {
     if let Some(player_id) = game_state.player_id {
         game_state
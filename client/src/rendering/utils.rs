@@ -1,31 +1,91 @@
+use crate::accessibility::Palette;
 use crate::game_state::MechState;
 use macroquad::prelude::*;
 use shared::constants::*;
+use shared::inventory::ItemType;
+use shared::render_constants::*;
 use shared::types::*;
 
+fn rgb((r, g, b): (f32, f32, f32)) -> Color {
+    Color::new(r, g, b, 1.0)
+}
+
 /// Get the color for a resource type
-pub fn get_resource_color(resource_type: ResourceType) -> Color {
-    match resource_type {
-        ResourceType::ScrapMetal => DARKGRAY,
-        ResourceType::ComputerComponents => GREEN,
-        ResourceType::Wiring => YELLOW,
-        ResourceType::Batteries => ORANGE,
+pub fn get_resource_color(resource_type: ResourceType, palette: Palette) -> Color {
+    match (palette, resource_type) {
+        (_, ResourceType::ScrapMetal) => DARKGRAY,
+        (Palette::Standard, ResourceType::ComputerComponents) => GREEN,
+        (Palette::Standard, ResourceType::Wiring) => YELLOW,
+        (Palette::Standard, ResourceType::Batteries) => ORANGE,
+        (Palette::ColorblindSafe, ResourceType::ComputerComponents) => {
+            rgb(COLORBLIND_COMPONENTS_COLOR)
+        }
+        (Palette::ColorblindSafe, ResourceType::Wiring) => rgb(COLORBLIND_WIRING_COLOR),
+        (Palette::ColorblindSafe, ResourceType::Batteries) => rgb(COLORBLIND_BATTERIES_COLOR),
+        (Palette::Standard, ResourceType::PowerCore) => MAGENTA,
+        (Palette::ColorblindSafe, ResourceType::PowerCore) => rgb(COLORBLIND_POWER_CORE_COLOR),
+    }
+}
+
+/// Get the color for anything that fits in an inventory slot
+pub fn get_item_color(item: ItemType, palette: Palette) -> Color {
+    match item {
+        ItemType::Resource(resource_type) => get_resource_color(resource_type, palette),
+        ItemType::Keycard => GOLD,
+        ItemType::Grenade => DARKGREEN,
+        ItemType::Scanner => SKYBLUE,
+    }
+}
+
+/// Draw an item centered on (x, y): resources as dots, keycards as cards,
+/// grenades as a body with a pin, scanners as a handset with a dish
+pub fn draw_item_icon(item: ItemType, x: f32, y: f32, size: f32, color: Color) {
+    match item {
+        ItemType::Resource(_) => draw_circle(x, y, size / 2.0, color),
+        ItemType::Keycard => {
+            draw_rectangle(x - size / 2.0, y - size * 0.3, size, size * 0.6, color);
+            draw_rectangle(x - size / 2.0, y - size * 0.1, size, size * 0.12, BLACK);
+        }
+        ItemType::Grenade => {
+            draw_circle(x, y + size * 0.1, size * 0.4, color);
+            draw_rectangle(
+                x - size * 0.1,
+                y - size * 0.5,
+                size * 0.2,
+                size * 0.25,
+                GRAY,
+            );
+        }
+        ItemType::Scanner => {
+            draw_rectangle(
+                x - size * 0.2,
+                y - size * 0.1,
+                size * 0.4,
+                size * 0.6,
+                color,
+            );
+            draw_circle_lines(x, y - size * 0.25, size * 0.3, 2.0, color);
+        }
     }
 }
 
 /// Get the color for a team
-pub fn get_team_color(team: TeamId) -> Color {
-    match team {
-        TeamId::Red => Color::new(0.8, 0.2, 0.2, 1.0),
-        TeamId::Blue => Color::new(0.2, 0.2, 0.8, 1.0),
+pub fn get_team_color(team: TeamId, palette: Palette) -> Color {
+    match (palette, team) {
+        (Palette::Standard, TeamId::Red) => Color::new(0.8, 0.2, 0.2, 1.0),
+        (Palette::Standard, TeamId::Blue) => Color::new(0.2, 0.2, 0.8, 1.0),
+        (Palette::ColorblindSafe, TeamId::Red) => rgb(COLORBLIND_TEAM_RED_COLOR),
+        (Palette::ColorblindSafe, TeamId::Blue) => rgb(COLORBLIND_TEAM_BLUE_COLOR),
     }
 }
 
 /// Get the color for a player based on their team
-pub fn get_player_color(team: TeamId) -> Color {
-    match team {
-        TeamId::Red => Color::new(1.0, 0.3, 0.3, 1.0),
-        TeamId::Blue => Color::new(0.3, 0.3, 1.0, 1.0),
+pub fn get_player_color(team: TeamId, palette: Palette) -> Color {
+    match (palette, team) {
+        (Palette::Standard, TeamId::Red) => Color::new(1.0, 0.3, 0.3, 1.0),
+        (Palette::Standard, TeamId::Blue) => Color::new(0.3, 0.3, 1.0, 1.0),
+        (Palette::ColorblindSafe, TeamId::Red) => rgb(COLORBLIND_PLAYER_RED_COLOR),
+        (Palette::ColorblindSafe, TeamId::Blue) => rgb(COLORBLIND_PLAYER_BLUE_COLOR),
     }
 }
 
@@ -34,12 +94,34 @@ pub fn get_station_color(station_type: StationType) -> Color {
     match station_type {
         StationType::WeaponLaser => RED,
         StationType::WeaponProjectile => ORANGE,
+        StationType::WeaponEmp => VIOLET,
         StationType::Engine => BLUE,
         StationType::Shield => SKYBLUE,
         StationType::Repair => GREEN,
         StationType::Electrical => YELLOW,
         StationType::Upgrade => PURPLE,
         StationType::Pilot => Color::new(0.5, 0.8, 0.5, 1.0), // Light green
+        StationType::TurretControl => PINK,
+        StationType::Radar => LIME,
+        StationType::PointDefense => GOLD,
+    }
+}
+
+/// Border color showing how far a station has been upgraded
+pub fn get_station_tier_color(level: u8) -> Color {
+    match level {
+        0 | 1 => WHITE,
+        2 => SKYBLUE,
+        _ => GOLD,
+    }
+}
+
+/// One pip in a station tile's corner for each tier bought past the first
+pub fn draw_station_tier_badge(x: f32, y: f32, size: f32, level: u8, color: Color) {
+    let radius = size * 0.08;
+    for pip in 0..level.saturating_sub(1) {
+        let pip_x = x + size - radius * 2.0 - pip as f32 * radius * 2.5;
+        draw_circle(pip_x, y + radius * 2.0, radius, color);
     }
 }
 
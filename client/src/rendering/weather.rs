@@ -0,0 +1,46 @@
+//! Night and weather washed over the whole view: a dark blue tint that
+//! deepens toward midnight, a grey haze in fog and falling streaks in rain.
+//! Drawn in screen space on top of the world and under the HUD.
+
+use crate::game_state::GameState;
+use macroquad::prelude::*;
+use shared::{
+    Weather, FOG_TINT_ALPHA, FOG_TINT_COLOR, NIGHT_TINT_ALPHA, NIGHT_TINT_COLOR, RAIN_FALL_SPEED,
+    RAIN_STREAKS, RAIN_STREAK_LENGTH,
+};
+
+pub fn render_environment(game_state: &GameState) {
+    let environment = &game_state.environment;
+    let (width, height) = (screen_width(), screen_height());
+
+    let night = NIGHT_TINT_ALPHA * (1.0 - environment.daylight());
+    if night > 0.0 {
+        let (r, g, b) = NIGHT_TINT_COLOR;
+        draw_rectangle(0.0, 0.0, width, height, Color::new(r, g, b, night));
+    }
+
+    match environment.weather {
+        Weather::Clear => {}
+        Weather::Fog => {
+            let (r, g, b) = FOG_TINT_COLOR;
+            draw_rectangle(0.0, 0.0, width, height, Color::new(r, g, b, FOG_TINT_ALPHA));
+        }
+        Weather::Rain => render_rain(width, height, get_time() as f32),
+    }
+}
+
+/// Slanted streaks, each falling on its own fixed column and phase so the
+/// pattern doesn't need any state between frames
+fn render_rain(width: f32, height: f32, time: f32) {
+    let color = Color::new(0.7, 0.75, 0.9, 0.35);
+    let drift = RAIN_STREAK_LENGTH * 0.3;
+    for i in 0..RAIN_STREAKS {
+        let seed = i as f32 * 12.9898;
+        let column = (seed.sin() * 43758.547).fract().abs();
+        let phase = ((seed * 1.7).sin() * 24634.635).fract().abs();
+        let x = column * width;
+        let y = ((phase * height + time * RAIN_FALL_SPEED) % (height + RAIN_STREAK_LENGTH))
+            - RAIN_STREAK_LENGTH;
+        draw_line(x, y, x - drift, y + RAIN_STREAK_LENGTH, 1.0, color);
+    }
+}
@@ -1,3 +1,5 @@
+use super::iff;
+use super::sprites::{draw_player, SpriteSheet};
 use super::utils::*;
 use super::RenderFlags;
 use crate::game_state::*;
@@ -5,9 +7,21 @@ use crate::vision::{ClientVisionSystem, FogOfWarRenderer};
 use macroquad::prelude::*;
 use shared::TileRange;
 use shared::{
+    arena_map::{hazard_color, map_tile_color, HazardKind},
+    balance::ESCORT_GOAL_RADIUS,
+    components::{StatusEffect, StatusEffectKind, StructureKind},
     constants::*,
     coordinates::{MechDoorPositions, ViewportCalculations},
+    decals::DecalKind,
+    pacing::PacingZoneKind,
+    render_constants::{
+        DECAL_OPACITY, INTERCEPTOR_TRACER_LENGTH, PACING_ZONE_OUTLINE_WIDTH, RESOURCE_GLOW_ALPHA,
+        RESOURCE_GLOW_PULSE_SPEED, RESOURCE_GLOW_RARE_RADIUS, RESOURCE_GLOW_UNCOMMON_RADIUS,
+        SAFE_ZONE_FILL_ALPHA, SAFE_ZONE_OUTLINE_WIDTH,
+    },
+    spawn_tables::Rarity,
     types::*,
+    WeaponType,
 };
 
 #[cfg(feature = "profiling")]
@@ -29,6 +43,7 @@ pub fn render_world_view_with_vision(
         cam_y,
         vision_system,
         &RenderFlags::default(),
+        None,
     );
 }
 
@@ -38,16 +53,26 @@ pub fn render_world_view_with_vision_and_flags(
     cam_y: f32,
     vision_system: Option<&ClientVisionSystem>,
     flags: &RenderFlags,
+    sprites: Option<&SpriteSheet>,
 ) {
+    // Zooming out shows more than a screen's worth of world
+    let visible_bounds = game_state.camera.visible_world_bounds();
+
     if flags.render_tiles {
         #[cfg(feature = "profiling")]
         scope!("grass_background");
-        render_grass_background(cam_x, cam_y, vision_system);
+        render_grass_background(cam_x, cam_y, visible_bounds, vision_system);
+        render_arena_map(game_state, cam_x, cam_y, vision_system);
+        render_structures(game_state, cam_x, cam_y, vision_system);
+        render_decals(game_state, cam_x, cam_y, vision_system);
     }
     if flags.render_tiles {
         #[cfg(feature = "profiling")]
         scope!("arena_boundaries");
-        render_arena_boundaries(cam_x, cam_y);
+        render_arena_boundaries(game_state, cam_x, cam_y);
+        render_pacing_zones(game_state, cam_x, cam_y);
+        render_safe_zones(game_state, cam_x, cam_y);
+        render_game_mode_goal(game_state, cam_x, cam_y);
     }
     if flags.render_mechs {
         #[cfg(feature = "profiling")]
@@ -72,7 +97,7 @@ pub fn render_world_view_with_vision_and_flags(
     if flags.render_players {
         #[cfg(feature = "profiling")]
         scope!("players");
-        render_players_in_world(game_state, cam_x, cam_y, vision_system);
+        render_players_in_world(game_state, cam_x, cam_y, vision_system, sprites);
     }
 
     // Render fog overlay for completely invisible areas
@@ -80,26 +105,27 @@ pub fn render_world_view_with_vision_and_flags(
         #[cfg(feature = "profiling")]
         scope!("fog_overlay");
         if let Some(vision) = vision_system {
-            render_fog_overlay(vision, cam_x, cam_y);
+            render_fog_overlay(vision, cam_x, cam_y, visible_bounds);
         }
     }
 
     // Debug info removed - now shown in debug overlay instead
 }
 
-fn render_grass_background(cam_x: f32, cam_y: f32, vision_system: Option<&ClientVisionSystem>) {
+fn render_grass_background(
+    cam_x: f32,
+    cam_y: f32,
+    (visible_min, visible_max): (WorldPos, WorldPos),
+    vision_system: Option<&ClientVisionSystem>,
+) {
     let grass_color = Color::new(0.2, 0.6, 0.2, 1.0);
     let grass_tile_size = TILE_SIZE * 2.0;
 
     // Calculate visible grass tiles using custom tile size
-    let screen_w = screen_width();
-    let screen_h = screen_height();
-    let camera_offset = WorldPos::new(cam_x, cam_y);
-
     let grass_region = ViewportCalculations::get_visible_range_with_tile_size(
-        camera_offset,
-        screen_w,
-        screen_h,
+        WorldPos::new(-visible_min.x, -visible_min.y),
+        visible_max.x - visible_min.x,
+        visible_max.y - visible_min.y,
         grass_tile_size,
         TileRange::new(1), // 1 tile padding
     );
@@ -139,13 +165,218 @@ fn render_grass_background(cam_x: f32, cam_y: f32, vision_system: Option<&Client
     }
 }
 
-fn render_arena_boundaries(cam_x: f32, cam_y: f32) {
-    let arena_width = ARENA_WIDTH_TILES as f32 * TILE_SIZE;
-    let arena_height = ARENA_HEIGHT_TILES as f32 * TILE_SIZE;
+fn render_arena_boundaries(game_state: &GameState, cam_x: f32, cam_y: f32) {
+    // Rooms can run smaller arenas than the full-size default
+    let (arena_width, arena_height) = game_state.arena_map.as_ref().map_or(
+        (
+            ARENA_WIDTH_TILES as f32 * TILE_SIZE,
+            ARENA_HEIGHT_TILES as f32 * TILE_SIZE,
+        ),
+        |map| map.world_size(),
+    );
 
     draw_rectangle_lines(cam_x, cam_y, arena_width, arena_height, 3.0, GRAY);
 }
 
+/// Painted tiles and hazards from the arena map, drawn over the grass
+fn render_arena_map(
+    game_state: &GameState,
+    cam_x: f32,
+    cam_y: f32,
+    vision_system: Option<&ClientVisionSystem>,
+) {
+    let Some(map) = &game_state.arena_map else {
+        return;
+    };
+    let visibility_at = |pos: TilePos| vision_system.map_or(1.0, |v| v.get_visibility(pos));
+
+    for map_tile in &map.tiles {
+        let visibility = visibility_at(map_tile.position);
+        if visibility < 0.05 {
+            continue;
+        }
+        let (r, g, b) = map_tile_color(map_tile.tile);
+        let color = FogOfWarRenderer::apply_fog_to_color(Color::new(r, g, b, 1.0), visibility);
+        let world = map_tile.position.to_world();
+        draw_rectangle(cam_x + world.x, cam_y + world.y, TILE_SIZE, TILE_SIZE, color);
+    }
+
+    for hazard in &map.hazards {
+        let visibility = visibility_at(hazard.position);
+        if visibility < 0.05 {
+            continue;
+        }
+        let (r, g, b) = hazard_color(hazard.kind);
+        let color = FogOfWarRenderer::apply_fog_to_color(Color::new(r, g, b, 0.5), visibility);
+        let world = hazard.position.to_world();
+        match hazard.kind {
+            HazardKind::Radiation => {
+                draw_rectangle(cam_x + world.x, cam_y + world.y, TILE_SIZE, TILE_SIZE, color)
+            }
+            HazardKind::Mine => draw_circle(
+                cam_x + world.x + TILE_SIZE / 2.0,
+                cam_y + world.y + TILE_SIZE / 2.0,
+                TILE_SIZE / 4.0,
+                color,
+            ),
+        }
+    }
+}
+
+/// Marks fighting left on the ground, drawn over the terrain but under
+/// everything standing on it
+fn render_decals(
+    game_state: &GameState,
+    cam_x: f32,
+    cam_y: f32,
+    vision_system: Option<&ClientVisionSystem>,
+) {
+    for decal in game_state.decals.iter() {
+        let visibility = vision_system.map_or(1.0, |v| v.get_visibility(decal.position));
+        if visibility < 0.05 {
+            continue;
+        }
+        let (x, y) = ViewportCalculations::tile_center_to_screen(
+            decal.position,
+            WorldPos::new(cam_x, cam_y),
+        );
+        let fogged = |r, g, b| {
+            FogOfWarRenderer::apply_fog_to_color(Color::new(r, g, b, DECAL_OPACITY), visibility)
+        };
+        match decal.kind {
+            DecalKind::Scorch => {
+                draw_circle(x, y, TILE_SIZE * 0.45, fogged(0.08, 0.07, 0.06));
+                draw_circle(x, y, TILE_SIZE * 0.25, fogged(0.02, 0.02, 0.02));
+            }
+            DecalKind::Gore => {
+                draw_circle(x, y, TILE_SIZE * 0.35, fogged(0.35, 0.02, 0.02));
+                draw_circle(
+                    x + TILE_SIZE * 0.2,
+                    y - TILE_SIZE * 0.1,
+                    TILE_SIZE * 0.12,
+                    fogged(0.45, 0.03, 0.03),
+                );
+            }
+            DecalKind::Burn => {
+                draw_circle(x, y, TILE_SIZE * 0.3, fogged(0.25, 0.08, 0.02));
+                draw_circle_lines(x, y, TILE_SIZE * 0.3, 1.5, fogged(0.6, 0.25, 0.05));
+            }
+            DecalKind::Debris => {
+                // Scatter fragments the same way every frame, picked by the tile
+                let seed = decal.position.x.wrapping_mul(31) ^ decal.position.y.wrapping_mul(17);
+                for i in 0..4 {
+                    let angle = (seed + i * 97) as f32;
+                    let (dx, dy) = (angle.cos() * TILE_SIZE * 0.3, angle.sin() * TILE_SIZE * 0.3);
+                    let size = TILE_SIZE * 0.12;
+                    draw_rectangle(
+                        x + dx - size / 2.0,
+                        y + dy - size / 2.0,
+                        size,
+                        size,
+                        fogged(0.4, 0.4, 0.42),
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Team base buildings: a floor outline for the repair bay and a crate for
+/// the resupply depot, in the owning team's color
+fn render_structures(
+    game_state: &GameState,
+    cam_x: f32,
+    cam_y: f32,
+    vision_system: Option<&ClientVisionSystem>,
+) {
+    for structure in &game_state.structures {
+        let center = TilePos::new(
+            structure.position.x + structure.width / 2,
+            structure.position.y + structure.height / 2,
+        );
+        let visibility = vision_system.map_or(1.0, |v| v.get_visibility(center));
+        let team_color = FogOfWarRenderer::apply_fog_to_color(
+            get_team_color(structure.team, game_state.accessibility.palette()),
+            visibility.max(0.3),
+        );
+        let world = structure.position.to_world();
+        let (x, y) = (cam_x + world.x, cam_y + world.y);
+        let (width, height) = (
+            structure.width as f32 * TILE_SIZE,
+            structure.height as f32 * TILE_SIZE,
+        );
+
+        match structure.kind {
+            StructureKind::RepairBay => {
+                draw_rectangle(x, y, width, height, Color::new(0.3, 0.3, 0.35, 0.35));
+                draw_rectangle_lines(x, y, width, height, 3.0, team_color);
+                draw_text("REPAIR BAY", x + 4.0, y + 14.0, 16.0, team_color);
+            }
+            StructureKind::ResupplyDepot => {
+                draw_rectangle(x, y, width, height, Color::new(0.45, 0.35, 0.2, 1.0));
+                draw_rectangle_lines(x, y, width, height, 2.0, team_color);
+                draw_line(x, y, x + width, y + height, 1.0, team_color);
+                draw_line(x + width, y, x, y + height, 1.0, team_color);
+            }
+        }
+    }
+}
+
+fn render_pacing_zones(game_state: &GameState, cam_x: f32, cam_y: f32) {
+    for zone in &game_state.pacing_zones {
+        let (x, y) = ViewportCalculations::world_to_screen(zone.center, WorldPos::new(cam_x, cam_y));
+        let (fill, outline) = match zone.kind {
+            PacingZoneKind::ResourceSurge => (
+                Color::new(1.0, 0.85, 0.2, 0.12),
+                Color::new(1.0, 0.85, 0.2, 0.8),
+            ),
+            PacingZoneKind::DamageBoost { .. } => (
+                Color::new(1.0, 0.3, 0.1, 0.15),
+                Color::new(1.0, 0.3, 0.1, 0.9),
+            ),
+            // Only the edge of the play area is drawn; the inside is safe ground
+            PacingZoneKind::PlayArea { .. } => (BLANK, Color::new(0.9, 0.1, 0.9, 0.9)),
+        };
+
+        if fill != BLANK {
+            draw_circle(x, y, zone.radius, fill);
+        }
+        draw_circle_lines(x, y, zone.radius, PACING_ZONE_OUTLINE_WIDTH, outline);
+    }
+}
+
+/// Borders of the squares around each team's spawn that enemies can't
+/// shoot into or linger in
+fn render_safe_zones(game_state: &GameState, cam_x: f32, cam_y: f32) {
+    let Some(map) = &game_state.arena_map else {
+        return;
+    };
+    for zone in map.safe_zones() {
+        let color = get_team_color(zone.team, game_state.accessibility.palette());
+        let world = zone.min.to_world();
+        let (x, y) = (cam_x + world.x, cam_y + world.y);
+        let width = (zone.max.x - zone.min.x + 1) as f32 * TILE_SIZE;
+        let height = (zone.max.y - zone.min.y + 1) as f32 * TILE_SIZE;
+        let fill = Color {
+            a: SAFE_ZONE_FILL_ALPHA,
+            ..color
+        };
+        draw_rectangle(x, y, width, height, fill);
+        draw_rectangle_lines(x, y, width, height, SAFE_ZONE_OUTLINE_WIDTH, color);
+    }
+}
+
+/// The spot the game mode is about, like the payload's destination
+fn render_game_mode_goal(game_state: &GameState, cam_x: f32, cam_y: f32) {
+    let Some(goal) = game_state.game_mode.as_ref().and_then(|hud| hud.goal) else {
+        return;
+    };
+    let (x, y) = ViewportCalculations::world_to_screen(goal, WorldPos::new(cam_x, cam_y));
+    let radius = ESCORT_GOAL_RADIUS * TILE_SIZE;
+    draw_circle(x, y, radius, Color::new(1.0, 1.0, 1.0, 0.08));
+    draw_circle_lines(x, y, radius, PACING_ZONE_OUTLINE_WIDTH, GOLD);
+}
+
 fn render_mechs(
     game_state: &GameState,
     cam_x: f32,
@@ -156,9 +387,11 @@ fn render_mechs(
         // Render mech first floor instead of solid rectangle
         render_mech_first_floor(game_state, mech, cam_x, cam_y, vision_system);
 
-        // Render mech outline for clarity
+        // Outline in the team's colour, or the enemy's red, for telling
+        // mechs apart at a glance
         let mech_size = MECH_SIZE_TILES as f32 * TILE_SIZE;
-        let mut outline_color = WHITE;
+        let mut outline_color = iff::mech_outline_color(game_state, mech.team);
+        let mut visibility = 1.0;
 
         // Use continuous world position for smooth movement
         let mech_x = cam_x + mech.world_position.x;
@@ -166,12 +399,20 @@ fn render_mechs(
 
         // Apply fog of war to outline
         if let Some(vision) = vision_system {
-            let visibility = vision.get_visibility(mech.position);
+            visibility = vision.get_visibility(mech.position);
             outline_color = FogOfWarRenderer::apply_fog_to_color(outline_color, visibility);
         }
 
-        // Mech outline border for visual clarity
         draw_rectangle_lines(mech_x, mech_y, mech_size, mech_size, 2.0, outline_color);
+        if visibility > 0.1
+            && iff::Allegiance::of(game_state, None, mech.team) == iff::Allegiance::Enemy
+        {
+            iff::draw_enemy_mech_brackets(mech_x, mech_y, mech_size, outline_color);
+        }
+
+        if let Some(effects) = game_state.status_effects.get(&mech.id) {
+            draw_status_badges(effects, mech_x, mech_y - 4.0);
+        }
 
         // Render visible interior tiles from other floors if looking into mech
         if let Some(vision) = vision_system {
@@ -276,15 +517,38 @@ fn render_mech_first_floor(
 
                     draw_rectangle(tile_x, tile_y, TILE_SIZE, TILE_SIZE, final_color);
 
-                    // Draw station border to indicate it's interactive
-                    let mut border_color = WHITE;
+                    // Draw station border to indicate it's interactive, colored by tier
+                    let level = game_state
+                        .stations
+                        .get(station_id)
+                        .map_or(1, |station| station.upgrade_level);
+                    let mut border_color = get_station_tier_color(level);
                     if let Some(_vision) = vision_system {
-                        border_color = FogOfWarRenderer::apply_fog_to_color(WHITE, visibility);
+                        border_color =
+                            FogOfWarRenderer::apply_fog_to_color(border_color, visibility);
                     }
                     draw_rectangle_lines(tile_x, tile_y, TILE_SIZE, TILE_SIZE, 2.0, border_color);
+                    draw_station_tier_badge(tile_x, tile_y, TILE_SIZE, level, border_color);
                 }
             }
         }
+
+        super::mech_interior::render_fires_on_floor(
+            game_state,
+            mech,
+            0,
+            cam_x + offset_x,
+            cam_y + offset_y,
+            vision_system,
+        );
+        super::mech_interior::render_barricades_on_floor(
+            game_state,
+            mech,
+            0,
+            cam_x + offset_x,
+            cam_y + offset_y,
+            vision_system,
+        );
     } else {
         // Fallback to colored rectangle if no floor data available
         #[cfg(not(target_arch = "wasm32"))]
@@ -293,7 +557,7 @@ fn render_mech_first_floor(
         info!("No floor data available for mech {}, using colored rectangle fallback", mech.id);
         
         let mech_size = MECH_SIZE_TILES as f32 * TILE_SIZE;
-        let mut color = get_team_color(mech.team);
+        let mut color = get_team_color(mech.team, game_state.accessibility.palette());
 
         let mech_x = cam_x + mech.world_position.x;
         let mech_y = cam_y + mech.world_position.y;
@@ -374,7 +638,7 @@ fn render_world_tiles(
     // TODO: Remove once server sends all tiles
     if game_state.visible_tiles.is_empty() {
         for mech in game_state.mechs.values() {
-            let team_color = get_team_color(mech.team);
+            let team_color = get_team_color(mech.team, game_state.accessibility.palette());
 
             // Render door tiles using continuous position for smooth movement
             let doors = MechDoorPositions::from_mech_position(mech.position);
@@ -517,7 +781,8 @@ fn render_resources(
     vision_system: Option<&ClientVisionSystem>,
 ) {
     for resource in &game_state.resources {
-        let mut color = get_resource_color(resource.resource_type);
+        let mut color =
+            get_resource_color(resource.resource_type, game_state.accessibility.palette());
         let resource_tile = TilePos::new(resource.position.x, resource.position.y);
 
         // Apply fog of war if vision system is available
@@ -532,8 +797,51 @@ fn render_resources(
         let (center_x, center_y) =
             ViewportCalculations::tile_center_to_screen(resource_tile, WorldPos::new(cam_x, cam_y));
 
+        // Rarer finds glow, rare ones with a slow pulse
+        let glow = match game_state.spawn_tables.rarity(resource.resource_type) {
+            Rarity::Common => None,
+            Rarity::Uncommon => Some((RESOURCE_GLOW_UNCOMMON_RADIUS, 1.0)),
+            Rarity::Rare if game_state.accessibility.reduced_flash => {
+                Some((RESOURCE_GLOW_RARE_RADIUS, 1.0))
+            }
+            Rarity::Rare => {
+                let pulse = (get_time() as f32 * RESOURCE_GLOW_PULSE_SPEED).sin() * 0.5 + 0.5;
+                Some((RESOURCE_GLOW_RARE_RADIUS, 0.6 + 0.4 * pulse))
+            }
+        };
+        if let Some((radius, strength)) = glow {
+            let halo = Color::new(
+                color.r,
+                color.g,
+                color.b,
+                color.a * RESOURCE_GLOW_ALPHA * strength,
+            );
+            draw_circle(center_x, center_y, radius * TILE_SIZE, halo);
+        }
+
         draw_circle(center_x, center_y, TILE_SIZE / 3.0, color);
     }
+
+    // Dropped items get a ring so they read as loot rather than spawns
+    for ground_item in &game_state.ground_items {
+        let mut color = get_item_color(ground_item.item, game_state.accessibility.palette());
+        let mut ring = WHITE;
+        if let Some(vision) = vision_system {
+            let visibility = vision.get_visibility(ground_item.position.to_tile());
+            if visibility < 0.05 {
+                continue;
+            }
+            color = FogOfWarRenderer::apply_fog_to_color(color, visibility);
+            ring = FogOfWarRenderer::apply_fog_to_color(ring, visibility);
+        }
+
+        let (x, y) = (
+            cam_x + ground_item.position.x,
+            cam_y + ground_item.position.y,
+        );
+        draw_circle_lines(x, y, TILE_SIZE / 2.5, 1.5, ring);
+        draw_item_icon(ground_item.item, x, y, TILE_SIZE / 2.0, color);
+    }
 }
 
 fn render_projectiles(
@@ -544,6 +852,9 @@ fn render_projectiles(
 ) {
     for projectile in &game_state.projectiles {
         let mut color = YELLOW;
+        if let Some(prediction) = projectile.prediction {
+            color.a *= prediction.alpha();
+        }
         let projectile_world_pos = WorldPos::new(projectile.position.x, projectile.position.y);
 
         // Apply fog of war if vision system is available
@@ -561,6 +872,21 @@ fn render_projectiles(
             WorldPos::new(cam_x, cam_y),
         );
 
+        // Point-defense rounds are drawn as tracer streaks
+        if projectile.weapon_type == WeaponType::Interceptor {
+            let mut tracer = Color::new(0.7, 1.0, 1.0, color.a);
+            tracer.a *= game_state.accessibility.flash_intensity();
+            draw_line(
+                screen_x - projectile.velocity.0 * INTERCEPTOR_TRACER_LENGTH,
+                screen_y - projectile.velocity.1 * INTERCEPTOR_TRACER_LENGTH,
+                screen_x,
+                screen_y,
+                2.0,
+                tracer,
+            );
+            continue;
+        }
+
         draw_circle(screen_x, screen_y, 5.0, color);
     }
 }
@@ -570,16 +896,18 @@ fn render_players_in_world(
     cam_x: f32,
     cam_y: f32,
     vision_system: Option<&ClientVisionSystem>,
+    sprites: Option<&SpriteSheet>,
 ) {
     for player in game_state.players.values() {
         if let PlayerLocation::OutsideWorld(pos) = player.location {
-            let mut color = get_player_color(player.team);
+            let mut color = get_player_color(player.team, game_state.accessibility.palette());
             let mut text_color = WHITE;
+            let mut visibility = 1.0;
 
             // Apply fog of war if vision system is available
             if let Some(vision) = vision_system {
                 let tile_pos = pos.to_tile();
-                let visibility = vision.get_visibility(tile_pos);
+                visibility = vision.get_visibility(tile_pos);
                 if visibility < 0.05 {
                     continue; // Don't render invisible players
                 }
@@ -587,50 +915,102 @@ fn render_players_in_world(
                 text_color = FogOfWarRenderer::apply_fog_to_color(text_color, visibility);
             }
 
-            // Player body
-            draw_circle(cam_x + pos.x, cam_y + pos.y, TILE_SIZE / 2.0, color);
+            draw_player(
+                sprites,
+                game_state.animations.get(&player._id),
+                player.facing,
+                cam_x + pos.x,
+                cam_y + pos.y,
+                TILE_SIZE / 2.0,
+                color,
+            );
+            iff::draw_player_mark(
+                game_state,
+                player._id,
+                player.team,
+                cam_x + pos.x,
+                cam_y + pos.y,
+                TILE_SIZE / 2.0,
+                visibility,
+            );
 
             // Player name
             draw_text(
-                &player.name,
+                &player.label(),
                 cam_x + pos.x - 20.0,
                 cam_y + pos.y - TILE_SIZE - 5.0,
                 16.0,
                 text_color,
             );
 
-            // Resource being carried
-            if let Some(resource_type) = player.carrying_resource {
-                let mut resource_color = get_resource_color(resource_type);
+            if let Some(effects) = game_state.status_effects.get(&player._id) {
+                draw_status_badges(effects, cam_x + pos.x - 20.0, cam_y + pos.y - TILE_SIZE - 22.0);
+            }
+
+            // Items being carried, stacked beside the player
+            let carried = player.inventory.slots().iter().flatten();
+            for (i, item) in carried.enumerate() {
+                let mut item_color = get_item_color(*item, game_state.accessibility.palette());
                 if let Some(vision) = vision_system {
                     let tile_pos = pos.to_tile();
                     let visibility = vision.get_visibility(tile_pos);
-                    resource_color =
-                        FogOfWarRenderer::apply_fog_to_color(resource_color, visibility);
+                    item_color = FogOfWarRenderer::apply_fog_to_color(item_color, visibility);
                 }
-                draw_circle(
+                draw_item_icon(
+                    *item,
                     cam_x + pos.x + TILE_SIZE,
-                    cam_y + pos.y,
-                    TILE_SIZE / 4.0,
-                    resource_color,
+                    cam_y + pos.y - TILE_SIZE / 2.0 + i as f32 * TILE_SIZE / 2.0,
+                    TILE_SIZE / 2.5,
+                    item_color,
                 );
             }
         }
     }
 }
 
+/// Draw a row of status effect badges with their bottom-left corner at (x, y)
+fn draw_status_badges(effects: &[StatusEffect], x: f32, y: f32) {
+    const BADGE_WIDTH: f32 = 30.0;
+    const BADGE_HEIGHT: f32 = 14.0;
+
+    for (i, effect) in effects.iter().enumerate() {
+        let badge_x = x + i as f32 * (BADGE_WIDTH + 2.0);
+        let badge_y = y - BADGE_HEIGHT;
+        let color = match effect.kind {
+            StatusEffectKind::Stunned => Color::new(1.0, 0.9, 0.2, 0.9),
+            StatusEffectKind::Slowed => Color::new(0.3, 0.6, 1.0, 0.9),
+            StatusEffectKind::Burning => Color::new(1.0, 0.4, 0.1, 0.9),
+            StatusEffectKind::Emp => Color::new(0.6, 0.3, 1.0, 0.9),
+            StatusEffectKind::Sabotaged => Color::new(0.9, 0.1, 0.1, 0.9),
+            StatusEffectKind::SpawnProtected => Color::new(0.9, 0.9, 0.9, 0.9),
+            StatusEffectKind::Braced => Color::new(0.5, 0.8, 0.5, 0.9),
+            StatusEffectKind::Overcharged => Color::new(1.0, 0.6, 0.9, 0.9),
+        };
+
+        draw_rectangle(badge_x, badge_y, BADGE_WIDTH, BADGE_HEIGHT, color);
+        let label = if effect.stacks > 1 {
+            format!("{}{}", effect.kind.badge(), effect.stacks)
+        } else {
+            effect.kind.badge().to_string()
+        };
+        draw_text(&label, badge_x + 2.0, badge_y + 11.0, 12.0, BLACK);
+    }
+}
+
 const FOG_FADE_DISTANCE: TileRange = TileRange::new(3);
 const VISION_RANGE: TileRange = TileRange::new(13); // Match vision system range in tiles
 
-fn render_fog_overlay(vision_system: &ClientVisionSystem, cam_x: f32, cam_y: f32) {
+fn render_fog_overlay(
+    vision_system: &ClientVisionSystem,
+    cam_x: f32,
+    cam_y: f32,
+    (visible_min, visible_max): (WorldPos, WorldPos),
+) {
     // Calculate visible area using viewport calculations
-    let screen_w = screen_width();
-    let screen_h = screen_height();
-    let camera_offset = WorldPos::new(cam_x, cam_y);
     let visible_region = ViewportCalculations::get_visible_tile_range(
-        camera_offset,
-        screen_w,
-        screen_h,
+        WorldPos::new(-visible_min.x, -visible_min.y),
+        visible_max.x - visible_min.x,
+        visible_max.y - visible_min.y,
         VISION_RANGE,
     );
 
@@ -0,0 +1,387 @@
+//! In-game server browser, opened with F9 or the console's `servers`.
+//!
+//! Lists what a master server knows about, plus (on native builds) whatever
+//! answers a UDP broadcast on the LAN, with a ping estimate for each. The
+//! browser never connects anything itself: picking a server hands its URL
+//! to the main loop through `take_connect`, the same way `connect` does.
+
+use egui::Context;
+use macroquad::prelude::*;
+use shared::{version_mismatch, ServerListing, MASTER_LIST_PATH};
+
+/// Key that opens and closes the browser
+pub const SERVER_BROWSER_KEY: KeyCode = KeyCode::F9;
+
+/// A server the browser found, and how
+#[derive(Debug, Clone)]
+pub struct BrowsedServer {
+    pub listing: ServerListing,
+    /// Round trip to the server, if we could measure one
+    pub ping_ms: Option<u32>,
+    /// Answered our LAN broadcast rather than coming from the master
+    pub lan: bool,
+}
+
+impl BrowsedServer {
+    /// Our protocol matches and there's room for us
+    fn joinable(&self) -> bool {
+        version_mismatch(self.listing.protocol_version).is_none() && self.listing.accepting_players
+    }
+}
+
+type RefreshResult = Result<Vec<BrowsedServer>, String>;
+
+pub struct ServerBrowser {
+    open: bool,
+    /// Base URL of the master list, e.g. `http://master.example:14191`
+    master_url: String,
+    search: String,
+    hide_full: bool,
+    /// Sent with the join once a server is picked
+    password: String,
+    servers: Vec<BrowsedServer>,
+    status: String,
+    refresh: Option<PendingRefresh>,
+    connect: Option<String>,
+    /// Put the cursor in the search box on the next frame
+    focus_search: bool,
+}
+
+impl ServerBrowser {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            master_url: default_master_url(),
+            search: String::new(),
+            hide_full: false,
+            password: String::new(),
+            servers: Vec::new(),
+            status: String::new(),
+            refresh: None,
+            connect: None,
+            focus_search: false,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Open the browser, refreshing the list, or close it
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+        self.focus_search = self.open;
+        if self.open && self.refresh.is_none() {
+            self.start_refresh();
+        }
+    }
+
+    /// The server the player picked, once
+    pub fn take_connect(&mut self) -> Option<String> {
+        self.connect.take()
+    }
+
+    /// Password typed into the browser, if any
+    pub fn password(&self) -> Option<String> {
+        (!self.password.is_empty()).then(|| self.password.clone())
+    }
+
+    /// Pick up finished lookups; call once a frame
+    pub fn update(&mut self) {
+        let Some(result) = self.refresh.as_mut().and_then(PendingRefresh::poll) else {
+            return;
+        };
+        self.refresh = None;
+        match result {
+            Ok(mut servers) => {
+                servers.sort_by_key(|s| s.ping_ms.unwrap_or(u32::MAX));
+                self.status = match servers.len() {
+                    1 => "Found 1 server".to_string(),
+                    n => format!("Found {n} servers"),
+                };
+                self.servers = servers;
+            }
+            Err(e) => self.status = e,
+        }
+    }
+
+    fn start_refresh(&mut self) {
+        self.status = "Looking for servers...".to_string();
+        let url = self.master_url.trim();
+        let url =
+            (!url.is_empty()).then(|| format!("{}{MASTER_LIST_PATH}", url.trim_end_matches('/')));
+        self.refresh = Some(PendingRefresh::start(url));
+    }
+
+    pub fn render_ui(&mut self, ctx: &Context) {
+        if !self.open {
+            return;
+        }
+        // Tab walks the controls; Enter in the search box joins the top
+        // match and Escape closes the browser
+        let mut open = !ctx.input(|i| i.key_pressed(egui::Key::Escape));
+        let mut submitted = false;
+        egui::Window::new("Server Browser")
+            .open(&mut open)
+            .default_width(520.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Master:");
+                    ui.text_edit_singleline(&mut self.master_url);
+                    let refreshing = self.refresh.is_some();
+                    if ui
+                        .add_enabled(!refreshing, egui::Button::new("Refresh"))
+                        .clicked()
+                    {
+                        self.start_refresh();
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Search:");
+                    let search = ui.text_edit_singleline(&mut self.search);
+                    if std::mem::take(&mut self.focus_search) {
+                        search.request_focus();
+                    }
+                    submitted =
+                        search.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                    ui.checkbox(&mut self.hide_full, "Hide full");
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Password:");
+                    ui.add(egui::TextEdit::singleline(&mut self.password).password(true))
+                        .on_hover_text("For password-protected rooms; leave empty otherwise");
+                });
+                ui.label(&self.status);
+                ui.separator();
+
+                let shown: Vec<_> = self
+                    .servers
+                    .iter()
+                    .filter(|s| s.listing.matches(&self.search))
+                    .filter(|s| !self.hide_full || s.listing.accepting_players)
+                    .collect();
+                if shown.is_empty() {
+                    ui.label("No servers to show");
+                    return;
+                }
+                if submitted {
+                    if let Some(server) = shown.iter().find(|s| s.joinable()) {
+                        self.connect = Some(server.listing.ws_url());
+                        self.open = false;
+                    }
+                }
+                egui::Grid::new("server_list")
+                    .striped(true)
+                    .num_columns(5)
+                    .show(ui, |ui| {
+                        ui.strong("Name");
+                        ui.strong("Players");
+                        ui.strong("Ping");
+                        ui.strong("Where");
+                        ui.end_row();
+
+                        for server in shown {
+                            let listing = &server.listing;
+                            ui.label(&listing.name);
+                            ui.label(format!("{} (+{} AI)", listing.players, listing.ai_players));
+                            ui.label(match server.ping_ms {
+                                Some(ms) => format!("{ms} ms"),
+                                None => "?".to_string(),
+                            });
+                            ui.label(if server.lan { "LAN" } else { "Internet" });
+
+                            let mismatch = version_mismatch(listing.protocol_version);
+                            let button =
+                                ui.add_enabled(server.joinable(), egui::Button::new("Connect"));
+                            let button = match &mismatch {
+                                Some(reason) => button.on_disabled_hover_text(reason),
+                                None if !listing.accepting_players => {
+                                    button.on_disabled_hover_text("Server is full")
+                                }
+                                None => button,
+                            };
+                            if button.clicked() {
+                                self.connect = Some(listing.ws_url());
+                                self.open = false;
+                            }
+                            ui.end_row();
+                        }
+                    });
+            });
+        self.open &= open;
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn default_master_url() -> String {
+    std::env::var("MECH_MASTER_URL").unwrap_or_default()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn default_master_url() -> String {
+    String::new()
+}
+
+/// A lookup running on a background thread
+#[cfg(not(target_arch = "wasm32"))]
+struct PendingRefresh {
+    result: std::sync::mpsc::Receiver<RefreshResult>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl PendingRefresh {
+    fn start(master_url: Option<String>) -> Self {
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = tx.send(native::find_servers(master_url.as_deref()));
+        });
+        Self { result: rx }
+    }
+
+    fn poll(&mut self) -> Option<RefreshResult> {
+        use std::sync::mpsc::TryRecvError;
+        match self.result.try_recv() {
+            Ok(result) => Some(result),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Err("Server lookup failed".to_string())),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod native {
+    use super::{BrowsedServer, RefreshResult};
+    use shared::{
+        http_request, ServerListing, LAN_DISCOVERY_PORT, LAN_DISCOVERY_QUERY,
+        LAN_DISCOVERY_WAIT_MS, SERVER_PING_TIMEOUT_MS,
+    };
+    use std::net::{SocketAddr, TcpStream, ToSocketAddrs, UdpSocket};
+    use std::time::{Duration, Instant};
+
+    /// Everything the master and the LAN know about. Only fails if both do.
+    pub fn find_servers(master_url: Option<&str>) -> RefreshResult {
+        let mut servers = lan_servers().unwrap_or_else(|e| {
+            log::warn!("LAN discovery failed: {e}");
+            Vec::new()
+        });
+        if let Some(url) = master_url {
+            let listings: Vec<ServerListing> = http_request("GET", url, None)
+                .and_then(|body| serde_json::from_str(&body).map_err(|e| e.to_string()))
+                .map_err(|e| format!("Master list unavailable: {e}"))?;
+            for listing in listings {
+                // Seen on the LAN already, with a better ping
+                if servers
+                    .iter()
+                    .any(|s| s.listing.address() == listing.address())
+                {
+                    continue;
+                }
+                servers.push(BrowsedServer {
+                    ping_ms: ping(&listing),
+                    listing,
+                    lan: false,
+                });
+            }
+        }
+        Ok(servers)
+    }
+
+    /// Time to open a TCP connection to the server, a fair stand-in for ping
+    fn ping(listing: &ServerListing) -> Option<u32> {
+        let addr = (listing.host.as_str(), listing.port)
+            .to_socket_addrs()
+            .ok()?
+            .next()?;
+        let start = Instant::now();
+        TcpStream::connect_timeout(&addr, Duration::from_millis(SERVER_PING_TIMEOUT_MS)).ok()?;
+        Some(start.elapsed().as_millis() as u32)
+    }
+
+    /// Broadcast a discovery query and collect the answers for a moment
+    fn lan_servers() -> std::io::Result<Vec<BrowsedServer>> {
+        let socket = UdpSocket::bind(("0.0.0.0", 0))?;
+        socket.set_broadcast(true)?;
+        let start = Instant::now();
+        socket.send_to(
+            LAN_DISCOVERY_QUERY,
+            SocketAddr::from(([255, 255, 255, 255], LAN_DISCOVERY_PORT)),
+        )?;
+
+        let wait = Duration::from_millis(LAN_DISCOVERY_WAIT_MS);
+        let mut servers: Vec<BrowsedServer> = Vec::new();
+        let mut buf = [0u8; 2048];
+        while let Some(remaining) = wait.checked_sub(start.elapsed()) {
+            socket.set_read_timeout(Some(remaining.max(Duration::from_millis(1))))?;
+            let Ok((len, from)) = socket.recv_from(&mut buf) else {
+                break;
+            };
+            let Ok(mut listing) = serde_json::from_slice::<ServerListing>(&buf[..len]) else {
+                continue;
+            };
+            listing.host = from.ip().to_string();
+            if servers
+                .iter()
+                .any(|s| s.listing.address() == listing.address())
+            {
+                continue;
+            }
+            servers.push(BrowsedServer {
+                listing,
+                ping_ms: Some(start.elapsed().as_millis() as u32),
+                lan: true,
+            });
+        }
+        Ok(servers)
+    }
+}
+
+// Browser fetch, from network_bindings.js
+#[cfg(target_arch = "wasm32")]
+#[link(wasm_import_module = "network_bindings")]
+extern "C" {
+    fn js_http_get(url_ptr: *const u8, url_len: usize) -> u32;
+    fn js_http_poll(request_id: u32, buffer_ptr: *mut u8, buffer_len: usize) -> i32;
+}
+
+/// A fetch of the master list; browsers can't broadcast or time raw
+/// connections, so there's no LAN discovery or ping here
+#[cfg(target_arch = "wasm32")]
+struct PendingRefresh {
+    request_id: Option<u32>,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl PendingRefresh {
+    fn start(master_url: Option<String>) -> Self {
+        let request_id = master_url.map(|url| unsafe { js_http_get(url.as_ptr(), url.len()) });
+        Self { request_id }
+    }
+
+    fn poll(&mut self) -> Option<RefreshResult> {
+        let Some(request_id) = self.request_id else {
+            return Some(Err(
+                "Set a master server URL to look for servers".to_string()
+            ));
+        };
+        let mut buffer = vec![0u8; shared::MESSAGE_BUFFER_SIZE];
+        let len = unsafe { js_http_poll(request_id, buffer.as_mut_ptr(), buffer.len()) };
+        match len {
+            -1 => None,
+            len if len < 0 => Some(Err("Master list unavailable".to_string())),
+            len => Some(
+                serde_json::from_slice::<Vec<ServerListing>>(&buffer[..len as usize])
+                    .map(|listings| {
+                        listings
+                            .into_iter()
+                            .map(|listing| BrowsedServer {
+                                listing,
+                                ping_ms: None,
+                                lan: false,
+                            })
+                            .collect()
+                    })
+                    .map_err(|e| format!("Master list unreadable: {e}")),
+            ),
+        }
+    }
+}
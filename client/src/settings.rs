@@ -1,8 +1,23 @@
+use crate::accessibility::AccessibilitySettings;
+use crate::capture::CaptureSettings;
+use crate::particles::EffectsQuality;
+use crate::rendering::render_scale::GraphicsSettings;
+use crate::voice::VoiceSettings;
+use macroquad::logging::warn;
 use serde::{Deserialize, Serialize};
+use shared::{NetConditions, CAMERA_SMOOTHING};
 
-/// Debug settings that can be persisted across sessions
-#[derive(Serialize, Deserialize, Clone)]
+/// Version written with every save. Bump it and add a step to `migrate`
+/// when a field is renamed or changes meaning.
+pub const SETTINGS_VERSION: u32 = 1;
+
+/// Debug settings that can be persisted across sessions. Fields missing from
+/// an older file fall back to their defaults.
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
 pub struct DebugSettings {
+    pub version: u32,
+
     // UI panel visibility
     pub show_performance: bool,
     pub show_server_state: bool,
@@ -18,6 +33,7 @@ pub struct DebugSettings {
     pub show_door_positions: bool,
     pub show_coordinate_grid: bool,
     pub show_floor_offsets: bool,
+    pub show_hitboxes: bool,
 
     // Mech control debug panel
     pub show_mech_controls: bool,
@@ -32,11 +48,39 @@ pub struct DebugSettings {
     pub render_fog: bool,
     pub render_tiles: bool,
     pub render_stations: bool,
+    pub render_hit_feedback: bool,
+    pub render_lighting: bool,
+
+    // How many particles to draw
+    pub effects_quality: EffectsQuality,
+
+    // Resolution the world is drawn at, fixed or following the frame rate
+    pub graphics: GraphicsSettings,
+
+    // Multiplier for HUD and window text, apart from world zoom
+    pub ui_scale: f32,
+
+    // Colour-blind palette and reduced flashing
+    pub accessibility: AccessibilitySettings,
+
+    // How quickly the camera catches up with what it follows
+    pub camera_smoothing: f32,
+
+    // Screenshot and clip recording
+    pub capture: CaptureSettings,
+
+    // Simulated bad network, for testing prediction
+    pub net_conditions: NetConditions,
+
+    // Push-to-talk mute and volumes
+    pub voice: VoiceSettings,
 }
 
 impl Default for DebugSettings {
     fn default() -> Self {
         Self {
+            version: SETTINGS_VERSION,
+
             // UI panel defaults (match debug_overlay.rs defaults)
             show_performance: false,
             show_server_state: true,
@@ -55,6 +99,7 @@ impl Default for DebugSettings {
             show_door_positions: true,
             show_coordinate_grid: false,
             show_floor_offsets: true,
+            show_hitboxes: false,
 
             // All rendering enabled by default
             render_mechs: true,
@@ -66,106 +111,224 @@ impl Default for DebugSettings {
             render_fog: true,
             render_tiles: true,
             render_stations: true,
+            render_hit_feedback: true,
+            render_lighting: true,
+
+            effects_quality: EffectsQuality::default(),
+
+            graphics: GraphicsSettings::default(),
+
+            ui_scale: 1.0,
+
+            accessibility: AccessibilitySettings::default(),
+
+            camera_smoothing: CAMERA_SMOOTHING,
+
+            capture: CaptureSettings::default(),
+
+            net_conditions: NetConditions::default(),
+
+            voice: VoiceSettings::default(),
         }
     }
 }
 
-// FFI functions for WebAssembly storage
-#[cfg(target_arch = "wasm32")]
-extern "C" {
-    fn js_settings_save(key_ptr: *const u8, key_len: usize, value_ptr: *const u8, value_len: usize) -> i32;
-    fn js_settings_load(key_ptr: *const u8, key_len: usize, buffer_ptr: *mut u8, buffer_len: usize) -> i32;
-    fn js_settings_exists(key_ptr: *const u8, key_len: usize) -> i32;
+/// Bring a stored settings document up to `SETTINGS_VERSION`
+fn migrate(mut doc: serde_json::Value) -> Option<DebugSettings> {
+    let version = doc.get("version").and_then(|v| v.as_u64()).unwrap_or(0);
+    if version > SETTINGS_VERSION as u64 {
+        warn!("Settings were saved by a newer client (version {version}), loading what this one understands");
+    }
+
+    // Version 0 is the unversioned JSON the web build wrote before settings
+    // were versioned. No field was renamed since, so it only needs tagging.
+    if version < 1 {
+        doc.as_object_mut()?.insert("version".into(), 1.into());
+    }
+
+    let mut settings: DebugSettings = serde_json::from_value(doc).ok()?;
+    settings.version = SETTINGS_VERSION;
+    Some(settings)
 }
 
-pub struct SettingsManager {
-    settings: DebugSettings,
+/// Where settings live between sessions
+pub trait SettingsStorage {
+    /// The stored settings as an untyped document, so it can be migrated
+    /// before being read into `DebugSettings`
+    fn load(&self) -> Option<serde_json::Value>;
+    fn save(&self, settings: &DebugSettings) -> Result<(), String>;
 }
 
-impl SettingsManager {
-    pub fn new() -> Self {
-        let settings = Self::load_settings().unwrap_or_default();
-        Self { settings }
+/// Native builds keep a TOML file in the platform config directory
+#[cfg(not(target_arch = "wasm32"))]
+pub struct FileStorage {
+    path: std::path::PathBuf,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl FileStorage {
+    /// None when the platform has no home or config directory
+    pub fn new() -> Option<Self> {
+        let dirs = directories::ProjectDirs::from("", "", "mech_battle_arena")?;
+        Some(Self {
+            path: dirs.config_dir().join("settings.toml"),
+        })
     }
+}
 
-    pub fn get_settings(&self) -> &DebugSettings {
-        &self.settings
+#[cfg(not(target_arch = "wasm32"))]
+impl SettingsStorage for FileStorage {
+    fn load(&self) -> Option<serde_json::Value> {
+        let text = std::fs::read_to_string(&self.path).ok()?;
+        match toml::from_str(&text) {
+            Ok(doc) => Some(doc),
+            Err(e) => {
+                warn!(
+                    "Ignoring unreadable settings file {}: {e}",
+                    self.path.display()
+                );
+                None
+            }
+        }
     }
 
-    pub fn update_settings(&mut self, settings: DebugSettings) {
-        self.settings = settings;
-        self.save_settings();
+    fn save(&self, settings: &DebugSettings) -> Result<(), String> {
+        let text = toml::to_string_pretty(settings).map_err(|e| e.to_string())?;
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| format!("{}: {e}", dir.display()))?;
+        }
+        std::fs::write(&self.path, text).map_err(|e| format!("{}: {e}", self.path.display()))
     }
+}
 
-    #[cfg(target_arch = "wasm32")]
-    fn load_settings() -> Option<DebugSettings> {
-        use std::slice;
+// FFI functions for WebAssembly storage
+#[cfg(target_arch = "wasm32")]
+extern "C" {
+    fn js_settings_save(
+        key_ptr: *const u8,
+        key_len: usize,
+        value_ptr: *const u8,
+        value_len: usize,
+    ) -> i32;
+    fn js_settings_load(
+        key_ptr: *const u8,
+        key_len: usize,
+        buffer_ptr: *mut u8,
+        buffer_len: usize,
+    ) -> i32;
+}
+
+/// Web builds keep JSON in the browser's localStorage
+#[cfg(target_arch = "wasm32")]
+pub struct LocalStorage;
 
-        let key = "debug_settings";
-        let key_bytes = key.as_bytes();
+#[cfg(target_arch = "wasm32")]
+impl LocalStorage {
+    const KEY: &'static str = "debug_settings";
+}
+
+#[cfg(target_arch = "wasm32")]
+impl SettingsStorage for LocalStorage {
+    fn load(&self) -> Option<serde_json::Value> {
+        let key_bytes = Self::KEY.as_bytes();
         let mut buffer = [0u8; 4096]; // Should be enough for settings JSON
-        
-        unsafe {
-            let result = js_settings_load(
+
+        let len = unsafe {
+            js_settings_load(
                 key_bytes.as_ptr(),
                 key_bytes.len(),
                 buffer.as_mut_ptr(),
                 buffer.len(),
-            );
-            
-            if result > 0 {
-                let data = slice::from_raw_parts(buffer.as_ptr(), result as usize);
-                let json_str = std::str::from_utf8(data).ok()?;
-                serde_json::from_str(json_str).ok()
-            } else {
-                None
-            }
+            )
+        };
+        if len <= 0 {
+            return None;
         }
+        serde_json::from_slice(&buffer[..len as usize]).ok()
     }
 
-    #[cfg(not(target_arch = "wasm32"))]
-    fn load_settings() -> Option<DebugSettings> {
-        // For native builds, we don't persist settings
-        // Could be extended to use a config file later
-        None
+    fn save(&self, settings: &DebugSettings) -> Result<(), String> {
+        let json = serde_json::to_string(settings).map_err(|e| e.to_string())?;
+        let key_bytes = Self::KEY.as_bytes();
+        let json_bytes = json.as_bytes();
+
+        let result = unsafe {
+            js_settings_save(
+                key_bytes.as_ptr(),
+                key_bytes.len(),
+                json_bytes.as_ptr(),
+                json_bytes.len(),
+            )
+        };
+        if result == 1 {
+            Ok(())
+        } else {
+            Err("localStorage refused the settings".to_string())
+        }
     }
+}
 
-    #[cfg(target_arch = "wasm32")]
-    fn save_settings(&self) {
-        if let Ok(json) = serde_json::to_string(&self.settings) {
-            let key = "debug_settings";
-            let key_bytes = key.as_bytes();
-            let json_bytes = json.as_bytes();
-            
-            unsafe {
-                js_settings_save(
-                    key_bytes.as_ptr(),
-                    key_bytes.len(),
-                    json_bytes.as_ptr(),
-                    json_bytes.len(),
-                );
-            }
+#[cfg(not(target_arch = "wasm32"))]
+fn default_storage() -> Option<Box<dyn SettingsStorage>> {
+    FileStorage::new().map(|storage| Box::new(storage) as Box<dyn SettingsStorage>)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn default_storage() -> Option<Box<dyn SettingsStorage>> {
+    Some(Box::new(LocalStorage))
+}
+
+/// Holds the settings being edited next to the last saved copy, so changes
+/// are staged until the player explicitly saves or reverts them
+pub struct SettingsManager {
+    storage: Option<Box<dyn SettingsStorage>>,
+    saved: DebugSettings,
+    current: DebugSettings,
+}
+
+impl SettingsManager {
+    pub fn new() -> Self {
+        let storage = default_storage();
+        let saved = storage
+            .as_ref()
+            .and_then(|storage| storage.load())
+            .and_then(migrate)
+            .unwrap_or_default();
+        Self {
+            storage,
+            current: saved.clone(),
+            saved,
         }
     }
 
-    #[cfg(not(target_arch = "wasm32"))]
-    fn save_settings(&self) {
-        // No-op for native builds
+    pub fn get_settings(&self) -> &DebugSettings {
+        &self.current
     }
 
-    #[cfg(target_arch = "wasm32")]
-    pub fn settings_exist() -> bool {
-        let key = "debug_settings";
-        let key_bytes = key.as_bytes();
-        
-        unsafe {
-            js_settings_exists(key_bytes.as_ptr(), key_bytes.len()) != 0
-        }
+    /// Stage new settings; nothing is written until `save`
+    pub fn update_settings(&mut self, settings: DebugSettings) {
+        self.current = settings;
     }
 
-    #[cfg(not(target_arch = "wasm32"))]
-    pub fn settings_exist() -> bool {
-        false
+    /// Whether there are staged changes that haven't been saved
+    pub fn is_dirty(&self) -> bool {
+        self.current != self.saved
+    }
+
+    pub fn save(&mut self) -> Result<(), String> {
+        let storage = self
+            .storage
+            .as_ref()
+            .ok_or("No settings storage on this platform")?;
+        storage.save(&self.current)?;
+        self.saved = self.current.clone();
+        Ok(())
+    }
+
+    /// Throw away staged changes, returning the saved settings to re-apply
+    pub fn revert(&mut self) -> &DebugSettings {
+        self.current = self.saved.clone();
+        &self.current
     }
 }
 
@@ -173,4 +336,4 @@ impl Default for SettingsManager {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
@@ -1,7 +1,8 @@
 use crate::game_state::{GameState, MechState};
 use shared::{
-    constants::*, coordinates::TileRange, tile_entity::TileVisual, MechDoorPositions,
-    MechVisionUtils, PlayerLocation, TilePos, WorldPos, TILE_SIZE,
+    constants::*, coordinates::TileRange, render_constants::TEAM_VISION_VISIBILITY,
+    tile_entity::TileVisual, Facing, MechDoorPositions, MechVisionUtils, PlayerLocation, TilePos,
+    WorldPos, TILE_SIZE,
 };
 use std::collections::{HashMap, HashSet};
 
@@ -20,6 +21,9 @@ pub struct ClientVisionSystem {
     /// Last position where visibility was calculated
     pub last_update_pos: WorldPos,
 
+    /// Which way the last cone of sight pointed; `None` for all-round sight
+    last_update_facing: Option<Facing>,
+
     /// Vision range in tiles
     pub vision_range: TileRange,
 
@@ -40,6 +44,7 @@ impl ClientVisionSystem {
             vision_range: TileRange::new(14),                // 8 tiles of vision range
             update_threshold: 16.0, // Half tile movement (increased sensitivity)
             frame_counter: 0,
+            last_update_facing: None,
         }
     }
 
@@ -52,17 +57,27 @@ impl ClientVisionSystem {
             return;
         }
 
-        let (player_pos, _player_location) = match Self::get_player_info(game_state) {
+        let (player_pos, player_location) = match Self::get_player_info(game_state) {
             Some(info) => info,
             None => return, // Player not found or not positioned
         };
 
-        // Check if we need to update (player moved significantly)
+        // Outside, sight is a cone the server shapes the same way
+        let facing = match player_location {
+            PlayerLocation::OutsideWorld(_) if game_state.sight.is_directional() => game_state
+                .player_id
+                .and_then(|id| game_state.players.get(&id))
+                .map(|player| player.facing),
+            _ => None,
+        };
+
+        // Check if we need to update (player moved significantly or turned)
         if game_state
             .vision_system
             .last_update_pos
             .distance_to(player_pos)
             < game_state.vision_system.update_threshold
+            && facing == game_state.vision_system.last_update_facing
         {
             return;
         }
@@ -73,10 +88,11 @@ impl ClientVisionSystem {
         game_state.vision_system.visible_interior_tiles.clear();
 
         // Calculate new visibility
-        Self::calculate_visibility(game_state, player_pos);
+        Self::calculate_visibility(game_state, player_pos, facing);
 
         // Update last position
         game_state.vision_system.last_update_pos = player_pos;
+        game_state.vision_system.last_update_facing = facing;
     }
 
     /// Force a visibility update regardless of movement
@@ -147,22 +163,39 @@ impl ClientVisionSystem {
     }
 
     /// Internal: Main visibility calculation
-    fn calculate_visibility(game_state: &mut GameState, viewer_pos: WorldPos) {
+    fn calculate_visibility(
+        game_state: &mut GameState,
+        viewer_pos: WorldPos,
+        facing: Option<Facing>,
+    ) {
         // Cast rays in multiple directions for 360-degree vision
         let num_rays = 72; // Every 5 degrees (reduced from 360 for performance)
         let angle_step = std::f32::consts::PI * 2.0 / num_rays as f32;
 
         for i in 0..num_rays {
             let angle = i as f32 * angle_step;
-            Self::cast_vision_ray(game_state, viewer_pos, angle);
+            let reach = facing.map_or(1.0, |facing| {
+                game_state
+                    .sight
+                    .reach(angle.to_degrees(), facing.angle().to_degrees())
+            });
+            Self::cast_vision_ray(game_state, viewer_pos, angle, reach);
         }
 
         // Calculate visibility for mech interiors
         Self::calculate_mech_interior_visibility(game_state, viewer_pos);
+
+        // Lift the fog wherever teammates or our mech sensors can see
+        let vision = &mut game_state.vision_system;
+        for tile_pos in &game_state.team_visible_tiles {
+            vision.visible_tiles.insert(*tile_pos);
+            let visibility = vision.visibility_mask.entry(*tile_pos).or_insert(0.0);
+            *visibility = visibility.max(TEAM_VISION_VISIBILITY);
+        }
     }
 
-    /// Internal: Cast a single vision ray
-    fn cast_vision_ray(game_state: &mut GameState, start_pos: WorldPos, angle: f32) {
+    /// Internal: Cast a single vision ray, `reach` of the full vision range
+    fn cast_vision_ray(game_state: &mut GameState, start_pos: WorldPos, angle: f32, reach: f32) {
         let dx = angle.cos();
         let dy = angle.sin();
         let step_size = TILE_SIZE * 0.5; // Half-tile steps (better performance)
@@ -171,7 +204,7 @@ impl ClientVisionSystem {
         let mut distance = 0.0;
         let mut vision_blocked = false;
 
-        while distance < game_state.vision_system.vision_range.to_world_distance()
+        while distance < game_state.vision_system.vision_range.to_world_distance() * reach
             && !vision_blocked
         {
             let tile_pos = current_pos.to_tile();
@@ -363,7 +396,7 @@ mod tests {
             resources: Vec::new(),
             projectiles: Vec::new(),
             weapon_effects: Vec::new(),
-            camera_offset: (0.0, 0.0),
+            camera: crate::rendering::camera::CameraController::new(),
             ui_state: crate::game_state::UIState {
                 pilot_station_open: false,
                 pilot_station_id: None,
@@ -0,0 +1,517 @@
+//! Push-to-talk voice chat with teammates. A join that agrees on the `Voice`
+//! feature gets a key, which opens a second socket at `/voice` carrying
+//! nothing but opus frames. Native builds capture and play through cpal when
+//! built with the `voice` feature; the browser does both in
+//! `network_bindings.js` with WebAudio and WebCodecs. Without either the
+//! socket still shows who is talking.
+
+use crate::game_state::GameState;
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+use shared::*;
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+/// Hold to talk to your team
+pub const PUSH_TO_TALK_KEY: KeyCode = KeyCode::V;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VoiceSettings {
+    /// Send nothing and hear nobody, whatever key is held
+    pub muted: bool,
+    /// Master volume, 0 to 1
+    pub volume: f32,
+    /// Volume for each teammate by name, 0 to 1; names outlast the player
+    /// ids a rejoin hands out
+    pub player_volumes: BTreeMap<String, f32>,
+}
+
+impl Default for VoiceSettings {
+    fn default() -> Self {
+        Self {
+            muted: false,
+            volume: 1.0,
+            player_volumes: BTreeMap::new(),
+        }
+    }
+}
+
+impl VoiceSettings {
+    /// How loud to play the named teammate
+    pub fn volume_for(&self, name: &str) -> f32 {
+        if self.muted {
+            return 0.0;
+        }
+        self.volume * self.player_volumes.get(name).copied().unwrap_or(1.0)
+    }
+}
+
+/// The `/voice` address next to the game socket at `game_url`, in the same room
+pub fn voice_url(game_url: &str, key: Uuid) -> Option<String> {
+    let mut url = url::Url::parse(game_url).ok()?;
+    let room = url
+        .query_pairs()
+        .find(|(name, _)| name == "room")
+        .map(|(_, room)| room.into_owned());
+    url.set_path("/voice");
+    url.query_pairs_mut()
+        .clear()
+        .append_pair("key", &key.to_string());
+    if let Some(room) = room {
+        url.query_pairs_mut().append_pair("room", &room);
+    }
+    Some(url.to_string())
+}
+
+pub struct VoiceChat {
+    link: Option<VoiceLink>,
+    audio: VoiceAudio,
+    sequence: u16,
+    /// When each teammate was last heard
+    heard: HashMap<PlayerId, f64>,
+    transmitting: bool,
+}
+
+impl VoiceChat {
+    pub fn new() -> Self {
+        Self {
+            link: None,
+            audio: VoiceAudio::new(),
+            sequence: 0,
+            heard: HashMap::new(),
+            transmitting: false,
+        }
+    }
+
+    /// Open the voice socket with the key the server just gave us
+    pub fn connect(&mut self, game_url: &str, key: Uuid) {
+        let Some(url) = voice_url(game_url, key) else {
+            warn!("Can't open voice chat next to {}", game_url);
+            return;
+        };
+        self.disconnect();
+        self.link = VoiceLink::connect(&url);
+    }
+
+    pub fn disconnect(&mut self) {
+        self.link = None;
+        self.heard.clear();
+        self.transmitting = false;
+        self.audio.set_capturing(false);
+    }
+
+    /// Send the microphone while the key is held, and play and note whoever
+    /// on the team is talking
+    pub fn update(
+        &mut self,
+        settings: &VoiceSettings,
+        talk_held: bool,
+        game: &GameState,
+        now: f64,
+    ) {
+        let Some(link) = self.link.as_mut() else {
+            return;
+        };
+
+        self.transmitting = self.audio.set_capturing(talk_held && !settings.muted);
+        for payload in self.audio.take_frames() {
+            link.send(&encode_voice_frame(self.sequence, &payload));
+            self.sequence = self.sequence.wrapping_add(1);
+        }
+
+        for bytes in link.poll() {
+            // A hard mute drops teammates before they're even shown talking
+            if settings.muted {
+                continue;
+            }
+            match decode_relayed_voice_frame(&bytes) {
+                Ok(frame) => {
+                    self.heard.insert(frame.speaker, now);
+                    let volume = game
+                        .players
+                        .get(&frame.speaker)
+                        .map_or(settings.volume, |player| settings.volume_for(&player.name));
+                    self.audio.play(frame.speaker, &frame.payload, volume);
+                }
+                Err(e) => warn!("Bad voice frame: {}", e),
+            }
+        }
+        self.heard
+            .retain(|_, heard_at| now - *heard_at < VOICE_SPEAKING_HOLD_SECONDS);
+    }
+
+    /// Who is talking right now, ourselves included while the key is held
+    pub fn speakers(&self, game: &GameState) -> Vec<PlayerId> {
+        let mut speakers: Vec<PlayerId> = self.heard.keys().copied().collect();
+        if self.transmitting {
+            speakers.extend(game.player_id);
+        }
+        speakers.sort();
+        speakers
+    }
+}
+
+/// The socket audio travels over, apart from the game's
+#[cfg(not(target_arch = "wasm32"))]
+struct VoiceLink {
+    sender: ws::Sender,
+    received: std::sync::Arc<std::sync::Mutex<Vec<Vec<u8>>>>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl VoiceLink {
+    fn connect(url: &str) -> Option<Self> {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let received = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let handler_received = received.clone();
+        let url = url.to_string();
+        std::thread::spawn(move || {
+            let result = ws::connect(url, |out| {
+                let _ = tx.send(out);
+                VoiceHandler {
+                    received: handler_received.clone(),
+                }
+            });
+            if let Err(e) = result {
+                warn!("Voice chat connection failed: {}", e);
+            }
+        });
+        let sender = rx.recv().ok()?;
+        info!("Voice chat connected");
+        Some(Self { sender, received })
+    }
+
+    fn send(&self, frame: &[u8]) {
+        let _ = self.sender.send(ws::Message::Binary(frame.to_vec()));
+    }
+
+    fn poll(&mut self) -> Vec<Vec<u8>> {
+        std::mem::take(&mut *self.received.lock().unwrap())
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+struct VoiceHandler {
+    received: std::sync::Arc<std::sync::Mutex<Vec<Vec<u8>>>>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl ws::Handler for VoiceHandler {
+    fn on_message(&mut self, msg: ws::Message) -> ws::Result<()> {
+        if let ws::Message::Binary(bytes) = msg {
+            self.received.lock().unwrap().push(bytes);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Drop for VoiceLink {
+    fn drop(&mut self) {
+        let _ = self.sender.close(ws::CloseCode::Normal);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+#[link(wasm_import_module = "network_bindings")]
+extern "C" {
+    fn js_ws_connect(url_ptr: *const u8, url_len: usize) -> u32;
+    fn js_ws_send_binary(socket_id: u32, data_ptr: *const u8, data_len: usize);
+    fn js_ws_close(socket_id: u32);
+    fn js_ws_poll_binary_message(socket_id: u32, buffer_ptr: *mut u8, buffer_len: usize) -> i32;
+    fn js_voice_set_capturing(capturing: u32) -> u32;
+    fn js_voice_poll_frame(buffer_ptr: *mut u8, buffer_len: usize) -> i32;
+    fn js_voice_play(speaker_ptr: *const u8, data_ptr: *const u8, data_len: usize, volume: f32);
+}
+
+#[cfg(target_arch = "wasm32")]
+struct VoiceLink {
+    socket_id: u32,
+    buffer: Vec<u8>,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl VoiceLink {
+    fn connect(url: &str) -> Option<Self> {
+        let socket_id = unsafe { js_ws_connect(url.as_ptr(), url.len()) };
+        (socket_id != 0).then(|| Self {
+            socket_id,
+            buffer: vec![0; 16 + 2 + VOICE_MAX_FRAME_BYTES],
+        })
+    }
+
+    fn send(&self, frame: &[u8]) {
+        unsafe { js_ws_send_binary(self.socket_id, frame.as_ptr(), frame.len()) }
+    }
+
+    fn poll(&mut self) -> Vec<Vec<u8>> {
+        let mut frames = Vec::new();
+        loop {
+            let len = unsafe {
+                js_ws_poll_binary_message(
+                    self.socket_id,
+                    self.buffer.as_mut_ptr(),
+                    self.buffer.len(),
+                )
+            };
+            if len < 0 {
+                return frames;
+            }
+            frames.push(self.buffer[..len as usize].to_vec());
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Drop for VoiceLink {
+    fn drop(&mut self) {
+        unsafe { js_ws_close(self.socket_id) }
+    }
+}
+
+/// The browser's microphone and speakers, driven from `network_bindings.js`
+#[cfg(target_arch = "wasm32")]
+struct VoiceAudio {
+    buffer: Vec<u8>,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl VoiceAudio {
+    fn new() -> Self {
+        Self {
+            buffer: vec![0; VOICE_MAX_FRAME_BYTES],
+        }
+    }
+
+    /// Turn the microphone on or off, returning whether it's sending
+    fn set_capturing(&mut self, capturing: bool) -> bool {
+        unsafe { js_voice_set_capturing(capturing as u32) != 0 }
+    }
+
+    fn take_frames(&mut self) -> Vec<Vec<u8>> {
+        let mut frames = Vec::new();
+        loop {
+            let len = unsafe { js_voice_poll_frame(self.buffer.as_mut_ptr(), self.buffer.len()) };
+            if len < 0 {
+                return frames;
+            }
+            frames.push(self.buffer[..len as usize].to_vec());
+        }
+    }
+
+    fn play(&mut self, speaker: PlayerId, payload: &[u8], volume: f32) {
+        let speaker = speaker.as_bytes();
+        unsafe { js_voice_play(speaker.as_ptr(), payload.as_ptr(), payload.len(), volume) }
+    }
+}
+
+#[cfg(all(not(target_arch = "wasm32"), feature = "voice"))]
+use native_audio::VoiceAudio;
+
+/// Microphone and speakers through cpal, opus in between
+#[cfg(all(not(target_arch = "wasm32"), feature = "voice"))]
+mod native_audio {
+    use audiopus::coder::{Decoder, Encoder};
+    use audiopus::{Application, Bitrate, Channels, SampleRate};
+    use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+    use macroquad::prelude::*;
+    use shared::*;
+    use std::collections::{HashMap, VecDeque};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    /// Most decoded audio kept per speaker; anything older is skipped so a
+    /// stall doesn't leave them lagging behind for good
+    const MAX_BUFFERED_SAMPLES: usize = VOICE_SAMPLE_RATE as usize / 2;
+
+    /// Opus is run at `VOICE_SAMPLE_RATE`
+    const OPUS_RATE: SampleRate = SampleRate::Hz48000;
+
+    /// Decoded audio waiting to be played, one queue per speaker so they mix
+    type Playback = Arc<Mutex<HashMap<PlayerId, VecDeque<f32>>>>;
+
+    pub struct VoiceAudio {
+        encoder: Option<Encoder>,
+        decoders: HashMap<PlayerId, Decoder>,
+        /// Microphone samples not encoded yet
+        captured: Arc<Mutex<Vec<f32>>>,
+        capturing: Arc<AtomicBool>,
+        playback: Playback,
+        input: Option<cpal::Stream>,
+        _output: Option<cpal::Stream>,
+    }
+
+    impl VoiceAudio {
+        pub fn new() -> Self {
+            let captured = Arc::new(Mutex::new(Vec::new()));
+            let capturing = Arc::new(AtomicBool::new(false));
+            let playback = Playback::default();
+            let host = cpal::default_host();
+
+            let input = open_input(&host, captured.clone(), capturing.clone())
+                .map_err(|e| warn!("No microphone for voice chat: {}", e))
+                .ok();
+            let output = open_output(&host, playback.clone())
+                .map_err(|e| warn!("No speakers for voice chat: {}", e))
+                .ok();
+            let encoder = Encoder::new(OPUS_RATE, Channels::Mono, Application::Voip)
+                .and_then(|mut encoder| {
+                    encoder.set_bitrate(Bitrate::BitsPerSecond(VOICE_BITRATE))?;
+                    Ok(encoder)
+                })
+                .map_err(|e| warn!("Voice encoder failed to start: {}", e))
+                .ok();
+
+            Self {
+                encoder,
+                decoders: HashMap::new(),
+                captured,
+                capturing,
+                playback,
+                input,
+                _output: output,
+            }
+        }
+
+        /// Turn the microphone on or off, returning whether it's sending
+        pub fn set_capturing(&mut self, capturing: bool) -> bool {
+            let capturing = capturing && self.input.is_some() && self.encoder.is_some();
+            let was_capturing = self.capturing.swap(capturing, Ordering::Relaxed);
+            if was_capturing && !capturing {
+                // The half frame left over when the key is let go is dropped
+                self.captured.lock().unwrap().clear();
+            }
+            capturing
+        }
+
+        pub fn take_frames(&mut self) -> Vec<Vec<u8>> {
+            let mut frames = Vec::new();
+            let Some(encoder) = self.encoder.as_ref() else {
+                return frames;
+            };
+            let mut captured = self.captured.lock().unwrap();
+            let mut packet = [0u8; VOICE_MAX_FRAME_BYTES];
+            while captured.len() >= VOICE_FRAME_SAMPLES {
+                let samples: Vec<f32> = captured.drain(..VOICE_FRAME_SAMPLES).collect();
+                match encoder.encode_float(&samples, &mut packet) {
+                    Ok(len) => frames.push(packet[..len].to_vec()),
+                    Err(e) => warn!("Failed to encode voice: {}", e),
+                }
+            }
+            frames
+        }
+
+        pub fn play(&mut self, speaker: PlayerId, payload: &[u8], volume: f32) {
+            if !self.decoders.contains_key(&speaker) {
+                match Decoder::new(OPUS_RATE, Channels::Mono) {
+                    Ok(decoder) => self.decoders.insert(speaker, decoder),
+                    Err(e) => {
+                        warn!("Voice decoder failed to start: {}", e);
+                        return;
+                    }
+                };
+            }
+            // Opus frames run up to 120ms
+            let mut samples = [0f32; VOICE_FRAME_SAMPLES * 6];
+            let decoded = self
+                .decoders
+                .get_mut(&speaker)
+                .map(|decoder| decoder.decode_float(Some(payload), &mut samples[..], false));
+            let len = match decoded {
+                Some(Ok(len)) => len,
+                Some(Err(e)) => {
+                    warn!("Failed to decode voice: {}", e);
+                    return;
+                }
+                None => return,
+            };
+
+            let mut playback = self.playback.lock().unwrap();
+            let queue = playback.entry(speaker).or_default();
+            queue.extend(samples[..len].iter().map(|sample| sample * volume));
+            let excess = queue.len().saturating_sub(MAX_BUFFERED_SAMPLES);
+            queue.drain(..excess);
+        }
+    }
+
+    fn open_input(
+        host: &cpal::Host,
+        captured: Arc<Mutex<Vec<f32>>>,
+        capturing: Arc<AtomicBool>,
+    ) -> Result<cpal::Stream, String> {
+        let device = host.default_input_device().ok_or("no input device")?;
+        let config = cpal::StreamConfig {
+            channels: 1,
+            sample_rate: cpal::SampleRate(VOICE_SAMPLE_RATE),
+            buffer_size: cpal::BufferSize::Default,
+        };
+        let stream = device
+            .build_input_stream(
+                &config,
+                move |data: &[f32], _: &cpal::InputCallbackInfo| {
+                    if capturing.load(Ordering::Relaxed) {
+                        captured.lock().unwrap().extend_from_slice(data);
+                    }
+                },
+                |e| warn!("Microphone error: {}", e),
+                None,
+            )
+            .map_err(|e| e.to_string())?;
+        stream.play().map_err(|e| e.to_string())?;
+        Ok(stream)
+    }
+
+    fn open_output(host: &cpal::Host, playback: Playback) -> Result<cpal::Stream, String> {
+        let device = host.default_output_device().ok_or("no output device")?;
+        let channels = device
+            .default_output_config()
+            .map_err(|e| e.to_string())?
+            .channels();
+        let config = cpal::StreamConfig {
+            channels,
+            sample_rate: cpal::SampleRate(VOICE_SAMPLE_RATE),
+            buffer_size: cpal::BufferSize::Default,
+        };
+        let stream = device
+            .build_output_stream(
+                &config,
+                move |data: &mut [f32], _: &cpal::OutputCallbackInfo| {
+                    let mut playback = playback.lock().unwrap();
+                    for frame in data.chunks_mut(channels as usize) {
+                        let mixed: f32 = playback
+                            .values_mut()
+                            .filter_map(|queue| queue.pop_front())
+                            .sum();
+                        frame.fill(mixed.clamp(-1.0, 1.0));
+                    }
+                },
+                |e| warn!("Speaker error: {}", e),
+                None,
+            )
+            .map_err(|e| e.to_string())?;
+        stream.play().map_err(|e| e.to_string())?;
+        Ok(stream)
+    }
+}
+
+/// Built without audio: the socket still shows who is talking
+#[cfg(all(not(target_arch = "wasm32"), not(feature = "voice")))]
+struct VoiceAudio;
+
+#[cfg(all(not(target_arch = "wasm32"), not(feature = "voice")))]
+impl VoiceAudio {
+    fn new() -> Self {
+        Self
+    }
+
+    fn set_capturing(&mut self, _capturing: bool) -> bool {
+        false
+    }
+
+    fn take_frames(&mut self) -> Vec<Vec<u8>> {
+        Vec::new()
+    }
+
+    fn play(&mut self, _speaker: PlayerId, _payload: &[u8], _volume: f32) {}
+}
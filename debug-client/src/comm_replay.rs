@@ -0,0 +1,292 @@
+use ai::CommEvent;
+use eframe::egui::{self, Color32, Pos2, Stroke};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+const NODE_RADIUS: f32 = 14.0;
+const GRAPH_HEIGHT: f32 = 420.0;
+
+/// Recording of AI-to-AI messages streamed from the server, played back as a
+/// node-edge graph so orders can be followed as they spread
+pub struct CommGraphReplay {
+    events: Vec<CommEvent>,
+    /// Playback position, in the server's comm time
+    pub playhead: f32,
+    pub playing: bool,
+    pub speed: f32,
+    /// Keep the playhead on the newest message as they arrive
+    pub follow_live: bool,
+    /// Seconds of messages drawn behind the playhead
+    pub window: f32,
+    /// Node clicked to show only its traffic
+    focus: Option<Uuid>,
+}
+
+impl CommGraphReplay {
+    pub fn new() -> Self {
+        Self {
+            events: Vec::new(),
+            playhead: 0.0,
+            playing: false,
+            speed: 1.0,
+            follow_live: true,
+            window: 5.0,
+            focus: None,
+        }
+    }
+
+    /// Append newly streamed events, skipping any already recorded
+    pub fn extend(&mut self, events: Vec<CommEvent>) {
+        let next = self.events.last().map_or(0, |last| last.sequence + 1);
+        self.events
+            .extend(events.into_iter().filter(|event| event.sequence >= next));
+        if self.follow_live {
+            self.playhead = self.end();
+        }
+    }
+
+    /// Advance playback by `dt` seconds of wall time
+    pub fn advance(&mut self, dt: f32) {
+        if self.follow_live {
+            self.playhead = self.end();
+        } else if self.playing {
+            self.playhead += dt * self.speed;
+            if self.playhead >= self.end() {
+                self.playhead = self.end();
+                self.playing = false;
+            }
+        }
+    }
+
+    fn start(&self) -> f32 {
+        self.events.first().map_or(0.0, |event| event.time)
+    }
+
+    fn end(&self) -> f32 {
+        self.events.last().map_or(0.0, |event| event.time)
+    }
+
+    /// Messages sent in the window leading up to the playhead, oldest first
+    fn visible(&self) -> impl Iterator<Item = &CommEvent> {
+        let (from, to) = (self.playhead - self.window, self.playhead);
+        self.events
+            .iter()
+            .filter(move |event| event.time > from && event.time <= to)
+            .filter(|event| match self.focus {
+                Some(id) => event.from == id || event.to == Some(id),
+                None => true,
+            })
+    }
+
+    /// Every AI seen in the recording, captains first, so the layout stays
+    /// put while scrubbing
+    fn nodes(&self) -> Vec<(Uuid, bool)> {
+        let mut nodes: Vec<(Uuid, bool)> = Vec::new();
+        for event in &self.events {
+            for id in std::iter::once(event.from).chain(event.to) {
+                let captain = event.from_captain && id == event.from;
+                match nodes.iter_mut().find(|(node, _)| *node == id) {
+                    Some((_, is_captain)) => *is_captain |= captain,
+                    None => nodes.push((id, captain)),
+                }
+            }
+        }
+        nodes.sort_by_key(|(_, captain)| !captain);
+        nodes
+    }
+}
+
+pub fn show_comm_graph(
+    ui: &mut egui::Ui,
+    replay: &mut CommGraphReplay,
+    names: &HashMap<Uuid, String>,
+) {
+    ui.heading(format!(
+        "Communication Replay ({} messages)",
+        replay.events.len()
+    ));
+
+    if replay.events.is_empty() {
+        ui.label("No AI messages yet. Connect to a server with AI players to record them.");
+        return;
+    }
+
+    ui.horizontal(|ui| {
+        if ui.button("⏮").clicked() {
+            replay.playhead = replay.start();
+            replay.follow_live = false;
+        }
+        if replay.playing && !replay.follow_live {
+            if ui.button("⏸ Pause").clicked() {
+                replay.playing = false;
+            }
+        } else if ui.button("▶ Play").clicked() {
+            if replay.playhead >= replay.end() {
+                replay.playhead = replay.start();
+            }
+            replay.playing = true;
+            replay.follow_live = false;
+        }
+        if ui.button("⏭ Live").clicked() {
+            replay.follow_live = true;
+        }
+        ui.checkbox(&mut replay.follow_live, "Follow live");
+
+        ui.separator();
+        ui.label("Speed:");
+        ui.add(egui::Slider::new(&mut replay.speed, 0.1..=4.0).suffix("x"));
+        ui.label("Trail:");
+        ui.add(egui::Slider::new(&mut replay.window, 1.0..=30.0).suffix(" s"));
+    });
+
+    let (start, end) = (replay.start(), replay.end());
+    let timeline = ui.add(
+        egui::Slider::new(&mut replay.playhead, start..=end)
+            .text("Time")
+            .suffix(" s"),
+    );
+    if timeline.changed() {
+        replay.follow_live = false;
+    }
+
+    let (response, painter) = ui.allocate_painter(
+        egui::vec2(ui.available_width(), GRAPH_HEIGHT),
+        egui::Sense::click(),
+    );
+    let rect = response.rect;
+    painter.rect_filled(rect, 4.0, Color32::from_gray(20));
+
+    // Captain in the middle when there's exactly one, everyone else in a ring
+    let nodes = replay.nodes();
+    let center = rect.center();
+    let ring = rect.width().min(rect.height()) * 0.4;
+    let single_captain = nodes.iter().filter(|(_, captain)| *captain).count() == 1;
+    let ring_count = nodes.len() - usize::from(single_captain);
+    let positions: HashMap<Uuid, Pos2> = nodes
+        .iter()
+        .enumerate()
+        .map(|(i, (id, captain))| {
+            if single_captain && *captain {
+                return (*id, center);
+            }
+            let slot = i - usize::from(single_captain);
+            let angle = slot as f32 / ring_count.max(1) as f32 * std::f32::consts::TAU;
+            (*id, center + ring * egui::vec2(angle.cos(), angle.sin()))
+        })
+        .collect();
+
+    let hovered = response.hover_pos().and_then(|pointer| {
+        positions
+            .iter()
+            .find(|(_, pos)| pos.distance(pointer) <= NODE_RADIUS)
+            .map(|(id, _)| *id)
+    });
+    if response.clicked() {
+        replay.focus = hovered;
+    }
+
+    // Edges fade out as they age; broadcasts are rings around the sender
+    let mut sent: HashMap<Uuid, usize> = HashMap::new();
+    for event in replay.visible() {
+        let age = (replay.playhead - event.time) / replay.window;
+        let color = priority_color(&event.priority).gamma_multiply(1.0 - age);
+        let Some(&from) = positions.get(&event.from) else {
+            continue;
+        };
+        *sent.entry(event.from).or_default() += 1;
+        match event.to.and_then(|to| positions.get(&to)) {
+            Some(&to) => {
+                let direction = (to - from).normalized();
+                let tail = from + direction * NODE_RADIUS;
+                let tip = to - direction * NODE_RADIUS;
+                painter.arrow(tail, tip - tail, Stroke::new(2.0, color));
+            }
+            None => {
+                painter.circle_stroke(
+                    from,
+                    NODE_RADIUS + 4.0 + age * 40.0,
+                    Stroke::new(2.0, color),
+                );
+            }
+        }
+    }
+
+    for (id, captain) in &nodes {
+        let pos = positions[id];
+        let fill = if *captain {
+            Color32::GOLD
+        } else {
+            Color32::from_rgb(70, 110, 160)
+        };
+        let outline = if replay.focus == Some(*id) || hovered == Some(*id) {
+            Stroke::new(2.0, Color32::WHITE)
+        } else {
+            Stroke::new(1.0, Color32::from_gray(120))
+        };
+        painter.circle(pos, NODE_RADIUS, fill, outline);
+        painter.text(
+            pos + egui::vec2(0.0, NODE_RADIUS + 4.0),
+            egui::Align2::CENTER_TOP,
+            node_name(*id, names),
+            egui::FontId::proportional(12.0),
+            Color32::LIGHT_GRAY,
+        );
+        if let Some(count) = sent.get(id) {
+            painter.text(
+                pos,
+                egui::Align2::CENTER_CENTER,
+                count.to_string(),
+                egui::FontId::monospace(11.0),
+                Color32::BLACK,
+            );
+        }
+    }
+
+    if let Some(id) = replay.focus {
+        ui.label(format!(
+            "Showing traffic for {} (click empty space to show everyone)",
+            node_name(id, names)
+        ));
+    }
+
+    ui.separator();
+    ui.label("Messages in view (newest first):");
+    let lines: Vec<String> = replay
+        .visible()
+        .map(|event| {
+            let to = event
+                .to
+                .map_or_else(|| "everyone".to_string(), |to| node_name(to, names));
+            format!(
+                "{:>7.2}s  tick {:<6} {} → {} : {} [{}]",
+                event.time,
+                event.tick,
+                node_name(event.from, names),
+                to,
+                event.message_type,
+                event.priority
+            )
+        })
+        .collect();
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        for line in lines.iter().rev() {
+            ui.monospace(line);
+        }
+    });
+}
+
+fn node_name(id: Uuid, names: &HashMap<Uuid, String>) -> String {
+    names
+        .get(&id)
+        .cloned()
+        .unwrap_or_else(|| format!("AI {}", &id.to_string()[..8]))
+}
+
+fn priority_color(priority: &str) -> Color32 {
+    match priority {
+        "Critical" => Color32::RED,
+        "High" => Color32::from_rgb(255, 165, 0),
+        "Low" => Color32::GRAY,
+        _ => Color32::LIGHT_BLUE,
+    }
+}
@@ -1,4 +1,4 @@
-use ai::{AIMetrics, AIVisualizationData};
+use ai::{AIMetrics, AITickMetrics, AIVisualizationData, GameView, UtilityWeights};
 use eframe::egui;
 use serde::{Deserialize, Serialize};
 use shared::*;
@@ -6,8 +6,12 @@ use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use uuid::Uuid;
 
+mod comm_replay;
 mod network;
+mod replay;
+use comm_replay::CommGraphReplay;
 use network::DebugConnection;
+use replay::DecisionLogReplay;
 
 /// Main application state
 struct AIDebugApp {
@@ -29,6 +33,26 @@ struct AIDebugApp {
     /// Server address
     server_address: String,
     connection_status: ConnectionStatus,
+    /// Offline decision log replay
+    decision_log_path: String,
+    replay: Option<DecisionLogReplay>,
+    replay_error: Option<String>,
+    /// AI conversation recorded from the server's debug stream
+    comm_replay: CommGraphReplay,
+    show_comm_replay: bool,
+    /// Task weights of the AIs we've asked about, as the server last sent them
+    ai_weights: HashMap<Uuid, UtilityWeights>,
+    /// Where "Save" writes the selected AI's weights, for `--ai-weights`
+    weights_path: String,
+    weights_status: Option<String>,
+    /// What each AI we've asked about was allowed to know, as the server
+    /// built it for them
+    ai_views: HashMap<Uuid, GameView>,
+    /// What the AIs' latest update cost on the server
+    tick_metrics: Option<AITickMetrics>,
+    /// What each server message type is costing, heaviest first
+    bandwidth: Vec<MessageBandwidth>,
+    show_bandwidth: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -61,6 +85,18 @@ impl Default for AIDebugApp {
             show_performance_metrics: true,
             server_address: "ws://0.0.0.0:14191/debug".to_string(),
             connection_status: ConnectionStatus::Disconnected,
+            decision_log_path: "logs/ai_decisions.jsonl".to_string(),
+            replay: None,
+            replay_error: None,
+            comm_replay: CommGraphReplay::new(),
+            show_comm_replay: false,
+            ai_weights: HashMap::new(),
+            weights_path: "ai_weights.toml".to_string(),
+            weights_status: None,
+            ai_views: HashMap::new(),
+            tick_metrics: None,
+            bandwidth: Vec::new(),
+            show_bandwidth: false,
         }
     }
 }
@@ -122,6 +158,21 @@ impl AIDebugApp {
             DebugMessage::SimulationPaused(paused) => {
                 self.sim_paused = paused;
             }
+            DebugMessage::CommEvents(events) => {
+                self.comm_replay.extend(events);
+            }
+            DebugMessage::AIWeights { ai_id, weights } => {
+                self.ai_weights.insert(ai_id, weights);
+            }
+            DebugMessage::AIView { ai_id, view } => {
+                self.ai_views.insert(ai_id, view);
+            }
+            DebugMessage::AIMetrics(metrics) => {
+                self.tick_metrics = Some(metrics);
+            }
+            DebugMessage::Bandwidth(rows) => {
+                self.bandwidth = rows;
+            }
         }
     }
 
@@ -131,6 +182,7 @@ impl AIDebugApp {
             mechs,
             resources,
             projectiles,
+            ..
         } = msg
         {
             self.game_state.players = players;
@@ -140,6 +192,27 @@ impl AIDebugApp {
         }
     }
 
+    fn load_decision_log(&mut self) {
+        match DecisionLogReplay::load(std::path::Path::new(&self.decision_log_path)) {
+            Ok(replay) => {
+                self.replay = Some(replay);
+                self.replay_error = None;
+            }
+            Err(e) => {
+                self.replay_error = Some(format!("Failed to load {}: {e}", self.decision_log_path));
+            }
+        }
+    }
+
+    /// Write the weights as a TOML file the server can start with
+    fn save_weights(&mut self, weights: &UtilityWeights) {
+        let status = match std::fs::write(&self.weights_path, weights.to_toml()) {
+            Ok(()) => format!("Saved to {}", self.weights_path),
+            Err(e) => format!("Failed to save {}: {e}", self.weights_path),
+        };
+        self.weights_status = Some(status);
+    }
+
     fn send_command(&self, cmd: DebugCommand) {
         if let Ok(conn_guard) = self.connection.lock() {
             if let Some(conn) = conn_guard.as_ref() {
@@ -154,6 +227,12 @@ impl eframe::App for AIDebugApp {
         // Update from server
         self.update_from_server();
 
+        let dt = ctx.input(|i| i.stable_dt);
+        if let Some(replay) = &mut self.replay {
+            replay.advance(dt);
+        }
+        self.comm_replay.advance(dt);
+
         // Top panel with connection status and controls
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             ui.horizontal(|ui| {
@@ -207,9 +286,34 @@ impl eframe::App for AIDebugApp {
                 {
                     self.send_command(DebugCommand::SetSimulationSpeed(self.sim_speed));
                 }
+
+                ui.separator();
+
+                // Offline decision log replay
+                ui.label("Decision log:");
+                ui.text_edit_singleline(&mut self.decision_log_path);
+                if ui.button("📂 Load decision log").clicked() {
+                    self.load_decision_log();
+                }
+                if self.replay.is_some() && ui.button("✖ Close replay").clicked() {
+                    self.replay = None;
+                }
+                if let Some(error) = &self.replay_error {
+                    ui.colored_label(egui::Color32::RED, error);
+                }
+
+                ui.separator();
+                ui.toggle_value(&mut self.show_comm_replay, "🕸 Comm replay");
+                ui.toggle_value(&mut self.show_bandwidth, "📶 Bandwidth");
             });
         });
 
+        if self.show_bandwidth {
+            egui::TopBottomPanel::bottom("bandwidth").show(ctx, |ui| {
+                show_bandwidth(ui, &self.bandwidth);
+            });
+        }
+
         // Left panel with AI list
         egui::SidePanel::left("ai_list").show(ctx, |ui| {
             ui.heading("AI Players");
@@ -221,6 +325,8 @@ impl eframe::App for AIDebugApp {
 
                         if ui.selectable_label(is_selected, &player.name).clicked() {
                             self.selected_ai = Some(*player_id);
+                            self.send_command(DebugCommand::RequestAIWeights(*player_id));
+                            self.send_command(DebugCommand::RequestAIView(*player_id));
                         }
 
                         if is_selected {
@@ -238,12 +344,24 @@ impl eframe::App for AIDebugApp {
                                         ));
                                     }
                                 }
+
+                                if let Some(view) = self.ai_views.get(player_id) {
+                                    show_knowledge(ui, view);
+                                }
+                                if ui.small_button("Refresh knowledge").clicked() {
+                                    self.send_command(DebugCommand::RequestAIView(*player_id));
+                                }
                             });
                         }
                     }
                 }
             });
 
+            if let Some(metrics) = &self.tick_metrics {
+                ui.separator();
+                show_tick_metrics(ui, metrics, &self.game_state.players);
+            }
+
             ui.separator();
 
             if ui.button("Add AI").clicked() {
@@ -254,9 +372,53 @@ impl eframe::App for AIDebugApp {
             }
         });
 
+        // Right panel with the selected AI's task weights, pushed to the
+        // server as they're dragged
+        let tuning = self
+            .selected_ai
+            .and_then(|ai_id| Some((ai_id, self.ai_weights.get(&ai_id)?.clone())));
+        if let Some((ai_id, mut weights)) = tuning {
+            egui::SidePanel::right("ai_tuning").show(ctx, |ui| {
+                ui.heading("Utility weights");
+                let mut changed = show_utility_weights(ui, &mut weights);
+                if ui.button("Reset to defaults").clicked() {
+                    weights = UtilityWeights::default();
+                    changed = true;
+                }
+                if changed {
+                    self.send_command(DebugCommand::SetAIWeights {
+                        ai_id,
+                        weights: weights.clone(),
+                    });
+                    self.ai_weights.insert(ai_id, weights.clone());
+                }
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.text_edit_singleline(&mut self.weights_path);
+                    if ui.button("💾 Save").clicked() {
+                        self.save_weights(&weights);
+                    }
+                });
+                if let Some(status) = &self.weights_status {
+                    ui.label(status);
+                }
+            });
+        }
+
         // Central panel with main view
         egui::CentralPanel::default().show(ctx, |ui| {
-            if let Some(ai_id) = self.selected_ai {
+            if let Some(replay) = &mut self.replay {
+                replay::show_replay(ui, replay);
+            } else if self.show_comm_replay {
+                let names = self
+                    .game_state
+                    .players
+                    .iter()
+                    .map(|(id, player)| (*id, player.name.clone()))
+                    .collect();
+                comm_replay::show_comm_graph(ui, &mut self.comm_replay, &names);
+            } else if let Some(ai_id) = self.selected_ai {
                 if let Some(ai_data) = self.ai_data.get(&ai_id) {
                     ui.heading(format!(
                         "AI Debug: {}",
@@ -364,6 +526,37 @@ fn show_decision_timeline(ui: &mut egui::Ui, ai_data: &AIVisualizationData) {
     });
 }
 
+/// A slider per weight; true if any moved
+fn show_utility_weights(ui: &mut egui::Ui, weights: &mut UtilityWeights) -> bool {
+    let mut changed = false;
+    egui::Grid::new("utility_weights").show(ui, |ui| {
+        for (name, value) in weights.entries_mut() {
+            let range = match name {
+                "stuck_threshold" => 0.0..=20.0,
+                "distance_falloff" => 10.0..=1000.0,
+                "difficulty_floor" | "shield_threat_severity" => 0.0..=1.0,
+                _ => 0.0..=3.0,
+            };
+            ui.label(name);
+            changed |= ui.add(egui::Slider::new(value, range)).changed();
+            ui.end_row();
+        }
+    });
+    changed
+}
+
+/// How much of the enemy an AI can see, as of the view the server last sent
+fn show_knowledge(ui: &mut egui::Ui, view: &GameView) {
+    let team = view.team_info.team_id;
+    let enemy_players = view.players.iter().filter(|p| p.team != team).count();
+    let enemy_mechs = view.mechs.iter().filter(|m| m.team != team).count();
+    ui.label(format!("Knowledge (tick {}):", view.tick));
+    ui.label(format!("  Enemy players: {enemy_players}"));
+    ui.label(format!("  Enemy mechs: {enemy_mechs}"));
+    ui.label(format!("  Resources: {}", view.resources.len()));
+    ui.label(format!("  Projectiles: {}", view.projectiles.len()));
+}
+
 fn show_performance_metrics(ui: &mut egui::Ui, metrics: &AIMetrics) {
     ui.heading("Performance Metrics");
 
@@ -385,6 +578,80 @@ fn show_performance_metrics(ui: &mut egui::Ui, metrics: &AIMetrics) {
     });
 }
 
+/// Totals for the AIs' latest update on the server, naming the slowest
+fn show_tick_metrics(
+    ui: &mut egui::Ui,
+    metrics: &AITickMetrics,
+    players: &HashMap<Uuid, PlayerState>,
+) {
+    ui.label(format!("AI update (tick {}):", metrics.tick));
+    ui.label(format!(
+        "  {:.2}ms total, {:.2}ms deciding",
+        metrics.update_ms, metrics.total_decision_ms
+    ));
+    if let Some(slowest) = metrics.slowest_ai {
+        let name = players
+            .get(&slowest)
+            .map_or("unknown", |player| player.name.as_str());
+        ui.label(format!(
+            "  Slowest: {name} ({:.2}ms)",
+            metrics.slowest_decision_ms
+        ));
+    }
+    ui.label(format!("  Commands: {}", metrics.command_count()));
+    for (kind, count) in &metrics.commands {
+        ui.label(format!("    {kind}: {count}"));
+    }
+}
+
+const BANDWIDTH_COLUMNS: [&str; 7] = [
+    "#",
+    "Message",
+    "msg/s",
+    "KB/s",
+    "Share",
+    "Total msgs",
+    "Total KB",
+];
+
+/// Server message types ranked by the bytes they took over the last second
+fn show_bandwidth(ui: &mut egui::Ui, rows: &[MessageBandwidth]) {
+    ui.heading("Bandwidth by message type");
+    if rows.is_empty() {
+        ui.label("Nothing sent yet");
+        return;
+    }
+    let total_rate = rows.iter().map(|row| row.bytes_per_second).sum::<u64>();
+    ui.label(format!(
+        "{:.1} KB/s to all clients",
+        total_rate as f64 / 1024.0
+    ));
+    egui::ScrollArea::vertical()
+        .max_height(240.0)
+        .show(ui, |ui| {
+            egui::Grid::new("bandwidth_table")
+                .striped(true)
+                .num_columns(BANDWIDTH_COLUMNS.len())
+                .show(ui, |ui| {
+                    for heading in BANDWIDTH_COLUMNS {
+                        ui.strong(heading);
+                    }
+                    ui.end_row();
+                    for (rank, row) in rows.iter().enumerate() {
+                        let share = row.bytes_per_second as f64 / total_rate.max(1) as f64;
+                        ui.label(format!("{}", rank + 1));
+                        ui.label(&row.message_type);
+                        ui.label(format!("{}", row.messages_per_second));
+                        ui.label(format!("{:.1}", row.bytes_per_second as f64 / 1024.0));
+                        ui.label(format!("{:.0}%", share * 100.0));
+                        ui.label(format!("{}", row.total_messages));
+                        ui.label(format!("{:.1}", row.total_bytes as f64 / 1024.0));
+                        ui.end_row();
+                    }
+                });
+        });
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 enum DebugMessage {
     GameState(ServerMessage),
@@ -393,6 +660,22 @@ enum DebugMessage {
         data: AIVisualizationData,
     },
     SimulationPaused(bool),
+    /// AI-to-AI messages, in order, as they're sent
+    CommEvents(Vec<ai::CommEvent>),
+    /// The task weights an AI is playing with
+    AIWeights {
+        ai_id: Uuid,
+        weights: UtilityWeights,
+    },
+    /// What an AI's team is allowed to know
+    AIView {
+        ai_id: Uuid,
+        view: GameView,
+    },
+    /// What the AIs' latest update cost
+    AIMetrics(AITickMetrics),
+    /// What each server message type is costing, heaviest first
+    Bandwidth(Vec<MessageBandwidth>),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -406,6 +689,14 @@ enum DebugCommand {
     },
     RemoveAI(Uuid),
     RequestAIData(Uuid),
+    RequestAIWeights(Uuid),
+    SetAIWeights {
+        ai_id: Uuid,
+        weights: UtilityWeights,
+    },
+    RequestAIView(Uuid),
+    /// Only forward game traffic on these channels
+    Subscribe(Vec<StateChannel>),
 }
 
 fn main() -> Result<(), eframe::Error> {
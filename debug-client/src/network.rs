@@ -83,6 +83,12 @@ impl Handler for ClientHandler {
     fn on_open(&mut self, _: Handshake) -> WsResult<()> {
         log::info!("Connected to debug server");
         *self.connected.lock().unwrap() = true;
+        // Snapshots carry the players, mechs and resources we draw; the
+        // rest of the game's traffic is wasted on us
+        let subscribe = DebugCommand::Subscribe(vec![shared::StateChannel::Snapshots]);
+        if let Ok(json) = serde_json::to_string(&subscribe) {
+            self.out.send(Message::text(json))?;
+        }
         Ok(())
     }
 
@@ -0,0 +1,199 @@
+use ai::DecisionRecord;
+use eframe::egui;
+use std::path::Path;
+
+/// Offline playback of a JSONL decision log, no server connection needed
+pub struct DecisionLogReplay {
+    pub records: Vec<DecisionRecord>,
+    /// Index of the record currently shown
+    pub cursor: usize,
+    pub playing: bool,
+    /// Records advanced per second while playing
+    pub playback_rate: f32,
+    /// Only show decisions from this AI (None = all)
+    pub ai_filter: Option<uuid::Uuid>,
+    accumulator: f32,
+}
+
+impl DecisionLogReplay {
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        let records = ai::load_decision_log(path)?;
+        Ok(Self {
+            records,
+            cursor: 0,
+            playing: false,
+            playback_rate: 10.0,
+            ai_filter: None,
+            accumulator: 0.0,
+        })
+    }
+
+    /// Advance playback by `dt` seconds of wall time
+    pub fn advance(&mut self, dt: f32) {
+        if !self.playing || self.records.is_empty() {
+            return;
+        }
+
+        self.accumulator += dt * self.playback_rate;
+        while self.accumulator >= 1.0 {
+            self.accumulator -= 1.0;
+            if !self.step_forward() {
+                self.playing = false;
+                break;
+            }
+        }
+    }
+
+    /// Move to the next record matching the filter; false at the end of the log
+    pub fn step_forward(&mut self) -> bool {
+        let next = (self.cursor + 1..self.records.len()).find(|&i| self.matches(i));
+        match next {
+            Some(index) => {
+                self.cursor = index;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Move to the previous record matching the filter
+    pub fn step_back(&mut self) {
+        if let Some(index) = (0..self.cursor).rev().find(|&i| self.matches(i)) {
+            self.cursor = index;
+        }
+    }
+
+    pub fn current(&self) -> Option<&DecisionRecord> {
+        self.records.get(self.cursor)
+    }
+
+    /// Distinct AI ids present in the log, in order of first appearance
+    pub fn ai_ids(&self) -> Vec<uuid::Uuid> {
+        let mut ids = Vec::new();
+        for record in &self.records {
+            if !ids.contains(&record.ai_id) {
+                ids.push(record.ai_id);
+            }
+        }
+        ids
+    }
+
+    fn matches(&self, index: usize) -> bool {
+        match self.ai_filter {
+            Some(ai_id) => self.records[index].ai_id == ai_id,
+            None => true,
+        }
+    }
+}
+
+pub fn show_replay(ui: &mut egui::Ui, replay: &mut DecisionLogReplay) {
+    ui.heading(format!("Decision Log Replay ({} records)", replay.records.len()));
+
+    if replay.records.is_empty() {
+        ui.label("Log contains no decisions");
+        return;
+    }
+
+    ui.horizontal(|ui| {
+        if ui.button("⏮").clicked() {
+            replay.step_back();
+        }
+        if replay.playing {
+            if ui.button("⏸ Pause").clicked() {
+                replay.playing = false;
+            }
+        } else if ui.button("▶ Play").clicked() {
+            replay.playing = true;
+        }
+        if ui.button("⏭").clicked() {
+            replay.step_forward();
+        }
+
+        ui.separator();
+        ui.label("Rate:");
+        ui.add(egui::Slider::new(&mut replay.playback_rate, 1.0..=60.0).suffix(" dec/s"));
+
+        ui.separator();
+        let selected = replay
+            .ai_filter
+            .map(|id| format!("AI {}", &id.to_string()[..8]))
+            .unwrap_or_else(|| "All AIs".to_string());
+        egui::ComboBox::from_label("Filter")
+            .selected_text(selected)
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut replay.ai_filter, None, "All AIs");
+                for ai_id in replay.ai_ids() {
+                    ui.selectable_value(
+                        &mut replay.ai_filter,
+                        Some(ai_id),
+                        format!("AI {}", &ai_id.to_string()[..8]),
+                    );
+                }
+            });
+    });
+
+    let max_index = replay.records.len() - 1;
+    ui.add(egui::Slider::new(&mut replay.cursor, 0..=max_index).text("Timeline"));
+
+    ui.separator();
+
+    let Some(record) = replay.current() else {
+        return;
+    };
+
+    ui.group(|ui| {
+        ui.label(format!(
+            "{} | tick {} | AI {}",
+            record.timestamp,
+            record.tick,
+            &record.ai_id.to_string()[..8]
+        ));
+        ui.label(format!("Hat: {}", record.hat));
+        ui.label(format!(
+            "Action: {}",
+            record.chosen_action.as_deref().unwrap_or("None")
+        ));
+        ui.label(format!("Confidence: {:.1}%", record.confidence * 100.0));
+        ui.label(format!("Reasoning: {}", record.reasoning));
+        ui.label(format!("Messages sent: {}", record.messages_sent));
+    });
+
+    ui.columns(2, |columns| {
+        columns[0].heading("Perception");
+        let perception = &record.perception;
+        columns[0].label(format!("Location: {}", perception.location));
+        columns[0].label(format!("Health: {}", perception.health_status));
+        columns[0].label(format!(
+            "Carrying: {}",
+            perception.carrying_resource.as_deref().unwrap_or("-")
+        ));
+        columns[0].label(format!(
+            "Station: {}",
+            perception.operating_station.as_deref().unwrap_or("-")
+        ));
+        columns[0].label(format!(
+            "Threats: {} (max severity {:.2})",
+            perception.threat_count, perception.max_threat_severity
+        ));
+        columns[0].label(format!("Opportunities: {}", perception.opportunity_count));
+        columns[0].label(format!(
+            "Combat readiness: {:.0}%",
+            perception.combat_readiness * 100.0
+        ));
+
+        columns[1].heading("Scored Options");
+        if record.scored_options.is_empty() {
+            columns[1].label("No options recorded");
+        }
+        for option in &record.scored_options {
+            columns[1].horizontal(|ui| {
+                ui.add(
+                    egui::ProgressBar::new(option.score.clamp(0.0, 1.0))
+                        .desired_width(120.0)
+                        .text(format!("{:.2}", option.score)),
+                );
+                ui.label(&option.name);
+            });
+        }
+    });
+}
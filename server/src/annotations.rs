@@ -0,0 +1,71 @@
+//! Coaching marks. Spectators and players draw lines, arrows and circles on
+//! the map for one team or for everyone; the server holds each author to
+//! `ANNOTATIONS_PER_SECOND` and forwards what gets through to the people
+//! it's meant for. Spectators see every mark.
+
+use crate::game::Game;
+use shared::*;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+#[derive(Debug, Default)]
+pub struct AnnotationLimiter {
+    /// When each author's current second started and marks drawn in it
+    rates: HashMap<Uuid, (Instant, u32)>,
+}
+
+impl AnnotationLimiter {
+    /// Count a mark against its author, or the seconds until they can
+    /// draw again if they're over their rate
+    pub fn allow(&mut self, author: Uuid, now: Instant) -> Result<(), f32> {
+        let window = Duration::from_secs(1);
+        self.rates
+            .retain(|_, (start, _)| now.saturating_duration_since(*start) < window);
+        let (start, drawn) = self.rates.entry(author).or_insert((now, 0));
+        if *drawn >= ANNOTATIONS_PER_SECOND {
+            return Err((window - now.saturating_duration_since(*start)).as_secs_f32());
+        }
+        *drawn += 1;
+        Ok(())
+    }
+}
+
+/// Who a mark for `audience` goes to, or None for everyone
+pub fn recipients(game: &Game, audience: AnnotationAudience) -> Option<Vec<Uuid>> {
+    let AnnotationAudience::Team(team) = audience else {
+        return None;
+    };
+    let mut ids: Vec<Uuid> = game
+        .players
+        .values()
+        .filter(|player| player.team == team)
+        .map(|player| player.id)
+        .chain(game.spectators.iter().copied())
+        .collect();
+    ids.sort_unstable();
+    Some(ids)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_each_author_gets_their_own_rate() {
+        let start = Instant::now();
+        let mut limiter = AnnotationLimiter::default();
+        let (coach, other) = (Uuid::from_u128(1), Uuid::from_u128(2));
+        for _ in 0..ANNOTATIONS_PER_SECOND {
+            assert!(limiter.allow(coach, start).is_ok());
+        }
+        let wait = limiter
+            .allow(coach, start + Duration::from_millis(250))
+            .unwrap_err();
+        assert!((wait - 0.75).abs() < 0.01);
+        assert!(limiter.allow(other, start).is_ok());
+
+        // A new second starts them over
+        assert!(limiter.allow(coach, start + Duration::from_secs(1)).is_ok());
+    }
+}
@@ -0,0 +1,71 @@
+//! Errors from the HTTP endpoints, answered with a status that fits the
+//! error's code and the same `{code, message}` a websocket client would get.
+
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Json, Response};
+use serde::Serialize;
+use shared::{ErrorCode, ServerError};
+
+#[derive(Debug)]
+pub struct ApiError(pub ServerError);
+
+impl<E: Into<ServerError>> From<E> for ApiError {
+    fn from(error: E) -> Self {
+        Self(error.into())
+    }
+}
+
+#[derive(Serialize)]
+struct ErrorBody {
+    code: ErrorCode,
+    message: String,
+}
+
+pub fn status_for(code: ErrorCode) -> StatusCode {
+    match code {
+        ErrorCode::InvalidInput => StatusCode::BAD_REQUEST,
+        ErrorCode::NotFound => StatusCode::NOT_FOUND,
+        ErrorCode::NotAllowed => StatusCode::FORBIDDEN,
+        ErrorCode::Conflict => StatusCode::CONFLICT,
+        ErrorCode::Cooldown => StatusCode::TOO_MANY_REQUESTS,
+        ErrorCode::InsufficientResources => StatusCode::UNPROCESSABLE_ENTITY,
+        ErrorCode::Incompatible => StatusCode::UPGRADE_REQUIRED,
+        ErrorCode::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+        ErrorCode::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let code = self.0.code();
+        if code == ErrorCode::Internal {
+            log::error!("Request failed: {}", self.0);
+        }
+        let body = ErrorBody {
+            code,
+            message: self.0.to_string(),
+        };
+        (status_for(code), Json(body)).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::{GameError, ValidationError};
+
+    #[test]
+    fn test_errors_map_to_statuses_by_code() {
+        let missing = ApiError::from(GameError::player_not_found(uuid::Uuid::nil()));
+        assert_eq!(missing.into_response().status(), StatusCode::NOT_FOUND);
+
+        let shutting_down = ApiError::from(GameError::ServerShuttingDown);
+        assert_eq!(
+            shutting_down.into_response().status(),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+
+        let invalid = ApiError::from(ValidationError::InvalidFloorNumber);
+        assert_eq!(invalid.into_response().status(), StatusCode::BAD_REQUEST);
+    }
+}
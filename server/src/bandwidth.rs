@@ -0,0 +1,194 @@
+use shared::MessageBandwidth;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Bytes written to every client socket, for the metrics endpoint and the
+/// debug client
+pub type SharedBandwidth = Arc<Mutex<BandwidthMeter>>;
+
+/// Message and byte counts for one message type
+#[derive(Debug, Clone, Copy, Default)]
+struct Tally {
+    messages: u64,
+    bytes: u64,
+}
+
+impl Tally {
+    fn add(&mut self, bytes: usize) {
+        self.messages += 1;
+        self.bytes += bytes as u64;
+    }
+}
+
+/// Counts what goes out per `ServerMessage` variant in whole-second
+/// windows, keeping the last finished second for rates and running totals
+pub struct BandwidthMeter {
+    window_start: Instant,
+    current: HashMap<&'static str, Tally>,
+    last_second: HashMap<&'static str, Tally>,
+    totals: HashMap<&'static str, Tally>,
+}
+
+impl BandwidthMeter {
+    pub fn new(now: Instant) -> Self {
+        Self {
+            window_start: now,
+            current: HashMap::new(),
+            last_second: HashMap::new(),
+            totals: HashMap::new(),
+        }
+    }
+
+    /// Count one frame of `bytes` carrying a message of `message_type`
+    pub fn record(&mut self, message_type: &'static str, bytes: usize, now: Instant) {
+        self.roll(now);
+        self.current.entry(message_type).or_default().add(bytes);
+        self.totals.entry(message_type).or_default().add(bytes);
+    }
+
+    /// Close the window once a second has passed; a gap of more than one
+    /// means nothing went out in the second before now
+    fn roll(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.window_start);
+        if elapsed < Duration::from_secs(1) {
+            return;
+        }
+        let finished = std::mem::take(&mut self.current);
+        self.last_second = if elapsed < Duration::from_secs(2) {
+            finished
+        } else {
+            HashMap::new()
+        };
+        self.window_start += Duration::from_secs(elapsed.as_secs());
+    }
+
+    /// Every message type seen, heaviest over the last second first and
+    /// then by total bytes
+    pub fn ranked(&mut self, now: Instant) -> Vec<MessageBandwidth> {
+        self.roll(now);
+        let mut rows: Vec<MessageBandwidth> = self
+            .totals
+            .iter()
+            .map(|(message_type, total)| {
+                let recent = self
+                    .last_second
+                    .get(message_type)
+                    .copied()
+                    .unwrap_or_default();
+                MessageBandwidth {
+                    message_type: message_type.to_string(),
+                    messages_per_second: recent.messages,
+                    bytes_per_second: recent.bytes,
+                    total_messages: total.messages,
+                    total_bytes: total.bytes,
+                }
+            })
+            .collect();
+        rows.sort_by(|a, b| {
+            (b.bytes_per_second, b.total_bytes, &a.message_type).cmp(&(
+                a.bytes_per_second,
+                a.total_bytes,
+                &b.message_type,
+            ))
+        });
+        rows
+    }
+}
+
+impl Default for BandwidthMeter {
+    fn default() -> Self {
+        Self::new(Instant::now())
+    }
+}
+
+/// The rows in Prometheus' text format
+pub fn prometheus_text(rows: &[MessageBandwidth]) -> String {
+    let mut text = String::new();
+    let mut metric = |name: &str, kind: &str, help: &str, value: fn(&MessageBandwidth) -> u64| {
+        let _ = writeln!(text, "# HELP {name} {help}");
+        let _ = writeln!(text, "# TYPE {name} {kind}");
+        for row in rows {
+            let _ = writeln!(
+                text,
+                "{name}{{type=\"{}\"}} {}",
+                row.message_type,
+                value(row)
+            );
+        }
+    };
+    metric(
+        "mech_server_messages_total",
+        "counter",
+        "Server messages written to client sockets",
+        |row| row.total_messages,
+    );
+    metric(
+        "mech_server_message_bytes_total",
+        "counter",
+        "Bytes of server messages written to client sockets",
+        |row| row.total_bytes,
+    );
+    metric(
+        "mech_server_messages_per_second",
+        "gauge",
+        "Server messages written over the last full second",
+        |row| row.messages_per_second,
+    );
+    metric(
+        "mech_server_message_bytes_per_second",
+        "gauge",
+        "Bytes of server messages written over the last full second",
+        |row| row.bytes_per_second,
+    );
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rates_cover_the_last_full_second() {
+        let start = Instant::now();
+        let at = |millis| start + Duration::from_millis(millis);
+        let mut meter = BandwidthMeter::new(start);
+        meter.record("PlayerMoved", 40, at(100));
+        meter.record("PlayerMoved", 40, at(600));
+        meter.record("GameState", 5000, at(900));
+
+        // Nothing's finished a second yet
+        let rows = meter.ranked(at(950));
+        assert!(rows.iter().all(|row| row.bytes_per_second == 0));
+        assert_eq!(rows[0].message_type, "GameState");
+
+        meter.record("PlayerMoved", 40, at(1200));
+        let rows = meter.ranked(at(1500));
+        assert_eq!(rows[0].message_type, "GameState");
+        assert_eq!(rows[0].bytes_per_second, 5000);
+        assert_eq!(rows[1].messages_per_second, 2);
+        assert_eq!(rows[1].total_messages, 3);
+        assert_eq!(rows[1].total_bytes, 120);
+
+        // A quiet second in between leaves no rate behind
+        let rows = meter.ranked(at(3100));
+        assert!(rows.iter().all(|row| row.messages_per_second == 0));
+        assert_eq!(rows[1].total_messages, 3);
+    }
+
+    #[test]
+    fn test_prometheus_text_labels_each_type() {
+        let rows = vec![MessageBandwidth {
+            message_type: "GameState".to_string(),
+            messages_per_second: 1,
+            bytes_per_second: 5000,
+            total_messages: 10,
+            total_bytes: 50000,
+        }];
+        let text = prometheus_text(&rows);
+        assert!(text.contains("# TYPE mech_server_message_bytes_total counter"));
+        assert!(text.contains("mech_server_message_bytes_total{type=\"GameState\"} 50000"));
+        assert!(text.contains("mech_server_messages_per_second{type=\"GameState\"} 1"));
+    }
+}
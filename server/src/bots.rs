@@ -0,0 +1,14 @@
+use ai::BotRegistry;
+
+// Bot crates register themselves with `ai::register_bot!`; linking one in is
+// all it takes for its bots to show up below
+#[cfg(feature = "scavenger")]
+use scavenger_bot as _;
+
+/// Bots this server can spawn with `--bot <name>` or `POST /ai/add` with a
+/// `bot` field: the built-in AIs plus every bot crate linked into the binary.
+/// To add your own, implement `ai::AIController` in a crate that calls
+/// `ai::register_bot!`, then depend on it behind a feature like `scavenger`.
+pub fn bot_registry() -> BotRegistry {
+    BotRegistry::new()
+}
@@ -1,31 +1,129 @@
 use axum::extract::ws::{Message, WebSocket};
 use futures::{sink::SinkExt, stream::StreamExt};
-use tokio::sync::broadcast;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{broadcast, Notify, RwLock};
 use uuid::Uuid;
 
+use crate::events::GameEvent;
+use crate::send_queue::ClientSendQueue;
+use crate::systems::station_effects;
 use crate::{game::Game, AppState};
+use crate::{scanner, station_upgrades};
 use shared::types::UpgradeType;
 use shared::*;
 
-pub async fn handle_client(socket: WebSocket, player_id: Uuid, state: AppState) {
+pub async fn handle_client(socket: WebSocket, player_id: Uuid, state: AppState, compression: bool) {
     let (mut sender, mut receiver) = socket.split();
-    let mut rx = state.tx.subscribe();
+    let rx = state.tx.subscribe();
 
-    // Spawn task to forward messages from broadcast to this client
+    // Each client drains the broadcast into its own queue, so a slow socket
+    // only ever loses its own low-priority traffic
+    let queue = Arc::new(Mutex::new(ClientSendQueue::new(CLIENT_SEND_QUEUE_CAPACITY)));
+    let ready = Arc::new(Notify::new());
+    state
+        .send_queues
+        .write()
+        .await
+        .insert(player_id, queue.clone());
+
+    let mut pump_task = tokio::spawn(pump_broadcasts(
+        player_id,
+        rx,
+        queue.clone(),
+        ready.clone(),
+        state.game.clone(),
+    ));
+
+    // Write queued messages to the socket as fast as it accepts them, held
+    // back first by the network conditioner if one is configured
+    let send_queue = queue.clone();
+    let bandwidth = state.bandwidth.clone();
+    let mut conditioner = Some(state.net_conditions)
+        .filter(NetConditions::is_active)
+        .map(NetConditioner::new);
+    // Pings carry the microseconds since this instant they were sent at, so
+    // the pong alone is enough to time the round trip
+    let started = tokio::time::Instant::now();
     let mut send_task = tokio::spawn(async move {
-        while let Ok((target_id, msg)) = rx.recv().await {
-            // Send to all if target is nil, or to specific player
-            if target_id == Uuid::nil() || target_id == player_id {
-                let msg_bytes = match rmp_serde::to_vec(&msg) {
-                    Ok(bytes) => bytes,
+        let mut ping =
+            tokio::time::interval(std::time::Duration::from_millis(LAG_COMP_PING_INTERVAL_MS));
+        // Frames follow the URL's compression setting until the join
+        // handshake settles the features for the rest of the connection
+        let mut compression = compression;
+        let mut binary = true;
+        loop {
+            let release = conditioner
+                .as_ref()
+                .and_then(NetConditioner::next_release)
+                .map(|release| started + std::time::Duration::from_secs_f64(release));
+            let ping_due = tokio::select! {
+                _ = ready.notified() => false,
+                _ = tokio::time::sleep_until(release.unwrap_or(started)), if release.is_some() => false,
+                _ = ping.tick() => true,
+            };
+            if ping_due {
+                let sent = started.elapsed().as_micros() as u64;
+                let frame = Message::Ping(sent.to_le_bytes().to_vec());
+                match conditioner.as_mut() {
+                    Some(conditioner) => conditioner.push(started.elapsed().as_secs_f64(), frame),
+                    None => {
+                        if sender.send(frame).await.is_err() {
+                            return;
+                        }
+                    }
+                }
+            }
+
+            loop {
+                let Some(msg) = send_queue.lock().unwrap().pop() else {
+                    break;
+                };
+                let frame = if binary {
+                    encode_server_frame(&msg, compression).map(Message::Binary)
+                } else {
+                    serde_json::to_string(&msg)
+                        .map(Message::Text)
+                        .map_err(NetworkError::from)
+                };
+                let frame = match frame {
+                    Ok(frame) => frame,
                     Err(e) => {
                         log::error!("Failed to serialize message: {e}");
                         log::error!("Message: {:?}", msg);
-                        break;
+                        return;
                     }
                 };
-                if sender.send(Message::Binary(msg_bytes)).await.is_err() {
-                    break;
+                let bytes = match &frame {
+                    Message::Binary(bytes) => bytes.len(),
+                    Message::Text(text) => text.len(),
+                    _ => 0,
+                };
+                bandwidth
+                    .lock()
+                    .unwrap()
+                    .record(msg.type_name(), bytes, std::time::Instant::now());
+                if let ServerMessage::JoinedGame { features, .. }
+                | ServerMessage::SpectatingGame { features } = &msg
+                {
+                    compression = features.contains(&ProtocolFeature::Compression);
+                    binary = features.contains(&ProtocolFeature::BinaryEncoding);
+                }
+                match conditioner.as_mut() {
+                    Some(conditioner) => conditioner.push(started.elapsed().as_secs_f64(), frame),
+                    None => {
+                        if sender.send(frame).await.is_err() {
+                            return;
+                        }
+                    }
+                }
+            }
+
+            if let Some(conditioner) = conditioner.as_mut() {
+                for frame in conditioner.pop_ready(started.elapsed().as_secs_f64()) {
+                    if sender.send(frame).await.is_err() {
+                        return;
+                    }
                 }
             }
         }
@@ -35,51 +133,53 @@ pub async fn handle_client(socket: WebSocket, player_id: Uuid, state: AppState)
     let tx = state.tx.clone();
     let game = state.game.clone();
     let mut recv_task = tokio::spawn(async move {
+        let mut input_noted = None;
         while let Some(msg) = receiver.next().await {
             match msg {
-                Ok(Message::Binary(bytes)) => {
-                    match rmp_serde::from_slice::<ClientMessage>(&bytes) {
-                        Ok(client_msg) => {
-                            // Validate the message before processing
-                            if let Err(e) = client_msg.validate() {
-                                log::warn!("Invalid message from player {player_id}: {e}");
-                                // Optionally send error back to client
-                                continue;
-                            }
-                            let command = crate::commands::create_command(client_msg);
-                            if let Err(e) = command.execute(&game, player_id, &tx).await {
-                                log::warn!("Command execution failed for player {player_id}: {e}");
-                            }
-                        }
-                        Err(e) => {
-                            log::warn!(
-                                "Failed to parse binary message from player {player_id}: {e}"
-                            );
-                        }
+                Ok(Message::Binary(bytes)) => match deserialize_client_message(&bytes) {
+                    Ok(client_msg) => {
+                        handle_client_message(client_msg, &game, player_id, &tx, &mut input_noted)
+                            .await;
                     }
-                }
+                    Err(e) => {
+                        report_error(&tx, player_id, e);
+                    }
+                },
                 Ok(Message::Text(text)) => {
                     // Legacy JSON support during migration
                     match serde_json::from_str::<ClientMessage>(&text) {
                         Ok(client_msg) => {
-                            // Validate the message before processing
-                            if let Err(e) = client_msg.validate() {
-                                log::warn!("Invalid message from player {player_id}: {e}");
-                                // Optionally send error back to client
-                                continue;
-                            }
-                            let command = crate::commands::create_command(client_msg);
-                            if let Err(e) = command.execute(&game, player_id, &tx).await {
-                                log::warn!("Command execution failed for player {player_id}: {e}");
-                            }
+                            handle_client_message(
+                                client_msg,
+                                &game,
+                                player_id,
+                                &tx,
+                                &mut input_noted,
+                            )
+                            .await;
                         }
                         Err(e) => {
-                            log::warn!("Failed to parse JSON message from player {player_id}: {e}");
+                            let error = NetworkError::InvalidMessage(e.to_string());
+                            report_error(&tx, player_id, error);
                         }
                     }
                 }
+                Ok(Message::Pong(payload)) => {
+                    // Answer to one of our pings; time the round trip
+                    let Ok(sent) = <[u8; 8]>::try_from(payload.as_slice()) else {
+                        continue;
+                    };
+                    let sent = std::time::Duration::from_micros(u64::from_le_bytes(sent));
+                    let round_trip = started.elapsed().saturating_sub(sent);
+                    let mut game = game.write().await;
+                    // Spectators have nothing to shoot with
+                    if game.players.contains_key(&player_id) {
+                        game.lag_compensation
+                            .record_round_trip(player_id, round_trip.as_secs_f32());
+                    }
+                }
                 Ok(_) => {
-                    // Ignore other message types (Close, Ping, Pong)
+                    // Ignore other message types (Close, Ping)
                     log::debug!("Received non-text/binary message from player {player_id}");
                 }
                 Err(e) => {
@@ -90,26 +190,156 @@ pub async fn handle_client(socket: WebSocket, player_id: Uuid, state: AppState)
         }
     });
 
-    // Wait for either task to complete
+    // Wait for any task to complete
     tokio::select! {
-        _ = (&mut send_task) => recv_task.abort(),
-        _ = (&mut recv_task) => send_task.abort(),
+        _ = (&mut pump_task) => {}
+        _ = (&mut send_task) => {}
+        _ = (&mut recv_task) => {}
     };
+    pump_task.abort();
+    send_task.abort();
+    recv_task.abort();
 
-    // Clean up player on disconnect
-    {
-        let mut game = state.game.write().await;
-        game.remove_player(&player_id);
+    state.send_queues.write().await.remove(&player_id);
+    let stats = queue.lock().unwrap().stats();
+    if stats.dropped > 0 || stats.lagged > 0 {
+        log::info!(
+            "Player {player_id} send queue: {} sent, {} dropped, {} coalesced, {} lagged, peak {}",
+            stats.sent,
+            stats.dropped,
+            stats.coalesced,
+            stats.lagged,
+            stats.peak_queued
+        );
     }
 
+    // Keep players who joined around for a while in case they reconnect,
+    // and clean up everyone else now
+    let cleanup_messages = {
+        let mut game = state.game.write().await;
+        game.collision_debug.forget(player_id);
+        if game.players.contains_key(&player_id) && game.sessions.hold(player_id) {
+            log::info!("Player {player_id} disconnected; holding them to resume");
+            return;
+        }
+        game.drop_player(player_id)
+    };
+
     // Notify other players
-    let _ = state
-        .tx
-        .send((Uuid::nil(), ServerMessage::PlayerDisconnected { player_id }));
+    for msg in cleanup_messages {
+        let _ = state.tx.send((Uuid::nil(), msg));
+    }
 
     log::info!("Player {player_id} disconnected");
 }
 
+/// Drain the broadcast into one client's queue, keeping what it's
+/// subscribed to. A client that lagged so far behind that broadcasts were
+/// lost is sent a fresh snapshot so it can't stay out of step.
+async fn pump_broadcasts(
+    player_id: Uuid,
+    mut rx: broadcast::Receiver<(Uuid, ServerMessage)>,
+    queue: Arc<Mutex<ClientSendQueue>>,
+    ready: Arc<Notify>,
+    game: Arc<RwLock<Game>>,
+) {
+    let mut subscription = Subscription::all();
+    loop {
+        match rx.recv().await {
+            // Send to all if target is nil, or to specific player, as
+            // long as they're subscribed to it
+            Ok((target_id, msg)) => {
+                if target_id != Uuid::nil() && target_id != player_id {
+                    continue;
+                }
+                if let ServerMessage::Subscribed { channels } = &msg {
+                    subscription = Subscription::only(channels);
+                }
+                if subscription.wants(&msg) {
+                    queue.lock().unwrap().push(msg);
+                    ready.notify_one();
+                }
+            }
+            Err(broadcast::error::RecvError::Lagged(missed)) => {
+                log::warn!("Player {player_id} missed {missed} broadcast messages, resyncing");
+                let snapshot = game.read().await.get_full_state();
+                let mut queue = queue.lock().unwrap();
+                queue.record_lagged(missed);
+                queue.push(snapshot);
+                ready.notify_one();
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+    }
+}
+
+/// Validate and run one message from a client, telling them if it failed
+async fn handle_client_message(
+    client_msg: ClientMessage,
+    game: &tokio::sync::RwLock<Game>,
+    player_id: Uuid,
+    tx: &broadcast::Sender<(Uuid, ServerMessage)>,
+    input_noted: &mut Option<tokio::time::Instant>,
+) {
+    if let Err(e) = client_msg.validate() {
+        report_error(tx, player_id, e);
+        return;
+    }
+    note_input(game, player_id, tx, input_noted).await;
+    // Nothing that plays the match goes through while crews are in the hangar
+    if !crate::hangar::allows(&client_msg) && game.read().await.hangar.is_some() {
+        return;
+    }
+    let command = crate::commands::create_command(client_msg);
+    if let Err(e) = command.execute(game, player_id, tx).await {
+        // Refused joins already got their own answer
+        if matches!(
+            e,
+            GameError::IncompatibleProtocol { .. }
+                | GameError::WrongPassword
+                | GameError::RoomFull { .. }
+        ) {
+            log::info!("Turned away player {player_id}: {e}");
+        } else {
+            report_error(tx, player_id, e);
+        }
+    }
+}
+
+/// Log a failed request and send the error to the player who made it
+pub fn report_error(
+    tx: &broadcast::Sender<(Uuid, ServerMessage)>,
+    player_id: Uuid,
+    error: impl Into<ServerError>,
+) {
+    let error = error.into();
+    match error.code() {
+        ErrorCode::Internal => log::error!("Request from player {player_id} failed: {error}"),
+        // A well-behaved client never sends these
+        ErrorCode::InvalidInput => log::warn!("Bad request from player {player_id}: {error}"),
+        _ => log::debug!("Request from player {player_id} refused: {error}"),
+    }
+    let _ = tx.send((player_id, error.to_message()));
+}
+
+/// Restart a player's idle timer on input, taking the game lock for it at
+/// most once every `IDLE_INPUT_NOTE_MS`
+async fn note_input(
+    game: &tokio::sync::RwLock<Game>,
+    player_id: Uuid,
+    tx: &broadcast::Sender<(Uuid, ServerMessage)>,
+    noted: &mut Option<tokio::time::Instant>,
+) {
+    let interval = std::time::Duration::from_millis(IDLE_INPUT_NOTE_MS);
+    if noted.is_some_and(|noted| noted.elapsed() < interval) {
+        return;
+    }
+    *noted = Some(tokio::time::Instant::now());
+    for message in game.write().await.mark_active(player_id) {
+        let _ = tx.send((Uuid::nil(), message));
+    }
+}
+
 pub async fn handle_action_key(
     game: &mut crate::game::Game,
     player_id: Uuid,
@@ -121,34 +351,40 @@ pub async fn handle_action_key(
                 // Mech entry is now automatic by walking into the door, no action key needed
 
                 // Check for resource deposit
-                if player.carrying_resource.is_some() {
+                if player.inventory.has_resource() {
                     let player_tile = pos.to_tile_pos();
-                    for mech in game.mechs.values_mut() {
-                        if mech.team == player.team
-                            && player_tile.distance_to(mech.position) < MECH_COLLISION_DISTANCE
-                        {
-                            // Deposit resource at mech
-                            if let Some(player) = game.players.get_mut(&player_id) {
-                                if let Some(resource_type) = player.carrying_resource.take() {
-                                    // Add to mech inventory
-                                    *mech.resource_inventory.entry(resource_type).or_insert(0) += 1;
-
-                                    let _ = tx.send((
-                                        Uuid::nil(),
-                                        ServerMessage::PlayerDroppedResource {
-                                            player_id,
-                                            resource_type,
-                                            position: player_tile,
-                                        },
-                                    ));
-                                }
-                            }
+                    let dropoff = game
+                        .mechs
+                        .values()
+                        .find(|mech| {
+                            mech.team == player.team
+                                && player_tile.distance_to(mech.position) < MECH_COLLISION_DISTANCE
+                        })
+                        .map(|mech| mech.id);
+                    if let Some(mech_id) = dropoff {
+                        let Some(player) = game.players.get_mut(&player_id) else {
                             return;
+                        };
+                        let resources = player.inventory.take_resources();
+                        let _ = tx.send((Uuid::nil(), player.inventory_message()));
+
+                        for resource_type in resources {
+                            let _ = tx.send((
+                                Uuid::nil(),
+                                ServerMessage::PlayerDroppedResource {
+                                    player_id,
+                                    resource_type,
+                                    position: player_tile,
+                                },
+                            ));
+                            for msg in game.deliver_resource(player_id, mech_id, resource_type) {
+                                let _ = tx.send((Uuid::nil(), msg));
+                            }
                         }
                     }
                 }
             }
-            PlayerLocation::InsideMech { pos, .. } => {
+            PlayerLocation::InsideMech { mech_id, pos } => {
                 // First check if player is operating a station and wants to exit
                 if let Some(station_id) = player.operating_station {
                     // Exit station
@@ -170,17 +406,47 @@ pub async fn handle_action_key(
                     return; // Exit early - don't check for entering another station
                 }
 
-                // Otherwise check for station to enter
-                let player_tile = pos.tile_pos();
-                let floor = pos.floor();
-                let station_to_enter = game
+                // Boarders can't crew enemy stations, only wreck them
+                let boarding = game
                     .mechs
-                    .values()
-                    .flat_map(|m| m.stations.values())
-                    .find(|s| {
-                        s.floor == floor && s.position == player_tile && s.operated_by.is_none()
-                    })
-                    .map(|s| s.id);
+                    .get(&mech_id)
+                    .is_some_and(|mech| mech.team != player.team);
+                if boarding {
+                    let target = game.mechs.get(&mech_id).and_then(|mech| {
+                        let floor_map = mech.interior.floors.get(pos.floor() as usize)?;
+                        crate::movement::tiles_in_reach(pos.tile_pos())
+                            .into_iter()
+                            .find_map(|tile| floor_map.get_station_at(tile))
+                    });
+                    if let Some(station_id) = target {
+                        for message in game.handle_sabotage(player_id, mech_id, station_id) {
+                            let _ = tx.send((Uuid::nil(), message));
+                        }
+                    }
+                    return;
+                }
+
+                // Otherwise check for a free, working station within reach;
+                // stations block movement so players operate them from an
+                // adjacent tile
+                let station_to_enter = game.mechs.get(&mech_id).and_then(|mech| {
+                    let floor_map = mech.interior.floors.get(pos.floor() as usize)?;
+                    crate::movement::tiles_in_reach(pos.tile_pos())
+                        .into_iter()
+                        .filter_map(|tile| floor_map.get_station_at(tile))
+                        .find(|station_id| {
+                            let sabotaged = game
+                                .entity_storage
+                                .status_effects
+                                .get(station_id)
+                                .is_some_and(|effects| effects.has(StatusEffectKind::Sabotaged));
+                            !sabotaged
+                                && mech
+                                    .stations
+                                    .get(station_id)
+                                    .is_some_and(|s| s.operated_by.is_none())
+                        })
+                });
 
                 if let Some(station_id) = station_to_enter {
                     // Enter station
@@ -197,10 +463,19 @@ pub async fn handle_action_key(
                                     station_id,
                                 },
                             ));
+                            let mech_id = mech.id;
+                            if let Some(layout) = game.station_layout(mech_id, station_id) {
+                                let _ = tx.send((player_id, layout));
+                            }
                             return;
                         }
                     }
                 }
+
+                // Nothing to operate here, try damage control
+                for message in game.handle_damage_control(player_id) {
+                    let _ = tx.send((Uuid::nil(), message));
+                }
             }
         }
     }
@@ -241,6 +516,17 @@ pub async fn handle_exit_mech(
                     }
                 }
             }
+
+            // Damage control tools stay with the mech
+            if player.held_tool.take().is_some() {
+                let _ = tx.send((
+                    Uuid::nil(),
+                    ServerMessage::PlayerToolChanged {
+                        player_id,
+                        tool: None,
+                    },
+                ));
+            }
         }
 
         // Calculate exit position without holding any borrows
@@ -310,11 +596,14 @@ pub async fn handle_exit_station(
 
 pub async fn handle_station_button(
     game: &mut Game,
+    player_id: Uuid,
     mech_id: Uuid,
     station_type: StationType,
     button_index: u8,
     tx: &broadcast::Sender<(Uuid, ServerMessage)>,
 ) {
+    // Tiers bought for the station itself, on top of the mech's upgrades
+    let station_bonus = game.station_bonus_levels(player_id);
     match station_type {
         StationType::WeaponLaser => {
             if button_index == 0 {
@@ -327,22 +616,33 @@ pub async fn handle_station_button(
                     }
                 };
 
+                // Aim at what the shooter saw: enemies where they stood one
+                // round trip ago, as far back as the rewind cap allows
+                let rewind = game.lag_compensation.rewind_for(player_id);
                 let target = game
                     .mechs
                     .values()
-                    .filter(|m| m.team != our_team)
-                    .min_by(|a, b| {
-                        let dist_a = a.position.distance_to(our_pos);
-                        let dist_b = b.position.distance_to(our_pos);
+                    .filter(|m| m.team != our_team && m.health > 0)
+                    .map(|m| {
+                        let seen_at = game
+                            .lag_compensation
+                            .position_at(m.id, rewind)
+                            .map_or(m.position, |pos| pos.to_tile());
+                        (m, seen_at)
+                    })
+                    .min_by(|(_, a), (_, b)| {
+                        let dist_a = a.distance_to(our_pos);
+                        let dist_b = b.distance_to(our_pos);
                         dist_a
                             .partial_cmp(&dist_b)
                             .unwrap_or(std::cmp::Ordering::Equal)
-                    });
+                    })
+                    .filter(|(_, seen_at)| seen_at.distance_to(our_pos) <= WEAPON_MAX_RANGE);
 
-                if let Some(target) = target {
+                if let Some((target, target_pos)) = target {
                     let target_id = target.id;
-                    let target_pos = target.position;
-                    let target_health = target.health;
+                    let target_shield = target.shield;
+                    let target_heading = target.drive.heading;
 
                     let _ = tx.send((
                         Uuid::nil(),
@@ -354,200 +654,329 @@ pub async fn handle_station_button(
                         },
                     ));
 
-                    // Instant damage for laser
-                    let damage =
-                        LASER_BASE_DAMAGE + (LASER_DAMAGE_PER_LEVEL * (laser_level as u32 - 1));
-                    let new_health = target_health.saturating_sub(damage);
+                    // Instant damage for laser, boosted if we fire from an
+                    // overcharge zone or with systems overcharged, and weaker
+                    // the farther the beam reaches
+                    let base_damage = LASER_BASE_DAMAGE
+                        + (LASER_DAMAGE_PER_LEVEL * (laser_level as u32 - 1))
+                        + station_bonus * STATION_DAMAGE_PER_LEVEL;
+                    let damage = (base_damage as f32
+                        * damage_multiplier_at(
+                            &game.pacing_zones,
+                            MechPositioning::mech_center(our_pos),
+                        )
+                        * game.damage_multiplier(mech_id)
+                        * damage_falloff(WeaponType::Laser, target_pos.distance_to(our_pos)))
+                    .round() as u32;
+                    let (from, to) = (
+                        MechPositioning::mech_center(our_pos),
+                        MechPositioning::mech_center(target_pos),
+                    );
+                    let hit = HitDirection::new(target_heading, (to.x - from.x, to.y - from.y));
 
-                    if let Some(target_mech) = game.mechs.get_mut(&target_id) {
-                        target_mech.health = new_health;
+                    // A raised shield soaks part of the beam where it crosses the bubble
+                    let contact = ShieldBubble::around(target_pos)
+                        .entry_point(
+                            MechPositioning::mech_center(our_pos),
+                            MechPositioning::mech_center(target_pos),
+                        )
+                        .filter(|_| target_shield > 0);
+                    let (absorbed, through) = match contact {
+                        Some(_) => ShieldBubble::attenuate_laser(damage, target_shield),
+                        None => (0, damage),
+                    };
+                    let mut messages = Vec::new();
+                    let dealt = game.damage_mech_split(
+                        target_id,
+                        (absorbed, through),
+                        contact.unwrap_or(to),
+                        Some(hit),
+                        Some(mech_id),
+                        &mut messages,
+                    );
+                    for message in messages {
+                        let _ = tx.send((Uuid::nil(), message));
+                    }
+                    if dealt.destroyed {
+                        return;
                     }
 
+                    // Lasers set the hull burning and scar the ground under it
+                    let scar =
+                        game.add_decal(MechPositioning::mech_center(target_pos), DecalKind::Burn);
+                    let _ = tx.send((Uuid::nil(), scar));
+                    let burn = game.apply_status_effect(
+                        target_id,
+                        StatusEffectKind::Burning,
+                        LASER_BURN_DURATION,
+                        LASER_BURN_DPS,
+                    );
+                    let _ = tx.send((Uuid::nil(), burn));
+                }
+            }
+        }
+        StationType::WeaponProjectile => {
+            if button_index == 0 {
+                let Some(projectile_level) = game
+                    .mechs
+                    .get(&mech_id)
+                    .map(|m| m.upgrades.projectile_level)
+                else {
+                    log::error!("Mech {mech_id} not found when firing projectile");
+                    return;
+                };
+                let base_damage = PROJECTILE_BASE_DAMAGE
+                    + (PROJECTILE_DAMAGE_PER_LEVEL * (projectile_level as u32 - 1))
+                    + station_bonus * STATION_DAMAGE_PER_LEVEL;
+                fire_at_nearest_enemy(
+                    game,
+                    mech_id,
+                    StationType::WeaponProjectile,
+                    WeaponType::Projectile,
+                    base_damage,
+                    PROJECTILE_BASE_SPEED,
+                    tx,
+                );
+            }
+        }
+        StationType::WeaponEmp => {
+            if button_index == 0 {
+                // EMP pulses do no hull damage; the combat system disables the target on hit
+                fire_at_nearest_enemy(
+                    game,
+                    mech_id,
+                    StationType::WeaponEmp,
+                    WeaponType::Emp,
+                    0,
+                    EMP_PROJECTILE_SPEED,
+                    tx,
+                );
+            }
+        }
+        StationType::Shield => {
+            if button_index == 0 {
+                // Activate shield boost
+                if let Some(mech) = game.mechs.get_mut(&mech_id) {
+                    let boost = SHIELD_BOOST_AMOUNT + station_bonus * STATION_BOOST_PER_LEVEL;
+                    mech.shield = (mech.shield + boost).min(mech.max_shield);
                     let _ = tx.send((
                         Uuid::nil(),
-                        ServerMessage::MechDamaged {
-                            mech_id: target_id,
-                            damage,
-                            health_remaining: new_health,
+                        ServerMessage::MechShieldChanged {
+                            mech_id,
+                            shield: mech.shield,
                         },
                     ));
                 }
             }
         }
-        StationType::WeaponProjectile => {
+        StationType::TurretControl => {
             if button_index == 0 {
-                // Fire projectile
-                let (our_team, our_pos, projectile_level) = match game.mechs.get(&mech_id) {
-                    Some(mech) => (mech.team, mech.position, mech.upgrades.projectile_level),
-                    None => {
-                        log::error!("Mech {mech_id} not found when firing projectile");
-                        return;
+                // Fires where the operator aimed, with the tighter manual spread
+                if let Some(fired) = game.fire_turret(mech_id) {
+                    let _ = tx.send((Uuid::nil(), fired));
+                }
+            }
+        }
+        StationType::Radar => {
+            if button_index == 0 {
+                match game.radar_sweep(mech_id) {
+                    Ok(messages) => {
+                        for message in messages {
+                            let _ = tx.send((Uuid::nil(), message));
+                        }
                     }
-                };
-
-                let target = game
-                    .mechs
-                    .values()
-                    .filter(|m| m.team != our_team)
-                    .min_by(|a, b| {
-                        let dist_a = a.position.distance_to(our_pos);
-                        let dist_b = b.position.distance_to(our_pos);
-                        dist_a
-                            .partial_cmp(&dist_b)
-                            .unwrap_or(std::cmp::Ordering::Equal)
-                    });
-
-                if let Some(target) = target {
-                    let target_pos = target.position;
-
-                    // Calculate projectile trajectory
-                    let start_pos = our_pos.to_world_pos();
-                    let target_world = target_pos.to_world_pos();
-                    let dx = target_world.x - start_pos.x;
-                    let dy = target_world.y - start_pos.y;
-                    let dist = (dx * dx + dy * dy).sqrt();
-                    let velocity = if dist > 0.0 {
-                        (
-                            dx / dist * PROJECTILE_BASE_SPEED,
-                            dy / dist * PROJECTILE_BASE_SPEED,
-                        )
-                    } else {
-                        (0.0, 0.0)
-                    };
-
-                    let damage = PROJECTILE_BASE_DAMAGE
-                        + (PROJECTILE_DAMAGE_PER_LEVEL * (projectile_level as u32 - 1));
-
-                    // Use the new pooled projectile system
-                    let actual_projectile_id = game.create_projectile(
-                        start_pos,
-                        velocity,
-                        damage,
-                        mech_id,
-                        PROJECTILE_LIFETIME,
-                    );
-
+                    Err(e) => report_error(tx, player_id, e),
+                }
+            }
+        }
+        StationType::PointDefense => match button_index {
+            0 => match game.fire_point_defense(mech_id) {
+                Ok(messages) => {
+                    for message in messages {
+                        let _ = tx.send((Uuid::nil(), message));
+                    }
+                }
+                Err(e) => report_error(tx, player_id, e),
+            },
+            1 => {
+                if let Some(mech) = game.mechs.get_mut(&mech_id) {
+                    mech.point_defense.auto = !mech.point_defense.auto;
                     let _ = tx.send((
                         Uuid::nil(),
-                        ServerMessage::WeaponFired {
+                        ServerMessage::PointDefenseChanged {
                             mech_id,
-                            weapon_type: StationType::WeaponProjectile,
-                            target_position: target_pos,
-                            projectile_id: Some(actual_projectile_id),
+                            point_defense: mech.point_defense,
                         },
                     ));
                 }
             }
-        }
-        StationType::Shield => {
-            if button_index == 0 {
-                // Activate shield boost
+            2 => {
+                // Priced in the station definition
+                let full = game
+                    .mechs
+                    .get(&mech_id)
+                    .is_none_or(|mech| mech.point_defense.ammo >= POINT_DEFENSE_MAGAZINE);
+                let cost = game
+                    .station_registry
+                    .get_definition(StationType::PointDefense)
+                    .and_then(|definition| {
+                        definition
+                            .button_definitions
+                            .iter()
+                            .find(|button| button.index == button_index)
+                    })
+                    .map(|button| button.resource_cost.clone())
+                    .unwrap_or_default();
+                if full || !spend_resources(game, mech_id, &cost, tx) {
+                    return;
+                }
                 if let Some(mech) = game.mechs.get_mut(&mech_id) {
-                    mech.shield = (mech.shield + SHIELD_BOOST_AMOUNT).min(mech.max_shield);
+                    mech.point_defense.restock();
                     let _ = tx.send((
                         Uuid::nil(),
-                        ServerMessage::MechShieldChanged {
+                        ServerMessage::PointDefenseChanged {
                             mech_id,
-                            shield: mech.shield,
+                            point_defense: mech.point_defense,
                         },
                     ));
                 }
             }
+            _ => {}
+        },
+        StationType::Pilot => {
+            if button_index == 0 {
+                let pilot = game.mechs.get(&mech_id).and_then(|mech| {
+                    mech.stations
+                        .values()
+                        .find(|s| s.station_type == StationType::Pilot)
+                        .and_then(|s| s.operated_by)
+                });
+                match game.stomp(mech_id, pilot) {
+                    Ok(messages) => {
+                        for message in messages {
+                            let _ = tx.send((Uuid::nil(), message));
+                        }
+                    }
+                    Err(e) => report_error(tx, player_id, e),
+                }
+            } else if button_index == 1 {
+                match game.brace(mech_id) {
+                    Ok(messages) => {
+                        for message in messages {
+                            let _ = tx.send((Uuid::nil(), message));
+                        }
+                    }
+                    Err(e) => report_error(tx, player_id, e),
+                }
+            }
         }
         StationType::Engine => {
             // Engine station now uses WASD controls via EngineControl messages
             // Station buttons are not used for movement anymore
         }
         StationType::Upgrade => {
-            // Upgrade station - use resources to upgrade mech systems
-            match button_index {
-                0 => {
-                    // Upgrade laser (costs 2 scrap metal + 1 computer component)
-                    if check_and_consume_resources(
-                        game,
-                        mech_id,
-                        upgrade_costs::LASER_UPGRADE.to_vec(),
-                    ) {
-                        if let Some(mech) = game.mechs.get_mut(&mech_id) {
-                            mech.upgrades.laser_level =
-                                (mech.upgrades.laser_level + 1).min(MAX_UPGRADE_LEVEL);
-                            let _ = tx.send((
-                                Uuid::nil(),
-                                ServerMessage::MechUpgraded {
-                                    mech_id,
-                                    upgrade_type: UpgradeType::Laser,
-                                    new_level: mech.upgrades.laser_level,
-                                },
-                            ));
-                        }
-                    }
+            // Each button's upgrade and price come from the station definition
+            let Some(button) = game
+                .station_registry
+                .get_definition(StationType::Upgrade)
+                .and_then(|definition| {
+                    definition
+                        .button_definitions
+                        .iter()
+                        .find(|button| button.index == button_index)
+                })
+            else {
+                return;
+            };
+            let (upgrade_type, cost) = match button.action {
+                StationAction::UpgradeMech { upgrade_type } => {
+                    (upgrade_type, button.resource_cost.clone())
                 }
-                1 => {
-                    // Upgrade projectile (costs 3 scrap metal)
-                    if check_and_consume_resources(
-                        game,
-                        mech_id,
-                        upgrade_costs::PROJECTILE_UPGRADE.to_vec(),
-                    ) {
-                        if let Some(mech) = game.mechs.get_mut(&mech_id) {
-                            mech.upgrades.projectile_level =
-                                (mech.upgrades.projectile_level + 1).min(MAX_UPGRADE_LEVEL);
-                            let _ = tx.send((
-                                Uuid::nil(),
-                                ServerMessage::MechUpgraded {
-                                    mech_id,
-                                    upgrade_type: UpgradeType::Projectile,
-                                    new_level: mech.upgrades.projectile_level,
-                                },
-                            ));
-                        }
+                StationAction::None => {
+                    // Step to the next station to upgrade
+                    if let Err(e) = station_upgrades::pick_next(game, mech_id) {
+                        report_error(tx, player_id, e);
                     }
+                    send_station_layout(game, player_id, mech_id, tx);
+                    return;
                 }
-                2 => {
-                    // Upgrade shields (costs 2 batteries + 1 wiring)
-                    if check_and_consume_resources(
-                        game,
-                        mech_id,
-                        upgrade_costs::SHIELD_UPGRADE.to_vec(),
-                    ) {
-                        if let Some(mech) = game.mechs.get_mut(&mech_id) {
-                            mech.upgrades.shield_level =
-                                (mech.upgrades.shield_level + 1).min(MAX_UPGRADE_LEVEL);
-                            mech.max_shield = MECH_MAX_SHIELD
-                                + (mech.upgrades.shield_level as u32 - 1) * SHIELD_PER_LEVEL;
-                            let _ = tx.send((
-                                Uuid::nil(),
-                                ServerMessage::MechUpgraded {
-                                    mech_id,
-                                    upgrade_type: UpgradeType::Shield,
-                                    new_level: mech.upgrades.shield_level,
-                                },
-                            ));
+                StationAction::UpgradeStation => {
+                    let Some(terminal_id) = game
+                        .players
+                        .get(&player_id)
+                        .and_then(|player| player.operating_station)
+                    else {
+                        return;
+                    };
+                    match station_effects::press(game, mech_id, terminal_id, button_index) {
+                        Ok(messages) => {
+                            for message in messages {
+                                let _ = tx.send((Uuid::nil(), message));
+                            }
                         }
+                        Err(e) => report_error(tx, player_id, e),
                     }
+                    send_station_layout(game, player_id, mech_id, tx);
+                    return;
+                }
+                _ => return,
+            };
+            // The registry turns away upgrades mechs don't keep
+            let Some(upgrade_type) = upgrade_type.upgrade_type() else {
+                return;
+            };
+
+            // Don't take resources for a system that's already maxed out
+            let Some(level) = game
+                .mechs
+                .get_mut(&mech_id)
+                .map(|mech| *mech.upgrades.level_mut(upgrade_type))
+                .filter(|level| *level < MAX_UPGRADE_LEVEL)
+            else {
+                return;
+            };
+            let cost = with_top_tier_cost(cost, level + 1, MAX_UPGRADE_LEVEL);
+            if !spend_resources(game, mech_id, &cost, tx) {
+                return;
+            }
+
+            if let Some(mech) = game.mechs.get_mut(&mech_id) {
+                let level = mech.upgrades.level_mut(upgrade_type);
+                *level += 1;
+                let new_level = *level;
+                if upgrade_type == UpgradeType::Shield {
+                    mech.max_shield = MECH_MAX_SHIELD + (new_level as u32 - 1) * SHIELD_PER_LEVEL;
                 }
-                3 => {
-                    // Upgrade engine (costs 2 computer components + 2 wiring)
-                    if check_and_consume_resources(
-                        game,
+                let _ = tx.send((
+                    Uuid::nil(),
+                    ServerMessage::MechUpgraded {
                         mech_id,
-                        upgrade_costs::ENGINE_UPGRADE.to_vec(),
-                    ) {
-                        if let Some(mech) = game.mechs.get_mut(&mech_id) {
-                            mech.upgrades.engine_level =
-                                (mech.upgrades.engine_level + 1).min(MAX_UPGRADE_LEVEL);
-                            let _ = tx.send((
-                                Uuid::nil(),
-                                ServerMessage::MechUpgraded {
-                                    mech_id,
-                                    upgrade_type: UpgradeType::Engine,
-                                    new_level: mech.upgrades.engine_level,
-                                },
-                            ));
-                        }
+                        upgrade_type,
+                        new_level,
+                    },
+                ));
+            }
+        }
+        StationType::Electrical if button_index == 2 => {
+            // Priced in the station definition
+            let cost = game
+                .station_registry
+                .get_definition(StationType::Electrical)
+                .and_then(|definition| {
+                    definition
+                        .button_definitions
+                        .iter()
+                        .find(|button| button.index == button_index)
+                })
+                .map(|button| button.resource_cost.clone())
+                .unwrap_or_default();
+            match scanner::issue(game, player_id, mech_id, &cost) {
+                Ok(messages) => {
+                    for message in messages {
+                        let _ = tx.send((Uuid::nil(), message));
                     }
                 }
-                _ => {}
+                Err(e) => report_error(tx, player_id, e),
             }
         }
         StationType::Repair => {
@@ -555,17 +984,20 @@ pub async fn handle_station_button(
                 // Repair mech (costs 1 scrap metal per 20 HP)
                 if let Some(mech) = game.mechs.get(&mech_id) {
                     let damage = mech.max_health.saturating_sub(mech.health);
-                    let scrap_needed = damage.div_ceil(REPAIR_HP_PER_SCRAP); // Round up
+                    let hp_per_scrap =
+                        REPAIR_HP_PER_SCRAP + station_bonus * STATION_BOOST_PER_LEVEL;
+                    let scrap_needed = damage.div_ceil(hp_per_scrap); // Round up
 
                     if scrap_needed > 0
-                        && check_and_consume_resources(
+                        && spend_resources(
                             game,
                             mech_id,
-                            vec![(ResourceType::ScrapMetal, scrap_needed as usize)],
+                            &HashMap::from([(ResourceType::ScrapMetal, scrap_needed)]),
+                            tx,
                         )
                     {
                         if let Some(mech) = game.mechs.get_mut(&mech_id) {
-                            let healed = scrap_needed * REPAIR_HP_PER_SCRAP;
+                            let healed = scrap_needed * hp_per_scrap;
                             mech.health = (mech.health + healed).min(mech.max_health);
                             let _ = tx.send((
                                 Uuid::nil(),
@@ -581,12 +1013,122 @@ pub async fn handle_station_button(
             }
         }
         _ => {
-            // Other stations not yet implemented
+            // Everything else does what its definition says
+            let Some(station_id) = game
+                .players
+                .get(&player_id)
+                .and_then(|player| player.operating_station)
+            else {
+                return;
+            };
+            match station_effects::press(game, mech_id, station_id, button_index) {
+                Ok(messages) => {
+                    for message in messages {
+                        let _ = tx.send((Uuid::nil(), message));
+                    }
+                }
+                Err(e) => report_error(tx, player_id, e),
+            }
         }
     }
 }
 
+/// Launch a projectile from our mech at the nearest enemy mech
+fn fire_at_nearest_enemy(
+    game: &mut Game,
+    mech_id: Uuid,
+    station_type: StationType,
+    weapon_type: WeaponType,
+    base_damage: u32,
+    speed: f32,
+    tx: &broadcast::Sender<(Uuid, ServerMessage)>,
+) {
+    let (our_team, our_pos) = match game.mechs.get(&mech_id) {
+        Some(mech) => (mech.team, mech.position),
+        None => {
+            log::error!("Mech {mech_id} not found when firing {weapon_type:?}");
+            return;
+        }
+    };
+
+    let target = game
+        .mechs
+        .values()
+        .filter(|m| m.team != our_team)
+        .min_by(|a, b| {
+            let dist_a = a.position.distance_to(our_pos);
+            let dist_b = b.position.distance_to(our_pos);
+            dist_a
+                .partial_cmp(&dist_b)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+    let Some(target) = target else {
+        return;
+    };
+    let target_pos = target.position;
+
+    // Calculate projectile trajectory
+    let start_pos = our_pos.to_world_pos();
+    let target_world = target_pos.to_world_pos();
+    let dx = target_world.x - start_pos.x;
+    let dy = target_world.y - start_pos.y;
+    let dist = (dx * dx + dy * dy).sqrt();
+    let velocity = if dist > 0.0 {
+        (dx / dist * speed, dy / dist * speed)
+    } else {
+        (0.0, 0.0)
+    };
+
+    let damage = (base_damage as f32
+        * damage_multiplier_at(&game.pacing_zones, MechPositioning::mech_center(our_pos))
+        * game.damage_multiplier(mech_id))
+    .round() as u32;
+
+    // Use the new pooled projectile system
+    let actual_projectile_id = game.create_projectile(
+        start_pos,
+        velocity,
+        damage,
+        mech_id,
+        PROJECTILE_LIFETIME,
+        weapon_type,
+    );
+
+    let _ = tx.send((
+        Uuid::nil(),
+        ServerMessage::WeaponFired {
+            mech_id,
+            weapon_type: station_type,
+            target_position: target_pos,
+            projectile_id: Some(actual_projectile_id),
+        },
+    ));
+}
+
 pub async fn handle_engine_control(game: &mut Game, player_id: Uuid, movement: (f32, f32)) {
+    // Crew at an engine or pilot station drive through the throttle and steering
+    let player_station = game
+        .players
+        .get(&player_id)
+        .and_then(|p| p.operating_station);
+    let driven_mech = player_station.and_then(|station_id| {
+        game.mechs
+            .values()
+            .find(|m| {
+                m.stations.get(&station_id).is_some_and(|s| {
+                    matches!(s.station_type, StationType::Engine | StationType::Pilot)
+                })
+            })
+            .map(|m| m.id)
+    });
+    if let Some(mech_id) = driven_mech {
+        if let Some(mech) = game.mechs.get_mut(&mech_id) {
+            mech.update_drive(|drive| drive.apply_input(movement, CONTINUOUS_MOVEMENT_DELTA));
+        }
+        return;
+    }
+
     // Debug mode: Allow direct mech control for debug builds
     #[cfg(debug_assertions)]
     {
@@ -608,81 +1150,44 @@ pub async fn handle_engine_control(game: &mut Game, player_id: Uuid, movement: (
                 
                 // Apply debug speed to normalized velocity
                 mech.velocity = (vx * debug_speed, vy * debug_speed);
-                return; // Exit early for debug mode
             }
         }
     }
+}
 
-    // Normal mode: Check if player is operating an engine station
-    let player_station = game
+/// Resend the controls of the station a player is crewing, after something
+/// on them has changed
+fn send_station_layout(
+    game: &Game,
+    player_id: Uuid,
+    mech_id: Uuid,
+    tx: &broadcast::Sender<(Uuid, ServerMessage)>,
+) {
+    if let Some(layout) = game
         .players
         .get(&player_id)
-        .and_then(|p| p.operating_station);
-
-    if let Some(station_id) = player_station {
-        // Find which mech contains this station
-        let mech_to_control = game
-            .mechs
-            .values()
-            .find(|m| m.stations.contains_key(&station_id))
-            .map(|m| m.id);
-
-        if let Some(mech_id) = mech_to_control {
-            // Verify it's an engine station
-            if let Some(mech) = game.mechs.get(&mech_id) {
-                if let Some(station) = mech.stations.get(&station_id) {
-                    if station.station_type == StationType::Engine {
-                        // Update the specific mech's velocity based on WASD input
-                        if let Some(mech) = game.mechs.get_mut(&mech_id) {
-                            let base_speed = MECH_BASE_SPEED
-                                + (mech.upgrades.engine_level as f32 - 1.0) * MECH_SPEED_PER_LEVEL;
-
-                            // Normalize diagonal movement
-                            let (mut vx, mut vy) = movement;
-                            let magnitude = (vx * vx + vy * vy).sqrt();
-                            if magnitude > 1.0 {
-                                vx /= magnitude;
-                                vy /= magnitude;
-                            }
-
-                            // Apply speed to normalized velocity
-                            mech.velocity = (vx * base_speed, vy * base_speed);
-                        }
-                    }
-                }
-            }
-        }
+        .and_then(|player| player.operating_station)
+        .and_then(|station_id| game.station_layout(mech_id, station_id))
+    {
+        let _ = tx.send((player_id, layout));
     }
 }
 
-fn check_and_consume_resources(
+/// Spend a cost from a mech's inventory, telling clients the new totals
+fn spend_resources(
     game: &mut Game,
     mech_id: Uuid,
-    required: Vec<(ResourceType, usize)>,
+    cost: &HashMap<ResourceType, u32>,
+    tx: &broadcast::Sender<(Uuid, ServerMessage)>,
 ) -> bool {
-    // Check if mech has all required resources in its inventory
-    if let Some(mech) = game.mechs.get(&mech_id) {
-        // Check if we have enough of each type
-        for (resource_type, needed) in &required {
-            let available = mech.resource_inventory.get(resource_type).unwrap_or(&0);
-            if available < &(*needed as u32) {
-                return false;
-            }
-        }
-
-        // We have enough - consume the resources from mech inventory
-        if let Some(mech) = game.mechs.get_mut(&mech_id) {
-            for (resource_type, needed) in required {
-                if let Some(count) = mech.resource_inventory.get_mut(&resource_type) {
-                    *count -= needed as u32;
-                }
-            }
-        }
-
-        true
-    } else {
-        false
+    let Some(mech) = game.mechs.get_mut(&mech_id) else {
+        return false;
+    };
+    if !mech.try_spend(cost) {
+        return false;
     }
+    let _ = tx.send((Uuid::nil(), mech.inventory_message()));
+    true
 }
 
 /// Check if a position is safe for a player to exit to
@@ -692,11 +1197,7 @@ fn is_position_safe(game: &Game, pos: shared::WorldPos) -> bool {
 
     // Check if position is within world bounds
     let tile_pos = pos.to_tile_pos();
-    if tile_pos.x < 0
-        || tile_pos.x >= ARENA_WIDTH_TILES
-        || tile_pos.y < 0
-        || tile_pos.y >= ARENA_HEIGHT_TILES
-    {
+    if !game.arena_map.in_bounds(tile_pos) {
         return false;
     }
 
@@ -723,3 +1224,36 @@ fn is_position_safe(game: &Game, pos: shared::WorldPos) -> bool {
 
     true
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_clients_that_lag_behind_the_broadcast_are_resynced() {
+        let player_id = Uuid::new_v4();
+        let game = Arc::new(RwLock::new(Game::new()));
+        let queue = Arc::new(Mutex::new(ClientSendQueue::new(CLIENT_SEND_QUEUE_CAPACITY)));
+        let (tx, rx) = broadcast::channel(2);
+        for _ in 0..5 {
+            tx.send((
+                Uuid::nil(),
+                ServerMessage::EffectExpired {
+                    effect_id: Uuid::new_v4(),
+                },
+            ))
+            .unwrap();
+        }
+        drop(tx);
+
+        pump_broadcasts(player_id, rx, queue.clone(), Arc::new(Notify::new()), game).await;
+        let mut queue = queue.lock().unwrap();
+        assert_eq!(queue.stats().lagged, 3);
+        // The snapshot comes first, then what was still in the broadcast
+        assert!(matches!(queue.pop(), Some(ServerMessage::GameState { .. })));
+        assert!(matches!(
+            queue.pop(),
+            Some(ServerMessage::EffectExpired { .. })
+        ));
+    }
+}
@@ -0,0 +1,89 @@
+//! Collision shapes for clients that asked for verbose state. Keeps who
+//! asked and a short trail behind every projectile in flight, recorded only
+//! while somebody is watching.
+
+use shared::*;
+use std::collections::{HashMap, HashSet, VecDeque};
+use uuid::Uuid;
+
+#[derive(Default)]
+pub struct CollisionDebug {
+    /// Connections that want the shapes
+    subscribers: HashSet<Uuid>,
+    /// Recent positions of each projectile, oldest first
+    trails: HashMap<Uuid, VecDeque<WorldPos>>,
+}
+
+impl CollisionDebug {
+    pub fn set_verbose(&mut self, connection_id: Uuid, enabled: bool) {
+        if enabled {
+            self.subscribers.insert(connection_id);
+        } else {
+            self.subscribers.remove(&connection_id);
+        }
+        if self.subscribers.is_empty() {
+            self.trails.clear();
+        }
+    }
+
+    pub fn forget(&mut self, connection_id: Uuid) {
+        self.set_verbose(connection_id, false);
+    }
+
+    pub fn is_watched(&self) -> bool {
+        !self.subscribers.is_empty()
+    }
+
+    pub fn subscribers(&self) -> impl Iterator<Item = Uuid> + '_ {
+        self.subscribers.iter().copied()
+    }
+
+    /// Note where every projectile is now, dropping trails of any that are gone
+    pub fn record_projectiles(&mut self, positions: impl IntoIterator<Item = (Uuid, WorldPos)>) {
+        let mut seen = HashSet::new();
+        for (id, position) in positions {
+            let trail = self.trails.entry(id).or_default();
+            trail.push_back(position);
+            if trail.len() > PROJECTILE_TRAIL_SAMPLES {
+                trail.pop_front();
+            }
+            seen.insert(id);
+        }
+        self.trails.retain(|id, _| seen.contains(id));
+    }
+
+    pub fn trail(&self, projectile_id: Uuid) -> Vec<WorldPos> {
+        self.trails
+            .get(&projectile_id)
+            .map(|trail| trail.iter().copied().collect())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trails_are_capped_and_kept_only_while_watched() {
+        let mut debug = CollisionDebug::default();
+        let (viewer, shot) = (Uuid::new_v4(), Uuid::new_v4());
+        debug.set_verbose(viewer, true);
+
+        for step in 0..PROJECTILE_TRAIL_SAMPLES + 5 {
+            debug.record_projectiles([(shot, WorldPos::new(step as f32, 0.0))]);
+        }
+        let trail = debug.trail(shot);
+        assert_eq!(trail.len(), PROJECTILE_TRAIL_SAMPLES);
+        assert_eq!(trail[0].x, 5.0);
+
+        // A projectile that's gone takes its trail with it
+        debug.record_projectiles([]);
+        assert!(debug.trail(shot).is_empty());
+
+        debug.record_projectiles([(shot, WorldPos::new(0.0, 0.0))]);
+        debug.forget(viewer);
+        assert!(!debug.is_watched());
+        assert!(debug.trail(shot).is_empty());
+    }
+}
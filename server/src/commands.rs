@@ -1,3 +1,4 @@
+use crate::events::GameEvent;
 use crate::game::Game;
 use crate::systems::PhysicsAction;
 use async_trait::async_trait;
@@ -20,6 +21,9 @@ pub trait Command: Send + Sync {
 pub struct JoinGameCommand {
     pub player_name: String,
     pub preferred_team: Option<TeamId>,
+    pub protocol_version: u32,
+    pub features: Vec<ProtocolFeature>,
+    pub password: Option<String>,
 }
 
 #[async_trait]
@@ -30,36 +34,102 @@ impl Command for JoinGameCommand {
         player_id: Uuid,
         tx: &broadcast::Sender<(Uuid, ServerMessage)>,
     ) -> GameResult<()> {
+        // Turn away clients we can't talk to, with a reason they can show
+        if let Some(reason) = version_mismatch(self.protocol_version) {
+            let _ = tx.send((
+                player_id,
+                ServerMessage::JoinRejected {
+                    reason,
+                    server_version: PROTOCOL_VERSION,
+                },
+            ));
+            return Err(GameError::IncompatibleProtocol {
+                client: self.protocol_version,
+                server: PROTOCOL_VERSION,
+            });
+        }
+
         // Sanitize player name
         let sanitized_name = sanitize_player_name(&self.player_name);
 
-        let (team, spawn_pos) = {
+        let features = negotiate_features(&self.features);
+        let (team, spawn_pos, sight, voice_key, resume_token, autofill_messages) = {
             let mut game = game.write().await;
-            game.add_player(player_id, sanitized_name.clone(), self.preferred_team)
+            if !game.accepting_players {
+                return Err(GameError::ServerShuttingDown);
+            }
+            if !game.access.admits(self.password.as_deref()) {
+                let wrong = self.password.is_some();
+                let _ = tx.send((player_id, ServerMessage::PasswordRequired { wrong }));
+                return Err(GameError::WrongPassword);
+            }
+            if let Some(max) = game.access.max_players {
+                let ai_players = game.get_ai_players().len();
+                let players = game.players.len() - ai_players;
+                if players >= max && !game.players.contains_key(&player_id) {
+                    let _ = tx.send((
+                        player_id,
+                        ServerMessage::JoinRejected {
+                            reason: format!("This room is full ({max} players)"),
+                            server_version: PROTOCOL_VERSION,
+                        },
+                    ));
+                    return Err(GameError::RoomFull { max });
+                }
+            }
+            game.spectators.remove(&player_id);
+            // Players coming back on a resume token pick up where they were
+            let resumed = game.players.get(&player_id).map(|player| {
+                let mech_pos = match player.location {
+                    PlayerLocation::InsideMech { mech_id, .. } => {
+                        game.mechs.get(&mech_id).map(|mech| mech.world_position)
+                    }
+                    PlayerLocation::OutsideWorld(_) => None,
+                };
+                (player.team, player.location.world_pos(mech_pos))
+            });
+            let (team, spawn_pos) = resumed.unwrap_or_else(|| {
+                game.add_player(player_id, sanitized_name.clone(), self.preferred_team)
+            });
+            let voice_key = features
+                .contains(&ProtocolFeature::Voice)
+                .then(|| game.voice.lock().unwrap().issue_key(player_id, team));
+            (
+                team,
+                spawn_pos,
+                game.sight,
+                voice_key,
+                game.sessions.issue(player_id),
+                game.balance_ai_crew(),
+            )
         };
+        for msg in autofill_messages {
+            let _ = tx.send((Uuid::nil(), msg));
+        }
 
         // Send join confirmation
         let join_msg = ServerMessage::JoinedGame {
             player_id,
             team,
             spawn_position: spawn_pos.to_tile(),
+            features,
+            sight,
         };
         let _ = tx.send((player_id, join_msg));
+        if let Some(key) = voice_key {
+            let _ = tx.send((player_id, ServerMessage::VoiceChannel { key }));
+        }
+        let _ = tx.send((
+            player_id,
+            ServerMessage::SessionResume {
+                token: resume_token,
+            },
+        ));
 
-        // Send full game state
-        let state_msg = {
-            let game = game.read().await;
-            game.get_full_state()
-        };
-        let _ = tx.send((player_id, state_msg));
-        
-        // Send mech floor data immediately when player joins
-        let floor_messages = {
-            let game = game.read().await;
-            game.get_mech_floor_data()
-        };
-        for floor_msg in floor_messages {
-            let _ = tx.send((player_id, floor_msg));
+        // Send the arena layout, full game state and mech floors
+        let snapshot = game.read().await.world_snapshot();
+        for msg in snapshot {
+            let _ = tx.send((player_id, msg));
         }
 
         log::info!("Player {player_id} joined as {sanitized_name} on team {team:?}");
@@ -67,6 +137,62 @@ impl Command for JoinGameCommand {
     }
 }
 
+/// Watch the match without a player
+pub struct SpectateCommand {
+    pub protocol_version: u32,
+    pub features: Vec<ProtocolFeature>,
+}
+
+#[async_trait]
+impl Command for SpectateCommand {
+    async fn execute(
+        &self,
+        game: &tokio::sync::RwLock<Game>,
+        player_id: Uuid,
+        tx: &broadcast::Sender<(Uuid, ServerMessage)>,
+    ) -> GameResult<()> {
+        if let Some(reason) = version_mismatch(self.protocol_version) {
+            let _ = tx.send((
+                player_id,
+                ServerMessage::JoinRejected {
+                    reason,
+                    server_version: PROTOCOL_VERSION,
+                },
+            ));
+            return Err(GameError::IncompatibleProtocol {
+                client: self.protocol_version,
+                server: PROTOCOL_VERSION,
+            });
+        }
+
+        let mut game = game.write().await;
+        if !game.accepting_players {
+            return Err(GameError::ServerShuttingDown);
+        }
+        if game.players.contains_key(&player_id) {
+            return Err(GameError::invalid_state("Players can't spectate"));
+        }
+        game.spectators.insert(player_id);
+
+        let _ = tx.send((
+            player_id,
+            ServerMessage::SpectatingGame {
+                features: negotiate_features(&self.features),
+            },
+        ));
+        for msg in game.world_snapshot() {
+            let _ = tx.send((player_id, msg));
+        }
+        // Start them on whatever the director is already showing
+        if let Some(suggestion) = game.camera_suggestion() {
+            let _ = tx.send((player_id, suggestion));
+        }
+
+        log::info!("{player_id} is spectating");
+        Ok(())
+    }
+}
+
 /// Player input command
 pub struct PlayerInputCommand {
     pub movement: (f32, f32),
@@ -144,14 +270,34 @@ impl Command for StationInputCommand {
         };
 
         if let Some((mech_id, station_type)) = station_info {
+            game.check_can_operate(player_id, mech_id)?;
+
+            // Buttons are defined per station type; ignore keys past the last one
+            let button_count = game
+                .station_registry
+                .get_definition(station_type)
+                .map_or(0, |definition| definition.button_count);
+            if self.button_index >= button_count {
+                return Err(GameError::invalid_input(format!(
+                    "Button index {} out of range for station type {station_type:?}",
+                    self.button_index
+                )));
+            }
+
             super::client::handle_station_button(
                 &mut game,
+                player_id,
                 mech_id,
                 station_type,
                 self.button_index,
                 tx,
             )
             .await;
+            game.events.publish(GameEvent::StationButtonPressed {
+                player_id,
+                station_id,
+                button_index: self.button_index,
+            });
         } else {
             return Err(GameError::station_not_found(station_id));
         }
@@ -174,6 +320,25 @@ impl Command for EngineControlCommand {
         _tx: &broadcast::Sender<(Uuid, ServerMessage)>,
     ) -> GameResult<()> {
         let mut game = game.write().await;
+
+        // Stunned pilots and EMP'd mechs can't drive
+        let player = game
+            .players
+            .get(&player_id)
+            .ok_or_else(|| GameError::player_not_found(player_id))?;
+        let controlled_mech = player
+            .operating_station
+            .and_then(|station_id| {
+                game.mechs
+                    .values()
+                    .find(|m| m.stations.contains_key(&station_id))
+            })
+            .or_else(|| game.mechs.values().find(|m| m.team == player.team))
+            .map(|m| m.id);
+        if let Some(mech_id) = controlled_mech {
+            game.check_can_operate(player_id, mech_id)?;
+        }
+
         super::client::handle_engine_control(&mut game, player_id, self.movement).await;
         Ok(())
     }
@@ -245,6 +410,474 @@ impl Command for ChatMessageCommand {
     }
 }
 
+/// Draw a coaching mark for a team or everyone
+pub struct AnnotateCommand {
+    pub shape: AnnotationShape,
+    pub color: AnnotationColor,
+    pub audience: AnnotationAudience,
+    pub ttl: f32,
+}
+
+#[async_trait]
+impl Command for AnnotateCommand {
+    async fn execute(
+        &self,
+        game: &tokio::sync::RwLock<Game>,
+        player_id: Uuid,
+        tx: &broadcast::Sender<(Uuid, ServerMessage)>,
+    ) -> GameResult<()> {
+        let mut game = game.write().await;
+        match game.players.get(&player_id) {
+            Some(player) => {
+                if let AnnotationAudience::Team(team) = self.audience {
+                    if team != player.team {
+                        return Err(GameError::InvalidTeam);
+                    }
+                }
+            }
+            None if game.spectators.contains(&player_id) => {}
+            None => return Err(GameError::player_not_found(player_id)),
+        }
+        if let Err(remaining_seconds) = game
+            .annotation_limiter
+            .allow(player_id, std::time::Instant::now())
+        {
+            return Err(GameError::AbilityOnCooldown {
+                ability: "Drawing",
+                remaining_seconds,
+            });
+        }
+
+        let message = ServerMessage::AnnotationDrawn {
+            annotation: Annotation {
+                id: game.rng.uuid(),
+                author: player_id,
+                shape: self.shape,
+                color: self.color,
+                ttl: self.ttl,
+            },
+        };
+        match crate::annotations::recipients(&game, self.audience) {
+            Some(recipients) => {
+                for recipient in recipients {
+                    let _ = tx.send((recipient, message.clone()));
+                }
+            }
+            None => {
+                let _ = tx.send((Uuid::nil(), message));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Developer console command, only honoured when the server runs with --dev-mode
+pub struct DevCommandCommand {
+    pub command: DevCommand,
+}
+
+#[async_trait]
+impl Command for DevCommandCommand {
+    async fn execute(
+        &self,
+        game: &tokio::sync::RwLock<Game>,
+        player_id: Uuid,
+        tx: &broadcast::Sender<(Uuid, ServerMessage)>,
+    ) -> GameResult<()> {
+        let mut game = game.write().await;
+        let reply = |success: bool, message: String| {
+            let _ = tx.send((player_id, ServerMessage::DevCommandResult { success, message }));
+        };
+
+        if !game.dev_mode {
+            reply(false, "Server is not in dev mode".to_string());
+            return Ok(());
+        }
+
+        let team = game
+            .players
+            .get(&player_id)
+            .map(|p| p.team)
+            .ok_or_else(|| GameError::player_not_found(player_id))?;
+
+        match self.command {
+            DevCommand::Teleport { x, y } => {
+                super::client::handle_exit_station(&mut game, player_id, tx).await;
+                let location = PlayerLocation::OutsideWorld(TilePos::new(x, y).to_world_center());
+                if let Some(player) = game.players.get_mut(&player_id) {
+                    player.location = location;
+                }
+                let _ = tx.send((
+                    Uuid::nil(),
+                    ServerMessage::PlayerMoved {
+                        player_id,
+                        location,
+                    },
+                ));
+                reply(true, format!("Teleported to ({x}, {y})"));
+            }
+            DevCommand::GiveResource {
+                resource_type,
+                amount,
+            } => {
+                let Some(mech) = game.mechs.values_mut().find(|m| m.team == team) else {
+                    reply(false, format!("{team:?} team has no mech"));
+                    return Ok(());
+                };
+                *mech.resource_inventory.entry(resource_type).or_insert(0) += amount;
+                let _ = tx.send((Uuid::nil(), mech.inventory_message()));
+                reply(true, format!("Gave {amount} {resource_type:?} to the {team:?} mech"));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Make another inventory slot the active one
+pub struct SelectInventorySlotCommand {
+    pub slot: u8,
+}
+
+#[async_trait]
+impl Command for SelectInventorySlotCommand {
+    async fn execute(
+        &self,
+        game: &tokio::sync::RwLock<Game>,
+        player_id: Uuid,
+        tx: &broadcast::Sender<(Uuid, ServerMessage)>,
+    ) -> GameResult<()> {
+        let mut game = game.write().await;
+        let player = game
+            .players
+            .get_mut(&player_id)
+            .ok_or_else(|| GameError::player_not_found(player_id))?;
+
+        if player.inventory.select(self.slot as usize) {
+            let _ = tx.send((Uuid::nil(), player.inventory_message()));
+        }
+        Ok(())
+    }
+}
+
+/// Drop the active item where the player stands. Anything else in reach is
+/// picked up straight away, which swaps items when the inventory was full.
+pub struct DropItemCommand;
+
+#[async_trait]
+impl Command for DropItemCommand {
+    async fn execute(
+        &self,
+        game: &tokio::sync::RwLock<Game>,
+        player_id: Uuid,
+        tx: &broadcast::Sender<(Uuid, ServerMessage)>,
+    ) -> GameResult<()> {
+        let mut game = game.write().await;
+        let player = game
+            .players
+            .get_mut(&player_id)
+            .ok_or_else(|| GameError::player_not_found(player_id))?;
+
+        let PlayerLocation::OutsideWorld(position) = player.location else {
+            return Err(GameError::InvalidPlayerLocation { id: player_id });
+        };
+        let slot = player.inventory.active_slot();
+        let item = player
+            .inventory
+            .take_active()
+            .ok_or(GameError::EmptyInventorySlot {
+                id: player_id,
+                slot,
+            })?;
+        let inventory_msg = player.inventory_message();
+
+        let _ = tx.send((Uuid::nil(), game.drop_item(player_id, item, position)));
+        let _ = tx.send((Uuid::nil(), inventory_msg));
+        for msg in game.pick_up_in_reach(player_id) {
+            let _ = tx.send((Uuid::nil(), msg));
+        }
+        Ok(())
+    }
+}
+
+/// Use whatever is in the active slot
+pub struct UseItemCommand;
+
+#[async_trait]
+impl Command for UseItemCommand {
+    async fn execute(
+        &self,
+        game: &tokio::sync::RwLock<Game>,
+        player_id: Uuid,
+        tx: &broadcast::Sender<(Uuid, ServerMessage)>,
+    ) -> GameResult<()> {
+        let mut game = game.write().await;
+        let player = game
+            .players
+            .get(&player_id)
+            .ok_or_else(|| GameError::player_not_found(player_id))?;
+        match player.inventory.active_item() {
+            Some(ItemType::Scanner) => {
+                // What the scanner found is for its holder's eyes only
+                let ping = crate::scanner::scan(&mut game, player_id)?;
+                let _ = tx.send((player_id, ping));
+                Ok(())
+            }
+            Some(item) => Err(GameError::ItemNotUsable { item: item.label() }),
+            None => Err(GameError::EmptyInventorySlot {
+                id: player_id,
+                slot: player.inventory.active_slot(),
+            }),
+        }
+    }
+}
+
+/// Close-quarters attack on the nearest enemy
+pub struct MeleeAttackCommand;
+
+#[async_trait]
+impl Command for MeleeAttackCommand {
+    async fn execute(
+        &self,
+        game: &tokio::sync::RwLock<Game>,
+        player_id: Uuid,
+        tx: &broadcast::Sender<(Uuid, ServerMessage)>,
+    ) -> GameResult<()> {
+        let mut game = game.write().await;
+        for msg in game.handle_melee_attack(player_id)? {
+            let _ = tx.send((Uuid::nil(), msg));
+        }
+        Ok(())
+    }
+}
+
+/// Quick burst of movement on a cooldown
+pub struct DashCommand {
+    pub direction: Facing,
+}
+
+#[async_trait]
+impl Command for DashCommand {
+    async fn execute(
+        &self,
+        game: &tokio::sync::RwLock<Game>,
+        player_id: Uuid,
+        tx: &broadcast::Sender<(Uuid, ServerMessage)>,
+    ) -> GameResult<()> {
+        let mut game = game.write().await;
+        for msg in game.dash(player_id, self.direction)? {
+            let _ = tx.send((Uuid::nil(), msg));
+        }
+        Ok(())
+    }
+}
+
+/// Build a barricade on a floor tile next to the player
+pub struct PlaceBarricadeCommand {
+    pub position: TilePos,
+}
+
+#[async_trait]
+impl Command for PlaceBarricadeCommand {
+    async fn execute(
+        &self,
+        game: &tokio::sync::RwLock<Game>,
+        player_id: Uuid,
+        tx: &broadcast::Sender<(Uuid, ServerMessage)>,
+    ) -> GameResult<()> {
+        let mut game = game.write().await;
+        for msg in game.place_barricade(player_id, self.position)? {
+            let _ = tx.send((Uuid::nil(), msg));
+        }
+        Ok(())
+    }
+}
+
+/// Take down one of our barricades next to the player
+pub struct RemoveBarricadeCommand {
+    pub position: TilePos,
+}
+
+#[async_trait]
+impl Command for RemoveBarricadeCommand {
+    async fn execute(
+        &self,
+        game: &tokio::sync::RwLock<Game>,
+        player_id: Uuid,
+        tx: &broadcast::Sender<(Uuid, ServerMessage)>,
+    ) -> GameResult<()> {
+        let mut game = game.write().await;
+        for msg in game.remove_barricade(player_id, self.position)? {
+            let _ = tx.send((Uuid::nil(), msg));
+        }
+        Ok(())
+    }
+}
+
+/// Turn the player to where their mouse points
+pub struct LookCommand {
+    pub facing: Facing,
+}
+
+#[async_trait]
+impl Command for LookCommand {
+    async fn execute(
+        &self,
+        game: &tokio::sync::RwLock<Game>,
+        player_id: Uuid,
+        tx: &broadcast::Sender<(Uuid, ServerMessage)>,
+    ) -> GameResult<()> {
+        let mut game = game.write().await;
+        let player = game
+            .players
+            .get_mut(&player_id)
+            .ok_or_else(|| GameError::player_not_found(player_id))?;
+        if let Some(msg) = player.face(self.facing) {
+            let _ = tx.send((Uuid::nil(), msg));
+        }
+        Ok(())
+    }
+}
+
+/// Start or stop sending this connection the collision shapes
+pub struct VerboseStateCommand {
+    pub enabled: bool,
+}
+
+#[async_trait]
+impl Command for VerboseStateCommand {
+    async fn execute(
+        &self,
+        game: &tokio::sync::RwLock<Game>,
+        player_id: Uuid,
+        _tx: &broadcast::Sender<(Uuid, ServerMessage)>,
+    ) -> GameResult<()> {
+        let mut game = game.write().await;
+        game.collision_debug.set_verbose(player_id, self.enabled);
+        log::debug!(
+            "Connection {player_id} turned collision shapes {}",
+            if self.enabled { "on" } else { "off" }
+        );
+        Ok(())
+    }
+}
+
+/// Pick the job we want once the match starts
+pub struct PickRoleCommand {
+    pub role: Option<CrewRole>,
+}
+
+#[async_trait]
+impl Command for PickRoleCommand {
+    async fn execute(
+        &self,
+        game: &tokio::sync::RwLock<Game>,
+        player_id: Uuid,
+        _tx: &broadcast::Sender<(Uuid, ServerMessage)>,
+    ) -> GameResult<()> {
+        let mut game = game.write().await;
+        crate::hangar::pick_role(&mut game, player_id, self.role)
+    }
+}
+
+/// Ready up in the hangar, or stand back down
+pub struct SetReadyCommand {
+    pub ready: bool,
+}
+
+#[async_trait]
+impl Command for SetReadyCommand {
+    async fn execute(
+        &self,
+        game: &tokio::sync::RwLock<Game>,
+        player_id: Uuid,
+        _tx: &broadcast::Sender<(Uuid, ServerMessage)>,
+    ) -> GameResult<()> {
+        let mut game = game.write().await;
+        crate::hangar::set_ready(&mut game, player_id, self.ready)
+    }
+}
+
+/// Narrow the updates a connection gets to some state channels. The
+/// connection's pump applies it once it sees the answer go by, so
+/// everything sent before then still arrives.
+pub struct SubscribeCommand {
+    pub channels: Vec<StateChannel>,
+}
+
+#[async_trait]
+impl Command for SubscribeCommand {
+    async fn execute(
+        &self,
+        _game: &tokio::sync::RwLock<Game>,
+        player_id: Uuid,
+        tx: &broadcast::Sender<(Uuid, ServerMessage)>,
+    ) -> GameResult<()> {
+        let subscription = Subscription::only(&self.channels);
+        log::debug!(
+            "Connection {player_id} subscribed to {:?}",
+            subscription.channels()
+        );
+        let _ = tx.send((
+            player_id,
+            ServerMessage::Subscribed {
+                channels: subscription.channels().to_vec(),
+            },
+        ));
+        Ok(())
+    }
+}
+
+/// Aim the turret of the mech whose turret station the player is crewing
+pub struct TurretAimCommand {
+    pub angle: f32,
+}
+
+#[async_trait]
+impl Command for TurretAimCommand {
+    async fn execute(
+        &self,
+        game: &tokio::sync::RwLock<Game>,
+        player_id: Uuid,
+        tx: &broadcast::Sender<(Uuid, ServerMessage)>,
+    ) -> GameResult<()> {
+        let mut game = game.write().await;
+        let station_id = game
+            .players
+            .get(&player_id)
+            .ok_or_else(|| GameError::player_not_found(player_id))?
+            .operating_station
+            .ok_or(GameError::NotOperatingStation)?;
+
+        let (mech_id, station_type) = game
+            .mechs
+            .values()
+            .find_map(|m| {
+                m.stations
+                    .get(&station_id)
+                    .map(|station| (m.id, station.station_type))
+            })
+            .ok_or_else(|| GameError::station_not_found(station_id))?;
+        if station_type != StationType::TurretControl {
+            return Err(GameError::InvalidStationOperation { station_type });
+        }
+        game.check_can_operate(player_id, mech_id)?;
+
+        if let Some(mech) = game.mechs.get_mut(&mech_id) {
+            mech.turret.aim_at(self.angle);
+            mech.turret.manual = true;
+            let _ = tx.send((
+                Uuid::nil(),
+                ServerMessage::TurretAimed {
+                    mech_id,
+                    turret: mech.turret,
+                },
+            ));
+        }
+        Ok(())
+    }
+}
+
 /// Floor transition command for changing floors in mechs
 pub struct FloorTransitionCommand {
     pub current_position: TilePos,
@@ -371,9 +1004,15 @@ pub fn create_command(msg: ClientMessage) -> Box<dyn Command> {
         ClientMessage::JoinGame {
             player_name,
             preferred_team,
+            protocol_version,
+            features,
+            password,
         } => Box::new(JoinGameCommand {
             player_name,
             preferred_team,
+            protocol_version,
+            features,
+            password,
         }),
         ClientMessage::PlayerInput {
             movement,
@@ -389,6 +1028,42 @@ pub fn create_command(msg: ClientMessage) -> Box<dyn Command> {
         ClientMessage::ExitMech => Box::new(ExitMechCommand),
         ClientMessage::ExitStation => Box::new(ExitStationCommand),
         ClientMessage::ChatMessage { message } => Box::new(ChatMessageCommand { message }),
+        ClientMessage::Annotate {
+            shape,
+            color,
+            audience,
+            ttl,
+        } => Box::new(AnnotateCommand {
+            shape,
+            color,
+            audience,
+            ttl,
+        }),
+        ClientMessage::DevCommand { command } => Box::new(DevCommandCommand { command }),
+        ClientMessage::SelectInventorySlot { slot } => {
+            Box::new(SelectInventorySlotCommand { slot })
+        }
+        ClientMessage::DropItem => Box::new(DropItemCommand),
+        ClientMessage::TurretAim { angle } => Box::new(TurretAimCommand { angle }),
+        ClientMessage::MeleeAttack => Box::new(MeleeAttackCommand),
+        ClientMessage::UseItem => Box::new(UseItemCommand),
+        ClientMessage::Dash { direction } => Box::new(DashCommand { direction }),
+        ClientMessage::PlaceBarricade { position } => Box::new(PlaceBarricadeCommand { position }),
+        ClientMessage::RemoveBarricade { position } => {
+            Box::new(RemoveBarricadeCommand { position })
+        }
+        ClientMessage::Look { facing } => Box::new(LookCommand { facing }),
+        ClientMessage::VerboseState { enabled } => Box::new(VerboseStateCommand { enabled }),
+        ClientMessage::PickRole { role } => Box::new(PickRoleCommand { role }),
+        ClientMessage::SetReady { ready } => Box::new(SetReadyCommand { ready }),
+        ClientMessage::Subscribe { channels } => Box::new(SubscribeCommand { channels }),
+        ClientMessage::Spectate {
+            protocol_version,
+            features,
+        } => Box::new(SpectateCommand {
+            protocol_version,
+            features,
+        }),
         ClientMessage::FloorTransition { current_position, target_floor, stairway_position } => {
             Box::new(FloorTransitionCommand { 
                 current_position, 
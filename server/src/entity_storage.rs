@@ -1,5 +1,6 @@
 use shared::{
     components::*,
+    tile_script::TileScript,
     types::{TilePos, WorldPos},
 };
 use std::collections::HashMap;
@@ -31,6 +32,13 @@ pub struct EntityStorage {
     pub resource_pickups: HashMap<Uuid, ResourcePickup>,
     pub mech_entrances: HashMap<Uuid, MechEntrance>,
     pub auto_interacts: HashMap<Uuid, AutoInteract>,
+    pub tile_scripts: HashMap<Uuid, TileScript>,
+
+    // Status effects, keyed by player or mech id
+    pub status_effects: HashMap<Uuid, StatusEffects>,
+
+    // Team base structures
+    pub structures: HashMap<Uuid, TeamStructure>,
 
     // Entity tracking
     entities: HashMap<Uuid, EntityInfo>,
@@ -74,6 +82,9 @@ impl EntityStorage {
             resource_pickups: HashMap::new(),
             mech_entrances: HashMap::new(),
             auto_interacts: HashMap::new(),
+            tile_scripts: HashMap::new(),
+            status_effects: HashMap::new(),
+            structures: HashMap::new(),
             entities: HashMap::new(),
             entities_by_position: HashMap::new(),
             entities_by_mech: HashMap::new(),
@@ -84,8 +95,7 @@ impl EntityStorage {
     // Entity Management
     // =============================================================================
 
-    pub fn create_entity(&mut self, name: String) -> Uuid {
-        let id = Uuid::new_v4();
+    pub fn create_entity(&mut self, id: Uuid, name: String) -> Uuid {
         self.entities.insert(
             id,
             EntityInfo {
@@ -97,8 +107,13 @@ impl EntityStorage {
         id
     }
 
-    pub fn spawn_from_template(&mut self, template: &EntityTemplate, position: Position) -> Uuid {
-        let entity_id = self.create_entity(template.name.clone());
+    pub fn spawn_from_template(
+        &mut self,
+        id: Uuid,
+        template: &EntityTemplate,
+        position: Position,
+    ) -> Uuid {
+        let entity_id = self.create_entity(id, template.name.clone());
 
         // Add position component and update spatial index
         self.add_position(entity_id, position);
@@ -161,6 +176,9 @@ impl EntityStorage {
         if let Some(auto_interact) = &template.components.auto_interact {
             self.auto_interacts.insert(entity_id, auto_interact.clone());
         }
+        if let Some(tile_script) = &template.components.tile_script {
+            self.tile_scripts.insert(entity_id, tile_script.clone());
+        }
 
         entity_id
     }
@@ -196,6 +214,8 @@ impl EntityStorage {
         self.resource_pickups.remove(&entity_id);
         self.mech_entrances.remove(&entity_id);
         self.auto_interacts.remove(&entity_id);
+        self.tile_scripts.remove(&entity_id);
+        self.structures.remove(&entity_id);
 
         self.entities.remove(&entity_id);
     }
@@ -371,7 +391,7 @@ mod tests {
     fn test_entity_creation_and_destruction() {
         let mut storage = EntityStorage::new();
 
-        let entity_id = storage.create_entity("Test Entity".to_string());
+        let entity_id = storage.create_entity(Uuid::new_v4(), "Test Entity".to_string());
         assert!(storage.entities.contains_key(&entity_id));
 
         storage.destroy_entity(entity_id);
@@ -406,7 +426,7 @@ mod tests {
             mech_id: Some(Uuid::new_v4()),
         };
 
-        let entity_id = storage.spawn_from_template(&template, position);
+        let entity_id = storage.spawn_from_template(Uuid::new_v4(), &template, position);
 
         assert!(storage.get_station(entity_id).is_some());
         assert!(storage.get_solid(entity_id).is_some());
@@ -419,7 +439,7 @@ mod tests {
         let mech_id = Uuid::new_v4();
 
         // Create entities at different positions
-        let entity1 = storage.create_entity("Entity 1".to_string());
+        let entity1 = storage.create_entity(Uuid::new_v4(), "Entity 1".to_string());
         storage.add_position(
             entity1,
             Position {
@@ -430,7 +450,7 @@ mod tests {
             },
         );
 
-        let entity2 = storage.create_entity("Entity 2".to_string());
+        let entity2 = storage.create_entity(Uuid::new_v4(), "Entity 2".to_string());
         storage.add_position(
             entity2,
             Position {
@@ -441,7 +461,7 @@ mod tests {
             },
         );
 
-        let entity3 = storage.create_entity("Entity 3".to_string());
+        let entity3 = storage.create_entity(Uuid::new_v4(), "Entity 3".to_string());
         storage.add_position(
             entity3,
             Position {
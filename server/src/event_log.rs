@@ -0,0 +1,175 @@
+//! A journal of recent gameplay events, so debug tooling that connects late
+//! can still see what happened. A listener on the room's broadcast channel
+//! copies every public message into a bounded ring, stamped with the tick it
+//! went out on; `/debug/events` reads it back.
+
+use crate::game::Game;
+use serde::Serialize;
+use shared::*;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{broadcast, RwLock};
+use uuid::Uuid;
+
+pub type SharedEventLog = Arc<Mutex<EventLog>>;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LoggedEvent {
+    pub tick: u64,
+    #[serde(rename = "type")]
+    pub event_type: &'static str,
+    pub event: ServerMessage,
+}
+
+/// The last `capacity` events, oldest first
+pub struct EventLog {
+    capacity: usize,
+    events: VecDeque<LoggedEvent>,
+}
+
+impl Default for EventLog {
+    fn default() -> Self {
+        Self::new(EVENT_LOG_CAPACITY)
+    }
+}
+
+impl EventLog {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            events: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Keep a message if it's an event rather than a state stream
+    pub fn record(&mut self, tick: u64, message: &ServerMessage) {
+        if !is_event(message) {
+            return;
+        }
+        if self.events.len() == self.capacity {
+            self.events.pop_front();
+        }
+        self.events.push_back(LoggedEvent {
+            tick,
+            event_type: message.type_name(),
+            event: message.clone(),
+        });
+    }
+
+    /// Events from `since_tick` on, optionally only those of one type
+    pub fn query(&self, since_tick: u64, event_type: Option<&str>) -> Vec<LoggedEvent> {
+        self.events
+            .iter()
+            .filter(|event| event.tick >= since_tick)
+            .filter(|event| event_type.is_none_or(|wanted| event.event_type == wanted))
+            .cloned()
+            .collect()
+    }
+
+    /// Tick of the oldest event still held; anything before it was dropped
+    pub fn oldest_tick(&self) -> Option<u64> {
+        self.events.front().map(|event| event.tick)
+    }
+}
+
+/// Messages that say something happened, as opposed to the snapshots,
+/// movement and vision that stream every tick and would crowd them out
+fn is_event(message: &ServerMessage) -> bool {
+    !matches!(
+        message,
+        ServerMessage::GameState { .. }
+            | ServerMessage::StateChecksum { .. }
+            | ServerMessage::MechFloorData { .. }
+            | ServerMessage::ArenaMapData { .. }
+            | ServerMessage::DecalLayer { .. }
+            | ServerMessage::PlayerMoved { .. }
+            | ServerMessage::PlayerFacing { .. }
+            | ServerMessage::MechMoved { .. }
+            | ServerMessage::TurretAimed { .. }
+            | ServerMessage::CameraSuggestion { .. }
+            | ServerMessage::TileUpdate { .. }
+            | ServerMessage::TileBatch { .. }
+            | ServerMessage::VisibilityUpdate { .. }
+            | ServerMessage::TeamVisionUpdate { .. }
+            | ServerMessage::RepairProgress { .. }
+            | ServerMessage::SabotageProgress { .. }
+            | ServerMessage::PacingZonesUpdated { .. }
+    )
+}
+
+/// Journal a room's public messages for as long as its channel is open
+pub async fn run_event_log(
+    game: Arc<RwLock<Game>>,
+    mut rx: broadcast::Receiver<(Uuid, ServerMessage)>,
+    log: SharedEventLog,
+) {
+    loop {
+        let first = match rx.recv().await {
+            Ok(message) => message,
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                log::warn!("Event log fell behind and missed {skipped} messages");
+                continue;
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        };
+        // Stamp everything that's queued up with one look at the clock
+        let mut batch = vec![first];
+        while let Ok(message) = rx.try_recv() {
+            batch.push(message);
+        }
+        let tick = game.read().await.tick_count;
+        let mut log = log.lock().unwrap();
+        for (target, message) in &batch {
+            if target.is_nil() {
+                log.record(tick, message);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn damaged(health_remaining: u32) -> ServerMessage {
+        ServerMessage::MechDamaged {
+            mech_id: Uuid::nil(),
+            damage: 10,
+            health_remaining,
+            position: WorldPos::new(0.0, 0.0),
+            hit: None,
+        }
+    }
+
+    #[test]
+    fn test_keeps_the_latest_events_and_filters_by_tick_and_type() {
+        let mut log = EventLog::new(3);
+        log.record(1, &damaged(90));
+        log.record(
+            2,
+            &ServerMessage::PlayerMoved {
+                player_id: Uuid::nil(),
+                location: PlayerLocation::OutsideWorld(WorldPos::new(0.0, 0.0)),
+            },
+        );
+        log.record(
+            2,
+            &ServerMessage::ResourceDestroyed {
+                resource_id: Uuid::nil(),
+            },
+        );
+        log.record(3, &damaged(80));
+        log.record(4, &damaged(70));
+
+        // Movement isn't kept, and the first hit has been pushed out
+        assert_eq!(log.oldest_tick(), Some(2));
+        assert_eq!(log.query(0, None).len(), 3);
+
+        let hits = log.query(3, Some("MechDamaged"));
+        assert_eq!(
+            hits.iter().map(|event| event.tick).collect::<Vec<_>>(),
+            vec![3, 4]
+        );
+        assert!(log.query(0, Some("PlayerMoved")).is_empty());
+    }
+}
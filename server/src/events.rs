@@ -0,0 +1,54 @@
+//! Gameplay events. Systems and command handlers publish what happened as it
+//! happens; listeners such as achievements read them back once per tick
+//! instead of every producer having to know who cares.
+
+use shared::types::ResourceType;
+use uuid::Uuid;
+
+/// Something that happened in the match worth reacting to
+#[derive(Debug, Clone, PartialEq)]
+pub enum GameEvent {
+    /// A player was knocked out; `killer` is None when no player did it
+    PlayerKilled { victim: Uuid, killer: Option<Uuid> },
+    ResourceDelivered {
+        player_id: Uuid,
+        mech_id: Uuid,
+        resource_type: ResourceType,
+    },
+    /// A mech's shield was taken down to nothing by fire from `attacker`
+    MechShieldBroken { mech_id: Uuid, attacker: Uuid },
+    StationSabotaged {
+        player_id: Uuid,
+        mech_id: Uuid,
+        station_id: Uuid,
+    },
+    StationButtonPressed {
+        player_id: Uuid,
+        station_id: Uuid,
+        button_index: u8,
+    },
+    /// A hit big enough to throw the crew of `mech_id` around
+    InteriorImpact { mech_id: Uuid, damage: u32 },
+}
+
+/// Events published since the listeners last caught up
+#[derive(Default)]
+pub struct EventBus {
+    pending: Vec<GameEvent>,
+}
+
+impl EventBus {
+    pub fn publish(&mut self, event: GameEvent) {
+        self.pending.push(event);
+    }
+
+    /// Everything published so far, for listeners that only look
+    pub fn pending(&self) -> &[GameEvent] {
+        &self.pending
+    }
+
+    /// Hand over everything published so far, oldest first
+    pub fn drain(&mut self) -> Vec<GameEvent> {
+        std::mem::take(&mut self.pending)
+    }
+}
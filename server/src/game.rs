@@ -1,18 +1,32 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use tokio::sync::broadcast;
 use uuid::Uuid;
 
+use crate::annotations::AnnotationLimiter;
+use crate::collision_debug::CollisionDebug;
 use crate::entity_storage::EntityStorage;
+use crate::events::{EventBus, GameEvent};
+use crate::hangar::Hangar;
+use crate::idle::IdleTracker;
+use crate::lag_compensation::LagCompensation;
 use crate::mech_generation::get_station_size;
+use crate::rng::GameRng;
+use crate::rooms::RoomAccess;
+use crate::sessions::SessionHolds;
 use crate::spatial_collision::SpatialCollisionManager;
+use crate::station_upgrades;
 use crate::systems::SystemManager;
 use crate::testing_modes::TestingManager;
-use shared::components::{Position, Station};
+use crate::vision_scheduler::{Viewpoint, VisionScheduler};
+use crate::voice::SharedVoiceRelay;
+use shared::components::{Fire, Position, Station};
 use shared::mech_layout::MechLayoutGenerator;
 use shared::object_pool::PoolManager;
 use shared::stations::StationRegistry;
-use shared::tile_entity::{Material, StaticTile, TileContent, TileMap, TileVisual, TransitionType};
-use shared::vision::VisionSystem;
+use shared::tile_entity::{
+    Barricade, Material, StaticTile, TileContent, TileMap, TileVisual, TransitionType,
+};
+use shared::vision::{SightShape, VisionSystem};
 use shared::*;
 
 pub struct Game {
@@ -22,14 +36,67 @@ pub struct Game {
     pub projectiles: HashMap<Uuid, PooledProjectile>,
     pub active_effects: HashMap<Uuid, PooledEffect>,
     pub tick_count: u64,
+    /// Seconds of simulation since the match began
+    pub match_time: f32,
     pub spatial_collision: SpatialCollisionManager,
     pub station_registry: StationRegistry,
+    /// What resources spawn where, and how rare each is
+    pub spawn_tables: SpawnTables,
     pub pool_manager: PoolManager,
     pub system_manager: SystemManager,
     pub tile_map: TileMap,
     pub entity_storage: EntityStorage,
-    pub vision_system: VisionSystem,
+    /// Line of sight for every player, recast a few at a time
+    pub vision_scheduler: VisionScheduler,
     pub testing_manager: TestingManager,
+    /// Minimum crew per team; AI players fill the gap when humans are missing
+    pub ai_autofill_min_crew: Option<usize>,
+    /// Zones injected by the match director (damage boosts, play area, ...)
+    pub pacing_zones: Vec<PacingZone>,
+    /// Objective of the match's game mode, if it has one
+    pub game_mode: Option<GameModeHud>,
+    /// Share each player's vision with their whole team; off for harder modes
+    pub team_vision: bool,
+    /// Layout the world was built from; mines are removed as they go off
+    pub arena_map: ArenaMap,
+    /// Accept developer console commands (teleport, give) from clients
+    pub dev_mode: bool,
+    /// Follow each full state with hashes of it so clients can spot desyncs
+    pub state_checksums: bool,
+    /// Items players have dropped in the world
+    pub ground_items: HashMap<ItemId, GroundItem>,
+    /// Scorches, burns and debris left on the ground by fighting
+    pub decals: DecalLayer,
+    /// Cleared once a shutdown starts so nobody new joins a dying server
+    pub accepting_players: bool,
+    /// Title, player cap and password the room was opened with
+    pub access: RoomAccess,
+    /// How far crew outside see around them
+    pub sight: SightShape,
+    /// Time of day and weather, which stretch or shorten that sight
+    pub environment: Environment,
+    /// Gameplay events waiting for listeners like achievements
+    pub events: EventBus,
+    /// Connections watching without a player, fed the camera director's picks
+    pub spectators: HashSet<Uuid>,
+    /// Recent positions and client round trips, for rewinding laser hits
+    pub lag_compensation: LagCompensation,
+    /// Connections drawing the server's collision shapes, and projectile trails for them
+    pub collision_debug: CollisionDebug,
+    /// Who has stopped sending input, and the AIs covering for them
+    pub idle: IdleTracker,
+    /// Players whose connection dropped, kept for them to come back to
+    pub sessions: SessionHolds,
+    /// How many coaching marks each author has drawn this second
+    pub annotation_limiter: AnnotationLimiter,
+    /// Teammates' voice sockets, shared with the `/voice` handlers
+    pub voice: SharedVoiceRelay,
+    /// How much the game loop is giving up to keep up
+    pub load: ServerLoad,
+    /// Crews picking roles before the match; the match is on once it's gone
+    pub hangar: Option<Hangar>,
+    /// Every roll and entity id the simulation makes, from the match's seed
+    pub rng: GameRng,
 }
 
 pub struct Player {
@@ -37,8 +104,26 @@ pub struct Player {
     pub name: String,
     pub team: TeamId,
     pub location: PlayerLocation,
-    pub carrying_resource: Option<ResourceType>,
+    pub inventory: Inventory,
     pub operating_station: Option<Uuid>,
+    pub held_tool: Option<ToolType>,
+    pub health: u32,
+    pub facing: Facing,
+    /// Match time at which the player can dash again
+    pub dash_ready_at: f32,
+    /// Match time at which the player's scanner can ping again
+    pub scanner_ready_at: f32,
+}
+
+/// What one hit did to a mech
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DamageDealt {
+    /// Soaked up by the shield
+    pub absorbed: u32,
+    /// Taken by the hull, after armor
+    pub hull: u32,
+    /// The hull gave out
+    pub destroyed: bool,
 }
 
 pub struct Mech {
@@ -53,10 +138,28 @@ pub struct Mech {
     pub stations: HashMap<Uuid, StationInstance>,
     pub interior: MechInterior,
     pub resource_inventory: HashMap<ResourceType, u32>,
+    pub delivery_score: u32,
     pub velocity: (f32, f32),     // tiles per second
+    pub drive: MechDrive,
+    pub fuel: f32,
+    pub turret: MechTurret,
+    pub point_defense: PointDefense,
     pub world_position: WorldPos, // For smooth movement
+    pub fires: HashMap<MechInteriorPos, Fire>,
+    /// Station the Upgrade terminal is set to upgrade next
+    pub upgrade_target: Option<Uuid>,
+    /// Sounding while boarders work one of its stations
+    pub alarm: Option<SabotageAlarm>,
 }
 
+/// Personalities cycled through when autofilling a team with AI crew
+const AUTOFILL_PERSONALITIES: [ai::Personality; 4] = [
+    ai::Personality::Balanced,
+    ai::Personality::Support,
+    ai::Personality::Aggressive,
+    ai::Personality::Defensive,
+];
+
 impl Clone for Player {
     fn clone(&self) -> Self {
         Self {
@@ -64,10 +167,106 @@ impl Clone for Player {
             name: self.name.clone(),
             team: self.team,
             location: self.location,
-            carrying_resource: self.carrying_resource,
+            inventory: self.inventory.clone(),
             operating_station: self.operating_station,
+            held_tool: self.held_tool,
+            health: self.health,
+            facing: self.facing,
+            dash_ready_at: self.dash_ready_at,
+            scanner_ready_at: self.scanner_ready_at,
+        }
+    }
+}
+
+impl Player {
+    pub fn inventory_message(&self) -> ServerMessage {
+        ServerMessage::InventoryUpdated {
+            player_id: self.id,
+            inventory: self.inventory.clone(),
         }
     }
+
+    /// Turn the way the player is moving; what to tell everyone if they turned
+    pub fn face_towards(&mut self, movement: (f32, f32)) -> Option<ServerMessage> {
+        self.face(Facing::from_movement(movement)?)
+    }
+
+    /// Turn to `facing`; what to tell everyone if that's a change
+    pub fn face(&mut self, facing: Facing) -> Option<ServerMessage> {
+        if facing == self.facing {
+            return None;
+        }
+        self.facing = facing;
+        Some(ServerMessage::PlayerFacing {
+            player_id: self.id,
+            facing,
+        })
+    }
+}
+
+impl Mech {
+    /// Change the drive and carry the difference into velocity, so knockback
+    /// and other impulses already on the mech survive
+    pub fn update_drive(&mut self, change: impl FnOnce(&mut MechDrive)) {
+        let before = self.drive.velocity();
+        change(&mut self.drive);
+        let after = self.drive.velocity();
+        self.velocity.0 += after.0 - before.0;
+        self.velocity.1 += after.1 - before.1;
+    }
+
+    /// Take a cost out of the inventory, only if all of it is there
+    pub fn try_spend(&mut self, cost: &HashMap<ResourceType, u32>) -> bool {
+        let affordable = cost.iter().all(|(resource_type, amount)| {
+            self.resource_inventory.get(resource_type).unwrap_or(&0) >= amount
+        });
+        if affordable {
+            for (resource_type, amount) in cost {
+                if let Some(count) = self.resource_inventory.get_mut(resource_type) {
+                    *count -= amount;
+                }
+            }
+        }
+        affordable
+    }
+
+    pub fn inventory_message(&self) -> ServerMessage {
+        ServerMessage::MechInventoryChanged {
+            mech_id: self.id,
+            inventory: self.resource_inventory.clone(),
+        }
+    }
+
+    /// Number of breached wall tiles across all floors
+    pub fn hull_breaches(&self) -> usize {
+        self.interior
+            .floors
+            .iter()
+            .flat_map(|floor| floor.static_tiles.values())
+            .filter(|tile| matches!(tile, StaticTile::DamagedWall))
+            .count()
+    }
+
+    /// Recompute max health from outstanding hull breaches, clamping current health
+    pub fn recalculate_max_health(&mut self) -> u32 {
+        let penalty = self.hull_breaches() as u32 * HULL_BREACH_MAX_HEALTH_PENALTY;
+        self.max_health = MECH_MAX_HEALTH
+            .saturating_sub(penalty)
+            .max(MIN_DAMAGED_MAX_HEALTH);
+        self.health = self.health.min(self.max_health);
+        self.max_health
+    }
+
+    pub fn fire_states(&self) -> Vec<FireState> {
+        self.fires
+            .iter()
+            .map(|(pos, fire)| FireState {
+                floor: pos.floor,
+                position: pos.tile_pos,
+                intensity: fire.intensity,
+            })
+            .collect()
+    }
 }
 
 pub struct Resource {
@@ -76,6 +275,22 @@ pub struct Resource {
     pub resource_type: ResourceType,
 }
 
+/// Something a player dropped. Whoever dropped it can't pick it back up
+/// until they've stepped out of reach.
+pub struct GroundItem {
+    pub id: ItemId,
+    pub item: ItemType,
+    pub position: WorldPos,
+    pub dropped_by: Option<PlayerId>,
+}
+
+/// Where an item in reach of a player is lying
+#[derive(Debug, Clone, Copy)]
+enum Pickup {
+    Resource(ResourceId, ResourceType),
+    Dropped(ItemId),
+}
+
 // Projectile is now handled by PooledProjectile from the object_pool module
 
 impl Default for Game {
@@ -116,41 +331,59 @@ impl Game {
 
     /// Remove a resource entity
     pub fn remove_resource(&mut self, id: Uuid) {
+        // Look the tile up before the entity, and its position, are gone
+        let tile = self
+            .entity_storage
+            .positions
+            .get(&id)
+            .map(|p| p.tile)
+            .unwrap_or(TilePos::new(0, 0));
         self.entity_storage.destroy_entity(id);
-        self.tile_map.remove_tile(
-            self.entity_storage
-                .positions
-                .get(&id)
-                .map(|p| p.tile)
-                .unwrap_or(TilePos::new(0, 0)),
-        );
+        self.tile_map.remove_tile(tile);
     }
 
     pub fn new() -> Self {
-        // Initialize the hybrid tile map
-        let mut tile_map = TileMap::new();
-
-        // Initialize world with grass tiles
-        for x in 0..ARENA_WIDTH_TILES {
-            for y in 0..ARENA_HEIGHT_TILES {
-                tile_map.set_world_tile(TilePos::new(x, y), TileContent::Static(StaticTile::Grass));
-            }
-        }
-
+        let arena_map = ArenaMap::default();
         let mut game = Self {
             players: HashMap::new(),
             mechs: HashMap::new(),
             projectiles: HashMap::new(),
             active_effects: HashMap::new(),
             tick_count: 0,
+            match_time: 0.0,
             spatial_collision: SpatialCollisionManager::new(),
             station_registry: StationRegistry::new(),
+            spawn_tables: SpawnTables::new(),
             pool_manager: PoolManager::new(),
             system_manager: SystemManager::new(),
-            tile_map,
+            tile_map: Self::build_world_tiles(&arena_map),
             entity_storage: EntityStorage::new(),
-            vision_system: VisionSystem::new(),
+            vision_scheduler: VisionScheduler::default(),
             testing_manager: TestingManager::new_normal(),
+            ai_autofill_min_crew: None,
+            pacing_zones: Vec::new(),
+            game_mode: None,
+            team_vision: true,
+            arena_map,
+            dev_mode: false,
+            state_checksums: false,
+            ground_items: HashMap::new(),
+            decals: DecalLayer::new(),
+            accepting_players: true,
+            access: RoomAccess::default(),
+            sight: SightShape::default(),
+            environment: Environment::default(),
+            events: EventBus::default(),
+            spectators: HashSet::new(),
+            lag_compensation: LagCompensation::default(),
+            collision_debug: CollisionDebug::default(),
+            idle: IdleTracker::default(),
+            sessions: SessionHolds::default(),
+            annotation_limiter: AnnotationLimiter::default(),
+            voice: SharedVoiceRelay::default(),
+            load: ServerLoad::Normal,
+            hangar: None,
+            rng: GameRng::from_entropy(),
         };
 
         // Initialize mechs and update tiles
@@ -161,32 +394,61 @@ impl Game {
 
     /// Create a new game with testing configuration
     pub fn new_with_testing(testing_config: crate::testing_modes::TestingConfig) -> Self {
-        // Initialize the hybrid tile map
-        let mut tile_map = TileMap::new();
-        // Initialize world with grass tiles
-        for x in 0..ARENA_WIDTH_TILES {
-            for y in 0..ARENA_HEIGHT_TILES {
-                tile_map.set_world_tile(TilePos::new(x, y), TileContent::Static(StaticTile::Grass));
-            }
-        }
+        Self::new_with_map(testing_config, ArenaMap::default(), rand::random())
+    }
+
+    /// Create a new game on an arena authored in the map editor, rolling
+    /// everything from `seed`
+    pub fn new_with_map(
+        testing_config: crate::testing_modes::TestingConfig,
+        arena_map: ArenaMap,
+        seed: u64,
+    ) -> Self {
         let mut game = Self {
             players: HashMap::new(),
             mechs: HashMap::new(),
             projectiles: HashMap::new(),
             active_effects: HashMap::new(),
             tick_count: 0,
+            match_time: 0.0,
             spatial_collision: SpatialCollisionManager::new(),
             station_registry: StationRegistry::new(),
+            spawn_tables: SpawnTables::new(),
             pool_manager: PoolManager::new(),
             system_manager: SystemManager::new(),
-            tile_map,
+            tile_map: Self::build_world_tiles(&arena_map),
             entity_storage: EntityStorage::new(),
-            vision_system: VisionSystem::new(),
+            vision_scheduler: VisionScheduler::default(),
             testing_manager: TestingManager::new(testing_config),
+            ai_autofill_min_crew: None,
+            pacing_zones: Vec::new(),
+            game_mode: None,
+            team_vision: true,
+            arena_map,
+            dev_mode: false,
+            state_checksums: false,
+            ground_items: HashMap::new(),
+            decals: DecalLayer::new(),
+            accepting_players: true,
+            access: RoomAccess::default(),
+            sight: SightShape::default(),
+            environment: Environment::default(),
+            events: EventBus::default(),
+            spectators: HashSet::new(),
+            lag_compensation: LagCompensation::default(),
+            collision_debug: CollisionDebug::default(),
+            idle: IdleTracker::default(),
+            sessions: SessionHolds::default(),
+            annotation_limiter: AnnotationLimiter::default(),
+            voice: SharedVoiceRelay::default(),
+            load: ServerLoad::Normal,
+            hangar: None,
+            rng: GameRng::new(seed),
         };
 
         // Initialize mechs and update tiles
         game.create_initial_mechs();
+        game.spawn_scripted_tiles();
 
         // Record initial positions for testing comparison
         game.testing_manager.record_initial_positions(&game.mechs);
@@ -194,6 +456,20 @@ impl Game {
         game
     }
 
+    /// Fill the world with grass, then lay the map's painted tiles over it
+    fn build_world_tiles(arena_map: &ArenaMap) -> TileMap {
+        let mut tile_map = TileMap::new();
+        for x in 0..arena_map.width {
+            for y in 0..arena_map.height {
+                tile_map.set_world_tile(TilePos::new(x, y), TileContent::Static(StaticTile::Grass));
+            }
+        }
+        for map_tile in &arena_map.tiles {
+            tile_map.set_world_tile(map_tile.position, TileContent::Static(map_tile.tile));
+        }
+        tile_map
+    }
+
     /// Add an AI player to the game
     pub fn add_ai_player(
         &mut self,
@@ -201,16 +477,7 @@ impl Game {
         personality: Option<ai::Personality>,
     ) -> Option<Uuid> {
         // Count teams for balancing
-        let red_count = self
-            .players
-            .values()
-            .filter(|p| p.team == TeamId::Red)
-            .count();
-        let blue_count = self
-            .players
-            .values()
-            .filter(|p| p.team == TeamId::Blue)
-            .count();
+        let (red_count, blue_count) = self.team_counts();
 
         // Get the AI system from the system manager
         let mut system_manager = std::mem::take(&mut self.system_manager);
@@ -218,8 +485,13 @@ impl Game {
             system_manager.get_system_mut::<crate::systems::ai::AISystem>()
         {
             // Add the AI player
-            let (ai_id, player) =
-                ai_system.add_ai_player(difficulty, personality, red_count, blue_count);
+            let (ai_id, player) = ai_system.add_ai_player(
+                difficulty,
+                personality,
+                red_count,
+                blue_count,
+                &mut self.rng,
+            );
             self.players.insert(ai_id, player);
             Some(ai_id)
         } else {
@@ -230,40 +502,1362 @@ impl Game {
         result
     }
 
+    /// Add a registered bot on the smaller team
+    pub fn add_bot_player(&mut self, bot: &str, difficulty: f32) -> Option<Uuid> {
+        self.add_ai_with(|ai_system, team, rng| {
+            ai_system.add_bot_player_to_team(bot, difficulty, team, rng)
+        })
+    }
+
+    /// Add a bot built outside the registry, like a WASM module, to the
+    /// smaller team
+    pub fn add_controller_player(
+        &mut self,
+        controller: Box<dyn ai::AIController>,
+        name: String,
+    ) -> Option<Uuid> {
+        self.add_ai_with(|ai_system, team, _| {
+            Some(ai_system.add_controller_to_team(controller, name, team))
+        })
+    }
+
+    /// Add the AI `add` makes on the smaller team to the match
+    fn add_ai_with(
+        &mut self,
+        add: impl FnOnce(
+            &mut crate::systems::ai::AISystem,
+            TeamId,
+            &mut GameRng,
+        ) -> Option<(Uuid, Player)>,
+    ) -> Option<Uuid> {
+        let (red_count, blue_count) = self.team_counts();
+        let team = if red_count <= blue_count {
+            TeamId::Red
+        } else {
+            TeamId::Blue
+        };
+
+        let mut system_manager = std::mem::take(&mut self.system_manager);
+        let result = if let Some(ai_system) =
+            system_manager.get_system_mut::<crate::systems::ai::AISystem>()
+        {
+            add(ai_system, team, &mut self.rng).map(|(ai_id, player)| {
+                self.players.insert(ai_id, player);
+                ai_id
+            })
+        } else {
+            log::error!("AI system not found in system manager");
+            None
+        };
+        self.system_manager = system_manager;
+        result
+    }
+
+    fn team_counts(&self) -> (usize, usize) {
+        let red_count = self
+            .players
+            .values()
+            .filter(|p| p.team == TeamId::Red)
+            .count();
+        (red_count, self.players.len() - red_count)
+    }
+
     /// Remove an AI player from the game
     pub fn remove_ai_player(&mut self, ai_id: Uuid) {
-        // Remove from players
-        self.players.remove(&ai_id);
+        // Remove from players, releasing any station they hold
+        self.remove_player(&ai_id);
 
         // Get the AI system from the system manager
         let mut system_manager = std::mem::take(&mut self.system_manager);
         if let Some(ai_system) = system_manager.get_system_mut::<crate::systems::ai::AISystem>() {
-            // Remove the AI player
+            // Remove the AI player
+            ai_system.remove_ai_player(ai_id);
+        } else {
+            log::error!("AI system not found in system manager");
+        }
+        self.system_manager = system_manager;
+    }
+
+    /// AI-to-AI messages from `sequence` on, for debug observers
+    pub fn ai_comm_events_since(&mut self, sequence: u64) -> Vec<ai::CommEvent> {
+        self.system_manager
+            .get_system_mut::<crate::systems::ai::AISystem>()
+            .map(|ai_system| ai_system.comm_events_since(sequence))
+            .unwrap_or_default()
+    }
+
+    /// Shed work for the new load level: AIs think less often and cosmetic
+    /// systems pause from `Strained` on. Everyone is told.
+    pub fn set_load(&mut self, level: ServerLoad) -> ServerMessage {
+        self.load = level;
+        if let Some(ai_system) = self
+            .system_manager
+            .get_system_mut::<crate::systems::ai::AISystem>()
+        {
+            ai_system.set_load_slowdown(level.ai_slowdown());
+        }
+        ServerMessage::ServerLoad { level }
+    }
+
+    /// What the AIs' latest update cost, for debug observers
+    pub fn ai_tick_metrics(&mut self) -> Option<ai::AITickMetrics> {
+        self.system_manager
+            .get_system_mut::<crate::systems::ai::AISystem>()
+            .map(|ai_system| ai_system.tick_metrics().clone())
+    }
+
+    /// What each team's AIs are allowed to know, built from the same
+    /// snapshot clients get and cut down to what the team can see
+    pub fn ai_views(&mut self) -> Vec<ai::GameView> {
+        let snapshot = self.get_full_state();
+        let visibility = self.team_visibility();
+        let Some(builder) = ai::GameViewBuilder::from_snapshot(&snapshot, self.tick_count) else {
+            return Vec::new();
+        };
+        let nothing = HashSet::new();
+        [TeamId::Red, TeamId::Blue]
+            .into_iter()
+            .map(|team| builder.build(team, Some(visibility.get(&team).unwrap_or(&nothing))))
+            .collect()
+    }
+
+    /// Task weights an AI is using, for debug observers
+    pub fn ai_weights(&mut self, ai_id: Uuid) -> Option<ai::UtilityWeights> {
+        self.system_manager
+            .get_system_mut::<crate::systems::ai::AISystem>()
+            .and_then(|ai_system| ai_system.ai_weights(ai_id))
+    }
+
+    /// Retune an AI's task weights while it plays; false if it has none
+    pub fn set_ai_weights(&mut self, ai_id: Uuid, weights: ai::UtilityWeights) -> bool {
+        self.system_manager
+            .get_system_mut::<crate::systems::ai::AISystem>()
+            .is_some_and(|ai_system| ai_system.set_ai_weights(ai_id, weights))
+    }
+
+    /// Get list of all AI players
+    pub fn get_ai_players(&self) -> Vec<Uuid> {
+        self.players
+            .iter()
+            .filter(|(_, p)| p.name.starts_with("AI_"))
+            .map(|(id, _)| *id)
+            .collect()
+    }
+
+    /// Apply a status effect to a player or mech, returning the update to broadcast
+    pub fn apply_status_effect(
+        &mut self,
+        entity_id: Uuid,
+        kind: StatusEffectKind,
+        duration: f32,
+        magnitude: f32,
+    ) -> ServerMessage {
+        let effects = self
+            .entity_storage
+            .status_effects
+            .entry(entity_id)
+            .or_default();
+        effects.apply(kind, duration, magnitude);
+
+        ServerMessage::StatusEffectsChanged {
+            entity_id,
+            effects: effects.effects.clone(),
+        }
+    }
+
+    /// Whether enemies can't hurt a player: they've just respawned, or
+    /// they're standing in their own team's safe zone
+    pub fn is_protected(&self, player_id: Uuid) -> bool {
+        let Some(player) = self.players.get(&player_id) else {
+            return false;
+        };
+        let spawn_protected = self
+            .entity_storage
+            .status_effects
+            .get(&player_id)
+            .is_some_and(|effects| effects.has(StatusEffectKind::SpawnProtected));
+        let at_home = match player.location {
+            PlayerLocation::OutsideWorld(pos) => self
+                .arena_map
+                .safe_zone(player.team)
+                .contains(pos.to_tile()),
+            PlayerLocation::InsideMech { .. } => false,
+        };
+        spawn_protected || at_home
+    }
+
+    /// End a player's spawn protection early, as they go on the attack
+    pub fn drop_spawn_protection(&mut self, player_id: Uuid) -> Option<ServerMessage> {
+        let effects = self.entity_storage.status_effects.get_mut(&player_id)?;
+        if !effects.has(StatusEffectKind::SpawnProtected) {
+            return None;
+        }
+        effects
+            .effects
+            .retain(|effect| effect.kind != StatusEffectKind::SpawnProtected);
+        Some(ServerMessage::StatusEffectsChanged {
+            entity_id: player_id,
+            effects: effects.effects.clone(),
+        })
+    }
+
+    /// Movement speed multiplier from status effects (1.0 when unaffected)
+    pub fn movement_multiplier(&self, entity_id: Uuid) -> f32 {
+        self.entity_storage
+            .status_effects
+            .get(&entity_id)
+            .map(|effects| effects.movement_multiplier())
+            .unwrap_or(1.0)
+    }
+
+    /// Weapon damage multiplier from status effects (1.0 when unaffected)
+    pub fn damage_multiplier(&self, entity_id: Uuid) -> f32 {
+        self.entity_storage
+            .status_effects
+            .get(&entity_id)
+            .map(|effects| effects.damage_multiplier())
+            .unwrap_or(1.0)
+    }
+
+    /// What a mech weighs with its stations, upgrades, hold and crew
+    pub fn mech_mass(&self, mech_id: Uuid) -> MechMass {
+        let Some(mech) = self.mechs.get(&mech_id) else {
+            return MechMass::default();
+        };
+        let stations = mech.stations.values().map(|station| {
+            let mass = self
+                .station_registry
+                .get_definition(station.station_type)
+                .map_or(0.0, |definition| definition.mass);
+            (mass, station.upgrade_level)
+        });
+        let crew = self
+            .players
+            .values()
+            .filter(|player| player.location.mech_id() == Some(mech_id))
+            .count();
+        MechMass::new(
+            stations,
+            &mech.upgrades,
+            mech.resource_inventory.values().sum(),
+            crew,
+        )
+    }
+
+    /// Check that neither the player, the mech they are working in nor the
+    /// station they are crewing is disabled by a status effect
+    pub fn check_can_operate(&self, player_id: Uuid, mech_id: Uuid) -> GameResult<()> {
+        let station_id = self
+            .players
+            .get(&player_id)
+            .and_then(|player| player.operating_station);
+        for id in [Some(player_id), Some(mech_id), station_id].into_iter().flatten() {
+            if let Some(effect) = self
+                .entity_storage
+                .status_effects
+                .get(&id)
+                .and_then(|effects| effects.blocks_station_use())
+            {
+                return Err(GameError::BlockedByStatusEffect { id, effect });
+            }
+        }
+        Ok(())
+    }
+
+    /// Tiers past the first on the station a player is crewing, which
+    /// strengthen whatever it does
+    pub fn station_bonus_levels(&self, player_id: Uuid) -> u32 {
+        self.players
+            .get(&player_id)
+            .and_then(|player| player.operating_station)
+            .and_then(|station_id| {
+                self.mechs
+                    .values()
+                    .find_map(|mech| mech.stations.get(&station_id))
+            })
+            .map_or(0, |station| station.bonus_levels())
+    }
+
+    /// Upgrade tier of the mech station covering a world tile, 1 if none does
+    pub fn station_level_at(&self, tile: TilePos) -> u8 {
+        self.mechs
+            .values()
+            .find_map(|mech| {
+                let (floor, pos) = MechInteriorCoordinates::world_to_interior(tile, mech.position)?;
+                mech.stations.values().find(|station| {
+                    let size = get_station_size(station.station_type);
+                    station.floor == floor
+                        && (station.position.x..station.position.x + size.width as i32)
+                            .contains(&pos.x)
+                        && (station.position.y..station.position.y + size.height as i32)
+                            .contains(&pos.y)
+                })
+            })
+            .map_or(1, |station| station.upgrade_level)
+    }
+
+    /// The controls for a station as its operator should see them
+    pub fn station_layout(&self, mech_id: Uuid, station_id: Uuid) -> Option<ServerMessage> {
+        let station = self.mechs.get(&mech_id)?.stations.get(&station_id)?;
+        let definition = self.station_registry.get_definition(station.station_type)?;
+        let mut buttons = definition.button_info();
+        for (button, info) in definition.button_definitions.iter().zip(&mut buttons) {
+            match button.action {
+                StationAction::UpgradeStation => {
+                    station_upgrades::describe_button(self, mech_id, info);
+                }
+                StationAction::UpgradeMech { upgrade_type } => {
+                    station_upgrades::price_mech_upgrade(self, mech_id, upgrade_type, info);
+                }
+                _ => {}
+            }
+        }
+        Some(ServerMessage::StationLayout {
+            station_id,
+            name: definition.name.clone(),
+            description: definition.description.clone(),
+            buttons,
+        })
+    }
+
+    /// Leave a decal on the tile under `position`, returning the update to broadcast
+    pub fn add_decal(&mut self, position: WorldPos, kind: DecalKind) -> ServerMessage {
+        let position = position.to_tile_pos();
+        self.decals.add(Decal { position, kind });
+        ServerMessage::DecalAdded { position, kind }
+    }
+
+    /// Blow apart resources lying within `radius` of `center`, leaving debris
+    pub fn destroy_resources_near(&mut self, center: WorldPos, radius: f32) -> Vec<ServerMessage> {
+        let caught: Vec<Resource> = self
+            .get_resources()
+            .into_iter()
+            .filter(|resource| resource.position.to_world_center().distance_to(center) <= radius)
+            .collect();
+
+        let mut messages = Vec::new();
+        for resource in caught {
+            self.remove_resource(resource.id);
+            messages.push(ServerMessage::ResourceDestroyed {
+                resource_id: resource.id,
+            });
+            messages.push(self.add_decal(resource.position.to_world_center(), DecalKind::Debris));
+        }
+        messages
+    }
+
+    /// Deal `damage` to a mech the way weapons do: the shield soaks what it
+    /// can, the hull takes the rest scaled by the armor on the side `hit`
+    /// came from, and a hull brought to nothing destroys the mech. Big hits
+    /// throw the crew around and may start fires or breaches. `attacker` is
+    /// credited with breaking the shield. Wrecks take no more damage.
+    pub fn damage_mech(
+        &mut self,
+        mech_id: Uuid,
+        damage: u32,
+        position: WorldPos,
+        hit: Option<HitDirection>,
+        attacker: Option<Uuid>,
+        messages: &mut Vec<ServerMessage>,
+    ) -> DamageDealt {
+        let Some(shield) = self.mechs.get(&mech_id).map(|mech| mech.shield) else {
+            return DamageDealt::default();
+        };
+        let absorbed = damage.min(shield);
+        self.damage_mech_split(
+            mech_id,
+            (absorbed, damage - absorbed),
+            position,
+            hit,
+            attacker,
+            messages,
+        )
+    }
+
+    /// [`Game::damage_mech`] for weapons that work out their own share
+    /// against the shield, like lasers that are only partly soaked by it.
+    /// Of `(absorbed, through)`, the first comes off the shield and the
+    /// second goes to the hull.
+    pub fn damage_mech_split(
+        &mut self,
+        mech_id: Uuid,
+        (absorbed, through): (u32, u32),
+        position: WorldPos,
+        hit: Option<HitDirection>,
+        attacker: Option<Uuid>,
+        messages: &mut Vec<ServerMessage>,
+    ) -> DamageDealt {
+        let Some(mech) = self.mechs.get_mut(&mech_id).filter(|mech| mech.health > 0) else {
+            return DamageDealt::default();
+        };
+        let absorbed = absorbed.min(mech.shield);
+        let shield_broken = absorbed > 0 && absorbed == mech.shield;
+        mech.shield -= absorbed;
+        let armor = hit.map_or(1.0, |hit| hit.arc.multiplier());
+        let hull = (through as f32 * armor).round() as u32;
+        mech.health = mech.health.saturating_sub(hull);
+        let health_remaining = mech.health;
+        let dealt = DamageDealt {
+            absorbed,
+            hull,
+            destroyed: health_remaining == 0,
+        };
+
+        if absorbed > 0 {
+            messages.push(self.shield_impact(mech_id, position, absorbed));
+        }
+        if shield_broken {
+            if let Some(attacker) = attacker {
+                self.events
+                    .publish(GameEvent::MechShieldBroken { mech_id, attacker });
+            }
+            messages.push(self.apply_status_effect(
+                mech_id,
+                StatusEffectKind::Emp,
+                SHIELD_BREAK_EMP_DURATION,
+                1.0,
+            ));
+        }
+        if hull == 0 {
+            return dealt;
+        }
+
+        messages.push(ServerMessage::MechDamaged {
+            mech_id,
+            damage: absorbed + hull,
+            health_remaining,
+            position,
+            hit,
+        });
+        if dealt.destroyed {
+            self.destroy_mech(mech_id, messages);
+        } else {
+            messages.extend(self.apply_hull_damage(mech_id, hull));
+            messages.extend(self.interior_impact(mech_id, hull, hit));
+        }
+        dealt
+    }
+
+    /// Flash the bubble where it was struck and report what's left of it
+    fn shield_impact(&mut self, mech_id: Uuid, position: WorldPos, absorbed: u32) -> ServerMessage {
+        let shield_remaining = self.mechs.get(&mech_id).map_or(0, |m| m.shield);
+        self.create_effect(
+            EffectType::ShieldHit,
+            position,
+            0.4,                  // 0.4 second duration
+            1.0,                  // Full intensity
+            (0.3, 0.7, 1.0, 1.0), // Shield blue
+        );
+        ServerMessage::ShieldImpact {
+            mech_id,
+            position,
+            absorbed,
+            shield_remaining,
+            collapsed: shield_remaining == 0,
+        }
+    }
+
+    /// A mech's hull gave out: its crew are thrown clear back to their
+    /// spawn and it goes up in a large explosion
+    pub fn destroy_mech(&mut self, mech_id: Uuid, messages: &mut Vec<ServerMessage>) {
+        let aboard: Vec<Uuid> = self
+            .players
+            .values()
+            .filter(|player| player.location.mech_id() == Some(mech_id))
+            .map(|player| player.id)
+            .collect();
+        for player_id in aboard {
+            if let Some(player) = self.players.get_mut(&player_id) {
+                let spawn_pos = self.arena_map.player_spawn(player.team);
+                player.location = PlayerLocation::OutsideWorld(spawn_pos);
+                player.inventory.clear();
+                player.operating_station = None;
+
+                messages.push(ServerMessage::PlayerMoved {
+                    player_id,
+                    location: player.location,
+                });
+                messages.push(player.inventory_message());
+            }
+        }
+
+        if let Some(mech) = self.mechs.get(&mech_id) {
+            let explosion_pos = mech.world_position;
+            let wreck_pos = MechPositioning::mech_center(mech.position);
+            messages.push(self.add_decal(wreck_pos, DecalKind::Scorch));
+            let explosion_color = (1.0, 0.2, 0.0, 1.0); // Red explosion
+            self.create_effect(
+                EffectType::Explosion,
+                explosion_pos,
+                3.0, // 3 second duration
+                2.0, // High intensity
+                explosion_color,
+            );
+        }
+
+        // TODO: Respawn mech after some time
+        log::info!("Mech {mech_id} destroyed");
+    }
+
+    /// Roll for interior fires and hull breaches after a mech takes hull damage
+    pub fn apply_hull_damage(&mut self, mech_id: Uuid, damage: u32) -> Vec<ServerMessage> {
+        use rand::seq::SliceRandom;
+        use rand::Rng;
+
+        let mut messages = Vec::new();
+        let rng = &mut self.rng;
+        let Some(mech) = self.mechs.get_mut(&mech_id) else {
+            return messages;
+        };
+        if damage == 0 || mech.health == 0 {
+            return messages;
+        }
+
+        let fire_chance = (damage as f32 * FIRE_CHANCE_PER_DAMAGE).min(1.0);
+        if mech.fires.len() < MAX_FIRES_PER_MECH && rng.gen::<f32>() < fire_chance {
+            let candidates: Vec<MechInteriorPos> = mech
+                .interior
+                .floors
+                .iter()
+                .enumerate()
+                .flat_map(|(floor, map)| {
+                    map.static_tiles
+                        .iter()
+                        .filter(|(_, tile)| tile.is_flammable())
+                        .map(move |(pos, _)| MechInteriorPos::new(floor as u8, *pos))
+                })
+                .filter(|pos| !mech.fires.contains_key(pos))
+                .collect();
+            if let Some(pos) = sorted(candidates).choose(rng) {
+                mech.fires.insert(*pos, Fire::ignite());
+                messages.push(ServerMessage::MechFiresUpdated {
+                    mech_id,
+                    fires: mech.fire_states(),
+                });
+            }
+        }
+
+        let breach_chance = (damage as f32 * HULL_BREACH_CHANCE_PER_DAMAGE).min(1.0);
+        if rng.gen::<f32>() < breach_chance {
+            let candidates: Vec<MechInteriorPos> = mech
+                .interior
+                .floors
+                .iter()
+                .enumerate()
+                .flat_map(|(floor, map)| {
+                    map.static_tiles
+                        .iter()
+                        .filter(|(_, tile)| matches!(tile, StaticTile::MetalWall))
+                        .map(move |(pos, _)| MechInteriorPos::new(floor as u8, *pos))
+                })
+                .collect();
+            if let Some(pos) = sorted(candidates).choose(rng).copied() {
+                self.set_mech_interior_tile(mech_id, pos, StaticTile::DamagedWall);
+                if let Some(message) = self.hull_breach_update(mech_id, pos) {
+                    messages.push(message);
+                }
+            }
+        }
+
+        messages
+    }
+
+    /// Throw the crew around after a hit that did at least
+    /// `INTERIOR_IMPACT_DAMAGE` to the hull: everyone aboard staggers,
+    /// operators may be thrown off their stations and cargo may spill out
+    /// onto the ground around the mech
+    pub fn interior_impact(
+        &mut self,
+        mech_id: Uuid,
+        damage: u32,
+        hit: Option<HitDirection>,
+    ) -> Vec<ServerMessage> {
+        use rand::Rng;
+
+        let mut messages = Vec::new();
+        let Some(mech) = self.mechs.get(&mech_id) else {
+            return messages;
+        };
+        if damage < INTERIOR_IMPACT_DAMAGE || mech.health == 0 {
+            return messages;
+        }
+        let severity = ((damage - INTERIOR_IMPACT_DAMAGE) as f32
+            / (INTERIOR_IMPACT_FULL_DAMAGE - INTERIOR_IMPACT_DAMAGE) as f32)
+            .clamp(0.0, 1.0);
+        let center = MechPositioning::mech_center(mech.position).to_tile_pos();
+
+        let mut crew: Vec<Uuid> = self
+            .players
+            .values()
+            .filter(|p| {
+                matches!(p.location, PlayerLocation::InsideMech { mech_id: inside, .. } if inside == mech_id)
+            })
+            .map(|p| p.id)
+            .collect();
+        crew.sort_unstable();
+        for player_id in crew {
+            messages.push(self.apply_status_effect(
+                player_id,
+                StatusEffectKind::Stunned,
+                INTERIOR_IMPACT_STAGGER_DURATION,
+                1.0,
+            ));
+            if self.rng.gen::<f32>() < INTERIOR_IMPACT_KNOCKOFF_CHANCE * severity {
+                self.leave_station(player_id, &mut messages);
+            }
+        }
+
+        let mut spilled = 0;
+        let reach = MECH_SIZE_TILES / 2 + INTERIOR_IMPACT_SPILL_RADIUS;
+        for _ in 0..INTERIOR_IMPACT_SPILL_MAX {
+            if self.rng.gen::<f32>() >= INTERIOR_IMPACT_SPILL_CHANCE * severity {
+                continue;
+            }
+            let Some(mech) = self.mechs.get(&mech_id) else {
+                break;
+            };
+            let held: Vec<ResourceType> = ResourceType::ALL
+                .into_iter()
+                .filter(|resource_type| {
+                    mech.resource_inventory
+                        .get(resource_type)
+                        .is_some_and(|&n| n > 0)
+                })
+                .collect();
+            if held.is_empty() {
+                break;
+            }
+            let resource_type = held[self.rng.gen_range(0..held.len())];
+            let mut landing = None;
+            for _ in 0..10 {
+                let pos = center.offset(
+                    self.rng.gen_range(-reach..=reach),
+                    self.rng.gen_range(-reach..=reach),
+                );
+                if self.can_drop_resource(pos) {
+                    landing = Some(pos);
+                    break;
+                }
+            }
+            let Some(pos) = landing else {
+                continue;
+            };
+
+            if let Some(count) = self
+                .mechs
+                .get_mut(&mech_id)
+                .and_then(|mech| mech.resource_inventory.get_mut(&resource_type))
+            {
+                *count -= 1;
+            }
+            let resource_id = self.spawn_resource_with_behavior(pos, resource_type);
+            messages.push(ServerMessage::ResourceSpawned {
+                resource_id,
+                position: pos,
+                resource_type,
+            });
+            spilled += 1;
+        }
+        if spilled > 0 {
+            if let Some(mech) = self.mechs.get(&mech_id) {
+                messages.push(mech.inventory_message());
+            }
+        }
+
+        self.events.publish(GameEvent::InteriorImpact { mech_id, damage });
+        messages.push(ServerMessage::InteriorImpact {
+            mech_id,
+            severity,
+            hit,
+        });
+        messages
+    }
+
+    /// Whether a resource can be left lying at `pos`: open ground in the
+    /// arena, clear of every mech
+    pub fn can_drop_resource(&self, pos: TilePos) -> bool {
+        let in_arena = self.arena_map.in_bounds(pos);
+        let walkable = match self.tile_map.get_world_tile(pos) {
+            Some(TileContent::Static(tile)) => tile.is_walkable(),
+            Some(TileContent::Entity(_)) => false,
+            _ => true,
+        };
+        let under_mech = self
+            .mechs
+            .values()
+            .any(|mech| MechPositioning::is_inside_mech(pos.to_world_pos(), mech.position));
+
+        in_arena && walkable && !under_mech
+    }
+
+    /// Patch a breached wall tile, restoring the mech's lost max health
+    pub fn repair_hull_breach(&mut self, mech_id: Uuid, pos: MechInteriorPos) -> Option<ServerMessage> {
+        let breached = self
+            .mechs
+            .get(&mech_id)?
+            .interior
+            .floors
+            .get(pos.floor as usize)?
+            .static_tiles
+            .get(&pos.tile_pos)
+            .is_some_and(|tile| matches!(tile, StaticTile::DamagedWall));
+        if !breached {
+            return None;
+        }
+
+        self.set_mech_interior_tile(mech_id, pos, StaticTile::MetalWall);
+        self.hull_breach_update(mech_id, pos)
+    }
+
+    /// Build a barricade out of the mech's scrap on a clear floor tile within
+    /// the player's reach. Crews only build inside their own mech.
+    pub fn place_barricade(
+        &mut self,
+        player_id: Uuid,
+        position: TilePos,
+    ) -> GameResult<Vec<ServerMessage>> {
+        let (mech_id, floor) = self.barricade_site(player_id, position)?;
+        let mech = self
+            .mechs
+            .get(&mech_id)
+            .ok_or_else(|| GameError::mech_not_found(mech_id))?;
+        let floor_map = &mech.interior.floors[floor as usize];
+        let site = MechInteriorPos::new(floor, position);
+        let occupied = self.players.values().any(|player| {
+            player.location == PlayerLocation::InsideMech { mech_id, pos: site }
+        });
+        // Stairs and the entrance stay open so nobody gets shut in
+        let transition = matches!(
+            floor_map.static_tiles.get(&position),
+            Some(StaticTile::TransitionZone { .. })
+        );
+        if occupied
+            || transition
+            || floor_map.barricades.contains_key(&position)
+            || !crate::movement::is_interior_walkable(floor_map, position)
+        {
+            return Err(GameError::TileNotClear {
+                x: position.x,
+                y: position.y,
+            });
+        }
+        let built: usize = mech
+            .interior
+            .floors
+            .iter()
+            .map(|floor_map| floor_map.barricades.len())
+            .sum();
+        if built >= MAX_BARRICADES_PER_MECH {
+            return Err(GameError::TooManyBarricades {
+                id: mech_id,
+                max: MAX_BARRICADES_PER_MECH,
+            });
+        }
+
+        let Some(mech) = self.mechs.get_mut(&mech_id) else {
+            return Err(GameError::mech_not_found(mech_id));
+        };
+        let cost = HashMap::from([(ResourceType::ScrapMetal, BARRICADE_SCRAP_COST)]);
+        if !mech.try_spend(&cost) {
+            return Err(GameError::InsufficientResources {
+                resource_type: ResourceType::ScrapMetal,
+                required: BARRICADE_SCRAP_COST,
+                available: mech
+                    .resource_inventory
+                    .get(&ResourceType::ScrapMetal)
+                    .copied()
+                    .unwrap_or(0),
+            });
+        }
+        let barricade = Barricade::new(mech.team);
+        mech.interior.floors[floor as usize]
+            .barricades
+            .insert(position, barricade);
+
+        Ok(vec![
+            ServerMessage::BarricadeUpdated {
+                mech_id,
+                floor,
+                position,
+                barricade: Some(barricade),
+            },
+            mech.inventory_message(),
+        ])
+    }
+
+    /// Take down one of the crew's barricades within the player's reach,
+    /// returning scrap in proportion to the health it has left
+    pub fn remove_barricade(
+        &mut self,
+        player_id: Uuid,
+        position: TilePos,
+    ) -> GameResult<Vec<ServerMessage>> {
+        let (mech_id, floor) = self.barricade_site(player_id, position)?;
+        let Some(mech) = self.mechs.get_mut(&mech_id) else {
+            return Err(GameError::mech_not_found(mech_id));
+        };
+        let barricade = mech.interior.floors[floor as usize]
+            .barricades
+            .remove(&position)
+            .ok_or(GameError::BarricadeNotFound {
+                x: position.x,
+                y: position.y,
+            })?;
+
+        let mut messages = vec![ServerMessage::BarricadeUpdated {
+            mech_id,
+            floor,
+            position,
+            barricade: None,
+        }];
+        let refund = BARRICADE_SCRAP_COST * barricade.health / BARRICADE_MAX_HEALTH;
+        if refund > 0 {
+            *mech
+                .resource_inventory
+                .entry(ResourceType::ScrapMetal)
+                .or_insert(0) += refund;
+            messages.push(mech.inventory_message());
+        }
+        Ok(messages)
+    }
+
+    /// Where a player can work on barricades: aboard their own team's mech,
+    /// off any station, with `position` within reach on their floor
+    fn barricade_site(&self, player_id: Uuid, position: TilePos) -> GameResult<(Uuid, u8)> {
+        let player = self
+            .players
+            .get(&player_id)
+            .ok_or_else(|| GameError::player_not_found(player_id))?;
+        if player.operating_station.is_some() {
+            return Err(GameError::OperatingStation { id: player_id });
+        }
+        let PlayerLocation::InsideMech { mech_id, pos } = player.location else {
+            return Err(GameError::InvalidPlayerLocation { id: player_id });
+        };
+        let mech = self
+            .mechs
+            .get(&mech_id)
+            .ok_or_else(|| GameError::mech_not_found(mech_id))?;
+        if mech.team != player.team {
+            return Err(GameError::WrongTeamMech {
+                player_team: player.team,
+                mech_team: mech.team,
+            });
+        }
+        if !crate::movement::tiles_in_reach(pos.tile_pos).contains(&position) {
+            return Err(GameError::invalid_input("That tile is out of reach"));
+        }
+        Ok((mech_id, pos.floor))
+    }
+
+    /// Knock `damage` off a barricade, tearing it down at zero. None when
+    /// there's no barricade there.
+    pub fn damage_barricade(
+        &mut self,
+        mech_id: Uuid,
+        pos: MechInteriorPos,
+        damage: u32,
+    ) -> Option<ServerMessage> {
+        let barricades = &mut self
+            .mechs
+            .get_mut(&mech_id)?
+            .interior
+            .floors
+            .get_mut(pos.floor as usize)?
+            .barricades;
+        let barricade = barricades.get_mut(&pos.tile_pos)?;
+        barricade.health = barricade.health.saturating_sub(damage);
+        let barricade = (barricade.health > 0).then_some(*barricade);
+        if barricade.is_none() {
+            barricades.remove(&pos.tile_pos);
+        }
+        Some(ServerMessage::BarricadeUpdated {
+            mech_id,
+            floor: pos.floor,
+            position: pos.tile_pos,
+            barricade,
+        })
+    }
+
+    /// Action key fallback inside a mech: tool lockers, extinguishing fires
+    /// and starting hull repairs. Empty when there was nothing to interact with.
+    pub fn handle_damage_control(&mut self, player_id: Uuid) -> Vec<ServerMessage> {
+        let mut system_manager = std::mem::take(&mut self.system_manager);
+        let messages = if let Some(repair_system) =
+            system_manager.get_system_mut::<crate::systems::repair::RepairSystem>()
+        {
+            repair_system.interact(self, player_id)
+        } else {
+            log::error!("Repair system not found in system manager");
+            Vec::new()
+        };
+        self.system_manager = system_manager;
+        messages
+    }
+
+    /// Swing at the closest enemy in reach
+    pub fn handle_melee_attack(&mut self, player_id: Uuid) -> GameResult<Vec<ServerMessage>> {
+        let mut system_manager = std::mem::take(&mut self.system_manager);
+        let result = match system_manager.get_system_mut::<crate::systems::boarding::BoardingSystem>()
+        {
+            Some(boarding_system) => boarding_system.attack(self, player_id),
+            None => Err(GameError::invalid_state("Boarding system not found")),
+        };
+        self.system_manager = system_manager;
+        result
+    }
+
+    /// Burst the player a few tiles the way `direction` points, stopping
+    /// short of walls and hulls
+    pub fn dash(&mut self, player_id: Uuid, direction: Facing) -> GameResult<Vec<ServerMessage>> {
+        let player = self
+            .players
+            .get(&player_id)
+            .ok_or_else(|| GameError::player_not_found(player_id))?;
+        if player.operating_station.is_some() {
+            return Err(GameError::OperatingStation { id: player_id });
+        }
+        let stunned = self
+            .entity_storage
+            .status_effects
+            .get(&player_id)
+            .is_some_and(|effects| effects.has(StatusEffectKind::Stunned));
+        if stunned {
+            return Err(GameError::BlockedByStatusEffect {
+                id: player_id,
+                effect: StatusEffectKind::Stunned,
+            });
+        }
+        if player.dash_ready_at > self.match_time {
+            return Err(GameError::AbilityOnCooldown {
+                ability: "Dash",
+                remaining_seconds: player.dash_ready_at - self.match_time,
+            });
+        }
+
+        let from = player.location;
+        let to = crate::movement::dash_destination(self, from, player.team, direction).ok_or_else(
+            || {
+                let blocked = match from {
+                    PlayerLocation::OutsideWorld(pos) => pos,
+                    PlayerLocation::InsideMech { pos, .. } => pos.tile_pos.to_world_center(),
+                };
+                GameError::CollisionDetected {
+                    x: blocked.x,
+                    y: blocked.y,
+                }
+            },
+        )?;
+
+        let match_time = self.match_time;
+        let Some(player) = self.players.get_mut(&player_id) else {
+            return Err(GameError::player_not_found(player_id));
+        };
+        player.location = to;
+        player.dash_ready_at = match_time + DASH_COOLDOWN;
+        let mut messages = Vec::from_iter(player.face(direction));
+        messages.push(ServerMessage::PlayerDashed {
+            player_id,
+            from,
+            to,
+        });
+        Ok(messages)
+    }
+
+    /// Sweep for enemy mechs from a mech's radar
+    pub fn radar_sweep(&mut self, mech_id: Uuid) -> GameResult<Vec<ServerMessage>> {
+        let mut system_manager = std::mem::take(&mut self.system_manager);
+        let result = match system_manager.get_system_mut::<crate::systems::radar::RadarSystem>() {
+            Some(radar_system) => radar_system.sweep(self, mech_id),
+            None => Err(GameError::invalid_state("Radar system not found")),
+        };
+        self.system_manager = system_manager;
+        result
+    }
+
+    /// Fire a point-defense interceptor from a mech at the nearest incoming shot
+    pub fn fire_point_defense(&mut self, mech_id: Uuid) -> GameResult<Vec<ServerMessage>> {
+        let mut system_manager = std::mem::take(&mut self.system_manager);
+        let result = match system_manager
+            .get_system_mut::<crate::systems::point_defense::PointDefenseSystem>()
+        {
+            Some(point_defense) => point_defense.fire(self, mech_id),
+            None => Err(GameError::invalid_state("Point defense system not found")),
+        };
+        self.system_manager = system_manager;
+        result
+    }
+
+    /// Start a stomp from a mech, credited to its pilot
+    pub fn stomp(&mut self, mech_id: Uuid, pilot: Option<Uuid>) -> GameResult<Vec<ServerMessage>> {
+        let mut system_manager = std::mem::take(&mut self.system_manager);
+        let result = match system_manager.get_system_mut::<crate::systems::stomp::StompSystem>() {
+            Some(stomp_system) => stomp_system.start(self, mech_id, pilot),
+            None => Err(GameError::invalid_state("Stomp system not found")),
+        };
+        self.system_manager = system_manager;
+        result
+    }
+
+    /// Brace a mech for a collision, as its pilot
+    pub fn brace(&mut self, mech_id: Uuid) -> GameResult<Vec<ServerMessage>> {
+        let mut system_manager = std::mem::take(&mut self.system_manager);
+        let result = match system_manager.get_system_mut::<crate::systems::ramming::RammingSystem>()
+        {
+            Some(ramming_system) => ramming_system.brace(self, mech_id),
+            None => Err(GameError::invalid_state("Ramming system not found")),
+        };
+        self.system_manager = system_manager;
+        result
+    }
+
+    /// What the camera director is showing spectators right now
+    pub fn camera_suggestion(&mut self) -> Option<ServerMessage> {
+        self.system_manager
+            .get_system_mut::<crate::systems::camera_director::CameraDirectorSystem>()
+            .and_then(|director| director.suggestion())
+    }
+
+    /// Start or stop sabotaging an enemy station
+    pub fn handle_sabotage(
+        &mut self,
+        player_id: Uuid,
+        mech_id: Uuid,
+        station_id: Uuid,
+    ) -> Vec<ServerMessage> {
+        let mut system_manager = std::mem::take(&mut self.system_manager);
+        let messages = if let Some(boarding_system) =
+            system_manager.get_system_mut::<crate::systems::boarding::BoardingSystem>()
+        {
+            boarding_system.sabotage(self, player_id, mech_id, station_id)
+        } else {
+            log::error!("Boarding system not found in system manager");
+            Vec::new()
+        };
+        self.system_manager = system_manager;
+        messages
+    }
+
+    /// Replace an interior tile in both the mech layout and the server tile map
+    fn set_mech_interior_tile(&mut self, mech_id: Uuid, pos: MechInteriorPos, tile: StaticTile) {
+        if let Some(floor) = self
+            .mechs
+            .get_mut(&mech_id)
+            .and_then(|mech| mech.interior.floors.get_mut(pos.floor as usize))
+        {
+            floor.static_tiles.insert(pos.tile_pos, tile);
+        }
+        if let Some(floor) = self
+            .tile_map
+            .mech_tiles
+            .get_mut(&mech_id)
+            .and_then(|mech_tiles| mech_tiles.get_floor_mut(pos.floor as usize))
+        {
+            floor.set_static_tile(pos.tile_pos, tile);
+        }
+    }
+
+    fn hull_breach_update(&mut self, mech_id: Uuid, pos: MechInteriorPos) -> Option<ServerMessage> {
+        let mech = self.mechs.get_mut(&mech_id)?;
+        let max_health = mech.recalculate_max_health();
+        let breached = mech.interior.floors[pos.floor as usize]
+            .static_tiles
+            .get(&pos.tile_pos)
+            .is_some_and(|tile| matches!(tile, StaticTile::DamagedWall));
+
+        Some(ServerMessage::HullBreachUpdated {
+            mech_id,
+            floor: pos.floor,
+            position: pos.tile_pos,
+            breached,
+            max_health,
+        })
+    }
+
+    /// Spawn or remove AI crew so every team has at least the configured
+    /// minimum number of players. Returns the messages to broadcast; new
+    /// crew go out in a full state, as AIs added through `/ai/add` do.
+    pub fn balance_ai_crew(&mut self) -> Vec<ServerMessage> {
+        let mut messages = Vec::new();
+        let mut spawned = false;
+        let Some(min_crew) = self.ai_autofill_min_crew else {
+            return messages;
+        };
+
+        let mut system_manager = std::mem::take(&mut self.system_manager);
+        let Some(ai_system) = system_manager.get_system_mut::<crate::systems::ai::AISystem>()
+        else {
+            log::error!("AI system not found in system manager");
+            self.system_manager = system_manager;
+            return messages;
+        };
+
+        for team in [TeamId::Red, TeamId::Blue] {
+            let ai_ids = ai_system.ai_players_on_team(team);
+            let human_count = self
+                .players
+                .values()
+                .filter(|p| p.team == team && !ai_system.is_ai_player(p.id))
+                .count();
+            let wanted_ai = min_crew.saturating_sub(human_count);
+
+            if ai_ids.len() < wanted_ai {
+                for i in ai_ids.len()..wanted_ai {
+                    let personality = AUTOFILL_PERSONALITIES[i % AUTOFILL_PERSONALITIES.len()];
+                    let (ai_id, mut player) = ai_system.add_ai_player_to_team(
+                        AI_AUTOFILL_DIFFICULTY,
+                        Some(personality),
+                        team,
+                        &mut self.rng,
+                    );
+                    player.location =
+                        PlayerLocation::OutsideWorld(self.arena_map.player_spawn(team));
+                    log::info!("Autofill: spawned {} ({ai_id}) on team {team:?}", player.name);
+                    self.players.insert(ai_id, player);
+                    spawned = true;
+                }
+            } else if ai_ids.len() > wanted_ai {
+                // Prefer removing AI that are not busy at a station
+                let mut removable = ai_ids;
+                removable.sort_by_key(|id| {
+                    self.players
+                        .get(id)
+                        .is_some_and(|p| p.operating_station.is_some())
+                });
+
+                let excess = removable.len() - wanted_ai;
+                for ai_id in removable.into_iter().take(excess) {
+                    ai_system.remove_ai_player(ai_id);
+
+                    // Exit the station first so the seat is free for the human
+                    let station_id = self
+                        .players
+                        .get_mut(&ai_id)
+                        .and_then(|p| p.operating_station.take());
+                    if let Some(station_id) = station_id {
+                        for mech in self.mechs.values_mut() {
+                            if let Some(station) = mech.stations.get_mut(&station_id) {
+                                station.operated_by = None;
+                                messages.push(ServerMessage::PlayerExitedStation {
+                                    player_id: ai_id,
+                                    station_id,
+                                });
+                                break;
+                            }
+                        }
+                    }
+
+                    self.players.remove(&ai_id);
+                    self.entity_storage.status_effects.remove(&ai_id);
+                    log::info!("Autofill: removed AI {ai_id} from team {team:?}");
+                    messages.push(ServerMessage::PlayerDisconnected { player_id: ai_id });
+                }
+            }
+        }
+
+        self.system_manager = system_manager;
+        if spawned {
+            messages.push(self.get_full_state());
+        }
+        messages
+    }
+
+    /// Age the idle timers of human players. Anyone who just went away is
+    /// let go of their station, which an AI stand-in takes over when the
+    /// room allows it.
+    fn update_idle(&mut self, delta_time: f32) -> Vec<ServerMessage> {
+        let mut messages = Vec::new();
+        let mut system_manager = std::mem::take(&mut self.system_manager);
+        let mut ai_system = system_manager.get_system_mut::<crate::systems::ai::AISystem>();
+        let humans: Vec<Uuid> = self
+            .players
+            .keys()
+            .copied()
+            .filter(|id| ai_system.as_ref().is_none_or(|ai| !ai.is_ai_player(*id)))
+            .collect();
+
+        for player_id in self.idle.tick(delta_time, humans) {
+            messages.push(ServerMessage::PlayerAfk {
+                player_id,
+                afk: true,
+            });
+            let Some(station_id) = self.leave_station(player_id, &mut messages) else {
+                continue;
+            };
+            if !self.idle.stand_ins {
+                continue;
+            }
+            let (Some(ai_system), Some(player)) =
+                (ai_system.as_mut(), self.players.get(&player_id))
+            else {
+                continue;
+            };
+
+            let (ai_id, mut stand_in) = ai_system.add_stand_in(
+                AI_AUTOFILL_DIFFICULTY,
+                player.team,
+                &player.name,
+                &mut self.rng,
+            );
+            stand_in.location = player.location;
+            stand_in.operating_station = Some(station_id);
+            for mech in self.mechs.values_mut() {
+                if let Some(station) = mech.stations.get_mut(&station_id) {
+                    station.operated_by = Some(ai_id);
+                }
+            }
+            log::info!("{} stands in for away player {player_id}", stand_in.name);
+            self.players.insert(ai_id, stand_in);
+            self.idle.cover(player_id, ai_id);
+            messages.push(ServerMessage::PlayerEnteredStation {
+                player_id: ai_id,
+                station_id,
+            });
+        }
+
+        self.system_manager = system_manager;
+        messages
+    }
+
+    /// A player did something: restart their idle timer, and if they were
+    /// away, welcome them back and send their stand-in home
+    pub fn mark_active(&mut self, player_id: Uuid) -> Vec<ServerMessage> {
+        if !self.idle.note_input(player_id) {
+            return Vec::new();
+        }
+        let mut messages = vec![ServerMessage::PlayerAfk {
+            player_id,
+            afk: false,
+        }];
+        messages.extend(self.dismiss_stand_in(player_id));
+        messages
+    }
+
+    /// Let go of dropped players nobody came back for
+    fn update_sessions(&mut self, delta_time: f32) -> Vec<ServerMessage> {
+        let mut messages = Vec::new();
+        for player_id in self.sessions.tick(delta_time) {
+            log::info!("Player {player_id} didn't come back in time");
+            messages.extend(self.drop_player(player_id));
+        }
+        messages
+    }
+
+    /// Take a player or spectator who left out of the match for good,
+    /// telling everyone
+    pub fn drop_player(&mut self, player_id: Uuid) -> Vec<ServerMessage> {
+        self.spectators.remove(&player_id);
+        self.collision_debug.forget(player_id);
+        let mut messages = vec![ServerMessage::PlayerDisconnected { player_id }];
+        messages.extend(self.dismiss_stand_in(player_id));
+        self.remove_player(&player_id);
+        messages.extend(self.balance_ai_crew());
+        messages
+    }
+
+    /// Remove the AI covering for a player, freeing the station it held
+    pub fn dismiss_stand_in(&mut self, player_id: Uuid) -> Vec<ServerMessage> {
+        let mut messages = Vec::new();
+        let Some(ai_id) = self.idle.take_stand_in(player_id) else {
+            return messages;
+        };
+        let mut system_manager = std::mem::take(&mut self.system_manager);
+        if let Some(ai_system) = system_manager.get_system_mut::<crate::systems::ai::AISystem>() {
             ai_system.remove_ai_player(ai_id);
-        } else {
-            log::error!("AI system not found in system manager");
         }
         self.system_manager = system_manager;
+
+        self.leave_station(ai_id, &mut messages);
+        self.remove_player(&ai_id);
+        messages.push(ServerMessage::PlayerDisconnected { player_id: ai_id });
+        messages
     }
 
-    /// Get list of all AI players
-    pub fn get_ai_players(&self) -> Vec<Uuid> {
-        self.players
-            .iter()
-            .filter(|(_, p)| p.name.starts_with("AI_"))
-            .map(|(id, _)| *id)
-            .collect()
+    /// Get a player up from the station they're crewing, if any
+    fn leave_station(
+        &mut self,
+        player_id: Uuid,
+        messages: &mut Vec<ServerMessage>,
+    ) -> Option<Uuid> {
+        let station_id = self.players.get_mut(&player_id)?.operating_station.take()?;
+        for mech in self.mechs.values_mut() {
+            if let Some(station) = mech.stations.get_mut(&station_id) {
+                station.operated_by = None;
+            }
+        }
+        messages.push(ServerMessage::PlayerExitedStation {
+            player_id,
+            station_id,
+        });
+        Some(station_id)
+    }
+
+    /// Send a downed player back to their team spawn with empty hands and
+    /// full health
+    pub fn knock_out(&mut self, player_id: Uuid, killer: Option<Uuid>) -> Vec<ServerMessage> {
+        let mut messages = Vec::new();
+        let Some(player) = self.players.get_mut(&player_id) else {
+            return messages;
+        };
+        let respawn_position = self.arena_map.player_spawn(player.team);
+        let station_id = player.operating_station.take();
+        let dropped_tool = player.held_tool.take().is_some();
+        player.location = PlayerLocation::OutsideWorld(respawn_position);
+        player.health = PLAYER_MAX_HEALTH;
+        player.inventory.clear();
+        messages.push(player.inventory_message());
+
+        if let Some(station_id) = station_id {
+            for mech in self.mechs.values_mut() {
+                if let Some(station) = mech.stations.get_mut(&station_id) {
+                    station.operated_by = None;
+                }
+            }
+            messages.push(ServerMessage::PlayerExitedStation {
+                player_id,
+                station_id,
+            });
+        }
+        if dropped_tool {
+            messages.push(ServerMessage::PlayerToolChanged {
+                player_id,
+                tool: None,
+            });
+        }
+        messages.push(ServerMessage::PlayerKilled {
+            player_id,
+            killer,
+            respawn_position,
+        });
+        messages.push(self.apply_status_effect(
+            player_id,
+            StatusEffectKind::SpawnProtected,
+            SPAWN_PROTECTION_DURATION,
+            1.0,
+        ));
+        self.events.publish(GameEvent::PlayerKilled {
+            victim: player_id,
+            killer,
+        });
+
+        messages
     }
 
     pub fn create_initial_mechs(&mut self) {
         // Red team mech
-        let red_mech_pos = TilePos::new(RED_MECH_SPAWN.0, RED_MECH_SPAWN.1);
+        let red_mech_pos = self
+            .arena_map
+            .mech_spawn(TeamId::Red)
+            .unwrap_or(TilePos::new(RED_MECH_SPAWN.0, RED_MECH_SPAWN.1));
         let red_mech = self.create_mech(red_mech_pos, TeamId::Red);
         let red_mech_id = red_mech.id;
         self.mechs.insert(red_mech.id, red_mech);
 
         // Blue team mech
-        let blue_mech_pos = TilePos::new(BLUE_MECH_SPAWN.0, BLUE_MECH_SPAWN.1);
+        let blue_mech_pos = self
+            .arena_map
+            .mech_spawn(TeamId::Blue)
+            .unwrap_or(TilePos::new(BLUE_MECH_SPAWN.0, BLUE_MECH_SPAWN.1));
         let blue_mech = self.create_mech(blue_mech_pos, TeamId::Blue);
         let blue_mech_id = blue_mech.id;
         self.mechs.insert(blue_mech.id, blue_mech);
@@ -271,6 +1865,36 @@ impl Game {
         // Update tiles for both mechs
         self.update_mech_tiles(red_mech_id, red_mech_pos);
         self.update_mech_tiles(blue_mech_id, blue_mech_pos);
+
+        self.spawn_team_structures(TeamId::Red, red_mech_pos);
+        self.spawn_team_structures(TeamId::Blue, blue_mech_pos);
+    }
+
+    /// A team's repair bay and resupply depot, built around its mech spawn
+    fn spawn_team_structures(&mut self, team: TeamId, mech_spawn: TilePos) {
+        use shared::components::*;
+
+        let structures = TeamStructure::base_for(
+            team,
+            mech_spawn,
+            self.arena_map.width,
+            self.arena_map.height,
+        );
+        for structure in structures {
+            let entity_id = self
+                .entity_storage
+                .create_entity(self.rng.uuid(), format!("{team:?}_{:?}", structure.kind));
+            self.entity_storage.add_position(
+                entity_id,
+                Position {
+                    tile: structure.position,
+                    world: structure.position.to_world_pos(),
+                    floor: None,
+                    mech_id: None,
+                },
+            );
+            self.entity_storage.structures.insert(entity_id, structure);
+        }
     }
 
     pub fn update_player_visibility(&mut self, tx: &broadcast::Sender<(Uuid, ServerMessage)>) {
@@ -279,7 +1903,7 @@ impl Game {
             return;
         }
 
-        // Calculate visibility for each player
+        // Sight comes from the scheduler, which recasts it as players move
         for (player_id, player) in &self.players {
             let world_pos = match player.location {
                 PlayerLocation::OutsideWorld(pos) => pos,
@@ -293,18 +1917,13 @@ impl Game {
                 },
             };
 
-            // Calculate visibility using the vision system
-            let visibility = self.vision_system.calculate_visibility(
-                *player_id,
-                world_pos,
-                100.0, // Base vision range
-                &self.tile_map,
-                &self.entity_storage,
-            );
+            let Some(visibility) = self.vision_scheduler.visible(*player_id) else {
+                continue;
+            };
 
             // Convert visible tiles to visuals
             let mut visible_tiles = Vec::new();
-            for tile_pos in &visibility.visible_tiles {
+            for tile_pos in visibility {
                 if let Some(tile_content) = self.tile_map.get_world_tile(*tile_pos) {
                     let visual = match tile_content {
                         TileContent::Empty => continue,
@@ -352,6 +1971,7 @@ impl Game {
                                 TileVisual::Station {
                                     station_type: station.station_type,
                                     active: station.operating,
+                                    level: self.station_level_at(*tile_pos),
                                 }
                             } else {
                                 continue;
@@ -374,15 +1994,232 @@ impl Game {
         }
     }
 
+    /// Tiles each team can currently see: line of sight from every crew
+    /// member outside plus a sensor sweep around each of the team's mechs.
+    /// Crew inside a mech see through its sensors.
+    pub fn team_visibility(&mut self) -> HashMap<TeamId, HashSet<TilePos>> {
+        let mut teams: HashMap<TeamId, HashSet<TilePos>> = HashMap::new();
+
+        // Anyone who arrived since the last tick still needs a first look
+        self.observe_viewers();
+        let (tile_map, storage, sight) = (&self.tile_map, &self.entity_storage, self.sight);
+        let range = self.environment.vision_range();
+        self.vision_scheduler
+            .catch_up(&mut |viewpoint| cast_sight(tile_map, storage, sight, range, viewpoint));
+
+        for player in self.players.values() {
+            if !matches!(player.location, PlayerLocation::OutsideWorld(_)) {
+                continue;
+            }
+            if let Some(visible) = self.vision_scheduler.visible(player.id) {
+                teams
+                    .entry(player.team)
+                    .or_default()
+                    .extend(visible.iter().copied());
+            }
+        }
+
+        // Sensors aren't line of sight, they sweep everything in range
+        for mech in self.mechs.values() {
+            let center = MechPositioning::mech_center(mech.position).to_tile_pos();
+            let visible = teams.entry(mech.team).or_default();
+            for dy in -MECH_SENSOR_RANGE..=MECH_SENSOR_RANGE {
+                for dx in -MECH_SENSOR_RANGE..=MECH_SENSOR_RANGE {
+                    if dx * dx + dy * dy <= MECH_SENSOR_RANGE * MECH_SENSOR_RANGE {
+                        visible.insert(center.offset(dx, dy));
+                    }
+                }
+            }
+        }
+
+        teams
+    }
+
+    /// Tell the vision scheduler where everyone is and what changed
+    fn observe_viewers(&mut self) {
+        let changed = self.tile_map.take_changed_tiles();
+        self.vision_scheduler
+            .invalidate(&changed, PLAYER_VISION_RANGE.ceil() as i32);
+        let players = &self.players;
+        self.vision_scheduler.retain(|id| players.contains_key(id));
+
+        for player in self.players.values() {
+            let viewpoint = match player.location {
+                PlayerLocation::OutsideWorld(pos) => {
+                    let facing = self.sight.is_directional().then_some(player.facing);
+                    (pos.to_tile_pos(), None, facing)
+                }
+                PlayerLocation::InsideMech { mech_id, pos } => {
+                    let Some(mech) = self.mechs.get(&mech_id) else {
+                        continue;
+                    };
+                    let world = pos.to_world_with_mech(mech.world_position);
+                    (world.to_tile_pos(), Some(pos.floor()), None)
+                }
+            };
+            self.vision_scheduler.observe(player.id, viewpoint);
+        }
+    }
+
+    /// Recast line of sight for players who moved, within this tick's budget
+    #[profiling::function]
+    pub fn schedule_vision(&mut self) {
+        self.observe_viewers();
+        let (tile_map, storage, sight) = (&self.tile_map, &self.entity_storage, self.sight);
+        let range = self.environment.vision_range();
+        self.vision_scheduler
+            .run(|viewpoint| cast_sight(tile_map, storage, sight, range, viewpoint));
+    }
+
+    /// Send every player the tiles their team can see
+    #[profiling::function]
+    pub fn update_team_vision(&mut self, tx: &broadcast::Sender<(Uuid, ServerMessage)>) {
+        if !self.team_vision || !self.tick_count.is_multiple_of(TEAM_VISION_UPDATE_TICKS) {
+            return;
+        }
+
+        let teams = self.team_visibility();
+        for player in self.players.values() {
+            let visible_tiles = teams
+                .get(&player.team)
+                .map(|tiles| tiles.iter().copied().collect())
+                .unwrap_or_default();
+            let _ = tx.send((player.id, ServerMessage::TeamVisionUpdate { visible_tiles }));
+        }
+    }
+
+    /// Send the collision shapes to everyone who asked for verbose state
+    pub fn send_collision_shapes(&self, tx: &broadcast::Sender<(Uuid, ServerMessage)>) {
+        if !self.collision_debug.is_watched()
+            || !self
+                .tick_count
+                .is_multiple_of(COLLISION_SHAPES_UPDATE_TICKS)
+        {
+            return;
+        }
+        let shapes = self.collision_shapes();
+        for connection_id in self.collision_debug.subscribers() {
+            let shapes = shapes.clone();
+            let _ = tx.send((connection_id, ServerMessage::CollisionShapes { shapes }));
+        }
+    }
+
+    /// Hitboxes, hulls, shields, shot paths and station reach as the server
+    /// checks them right now
+    pub fn collision_shapes(&self) -> CollisionShapes {
+        let players = self
+            .players
+            .values()
+            .filter_map(|player| {
+                let (center, aboard) = match player.location {
+                    PlayerLocation::OutsideWorld(position) => (position, None),
+                    PlayerLocation::InsideMech { mech_id, pos } => {
+                        let mech = self.mechs.get(&mech_id)?;
+                        let tile = MechInteriorCoordinates::interior_to_world(
+                            mech.position,
+                            pos.floor,
+                            pos.tile_pos,
+                        );
+                        (tile.to_world_center(), Some((mech_id, pos.floor)))
+                    }
+                };
+                Some(PlayerHitbox {
+                    player_id: player.id,
+                    center,
+                    radius: PLAYER_COLLISION_RADIUS * TILE_SIZE,
+                    aboard,
+                })
+            })
+            .collect();
+
+        let mechs = self
+            .mechs
+            .values()
+            .map(|mech| MechHitbox {
+                mech_id: mech.id,
+                hull: AABB::mech_bounds(mech.world_position),
+                shield: (mech.shield > 0).then(|| ShieldBubble::around(mech.position)),
+            })
+            .collect();
+
+        let projectiles = self
+            .projectiles
+            .values()
+            .map(|projectile| ProjectilePath {
+                id: projectile.id,
+                position: projectile.position,
+                velocity: projectile.velocity,
+                radius: PROJECTILE_COLLISION_RADIUS * TILE_SIZE,
+                trail: self.collision_debug.trail(projectile.id),
+            })
+            .collect();
+
+        // Stations are taken over from any tile touching them, so their
+        // reach is their footprint grown by a tile all round
+        let stations = self
+            .mechs
+            .values()
+            .flat_map(|mech| {
+                mech.stations.values().map(|station| {
+                    let size = get_station_size(station.station_type);
+                    let corner = |dx, dy| {
+                        MechInteriorCoordinates::interior_to_world(
+                            mech.position,
+                            station.floor,
+                            station.position.offset(dx, dy),
+                        )
+                        .to_world()
+                    };
+                    StationReach {
+                        station_id: station.id,
+                        mech_id: mech.id,
+                        floor: station.floor,
+                        area: AABB::new(
+                            corner(-1, -1),
+                            corner(size.width as i32 + 1, size.height as i32 + 1),
+                        ),
+                    }
+                })
+            })
+            .collect();
+
+        CollisionShapes {
+            tick: self.tick_count,
+            players,
+            mechs,
+            projectiles,
+            stations,
+        }
+    }
+
     fn create_mech(&mut self, position: TilePos, team: TeamId) -> Mech {
-        let id = Uuid::new_v4();
+        let id = self.rng.uuid();
         let mut mech_stations = HashMap::new();
-        let interior = MechLayoutGenerator::create_mech_interior(&mut mech_stations);
+        let mut interior = MechLayoutGenerator::create_mech_interior(&mut mech_stations);
+
+        // The layout draws station ids from the OS; swap them for ones off
+        // the match's seed, handed out in a fixed order
+        let mut layout: Vec<shared::mech_layout::MechStation> =
+            mech_stations.into_values().collect();
+        layout.sort_unstable_by_key(|station| {
+            (station.floor, station.position.x, station.position.y)
+        });
+        for mech_station in &mut layout {
+            let station_id = self.rng.uuid();
+            let floor = &mut interior.floors[mech_station.floor as usize];
+            for owner in floor.multi_tile_stations.values_mut() {
+                if *owner == mech_station.id {
+                    *owner = station_id;
+                }
+            }
+            mech_station.id = station_id;
+        }
 
         // Convert MechStations to Stations using the registry
         let mut stations = HashMap::new();
-        for (station_id, mech_station) in mech_stations {
-            let station = self
+        for mech_station in layout {
+            let station_id = mech_station.id;
+            let mut station = self
                 .station_registry
                 .create_station(
                     mech_station.station_type,
@@ -390,6 +2227,8 @@ impl Game {
                     mech_station.position,
                 )
                 .expect("Failed to create station from registry");
+            // Keep the layout's id so the station is known by one id everywhere
+            station.id = station_id;
             stations.insert(station_id, station);
         }
 
@@ -406,12 +2245,21 @@ impl Game {
                 projectile_level: INITIAL_UPGRADE_LEVEL,
                 engine_level: INITIAL_UPGRADE_LEVEL,
                 shield_level: INITIAL_UPGRADE_LEVEL,
+                ecm_level: 0,
             },
             stations,
             interior,
             resource_inventory: HashMap::new(),
+            delivery_score: 0,
             velocity: (0.0, 0.0),
+            drive: MechDrive::default(),
+            fuel: MECH_FUEL_CAPACITY,
+            turret: MechTurret::default(),
+            point_defense: PointDefense::default(),
             world_position: position.to_world_pos(),
+            fires: HashMap::new(),
+            upgrade_target: None,
+            alarm: None,
         }
     }
 
@@ -494,6 +2342,38 @@ impl Game {
         self.create_mech_cargo_dropoff(mech_id);
     }
 
+    /// Give each of the map's scripted tiles an entity for the tile
+    /// behavior system to run
+    fn spawn_scripted_tiles(&mut self) {
+        for scripted in self.arena_map.scripted_tiles.clone() {
+            let entity_id = self.entity_storage.create_entity(
+                self.rng.uuid(),
+                format!("Script_{}_{}", scripted.position.x, scripted.position.y),
+            );
+            self.entity_storage.add_position(
+                entity_id,
+                shared::components::Position {
+                    tile: scripted.position,
+                    world: scripted.position.to_world_center(),
+                    floor: None,
+                    mech_id: None,
+                },
+            );
+            self.entity_storage
+                .tile_scripts
+                .insert(entity_id, scripted.script);
+        }
+    }
+
+    /// What to spawn at `pos`, rolled from the spawn table of its region
+    pub fn roll_resource_type(&mut self, pos: TilePos) -> ResourceType {
+        use rand::Rng;
+
+        let roll = self.rng.gen::<f32>();
+        self.spawn_tables
+            .sample(pos, self.arena_map.width, self.arena_map.height, roll)
+    }
+
     pub fn spawn_resource_with_behavior(
         &mut self,
         position: TilePos,
@@ -504,7 +2384,7 @@ impl Game {
         // Create the entity
         let entity_id = self
             .entity_storage
-            .create_entity(format!("Resource_{resource_type:?}"));
+            .create_entity(self.rng.uuid(), format!("Resource_{resource_type:?}"));
 
         // Add position
         self.entity_storage.add_position(
@@ -551,7 +2431,7 @@ impl Game {
         // Create the entity
         let entity_id = self
             .entity_storage
-            .create_entity("MechEntrance".to_string());
+            .create_entity(self.rng.uuid(), "MechEntrance".to_string());
 
         // Add position
         self.entity_storage.add_position(
@@ -610,7 +2490,7 @@ impl Game {
         let center_y = cargo_y + 1;
         let entity_id = self
             .entity_storage
-            .create_entity("CargoBayDropoff".to_string());
+            .create_entity(self.rng.uuid(), "CargoBayDropoff".to_string());
 
         // Add position (inside mech on floor 0)
         self.entity_storage.add_position(
@@ -641,10 +2521,11 @@ impl Game {
             ResourceType::ComputerComponents,
             ResourceType::Wiring,
             ResourceType::Batteries,
+            ResourceType::PowerCore,
         ] {
             let entity_id = self
                 .entity_storage
-                .create_entity(format!("CargoBay_{resource_type:?}"));
+                .create_entity(self.rng.uuid(), format!("CargoBay_{resource_type:?}"));
 
             self.entity_storage.add_position(
                 entity_id,
@@ -681,7 +2562,7 @@ impl Game {
         // Create the entity
         let entity_id = self
             .entity_storage
-            .create_entity("ResourceDropoff".to_string());
+            .create_entity(self.rng.uuid(), "ResourceDropoff".to_string());
 
         // Add position
         self.entity_storage.add_position(
@@ -715,16 +2596,20 @@ impl Game {
 
     pub fn spawn_initial_resources(&mut self) {
         use rand::Rng;
-        let mut rng = rand::thread_rng();
+
+        // Maps with placed spawners get exactly those resources
+        if !self.arena_map.resource_spawners.is_empty() {
+            for spawner in self.arena_map.resource_spawners.clone() {
+                let resource_type = spawner
+                    .resource_type
+                    .unwrap_or_else(|| self.roll_resource_type(spawner.position));
+                self.spawn_resource_with_behavior(spawner.position, resource_type);
+            }
+            return;
+        }
 
         // Spawn 5-8 initial resources randomly
-        let num_initial_resources = rng.gen_range(5..=8);
-        let resource_types = [
-            ResourceType::ScrapMetal,
-            ResourceType::ComputerComponents,
-            ResourceType::Wiring,
-            ResourceType::Batteries,
-        ];
+        let num_initial_resources = self.rng.gen_range(5..=8);
 
         for _ in 0..num_initial_resources {
             // Try to find a valid spawn position
@@ -733,8 +2618,8 @@ impl Game {
 
             while attempts < MAX_ATTEMPTS {
                 // Generate random position (avoiding edges)
-                let x = rng.gen_range(10..(ARENA_WIDTH_TILES - 10)) as i32;
-                let y = rng.gen_range(10..(ARENA_HEIGHT_TILES - 10)) as i32;
+                let x = self.rng.gen_range(10..(self.arena_map.width - 10));
+                let y = self.rng.gen_range(10..(self.arena_map.height - 10));
                 let pos = TilePos::new(x, y);
 
                 // Check if position is valid (simple check for initial spawn)
@@ -769,8 +2654,7 @@ impl Game {
                 }
 
                 if valid {
-                    // Pick a random resource type
-                    let resource_type = resource_types[rng.gen_range(0..resource_types.len())];
+                    let resource_type = self.roll_resource_type(pos);
                     self.spawn_resource_with_behavior(pos, resource_type);
                     break;
                 }
@@ -813,27 +2697,24 @@ impl Game {
         };
 
         // Spawn near team mech (but not inside it!)
-        let spawn_pos = match team {
-            TeamId::Red => WorldPos::new(
-                RED_PLAYER_SPAWN.0 * TILE_SIZE,
-                RED_PLAYER_SPAWN.1 * TILE_SIZE,
-            ),
-            TeamId::Blue => WorldPos::new(
-                BLUE_PLAYER_SPAWN.0 * TILE_SIZE,
-                BLUE_PLAYER_SPAWN.1 * TILE_SIZE,
-            ),
-        };
+        let spawn_pos = self.arena_map.player_spawn(team);
 
         let player = Player {
             id,
             name,
             team,
             location: PlayerLocation::OutsideWorld(spawn_pos),
-            carrying_resource: None,
+            inventory: Inventory::default(),
             operating_station: None,
+            held_tool: None,
+            health: PLAYER_MAX_HEALTH,
+            facing: Facing::default(),
+            dash_ready_at: 0.0,
+            scanner_ready_at: 0.0,
         };
 
         self.players.insert(id, player);
+        crate::hangar::board(self, id);
         (team, spawn_pos)
     }
 
@@ -850,8 +2731,168 @@ impl Game {
         }
 
         self.players.remove(player_id);
+        crate::hangar::leave(self, *player_id);
+        self.entity_storage.status_effects.remove(player_id);
+        self.lag_compensation.forget_player(*player_id);
+        self.voice.lock().unwrap().forget(*player_id);
+    }
+
+    /// Remember where mechs and players are so instant hits can be checked
+    /// against what a lagging shooter saw
+    fn record_positions(&mut self, delta_time: f32) {
+        let mechs = self
+            .mechs
+            .values()
+            .map(|mech| (mech.id, mech.world_position));
+        let players = self.players.values().map(|player| {
+            let mech_position = player
+                .location
+                .mech_id()
+                .and_then(|mech_id| self.mechs.get(&mech_id))
+                .map(|mech| mech.world_position);
+            (player.id, player.location.world_pos(mech_position))
+        });
+        self.lag_compensation
+            .record(delta_time, mechs.chain(players));
+    }
+
+    /// Credit a resource a player dropped off to a mech's inventory, or its
+    /// fuel tank for batteries, and the crew's delivery score
+    pub fn deliver_resource(
+        &mut self,
+        player_id: Uuid,
+        mech_id: Uuid,
+        resource_type: ResourceType,
+    ) -> Vec<ServerMessage> {
+        let Some(mech) = self.mechs.get_mut(&mech_id) else {
+            return Vec::new();
+        };
+        mech.delivery_score += DELIVERY_SCORE_PER_RESOURCE;
+        self.events.publish(GameEvent::ResourceDelivered {
+            player_id,
+            mech_id,
+            resource_type,
+        });
+        let delivered = ServerMessage::ResourceDelivered {
+            player_id,
+            mech_id,
+            resource_type,
+            delivery_score: mech.delivery_score,
+        };
+
+        // Batteries go down the fuel intake until the tank is full
+        if resource_type == ResourceType::Batteries && mech.fuel < MECH_FUEL_CAPACITY {
+            mech.fuel = (mech.fuel + FUEL_PER_BATTERY).min(MECH_FUEL_CAPACITY);
+            return vec![
+                delivered,
+                ServerMessage::MechFuelChanged {
+                    mech_id,
+                    fuel: mech.fuel,
+                },
+            ];
+        }
+        *mech.resource_inventory.entry(resource_type).or_insert(0) += 1;
+        vec![delivered, mech.inventory_message()]
+    }
+
+    /// Pick up the best item within reach of a player outside, if they have
+    /// a free slot
+    pub fn pick_up_in_reach(&mut self, player_id: Uuid) -> Vec<ServerMessage> {
+        let Some(PlayerLocation::OutsideWorld(player_pos)) =
+            self.players.get(&player_id).map(|p| p.location)
+        else {
+            return Vec::new();
+        };
+        let reach = RESOURCE_PICKUP_DISTANCE * TILE_SIZE;
+
+        // What a player dropped becomes fair game again once they walk away
+        for item in self.ground_items.values_mut() {
+            if item.dropped_by == Some(player_id) && item.position.distance_to(player_pos) > reach {
+                item.dropped_by = None;
+            }
+        }
+
+        let mut in_reach: Vec<(f32, Pickup, ItemType)> = self
+            .spatial_collision
+            .check_player_resource_collisions(player_id, player_pos)
+            .into_iter()
+            .filter_map(|id| self.get_resource(id))
+            .map(|resource| {
+                (
+                    resource.position.to_world_center().distance_to(player_pos),
+                    Pickup::Resource(resource.id, resource.resource_type),
+                    ItemType::Resource(resource.resource_type),
+                )
+            })
+            .collect();
+        in_reach.extend(
+            self.ground_items
+                .values()
+                .filter(|item| item.dropped_by.is_none())
+                .map(|item| {
+                    let distance = item.position.distance_to(player_pos);
+                    (distance, Pickup::Dropped(item.id), item.item)
+                })
+                .filter(|(distance, ..)| *distance <= reach),
+        );
+        in_reach.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let Some(player) = self.players.get_mut(&player_id) else {
+            return Vec::new();
+        };
+        let Some((pickup, item)) = player
+            .inventory
+            .choose_pickup(in_reach.into_iter().map(|(_, pickup, item)| (pickup, item)))
+        else {
+            return Vec::new();
+        };
+        player.inventory.add(item);
+        let inventory_msg = player.inventory_message();
+
+        let pickup_msg = match pickup {
+            Pickup::Resource(resource_id, resource_type) => {
+                self.remove_resource(resource_id);
+                log::info!("Player {player_id} picked up {resource_type:?} resource");
+                ServerMessage::PlayerPickedUpResource {
+                    player_id,
+                    resource_type,
+                    resource_id,
+                }
+            }
+            Pickup::Dropped(item_id) => {
+                self.ground_items.remove(&item_id);
+                ServerMessage::ItemPickedUp { player_id, item_id }
+            }
+        };
+        vec![pickup_msg, inventory_msg]
+    }
+
+    /// Leave an item on the ground where a player stands
+    pub fn drop_item(
+        &mut self,
+        player_id: Uuid,
+        item: ItemType,
+        position: WorldPos,
+    ) -> ServerMessage {
+        let item_id = self.rng.uuid();
+        self.ground_items.insert(
+            item_id,
+            GroundItem {
+                id: item_id,
+                item,
+                position,
+                dropped_by: Some(player_id),
+            },
+        );
+        ServerMessage::ItemDropped {
+            player_id,
+            item_id,
+            item,
+            position,
+        }
     }
 
+    #[profiling::function]
     pub fn get_full_state(&self) -> ServerMessage {
         let players: HashMap<Uuid, PlayerState> = self
             .players
@@ -864,8 +2905,12 @@ impl Game {
                         name: p.name.clone(),
                         team: p.team,
                         location: p.location,
-                        carrying_resource: p.carrying_resource,
+                        inventory: p.inventory.clone(),
                         operating_station: p.operating_station,
+                        held_tool: p.held_tool,
+                        health: p.health,
+                        facing: p.facing,
+                        afk: self.idle.is_away(p.id),
                     },
                 )
             })
@@ -885,6 +2930,9 @@ impl Game {
                         position: s.position,
                         size: get_station_size(s.station_type),
                         operated_by: s.operated_by,
+                        health: s.health,
+                        max_health: s.max_health,
+                        upgrade_level: s.upgrade_level,
                     })
                     .collect();
 
@@ -896,10 +2944,19 @@ impl Game {
                         position: m.position,
                         world_position: m.world_position,
                         health: m.health,
+                        max_health: m.max_health,
                         shield: m.shield,
                         upgrades: m.upgrades,
+                        drive: m.drive,
+                        mass: self.mech_mass(m.id),
+                        fuel: m.fuel,
+                        turret: m.turret,
+                        point_defense: m.point_defense,
                         stations,
                         resource_inventory: m.resource_inventory.clone(),
+                        delivery_score: m.delivery_score,
+                        fires: m.fire_states(),
+                        alarm: m.alarm,
                     },
                 )
             })
@@ -924,6 +2981,17 @@ impl Game {
                 velocity: p.velocity,
                 damage: p.damage,
                 owner_mech_id: p.owner_mech_id,
+                weapon_type: p.weapon_type,
+            })
+            .collect();
+
+        let ground_items = self
+            .ground_items
+            .values()
+            .map(|item| GroundItemState {
+                id: item.id,
+                item: item.item,
+                position: item.position,
             })
             .collect();
 
@@ -932,10 +3000,63 @@ impl Game {
             mechs,
             resources,
             projectiles,
+            pacing_zones: self.pacing_zones.clone(),
+            ground_items,
+            structures: self.entity_storage.structures.values().copied().collect(),
+            game_mode: self.game_mode.clone(),
+            environment: self.environment,
+        }
+    }
+
+    /// Hash of every player, mech and resource as clients mirror them
+    pub fn state_dump(&self) -> StateDump {
+        let mut dump = StateDump::default();
+        for player in self.players.values() {
+            dump.add_player(player.id, player.team, player.location, player.health);
+        }
+        for mech in self.mechs.values() {
+            dump.add_mech(
+                mech.id,
+                mech.team,
+                mech.position,
+                mech.health,
+                mech.shield,
+                &mech.resource_inventory,
+            );
+        }
+        for resource in self.get_resources() {
+            dump.add_resource(resource.id, resource.resource_type, resource.position);
+        }
+        dump
+    }
+
+    /// Hashes of the state `get_full_state` sends, to follow it out
+    pub fn state_checksum(&self) -> ServerMessage {
+        ServerMessage::StateChecksum {
+            tick: self.tick_count,
+            hashes: self.state_dump().hashes(),
         }
     }
 
+    /// Everything a watcher arriving now needs to draw the match: the arena,
+    /// its decals, the full state and every mech's floors
+    pub fn world_snapshot(&self) -> Vec<ServerMessage> {
+        let mut messages = vec![
+            ServerMessage::ArenaMapData {
+                map: self.arena_map.clone(),
+            },
+            ServerMessage::DecalLayer {
+                decals: self.decals.to_vec(),
+            },
+            self.get_full_state(),
+        ];
+        messages.extend(self.get_mech_floor_data());
+        messages.extend(crate::hangar::roster(self));
+        messages
+    }
+
     /// Generate MechFloorData messages for all mechs
+    #[profiling::function]
     pub fn get_mech_floor_data(&self) -> Vec<ServerMessage> {
         let mut messages = Vec::new();
         
@@ -956,13 +3077,11 @@ impl Game {
                 })
                 .collect();
 
-            // Generate interior layout using the same method as mech creation
-            let mut temp_stations = HashMap::new();
-            let interior = shared::mech_layout::MechLayoutGenerator::create_mech_interior(&mut temp_stations);
-
+            // The live floors, so breaches and barricades built before this
+            // player joined show up too
             messages.push(ServerMessage::MechFloorData {
                 mech_id: mech.id,
-                interior,
+                interior: mech.interior.clone(),
                 stations,
             });
         }
@@ -976,36 +3095,10 @@ impl Game {
     }
 
     pub fn check_resource_pickups(&mut self, tx: &broadcast::Sender<(Uuid, ServerMessage)>) {
-        let mut pickups = Vec::new();
-
-        for player in self.players.values() {
-            if player.carrying_resource.is_some() {
-                continue;
-            }
-
-            if let PlayerLocation::OutsideWorld(player_pos) = player.location {
-                let player_tile = player_pos.to_tile_pos();
-                for resource in self.get_resources() {
-                    if resource.position.distance_to(player_tile) < RESOURCE_PICKUP_DISTANCE {
-                        pickups.push((player.id, resource.id, resource.resource_type));
-                        break;
-                    }
-                }
-            }
-        }
-
-        for (player_id, resource_id, resource_type) in pickups {
-            if let Some(player) = self.players.get_mut(&player_id) {
-                player.carrying_resource = Some(resource_type);
-                self.remove_resource(resource_id);
-
-                let msg = ServerMessage::PlayerPickedUpResource {
-                    player_id,
-                    resource_type,
-                    resource_id,
-                };
+        let player_ids: Vec<Uuid> = self.players.keys().copied().collect();
+        for player_id in player_ids {
+            for msg in self.pick_up_in_reach(player_id) {
                 let _ = tx.send((Uuid::nil(), msg));
-                log::info!("Player {player_id} picked up {resource_type:?} resource");
             }
         }
     }
@@ -1039,13 +3132,18 @@ impl Game {
                     && proj_tile.y >= mech_min.y
                     && proj_tile.y <= mech_max.y
                 {
-                    hits.push((projectile.id, mech.id, projectile.damage));
+                    hits.push((
+                        projectile.id,
+                        mech.id,
+                        projectile.damage,
+                        projectile.position,
+                    ));
                     break;
                 }
             }
         }
 
-        for (proj_id, mech_id, damage) in hits {
+        for (proj_id, mech_id, damage, hit_pos) in hits {
             self.projectiles.remove(&proj_id);
 
             if let Some(mech) = self.mechs.get_mut(&mech_id) {
@@ -1061,6 +3159,8 @@ impl Game {
                         mech_id,
                         damage,
                         health_remaining: mech.health,
+                        position: hit_pos,
+                        hit: None,
                     },
                 ));
 
@@ -1070,20 +3170,55 @@ impl Game {
                         projectile_id: proj_id,
                         hit_mech_id: Some(mech_id),
                         damage_dealt: damage,
+                        position: hit_pos,
                     },
                 ));
             }
         }
     }
 
+    #[profiling::function]
     pub fn update(&mut self, delta_time: f32) -> Vec<ServerMessage> {
         // Update tick count
         self.tick_count += 1;
+        let mut messages = self.update_sessions(delta_time);
+        // Nothing plays out while crews are still in the hangar
+        if self.hangar.is_some() {
+            messages.extend(crate::hangar::update(self, delta_time));
+            return messages;
+        }
+        self.match_time += delta_time;
 
         // Temporarily take the system manager to avoid borrowing issues
         let mut system_manager = std::mem::take(&mut self.system_manager);
-        let messages = system_manager.update_all(self, delta_time);
+        messages.extend(system_manager.update_all(self, delta_time));
+        if let Some(director) =
+            system_manager.get_system_mut::<crate::systems::director::DirectorSystem>()
+        {
+            director.record_events(&messages);
+        }
+        let swings = system_manager
+            .get_system_mut::<crate::systems::ai::AISystem>()
+            .map(|ai_system| ai_system.take_swings())
+            .unwrap_or_default();
         self.system_manager = system_manager;
+        for player_id in swings {
+            match self.handle_melee_attack(player_id) {
+                Ok(attacked) => messages.extend(attacked),
+                // Usually nobody in reach any more, or still recovering
+                Err(e) => log::trace!("AI {player_id} couldn't swing: {e}"),
+            }
+        }
+        if self.load.sheds_cosmetics() {
+            messages.retain(|msg| !matches!(msg, ServerMessage::EffectCreated { .. }));
+        }
+        messages.extend(self.update_idle(delta_time));
+        self.schedule_vision();
+        self.record_positions(delta_time);
+        if self.collision_debug.is_watched() {
+            let projectiles = self.projectiles.values().map(|p| (p.id, p.position));
+            self.collision_debug.record_projectiles(projectiles);
+        }
 
         messages
     }
@@ -1097,14 +3232,58 @@ impl Game {
         damage: u32,
         owner_mech_id: Uuid,
         max_lifetime: f32,
+        weapon_type: WeaponType,
     ) -> Uuid {
         let mut projectile = self.pool_manager.get_projectile();
         projectile.initialize(position, velocity, damage, owner_mech_id, max_lifetime);
+        projectile.id = self.rng.uuid();
+        projectile.weapon_type = weapon_type;
         let projectile_id = projectile.id;
         self.projectiles.insert(projectile_id, projectile);
         projectile_id
     }
 
+    /// Fire a mech's turret along its aim, scattered by the turret's spread.
+    /// Nothing happens while it's reloading or the mech is disabled.
+    pub fn fire_turret(&mut self, mech_id: Uuid) -> Option<ServerMessage> {
+        use rand::Rng;
+
+        let disabled = self
+            .entity_storage
+            .status_effects
+            .get(&mech_id)
+            .and_then(|effects| effects.blocks_station_use())
+            .is_some();
+        let mech = self.mechs.get_mut(&mech_id)?;
+        if disabled || !mech.turret.ready() {
+            return None;
+        }
+
+        let angle = mech.turret.fire(self.rng.gen_range(-1.0..=1.0));
+        let origin = MechPositioning::mech_center(mech.position);
+        let (dx, dy) = (angle.cos(), angle.sin());
+        let range = TURRET_RANGE * TILE_SIZE;
+        let damage = (TURRET_DAMAGE as f32
+            * damage_multiplier_at(&self.pacing_zones, origin)
+            * self.damage_multiplier(mech_id))
+        .round() as u32;
+        let projectile_id = self.create_projectile(
+            origin,
+            (dx * TURRET_PROJECTILE_SPEED, dy * TURRET_PROJECTILE_SPEED),
+            damage,
+            mech_id,
+            range / TURRET_PROJECTILE_SPEED,
+            WeaponType::Projectile,
+        );
+
+        Some(ServerMessage::WeaponFired {
+            mech_id,
+            weapon_type: StationType::TurretControl,
+            target_position: WorldPos::new(origin.x + dx * range, origin.y + dy * range).to_tile(),
+            projectile_id: Some(projectile_id),
+        })
+    }
+
     /// Create a new visual effect using the object pool
     pub fn create_effect(
         &mut self,
@@ -1186,3 +3365,34 @@ impl Game {
         );
     }
 }
+
+/// Interior tiles in a fixed order, so picking one at random depends only
+/// on the match's seed and not on how a map happened to hash them
+fn sorted(mut positions: Vec<MechInteriorPos>) -> Vec<MechInteriorPos> {
+    positions.sort_unstable_by_key(|pos| (pos.floor, pos.tile_pos.x, pos.tile_pos.y));
+    positions
+}
+
+/// What a player can see from a viewpoint out to `range` tiles, shaped by
+/// `sight` when they face a particular way
+fn cast_sight(
+    tile_map: &TileMap,
+    storage: &EntityStorage,
+    sight: SightShape,
+    range: f32,
+    (tile, _, facing): Viewpoint,
+) -> HashSet<TilePos> {
+    let (facing, shape) = match facing {
+        Some(facing) => (facing.angle().to_degrees(), sight),
+        None => (0.0, SightShape::Radial),
+    };
+    VisionSystem::cast_facing(
+        tile.to_world_center(),
+        facing,
+        shape,
+        range * TILE_SIZE,
+        tile_map,
+        storage,
+    )
+    .visible_tiles
+}
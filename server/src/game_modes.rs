@@ -0,0 +1,310 @@
+//! How matches are won. Each room can run under a `GameMode`, which sets up
+//! its rules when the match starts, scores gameplay events as they happen,
+//! decides when someone has won and describes the objective for the HUD.
+//! `GameModeSystem` drives whichever mode is loaded.
+
+use crate::events::GameEvent;
+use crate::game::{Game, Mech};
+use shared::*;
+use std::collections::HashMap;
+
+pub trait GameMode: Send + Sync {
+    fn kind(&self) -> GameModeKind;
+
+    /// Set the match up for this mode, once before the first tick
+    fn init(&mut self, _game: &mut Game) {}
+
+    /// Score something that happened this tick
+    fn on_event(&mut self, _game: &Game, _event: &GameEvent) {}
+
+    /// How the match ended, once it has
+    fn victory(&self, game: &Game) -> Option<MatchResult>;
+
+    /// What clients should show about the objective
+    fn hud(&self, game: &Game) -> GameModeHud;
+}
+
+pub fn create(kind: GameModeKind) -> Box<dyn GameMode> {
+    match kind {
+        GameModeKind::Deathmatch => Box::new(Deathmatch),
+        GameModeKind::ResourceRace => Box::<ResourceRace>::default(),
+        GameModeKind::Escort => Box::<Escort>::default(),
+    }
+}
+
+const TEAMS: [TeamId; 2] = [TeamId::Red, TeamId::Blue];
+
+fn enemy_of(team: TeamId) -> TeamId {
+    match team {
+        TeamId::Red => TeamId::Blue,
+        TeamId::Blue => TeamId::Red,
+    }
+}
+
+fn team_mech(game: &Game, team: TeamId) -> Option<&Mech> {
+    game.mechs.values().find(|mech| mech.team == team)
+}
+
+fn win(team: TeamId, reason: impl Into<String>) -> Option<MatchResult> {
+    Some(MatchResult {
+        winner: Some(team),
+        reason: reason.into(),
+    })
+}
+
+/// Last team with a mech standing wins
+pub struct Deathmatch;
+
+impl GameMode for Deathmatch {
+    fn kind(&self) -> GameModeKind {
+        GameModeKind::Deathmatch
+    }
+
+    fn victory(&self, game: &Game) -> Option<MatchResult> {
+        let standing = |team| team_mech(game, team).is_some_and(|mech| mech.health > 0);
+        match (standing(TeamId::Red), standing(TeamId::Blue)) {
+            (true, false) => win(TeamId::Red, "Blue's mech was destroyed"),
+            (false, true) => win(TeamId::Blue, "Red's mech was destroyed"),
+            (false, false) => Some(MatchResult {
+                winner: None,
+                reason: "Both mechs were destroyed".to_string(),
+            }),
+            (true, true) => None,
+        }
+    }
+
+    fn hud(&self, game: &Game) -> GameModeHud {
+        let teams = TEAMS
+            .into_iter()
+            .map(|team| {
+                let enemy = team_mech(game, enemy_of(team));
+                TeamObjective {
+                    team,
+                    label: "Enemy hull destroyed".to_string(),
+                    value: enemy.map_or(0, |mech| mech.max_health.saturating_sub(mech.health)),
+                    target: enemy.map_or(0, |mech| mech.max_health),
+                }
+            })
+            .collect();
+        GameModeHud {
+            mode: self.kind(),
+            objective: "Destroy the enemy mech".to_string(),
+            teams,
+            time_left: None,
+            goal: None,
+            result: None,
+        }
+    }
+}
+
+/// First team to deliver `RESOURCE_RACE_TARGET` resources wins
+#[derive(Default)]
+pub struct ResourceRace {
+    delivered: HashMap<TeamId, u32>,
+}
+
+impl GameMode for ResourceRace {
+    fn kind(&self) -> GameModeKind {
+        GameModeKind::ResourceRace
+    }
+
+    fn on_event(&mut self, game: &Game, event: &GameEvent) {
+        if let GameEvent::ResourceDelivered { mech_id, .. } = event {
+            if let Some(mech) = game.mechs.get(mech_id) {
+                *self.delivered.entry(mech.team).or_default() += 1;
+            }
+        }
+    }
+
+    fn victory(&self, _game: &Game) -> Option<MatchResult> {
+        TEAMS
+            .into_iter()
+            .find(|team| self.delivered.get(team).copied().unwrap_or(0) >= RESOURCE_RACE_TARGET)
+            .and_then(|team| {
+                win(
+                    team,
+                    format!("{team:?} delivered {RESOURCE_RACE_TARGET} resources"),
+                )
+            })
+    }
+
+    fn hud(&self, _game: &Game) -> GameModeHud {
+        let teams = TEAMS
+            .into_iter()
+            .map(|team| TeamObjective {
+                team,
+                label: "Resources delivered".to_string(),
+                value: self.delivered.get(&team).copied().unwrap_or(0),
+                target: RESOURCE_RACE_TARGET,
+            })
+            .collect();
+        GameModeHud {
+            mode: self.kind(),
+            objective: format!("Deliver {RESOURCE_RACE_TARGET} resources to your mech first"),
+            teams,
+            time_left: None,
+            goal: None,
+            result: None,
+        }
+    }
+}
+
+/// Red's mech is the payload and has to reach the spot Blue's mech started
+/// from. Blue wins by destroying it or holding out until time runs out.
+pub struct Escort {
+    goal: WorldPos,
+    /// How far the payload started from the goal, in pixels
+    start_distance: f32,
+}
+
+impl Default for Escort {
+    fn default() -> Self {
+        Self {
+            goal: WorldPos::new(0.0, 0.0),
+            start_distance: 0.0,
+        }
+    }
+}
+
+impl Escort {
+    const ATTACKERS: TeamId = TeamId::Red;
+    const DEFENDERS: TeamId = TeamId::Blue;
+
+    fn payload_distance(&self, game: &Game) -> Option<f32> {
+        team_mech(game, Self::ATTACKERS)
+            .map(|mech| MechPositioning::mech_center(mech.position).distance_to(self.goal))
+    }
+
+    /// Share of the way to the goal the payload has come, 0-100
+    fn progress(&self, game: &Game) -> u32 {
+        let reach = self.start_distance - ESCORT_GOAL_RADIUS * TILE_SIZE;
+        match self.payload_distance(game) {
+            Some(distance) if reach > 0.0 => {
+                ((self.start_distance - distance) / reach * 100.0).clamp(0.0, 100.0) as u32
+            }
+            _ => 0,
+        }
+    }
+}
+
+impl GameMode for Escort {
+    fn kind(&self) -> GameModeKind {
+        GameModeKind::Escort
+    }
+
+    fn init(&mut self, game: &mut Game) {
+        let defenders = team_mech(game, Self::DEFENDERS).map(|mech| mech.position);
+        let spawn = game.arena_map.mech_spawn(Self::DEFENDERS).or(defenders);
+        self.goal = spawn.map_or_else(
+            || {
+                let (width, height) = game.arena_map.world_size();
+                WorldPos::new(width / 2.0, height / 2.0)
+            },
+            MechPositioning::mech_center,
+        );
+        self.start_distance = self.payload_distance(game).unwrap_or(0.0);
+    }
+
+    fn victory(&self, game: &Game) -> Option<MatchResult> {
+        let payload = team_mech(game, Self::ATTACKERS)?;
+        if payload.health == 0 {
+            return win(Self::DEFENDERS, "The payload was destroyed");
+        }
+        if self
+            .payload_distance(game)
+            .is_some_and(|distance| distance <= ESCORT_GOAL_RADIUS * TILE_SIZE)
+        {
+            return win(Self::ATTACKERS, "The payload reached its goal");
+        }
+        if game.match_time >= ESCORT_TIME_LIMIT {
+            return win(Self::DEFENDERS, "The defenders held out");
+        }
+        None
+    }
+
+    fn hud(&self, game: &Game) -> GameModeHud {
+        let held = game.match_time.min(ESCORT_TIME_LIMIT);
+        GameModeHud {
+            mode: self.kind(),
+            objective: "Red: drive your mech to the goal. Blue: stop it".to_string(),
+            teams: vec![
+                TeamObjective {
+                    team: Self::ATTACKERS,
+                    label: "Payload progress".to_string(),
+                    value: self.progress(game),
+                    target: 100,
+                },
+                TeamObjective {
+                    team: Self::DEFENDERS,
+                    label: "Seconds held".to_string(),
+                    value: held as u32,
+                    target: ESCORT_TIME_LIMIT as u32,
+                },
+            ],
+            time_left: Some((ESCORT_TIME_LIMIT - held).ceil() as u32),
+            goal: Some(self.goal),
+            result: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn mech_of(game: &mut Game, team: TeamId) -> &mut Mech {
+        game.mechs.values_mut().find(|m| m.team == team).unwrap()
+    }
+
+    #[test]
+    fn test_race_counts_deliveries_per_team() {
+        let mut game = Game::new();
+        let red = mech_of(&mut game, TeamId::Red).id;
+        let mut race = ResourceRace::default();
+        for _ in 0..RESOURCE_RACE_TARGET - 1 {
+            race.on_event(
+                &game,
+                &GameEvent::ResourceDelivered {
+                    player_id: Uuid::new_v4(),
+                    mech_id: red,
+                    resource_type: ResourceType::Wiring,
+                },
+            );
+        }
+        assert_eq!(race.victory(&game), None);
+        assert_eq!(race.hud(&game).teams[0].value, RESOURCE_RACE_TARGET - 1);
+
+        race.on_event(
+            &game,
+            &GameEvent::ResourceDelivered {
+                player_id: Uuid::new_v4(),
+                mech_id: red,
+                resource_type: ResourceType::Batteries,
+            },
+        );
+        assert_eq!(race.victory(&game).unwrap().winner, Some(TeamId::Red));
+    }
+
+    #[test]
+    fn test_escort_is_won_at_the_goal_and_lost_on_the_clock() {
+        let mut game = Game::new();
+        let mut escort = Escort::default();
+        escort.init(&mut game);
+        assert!(escort.start_distance > 0.0);
+        assert_eq!(escort.hud(&game).teams[0].value, 0);
+        assert_eq!(escort.victory(&game), None);
+
+        let goal = escort.goal.to_tile();
+        let size = MECH_SIZE_TILES / 2;
+        mech_of(&mut game, TeamId::Red).position = TilePos::new(goal.x - size, goal.y - size);
+        assert_eq!(escort.hud(&game).teams[0].value, 100);
+        assert_eq!(escort.victory(&game).unwrap().winner, Some(TeamId::Red));
+
+        let mut game = Game::new();
+        escort.init(&mut game);
+        game.match_time = ESCORT_TIME_LIMIT;
+        assert_eq!(escort.victory(&game).unwrap().winner, Some(TeamId::Blue));
+        assert_eq!(escort.hud(&game).time_left, Some(0));
+    }
+}
@@ -6,7 +6,9 @@ mod tests {
         coordinates::MechDoorPositions,
         tile_entity::TileEvent,
         types::{TilePos, WorldPos},
-        PlayerLocation, TeamId,
+        MechInteriorPos, MechPositioning, PlayerLocation, ServerMessage, StateDump, TeamId,
+        HULL_BREACH_MAX_HEALTH_PENALTY, MECH_MAX_HEALTH, MECH_SENSOR_RANGE, PLAYER_MAX_HEALTH,
+        TILE_SIZE,
     };
 
     use uuid::Uuid;
@@ -389,4 +391,1529 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_ai_autofill_maintains_minimum_crew() {
+        let mut game = create_test_game();
+        game.ai_autofill_min_crew = Some(2);
+        // New crew go out in a full state so connected clients see them
+        let messages = game.balance_ai_crew();
+        assert!(matches!(
+            messages.last(),
+            Some(ServerMessage::GameState { players, .. }) if players.len() == 4
+        ));
+
+        let team_size = |game: &Game, team: TeamId| {
+            game.players.values().filter(|p| p.team == team).count()
+        };
+        assert_eq!(team_size(&game, TeamId::Red), 2, "Red should be filled with AI");
+        assert_eq!(team_size(&game, TeamId::Blue), 2, "Blue should be filled with AI");
+
+        // A human joining red replaces one AI crew member
+        let human_id = Uuid::new_v4();
+        game.players.insert(
+            human_id,
+            crate::game::Player {
+                id: human_id,
+                name: "Human".to_string(),
+                team: TeamId::Red,
+                location: PlayerLocation::OutsideWorld(WorldPos::new(0.0, 0.0)),
+                inventory: shared::Inventory::default(),
+                operating_station: None,
+                held_tool: None,
+                health: PLAYER_MAX_HEALTH,
+                facing: shared::Facing::default(),
+                dash_ready_at: 0.0,
+                scanner_ready_at: 0.0,
+            },
+        );
+        let messages = game.balance_ai_crew();
+
+        assert_eq!(messages.len(), 1, "One AI should be removed");
+        assert_eq!(team_size(&game, TeamId::Red), 2);
+        assert!(game.players.contains_key(&human_id));
+
+        // Leaving brings the AI back
+        game.remove_player(&human_id);
+        game.balance_ai_crew();
+        assert_eq!(team_size(&game, TeamId::Red), 2);
+    }
+
+    #[test]
+    fn test_hull_breach_reduces_and_repair_restores_max_health() {
+        let mut game = create_test_game();
+        let mech_id = *game.mechs.keys().next().unwrap();
+
+        // Heavy enough damage to guarantee both a fire and a breach
+        let messages = game.apply_hull_damage(mech_id, 100);
+        let breach = messages.iter().find_map(|msg| match msg {
+            ServerMessage::HullBreachUpdated {
+                floor, position, ..
+            } => Some(MechInteriorPos::new(*floor, *position)),
+            _ => None,
+        });
+        let breach = breach.expect("Damage should breach the hull");
+
+        let mech = &game.mechs[&mech_id];
+        assert_eq!(mech.fires.len(), 1, "Damage should start a fire");
+        assert_eq!(mech.hull_breaches(), 1);
+        assert_eq!(mech.max_health, MECH_MAX_HEALTH - HULL_BREACH_MAX_HEALTH_PENALTY);
+
+        assert!(game.repair_hull_breach(mech_id, breach).is_some());
+        let mech = &game.mechs[&mech_id];
+        assert_eq!(mech.hull_breaches(), 0);
+        assert_eq!(mech.max_health, MECH_MAX_HEALTH);
+
+        // Nothing left to patch
+        assert!(game.repair_hull_breach(mech_id, breach).is_none());
+    }
+
+    #[test]
+    fn test_massive_hit_throws_the_crew_around() {
+        use shared::{
+            ResourceType, StatusEffectKind, INTERIOR_IMPACT_DAMAGE, INTERIOR_IMPACT_FULL_DAMAGE,
+        };
+
+        let mut game = create_test_game();
+        let crew = add_test_player(&mut game, "Crew", Some(TeamId::Blue));
+        let mech_id = get_team_mech(&game, TeamId::Blue).unwrap().id;
+        game.players.get_mut(&crew).unwrap().location = PlayerLocation::InsideMech {
+            mech_id,
+            pos: MechInteriorPos::new(0, TilePos::new(5, 5)),
+        };
+        let station_id = *game.mechs[&mech_id].stations.keys().next().unwrap();
+        game.mechs
+            .get_mut(&mech_id)
+            .unwrap()
+            .resource_inventory
+            .insert(ResourceType::ScrapMetal, 50);
+
+        // A glancing blow doesn't reach the crew
+        assert!(game
+            .interior_impact(mech_id, INTERIOR_IMPACT_DAMAGE - 1, None)
+            .is_empty());
+
+        let mut thrown_off = false;
+        let mut spilled = 0;
+        for _ in 0..20 {
+            game.mechs
+                .get_mut(&mech_id)
+                .unwrap()
+                .stations
+                .get_mut(&station_id)
+                .unwrap()
+                .operated_by = Some(crew);
+            game.players.get_mut(&crew).unwrap().operating_station = Some(station_id);
+
+            let messages = game.interior_impact(mech_id, INTERIOR_IMPACT_FULL_DAMAGE, None);
+            assert!(messages.iter().any(|msg| matches!(
+                msg,
+                ServerMessage::InteriorImpact { severity, .. } if *severity == 1.0
+            )));
+            assert!(game
+                .entity_storage
+                .status_effects
+                .get(&crew)
+                .is_some_and(|effects| effects.has(StatusEffectKind::Stunned)));
+
+            thrown_off |= game.players[&crew].operating_station.is_none();
+            for msg in &messages {
+                if let ServerMessage::ResourceSpawned { position, .. } = msg {
+                    let hull = game.mechs[&mech_id].position;
+                    assert!(!MechPositioning::is_inside_mech(
+                        position.to_world_pos(),
+                        hull
+                    ));
+                    spilled += 1;
+                }
+            }
+        }
+        assert!(thrown_off, "Full-force hits should unseat an operator");
+        assert!(spilled > 0, "Full-force hits should shake cargo loose");
+        assert_eq!(
+            game.mechs[&mech_id].resource_inventory[&ResourceType::ScrapMetal],
+            50 - spilled
+        );
+    }
+
+    #[test]
+    fn test_team_vision_shares_crew_and_sensor_sight() {
+        let mut game = create_test_game();
+        let red_id = add_test_player(&mut game, "Scout", Some(TeamId::Red));
+        let blue_id = add_test_player(&mut game, "Other", Some(TeamId::Blue));
+
+        // Put the red scout out in the open, far from every mech
+        let scout_pos = WorldPos::new(50.0 * TILE_SIZE, 5.0 * TILE_SIZE);
+        game.players.get_mut(&red_id).unwrap().location = PlayerLocation::OutsideWorld(scout_pos);
+        game.players.get_mut(&blue_id).unwrap().location =
+            PlayerLocation::OutsideWorld(WorldPos::new(TILE_SIZE, TILE_SIZE));
+
+        let teams = game.team_visibility();
+        let red = &teams[&TeamId::Red];
+        let blue = &teams[&TeamId::Blue];
+
+        assert!(red.contains(&scout_pos.to_tile_pos()));
+        assert!(!blue.contains(&scout_pos.to_tile_pos()));
+
+        // Each team sees around its own mechs through their sensors
+        for mech in game.mechs.values() {
+            let center = MechPositioning::mech_center(mech.position).to_tile_pos();
+            let edge = center.offset(MECH_SENSOR_RANGE, 0);
+            let seen_by = if mech.team == TeamId::Red { red } else { blue };
+            assert!(seen_by.contains(&edge));
+        }
+    }
+
+    #[test]
+    fn test_cone_sight_is_blind_behind() {
+        use shared::{Facing, SightShape};
+
+        let mut game = create_test_game();
+        let scout_id = add_test_player(&mut game, "Scout", Some(TeamId::Red));
+        let scout_pos = WorldPos::new(50.5 * TILE_SIZE, 5.5 * TILE_SIZE);
+        let scout = game.players.get_mut(&scout_id).unwrap();
+        scout.location = PlayerLocation::OutsideWorld(scout_pos);
+        scout.facing = Facing::East;
+
+        let (ahead, behind) = (
+            scout_pos.to_tile_pos().offset(6, 0),
+            scout_pos.to_tile_pos().offset(-6, 0),
+        );
+        let red = &game.team_visibility()[&TeamId::Red];
+        assert!(red.contains(&ahead));
+        assert!(!red.contains(&behind));
+
+        // Turning around swaps them once sight is recast
+        game.players.get_mut(&scout_id).unwrap().facing = Facing::West;
+        game.schedule_vision();
+        let red = &game.team_visibility()[&TeamId::Red];
+        assert!(!red.contains(&ahead));
+        assert!(red.contains(&behind));
+
+        // Radial sight sees both ways
+        game.sight = SightShape::Radial;
+        game.schedule_vision();
+        let red = &game.team_visibility()[&TeamId::Red];
+        assert!(red.contains(&ahead) && red.contains(&behind));
+    }
+
+    #[tokio::test]
+    async fn test_dev_commands_require_dev_mode() {
+        use crate::commands::{Command, DevCommandCommand};
+        use shared::{DevCommand, ResourceType};
+
+        let mut game = create_test_game();
+        let player_id = add_test_player(&mut game, "Dev", Some(TeamId::Red));
+        let game = tokio::sync::RwLock::new(game);
+        let (tx, mut rx) = tokio::sync::broadcast::channel(100);
+
+        let teleport = DevCommandCommand {
+            command: DevCommand::Teleport { x: 5, y: 6 },
+        };
+        teleport.execute(&game, player_id, &tx).await.unwrap();
+        assert!(matches!(
+            rx.try_recv(),
+            Ok((_, ServerMessage::DevCommandResult { success: false, .. }))
+        ));
+
+        game.write().await.dev_mode = true;
+        teleport.execute(&game, player_id, &tx).await.unwrap();
+        assert_eq!(
+            get_player_world_pos(&*game.read().await, player_id),
+            Some(TilePos::new(5, 6).to_world_center())
+        );
+
+        let give = DevCommandCommand {
+            command: DevCommand::GiveResource {
+                resource_type: ResourceType::Batteries,
+                amount: 7,
+            },
+        };
+        give.execute(&game, player_id, &tx).await.unwrap();
+        let game = game.read().await;
+        let red_mech = get_team_mech(&game, TeamId::Red).unwrap();
+        assert_eq!(red_mech.resource_inventory[&ResourceType::Batteries], 7);
+    }
+
+    #[tokio::test]
+    async fn test_annotations_reach_their_audience_at_a_limited_rate() {
+        use crate::commands::{AnnotateCommand, Command};
+        use shared::{
+            AnnotationAudience, AnnotationColor, AnnotationShape, GameError, ANNOTATIONS_PER_SECOND,
+        };
+
+        let mut game = create_test_game();
+        let red = add_test_player(&mut game, "Red", Some(TeamId::Red));
+        let blue = add_test_player(&mut game, "Blue", Some(TeamId::Blue));
+        let coach = Uuid::new_v4();
+        game.spectators.insert(coach);
+        let game = tokio::sync::RwLock::new(game);
+        let (tx, mut rx) = tokio::sync::broadcast::channel(100);
+        let draw = |audience| AnnotateCommand {
+            shape: AnnotationShape::Circle {
+                center: WorldPos::new(64.0, 64.0),
+                radius: 32.0,
+            },
+            color: AnnotationColor::Yellow,
+            audience,
+            ttl: 5.0,
+        };
+        let mut recipients = || {
+            let mut ids = Vec::new();
+            while let Ok((target, msg)) = rx.try_recv() {
+                assert!(matches!(msg, ServerMessage::AnnotationDrawn { .. }));
+                ids.push(target);
+            }
+            ids
+        };
+
+        // A team's marks go to its players and every spectator
+        draw(AnnotationAudience::Team(TeamId::Red))
+            .execute(&game, red, &tx)
+            .await
+            .unwrap();
+        let sent = recipients();
+        assert!(sent.contains(&red) && sent.contains(&coach));
+        assert!(!sent.contains(&blue));
+
+        // Players can't draw on the other team's map
+        assert!(matches!(
+            draw(AnnotationAudience::Team(TeamId::Red))
+                .execute(&game, blue, &tx)
+                .await,
+            Err(GameError::InvalidTeam)
+        ));
+
+        draw(AnnotationAudience::Everyone)
+            .execute(&game, coach, &tx)
+            .await
+            .unwrap();
+        assert_eq!(recipients(), vec![Uuid::nil()]);
+
+        for _ in 1..ANNOTATIONS_PER_SECOND {
+            draw(AnnotationAudience::Everyone)
+                .execute(&game, coach, &tx)
+                .await
+                .unwrap();
+        }
+        assert!(matches!(
+            draw(AnnotationAudience::Everyone)
+                .execute(&game, coach, &tx)
+                .await,
+            Err(GameError::AbilityOnCooldown { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_delivered_resources_pay_for_upgrades() {
+        use shared::{ResourceType, StationType, UpgradeType, DELIVERY_SCORE_PER_RESOURCE};
+
+        let mut game = create_test_game();
+        let player_id = add_test_player(&mut game, "Hauler", Some(TeamId::Red));
+        let mech_id = get_team_mech(&game, TeamId::Red).unwrap().id;
+        let (tx, mut rx) = tokio::sync::broadcast::channel(100);
+
+        // Laser upgrade costs 2 scrap metal and 1 computer component
+        for resource_type in [
+            ResourceType::ScrapMetal,
+            ResourceType::ScrapMetal,
+            ResourceType::ComputerComponents,
+        ] {
+            let messages = game.deliver_resource(player_id, mech_id, resource_type);
+            assert!(matches!(
+                messages[0],
+                ServerMessage::ResourceDelivered { .. }
+            ));
+        }
+        assert_eq!(
+            game.mechs[&mech_id].delivery_score,
+            3 * DELIVERY_SCORE_PER_RESOURCE
+        );
+
+        crate::client::handle_station_button(
+            &mut game,
+            player_id,
+            mech_id,
+            StationType::Upgrade,
+            0,
+            &tx,
+        )
+        .await;
+        let mech = &game.mechs[&mech_id];
+        assert_eq!(mech.upgrades.laser_level, 2);
+        assert!(mech.resource_inventory.values().all(|count| *count == 0));
+
+        let mut saw_inventory = false;
+        while let Ok((_, msg)) = rx.try_recv() {
+            saw_inventory |= matches!(msg, ServerMessage::MechInventoryChanged { .. });
+            if let ServerMessage::MechUpgraded { upgrade_type, .. } = msg {
+                assert_eq!(upgrade_type, UpgradeType::Laser);
+            }
+        }
+        assert!(saw_inventory);
+
+        // Nothing left to pay with
+        crate::client::handle_station_button(
+            &mut game,
+            player_id,
+            mech_id,
+            StationType::Upgrade,
+            0,
+            &tx,
+        )
+        .await;
+        assert_eq!(game.mechs[&mech_id].upgrades.laser_level, 2);
+    }
+
+    #[tokio::test]
+    async fn test_dropping_an_item_swaps_it_for_one_underfoot() {
+        use crate::commands::{Command, DropItemCommand};
+        use crate::game::GroundItem;
+        use shared::{ItemType, ResourceType};
+
+        const SCRAP: ItemType = ItemType::Resource(ResourceType::ScrapMetal);
+
+        let mut game = create_test_game();
+        let player_id = add_test_player(&mut game, "Scavenger", Some(TeamId::Red));
+        let here = TilePos::new(50, 50).to_world_center();
+        let elsewhere = TilePos::new(60, 60).to_world_center();
+        let keycard_id = Uuid::new_v4();
+        game.ground_items.insert(
+            keycard_id,
+            GroundItem {
+                id: keycard_id,
+                item: ItemType::Keycard,
+                position: here,
+                dropped_by: None,
+            },
+        );
+        let player = game.players.get_mut(&player_id).unwrap();
+        player.location = PlayerLocation::OutsideWorld(here);
+        for _ in 0..3 {
+            player.inventory.add(SCRAP);
+        }
+
+        let game = tokio::sync::RwLock::new(game);
+        let (tx, _rx) = tokio::sync::broadcast::channel(100);
+        DropItemCommand.execute(&game, player_id, &tx).await.unwrap();
+
+        let mut game = game.into_inner();
+        let player = &game.players[&player_id];
+        assert_eq!(player.inventory.active_item(), Some(ItemType::Keycard));
+        assert!(!game.ground_items.contains_key(&keycard_id));
+        let dropped = game.ground_items.values().next().unwrap();
+        assert_eq!(dropped.item, SCRAP);
+
+        // The dropper leaves it alone until they've walked away and back
+        game.players
+            .get_mut(&player_id)
+            .unwrap()
+            .inventory
+            .remove(SCRAP);
+        assert!(game.pick_up_in_reach(player_id).is_empty());
+        game.players.get_mut(&player_id).unwrap().location =
+            PlayerLocation::OutsideWorld(elsewhere);
+        assert!(game.pick_up_in_reach(player_id).is_empty());
+        game.players.get_mut(&player_id).unwrap().location = PlayerLocation::OutsideWorld(here);
+        assert!(matches!(
+            game.pick_up_in_reach(player_id)[0],
+            ServerMessage::ItemPickedUp { .. }
+        ));
+        assert!(game.ground_items.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_no_joins_while_shutting_down() {
+        use crate::commands::{Command, JoinGameCommand};
+        use shared::GameError;
+
+        let mut game = create_test_game();
+        game.accepting_players = false;
+        let game = tokio::sync::RwLock::new(game);
+        let (tx, _rx) = tokio::sync::broadcast::channel(100);
+
+        let join = JoinGameCommand {
+            player_name: "Latecomer".to_string(),
+            preferred_team: None,
+            protocol_version: shared::PROTOCOL_VERSION,
+            features: Vec::new(),
+            password: None,
+        };
+        assert!(matches!(
+            join.execute(&game, Uuid::new_v4(), &tx).await,
+            Err(GameError::ServerShuttingDown)
+        ));
+        assert!(game.read().await.players.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_join_checks_protocol_version_and_features() {
+        use crate::commands::{Command, JoinGameCommand};
+        use shared::{GameError, ProtocolFeature, PROTOCOL_VERSION};
+
+        let game = tokio::sync::RwLock::new(create_test_game());
+        let (tx, mut rx) = tokio::sync::broadcast::channel(100);
+
+        // A client from before versioning is told why it can't join
+        let outdated = JoinGameCommand {
+            player_name: "Oldtimer".to_string(),
+            preferred_team: None,
+            protocol_version: 0,
+            features: Vec::new(),
+            password: None,
+        };
+        let player_id = Uuid::new_v4();
+        assert!(matches!(
+            outdated.execute(&game, player_id, &tx).await,
+            Err(GameError::IncompatibleProtocol { client: 0, .. })
+        ));
+        assert!(matches!(
+            rx.try_recv().unwrap(),
+            (id, ServerMessage::JoinRejected { server_version: PROTOCOL_VERSION, .. })
+                if id == player_id
+        ));
+        assert!(game.read().await.players.is_empty());
+
+        // Current clients get back the features both sides support
+        let current = JoinGameCommand {
+            player_name: "Newcomer".to_string(),
+            preferred_team: None,
+            protocol_version: PROTOCOL_VERSION,
+            features: vec![ProtocolFeature::Unknown, ProtocolFeature::Compression],
+            password: None,
+        };
+        current.execute(&game, player_id, &tx).await.unwrap();
+        let features = std::iter::from_fn(|| rx.try_recv().ok())
+            .find_map(|(_, msg)| match msg {
+                ServerMessage::JoinedGame { features, .. } => Some(features),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(features, vec![ProtocolFeature::Compression]);
+    }
+
+    #[tokio::test]
+    async fn test_joining_with_voice_hands_out_a_voice_key() {
+        use crate::commands::{Command, JoinGameCommand};
+        use shared::{ProtocolFeature, PROTOCOL_VERSION};
+
+        let game = tokio::sync::RwLock::new(create_test_game());
+        let (tx, mut rx) = tokio::sync::broadcast::channel(100);
+        let join = |features| JoinGameCommand {
+            player_name: "Talker".to_string(),
+            preferred_team: None,
+            protocol_version: PROTOCOL_VERSION,
+            features,
+            password: None,
+        };
+        let voice_key = |rx: &mut tokio::sync::broadcast::Receiver<(Uuid, ServerMessage)>| {
+            std::iter::from_fn(|| rx.try_recv().ok()).find_map(|(_, msg)| match msg {
+                ServerMessage::VoiceChannel { key } => Some(key),
+                _ => None,
+            })
+        };
+
+        let quiet = Uuid::new_v4();
+        join(vec![]).execute(&game, quiet, &tx).await.unwrap();
+        assert_eq!(voice_key(&mut rx), None);
+
+        let talker = Uuid::new_v4();
+        join(vec![ProtocolFeature::Voice])
+            .execute(&game, talker, &tx)
+            .await
+            .unwrap();
+        let key = voice_key(&mut rx).unwrap();
+        let mut game = game.write().await;
+        let team = game.players[&talker].team;
+        assert_eq!(game.voice.lock().unwrap().redeem(key), Some((talker, team)));
+
+        // Leaving closes the voice socket along with everything else
+        let _frames = game.voice.lock().unwrap().connect(talker, team);
+        game.remove_player(&talker);
+        assert_eq!(game.voice.lock().unwrap().listener_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_join_checks_room_password_and_player_cap() {
+        use crate::commands::{Command, JoinGameCommand};
+        use shared::{GameError, PROTOCOL_VERSION};
+
+        let mut game = create_test_game();
+        game.access.password = Some("hunter2".to_string());
+        game.access.max_players = Some(1);
+        let game = tokio::sync::RwLock::new(game);
+        let (tx, mut rx) = tokio::sync::broadcast::channel(100);
+        let join = |name: &str, password: Option<&str>| JoinGameCommand {
+            player_name: name.to_string(),
+            preferred_team: None,
+            protocol_version: PROTOCOL_VERSION,
+            features: Vec::new(),
+            password: password.map(str::to_string),
+        };
+
+        // No password, then a wrong one, each answered with a prompt
+        let player_id = Uuid::new_v4();
+        for (password, wrong) in [(None, false), (Some("hunter3"), true)] {
+            assert!(matches!(
+                join("Guest", password).execute(&game, player_id, &tx).await,
+                Err(GameError::WrongPassword)
+            ));
+            assert!(matches!(
+                rx.try_recv().unwrap(),
+                (id, ServerMessage::PasswordRequired { wrong: w }) if id == player_id && w == wrong
+            ));
+        }
+        assert!(game.read().await.players.is_empty());
+
+        join("Guest", Some("hunter2"))
+            .execute(&game, player_id, &tx)
+            .await
+            .unwrap();
+        assert!(game.read().await.players.contains_key(&player_id));
+
+        // The room only holds one
+        assert!(matches!(
+            join("Second", Some("hunter2"))
+                .execute(&game, Uuid::new_v4(), &tx)
+                .await,
+            Err(GameError::RoomFull { max: 1 })
+        ));
+        assert_eq!(game.read().await.players.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_dropped_players_resume_with_their_token() {
+        use crate::commands::{Command, JoinGameCommand};
+        use shared::{FRAME_DELTA_SECONDS, PROTOCOL_VERSION};
+
+        let game = tokio::sync::RwLock::new(create_test_game());
+        let (tx, mut rx) = tokio::sync::broadcast::channel(100);
+        let join = JoinGameCommand {
+            player_name: "Flaky".to_string(),
+            preferred_team: None,
+            protocol_version: PROTOCOL_VERSION,
+            features: Vec::new(),
+            password: None,
+        };
+        let player_id = Uuid::new_v4();
+        join.execute(&game, player_id, &tx).await.unwrap();
+        let token = std::iter::from_fn(|| rx.try_recv().ok())
+            .find_map(|(_, msg)| match msg {
+                ServerMessage::SessionResume { token } => Some(token),
+                _ => None,
+            })
+            .unwrap();
+
+        // The socket drops and a new one comes back with the token
+        let team = {
+            let mut game = game.write().await;
+            assert!(game.sessions.hold(player_id));
+            assert_eq!(game.sessions.resume(token), Some(player_id));
+            game.players[&player_id].team
+        };
+        join.execute(&game, player_id, &tx).await.unwrap();
+        let mut game = game.write().await;
+        assert_eq!(game.players[&player_id].team, team);
+        assert_eq!(game.sessions.issue(player_id), token);
+
+        // Nobody comes back this time, so they're let go after the grace
+        game.sessions.grace = 0.1;
+        assert!(game.sessions.hold(player_id));
+        let messages: Vec<_> = (0..5)
+            .flat_map(|_| game.update(FRAME_DELTA_SECONDS))
+            .collect();
+        assert!(messages.iter().any(|msg| matches!(
+            msg,
+            ServerMessage::PlayerDisconnected { player_id: id } if *id == player_id
+        )));
+        assert!(!game.players.contains_key(&player_id));
+        assert_eq!(game.sessions.resume(token), None);
+    }
+
+    #[tokio::test]
+    async fn test_spectators_watch_without_a_player() {
+        use crate::commands::{Command, SpectateCommand};
+        use shared::{ProtocolFeature, PROTOCOL_VERSION};
+
+        let game = tokio::sync::RwLock::new(create_test_game());
+        let (tx, mut rx) = tokio::sync::broadcast::channel(100);
+        let spectator_id = Uuid::new_v4();
+
+        let spectate = SpectateCommand {
+            protocol_version: PROTOCOL_VERSION,
+            features: vec![ProtocolFeature::Compression],
+        };
+        spectate.execute(&game, spectator_id, &tx).await.unwrap();
+        assert!(matches!(
+            rx.try_recv().unwrap(),
+            (id, ServerMessage::SpectatingGame { features })
+                if id == spectator_id && features == vec![ProtocolFeature::Compression]
+        ));
+        assert!(std::iter::from_fn(|| rx.try_recv().ok())
+            .any(|(_, msg)| matches!(msg, ServerMessage::GameState { .. })));
+
+        let game = game.read().await;
+        assert!(game.players.is_empty());
+        assert!(game.spectators.contains(&spectator_id));
+    }
+
+    #[test]
+    fn test_players_only_announce_turns() {
+        use shared::Facing;
+
+        let mut game = create_test_game();
+        let player_id = add_test_player(&mut game, "Walker", Some(TeamId::Red));
+        let player = game.players.get_mut(&player_id).unwrap();
+
+        assert!(matches!(
+            player.face_towards((1.0, -1.0)),
+            Some(ServerMessage::PlayerFacing {
+                facing: Facing::NorthEast,
+                ..
+            })
+        ));
+        // Still walking the same way, or stopped
+        assert!(player.face_towards((0.7, -0.7)).is_none());
+        assert!(player.face_towards((0.0, 0.0)).is_none());
+        assert_eq!(player.facing, Facing::NorthEast);
+
+        let full_state = serde_json::to_value(game.get_full_state()).unwrap();
+        assert_eq!(
+            full_state["players"][player_id.to_string()]["facing"],
+            Facing::NorthEast as u8
+        );
+    }
+
+    #[test]
+    fn test_boarders_get_through_broken_doors() {
+        let mut game = create_test_game();
+        let player_id = add_test_player(&mut game, "Boarder", Some(TeamId::Red));
+        let blue_mech_id = get_team_mech(&game, TeamId::Blue).unwrap().id;
+
+        // Knock the enemy mech down to a quarter health
+        let blue_mech = game.mechs.get_mut(&blue_mech_id).unwrap();
+        blue_mech.health = blue_mech.max_health / 4;
+        let doors = MechDoorPositions::from_mech_position(blue_mech.position);
+
+        let events = simulate_walk_to_tile(&mut game, player_id, doors.left_door);
+        process_tile_events_sync(&mut game, events);
+
+        assert_player_in_mech(&game, player_id, blue_mech_id);
+    }
+
+    /// A red boarder and a blue defender side by side on the blue mech's
+    /// first floor
+    fn setup_boarding(game: &mut Game) -> (Uuid, Uuid, Uuid) {
+        let boarder = add_test_player(game, "Boarder", Some(TeamId::Red));
+        let defender = add_test_player(game, "Defender", Some(TeamId::Blue));
+        let mech_id = get_team_mech(game, TeamId::Blue).unwrap().id;
+        for (player_id, x) in [(boarder, 5), (defender, 6)] {
+            game.players.get_mut(&player_id).unwrap().location = PlayerLocation::InsideMech {
+                mech_id,
+                pos: MechInteriorPos::new(0, TilePos::new(x, 5)),
+            };
+        }
+        (boarder, defender, mech_id)
+    }
+
+    #[test]
+    fn test_defenders_knock_out_boarders() {
+        use crate::systems::{boarding::BoardingSystem, GameSystem};
+        use shared::{GameError, MELEE_COOLDOWN, MELEE_DAMAGE};
+
+        let mut game = create_test_game();
+        let (boarder, defender, _) = setup_boarding(&mut game);
+        let mut boarding = BoardingSystem::new();
+
+        let swings = PLAYER_MAX_HEALTH.div_ceil(MELEE_DAMAGE);
+        let mut messages = Vec::new();
+        for _ in 0..swings {
+            messages = boarding.attack(&mut game, defender).unwrap();
+            assert!(matches!(
+                boarding.attack(&mut game, defender),
+                Err(GameError::WeaponOnCooldown { .. })
+            ));
+            boarding.update(&mut game, MELEE_COOLDOWN);
+        }
+
+        assert!(messages.iter().any(|msg| matches!(
+            msg,
+            ServerMessage::PlayerKilled { player_id, killer: Some(killer), .. }
+                if *player_id == boarder && *killer == defender
+        )));
+        assert_player_outside_world(&game, boarder);
+        assert_eq!(game.players[&boarder].health, PLAYER_MAX_HEALTH);
+
+        // Nobody left in reach
+        assert!(matches!(
+            boarding.attack(&mut game, defender),
+            Err(GameError::NoValidTarget)
+        ));
+    }
+
+    #[test]
+    fn test_sabotage_takes_a_station_offline() {
+        use crate::systems::{boarding::BoardingSystem, GameSystem};
+        use shared::{GameError, SABOTAGE_CHANNEL_TIME};
+
+        let mut game = create_test_game();
+        let (boarder, defender, mech_id) = setup_boarding(&mut game);
+        let (station_id, station_pos) = game.mechs[&mech_id]
+            .stations
+            .iter()
+            .find(|(_, station)| station.floor == 0)
+            .map(|(id, station)| (*id, station.position))
+            .unwrap();
+
+        // The defender is crewing the station the boarder is standing at
+        game.mechs
+            .get_mut(&mech_id)
+            .unwrap()
+            .stations
+            .get_mut(&station_id)
+            .unwrap()
+            .operated_by = Some(defender);
+        game.players.get_mut(&defender).unwrap().operating_station = Some(station_id);
+        game.players.get_mut(&boarder).unwrap().location = PlayerLocation::InsideMech {
+            mech_id,
+            pos: MechInteriorPos::new(0, station_pos),
+        };
+
+        let mut boarding = BoardingSystem::new();
+        boarding.sabotage(&mut game, boarder, mech_id, station_id);
+        boarding.update(&mut game, SABOTAGE_CHANNEL_TIME + 0.1);
+
+        assert_eq!(game.players[&defender].operating_station, None);
+        assert_eq!(game.mechs[&mech_id].stations[&station_id].operated_by, None);
+
+        // Climbing back on doesn't help until the effect wears off
+        game.players.get_mut(&defender).unwrap().operating_station = Some(station_id);
+        assert!(matches!(
+            game.check_can_operate(defender, mech_id),
+            Err(GameError::BlockedByStatusEffect { id, .. }) if id == station_id
+        ));
+    }
+
+    #[test]
+    fn test_sabotage_sounds_the_alarm_until_it_dies_down() {
+        use crate::systems::{boarding::BoardingSystem, GameSystem};
+        use shared::SABOTAGE_ALARM_DURATION;
+
+        let mut game = create_test_game();
+        let (boarder, _defender, mech_id) = setup_boarding(&mut game);
+        let (station_id, station_pos) = game.mechs[&mech_id]
+            .stations
+            .iter()
+            .find(|(_, station)| station.floor == 0)
+            .map(|(id, station)| (*id, station.position))
+            .unwrap();
+        game.players.get_mut(&boarder).unwrap().location = PlayerLocation::InsideMech {
+            mech_id,
+            pos: MechInteriorPos::new(0, station_pos),
+        };
+
+        let mut boarding = BoardingSystem::new();
+        let messages = boarding.sabotage(&mut game, boarder, mech_id, station_id);
+        assert!(messages.iter().any(|msg| matches!(
+            msg,
+            ServerMessage::MechAlarm { mech_id: id, alarm: Some(alarm) }
+                if *id == mech_id && alarm.station_id == station_id && alarm.intruder == boarder
+        )));
+
+        // Working away keeps it sounding without repeating the news
+        let messages = boarding.update(&mut game, 0.1);
+        assert!(!messages
+            .iter()
+            .any(|msg| matches!(msg, ServerMessage::MechAlarm { .. })));
+
+        // Once they stop, it quiets down on its own
+        boarding.sabotage(&mut game, boarder, mech_id, station_id);
+        boarding.update(&mut game, SABOTAGE_ALARM_DURATION / 2.0);
+        assert!(game.mechs[&mech_id].alarm.is_some());
+        let messages = boarding.update(&mut game, SABOTAGE_ALARM_DURATION / 2.0 + 0.1);
+        assert!(game.mechs[&mech_id].alarm.is_none());
+        assert!(messages
+            .iter()
+            .any(|msg| matches!(msg, ServerMessage::MechAlarm { alarm: None, .. })));
+    }
+
+    #[test]
+    fn test_ai_weights_apply_to_new_ais_and_retune_live() {
+        use crate::rooms::RoomConfig;
+        use crate::testing_modes::TestingConfig;
+
+        let weights = ai::UtilityWeights::from_toml("engine_unmanned = 3.5").unwrap();
+        assert_eq!(weights.engine_manned, ai::UtilityWeights::default().engine_manned);
+        let config = RoomConfig {
+            ai_weights: weights.clone(),
+            ..Default::default()
+        };
+        let mut game = config.create_game(TestingConfig::create_normal_config());
+
+        // Only utility AIs (difficulty above 0.7) score tasks by weight
+        let utility = game.add_ai_player(0.9, None).unwrap();
+        let simple = game.add_ai_player(0.2, None).unwrap();
+        assert_eq!(game.ai_weights(utility), Some(weights.clone()));
+        assert_eq!(game.ai_weights(simple), None);
+
+        let retuned = ai::UtilityWeights {
+            stuck_penalty: 0.1,
+            ..weights
+        };
+        assert!(game.set_ai_weights(utility, retuned.clone()));
+        assert!(!game.set_ai_weights(simple, retuned.clone()));
+        assert_eq!(game.ai_weights(utility), Some(retuned));
+    }
+
+    #[test]
+    fn test_ai_views_only_show_what_the_team_can_see() {
+        let mut game = create_test_game();
+        let scout_id = add_test_player(&mut game, "Scout", Some(TeamId::Red));
+        let scout_pos = WorldPos::new(50.0 * TILE_SIZE, 5.0 * TILE_SIZE);
+        game.players.get_mut(&scout_id).unwrap().location = PlayerLocation::OutsideWorld(scout_pos);
+
+        let views = game.ai_views();
+        let view_of = |team| {
+            views
+                .iter()
+                .find(|view| view.team_info.team_id == team)
+                .unwrap()
+        };
+        let (red, blue) = (view_of(TeamId::Red), view_of(TeamId::Blue));
+
+        // The scout is out of blue's sight, but red always knows its own
+        assert!(red.players.iter().any(|p| p.id == scout_id));
+        assert!(!blue.players.iter().any(|p| p.id == scout_id));
+        assert_eq!(red.team_info.player_count, 1);
+
+        // Nobody learns who's crewing the other side's stations
+        for mech in blue.mechs.iter().filter(|mech| mech.team == TeamId::Red) {
+            assert!(mech.resource_inventory.is_empty());
+            assert!(mech.stations.iter().all(|s| s.operated_by.is_none()));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_lasers_hit_where_a_lagging_shooter_saw_the_target() {
+        use shared::{StationType, WEAPON_MAX_RANGE};
+
+        let mut game = create_test_game();
+        let gunner = add_test_player(&mut game, "Gunner", Some(TeamId::Red));
+        let red = get_team_mech(&game, TeamId::Red).unwrap().id;
+        let blue = get_team_mech(&game, TeamId::Blue).unwrap().id;
+        let (tx, _rx) = tokio::sync::broadcast::channel(100);
+        game.mechs.get_mut(&blue).unwrap().shield = 0;
+
+        // Blue sat in range for a while, then just slipped out of it
+        let red_tile = game.mechs[&red].position;
+        let in_range = TilePos::new(red_tile.x + 20, red_tile.y);
+        let out_of_range = TilePos::new(red_tile.x + WEAPON_MAX_RANGE as i32 + 10, red_tile.y);
+        for tile in [in_range, in_range, in_range, in_range, out_of_range] {
+            let mech = game.mechs.get_mut(&blue).unwrap();
+            mech.position = tile;
+            mech.world_position = tile.to_world();
+            game.lag_compensation.record(
+                0.05,
+                game.mechs
+                    .values()
+                    .map(|mech| (mech.id, mech.world_position)),
+            );
+        }
+        // Without a measured round trip the shot goes where blue is now
+        let health = game.mechs[&blue].health;
+        crate::client::handle_station_button(
+            &mut game,
+            gunner,
+            red,
+            StationType::WeaponLaser,
+            0,
+            &tx,
+        )
+        .await;
+        assert_eq!(game.mechs[&blue].health, health);
+
+        // A shooter a tenth of a second behind still saw it in range
+        game.lag_compensation.record_round_trip(gunner, 0.1);
+        crate::client::handle_station_button(
+            &mut game,
+            gunner,
+            red,
+            StationType::WeaponLaser,
+            0,
+            &tx,
+        )
+        .await;
+        assert!(game.mechs[&blue].health < health);
+    }
+
+    #[tokio::test]
+    async fn test_a_lethal_laser_wrecks_the_mech_and_ejects_its_crew() {
+        use shared::StationType;
+
+        let mut game = create_test_game();
+        let gunner = add_test_player(&mut game, "Gunner", Some(TeamId::Red));
+        let crew = add_test_player(&mut game, "Crew", Some(TeamId::Blue));
+        let red = get_team_mech(&game, TeamId::Red).unwrap().id;
+        let blue = get_team_mech(&game, TeamId::Blue).unwrap().id;
+        let (tx, mut rx) = tokio::sync::broadcast::channel(100);
+
+        let red_tile = game.mechs[&red].position;
+        let mech = game.mechs.get_mut(&blue).unwrap();
+        mech.position = TilePos::new(red_tile.x + 20, red_tile.y);
+        mech.world_position = mech.position.to_world();
+        mech.shield = 0;
+        mech.health = 1;
+        game.players.get_mut(&crew).unwrap().location = PlayerLocation::InsideMech {
+            mech_id: blue,
+            pos: MechInteriorPos::new(0, TilePos::new(2, 2)),
+        };
+
+        crate::client::handle_station_button(
+            &mut game,
+            gunner,
+            red,
+            StationType::WeaponLaser,
+            0,
+            &tx,
+        )
+        .await;
+        assert_eq!(game.mechs[&blue].health, 0);
+        assert!(matches!(
+            game.players[&crew].location,
+            PlayerLocation::OutsideWorld(_)
+        ));
+
+        // The wreck isn't a target any more
+        while rx.try_recv().is_ok() {}
+        crate::client::handle_station_button(
+            &mut game,
+            gunner,
+            red,
+            StationType::WeaponLaser,
+            0,
+            &tx,
+        )
+        .await;
+        while let Ok((_, message)) = rx.try_recv() {
+            assert!(!matches!(
+                message,
+                ServerMessage::MechDamaged { mech_id, .. } if mech_id == blue
+            ));
+        }
+    }
+
+    #[test]
+    fn test_away_players_hand_their_station_to_a_stand_in() {
+        let mut game = create_test_game();
+        game.idle = crate::idle::IdleTracker::new(30.0, true);
+        let (crew, _, mech_id) = setup_boarding(&mut game);
+        let station_id = *game.mechs[&mech_id].stations.keys().next().unwrap();
+        game.mechs
+            .get_mut(&mech_id)
+            .unwrap()
+            .stations
+            .get_mut(&station_id)
+            .unwrap()
+            .operated_by = Some(crew);
+        game.players.get_mut(&crew).unwrap().operating_station = Some(station_id);
+        let crew_before = game.players.len();
+
+        let messages = game.update(31.0);
+        assert!(messages.iter().any(|msg| matches!(
+            msg,
+            ServerMessage::PlayerAfk { player_id, afk: true } if *player_id == crew
+        )));
+        assert_eq!(game.players[&crew].operating_station, None);
+        let stand_in = game.mechs[&mech_id].stations[&station_id]
+            .operated_by
+            .unwrap();
+        assert_ne!(stand_in, crew);
+        assert_eq!(game.players.len(), crew_before + 1);
+        match game.get_full_state() {
+            ServerMessage::GameState { players, .. } => assert!(players[&crew].afk),
+            _ => unreachable!(),
+        }
+
+        // Autofill doesn't count the stand-in as crew to trim
+        game.balance_ai_crew();
+        assert!(game.players.contains_key(&stand_in));
+
+        // Any input brings them back and sends the stand-in home
+        let messages = game.mark_active(crew);
+        assert!(messages.iter().any(|msg| matches!(
+            msg,
+            ServerMessage::PlayerAfk { player_id, afk: false } if *player_id == crew
+        )));
+        assert!(!game.players.contains_key(&stand_in));
+        assert_eq!(game.mechs[&mech_id].stations[&station_id].operated_by, None);
+        assert!(game.mark_active(crew).is_empty());
+    }
+
+    #[test]
+    fn test_dash_moves_the_player_then_recharges() {
+        use shared::{Facing, GameError, DASH_COOLDOWN};
+
+        let mut game = create_test_game();
+        game.match_time = 100.0;
+        let player_id = add_test_player(&mut game, "Sprinter", Some(TeamId::Red));
+        let start = WorldPos::new(50.5 * TILE_SIZE, 5.5 * TILE_SIZE);
+        game.players.get_mut(&player_id).unwrap().location = PlayerLocation::OutsideWorld(start);
+
+        let messages = game.dash(player_id, Facing::East).unwrap();
+        let Some(ServerMessage::PlayerDashed { from, to, .. }) = messages.last() else {
+            panic!("expected a dash, got {messages:?}");
+        };
+        assert_eq!(*from, PlayerLocation::OutsideWorld(start));
+        assert_eq!(game.players[&player_id].location, *to);
+        assert_eq!(game.players[&player_id].facing, Facing::East);
+
+        assert!(matches!(
+            game.dash(player_id, Facing::West),
+            Err(GameError::AbilityOnCooldown { .. })
+        ));
+        game.match_time += DASH_COOLDOWN;
+        assert!(game.dash(player_id, Facing::West).is_ok());
+    }
+
+    #[test]
+    fn test_night_fog_shortens_sight_and_rain_slows_walkers() {
+        use crate::movement::MovementResolver;
+        use crate::systems::{environment::EnvironmentSystem, GameSystem};
+        use shared::{SightShape, Weather, DAY_LENGTH};
+
+        let mut game = create_test_game();
+        game.sight = SightShape::Radial;
+        let scout_id = add_test_player(&mut game, "Scout", Some(TeamId::Red));
+        let scout_pos = WorldPos::new(50.5 * TILE_SIZE, 5.5 * TILE_SIZE);
+        game.players.get_mut(&scout_id).unwrap().location =
+            PlayerLocation::OutsideWorld(scout_pos);
+        let far = scout_pos.to_tile_pos().offset(6, 0);
+        assert!(game.team_visibility()[&TeamId::Red].contains(&far));
+
+        // Fog at midnight: sight is recast shorter
+        let mut environment = EnvironmentSystem::new();
+        game.environment.time_of_day = DAY_LENGTH / 2.0;
+        game.environment.weather = Weather::Fog;
+        environment.update(&mut game, 0.0);
+        game.schedule_vision();
+        assert!(!game.team_visibility()[&TeamId::Red].contains(&far));
+
+        // Rain doesn't hide anything but slows crew on foot
+        let walked = |game: &Game| {
+            let Some(PlayerLocation::OutsideWorld(pos)) =
+                MovementResolver::new().resolve(game, scout_id, (1.0, 0.0), 0.5)
+            else {
+                panic!("scout didn't move");
+            };
+            pos.x - scout_pos.x
+        };
+        game.environment.weather = Weather::Clear;
+        let dry = walked(&game);
+        game.environment.weather = Weather::Rain;
+        let wet = walked(&game);
+        assert!(wet < dry && wet > 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_verbose_connections_get_collision_shapes() {
+        use crate::commands::{Command, VerboseStateCommand};
+        use shared::{WeaponType, COLLISION_SHAPES_UPDATE_TICKS};
+
+        let mut game = create_test_game();
+        let watcher = add_test_player(&mut game, "Watcher", Some(TeamId::Red));
+        let bystander = add_test_player(&mut game, "Bystander", Some(TeamId::Blue));
+        let mech_id = get_team_mech(&game, TeamId::Red).unwrap().id;
+        let shot = game.create_projectile(
+            WorldPos::new(10.0 * TILE_SIZE, 10.0 * TILE_SIZE),
+            (TILE_SIZE, 0.0),
+            10,
+            mech_id,
+            30.0,
+            WeaponType::Projectile,
+        );
+        let game = tokio::sync::RwLock::new(game);
+        let (tx, mut rx) = tokio::sync::broadcast::channel(100);
+
+        let verbose = VerboseStateCommand { enabled: true };
+        verbose.execute(&game, watcher, &tx).await.unwrap();
+        let mut game = game.into_inner();
+        for _ in 0..COLLISION_SHAPES_UPDATE_TICKS {
+            game.update(0.1);
+            game.tick_count += 1;
+        }
+        game.send_collision_shapes(&tx);
+
+        let (target, message) = rx.try_recv().unwrap();
+        assert_eq!(target, watcher);
+        let ServerMessage::CollisionShapes { shapes } = message else {
+            panic!("expected collision shapes");
+        };
+        assert!(shapes.players.iter().any(|p| p.player_id == bystander));
+        assert!(shapes.mechs.iter().any(|m| m.mech_id == mech_id && m.shield.is_some()));
+        assert!(!shapes.stations.is_empty());
+        let path = shapes.projectiles.iter().find(|p| p.id == shot).unwrap();
+        assert_eq!(path.trail.len(), COLLISION_SHAPES_UPDATE_TICKS as usize);
+        assert!(rx.try_recv().is_err());
+
+        // Nobody else is sent anything once the watcher disconnects
+        game.collision_debug.forget(watcher);
+        game.send_collision_shapes(&tx);
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_barricades_cost_scrap_hold_off_boarders_and_break() {
+        use crate::movement::MovementResolver;
+        use shared::{
+            GameError, ResourceType, BARRICADE_MAX_HEALTH, BARRICADE_SCRAP_COST, MELEE_DAMAGE,
+        };
+
+        let mut game = create_test_game();
+        let builder = add_test_player(&mut game, "Builder", Some(TeamId::Red));
+        let boarder = add_test_player(&mut game, "Boarder", Some(TeamId::Blue));
+        let mech_id = get_team_mech(&game, TeamId::Red).unwrap().id;
+        let place = |game: &mut Game, player_id: Uuid, x: i32| {
+            game.players.get_mut(&player_id).unwrap().location = PlayerLocation::InsideMech {
+                mech_id,
+                pos: MechInteriorPos::new(0, TilePos::new(x, 7)),
+            };
+        };
+        let set_scrap = |game: &mut Game, amount: u32| {
+            game.mechs
+                .get_mut(&mech_id)
+                .unwrap()
+                .resource_inventory
+                .insert(ResourceType::ScrapMetal, amount);
+        };
+        place(&mut game, builder, 2);
+        let site = TilePos::new(3, 7);
+
+        set_scrap(&mut game, 0);
+        assert!(matches!(
+            game.place_barricade(builder, site),
+            Err(GameError::InsufficientResources { .. })
+        ));
+        set_scrap(&mut game, BARRICADE_SCRAP_COST * 2);
+        assert!(
+            game.place_barricade(builder, TilePos::new(5, 7)).is_err(),
+            "out of reach"
+        );
+        let messages = game.place_barricade(builder, site).unwrap();
+        assert!(matches!(
+            messages[0],
+            ServerMessage::BarricadeUpdated {
+                barricade: Some(_),
+                ..
+            }
+        ));
+        assert_eq!(
+            game.mechs[&mech_id].resource_inventory[&ResourceType::ScrapMetal],
+            BARRICADE_SCRAP_COST
+        );
+        assert!(matches!(
+            game.place_barricade(builder, site),
+            Err(GameError::TileNotClear { .. })
+        ));
+
+        // The boarder walks into it and stays put, while the crew walks through
+        place(&mut game, boarder, 4);
+        let mut resolver = MovementResolver::new();
+        for _ in 0..20 {
+            assert!(resolver
+                .resolve(&game, boarder, (-1.0, 0.0), 0.033)
+                .is_none());
+        }
+        assert!(resolver.resolve(&game, builder, (1.0, 0.0), 0.5).is_some());
+
+        // With nobody in reach, a swing lands on the barricade
+        let messages = game.handle_melee_attack(boarder).unwrap();
+        let floor_barricades =
+            |game: &Game| game.mechs[&mech_id].interior.floors[0].barricades.clone();
+        assert!(matches!(
+            messages[0],
+            ServerMessage::BarricadeUpdated {
+                barricade: Some(_),
+                ..
+            }
+        ));
+        assert_eq!(
+            floor_barricades(&game)[&site].health,
+            BARRICADE_MAX_HEALTH - MELEE_DAMAGE
+        );
+        let pos = MechInteriorPos::new(0, site);
+        while game.damage_barricade(mech_id, pos, MELEE_DAMAGE).is_some() {}
+        assert!(floor_barricades(&game).is_empty());
+
+        // Taking one down hands back what's left of its scrap
+        set_scrap(&mut game, BARRICADE_SCRAP_COST);
+        game.place_barricade(builder, site).unwrap();
+        game.damage_barricade(mech_id, pos, BARRICADE_MAX_HEALTH / 2);
+        game.remove_barricade(builder, site).unwrap();
+        assert_eq!(
+            game.mechs[&mech_id].resource_inventory[&ResourceType::ScrapMetal],
+            BARRICADE_SCRAP_COST / 2
+        );
+        assert!(matches!(
+            game.remove_barricade(builder, site),
+            Err(GameError::BarricadeNotFound { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_scanners_are_built_at_electrical_and_ping_privately() {
+        use crate::commands::{Command, UseItemCommand};
+        use crate::game::GroundItem;
+        use shared::{
+            GameError, ItemType, ResourceType, StationType, SCANNER_COOLDOWN, SCANNER_HINT_RANGE,
+        };
+
+        let mut game = create_test_game();
+        game.match_time = 100.0;
+        let player_id = add_test_player(&mut game, "Prospector", Some(TeamId::Red));
+        let mech_id = get_team_mech(&game, TeamId::Red).unwrap().id;
+        let (tx, mut rx) = tokio::sync::broadcast::channel(100);
+
+        // Wired up out of the mech's stores
+        let stores = &mut game.mechs.get_mut(&mech_id).unwrap().resource_inventory;
+        stores.insert(ResourceType::Wiring, 1);
+        stores.insert(ResourceType::ComputerComponents, 1);
+        for _ in 0..2 {
+            crate::client::handle_station_button(
+                &mut game,
+                player_id,
+                mech_id,
+                StationType::Electrical,
+                2,
+                &tx,
+            )
+            .await;
+        }
+        let player = &game.players[&player_id];
+        assert_eq!(player.inventory.active_item(), Some(ItemType::Scanner));
+        assert_eq!(player.inventory.resources().count(), 0);
+        assert!(game.mechs[&mech_id]
+            .resource_inventory
+            .values()
+            .all(|count| *count == 0));
+
+        // One resource close by, one a way off to the east, one out of range
+        let here = TilePos::new(50, 50);
+        game.entity_storage.resource_pickups.clear();
+        for (dx, resource_type) in [
+            (5, ResourceType::ScrapMetal),
+            (20, ResourceType::Batteries),
+            (SCANNER_HINT_RANGE as i32 + 5, ResourceType::Wiring),
+        ] {
+            let id = Uuid::new_v4();
+            game.ground_items.insert(
+                id,
+                GroundItem {
+                    id,
+                    item: ItemType::Resource(resource_type),
+                    position: TilePos::new(50 + dx, 50).to_world_center(),
+                    dropped_by: None,
+                },
+            );
+        }
+        game.players.get_mut(&player_id).unwrap().location =
+            PlayerLocation::OutsideWorld(here.to_world_center());
+        while rx.try_recv().is_ok() {}
+
+        let game = tokio::sync::RwLock::new(game);
+        UseItemCommand.execute(&game, player_id, &tx).await.unwrap();
+        let (target, ping) = rx.try_recv().unwrap();
+        assert_eq!(target, player_id);
+        let ServerMessage::ScannerPing {
+            contacts, bearings, ..
+        } = ping
+        else {
+            panic!("expected a scanner ping, got {ping:?}");
+        };
+        assert_eq!(contacts.len(), 1);
+        assert_eq!(contacts[0].1, ResourceType::ScrapMetal);
+        assert_eq!(bearings.len(), 1);
+        assert!(bearings[0].abs() < 0.01);
+
+        assert!(matches!(
+            UseItemCommand.execute(&game, player_id, &tx).await,
+            Err(GameError::AbilityOnCooldown { .. })
+        ));
+        game.write().await.match_time += SCANNER_COOLDOWN;
+        assert!(UseItemCommand.execute(&game, player_id, &tx).await.is_ok());
+    }
+
+    #[test]
+    fn test_hangar_holds_the_match_until_everyone_is_ready() {
+        use crate::hangar;
+        use crate::rooms::RoomConfig;
+        use crate::systems::ai::AISystem;
+        use crate::testing_modes::TestingConfig;
+        use shared::CrewRole;
+
+        let config = RoomConfig {
+            hangar: Some(30.0),
+            ..Default::default()
+        };
+        let mut game = config.create_game(TestingConfig::create_normal_config());
+        let ai_id = game.add_ai_player(0.9, None).unwrap();
+        let team = game.players[&ai_id].team;
+        let pilot = add_test_player(&mut game, "Pilot", Some(team));
+        let other = add_test_player(&mut game, "Other", None);
+
+        // Crews wait aboard their own mech
+        let PlayerLocation::InsideMech { mech_id, .. } = game.players[&pilot].location else {
+            panic!("players should board their mech in the hangar");
+        };
+        assert_eq!(game.mechs[&mech_id].team, team);
+
+        let messages = game.update(0.5);
+        assert_eq!(game.match_time, 0.0);
+        assert!(messages.iter().any(|msg| matches!(
+            msg,
+            ServerMessage::HangarUpdated { crew, .. } if crew.len() == 2
+        )));
+
+        hangar::pick_role(&mut game, pilot, Some(CrewRole::Pilot)).unwrap();
+        hangar::set_ready(&mut game, pilot, true).unwrap();
+        let messages = game.update(0.5);
+        assert!(game.hangar.is_some());
+        assert!(messages.iter().any(|msg| matches!(
+            msg,
+            ServerMessage::HangarUpdated { crew, .. }
+                if crew.iter().any(|c| c.player_id == pilot && c.ready && c.role == Some(CrewRole::Pilot))
+        )));
+
+        hangar::set_ready(&mut game, other, true).unwrap();
+        let messages = game.update(0.5);
+        assert!(game.hangar.is_none());
+        assert!(messages
+            .iter()
+            .any(|msg| matches!(msg, ServerMessage::MatchStarted)));
+        assert!(hangar::set_ready(&mut game, other, false).is_err());
+
+        // The AI gets the first role its team left open
+        let hat = game
+            .system_manager
+            .get_system_mut::<AISystem>()
+            .and_then(|ai_system| ai_system.get_ai_debug_info(ai_id))
+            .unwrap()
+            .current_hat;
+        assert_eq!(hat, "Gunner");
+    }
+
+    #[test]
+    fn test_hangar_starts_the_match_when_time_runs_out() {
+        use crate::rooms::RoomConfig;
+        use crate::testing_modes::TestingConfig;
+
+        let config = RoomConfig {
+            hangar: Some(1.0),
+            ..Default::default()
+        };
+        let mut game = config.create_game(TestingConfig::create_normal_config());
+        add_test_player(&mut game, "Idler", None);
+
+        game.update(0.6);
+        assert!(game.hangar.is_some());
+        assert!(game
+            .update(0.6)
+            .iter()
+            .any(|msg| matches!(msg, ServerMessage::MatchStarted)));
+        game.update(0.5);
+        assert!(game.match_time > 0.0);
+    }
+
+    #[test]
+    fn test_state_checksum_matches_a_mirror_of_the_full_state() {
+        let mut game = create_test_game();
+        let player_id = add_test_player(&mut game, "Mirror", None);
+        game.update(0.1);
+
+        // What a client rebuilds from the snapshot hashes the same
+        let ServerMessage::GameState {
+            players,
+            mechs,
+            resources,
+            ..
+        } = game.get_full_state()
+        else {
+            unreachable!();
+        };
+        let mut mirror = StateDump::default();
+        for player in players.values() {
+            mirror.add_player(player.id, player.team, player.location, player.health);
+        }
+        for mech in mechs.values() {
+            mirror.add_mech(
+                mech.id,
+                mech.team,
+                mech.position,
+                mech.health,
+                mech.shield,
+                &mech.resource_inventory,
+            );
+        }
+        for resource in &resources {
+            mirror.add_resource(resource.id, resource.resource_type, resource.position);
+        }
+        let ServerMessage::StateChecksum { hashes, .. } = game.state_checksum() else {
+            unreachable!();
+        };
+        assert_eq!(hashes, mirror.hashes());
+
+        // A missed update shows up in the right subsystem
+        game.players.get_mut(&player_id).unwrap().health -= 10;
+        let drifted = game.state_dump();
+        assert_eq!(
+            drifted.hashes().mismatches(&mirror.hashes()),
+            vec!["players"]
+        );
+        assert_eq!(
+            drifted.diff(&mirror),
+            vec![format!("players {player_id} differs")]
+        );
+    }
+
+    #[test]
+    fn test_same_seed_plays_out_the_same_match() {
+        use crate::rooms::RoomConfig;
+        use crate::testing_modes::TestingConfig;
+
+        let player_id = Uuid::new_v4();
+        let play = |seed: u64| {
+            let config = RoomConfig {
+                seed: Some(seed),
+                ..Default::default()
+            };
+            let mut game = config.create_game(TestingConfig::create_normal_config());
+            game.add_player(player_id, "Rerun".to_string(), Some(TeamId::Red));
+            game.add_ai_player(0.9, None).unwrap();
+            for _ in 0..30 {
+                game.update(0.1);
+            }
+            game
+        };
+
+        let (first, second) = (play(7), play(7));
+        assert_eq!(first.rng.seed(), 7);
+        assert_eq!(first.state_dump(), second.state_dump());
+        let ai_player = |game: &Game| {
+            let ai = game.players.values().find(|p| p.id != player_id).unwrap();
+            (ai.id, ai.location)
+        };
+        assert_eq!(ai_player(&first), ai_player(&second));
+        let station_ids = |game: &Game| {
+            let mut ids: Vec<Uuid> = game
+                .mechs
+                .values()
+                .flat_map(|mech| mech.stations.keys().copied())
+                .collect();
+            ids.sort();
+            ids
+        };
+        assert_eq!(station_ids(&first), station_ids(&second));
+
+        let other = play(8);
+        assert_ne!(first.state_dump().mechs, other.state_dump().mechs);
+    }
 }
@@ -0,0 +1,193 @@
+//! The hangar before a match. Rooms that open with one keep their crews
+//! aboard their mechs while everyone picks the job they want and readies
+//! up; nothing moves until the match starts, once every player is ready or
+//! the clock runs out. Roles nobody on a team picked go to that team's AI
+//! crew as the hat they start out in.
+
+use crate::game::Game;
+use crate::systems::ai::AISystem;
+use shared::*;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Pick {
+    role: Option<CrewRole>,
+    ready: bool,
+}
+
+/// Everyone's picks and how long is left
+pub struct Hangar {
+    picks: HashMap<Uuid, Pick>,
+    time_left: f32,
+    /// Picks changed since clients were last told
+    changed: bool,
+}
+
+impl Hangar {
+    pub fn new(duration: f32) -> Self {
+        Self {
+            picks: HashMap::new(),
+            time_left: duration,
+            changed: true,
+        }
+    }
+
+    fn pick_mut(&mut self, player_id: Uuid) -> GameResult<&mut Pick> {
+        self.changed = true;
+        self.picks
+            .get_mut(&player_id)
+            .ok_or_else(|| GameError::player_not_found(player_id))
+    }
+
+    /// Whether the match can start before the clock runs out: somebody's
+    /// here and all of them are ready
+    pub fn all_ready(&self) -> bool {
+        !self.picks.is_empty() && self.picks.values().all(|pick| pick.ready)
+    }
+}
+
+/// Open the hangar for `duration` seconds; players board as they join
+pub fn open(game: &mut Game, duration: f32) {
+    game.hangar = Some(Hangar::new(duration));
+}
+
+/// Put a player who joined while the hangar is open aboard their team's
+/// mech and on the roster
+pub fn board(game: &mut Game, player_id: Uuid) {
+    let Some(hangar) = game.hangar.as_mut() else {
+        return;
+    };
+    hangar.picks.insert(player_id, Pick::default());
+    hangar.changed = true;
+
+    let Some(player) = game.players.get_mut(&player_id) else {
+        return;
+    };
+    let Some(mech) = game
+        .mechs
+        .values_mut()
+        .find(|mech| mech.team == player.team)
+    else {
+        return;
+    };
+    let pos = MechInteriorPos::new(
+        0,
+        TilePos::new(FLOOR_WIDTH_TILES / 2, FLOOR_HEIGHT_TILES - 2),
+    );
+    player.location = PlayerLocation::InsideMech {
+        mech_id: mech.id,
+        pos,
+    };
+    mech.interior.set_player_floor(player_id, 0);
+}
+
+/// Take a player who left off the roster
+pub fn leave(game: &mut Game, player_id: Uuid) {
+    if let Some(hangar) = game.hangar.as_mut() {
+        hangar.changed |= hangar.picks.remove(&player_id).is_some();
+    }
+}
+
+fn open_hangar(game: &mut Game) -> GameResult<&mut Hangar> {
+    game.hangar
+        .as_mut()
+        .ok_or_else(|| GameError::invalid_state("The match has already started"))
+}
+
+pub fn pick_role(game: &mut Game, player_id: Uuid, role: Option<CrewRole>) -> GameResult<()> {
+    open_hangar(game)?.pick_mut(player_id)?.role = role;
+    Ok(())
+}
+
+pub fn set_ready(game: &mut Game, player_id: Uuid, ready: bool) -> GameResult<()> {
+    open_hangar(game)?.pick_mut(player_id)?.ready = ready;
+    Ok(())
+}
+
+/// Everyone's picks, for clients
+pub fn roster(game: &Game) -> Option<ServerMessage> {
+    let hangar = game.hangar.as_ref()?;
+    let mut crew: Vec<HangarCrew> = hangar
+        .picks
+        .iter()
+        .filter_map(|(player_id, pick)| {
+            let player = game.players.get(player_id)?;
+            Some(HangarCrew {
+                player_id: *player_id,
+                name: player.name.clone(),
+                team: player.team,
+                role: pick.role,
+                ready: pick.ready,
+            })
+        })
+        .collect();
+    crew.sort_by(|a, b| a.name.cmp(&b.name).then(a.player_id.cmp(&b.player_id)));
+    Some(ServerMessage::HangarUpdated {
+        crew,
+        time_left: hangar.time_left,
+    })
+}
+
+/// Count the hangar down, telling clients about new picks, and start the
+/// match once everyone's ready or time's up
+pub fn update(game: &mut Game, delta_time: f32) -> Vec<ServerMessage> {
+    let Some(hangar) = game.hangar.as_mut() else {
+        return Vec::new();
+    };
+    hangar.time_left = (hangar.time_left - delta_time).max(0.0);
+    if hangar.all_ready() || hangar.time_left <= 0.0 {
+        return start(game);
+    }
+    if !std::mem::take(&mut hangar.changed) {
+        return Vec::new();
+    }
+    roster(game).into_iter().collect()
+}
+
+/// Close the hangar and hand the roles nobody picked to each team's AIs
+pub fn start(game: &mut Game) -> Vec<ServerMessage> {
+    let Some(hangar) = game.hangar.take() else {
+        return Vec::new();
+    };
+    let picked: Vec<(TeamId, CrewRole)> = hangar
+        .picks
+        .iter()
+        .filter_map(|(player_id, pick)| Some((game.players.get(player_id)?.team, pick.role?)))
+        .collect();
+
+    if let Some(ai_system) = game.system_manager.get_system_mut::<AISystem>() {
+        for team in [TeamId::Red, TeamId::Blue] {
+            let unfilled = CrewRole::ALL
+                .into_iter()
+                .filter(|role| !picked.contains(&(team, *role)));
+            let mut ais = ai_system.ai_players_on_team(team);
+            ais.sort();
+            for (ai_id, role) in ais.into_iter().zip(unfilled) {
+                ai_system.assign_hat(ai_id, ai::Hat::for_role(role));
+                log::debug!("AI {ai_id} takes the unfilled {} role", role.name());
+            }
+        }
+    }
+
+    log::info!("Hangar closed, match starting");
+    vec![ServerMessage::MatchStarted]
+}
+
+/// Whether a message can be acted on while the hangar is open; anything
+/// that would play the match early waits
+pub fn allows(msg: &ClientMessage) -> bool {
+    matches!(
+        msg,
+        ClientMessage::JoinGame { .. }
+            | ClientMessage::Spectate { .. }
+            | ClientMessage::ChatMessage { .. }
+            | ClientMessage::DevCommand { .. }
+            | ClientMessage::VerboseState { .. }
+            | ClientMessage::Look { .. }
+            | ClientMessage::SelectInventorySlot { .. }
+            | ClientMessage::PickRole { .. }
+            | ClientMessage::SetReady { .. }
+            | ClientMessage::Subscribe { .. }
+    )
+}
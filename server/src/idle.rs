@@ -0,0 +1,105 @@
+//! Spotting players who have stopped playing. A player who sends no input
+//! for `timeout` seconds counts as away: they're let go of any station so it
+//! doesn't sit locked, everyone is told so the UI can badge them, and when
+//! stand-ins are on an AI takes their seat until they're back.
+
+use shared::*;
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+pub struct IdleTracker {
+    /// Seconds without input before a player counts as away
+    pub timeout: f32,
+    /// Whether an AI crews an away player's station until they return
+    pub stand_ins: bool,
+    /// Seconds since each player's last input
+    idle: HashMap<Uuid, f32>,
+    away: HashSet<Uuid>,
+    /// Away players and the AI holding their station
+    covering: HashMap<Uuid, Uuid>,
+}
+
+impl Default for IdleTracker {
+    fn default() -> Self {
+        Self::new(AFK_TIMEOUT_SECONDS, false)
+    }
+}
+
+impl IdleTracker {
+    pub fn new(timeout: f32, stand_ins: bool) -> Self {
+        Self {
+            timeout,
+            stand_ins,
+            idle: HashMap::new(),
+            away: HashSet::new(),
+            covering: HashMap::new(),
+        }
+    }
+
+    /// Restart a player's idle timer; true if they were away until now
+    pub fn note_input(&mut self, player_id: Uuid) -> bool {
+        self.idle.insert(player_id, 0.0);
+        self.away.remove(&player_id)
+    }
+
+    /// Age every timer by `delta_time`, forgetting anyone not in `players`.
+    /// Returns the players who went away just now.
+    pub fn tick(&mut self, delta_time: f32, players: impl IntoIterator<Item = Uuid>) -> Vec<Uuid> {
+        let players: HashSet<Uuid> = players.into_iter().collect();
+        self.idle.retain(|id, _| players.contains(id));
+        self.away.retain(|id| players.contains(id));
+
+        let mut gone_away = Vec::new();
+        for player_id in players {
+            let idle = self.idle.entry(player_id).or_insert(0.0);
+            *idle += delta_time;
+            if *idle >= self.timeout && self.away.insert(player_id) {
+                gone_away.push(player_id);
+            }
+        }
+        gone_away
+    }
+
+    pub fn is_away(&self, player_id: Uuid) -> bool {
+        self.away.contains(&player_id)
+    }
+
+    /// Remember that `stand_in` is holding an away player's station
+    pub fn cover(&mut self, player_id: Uuid, stand_in: Uuid) {
+        self.covering.insert(player_id, stand_in);
+    }
+
+    /// The AI covering for a player, no longer needed
+    pub fn take_stand_in(&mut self, player_id: Uuid) -> Option<Uuid> {
+        self.covering.remove(&player_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_players_go_away_once_and_come_back_on_input() {
+        let mut tracker = IdleTracker::new(10.0, false);
+        let (busy, idle) = (Uuid::new_v4(), Uuid::new_v4());
+
+        assert!(tracker.tick(6.0, [busy, idle]).is_empty());
+        tracker.note_input(busy);
+        assert_eq!(tracker.tick(6.0, [busy, idle]), vec![idle]);
+        assert!(tracker.is_away(idle));
+        assert!(!tracker.is_away(busy));
+
+        // Reported once, not every tick they stay away
+        assert!(tracker.tick(6.0, [busy, idle]).contains(&busy));
+        assert!(!tracker.tick(6.0, [busy, idle]).contains(&idle));
+
+        assert!(tracker.note_input(idle));
+        assert!(!tracker.is_away(idle));
+        assert!(!tracker.note_input(idle));
+
+        // Leaving the game forgets them
+        tracker.tick(60.0, [busy]);
+        assert!(tracker.tick(0.0, [idle]).is_empty());
+    }
+}
@@ -0,0 +1,119 @@
+//! Lag compensation for instant weapons. A client's view of the world is
+//! about one round trip old by the time its fire command arrives, so laser
+//! hit checks look up where targets were that long ago instead of where
+//! they are now. The rewind is capped so a slow or lying connection can't
+//! reach further into the past than `LAG_COMP_MAX_REWIND`.
+
+use shared::*;
+use std::collections::{HashMap, HashSet, VecDeque};
+use uuid::Uuid;
+
+/// Recent positions of mechs and players, and each client's measured
+/// round trip
+#[derive(Default)]
+pub struct LagCompensation {
+    /// Seconds of simulation recorded so far
+    now: f64,
+    /// Samples per entity, oldest first
+    history: HashMap<Uuid, VecDeque<(f64, WorldPos)>>,
+    round_trips: HashMap<Uuid, f32>,
+}
+
+impl LagCompensation {
+    /// Note where everything is after a tick of `delta_time` seconds,
+    /// forgetting anything that has left the game
+    pub fn record(
+        &mut self,
+        delta_time: f32,
+        positions: impl IntoIterator<Item = (Uuid, WorldPos)>,
+    ) {
+        self.now += f64::from(delta_time);
+        let now = self.now;
+        let mut seen = HashSet::new();
+        for (id, position) in positions {
+            let samples = self.history.entry(id).or_default();
+            samples.push_back((now, position));
+            while samples
+                .front()
+                .is_some_and(|(time, _)| now - time > f64::from(LAG_COMP_HISTORY_SECONDS))
+            {
+                samples.pop_front();
+            }
+            seen.insert(id);
+        }
+        self.history.retain(|id, _| seen.contains(id));
+    }
+
+    /// Fold a fresh round-trip measurement into the player's estimate
+    pub fn record_round_trip(&mut self, player_id: Uuid, seconds: f32) {
+        let estimate = self.round_trips.entry(player_id).or_insert(seconds);
+        *estimate += (seconds - *estimate) * LAG_COMP_RTT_SMOOTHING;
+    }
+
+    pub fn forget_player(&mut self, player_id: Uuid) {
+        self.round_trips.remove(&player_id);
+    }
+
+    /// How far back to look for a shot from this player
+    pub fn rewind_for(&self, player_id: Uuid) -> f32 {
+        self.round_trips
+            .get(&player_id)
+            .map_or(0.0, |rtt| rtt.clamp(0.0, LAG_COMP_MAX_REWIND))
+    }
+
+    /// Where an entity was `rewind` seconds ago, between the two samples
+    /// either side; `None` when it has no history
+    pub fn position_at(&self, id: Uuid, rewind: f32) -> Option<WorldPos> {
+        let samples = self.history.get(&id)?;
+        let time = self.now - f64::from(rewind.clamp(0.0, LAG_COMP_MAX_REWIND));
+        let after = samples.iter().position(|(at, _)| *at >= time);
+        match after {
+            Some(0) => samples.front().map(|(_, position)| *position),
+            Some(i) => {
+                let (t0, p0) = samples[i - 1];
+                let (t1, p1) = samples[i];
+                let f = if t1 > t0 {
+                    ((time - t0) / (t1 - t0)) as f32
+                } else {
+                    1.0
+                };
+                Some(WorldPos::new(
+                    p0.x + (p1.x - p0.x) * f,
+                    p0.y + (p1.y - p0.y) * f,
+                ))
+            }
+            None => samples.back().map(|(_, position)| *position),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rewinds_between_samples_and_caps_the_window() {
+        let mut lag = LagCompensation::default();
+        let mech = Uuid::new_v4();
+        for step in 0..30 {
+            lag.record(0.05, [(mech, WorldPos::new(step as f32 * 10.0, 0.0))]);
+        }
+
+        let x_at = |rewind| lag.position_at(mech, rewind).unwrap().x;
+        // Now at x = 290; 0.075s ago was halfway between 270 and 280
+        assert_eq!(x_at(0.0), 290.0);
+        assert!((x_at(0.075) - 275.0).abs() < 0.01);
+        // Asking for more than the cap only goes back as far as the cap
+        let capped = 290.0 - LAG_COMP_MAX_REWIND / 0.05 * 10.0;
+        assert!((x_at(10.0) - capped).abs() < 0.01);
+
+        let player = Uuid::new_v4();
+        assert_eq!(lag.rewind_for(player), 0.0);
+        lag.record_round_trip(player, 5.0);
+        assert_eq!(lag.rewind_for(player), LAG_COMP_MAX_REWIND);
+
+        // Entities that stop being recorded are forgotten
+        lag.record(0.05, []);
+        assert_eq!(lag.position_at(mech, 0.0), None);
+    }
+}
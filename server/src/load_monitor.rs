@@ -0,0 +1,139 @@
+//! Decides how much the game loop gives up when it can't keep up. Every
+//! frame's work is weighed against the frame budget and smoothed; past a
+//! threshold the load level rises at once, and it only eases off after
+//! holding for a while well under it, so a slow frame or two doesn't make
+//! clients' effects flicker on and off.
+
+use shared::*;
+use std::time::Duration;
+
+pub struct LoadMonitor {
+    level: ServerLoad,
+    /// Smoothed share of the frame budget spent working
+    load: f32,
+    /// Work done since the last frame finished
+    work: Duration,
+    /// Frames since the level last changed
+    held: u32,
+}
+
+impl LoadMonitor {
+    pub fn new() -> Self {
+        Self {
+            level: ServerLoad::Normal,
+            load: 0.0,
+            work: Duration::ZERO,
+            held: 0,
+        }
+    }
+
+    pub fn level(&self) -> ServerLoad {
+        self.level
+    }
+
+    pub fn load(&self) -> f32 {
+        self.load
+    }
+
+    /// Count time spent simulating or broadcasting toward this frame
+    pub fn record_work(&mut self, elapsed: Duration) {
+        self.work += elapsed;
+    }
+
+    /// Weigh the frame's work against its budget. A frame that hit the
+    /// catch-up cap counts as a full budget however quick its steps were.
+    /// The new level if it changed.
+    pub fn finish_frame(&mut self, budget: Duration, capped: bool) -> Option<ServerLoad> {
+        let mut sample = std::mem::take(&mut self.work).as_secs_f32() / budget.as_secs_f32();
+        if capped {
+            sample = sample.max(1.0);
+        }
+        self.load += (sample - self.load) * LOAD_SMOOTHING;
+        self.held = self.held.saturating_add(1);
+
+        let target = if self.load >= LOAD_OVERLOADED {
+            ServerLoad::Overloaded
+        } else if self.load >= LOAD_STRAINED {
+            ServerLoad::Strained
+        } else {
+            ServerLoad::Normal
+        };
+        let level = if target > self.level {
+            target
+        } else if self.held >= LOAD_MIN_HOLD_FRAMES
+            && self.load < threshold(self.level) - LOAD_RECOVERY_MARGIN
+        {
+            // One step at a time on the way down
+            match self.level {
+                ServerLoad::Overloaded => ServerLoad::Strained,
+                _ => ServerLoad::Normal,
+            }
+        } else {
+            self.level
+        };
+
+        if level == self.level {
+            return None;
+        }
+        self.level = level;
+        self.held = 0;
+        Some(level)
+    }
+}
+
+impl Default for LoadMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Smoothed load that brings on `level`
+fn threshold(level: ServerLoad) -> f32 {
+    match level {
+        ServerLoad::Normal => 0.0,
+        ServerLoad::Strained => LOAD_STRAINED,
+        ServerLoad::Overloaded => LOAD_OVERLOADED,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BUDGET: Duration = Duration::from_millis(33);
+
+    fn run(monitor: &mut LoadMonitor, work: Duration, frames: u32) -> Vec<ServerLoad> {
+        let mut changes = Vec::new();
+        for _ in 0..frames {
+            monitor.record_work(work);
+            changes.extend(monitor.finish_frame(BUDGET, false));
+        }
+        changes
+    }
+
+    #[test]
+    fn test_load_rises_at_once_and_eases_off_a_step_at_a_time() {
+        let mut monitor = LoadMonitor::new();
+        assert!(run(&mut monitor, Duration::from_millis(10), 100).is_empty());
+
+        // Frames taking longer than their budget climb through both levels
+        let changes = run(&mut monitor, Duration::from_millis(50), 60);
+        assert_eq!(changes, vec![ServerLoad::Strained, ServerLoad::Overloaded]);
+
+        // Light frames again: held a while, then down one level at a time
+        let changes = run(&mut monitor, Duration::from_millis(5), 30);
+        assert!(changes.is_empty());
+        let changes = run(&mut monitor, Duration::from_millis(5), 400);
+        assert_eq!(changes, vec![ServerLoad::Strained, ServerLoad::Normal]);
+    }
+
+    #[test]
+    fn test_capped_frames_count_as_a_full_budget() {
+        let mut monitor = LoadMonitor::new();
+        for _ in 0..60 {
+            monitor.finish_frame(BUDGET, true);
+        }
+        assert_eq!(monitor.level(), ServerLoad::Overloaded);
+        assert!(monitor.load() > LOAD_OVERLOADED);
+    }
+}
@@ -1,6 +1,7 @@
 use anyhow::Result;
 use axum::{
-    extract::{ws::WebSocket, Path, State, WebSocketUpgrade},
+    extract::{ws::WebSocket, ConnectInfo, Path, Query, State, WebSocketUpgrade},
+    http::StatusCode,
     response::IntoResponse,
     routing::{get, post},
     Json, Router,
@@ -14,16 +15,48 @@ use uuid::Uuid;
 
 use shared::*;
 
+mod annotations;
+mod api_error;
+mod bandwidth;
+mod bots;
 mod client;
+mod collision_debug;
 mod commands;
 mod entity_storage;
+mod event_log;
+mod events;
 mod game;
+mod game_modes;
+mod hangar;
 mod game_tests;
+mod idle;
+mod lag_compensation;
+mod load_monitor;
+mod master;
+mod matchmaking;
 mod mech_generation;
+mod movement;
+mod overlay;
+mod profiler;
+mod ratings;
+mod replay;
+mod rng;
+mod rooms;
+mod scanner;
+mod send_queue;
+mod sessions;
+mod shutdown;
 mod spatial_collision;
+mod station_upgrades;
 mod systems;
 mod testing_modes;
+mod tick_clock;
+mod vision_scheduler;
+mod voice;
+#[cfg(feature = "wasm-bots")]
+mod wasm_bots;
 
+use api_error::ApiError;
 use client::handle_client;
 use game::Game;
 
@@ -31,12 +64,56 @@ use game::Game;
 pub struct AppState {
     pub game: Arc<RwLock<Game>>,
     pub tx: broadcast::Sender<(Uuid, ServerMessage)>,
+    pub send_queues: send_queue::SendQueues,
+    /// What every client socket has been sent, by message type
+    pub bandwidth: bandwidth::SharedBandwidth,
+    pub tick_metrics: tick_clock::SharedTickMetrics,
+    pub event_log: event_log::SharedEventLog,
+    pub shutdown: shutdown::Shutdown,
+    pub profiler: profiler::Profiler,
+    /// Simulated latency and loss applied to everything sent to clients
+    pub net_conditions: NetConditions,
+    /// Every room, including the default one `game` and `tx` belong to
+    pub rooms: rooms::Rooms,
+    /// Other servers that announced themselves here, for server browsers
+    pub master_list: master::MasterList,
+    /// Players queued for pick-up games
+    pub matchmaking: matchmaking::Matchmaking,
+    /// Loads the modules behind `wasm:` personalities
+    #[cfg(feature = "wasm-bots")]
+    pub wasm_bots: Arc<wasm_bots::WasmBots>,
+}
+
+impl AppState {
+    /// This state pointed at another room's game
+    fn for_room(&self, room: &rooms::Room) -> Self {
+        Self {
+            game: room.game.clone(),
+            tx: room.tx.clone(),
+            tick_metrics: room.tick_metrics.clone(),
+            event_log: room.event_log.clone(),
+            ..self.clone()
+        }
+    }
+
+    /// This state pointed at the named room, opening it if it's new
+    async fn in_room(&self, room: Option<&str>) -> Result<Self, ApiError> {
+        match room {
+            None | Some(DEFAULT_ROOM) => Ok(self.clone()),
+            Some(name) => {
+                let room = self.rooms.get_or_create(name).await?;
+                Ok(self.for_room(&room))
+            }
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
 struct AddAIRequest {
     difficulty: Option<f32>,
     personality: Option<String>,
+    /// Name of a registered bot; the built-in AI is used when absent
+    bot: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -46,6 +123,43 @@ struct AddAIResponse {
     team: TeamId,
 }
 
+#[derive(Debug, Deserialize)]
+struct ProfileQuery {
+    secs: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EventsQuery {
+    since_tick: Option<u64>,
+    /// A message type name such as `MechDamaged`
+    #[serde(rename = "type")]
+    event_type: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct EventsResponse {
+    /// Tick of the oldest event still held, so callers can tell when what
+    /// they asked for has already been dropped
+    oldest_tick: Option<u64>,
+    events: Vec<event_log::LoggedEvent>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StateHashQuery {
+    /// Include every entity's hash, for diffing against a client's dump
+    #[serde(default)]
+    entities: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct StateHashResponse {
+    tick: u64,
+    combined: u64,
+    hashes: StateHashes,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    entities: Option<StateDump>,
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     env_logger::init();
@@ -67,75 +181,529 @@ async fn main() -> Result<()> {
         );
     }
 
-    // Create broadcast channel for game messages
-    let (tx, _) = broadcast::channel(BROADCAST_CHANNEL_SIZE);
+    let ai_autofill_min_crew = parse_ai_autofill_arg(&args);
+    if let Some(min_crew) = ai_autofill_min_crew {
+        log::info!("AI autofill enabled: keeping at least {min_crew} crew per team");
+    }
 
-    // Initialize game state with testing configuration
-    let game = Arc::new(RwLock::new(Game::new_with_testing(testing_config)));
+    let team_vision = !args.iter().any(|arg| arg == "--no-team-vision");
+    if !team_vision {
+        log::info!("Team vision disabled: players only see what they see themselves");
+    }
 
-    // Initialize 2 mechs for testing
-    {
-        let mut game = game.write().await;
-        game.spawn_initial_resources();
+    // Crew sight outside: --sight radial|cone|cone:<width>:<rear range>,
+    // fatal when malformed
+    let sight = flag_value(&args, "--sight")
+        .map(|spec| spec.parse::<SightShape>())
+        .transpose()?
+        .unwrap_or_default();
+
+    let dev_mode = args.iter().any(|arg| arg == "--dev-mode");
+    if dev_mode {
+        log::warn!("Dev mode enabled: clients may teleport and grant resources");
+    }
+
+    // Simulated bad network from MECH_NET_* variables, dev mode only
+    let net_conditions = match NetConditions::from_env() {
+        Some(conditions) if dev_mode => {
+            log::warn!("Simulating network conditions on sends: {conditions}");
+            conditions
+        }
+        Some(_) => {
+            log::warn!("Ignoring MECH_NET_* network conditions without --dev-mode");
+            NetConditions::default()
+        }
+        None => NetConditions::default(),
+    };
+
+    // Optional arena from the map editor: --map <path.json>
+    let arena_map = flag_value(&args, "--map").and_then(|path| {
+        match std::fs::read_to_string(path).map_err(|e| e.to_string()).and_then(|json| {
+            ArenaMap::from_json(&json).map_err(|e| e.to_string())
+        }) {
+            Ok(map) => {
+                log::info!("Loaded arena map '{}' from {path}", map.name);
+                Some(map)
+            }
+            Err(e) => {
+                log::error!("Failed to load arena map {path}: {e}, using the default arena");
+                None
+            }
+        }
+    });
+
+    // Optional station definitions: --stations <path.toml>. Playing by
+    // broken rules is worse than not starting, so a bad file is fatal.
+    let stations =
+        StationRegistry::load(flag_value(&args, "--stations").map(std::path::Path::new))?;
+
+    // Optional resource rarities and spawn tables: --resources <path.toml>,
+    // fatal when broken for the same reason
+    let spawn_tables =
+        SpawnTables::load(flag_value(&args, "--resources").map(std::path::Path::new))?;
+
+    // Optional guided scenario: --tutorial <path.txt>, fatal when broken
+    // for the same reason
+    let tutorial = flag_value(&args, "--tutorial")
+        .map(|path| systems::tutorial::TutorialScript::load(std::path::Path::new(path)))
+        .transpose()?;
+
+    // Optional win conditions: --mode deathmatch|race|escort
+    let game_mode = flag_value(&args, "--mode")
+        .map(|name| name.parse::<GameModeKind>())
+        .transpose()?;
+    if let Some(mode) = game_mode {
+        log::info!("Playing {}", mode.title());
+    }
+
+    // Optional utility AI tuning: --ai-weights <path.toml>, fatal when
+    // broken like the other rule files
+    let ai_weights = match flag_value(&args, "--ai-weights") {
+        Some(path) => {
+            let weights = ai::UtilityWeights::load(std::path::Path::new(path))
+                .map_err(GameError::invalid_input)?;
+            log::info!("Loaded AI weights from {path}");
+            weights
+        }
+        None => ai::UtilityWeights::default(),
+    };
+
+    // How often the AIs decide, off the game tick: --ai-hz <N>
+    let mut systems = systems::SystemConfig::default();
+    if let Some(hz) = flag_value(&args, "--ai-hz") {
+        match hz.parse::<f32>() {
+            Ok(hz) if hz > 0.0 => systems.ai_hz = hz,
+            _ => log::warn!("--ai-hz expects updates per second, ignoring it"),
+        }
+    }
+    // How much of a frame an AI update may take before it's logged: --ai-budget <share>
+    if let Some(share) = flag_value(&args, "--ai-budget") {
+        match share.parse::<f32>() {
+            Ok(share) if share > 0.0 => systems.ai_budget_share = share,
+            _ => log::warn!("--ai-budget expects a share of the frame like 0.25, ignoring it"),
+        }
+    }
+
+    // Idle players: --afk-seconds <N> before they count as away, and
+    // --afk-stand-ins to have an AI hold their station meanwhile
+    let mut afk_timeout = AFK_TIMEOUT_SECONDS;
+    if let Some(seconds) = flag_value(&args, "--afk-seconds") {
+        match seconds.parse::<f32>() {
+            Ok(seconds) if seconds > 0.0 => afk_timeout = seconds,
+            _ => log::warn!("--afk-seconds expects a number of seconds, ignoring it"),
+        }
+    }
+    let afk_stand_ins = args.iter().any(|arg| arg == "--afk-stand-ins");
+
+    // Pre-match hangar where crews pick roles and ready up: --hangar <seconds>
+    let hangar = match flag_value(&args, "--hangar") {
+        Some(seconds) => match seconds.parse::<f32>() {
+            Ok(seconds) if seconds > 0.0 => Some(seconds),
+            _ => {
+                log::warn!("--hangar expects a number of seconds, opening without one");
+                None
+            }
+        },
+        None => None,
+    };
+
+    // Hashes after every full state for clients to check for desyncs
+    let state_checksums = args.iter().any(|arg| arg == "--state-checksums");
+
+    // Replay a match's rolls exactly: --seed <number>
+    let seed = match flag_value(&args, "--seed") {
+        Some(seed) => match seed.parse::<u64>() {
+            Ok(seed) => Some(seed),
+            Err(_) => {
+                log::warn!("--seed expects a whole number, rolling a fresh one");
+                None
+            }
+        },
+        None => None,
+    };
+
+    // Settings every room starts from
+    let room_defaults = rooms::RoomConfig {
+        arena_map: arena_map.unwrap_or_default(),
+        ai_autofill_min_crew,
+        team_vision,
+        sight,
+        dev_mode,
+        stations,
+        spawn_tables,
+        tutorial,
+        game_mode,
+        access: rooms::RoomAccess::default(),
+        ai_weights,
+        systems,
+        afk_timeout,
+        afk_stand_ins,
+        hangar,
+        state_checksums,
+        seed,
+    };
+
+    // Initialize the default room's game with the testing configuration
+    let mut game = room_defaults.create_game(testing_config);
+
+    // Registered bots to start with: --bot <name>, repeatable
+    for bot in flag_values(&args, "--bot") {
+        if game.add_bot_player(bot, AI_AUTOFILL_DIFFICULTY).is_none() {
+            log::error!("Unknown bot '{bot}'");
+        }
+    }
+    game.balance_ai_crew();
+
+    // Lock the default room: --password <secret>, --max-players <N>
+    game.access.password = flag_value(&args, "--password").map(|p| p.to_string());
+    if let Some(max) = flag_value(&args, "--max-players") {
+        match max.parse() {
+            Ok(max) if max > 0 => game.access.max_players = Some(max),
+            _ => log::warn!("--max-players expects a player count, ignoring it"),
+        }
     }
 
+    // Optional structured decision log: --ai-decision-log <path.jsonl>
+    if let Some(path) = flag_value(&args, "--ai-decision-log") {
+        if let Some(ai_system) = game
+            .system_manager
+            .get_system_mut::<systems::ai::AISystem>()
+        {
+            match ai_system.enable_decision_log(path.into()) {
+                Ok(()) => log::info!("Writing AI decisions to {path}"),
+                Err(e) => log::error!("Failed to open AI decision log {path}: {e}"),
+            }
+        }
+    }
+
+    // Start the default room's game loop, then any rooms set up with
+    // --room <name>[:size=WxH,ai=N,vision=team|solo,sight=radial,mode=race,
+    // title=...,max=N,password=...], repeatable
+    let rooms = rooms::Rooms::new(room_defaults.clone());
+    let default_room = rooms
+        .open(DEFAULT_ROOM, game, true)
+        .await
+        .expect("the default room is the first one opened");
+    for spec in flag_values(&args, "--room") {
+        match rooms::parse_room_spec(spec, &room_defaults) {
+            Ok((name, _)) if name == DEFAULT_ROOM => {
+                log::error!("Ignoring --room {spec}: the default room uses the server-wide options")
+            }
+            Ok((name, config)) => {
+                let game = config.create_game(testing_modes::TestingConfig::create_normal_config());
+                if let Err(e) = rooms.open(&name, game, true).await {
+                    log::error!("Ignoring --room {spec}: {e}");
+                }
+            }
+            Err(e) => log::error!("Ignoring --room {spec}: {e}"),
+        }
+    }
+    rooms.spawn_reaper();
+
+    // Optional match recording for the replay viewer: --record-replay <path.replay>
+    if let Some(path) = flag_value(&args, "--record-replay") {
+        match std::fs::File::create(path) {
+            Ok(file) => {
+                tokio::spawn(replay::run_recorder(
+                    default_room.game.clone(),
+                    default_room.tx.subscribe(),
+                    file,
+                ));
+                log::info!("Recording the match to {path}");
+            }
+            Err(e) => log::error!("Failed to create replay file {path}: {e}"),
+        }
+    }
+
+    // Pick-up games rated by records kept at --ratings <path.json>, which
+    // are fatal when unreadable rather than quietly started over
+    let matchmaking = matchmaking::Matchmaking::new(match flag_value(&args, "--ratings") {
+        Some(path) => {
+            let ratings = ratings::Ratings::load(path.into())?;
+            log::info!("Keeping player ratings in {path}");
+            ratings
+        }
+        None => ratings::Ratings::default(),
+    });
+    matchmaking.spawn(rooms.clone());
+
     let app_state = AppState {
-        game: Arc::clone(&game),
-        tx: tx.clone(),
+        game: default_room.game.clone(),
+        tx: default_room.tx.clone(),
+        send_queues: Default::default(),
+        bandwidth: Default::default(),
+        tick_metrics: default_room.tick_metrics.clone(),
+        event_log: default_room.event_log.clone(),
+        shutdown: Default::default(),
+        profiler: Default::default(),
+        net_conditions,
+        rooms,
+        master_list: Default::default(),
+        matchmaking,
+        // Modules for `wasm:` personalities: --wasm-bots <dir>
+        #[cfg(feature = "wasm-bots")]
+        wasm_bots: Arc::new(wasm_bots::WasmBots::new(
+            flag_value(&args, "--wasm-bots")
+                .map(String::as_str)
+                .unwrap_or(wasm_bots::DEFAULT_WASM_BOT_DIR),
+            Default::default(),
+        )),
     };
 
-    // Start game update loop
-    let game_loop = game.clone();
-    let tx_loop = tx.clone();
+    // How we show up in server browsers: --server-name <name>, and
+    // --public-host <host> when the address we're reached at isn't the one
+    // our announcements come from
+    let announcement = master::Announcement {
+        name: flag_value(&args, "--server-name")
+            .cloned()
+            .unwrap_or_else(|| DEFAULT_SERVER_NAME.to_string()),
+        host: flag_value(&args, "--public-host")
+            .cloned()
+            .unwrap_or_default(),
+        port: SERVER_PORT,
+    };
+    // Optional master list to announce ourselves to: --master <http://host:port>
+    if let Some(master_url) = flag_value(&args, "--master") {
+        master::spawn_registration(
+            master_url.clone(),
+            announcement.clone(),
+            app_state.rooms.clone(),
+        );
+    }
+    if !args.iter().any(|arg| arg == "--no-lan") {
+        master::spawn_lan_responder(announcement, app_state.rooms.clone());
+    }
+
+    // First Ctrl-C warns players and shuts down gracefully, a second one
+    // gives up waiting
+    let ctrl_c_shutdown = app_state.shutdown.clone();
     tokio::spawn(async move {
-        game_loop::run_game_loop(game_loop, tx_loop).await;
+        while tokio::signal::ctrl_c().await.is_ok() {
+            if !ctrl_c_shutdown.request(shutdown::ShutdownRequest {
+                reason: "Server stopped by operator".to_string(),
+                seconds: SHUTDOWN_GRACE_SECONDS,
+            }) {
+                log::warn!("Second Ctrl-C, exiting immediately");
+                std::process::exit(1);
+            }
+        }
     });
 
+    // Optional state snapshot written on shutdown: --shutdown-snapshot <path.json>
+    let shutdown_snapshot = flag_value(&args, "--shutdown-snapshot").map(std::path::PathBuf::from);
+
     // Build our application with routes
     let app = Router::new()
         .route("/", get(index))
         .route("/ws", get(websocket_handler))
+        .route("/voice", get(voice_handler))
+        .route("/rooms", get(list_rooms))
+        .route(MATCHMAKING_PATH, post(join_queue))
+        .route(
+            &format!("{MATCHMAKING_PATH}/:ticket"),
+            get(queue_status).delete(leave_queue),
+        )
+        .route(
+            MASTER_LIST_PATH,
+            get(master::list_servers).post(master::register_server),
+        )
+        .route("/overlay", get(overlay::page))
+        .route("/overlay/score", get(overlay::score))
         .route("/ai/add", post(add_ai_player))
         .route("/debug", get(debug_websocket_handler))
         .route("/debug/ai/:id", get(get_ai_debug_info))
+        .route("/metrics", get(get_metrics))
+        .route("/debug/queues", get(get_send_queue_stats))
+        .route("/debug/ticks", get(get_tick_metrics))
+        .route("/debug/events", get(get_events))
+        .route("/debug/state-hash", get(get_state_hash))
+        .route("/debug/profile", get(capture_profile))
+        .route("/admin/shutdown", post(admin_shutdown))
         .layer(
             ServiceBuilder::new()
                 .layer(axum::middleware::from_fn(cors_layer))
                 .into_inner(),
         )
-        .with_state(app_state);
+        .with_state(app_state.clone());
 
     // Run it
     let addr = SocketAddr::from(([0, 0, 0, 0], SERVER_PORT));
     log::info!("Server listening on {addr}");
 
     let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(async move {
+        let request = app_state.shutdown.requested().await;
+        shutdown::drain(&app_state, request, shutdown_snapshot.as_deref()).await;
+    })
+    .await?;
+    log::info!("Server shut down cleanly");
     Ok(())
 }
 
+/// Value following a `--flag` on the command line
+fn flag_value<'a>(args: &'a [String], flag: &str) -> Option<&'a String> {
+    let pos = args.iter().position(|arg| arg == flag)?;
+    args.get(pos + 1)
+}
+
+/// Every value following a repeatable `--flag`
+fn flag_values<'a>(args: &'a [String], flag: &'a str) -> impl Iterator<Item = &'a String> {
+    args.windows(2)
+        .filter(move |pair| pair[0] == flag)
+        .map(|pair| &pair[1])
+}
+
+/// Parse `--ai-autofill <N>`: minimum crew per team, topped up with AI players
+fn parse_ai_autofill_arg(args: &[String]) -> Option<usize> {
+    args.iter().position(|arg| arg == "--ai-autofill")?;
+    match flag_value(args, "--ai-autofill").map(|n| n.parse::<usize>()) {
+        Some(Ok(min_crew)) => Some(min_crew),
+        _ => {
+            log::warn!("--ai-autofill expects a crew size, autofill disabled");
+            None
+        }
+    }
+}
+
 async fn index() -> &'static str {
     "Mech Battle Arena Server - Connect via WebSocket at /ws"
 }
 
+/// Options a client can request when opening the game websocket
+#[derive(Deserialize)]
+struct WebSocketParams {
+    /// `deflate` to receive compressed frames, see `shared::encode_server_frame`
+    compression: Option<String>,
+    /// Match to join, opened on first use; the default room when absent
+    room: Option<String>,
+    /// `SessionResume` token of a player whose connection dropped, to take
+    /// them back
+    resume: Option<Uuid>,
+}
+
 async fn websocket_handler(
     ws: WebSocketUpgrade,
+    Query(params): Query<WebSocketParams>,
     State(state): State<AppState>,
-) -> impl IntoResponse {
-    ws.on_upgrade(move |socket| handle_socket(socket, state))
+) -> axum::response::Response {
+    if state.shutdown.is_requested() {
+        return ApiError::from(GameError::ServerShuttingDown).into_response();
+    }
+    let compression = params.compression.as_deref() == Some("deflate");
+    let state = match state.in_room(params.room.as_deref()).await {
+        Ok(state) => state,
+        Err(e) => return e.into_response(),
+    };
+    ws.on_upgrade(move |socket| handle_socket(socket, state, compression, params.resume))
+        .into_response()
+}
+
+/// Opening the voice socket, with the key the join handed out
+#[derive(Deserialize)]
+struct VoiceParams {
+    key: Uuid,
+    /// Room the key was issued in; the default room when absent
+    room: Option<String>,
+}
+
+async fn voice_handler(
+    ws: WebSocketUpgrade,
+    Query(params): Query<VoiceParams>,
+    State(state): State<AppState>,
+) -> axum::response::Response {
+    let state = match state.in_room(params.room.as_deref()).await {
+        Ok(state) => state,
+        Err(e) => return e.into_response(),
+    };
+    let relay = state.game.read().await.voice.clone();
+    let Some((player_id, team)) = relay.lock().unwrap().redeem(params.key) else {
+        return ApiError::from(NetworkError::Unauthorized { id: params.key }).into_response();
+    };
+    ws.on_upgrade(move |socket| voice::handle_voice_socket(socket, relay, player_id, team))
+        .into_response()
 }
 
-async fn handle_socket(socket: WebSocket, state: AppState) {
-    let player_id = Uuid::new_v4();
-    handle_client(socket, player_id, state).await;
+/// Open rooms and who is in them, for server browsers
+async fn list_rooms(State(state): State<AppState>) -> Json<Vec<rooms::RoomInfo>> {
+    Json(state.rooms.list().await)
+}
+
+/// Join the pick-up queue
+async fn join_queue(
+    State(state): State<AppState>,
+    Json(request): Json<QueueRequest>,
+) -> Result<Json<QueueTicket>, ApiError> {
+    if state.shutdown.is_requested() {
+        return Err(GameError::ServerShuttingDown.into());
+    }
+    Ok(Json(state.matchmaking.join(&request)?))
+}
+
+/// Where a queued player stands, and their room once they're matched
+async fn queue_status(
+    State(state): State<AppState>,
+    Path(ticket): Path<Uuid>,
+) -> Result<Json<QueueStatus>, ApiError> {
+    Ok(Json(state.matchmaking.status(ticket)?))
+}
+
+async fn leave_queue(
+    State(state): State<AppState>,
+    Path(ticket): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    state.matchmaking.leave(ticket)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn handle_socket(
+    socket: WebSocket,
+    state: AppState,
+    compression: bool,
+    resume: Option<Uuid>,
+) {
+    let resumed = match resume {
+        Some(token) => state.game.write().await.sessions.resume(token),
+        None => None,
+    };
+    if let Some(player_id) = resumed {
+        log::info!("Player {player_id} is back");
+    }
+    let player_id = resumed.unwrap_or_else(Uuid::new_v4);
+    handle_client(socket, player_id, state, compression).await;
 }
 
 async fn add_ai_player(
     State(state): State<AppState>,
     Json(request): Json<AddAIRequest>,
-) -> Result<Json<AddAIResponse>, &'static str> {
+) -> Result<Json<AddAIResponse>, ApiError> {
     let difficulty = request.difficulty.unwrap_or(0.5).clamp(0.0, 1.0);
 
+    // WASM brains are loaded before taking the game lock; compiling a
+    // module can take a moment
+    let wasm_bot = match request
+        .personality
+        .as_deref()
+        .and_then(|p| p.strip_prefix("wasm:"))
+    {
+        #[cfg(feature = "wasm-bots")]
+        Some(module) => Some((
+            state.wasm_bots.load(module, Uuid::new_v4())?,
+            format!("Wasm_{module}"),
+        )),
+        #[cfg(not(feature = "wasm-bots"))]
+        Some(module) => {
+            return Err(GameError::invalid_input(format!(
+                "Can't load WASM bot '{module}': this server was built without plugin support"
+            ))
+            .into())
+        }
+        None => None,
+    };
+
     // Parse personality
     let personality = request
         .personality
@@ -151,26 +719,66 @@ async fn add_ai_player(
     // Add AI player to the game
     let mut game = state.game.write().await;
 
-    if let Some(ai_id) = game.add_ai_player(difficulty, personality) {
-        // Get player info for response
-        if let Some(player) = game.players.get(&ai_id) {
-            let response = AddAIResponse {
-                ai_id,
-                name: player.name.clone(),
-                team: player.team,
-            };
+    let ai_id = match (wasm_bot, &request.bot) {
+        (Some((controller, name)), _) => game.add_controller_player(controller, name),
+        (None, Some(bot)) => game.add_bot_player(bot, difficulty),
+        (None, None) => game.add_ai_player(difficulty, personality),
+    };
+    let ai_id = ai_id.ok_or_else(|| GameError::invalid_state("Failed to add AI player"))?;
+    // Get player info for response
+    let player = game
+        .players
+        .get(&ai_id)
+        .ok_or_else(|| GameError::player_not_found(ai_id))?;
+    let response = AddAIResponse {
+        ai_id,
+        name: player.name.clone(),
+        team: player.team,
+    };
 
-            // Broadcast game state update
-            let game_state = game.get_full_state();
-            let _ = state.tx.send((Uuid::nil(), game_state));
+    // Broadcast game state update
+    let game_state = game.get_full_state();
+    let _ = state.tx.send((Uuid::nil(), game_state));
 
-            Ok(Json(response))
-        } else {
-            Err("Failed to retrieve AI player info")
-        }
-    } else {
-        Err("Failed to add AI player")
+    Ok(Json(response))
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct AdminShutdownRequest {
+    reason: Option<String>,
+    seconds: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+struct AdminShutdownResponse {
+    /// False when a shutdown was already under way
+    accepted: bool,
+    seconds: u32,
+}
+
+/// Start a graceful shutdown; only accepted from the machine the server runs on
+async fn admin_shutdown(
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    State(state): State<AppState>,
+    request: Option<Json<AdminShutdownRequest>>,
+) -> Result<Json<AdminShutdownResponse>, (StatusCode, &'static str)> {
+    if !peer.ip().is_loopback() {
+        log::warn!("Refused shutdown request from {peer}");
+        return Err((
+            StatusCode::FORBIDDEN,
+            "Shutdown is only allowed from localhost",
+        ));
     }
+
+    let Json(request) = request.unwrap_or_default();
+    let seconds = request.seconds.unwrap_or(SHUTDOWN_GRACE_SECONDS);
+    let accepted = state.shutdown.request(shutdown::ShutdownRequest {
+        reason: request
+            .reason
+            .unwrap_or_else(|| "Server is restarting".to_string()),
+        seconds,
+    });
+    Ok(Json(AdminShutdownResponse { accepted, seconds }))
 }
 
 // Simple CORS middleware
@@ -191,7 +799,7 @@ async fn cors_layer(
     );
     response.headers_mut().insert(
         "Access-Control-Allow-Methods",
-        match "GET, POST, OPTIONS".parse() {
+        match "GET, POST, DELETE, OPTIONS".parse() {
             Ok(val) => val,
             Err(e) => {
                 log::error!("Failed to parse CORS methods header: {e}");
@@ -214,42 +822,122 @@ async fn cors_layer(
 
 mod game_loop {
     use super::*;
-    use std::time::Duration;
-    use tokio::time;
+    use crate::load_monitor::LoadMonitor;
+    use crate::tick_clock::{FixedTimestep, SharedTickMetrics};
+    use std::time::{Duration, Instant};
+    use tokio::time::{self, MissedTickBehavior};
 
+    /// Simulates in fixed steps, catching up after slow frames, and flushes
+    /// what the simulation produced on its own broadcast cadence
     pub async fn run_game_loop(
         game: Arc<RwLock<Game>>,
         tx: broadcast::Sender<(Uuid, ServerMessage)>,
+        metrics: SharedTickMetrics,
     ) {
-        let mut interval = time::interval(Duration::from_millis(FRAME_DURATION_MS)); // ~30 FPS
+        let mut clock = FixedTimestep::new(
+            Duration::from_millis(FRAME_DURATION_MS),
+            MAX_CATCH_UP_STEPS,
+            Instant::now(),
+        );
+        let mut simulate = time::interval(Duration::from_millis(FRAME_DURATION_MS)); // ~30 FPS
+        let mut flush = time::interval(Duration::from_millis(BROADCAST_INTERVAL_MS));
+        // The accumulator makes up for late wakeups, so missed ticks aren't replayed
+        simulate.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        flush.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+        let mut load = LoadMonitor::new();
+        let mut pending: Vec<ServerMessage> = Vec::new();
+        let mut full_state_due = false;
+        let mut floor_data_due = false;
+        let mut flushes: u64 = 0;
 
         loop {
-            interval.tick().await;
+            tokio::select! {
+                _ = simulate.tick() => {
+                    // A profiler frame is one simulation tick plus any flush since the last
+                    profiling::finish_frame!();
+                    let capped_frames = clock.metrics().capped_frames;
+                    let steps = clock.advance(Instant::now());
+                    let capped = clock.metrics().capped_frames > capped_frames;
+                    if capped {
+                        log::warn!(
+                            "Game loop fell behind; {:.0}ms of simulation dropped so far",
+                            clock.metrics().dropped_ms
+                        );
+                    }
+                    let mut game = game.write().await;
 
-            let mut game = game.write().await;
+                    for _ in 0..steps {
+                        profiling::scope!("simulate_step");
+                        let step_start = Instant::now();
 
-            // Update all systems through SystemManager
-            let messages = game.update(FRAME_DELTA_SECONDS);
-            for msg in messages {
-                let _ = tx.send((Uuid::nil(), msg));
-            }
+                        // Update all systems through SystemManager
+                        pending.extend(game.update(FRAME_DELTA_SECONDS));
+
+                        // Full state every second, mech floor data every 10
+                        // seconds, both less often while overloaded
+                        let state_interval = STATE_UPDATE_INTERVAL * game.load.broadcast_stride();
+                        if game.tick_count % state_interval == 0 {
+                            full_state_due = true;
+                            floor_data_due |= game.tick_count % (state_interval * 10) == 0;
+                        }
 
-            // Send periodic full state updates
-            if game.tick_count % STATE_UPDATE_INTERVAL == 0 {
-                // Every second
-                let state_msg = game.get_full_state();
-                let _ = tx.send((Uuid::nil(), state_msg));
-                
-                // Send mech floor data every 10 seconds (less frequently than game state)
-                if game.tick_count % (STATE_UPDATE_INTERVAL * 10) == 0 {
-                    let floor_messages = game.get_mech_floor_data();
-                    for floor_msg in floor_messages {
-                        let _ = tx.send((Uuid::nil(), floor_msg));
+                        game.tick_count += 1;
+                        let elapsed = step_start.elapsed();
+                        clock.record_step(elapsed);
+                        load.record_work(elapsed);
                     }
+
+                    let budget = Duration::from_millis(FRAME_DURATION_MS);
+                    if let Some(level) = load.finish_frame(budget, capped) {
+                        if level > game.load {
+                            log::warn!(
+                                "Server load {:.0}% of frame budget; shedding to {level:?}",
+                                load.load() * 100.0
+                            );
+                        } else {
+                            log::info!("Server load eased to {level:?}");
+                        }
+                        pending.push(game.set_load(level));
+                    }
+                    clock.record_load(load.load(), load.level());
+                    *metrics.write().await = clock.metrics().clone();
                 }
-            }
+                _ = flush.tick() => {
+                    // Overloaded, only every few flushes go out; the rest wait in `pending`
+                    flushes += 1;
+                    let mut game = game.write().await;
+                    if !flushes.is_multiple_of(game.load.broadcast_stride()) {
+                        continue;
+                    }
+                    profiling::scope!("flush");
+                    let flush_start = Instant::now();
+                    for msg in pending.drain(..) {
+                        let _ = tx.send((Uuid::nil(), msg));
+                    }
+                    game.update_team_vision(&tx);
+                    game.send_collision_shapes(&tx);
 
-            game.tick_count += 1;
+                    if std::mem::take(&mut full_state_due) {
+                        let _ = tx.send((Uuid::nil(), game.get_full_state()));
+                        if game.state_checksums {
+                            let _ = tx.send((Uuid::nil(), game.state_checksum()));
+                        }
+                        // For anyone who joined since the load last changed
+                        if game.load != ServerLoad::Normal {
+                            let level = game.load;
+                            let _ = tx.send((Uuid::nil(), ServerMessage::ServerLoad { level }));
+                        }
+                    }
+                    if std::mem::take(&mut floor_data_due) {
+                        for floor_msg in game.get_mech_floor_data() {
+                            let _ = tx.send((Uuid::nil(), floor_msg));
+                        }
+                    }
+                    clock.record_broadcast();
+                    load.record_work(flush_start.elapsed());
+                }
+            }
         }
     }
 }
@@ -269,20 +957,103 @@ async fn handle_debug_socket(socket: WebSocket, state: AppState) {
 
     let (mut sender, mut receiver) = socket.split();
 
-    // Spawn task to handle incoming debug commands
+    // Spawn task to handle incoming debug commands, passing any replies to
+    // the send loop below
     let game = state.game.clone();
+    let (reply_tx, mut replies) = tokio::sync::mpsc::unbounded_channel();
+    let command_game = game.clone();
+    let subscription = Arc::new(std::sync::Mutex::new(Subscription::all()));
+    let command_subscription = subscription.clone();
     tokio::spawn(async move {
         while let Some(Ok(msg)) = receiver.next().await {
-            if let Ok(text) = msg.to_text() {
-                // Handle debug commands
+            let Ok(text) = msg.to_text() else { continue };
+            let Ok(command) = serde_json::from_str::<DebugSocketCommand>(text) else {
                 log::debug!("Debug command: {text}");
+                continue;
+            };
+            let mut game = command_game.write().await;
+            let reply = match command {
+                DebugSocketCommand::RequestAIWeights(ai_id) => game
+                    .ai_weights(ai_id)
+                    .map(|weights| DebugStreamMessage::AIWeights { ai_id, weights }),
+                DebugSocketCommand::SetAIWeights { ai_id, weights } => {
+                    // The AI takes them on once its worker is free; answer
+                    // with what it was given rather than wait
+                    game.set_ai_weights(ai_id, weights.clone()).then(|| {
+                        log::info!("Retuned AI {ai_id} from the debug client");
+                        DebugStreamMessage::AIWeights { ai_id, weights }
+                    })
+                }
+                DebugSocketCommand::Subscribe(channels) => {
+                    *command_subscription.lock().unwrap() = Subscription::only(&channels);
+                    None
+                }
+                DebugSocketCommand::RequestAIView(ai_id) => {
+                    let team = game.players.get(&ai_id).map(|player| player.team);
+                    team.and_then(|team| {
+                        game.ai_views()
+                            .into_iter()
+                            .find(|view| view.team_info.team_id == team)
+                    })
+                    .map(|view| DebugStreamMessage::AIView { ai_id, view })
+                }
+            };
+            if let Some(reply) = reply {
+                let _ = reply_tx.send(reply);
             }
         }
     });
 
-    // Send game updates to debug client
-    while let Ok((_, msg)) = rx.recv().await {
-        if let Ok(json) = serde_json::to_string(&msg) {
+    // Send the game updates the debug client subscribed to, along with the AI conversation so
+    // far and then whatever is said from here on, and what the AIs' updates
+    // are costing
+    let mut comm_poll = tokio::time::interval(std::time::Duration::from_millis(
+        DEBUG_COMM_POLL_INTERVAL_MS,
+    ));
+    let mut metrics_poll = tokio::time::interval(std::time::Duration::from_millis(
+        DEBUG_AI_METRICS_INTERVAL_MS,
+    ));
+    let mut bandwidth_poll = tokio::time::interval(std::time::Duration::from_millis(
+        DEBUG_BANDWIDTH_INTERVAL_MS,
+    ));
+    let mut next_comm_sequence = 0;
+    let mut last_metrics_tick = None;
+    loop {
+        let json = tokio::select! {
+            msg = rx.recv() => {
+                let Ok((_, msg)) = msg else { break };
+                if !subscription.lock().unwrap().wants(&msg) {
+                    continue;
+                }
+                serde_json::to_string(&msg)
+            }
+            _ = comm_poll.tick() => {
+                let events = game.write().await.ai_comm_events_since(next_comm_sequence);
+                let Some(last) = events.last() else { continue };
+                next_comm_sequence = last.sequence + 1;
+                serde_json::to_string(&DebugStreamMessage::CommEvents(events))
+            }
+            _ = metrics_poll.tick() => {
+                let Some(metrics) = game.write().await.ai_tick_metrics() else { continue };
+                if metrics.ai_count == 0 || last_metrics_tick == Some(metrics.tick) {
+                    continue;
+                }
+                last_metrics_tick = Some(metrics.tick);
+                serde_json::to_string(&DebugStreamMessage::AIMetrics(metrics))
+            }
+            _ = bandwidth_poll.tick() => {
+                let rows = state.bandwidth.lock().unwrap().ranked(std::time::Instant::now());
+                if rows.is_empty() {
+                    continue;
+                }
+                serde_json::to_string(&DebugStreamMessage::Bandwidth(rows))
+            }
+            reply = replies.recv() => {
+                let Some(reply) = reply else { break };
+                serde_json::to_string(&reply)
+            }
+        };
+        if let Ok(json) = json {
             if sender.send(Message::Text(json)).await.is_err() {
                 break;
             }
@@ -290,15 +1061,140 @@ async fn handle_debug_socket(socket: WebSocket, state: AppState) {
     }
 }
 
+/// Observer-only messages on the /debug socket, mirroring the debug client's
+/// `DebugMessage`
+#[derive(Serialize)]
+enum DebugStreamMessage {
+    CommEvents(Vec<ai::CommEvent>),
+    /// What the latest AI update cost, all AIs together
+    AIMetrics(ai::AITickMetrics),
+    /// What each message type is costing, heaviest first
+    Bandwidth(Vec<MessageBandwidth>),
+    /// The task weights an AI is playing with, after a request or change
+    AIWeights {
+        ai_id: Uuid,
+        weights: ai::UtilityWeights,
+    },
+    /// Everything an AI's team is allowed to know right now
+    AIView {
+        ai_id: Uuid,
+        view: ai::GameView,
+    },
+}
+
+/// Debug client commands the server acts on, mirroring part of its
+/// `DebugCommand`. Anything else it sends is only logged.
+#[derive(Deserialize)]
+enum DebugSocketCommand {
+    RequestAIWeights(Uuid),
+    SetAIWeights {
+        ai_id: Uuid,
+        weights: ai::UtilityWeights,
+    },
+    RequestAIView(Uuid),
+    /// Only forward game traffic on these channels
+    Subscribe(Vec<StateChannel>),
+}
+
+/// Bytes and messages sent per message type, totals and over the last
+/// second, for Prometheus or anyone with curl
+async fn get_metrics(State(state): State<AppState>) -> String {
+    let rows = state
+        .bandwidth
+        .lock()
+        .unwrap()
+        .ranked(std::time::Instant::now());
+    bandwidth::prometheus_text(&rows)
+}
+
+/// Outgoing queue pressure for every connected client
+async fn get_send_queue_stats(
+    State(state): State<AppState>,
+) -> Json<std::collections::HashMap<Uuid, send_queue::SendQueueStats>> {
+    let queues = state.send_queues.read().await;
+    Json(
+        queues
+            .iter()
+            .map(|(player_id, queue)| (*player_id, queue.lock().unwrap().stats()))
+            .collect(),
+    )
+}
+
+/// Profile the game loop for a few seconds and download it as a `.puffin`
+/// file for puffin_viewer
+async fn capture_profile(
+    Query(query): Query<ProfileQuery>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    use axum::http::header;
+
+    let secs = query
+        .secs
+        .unwrap_or(PROFILE_DEFAULT_SECONDS)
+        .clamp(1, PROFILE_MAX_SECONDS);
+    log::info!("Capturing a {secs}s profile");
+    let bytes = state
+        .profiler
+        .capture(std::time::Duration::from_secs(secs))
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let disposition = format!("attachment; filename=\"server-{timestamp}.puffin\"");
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/octet-stream".to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        bytes,
+    ))
+}
+
+/// Fixed-timestep loop health: catch-up, dropped time and drift
+async fn get_tick_metrics(State(state): State<AppState>) -> Json<tick_clock::TickMetrics> {
+    Json(state.tick_metrics.read().await.clone())
+}
+
+/// Recent gameplay events, for tooling that wasn't connected when they happened
+async fn get_events(
+    Query(query): Query<EventsQuery>,
+    State(state): State<AppState>,
+) -> Json<EventsResponse> {
+    let log = state.event_log.lock().unwrap();
+    Json(EventsResponse {
+        oldest_tick: log.oldest_tick(),
+        events: log.query(query.since_tick.unwrap_or(0), query.event_type.as_deref()),
+    })
+}
+
+/// Hashes of the authoritative state, the same ones StateChecksum carries
+async fn get_state_hash(
+    Query(query): Query<StateHashQuery>,
+    State(state): State<AppState>,
+) -> Json<StateHashResponse> {
+    let game = state.game.read().await;
+    let dump = game.state_dump();
+    let hashes = dump.hashes();
+    Json(StateHashResponse {
+        tick: game.tick_count,
+        combined: hashes.combined(),
+        hashes,
+        entities: query.entities.then_some(dump),
+    })
+}
+
 async fn get_ai_debug_info(
     Path(ai_id): Path<Uuid>,
     State(state): State<AppState>,
-) -> Result<Json<serde_json::Value>, &'static str> {
+) -> Result<Json<serde_json::Value>, ApiError> {
     let game = state.game.read().await;
 
     // Check if AI exists
     if !game.get_ai_players().contains(&ai_id) {
-        return Err("AI not found");
+        return Err(GameError::player_not_found(ai_id).into());
     }
 
     Ok(Json(serde_json::json!({
@@ -0,0 +1,202 @@
+use axum::{
+    extract::{ConnectInfo, State},
+    http::StatusCode,
+    Json,
+};
+use shared::*;
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::sync::RwLock;
+
+use crate::{rooms::Rooms, AppState};
+
+/// Servers that have announced themselves here recently, by address. Every
+/// server keeps one, so any of them can be pointed at as a master.
+#[derive(Clone, Default)]
+pub struct MasterList {
+    listings: Arc<RwLock<HashMap<String, (ServerListing, Instant)>>>,
+}
+
+impl MasterList {
+    pub async fn register(&self, listing: ServerListing) -> ValidationResult<()> {
+        listing.validate()?;
+        let mut listings = self.listings.write().await;
+        prune(&mut listings);
+        let address = listing.address();
+        if !listings.contains_key(&address) && listings.len() >= MAX_MASTER_LISTINGS {
+            return Err(ValidationError::InvalidServerListing {
+                reason: "the master list is full".to_string(),
+            });
+        }
+        listings.insert(address, (listing, Instant::now()));
+        Ok(())
+    }
+
+    pub async fn list(&self) -> Vec<ServerListing> {
+        let mut listings = self.listings.write().await;
+        prune(&mut listings);
+        let mut servers: Vec<_> = listings.values().map(|(l, _)| l.clone()).collect();
+        servers.sort_by(|a, b| b.players.cmp(&a.players).then(a.name.cmp(&b.name)));
+        servers
+    }
+}
+
+fn prune(listings: &mut HashMap<String, (ServerListing, Instant)>) {
+    let ttl = Duration::from_secs(MASTER_LISTING_TTL_SECS);
+    listings.retain(|_, (_, heard)| heard.elapsed() < ttl);
+}
+
+/// How this server describes itself to masters and LAN browsers
+#[derive(Debug, Clone)]
+pub struct Announcement {
+    pub name: String,
+    /// Public host name or IP; empty lets whoever hears us use our source address
+    pub host: String,
+    pub port: u16,
+}
+
+impl Announcement {
+    pub async fn listing(&self, rooms: &Rooms) -> ServerListing {
+        let rooms = rooms.list().await;
+        ServerListing {
+            name: self.name.clone(),
+            host: self.host.clone(),
+            port: self.port,
+            players: rooms.iter().map(|r| r.players).sum(),
+            ai_players: rooms.iter().map(|r| r.ai_players).sum(),
+            rooms: rooms.len(),
+            accepting_players: rooms.iter().any(|r| r.accepting_players),
+            protocol_version: PROTOCOL_VERSION,
+        }
+    }
+}
+
+/// Everything announced to this server's master list
+pub async fn list_servers(State(state): State<AppState>) -> Json<Vec<ServerListing>> {
+    Json(state.master_list.list().await)
+}
+
+/// A server announcing itself, filling in its address from the connection
+/// when it didn't give one
+pub async fn register_server(
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    State(state): State<AppState>,
+    Json(mut listing): Json<ServerListing>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    if listing.host.is_empty() {
+        listing.host = peer.ip().to_string();
+    }
+    state
+        .master_list
+        .register(listing)
+        .await
+        .map(|()| StatusCode::NO_CONTENT)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))
+}
+
+/// Announce this server to `master_url` now and every `MASTER_HEARTBEAT_SECS`
+pub fn spawn_registration(master_url: String, announcement: Announcement, rooms: Rooms) {
+    let url = format!("{}{MASTER_LIST_PATH}", master_url.trim_end_matches('/'));
+    log::info!("Announcing '{}' to the master list at {url}", announcement.name);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(MASTER_HEARTBEAT_SECS));
+        let mut failing = false;
+        loop {
+            interval.tick().await;
+            let body = match serde_json::to_string(&announcement.listing(&rooms).await) {
+                Ok(body) => body,
+                Err(e) => {
+                    log::error!("Failed to encode server listing: {e}");
+                    continue;
+                }
+            };
+            let url = url.clone();
+            let result =
+                tokio::task::spawn_blocking(move || http_request("POST", &url, Some(&body)))
+                    .await
+                    .unwrap_or_else(|e| Err(e.to_string()));
+            // Only speak up when the master goes away or comes back
+            match result {
+                Ok(_) if failing => {
+                    log::info!("Master list reachable again");
+                    failing = false;
+                }
+                Err(e) if !failing => {
+                    log::warn!("Failed to announce to the master list: {e}");
+                    failing = true;
+                }
+                _ => {}
+            }
+        }
+    });
+}
+
+/// Answer LAN discovery broadcasts with this server's listing
+pub fn spawn_lan_responder(announcement: Announcement, rooms: Rooms) {
+    tokio::spawn(async move {
+        let addr = SocketAddr::from(([0, 0, 0, 0], LAN_DISCOVERY_PORT));
+        let socket = match tokio::net::UdpSocket::bind(addr).await {
+            Ok(socket) => socket,
+            Err(e) => {
+                log::warn!("LAN discovery disabled, can't listen on UDP {addr}: {e}");
+                return;
+            }
+        };
+        log::info!("Answering LAN discovery on UDP {addr}");
+
+        let mut buf = [0u8; 64];
+        loop {
+            let Ok((len, from)) = socket.recv_from(&mut buf).await else {
+                continue;
+            };
+            if &buf[..len] != LAN_DISCOVERY_QUERY {
+                continue;
+            }
+            // The browser knows where the reply came from better than we do
+            let mut listing = announcement.listing(&rooms).await;
+            listing.host.clear();
+            match serde_json::to_vec(&listing) {
+                Ok(reply) => {
+                    if let Err(e) = socket.send_to(&reply, from).await {
+                        log::debug!("Failed to answer LAN discovery from {from}: {e}");
+                    }
+                }
+                Err(e) => log::error!("Failed to encode server listing: {e}"),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn listing(name: &str, host: &str) -> ServerListing {
+        ServerListing {
+            name: name.to_string(),
+            host: host.to_string(),
+            port: SERVER_PORT,
+            players: 0,
+            ai_players: 0,
+            rooms: 1,
+            accepting_players: true,
+            protocol_version: PROTOCOL_VERSION,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_master_list_keeps_one_listing_per_address() {
+        let master = MasterList::default();
+        master.register(listing("First", "10.0.0.1")).await.unwrap();
+        master.register(listing("Renamed", "10.0.0.1")).await.unwrap();
+        master.register(listing("Second", "10.0.0.2")).await.unwrap();
+        assert!(master.register(listing("", "10.0.0.3")).await.is_err());
+
+        let names: Vec<_> = master.list().await.into_iter().map(|l| l.name).collect();
+        assert_eq!(names, vec!["Renamed", "Second"]);
+    }
+}
@@ -0,0 +1,387 @@
+//! The pick-up queue behind `MATCHMAKING_PATH`. Tickets wait in the order
+//! they joined; every `MATCHMAKING_CHECK_SECS` the oldest `MATCH_SIZE` are
+//! split into teams of about equal rating and given a room of their own.
+//! When a matched room's game mode declares a result, everyone matched into
+//! it who is still in the room gets it added to their record, scored for
+//! the team they ended up playing on.
+
+use crate::ratings::{Ratings, SharedRatings};
+use crate::rooms::{RoomAccess, RoomConfig, Rooms};
+use shared::*;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+struct Ticket {
+    id: Uuid,
+    player_name: String,
+    rating: f32,
+    /// Last time its holder asked after it
+    last_seen: Instant,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchedPlayer {
+    pub ticket: Uuid,
+    pub player_name: String,
+    pub team: TeamId,
+}
+
+/// A room's worth of players and the teams they're to play on
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlannedMatch {
+    pub room: String,
+    pub players: Vec<MatchedPlayer>,
+}
+
+#[derive(Debug, Default)]
+pub struct Matchmaker {
+    /// Oldest first
+    waiting: Vec<Ticket>,
+    /// Tickets already given a room, kept while their holders still ask
+    matched: HashMap<Uuid, (QueueStatus, Instant)>,
+}
+
+impl Matchmaker {
+    /// Queue a player, or hand back the ticket they're already waiting on
+    pub fn join(&mut self, player_name: &str, rating: f32, now: Instant) -> GameResult<Uuid> {
+        if let Some(ticket) = self
+            .waiting
+            .iter_mut()
+            .find(|ticket| ticket.player_name == player_name)
+        {
+            ticket.last_seen = now;
+            return Ok(ticket.id);
+        }
+        if self.waiting.len() >= MAX_MATCHMAKING_QUEUE {
+            return Err(GameError::QueueFull);
+        }
+        let id = Uuid::new_v4();
+        self.waiting.push(Ticket {
+            id,
+            player_name: player_name.to_string(),
+            rating,
+            last_seen: now,
+        });
+        Ok(id)
+    }
+
+    pub fn leave(&mut self, ticket: Uuid) -> GameResult<()> {
+        let before = self.waiting.len();
+        self.waiting.retain(|waiting| waiting.id != ticket);
+        if self.waiting.len() == before && self.matched.remove(&ticket).is_none() {
+            return Err(GameError::TicketNotFound { id: ticket });
+        }
+        Ok(())
+    }
+
+    /// Where a ticket stands, which also keeps it in the queue
+    pub fn status(&mut self, ticket: Uuid, now: Instant) -> GameResult<QueueStatus> {
+        if let Some((status, last_seen)) = self.matched.get_mut(&ticket) {
+            *last_seen = now;
+            return Ok(status.clone());
+        }
+        let queued = self.waiting.len();
+        let (index, waiting) = self
+            .waiting
+            .iter_mut()
+            .enumerate()
+            .find(|(_, waiting)| waiting.id == ticket)
+            .ok_or(GameError::TicketNotFound { id: ticket })?;
+        waiting.last_seen = now;
+        Ok(QueueStatus::Waiting {
+            position: index + 1,
+            queued,
+        })
+    }
+
+    /// Drop tickets nobody has asked after in `MATCHMAKING_TICKET_TTL_SECS`
+    fn prune(&mut self, now: Instant) {
+        let ttl = Duration::from_secs(MATCHMAKING_TICKET_TTL_SECS);
+        let fresh = |last_seen: Instant| now.saturating_duration_since(last_seen) < ttl;
+        self.waiting.retain(|ticket| fresh(ticket.last_seen));
+        self.matched.retain(|_, (_, last_seen)| fresh(*last_seen));
+    }
+
+    /// The next `MATCH_SIZE` players to match, oldest first, without taking
+    /// them out of the queue yet
+    pub fn next_match(&mut self, now: Instant) -> Option<PlannedMatch> {
+        self.prune(now);
+        let group = self.waiting.get(..MATCH_SIZE)?;
+        let ratings: Vec<f32> = group.iter().map(|ticket| ticket.rating).collect();
+        let players = group
+            .iter()
+            .zip(balance_teams(&ratings))
+            .map(|(ticket, team)| MatchedPlayer {
+                ticket: ticket.id,
+                player_name: ticket.player_name.clone(),
+                team,
+            })
+            .collect();
+        Some(PlannedMatch {
+            room: format!("match-{}", &Uuid::new_v4().simple().to_string()[..8]),
+            players,
+        })
+    }
+
+    /// Take a match's players out of the queue, telling each their room
+    pub fn confirm(&mut self, planned: &PlannedMatch, now: Instant) {
+        for player in &planned.players {
+            self.waiting.retain(|ticket| ticket.id != player.ticket);
+            let status = QueueStatus::Matched {
+                room: planned.room.clone(),
+                team: player.team,
+            };
+            self.matched.insert(player.ticket, (status, now));
+        }
+    }
+}
+
+/// Teams for players with these ratings: strongest first, each onto the
+/// side with less rating so far until it's full
+pub fn balance_teams(ratings: &[f32]) -> Vec<TeamId> {
+    let mut order: Vec<usize> = (0..ratings.len()).collect();
+    order.sort_by(|a, b| ratings[*b].total_cmp(&ratings[*a]));
+    let per_team = ratings.len().div_ceil(2);
+    let mut teams = vec![TeamId::Red; ratings.len()];
+    let (mut red, mut blue) = ((0, 0.0), (0, 0.0));
+    for index in order {
+        let to_red = blue.0 >= per_team || (red.0 < per_team && red.1 <= blue.1);
+        let side = if to_red { &mut red } else { &mut blue };
+        side.0 += 1;
+        side.1 += ratings[index];
+        teams[index] = if to_red { TeamId::Red } else { TeamId::Blue };
+    }
+    teams
+}
+
+/// The queue and the records it rates players by
+#[derive(Clone, Default)]
+pub struct Matchmaking {
+    queue: Arc<Mutex<Matchmaker>>,
+    ratings: SharedRatings,
+}
+
+impl Matchmaking {
+    pub fn new(ratings: Ratings) -> Self {
+        Self {
+            queue: Default::default(),
+            ratings: Arc::new(Mutex::new(ratings)),
+        }
+    }
+
+    pub fn join(&self, request: &QueueRequest) -> Result<QueueTicket, ServerError> {
+        request.validate()?;
+        let rating = self.ratings.lock().unwrap().rating(&request.player_name);
+        let ticket =
+            self.queue
+                .lock()
+                .unwrap()
+                .join(&request.player_name, rating, Instant::now())?;
+        Ok(QueueTicket { ticket })
+    }
+
+    pub fn status(&self, ticket: Uuid) -> GameResult<QueueStatus> {
+        self.queue.lock().unwrap().status(ticket, Instant::now())
+    }
+
+    pub fn leave(&self, ticket: Uuid) -> GameResult<()> {
+        self.queue.lock().unwrap().leave(ticket)
+    }
+
+    /// Open a room for every full match waiting, and score the matched
+    /// rooms in `playing` that have finished
+    pub async fn check(&self, rooms: &Rooms, playing: &mut Vec<PlannedMatch>) {
+        loop {
+            let Some(planned) = self.queue.lock().unwrap().next_match(Instant::now()) else {
+                break;
+            };
+            if let Err(e) = rooms
+                .open_configured(&planned.room, &match_config(rooms.defaults()))
+                .await
+            {
+                log::warn!("Couldn't open a room for a match, trying again later: {e}");
+                break;
+            }
+            self.queue.lock().unwrap().confirm(&planned, Instant::now());
+            log::info!(
+                "Matched {} players into '{}'",
+                planned.players.len(),
+                planned.room
+            );
+            playing.push(planned);
+        }
+
+        let mut still_playing = Vec::new();
+        for planned in playing.drain(..) {
+            let Some(room) = rooms.get(&planned.room).await else {
+                continue;
+            };
+            let game = room.game.read().await;
+            let Some(result) = game.game_mode.as_ref().and_then(|hud| hud.result.as_ref()) else {
+                still_playing.push(planned);
+                continue;
+            };
+            // Players can switch sides or never turn up, so go by who's in
+            // the room rather than the plan
+            let players: Vec<(String, TeamId)> = planned
+                .players
+                .iter()
+                .filter_map(|matched| {
+                    game.players
+                        .values()
+                        .find(|player| player.name == matched.player_name)
+                        .map(|player| (player.name.clone(), player.team))
+                })
+                .collect();
+            self.ratings.lock().unwrap().record(&players, result.winner);
+        }
+        *playing = still_playing;
+    }
+
+    /// Form matches in the background for as long as the server runs
+    pub fn spawn(&self, rooms: Rooms) {
+        let matchmaking = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(MATCHMAKING_CHECK_SECS));
+            let mut playing = Vec::new();
+            loop {
+                interval.tick().await;
+                matchmaking.check(&rooms, &mut playing).await;
+            }
+        });
+    }
+}
+
+/// How a matched room is set up: the server's defaults, played to a
+/// result, with room for just the players matched into it
+fn match_config(defaults: &RoomConfig) -> RoomConfig {
+    let mut config = defaults.clone();
+    config.game_mode = config.game_mode.or(Some(GameModeKind::Deathmatch));
+    config.access = RoomAccess {
+        title: Some("Pick-up match".to_string()),
+        max_players: Some(MATCH_SIZE),
+        password: None,
+    };
+    config
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_teams_even_out_ratings() {
+        let teams = balance_teams(&[900.0, 1200.0, 1000.0, 1100.0]);
+        assert_eq!(
+            teams,
+            vec![TeamId::Red, TeamId::Red, TeamId::Blue, TeamId::Blue]
+        );
+        // Sizes stay even even when one player outweighs the rest
+        let teams = balance_teams(&[3000.0, 1000.0, 1000.0, 1000.0]);
+        assert_eq!(teams.iter().filter(|t| **t == TeamId::Red).count(), 2);
+    }
+
+    #[test]
+    fn test_queue_forms_matches_oldest_first_and_forgets_the_silent() {
+        let start = Instant::now();
+        let mut matchmaker = Matchmaker::default();
+        let tickets: Vec<Uuid> = (0..MATCH_SIZE + 1)
+            .map(|i| {
+                matchmaker
+                    .join(&format!("Player{i}"), RATING_START, start)
+                    .unwrap()
+            })
+            .collect();
+        assert_eq!(
+            matchmaker.join("Player0", RATING_START, start).unwrap(),
+            tickets[0]
+        );
+        assert_eq!(
+            matchmaker.status(tickets[1], start).unwrap(),
+            QueueStatus::Waiting {
+                position: 2,
+                queued: MATCH_SIZE + 1
+            }
+        );
+
+        let planned = matchmaker.next_match(start).unwrap();
+        assert_eq!(planned.players.len(), MATCH_SIZE);
+        assert_eq!(planned.players[0].ticket, tickets[0]);
+        matchmaker.confirm(&planned, start);
+        assert!(matches!(
+            matchmaker.status(tickets[0], start).unwrap(),
+            QueueStatus::Matched { ref room, .. } if *room == planned.room
+        ));
+        let last = tickets[MATCH_SIZE];
+        assert_eq!(
+            matchmaker.status(last, start).unwrap(),
+            QueueStatus::Waiting {
+                position: 1,
+                queued: 1
+            }
+        );
+        assert!(matchmaker.next_match(start).is_none());
+
+        // Nobody asks after the last ticket, so it goes
+        let later = start + Duration::from_secs(MATCHMAKING_TICKET_TTL_SECS);
+        matchmaker.next_match(later);
+        assert!(matchmaker.status(last, later).is_err());
+        assert!(matchmaker.leave(last).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_matches_get_a_room_and_results_are_recorded() {
+        let rooms = Rooms::new(RoomConfig::default());
+        let matchmaking = Matchmaking::default();
+        let tickets: Vec<Uuid> = (0..MATCH_SIZE)
+            .map(|i| {
+                matchmaking
+                    .join(&QueueRequest {
+                        player_name: format!("Player{i}"),
+                    })
+                    .unwrap()
+                    .ticket
+            })
+            .collect();
+
+        let mut playing = Vec::new();
+        matchmaking.check(&rooms, &mut playing).await;
+        let QueueStatus::Matched { room, team } = matchmaking.status(tickets[0]).unwrap() else {
+            panic!("not matched");
+        };
+        let opened = rooms.get(&room).await.unwrap();
+        let other = match team {
+            TeamId::Red => TeamId::Blue,
+            TeamId::Blue => TeamId::Red,
+        };
+        {
+            let mut game = opened.game.write().await;
+            assert_eq!(game.access.max_players, Some(MATCH_SIZE));
+            // Player0 switches sides, Player1 plays for the winners, and
+            // the rest never show
+            game.add_player(Uuid::new_v4(), "Player0".to_string(), Some(other));
+            game.add_player(Uuid::new_v4(), "Player1".to_string(), Some(team));
+        }
+
+        opened.game.write().await.game_mode = Some(GameModeHud {
+            mode: GameModeKind::Deathmatch,
+            objective: String::new(),
+            teams: Vec::new(),
+            time_left: None,
+            goal: None,
+            result: Some(MatchResult {
+                winner: Some(team),
+                reason: String::new(),
+            }),
+        });
+        matchmaking.check(&rooms, &mut playing).await;
+        assert!(playing.is_empty());
+        let ratings = matchmaking.ratings.lock().unwrap();
+        assert_eq!(ratings.rating("Player0"), RATING_START - RATING_PER_RESULT);
+        assert_eq!(ratings.rating("Player1"), RATING_START + RATING_PER_RESULT);
+        assert_eq!(ratings.rating("Player2"), RATING_START);
+    }
+}
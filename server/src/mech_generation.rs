@@ -224,8 +224,8 @@ pub fn get_station_size(station_type: StationType) -> StationSize {
         StationType::Engine => StationSize::LARGE, // 2x2
         StationType::Pilot => StationSize::WIDE,   // 2x1
         StationType::Repair => StationSize::WIDE,  // 2x1
-        StationType::WeaponLaser | StationType::WeaponProjectile => StationSize::SINGLE, // 1x1
-        StationType::Shield | StationType::Electrical | StationType::Upgrade => StationSize::SINGLE, // 1x1
+        StationType::WeaponLaser | StationType::WeaponProjectile | StationType::WeaponEmp | StationType::TurretControl => StationSize::SINGLE, // 1x1
+        StationType::Shield | StationType::Electrical | StationType::Upgrade | StationType::Radar | StationType::PointDefense => StationSize::SINGLE, // 1x1
     }
 }
 
@@ -0,0 +1,407 @@
+//! Authoritative player movement.
+//!
+//! All player movement is resolved here so native and WASM clients get the
+//! same `PlayerMoved` response regardless of what they predicted locally.
+
+use crate::game::Game;
+use shared::tile_entity::{FloorMap, TileContent};
+use shared::*;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Resolves movement input into new player locations
+pub struct MovementResolver {
+    /// Sub-tile offset from the tile center for players inside mechs. Interior
+    /// positions are synced per tile, so small per-tick steps accumulate here
+    /// until they carry the player into the next tile.
+    interior_offsets: HashMap<Uuid, (MechInteriorPos, WorldPos)>,
+}
+
+impl MovementResolver {
+    pub fn new() -> Self {
+        Self {
+            interior_offsets: HashMap::new(),
+        }
+    }
+
+    /// Apply a movement input for one player. Returns the new location when
+    /// it changed and should be broadcast.
+    pub fn resolve(
+        &mut self,
+        game: &Game,
+        player_id: Uuid,
+        movement: (f32, f32),
+        delta_time: f32,
+    ) -> Option<PlayerLocation> {
+        let player = game.players.get(&player_id)?;
+        let mut speed = PLAYER_MOVE_SPEED * game.movement_multiplier(player_id);
+        if matches!(player.location, PlayerLocation::OutsideWorld(_)) {
+            speed *= game.environment.movement_scale();
+        }
+        let delta = (
+            movement.0 * speed * TILE_SIZE * delta_time,
+            movement.1 * speed * TILE_SIZE * delta_time,
+        );
+
+        match player.location {
+            PlayerLocation::OutsideWorld(pos) => {
+                Some(PlayerLocation::OutsideWorld(resolve_outside(game, pos, delta)))
+            }
+            PlayerLocation::InsideMech { mech_id, pos } => {
+                let floor_map = game
+                    .mechs
+                    .get(&mech_id)?
+                    .interior
+                    .floors
+                    .get(pos.floor as usize)?;
+
+                // Discard the remembered offset if the player was moved some
+                // other way (floor change, re-entering the mech)
+                let offset = match self.interior_offsets.get(&player_id) {
+                    Some((last_pos, offset)) if *last_pos == pos => *offset,
+                    _ => WorldPos::new(0.0, 0.0),
+                };
+                let center = pos.tile_pos.to_world_center();
+                let from = WorldPos::new(center.x + offset.x, center.y + offset.y);
+
+                let to = resolve_inside(floor_map, player.team, from, delta);
+                let new_tile = to.to_tile();
+                let new_center = new_tile.to_world_center();
+                let new_pos = MechInteriorPos::new(pos.floor, new_tile);
+                self.interior_offsets.insert(
+                    player_id,
+                    (new_pos, WorldPos::new(to.x - new_center.x, to.y - new_center.y)),
+                );
+
+                (new_pos != pos).then_some(PlayerLocation::InsideMech {
+                    mech_id,
+                    pos: new_pos,
+                })
+            }
+        }
+    }
+
+    /// Forget state for players that have left the game
+    pub fn retain_players(&mut self, game: &Game) {
+        self.interior_offsets
+            .retain(|id, _| game.players.contains_key(id));
+    }
+}
+
+/// Move a player in the open world, stopping against mech hulls, rocks and
+/// walls painted into the arena map, and the arena edge
+pub fn resolve_outside(game: &Game, pos: WorldPos, delta: (f32, f32)) -> WorldPos {
+    let obstacles: Vec<CollisionShape> = game
+        .mechs
+        .values()
+        .map(|mech| CollisionShape::mech(mech.world_position))
+        .collect();
+    let player_shape = CollisionShape::player(pos);
+    let safe_movement =
+        CollisionUtils::calculate_safe_movement(pos, delta, &player_shape, &obstacles);
+
+    let (world_width, world_height) = game.arena_map.world_size();
+    let target = WorldPos::new(
+        (pos.x + safe_movement.0).clamp(0.0, world_width),
+        (pos.y + safe_movement.1).clamp(0.0, world_height),
+    );
+
+    // Slide along blocked world tiles instead of stopping dead
+    [
+        target,
+        WorldPos::new(target.x, pos.y),
+        WorldPos::new(pos.x, target.y),
+    ]
+    .into_iter()
+    .find(|candidate| is_world_walkable(game, *candidate))
+    .unwrap_or(pos)
+}
+
+/// Whether a player outside can stand at a world position
+pub fn is_world_walkable(game: &Game, pos: WorldPos) -> bool {
+    match game.tile_map.get_world_tile(pos.to_tile()) {
+        Some(TileContent::Static(tile)) => tile.is_walkable(),
+        _ => true,
+    }
+}
+
+/// Move a player of `team` across a mech floor. Each axis is resolved
+/// separately so a blocked diagonal slides along the wall instead of
+/// stopping dead.
+pub fn resolve_inside(
+    floor_map: &FloorMap,
+    team: TeamId,
+    from: WorldPos,
+    delta: (f32, f32),
+) -> WorldPos {
+    // Never step more than one tile per update so walls can't be skipped
+    let max_step = TILE_SIZE - COLLISION_EPSILON;
+    let delta = (
+        delta.0.clamp(-max_step, max_step),
+        delta.1.clamp(-max_step, max_step),
+    );
+
+    let after_x = step_axis(floor_map, team, from, delta.0, Axis::X);
+    step_axis(floor_map, team, after_x, delta.1, Axis::Y)
+}
+
+/// Where a dash from `location` the way `direction` points comes to rest:
+/// up to `DASH_DISTANCE` tiles on, stopping short of the first thing in the
+/// way. Inside, enemy barricades stop a dash of `team` like walls do.
+/// None when the very first step is blocked.
+pub fn dash_destination(
+    game: &Game,
+    location: PlayerLocation,
+    team: TeamId,
+    direction: Facing,
+) -> Option<PlayerLocation> {
+    match location {
+        PlayerLocation::OutsideWorld(from) => {
+            let angle = direction.angle();
+            let (world_width, world_height) = game.arena_map.world_size();
+            let hulls: Vec<AABB> = game
+                .mechs
+                .values()
+                .map(|mech| AABB::mech_bounds(mech.world_position))
+                .collect();
+            // Small steps so nothing thinner than a quarter tile is skipped
+            let step = TILE_SIZE / 4.0;
+            let steps = (DASH_DISTANCE * TILE_SIZE / step) as u32;
+            let mut to = from;
+            for i in 1..=steps {
+                let distance = step * i as f32;
+                let next = WorldPos::new(
+                    from.x + angle.cos() * distance,
+                    from.y + angle.sin() * distance,
+                );
+                let inside_arena =
+                    (0.0..=world_width).contains(&next.x) && (0.0..=world_height).contains(&next.y);
+                let bounds = AABB::player_bounds(next);
+                if !inside_arena
+                    || !is_world_walkable(game, next)
+                    || hulls.iter().any(|hull| hull.intersects(&bounds))
+                {
+                    break;
+                }
+                to = next;
+            }
+            (to != from).then_some(PlayerLocation::OutsideWorld(to))
+        }
+        PlayerLocation::InsideMech { mech_id, pos } => {
+            let floor_map = game
+                .mechs
+                .get(&mech_id)?
+                .interior
+                .floors
+                .get(pos.floor as usize)?;
+            let angle = direction.angle();
+            let (dx, dy) = (angle.cos().round() as i32, angle.sin().round() as i32);
+            let mut tile = pos.tile_pos;
+            for _ in 0..DASH_DISTANCE as i32 {
+                let next = TilePos::new(tile.x + dx, tile.y + dy);
+                // Diagonals can't squeeze between two blocked corners
+                let corners_clear = dx == 0
+                    || dy == 0
+                    || is_interior_passable(floor_map, TilePos::new(tile.x + dx, tile.y), team)
+                        && is_interior_passable(floor_map, TilePos::new(tile.x, tile.y + dy), team);
+                if !corners_clear || !is_interior_passable(floor_map, next, team) {
+                    break;
+                }
+                tile = next;
+            }
+            (tile != pos.tile_pos).then_some(PlayerLocation::InsideMech {
+                mech_id,
+                pos: MechInteriorPos::new(pos.floor, tile),
+            })
+        }
+    }
+}
+
+/// Whether a player can stand on an interior tile. Walls, stations, turrets
+/// and anything else occupying the tile block movement.
+pub fn is_interior_walkable(floor_map: &FloorMap, tile: TilePos) -> bool {
+    !floor_map.entity_tiles.contains_key(&tile)
+        && floor_map
+            .static_tiles
+            .get(&tile)
+            .is_some_and(|static_tile| static_tile.is_walkable())
+}
+
+/// Whether a player of `team` can step onto an interior tile: walkable and
+/// not held by an enemy barricade
+pub fn is_interior_passable(floor_map: &FloorMap, tile: TilePos, team: TeamId) -> bool {
+    is_interior_walkable(floor_map, tile) && !floor_map.barricade_blocks(tile, team)
+}
+
+/// Tiles a player can interact with: the one they stand on plus the eight around it
+pub fn tiles_in_reach(center: TilePos) -> Vec<TilePos> {
+    let mut tiles = Vec::with_capacity(9);
+    for dy in -1..=1 {
+        for dx in -1..=1 {
+            tiles.push(TilePos::new(center.x + dx, center.y + dy));
+        }
+    }
+    tiles
+}
+
+#[derive(Clone, Copy)]
+enum Axis {
+    X,
+    Y,
+}
+
+fn step_axis(
+    floor_map: &FloorMap,
+    team: TeamId,
+    from: WorldPos,
+    delta: f32,
+    axis: Axis,
+) -> WorldPos {
+    if delta == 0.0 {
+        return from;
+    }
+
+    let mut to = from;
+    match axis {
+        Axis::X => to.x += delta,
+        Axis::Y => to.y += delta,
+    }
+
+    let current_tile = from.to_tile();
+    let target_tile = to.to_tile();
+    if target_tile == current_tile || is_interior_passable(floor_map, target_tile, team) {
+        return to;
+    }
+
+    // Blocked: stop flush against the edge of the current tile
+    let tile_origin = current_tile.to_world();
+    match axis {
+        Axis::X => {
+            to.x = to
+                .x
+                .clamp(tile_origin.x, tile_origin.x + TILE_SIZE - COLLISION_EPSILON)
+        }
+        Axis::Y => {
+            to.y = to
+                .y
+                .clamp(tile_origin.y, tile_origin.y + TILE_SIZE - COLLISION_EPSILON)
+        }
+    }
+    to
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::mech_layout::MechLayoutGenerator;
+    use shared::tile_entity::StaticTile;
+
+    fn open_floor() -> FloorMap {
+        let mut floor = FloorMap::new();
+        MechLayoutGenerator::generate_basic_floor_layout(&mut floor);
+        floor
+    }
+
+    #[test]
+    fn test_walls_and_stations_block() {
+        let mut floor = open_floor();
+        floor.set_multi_tile_station(&[TilePos::new(3, 3)], Uuid::new_v4());
+
+        assert!(is_interior_walkable(&floor, TilePos::new(2, 2)));
+        assert!(!is_interior_walkable(&floor, TilePos::new(0, 2)));
+        assert!(!is_interior_walkable(&floor, TilePos::new(3, 3)));
+        assert!(!is_interior_walkable(&floor, TilePos::new(-1, 2)));
+
+        floor.set_static_tile(TilePos::new(2, 2), StaticTile::DamagedWall);
+        assert!(!is_interior_walkable(&floor, TilePos::new(2, 2)));
+    }
+
+    #[test]
+    fn test_blocked_diagonal_slides_along_wall() {
+        let floor = open_floor();
+        let from = TilePos::new(1, 4).to_world_center();
+
+        // Pushing into the left wall while moving down keeps the downward motion
+        let to = resolve_inside(&floor, TeamId::Red, from, (-TILE_SIZE, TILE_SIZE * 0.75));
+        assert_eq!(to.to_tile().x, 1);
+        assert_eq!(to.x, TILE_SIZE);
+        assert_eq!(to.to_tile().y, 5);
+    }
+
+    #[test]
+    fn test_small_steps_accumulate_into_tile_moves() {
+        let mut game = Game::new();
+        let mech_id = *game.mechs.keys().next().unwrap();
+        let player_id = Uuid::new_v4();
+        game.add_player(player_id, "Walker".to_string(), None);
+        let start = MechInteriorPos::new(0, TilePos::new(2, 7));
+        game.players.get_mut(&player_id).unwrap().location = PlayerLocation::InsideMech {
+            mech_id,
+            pos: start,
+        };
+
+        let mut resolver = MovementResolver::new();
+        let mut moved_to = None;
+        for _ in 0..20 {
+            if let Some(location) = resolver.resolve(&game, player_id, (1.0, 0.0), 0.033) {
+                moved_to = Some(location);
+                break;
+            }
+        }
+
+        match moved_to {
+            Some(PlayerLocation::InsideMech { pos, .. }) => {
+                assert_eq!(pos.tile_pos, TilePos::new(3, 7));
+            }
+            other => panic!("Expected to move one tile right, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_map_rocks_block_players_outside() {
+        let mut game = Game::new();
+        for y in 49..=51 {
+            game.tile_map
+                .set_world_tile(TilePos::new(50, y), TileContent::Static(StaticTile::Rock));
+        }
+
+        // Walking right into the rock wall stops at the tile before it
+        let from = TilePos::new(49, 50).to_world_center();
+        let to = resolve_outside(&game, from, (TILE_SIZE, 0.0));
+        assert_eq!(to, from);
+
+        // Moving diagonally slides down along it
+        let to = resolve_outside(&game, from, (TILE_SIZE, TILE_SIZE));
+        assert_eq!(to.to_tile(), TilePos::new(49, 51));
+    }
+
+    #[test]
+    fn test_dashes_stop_short_of_rocks_and_walls() {
+        let mut game = Game::new();
+        game.tile_map
+            .set_world_tile(TilePos::new(52, 50), TileContent::Static(StaticTile::Rock));
+
+        // Open ground: the full distance
+        let from = TilePos::new(49, 50).to_world_center();
+        let outside = PlayerLocation::OutsideWorld(from);
+        match dash_destination(&game, outside, TeamId::Red, Facing::North) {
+            Some(PlayerLocation::OutsideWorld(to)) => {
+                assert!((from.y - to.y - DASH_DISTANCE * TILE_SIZE).abs() < 0.01);
+            }
+            other => panic!("Expected a full dash north, got {other:?}"),
+        }
+        // A rock two tiles east cuts it short
+        match dash_destination(&game, outside, TeamId::Red, Facing::East) {
+            Some(PlayerLocation::OutsideWorld(to)) => assert_eq!(to.to_tile().x, 51),
+            other => panic!("Expected to stop before the rock, got {other:?}"),
+        }
+
+        // Inside, the hull wall a tile to the left leaves nowhere to go
+        let mech_id = *game.mechs.keys().next().unwrap();
+        let inside = PlayerLocation::InsideMech {
+            mech_id,
+            pos: MechInteriorPos::new(0, TilePos::new(1, 7)),
+        };
+        assert!(dash_destination(&game, inside, TeamId::Red, Facing::West).is_none());
+    }
+}
@@ -0,0 +1,143 @@
+//! Read-only match score for stream overlays. `/overlay/score` is the live
+//! numbers as JSON; `/overlay` is a small page that polls it, meant to be
+//! added to OBS as a browser source. Both take `?room=` to pick a match,
+//! and the page takes `?refresh=<ms>` and `?theme=light`.
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::Html,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use shared::*;
+
+use crate::{game::Game, AppState};
+
+const OVERLAY_PAGE: &str = include_str!("overlay.html");
+
+#[derive(Debug, Deserialize)]
+pub struct OverlayParams {
+    /// Match to show; the default room when absent
+    room: Option<String>,
+    /// Milliseconds between polls, for the page
+    refresh: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScoreboardPlayer {
+    pub name: String,
+    pub ai: bool,
+    pub health: u32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TeamScore {
+    pub team: TeamId,
+    pub score: u32,
+    pub mech_health: u32,
+    pub mech_max_health: u32,
+    pub shield: u32,
+    pub max_shield: u32,
+    pub players: Vec<ScoreboardPlayer>,
+}
+
+/// Everything the overlay shows, red team first
+#[derive(Debug, Serialize)]
+pub struct Scoreboard {
+    pub room: String,
+    pub match_time: f32, // seconds
+    pub teams: Vec<TeamScore>,
+}
+
+impl Scoreboard {
+    pub fn from_game(room: &str, game: &Game) -> Self {
+        let ai_players = game.get_ai_players();
+        let teams = [TeamId::Red, TeamId::Blue]
+            .into_iter()
+            .filter_map(|team| {
+                let mech = game.mechs.values().find(|mech| mech.team == team)?;
+                let mut players: Vec<ScoreboardPlayer> = game
+                    .players
+                    .values()
+                    .filter(|player| player.team == team)
+                    .map(|player| ScoreboardPlayer {
+                        name: player.name.clone(),
+                        ai: ai_players.contains(&player.id),
+                        health: player.health,
+                    })
+                    .collect();
+                players.sort_by(|a, b| a.ai.cmp(&b.ai).then_with(|| a.name.cmp(&b.name)));
+                Some(TeamScore {
+                    team,
+                    score: mech.delivery_score,
+                    mech_health: mech.health,
+                    mech_max_health: mech.max_health,
+                    shield: mech.shield,
+                    max_shield: mech.max_shield,
+                    players,
+                })
+            })
+            .collect();
+        Self {
+            room: room.to_string(),
+            match_time: game.match_time,
+            teams,
+        }
+    }
+}
+
+pub async fn score(
+    State(state): State<AppState>,
+    Query(params): Query<OverlayParams>,
+) -> Result<Json<Scoreboard>, (StatusCode, String)> {
+    let name = params.room.as_deref().unwrap_or(DEFAULT_ROOM);
+    let room = state
+        .rooms
+        .get(name)
+        .await
+        .ok_or_else(|| (StatusCode::NOT_FOUND, format!("no room named '{name}'")))?;
+    let game = room.game.read().await;
+    Ok(Json(Scoreboard::from_game(name, &game)))
+}
+
+pub async fn page(Query(params): Query<OverlayParams>) -> Html<String> {
+    let refresh = params
+        .refresh
+        .unwrap_or(OVERLAY_REFRESH_MS)
+        .clamp(OVERLAY_MIN_REFRESH_MS, OVERLAY_MAX_REFRESH_MS);
+    Html(OVERLAY_PAGE.replace("{{REFRESH_MS}}", &refresh.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_scoreboard_lists_both_teams_with_humans_first() {
+        let mut game = Game::new();
+        game.add_player(Uuid::new_v4(), "Zed".to_string(), Some(TeamId::Red));
+        game.add_player(Uuid::new_v4(), "AI_Helper".to_string(), Some(TeamId::Red));
+        game.add_player(Uuid::new_v4(), "Amy".to_string(), Some(TeamId::Blue));
+        let red = game
+            .mechs
+            .values_mut()
+            .find(|m| m.team == TeamId::Red)
+            .unwrap();
+        red.delivery_score = 7;
+        game.match_time = 42.0;
+
+        let scoreboard = Scoreboard::from_game(DEFAULT_ROOM, &game);
+        assert_eq!(scoreboard.match_time, 42.0);
+        let teams: Vec<TeamId> = scoreboard.teams.iter().map(|t| t.team).collect();
+        assert_eq!(teams, vec![TeamId::Red, TeamId::Blue]);
+
+        let red = &scoreboard.teams[0];
+        assert_eq!(red.score, 7);
+        let names: Vec<&str> = red.players.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["Zed", "AI_Helper"]);
+        assert!(red.players[1].ai);
+        assert_eq!(scoreboard.teams[1].players.len(), 1);
+    }
+}
@@ -0,0 +1,79 @@
+//! On-demand puffin profiling. Scopes stay off until a capture asks for them,
+//! so a server nobody is profiling only pays an atomic load per scope.
+
+use puffin::{FrameView, GlobalProfiler};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Records the game loop's frames into `.puffin` files that open in
+/// puffin_viewer
+#[derive(Clone, Default)]
+pub struct Profiler {
+    /// Captures running right now; scopes go off when the last one ends
+    active_captures: Arc<AtomicUsize>,
+}
+
+impl Profiler {
+    /// Record every frame for `duration` and return them as a `.puffin` file
+    pub async fn capture(&self, duration: Duration) -> anyhow::Result<Vec<u8>> {
+        let view = Arc::new(Mutex::new(FrameView::default()));
+        let sink = {
+            let view = Arc::clone(&view);
+            let mut profiler = GlobalProfiler::lock();
+            let sink = profiler.add_sink(Box::new(move |frame| {
+                view.lock().unwrap().add_frame(frame);
+            }));
+            // Scopes seen by earlier captures are only described once, so
+            // have the next frame describe them all again
+            profiler.emit_scope_snapshot();
+            sink
+        };
+
+        self.active_captures.fetch_add(1, Ordering::SeqCst);
+        puffin::set_scopes_on(true);
+        tokio::time::sleep(duration).await;
+        if self.active_captures.fetch_sub(1, Ordering::SeqCst) == 1 {
+            puffin::set_scopes_on(false);
+        }
+        GlobalProfiler::lock().remove_sink(sink);
+
+        let view = view.lock().unwrap();
+        let mut bytes = Vec::new();
+        view.write(&mut bytes)?;
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_capture_records_frames() {
+        let profiler = Profiler::default();
+        let capture = tokio::spawn({
+            let profiler = profiler.clone();
+            async move { profiler.capture(Duration::from_millis(200)).await }
+        });
+        while !puffin::are_scopes_on() {
+            tokio::task::yield_now().await;
+        }
+
+        let frames = std::thread::spawn(|| {
+            for _ in 0..20 {
+                {
+                    profiling::scope!("test_frame");
+                    std::thread::sleep(Duration::from_millis(5));
+                }
+                profiling::finish_frame!();
+            }
+        });
+        frames.join().unwrap();
+
+        let bytes = capture.await.unwrap().unwrap();
+        assert!(bytes.starts_with(b"PUF0"));
+        assert!(bytes.len() > 4, "no frames were captured");
+        assert!(!puffin::are_scopes_on());
+    }
+}
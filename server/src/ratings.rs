@@ -0,0 +1,109 @@
+//! Win and loss records kept across matches, which matchmaking rates
+//! players by. Players have no accounts, so records go by name. With
+//! `--ratings <path.json>` they're written out after every rated match and
+//! read back on startup; without it they last as long as the server.
+
+use serde::{Deserialize, Serialize};
+use shared::*;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+pub type SharedRatings = Arc<Mutex<Ratings>>;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct PlayerRecord {
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+}
+
+impl PlayerRecord {
+    /// `RATING_START` moved by `RATING_PER_RESULT` for each win or loss
+    pub fn rating(&self) -> f32 {
+        RATING_START + RATING_PER_RESULT * (self.wins as f32 - self.losses as f32)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Ratings {
+    /// Where records are saved, if anywhere
+    path: Option<PathBuf>,
+    records: HashMap<String, PlayerRecord>,
+}
+
+impl Ratings {
+    /// Records saved at `path`, starting empty if there's no file yet
+    pub fn load(path: PathBuf) -> anyhow::Result<Self> {
+        let records = match std::fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self {
+            path: Some(path),
+            records,
+        })
+    }
+
+    pub fn rating(&self, player_name: &str) -> f32 {
+        self.records
+            .get(player_name)
+            .copied()
+            .unwrap_or_default()
+            .rating()
+    }
+
+    /// Score a finished match for everyone who was in it, then save
+    pub fn record(&mut self, players: &[(String, TeamId)], winner: Option<TeamId>) {
+        for (name, team) in players {
+            let record = self.records.entry(name.clone()).or_default();
+            match winner {
+                Some(winner) if winner == *team => record.wins += 1,
+                Some(_) => record.losses += 1,
+                None => record.draws += 1,
+            }
+        }
+        let Some(path) = &self.path else {
+            return;
+        };
+        let saved = serde_json::to_string_pretty(&self.records)
+            .map_err(anyhow::Error::from)
+            .and_then(|json| Ok(std::fs::write(path, json)?));
+        if let Err(e) = saved {
+            log::error!("Failed to save ratings to {}: {e}", path.display());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_results_move_ratings_and_survive_a_restart() {
+        let path = std::env::temp_dir().join(format!("ratings-{}.json", uuid::Uuid::new_v4()));
+        let mut ratings = Ratings::load(path.clone()).unwrap();
+        assert_eq!(ratings.rating("Ace"), RATING_START);
+
+        let players = [
+            ("Ace".to_string(), TeamId::Red),
+            ("Rookie".to_string(), TeamId::Blue),
+        ];
+        ratings.record(&players, Some(TeamId::Red));
+        ratings.record(&players, None);
+        assert_eq!(ratings.rating("Ace"), RATING_START + RATING_PER_RESULT);
+        assert_eq!(ratings.rating("Rookie"), RATING_START - RATING_PER_RESULT);
+
+        let reloaded = Ratings::load(path.clone()).unwrap();
+        assert_eq!(
+            reloaded.records["Ace"],
+            PlayerRecord {
+                wins: 1,
+                losses: 0,
+                draws: 1
+            }
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+}
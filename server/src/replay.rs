@@ -0,0 +1,80 @@
+//! Writes a room's match to a replay file for the replay viewer: the seed
+//! the match rolls from, the world as it stood when recording began, then
+//! every public broadcast, stamped with the tick it went out on.
+
+use crate::game::Game;
+use shared::*;
+use std::io::Write;
+use std::sync::Arc;
+use tokio::sync::{broadcast, RwLock};
+use uuid::Uuid;
+
+/// Record the room into `file` for as long as its channel is open
+pub async fn run_recorder(
+    game: Arc<RwLock<Game>>,
+    mut rx: broadcast::Receiver<(Uuid, ServerMessage)>,
+    file: std::fs::File,
+) {
+    let mut out = std::io::BufWriter::new(file);
+    let (header, tick, snapshot) = {
+        let game = game.read().await;
+        let header = ReplayHeader {
+            seed: game.rng.seed(),
+        };
+        (header, game.tick_count, game.world_snapshot())
+    };
+    let written = encode_replay_header(&header)
+        .map_err(|e| e.to_string())
+        .and_then(|bytes| out.write_all(&bytes).map_err(|e| e.to_string()));
+    if let Err(e) = written {
+        log::error!("Couldn't start recording the replay: {e}");
+        return;
+    }
+    let mut frames: Vec<ReplayFrame> = snapshot
+        .into_iter()
+        .map(|message| ReplayFrame { tick, message })
+        .collect();
+
+    loop {
+        if !write_frames(&mut out, &frames) {
+            return;
+        }
+        frames.clear();
+
+        let first = match rx.recv().await {
+            Ok(message) => message,
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                log::warn!("Replay recorder fell behind and missed {skipped} messages");
+                continue;
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        };
+        // Stamp everything that's queued up with one look at the clock
+        let mut batch = vec![first];
+        while let Ok(message) = rx.try_recv() {
+            batch.push(message);
+        }
+        let tick = game.read().await.tick_count;
+        frames.extend(
+            batch
+                .into_iter()
+                .filter(|(target, _)| target.is_nil())
+                .map(|(_, message)| ReplayFrame { tick, message }),
+        );
+    }
+    let _ = out.flush();
+}
+
+/// False once the file can't be written, which ends the recording
+fn write_frames(out: &mut impl Write, frames: &[ReplayFrame]) -> bool {
+    for frame in frames {
+        let written = encode_replay_frame(frame)
+            .map_err(|e| e.to_string())
+            .and_then(|bytes| out.write_all(&bytes).map_err(|e| e.to_string()));
+        if let Err(e) = written {
+            log::error!("Stopped recording the replay: {e}");
+            return false;
+        }
+    }
+    out.flush().is_ok()
+}
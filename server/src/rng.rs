@@ -0,0 +1,85 @@
+//! The match's one source of randomness. Every roll the simulation makes
+//! and every id it hands out comes from the game's `GameRng`, so a match
+//! started from the same seed with the same inputs plays out the same way.
+//! AIs get ids and generators of their own from it when they join; only
+//! player ids, which come from their connections, stay outside it.
+
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+use uuid::Uuid;
+
+pub struct GameRng {
+    seed: u64,
+    rng: StdRng,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// A generator on a fresh seed, for matches nobody asked to reproduce
+    pub fn from_entropy() -> Self {
+        Self::new(rand::random())
+    }
+
+    /// The seed the match started from
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// A v4 id drawn from the match's stream rather than the OS
+    pub fn uuid(&mut self) -> Uuid {
+        let mut bytes = [0; 16];
+        self.rng.fill_bytes(&mut bytes);
+        uuid::Builder::from_random_bytes(bytes).into_uuid()
+    }
+
+    /// A generator of its own, seeded from the match's stream, for whatever
+    /// rolls on a schedule of its own, like an AI thinking on its worker
+    pub fn fork(&mut self) -> StdRng {
+        StdRng::seed_from_u64(self.rng.next_u64())
+    }
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        Self::from_entropy()
+    }
+}
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.rng.try_fill_bytes(dest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    #[test]
+    fn test_same_seed_same_stream() {
+        let (mut a, mut b) = (GameRng::new(7), GameRng::new(7));
+        assert_eq!(a.uuid(), b.uuid());
+        assert_eq!(a.gen_range(0..1000), b.gen_range(0..1000));
+        assert_eq!(a.uuid().get_version_num(), 4);
+        assert_ne!(GameRng::new(8).uuid(), GameRng::new(7).uuid());
+    }
+}
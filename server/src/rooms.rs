@@ -0,0 +1,566 @@
+//! Independent matches on one server. Each room has its own `Game`,
+//! broadcast channel and game loop task; clients pick one with `/ws?room=`.
+
+use serde::Serialize;
+use shared::*;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, RwLock};
+use tokio::task::AbortHandle;
+use uuid::Uuid;
+
+use crate::event_log::SharedEventLog;
+use crate::game::Game;
+use crate::game_modes;
+use crate::hangar;
+use crate::idle::IdleTracker;
+use crate::systems::ai::AISystem;
+use crate::systems::game_mode::GameModeSystem;
+use crate::systems::tutorial::{TutorialScript, TutorialSystem};
+use crate::systems::SystemConfig;
+use crate::testing_modes::TestingConfig;
+use crate::tick_clock::SharedTickMetrics;
+
+/// Who may join a room and how browsers show it
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RoomAccess {
+    /// Shown in listings in place of the room's key
+    pub title: Option<String>,
+    /// Most human players at once; AI crew don't count
+    pub max_players: Option<usize>,
+    /// Players must send this in `JoinGame` to get in
+    pub password: Option<String>,
+}
+
+impl RoomAccess {
+    /// Whether `attempt` opens the room
+    pub fn admits(&self, attempt: Option<&str>) -> bool {
+        self.password
+            .as_deref()
+            .is_none_or(|password| attempt == Some(password))
+    }
+}
+
+/// How a room's match is set up. Rooms opened on demand use the server's
+/// defaults; `--room` can override them per room.
+#[derive(Debug, Clone)]
+pub struct RoomConfig {
+    pub arena_map: ArenaMap,
+    pub ai_autofill_min_crew: Option<usize>,
+    pub team_vision: bool,
+    /// How far crew outside see around them
+    pub sight: SightShape,
+    pub dev_mode: bool,
+    pub stations: StationRegistry,
+    pub spawn_tables: SpawnTables,
+    /// Guided scenario every player joining works through
+    pub tutorial: Option<TutorialScript>,
+    /// How the match is won; open-ended without one
+    pub game_mode: Option<GameModeKind>,
+    pub access: RoomAccess,
+    /// Task weights the room's utility AIs start with
+    pub ai_weights: ai::UtilityWeights,
+    /// Update rates, like how often the AIs decide what to do
+    pub systems: SystemConfig,
+    /// Seconds without input before a player counts as away
+    pub afk_timeout: f32,
+    /// Whether an AI holds an away player's station until they're back
+    pub afk_stand_ins: bool,
+    /// Seconds crews get in the hangar picking roles before the match
+    /// starts; straight into the match without one
+    pub hangar: Option<f32>,
+    /// Follow each full state with hashes of it for clients to check
+    pub state_checksums: bool,
+    /// What the match rolls from, to play one back exactly; fresh each
+    /// match without one
+    pub seed: Option<u64>,
+}
+
+impl Default for RoomConfig {
+    fn default() -> Self {
+        Self {
+            arena_map: ArenaMap::default(),
+            ai_autofill_min_crew: None,
+            team_vision: true,
+            sight: SightShape::default(),
+            dev_mode: false,
+            stations: StationRegistry::new(),
+            spawn_tables: SpawnTables::new(),
+            tutorial: None,
+            game_mode: None,
+            access: RoomAccess::default(),
+            ai_weights: ai::UtilityWeights::default(),
+            systems: SystemConfig::default(),
+            afk_timeout: AFK_TIMEOUT_SECONDS,
+            afk_stand_ins: false,
+            hangar: None,
+            state_checksums: false,
+            seed: None,
+        }
+    }
+}
+
+impl RoomConfig {
+    /// A fresh match with this config applied and resources scattered
+    pub fn create_game(&self, testing_config: TestingConfig) -> Game {
+        let seed = self.seed.unwrap_or_else(rand::random);
+        let mut game = Game::new_with_map(testing_config, self.arena_map.clone(), seed);
+        log::info!("Match seed {seed}");
+        game.spawn_tables = self.spawn_tables.clone();
+        game.spawn_initial_resources();
+        game.ai_autofill_min_crew = self.ai_autofill_min_crew;
+        game.team_vision = self.team_vision;
+        game.sight = self.sight;
+        game.dev_mode = self.dev_mode;
+        game.state_checksums = self.state_checksums;
+        game.station_registry = self.stations.clone();
+        game.access = self.access.clone();
+        game.idle = IdleTracker::new(self.afk_timeout, self.afk_stand_ins);
+        if let Some(ai_system) = game.system_manager.get_system_mut::<AISystem>() {
+            ai_system.set_default_weights(self.ai_weights.clone());
+            ai_system.set_tick_rate(self.systems.ai_hz);
+            ai_system.set_budget_share(self.systems.ai_budget_share);
+        }
+        if let Some(script) = &self.tutorial {
+            if let Some(tutorial) = game.system_manager.get_system_mut::<TutorialSystem>() {
+                tutorial.set_script(script.clone());
+            }
+        }
+        if let Some(kind) = self.game_mode {
+            if let Some(system) = game.system_manager.get_system_mut::<GameModeSystem>() {
+                system.set_mode(game_modes::create(kind));
+            }
+        }
+        if let Some(duration) = self.hangar {
+            hangar::open(&mut game, duration);
+        }
+        game.balance_ai_crew();
+        game
+    }
+}
+
+/// Parse a `--room` value: `name` or `name:key=value,...` with keys
+/// `size=WxH`, `ai=N`, `vision=team|solo`, `sight=<sight shape>`,
+/// `mode=<game mode>`, `title=<shown name>`, `max=<players>`,
+/// `password=<secret>`, `hangar=<seconds>` and `seed=<number>`. Unset keys
+/// come from `base`.
+pub fn parse_room_spec(spec: &str, base: &RoomConfig) -> GameResult<(String, RoomConfig)> {
+    let (name, overrides) = spec.split_once(':').unwrap_or((spec, ""));
+    validate_room_name(name)?;
+
+    let mut config = base.clone();
+    for entry in overrides.split(',').filter(|entry| !entry.is_empty()) {
+        let invalid = || GameError::invalid_input(format!("bad room option '{entry}'"));
+        let (key, value) = entry.split_once('=').ok_or_else(invalid)?;
+        match key {
+            "size" => {
+                let (width, height) = value.split_once('x').ok_or_else(invalid)?;
+                let width = width.parse().map_err(|_| invalid())?;
+                let height = height.parse().map_err(|_| invalid())?;
+                let arena_map = ArenaMap::sized(width, height);
+                arena_map.validate()?;
+                config.arena_map = arena_map;
+            }
+            "ai" => config.ai_autofill_min_crew = Some(value.parse().map_err(|_| invalid())?),
+            "vision" => {
+                config.team_vision = match value {
+                    "team" => true,
+                    "solo" => false,
+                    _ => return Err(invalid()),
+                }
+            }
+            "mode" => config.game_mode = Some(value.parse()?),
+            "sight" => config.sight = value.parse()?,
+            "title" => {
+                let title = value.trim();
+                if title.is_empty() || title.len() > MAX_ROOM_TITLE_LENGTH {
+                    return Err(invalid());
+                }
+                config.access.title = Some(title.to_string());
+            }
+            "max" => match value.parse() {
+                Ok(max) if max > 0 => config.access.max_players = Some(max),
+                _ => return Err(invalid()),
+            },
+            "password" => {
+                if value.is_empty() || value.len() > MAX_ROOM_PASSWORD_LENGTH {
+                    return Err(invalid());
+                }
+                config.access.password = Some(value.to_string());
+            }
+            "hangar" => match value.parse() {
+                Ok(seconds) if seconds > 0.0 => config.hangar = Some(seconds),
+                _ => return Err(invalid()),
+            },
+            "seed" => config.seed = Some(value.parse().map_err(|_| invalid())?),
+            _ => return Err(invalid()),
+        }
+    }
+    Ok((name.to_string(), config))
+}
+
+fn validate_room_name(name: &str) -> GameResult<()> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_ROOM_NAME_LENGTH
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(GameError::invalid_input(format!(
+            "room names are 1-{MAX_ROOM_NAME_LENGTH} letters, digits, '-' or '_'"
+        )))
+    }
+}
+
+/// One running match
+pub struct Room {
+    pub name: String,
+    pub game: Arc<RwLock<Game>>,
+    pub tx: broadcast::Sender<(Uuid, ServerMessage)>,
+    pub tick_metrics: SharedTickMetrics,
+    pub event_log: SharedEventLog,
+    /// Rooms set up at startup stay open when everyone leaves
+    persistent: bool,
+    /// When the last client left, if the room is empty
+    empty_since: Mutex<Option<Instant>>,
+    game_loop: AbortHandle,
+    event_listener: AbortHandle,
+}
+
+impl Room {
+    /// Spawn the game loop and event log for a new room
+    fn start(name: &str, game: Game, persistent: bool) -> Self {
+        let game = Arc::new(RwLock::new(game));
+        let (tx, _) = broadcast::channel(BROADCAST_CHANNEL_SIZE);
+        let tick_metrics = SharedTickMetrics::default();
+        let game_loop = tokio::spawn(crate::game_loop::run_game_loop(
+            game.clone(),
+            tx.clone(),
+            tick_metrics.clone(),
+        ))
+        .abort_handle();
+        let event_log = SharedEventLog::default();
+        let event_listener = tokio::spawn(crate::event_log::run_event_log(
+            game.clone(),
+            tx.subscribe(),
+            event_log.clone(),
+        ))
+        .abort_handle();
+
+        Self {
+            name: name.to_string(),
+            game,
+            tx,
+            tick_metrics,
+            event_log,
+            persistent,
+            empty_since: Mutex::new(None),
+            game_loop,
+            event_listener,
+        }
+    }
+}
+
+/// A room as listed by `/rooms`
+#[derive(Debug, Serialize)]
+pub struct RoomInfo {
+    pub name: String,
+    /// What to show for the room; its name unless the creator gave a title
+    pub title: String,
+    pub players: usize,
+    pub max_players: Option<usize>,
+    /// Joining takes a password
+    pub locked: bool,
+    pub ai_players: usize,
+    pub arena_width: i32,  // tiles
+    pub arena_height: i32, // tiles
+    pub accepting_players: bool,
+    pub game_mode: Option<GameModeKind>,
+}
+
+/// Every open room, by name
+#[derive(Clone)]
+pub struct Rooms {
+    rooms: Arc<RwLock<HashMap<String, Arc<Room>>>>,
+    /// Config for rooms opened on demand
+    defaults: Arc<RoomConfig>,
+}
+
+impl Rooms {
+    pub fn new(defaults: RoomConfig) -> Self {
+        Self {
+            rooms: Default::default(),
+            defaults: Arc::new(defaults),
+        }
+    }
+
+    /// Start a room's game loop and make it joinable. Fails if a room of
+    /// that name is already open.
+    pub async fn open(&self, name: &str, game: Game, persistent: bool) -> GameResult<Arc<Room>> {
+        self.open_with(name, persistent, false, move || game).await
+    }
+
+    /// The named room, opening it with the default config if it doesn't
+    /// exist yet
+    pub async fn get_or_create(&self, name: &str) -> GameResult<Arc<Room>> {
+        validate_room_name(name)?;
+        if let Some(room) = self.rooms.read().await.get(name) {
+            return Ok(room.clone());
+        }
+        self.open_with(name, false, true, || {
+            self.defaults
+                .create_game(TestingConfig::create_normal_config())
+        })
+        .await
+    }
+
+    /// Open a new on-demand room set up with `config`
+    pub async fn open_configured(&self, name: &str, config: &RoomConfig) -> GameResult<Arc<Room>> {
+        validate_room_name(name)?;
+        self.open_with(name, false, false, || {
+            config.create_game(TestingConfig::create_normal_config())
+        })
+        .await
+    }
+
+    /// Check for the room and open it under one write lock, so callers
+    /// racing to open the same name get one room between them and the room
+    /// cap holds. Only the caller that opens the room builds its game and
+    /// starts its tasks; the rest get the open room if `join_open`, or an
+    /// error.
+    async fn open_with(
+        &self,
+        name: &str,
+        persistent: bool,
+        join_open: bool,
+        game: impl FnOnce() -> Game,
+    ) -> GameResult<Arc<Room>> {
+        let mut rooms = self.rooms.write().await;
+        if let Some(room) = rooms.get(name) {
+            return if join_open {
+                Ok(room.clone())
+            } else {
+                Err(GameError::invalid_state(format!(
+                    "room '{name}' is already open"
+                )))
+            };
+        }
+        if !persistent && rooms.len() >= MAX_ROOMS {
+            return Err(GameError::invalid_state(format!(
+                "the server already has {MAX_ROOMS} rooms open"
+            )));
+        }
+        let room = Arc::new(Room::start(name, game(), persistent));
+        rooms.insert(name.to_string(), room.clone());
+        log::info!("Opened room '{name}'");
+        Ok(room)
+    }
+
+    /// Config for rooms opened on demand
+    pub fn defaults(&self) -> &RoomConfig {
+        &self.defaults
+    }
+
+    /// An open room, without opening it if it isn't
+    pub async fn get(&self, name: &str) -> Option<Arc<Room>> {
+        self.rooms.read().await.get(name).cloned()
+    }
+
+    pub async fn all(&self) -> Vec<Arc<Room>> {
+        self.rooms.read().await.values().cloned().collect()
+    }
+
+    pub async fn list(&self) -> Vec<RoomInfo> {
+        let mut infos = Vec::new();
+        for room in self.all().await {
+            let game = room.game.read().await;
+            let ai_players = game.get_ai_players().len();
+            let players = game.players.len() - ai_players;
+            infos.push(RoomInfo {
+                name: room.name.clone(),
+                title: game
+                    .access
+                    .title
+                    .clone()
+                    .unwrap_or_else(|| room.name.clone()),
+                players,
+                max_players: game.access.max_players,
+                locked: game.access.password.is_some(),
+                ai_players,
+                arena_width: game.arena_map.width,
+                arena_height: game.arena_map.height,
+                accepting_players: game.accepting_players
+                    && game.access.max_players.is_none_or(|max| players < max),
+                game_mode: game.game_mode.as_ref().map(|hud| hud.mode),
+            });
+        }
+        infos.sort_by(|a, b| a.name.cmp(&b.name));
+        infos
+    }
+
+    /// Close on-demand rooms nobody has been connected to for
+    /// `ROOM_IDLE_TIMEOUT_SECS`
+    pub async fn close_idle(&self, now: Instant) {
+        let timeout = Duration::from_secs(ROOM_IDLE_TIMEOUT_SECS);
+        self.rooms.write().await.retain(|name, room| {
+            if room.persistent {
+                return true;
+            }
+            let mut empty_since = room.empty_since.lock().unwrap();
+            // The event log always listens, so it takes a client beyond that
+            if room.tx.receiver_count() > 1 {
+                *empty_since = None;
+                return true;
+            }
+            let since = *empty_since.get_or_insert(now);
+            if now.duration_since(since) < timeout {
+                return true;
+            }
+            log::info!("Closing idle room '{name}'");
+            room.game_loop.abort();
+            room.event_listener.abort();
+            false
+        });
+    }
+
+    /// Check for idle rooms in the background for as long as the server runs
+    pub fn spawn_reaper(&self) {
+        let rooms = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(ROOM_IDLE_CHECK_SECS));
+            loop {
+                interval.tick().await;
+                rooms.close_idle(Instant::now()).await;
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_room_spec_overrides_only_what_it_names() {
+        let base = RoomConfig {
+            ai_autofill_min_crew: Some(1),
+            ..Default::default()
+        };
+
+        let (name, config) = parse_room_spec("alpha", &base).unwrap();
+        assert_eq!(name, "alpha");
+        assert_eq!(config.arena_map, base.arena_map);
+
+        let (name, config) = parse_room_spec(
+            "small-1:size=50x60,vision=solo,sight=radial,mode=race",
+            &base,
+        )
+        .unwrap();
+        assert_eq!(name, "small-1");
+        assert_eq!((config.arena_map.width, config.arena_map.height), (50, 60));
+        assert!(!config.team_vision);
+        assert_eq!(config.sight, SightShape::Radial);
+        assert_eq!(config.game_mode, Some(GameModeKind::ResourceRace));
+        assert_eq!(config.ai_autofill_min_crew, Some(1));
+
+        assert!(parse_room_spec("bad name", &base).is_err());
+        assert!(parse_room_spec("alpha:size=10x10", &base).is_err());
+        assert!(parse_room_spec("alpha:speed=2", &base).is_err());
+        assert!(parse_room_spec("alpha:mode=tag", &base).is_err());
+
+        let (_, config) =
+            parse_room_spec("locked:title=Friday Night,max=4,password=hunter2", &base).unwrap();
+        assert_eq!(config.access.title.as_deref(), Some("Friday Night"));
+        assert_eq!(config.access.max_players, Some(4));
+        assert!(config.access.admits(Some("hunter2")));
+        assert!(!config.access.admits(Some("hunter3")));
+        assert!(!config.access.admits(None));
+        assert!(base.access.admits(None));
+        assert!(parse_room_spec("alpha:max=0", &base).is_err());
+        assert!(parse_room_spec("alpha:password=", &base).is_err());
+
+        let (_, config) = parse_room_spec("crews:hangar=45", &base).unwrap();
+        assert_eq!(config.hangar, Some(45.0));
+        assert_eq!(base.hangar, None);
+        assert!(parse_room_spec("alpha:hangar=soon", &base).is_err());
+
+        let (_, config) = parse_room_spec("rerun:seed=1234", &base).unwrap();
+        assert_eq!(config.seed, Some(1234));
+        assert!(parse_room_spec("alpha:seed=-1", &base).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_empty_rooms_close_after_the_timeout() {
+        let rooms = Rooms::new(RoomConfig::default());
+        let persistent = rooms
+            .open(DEFAULT_ROOM, Game::new(), true)
+            .await
+            .unwrap()
+            .name
+            .clone();
+        let room = rooms.get_or_create("beta").await.unwrap();
+        let listener = room.tx.subscribe();
+        drop(room);
+
+        let start = Instant::now();
+        let later = start + Duration::from_secs(ROOM_IDLE_TIMEOUT_SECS);
+        rooms.close_idle(start).await;
+        rooms.close_idle(later).await;
+        assert_eq!(rooms.list().await.len(), 2);
+
+        // The clock starts when the last client leaves
+        drop(listener);
+        rooms.close_idle(later).await;
+        rooms.close_idle(later + Duration::from_secs(1)).await;
+        assert_eq!(rooms.list().await.len(), 2);
+        rooms
+            .close_idle(later + Duration::from_secs(ROOM_IDLE_TIMEOUT_SECS))
+            .await;
+        let names: Vec<_> = rooms.list().await.into_iter().map(|r| r.name).collect();
+        assert_eq!(names, vec![persistent]);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_racing_opens_share_one_room_and_respect_the_cap() {
+        let rooms = Rooms::new(RoomConfig::default());
+        let joins: Vec<_> = (0..8)
+            .map(|_| {
+                let rooms = rooms.clone();
+                tokio::spawn(async move { rooms.get_or_create("race").await.unwrap() })
+            })
+            .collect();
+        let mut joined = Vec::new();
+        for join in joins {
+            joined.push(join.await.unwrap());
+        }
+        assert_eq!(rooms.all().await.len(), 1);
+        assert!(joined
+            .windows(2)
+            .all(|pair| Arc::ptr_eq(&pair[0], &pair[1])));
+        assert!(rooms
+            .open_configured("race", rooms.defaults())
+            .await
+            .is_err());
+
+        let opens: Vec<_> = (0..MAX_ROOMS + 4)
+            .map(|i| {
+                let rooms = rooms.clone();
+                tokio::spawn(async move {
+                    rooms
+                        .open_configured(&format!("room-{i}"), rooms.defaults())
+                        .await
+                        .is_ok()
+                })
+            })
+            .collect();
+        let mut opened = 0;
+        for open in opens {
+            opened += open.await.unwrap() as usize;
+        }
+        assert_eq!(opened, MAX_ROOMS - 1);
+        assert_eq!(rooms.all().await.len(), MAX_ROOMS);
+    }
+}
@@ -0,0 +1,118 @@
+//! The handheld resource scanner. Crews wire one up at an Electrical
+//! station; using it pings the resources around whoever holds it, fog or
+//! not. Only the holder sees what it found: where the close ones lie and
+//! which way to head for the ones further out.
+
+use crate::game::Game;
+use shared::*;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Wire up a scanner for a crew member at their mech's Electrical station,
+/// paid for out of the mech's stores
+pub fn issue(
+    game: &mut Game,
+    player_id: Uuid,
+    mech_id: Uuid,
+    cost: &HashMap<ResourceType, u32>,
+) -> GameResult<Vec<ServerMessage>> {
+    let player = game
+        .players
+        .get(&player_id)
+        .ok_or_else(|| GameError::player_not_found(player_id))?;
+    if player.inventory.contains(ItemType::Scanner) {
+        return Err(GameError::invalid_state("Already carrying a scanner"));
+    }
+    if player.inventory.is_full() {
+        return Err(GameError::invalid_state("No free slot for a scanner"));
+    }
+
+    let mech = game
+        .mechs
+        .get_mut(&mech_id)
+        .ok_or_else(|| GameError::mech_not_found(mech_id))?;
+    let stock = |resource_type: &ResourceType| {
+        mech.resource_inventory
+            .get(resource_type)
+            .copied()
+            .unwrap_or(0)
+    };
+    if let Some((&resource_type, &required)) = cost
+        .iter()
+        .find(|(resource_type, amount)| stock(resource_type) < **amount)
+    {
+        return Err(GameError::InsufficientResources {
+            resource_type,
+            required,
+            available: stock(&resource_type),
+        });
+    }
+    mech.try_spend(cost);
+    let mech_inventory = mech.inventory_message();
+
+    let player = game
+        .players
+        .get_mut(&player_id)
+        .ok_or_else(|| GameError::player_not_found(player_id))?;
+    player.inventory.add(ItemType::Scanner);
+    Ok(vec![mech_inventory, player.inventory_message()])
+}
+
+/// Ping from where the player is, inside a mech or out
+pub fn scan(game: &mut Game, player_id: Uuid) -> GameResult<ServerMessage> {
+    let match_time = game.match_time;
+    let player = game
+        .players
+        .get(&player_id)
+        .ok_or_else(|| GameError::player_not_found(player_id))?;
+    if player.scanner_ready_at > match_time {
+        return Err(GameError::AbilityOnCooldown {
+            ability: "Scanner",
+            remaining_seconds: player.scanner_ready_at - match_time,
+        });
+    }
+    let origin = match player.location {
+        PlayerLocation::OutsideWorld(pos) => pos,
+        PlayerLocation::InsideMech { mech_id, .. } => game
+            .mechs
+            .get(&mech_id)
+            .map(|mech| MechPositioning::mech_center(mech.position))
+            .ok_or_else(|| GameError::mech_not_found(mech_id))?,
+    };
+
+    let lying_around = game
+        .get_resources()
+        .into_iter()
+        .map(|resource| (resource.position.to_world_center(), resource.resource_type))
+        .chain(game.ground_items.values().filter_map(|item| {
+            item.item
+                .as_resource()
+                .map(|resource_type| (item.position, resource_type))
+        }));
+    let mut contacts = Vec::new();
+    let mut bearings = Vec::new();
+    for (position, resource_type) in lying_around {
+        let distance = position.distance_to(origin);
+        if distance <= SCANNER_RANGE * TILE_SIZE {
+            contacts.push((position, resource_type));
+        } else if distance <= SCANNER_HINT_RANGE * TILE_SIZE {
+            bearings.push((position.y - origin.y).atan2(position.x - origin.x));
+        }
+    }
+
+    if let Some(player) = game.players.get_mut(&player_id) {
+        player.scanner_ready_at = match_time + SCANNER_COOLDOWN;
+    }
+    log::debug!(
+        "Player {player_id} scanned, {} contacts and {} bearings",
+        contacts.len(),
+        bearings.len()
+    );
+    Ok(ServerMessage::ScannerPing {
+        player_id,
+        origin,
+        contacts,
+        bearings,
+        duration: SCANNER_PING_DURATION,
+    })
+}
@@ -0,0 +1,285 @@
+use serde::Serialize;
+use shared::*;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+/// Every connected client's outgoing queue, for the stats endpoint
+pub type SendQueues = Arc<RwLock<HashMap<Uuid, Arc<Mutex<ClientSendQueue>>>>>;
+
+/// How a queued message is treated when its client can't keep up
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SendPriority {
+    /// Never dropped
+    Critical,
+    /// Dropped oldest first once the queue is full
+    Normal,
+    /// Only the latest update for the entity is kept
+    Positional(Uuid),
+}
+
+impl SendPriority {
+    pub fn of(msg: &ServerMessage) -> Self {
+        match msg {
+            ServerMessage::JoinedGame { .. }
+            | ServerMessage::SpectatingGame { .. }
+            | ServerMessage::JoinRejected { .. }
+            | ServerMessage::PasswordRequired { .. }
+            | ServerMessage::VoiceChannel { .. }
+            | ServerMessage::PlayerDisconnected { .. }
+            | ServerMessage::GameState { .. }
+            | ServerMessage::StateChecksum { .. }
+            | ServerMessage::MechFloorData { .. }
+            | ServerMessage::ArenaMapData { .. }
+            | ServerMessage::DecalLayer { .. }
+            | ServerMessage::PlayerKilled { .. }
+            | ServerMessage::ServerShutdown { .. }
+            | ServerMessage::Error { .. } => SendPriority::Critical,
+            ServerMessage::MechMoved { mech_id, .. } => SendPriority::Positional(*mech_id),
+            ServerMessage::PlayerMoved { player_id, .. } => SendPriority::Positional(*player_id),
+            _ => SendPriority::Normal,
+        }
+    }
+}
+
+/// Queue pressure for one client
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SendQueueStats {
+    /// Messages waiting to be written to the socket
+    pub queued: usize,
+    pub peak_queued: usize,
+    pub sent: u64,
+    /// Updates replaced by a newer one, or by a full snapshot, before they
+    /// went out
+    pub coalesced: u64,
+    /// Normal messages thrown away because the queue was full
+    pub dropped: u64,
+    /// Broadcasts missed before they reached this queue at all
+    pub lagged: u64,
+}
+
+/// Bounded outgoing queue for one client connection. Everything goes out
+/// in the order it was queued, so the client applies events the way the
+/// server did; priority only decides what gives when the client falls
+/// behind. Critical messages are never dropped, positional updates are
+/// coalesced in place, and a full snapshot replaces every update queued
+/// ahead of it.
+pub struct ClientSendQueue {
+    /// How many droppable messages may wait at once
+    capacity: usize,
+    queue: VecDeque<(SendPriority, ServerMessage)>,
+    stats: SendQueueStats,
+}
+
+impl ClientSendQueue {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            queue: VecDeque::new(),
+            stats: SendQueueStats::default(),
+        }
+    }
+
+    pub fn push(&mut self, msg: ServerMessage) {
+        let priority = SendPriority::of(&msg);
+        match priority {
+            SendPriority::Critical => {
+                // A full snapshot already holds what every update queued
+                // ahead of it would change; sending them after it would
+                // roll the client back
+                if matches!(msg, ServerMessage::GameState { .. }) {
+                    let before = self.queue.len();
+                    self.queue
+                        .retain(|(priority, _)| *priority == SendPriority::Critical);
+                    self.stats.coalesced += (before - self.queue.len()) as u64;
+                }
+            }
+            SendPriority::Positional(_) => {
+                if let Some((_, queued)) = self
+                    .queue
+                    .iter_mut()
+                    .find(|(queued, _)| *queued == priority)
+                {
+                    *queued = msg;
+                    self.stats.coalesced += 1;
+                    return;
+                }
+            }
+            SendPriority::Normal => {}
+        }
+        if priority != SendPriority::Critical && self.droppable() >= self.capacity {
+            self.drop_oldest();
+        }
+        self.queue.push_back((priority, msg));
+        self.stats.peak_queued = self.stats.peak_queued.max(self.len());
+    }
+
+    /// Messages that may be dropped or coalesced
+    fn droppable(&self) -> usize {
+        self.queue
+            .iter()
+            .filter(|(priority, _)| *priority != SendPriority::Critical)
+            .count()
+    }
+
+    /// Prefer dropping stale normal traffic; positions only go when nothing
+    /// else droppable is queued
+    fn drop_oldest(&mut self) {
+        let victim = self
+            .queue
+            .iter()
+            .position(|(priority, _)| *priority == SendPriority::Normal)
+            .or_else(|| {
+                self.queue
+                    .iter()
+                    .position(|(priority, _)| *priority != SendPriority::Critical)
+            });
+        if let Some(victim) = victim {
+            self.queue.remove(victim);
+            self.stats.dropped += 1;
+        }
+    }
+
+    pub fn pop(&mut self) -> Option<ServerMessage> {
+        let (_, msg) = self.queue.pop_front()?;
+        self.stats.sent += 1;
+        Some(msg)
+    }
+
+    pub fn record_lagged(&mut self, missed: u64) {
+        self.stats.lagged += missed;
+    }
+
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    pub fn stats(&self) -> SendQueueStats {
+        SendQueueStats {
+            queued: self.len(),
+            ..self.stats.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn player_moved(player_id: Uuid, x: f32) -> ServerMessage {
+        ServerMessage::PlayerMoved {
+            player_id,
+            location: PlayerLocation::OutsideWorld(WorldPos::new(x, 0.0)),
+        }
+    }
+
+    fn effect_expired() -> ServerMessage {
+        ServerMessage::EffectExpired {
+            effect_id: Uuid::new_v4(),
+        }
+    }
+
+    #[test]
+    fn test_positional_updates_keep_only_the_latest() {
+        let mut queue = ClientSendQueue::new(8);
+        let player_id = Uuid::new_v4();
+        queue.push(player_moved(player_id, 1.0));
+        queue.push(effect_expired());
+        queue.push(player_moved(player_id, 2.0));
+
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.stats().coalesced, 1);
+        match queue.pop() {
+            Some(ServerMessage::PlayerMoved {
+                location: PlayerLocation::OutsideWorld(pos),
+                ..
+            }) => assert_eq!(pos.x, 2.0),
+            other => panic!("expected the latest move first, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_full_queue_drops_normal_but_never_critical() {
+        let mut queue = ClientSendQueue::new(2);
+        let player_id = Uuid::new_v4();
+        queue.push(player_moved(player_id, 1.0));
+        queue.push(effect_expired());
+        queue.push(effect_expired());
+        for _ in 0..3 {
+            queue.push(ServerMessage::PlayerDisconnected {
+                player_id: Uuid::new_v4(),
+            });
+        }
+
+        let stats = queue.stats();
+        assert_eq!(stats.dropped, 1);
+        assert_eq!(queue.len(), 5);
+
+        // Still in the order they were queued, less the oldest effect
+        assert!(matches!(
+            queue.pop(),
+            Some(ServerMessage::PlayerMoved { .. })
+        ));
+        assert!(matches!(
+            queue.pop(),
+            Some(ServerMessage::EffectExpired { .. })
+        ));
+        for _ in 0..3 {
+            assert!(matches!(
+                queue.pop(),
+                Some(ServerMessage::PlayerDisconnected { .. })
+            ));
+        }
+        assert!(queue.pop().is_none());
+        assert_eq!(queue.stats().sent, 5);
+    }
+
+    #[test]
+    fn test_snapshots_replace_the_updates_queued_before_them() {
+        let mut queue = ClientSendQueue::new(8);
+        let player_id = Uuid::new_v4();
+        queue.push(player_moved(player_id, 1.0));
+        queue.push(effect_expired());
+        queue.push(ServerMessage::PlayerDisconnected {
+            player_id: Uuid::new_v4(),
+        });
+        queue.push(crate::game::Game::new().get_full_state());
+        queue.push(effect_expired());
+
+        // The move and effect are already in the snapshot; the disconnect
+        // keeps its place ahead of it
+        assert_eq!(queue.stats().coalesced, 2);
+        assert!(matches!(
+            queue.pop(),
+            Some(ServerMessage::PlayerDisconnected { .. })
+        ));
+        assert!(matches!(queue.pop(), Some(ServerMessage::GameState { .. })));
+        assert!(matches!(
+            queue.pop(),
+            Some(ServerMessage::EffectExpired { .. })
+        ));
+        assert!(queue.pop().is_none());
+    }
+
+    #[test]
+    fn test_critical_messages_wait_their_turn() {
+        let mut queue = ClientSendQueue::new(8);
+        let player_id = Uuid::new_v4();
+        queue.push(effect_expired());
+        queue.push(ServerMessage::PlayerKilled {
+            player_id,
+            killer: None,
+            respawn_position: WorldPos::new(0.0, 0.0),
+        });
+
+        assert!(matches!(
+            queue.pop(),
+            Some(ServerMessage::EffectExpired { .. })
+        ));
+        assert!(matches!(
+            queue.pop(),
+            Some(ServerMessage::PlayerKilled { .. })
+        ));
+    }
+}
@@ -0,0 +1,109 @@
+//! Holding on to players whose connection dropped. Everyone who joins is
+//! handed a resume token; when their socket closes the player stays in the
+//! match for `grace` seconds, and a new socket opened with that token takes
+//! them back as they were. Players nobody comes back for are let go.
+
+use shared::*;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+pub struct SessionHolds {
+    /// Seconds a dropped player is kept for
+    pub grace: f32,
+    /// Resume token of every player who joined
+    tokens: HashMap<Uuid, Uuid>,
+    /// Dropped players and seconds left until they're let go
+    held: HashMap<Uuid, f32>,
+}
+
+impl Default for SessionHolds {
+    fn default() -> Self {
+        Self::new(SESSION_RESUME_GRACE_SECS)
+    }
+}
+
+impl SessionHolds {
+    pub fn new(grace: f32) -> Self {
+        Self {
+            grace,
+            tokens: HashMap::new(),
+            held: HashMap::new(),
+        }
+    }
+
+    /// The player's resume token, made on their first join
+    pub fn issue(&mut self, player_id: Uuid) -> Uuid {
+        if let Some((token, _)) = self.tokens.iter().find(|(_, id)| **id == player_id) {
+            return *token;
+        }
+        let token = Uuid::new_v4();
+        self.tokens.insert(token, player_id);
+        token
+    }
+
+    /// Keep a player whose connection dropped; false if they never joined,
+    /// so there's nothing to come back to
+    pub fn hold(&mut self, player_id: Uuid) -> bool {
+        if self.grace <= 0.0 || !self.tokens.values().any(|id| *id == player_id) {
+            return false;
+        }
+        self.held.insert(player_id, self.grace);
+        true
+    }
+
+    /// The held player `token` belongs to, no longer held
+    pub fn resume(&mut self, token: Uuid) -> Option<Uuid> {
+        let player_id = *self.tokens.get(&token)?;
+        self.held.remove(&player_id).map(|_| player_id)
+    }
+
+    pub fn is_held(&self, player_id: Uuid) -> bool {
+        self.held.contains_key(&player_id)
+    }
+
+    /// Age every hold by `delta_time`. Returns the players whose grace ran
+    /// out, whose tokens are forgotten with them.
+    pub fn tick(&mut self, delta_time: f32) -> Vec<Uuid> {
+        let mut expired = Vec::new();
+        self.held.retain(|player_id, left| {
+            *left -= delta_time;
+            if *left > 0.0 {
+                return true;
+            }
+            expired.push(*player_id);
+            false
+        });
+        self.tokens
+            .retain(|_, player_id| !expired.contains(player_id));
+        expired
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dropped_players_come_back_until_their_grace_runs_out() {
+        let mut sessions = SessionHolds::new(10.0);
+        let (player, stranger) = (Uuid::new_v4(), Uuid::new_v4());
+        let token = sessions.issue(player);
+        assert_eq!(sessions.issue(player), token);
+
+        // Only players who joined are held, and only held ones resume
+        assert!(!sessions.hold(stranger));
+        assert_eq!(sessions.resume(token), None);
+        assert!(sessions.hold(player));
+        assert!(sessions.is_held(player));
+        assert!(sessions.tick(6.0).is_empty());
+        assert_eq!(sessions.resume(token), Some(player));
+        assert!(!sessions.is_held(player));
+
+        // Dropping again restarts the grace, and running out forgets them
+        assert!(sessions.hold(player));
+        assert!(sessions.tick(6.0).is_empty());
+        assert_eq!(sessions.tick(6.0), vec![player]);
+        assert_eq!(sessions.resume(token), None);
+        assert!(!sessions.hold(player));
+    }
+}
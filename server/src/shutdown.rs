@@ -0,0 +1,145 @@
+use serde::Deserialize;
+use shared::*;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{watch, RwLock};
+use uuid::Uuid;
+
+use crate::game::Game;
+use crate::AppState;
+
+/// Why the server is going down and how long players get before it does
+#[derive(Debug, Clone, Deserialize)]
+pub struct ShutdownRequest {
+    pub reason: String,
+    pub seconds: u32,
+}
+
+/// Handle for starting a graceful shutdown, shared by the Ctrl-C handler and
+/// the admin endpoint. Only the first request counts.
+#[derive(Clone)]
+pub struct Shutdown {
+    requested: Arc<watch::Sender<Option<ShutdownRequest>>>,
+}
+
+impl Shutdown {
+    pub fn new() -> Self {
+        let (requested, _) = watch::channel(None);
+        Self {
+            requested: Arc::new(requested),
+        }
+    }
+
+    /// Start shutting down; false if a shutdown is already under way
+    pub fn request(&self, request: ShutdownRequest) -> bool {
+        self.requested.send_if_modified(|current| {
+            if current.is_some() {
+                return false;
+            }
+            *current = Some(request);
+            true
+        })
+    }
+
+    pub fn is_requested(&self) -> bool {
+        self.requested.borrow().is_some()
+    }
+
+    /// Resolves once someone asks for a shutdown
+    pub async fn requested(&self) -> ShutdownRequest {
+        let mut rx = self.requested.subscribe();
+        loop {
+            if let Some(request) = rx.borrow_and_update().clone() {
+                return request;
+            }
+            // We hold the sender, so the channel can't close under us
+            let _ = rx.changed().await;
+        }
+    }
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Warn everyone, stop taking new players, save a snapshot if asked and let
+/// the client send queues drain. Resolves once it's safe to exit.
+pub async fn drain(state: &AppState, request: ShutdownRequest, snapshot: Option<&Path>) {
+    log::warn!("Shutting down in {}s: {}", request.seconds, request.reason);
+    for room in state.rooms.all().await {
+        room.game.write().await.accepting_players = false;
+        let _ = room.tx.send((
+            Uuid::nil(),
+            ServerMessage::ServerShutdown {
+                reason: request.reason.clone(),
+                seconds: request.seconds,
+            },
+        ));
+    }
+
+    tokio::time::sleep(Duration::from_secs(request.seconds as u64)).await;
+
+    if let Some(path) = snapshot {
+        match write_snapshot(&state.game, path).await {
+            Ok(()) => log::info!("Wrote state snapshot to {}", path.display()),
+            Err(e) => log::error!("Failed to write state snapshot to {}: {e}", path.display()),
+        }
+    }
+
+    let deadline = Instant::now() + Duration::from_millis(SHUTDOWN_FLUSH_TIMEOUT_MS);
+    loop {
+        let queued: usize = state
+            .send_queues
+            .read()
+            .await
+            .values()
+            .map(|queue| queue.lock().unwrap().len())
+            .sum();
+        if queued == 0 {
+            break;
+        }
+        if Instant::now() >= deadline {
+            log::warn!("Giving up on {queued} unsent messages");
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(SHUTDOWN_FLUSH_POLL_MS)).await;
+    }
+}
+
+/// The full game state as the clients see it, as JSON
+async fn write_snapshot(game: &RwLock<Game>, path: &Path) -> anyhow::Result<()> {
+    let json = serde_json::to_string_pretty(&game.read().await.get_full_state())?;
+    tokio::fs::write(path, json).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_only_the_first_request_counts() {
+        let shutdown = Shutdown::new();
+        assert!(!shutdown.is_requested());
+
+        let waiter = tokio::spawn({
+            let shutdown = shutdown.clone();
+            async move { shutdown.requested().await }
+        });
+        assert!(shutdown.request(ShutdownRequest {
+            reason: "maintenance".to_string(),
+            seconds: 5,
+        }));
+        assert!(!shutdown.request(ShutdownRequest {
+            reason: "again".to_string(),
+            seconds: 0,
+        }));
+
+        let request = waiter.await.unwrap();
+        assert_eq!(request.reason, "maintenance");
+        assert!(shutdown.is_requested());
+    }
+}
@@ -0,0 +1,205 @@
+//! Buying tiers for individual stations at the Upgrade terminal. The
+//! operator steps through the mech's upgradeable stations with "Next
+//! Station" and buys the picked one's next tier with "Upgrade Station". The
+//! purchase is pressed through `station_effects` like any other button, so
+//! the price, the cooldown and the tier cap all come from one place. The
+//! terminal's mech upgrade buttons are priced here too, since their top
+//! level costs more than the rest.
+
+use crate::game::Game;
+use shared::*;
+use uuid::Uuid;
+
+/// Stations aboard that have tiers to buy, in floor then reading order
+fn upgradeable_stations(game: &Game, mech_id: Uuid) -> Vec<Uuid> {
+    let Some(mech) = game.mechs.get(&mech_id) else {
+        return Vec::new();
+    };
+    let mut stations: Vec<&StationInstance> = mech
+        .stations
+        .values()
+        .filter(|station| {
+            game.station_registry
+                .get_definition(station.station_type)
+                .is_some_and(|definition| definition.upgradeable())
+        })
+        .collect();
+    stations.sort_by_key(|station| (station.floor, station.position.y, station.position.x));
+    stations.into_iter().map(|station| station.id).collect()
+}
+
+/// Point the terminal at the next upgradeable station, wrapping around
+pub fn pick_next(game: &mut Game, mech_id: Uuid) -> GameResult<Uuid> {
+    let candidates = upgradeable_stations(game, mech_id);
+    let mech = game
+        .mechs
+        .get_mut(&mech_id)
+        .ok_or_else(|| GameError::mech_not_found(mech_id))?;
+    let next = match mech
+        .upgrade_target
+        .and_then(|current| candidates.iter().position(|id| *id == current))
+    {
+        Some(index) => candidates.get((index + 1) % candidates.len()),
+        None => candidates.first(),
+    }
+    .copied()
+    .ok_or_else(|| GameError::invalid_state("Nothing aboard can be upgraded"))?;
+    mech.upgrade_target = Some(next);
+    Ok(next)
+}
+
+/// The "Upgrade Station" button as the operator should see it: naming the
+/// picked station and its next tier, and priced for it
+pub fn describe_button(game: &Game, mech_id: Uuid, button: &mut StationButtonInfo) {
+    let picked = game.mechs.get(&mech_id).and_then(|mech| {
+        mech.upgrade_target
+            .and_then(|station_id| mech.stations.get(&station_id))
+    });
+    let Some(station) = picked else {
+        button.label = "Upgrade Station (pick one first)".to_string();
+        return;
+    };
+    let Some(definition) = game.station_registry.get_definition(station.station_type) else {
+        return;
+    };
+    match definition.upgrade_cost(station.upgrade_level) {
+        Some(cost) => {
+            button.label = format!(
+                "Upgrade {} to tier {}",
+                definition.short_name,
+                station.upgrade_level + 1
+            );
+            button.resource_cost = cost;
+        }
+        None => {
+            button.label = format!("{} is at the top tier", definition.short_name);
+            button.resource_cost.clear();
+        }
+    }
+}
+
+/// An "Upgrade <system>" button priced for the mech's next level of that
+/// system, which for the top level takes power cores too
+pub fn price_mech_upgrade(
+    game: &Game,
+    mech_id: Uuid,
+    upgrade_type: MechUpgradeType,
+    button: &mut StationButtonInfo,
+) {
+    let level = upgrade_type
+        .upgrade_type()
+        .zip(game.mechs.get(&mech_id))
+        .map(|(upgrade_type, mech)| mech.upgrades.level(upgrade_type));
+    if let Some(level) = level.filter(|level| *level < MAX_UPGRADE_LEVEL) {
+        button.resource_cost = with_top_tier_cost(
+            std::mem::take(&mut button.resource_cost),
+            level + 1,
+            MAX_UPGRADE_LEVEL,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::systems::station_effects::press;
+
+    #[test]
+    fn test_picking_cycles_and_tiers_cost_more_each_time() {
+        let mut game = Game::new();
+        game.match_time = 100.0;
+        let mech_id = *game.mechs.keys().next().unwrap();
+        let terminal_id = game.mechs[&mech_id]
+            .stations
+            .values()
+            .find(|station| station.station_type == StationType::Upgrade)
+            .unwrap()
+            .id;
+        let button = game
+            .station_registry
+            .get_definition(StationType::Upgrade)
+            .unwrap()
+            .button_definitions
+            .iter()
+            .find(|button| matches!(button.action, StationAction::UpgradeStation))
+            .unwrap()
+            .index;
+
+        // Nothing picked yet, so nothing to buy
+        assert!(press(&mut game, mech_id, terminal_id, button).is_err());
+
+        let candidates = upgradeable_stations(&game, mech_id);
+        assert!(!candidates.contains(&terminal_id));
+        let first = pick_next(&mut game, mech_id).unwrap();
+        assert_eq!(first, candidates[0]);
+        if candidates.len() > 1 {
+            assert_eq!(pick_next(&mut game, mech_id).unwrap(), candidates[1]);
+            game.mechs.get_mut(&mech_id).unwrap().upgrade_target = Some(first);
+        }
+
+        let station_type = game.mechs[&mech_id].stations[&first].station_type;
+        let definition = game
+            .station_registry
+            .get_definition(station_type)
+            .unwrap()
+            .clone();
+        let tier_two = definition.upgrade_cost(1).unwrap();
+        let tier_three = definition.upgrade_cost(2).unwrap();
+        game.mechs.get_mut(&mech_id).unwrap().resource_inventory = tier_two.clone();
+
+        let messages = press(&mut game, mech_id, terminal_id, button).unwrap();
+        assert_eq!(game.mechs[&mech_id].stations[&first].upgrade_level, 2);
+        assert!(messages.iter().any(|message| matches!(
+            message,
+            ServerMessage::MechInteriorUpdate { station_changes, .. }
+                if matches!(station_changes[..], [StationUpdate::Upgraded { new_level: 2, .. }])
+        )));
+        assert!(game.mechs[&mech_id]
+            .resource_inventory
+            .values()
+            .all(|count| *count == 0));
+
+        // The next tier costs more than what's left, and is priced on the button
+        game.match_time += 10.0;
+        assert!(press(&mut game, mech_id, terminal_id, button).is_err());
+        let mut info = game
+            .station_registry
+            .get_definition(StationType::Upgrade)
+            .unwrap()
+            .button_info()
+            .remove(button as usize);
+        describe_button(&game, mech_id, &mut info);
+        assert_eq!(info.resource_cost, tier_three);
+    }
+
+    #[test]
+    fn test_top_mech_upgrade_is_priced_in_power_cores() {
+        let mut game = Game::new();
+        let mech_id = *game.mechs.keys().next().unwrap();
+        let terminal_id = game.mechs[&mech_id]
+            .stations
+            .values()
+            .find(|station| station.station_type == StationType::Upgrade)
+            .unwrap()
+            .id;
+        let laser_cost = |game: &Game| {
+            let Some(ServerMessage::StationLayout { buttons, .. }) =
+                game.station_layout(mech_id, terminal_id)
+            else {
+                panic!("the terminal has a layout");
+            };
+            buttons
+                .into_iter()
+                .find(|button| button.label == "Upgrade Laser")
+                .unwrap()
+                .resource_cost
+        };
+
+        assert!(!laser_cost(&game).contains_key(&ResourceType::PowerCore));
+        game.mechs.get_mut(&mech_id).unwrap().upgrades.laser_level = MAX_UPGRADE_LEVEL - 1;
+        assert_eq!(
+            laser_cost(&game).get(&ResourceType::PowerCore),
+            Some(&TOP_TIER_CORE_COST)
+        );
+    }
+}
@@ -0,0 +1,168 @@
+use super::GameSystem;
+use crate::events::GameEvent;
+use crate::game::Game;
+use shared::*;
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+/// What a player has done toward their achievements this match
+#[derive(Default)]
+struct PlayerProgress {
+    deliveries: u32,
+    unlocked: HashSet<Achievement>,
+}
+
+/// Reads the gameplay event stream and tells players when they've earned
+/// an achievement. Each one unlocks at most once per player.
+pub struct AchievementSystem {
+    progress: HashMap<Uuid, PlayerProgress>,
+    first_blood_taken: bool,
+}
+
+impl AchievementSystem {
+    pub fn new() -> Self {
+        Self {
+            progress: HashMap::new(),
+            first_blood_taken: false,
+        }
+    }
+
+    /// Who an event earns what, whether or not they already have it
+    fn evaluate(&mut self, game: &Game, event: &GameEvent) -> Vec<(Uuid, Achievement)> {
+        match *event {
+            GameEvent::PlayerKilled {
+                killer: Some(killer),
+                ..
+            } if !self.first_blood_taken => {
+                self.first_blood_taken = true;
+                vec![(killer, Achievement::FirstBlood)]
+            }
+            GameEvent::PlayerKilled { .. } => Vec::new(),
+            GameEvent::ResourceDelivered { player_id, .. } => {
+                let progress = self.progress.entry(player_id).or_default();
+                progress.deliveries += 1;
+                if progress.deliveries >= ACHIEVEMENT_COURIER_DELIVERIES {
+                    vec![(player_id, Achievement::Courier)]
+                } else {
+                    Vec::new()
+                }
+            }
+            // Everyone aboard the mech that fired shares the credit
+            GameEvent::MechShieldBroken { attacker, .. } => game
+                .players
+                .values()
+                .filter(|p| {
+                    matches!(p.location, PlayerLocation::InsideMech { mech_id, .. } if mech_id == attacker)
+                })
+                .map(|p| (p.id, Achievement::ShieldBreaker))
+                .collect(),
+            GameEvent::StationSabotaged { player_id, .. } => {
+                vec![(player_id, Achievement::Saboteur)]
+            }
+            GameEvent::StationButtonPressed { .. } | GameEvent::InteriorImpact { .. } => {
+                Vec::new()
+            }
+        }
+    }
+}
+
+impl GameSystem for AchievementSystem {
+    fn update(&mut self, game: &mut Game, _delta_time: f32) -> Vec<ServerMessage> {
+        profiling::scope!("achievements");
+        let mut messages = Vec::new();
+
+        for event in game.events.drain() {
+            for (player_id, achievement) in self.evaluate(game, &event) {
+                if !game.players.contains_key(&player_id) {
+                    continue;
+                }
+                let progress = self.progress.entry(player_id).or_default();
+                if progress.unlocked.insert(achievement) {
+                    log::info!("Player {player_id} unlocked {}", achievement.title());
+                    messages.push(ServerMessage::AchievementUnlocked {
+                        player_id,
+                        achievement,
+                    });
+                }
+            }
+        }
+
+        self.progress.retain(|id, _| game.players.contains_key(id));
+        messages
+    }
+
+    fn name(&self) -> &'static str {
+        "achievements"
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unlocked(messages: &[ServerMessage]) -> Vec<(Uuid, Achievement)> {
+        messages
+            .iter()
+            .filter_map(|message| match message {
+                ServerMessage::AchievementUnlocked {
+                    player_id,
+                    achievement,
+                } => Some((*player_id, *achievement)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_first_blood_goes_to_the_first_killer_only() {
+        let mut game = Game::new();
+        let first = Uuid::new_v4();
+        let second = Uuid::new_v4();
+        game.add_player(first, "First".to_string(), None);
+        game.add_player(second, "Second".to_string(), None);
+        let mut achievements = AchievementSystem::new();
+
+        // Deaths nobody caused don't count
+        game.events.publish(GameEvent::PlayerKilled {
+            victim: second,
+            killer: None,
+        });
+        game.events.publish(GameEvent::PlayerKilled {
+            victim: second,
+            killer: Some(first),
+        });
+        game.events.publish(GameEvent::PlayerKilled {
+            victim: first,
+            killer: Some(second),
+        });
+        let messages = achievements.update(&mut game, 0.0);
+
+        assert_eq!(unlocked(&messages), vec![(first, Achievement::FirstBlood)]);
+    }
+
+    #[test]
+    fn test_courier_unlocks_once_at_the_delivery_count() {
+        let mut game = Game::new();
+        let player_id = Uuid::new_v4();
+        game.add_player(player_id, "Runner".to_string(), None);
+        let mech_id = *game.mechs.keys().next().unwrap();
+        let mut achievements = AchievementSystem::new();
+
+        let mut deliver = |game: &mut Game| {
+            game.deliver_resource(player_id, mech_id, ResourceType::ScrapMetal);
+            achievements.update(game, 0.0)
+        };
+        for _ in 1..ACHIEVEMENT_COURIER_DELIVERIES {
+            assert!(unlocked(&deliver(&mut game)).is_empty());
+        }
+        assert_eq!(
+            unlocked(&deliver(&mut game)),
+            vec![(player_id, Achievement::Courier)]
+        );
+        assert!(unlocked(&deliver(&mut game)).is_empty());
+    }
+}
@@ -1,32 +1,342 @@
-use crate::{systems::GameSystem, Game};
-use ai::{AICommand, AIManager, GameView};
+use crate::{rng::GameRng, systems::GameSystem, Game};
+use ai::{AICommand, AIManager, AITickMetrics, BotRegistry, GameView};
 use shared::*;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard, TryLockError};
+use tokio::sync::{mpsc, watch};
 use uuid::Uuid;
 
-/// AI System that manages AI players
+/// AI System that manages AI players. Under a tokio runtime the AIs think
+/// on a worker task of their own, at `tick_rate` times a second, from a
+/// snapshot of what their teams can see; the game tick only publishes
+/// snapshots and carries out the orders that come back. Without a runtime,
+/// as in tests, they think inline every tick instead. The game never waits
+/// on the worker: changes to the AIs wait for it if it's busy, and what it
+/// said and cost comes back with its orders.
 pub struct AISystem {
-    ai_manager: AIManager,
+    ai_manager: Arc<Mutex<AIManager>>,
+    /// Changes made while the worker had the manager, applied in order
+    /// before anything else touches it
+    edits: Arc<Mutex<Vec<AIEdit>>>,
     ai_players: HashMap<Uuid, AIPlayerInfo>,
+    bots: BotRegistry,
+    /// Decisions per second on the worker
+    tick_rate: f32,
+    /// Simulated seconds since the last snapshot went to the worker
+    since_snapshot: f32,
+    worker: Option<AIWorker>,
+    /// Each AI's latest movement or steering order, carried out every tick
+    /// until new orders replace it or it goes stale
+    standing_orders: HashMap<Uuid, (AICommand, u64)>,
+    /// Share of a game frame one AI update may take before it's flagged
+    budget_share: f32,
+    /// Multiple of the usual wait between snapshots while the server sheds load
+    load_slowdown: f32,
+    /// Timings from the latest orders taken
+    last_metrics: AITickMetrics,
+    /// Messages the AIs have sent each other, newest last
+    comm_log: VecDeque<ai::CommEvent>,
+    /// First message the manager hasn't handed over yet
+    comm_cursor: u64,
+    /// Updates over budget since the last warning, and when that was
+    over_budget: u32,
+    last_budget_warning: Option<f32>,
+    /// AIs swinging this tick; melee goes through the boarding system, so
+    /// the game carries these out once every system has run
+    swings: Vec<Uuid>,
+}
+
+/// What the AIs decide from, tagged with the tick it was taken on
+struct AISnapshot {
+    tick: u64,
+    views: Vec<GameView>,
+    delta_time: f32,
+}
+
+/// Everything the AIs decided from one snapshot
+struct AIOrders {
+    tick: u64,
+    commands: Vec<AICommand>,
+    metrics: AITickMetrics,
+    /// What they said to each other while deciding
+    comm_events: Vec<ai::CommEvent>,
+}
+
+type AIEdit = Box<dyn FnOnce(&mut AIManager) + Send>;
+
+struct AIWorker {
+    snapshots: watch::Sender<Option<Arc<AISnapshot>>>,
+    orders: mpsc::UnboundedReceiver<AIOrders>,
+    task: tokio::task::AbortHandle,
+}
+
+impl Drop for AIWorker {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Think about each new snapshot as it arrives, skipping any that came and
+/// went while the last one was still being worked on
+async fn run_worker(
+    ai_manager: Arc<Mutex<AIManager>>,
+    edits: Arc<Mutex<Vec<AIEdit>>>,
+    mut snapshots: watch::Receiver<Option<Arc<AISnapshot>>>,
+    orders: mpsc::UnboundedSender<AIOrders>,
+    mut comm_cursor: u64,
+) {
+    while snapshots.changed().await.is_ok() {
+        let Some(snapshot) = snapshots.borrow_and_update().clone() else {
+            continue;
+        };
+        let (ai_manager, edits) = (ai_manager.clone(), edits.clone());
+        let thinking = tokio::task::spawn_blocking(move || {
+            think(&mut lock(&ai_manager), &edits, &snapshot, comm_cursor)
+        });
+        let Ok(decided) = thinking.await else {
+            log::error!("AI worker panicked while deciding; stopping it");
+            return;
+        };
+        if let Some(last) = decided.comm_events.last() {
+            comm_cursor = last.sequence + 1;
+        }
+        if orders.send(decided).is_err() {
+            return;
+        }
+    }
+}
+
+/// Catch up on the game's changes, then decide from `snapshot`, passing on
+/// whatever the AIs said from `comm_cursor` on
+fn think(
+    ai_manager: &mut AIManager,
+    edits: &Mutex<Vec<AIEdit>>,
+    snapshot: &AISnapshot,
+    comm_cursor: u64,
+) -> AIOrders {
+    apply_edits(ai_manager, edits);
+    let commands = ai_manager.update(&snapshot.views, snapshot.delta_time);
+    AIOrders {
+        tick: snapshot.tick,
+        commands,
+        metrics: ai_manager.tick_metrics().clone(),
+        comm_events: ai_manager.comm_events_since(comm_cursor),
+    }
+}
+
+fn apply_edits(ai_manager: &mut AIManager, edits: &Mutex<Vec<AIEdit>>) {
+    for edit in std::mem::take(&mut *lock(edits)) {
+        edit(ai_manager);
+    }
+}
+
+/// The manager survives an AI panicking mid-update, so carry on with it
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
 }
 
 struct AIPlayerInfo {
     name: String,
     team: TeamId,
     mech_id: Option<Uuid>,
+    /// Covering for an away player rather than filling out the crew
+    stand_in: bool,
+    /// Scores tasks by weights that can be retuned
+    weighted: bool,
 }
 
 impl AISystem {
     pub fn new() -> Self {
         Self {
-            ai_manager: AIManager::new(Default::default()),
+            ai_manager: Arc::new(Mutex::new(AIManager::new(Default::default()))),
+            edits: Arc::new(Mutex::new(Vec::new())),
             ai_players: HashMap::new(),
+            bots: crate::bots::bot_registry(),
+            tick_rate: super::SystemConfig::default().ai_hz,
+            since_snapshot: 0.0,
+            worker: None,
+            standing_orders: HashMap::new(),
+            budget_share: AI_FRAME_BUDGET_SHARE,
+            load_slowdown: 1.0,
+            last_metrics: AITickMetrics::default(),
+            comm_log: VecDeque::new(),
+            comm_cursor: 0,
+            over_budget: 0,
+            last_budget_warning: None,
+            swings: Vec::new(),
+        }
+    }
+
+    /// How many times a second the AIs decide what to do when they have a
+    /// worker of their own
+    pub fn set_tick_rate(&mut self, ai_hz: f32) {
+        self.tick_rate = ai_hz.max(1.0);
+    }
+
+    /// Share of a game frame one AI update may take before it's logged
+    pub fn set_budget_share(&mut self, share: f32) {
+        self.budget_share = share.max(0.0);
+    }
+
+    /// Think less often while the server is behind. Never so seldom that
+    /// standing orders go stale between decisions.
+    pub fn set_load_slowdown(&mut self, slowdown: f32) {
+        self.load_slowdown = slowdown.max(1.0);
+    }
+
+    /// Seconds between snapshots sent to the worker
+    fn snapshot_interval(&self) -> f32 {
+        let interval = 1.0 / self.tick_rate;
+        interval.max((interval * self.load_slowdown).min(LOAD_AI_MAX_INTERVAL))
+    }
+
+    /// What the AIs' latest update cost, all of them together
+    pub fn tick_metrics(&self) -> &AITickMetrics {
+        &self.last_metrics
+    }
+
+    /// Note what an update cost, warning now and then while updates keep
+    /// running over budget. True if this one did.
+    fn check_budget(&mut self, metrics: &AITickMetrics, now: f32) -> bool {
+        let budget_ms = self.budget_share * FRAME_DELTA_SECONDS * 1000.0;
+        if metrics.update_ms <= budget_ms {
+            return false;
+        }
+        self.over_budget += 1;
+        let due = self
+            .last_budget_warning
+            .is_none_or(|last| now - last >= AI_BUDGET_WARN_INTERVAL);
+        if due {
+            let slowest = metrics
+                .slowest_ai
+                .and_then(|id| self.ai_players.get(&id))
+                .map_or("none", |info| info.name.as_str());
+            log::warn!(
+                "AI update took {:.1}ms of a {budget_ms:.1}ms budget ({} over since last warning); \
+                 {} AIs spent {:.1}ms deciding, slowest {slowest} at {:.1}ms",
+                metrics.update_ms,
+                self.over_budget,
+                metrics.ai_count,
+                metrics.total_decision_ms,
+                metrics.slowest_decision_ms,
+            );
+            self.over_budget = 0;
+            self.last_budget_warning = Some(now);
+        }
+        true
+    }
+
+    /// Start the worker the first time the system runs inside a runtime
+    fn ensure_worker(&mut self) -> Option<&mut AIWorker> {
+        if self.worker.is_none() {
+            let runtime = tokio::runtime::Handle::try_current().ok()?;
+            let (snapshots, snapshot_rx) = watch::channel(None);
+            let (order_tx, orders) = mpsc::unbounded_channel();
+            let task = runtime
+                .spawn(run_worker(
+                    self.ai_manager.clone(),
+                    self.edits.clone(),
+                    snapshot_rx,
+                    order_tx,
+                    self.comm_cursor,
+                ))
+                .abort_handle();
+            self.worker = Some(AIWorker {
+                snapshots,
+                orders,
+                task,
+            });
+        }
+        self.worker.as_mut()
+    }
+
+    /// Stream AI decisions to a rotating JSONL log for offline replay
+    pub fn enable_decision_log(&mut self, path: std::path::PathBuf) -> std::io::Result<()> {
+        lock(&self.ai_manager).enable_jsonl_logging(path)
+    }
+
+    /// The manager, caught up on the game's changes, unless the worker has
+    /// it right now
+    fn try_manager(&self) -> Option<MutexGuard<'_, AIManager>> {
+        let mut ai_manager = match self.ai_manager.try_lock() {
+            Ok(ai_manager) => ai_manager,
+            Err(TryLockError::Poisoned(poisoned)) => poisoned.into_inner(),
+            Err(TryLockError::WouldBlock) => return None,
+        };
+        apply_edits(&mut ai_manager, &self.edits);
+        Some(ai_manager)
+    }
+
+    /// Change the manager now if it's free, or else when the worker starts
+    /// its next pass
+    fn edit(&self, edit: impl FnOnce(&mut AIManager) + Send + 'static) {
+        match self.try_manager() {
+            Some(mut ai_manager) => edit(&mut ai_manager),
+            None => lock(&self.edits).push(Box::new(edit)),
         }
     }
 
-    /// Get debug info for a specific AI
+    /// Messages the AIs have sent each other from `sequence` on, as of the
+    /// latest orders taken
+    pub fn comm_events_since(&self, sequence: u64) -> Vec<ai::CommEvent> {
+        self.comm_log
+            .iter()
+            .filter(|event| event.sequence >= sequence)
+            .cloned()
+            .collect()
+    }
+
+    fn record_comm_events(&mut self, events: Vec<ai::CommEvent>) {
+        if let Some(last) = events.last() {
+            self.comm_cursor = last.sequence + 1;
+        }
+        self.comm_log.extend(events);
+        let excess = self.comm_log.len().saturating_sub(AI_COMM_HISTORY);
+        self.comm_log.drain(..excess);
+    }
+
+    /// Task weights for every AI in the room, including ones added later
+    pub fn set_default_weights(&mut self, weights: ai::UtilityWeights) {
+        self.edit(move |ai_manager| ai_manager.set_default_weights(weights));
+    }
+
+    /// Retune one AI's task weights; false if it doesn't use any
+    pub fn set_ai_weights(&mut self, ai_id: Uuid, weights: ai::UtilityWeights) -> bool {
+        if !self
+            .ai_players
+            .get(&ai_id)
+            .is_some_and(|info| info.weighted)
+        {
+            return false;
+        }
+        self.edit(move |ai_manager| {
+            ai_manager.set_weights(ai_id, weights);
+        });
+        true
+    }
+
+    /// Start an AI off wearing `hat`; false if there's no such AI
+    pub fn assign_hat(&mut self, ai_id: Uuid, hat: ai::Hat) -> bool {
+        if !self.ai_players.contains_key(&ai_id) {
+            return false;
+        }
+        self.edit(move |ai_manager| {
+            ai_manager.assign_hat(ai_id, hat);
+        });
+        true
+    }
+
+    /// The task weights an AI is using. None if it uses none, or if the
+    /// worker is busy with the manager.
+    pub fn ai_weights(&self, ai_id: Uuid) -> Option<ai::UtilityWeights> {
+        self.try_manager()?.weights(ai_id)
+    }
+
+    /// Get debug info for a specific AI, unless the worker is busy with it
     pub fn get_ai_debug_info(&self, ai_id: Uuid) -> Option<ai::AIDebugInfo> {
-        self.ai_manager.get_debug_info(ai_id)
+        self.try_manager()?.get_debug_info(ai_id)
     }
 
     /// Add an AI player to the manager
@@ -36,13 +346,8 @@ impl AISystem {
         personality: Option<ai::Personality>,
         red_count: usize,
         blue_count: usize,
+        rng: &mut GameRng,
     ) -> (Uuid, crate::game::Player) {
-        let personality = personality.unwrap_or(ai::Personality::Balanced);
-        let ai_id = self.ai_manager.add_ai(personality, difficulty);
-
-        // Create player name
-        let name = format!("AI_{}", personality.name_suffix());
-
         // Determine team (balance teams)
         let team = if red_count <= blue_count {
             TeamId::Red
@@ -50,14 +355,96 @@ impl AISystem {
             TeamId::Blue
         };
 
+        self.add_ai_player_to_team(difficulty, personality, team, rng)
+    }
+
+    /// Add an AI player to the manager on a specific team. Its id and
+    /// whatever it rolls come from the match's `rng`.
+    pub fn add_ai_player_to_team(
+        &mut self,
+        difficulty: f32,
+        personality: Option<ai::Personality>,
+        team: TeamId,
+        rng: &mut GameRng,
+    ) -> (Uuid, crate::game::Player) {
+        let personality = personality.unwrap_or(ai::Personality::Balanced);
+        let controller = AIManager::build_ai(rng.uuid(), rng.fork(), personality, difficulty);
+        let name = format!("AI_{}", personality.name_suffix());
+        self.add_controller_to_team(controller, name, team)
+    }
+
+    /// Add a bot from the registry on a specific team. None if no bot is
+    /// registered under that name.
+    pub fn add_bot_player_to_team(
+        &mut self,
+        bot: &str,
+        difficulty: f32,
+        team: TeamId,
+        rng: &mut GameRng,
+    ) -> Option<(Uuid, crate::game::Player)> {
+        let controller = self.bots.create(bot, rng.uuid(), difficulty)?;
+        Some(self.add_controller_to_team(controller, format!("Bot_{bot}"), team))
+    }
+
+    /// Add a bot built elsewhere, like a WASM module, on a specific team
+    pub fn add_controller_to_team(
+        &mut self,
+        controller: Box<dyn ai::AIController>,
+        name: String,
+        team: TeamId,
+    ) -> (Uuid, crate::game::Player) {
+        let ai_id = controller.id();
+        let weighted = controller.utility_weights().is_some();
+        self.edit(move |ai_manager| {
+            ai_manager.add_controller(controller);
+        });
+        let player = self.track_ai_player(ai_id, name, team, weighted);
+        (ai_id, player)
+    }
+
+    /// Add an AI to hold an away player's station until they're back. It
+    /// doesn't count towards autofill, so balancing the crew leaves it be.
+    pub fn add_stand_in(
+        &mut self,
+        difficulty: f32,
+        team: TeamId,
+        covering_for: &str,
+        rng: &mut GameRng,
+    ) -> (Uuid, crate::game::Player) {
+        let controller = AIManager::build_ai(
+            rng.uuid(),
+            rng.fork(),
+            ai::Personality::Balanced,
+            difficulty,
+        );
+        let name = format!("{covering_for} (stand-in)");
+        let (ai_id, player) = self.add_controller_to_team(controller, name, team);
+        if let Some(info) = self.ai_players.get_mut(&ai_id) {
+            info.stand_in = true;
+        }
+        (ai_id, player)
+    }
+
+    fn track_ai_player(
+        &mut self,
+        ai_id: Uuid,
+        name: String,
+        team: TeamId,
+        weighted: bool,
+    ) -> crate::game::Player {
         // Create player
         let player = crate::game::Player {
             id: ai_id,
             name: name.clone(),
             location: PlayerLocation::OutsideWorld(WorldPos { x: 0.0, y: 0.0 }),
             team,
-            carrying_resource: None,
+            inventory: Inventory::default(),
             operating_station: None,
+            held_tool: None,
+            health: PLAYER_MAX_HEALTH,
+            facing: Facing::default(),
+            dash_ready_at: 0.0,
+            scanner_ready_at: 0.0,
         };
 
         // Track AI info
@@ -67,146 +454,33 @@ impl AISystem {
                 name,
                 team,
                 mech_id: None,
+                stand_in: false,
+                weighted,
             },
         );
 
-        (ai_id, player)
+        player
     }
 
     /// Remove an AI player
     pub fn remove_ai_player(&mut self, ai_id: Uuid) -> bool {
-        self.ai_manager.remove_ai(ai_id);
+        self.edit(move |ai_manager| ai_manager.remove_ai(ai_id));
+        self.standing_orders.remove(&ai_id);
         self.ai_players.remove(&ai_id).is_some()
     }
 
-    /// Convert game state to AI view
-    fn create_game_view(&self, game: &Game, ai_id: Uuid) -> GameView {
-        let player = game.players.get(&ai_id).unwrap();
-
-        // Get all players as PlayerView
-        let players: Vec<ai::PlayerView> = game
-            .players
-            .iter()
-            .map(|(id, p)| {
-                // Get the operating station type if player is operating a station
-                let operating_station = if let Some(station_id) = p.operating_station {
-                    // Find the station in any mech
-                    game.mechs.values().find_map(|mech| {
-                        mech.stations
-                            .get(&station_id)
-                            .map(|station| station.station_type)
-                    })
-                } else {
-                    None
-                };
-
-                ai::PlayerView {
-                    id: *id,
-                    name: p.name.clone(),
-                    team: p.team,
-                    location: p.location,
-                    carrying_resource: p.carrying_resource,
-                    operating_station,
-                    is_self: *id == ai_id,
-                }
-            })
-            .collect();
-
-        // Get all mechs as MechView
-        let mechs: Vec<ai::MechView> = game
-            .mechs
-            .iter()
-            .map(|(id, m)| {
-                // Convert stations to StationView
-                let stations: Vec<ai::StationView> = m
-                    .stations
-                    .values()
-                    .map(|s| ai::StationView {
-                        id: s.id,
-                        station_type: s.station_type,
-                        operated_by: s.operated_by,
-                        position: s.position,
-                        floor: s.floor,
-                    })
-                    .collect();
-
-                ai::MechView {
-                    id: *id,
-                    team: m.team,
-                    position: m.world_position,
-                    health: m.health,
-                    shield: m.shield,
-                    velocity: m.velocity,
-                    stations,
-                    resource_inventory: m.resource_inventory.clone(),
-                }
-            })
-            .collect();
+    /// Check whether a player is controlled by this system
+    pub fn is_ai_player(&self, player_id: Uuid) -> bool {
+        self.ai_players.contains_key(&player_id)
+    }
 
-        // Get visible resources
-        let resources: Vec<ai::ResourceView> = game
-            .get_resources()
-            .iter()
-            .map(|r| ai::ResourceView {
-                id: r.id,
-                position: r.position.to_world_pos(),
-                resource_type: r.resource_type,
-            })
-            .collect();
-
-        // Get visible projectiles
-        let projectiles: Vec<ai::ProjectileView> = game
-            .projectiles
+    /// Get the AI players filling out a team's crew, stand-ins aside
+    pub fn ai_players_on_team(&self, team: TeamId) -> Vec<Uuid> {
+        self.ai_players
             .iter()
-            .map(|(id, p)| {
-                // Find the team of the owner mech
-                let owner_team = game
-                    .mechs
-                    .get(&p.owner_mech_id)
-                    .map(|m| m.team)
-                    .unwrap_or(TeamId::Red); // Default if mech not found
-
-                ai::ProjectileView {
-                    id: *id,
-                    position: p.position,
-                    velocity: p.velocity,
-                    owner_team,
-                }
-            })
-            .collect();
-
-        // Get team info
-        let team_members: Vec<Uuid> = game
-            .players
-            .values()
-            .filter(|p| p.team == player.team)
-            .map(|p| p.id)
-            .collect();
-
-        let team_info = ai::TeamInfo {
-            team_id: player.team,
-            player_count: team_members.len(),
-            mech_count: game
-                .mechs
-                .values()
-                .filter(|m| m.team == player.team)
-                .count(),
-            total_resources: game
-                .mechs
-                .values()
-                .find(|m| m.team == player.team)
-                .map(|m| m.resource_inventory.clone())
-                .unwrap_or_default(),
-        };
-
-        GameView {
-            tick: game.tick_count,
-            players,
-            mechs,
-            resources,
-            projectiles,
-            team_info,
-        }
+            .filter(|(_, info)| info.team == team && !info.stand_in)
+            .map(|(id, _)| *id)
+            .collect()
     }
 
     /// Convert AI commands to game messages
@@ -241,6 +515,9 @@ impl AISystem {
                     // Would need to handle engine control for mechs
                     log::debug!("AI {player_id} wants to control engine: {movement:?}");
                 }
+                AICommand::Dash { .. } | AICommand::Melee { .. } => {
+                    // Dashes and swings happen as orders are taken
+                }
             }
         }
 
@@ -248,114 +525,210 @@ impl AISystem {
     }
 }
 
-impl GameSystem for AISystem {
-    fn update(&mut self, game: &mut Game, delta_time: f32) -> Vec<ServerMessage> {
-        let mut all_messages = Vec::new();
+impl AISystem {
+    /// Swings the AIs asked for since this was last called
+    pub fn take_swings(&mut self) -> Vec<Uuid> {
+        std::mem::take(&mut self.swings)
+    }
 
-        // Check if we have any AI players
-        if self.ai_players.is_empty() {
-            return all_messages;
+    /// Take on a batch of orders: one-off actions happen now, and movement
+    /// replaces every AI's standing orders. Orders decided from a snapshot
+    /// too old to trust are thrown away whole.
+    fn take_orders(&mut self, game: &mut Game, orders: AIOrders) -> Vec<ServerMessage> {
+        let mut messages = Vec::new();
+        self.record_comm_events(orders.comm_events);
+        let age = game.tick_count.saturating_sub(orders.tick);
+        if age > AI_ORDER_MAX_AGE_TICKS {
+            log::debug!("Dropping AI orders {age} ticks old");
+            return messages;
         }
+        self.check_budget(&orders.metrics, game.match_time);
+        self.last_metrics = orders.metrics;
 
-        // Create a game view for the first AI (the AIManager expects a single view)
-        // In a more sophisticated implementation, we might create per-team views
-        let first_ai_id = self.ai_players.keys().next().copied();
-        if let Some(ai_id) = first_ai_id {
-            if game.players.contains_key(&ai_id) {
-                let game_view = self.create_game_view(game, ai_id);
+        self.standing_orders.clear();
+        for command in orders.commands {
+            match command {
+                AICommand::Move { player_id, .. } | AICommand::EngineControl { player_id, .. } => {
+                    self.standing_orders
+                        .insert(player_id, (command, orders.tick));
+                }
+                AICommand::PressButton {
+                    player_id,
+                    button_index,
+                } => press_button(game, player_id, button_index),
+                AICommand::Dash {
+                    player_id,
+                    direction,
+                } => match game.dash(player_id, direction) {
+                    Ok(dashed) => messages.extend(dashed),
+                    // Usually still recharging from the last one; walking will do
+                    Err(e) => log::trace!("AI {player_id} couldn't dash: {e}"),
+                },
+                AICommand::Melee { player_id } => self.swings.push(player_id),
+                _ => {}
+            }
+        }
+        messages
+    }
 
-                // Update all AIs and get commands
-                let commands = self.ai_manager.update(&game_view, delta_time);
+    /// Keep every AI moving or steering the way it last decided, until
+    /// its orders go stale
+    fn follow_standing_orders(&mut self, game: &mut Game, delta_time: f32) -> Vec<ServerMessage> {
+        let mut all_messages = Vec::new();
+        let now = game.tick_count;
+        self.standing_orders
+            .retain(|_, (_, tick)| now.saturating_sub(*tick) <= AI_ORDER_MAX_AGE_TICKS);
 
-                // Process commands into game actions
-                for command in commands {
-                    match command {
-                        AICommand::Move {
-                            player_id,
-                            movement,
-                        } => {
-                            if let Some(player) = game.players.get_mut(&player_id) {
-                                // Apply movement only if player is outside world
-                                if let PlayerLocation::OutsideWorld(mut position) = player.location
-                                {
-                                    // Update position based on movement
-                                    position.x += movement.0 * delta_time * 100.0;
-                                    position.y += movement.1 * delta_time * 100.0;
-
-                                    // Keep in bounds
-                                    position.x = position
-                                        .x
-                                        .max(0.0)
-                                        .min((ARENA_WIDTH_TILES as f32) * TILE_SIZE);
-                                    position.y = position
-                                        .y
-                                        .max(0.0)
-                                        .min((ARENA_HEIGHT_TILES as f32) * TILE_SIZE);
-
-                                    // Update player location
-                                    player.location = PlayerLocation::OutsideWorld(position);
-
-                                    all_messages.push(ServerMessage::PlayerMoved {
-                                        player_id,
-                                        location: player.location,
-                                    });
-                                }
-                            }
-                        }
-                        AICommand::PressButton {
+        for (command, _) in self.standing_orders.values() {
+            match *command {
+                AICommand::Move {
+                    player_id,
+                    movement,
+                } => {
+                    // Apply movement only if player is outside world
+                    let Some(PlayerLocation::OutsideWorld(position)) =
+                        game.players.get(&player_id).map(|p| p.location)
+                    else {
+                        continue;
+                    };
+
+                    // Same collision resolution as human players
+                    let delta = (
+                        movement.0 * delta_time * 100.0,
+                        movement.1 * delta_time * 100.0,
+                    );
+                    let position = crate::movement::resolve_outside(game, position, delta);
+
+                    if let Some(player) = game.players.get_mut(&player_id) {
+                        player.location = PlayerLocation::OutsideWorld(position);
+                        all_messages.extend(player.face_towards(movement));
+
+                        all_messages.push(ServerMessage::PlayerMoved {
                             player_id,
-                            button_index,
-                        } => {
-                            if let Some(player) = game.players.get(&player_id) {
-                                if let Some(station_id) = player.operating_station {
-                                    // Find which mech contains this station
-                                    let mech_station_info =
-                                        game.mechs.iter_mut().find_map(|(mech_id, mech)| {
-                                            mech.stations
-                                                .get(&station_id)
-                                                .map(|station| (*mech_id, station.station_type))
-                                        });
-
-                                    if let Some((mech_id, station_type)) = mech_station_info {
-                                        // Simulate button press based on station type
-                                        match station_type {
-                                            StationType::WeaponLaser => {
-                                                if let Some(_mech) = game.mechs.get(&mech_id) {
-                                                    // Fire laser weapon
-                                                    // For now, log the action
-                                                    log::debug!(
-                                                        "AI {player_id} pressed button {button_index} on laser station"
-                                                    );
-                                                }
-                                            }
-                                            StationType::WeaponProjectile => {
-                                                if let Some(_mech) = game.mechs.get(&mech_id) {
-                                                    // Fire projectile weapon
-                                                    // For now, log the action
-                                                    log::debug!("AI {player_id} pressed button {button_index} on projectile station");
-                                                }
-                                            }
-                                            StationType::Shield => {
-                                                // Activate shield
-                                                // For now, log the action
-                                                log::debug!(
-                                                    "AI {player_id} pressed button {button_index} on shield station"
-                                                );
-                                            }
-                                            _ => {}
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                        _ => {}
+                            location: player.location,
+                        });
                     }
                 }
+                AICommand::EngineControl {
+                    player_id,
+                    movement,
+                } => {
+                    // AI drivers ask for a direction; steer the throttle toward it
+                    let Some(station_id) = game
+                        .players
+                        .get(&player_id)
+                        .and_then(|p| p.operating_station)
+                    else {
+                        continue;
+                    };
+                    let driven = game.mechs.values_mut().find(|m| {
+                        m.stations.get(&station_id).is_some_and(|s| {
+                            matches!(s.station_type, StationType::Engine | StationType::Pilot)
+                        })
+                    });
+                    if let Some(mech) = driven {
+                        let input = mech.drive.input_toward(movement);
+                        mech.update_drive(|drive| drive.apply_input(input, delta_time));
+                    }
+                }
+                _ => {}
             }
         }
 
         all_messages
     }
+}
+
+fn press_button(game: &mut Game, player_id: Uuid, button_index: u8) {
+    let Some(station_id) = game
+        .players
+        .get(&player_id)
+        .and_then(|p| p.operating_station)
+    else {
+        return;
+    };
+    // Find which mech contains this station
+    let station_type = game.mechs.values().find_map(|mech| {
+        mech.stations
+            .get(&station_id)
+            .map(|station| station.station_type)
+    });
+
+    // Simulate button press based on station type
+    match station_type {
+        Some(StationType::WeaponLaser) => {
+            // Fire laser weapon
+            // For now, log the action
+            log::debug!("AI {player_id} pressed button {button_index} on laser station");
+        }
+        Some(StationType::WeaponProjectile) => {
+            // Fire projectile weapon
+            // For now, log the action
+            log::debug!("AI {player_id} pressed button {button_index} on projectile station");
+        }
+        Some(StationType::Shield) => {
+            // Activate shield
+            // For now, log the action
+            log::debug!("AI {player_id} pressed button {button_index} on shield station");
+        }
+        _ => {}
+    }
+}
+
+impl GameSystem for AISystem {
+    fn update(&mut self, game: &mut Game, delta_time: f32) -> Vec<ServerMessage> {
+        profiling::scope!("AISystem");
+
+        // Check if we have any AI players
+        if self.ai_players.is_empty() {
+            return Vec::new();
+        }
+
+        let snapshot_interval = self.snapshot_interval();
+        let pending = match self.ensure_worker() {
+            Some(worker) => {
+                let mut pending = Vec::new();
+                while let Ok(orders) = worker.orders.try_recv() {
+                    pending.push(orders);
+                }
+                pending
+            }
+            // Each team's AIs only get to know what their team can see
+            None => {
+                let snapshot = AISnapshot {
+                    tick: game.tick_count,
+                    views: game.ai_views(),
+                    delta_time,
+                };
+                let mut ai_manager = lock(&self.ai_manager);
+                vec![think(
+                    &mut ai_manager,
+                    &self.edits,
+                    &snapshot,
+                    self.comm_cursor,
+                )]
+            }
+        };
+        let mut messages = Vec::new();
+        for orders in pending {
+            messages.extend(self.take_orders(game, orders));
+        }
+
+        if let Some(worker) = &self.worker {
+            self.since_snapshot += delta_time;
+            if self.since_snapshot >= snapshot_interval {
+                let snapshot = AISnapshot {
+                    tick: game.tick_count,
+                    views: game.ai_views(),
+                    delta_time: std::mem::take(&mut self.since_snapshot),
+                };
+                worker.snapshots.send_replace(Some(Arc::new(snapshot)));
+            }
+        }
+
+        messages.extend(self.follow_standing_orders(game, delta_time));
+        messages
+    }
 
     fn name(&self) -> &'static str {
         "AISystem"
@@ -365,3 +738,186 @@ impl GameSystem for AISystem {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outside_position(game: &Game, player_id: Uuid) -> WorldPos {
+        match game.players[&player_id].location {
+            PlayerLocation::OutsideWorld(position) => position,
+            PlayerLocation::InsideMech { .. } => panic!("AI should be outside"),
+        }
+    }
+
+    #[test]
+    fn test_stale_orders_are_dropped_and_fresh_ones_stand_until_stale() {
+        let mut game = Game::new();
+        let ai_id = game.add_ai_player(0.5, None).unwrap();
+        let spawn = game.arena_map.player_spawn(game.players[&ai_id].team);
+        game.players.get_mut(&ai_id).unwrap().location = PlayerLocation::OutsideWorld(spawn);
+        game.tick_count = 100;
+
+        let mut system_manager = std::mem::take(&mut game.system_manager);
+        let ai_system = system_manager.get_system_mut::<AISystem>().unwrap();
+        let walk_east = |tick| AIOrders {
+            tick,
+            commands: vec![AICommand::Move {
+                player_id: ai_id,
+                movement: (1.0, 0.0),
+            }],
+            metrics: AITickMetrics::default(),
+            comm_events: Vec::new(),
+        };
+
+        ai_system.take_orders(&mut game, walk_east(100 - AI_ORDER_MAX_AGE_TICKS - 1));
+        ai_system.follow_standing_orders(&mut game, 0.1);
+        assert_eq!(outside_position(&game, ai_id), spawn);
+
+        // Fresh orders keep being carried out between decisions
+        ai_system.take_orders(&mut game, walk_east(100));
+        ai_system.follow_standing_orders(&mut game, 0.1);
+        game.tick_count += 1;
+        ai_system.follow_standing_orders(&mut game, 0.1);
+        let walked = outside_position(&game, ai_id);
+        assert!(walked.x > spawn.x);
+
+        // With no newer decision they lapse rather than walk on forever
+        game.tick_count = 100 + AI_ORDER_MAX_AGE_TICKS + 1;
+        ai_system.follow_standing_orders(&mut game, 0.1);
+        assert_eq!(outside_position(&game, ai_id), walked);
+        assert!(ai_system.standing_orders.is_empty());
+    }
+
+    #[test]
+    fn test_updates_over_budget_are_flagged_and_warned_about_sparingly() {
+        let mut game = Game::new();
+        game.add_ai_player(0.5, None).unwrap();
+        game.match_time = 100.0;
+
+        let mut system_manager = std::mem::take(&mut game.system_manager);
+        let ai_system = system_manager.get_system_mut::<AISystem>().unwrap();
+        ai_system.update(&mut game, 0.1);
+        let metrics = ai_system.tick_metrics().clone();
+        assert_eq!(metrics.ai_count, 1);
+        assert!(metrics.update_ms >= metrics.total_decision_ms);
+        assert_eq!(metrics.slowest_decision_ms, metrics.total_decision_ms);
+
+        let budget_ms = AI_FRAME_BUDGET_SHARE * FRAME_DELTA_SECONDS * 1000.0;
+        let slow = AITickMetrics {
+            update_ms: budget_ms * 2.0,
+            ..metrics
+        };
+        assert!(ai_system.check_budget(&slow, 100.0));
+        assert!(ai_system.check_budget(&slow, 101.0));
+        // The second one is held back until the next warning is due
+        assert_eq!(ai_system.over_budget, 1);
+        assert!(ai_system.check_budget(&slow, 100.0 + AI_BUDGET_WARN_INTERVAL));
+        assert_eq!(ai_system.over_budget, 0);
+
+        ai_system.set_budget_share(1.0);
+        assert!(!ai_system.check_budget(&slow, 200.0));
+    }
+
+    #[test]
+    fn test_shedding_load_slows_ai_decisions_within_order_lifetime() {
+        let mut ai_system = AISystem::new();
+        ai_system.set_tick_rate(20.0);
+        assert!((ai_system.snapshot_interval() - 0.05).abs() < 1e-6);
+
+        ai_system.set_load_slowdown(ServerLoad::Overloaded.ai_slowdown());
+        assert!(ai_system.snapshot_interval() > 0.05);
+        assert!(ai_system.snapshot_interval() <= LOAD_AI_MAX_INTERVAL);
+
+        // Already slower than the cap; left alone rather than sped up
+        ai_system.set_tick_rate(2.0);
+        assert!((ai_system.snapshot_interval() - 0.5).abs() < 1e-6);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_worker_decides_off_the_game_tick() {
+        let mut game = Game::new();
+        game.add_ai_player(0.5, None).unwrap();
+
+        let mut system_manager = std::mem::take(&mut game.system_manager);
+        let ai_system = system_manager.get_system_mut::<AISystem>().unwrap();
+        ai_system.set_tick_rate(30.0);
+
+        // The first update only starts the worker and hands it a snapshot
+        ai_system.update(&mut game, 1.0);
+        assert!(ai_system.worker.is_some());
+        let mut decided = false;
+        for _ in 0..200 {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            game.tick_count += 1;
+            ai_system.update(&mut game, 1.0 / 30.0);
+            if !ai_system.standing_orders.is_empty() {
+                decided = true;
+                break;
+            }
+        }
+        assert!(decided, "worker never sent back orders");
+    }
+
+    #[test]
+    fn test_changes_wait_for_a_busy_worker_instead_of_blocking() {
+        let mut ai_system = AISystem::new();
+        let mut rng = GameRng::new(3);
+        let worker = ai_system.ai_manager.clone();
+        let busy = lock(&worker);
+
+        let (ai_id, _) = ai_system.add_ai_player_to_team(0.9, None, TeamId::Red, &mut rng);
+        let retuned = ai::UtilityWeights {
+            stuck_penalty: 0.1,
+            ..Default::default()
+        };
+        assert!(ai_system.set_ai_weights(ai_id, retuned.clone()));
+        assert!(ai_system.assign_hat(ai_id, ai::Hat::Pilot));
+        assert_eq!(ai_system.ai_weights(ai_id), None);
+        assert_eq!(lock(&ai_system.edits).len(), 3);
+        assert_eq!(busy.weights(ai_id), None);
+
+        // Once the worker lets go, the changes land in the order they were made
+        drop(busy);
+        assert_eq!(ai_system.ai_weights(ai_id), Some(retuned));
+        assert!(lock(&ai_system.edits).is_empty());
+
+        let (simple, _) = ai_system.add_ai_player_to_team(0.2, None, TeamId::Red, &mut rng);
+        assert!(!ai_system.set_ai_weights(simple, Default::default()));
+    }
+
+    #[test]
+    fn test_ai_chatter_comes_back_with_the_orders() {
+        let mut game = Game::new();
+        let mut system_manager = std::mem::take(&mut game.system_manager);
+        let ai_system = system_manager.get_system_mut::<AISystem>().unwrap();
+        let event = |sequence| ai::CommEvent {
+            sequence,
+            tick: 0,
+            time: 0.0,
+            from: Uuid::nil(),
+            to: None,
+            from_captain: false,
+            message_type: "Status".to_string(),
+            priority: "Normal".to_string(),
+        };
+        let orders = AIOrders {
+            tick: 0,
+            commands: Vec::new(),
+            metrics: AITickMetrics::default(),
+            comm_events: (0..AI_COMM_HISTORY as u64 + 5).map(event).collect(),
+        };
+
+        ai_system.take_orders(&mut game, orders);
+        assert_eq!(ai_system.comm_cursor, AI_COMM_HISTORY as u64 + 5);
+        let kept = ai_system.comm_events_since(0);
+        assert_eq!(kept.len(), AI_COMM_HISTORY);
+        assert_eq!(kept[0].sequence, 5);
+        assert_eq!(
+            ai_system
+                .comm_events_since(AI_COMM_HISTORY as u64 + 4)
+                .len(),
+            1
+        );
+    }
+}
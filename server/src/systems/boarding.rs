@@ -0,0 +1,385 @@
+use super::GameSystem;
+use crate::events::GameEvent;
+use crate::game::Game;
+use crate::movement::tiles_in_reach;
+use shared::components::{SabotageAlarm, SabotageChannel, StatusEffectKind};
+use shared::*;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Fighting inside enemy mechs: close-quarters attacks between players and
+/// boarders sabotaging the stations they can reach, which sets off the
+/// mech's alarm for its crew
+pub struct BoardingSystem {
+    /// Sabotage in progress, keyed by the boarder channelling it
+    channels: HashMap<Uuid, SabotageChannel>,
+    /// Seconds until each player can swing again
+    melee_cooldowns: HashMap<Uuid, f32>,
+}
+
+impl BoardingSystem {
+    pub fn new() -> Self {
+        Self {
+            channels: HashMap::new(),
+            melee_cooldowns: HashMap::new(),
+        }
+    }
+
+    /// Hit the closest enemy in reach, or an enemy barricade when nobody is.
+    /// Knocking someone out of a sabotage interrupts it.
+    pub fn attack(&mut self, game: &mut Game, attacker_id: Uuid) -> GameResult<Vec<ServerMessage>> {
+        let attacker = game
+            .players
+            .get(&attacker_id)
+            .ok_or_else(|| GameError::player_not_found(attacker_id))?;
+        if attacker.operating_station.is_some() {
+            return Err(GameError::OperatingStation { id: attacker_id });
+        }
+        let stunned = game
+            .entity_storage
+            .status_effects
+            .get(&attacker_id)
+            .is_some_and(|effects| effects.has(StatusEffectKind::Stunned));
+        if stunned {
+            return Err(GameError::BlockedByStatusEffect {
+                id: attacker_id,
+                effect: StatusEffectKind::Stunned,
+            });
+        }
+        if let Some(remaining) = self.melee_cooldowns.get(&attacker_id) {
+            return Err(GameError::WeaponOnCooldown {
+                remaining_seconds: *remaining,
+            });
+        }
+
+        let target_id = game
+            .players
+            .values()
+            .filter(|p| p.team != attacker.team && !game.is_protected(p.id))
+            .filter_map(|p| Some((p.id, melee_distance(attacker.location, p.location)?)))
+            .filter(|(_, distance)| *distance <= MELEE_RANGE)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(id, _)| id);
+        let Some(target_id) = target_id else {
+            let (location, team) = (attacker.location, attacker.team);
+            let message = barricade_in_reach(game, location, team)
+                .and_then(|(mech_id, pos)| game.damage_barricade(mech_id, pos, MELEE_DAMAGE))
+                .ok_or(GameError::NoValidTarget)?;
+            self.melee_cooldowns.insert(attacker_id, MELEE_COOLDOWN);
+            let mut messages = vec![message];
+            messages.extend(game.drop_spawn_protection(attacker_id));
+            return Ok(messages);
+        };
+
+        self.melee_cooldowns.insert(attacker_id, MELEE_COOLDOWN);
+        // Swinging at someone gives up the swinger's own spawn protection
+        let dropped = game.drop_spawn_protection(attacker_id);
+        let Some(target) = game.players.get_mut(&target_id) else {
+            return Err(GameError::player_not_found(target_id));
+        };
+        target.health = target.health.saturating_sub(MELEE_DAMAGE);
+        let health = target.health;
+
+        let mut messages = vec![ServerMessage::PlayerAttacked {
+            attacker: attacker_id,
+            target: target_id,
+            damage: MELEE_DAMAGE,
+            health,
+        }];
+        messages.extend(dropped);
+        messages.extend(self.cancel_channel(target_id));
+        if health == 0 {
+            log::info!("Player {attacker_id} knocked out {target_id}");
+            messages.extend(game.knock_out(target_id, Some(attacker_id)));
+        }
+        Ok(messages)
+    }
+
+    /// Start wrecking an enemy station, or stop if we're already at it
+    pub fn sabotage(
+        &mut self,
+        game: &mut Game,
+        player_id: Uuid,
+        mech_id: Uuid,
+        station_id: Uuid,
+    ) -> Vec<ServerMessage> {
+        if let Some(message) = self.cancel_channel(player_id) {
+            return vec![message];
+        }
+        let already_down = game
+            .entity_storage
+            .status_effects
+            .get(&station_id)
+            .is_some_and(|effects| effects.has(StatusEffectKind::Sabotaged));
+        if already_down {
+            return Vec::new();
+        }
+
+        self.channels.insert(
+            player_id,
+            SabotageChannel {
+                mech_id,
+                station_id,
+                progress: 0.0,
+            },
+        );
+        let mut messages = vec![ServerMessage::SabotageProgress {
+            player_id,
+            station_id,
+            progress: Some(0.0),
+        }];
+        messages.extend(sound_alarm(game, mech_id, station_id, player_id));
+        messages
+    }
+
+    fn cancel_channel(&mut self, player_id: Uuid) -> Option<ServerMessage> {
+        self.channels
+            .remove(&player_id)
+            .map(|channel| ServerMessage::SabotageProgress {
+                player_id,
+                station_id: channel.station_id,
+                progress: None,
+            })
+    }
+
+    /// Advance sabotage, cancelling any whose boarder walked away
+    fn update_channels(&mut self, game: &mut Game, delta_time: f32) -> Vec<ServerMessage> {
+        let mut messages = Vec::new();
+        let mut finished = Vec::new();
+
+        for (player_id, channel) in self.channels.iter_mut() {
+            let in_position = game.players.get(player_id).is_some_and(|player| {
+                let PlayerLocation::InsideMech { mech_id, pos } = player.location else {
+                    return false;
+                };
+                mech_id == channel.mech_id
+                    && game
+                        .mechs
+                        .get(&mech_id)
+                        .and_then(|mech| mech.interior.floors.get(pos.floor as usize))
+                        .is_some_and(|floor_map| {
+                            tiles_in_reach(pos.tile_pos)
+                                .into_iter()
+                                .any(|tile| floor_map.get_station_at(tile) == Some(channel.station_id))
+                        })
+            });
+            if !in_position {
+                finished.push((*player_id, false));
+                continue;
+            }
+            // Keeps sounding for as long as anyone's at it
+            messages.extend(sound_alarm(
+                game,
+                channel.mech_id,
+                channel.station_id,
+                *player_id,
+            ));
+
+            let previous_step = (channel.progress * 10.0) as u32;
+            channel.progress += delta_time / SABOTAGE_CHANNEL_TIME;
+            if channel.progress >= 1.0 {
+                finished.push((*player_id, true));
+            } else if (channel.progress * 10.0) as u32 != previous_step {
+                // Report in 10% steps rather than every tick
+                messages.push(ServerMessage::SabotageProgress {
+                    player_id: *player_id,
+                    station_id: channel.station_id,
+                    progress: Some(channel.progress),
+                });
+            }
+        }
+
+        for (player_id, completed) in finished {
+            let Some(channel) = self.channels.remove(&player_id) else {
+                continue;
+            };
+            messages.push(ServerMessage::SabotageProgress {
+                player_id,
+                station_id: channel.station_id,
+                progress: None,
+            });
+            if completed {
+                messages.extend(self.disable_station(game, player_id, channel));
+            }
+        }
+
+        messages
+    }
+
+    /// Take a sabotaged station offline and throw out whoever was crewing it
+    fn disable_station(
+        &mut self,
+        game: &mut Game,
+        saboteur: Uuid,
+        channel: SabotageChannel,
+    ) -> Vec<ServerMessage> {
+        log::info!(
+            "Station {} on mech {} was sabotaged",
+            channel.station_id,
+            channel.mech_id
+        );
+        game.events.publish(GameEvent::StationSabotaged {
+            player_id: saboteur,
+            mech_id: channel.mech_id,
+            station_id: channel.station_id,
+        });
+        let mut messages = vec![game.apply_status_effect(
+            channel.station_id,
+            StatusEffectKind::Sabotaged,
+            SABOTAGE_DISABLE_DURATION,
+            1.0,
+        )];
+
+        let operator = game
+            .mechs
+            .get_mut(&channel.mech_id)
+            .and_then(|mech| mech.stations.get_mut(&channel.station_id))
+            .and_then(|station| station.operated_by.take());
+        if let Some(player) = operator.and_then(|id| game.players.get_mut(&id)) {
+            player.operating_station = None;
+            messages.push(ServerMessage::PlayerExitedStation {
+                player_id: player.id,
+                station_id: channel.station_id,
+            });
+        }
+        messages
+    }
+}
+
+/// Set off a mech's alarm for boarders at one of its stations, or keep it
+/// going. The crew only hear about it when it starts or moves to another
+/// station or boarder.
+fn sound_alarm(
+    game: &mut Game,
+    mech_id: Uuid,
+    station_id: Uuid,
+    intruder: Uuid,
+) -> Option<ServerMessage> {
+    let mech = game.mechs.get_mut(&mech_id)?;
+    let alarm = SabotageAlarm {
+        station_id,
+        intruder,
+        remaining: SABOTAGE_ALARM_DURATION,
+    };
+    let unchanged = mech
+        .alarm
+        .is_some_and(|old| old.station_id == station_id && old.intruder == intruder);
+    mech.alarm = Some(alarm);
+    if unchanged {
+        return None;
+    }
+    log::info!("Alarm on mech {mech_id}: boarder {intruder} at station {station_id}");
+    Some(ServerMessage::MechAlarm {
+        mech_id,
+        alarm: Some(alarm),
+    })
+}
+
+/// Run down alarms nobody's kept going
+fn quiet_alarms(game: &mut Game, delta_time: f32) -> Vec<ServerMessage> {
+    let mut messages = Vec::new();
+    for mech in game.mechs.values_mut() {
+        let Some(alarm) = mech.alarm.as_mut() else {
+            continue;
+        };
+        alarm.remaining -= delta_time;
+        if alarm.remaining <= 0.0 {
+            mech.alarm = None;
+            messages.push(ServerMessage::MechAlarm {
+                mech_id: mech.id,
+                alarm: None,
+            });
+        }
+    }
+    messages
+}
+
+/// The closest enemy barricade a player of `team` standing at `location`
+/// can swing at
+fn barricade_in_reach(
+    game: &Game,
+    location: PlayerLocation,
+    team: TeamId,
+) -> Option<(Uuid, MechInteriorPos)> {
+    let PlayerLocation::InsideMech { mech_id, pos } = location else {
+        return None;
+    };
+    let floor_map = game
+        .mechs
+        .get(&mech_id)?
+        .interior
+        .floors
+        .get(pos.floor as usize)?;
+    floor_map
+        .barricades
+        .iter()
+        .filter(|(_, barricade)| barricade.team != team)
+        .map(|(tile, _)| (*tile, pos.tile_pos.distance_to(*tile)))
+        .filter(|(_, distance)| *distance <= MELEE_RANGE)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(tile, _)| (mech_id, MechInteriorPos::new(pos.floor, tile)))
+}
+
+/// Tiles between two players who can reach each other: both out in the world,
+/// or on the same floor of the same mech
+fn melee_distance(a: PlayerLocation, b: PlayerLocation) -> Option<f32> {
+    match (a, b) {
+        (PlayerLocation::OutsideWorld(a), PlayerLocation::OutsideWorld(b)) => {
+            Some(a.distance_to(b) / TILE_SIZE)
+        }
+        (
+            PlayerLocation::InsideMech { mech_id, pos: a },
+            PlayerLocation::InsideMech {
+                mech_id: other_mech,
+                pos: b,
+            },
+        ) if mech_id == other_mech && a.floor == b.floor => Some(a.tile_pos.distance_to(b.tile_pos)),
+        _ => None,
+    }
+}
+
+impl GameSystem for BoardingSystem {
+    fn update(&mut self, game: &mut Game, delta_time: f32) -> Vec<ServerMessage> {
+        profiling::scope!("boarding");
+        for remaining in self.melee_cooldowns.values_mut() {
+            *remaining -= delta_time;
+        }
+        self.melee_cooldowns.retain(|_, remaining| *remaining > 0.0);
+
+        let mut messages = quiet_alarms(game, delta_time);
+        messages.extend(self.update_channels(game, delta_time));
+        messages
+    }
+
+    fn name(&self) -> &'static str {
+        "boarding"
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_melee_needs_a_shared_floor() {
+        let mech_id = Uuid::new_v4();
+        let inside = |floor, x| PlayerLocation::InsideMech {
+            mech_id,
+            pos: MechInteriorPos::new(floor, TilePos::new(x, 4)),
+        };
+
+        assert_eq!(melee_distance(inside(0, 3), inside(0, 4)), Some(1.0));
+        assert_eq!(melee_distance(inside(0, 3), inside(1, 3)), None);
+        assert_eq!(
+            melee_distance(
+                inside(0, 3),
+                PlayerLocation::OutsideWorld(WorldPos::new(0.0, 0.0))
+            ),
+            None
+        );
+    }
+}
@@ -0,0 +1,219 @@
+use super::GameSystem;
+use crate::game::Game;
+use shared::*;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Picks what spectators watch. Every mech is scored on recent damage, how
+/// close the nearest enemy mech is and how low its health has fallen; the
+/// camera cuts to the best one, but only after holding on the current mech
+/// for `CAMERA_DIRECTOR_MIN_HOLD` and only for something clearly better, so
+/// the picture doesn't flicker between two close calls.
+pub struct CameraDirectorSystem {
+    evaluate_timer: f32,
+    /// Decaying sum of damage each mech has taken
+    heat: HashMap<Uuid, f32>,
+    last_health: HashMap<Uuid, u32>,
+    focus: Option<Focus>,
+}
+
+struct Focus {
+    mech_id: Uuid,
+    reason: DirectorFocus,
+    held: f32,
+}
+
+impl CameraDirectorSystem {
+    pub fn new() -> Self {
+        Self {
+            evaluate_timer: 0.0,
+            heat: HashMap::new(),
+            last_health: HashMap::new(),
+            focus: None,
+        }
+    }
+
+    /// The current pick, for spectators who just arrived
+    pub fn suggestion(&self) -> Option<ServerMessage> {
+        self.focus
+            .as_ref()
+            .map(|focus| ServerMessage::CameraSuggestion {
+                target: focus.mech_id,
+                reason: focus.reason,
+            })
+    }
+
+    /// Warm up on damage since the last tick, read from mech health so every
+    /// source counts, and cool off over `CAMERA_DIRECTOR_HEAT_WINDOW`
+    fn sample_damage(&mut self, game: &Game, delta_time: f32) {
+        let decay = (-delta_time / CAMERA_DIRECTOR_HEAT_WINDOW).exp();
+        for heat in self.heat.values_mut() {
+            *heat *= decay;
+        }
+        for mech in game.mechs.values() {
+            if let Some(previous) = self.last_health.insert(mech.id, mech.health) {
+                let damage = previous.saturating_sub(mech.health);
+                if damage > 0 {
+                    *self.heat.entry(mech.id).or_default() += damage as f32;
+                }
+            }
+        }
+        self.last_health.retain(|id, _| game.mechs.contains_key(id));
+        self.heat.retain(|id, _| game.mechs.contains_key(id));
+    }
+
+    /// How worth watching a mech is, and the biggest reason why
+    fn score(&self, game: &Game, mech_id: Uuid) -> Option<(f32, DirectorFocus)> {
+        let mech = game.mechs.get(&mech_id).filter(|mech| mech.health > 0)?;
+        let center = MechPositioning::mech_center(mech.position);
+
+        let damage =
+            self.heat.get(&mech_id).copied().unwrap_or(0.0) * CAMERA_DIRECTOR_DAMAGE_WEIGHT;
+
+        let range = CAMERA_DIRECTOR_PROXIMITY_RANGE * TILE_SIZE;
+        let nearest_enemy = game
+            .mechs
+            .values()
+            .filter(|other| other.team != mech.team && other.health > 0)
+            .map(|other| MechPositioning::mech_center(other.position).distance_to(center))
+            .fold(f32::INFINITY, f32::min);
+        let closing = (1.0 - nearest_enemy / range).max(0.0) * CAMERA_DIRECTOR_PROXIMITY_WEIGHT;
+
+        let health = mech.health as f32 / mech.max_health.max(1) as f32;
+        let low_health = if health < CAMERA_DIRECTOR_LOW_HEALTH {
+            (1.0 - health) * CAMERA_DIRECTOR_LOW_HEALTH_WEIGHT
+        } else {
+            0.0
+        };
+
+        let reason = [
+            (damage, DirectorFocus::UnderFire),
+            (closing, DirectorFocus::Closing),
+            (low_health, DirectorFocus::LowHealth),
+        ]
+        .into_iter()
+        .filter(|(score, _)| *score > 0.0)
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+        .map_or(DirectorFocus::Idle, |(_, reason)| reason);
+        Some((damage + closing + low_health, reason))
+    }
+
+    /// Cut to the most interesting mech if it's worth leaving the current one
+    fn evaluate(&mut self, game: &Game) -> Option<ServerMessage> {
+        let best = game
+            .mechs
+            .keys()
+            .filter_map(|id| {
+                self.score(game, *id)
+                    .map(|(score, reason)| (*id, score, reason))
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1).then(b.0.cmp(&a.0)))?;
+
+        if let Some(focus) = self.focus.as_mut() {
+            if focus.mech_id == best.0 {
+                // Keep watching, but say so if the story changed
+                if focus.reason == best.2 {
+                    return None;
+                }
+                focus.reason = best.2;
+                return self.suggestion();
+            }
+        }
+
+        // Stay put unless the mech we were on is gone, or we've given it
+        // long enough and there's something clearly better
+        let stay = self.focus.as_ref().is_some_and(|focus| {
+            self.score(game, focus.mech_id).is_some_and(|(score, _)| {
+                focus.held < CAMERA_DIRECTOR_MIN_HOLD
+                    || best.1 <= score * CAMERA_DIRECTOR_SWITCH_MARGIN
+            })
+        });
+        if stay {
+            return None;
+        }
+
+        self.focus = Some(Focus {
+            mech_id: best.0,
+            reason: best.2,
+            held: 0.0,
+        });
+        self.suggestion()
+    }
+}
+
+impl GameSystem for CameraDirectorSystem {
+    fn update(&mut self, game: &mut Game, delta_time: f32) -> Vec<ServerMessage> {
+        profiling::scope!("camera_director");
+        self.sample_damage(game, delta_time);
+        if let Some(focus) = self.focus.as_mut() {
+            focus.held += delta_time;
+        }
+
+        self.evaluate_timer += delta_time;
+        // Nobody's watching, so there's nobody to tell
+        if self.evaluate_timer < CAMERA_DIRECTOR_INTERVAL || game.spectators.is_empty() {
+            return Vec::new();
+        }
+        self.evaluate_timer = 0.0;
+        self.evaluate(game).into_iter().collect()
+    }
+
+    fn name(&self) -> &'static str {
+        "camera_director"
+    }
+
+    fn is_cosmetic(&self) -> bool {
+        true
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn suggested(messages: &[ServerMessage]) -> Option<(Uuid, DirectorFocus)> {
+        messages.iter().find_map(|message| match message {
+            ServerMessage::CameraSuggestion { target, reason } => Some((*target, *reason)),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn test_camera_cuts_to_damage_but_holds_before_cutting_again() {
+        let mut game = Game::new();
+        let mut director = CameraDirectorSystem::new();
+        let (a, b) = {
+            let mut ids = game.mechs.keys();
+            (*ids.next().unwrap(), *ids.next().unwrap())
+        };
+
+        // Nothing to say until someone is watching
+        assert!(director
+            .update(&mut game, CAMERA_DIRECTOR_INTERVAL)
+            .is_empty());
+        // Far enough apart that only damage matters
+        let far = game.mechs[&a].position.offset(MECH_SIZE_TILES * 6, 0);
+        game.mechs.get_mut(&b).unwrap().position = far;
+
+        game.spectators.insert(Uuid::new_v4());
+        let (opening, _) =
+            suggested(&director.update(&mut game, CAMERA_DIRECTOR_INTERVAL)).unwrap();
+        // Cut away from whichever mech the camera opened on
+        let (first, second) = if opening == a { (b, a) } else { (a, b) };
+
+        game.mechs.get_mut(&first).unwrap().health -= 20;
+        let cut = suggested(&director.update(&mut game, CAMERA_DIRECTOR_MIN_HOLD));
+        assert_eq!(cut, Some((first, DirectorFocus::UnderFire)));
+
+        // A bigger fight elsewhere has to wait out the hold
+        game.mechs.get_mut(&second).unwrap().health -= 60;
+        let messages = director.update(&mut game, CAMERA_DIRECTOR_INTERVAL);
+        assert_ne!(suggested(&messages).map(|(id, _)| id), Some(second));
+        let messages = director.update(&mut game, CAMERA_DIRECTOR_MIN_HOLD);
+        assert_eq!(suggested(&messages).map(|(id, _)| id), Some(second));
+    }
+}
@@ -62,7 +62,7 @@ impl CollisionSystem {
                             RUN_OVER_MIN_VELOCITY,
                         ) {
                             info!("Player {player_id} was run over by mech {mech_id}");
-                            killed_players.push(*player_id);
+                            killed_players.push((*player_id, *mech_id, player_pos));
                             break; // Player is dead, no need to check other mechs
                         } else {
                             // Push player away from mech
@@ -76,36 +76,26 @@ impl CollisionSystem {
             }
         }
 
-        // Handle killed players
-        for player_id in killed_players {
-            if let Some(player) = game.players.get(&player_id) {
-                let spawn_pos = match player.team {
-                    TeamId::Red => WorldPos::new(
-                        RED_PLAYER_SPAWN.0 * TILE_SIZE,
-                        RED_PLAYER_SPAWN.1 * TILE_SIZE,
-                    ),
-                    TeamId::Blue => WorldPos::new(
-                        BLUE_PLAYER_SPAWN.0 * TILE_SIZE,
-                        BLUE_PLAYER_SPAWN.1 * TILE_SIZE,
-                    ),
-                };
-
-                messages.push(ServerMessage::PlayerKilled {
-                    player_id,
-                    killer: None, // Killed by mech
-                    respawn_position: spawn_pos,
-                });
-
-                // Reset player state
-                if let Some(player_mut) = game.players.get_mut(&player_id) {
-                    player_mut.location = PlayerLocation::OutsideWorld(spawn_pos);
-                    player_mut.carrying_resource = None;
-                    player_mut.operating_station = None;
-                }
-            }
+        // Run-over players are knocked out, credited to whoever was driving,
+        // and leave a mess behind
+        for (player_id, mech_id, position) in killed_players {
+            let pilot = game.mechs.get(&mech_id).and_then(|mech| {
+                mech.stations
+                    .values()
+                    .find(|station| station.station_type == StationType::Pilot)
+                    .and_then(|station| station.operated_by)
+            });
+            messages.push(ServerMessage::PlayerRunOver {
+                player_id,
+                mech_id,
+                position,
+            });
+            messages.extend(game.knock_out(player_id, pilot));
+            messages.push(game.add_decal(position, DecalKind::Gore));
         }
 
         // Apply player pushes (non-lethal collisions)
+        let (world_width, world_height) = game.arena_map.world_size();
         for (player_id, push_x, push_y) in player_pushes {
             if let Some(player) = game.players.get_mut(&player_id) {
                 if let PlayerLocation::OutsideWorld(ref mut pos) = player.location {
@@ -113,8 +103,8 @@ impl CollisionSystem {
                     pos.y += push_y;
 
                     // Keep within world bounds
-                    pos.x = pos.x.max(0.0).min((ARENA_WIDTH_TILES as f32) * TILE_SIZE);
-                    pos.y = pos.y.max(0.0).min((ARENA_HEIGHT_TILES as f32) * TILE_SIZE);
+                    pos.x = pos.x.max(0.0).min(world_width);
+                    pos.y = pos.y.max(0.0).min(world_height);
 
                     messages.push(ServerMessage::PlayerMoved {
                         player_id,
@@ -154,6 +144,7 @@ impl CollisionSystem {
         }
 
         // Apply mech separations
+        let (world_width, world_height) = game.arena_map.world_size();
         for (mech_id, sep_x, sep_y) in separations {
             if let Some(mech) = game.mechs.get_mut(&mech_id) {
                 mech.world_position.x += sep_x;
@@ -164,12 +155,12 @@ impl CollisionSystem {
                     .world_position
                     .x
                     .max(0.0)
-                    .min((ARENA_WIDTH_TILES as f32 - MECH_SIZE_TILES as f32) * TILE_SIZE);
+                    .min(world_width - MECH_SIZE_TILES as f32 * TILE_SIZE);
                 mech.world_position.y = mech
                     .world_position
                     .y
                     .max(0.0)
-                    .min((ARENA_HEIGHT_TILES as f32 - MECH_SIZE_TILES as f32) * TILE_SIZE);
+                    .min(world_height - MECH_SIZE_TILES as f32 * TILE_SIZE);
 
                 // Update tile position
                 let new_tile_pos = mech.world_position.to_tile_pos();
@@ -181,6 +172,7 @@ impl CollisionSystem {
                     mech_id,
                     position: mech.position,
                     world_position: mech.world_position,
+                    drive: mech.drive,
                 });
             }
         }
@@ -223,6 +215,7 @@ impl CollisionSystem {
 
 impl GameSystem for CollisionSystem {
     fn update(&mut self, game: &mut Game, _delta_time: f32) -> Vec<ServerMessage> {
+        profiling::scope!("collision");
         let mut messages = Vec::new();
 
         // Update collision shapes with current positions
@@ -1,31 +1,49 @@
 use super::GameSystem;
 use crate::game::Game;
 use shared::*;
+use std::collections::HashMap;
 use uuid::Uuid;
 
 /// Combat system handles weapon firing, projectile collisions, and damage
 pub struct CombatSystem {
     last_damage_tick: u64,
+    /// Fractional burn damage carried between ticks, per mech
+    burn_accumulator: HashMap<Uuid, f32>,
 }
 
 impl CombatSystem {
     pub fn new() -> Self {
         Self {
             last_damage_tick: 0,
+            burn_accumulator: HashMap::new(),
         }
     }
 
-    /// Check projectile collisions with mechs
-    fn check_projectile_collisions(&self, game: &mut Game) -> Vec<ServerMessage> {
+    /// Check projectile collisions with mechs. A raised shield stops
+    /// projectiles where their path crosses the bubble; otherwise they have
+    /// to reach the hull.
+    fn check_projectile_collisions(&self, game: &mut Game, delta_time: f32) -> Vec<ServerMessage> {
         let mut messages = Vec::new();
         let mut hits = Vec::new();
 
         for projectile in game.projectiles.values() {
-            if !projectile.is_active() {
+            // Interceptors only hit other shots
+            if !projectile.is_active() || projectile.weapon_type == WeaponType::Interceptor {
                 continue;
             }
 
             let proj_tile = projectile.position.to_tile_pos();
+            let path_start = WorldPos::new(
+                projectile.position.x - projectile.velocity.0 * delta_time,
+                projectile.position.y - projectile.velocity.1 * delta_time,
+            );
+
+            // Shots fly straight, so how far they've come is speed times age
+            let (vx, vy) = projectile.velocity;
+            let travelled = (vx * vx + vy * vy).sqrt() * projectile.lifetime / TILE_SIZE;
+            let damage = (projectile.damage as f32
+                * damage_falloff(projectile.weapon_type, travelled))
+            .round() as u32;
 
             for mech in game.mechs.values() {
                 // Don't hit the mech that fired the projectile
@@ -33,6 +51,28 @@ impl CombatSystem {
                     continue;
                 }
 
+                let hit = |position, shielded| {
+                    (
+                        projectile.id,
+                        mech.id,
+                        damage,
+                        position,
+                        projectile.velocity,
+                        projectile.weapon_type,
+                        shielded,
+                        projectile.owner_mech_id,
+                        HitDirection::new(mech.drive.heading, projectile.velocity),
+                    )
+                };
+
+                if mech.shield > 0 {
+                    let bubble = ShieldBubble::around(mech.position);
+                    if let Some(contact) = bubble.entry_point(path_start, projectile.position) {
+                        hits.push(hit(contact, true));
+                        break;
+                    }
+                }
+
                 let mech_min = mech.position;
                 let mech_max = mech.position.offset(MECH_SIZE_TILES, MECH_SIZE_TILES);
 
@@ -42,36 +82,73 @@ impl CombatSystem {
                     && proj_tile.y >= mech_min.y
                     && proj_tile.y <= mech_max.y
                 {
-                    hits.push((projectile.id, mech.id, projectile.damage));
+                    hits.push(hit(projectile.position, false));
                     break;
                 }
             }
         }
 
         // Process hits
-        for (proj_id, mech_id, damage) in hits {
+        for (proj_id, mech_id, damage, hit_pos, proj_velocity, weapon_type, shielded, owner, hit) in
+            hits
+        {
             // Remove projectile and return to pool
             if let Some(mut projectile) = game.projectiles.remove(&proj_id) {
                 projectile.reset();
                 game.pool_manager.return_projectile(projectile);
             }
 
-            // Apply damage to mech
-            let (explosion_pos, health_remaining, is_destroyed) = {
-                if let Some(mech) = game.mechs.get_mut(&mech_id) {
-                    // Apply damage to shield first, then health
-                    let shield_damage = damage.min(mech.shield);
-                    mech.shield -= shield_damage;
-                    let health_damage = damage - shield_damage;
-                    mech.health = mech.health.saturating_sub(health_damage);
-
-                    (mech.world_position, mech.health, mech.health == 0)
-                } else {
-                    continue;
-                }
+            if weapon_type == WeaponType::Emp {
+                messages.extend(self.apply_emp_hit(game, proj_id, mech_id, hit_pos));
+                continue;
+            }
+
+            let Some(explosion_pos) = game.mechs.get(&mech_id).map(|m| m.world_position) else {
+                continue;
             };
+            // Shield first, then the hull, armored by arc
+            let dealt = game.damage_mech(
+                mech_id,
+                damage,
+                hit_pos,
+                Some(hit),
+                Some(owner),
+                &mut messages,
+            );
+
+            // Absorbed at the bubble surface: nothing reaches the hull
+            if shielded && dealt.hull == 0 {
+                messages.push(ServerMessage::ProjectileHit {
+                    projectile_id: proj_id,
+                    hit_mech_id: Some(mech_id),
+                    damage_dealt: 0,
+                    position: hit_pos,
+                });
+                continue;
+            }
+
+            // Knock the mech back along the projectile's path, unless the
+            // bubble took all of it
+            let speed =
+                (proj_velocity.0 * proj_velocity.0 + proj_velocity.1 * proj_velocity.1).sqrt();
+            if let Some(mech) = game.mechs.get_mut(&mech_id) {
+                if speed > 0.0 && dealt.hull > 0 {
+                    mech.velocity.0 += proj_velocity.0 / speed * PROJECTILE_KNOCKBACK_SPEED;
+                    mech.velocity.1 += proj_velocity.1 / speed * PROJECTILE_KNOCKBACK_SPEED;
+                }
+            }
+
+            messages.push(game.apply_status_effect(
+                mech_id,
+                StatusEffectKind::Slowed,
+                PROJECTILE_SLOW_DURATION,
+                PROJECTILE_SLOW_AMOUNT,
+            ));
+            messages.extend(self.apply_blast_to_players(game, hit_pos));
+            messages
+                .extend(game.destroy_resources_near(hit_pos, EXPLOSION_STUN_RADIUS * TILE_SIZE));
+            messages.push(game.add_decal(hit_pos, DecalKind::Scorch));
 
-            // Create explosion effect after releasing the mutable borrow
             let explosion_color = (1.0, 0.5, 0.0, 1.0); // Orange explosion
             game.create_effect(
                 EffectType::Explosion,
@@ -81,90 +158,236 @@ impl CombatSystem {
                 explosion_color,
             );
 
-            messages.push(ServerMessage::MechDamaged {
-                mech_id,
-                damage,
-                health_remaining,
-            });
-
             messages.push(ServerMessage::ProjectileHit {
                 projectile_id: proj_id,
                 hit_mech_id: Some(mech_id),
-                damage_dealt: damage,
+                damage_dealt: dealt.absorbed + dealt.hull,
+                position: hit_pos,
             });
+        }
 
-            // Check if mech is destroyed
-            if is_destroyed {
-                self.handle_mech_destroyed(game, mech_id, &mut messages);
+        messages
+    }
+
+    /// Knock down shots that a point-defense interceptor passed close to this
+    /// tick. Enemy shots go into a spatial grid so each interceptor only
+    /// checks the ones near its path.
+    fn check_interceptions(&self, game: &mut Game, delta_time: f32) -> Vec<ServerMessage> {
+        let team_of = |mech_id: &Uuid| game.mechs.get(mech_id).map(|mech| mech.team);
+        let hit_radius = INTERCEPTOR_HIT_RADIUS * TILE_SIZE;
+
+        let mut shots = SpatialGrid::for_arena(TILE_SIZE * 2.0);
+        let mut interceptors = Vec::new();
+        let mut fastest_shot = 0.0_f32;
+        for projectile in game.projectiles.values().filter(|p| p.is_active()) {
+            let Some(team) = team_of(&projectile.owner_mech_id) else {
+                continue;
+            };
+            if projectile.weapon_type == WeaponType::Interceptor {
+                interceptors.push((
+                    projectile.id,
+                    team,
+                    projectile.position,
+                    projectile.velocity,
+                ));
+            } else {
+                let (vx, vy) = projectile.velocity;
+                fastest_shot = fastest_shot.max((vx * vx + vy * vy).sqrt());
+                shots.insert(SpatialEntity::new(
+                    projectile.id,
+                    projectile.position,
+                    0.0,
+                    (team, projectile.velocity),
+                ));
+            }
+        }
+
+        let mut intercepted = Vec::new();
+        for (interceptor_id, team, position, velocity) in interceptors {
+            // Far enough to cover both shots' travel over the tick
+            let speed = (velocity.0 * velocity.0 + velocity.1 * velocity.1).sqrt();
+            let reach = hit_radius + (speed + fastest_shot) * delta_time;
+            let caught = shots
+                .query_radius(position, reach)
+                .into_iter()
+                .filter(|near| near.entity.data.0 != team)
+                .filter(|near| {
+                    !intercepted
+                        .iter()
+                        .any(|(_, shot, _)| *shot == near.entity.id)
+                })
+                .find(|near| {
+                    let (_, shot_velocity) = near.entity.data;
+                    closest_approach(
+                        position,
+                        velocity,
+                        near.entity.position,
+                        shot_velocity,
+                        delta_time,
+                    ) <= hit_radius
+                });
+            if let Some(near) = caught {
+                intercepted.push((interceptor_id, near.entity.id, near.entity.position));
             }
         }
 
+        let mut messages = Vec::new();
+        for (interceptor_id, projectile_id, position) in intercepted {
+            for id in [interceptor_id, projectile_id] {
+                if let Some(mut projectile) = game.projectiles.remove(&id) {
+                    projectile.reset();
+                    game.pool_manager.return_projectile(projectile);
+                }
+            }
+            messages.push(ServerMessage::ProjectileIntercepted {
+                projectile_id,
+                interceptor_id,
+                position,
+            });
+        }
         messages
     }
 
-    /// Handle mech destruction
-    fn handle_mech_destroyed(
+    /// An EMP shuts down the target's stations and weapons instead of
+    /// damaging the hull
+    fn apply_emp_hit(
         &self,
         game: &mut Game,
+        proj_id: Uuid,
         mech_id: Uuid,
-        messages: &mut Vec<ServerMessage>,
-    ) {
-        // Eject all players from destroyed mech
-        let mut players_to_eject = Vec::new();
+        hit_pos: WorldPos,
+    ) -> Vec<ServerMessage> {
+        let mut messages = vec![game.apply_status_effect(
+            mech_id,
+            StatusEffectKind::Emp,
+            EMP_DISABLE_DURATION,
+            1.0,
+        )];
+
+        game.create_effect(
+            EffectType::ShieldHit,
+            hit_pos,
+            0.5,                  // 0.5 second duration
+            1.0,                  // Full intensity
+            (0.6, 0.3, 1.0, 1.0), // Violet pulse
+        );
+
+        messages.push(ServerMessage::ProjectileHit {
+            projectile_id: proj_id,
+            hit_mech_id: Some(mech_id),
+            damage_dealt: 0,
+            position: hit_pos,
+        });
+        messages
+    }
 
-        for (player_id, player) in game.players.iter() {
-            if let PlayerLocation::InsideMech {
-                mech_id: player_mech_id,
-                ..
-            } = player.location
-            {
-                if player_mech_id == mech_id {
-                    players_to_eject.push(*player_id);
+    /// Stun and throw back players standing outside near an explosion
+    fn apply_blast_to_players(&self, game: &mut Game, center: WorldPos) -> Vec<ServerMessage> {
+        let mut messages = Vec::new();
+        let radius = EXPLOSION_STUN_RADIUS * TILE_SIZE;
+
+        let caught: Vec<(Uuid, WorldPos)> = game
+            .players
+            .values()
+            .filter_map(|p| match p.location {
+                PlayerLocation::OutsideWorld(pos) if pos.distance_to(center) <= radius => {
+                    Some((p.id, pos))
                 }
-            }
-        }
+                _ => None,
+            })
+            .collect();
+
+        for (player_id, pos) in caught {
+            let dx = pos.x - center.x;
+            let dy = pos.y - center.y;
+            let dist = (dx * dx + dy * dy).sqrt().max(COLLISION_EPSILON);
+            let push = EXPLOSION_KNOCKBACK_DISTANCE * TILE_SIZE;
+            let (world_width, world_height) = game.arena_map.world_size();
+            let new_pos = WorldPos::new(
+                (pos.x + dx / dist * push).clamp(0.0, world_width),
+                (pos.y + dy / dist * push).clamp(0.0, world_height),
+            );
 
-        // Eject players to their team spawn
-        for player_id in players_to_eject {
             if let Some(player) = game.players.get_mut(&player_id) {
-                let spawn_pos = match player.team {
-                    TeamId::Red => WorldPos::new(
-                        RED_PLAYER_SPAWN.0 * TILE_SIZE,
-                        RED_PLAYER_SPAWN.1 * TILE_SIZE,
-                    ),
-                    TeamId::Blue => WorldPos::new(
-                        BLUE_PLAYER_SPAWN.0 * TILE_SIZE,
-                        BLUE_PLAYER_SPAWN.1 * TILE_SIZE,
-                    ),
-                };
-
-                player.location = PlayerLocation::OutsideWorld(spawn_pos);
-                player.carrying_resource = None;
-                player.operating_station = None;
-
+                player.location = PlayerLocation::OutsideWorld(new_pos);
                 messages.push(ServerMessage::PlayerMoved {
                     player_id,
                     location: player.location,
                 });
             }
+
+            messages.push(game.apply_status_effect(
+                player_id,
+                StatusEffectKind::Stunned,
+                EXPLOSION_STUN_DURATION,
+                1.0,
+            ));
         }
 
-        // Create large explosion effect
-        if let Some(mech) = game.mechs.get(&mech_id) {
-            let explosion_pos = mech.world_position;
-            let explosion_color = (1.0, 0.2, 0.0, 1.0); // Red explosion
-            game.create_effect(
-                EffectType::Explosion,
-                explosion_pos,
-                3.0, // 3 second duration
-                2.0, // High intensity
-                explosion_color,
-            );
+        messages
+    }
+
+    /// Advance status effect timers and apply burning damage to mechs
+    fn update_status_effects(&mut self, game: &mut Game, delta_time: f32) -> Vec<ServerMessage> {
+        let mut messages = Vec::new();
+        let mut burn_damage = Vec::new();
+
+        for (entity_id, effects) in game.entity_storage.status_effects.iter_mut() {
+            let (damage, expired) = effects.tick(delta_time);
+            if damage > 0.0 {
+                burn_damage.push((*entity_id, damage));
+            }
+            if expired {
+                messages.push(ServerMessage::StatusEffectsChanged {
+                    entity_id: *entity_id,
+                    effects: effects.effects.clone(),
+                });
+            }
         }
 
-        // TODO: Respawn mech after some time
-        // For now, just log the destruction
-        log::info!("Mech {mech_id} destroyed");
+        // Drop empty sets and entities that no longer exist
+        let players = &game.players;
+        let mechs = &game.mechs;
+        game.entity_storage.status_effects.retain(|id, effects| {
+            !effects.is_empty()
+                && (players.contains_key(id)
+                    || mechs.contains_key(id)
+                    || mechs.values().any(|mech| mech.stations.contains_key(id)))
+        });
+        self.burn_accumulator
+            .retain(|id, _| game.entity_storage.status_effects.contains_key(id));
+
+        for (mech_id, damage) in burn_damage {
+            let accumulated = self.burn_accumulator.entry(mech_id).or_insert(0.0);
+            *accumulated += damage;
+            let whole = accumulated.floor() as u32;
+            if whole == 0 {
+                continue;
+            }
+            *accumulated -= whole as f32;
+
+            let Some(mech) = game.mechs.get_mut(&mech_id) else {
+                continue;
+            };
+            if mech.health == 0 {
+                continue;
+            }
+            mech.health = mech.health.saturating_sub(whole);
+            let health_remaining = mech.health;
+
+            messages.push(ServerMessage::MechDamaged {
+                mech_id,
+                damage: whole,
+                health_remaining,
+                position: MechPositioning::mech_center(mech.position),
+                hit: None,
+            });
+            if health_remaining == 0 {
+                game.destroy_mech(mech_id, &mut messages);
+            }
+        }
+
+        messages
     }
 
     /// Apply damage over time effects
@@ -194,6 +417,61 @@ impl CombatSystem {
         messages
     }
 
+    /// Reload turrets and hand them between operator and auto tracker. An
+    /// unmanned turret tracks the nearest enemy in range and fires on its own.
+    fn update_turrets(&self, game: &mut Game, delta_time: f32) -> Vec<ServerMessage> {
+        let mut messages = Vec::new();
+        let targets: Vec<(TeamId, WorldPos)> = game
+            .mechs
+            .values()
+            .map(|m| (m.team, MechPositioning::mech_center(m.position)))
+            .collect();
+        let range = TURRET_RANGE * TILE_SIZE;
+
+        let mut auto_fire = Vec::new();
+        for mech in game.mechs.values_mut() {
+            mech.turret.reload(delta_time);
+
+            let manned = mech.stations.values().any(|station| {
+                station.station_type == StationType::TurretControl && station.operated_by.is_some()
+            });
+            if manned != mech.turret.manual {
+                mech.turret.manual = manned;
+                messages.push(ServerMessage::TurretAimed {
+                    mech_id: mech.id,
+                    turret: mech.turret,
+                });
+            }
+            if manned {
+                continue;
+            }
+
+            let center = MechPositioning::mech_center(mech.position);
+            let nearest = targets
+                .iter()
+                .filter(|(team, pos)| *team != mech.team && center.distance_to(*pos) <= range)
+                .map(|(_, pos)| *pos)
+                .min_by(|a, b| {
+                    center
+                        .distance_to(*a)
+                        .partial_cmp(&center.distance_to(*b))
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                });
+            if let Some(target) = nearest {
+                mech.turret
+                    .aim_at(MechTurret::angle_between(center, target));
+                if mech.turret.ready() {
+                    auto_fire.push(mech.id);
+                }
+            }
+        }
+
+        for mech_id in auto_fire {
+            messages.extend(game.fire_turret(mech_id));
+        }
+        messages
+    }
+
     /// Process weapon range and targeting
     fn process_weapon_targeting(
         &self,
@@ -269,16 +547,29 @@ impl CombatSystem {
 
 impl GameSystem for CombatSystem {
     fn update(&mut self, game: &mut Game, delta_time: f32) -> Vec<ServerMessage> {
+        profiling::scope!("combat");
         let mut messages = Vec::new();
 
+        // Interceptors get their chance before shots reach the hull
+        let interception_messages = self.check_interceptions(game, delta_time);
+        messages.extend(interception_messages);
+
         // Check projectile collisions
-        let collision_messages = self.check_projectile_collisions(game);
+        let collision_messages = self.check_projectile_collisions(game, delta_time);
         messages.extend(collision_messages);
 
+        // Tick status effects (burning, stuns, slows, EMP)
+        let status_messages = self.update_status_effects(game, delta_time);
+        messages.extend(status_messages);
+
         // Apply damage over time effects
         let dot_messages = self.apply_damage_over_time(game);
         messages.extend(dot_messages);
 
+        // Crewed and auto-tracking turrets
+        let turret_messages = self.update_turrets(game, delta_time);
+        messages.extend(turret_messages);
+
         messages
     }
 
@@ -300,3 +591,277 @@ impl Default for CombatSystem {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_emp_hit_disables_stations_without_damage() {
+        let mut game = Game::new();
+        let red = game.mechs.values().find(|m| m.team == TeamId::Red).unwrap();
+        let blue = game
+            .mechs
+            .values()
+            .find(|m| m.team == TeamId::Blue)
+            .unwrap();
+        let (red_id, blue_id, blue_pos) = (red.id, blue.id, blue.position);
+        let (health, shield) = (blue.health, blue.shield);
+
+        let hit_pos = MechPositioning::mech_center(blue_pos);
+        game.create_projectile(hit_pos, (0.0, 0.0), 0, red_id, 1.0, WeaponType::Emp);
+
+        let mut combat = CombatSystem::new();
+        combat.update(&mut game, 0.0);
+
+        assert!(game.projectiles.is_empty());
+        assert_eq!(game.mechs[&blue_id].health, health);
+        assert_eq!(game.mechs[&blue_id].shield, shield);
+        assert!(matches!(
+            game.check_can_operate(Uuid::new_v4(), blue_id),
+            Err(GameError::BlockedByStatusEffect {
+                effect: StatusEffectKind::Emp,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_explosions_scorch_the_ground_and_wreck_nearby_resources() {
+        let mut game = Game::new();
+        let red_id = game
+            .mechs
+            .values()
+            .find(|m| m.team == TeamId::Red)
+            .unwrap()
+            .id;
+        let blue = game
+            .mechs
+            .values_mut()
+            .find(|m| m.team == TeamId::Blue)
+            .unwrap();
+        blue.shield = 0;
+        let center = MechPositioning::mech_center(blue.position);
+        let near = center.to_tile_pos().offset(1, 0);
+        let far = center.to_tile_pos().offset(-8, 0);
+        let near_id = game.spawn_resource_with_behavior(near, ResourceType::ScrapMetal);
+        let far_id = game.spawn_resource_with_behavior(far, ResourceType::Wiring);
+
+        game.create_projectile(center, (0.0, 0.0), 10, red_id, 1.0, WeaponType::Projectile);
+        let messages = CombatSystem::new().update(&mut game, 0.0);
+
+        let hit_pos = messages
+            .iter()
+            .find_map(|msg| match msg {
+                ServerMessage::ProjectileHit { position, .. } => Some(*position),
+                _ => None,
+            })
+            .expect("projectile hit the hull");
+        assert_eq!(
+            game.decals.get(hit_pos.to_tile_pos()),
+            Some(DecalKind::Scorch)
+        );
+        assert_eq!(game.decals.get(near), Some(DecalKind::Debris));
+        assert!(messages.iter().any(|msg| matches!(
+            msg,
+            ServerMessage::ResourceDestroyed { resource_id } if *resource_id == near_id
+        )));
+        let left: Vec<Uuid> = game.get_resources().iter().map(|r| r.id).collect();
+        assert_eq!(left, vec![far_id]);
+    }
+
+    #[test]
+    fn test_rear_hits_hurt_more_than_front_hits() {
+        let mut game = Game::new();
+        let red_id = game
+            .mechs
+            .values()
+            .find(|m| m.team == TeamId::Red)
+            .unwrap()
+            .id;
+        let blue = game
+            .mechs
+            .values_mut()
+            .find(|m| m.team == TeamId::Blue)
+            .unwrap();
+        blue.shield = 0;
+        blue.drive.heading = 0.0;
+        let (blue_id, center) = (blue.id, MechPositioning::mech_center(blue.position));
+
+        let mut hull_damage = |game: &mut Game, velocity| {
+            let before = game.mechs[&blue_id].health;
+            game.create_projectile(center, velocity, 20, red_id, 1.0, WeaponType::Projectile);
+            let messages = CombatSystem::new().update(game, 0.0);
+            let hit = messages.iter().find_map(|msg| match msg {
+                ServerMessage::MechDamaged { hit, .. } => *hit,
+                _ => None,
+            });
+            (before - game.mechs[&blue_id].health, hit.map(|hit| hit.arc))
+        };
+
+        // Facing +x, a shot flying -x meets the nose and one flying +x the tail
+        assert_eq!(
+            hull_damage(&mut game, (-1.0, 0.0)),
+            (15, Some(ArmorArc::Front))
+        );
+        assert_eq!(
+            hull_damage(&mut game, (1.0, 0.0)),
+            (25, Some(ArmorArc::Rear))
+        );
+    }
+
+    #[test]
+    fn test_shield_bubble_stops_projectiles_at_its_surface() {
+        let mut game = Game::new();
+        let red_id = game
+            .mechs
+            .values()
+            .find(|m| m.team == TeamId::Red)
+            .unwrap()
+            .id;
+        let blue = game
+            .mechs
+            .values()
+            .find(|m| m.team == TeamId::Blue)
+            .unwrap();
+        let (blue_id, health, shield) = (blue.id, blue.health, blue.shield);
+        let bubble = ShieldBubble::around(blue.position);
+
+        // Just inside the bubble but well clear of the hull, flying inward
+        let inside = WorldPos::new(bubble.center.x + bubble.radius - 5.0, bubble.center.y);
+        game.create_projectile(
+            inside,
+            (-300.0, 0.0),
+            10,
+            red_id,
+            1.0,
+            WeaponType::Projectile,
+        );
+
+        let mut combat = CombatSystem::new();
+        let messages = combat.update(&mut game, 0.1);
+
+        assert!(game.projectiles.is_empty());
+        assert_eq!(game.mechs[&blue_id].health, health);
+        assert_eq!(game.mechs[&blue_id].shield, shield - 10);
+        let contact = messages
+            .iter()
+            .find_map(|msg| match msg {
+                ServerMessage::ShieldImpact { position, .. } => Some(*position),
+                _ => None,
+            })
+            .expect("shield impact reported");
+        assert!((contact.distance_to(bubble.center) - bubble.radius).abs() < 0.01);
+
+        // With the shield down the same shot sails on toward the hull
+        game.mechs.get_mut(&blue_id).unwrap().shield = 0;
+        game.create_projectile(
+            inside,
+            (-300.0, 0.0),
+            10,
+            red_id,
+            1.0,
+            WeaponType::Projectile,
+        );
+        combat.update(&mut game, 0.1);
+        assert_eq!(game.projectiles.len(), 1);
+    }
+
+    #[test]
+    fn test_interceptors_only_knock_down_enemy_shots_they_pass() {
+        let mut game = Game::new();
+        let team_mech = |team| game.mechs.values().find(|m| m.team == team).unwrap().id;
+        let (red_id, blue_id) = (team_mech(TeamId::Red), team_mech(TeamId::Blue));
+
+        // Head-on, and already past each other by the end of the step
+        let shot = game.create_projectile(
+            WorldPos::new(410.0, 300.0),
+            (300.0, 0.0),
+            20,
+            red_id,
+            1.0,
+            WeaponType::Projectile,
+        );
+        let interceptor = game.create_projectile(
+            WorldPos::new(390.0, 300.0),
+            (-INTERCEPTOR_SPEED, 0.0),
+            0,
+            blue_id,
+            1.0,
+            WeaponType::Interceptor,
+        );
+        // A friendly shot right alongside is left alone
+        let friendly = game.create_projectile(
+            WorldPos::new(390.0, 305.0),
+            (300.0, 0.0),
+            20,
+            blue_id,
+            1.0,
+            WeaponType::Projectile,
+        );
+
+        let combat = CombatSystem::new();
+        let messages = combat.check_interceptions(&mut game, 0.05);
+
+        assert!(matches!(
+            messages.as_slice(),
+            [ServerMessage::ProjectileIntercepted { projectile_id, interceptor_id, .. }]
+                if *projectile_id == shot && *interceptor_id == interceptor
+        ));
+        assert_eq!(game.projectiles.keys().collect::<Vec<_>>(), vec![&friendly]);
+    }
+
+    #[test]
+    fn test_unmanned_turret_fires_and_operator_takes_over() {
+        let mut game = Game::new();
+        let red = game.mechs.values().find(|m| m.team == TeamId::Red).unwrap();
+        let (red_id, red_pos) = (red.id, red.position);
+        let turret_station = *red
+            .stations
+            .iter()
+            .find(|(_, s)| s.station_type == StationType::TurretControl)
+            .unwrap()
+            .0;
+        let blue_id = game
+            .mechs
+            .values()
+            .find(|m| m.team == TeamId::Blue)
+            .unwrap()
+            .id;
+        // Park the blue mech in range, due east of the red one
+        game.mechs.get_mut(&blue_id).unwrap().position =
+            TilePos::new(red_pos.x + TURRET_RANGE as i32 - 5, red_pos.y);
+
+        let mut combat = CombatSystem::new();
+        combat.update(&mut game, 0.0);
+        let shot = game
+            .projectiles
+            .values()
+            .find(|p| p.owner_mech_id == red_id)
+            .unwrap();
+        let heading = shot.velocity.1.atan2(shot.velocity.0);
+        assert!(heading.abs() <= TURRET_AUTO_SPREAD + 1e-4);
+        assert!(!game.mechs[&red_id].turret.ready());
+
+        // Crewing the station hands the turret to the operator, who does the firing
+        game.mechs
+            .get_mut(&red_id)
+            .unwrap()
+            .stations
+            .get_mut(&turret_station)
+            .unwrap()
+            .operated_by = Some(Uuid::new_v4());
+        let messages = combat.update(&mut game, TURRET_AUTO_COOLDOWN);
+        assert!(messages.iter().any(|m| matches!(
+            m,
+            ServerMessage::TurretAimed { mech_id, turret } if *mech_id == red_id && turret.manual
+        )));
+        let red_shots = game
+            .projectiles
+            .values()
+            .filter(|p| p.owner_mech_id == red_id)
+            .count();
+        assert_eq!(red_shots, 1);
+        assert!(game.fire_turret(red_id).is_some());
+    }
+}
@@ -0,0 +1,371 @@
+use super::GameSystem;
+use crate::game::Game;
+use rand::Rng;
+use shared::*;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Macro-level match pacing. Watches how much fighting is happening and
+/// injects events to break stalemates or give a quiet match something to
+/// fight over, then closes the arena in once the match runs long.
+pub struct DirectorSystem {
+    match_time: f32,
+    evaluate_timer: f32,
+    /// Time since the last pacing event, starts full so the first one can fire
+    since_last_event: f32,
+    time_since_damage: f32,
+    /// Rolling average of damage per second, with deaths weighted in
+    intensity: f32,
+    /// Deaths reported since the last update
+    pending_deaths: u32,
+    last_mech_health: HashMap<Uuid, u32>,
+    /// Fractional play area damage carried between ticks, per mech
+    outside_damage: HashMap<Uuid, f32>,
+}
+
+impl DirectorSystem {
+    pub fn new() -> Self {
+        Self {
+            match_time: 0.0,
+            evaluate_timer: 0.0,
+            since_last_event: DIRECTOR_EVENT_COOLDOWN,
+            time_since_damage: 0.0,
+            intensity: 0.0,
+            pending_deaths: 0,
+            last_mech_health: HashMap::new(),
+            outside_damage: HashMap::new(),
+        }
+    }
+
+    /// Feed the messages produced by a tick so deaths count toward intensity
+    pub fn record_events(&mut self, messages: &[ServerMessage]) {
+        self.pending_deaths += messages
+            .iter()
+            .filter(|message| matches!(message, ServerMessage::PlayerKilled { .. }))
+            .count() as u32;
+    }
+
+    /// Measure damage dealt since the last tick from mech health. Sampling
+    /// health catches every damage source, including instant laser hits that
+    /// never pass through a system.
+    fn sample_intensity(&mut self, game: &Game, delta_time: f32) {
+        let mut damage = 0;
+        for mech in game.mechs.values() {
+            if let Some(previous) = self.last_mech_health.insert(mech.id, mech.health) {
+                damage += previous.saturating_sub(mech.health);
+            }
+        }
+        self.last_mech_health
+            .retain(|id, _| game.mechs.contains_key(id));
+
+        let deaths = std::mem::take(&mut self.pending_deaths);
+        let activity = damage as f32 + deaths as f32 * DIRECTOR_DEATH_WEIGHT;
+
+        if activity > 0.0 {
+            self.time_since_damage = 0.0;
+        } else {
+            self.time_since_damage += delta_time;
+        }
+
+        let decay = (-delta_time / DIRECTOR_INTENSITY_WINDOW).exp();
+        self.intensity = self.intensity * decay + activity / DIRECTOR_INTENSITY_WINDOW;
+    }
+
+    /// Advance zone timers and drop the ones that have run out
+    fn update_zones(&mut self, game: &mut Game, delta_time: f32) -> Vec<ServerMessage> {
+        let before = game.pacing_zones.len();
+        game.pacing_zones
+            .retain_mut(|zone| !zone.advance(delta_time));
+
+        if game.pacing_zones.len() == before {
+            return Vec::new();
+        }
+        vec![ServerMessage::PacingZonesUpdated {
+            zones: game.pacing_zones.clone(),
+        }]
+    }
+
+    /// Wear down mechs caught outside the play area. The zone never finishes
+    /// a mech off; the kill is left to the other team.
+    fn damage_outside_play_area(&mut self, game: &mut Game, delta_time: f32) -> Vec<ServerMessage> {
+        let mut messages = Vec::new();
+        let Some(play_area) = game
+            .pacing_zones
+            .iter()
+            .find(|zone| matches!(zone.kind, PacingZoneKind::PlayArea { .. }))
+            .copied()
+        else {
+            return messages;
+        };
+
+        for mech in game.mechs.values_mut() {
+            if play_area.contains(MechPositioning::mech_center(mech.position)) {
+                self.outside_damage.remove(&mech.id);
+                continue;
+            }
+
+            let accumulated = self.outside_damage.entry(mech.id).or_insert(0.0);
+            *accumulated += PLAY_AREA_OUTSIDE_DPS * delta_time;
+            let whole = (accumulated.floor() as u32).min(mech.health.saturating_sub(1));
+            if whole == 0 {
+                continue;
+            }
+            *accumulated -= whole as f32;
+
+            mech.health -= whole;
+            messages.push(ServerMessage::MechDamaged {
+                mech_id: mech.id,
+                damage: whole,
+                health_remaining: mech.health,
+                position: MechPositioning::mech_center(mech.position),
+                hit: None,
+            });
+        }
+
+        messages
+    }
+
+    /// Decide whether the match needs a nudge
+    fn evaluate(&mut self, game: &mut Game) -> Vec<ServerMessage> {
+        let mut messages = Vec::new();
+
+        let has_play_area = game
+            .pacing_zones
+            .iter()
+            .any(|zone| matches!(zone.kind, PacingZoneKind::PlayArea { .. }));
+        if !has_play_area && self.match_time >= PLAY_AREA_SHRINK_START {
+            messages.push(self.start_play_area(game));
+        }
+
+        if self.since_last_event < DIRECTOR_EVENT_COOLDOWN {
+            return messages;
+        }
+
+        if self.time_since_damage >= DIRECTOR_STALEMATE_TIME {
+            messages.push(self.start_damage_boost(game));
+        } else if self.intensity < DIRECTOR_LOW_INTENSITY {
+            messages.extend(self.start_resource_surge(game));
+        } else {
+            return messages;
+        }
+
+        self.since_last_event = 0.0;
+        messages
+    }
+
+    /// Somewhere between the two teams, so events pull them together
+    fn contested_position(game: &mut Game) -> WorldPos {
+        let team_center = |team: TeamId| {
+            let centers: Vec<WorldPos> = game
+                .mechs
+                .values()
+                .filter(|mech| mech.team == team)
+                .map(|mech| MechPositioning::mech_center(mech.position))
+                .collect();
+            if centers.is_empty() {
+                return None;
+            }
+            let count = centers.len() as f32;
+            Some(WorldPos::new(
+                centers.iter().map(|pos| pos.x).sum::<f32>() / count,
+                centers.iter().map(|pos| pos.y).sum::<f32>() / count,
+            ))
+        };
+
+        let (world_width, world_height) = game.arena_map.world_size();
+        let arena_center = WorldPos::new(world_width / 2.0, world_height / 2.0);
+        let midpoint = match (team_center(TeamId::Red), team_center(TeamId::Blue)) {
+            (Some(red), Some(blue)) => {
+                WorldPos::new((red.x + blue.x) / 2.0, (red.y + blue.y) / 2.0)
+            }
+            _ => arena_center,
+        };
+
+        // A little jitter so repeated events don't land on the same spot
+        let jitter = 3.0 * TILE_SIZE;
+        WorldPos::new(
+            (midpoint.x + game.rng.gen_range(-jitter..=jitter)).clamp(0.0, world_width),
+            (midpoint.y + game.rng.gen_range(-jitter..=jitter)).clamp(0.0, world_height),
+        )
+    }
+
+    fn start_zone(game: &mut Game, zone: PacingZone, announcement: &str) -> ServerMessage {
+        log::info!("Director: {announcement}");
+        game.pacing_zones.push(zone);
+        ServerMessage::PacingEventStarted {
+            announcement: announcement.to_string(),
+            zone,
+        }
+    }
+
+    fn start_damage_boost(&self, game: &mut Game) -> ServerMessage {
+        let zone = PacingZone {
+            id: game.rng.uuid(),
+            kind: PacingZoneKind::DamageBoost {
+                multiplier: DAMAGE_BOOST_MULTIPLIER,
+            },
+            center: Self::contested_position(game),
+            radius: DAMAGE_BOOST_RADIUS * TILE_SIZE,
+            remaining: Some(DAMAGE_BOOST_DURATION),
+        };
+        Self::start_zone(
+            game,
+            zone,
+            "Overcharge zone active: weapons fired inside deal extra damage",
+        )
+    }
+
+    fn start_resource_surge(&self, game: &mut Game) -> Vec<ServerMessage> {
+        let center = Self::contested_position(game);
+        let center_tile = center.to_tile_pos();
+        let radius = RESOURCE_SURGE_RADIUS as i32;
+
+        let mut messages = Vec::new();
+        let mut spawned = 0;
+        for _ in 0..RESOURCE_SURGE_COUNT * 10 {
+            if spawned == RESOURCE_SURGE_COUNT {
+                break;
+            }
+            let pos = center_tile.offset(
+                game.rng.gen_range(-radius..=radius),
+                game.rng.gen_range(-radius..=radius),
+            );
+            if !game.can_drop_resource(pos) {
+                continue;
+            }
+
+            let resource_type = game.roll_resource_type(pos);
+            let resource_id = game.spawn_resource_with_behavior(pos, resource_type);
+            messages.push(ServerMessage::ResourceSpawned {
+                resource_id,
+                position: pos,
+                resource_type,
+            });
+            spawned += 1;
+        }
+
+        if spawned == 0 {
+            return messages;
+        }
+
+        let zone = PacingZone {
+            id: game.rng.uuid(),
+            kind: PacingZoneKind::ResourceSurge,
+            center,
+            radius: RESOURCE_SURGE_RADIUS * TILE_SIZE,
+            remaining: Some(RESOURCE_SURGE_DURATION),
+        };
+        messages.push(Self::start_zone(
+            game,
+            zone,
+            "Supply drop: resources surging in the marked area",
+        ));
+        messages
+    }
+
+    fn start_play_area(&self, game: &mut Game) -> ServerMessage {
+        let (width, height) = game.arena_map.world_size();
+        let zone = PacingZone {
+            id: game.rng.uuid(),
+            kind: PacingZoneKind::PlayArea {
+                target_radius: PLAY_AREA_MIN_RADIUS * TILE_SIZE,
+                shrink_speed: PLAY_AREA_SHRINK_SPEED * TILE_SIZE,
+            },
+            center: WorldPos::new(width / 2.0, height / 2.0),
+            // Start out covering the corners so nobody is caught outside at once
+            radius: (width * width + height * height).sqrt() / 2.0,
+            remaining: None,
+        };
+        Self::start_zone(
+            game,
+            zone,
+            "The arena is closing in: stay inside the circle",
+        )
+    }
+}
+
+impl GameSystem for DirectorSystem {
+    fn update(&mut self, game: &mut Game, delta_time: f32) -> Vec<ServerMessage> {
+        profiling::scope!("director");
+        let mut messages = Vec::new();
+
+        self.match_time += delta_time;
+        self.since_last_event += delta_time;
+        self.sample_intensity(game, delta_time);
+
+        messages.extend(self.update_zones(game, delta_time));
+        messages.extend(self.damage_outside_play_area(game, delta_time));
+
+        self.evaluate_timer += delta_time;
+        if self.evaluate_timer >= DIRECTOR_EVALUATE_INTERVAL {
+            self.evaluate_timer = 0.0;
+            messages.extend(self.evaluate(game));
+        }
+
+        messages
+    }
+
+    fn name(&self) -> &'static str {
+        "director"
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stalemate_triggers_damage_boost() {
+        let mut game = Game::new();
+        let mut director = DirectorSystem::new();
+
+        // Sit through a quiet match until the director steps in
+        let mut started = Vec::new();
+        for _ in 0..((DIRECTOR_STALEMATE_TIME + 2.0 * DIRECTOR_EVENT_COOLDOWN) as usize) {
+            for message in director.update(&mut game, 1.0) {
+                if let ServerMessage::PacingEventStarted { zone, .. } = message {
+                    started.push(zone.kind);
+                }
+            }
+        }
+
+        // A quiet match gets a resource surge first, then the stalemate breaker
+        assert!(matches!(
+            started.first(),
+            Some(PacingZoneKind::ResourceSurge)
+        ));
+        assert!(started
+            .iter()
+            .any(|kind| matches!(kind, PacingZoneKind::DamageBoost { .. })));
+        assert!(!game.get_resources().is_empty());
+    }
+
+    #[test]
+    fn test_play_area_wears_down_mechs_outside() {
+        let mut game = Game::new();
+        let mut director = DirectorSystem::new();
+        let mech_id = *game.mechs.keys().next().unwrap();
+        let center = MechPositioning::mech_center(game.mechs[&mech_id].position);
+
+        game.pacing_zones.push(PacingZone {
+            id: Uuid::new_v4(),
+            kind: PacingZoneKind::PlayArea {
+                target_radius: TILE_SIZE,
+                shrink_speed: 0.0,
+            },
+            center: WorldPos::new(center.x + 50.0 * TILE_SIZE, center.y),
+            radius: TILE_SIZE,
+            remaining: None,
+        });
+        game.mechs.get_mut(&mech_id).unwrap().health = 3;
+
+        for _ in 0..10 {
+            director.update(&mut game, 1.0);
+        }
+        assert_eq!(game.mechs[&mech_id].health, 1);
+    }
+}
@@ -0,0 +1,102 @@
+use super::GameSystem;
+use crate::game::Game;
+use rand::Rng;
+use shared::*;
+
+/// Runs the day and night clock and the weather. Clear spells alternate with
+/// fog or rain, each lasting a random while. Everyone hears when the weather
+/// turns; the clock itself goes out with every full state.
+pub struct EnvironmentSystem {
+    /// Sight range the vision cache was cast with
+    vision_range: f32,
+}
+
+impl EnvironmentSystem {
+    pub fn new() -> Self {
+        Self {
+            vision_range: PLAYER_VISION_RANGE,
+        }
+    }
+
+    /// What comes after the weather that just ran out, and for how long
+    fn next_weather(current: Weather, rng: &mut impl Rng) -> (Weather, f32) {
+        match current {
+            Weather::Clear => {
+                let weather = if rng.gen_bool(0.5) {
+                    Weather::Fog
+                } else {
+                    Weather::Rain
+                };
+                let (min, max) = WEATHER_EVENT_SECONDS;
+                (weather, rng.gen_range(min..=max))
+            }
+            Weather::Fog | Weather::Rain => {
+                let (min, max) = WEATHER_CLEAR_SECONDS;
+                (Weather::Clear, rng.gen_range(min..=max))
+            }
+        }
+    }
+}
+
+impl GameSystem for EnvironmentSystem {
+    fn update(&mut self, game: &mut Game, delta_time: f32) -> Vec<ServerMessage> {
+        profiling::scope!("environment");
+        let mut messages = Vec::new();
+        if game.environment.advance(delta_time) {
+            let (weather, duration) = Self::next_weather(game.environment.weather, &mut game.rng);
+            game.environment.weather = weather;
+            game.environment.weather_remaining = duration;
+            log::info!("Weather turned {} for {duration:.0}s", weather.name());
+            messages.push(ServerMessage::WeatherChanged {
+                environment: game.environment,
+            });
+        }
+
+        // Sight cast before dusk or the fog is wrong now
+        let range = game.environment.vision_range();
+        if range != self.vision_range {
+            self.vision_range = range;
+            game.vision_scheduler.invalidate_all();
+        }
+        messages
+    }
+
+    fn name(&self) -> &'static str {
+        "environment"
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_weather_alternates_between_clear_spells_and_events() {
+        let mut game = Game::new();
+        let mut environment = EnvironmentSystem::new();
+        game.environment.weather_remaining = 1.0;
+
+        assert!(environment.update(&mut game, 0.5).is_empty());
+        let messages = environment.update(&mut game, 0.5);
+        assert!(matches!(
+            messages[..],
+            [ServerMessage::WeatherChanged {
+                environment: Environment {
+                    weather: Weather::Fog | Weather::Rain,
+                    ..
+                }
+            }]
+        ));
+        let (min, max) = WEATHER_EVENT_SECONDS;
+        assert!((min..=max).contains(&game.environment.weather_remaining));
+
+        // The next change clears it up again
+        game.environment.weather_remaining = 0.0;
+        environment.update(&mut game, 0.1);
+        assert_eq!(game.environment.weather, Weather::Clear);
+    }
+}
@@ -0,0 +1,73 @@
+//! Runs the room's game mode: initialises it on the first tick, feeds it
+//! this tick's events and sends clients its objective whenever that
+//! changes. Once the mode declares a result the match is over and nothing
+//! more is scored. Does nothing for rooms without a mode.
+
+use super::GameSystem;
+use crate::game::Game;
+use crate::game_modes::GameMode;
+use shared::*;
+
+pub struct GameModeSystem {
+    mode: Option<Box<dyn GameMode>>,
+    started: bool,
+}
+
+impl GameModeSystem {
+    pub fn new() -> Self {
+        Self {
+            mode: None,
+            started: false,
+        }
+    }
+
+    /// Play the match under `mode` from the next tick on
+    pub fn set_mode(&mut self, mode: Box<dyn GameMode>) {
+        self.mode = Some(mode);
+        self.started = false;
+    }
+}
+
+impl GameSystem for GameModeSystem {
+    fn update(&mut self, game: &mut Game, _delta_time: f32) -> Vec<ServerMessage> {
+        profiling::scope!("game_mode");
+        let Some(mode) = self.mode.as_mut() else {
+            return Vec::new();
+        };
+        if !self.started {
+            mode.init(game);
+            self.started = true;
+            log::info!("Match started as {}", mode.kind().title());
+        }
+        if game
+            .game_mode
+            .as_ref()
+            .is_some_and(|hud| hud.result.is_some())
+        {
+            return Vec::new();
+        }
+
+        for event in game.events.pending() {
+            mode.on_event(game, event);
+        }
+        let mut hud = mode.hud(game);
+        hud.result = mode.victory(game);
+        if let Some(result) = &hud.result {
+            log::info!("Match over, winner {:?}: {}", result.winner, result.reason);
+        }
+
+        if game.game_mode.as_ref() == Some(&hud) {
+            return Vec::new();
+        }
+        game.game_mode = Some(hud.clone());
+        vec![ServerMessage::GameModeUpdated { hud }]
+    }
+
+    fn name(&self) -> &'static str {
+        "game_mode"
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
@@ -0,0 +1,212 @@
+use super::GameSystem;
+use crate::game::Game;
+use shared::*;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Applies the arena map's hazards to mechs walking over them. Radiation
+/// wears a mech down while it stands in it; mines go off once and are gone.
+pub struct HazardSystem {
+    /// Fractional radiation damage carried between ticks, per mech
+    radiation_damage: HashMap<Uuid, f32>,
+}
+
+impl HazardSystem {
+    pub fn new() -> Self {
+        Self {
+            radiation_damage: HashMap::new(),
+        }
+    }
+
+    fn covers(mech_pos: TilePos, tile: TilePos) -> bool {
+        (mech_pos.x..mech_pos.x + MECH_SIZE_TILES).contains(&tile.x)
+            && (mech_pos.y..mech_pos.y + MECH_SIZE_TILES).contains(&tile.y)
+    }
+
+    /// Like the play area, radiation never finishes a mech off
+    fn apply_radiation(&mut self, game: &mut Game, delta_time: f32) -> Vec<ServerMessage> {
+        let mut messages = Vec::new();
+
+        for mech in game.mechs.values_mut() {
+            let irradiated = game
+                .arena_map
+                .hazards
+                .iter()
+                .filter(|h| {
+                    h.kind == HazardKind::Radiation && Self::covers(mech.position, h.position)
+                })
+                .count();
+            if irradiated == 0 {
+                self.radiation_damage.remove(&mech.id);
+                continue;
+            }
+
+            let accumulated = self.radiation_damage.entry(mech.id).or_insert(0.0);
+            *accumulated += irradiated as f32 * HAZARD_RADIATION_DPS * delta_time;
+            let whole = (accumulated.floor() as u32).min(mech.health.saturating_sub(1));
+            if whole == 0 {
+                continue;
+            }
+            *accumulated -= whole as f32;
+
+            mech.health -= whole;
+            messages.push(ServerMessage::MechDamaged {
+                mech_id: mech.id,
+                damage: whole,
+                health_remaining: mech.health,
+                position: MechPositioning::mech_center(mech.position),
+                hit: None,
+            });
+        }
+
+        messages
+    }
+
+    fn detonate_mines(&mut self, game: &mut Game) -> Vec<ServerMessage> {
+        let mut messages = Vec::new();
+        let mut triggered = Vec::new(); // (mech, mine tile)
+
+        game.arena_map.hazards.retain(|hazard| {
+            if hazard.kind != HazardKind::Mine {
+                return true;
+            }
+            match game
+                .mechs
+                .values()
+                .find(|mech| Self::covers(mech.position, hazard.position))
+            {
+                Some(mech) => {
+                    triggered.push((mech.id, hazard.position));
+                    false
+                }
+                None => true,
+            }
+        });
+        if triggered.is_empty() {
+            return messages;
+        }
+
+        for (mech_id, mine_tile) in triggered {
+            let position = mine_tile.to_world_center();
+            messages.push(game.add_decal(position, DecalKind::Scorch));
+            game.damage_mech(
+                mech_id,
+                HAZARD_MINE_DAMAGE,
+                position,
+                None,
+                None,
+                &mut messages,
+            );
+        }
+
+        messages.push(ServerMessage::HazardsUpdated {
+            hazards: game.arena_map.hazards.clone(),
+        });
+        messages
+    }
+}
+
+impl GameSystem for HazardSystem {
+    fn update(&mut self, game: &mut Game, delta_time: f32) -> Vec<ServerMessage> {
+        profiling::scope!("hazard");
+        let mut messages = self.apply_radiation(game, delta_time);
+        messages.extend(self.detonate_mines(game));
+        messages
+    }
+
+    fn name(&self) -> &'static str {
+        "hazard"
+    }
+
+    fn should_update(&self, game: &Game) -> bool {
+        !game.arena_map.hazards.is_empty()
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mine_detonates_once_under_a_mech() {
+        let mut game = Game::new();
+        let mech_id = *game.mechs.keys().next().unwrap();
+        let mech_pos = game.mechs[&mech_id].position;
+        let health = game.mechs[&mech_id].health;
+        let shield = game.mechs[&mech_id].shield;
+        game.arena_map.set_hazard(
+            TilePos::new(mech_pos.x + 2, mech_pos.y + 2),
+            HazardKind::Mine,
+        );
+
+        // The shield takes the blast before the hull does
+        let mut hazards = HazardSystem::new();
+        hazards.update(&mut game, 0.1);
+        let mech = &game.mechs[&mech_id];
+        assert_eq!(
+            mech.shield + mech.health,
+            shield + health - HAZARD_MINE_DAMAGE
+        );
+        assert!(mech.shield < shield);
+        assert!(game.arena_map.hazards.is_empty());
+
+        hazards.update(&mut game, 0.1);
+        let mech = &game.mechs[&mech_id];
+        assert_eq!(
+            mech.shield + mech.health,
+            shield + health - HAZARD_MINE_DAMAGE
+        );
+    }
+
+    #[test]
+    fn test_lethal_mine_wrecks_the_mech_and_ejects_its_crew() {
+        let mut game = Game::new();
+        let mech_id = *game.mechs.keys().next().unwrap();
+        let player_id = Uuid::new_v4();
+        game.add_player(player_id, "Crew".to_string(), None);
+        game.players.get_mut(&player_id).unwrap().location = PlayerLocation::InsideMech {
+            mech_id,
+            pos: MechInteriorPos::new(0, TilePos::new(2, 2)),
+        };
+        let mech = game.mechs.get_mut(&mech_id).unwrap();
+        mech.shield = 0;
+        mech.health = HAZARD_MINE_DAMAGE / 2;
+        let mech_pos = mech.position;
+        game.arena_map.set_hazard(
+            TilePos::new(mech_pos.x + 2, mech_pos.y + 2),
+            HazardKind::Mine,
+        );
+
+        let messages = HazardSystem::new().update(&mut game, 0.1);
+        assert_eq!(game.mechs[&mech_id].health, 0);
+        assert!(matches!(
+            game.players[&player_id].location,
+            PlayerLocation::OutsideWorld(_)
+        ));
+        assert!(messages.iter().any(|message| matches!(
+            message,
+            ServerMessage::PlayerMoved { player_id: moved, .. } if *moved == player_id
+        )));
+    }
+
+    #[test]
+    fn test_radiation_wears_down_but_never_kills() {
+        let mut game = Game::new();
+        let mech_id = *game.mechs.keys().next().unwrap();
+        let mech_pos = game.mechs[&mech_id].position;
+        game.arena_map.set_hazard(mech_pos, HazardKind::Radiation);
+
+        let mut hazards = HazardSystem::new();
+        hazards.update(&mut game, 2.0);
+        assert!(game.mechs[&mech_id].health < MECH_INITIAL_HEALTH);
+
+        for _ in 0..1000 {
+            hazards.update(&mut game, 1.0);
+        }
+        assert_eq!(game.mechs[&mech_id].health, 1);
+    }
+}
@@ -1,10 +1,26 @@
+pub mod achievements;
 pub mod ai;
+pub mod boarding;
+pub mod camera_director;
 pub mod collision;
 pub mod combat;
+pub mod director;
+pub mod environment;
+pub mod game_mode;
+pub mod hazard;
 pub mod networking;
 pub mod physics;
+pub mod point_defense;
+pub mod radar;
+pub mod ramming;
+pub mod repair;
 pub mod resource;
+pub mod spawn_protection;
+pub mod station_effects;
+pub mod stomp;
+pub mod structures;
 pub mod tile_behavior;
+pub mod tutorial;
 
 // Export action types for external use
 pub use physics::PhysicsAction;
@@ -26,6 +42,12 @@ pub trait GameSystem {
         true
     }
 
+    /// Only changes how the match looks, so it can be paused while the
+    /// server sheds load
+    fn is_cosmetic(&self) -> bool {
+        false
+    }
+
     /// Get self as Any for downcasting
     fn as_any_mut(&mut self) -> &mut dyn std::any::Any;
 }
@@ -48,10 +70,29 @@ impl SystemManager {
         manager.register_system(Box::new(tile_behavior::TileBehaviorSystem::new()));
         manager.register_system(Box::new(physics::PhysicsSystem::new()));
         manager.register_system(Box::new(collision::CollisionSystem::new()));
+        manager.register_system(Box::new(ramming::RammingSystem::new()));
+        // Enemy shots fizzle in safe zones before they can land
+        manager.register_system(Box::new(spawn_protection::SpawnProtectionSystem::new()));
         manager.register_system(Box::new(combat::CombatSystem::new()));
+        manager.register_system(Box::new(repair::RepairSystem::new()));
+        manager.register_system(Box::new(structures::StructureSystem::new()));
+        manager.register_system(Box::new(boarding::BoardingSystem::new()));
+        manager.register_system(Box::new(point_defense::PointDefenseSystem::new()));
+        manager.register_system(Box::new(radar::RadarSystem::new()));
+        manager.register_system(Box::new(stomp::StompSystem::new()));
         manager.register_system(Box::new(resource::ResourceSystem::new()));
+        manager.register_system(Box::new(hazard::HazardSystem::new()));
+        manager.register_system(Box::new(environment::EnvironmentSystem::new()));
+        manager.register_system(Box::new(director::DirectorSystem::new()));
+        manager.register_system(Box::new(camera_director::CameraDirectorSystem::new()));
         manager.register_system(Box::new(networking::NetworkingSystem::new()));
         manager.register_system(Box::new(ai::AISystem::new()));
+        // Scores this tick's events before achievements drain them
+        manager.register_system(Box::new(game_mode::GameModeSystem::new()));
+        // Before achievements drain the events it waits on
+        manager.register_system(Box::new(tutorial::TutorialSystem::new()));
+        // Last, so it sees every event published this tick
+        manager.register_system(Box::new(achievements::AchievementSystem::new()));
 
         manager
     }
@@ -67,7 +108,11 @@ impl SystemManager {
 
         self.tick_count += 1;
 
+        let shed_cosmetics = game.load.sheds_cosmetics();
         for system in &mut self.systems {
+            if shed_cosmetics && system.is_cosmetic() {
+                continue;
+            }
             if system.should_update(game) {
                 let messages = system.update(game, delta_time);
                 all_messages.extend(messages);
@@ -128,6 +173,8 @@ pub struct SystemConfig {
     pub resource_hz: f32,
     pub networking_hz: f32,
     pub ai_hz: f32,
+    /// Share of a game frame one AI update may take before it's flagged
+    pub ai_budget_share: f32,
 }
 
 impl Default for SystemConfig {
@@ -138,6 +185,7 @@ impl Default for SystemConfig {
             resource_hz: 10.0,   // 10 FPS resource spawning
             networking_hz: 20.0, // 20 FPS network broadcasts
             ai_hz: 20.0,         // 20 FPS AI updates
+            ai_budget_share: shared::AI_FRAME_BUDGET_SHARE,
         }
     }
 }
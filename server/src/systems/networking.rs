@@ -44,6 +44,7 @@ impl NetworkingSystem {
                         mech_id: mech.id,
                         position: mech.position,
                         world_position: mech.world_position,
+                        drive: mech.drive,
                     });
                 }
             }
@@ -142,6 +143,7 @@ impl NetworkingSystem {
                     mech_id,
                     position,
                     world_position,
+                    ..
                 } => {
                     // Validate that positions are reasonable
                     if position.x < 0
@@ -172,6 +174,7 @@ impl NetworkingSystem {
 
 impl GameSystem for NetworkingSystem {
     fn update(&mut self, game: &mut Game, delta_time: f32) -> Vec<ServerMessage> {
+        profiling::scope!("networking");
         let mut messages = Vec::new();
         self.frame_count += 1;
 
@@ -1,8 +1,9 @@
 use super::GameSystem;
 use crate::game::Game;
+use crate::movement::MovementResolver;
 use shared::*;
 use uuid::Uuid;
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 
 /// Actions that can be queued for physics processing
 #[derive(Debug, Clone)]
@@ -24,6 +25,10 @@ pub struct PhysicsSystem {
     last_cleanup_time: f32,
     cleanup_interval: f32,
     action_queue: VecDeque<PhysicsAction>,
+    movement: MovementResolver,
+    /// Drive state last sent to clients, so throttle and pivot changes on a
+    /// standing mech still reach the pilot's gauges
+    sent_drives: HashMap<Uuid, MechDrive>,
 }
 
 impl PhysicsSystem {
@@ -32,6 +37,8 @@ impl PhysicsSystem {
             last_cleanup_time: 0.0,
             cleanup_interval: 5.0, // Clean up pools every 5 seconds
             action_queue: VecDeque::new(),
+            movement: MovementResolver::new(),
+            sent_drives: HashMap::new(),
         }
     }
 
@@ -40,8 +47,36 @@ impl PhysicsSystem {
         self.action_queue.push_back(action);
     }
 
+    /// Accelerate or brake each mech toward its throttle setting, as hard as
+    /// its engine can for what it weighs, burning fuel as it goes. Clients
+    /// hear about the tank each whole unit it drops.
+    fn update_mech_drives(&self, game: &mut Game, delta_time: f32) -> Vec<ServerMessage> {
+        let mut messages = Vec::new();
+        let masses: HashMap<Uuid, f32> = game
+            .mechs
+            .keys()
+            .map(|id| (*id, game.mech_mass(*id).total()))
+            .collect();
+        for mech in game.mechs.values_mut() {
+            let before = mech.fuel;
+            mech.fuel = (mech.fuel - mech.drive.fuel_burn(delta_time)).max(0.0);
+            if mech.fuel.ceil() != before.ceil() {
+                messages.push(ServerMessage::MechFuelChanged {
+                    mech_id: mech.id,
+                    fuel: mech.fuel,
+                });
+            }
+
+            let mass = masses.get(&mech.id).copied().unwrap_or(MECH_REFERENCE_MASS);
+            let performance =
+                MechPerformance::new(mech.upgrades.engine_level, mass).fueled(mech.fuel);
+            mech.update_drive(|drive| drive.step(&performance, delta_time));
+        }
+        messages
+    }
+
     /// Update mech positions based on their velocity
-    fn update_mech_positions(&self, game: &mut Game, delta_time: f32) -> Vec<ServerMessage> {
+    fn update_mech_positions(&mut self, game: &mut Game, delta_time: f32) -> Vec<ServerMessage> {
         let mut messages = Vec::new();
 
         // Collect mech velocities for testing manager override
@@ -67,9 +102,10 @@ impl PhysicsSystem {
                 .unwrap_or(mech.velocity);
             
             if effective_velocity.0 != 0.0 || effective_velocity.1 != 0.0 {
+                let speed_multiplier = game.movement_multiplier(mech.id);
                 let desired_movement = (
-                    effective_velocity.0 * TILE_SIZE * delta_time,
-                    effective_velocity.1 * TILE_SIZE * delta_time,
+                    effective_velocity.0 * speed_multiplier * TILE_SIZE * delta_time,
+                    effective_velocity.1 * speed_multiplier * TILE_SIZE * delta_time,
                 );
                 moving_mechs.push((mech.id, mech.world_position, desired_movement));
             }
@@ -88,6 +124,7 @@ impl PhysicsSystem {
         }
 
         // Now apply safe movement (mutable borrow)
+        let (world_width, world_height) = game.arena_map.world_size();
         for (mech_id, current_pos, desired_movement) in moving_mechs {
             if let Some(mech) = game.mechs.get_mut(&mech_id) {
                 let obstacles = obstacles_map.get(&mech_id).unwrap();
@@ -108,12 +145,12 @@ impl PhysicsSystem {
                     .world_position
                     .x
                     .max(0.0)
-                    .min((ARENA_WIDTH_TILES as f32 - MECH_SIZE_TILES as f32) * TILE_SIZE);
+                    .min(world_width - MECH_SIZE_TILES as f32 * TILE_SIZE);
                 mech.world_position.y = mech
                     .world_position
                     .y
                     .max(0.0)
-                    .min((ARENA_HEIGHT_TILES as f32 - MECH_SIZE_TILES as f32) * TILE_SIZE);
+                    .min(world_height - MECH_SIZE_TILES as f32 * TILE_SIZE);
 
                 // Update tile position
                 let new_tile_pos = mech.world_position.to_tile_pos();
@@ -121,16 +158,31 @@ impl PhysicsSystem {
                     mech.position = new_tile_pos;
                 }
 
-                mech_updates.push((mech.id, mech.position, mech.world_position));
+                mech_updates.push(mech.id);
             }
         }
 
+        // Standing mechs whose pilot moved the throttle or pivoted
+        mech_updates.extend(
+            game.mechs
+                .values()
+                .filter(|m| self.sent_drives.get(&m.id) != Some(&m.drive))
+                .map(|m| m.id)
+                .filter(|id| !mech_updates.contains(id))
+                .collect::<Vec<_>>(),
+        );
+
         // Send mech position updates
-        for (mech_id, position, world_position) in mech_updates {
+        for mech_id in mech_updates {
+            let Some(mech) = game.mechs.get(&mech_id) else {
+                continue;
+            };
+            self.sent_drives.insert(mech_id, mech.drive);
             messages.push(ServerMessage::MechMoved {
                 mech_id,
-                position,
-                world_position,
+                position: mech.position,
+                world_position: mech.world_position,
+                drive: mech.drive,
             });
         }
 
@@ -145,7 +197,11 @@ impl PhysicsSystem {
         while let Some(action) = self.action_queue.pop_front() {
             match action {
                 PhysicsAction::PlayerMovement { player_id, movement, .. } => {
-                    if let Some(updated_location) = self.calculate_player_movement(game, player_id, movement, delta_time) {
+                    // Turn even when blocked, so walking into a wall faces it
+                    if let Some(player) = game.players.get_mut(&player_id) {
+                        messages.extend(player.face_towards(movement));
+                    }
+                    if let Some(updated_location) = self.movement.resolve(game, player_id, movement, delta_time) {
                         // Update player position
                         if let Some(player) = game.players.get_mut(&player_id) {
                             player.location = updated_location;
@@ -175,68 +231,6 @@ impl PhysicsSystem {
         messages
     }
 
-    /// Calculate new player position with collision detection
-    fn calculate_player_movement(&self, game: &Game, player_id: Uuid, movement: (f32, f32), delta_time: f32) -> Option<PlayerLocation> {
-        let player = game.players.get(&player_id)?;
-        let movement_speed = shared::balance::PLAYER_MOVE_SPEED;
-        
-        // Calculate movement delta
-        let delta_x = movement.0 * movement_speed * TILE_SIZE * delta_time;
-        let delta_y = movement.1 * movement_speed * TILE_SIZE * delta_time;
-
-        match &player.location {
-            PlayerLocation::OutsideWorld(pos) => {
-                // Check for collisions and calculate safe movement
-                let desired_movement = (delta_x, delta_y);
-                let safe_movement = {
-                    // Create collision obstacles from all mechs
-                    let mut obstacles = Vec::new();
-                    for mech in game.mechs.values() {
-                        obstacles.push(CollisionShape::mech(mech.world_position));
-                    }
-                    
-                    let player_shape = CollisionShape::player(*pos);
-                    CollisionUtils::calculate_safe_movement(
-                        *pos,
-                        desired_movement,
-                        &player_shape,
-                        &obstacles,
-                    )
-                };
-
-                let mut new_pos = *pos;
-                new_pos.x += safe_movement.0;
-                new_pos.y += safe_movement.1;
-
-                // Keep within world bounds
-                new_pos.x = new_pos.x.max(0.0).min((ARENA_WIDTH_TILES as f32) * TILE_SIZE);
-                new_pos.y = new_pos.y.max(0.0).min((ARENA_HEIGHT_TILES as f32) * TILE_SIZE);
-
-                Some(PlayerLocation::OutsideWorld(new_pos))
-            }
-            PlayerLocation::InsideMech { mech_id, pos } => {
-                // Convert to local world position, apply movement, then convert back
-                let mut new_world_pos = pos.to_local_world();
-                new_world_pos.x += delta_x;
-                new_world_pos.y += delta_y;
-
-                // Keep within proper mech floor bounds
-                let floor_width_pixels = (shared::FLOOR_WIDTH_TILES as f32) * TILE_SIZE;
-                let floor_height_pixels = (shared::FLOOR_HEIGHT_TILES as f32) * TILE_SIZE;
-                new_world_pos.x = new_world_pos.x.max(0.0).min(floor_width_pixels);
-                new_world_pos.y = new_world_pos.y.max(0.0).min(floor_height_pixels);
-
-                // Convert back to MechInteriorPos, preserving floor
-                let new_pos = MechInteriorPos::new(pos.floor(), new_world_pos.to_tile());
-
-                Some(PlayerLocation::InsideMech {
-                    mech_id: *mech_id,
-                    pos: new_pos,
-                })
-            }
-        }
-    }
-
     /// Check for tile events at player position
     fn check_tile_events(&self, game: &mut Game, player_id: Uuid, pos: WorldPos) {
         let tile_pos = pos.to_tile();
@@ -290,8 +284,17 @@ impl PhysicsSystem {
                 for (mech_id, mech) in &game.mechs {
                     let doors = shared::coordinates::MechDoorPositions::from_mech_position(mech.position);
                     if tile_pos == doors.left_door || tile_pos == doors.right_door {
-                        // Check team access
-                        if mech.team == player.team {
+                        // Crew walk in; enemies only get through doors broken by damage
+                        let boarding = mech.team != player.team;
+                        if !boarding
+                            || shared::coordinates::MechDoorPositions::breached(
+                                mech.health,
+                                mech.max_health,
+                            )
+                        {
+                            if boarding {
+                                log::info!("Player {player_id} boarded enemy mech {mech_id}");
+                            }
                             let entry_world_pos = doors.get_entry_position(tile_pos);
                             let entry_pos = MechInteriorPos::new(0, entry_world_pos.to_tile());
                             entry_info = Some((*mech_id, entry_pos));
@@ -356,19 +359,23 @@ impl PhysicsSystem {
 
     /// Apply physics constraints and limits
     fn apply_physics_constraints(&self, game: &mut Game) {
-        // Apply velocity decay to mechs (friction)
+        // Apply velocity decay to knockback and other impulses (friction);
+        // the drive's own momentum is handled by its braking
         for mech in game.mechs.values_mut() {
             let decay = 0.95; // 5% velocity decay per frame
-            mech.velocity.0 *= decay;
-            mech.velocity.1 *= decay;
+            let drive = mech.drive.velocity();
+            let mut impulse = (mech.velocity.0 - drive.0, mech.velocity.1 - drive.1);
+            impulse.0 *= decay;
+            impulse.1 *= decay;
 
             // Stop very slow movement to prevent jitter
-            if mech.velocity.0.abs() < 0.01 {
-                mech.velocity.0 = 0.0;
+            if impulse.0.abs() < 0.01 {
+                impulse.0 = 0.0;
             }
-            if mech.velocity.1.abs() < 0.01 {
-                mech.velocity.1 = 0.0;
+            if impulse.1.abs() < 0.01 {
+                impulse.1 = 0.0;
             }
+            mech.velocity = (drive.0 + impulse.0, drive.1 + impulse.1);
         }
     }
 
@@ -376,6 +383,8 @@ impl PhysicsSystem {
     fn cleanup_pools(&mut self, game: &mut Game, current_time: f32) {
         if current_time - self.last_cleanup_time >= self.cleanup_interval {
             game.cleanup_pools();
+            self.movement.retain_players(game);
+            self.sent_drives.retain(|id, _| game.mechs.contains_key(id));
             self.last_cleanup_time = current_time;
         }
     }
@@ -383,6 +392,7 @@ impl PhysicsSystem {
 
 impl GameSystem for PhysicsSystem {
     fn update(&mut self, game: &mut Game, delta_time: f32) -> Vec<ServerMessage> {
+        profiling::scope!("physics");
         let mut messages = Vec::new();
 
         // Process queued player movements first
@@ -394,6 +404,7 @@ impl GameSystem for PhysicsSystem {
         messages.extend(pooled_messages);
 
         // Update mech positions
+        messages.extend(self.update_mech_drives(game, delta_time));
         let mech_messages = self.update_mech_positions(game, delta_time);
         messages.extend(mech_messages);
 
@@ -436,3 +447,105 @@ impl Default for PhysicsSystem {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_throttle_builds_speed_along_heading() {
+        let mut game = Game::new();
+        let mech_id = *game.mechs.keys().next().unwrap();
+        let start = game.mechs[&mech_id].world_position;
+        game.mechs
+            .get_mut(&mech_id)
+            .unwrap()
+            .update_drive(|drive| drive.throttle = 1.0);
+
+        let mut physics = PhysicsSystem::new();
+        physics.update(&mut game, 0.1);
+        let first_speed = game.mechs[&mech_id].drive.speed;
+        assert!(first_speed > 0.0 && first_speed < MechDrive::max_speed(1));
+
+        for _ in 0..20 {
+            physics.update(&mut game, 0.1);
+        }
+        let mech = &game.mechs[&mech_id];
+        assert!(mech.drive.speed > first_speed);
+        assert!(mech.world_position.x > start.x);
+        assert_eq!(mech.world_position.y, start.y);
+    }
+
+    #[test]
+    fn test_running_dry_slows_the_mech_to_a_crawl() {
+        let mut game = Game::new();
+        let mech_id = *game.mechs.keys().next().unwrap();
+        let mech = game.mechs.get_mut(&mech_id).unwrap();
+        mech.fuel = MECH_FUEL_BURN_RATE * 0.5;
+        mech.update_drive(|drive| drive.throttle = 1.0);
+
+        let physics = PhysicsSystem::new();
+        let messages = physics.update_mech_drives(&mut game, 1.0);
+        assert!(matches!(
+            messages[..],
+            [ServerMessage::MechFuelChanged { fuel, .. }] if fuel == 0.0
+        ));
+        for _ in 0..100 {
+            physics.update_mech_drives(&mut game, 0.1);
+        }
+        let dry = MechPerformance::new(1, game.mech_mass(mech_id).total())
+            .fueled(0.0)
+            .max_speed;
+        assert!((game.mechs[&mech_id].drive.speed - dry).abs() < 1e-4);
+
+        // A battery puts the tank back in business
+        let player_id = Uuid::new_v4();
+        game.deliver_resource(player_id, mech_id, ResourceType::Batteries);
+        assert_eq!(game.mechs[&mech_id].fuel, FUEL_PER_BATTERY);
+        assert!(game.mechs[&mech_id].resource_inventory.is_empty());
+    }
+
+    #[test]
+    fn test_a_loaded_hold_slows_the_mech() {
+        let mut game = Game::new();
+        let mut ids = game.mechs.keys().copied();
+        let (light, loaded) = (ids.next().unwrap(), ids.next().unwrap());
+        let light_mass = game.mech_mass(light).total();
+        for id in [light, loaded] {
+            game.mechs
+                .get_mut(&id)
+                .unwrap()
+                .update_drive(|drive| drive.throttle = 1.0);
+        }
+        game.mechs
+            .get_mut(&loaded)
+            .unwrap()
+            .resource_inventory
+            .insert(ResourceType::ScrapMetal, 200);
+        assert!(game.mech_mass(loaded).total() > light_mass);
+
+        let physics = PhysicsSystem::new();
+        physics.update_mech_drives(&mut game, 0.5);
+        assert!(game.mechs[&loaded].drive.speed < game.mechs[&light].drive.speed);
+    }
+
+    #[test]
+    fn test_knockback_decays_without_stopping_the_drive() {
+        let mut game = Game::new();
+        let mech_id = *game.mechs.keys().next().unwrap();
+        let mech = game.mechs.get_mut(&mech_id).unwrap();
+        mech.update_drive(|drive| {
+            drive.throttle = 1.0;
+            drive.speed = MechDrive::max_speed(1);
+        });
+        mech.velocity.1 += PROJECTILE_KNOCKBACK_SPEED;
+
+        let physics = PhysicsSystem::new();
+        for _ in 0..200 {
+            physics.apply_physics_constraints(&mut game);
+        }
+        let mech = &game.mechs[&mech_id];
+        assert_eq!(mech.velocity, mech.drive.velocity());
+        assert!(mech.velocity.0 > 0.0);
+    }
+}
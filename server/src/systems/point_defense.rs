@@ -0,0 +1,215 @@
+use super::GameSystem;
+use crate::game::Game;
+use shared::*;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Point-defense batteries: interceptors fired at enemy shots closing on the
+/// mech. On auto a battery engages by itself; the operator can also fire at
+/// the nearest threat by hand. The combat system decides what they hit.
+pub struct PointDefenseSystem {
+    /// Interceptors in flight and the shot each was sent after, so two
+    /// rounds aren't wasted on the same one
+    engaged: HashMap<Uuid, Uuid>,
+}
+
+impl PointDefenseSystem {
+    pub fn new() -> Self {
+        Self {
+            engaged: HashMap::new(),
+        }
+    }
+
+    /// Fire one interceptor from a mech at the closest enemy shot heading
+    /// its way that nothing is already chasing
+    pub fn fire(&mut self, game: &mut Game, mech_id: Uuid) -> GameResult<Vec<ServerMessage>> {
+        if let Some(effect) = game
+            .entity_storage
+            .status_effects
+            .get(&mech_id)
+            .and_then(|effects| effects.blocks_station_use())
+        {
+            return Err(GameError::BlockedByStatusEffect {
+                id: mech_id,
+                effect,
+            });
+        }
+        let mech = game
+            .mechs
+            .get(&mech_id)
+            .ok_or_else(|| GameError::mech_not_found(mech_id))?;
+        if mech.point_defense.ammo == 0 {
+            return Err(GameError::invalid_state(
+                "Point defense is out of interceptors",
+            ));
+        }
+        if !mech.point_defense.ready() {
+            return Err(GameError::WeaponOnCooldown {
+                remaining_seconds: mech.point_defense.cooldown,
+            });
+        }
+
+        let origin = MechPositioning::mech_center(mech.position);
+        let team = mech.team;
+        let (target_id, aim) = game
+            .projectiles
+            .values()
+            .filter(|shot| {
+                shot.is_active()
+                    && shot.weapon_type != WeaponType::Interceptor
+                    && !self.engaged.values().any(|target| *target == shot.id)
+                    && game
+                        .mechs
+                        .get(&shot.owner_mech_id)
+                        .is_some_and(|owner| owner.team != team)
+                    && incoming(origin, shot.position, shot.velocity)
+            })
+            .filter_map(|shot| {
+                let aim = intercept_point(origin, shot.position, shot.velocity, INTERCEPTOR_SPEED)?;
+                (aim.distance_to(origin) <= POINT_DEFENSE_RANGE * TILE_SIZE)
+                    .then_some((shot.id, aim))
+            })
+            .min_by(|(_, a), (_, b)| {
+                a.distance_to(origin)
+                    .partial_cmp(&b.distance_to(origin))
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+            .ok_or(GameError::NoValidTarget)?;
+
+        let Some(mech) = game.mechs.get_mut(&mech_id) else {
+            return Err(GameError::mech_not_found(mech_id));
+        };
+        mech.point_defense.fire();
+        let battery = mech.point_defense;
+
+        let distance = aim.distance_to(origin).max(1.0);
+        let velocity = (
+            (aim.x - origin.x) / distance * INTERCEPTOR_SPEED,
+            (aim.y - origin.y) / distance * INTERCEPTOR_SPEED,
+        );
+        // Lives just past the meeting point, so a miss doesn't fly on forever
+        let lifetime = distance / INTERCEPTOR_SPEED + POINT_DEFENSE_COOLDOWN;
+        let interceptor_id = game.create_projectile(
+            origin,
+            velocity,
+            0,
+            mech_id,
+            lifetime,
+            WeaponType::Interceptor,
+        );
+        self.engaged.insert(interceptor_id, target_id);
+
+        Ok(vec![
+            ServerMessage::WeaponFired {
+                mech_id,
+                weapon_type: StationType::PointDefense,
+                target_position: aim.to_tile(),
+                projectile_id: Some(interceptor_id),
+            },
+            ServerMessage::PointDefenseChanged {
+                mech_id,
+                point_defense: battery,
+            },
+        ])
+    }
+}
+
+/// A shot at `position` flying with `velocity` is getting closer to `origin`
+fn incoming(origin: WorldPos, position: WorldPos, velocity: (f32, f32)) -> bool {
+    (origin.x - position.x) * velocity.0 + (origin.y - position.y) * velocity.1 > 0.0
+}
+
+impl GameSystem for PointDefenseSystem {
+    fn update(&mut self, game: &mut Game, delta_time: f32) -> Vec<ServerMessage> {
+        profiling::scope!("point_defense");
+        self.engaged.retain(|interceptor, target| {
+            game.projectiles.contains_key(interceptor) && game.projectiles.contains_key(target)
+        });
+
+        let mut on_auto = Vec::new();
+        for mech in game.mechs.values_mut() {
+            mech.point_defense.reload(delta_time);
+            let has_battery = mech
+                .stations
+                .values()
+                .any(|station| station.station_type == StationType::PointDefense);
+            if has_battery && mech.point_defense.auto && mech.point_defense.ready() {
+                on_auto.push(mech.id);
+            }
+        }
+
+        // Nothing to shoot at is the usual case, not worth reporting
+        on_auto
+            .into_iter()
+            .filter_map(|mech_id| self.fire(game, mech_id).ok())
+            .flatten()
+            .collect()
+    }
+
+    fn name(&self) -> &'static str {
+        "point_defense"
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_auto_battery_engages_each_incoming_shot_once() {
+        let mut game = Game::new();
+        let mut point_defense = PointDefenseSystem::new();
+        let (mech_id, enemy_id, center) = {
+            let mut mechs = game.mechs.values();
+            let ours = mechs.next().unwrap();
+            let theirs = mechs.find(|m| m.team != ours.team).unwrap();
+            (
+                ours.id,
+                theirs.id,
+                MechPositioning::mech_center(ours.position),
+            )
+        };
+
+        // One shot closing in from the east, one flying away to the west
+        let start = WorldPos::new(center.x + 6.0 * TILE_SIZE, center.y);
+        let incoming = game.create_projectile(
+            start,
+            (-PROJECTILE_BASE_SPEED, 0.0),
+            20,
+            enemy_id,
+            PROJECTILE_LIFETIME,
+            WeaponType::Projectile,
+        );
+        game.create_projectile(
+            WorldPos::new(center.x - 2.0 * TILE_SIZE, center.y),
+            (-PROJECTILE_BASE_SPEED, 0.0),
+            20,
+            enemy_id,
+            PROJECTILE_LIFETIME,
+            WeaponType::Projectile,
+        );
+
+        let messages = point_defense.update(&mut game, 0.0);
+        assert!(messages.iter().any(|m| matches!(
+            m,
+            ServerMessage::WeaponFired {
+                weapon_type: StationType::PointDefense,
+                ..
+            }
+        )));
+        assert_eq!(point_defense.engaged.values().next(), Some(&incoming));
+        assert_eq!(
+            game.mechs[&mech_id].point_defense.ammo,
+            POINT_DEFENSE_MAGAZINE - 1
+        );
+
+        // Once it's cooled down there's nothing new left to shoot at
+        let messages = point_defense.update(&mut game, POINT_DEFENSE_COOLDOWN);
+        assert!(messages.is_empty());
+        assert_eq!(point_defense.engaged.len(), 1);
+    }
+}
@@ -0,0 +1,193 @@
+use super::GameSystem;
+use crate::game::Game;
+use rand::Rng;
+use shared::*;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Radar sweeps: spotting enemy mechs past what the crew can see. A sweep
+/// costs batteries and shows the team blips, not full vision, and enemy ECM
+/// shortens its reach and throws the blips off.
+pub struct RadarSystem {
+    /// Seconds until each mech's radar can sweep again
+    cooldowns: HashMap<Uuid, f32>,
+}
+
+impl RadarSystem {
+    pub fn new() -> Self {
+        Self {
+            cooldowns: HashMap::new(),
+        }
+    }
+
+    /// Sweep from a mech, spending its batteries
+    pub fn sweep(&mut self, game: &mut Game, mech_id: Uuid) -> GameResult<Vec<ServerMessage>> {
+        if let Some(remaining) = self.cooldowns.get(&mech_id) {
+            return Err(GameError::WeaponOnCooldown {
+                remaining_seconds: *remaining,
+            });
+        }
+        let mech = game
+            .mechs
+            .get_mut(&mech_id)
+            .ok_or_else(|| GameError::mech_not_found(mech_id))?;
+        let cost = HashMap::from([(ResourceType::Batteries, RADAR_SWEEP_BATTERY_COST)]);
+        if !mech.try_spend(&cost) {
+            return Err(GameError::InsufficientResources {
+                resource_type: ResourceType::Batteries,
+                required: RADAR_SWEEP_BATTERY_COST,
+                available: mech
+                    .resource_inventory
+                    .get(&ResourceType::Batteries)
+                    .copied()
+                    .unwrap_or(0),
+            });
+        }
+        let inventory = mech.inventory_message();
+        let team = mech.team;
+        let origin = MechPositioning::mech_center(mech.position);
+        self.cooldowns.insert(mech_id, RADAR_SWEEP_COOLDOWN);
+
+        let rng = &mut game.rng;
+        let mut enemies: Vec<&crate::game::Mech> = game
+            .mechs
+            .values()
+            .filter(|other| other.team != team)
+            .collect();
+        enemies.sort_unstable_by_key(|other| other.id);
+        let blips = enemies
+            .into_iter()
+            .filter_map(|other| {
+                let ecm_level = other.upgrades.ecm_level as f32;
+                let center = MechPositioning::mech_center(other.position);
+                let range = (RADAR_RANGE - ecm_level * RADAR_ECM_RANGE_REDUCTION) * TILE_SIZE;
+                if center.distance_to(origin) > range {
+                    return None;
+                }
+                let error = ecm_level * RADAR_ECM_BLIP_ERROR * TILE_SIZE;
+                if error <= 0.0 {
+                    return Some(center);
+                }
+                Some(WorldPos::new(
+                    center.x + rng.gen_range(-error..=error),
+                    center.y + rng.gen_range(-error..=error),
+                ))
+            })
+            .collect::<Vec<_>>();
+
+        log::debug!("Mech {mech_id} swept radar, {} contacts", blips.len());
+        Ok(vec![
+            inventory,
+            ServerMessage::RadarSweep {
+                mech_id,
+                team,
+                blips,
+                duration: RADAR_BLIP_DURATION,
+            },
+        ])
+    }
+}
+
+impl GameSystem for RadarSystem {
+    fn update(&mut self, game: &mut Game, delta_time: f32) -> Vec<ServerMessage> {
+        profiling::scope!("radar");
+        for remaining in self.cooldowns.values_mut() {
+            *remaining -= delta_time;
+        }
+        self.cooldowns
+            .retain(|mech_id, remaining| *remaining > 0.0 && game.mechs.contains_key(mech_id));
+        Vec::new()
+    }
+
+    fn name(&self) -> &'static str {
+        "radar"
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blips(messages: &[ServerMessage]) -> Vec<WorldPos> {
+        messages
+            .iter()
+            .find_map(|message| match message {
+                ServerMessage::RadarSweep { blips, .. } => Some(blips.clone()),
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_sweep_costs_batteries_and_cools_down() {
+        let mut game = Game::new();
+        let mut radar = RadarSystem::new();
+        let (mech_id, enemy_id) = {
+            let mut mechs = game.mechs.values();
+            let first = mechs.next().unwrap();
+            let enemy = mechs.find(|m| m.team != first.team).unwrap();
+            (first.id, enemy.id)
+        };
+        game.mechs.get_mut(&enemy_id).unwrap().position = game.mechs[&mech_id].position;
+        game.mechs
+            .get_mut(&mech_id)
+            .unwrap()
+            .resource_inventory
+            .insert(ResourceType::Batteries, 1);
+
+        let messages = radar.sweep(&mut game, mech_id).unwrap();
+        let enemy_center = MechPositioning::mech_center(game.mechs[&enemy_id].position);
+        assert_eq!(blips(&messages), vec![enemy_center]);
+        assert!(matches!(
+            radar.sweep(&mut game, mech_id),
+            Err(GameError::WeaponOnCooldown { .. })
+        ));
+
+        radar.update(&mut game, RADAR_SWEEP_COOLDOWN);
+        assert!(matches!(
+            radar.sweep(&mut game, mech_id),
+            Err(GameError::InsufficientResources { .. })
+        ));
+    }
+
+    #[test]
+    fn test_ecm_shortens_range_and_jitters_blips() {
+        let mut game = Game::new();
+        let mut radar = RadarSystem::new();
+        let (mech_id, enemy_id) = {
+            let mut mechs = game.mechs.values();
+            let first = mechs.next().unwrap();
+            let enemy = mechs.find(|m| m.team != first.team).unwrap();
+            (first.id, enemy.id)
+        };
+        game.mechs
+            .get_mut(&mech_id)
+            .unwrap()
+            .resource_inventory
+            .insert(ResourceType::Batteries, 2);
+        let origin = game.mechs[&mech_id].position;
+        let enemy = game.mechs.get_mut(&enemy_id).unwrap();
+        enemy.upgrades.ecm_level = 1;
+        enemy.position = TilePos::new(
+            origin.x + (RADAR_RANGE - RADAR_ECM_RANGE_REDUCTION / 2.0) as i32,
+            origin.y,
+        );
+
+        // Inside plain radar range but past what the jammed sweep reaches
+        assert!(blips(&radar.sweep(&mut game, mech_id).unwrap()).is_empty());
+
+        radar.update(&mut game, RADAR_SWEEP_COOLDOWN);
+        let enemy = game.mechs.get_mut(&enemy_id).unwrap();
+        enemy.position = origin;
+        let enemy_center = MechPositioning::mech_center(origin);
+        let found = blips(&radar.sweep(&mut game, mech_id).unwrap());
+        assert_eq!(found.len(), 1);
+        let error = RADAR_ECM_BLIP_ERROR * TILE_SIZE;
+        assert!((found[0].x - enemy_center.x).abs() <= error);
+        assert!((found[0].y - enemy_center.y).abs() <= error);
+    }
+}
@@ -0,0 +1,274 @@
+use super::GameSystem;
+use crate::game::Game;
+use shared::*;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Mechs crashing into each other. Hulls that meet while closing faster
+/// than `RAM_MIN_CLOSING_SPEED` both take damage by how fast they came
+/// together, through shields and the armor on the side that was hit, and
+/// bounce apart.
+/// A pilot who sees it coming can brace, halving what their mech takes.
+pub struct RammingSystem {
+    /// Seconds until each mech can brace again
+    brace_cooldowns: HashMap<Uuid, f32>,
+    /// Seconds until two mechs that just collided can hurt each other
+    /// again, keyed smaller id first
+    recent: HashMap<(Uuid, Uuid), f32>,
+}
+
+impl RammingSystem {
+    pub fn new() -> Self {
+        Self {
+            brace_cooldowns: HashMap::new(),
+            recent: HashMap::new(),
+        }
+    }
+
+    /// Brace a mech for `BRACE_DURATION`
+    pub fn brace(&mut self, game: &mut Game, mech_id: Uuid) -> GameResult<Vec<ServerMessage>> {
+        if let Some(remaining) = self.brace_cooldowns.get(&mech_id) {
+            return Err(GameError::WeaponOnCooldown {
+                remaining_seconds: *remaining,
+            });
+        }
+        if !game.mechs.contains_key(&mech_id) {
+            return Err(GameError::mech_not_found(mech_id));
+        }
+        self.brace_cooldowns.insert(mech_id, BRACE_COOLDOWN);
+        Ok(vec![game.apply_status_effect(
+            mech_id,
+            StatusEffectKind::Braced,
+            BRACE_DURATION,
+            BRACE_RAM_MULTIPLIER,
+        )])
+    }
+
+    /// Pairs of mechs with touching hulls, the way from the first to the
+    /// second and how fast they're closing along it
+    fn contacts(game: &Game) -> Vec<(Uuid, Uuid, (f32, f32), f32)> {
+        let reach = (MECH_SIZE_TILES as f32 + RAM_CONTACT_MARGIN) * TILE_SIZE;
+        let mut mechs: Vec<&crate::game::Mech> =
+            game.mechs.values().filter(|mech| mech.health > 0).collect();
+        mechs.sort_unstable_by_key(|mech| mech.id);
+
+        let mut contacts = Vec::new();
+        for (i, a) in mechs.iter().enumerate() {
+            for b in &mechs[i + 1..] {
+                let dx = b.world_position.x - a.world_position.x;
+                let dy = b.world_position.y - a.world_position.y;
+                if dx.abs() > reach || dy.abs() > reach {
+                    continue;
+                }
+                let distance = (dx * dx + dy * dy).sqrt();
+                if distance <= f32::EPSILON {
+                    continue;
+                }
+                let normal = (dx / distance, dy / distance);
+                let closing = (a.velocity.0 - b.velocity.0) * normal.0
+                    + (a.velocity.1 - b.velocity.1) * normal.1;
+                contacts.push((a.id, b.id, normal, closing));
+            }
+        }
+        contacts
+    }
+}
+
+/// Hurt both mechs for a collision and throw them apart. `normal` points
+/// from `a` toward `b`.
+fn ram(
+    game: &mut Game,
+    a: Uuid,
+    b: Uuid,
+    normal: (f32, f32),
+    closing_speed: f32,
+) -> Vec<ServerMessage> {
+    let Some(mech_a) = game.mechs.get(&a) else {
+        return Vec::new();
+    };
+    let half = MECH_SIZE_TILES as f32 * TILE_SIZE / 2.0;
+    let position = WorldPos::new(
+        mech_a.world_position.x + half + normal.0 * half,
+        mech_a.world_position.y + half + normal.1 * half,
+    );
+    let mut messages = vec![ServerMessage::MechsCollided {
+        mech_ids: (a, b),
+        position,
+        closing_speed,
+    }];
+
+    let base = closing_speed * RAM_DAMAGE_PER_SPEED;
+    // Each is struck coming in from the other's side
+    for (mech_id, toward_other) in [(a, normal), (b, (-normal.0, -normal.1))] {
+        let braced = game
+            .entity_storage
+            .status_effects
+            .get(&mech_id)
+            .and_then(|effects| {
+                effects
+                    .effects
+                    .iter()
+                    .find(|effect| effect.kind == StatusEffectKind::Braced)
+            })
+            .map_or(1.0, |effect| effect.magnitude);
+        let Some(mech) = game.mechs.get_mut(&mech_id) else {
+            continue;
+        };
+        let hit = HitDirection::new(mech.drive.heading, (-toward_other.0, -toward_other.1));
+        // Stopped dead against the other hull, then knocked back off it
+        mech.update_drive(|drive| drive.speed = 0.0);
+        let into = (mech.velocity.0 * toward_other.0 + mech.velocity.1 * toward_other.1).max(0.0);
+        mech.velocity.0 -= toward_other.0 * (into + RAM_BOUNCE_SPEED);
+        mech.velocity.1 -= toward_other.1 * (into + RAM_BOUNCE_SPEED);
+        let other = if mech_id == a { b } else { a };
+        game.damage_mech(
+            mech_id,
+            (base * braced).round() as u32,
+            position,
+            Some(hit),
+            Some(other),
+            &mut messages,
+        );
+    }
+
+    log::debug!("Mechs {a} and {b} collided at {closing_speed:.1} tiles/s");
+    messages
+}
+
+impl GameSystem for RammingSystem {
+    fn update(&mut self, game: &mut Game, delta_time: f32) -> Vec<ServerMessage> {
+        profiling::scope!("ramming");
+        for remaining in self.brace_cooldowns.values_mut() {
+            *remaining -= delta_time;
+        }
+        self.brace_cooldowns
+            .retain(|mech_id, remaining| *remaining > 0.0 && game.mechs.contains_key(mech_id));
+        for remaining in self.recent.values_mut() {
+            *remaining -= delta_time;
+        }
+        self.recent.retain(|_, remaining| *remaining > 0.0);
+
+        let mut messages = Vec::new();
+        for (a, b, normal, closing_speed) in Self::contacts(game) {
+            if closing_speed < RAM_MIN_CLOSING_SPEED || self.recent.contains_key(&(a, b)) {
+                continue;
+            }
+            self.recent.insert((a, b), RAM_COOLDOWN);
+            messages.extend(ram(game, a, b, normal, closing_speed));
+        }
+        messages
+    }
+
+    fn name(&self) -> &'static str {
+        "ramming"
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+impl Default for RammingSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two mechs hull to hull along x, the first driving into the second
+    fn head_on(game: &mut Game) -> (Uuid, Uuid) {
+        let mut ids: Vec<Uuid> = game.mechs.keys().copied().collect();
+        ids.sort_unstable();
+        let (a, b) = (ids[0], ids[1]);
+        let width = MECH_SIZE_TILES as f32 * TILE_SIZE;
+        for (id, x, heading) in [(a, 0.0, 0.0), (b, width, std::f32::consts::PI)] {
+            let mech = game.mechs.get_mut(&id).unwrap();
+            mech.world_position = WorldPos::new(20.0 * TILE_SIZE + x, 20.0 * TILE_SIZE);
+            mech.position = mech.world_position.to_tile_pos();
+            mech.drive = MechDrive {
+                throttle: 0.0,
+                heading,
+                speed: 0.0,
+            };
+            mech.velocity = (0.0, 0.0);
+            // Bare hulls, so the armor shows in what they take
+            mech.shield = 0;
+        }
+        game.mechs.get_mut(&a).unwrap().velocity = (2.0, 0.0);
+        (a, b)
+    }
+
+    /// What each mech was told it took
+    fn damage_to(messages: &[ServerMessage], mech: Uuid) -> Option<u32> {
+        messages.iter().find_map(|msg| match msg {
+            ServerMessage::MechDamaged {
+                mech_id, damage, ..
+            } if *mech_id == mech => Some(*damage),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn test_rams_hurt_both_mechs_once_and_bracing_halves_it() {
+        let mut game = Game::new();
+        let mut ramming = RammingSystem::new();
+        let (a, b) = head_on(&mut game);
+
+        let messages = ramming.update(&mut game, 0.0);
+        assert!(messages
+            .iter()
+            .any(|msg| matches!(msg, ServerMessage::MechsCollided { .. })));
+        // Nose to nose, both take it on the front armor
+        let expected = (2.0 * RAM_DAMAGE_PER_SPEED * FRONT_ARMOR_MULTIPLIER).round() as u32;
+        assert_eq!(damage_to(&messages, a), Some(expected));
+        assert_eq!(damage_to(&messages, b), Some(expected));
+        assert!(
+            game.mechs[&a].velocity.0 < 0.0,
+            "Rams bounce the mechs apart"
+        );
+
+        // Still touching, but the same two can't hurt each other again yet
+        head_on(&mut game);
+        assert!(ramming.update(&mut game, 0.1).is_empty());
+
+        game.mechs.get_mut(&a).unwrap().velocity = (0.0, 0.0);
+        assert!(ramming.update(&mut game, RAM_COOLDOWN).is_empty());
+        ramming.brace(&mut game, b).unwrap();
+        assert!(matches!(
+            ramming.brace(&mut game, b),
+            Err(GameError::WeaponOnCooldown { .. })
+        ));
+        head_on(&mut game);
+        let messages = ramming.update(&mut game, 0.0);
+        let braced = (expected as f32 * BRACE_RAM_MULTIPLIER).round() as u32;
+        assert_eq!(damage_to(&messages, a), Some(expected));
+        assert_eq!(damage_to(&messages, b), Some(braced));
+    }
+
+    #[test]
+    fn test_shields_soak_rams_before_the_hull() {
+        let mut game = Game::new();
+        let mut ramming = RammingSystem::new();
+        let (a, _) = head_on(&mut game);
+        let mech = game.mechs.get_mut(&a).unwrap();
+        mech.shield = MECH_INITIAL_SHIELD;
+        let health = mech.health;
+
+        ramming.update(&mut game, 0.0);
+        let rammed = (2.0 * RAM_DAMAGE_PER_SPEED).round() as u32;
+        assert_eq!(game.mechs[&a].shield, MECH_INITIAL_SHIELD - rammed);
+        assert_eq!(game.mechs[&a].health, health);
+    }
+
+    #[test]
+    fn test_slow_bumps_do_no_damage() {
+        let mut game = Game::new();
+        let mut ramming = RammingSystem::new();
+        let (a, _) = head_on(&mut game);
+        game.mechs.get_mut(&a).unwrap().velocity = (RAM_MIN_CLOSING_SPEED * 0.5, 0.0);
+        assert!(ramming.update(&mut game, 0.0).is_empty());
+    }
+}
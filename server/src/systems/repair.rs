@@ -0,0 +1,332 @@
+use super::GameSystem;
+use crate::game::Game;
+use crate::movement::tiles_in_reach;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use shared::components::{Fire, RepairChannel, StatusEffectKind};
+use shared::tile_entity::StaticTile;
+use shared::*;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Damage control inside mechs: spreading fires, crew burns and hull repairs
+pub struct RepairSystem {
+    /// Hull repairs in progress, keyed by the player channelling them
+    channels: HashMap<Uuid, RepairChannel>,
+    /// Seconds each crew member has spent standing in fire
+    fire_exposure: HashMap<Uuid, f32>,
+}
+
+impl RepairSystem {
+    pub fn new() -> Self {
+        Self {
+            channels: HashMap::new(),
+            fire_exposure: HashMap::new(),
+        }
+    }
+
+    /// Action key handling for tool lockers, extinguishers and repair tools.
+    /// Returns no messages when there was nothing in reach to use.
+    pub fn interact(&mut self, game: &mut Game, player_id: Uuid) -> Vec<ServerMessage> {
+        let Some(player) = game.players.get(&player_id) else {
+            return Vec::new();
+        };
+        let PlayerLocation::InsideMech { mech_id, pos } = player.location else {
+            return Vec::new();
+        };
+        let held_tool = player.held_tool;
+        let Some(floor_map) = game
+            .mechs
+            .get(&mech_id)
+            .and_then(|mech| mech.interior.floors.get(pos.floor as usize))
+        else {
+            return Vec::new();
+        };
+        let in_reach = tiles_in_reach(pos.tile_pos);
+
+        // Lockers swap tools: take theirs, or put ours back
+        let locker_tool = in_reach.iter().find_map(|tile| match floor_map.static_tiles.get(tile) {
+            Some(StaticTile::ToolLocker { tool }) => Some(*tool),
+            _ => None,
+        });
+        if let Some(tool) = locker_tool {
+            let new_tool = if held_tool == Some(tool) {
+                None
+            } else {
+                Some(tool)
+            };
+            let mut messages = self.cancel_channel(player_id).into_iter().collect::<Vec<_>>();
+            if let Some(player) = game.players.get_mut(&player_id) {
+                player.held_tool = new_tool;
+            }
+            messages.push(ServerMessage::PlayerToolChanged {
+                player_id,
+                tool: new_tool,
+            });
+            return messages;
+        }
+
+        match held_tool {
+            Some(ToolType::Extinguisher) => {
+                let Some(mech) = game.mechs.get_mut(&mech_id) else {
+                    return Vec::new();
+                };
+                let targets: Vec<MechInteriorPos> = in_reach
+                    .iter()
+                    .map(|tile| MechInteriorPos::new(pos.floor, *tile))
+                    .filter(|fire_pos| mech.fires.contains_key(fire_pos))
+                    .collect();
+                if targets.is_empty() {
+                    return Vec::new();
+                }
+
+                for fire_pos in targets {
+                    let out = mech
+                        .fires
+                        .get_mut(&fire_pos)
+                        .is_some_and(|fire| fire.douse(EXTINGUISHER_POWER));
+                    if out {
+                        mech.fires.remove(&fire_pos);
+                    }
+                }
+                vec![ServerMessage::MechFiresUpdated {
+                    mech_id,
+                    fires: mech.fire_states(),
+                }]
+            }
+            Some(ToolType::RepairTool) => {
+                let Some(target) = in_reach.iter().copied().find(|tile| {
+                    matches!(floor_map.static_tiles.get(tile), Some(StaticTile::DamagedWall))
+                }) else {
+                    return Vec::new();
+                };
+                if self.channels.contains_key(&player_id) {
+                    // Already patching; pressing again shouldn't restart the channel
+                    return Vec::new();
+                }
+
+                self.channels.insert(
+                    player_id,
+                    RepairChannel {
+                        mech_id,
+                        floor: pos.floor,
+                        target,
+                        progress: 0.0,
+                    },
+                );
+                vec![ServerMessage::RepairProgress {
+                    player_id,
+                    mech_id,
+                    progress: Some(0.0),
+                }]
+            }
+            None => Vec::new(),
+        }
+    }
+
+    fn cancel_channel(&mut self, player_id: Uuid) -> Option<ServerMessage> {
+        self.channels
+            .remove(&player_id)
+            .map(|channel| ServerMessage::RepairProgress {
+                player_id,
+                mech_id: channel.mech_id,
+                progress: None,
+            })
+    }
+
+    /// Grow fires and let strong ones spread to neighbouring floor tiles
+    fn update_fires(&mut self, game: &mut Game, delta_time: f32) -> Vec<ServerMessage> {
+        let mut messages = Vec::new();
+        let rng = &mut game.rng;
+
+        // Roll in a fixed order so the same seed spreads the same fires
+        let mut mechs: Vec<&mut crate::game::Mech> = game.mechs.values_mut().collect();
+        mechs.sort_unstable_by_key(|mech| mech.id);
+        for mech in mechs {
+            if mech.fires.is_empty() {
+                continue;
+            }
+
+            let mut burning: Vec<MechInteriorPos> = mech
+                .fires
+                .iter_mut()
+                .filter_map(|(pos, fire)| fire.burn(delta_time).then_some(*pos))
+                .collect();
+            burning.sort_unstable_by_key(|pos| (pos.floor, pos.tile_pos.x, pos.tile_pos.y));
+            let spreading: Vec<MechInteriorPos> = burning
+                .into_iter()
+                .filter(|_| rng.gen::<f32>() < FIRE_SPREAD_CHANCE)
+                .collect();
+
+            let mut spread = false;
+            for origin in spreading {
+                if mech.fires.len() >= MAX_FIRES_PER_MECH {
+                    break;
+                }
+                let Some(floor_map) = mech.interior.floors.get(origin.floor as usize) else {
+                    continue;
+                };
+                let candidates: Vec<MechInteriorPos> = [(0, -1), (1, 0), (0, 1), (-1, 0)]
+                    .iter()
+                    .map(|(dx, dy)| {
+                        MechInteriorPos::from_coords(
+                            origin.floor,
+                            origin.tile_pos.x + dx,
+                            origin.tile_pos.y + dy,
+                        )
+                    })
+                    .filter(|pos| {
+                        floor_map
+                            .static_tiles
+                            .get(&pos.tile_pos)
+                            .is_some_and(|tile| tile.is_flammable())
+                            && !mech.fires.contains_key(pos)
+                    })
+                    .collect();
+
+                if let Some(pos) = candidates.choose(rng) {
+                    mech.fires.insert(*pos, Fire::ignite());
+                    spread = true;
+                }
+            }
+
+            if spread {
+                messages.push(ServerMessage::MechFiresUpdated {
+                    mech_id: mech.id,
+                    fires: mech.fire_states(),
+                });
+            }
+        }
+
+        messages
+    }
+
+    /// Burn crew standing in fire, knocking out anyone who stays too long
+    fn burn_crew(&mut self, game: &mut Game, delta_time: f32) -> Vec<ServerMessage> {
+        let mut messages = Vec::new();
+
+        let in_fire: Vec<(Uuid, f32)> = game
+            .players
+            .values()
+            .filter_map(|player| match player.location {
+                PlayerLocation::InsideMech { mech_id, pos } => game
+                    .mechs
+                    .get(&mech_id)
+                    .and_then(|mech| mech.fires.get(&pos))
+                    .map(|fire| (player.id, fire.intensity)),
+                _ => None,
+            })
+            .collect();
+
+        self.fire_exposure
+            .retain(|id, _| in_fire.iter().any(|(player_id, _)| player_id == id));
+
+        for (player_id, intensity) in in_fire {
+            let exposure = self.fire_exposure.entry(player_id).or_insert(0.0);
+            *exposure += delta_time;
+            if *exposure >= FIRE_CREW_KNOCKOUT_TIME {
+                self.fire_exposure.remove(&player_id);
+                messages.extend(self.knock_out(game, player_id));
+                continue;
+            }
+
+            // Refresh the burn only once it lapses to avoid flooding clients
+            let already_burning = game
+                .entity_storage
+                .status_effects
+                .get(&player_id)
+                .is_some_and(|effects| effects.has(StatusEffectKind::Burning));
+            if !already_burning {
+                messages.push(game.apply_status_effect(
+                    player_id,
+                    StatusEffectKind::Burning,
+                    FIRE_CREW_BURN_DURATION,
+                    intensity,
+                ));
+            }
+        }
+
+        messages
+    }
+
+    /// Drag a crew member out of the fire and back to their team spawn
+    fn knock_out(&mut self, game: &mut Game, player_id: Uuid) -> Vec<ServerMessage> {
+        let mut messages: Vec<ServerMessage> = self.cancel_channel(player_id).into_iter().collect();
+        messages.extend(game.knock_out(player_id, None));
+        messages
+    }
+
+    /// Advance hull repairs, cancelling any whose player walked away
+    fn update_channels(&mut self, game: &mut Game, delta_time: f32) -> Vec<ServerMessage> {
+        let mut messages = Vec::new();
+        let mut finished = Vec::new();
+
+        for (player_id, channel) in self.channels.iter_mut() {
+            let in_position = game.players.get(player_id).is_some_and(|player| {
+                player.held_tool == Some(ToolType::RepairTool)
+                    && matches!(
+                        player.location,
+                        PlayerLocation::InsideMech { mech_id, pos }
+                            if mech_id == channel.mech_id
+                                && pos.floor == channel.floor
+                                && tiles_in_reach(pos.tile_pos).contains(&channel.target)
+                    )
+            });
+            if !in_position {
+                finished.push((*player_id, false));
+                continue;
+            }
+
+            let previous_step = (channel.progress * 10.0) as u32;
+            channel.progress += delta_time / HULL_REPAIR_CHANNEL_TIME;
+            if channel.progress >= 1.0 {
+                finished.push((*player_id, true));
+            } else if (channel.progress * 10.0) as u32 != previous_step {
+                // Report in 10% steps rather than every tick
+                messages.push(ServerMessage::RepairProgress {
+                    player_id: *player_id,
+                    mech_id: channel.mech_id,
+                    progress: Some(channel.progress),
+                });
+            }
+        }
+
+        for (player_id, completed) in finished {
+            let Some(channel) = self.channels.remove(&player_id) else {
+                continue;
+            };
+            messages.push(ServerMessage::RepairProgress {
+                player_id,
+                mech_id: channel.mech_id,
+                progress: None,
+            });
+            if completed {
+                let pos = MechInteriorPos::new(channel.floor, channel.target);
+                messages.extend(game.repair_hull_breach(channel.mech_id, pos));
+            }
+        }
+
+        messages
+    }
+}
+
+impl GameSystem for RepairSystem {
+    fn update(&mut self, game: &mut Game, delta_time: f32) -> Vec<ServerMessage> {
+        profiling::scope!("repair");
+        let mut messages = Vec::new();
+
+        messages.extend(self.update_fires(game, delta_time));
+        messages.extend(self.burn_crew(game, delta_time));
+        messages.extend(self.update_channels(game, delta_time));
+
+        messages
+    }
+
+    fn name(&self) -> &'static str {
+        "repair"
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
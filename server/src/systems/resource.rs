@@ -25,16 +25,15 @@ impl ResourceSystem {
     }
 
     /// Generate a random valid spawn position for a resource
-    fn generate_random_spawn_position(&self, game: &Game) -> Option<TilePos> {
-        let mut rng = rand::thread_rng();
+    fn generate_random_spawn_position(&self, game: &mut Game) -> Option<TilePos> {
         let mut attempts = 0;
         const MAX_ATTEMPTS: i32 = 100;
 
         while attempts < MAX_ATTEMPTS {
             // Generate random position within arena bounds
             // Leave some margin from edges (5 tiles)
-            let x = rng.gen_range(5..(ARENA_WIDTH_TILES - 5)) as i32;
-            let y = rng.gen_range(5..(ARENA_HEIGHT_TILES - 5)) as i32;
+            let x = game.rng.gen_range(5..(game.arena_map.width - 5));
+            let y = game.rng.gen_range(5..(game.arena_map.height - 5));
             let pos = TilePos::new(x, y);
 
             // Check if position is valid
@@ -106,7 +105,7 @@ impl ResourceSystem {
             for _ in 0..resources_to_spawn.min(3) {
                 // Spawn max 3 at once
                 if let Some(spawn_pos) = self.generate_random_spawn_position(game) {
-                    let resource_type = self.get_random_resource_type();
+                    let resource_type = game.roll_resource_type(spawn_pos);
 
                     let resource_id = game.spawn_resource_with_behavior(spawn_pos, resource_type);
 
@@ -124,61 +123,22 @@ impl ResourceSystem {
         messages
     }
 
-    /// Get a random resource type
-    fn get_random_resource_type(&self) -> ResourceType {
-        let mut rng = rand::thread_rng();
-        let resource_types = [
-            ResourceType::ScrapMetal,
-            ResourceType::Wiring,
-            ResourceType::ComputerComponents,
-            ResourceType::Batteries,
-        ];
-
-        resource_types[rng.gen_range(0..resource_types.len())]
-    }
-
     /// Handle resource pickup logic
     fn handle_resource_pickups(&self, game: &mut Game) -> Vec<ServerMessage> {
-        let mut messages = Vec::new();
-        let mut pickups = Vec::new();
-
-        // Use spatial collision manager for efficient pickup detection
-        for player in game.players.values() {
-            if player.carrying_resource.is_some() {
-                continue; // Already carrying something
-            }
-
-            if let PlayerLocation::OutsideWorld(player_pos) = player.location {
-                let nearby_resources = game
-                    .spatial_collision
-                    .check_player_resource_collisions(player.id, player_pos);
-
-                // Pick up the nearest resource
-                if let Some(resource_id) = nearby_resources.first() {
-                    if let Some(resource) = game.get_resource(*resource_id) {
-                        pickups.push((player.id, *resource_id, resource.resource_type));
-                    }
-                }
-            }
-        }
-
-        // Process pickups
-        for (player_id, resource_id, resource_type) in pickups {
-            if let Some(player) = game.players.get_mut(&player_id) {
-                player.carrying_resource = Some(resource_type);
-                game.remove_resource(resource_id);
-
-                messages.push(ServerMessage::PlayerPickedUpResource {
-                    player_id,
-                    resource_type,
-                    resource_id,
-                });
-
-                log::info!("Player {player_id} picked up {resource_type:?} resource");
-            }
-        }
-
-        messages
+        let outside: Vec<Uuid> = game
+            .players
+            .values()
+            .filter(|player| {
+                matches!(player.location, PlayerLocation::OutsideWorld(_))
+                    && !player.inventory.is_full()
+            })
+            .map(|player| player.id)
+            .collect();
+
+        outside
+            .into_iter()
+            .flat_map(|player_id| game.pick_up_in_reach(player_id))
+            .collect()
     }
 
     /// Handle resource delivery to mechs
@@ -187,31 +147,30 @@ impl ResourceSystem {
         let mut deliveries = Vec::new();
 
         for player in game.players.values() {
-            if let Some(resource_type) = player.carrying_resource {
-                if let PlayerLocation::InsideMech { mech_id, .. } = player.location {
-                    // Player is inside a mech with a resource - deliver it
-                    deliveries.push((player.id, mech_id, resource_type));
+            if let PlayerLocation::InsideMech { mech_id, .. } = player.location {
+                if player.inventory.has_resource() && game.mechs.contains_key(&mech_id) {
+                    // Player is inside a mech with resources - deliver them
+                    deliveries.push((player.id, mech_id));
                 }
             }
         }
 
         // Process deliveries
-        for (player_id, mech_id, resource_type) in deliveries {
-            if let Some(player) = game.players.get_mut(&player_id) {
-                player.carrying_resource = None;
-
-                if let Some(mech) = game.mechs.get_mut(&mech_id) {
-                    let current_count = mech.resource_inventory.get(&resource_type).unwrap_or(&0);
-                    mech.resource_inventory
-                        .insert(resource_type, current_count + 1);
-
-                    messages.push(ServerMessage::ResourceCollected {
-                        resource_id: Uuid::new_v4(), // Placeholder
-                        player_id,
-                    });
+        for (player_id, mech_id) in deliveries {
+            let Some(player) = game.players.get_mut(&player_id) else {
+                continue;
+            };
+            let resources = player.inventory.take_resources();
+            messages.push(player.inventory_message());
+
+            for resource_type in resources {
+                messages.push(ServerMessage::ResourceCollected {
+                    resource_id: game.rng.uuid(), // Placeholder
+                    player_id,
+                });
+                messages.extend(game.deliver_resource(player_id, mech_id, resource_type));
 
-                    log::info!("Player {player_id} delivered {resource_type:?} to mech {mech_id}");
-                }
+                log::info!("Player {player_id} delivered {resource_type:?} to mech {mech_id}");
             }
         }
 
@@ -223,26 +182,12 @@ impl ResourceSystem {
         let mut underrepresented_areas = Vec::new();
 
         // Divide map into quadrants and check resource density
+        let (width, height) = (game.arena_map.width, game.arena_map.height);
         let quadrants = [
-            (0, 0, ARENA_WIDTH_TILES / 2, ARENA_HEIGHT_TILES / 2),
-            (
-                ARENA_WIDTH_TILES / 2,
-                0,
-                ARENA_WIDTH_TILES,
-                ARENA_HEIGHT_TILES / 2,
-            ),
-            (
-                0,
-                ARENA_HEIGHT_TILES / 2,
-                ARENA_WIDTH_TILES / 2,
-                ARENA_HEIGHT_TILES,
-            ),
-            (
-                ARENA_WIDTH_TILES / 2,
-                ARENA_HEIGHT_TILES / 2,
-                ARENA_WIDTH_TILES,
-                ARENA_HEIGHT_TILES,
-            ),
+            (0, 0, width / 2, height / 2),
+            (width / 2, 0, width, height / 2),
+            (0, height / 2, width / 2, height),
+            (width / 2, height / 2, width, height),
         ];
 
         for (min_x, min_y, max_x, max_y) in quadrants {
@@ -271,6 +216,7 @@ impl ResourceSystem {
 
 impl GameSystem for ResourceSystem {
     fn update(&mut self, game: &mut Game, delta_time: f32) -> Vec<ServerMessage> {
+        profiling::scope!("resource");
         let mut messages = Vec::new();
 
         // Check if new resources need to be spawned
@@ -0,0 +1,200 @@
+use super::GameSystem;
+use crate::game::Game;
+use shared::*;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Keeps team spawns from being farmed. Enemy shots fizzle as they cross
+/// into a team's safe zone, and enemies on foot inside it are slowed and
+/// worn down until they leave. Respawned players' own protection is a
+/// status effect handed out by `Game::knock_out`.
+pub struct SpawnProtectionSystem {
+    /// Fractional aura damage carried between ticks, per intruder
+    aura_damage: HashMap<Uuid, f32>,
+}
+
+impl SpawnProtectionSystem {
+    pub fn new() -> Self {
+        Self {
+            aura_damage: HashMap::new(),
+        }
+    }
+
+    fn nullify_projectiles(&self, game: &mut Game) -> Vec<ServerMessage> {
+        let zones = game.arena_map.safe_zones();
+        let fizzled: Vec<Uuid> = game
+            .projectiles
+            .values()
+            .filter(|projectile| {
+                let Some(owner) = game.mechs.get(&projectile.owner_mech_id) else {
+                    return false;
+                };
+                zones
+                    .iter()
+                    .any(|zone| zone.hostile_to(owner.team, projectile.position))
+            })
+            .map(|projectile| projectile.id)
+            .collect();
+
+        let mut messages = Vec::new();
+        for projectile_id in fizzled {
+            if let Some(mut projectile) = game.projectiles.remove(&projectile_id) {
+                projectile.reset();
+                game.pool_manager.return_projectile(projectile);
+                messages.push(ServerMessage::ProjectileExpired { projectile_id });
+            }
+        }
+        messages
+    }
+
+    fn punish_intruders(&mut self, game: &mut Game, delta_time: f32) -> Vec<ServerMessage> {
+        let zones = game.arena_map.safe_zones();
+        let intruders: Vec<Uuid> = game
+            .players
+            .values()
+            .filter(|player| match player.location {
+                PlayerLocation::OutsideWorld(pos) => {
+                    zones.iter().any(|zone| zone.hostile_to(player.team, pos))
+                }
+                PlayerLocation::InsideMech { .. } => false,
+            })
+            .map(|player| player.id)
+            .collect();
+        self.aura_damage.retain(|id, _| intruders.contains(id));
+
+        let mut messages = Vec::new();
+        for player_id in intruders {
+            // Hold the slow at one stack for as long as they stay
+            let slowed = game
+                .entity_storage
+                .status_effects
+                .get_mut(&player_id)
+                .and_then(|effects| {
+                    effects
+                        .effects
+                        .iter_mut()
+                        .find(|effect| effect.kind == StatusEffectKind::Slowed)
+                });
+            match slowed {
+                Some(effect) => effect.remaining = effect.remaining.max(SAFE_ZONE_SLOW_DURATION),
+                None => messages.push(game.apply_status_effect(
+                    player_id,
+                    StatusEffectKind::Slowed,
+                    SAFE_ZONE_SLOW_DURATION,
+                    SAFE_ZONE_SLOW_AMOUNT,
+                )),
+            }
+
+            let accumulated = self.aura_damage.entry(player_id).or_insert(0.0);
+            *accumulated += SAFE_ZONE_AURA_DPS * delta_time;
+            let whole = accumulated.floor() as u32;
+            if whole == 0 {
+                continue;
+            }
+            *accumulated -= whole as f32;
+
+            let Some(player) = game.players.get_mut(&player_id) else {
+                continue;
+            };
+            player.health = player.health.saturating_sub(whole);
+            let health = player.health;
+            // The zone itself is the attacker
+            messages.push(ServerMessage::PlayerAttacked {
+                attacker: Uuid::nil(),
+                target: player_id,
+                damage: whole,
+                health,
+            });
+            if health == 0 {
+                self.aura_damage.remove(&player_id);
+                messages.extend(game.knock_out(player_id, None));
+            }
+        }
+        messages
+    }
+}
+
+impl GameSystem for SpawnProtectionSystem {
+    fn update(&mut self, game: &mut Game, delta_time: f32) -> Vec<ServerMessage> {
+        profiling::scope!("spawn_protection");
+        let mut messages = self.nullify_projectiles(game);
+        messages.extend(self.punish_intruders(game, delta_time));
+        messages
+    }
+
+    fn name(&self) -> &'static str {
+        "spawn_protection"
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blue_player_at(game: &mut Game, pos: WorldPos) -> Uuid {
+        let player_id = Uuid::new_v4();
+        game.add_player(player_id, "Intruder".to_string(), Some(TeamId::Blue));
+        game.players.get_mut(&player_id).unwrap().location = PlayerLocation::OutsideWorld(pos);
+        player_id
+    }
+
+    #[test]
+    fn test_intruders_are_slowed_and_worn_down() {
+        let mut game = Game::new();
+        let red_spawn = game.arena_map.player_spawn(TeamId::Red);
+        let player_id = blue_player_at(&mut game, red_spawn);
+
+        let mut system = SpawnProtectionSystem::new();
+        system.update(&mut game, 1.0);
+        assert_eq!(
+            game.players[&player_id].health,
+            PLAYER_MAX_HEALTH - SAFE_ZONE_AURA_DPS as u32
+        );
+        assert!(game.movement_multiplier(player_id) < 1.0);
+
+        // Staying doesn't pile on more slow
+        system.update(&mut game, 0.1);
+        assert_eq!(
+            game.movement_multiplier(player_id),
+            1.0 - SAFE_ZONE_SLOW_AMOUNT
+        );
+
+        // Lingering ends with a trip back to their own spawn
+        for _ in 0..20 {
+            system.update(&mut game, 1.0);
+        }
+        let blue_zone = game.arena_map.safe_zone(TeamId::Blue);
+        match game.players[&player_id].location {
+            PlayerLocation::OutsideWorld(pos) => assert!(blue_zone.contains(pos.to_tile())),
+            _ => panic!("Knocked out players respawn outside"),
+        }
+        assert!(game.is_protected(player_id));
+    }
+
+    #[test]
+    fn test_enemy_shots_fizzle_in_a_safe_zone() {
+        let mut game = Game::new();
+        let blue_mech = game
+            .mechs
+            .values()
+            .find(|mech| mech.team == TeamId::Blue)
+            .unwrap()
+            .id;
+        let red_spawn = game.arena_map.player_spawn(TeamId::Red);
+        let mut projectile = game.pool_manager.get_projectile();
+        projectile.initialize(red_spawn, (0.0, 0.0), 10, blue_mech, 5.0);
+        let projectile_id = projectile.id;
+        game.projectiles.insert(projectile_id, projectile);
+
+        let messages = SpawnProtectionSystem::new().update(&mut game, 0.1);
+        assert!(!game.projectiles.contains_key(&projectile_id));
+        assert!(messages.iter().any(|msg| matches!(
+            msg,
+            ServerMessage::ProjectileExpired { projectile_id: id } if *id == projectile_id
+        )));
+    }
+}
@@ -0,0 +1,426 @@
+//! Carrying out station buttons. The station registry works out what a
+//! press does as a list of `StationEffect`s; `resolve` applies every one of
+//! them to the game and returns what clients need to hear, so a button
+//! defined in the stations file works without a handler of its own. Unlike
+//! the other systems this one has nothing to do between presses.
+
+use crate::game::Game;
+use shared::*;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Press a button on one of a mech's stations, paying for it and applying
+/// whatever it does
+pub fn press(
+    game: &mut Game,
+    mech_id: Uuid,
+    station_id: Uuid,
+    button_index: u8,
+) -> GameResult<Vec<ServerMessage>> {
+    let context = context_for(game, mech_id)?;
+    let station = game
+        .mechs
+        .get_mut(&mech_id)
+        .ok_or_else(|| GameError::mech_not_found(mech_id))?
+        .stations
+        .get_mut(&station_id)
+        .ok_or_else(|| GameError::station_not_found(station_id))?;
+    let station_type = station.station_type;
+    let result = game
+        .station_registry
+        .execute_button_action(station, button_index, &context)?;
+    if !result.success {
+        return Err(GameError::invalid_state(result.message));
+    }
+    Ok(resolve(game, mech_id, station_type, result.effects))
+}
+
+/// What the registry needs to know about a mech to work out a press
+fn context_for(game: &Game, mech_id: Uuid) -> GameResult<StationActionContext> {
+    let mech = game
+        .mechs
+        .get(&mech_id)
+        .ok_or_else(|| GameError::mech_not_found(mech_id))?;
+    let origin = MechPositioning::mech_center(mech.position);
+    let nearest_enemy = game
+        .mechs
+        .values()
+        .filter(|other| other.team != mech.team)
+        .map(|other| (other, other.position.distance_to(mech.position)))
+        .filter(|(_, distance)| *distance <= WEAPON_MAX_RANGE)
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(other, _)| other);
+    let direction_to_target = nearest_enemy.and_then(|enemy| {
+        let target = MechPositioning::mech_center(enemy.position);
+        let (dx, dy) = (target.x - origin.x, target.y - origin.y);
+        let length = (dx * dx + dy * dy).sqrt();
+        (length > 0.0).then(|| (dx / length, dy / length))
+    });
+    let upgrades = &mech.upgrades;
+    let upgrade_target = mech
+        .upgrade_target
+        .and_then(|station_id| mech.stations.get(&station_id))
+        .map(|station| UpgradeTarget {
+            station_id: station.id,
+            station_type: station.station_type,
+            level: station.upgrade_level,
+        });
+    Ok(StationActionContext {
+        current_time: game.match_time,
+        mech_id: Some(mech_id),
+        station_world_pos: origin,
+        available_resources: mech.resource_inventory.clone(),
+        nearest_enemy: nearest_enemy.map(|enemy| enemy.id),
+        direction_to_target,
+        current_upgrade_levels: HashMap::from([
+            (MechUpgradeType::Laser, upgrades.laser_level),
+            (MechUpgradeType::Projectile, upgrades.projectile_level),
+            (MechUpgradeType::Shield, upgrades.shield_level),
+            (MechUpgradeType::Engine, upgrades.engine_level),
+            (MechUpgradeType::Ecm, upgrades.ecm_level),
+        ]),
+        upgrade_target,
+    })
+}
+
+/// Apply what a `station_type` station aboard `mech_id` did, returning the
+/// messages to broadcast. Resources spent come out of that mech's hold.
+pub fn resolve(
+    game: &mut Game,
+    mech_id: Uuid,
+    station_type: StationType,
+    effects: Vec<StationEffect>,
+) -> Vec<ServerMessage> {
+    let mut messages = Vec::new();
+    let mut spent = false;
+    for effect in effects {
+        match effect {
+            StationEffect::Damage {
+                target_id,
+                amount,
+                weapon_type,
+            } => {
+                let (Some(attacker), Some(target)) =
+                    (game.mechs.get(&mech_id), game.mechs.get(&target_id))
+                else {
+                    continue;
+                };
+                // Hits like a shot fired straight from one mech to the other
+                let (from, to) = (
+                    MechPositioning::mech_center(attacker.position),
+                    MechPositioning::mech_center(target.position),
+                );
+                let hit = HitDirection::new(target.drive.heading, (to.x - from.x, to.y - from.y));
+                let falloff =
+                    damage_falloff(weapon_type, target.position.distance_to(attacker.position));
+                let amount = (boosted(game, mech_id, amount) as f32 * falloff).round() as u32;
+                game.damage_mech(
+                    target_id,
+                    amount,
+                    to,
+                    Some(hit),
+                    Some(mech_id),
+                    &mut messages,
+                );
+            }
+            StationEffect::Heal { target_id, amount } => {
+                if let Some(mech) = game.mechs.get_mut(&target_id) {
+                    let before = mech.health;
+                    mech.health = (mech.health + amount).min(mech.max_health);
+                    messages.push(ServerMessage::MechRepaired {
+                        mech_id: target_id,
+                        health_restored: mech.health - before,
+                        new_health: mech.health,
+                    });
+                }
+            }
+            StationEffect::ShieldBoost { target_id, amount } => {
+                if let Some(mech) = game.mechs.get_mut(&target_id) {
+                    mech.shield = (mech.shield + amount).min(mech.max_shield);
+                    messages.push(ServerMessage::MechShieldChanged {
+                        mech_id: target_id,
+                        shield: mech.shield,
+                    });
+                }
+            }
+            StationEffect::ResourceConsumed {
+                resource_type,
+                amount,
+            } => {
+                if let Some(count) = game
+                    .mechs
+                    .get_mut(&mech_id)
+                    .and_then(|mech| mech.resource_inventory.get_mut(&resource_type))
+                {
+                    *count = count.saturating_sub(amount);
+                    spent = true;
+                }
+            }
+            StationEffect::UpgradeApplied {
+                target_id,
+                upgrade_type,
+                new_level,
+            } => {
+                // The registry turns away upgrades mechs don't keep
+                let Some(upgrade_type) = upgrade_type.upgrade_type() else {
+                    continue;
+                };
+                let Some(mech) = game.mechs.get_mut(&target_id) else {
+                    continue;
+                };
+                let new_level = new_level.min(MAX_UPGRADE_LEVEL);
+                *mech.upgrades.level_mut(upgrade_type) = new_level;
+                if upgrade_type == UpgradeType::Shield {
+                    mech.max_shield = MECH_MAX_SHIELD + (new_level as u32 - 1) * SHIELD_PER_LEVEL;
+                }
+                messages.push(ServerMessage::MechUpgraded {
+                    mech_id: target_id,
+                    upgrade_type,
+                    new_level,
+                });
+            }
+            StationEffect::StationUpgraded {
+                station_id,
+                new_level,
+            } => {
+                let Some((owner, station)) = game.mechs.values_mut().find_map(|mech| {
+                    let owner = mech.id;
+                    mech.stations
+                        .get_mut(&station_id)
+                        .map(|station| (owner, station))
+                }) else {
+                    continue;
+                };
+                station.upgrade_level = new_level;
+                messages.push(ServerMessage::MechInteriorUpdate {
+                    mech_id: owner,
+                    floor: station.floor,
+                    tile_updates: Vec::new(),
+                    station_changes: vec![StationUpdate::Upgraded {
+                        station_id,
+                        new_level,
+                    }],
+                });
+            }
+            StationEffect::ProjectileCreated {
+                position,
+                velocity,
+                damage,
+                weapon_type,
+                ..
+            } => {
+                // The pool hands out its own id for the projectile
+                let damage = boosted(game, mech_id, damage);
+                let projectile_id = game.create_projectile(
+                    position,
+                    velocity,
+                    damage,
+                    mech_id,
+                    PROJECTILE_LIFETIME,
+                    weapon_type,
+                );
+                let (vx, vy) = velocity;
+                messages.push(ServerMessage::WeaponFired {
+                    mech_id,
+                    weapon_type: station_type,
+                    target_position: WorldPos::new(
+                        position.x + vx * PROJECTILE_LIFETIME,
+                        position.y + vy * PROJECTILE_LIFETIME,
+                    )
+                    .to_tile(),
+                    projectile_id: Some(projectile_id),
+                });
+            }
+            StationEffect::VisualEffect {
+                effect_type,
+                position,
+                duration,
+            } => messages.push(ServerMessage::EffectCreated {
+                effect_id: game.rng.uuid(),
+                effect_type,
+                position,
+                duration,
+            }),
+            StationEffect::EnergyCharge { target_id, amount } => {
+                if let Some(mech) = game.mechs.get_mut(&target_id) {
+                    mech.fuel = (mech.fuel + amount as f32).min(MECH_FUEL_CAPACITY);
+                    messages.push(ServerMessage::MechFuelChanged {
+                        mech_id: target_id,
+                        fuel: mech.fuel,
+                    });
+                }
+            }
+            StationEffect::TemporaryBuff {
+                target_id,
+                buff_type,
+                duration,
+            } => match buff_type.as_str() {
+                "EnergyBoost" => messages.push(game.apply_status_effect(
+                    target_id,
+                    StatusEffectKind::Overcharged,
+                    duration,
+                    OVERCHARGE_DAMAGE_MULTIPLIER,
+                )),
+                other => log::warn!("No buff called {other} to apply"),
+            },
+        }
+    }
+    if spent {
+        if let Some(mech) = game.mechs.get(&mech_id) {
+            messages.push(mech.inventory_message());
+        }
+    }
+    messages
+}
+
+/// Weapon damage from a mech, strengthened by overcharge and pacing zones
+fn boosted(game: &Game, mech_id: Uuid, damage: u32) -> u32 {
+    let Some(mech) = game.mechs.get(&mech_id) else {
+        return damage;
+    };
+    let zone = damage_multiplier_at(
+        &game.pacing_zones,
+        MechPositioning::mech_center(mech.position),
+    );
+    (damage as f32 * zone * game.damage_multiplier(mech_id)).round() as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn electrical(game: &Game, mech_id: Uuid) -> Uuid {
+        game.mechs[&mech_id]
+            .stations
+            .values()
+            .find(|station| station.station_type == StationType::Electrical)
+            .unwrap()
+            .id
+    }
+
+    #[test]
+    fn test_recharge_and_boost_work_from_their_definitions() {
+        let mut game = Game::new();
+        let mech_id = *game.mechs.keys().next().unwrap();
+        let station_id = electrical(&game, mech_id);
+        {
+            let mech = game.mechs.get_mut(&mech_id).unwrap();
+            mech.fuel = 50.0;
+            mech.resource_inventory =
+                HashMap::from([(ResourceType::Batteries, 3), (ResourceType::Wiring, 1)]);
+        }
+
+        // Recharge turns a battery into fuel
+        let messages = press(&mut game, mech_id, station_id, 0).unwrap();
+        assert_eq!(game.mechs[&mech_id].fuel, 60.0);
+        assert_eq!(
+            game.mechs[&mech_id].resource_inventory[&ResourceType::Batteries],
+            2
+        );
+        assert!(messages
+            .iter()
+            .any(|message| matches!(message, ServerMessage::MechFuelChanged { fuel, .. } if *fuel == 60.0)));
+        assert!(messages
+            .iter()
+            .any(|message| matches!(message, ServerMessage::MechInventoryChanged { .. })));
+
+        // Boost Systems overcharges the mech's weapons for its duration,
+        // once the station's cooldown from the last press has passed
+        assert_eq!(game.damage_multiplier(mech_id), 1.0);
+        assert!(press(&mut game, mech_id, station_id, 1).is_err());
+        game.match_time += 30.0;
+        press(&mut game, mech_id, station_id, 1).unwrap();
+        assert_eq!(
+            game.damage_multiplier(mech_id),
+            OVERCHARGE_DAMAGE_MULTIPLIER
+        );
+        let inventory = &game.mechs[&mech_id].resource_inventory;
+        assert_eq!(inventory[&ResourceType::Batteries], 0);
+        assert_eq!(inventory[&ResourceType::Wiring], 0);
+
+        // Once it cools down there's nothing left to pay with
+        game.match_time += 60.0;
+        assert!(press(&mut game, mech_id, station_id, 1).is_err());
+    }
+
+    #[test]
+    fn test_every_effect_reaches_the_game() {
+        let mut game = Game::new();
+        let mut ids: Vec<Uuid> = game.mechs.keys().copied().collect();
+        ids.sort_unstable();
+        let (ours, theirs) = (ids[0], ids[1]);
+        {
+            let mech = game.mechs.get_mut(&ours).unwrap();
+            mech.health = mech.max_health - 30;
+            mech.shield = 0;
+        }
+        game.mechs.get_mut(&theirs).unwrap().shield = 0;
+        let health = game.mechs[&theirs].health;
+        let position = WorldPos::new(10.0 * TILE_SIZE, 10.0 * TILE_SIZE);
+
+        let messages = resolve(
+            &mut game,
+            ours,
+            StationType::WeaponProjectile,
+            vec![
+                StationEffect::Damage {
+                    target_id: theirs,
+                    amount: 10,
+                    weapon_type: WeaponType::Laser,
+                },
+                StationEffect::Heal {
+                    target_id: ours,
+                    amount: 100,
+                },
+                StationEffect::ShieldBoost {
+                    target_id: ours,
+                    amount: 5,
+                },
+                StationEffect::UpgradeApplied {
+                    target_id: ours,
+                    upgrade_type: MechUpgradeType::Shield,
+                    new_level: 2,
+                },
+                StationEffect::ProjectileCreated {
+                    projectile_id: Uuid::nil(),
+                    position,
+                    velocity: (100.0, 0.0),
+                    damage: 20,
+                    weapon_type: WeaponType::Missile,
+                },
+                StationEffect::VisualEffect {
+                    effect_type: "spark".to_string(),
+                    position,
+                    duration: 1.0,
+                },
+            ],
+        );
+
+        assert!(game.mechs[&theirs].health < health);
+        let mech = &game.mechs[&ours];
+        assert_eq!(mech.health, mech.max_health);
+        assert_eq!(mech.shield, 5);
+        assert_eq!(mech.upgrades.shield_level, 2);
+        assert_eq!(mech.max_shield, MECH_MAX_SHIELD + SHIELD_PER_LEVEL);
+        assert!(messages.iter().any(|message| matches!(
+            message,
+            ServerMessage::MechRepaired {
+                health_restored: 30,
+                ..
+            }
+        )));
+        let projectile_id = messages
+            .iter()
+            .find_map(|message| match message {
+                ServerMessage::WeaponFired { projectile_id, .. } => *projectile_id,
+                _ => None,
+            })
+            .unwrap();
+        let projectile = &game.projectiles[&projectile_id];
+        assert_eq!(projectile.owner_mech_id, ours);
+        assert_eq!(projectile.weapon_type, WeaponType::Missile);
+        assert!(messages
+            .iter()
+            .any(|message| matches!(message, ServerMessage::EffectCreated { effect_type, .. } if effect_type == "spark")));
+    }
+}
@@ -0,0 +1,250 @@
+use super::GameSystem;
+use crate::game::Game;
+use shared::*;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// The pilot's stomp: a slam that lands `STOMP_WINDUP` after the button is
+/// pressed, throwing back and hurting enemy players around the mech and
+/// denting enemy mechs right alongside. The windup is broadcast so anyone
+/// in the way can run for it.
+pub struct StompSystem {
+    /// Seconds until each mech can stomp again
+    cooldowns: HashMap<Uuid, f32>,
+    /// Stomps on their way down, with the pilot who called them
+    windups: HashMap<Uuid, Windup>,
+}
+
+struct Windup {
+    remaining: f32,
+    pilot: Option<Uuid>,
+}
+
+impl StompSystem {
+    pub fn new() -> Self {
+        Self {
+            cooldowns: HashMap::new(),
+            windups: HashMap::new(),
+        }
+    }
+
+    /// Start winding up a stomp from a mech
+    pub fn start(
+        &mut self,
+        game: &Game,
+        mech_id: Uuid,
+        pilot: Option<Uuid>,
+    ) -> GameResult<Vec<ServerMessage>> {
+        if let Some(remaining) = self.cooldowns.get(&mech_id) {
+            return Err(GameError::WeaponOnCooldown {
+                remaining_seconds: *remaining,
+            });
+        }
+        if !game.mechs.contains_key(&mech_id) {
+            return Err(GameError::mech_not_found(mech_id));
+        }
+        self.cooldowns.insert(mech_id, STOMP_COOLDOWN);
+        self.windups.insert(
+            mech_id,
+            Windup {
+                remaining: STOMP_WINDUP,
+                pilot,
+            },
+        );
+        Ok(vec![ServerMessage::MechStompWindup {
+            mech_id,
+            windup: STOMP_WINDUP,
+        }])
+    }
+}
+
+/// Land a stomp: hit everything in reach of the mech
+fn land(game: &mut Game, mech_id: Uuid, pilot: Option<Uuid>) -> Vec<ServerMessage> {
+    let Some(mech) = game.mechs.get(&mech_id) else {
+        return Vec::new();
+    };
+    let team = mech.team;
+    let center = MechPositioning::mech_center(mech.position);
+    let radius = STOMP_RADIUS * TILE_SIZE;
+    let mut messages = vec![ServerMessage::MechStomped {
+        mech_id,
+        position: center,
+        radius,
+    }];
+
+    let caught: Vec<(Uuid, WorldPos)> = game
+        .players
+        .values()
+        .filter(|player| player.team != team && !game.is_protected(player.id))
+        .filter_map(|player| match player.location {
+            PlayerLocation::OutsideWorld(pos) if pos.distance_to(center) <= radius => {
+                Some((player.id, pos))
+            }
+            _ => None,
+        })
+        .collect();
+    for (player_id, pos) in caught {
+        let Some(player) = game.players.get_mut(&player_id) else {
+            continue;
+        };
+        player.health = player.health.saturating_sub(STOMP_PLAYER_DAMAGE);
+        let health = player.health;
+        let thrown = knockback(center, pos);
+        player.location = PlayerLocation::OutsideWorld(thrown);
+        messages.push(ServerMessage::PlayerMoved {
+            player_id,
+            location: player.location,
+        });
+        if let Some(attacker) = pilot {
+            messages.push(ServerMessage::PlayerAttacked {
+                attacker,
+                target: player_id,
+                damage: STOMP_PLAYER_DAMAGE,
+                health,
+            });
+        }
+        if health == 0 {
+            messages.extend(game.knock_out(player_id, pilot));
+        }
+    }
+
+    // Hulls are side by side when their centers are a mech's width apart
+    let reach = (MECH_SIZE_TILES as f32 + STOMP_MECH_REACH) * TILE_SIZE;
+    let dented: Vec<Uuid> = game
+        .mechs
+        .values()
+        .filter(|other| other.team != team && other.health > 0)
+        .filter(|other| MechPositioning::mech_center(other.position).distance_to(center) <= reach)
+        .map(|other| other.id)
+        .collect();
+    for target_id in dented {
+        let Some(target) = game.mechs.get(&target_id) else {
+            continue;
+        };
+        let position = MechPositioning::mech_center(target.position);
+        game.damage_mech(
+            target_id,
+            STOMP_MECH_DAMAGE,
+            position,
+            None,
+            Some(mech_id),
+            &mut messages,
+        );
+    }
+
+    log::debug!("Mech {mech_id} stomped, {} messages", messages.len());
+    messages
+}
+
+/// Where a player standing at `pos` lands when thrown away from `center`
+fn knockback(center: WorldPos, pos: WorldPos) -> WorldPos {
+    let distance = pos.distance_to(center);
+    // Dead center has no away; throw them south, out of the door
+    let (dx, dy) = if distance > f32::EPSILON {
+        ((pos.x - center.x) / distance, (pos.y - center.y) / distance)
+    } else {
+        (0.0, 1.0)
+    };
+    let throw = STOMP_KNOCKBACK * TILE_SIZE;
+    WorldPos::new(pos.x + dx * throw, pos.y + dy * throw).clamp_to_world_bounds()
+}
+
+impl GameSystem for StompSystem {
+    fn update(&mut self, game: &mut Game, delta_time: f32) -> Vec<ServerMessage> {
+        profiling::scope!("stomp");
+        for remaining in self.cooldowns.values_mut() {
+            *remaining -= delta_time;
+        }
+        self.cooldowns
+            .retain(|mech_id, remaining| *remaining > 0.0 && game.mechs.contains_key(mech_id));
+
+        let mut landed = Vec::new();
+        self.windups.retain(|mech_id, windup| {
+            windup.remaining -= delta_time;
+            if windup.remaining > 0.0 {
+                return true;
+            }
+            landed.push((*mech_id, windup.pilot));
+            false
+        });
+        landed
+            .into_iter()
+            .flat_map(|(mech_id, pilot)| land(game, mech_id, pilot))
+            .collect()
+    }
+
+    fn name(&self) -> &'static str {
+        "stomp"
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stomp_lands_after_windup_and_cools_down() {
+        let mut game = Game::new();
+        let mut stomp = StompSystem::new();
+        let (mech_id, enemy_mech_id, enemy_team) = {
+            let mut mechs = game.mechs.values();
+            let first = mechs.next().unwrap();
+            let enemy = mechs.find(|m| m.team != first.team).unwrap();
+            (first.id, enemy.id, enemy.team)
+        };
+        let origin = game.mechs[&mech_id].position;
+        let center = MechPositioning::mech_center(origin);
+        game.mechs.get_mut(&enemy_mech_id).unwrap().position =
+            TilePos::new(origin.x + MECH_SIZE_TILES + 1, origin.y);
+        // Down to bare hull, so the dent shows in its health
+        game.mechs.get_mut(&enemy_mech_id).unwrap().shield = 0;
+        let enemy_health = game.mechs[&enemy_mech_id].health;
+
+        let (near, far) = (Uuid::new_v4(), Uuid::new_v4());
+        for (id, pos) in [
+            (near, WorldPos::new(center.x + TILE_SIZE * 6.0, center.y)),
+            (far, WorldPos::new(center.x, center.y + TILE_SIZE * 20.0)),
+        ] {
+            game.add_player(id, "Target".to_string(), Some(enemy_team));
+            game.players.get_mut(&id).unwrap().location = PlayerLocation::OutsideWorld(pos);
+        }
+
+        let messages = stomp.start(&game, mech_id, None).unwrap();
+        assert!(matches!(
+            messages[0],
+            ServerMessage::MechStompWindup { windup, .. } if windup == STOMP_WINDUP
+        ));
+        // Nothing lands during the windup
+        assert!(stomp.update(&mut game, STOMP_WINDUP / 2.0).is_empty());
+        assert_eq!(game.players[&near].health, PLAYER_MAX_HEALTH);
+
+        let messages = stomp.update(&mut game, STOMP_WINDUP);
+        assert!(matches!(messages[0], ServerMessage::MechStomped { .. }));
+        let near_player = &game.players[&near];
+        assert_eq!(near_player.health, PLAYER_MAX_HEALTH - STOMP_PLAYER_DAMAGE);
+        let PlayerLocation::OutsideWorld(thrown) = near_player.location else {
+            panic!("stomped player ended up inside a mech");
+        };
+        assert!(thrown.x > center.x + TILE_SIZE * 6.0);
+        assert_eq!(game.players[&far].health, PLAYER_MAX_HEALTH);
+        // Read off the hit itself; a hull breach it opens can lower the
+        // mech's max health below that afterwards
+        assert!(messages.iter().any(|msg| matches!(
+            msg,
+            ServerMessage::MechDamaged { mech_id, health_remaining, .. }
+                if *mech_id == enemy_mech_id
+                    && *health_remaining == enemy_health - STOMP_MECH_DAMAGE
+        )));
+
+        assert!(matches!(
+            stomp.start(&game, mech_id, None),
+            Err(GameError::WeaponOnCooldown { .. })
+        ));
+        stomp.update(&mut game, STOMP_COOLDOWN);
+        assert!(stomp.start(&game, mech_id, None).is_ok());
+    }
+}
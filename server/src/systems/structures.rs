@@ -0,0 +1,180 @@
+use super::GameSystem;
+use crate::game::Game;
+use shared::components::StructureKind;
+use shared::*;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Team base structures: repair bays patch up friendly mechs parked in
+/// them, and resupply depots put out scrap for the crew to collect
+pub struct StructureSystem {
+    /// Fractional hull repair carried between ticks, per mech
+    bay_repair: HashMap<Uuid, f32>,
+    /// Seconds until each depot's next resource
+    depot_timers: HashMap<Uuid, f32>,
+}
+
+impl StructureSystem {
+    pub fn new() -> Self {
+        Self {
+            bay_repair: HashMap::new(),
+            depot_timers: HashMap::new(),
+        }
+    }
+
+    fn repair_parked_mechs(&mut self, game: &mut Game, delta_time: f32) -> Vec<ServerMessage> {
+        let mut messages = Vec::new();
+        let bays: Vec<_> = game
+            .entity_storage
+            .structures
+            .values()
+            .filter(|s| s.kind == StructureKind::RepairBay)
+            .copied()
+            .collect();
+
+        for mech in game.mechs.values_mut() {
+            let (vx, vy) = mech.velocity;
+            let parked = (vx * vx + vy * vy).sqrt() <= REPAIR_BAY_MAX_PARKED_SPEED;
+            let in_bay = bays
+                .iter()
+                .any(|bay| bay.team == mech.team && bay.holds_mech(mech.position));
+            if !parked || !in_bay || mech.health >= mech.max_health {
+                self.bay_repair.remove(&mech.id);
+                continue;
+            }
+
+            let accumulated = self.bay_repair.entry(mech.id).or_insert(0.0);
+            *accumulated += REPAIR_BAY_HEAL_RATE * delta_time;
+            let whole = (accumulated.floor() as u32).min(mech.max_health - mech.health);
+            if whole == 0 {
+                continue;
+            }
+            *accumulated -= whole as f32;
+
+            mech.health += whole;
+            messages.push(ServerMessage::MechRepaired {
+                mech_id: mech.id,
+                health_restored: whole,
+                new_health: mech.health,
+            });
+        }
+
+        messages
+    }
+
+    /// A depot holds one resource at a time; its timer only runs while empty
+    fn run_depots(&mut self, game: &mut Game, delta_time: f32) -> Vec<ServerMessage> {
+        let mut messages = Vec::new();
+        let depots: Vec<_> = game
+            .entity_storage
+            .structures
+            .iter()
+            .filter(|(_, s)| s.kind == StructureKind::ResupplyDepot)
+            .map(|(id, s)| (*id, s.position))
+            .collect();
+
+        for (depot_id, position) in depots {
+            let stocked = game.entity_storage.resource_pickups.keys().any(|id| {
+                game.entity_storage
+                    .positions
+                    .get(id)
+                    .is_some_and(|p| p.tile == position)
+            });
+            let timer = self
+                .depot_timers
+                .entry(depot_id)
+                .or_insert(RESUPPLY_DEPOT_INTERVAL);
+            if stocked {
+                *timer = RESUPPLY_DEPOT_INTERVAL;
+                continue;
+            }
+            *timer -= delta_time;
+            if *timer > 0.0 {
+                continue;
+            }
+            *timer = RESUPPLY_DEPOT_INTERVAL;
+
+            let resource_type = ResourceType::ScrapMetal;
+            let resource_id = game.spawn_resource_with_behavior(position, resource_type);
+            messages.push(ServerMessage::ResourceSpawned {
+                resource_id,
+                position,
+                resource_type,
+            });
+        }
+
+        messages
+    }
+}
+
+impl GameSystem for StructureSystem {
+    fn update(&mut self, game: &mut Game, delta_time: f32) -> Vec<ServerMessage> {
+        profiling::scope!("structures");
+        let mut messages = self.repair_parked_mechs(game, delta_time);
+        messages.extend(self.run_depots(game, delta_time));
+        messages
+    }
+
+    fn name(&self) -> &'static str {
+        "structures"
+    }
+
+    fn should_update(&self, game: &Game) -> bool {
+        !game.entity_storage.structures.is_empty()
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn red_mech(game: &Game) -> Uuid {
+        game.mechs
+            .values()
+            .find(|mech| mech.team == TeamId::Red)
+            .unwrap()
+            .id
+    }
+
+    #[test]
+    fn test_repair_bay_heals_a_parked_friendly_mech() {
+        let mut game = Game::new();
+        let mech_id = red_mech(&game);
+        let max_health = game.mechs[&mech_id].max_health;
+        game.mechs.get_mut(&mech_id).unwrap().health = max_health - 10;
+
+        let mut structures = StructureSystem::new();
+        let messages = structures.update(&mut game, 1.0);
+        assert_eq!(
+            game.mechs[&mech_id].health,
+            max_health - 10 + REPAIR_BAY_HEAL_RATE as u32
+        );
+        assert!(matches!(messages[0], ServerMessage::MechRepaired { .. }));
+
+        // Driving through doesn't count
+        game.mechs.get_mut(&mech_id).unwrap().velocity = (1.0, 0.0);
+        let health = game.mechs[&mech_id].health;
+        structures.update(&mut game, 1.0);
+        assert_eq!(game.mechs[&mech_id].health, health);
+    }
+
+    #[test]
+    fn test_resupply_depot_restocks_only_when_empty() {
+        let mut game = Game::new();
+        let mut structures = StructureSystem::new();
+        let pickups = game.entity_storage.resource_pickups.len();
+
+        structures.update(&mut game, RESUPPLY_DEPOT_INTERVAL / 2.0);
+        assert_eq!(game.entity_storage.resource_pickups.len(), pickups);
+        structures.update(&mut game, RESUPPLY_DEPOT_INTERVAL / 2.0);
+        // One per team
+        assert_eq!(game.entity_storage.resource_pickups.len(), pickups + 2);
+
+        structures.update(&mut game, RESUPPLY_DEPOT_INTERVAL);
+        assert_eq!(game.entity_storage.resource_pickups.len(), pickups + 2);
+    }
+}
@@ -7,15 +7,26 @@ use shared::{
     components::*,
     coordinates::{MechDoorPositions, MechInteriorPos},
     tile_entity::TileEvent,
+    tile_script::{ScriptAction, ScriptTrigger, TileScript},
     types::{TilePos, WorldPos},
-    PlayerLocation, ServerMessage,
+    ItemType, PlayerLocation, ServerMessage, TILE_SIZE,
 };
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 
 pub struct TileBehaviorSystem {
     pub event_queue: Vec<TileEvent>,
     pub time_elapsed: f32,
+    /// What each scripted tile remembers between ticks
+    script_state: HashMap<Uuid, ScriptState>,
+}
+
+/// Per rule of a scripted tile: who it has already gone off for, and for
+/// timed rules, seconds until the next go
+#[derive(Default)]
+struct ScriptState {
+    inside: Vec<HashSet<Uuid>>,
+    timers: Vec<f32>,
 }
 
 impl TileBehaviorSystem {
@@ -23,6 +34,7 @@ impl TileBehaviorSystem {
         Self {
             event_queue: Vec::new(),
             time_elapsed: 0.0,
+            script_state: HashMap::new(),
         }
     }
 
@@ -42,6 +54,9 @@ impl TileBehaviorSystem {
         // Process auto interactions
         self.process_auto_interactions(&game.entity_storage, &game.players);
 
+        // Process map scripts
+        self.process_tile_scripts(&game.entity_storage, &game.players, delta_time);
+
         // Return collected events
         std::mem::take(&mut self.event_queue)
     }
@@ -110,8 +125,8 @@ impl TileBehaviorSystem {
 
             // Check each player
             for (player_id, player) in players {
-                // Skip if player already carrying something
-                if player.carrying_resource.is_some() {
+                // Skip if player has no free slot
+                if player.inventory.is_full() {
                     continue;
                 }
 
@@ -200,10 +215,10 @@ impl TileBehaviorSystem {
             for (player_id, player) in players {
                 // Check conditions
                 let conditions_met = auto.conditions.iter().all(|cond| match cond {
-                    InteractionCondition::PlayerNotCarrying => player.carrying_resource.is_none(),
-                    InteractionCondition::PlayerCarrying(resource_type) => {
-                        player.carrying_resource == Some(*resource_type)
-                    }
+                    InteractionCondition::PlayerNotCarrying => player.inventory.is_empty(),
+                    InteractionCondition::PlayerCarrying(resource_type) => player
+                        .inventory
+                        .contains(ItemType::Resource(*resource_type)),
                     InteractionCondition::PlayerOnTeam(team) => player.team == *team,
                     InteractionCondition::PlayerOperatingStation(operating) => {
                         player.operating_station.is_some() == *operating
@@ -228,6 +243,84 @@ impl TileBehaviorSystem {
         }
     }
 
+    fn process_tile_scripts(
+        &mut self,
+        entities: &EntityStorage,
+        players: &HashMap<Uuid, Player>,
+        delta_time: f32,
+    ) {
+        self.script_state
+            .retain(|entity_id, _| entities.tile_scripts.contains_key(entity_id));
+
+        for (entity_id, script) in &entities.tile_scripts {
+            let Some(entity_pos) = entities.positions.get(entity_id) else {
+                continue;
+            };
+            let state = self
+                .script_state
+                .entry(*entity_id)
+                .or_insert_with(|| ScriptState::new(script));
+
+            for (rule_index, rule) in script.rules.iter().enumerate() {
+                // Only crew out in the world can walk over map tiles
+                let in_reach: HashSet<Uuid> = players
+                    .iter()
+                    .filter_map(|(player_id, player)| match player.location {
+                        PlayerLocation::OutsideWorld(pos) => Some((*player_id, pos)),
+                        PlayerLocation::InsideMech { .. } => None,
+                    })
+                    .filter(|(_, pos)| match rule.trigger {
+                        ScriptTrigger::OnProximity { range } => {
+                            calculate_distance(&entity_pos.world, pos) <= range * TILE_SIZE
+                                || pos.to_tile_pos() == entity_pos.tile
+                        }
+                        ScriptTrigger::OnEnter | ScriptTrigger::OnTick { .. } => {
+                            pos.to_tile_pos() == entity_pos.tile
+                        }
+                    })
+                    .map(|(player_id, _)| player_id)
+                    .collect();
+
+                match rule.trigger {
+                    // Once per arrival; leaving and coming back goes off again
+                    ScriptTrigger::OnEnter | ScriptTrigger::OnProximity { .. } => {
+                        let inside = &mut state.inside[rule_index];
+                        for actor in in_reach.difference(inside) {
+                            self.event_queue.push(TileEvent::ScriptTriggered {
+                                entity: *entity_id,
+                                rule: rule_index,
+                                actor: Some(*actor),
+                            });
+                        }
+                        *inside = in_reach;
+                    }
+                    ScriptTrigger::OnTick { interval } => {
+                        let timer = &mut state.timers[rule_index];
+                        *timer -= delta_time;
+                        if *timer > 0.0 {
+                            continue;
+                        }
+                        *timer += interval;
+                        if in_reach.is_empty() {
+                            self.event_queue.push(TileEvent::ScriptTriggered {
+                                entity: *entity_id,
+                                rule: rule_index,
+                                actor: None,
+                            });
+                        }
+                        for actor in in_reach {
+                            self.event_queue.push(TileEvent::ScriptTriggered {
+                                entity: *entity_id,
+                                rule: rule_index,
+                                actor: Some(actor),
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+
     /// Process tile events synchronously for testing purposes
     /// This method exposes the tile event processing logic for unit tests
     #[cfg(test)]
@@ -256,8 +349,14 @@ impl TileBehaviorSystem {
                                         if tile_pos == doors.left_door
                                             || tile_pos == doors.right_door
                                         {
-                                            // Check team access
-                                            if mech.team == player.team {
+                                            // Crew walk in; enemies only get
+                                            // through doors broken by damage
+                                            if mech.team == player.team
+                                                || MechDoorPositions::breached(
+                                                    mech.health,
+                                                    mech.max_health,
+                                                )
+                                            {
                                                 // Update player location to be inside mech
                                                 if let Some(player) = game.players.get_mut(&actor) {
                                                     let entry_pos =
@@ -312,6 +411,7 @@ impl TileBehaviorSystem {
 
 impl GameSystem for TileBehaviorSystem {
     fn update(&mut self, game: &mut Game, delta_time: f32) -> Vec<ServerMessage> {
+        profiling::scope!("TileBehaviorSystem");
         // Process tile behaviors and get events
         let events = self.process_behaviors(delta_time, game);
 
@@ -320,25 +420,9 @@ impl GameSystem for TileBehaviorSystem {
 
         for event in events {
             match event {
-                TileEvent::ResourcePickedUp {
-                    resource_entity,
-                    actor,
-                    resource_type,
-                } => {
-                    // Update game state
-                    if let Some(player) = game.players.get_mut(&actor) {
-                        if player.carrying_resource.is_none() {
-                            player.carrying_resource = Some(resource_type);
-                            // Remove resource from world
-                            game.remove_resource(resource_entity);
-
-                            messages.push(ServerMessage::PlayerPickedUpResource {
-                                player_id: actor,
-                                resource_id: resource_entity,
-                                resource_type,
-                            });
-                        }
-                    }
+                TileEvent::ResourcePickedUp { actor, .. } => {
+                    // Several things may be in reach; the inventory decides which
+                    messages.extend(game.pick_up_in_reach(actor));
                 }
                 TileEvent::MechEntered {
                     mech_id,
@@ -398,7 +482,7 @@ impl GameSystem for TileBehaviorSystem {
                     // Handle based on action type
                     if let AutoInteractionType::DropResource = action {
                         if let Some(player) = game.players.get_mut(&actor) {
-                            if let Some(resource_type) = player.carrying_resource {
+                            if player.inventory.has_resource() {
                                 // Get player's current location
                                 let (mech_to_deposit, tile_pos) = match player.location {
                                     PlayerLocation::InsideMech { mech_id, pos, .. } => {
@@ -426,31 +510,41 @@ impl GameSystem for TileBehaviorSystem {
                                 };
 
                                 // Deposit resource to the mech
-                                if let Some(mech_id) = mech_to_deposit {
-                                    if let Some(mech) = game.mechs.get_mut(&mech_id) {
-                                        if mech.team == player.team {
-                                            *mech
-                                                .resource_inventory
-                                                .entry(resource_type)
-                                                .or_insert(0) += 1;
-                                            player.carrying_resource = None;
-
-                                            messages.push(ServerMessage::PlayerDroppedResource {
-                                                player_id: actor,
-                                                resource_type,
-                                                position: tile_pos,
-                                            });
-
-                                            log::info!(
-                                                "Player {actor} deposited {resource_type:?} to mech cargo bay"
-                                            );
-                                        }
+                                let team = player.team;
+                                if let Some(mech_id) = mech_to_deposit.filter(|id| {
+                                    game.mechs.get(id).is_some_and(|mech| mech.team == team)
+                                }) {
+                                    let resources = player.inventory.take_resources();
+                                    messages.push(player.inventory_message());
+
+                                    for resource_type in resources {
+                                        messages.push(ServerMessage::PlayerDroppedResource {
+                                            player_id: actor,
+                                            resource_type,
+                                            position: tile_pos,
+                                        });
+                                        messages.extend(game.deliver_resource(
+                                            actor,
+                                            mech_id,
+                                            resource_type,
+                                        ));
+
+                                        log::info!(
+                                            "Player {actor} deposited {resource_type:?} to mech cargo bay"
+                                        );
                                     }
                                 }
                             }
                         }
                     }
                 }
+                TileEvent::ScriptTriggered {
+                    entity,
+                    rule,
+                    actor,
+                } => {
+                    messages.extend(run_script_rule(game, entity, rule, actor));
+                }
                 TileEvent::BeginTransition {
                     actor,
                     zone_id: _,
@@ -470,8 +564,14 @@ impl GameSystem for TileBehaviorSystem {
                                         if tile_pos == doors.left_door
                                             || tile_pos == doors.right_door
                                         {
-                                            // Check team access
-                                            if mech.team == player.team {
+                                            // Crew walk in; enemies only get
+                                            // through doors broken by damage
+                                            if mech.team == player.team
+                                                || MechDoorPositions::breached(
+                                                    mech.health,
+                                                    mech.max_health,
+                                                )
+                                            {
                                                 // Generate MechEntered event
                                                 messages.push(ServerMessage::PlayerMoved {
                                                     player_id: actor,
@@ -537,6 +637,101 @@ impl GameSystem for TileBehaviorSystem {
     }
 }
 
+impl ScriptState {
+    fn new(script: &TileScript) -> Self {
+        Self {
+            inside: vec![HashSet::new(); script.rules.len()],
+            timers: script
+                .rules
+                .iter()
+                .map(|rule| match rule.trigger {
+                    ScriptTrigger::OnTick { interval } => interval,
+                    _ => 0.0,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Carry out one rule of a tile's script on the player who set it off.
+/// Without a player only effects happen.
+fn run_script_rule(
+    game: &mut Game,
+    entity: Uuid,
+    rule: usize,
+    actor: Option<Uuid>,
+) -> Vec<ServerMessage> {
+    let mut messages = Vec::new();
+    let (Some(script), Some(position)) = (
+        game.entity_storage.tile_scripts.get(&entity),
+        game.entity_storage.positions.get(&entity),
+    ) else {
+        return messages;
+    };
+    let Some(actions) = script.rules.get(rule).map(|rule| rule.actions.clone()) else {
+        return messages;
+    };
+    let center = position.world;
+
+    for action in actions {
+        if let ScriptAction::SpawnEffect { name, duration } = &action {
+            messages.push(ServerMessage::EffectCreated {
+                effect_id: game.rng.uuid(),
+                effect_type: name.clone(),
+                position: center,
+                duration: *duration,
+            });
+            continue;
+        }
+        let Some(player) = actor.and_then(|id| game.players.get_mut(&id)) else {
+            continue;
+        };
+        let player_id = player.id;
+        match action {
+            ScriptAction::Damage(damage) => {
+                player.health = player.health.saturating_sub(damage);
+                let health = player.health;
+                // The tile itself is the attacker
+                messages.push(ServerMessage::PlayerAttacked {
+                    attacker: entity,
+                    target: player_id,
+                    damage,
+                    health,
+                });
+                if health == 0 {
+                    // Nothing else happens to someone who's been sent home
+                    messages.extend(game.knock_out(player_id, None));
+                    break;
+                }
+            }
+            ScriptAction::Teleport(target) => {
+                player.location = PlayerLocation::OutsideWorld(target.to_world_center());
+                messages.push(ServerMessage::PlayerMoved {
+                    player_id,
+                    location: player.location,
+                });
+            }
+            ScriptAction::GrantResource {
+                resource_type,
+                amount,
+            } => {
+                for _ in 0..amount {
+                    if player
+                        .inventory
+                        .add(ItemType::Resource(resource_type))
+                        .is_none()
+                    {
+                        break;
+                    }
+                }
+                messages.push(player.inventory_message());
+            }
+            ScriptAction::SpawnEffect { .. } => {}
+        }
+    }
+    messages
+}
+
 // Helper functions
 fn get_player_world_pos(location: &PlayerLocation) -> WorldPos {
     // Note: This is used for resource pickup distance calculations
@@ -591,8 +786,13 @@ mod tests {
                 name: "Test".to_string(),
                 team: TeamId::Red,
                 location: PlayerLocation::OutsideWorld(WorldPos::new(85.0, 85.0)),
-                carrying_resource: None,
+                inventory: shared::Inventory::default(),
                 operating_station: None,
+                held_tool: None,
+                health: shared::PLAYER_MAX_HEALTH,
+                facing: shared::Facing::default(),
+                dash_ready_at: 0.0,
+                scanner_ready_at: 0.0,
             },
         );
 
@@ -609,4 +809,48 @@ mod tests {
             _ => panic!("Wrong event type"),
         }
     }
+
+    #[test]
+    fn test_map_scripts_fire_on_arrival_and_on_a_timer() {
+        use crate::testing_modes::TestingConfig;
+        use shared::{ArenaMap, ResourceType, PLAYER_MAX_HEALTH};
+
+        let (trap, pad) = (TilePos::new(40, 40), TilePos::new(44, 40));
+        let mut map = ArenaMap::default();
+        map.set_script(trap, "enter: damage 10, teleport 60 60".parse().unwrap());
+        map.set_script(pad, "every 2: give wiring, effect glow".parse().unwrap());
+        let mut game = Game::new_with_map(TestingConfig::create_normal_config(), map, 0);
+        let mut system = TileBehaviorSystem::new();
+
+        let player_id = Uuid::new_v4();
+        game.add_player(player_id, "Walker".to_string(), Some(TeamId::Red));
+        let step_onto = |game: &mut Game, tile: TilePos| {
+            game.players.get_mut(&player_id).unwrap().location =
+                PlayerLocation::OutsideWorld(tile.to_world_center());
+        };
+
+        step_onto(&mut game, trap);
+        system.update(&mut game, 0.1);
+        let player = &game.players[&player_id];
+        assert_eq!(player.health, PLAYER_MAX_HEALTH - 10);
+        assert_eq!(
+            player.location,
+            PlayerLocation::OutsideWorld(TilePos::new(60, 60).to_world_center())
+        );
+
+        // The pad only pays out once its timer comes round
+        step_onto(&mut game, pad);
+        system.update(&mut game, 1.0);
+        assert!(game.players[&player_id].inventory.is_empty());
+        let messages = system.update(&mut game, 1.0);
+        assert!(game.players[&player_id]
+            .inventory
+            .contains(ItemType::Resource(ResourceType::Wiring)));
+        assert!(messages
+            .iter()
+            .any(|m| matches!(
+                m,
+                ServerMessage::EffectCreated { effect_type, .. } if effect_type == "glow"
+            )));
+    }
 }
@@ -0,0 +1,347 @@
+//! Guided scenarios. A tutorial is a list of commands, one per line, that
+//! every player joining the match works through at their own pace:
+//!
+//! ```text
+//! # Lines starting with '#' are comments
+//! say Walk through the door of your mech
+//! wait mech
+//! say Take the laser with Space and press 1 to fire
+//! wait station WeaponLaser
+//! wait button 0
+//! spawn scrap_metal 24 18
+//! say Bring that scrap back to the mech
+//! wait 10
+//! ```
+//!
+//! `say <text>` shows the player an instruction, `spawn <resource> <x> <y>`
+//! drops a resource on a world tile and `wait` holds the script until the
+//! player is in a mech (`mech`), crews a station type (`station <type>`),
+//! presses a station button (`button <n>`), stands on a tile
+//! (`tile <x> <y>`) or some seconds have passed (`<seconds>`).
+
+use super::GameSystem;
+use crate::events::GameEvent;
+use crate::game::Game;
+use shared::*;
+use std::collections::HashMap;
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// What a `wait` holds out for
+#[derive(Debug, Clone, PartialEq)]
+pub enum TutorialCondition {
+    InMech,
+    OperatingStation(StationType),
+    ButtonPressed(u8),
+    OnTile(TilePos),
+    Seconds(f32),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TutorialCommand {
+    Say(String),
+    SpawnResource {
+        resource_type: ResourceType,
+        position: TilePos,
+    },
+    Wait(TutorialCondition),
+}
+
+/// A parsed tutorial, in the order its commands run
+#[derive(Debug, Clone, PartialEq)]
+pub struct TutorialScript {
+    pub commands: Vec<TutorialCommand>,
+}
+
+impl TutorialScript {
+    pub fn load(path: &std::path::Path) -> GameResult<Self> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| GameError::invalid_input(format!("can't read {}: {e}", path.display())))?;
+        text.parse()
+            .map_err(|e| GameError::invalid_input(format!("bad tutorial {}: {e}", path.display())))
+    }
+
+    /// How many instructions the player will be shown
+    fn instruction_count(&self) -> u32 {
+        self.commands
+            .iter()
+            .filter(|command| matches!(command, TutorialCommand::Say(_)))
+            .count() as u32
+    }
+}
+
+impl FromStr for TutorialScript {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, String> {
+        let commands = text
+            .lines()
+            .enumerate()
+            .map(|(number, line)| (number + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+            .map(|(number, line)| parse_command(line).map_err(|e| format!("line {number}: {e}")))
+            .collect::<Result<Vec<_>, _>>()?;
+        if commands.is_empty() {
+            return Err("tutorial has no commands".to_string());
+        }
+        Ok(Self { commands })
+    }
+}
+
+fn parse_command(line: &str) -> Result<TutorialCommand, String> {
+    let (verb, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    let words: Vec<&str> = rest.split_whitespace().collect();
+    match (verb, words.as_slice()) {
+        ("say", [_, ..]) => Ok(TutorialCommand::Say(rest.trim().to_string())),
+        ("spawn", [resource, x, y]) => {
+            let Some((_, resource_type)) = RESOURCE_NAMES.iter().find(|(n, _)| n == resource)
+            else {
+                return Err(format!("unknown resource '{resource}'"));
+            };
+            Ok(TutorialCommand::SpawnResource {
+                resource_type: *resource_type,
+                position: TilePos::new(coordinate(x)?, coordinate(y)?),
+            })
+        }
+        ("wait", words) => parse_condition(words).map(TutorialCommand::Wait),
+        _ => Err(format!(
+            "unknown command '{line}', expected say, spawn or wait"
+        )),
+    }
+}
+
+fn parse_condition(words: &[&str]) -> Result<TutorialCondition, String> {
+    match words {
+        ["mech"] => Ok(TutorialCondition::InMech),
+        ["station", name] => StationType::ALL
+            .into_iter()
+            .find(|station_type| format!("{station_type:?}").eq_ignore_ascii_case(name))
+            .map(TutorialCondition::OperatingStation)
+            .ok_or_else(|| format!("unknown station type '{name}'")),
+        ["button", index] => index
+            .parse()
+            .map(TutorialCondition::ButtonPressed)
+            .map_err(|_| format!("'{index}' is not a button number")),
+        ["tile", x, y] => Ok(TutorialCondition::OnTile(TilePos::new(
+            coordinate(x)?,
+            coordinate(y)?,
+        ))),
+        [seconds] => seconds
+            .parse::<f32>()
+            .ok()
+            .filter(|seconds| seconds.is_finite() && *seconds >= 0.0)
+            .map(TutorialCondition::Seconds)
+            .ok_or_else(|| format!("'{seconds}' is not a number of seconds")),
+        _ => Err(
+            "wait needs 'mech', 'station <type>', 'button <n>', 'tile <x> <y>' or seconds"
+                .to_string(),
+        ),
+    }
+}
+
+fn coordinate(word: &str) -> Result<i32, String> {
+    word.parse()
+        .map_err(|_| format!("'{word}' is not a tile coordinate"))
+}
+
+/// Where a player is in the tutorial
+#[derive(Default)]
+struct TutorialProgress {
+    next: usize,
+    /// Seconds spent on the current `wait`
+    waited: f32,
+    instructions_shown: u32,
+}
+
+/// Runs the loaded tutorial for every human player, advancing each past
+/// `wait`s as they meet them. Does nothing when no tutorial is loaded.
+pub struct TutorialSystem {
+    script: Option<TutorialScript>,
+    progress: HashMap<Uuid, TutorialProgress>,
+}
+
+impl TutorialSystem {
+    pub fn new() -> Self {
+        Self {
+            script: None,
+            progress: HashMap::new(),
+        }
+    }
+
+    /// Start running `script`, from the top for everyone
+    pub fn set_script(&mut self, script: TutorialScript) {
+        self.script = Some(script);
+        self.progress.clear();
+    }
+
+    fn is_met(game: &Game, player_id: Uuid, condition: &TutorialCondition, waited: f32) -> bool {
+        let Some(player) = game.players.get(&player_id) else {
+            return false;
+        };
+        match condition {
+            TutorialCondition::InMech => player.location.mech_id().is_some(),
+            TutorialCondition::OperatingStation(station_type) => {
+                player.operating_station.is_some_and(|station_id| {
+                    game.mechs.values().any(|mech| {
+                        mech.stations
+                            .get(&station_id)
+                            .is_some_and(|station| station.station_type == *station_type)
+                    })
+                })
+            }
+            TutorialCondition::ButtonPressed(button) => game.events.pending().iter().any(|event| {
+                matches!(event, GameEvent::StationButtonPressed { player_id: presser, button_index, .. }
+                    if *presser == player_id && button_index == button)
+            }),
+            TutorialCondition::OnTile(tile) => {
+                matches!(player.location, PlayerLocation::OutsideWorld(pos) if pos.to_tile() == *tile)
+            }
+            TutorialCondition::Seconds(seconds) => waited >= *seconds,
+        }
+    }
+}
+
+impl GameSystem for TutorialSystem {
+    fn update(&mut self, game: &mut Game, delta_time: f32) -> Vec<ServerMessage> {
+        profiling::scope!("tutorial");
+        let Some(script) = &self.script else {
+            return Vec::new();
+        };
+        let mut messages = Vec::new();
+        let ai_players = game.get_ai_players();
+        let total = script.instruction_count();
+
+        self.progress.retain(|id, _| game.players.contains_key(id));
+        let mut player_ids: Vec<Uuid> = game.players.keys().copied().collect();
+        player_ids.retain(|id| !ai_players.contains(id));
+
+        for player_id in player_ids {
+            let progress = self.progress.entry(player_id).or_default();
+            if progress.next >= script.commands.len() {
+                continue;
+            }
+            progress.waited += delta_time;
+
+            // Run commands until one has to wait or the script runs out
+            while let Some(command) = script.commands.get(progress.next) {
+                match command {
+                    TutorialCommand::Say(text) => {
+                        progress.instructions_shown += 1;
+                        messages.push(ServerMessage::TutorialInstruction {
+                            player_id,
+                            text: text.clone(),
+                            step: progress.instructions_shown,
+                            total,
+                        });
+                    }
+                    TutorialCommand::SpawnResource {
+                        resource_type,
+                        position,
+                    } => {
+                        let resource_id =
+                            game.spawn_resource_with_behavior(*position, *resource_type);
+                        messages.push(ServerMessage::ResourceSpawned {
+                            resource_id,
+                            position: *position,
+                            resource_type: *resource_type,
+                        });
+                    }
+                    TutorialCommand::Wait(condition) => {
+                        if !Self::is_met(game, player_id, condition, progress.waited) {
+                            break;
+                        }
+                    }
+                }
+                progress.next += 1;
+                progress.waited = 0.0;
+                if progress.next == script.commands.len() {
+                    log::info!("Player {player_id} finished the tutorial");
+                    messages.push(ServerMessage::TutorialComplete { player_id });
+                }
+            }
+        }
+
+        messages
+    }
+
+    fn name(&self) -> &'static str {
+        "tutorial"
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn instructions(messages: &[ServerMessage]) -> Vec<&str> {
+        messages
+            .iter()
+            .filter_map(|message| match message {
+                ServerMessage::TutorialInstruction { text, .. } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_reports_the_bad_line() {
+        let script: TutorialScript = "# intro\nsay Hello there\n\nwait station Engine\nwait 2.5"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            script.commands,
+            vec![
+                TutorialCommand::Say("Hello there".to_string()),
+                TutorialCommand::Wait(TutorialCondition::OperatingStation(StationType::Engine)),
+                TutorialCommand::Wait(TutorialCondition::Seconds(2.5)),
+            ]
+        );
+
+        let error = "say Hi\nwait forever"
+            .parse::<TutorialScript>()
+            .unwrap_err();
+        assert!(error.starts_with("line 2:"), "{error}");
+        assert!("spawn gold 1 2".parse::<TutorialScript>().is_err());
+
+        // The sample that ships with the game stays loadable
+        include_str!("../../../shared/data/tutorial.txt")
+            .parse::<TutorialScript>()
+            .unwrap();
+    }
+
+    #[test]
+    fn test_each_player_advances_on_their_own_button_press() {
+        let mut game = Game::new();
+        let first = Uuid::new_v4();
+        let second = Uuid::new_v4();
+        game.add_player(first, "First".to_string(), None);
+        game.add_player(second, "Second".to_string(), None);
+        let mut tutorial = TutorialSystem::new();
+        tutorial.set_script(
+            "say Press fire\nwait button 0\nsay Well done"
+                .parse()
+                .unwrap(),
+        );
+
+        let messages = tutorial.update(&mut game, 0.1);
+        assert_eq!(instructions(&messages), vec!["Press fire", "Press fire"]);
+        assert!(instructions(&tutorial.update(&mut game, 0.1)).is_empty());
+
+        game.events.publish(GameEvent::StationButtonPressed {
+            player_id: second,
+            station_id: Uuid::new_v4(),
+            button_index: 0,
+        });
+        let messages = tutorial.update(&mut game, 0.1);
+        assert_eq!(instructions(&messages), vec!["Well done"]);
+        assert!(messages.iter().any(|message| matches!(
+            message,
+            ServerMessage::TutorialComplete { player_id } if *player_id == second
+        )));
+        assert_eq!(tutorial.progress[&first].next, 1);
+    }
+}
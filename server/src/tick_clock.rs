@@ -0,0 +1,155 @@
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+
+/// Latest loop timing, for the metrics endpoint
+pub type SharedTickMetrics = Arc<RwLock<TickMetrics>>;
+
+/// How well the simulation is keeping up with wall-clock time
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TickMetrics {
+    /// Fixed steps simulated since the loop started
+    pub steps: u64,
+    pub frames: u64,
+    /// Steps run on the most recent frame; more than one means it was catching up
+    pub last_frame_steps: u32,
+    pub peak_frame_steps: u32,
+    /// Frames that hit the catch-up cap and dropped the rest of their backlog
+    pub capped_frames: u64,
+    /// Simulation time given up to the catch-up cap
+    pub dropped_ms: f64,
+    /// Wall-clock time minus simulated time
+    pub drift_ms: f64,
+    /// Time waiting in the accumulator for the next step
+    pub backlog_ms: f64,
+    /// Wall time spent simulating one step
+    pub last_step_ms: f64,
+    pub peak_step_ms: f64,
+    pub broadcasts: u64,
+    /// Smoothed share of the frame budget spent working
+    pub load: f32,
+    pub load_level: shared::ServerLoad,
+}
+
+/// Fixed-timestep accumulator: wall-clock time goes in, a whole number of
+/// simulation steps comes out, so physics sees the same delta however late
+/// the loop wakes up.
+pub struct FixedTimestep {
+    step: Duration,
+    max_steps: u32,
+    accumulator: Duration,
+    started: Instant,
+    last: Instant,
+    metrics: TickMetrics,
+}
+
+impl FixedTimestep {
+    pub fn new(step: Duration, max_steps: u32, now: Instant) -> Self {
+        Self {
+            step,
+            max_steps,
+            accumulator: Duration::ZERO,
+            started: now,
+            last: now,
+            metrics: TickMetrics::default(),
+        }
+    }
+
+    /// Steps owed for the time since the last call. Past `max_steps` the
+    /// rest of the backlog is dropped rather than letting one slow frame
+    /// snowball into the next.
+    pub fn advance(&mut self, now: Instant) -> u32 {
+        self.accumulator += now.saturating_duration_since(self.last);
+        self.last = now;
+
+        let owed = self.accumulator.as_nanos() / self.step.as_nanos();
+        let steps = owed.min(self.max_steps as u128) as u32;
+        self.accumulator -= self.step * steps;
+
+        if owed > self.max_steps as u128 {
+            let remainder =
+                Duration::from_nanos((self.accumulator.as_nanos() % self.step.as_nanos()) as u64);
+            self.metrics.capped_frames += 1;
+            self.metrics.dropped_ms += millis(self.accumulator - remainder);
+            self.accumulator = remainder;
+        }
+
+        self.metrics.frames += 1;
+        self.metrics.steps += steps as u64;
+        self.metrics.last_frame_steps = steps;
+        self.metrics.peak_frame_steps = self.metrics.peak_frame_steps.max(steps);
+        self.metrics.backlog_ms = millis(self.accumulator);
+        self.metrics.drift_ms = millis(now.saturating_duration_since(self.started))
+            - millis(self.step) * self.metrics.steps as f64;
+        steps
+    }
+
+    /// Wall time one step took to simulate
+    pub fn record_step(&mut self, elapsed: Duration) {
+        self.metrics.last_step_ms = millis(elapsed);
+        self.metrics.peak_step_ms = self.metrics.peak_step_ms.max(self.metrics.last_step_ms);
+    }
+
+    pub fn record_broadcast(&mut self) {
+        self.metrics.broadcasts += 1;
+    }
+
+    pub fn record_load(&mut self, load: f32, level: shared::ServerLoad) {
+        self.metrics.load = load;
+        self.metrics.load_level = level;
+    }
+
+    pub fn metrics(&self) -> &TickMetrics {
+        &self.metrics
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STEP: Duration = Duration::from_millis(30);
+
+    #[test]
+    fn test_steps_follow_elapsed_time() {
+        let start = Instant::now();
+        let mut clock = FixedTimestep::new(STEP, 5, start);
+
+        assert_eq!(clock.advance(start + Duration::from_millis(75)), 2);
+        assert!((clock.metrics().backlog_ms - 15.0).abs() < 0.01);
+
+        // The leftover carries into the next frame
+        assert_eq!(clock.advance(start + Duration::from_millis(90)), 1);
+        assert_eq!(clock.metrics().steps, 3);
+        assert!(clock.metrics().drift_ms.abs() < 0.01);
+    }
+
+    #[test]
+    fn test_catch_up_is_capped() {
+        let start = Instant::now();
+        let mut clock = FixedTimestep::new(STEP, 5, start);
+
+        // A 20-step stall only replays the cap and drops the rest
+        assert_eq!(
+            clock.advance(start + STEP * 20 + Duration::from_millis(10)),
+            5
+        );
+        let metrics = clock.metrics();
+        assert_eq!(metrics.capped_frames, 1);
+        assert!((metrics.dropped_ms - 450.0).abs() < 0.01);
+        assert!((metrics.backlog_ms - 10.0).abs() < 0.01);
+        assert!(metrics.drift_ms > 450.0);
+
+        // Back on schedule afterwards
+        assert_eq!(
+            clock.advance(start + STEP * 21 + Duration::from_millis(10)),
+            1
+        );
+        assert_eq!(clock.metrics().capped_frames, 1);
+    }
+}
@@ -0,0 +1,223 @@
+use shared::*;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// Where sight is cast from: a world tile, plus the mech floor for crew
+/// inside and the way crew outside face when sight is a cone
+pub type Viewpoint = (TilePos, Option<u8>, Option<Facing>);
+
+struct Viewer {
+    /// Where `visible` was cast from; `None` until the first cast
+    cast_from: Option<Viewpoint>,
+    /// Where the viewer is now
+    at: Viewpoint,
+    visible: Arc<HashSet<TilePos>>,
+    queued: bool,
+}
+
+/// Spreads line-of-sight raycasts across ticks. Viewers are only recast
+/// once they've moved more than `VISION_RECALC_DISTANCE` tiles or the world
+/// around them changed, a few per tick within a time budget, and sight is
+/// cached per viewpoint so crew standing together share one cast.
+#[derive(Default)]
+pub struct VisionScheduler {
+    viewers: HashMap<Uuid, Viewer>,
+    /// Viewers waiting for a recast, oldest first
+    stale: VecDeque<Uuid>,
+    cache: HashMap<Viewpoint, Arc<HashSet<TilePos>>>,
+}
+
+impl VisionScheduler {
+    /// Note where a viewer is, queueing a recast once they've moved far enough
+    pub fn observe(&mut self, viewer_id: Uuid, at: Viewpoint) {
+        let viewer = self.viewers.entry(viewer_id).or_insert_with(|| Viewer {
+            cast_from: None,
+            at,
+            visible: Arc::default(),
+            queued: false,
+        });
+        viewer.at = at;
+        let Some((tile, floor, facing)) = viewer.cast_from else {
+            return;
+        };
+        let moved = (tile.x - at.0.x).abs().max((tile.y - at.0.y).abs());
+        let turned = floor != at.1 || facing != at.2;
+        if (moved > VISION_RECALC_DISTANCE || turned) && !viewer.queued {
+            viewer.queued = true;
+            self.stale.push_back(viewer_id);
+        }
+    }
+
+    /// Forget viewers that have left
+    pub fn retain(&mut self, keep: impl Fn(&Uuid) -> bool) {
+        self.viewers.retain(|id, _| keep(id));
+        self.stale.retain(|id| keep(id));
+    }
+
+    /// Throw out sight that could have passed over changed world tiles and
+    /// queue everyone who was looking at them
+    pub fn invalidate(&mut self, changed: &HashSet<TilePos>, range: i32) {
+        if changed.is_empty() {
+            return;
+        }
+        let near = |tile: TilePos| {
+            changed
+                .iter()
+                .any(|c| (c.x - tile.x).abs() <= range && (c.y - tile.y).abs() <= range)
+        };
+        self.cache.retain(|(tile, ..), _| !near(*tile));
+        for (id, viewer) in &mut self.viewers {
+            if !viewer.queued && viewer.cast_from.is_some_and(|(tile, ..)| near(tile)) {
+                viewer.queued = true;
+                self.stale.push_back(*id);
+            }
+        }
+    }
+
+    /// Throw out every cached sight and queue everyone for a recast, for when
+    /// how far anyone can see has changed
+    pub fn invalidate_all(&mut self) {
+        self.cache.clear();
+        for (id, viewer) in &mut self.viewers {
+            if !viewer.queued && viewer.cast_from.is_some() {
+                viewer.queued = true;
+                self.stale.push_back(*id);
+            }
+        }
+    }
+
+    /// Cast for viewers who have never had sight; they can't wait a turn
+    pub fn catch_up(&mut self, cast: &mut impl FnMut(Viewpoint) -> HashSet<TilePos>) {
+        let fresh: Vec<Uuid> = self
+            .viewers
+            .iter()
+            .filter(|(_, viewer)| viewer.cast_from.is_none())
+            .map(|(id, _)| *id)
+            .collect();
+        for viewer_id in fresh {
+            self.recast(viewer_id, cast);
+        }
+    }
+
+    /// Catch up new viewers, then recast stale ones oldest first until the
+    /// per-tick count or time budget runs out
+    pub fn run(&mut self, mut cast: impl FnMut(Viewpoint) -> HashSet<TilePos>) {
+        self.catch_up(&mut cast);
+
+        let start = Instant::now();
+        let budget = Duration::from_secs_f32(VISION_RECALC_BUDGET_MS / 1000.0);
+        let mut recast = 0;
+        while recast < VISION_RECALCS_PER_TICK && start.elapsed() < budget {
+            let Some(viewer_id) = self.stale.pop_front() else {
+                break;
+            };
+            self.recast(viewer_id, &mut cast);
+            recast += 1;
+        }
+    }
+
+    fn recast(&mut self, viewer_id: Uuid, cast: &mut impl FnMut(Viewpoint) -> HashSet<TilePos>) {
+        let Some(viewer) = self.viewers.get_mut(&viewer_id) else {
+            return;
+        };
+        let at = viewer.at;
+        if self.cache.len() >= VISION_CACHE_CAPACITY && !self.cache.contains_key(&at) {
+            self.cache.clear();
+        }
+        viewer.visible = self
+            .cache
+            .entry(at)
+            .or_insert_with(|| Arc::new(cast(at)))
+            .clone();
+        viewer.cast_from = Some(at);
+        viewer.queued = false;
+    }
+
+    /// What a viewer saw at their last cast
+    pub fn visible(&self, viewer_id: Uuid) -> Option<&HashSet<TilePos>> {
+        self.viewers
+            .get(&viewer_id)
+            .filter(|viewer| viewer.cast_from.is_some())
+            .map(|viewer| viewer.visible.as_ref())
+    }
+
+    /// Viewers waiting for a recast
+    pub fn backlog(&self) -> usize {
+        self.stale.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counting_cast(casts: &mut usize) -> impl FnMut(Viewpoint) -> HashSet<TilePos> + '_ {
+        move |(tile, ..)| {
+            *casts += 1;
+            HashSet::from([tile])
+        }
+    }
+
+    #[test]
+    fn test_viewers_recast_only_after_moving_and_share_viewpoints() {
+        let mut scheduler = VisionScheduler::default();
+        let (first, second) = (Uuid::new_v4(), Uuid::new_v4());
+        let origin = TilePos::new(10, 10);
+        scheduler.observe(first, (origin, None, None));
+        scheduler.observe(second, (origin, None, None));
+
+        let mut casts = 0;
+        scheduler.run(counting_cast(&mut casts));
+        assert_eq!(casts, 1, "viewers on the same tile share a cast");
+        assert!(scheduler.visible(second).unwrap().contains(&origin));
+
+        // Shuffling within a tile keeps the old sight
+        scheduler.observe(
+            first,
+            (origin.offset(VISION_RECALC_DISTANCE, 0), None, None),
+        );
+        assert_eq!(scheduler.backlog(), 0);
+
+        let moved = origin.offset(VISION_RECALC_DISTANCE + 1, 0);
+        scheduler.observe(first, (moved, None, None));
+        scheduler.observe(second, (origin, Some(1), None));
+        assert_eq!(scheduler.backlog(), 2);
+        scheduler.run(counting_cast(&mut casts));
+        assert_eq!(casts, 3);
+        assert!(scheduler.visible(first).unwrap().contains(&moved));
+
+        // Turning on the spot changes what a cone sees
+        scheduler.observe(first, (moved, None, Some(Facing::North)));
+        assert_eq!(scheduler.backlog(), 1);
+    }
+
+    #[test]
+    fn test_recasts_are_spread_over_ticks_and_follow_world_changes() {
+        let mut scheduler = VisionScheduler::default();
+        let viewers: Vec<Uuid> = (0..VISION_RECALCS_PER_TICK * 2)
+            .map(|_| Uuid::new_v4())
+            .collect();
+        for (i, id) in viewers.iter().enumerate() {
+            scheduler.observe(*id, (TilePos::new(i as i32 * 10, 0), None, None));
+        }
+        scheduler.run(counting_cast(&mut 0));
+
+        for (i, id) in viewers.iter().enumerate() {
+            scheduler.observe(*id, (TilePos::new(i as i32 * 10, 5), None, None));
+        }
+        scheduler.run(counting_cast(&mut 0));
+        assert!(scheduler.backlog() >= VISION_RECALCS_PER_TICK);
+        while scheduler.backlog() > 0 {
+            scheduler.run(counting_cast(&mut 0));
+        }
+
+        // A wall going up next to the first viewer queues only them
+        scheduler.invalidate(&HashSet::from([TilePos::new(1, 5)]), 2);
+        assert_eq!(scheduler.backlog(), 1);
+        let mut casts = 0;
+        scheduler.run(counting_cast(&mut casts));
+        assert_eq!(casts, 1, "the cached viewpoint was thrown out");
+    }
+}
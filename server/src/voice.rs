@@ -0,0 +1,247 @@
+//! Push-to-talk relay. A join that agrees on the `Voice` feature gets a
+//! one-use key; the client opens `/voice` with it, and every frame it sends
+//! from then on goes to its teammates' voice sockets. Audio is never decoded
+//! here, only checked for size and rate.
+
+use axum::extract::ws::{Message, WebSocket};
+use futures::{sink::SinkExt, stream::StreamExt};
+use shared::*;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+pub type SharedVoiceRelay = Arc<Mutex<VoiceRelay>>;
+
+/// A voice socket frames are written to
+struct Listener {
+    /// Which of the player's sockets this is, so an old one closing late
+    /// leaves its replacement alone
+    connection: u64,
+    team: TeamId,
+    frames: mpsc::Sender<Vec<u8>>,
+}
+
+#[derive(Default)]
+pub struct VoiceRelay {
+    /// Keys handed out and not used yet, with who they open the channel for
+    keys: HashMap<Uuid, (PlayerId, TeamId)>,
+    listeners: HashMap<PlayerId, Listener>,
+    /// When each speaker's current second started and frames sent in it
+    rates: HashMap<PlayerId, (Instant, u32)>,
+    next_connection: u64,
+}
+
+impl VoiceRelay {
+    /// A fresh key for the player, replacing any they didn't use
+    pub fn issue_key(&mut self, player_id: PlayerId, team: TeamId) -> Uuid {
+        self.keys.retain(|_, (owner, _)| *owner != player_id);
+        let key = Uuid::new_v4();
+        self.keys.insert(key, (player_id, team));
+        key
+    }
+
+    /// Who the key was issued to; it can't be used again
+    pub fn redeem(&mut self, key: Uuid) -> Option<(PlayerId, TeamId)> {
+        self.keys.remove(&key)
+    }
+
+    /// Start listening for teammates, replacing an older socket of the
+    /// player's. Returns the connection to hand `disconnect` when it closes.
+    pub fn connect(&mut self, player_id: PlayerId, team: TeamId) -> (u64, mpsc::Receiver<Vec<u8>>) {
+        let (frames, receiver) = mpsc::channel(VOICE_RELAY_BUFFER_FRAMES);
+        let connection = self.next_connection;
+        self.next_connection += 1;
+        self.listeners.insert(
+            player_id,
+            Listener {
+                connection,
+                team,
+                frames,
+            },
+        );
+        (connection, receiver)
+    }
+
+    /// A socket closed. Forget the player only if it was still their
+    /// current one; a reconnect that replaced it keeps going.
+    pub fn disconnect(&mut self, player_id: PlayerId, connection: u64) {
+        let current = self
+            .listeners
+            .get(&player_id)
+            .is_some_and(|listener| listener.connection == connection);
+        if current {
+            self.forget(player_id);
+        }
+    }
+
+    /// Drop the player's socket and key; their socket closes once its
+    /// queue runs dry
+    pub fn forget(&mut self, player_id: PlayerId) {
+        self.keys.retain(|_, (owner, _)| *owner != player_id);
+        self.listeners.remove(&player_id);
+        self.rates.remove(&player_id);
+    }
+
+    pub fn listener_count(&self) -> usize {
+        self.listeners.len()
+    }
+
+    /// Pass a frame from the speaker on to their teammates, returning how
+    /// many got it. Malformed frames and ones over the speaker's rate are
+    /// dropped, as is anything a listener is too far behind to take.
+    pub fn relay(&mut self, speaker: PlayerId, bytes: &[u8], now: Instant) -> usize {
+        let Some(team) = self.listeners.get(&speaker).map(|listener| listener.team) else {
+            return 0;
+        };
+        let (sequence, payload) = match decode_voice_frame(bytes) {
+            Ok(frame) => frame,
+            Err(e) => {
+                log::debug!("Dropping voice frame from {speaker}: {e}");
+                return 0;
+            }
+        };
+        let (window_start, sent) = self.rates.entry(speaker).or_insert((now, 0));
+        if now.duration_since(*window_start) >= Duration::from_secs(1) {
+            *window_start = now;
+            *sent = 0;
+        }
+        if *sent >= VOICE_MAX_FRAMES_PER_SECOND {
+            return 0;
+        }
+        *sent += 1;
+
+        let relayed = encode_relayed_voice_frame(speaker, sequence, payload);
+        self.listeners
+            .iter()
+            .filter(|(id, listener)| **id != speaker && listener.team == team)
+            .filter(|(_, listener)| listener.frames.try_send(relayed.clone()).is_ok())
+            .count()
+    }
+}
+
+/// Carry one player's voice socket until either side closes it
+pub async fn handle_voice_socket(
+    socket: WebSocket,
+    relay: SharedVoiceRelay,
+    player_id: PlayerId,
+    team: TeamId,
+) {
+    let (mut sender, mut receiver) = socket.split();
+    let (connection, mut frames) = relay.lock().unwrap().connect(player_id, team);
+    log::info!("Player {player_id} opened voice chat");
+
+    let mut send_task = tokio::spawn(async move {
+        while let Some(bytes) = frames.recv().await {
+            if sender.send(Message::Binary(bytes)).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let receive_relay = relay.clone();
+    let mut receive_task = tokio::spawn(async move {
+        while let Some(Ok(msg)) = receiver.next().await {
+            match msg {
+                Message::Binary(bytes) => {
+                    receive_relay
+                        .lock()
+                        .unwrap()
+                        .relay(player_id, &bytes, Instant::now());
+                }
+                Message::Close(_) => break,
+                _ => {}
+            }
+        }
+    });
+
+    tokio::select! {
+        _ = &mut send_task => receive_task.abort(),
+        _ = &mut receive_task => send_task.abort(),
+    }
+    relay.lock().unwrap().disconnect(player_id, connection);
+    log::info!("Player {player_id} closed voice chat");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keys_open_the_channel_once() {
+        let mut relay = VoiceRelay::default();
+        let player = Uuid::new_v4();
+        let stale = relay.issue_key(player, TeamId::Red);
+        let key = relay.issue_key(player, TeamId::Red);
+
+        assert_eq!(relay.redeem(stale), None);
+        assert_eq!(relay.redeem(key), Some((player, TeamId::Red)));
+        assert_eq!(relay.redeem(key), None);
+    }
+
+    #[test]
+    fn test_frames_reach_teammates_only() {
+        let mut relay = VoiceRelay::default();
+        let (speaker, teammate, enemy) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let (_, mut own) = relay.connect(speaker, TeamId::Red);
+        let (_, mut friendly) = relay.connect(teammate, TeamId::Red);
+        let (_, mut hostile) = relay.connect(enemy, TeamId::Blue);
+
+        let reached = relay.relay(speaker, &encode_voice_frame(4, &[9, 9]), Instant::now());
+        assert_eq!(reached, 1);
+        let heard = decode_relayed_voice_frame(&friendly.try_recv().unwrap()).unwrap();
+        assert_eq!((heard.speaker, heard.sequence), (speaker, 4));
+        assert!(own.try_recv().is_err());
+        assert!(hostile.try_recv().is_err());
+
+        // Once the speaker leaves nothing they send goes anywhere
+        relay.forget(speaker);
+        assert_eq!(
+            relay.relay(speaker, &encode_voice_frame(5, &[9]), Instant::now()),
+            0
+        );
+    }
+
+    #[test]
+    fn test_speakers_over_the_frame_rate_are_cut_off_until_the_next_second() {
+        let mut relay = VoiceRelay::default();
+        let (speaker, listener) = (Uuid::new_v4(), Uuid::new_v4());
+        let _own = relay.connect(speaker, TeamId::Red);
+        let (_, mut frames) = relay.connect(listener, TeamId::Red);
+        let start = Instant::now();
+        let frame = encode_voice_frame(0, &[1]);
+
+        let mut reached = 0;
+        for _ in 0..VOICE_MAX_FRAMES_PER_SECOND + 5 {
+            reached += relay.relay(speaker, &frame, start);
+            // Keep the listener's queue from filling up
+            while frames.try_recv().is_ok() {}
+        }
+        assert_eq!(reached, VOICE_MAX_FRAMES_PER_SECOND as usize);
+        assert_eq!(
+            relay.relay(speaker, &frame, start + Duration::from_secs(1)),
+            1
+        );
+    }
+
+    #[test]
+    fn test_an_old_socket_closing_leaves_the_reconnect_alone() {
+        let mut relay = VoiceRelay::default();
+        let (speaker, listener) = (Uuid::new_v4(), Uuid::new_v4());
+        let _own = relay.connect(speaker, TeamId::Red);
+        let (old, _) = relay.connect(listener, TeamId::Red);
+        let (new, mut frames) = relay.connect(listener, TeamId::Red);
+        let frame = encode_voice_frame(0, &[1]);
+
+        // The replaced socket only notices it's gone after the new one opened
+        relay.disconnect(listener, old);
+        assert_eq!(relay.listener_count(), 2);
+        assert_eq!(relay.relay(speaker, &frame, Instant::now()), 1);
+        assert!(frames.try_recv().is_ok());
+
+        relay.disconnect(listener, new);
+        assert_eq!(relay.listener_count(), 1);
+        assert_eq!(relay.relay(speaker, &frame, Instant::now()), 0);
+    }
+}
@@ -0,0 +1,675 @@
+//! Bots compiled to WebAssembly, run in a wasmtime sandbox.
+//!
+//! A module is picked per AI with `"personality": "wasm:<name>"` on
+//! `/ai/add` and loaded from `<dir>/<name>.wasm`. The guest API is one call
+//! per decision:
+//!
+//! - the module exports its `memory`, `alloc(len: i32) -> i32` and
+//!   `decide(ptr: i32, len: i32) -> i64`;
+//! - the host asks `alloc` for room, writes the AI's perception there as
+//!   [`GuestPerception`] JSON, and calls `decide` with it;
+//! - `decide` returns where its [`GuestDecision`] JSON is, packed as
+//!   `ptr << 32 | len`. It has to lie inside the module's memory and be no
+//!   longer than `WASM_BOT_MAX_OUTPUT`.
+//!
+//! Modules get no imports. Every call runs on a fuel budget and a wall-clock
+//! deadline, and memory is capped, so a runaway bot is cut off and idles
+//! for the update instead of stalling the tick.
+
+use ai::{AIController, AIMessage, Decision, OpportunityType, Perception, TaskAction, ThreatType};
+use serde::{Deserialize, Serialize};
+use shared::*;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use uuid::Uuid;
+use wasmtime::{
+    Config, Engine, Instance, Linker, Memory, Module, Store, StoreLimits, StoreLimitsBuilder,
+    TypedFunc,
+};
+
+/// Where `wasm:<name>` modules are looked for without `--wasm-bots <dir>`
+pub const DEFAULT_WASM_BOT_DIR: &str = "bots/wasm";
+
+/// How often the deadline clock ticks
+const EPOCH_TICK: Duration = Duration::from_millis(5);
+
+/// What one decision may cost a guest
+#[derive(Debug, Clone, Copy)]
+pub struct WasmLimits {
+    pub fuel: u64,
+    pub time_limit: Duration,
+    pub max_memory: usize, // bytes
+}
+
+impl Default for WasmLimits {
+    fn default() -> Self {
+        Self {
+            fuel: WASM_BOT_FUEL,
+            time_limit: Duration::from_millis(WASM_BOT_TIME_LIMIT_MS),
+            max_memory: WASM_BOT_MAX_MEMORY,
+        }
+    }
+}
+
+impl WasmLimits {
+    /// The time limit in deadline clock ticks, at least one
+    fn epoch_ticks(&self) -> u64 {
+        (self.time_limit.as_millis() / EPOCH_TICK.as_millis()).max(1) as u64
+    }
+}
+
+/// Loads `wasm:` bots, compiling each module once
+pub struct WasmBots {
+    dir: PathBuf,
+    limits: WasmLimits,
+    engine: Engine,
+    modules: Mutex<HashMap<String, Module>>,
+}
+
+impl WasmBots {
+    pub fn new(dir: impl Into<PathBuf>, limits: WasmLimits) -> Self {
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        config.epoch_interruption(true);
+        let engine = Engine::new(&config).expect("fuel and epochs are supported everywhere");
+
+        // Tick the deadline clock for as long as anyone holds the engine
+        let weak = engine.weak();
+        std::thread::spawn(move || loop {
+            std::thread::sleep(EPOCH_TICK);
+            match weak.upgrade() {
+                Some(engine) => engine.increment_epoch(),
+                None => break,
+            }
+        });
+
+        Self {
+            dir: dir.into(),
+            limits,
+            engine,
+            modules: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// A fresh instance of the named module, as the bot with `ai_id`
+    pub fn load(&self, name: &str, ai_id: Uuid) -> GameResult<Box<dyn AIController>> {
+        let valid = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            return Err(GameError::invalid_input(
+                "WASM bot names are letters, digits, '-' or '_'",
+            ));
+        }
+        let failed = |e: anyhow::Error| {
+            GameError::invalid_input(format!("Can't load WASM bot '{name}': {e}"))
+        };
+
+        let module = {
+            let mut modules = self.modules.lock().unwrap();
+            match modules.get(name) {
+                Some(module) => module.clone(),
+                None => {
+                    let path = self.dir.join(format!("{name}.wasm"));
+                    let module = Module::from_file(&self.engine, &path).map_err(failed)?;
+                    modules.insert(name.to_string(), module.clone());
+                    module
+                }
+            }
+        };
+        let bot = WasmBot::instantiate(&self.engine, &module, name, ai_id, self.limits)
+            .map_err(failed)?;
+        log::info!("Loaded WASM bot '{name}' as {ai_id}");
+        Ok(Box::new(bot))
+    }
+}
+
+struct GuestState {
+    limits: StoreLimits,
+}
+
+/// One running instance of a guest module
+pub struct WasmBot {
+    id: Uuid,
+    name: String,
+    limits: WasmLimits,
+    store: Store<GuestState>,
+    memory: Memory,
+    alloc: TypedFunc<i32, i32>,
+    decide: TypedFunc<(i32, i32), i64>,
+    /// Whether the last call failed, so a broken bot is logged once
+    failing: bool,
+}
+
+impl WasmBot {
+    fn instantiate(
+        engine: &Engine,
+        module: &Module,
+        name: &str,
+        id: Uuid,
+        limits: WasmLimits,
+    ) -> anyhow::Result<Self> {
+        let state = GuestState {
+            limits: StoreLimitsBuilder::new()
+                .memory_size(limits.max_memory)
+                .instances(1)
+                .build(),
+        };
+        let mut store = Store::new(engine, state);
+        store.limiter(|state| &mut state.limits);
+        // A start function runs on the same budget as a decision
+        store.set_fuel(limits.fuel)?;
+        store.set_epoch_deadline(limits.epoch_ticks());
+
+        let instance: Instance = Linker::new(engine).instantiate(&mut store, module)?;
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or_else(|| anyhow::anyhow!("module doesn't export its memory"))?;
+        let alloc = instance.get_typed_func(&mut store, "alloc")?;
+        let decide = instance.get_typed_func(&mut store, "decide")?;
+        Ok(Self {
+            id,
+            name: name.to_string(),
+            limits,
+            store,
+            memory,
+            alloc,
+            decide,
+            failing: false,
+        })
+    }
+
+    /// Hand the guest `input` and get back what it answered
+    fn call(&mut self, input: &[u8]) -> anyhow::Result<Vec<u8>> {
+        self.store.set_fuel(self.limits.fuel)?;
+        self.store.set_epoch_deadline(self.limits.epoch_ticks());
+
+        let len = i32::try_from(input.len())?;
+        let ptr = self.alloc.call(&mut self.store, len)?;
+        self.memory
+            .write(&mut self.store, ptr as u32 as usize, input)?;
+        let packed = self.decide.call(&mut self.store, (ptr, len))? as u64;
+
+        // The guest says where its answer is; check that before copying it
+        let (out_ptr, out_len) = ((packed >> 32) as usize, (packed & 0xffff_ffff) as usize);
+        if out_len > WASM_BOT_MAX_OUTPUT {
+            anyhow::bail!("answered with {out_len} bytes, more than {WASM_BOT_MAX_OUTPUT}");
+        }
+        let output = out_ptr
+            .checked_add(out_len)
+            .filter(|end| *end <= self.memory.data_size(&self.store))
+            .and_then(|end| self.memory.data(&self.store).get(out_ptr..end))
+            .ok_or_else(|| anyhow::anyhow!("answered from outside its memory"))?;
+        Ok(output.to_vec())
+    }
+
+    fn try_decide(&mut self, perception: &Perception) -> anyhow::Result<Decision> {
+        let input = serde_json::to_vec(&GuestPerception::from(perception))?;
+        let output = self.call(&input)?;
+        let decision: GuestDecision = serde_json::from_slice(&output)?;
+        Ok(decision.into_decision())
+    }
+}
+
+impl AIController for WasmBot {
+    fn id(&self) -> Uuid {
+        self.id
+    }
+
+    fn decide(
+        &mut self,
+        perception: &Perception,
+        _messages: &[AIMessage],
+        _delta_time: f32,
+    ) -> Decision {
+        match self.try_decide(perception) {
+            Ok(decision) => {
+                self.failing = false;
+                decision
+            }
+            Err(e) => {
+                if !self.failing {
+                    log::warn!("WASM bot '{}' ({}) failed: {e}", self.name, self.id);
+                }
+                self.failing = true;
+                Decision {
+                    chosen_action: Some(TaskAction::Idle),
+                    confidence: 0.0,
+                    reasoning: format!("WASM bot '{}' failed: {e}", self.name),
+                    messages: Vec::new(),
+                }
+            }
+        }
+    }
+}
+
+/// Where the AI is, as the guest sees it
+#[derive(Debug, Serialize)]
+#[serde(tag = "where", rename_all = "snake_case")]
+pub enum GuestLocation {
+    Outside {
+        x: f32,
+        y: f32,
+    },
+    Inside {
+        mech_id: Uuid,
+        floor: u8,
+        x: i32,
+        y: i32,
+    },
+}
+
+#[derive(Debug, Serialize)]
+pub struct GuestThreat {
+    /// `mech`, `projectile` or `hazard`
+    pub kind: &'static str,
+    pub x: f32,
+    pub y: f32,
+    pub severity: f32,
+    pub distance: f32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GuestOpportunity {
+    /// `resource`, `station`, `enemy` or `objective`
+    pub kind: &'static str,
+    pub x: f32,
+    pub y: f32,
+    pub value: f32,
+    pub distance: f32,
+    pub resource: Option<ResourceType>,
+    pub station: Option<StationType>,
+}
+
+/// What the host sends the guest each decision
+#[derive(Debug, Serialize)]
+pub struct GuestPerception {
+    pub id: Uuid,
+    pub location: GuestLocation,
+    pub carrying: Option<ResourceType>,
+    pub station: Option<StationType>,
+    /// Nearest friendly mech's door, if there is one
+    pub home: Option<(f32, f32)>,
+    /// Our mech's health as a fraction of full
+    pub mech_health: f32,
+    /// Enemies on foot nearby minus friends
+    pub outnumbered_by: i32,
+    pub threats: Vec<GuestThreat>,
+    /// Best first
+    pub opportunities: Vec<GuestOpportunity>,
+}
+
+impl From<&Perception> for GuestPerception {
+    fn from(perception: &Perception) -> Self {
+        let location = match perception.my_state.location {
+            PlayerLocation::OutsideWorld(pos) => GuestLocation::Outside { x: pos.x, y: pos.y },
+            PlayerLocation::InsideMech { mech_id, pos } => GuestLocation::Inside {
+                mech_id,
+                floor: pos.floor,
+                x: pos.tile_pos.x,
+                y: pos.tile_pos.y,
+            },
+        };
+        let threats = perception
+            .threats
+            .iter()
+            .map(|threat| GuestThreat {
+                kind: match threat.threat_type {
+                    ThreatType::EnemyMech { .. } => "mech",
+                    ThreatType::Projectile { .. } => "projectile",
+                    ThreatType::EnvironmentalHazard => "hazard",
+                },
+                x: threat.position.x,
+                y: threat.position.y,
+                severity: threat.severity,
+                distance: threat.distance,
+            })
+            .collect();
+        let opportunities = perception
+            .opportunities
+            .iter()
+            .map(|opportunity| {
+                let (kind, resource, station) = match opportunity.opportunity_type {
+                    OpportunityType::Resource { resource_type } => {
+                        ("resource", Some(resource_type), None)
+                    }
+                    OpportunityType::UnmannedStation { station_type } => {
+                        ("station", None, Some(station_type))
+                    }
+                    OpportunityType::WeakEnemy { .. } => ("enemy", None, None),
+                    OpportunityType::TeamObjective { .. } => ("objective", None, None),
+                };
+                GuestOpportunity {
+                    kind,
+                    x: opportunity.position.x,
+                    y: opportunity.position.y,
+                    value: opportunity.value,
+                    distance: opportunity.distance,
+                    resource,
+                    station,
+                }
+            })
+            .collect();
+
+        Self {
+            id: perception.my_id,
+            location,
+            carrying: perception.my_state.carrying_resource,
+            station: perception.my_state.operating_station,
+            home: perception
+                .my_state
+                .nearest_safe_location
+                .map(|pos| (pos.x, pos.y)),
+            mech_health: perception.danger.mech_health,
+            outnumbered_by: perception.danger.outnumbered_by,
+            threats,
+            opportunities,
+        }
+    }
+}
+
+/// What the guest can choose to do
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum GuestAction {
+    MoveTo {
+        x: f32,
+        y: f32,
+    },
+    Collect {
+        #[serde(default)]
+        resource: Option<ResourceType>,
+    },
+    Operate {
+        station: StationType,
+    },
+    Follow {
+        player: Uuid,
+    },
+    Attack {
+        target: Uuid,
+    },
+    Defend {
+        x: f32,
+        y: f32,
+    },
+    Repair,
+    Idle,
+}
+
+/// What the guest answers each decision
+#[derive(Debug, Deserialize)]
+pub struct GuestDecision {
+    pub action: GuestAction,
+    #[serde(default)]
+    pub reasoning: String,
+    #[serde(default = "full_confidence")]
+    pub confidence: f32,
+}
+
+fn full_confidence() -> f32 {
+    1.0
+}
+
+impl GuestDecision {
+    fn into_decision(self) -> Decision {
+        let chosen_action = match self.action {
+            GuestAction::MoveTo { x, y } => TaskAction::MoveToPosition {
+                target: WorldPos::new(x, y),
+                reason: self.reasoning.clone(),
+            },
+            GuestAction::Collect { resource } => TaskAction::CollectResource {
+                resource_type: resource,
+            },
+            GuestAction::Operate { station } => TaskAction::OperateStation {
+                station_type: station,
+            },
+            GuestAction::Follow { player } => TaskAction::FollowPlayer { player_id: player },
+            GuestAction::Attack { target } => TaskAction::AttackTarget { target_id: target },
+            GuestAction::Defend { x, y } => TaskAction::DefendPosition {
+                position: WorldPos::new(x, y),
+            },
+            GuestAction::Repair => TaskAction::RepairMech,
+            GuestAction::Idle => TaskAction::Idle,
+        };
+        Decision {
+            chosen_action: Some(chosen_action),
+            confidence: self.confidence.clamp(0.0, 1.0),
+            reasoning: self.reasoning,
+            messages: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ai::{GameView, MechView, PlayerView, ResourceView, TeamInfo};
+
+    /// A bump allocator, and `decide` as given
+    fn guest(decide: &str, data: &str) -> String {
+        format!(
+            r#"(module
+                (memory (export "memory") 1)
+                (global $next (mut i32) (i32.const 4096))
+                (func (export "alloc") (param $len i32) (result i32)
+                    (local $ptr i32)
+                    (local.set $ptr (global.get $next))
+                    (global.set $next (i32.add (global.get $next) (local.get $len)))
+                    (local.get $ptr))
+                {decide}
+                (data (i32.const 0) "{data}"))"#
+        )
+    }
+
+    /// A guest answering every decision with `answer`
+    fn answering(answer: &str) -> String {
+        guest(
+            &format!(
+                r#"(func (export "decide") (param i32 i32) (result i64)
+                    (i64.const {}))"#,
+                answer.len()
+            ),
+            &answer.replace('"', "\\\""),
+        )
+    }
+
+    fn bots_with(modules: &[(&str, String)], limits: WasmLimits) -> WasmBots {
+        let dir = std::env::temp_dir().join(format!("wasm-bots-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        // wasmtime takes the text format as readily as binaries
+        for (name, source) in modules {
+            std::fs::write(dir.join(format!("{name}.wasm")), source).unwrap();
+        }
+        WasmBots::new(dir, limits)
+    }
+
+    fn perception(ai_id: Uuid) -> Perception {
+        let game_view = GameView {
+            tick: 1,
+            players: vec![PlayerView {
+                id: ai_id,
+                name: "Bot".to_string(),
+                team: TeamId::Red,
+                location: PlayerLocation::OutsideWorld(WorldPos::new(640.0, 640.0)),
+                carrying_resource: None,
+                operating_station: None,
+            }],
+            mechs: vec![MechView {
+                id: Uuid::new_v4(),
+                team: TeamId::Red,
+                position: WorldPos::new(320.0, 320.0),
+                health: MECH_INITIAL_HEALTH,
+                max_health: MECH_MAX_HEALTH,
+                shield: MECH_INITIAL_SHIELD,
+                velocity: (0.0, 0.0),
+                turret: MechTurret::default(),
+                stations: Vec::new(),
+                resource_inventory: HashMap::new(),
+                alarm: None,
+            }],
+            resources: vec![ResourceView {
+                id: Uuid::new_v4(),
+                position: WorldPos::new(800.0, 640.0),
+                resource_type: ResourceType::Wiring,
+            }],
+            projectiles: Vec::new(),
+            team_info: TeamInfo {
+                team_id: TeamId::Red,
+                player_count: 1,
+                mech_count: 1,
+                total_resources: HashMap::new(),
+            },
+        };
+        Perception::from_game_view(&game_view, ai_id)
+    }
+
+    #[test]
+    fn test_guest_decisions_come_back_as_bot_decisions() {
+        let bots = bots_with(
+            &[(
+                "helm",
+                answering(
+                    r#"{"action":{"type":"operate","station":"Pilot"},"reasoning":"crew the helm","confidence":0.7}"#,
+                ),
+            )],
+            WasmLimits::default(),
+        );
+        let ai_id = Uuid::new_v4();
+        let mut bot = bots.load("helm", ai_id).unwrap();
+        assert_eq!(bot.id(), ai_id);
+
+        let decision = bot.decide(&perception(ai_id), &[], 0.1);
+        assert!(matches!(
+            decision.chosen_action,
+            Some(TaskAction::OperateStation {
+                station_type: StationType::Pilot
+            })
+        ));
+        assert_eq!(decision.reasoning, "crew the helm");
+        assert_eq!(decision.confidence, 0.7);
+    }
+
+    #[test]
+    fn test_guests_get_the_perception_as_json() {
+        let echo = guest(
+            r#"(func (export "decide") (param $ptr i32) (param $len i32) (result i64)
+                (i64.or
+                    (i64.shl (i64.extend_i32_u (local.get $ptr)) (i64.const 32))
+                    (i64.extend_i32_u (local.get $len))))"#,
+            "",
+        );
+        let bots = bots_with(&[("echo", echo)], WasmLimits::default());
+        let engine = &bots.engine;
+        let module =
+            Module::new(engine, std::fs::read(bots.dir.join("echo.wasm")).unwrap()).unwrap();
+        let ai_id = Uuid::new_v4();
+        let mut bot = WasmBot::instantiate(engine, &module, "echo", ai_id, bots.limits).unwrap();
+
+        let input = serde_json::to_vec(&GuestPerception::from(&perception(ai_id))).unwrap();
+        let output = bot.call(&input).unwrap();
+        let sent: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(sent["id"], ai_id.to_string());
+        assert_eq!(sent["location"]["where"], "outside");
+        assert_eq!(sent["opportunities"][0]["kind"], "resource");
+        assert_eq!(sent["opportunities"][0]["resource"], "Wiring");
+        assert!(sent["home"].is_array());
+
+        // Echoing the perception back isn't a decision, so the bot idles
+        let mut bot: Box<dyn AIController> = Box::new(bot);
+        let decision = bot.decide(&perception(ai_id), &[], 0.1);
+        assert!(matches!(decision.chosen_action, Some(TaskAction::Idle)));
+    }
+
+    #[test]
+    fn test_runaway_guests_are_cut_off() {
+        let spin = guest(
+            r#"(func (export "decide") (param i32 i32) (result i64)
+                (loop $forever (br $forever))
+                (i64.const 0))"#,
+            "",
+        );
+        let ai_id = Uuid::new_v4();
+
+        // Out of fuel long before the deadline, however busy the machine
+        let limits = WasmLimits {
+            time_limit: Duration::from_secs(60),
+            ..Default::default()
+        };
+        let bots = bots_with(&[("spin", spin.clone())], limits);
+        let mut bot = bots.load("spin", ai_id).unwrap();
+        let decision = bot.decide(&perception(ai_id), &[], 0.1);
+        assert!(matches!(decision.chosen_action, Some(TaskAction::Idle)));
+        assert!(decision.reasoning.contains("spin"));
+
+        let module = Module::new(&bots.engine, &spin).unwrap();
+        let mut bot =
+            WasmBot::instantiate(&bots.engine, &module, "spin", ai_id, bots.limits).unwrap();
+        let error = bot.call(b"{}").unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<wasmtime::Trap>(),
+            Some(&wasmtime::Trap::OutOfFuel)
+        );
+
+        // With fuel to burn, the deadline stops it
+        let limits = WasmLimits {
+            fuel: u64::MAX,
+            time_limit: Duration::from_millis(20),
+            ..Default::default()
+        };
+        let bots = bots_with(&[], limits);
+        let module = Module::new(&bots.engine, &spin).unwrap();
+        let mut bot = WasmBot::instantiate(&bots.engine, &module, "spin", ai_id, limits).unwrap();
+        let started = std::time::Instant::now();
+        let error = bot.call(b"{}").unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<wasmtime::Trap>(),
+            Some(&wasmtime::Trap::Interrupt)
+        );
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_answers_outside_guest_memory_are_refused() {
+        let ai_id = Uuid::new_v4();
+        let bots = bots_with(&[], WasmLimits::default());
+        for (packed, refusal) in [
+            // Four gigabytes from the start of memory
+            ("0x0000_0000_ffff_ffff", "more than"),
+            // A small answer past the end of a one-page memory
+            ("0x0001_0000_0000_0010", "outside its memory"),
+        ] {
+            let liar = guest(
+                &format!(
+                    r#"(func (export "decide") (param i32 i32) (result i64)
+                        (i64.const {packed}))"#
+                ),
+                "",
+            );
+            let module = Module::new(&bots.engine, &liar).unwrap();
+            let mut bot =
+                WasmBot::instantiate(&bots.engine, &module, "liar", ai_id, bots.limits).unwrap();
+            let error = bot.call(b"{}").unwrap_err();
+            assert!(error.to_string().contains(refusal), "{error}");
+        }
+    }
+
+    #[test]
+    fn test_only_sandboxed_modules_by_plain_names_load() {
+        let wants_imports = r#"(module
+            (import "env" "open" (func (param i32) (result i32)))
+            (memory (export "memory") 1))"#;
+        let bots = bots_with(
+            &[("escape", wants_imports.to_string())],
+            WasmLimits::default(),
+        );
+        let ai_id = Uuid::new_v4();
+        assert!(bots.load("escape", ai_id).is_err());
+        assert!(bots.load("missing", ai_id).is_err());
+        assert!(bots.load("../escape", ai_id).is_err());
+        assert!(bots.load("", ai_id).is_err());
+    }
+}
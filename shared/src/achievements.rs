@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+
+/// Milestones a player can unlock once per match
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Achievement {
+    /// Knocked out the first player of the match
+    FirstBlood,
+    /// Delivered `ACHIEVEMENT_COURIER_DELIVERIES` resources
+    Courier,
+    /// Crewed a mech that collapsed an enemy shield
+    ShieldBreaker,
+    /// Took an enemy station offline
+    Saboteur,
+}
+
+impl Achievement {
+    pub fn title(self) -> &'static str {
+        match self {
+            Achievement::FirstBlood => "First Blood",
+            Achievement::Courier => "Courier",
+            Achievement::ShieldBreaker => "Shield Breaker",
+            Achievement::Saboteur => "Saboteur",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Achievement::FirstBlood => "Took down the first player of the match",
+            Achievement::Courier => "Delivered 10 resources",
+            Achievement::ShieldBreaker => "Brought down an enemy shield",
+            Achievement::Saboteur => "Sabotaged an enemy station",
+        }
+    }
+}
@@ -0,0 +1,150 @@
+//! Coaching marks drawn over the map: lines, arrows and circles a spectator
+//! or player sketches for their team or for everyone. The server only checks
+//! and forwards them; each client keeps a mark until its time to live runs
+//! out.
+
+use crate::constants::{ARENA_HEIGHT_TILES, ARENA_WIDTH_TILES, TILE_SIZE};
+use crate::types::{TeamId, WorldPos};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Longest a mark stays up
+pub const ANNOTATION_MAX_TTL: f32 = 30.0; // seconds
+/// Biggest circle a mark can draw
+pub const ANNOTATION_MAX_RADIUS: f32 = 20.0 * TILE_SIZE;
+/// Marks one author can draw a second before the rest are turned away
+pub const ANNOTATIONS_PER_SECOND: u32 = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum AnnotationShape {
+    Line {
+        from: WorldPos,
+        to: WorldPos,
+    },
+    /// A line with a head at `to`
+    Arrow {
+        from: WorldPos,
+        to: WorldPos,
+    },
+    Circle {
+        center: WorldPos,
+        radius: f32,
+    },
+}
+
+impl AnnotationShape {
+    /// Why the shape can't be drawn, if it can't: every point has to be on
+    /// the map and circles can't be empty or huge
+    pub fn problem(&self) -> Option<&'static str> {
+        let on_map = |pos: &WorldPos| {
+            pos.x.is_finite()
+                && pos.y.is_finite()
+                && (0.0..=ARENA_WIDTH_TILES as f32 * TILE_SIZE).contains(&pos.x)
+                && (0.0..=ARENA_HEIGHT_TILES as f32 * TILE_SIZE).contains(&pos.y)
+        };
+        match self {
+            AnnotationShape::Line { from, to } | AnnotationShape::Arrow { from, to } => {
+                (!on_map(from) || !on_map(to)).then_some("Points must be on the map")
+            }
+            AnnotationShape::Circle { center, radius } => {
+                if !on_map(center) {
+                    Some("Points must be on the map")
+                } else if !(*radius > 0.0 && *radius <= ANNOTATION_MAX_RADIUS) {
+                    Some("Circle radius is out of range")
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AnnotationColor {
+    Yellow,
+    Red,
+    Cyan,
+    White,
+}
+
+impl AnnotationColor {
+    pub const ALL: &'static [AnnotationColor] = &[
+        AnnotationColor::Yellow,
+        AnnotationColor::Red,
+        AnnotationColor::Cyan,
+        AnnotationColor::White,
+    ];
+
+    pub fn rgb(self) -> (f32, f32, f32) {
+        match self {
+            AnnotationColor::Yellow => (1.0, 0.85, 0.1),
+            AnnotationColor::Red => (1.0, 0.25, 0.2),
+            AnnotationColor::Cyan => (0.2, 0.9, 1.0),
+            AnnotationColor::White => (1.0, 1.0, 1.0),
+        }
+    }
+}
+
+/// Who sees a mark. Spectators see every mark whoever it was meant for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AnnotationAudience {
+    Team(TeamId),
+    Everyone,
+}
+
+/// A mark as clients get it
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Annotation {
+    pub id: Uuid,
+    pub author: Uuid,
+    pub shape: AnnotationShape,
+    pub color: AnnotationColor,
+    /// Seconds it stays up once it arrives
+    pub ttl: f32,
+}
+
+/// Whether a mark can stay up for `ttl` seconds
+pub fn valid_annotation_ttl(ttl: f32) -> bool {
+    ttl > 0.0 && ttl <= ANNOTATION_MAX_TTL
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shapes_must_stay_on_the_map() {
+        let inside = WorldPos::new(TILE_SIZE, TILE_SIZE);
+        let line = AnnotationShape::Arrow {
+            from: inside,
+            to: WorldPos::new(5.0 * TILE_SIZE, 2.0 * TILE_SIZE),
+        };
+        assert_eq!(line.problem(), None);
+        let off_map = AnnotationShape::Line {
+            from: inside,
+            to: WorldPos::new(-TILE_SIZE, 0.0),
+        };
+        assert!(off_map.problem().is_some());
+        let nan = AnnotationShape::Line {
+            from: inside,
+            to: WorldPos::new(f32::NAN, 0.0),
+        };
+        assert!(nan.problem().is_some());
+
+        for (radius, ok) in [
+            (TILE_SIZE, true),
+            (0.0, false),
+            (ANNOTATION_MAX_RADIUS * 2.0, false),
+        ] {
+            let circle = AnnotationShape::Circle {
+                center: inside,
+                radius,
+            };
+            assert_eq!(circle.problem().is_none(), ok, "radius {radius}");
+        }
+
+        assert!(valid_annotation_ttl(ANNOTATION_MAX_TTL));
+        assert!(!valid_annotation_ttl(0.0));
+        assert!(!valid_annotation_ttl(f32::NAN));
+    }
+}
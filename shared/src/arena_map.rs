@@ -0,0 +1,414 @@
+use crate::balance::{
+    BLUE_MECH_SPAWN, PLAYER_SPAWN_OFFSET, RED_MECH_SPAWN, SAFE_ZONE_RADIUS_TILES,
+};
+use crate::constants::{ARENA_HEIGHT_TILES, ARENA_MIN_TILES, ARENA_WIDTH_TILES, TILE_SIZE};
+use crate::errors::{GameError, GameResult};
+use crate::tile_entity::StaticTile;
+use crate::tile_script::{ScriptAction, TileScript};
+use crate::types::{ResourceType, TeamId, TilePos, WorldPos};
+use serde::{Deserialize, Serialize};
+
+/// World tiles the map editor can paint. Anything else is mech-interior only.
+pub const PAINTABLE_TILES: &[StaticTile] = &[
+    StaticTile::Grass,
+    StaticTile::Rock,
+    StaticTile::MetalFloor,
+    StaticTile::MetalWall,
+    StaticTile::ReinforcedWall,
+];
+
+/// An arena layout authored in the map editor and loaded by the server at
+/// startup. Stored as JSON; every tile not listed is grass.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArenaMap {
+    pub name: String,
+    pub width: i32,  // tiles
+    pub height: i32, // tiles
+    pub tiles: Vec<MapTile>,
+    pub mech_spawns: Vec<MechSpawnPoint>,
+    /// Empty keeps the server's random initial resource scatter
+    pub resource_spawners: Vec<ResourceSpawner>,
+    #[serde(default)]
+    pub hazards: Vec<MapHazard>,
+    /// Tiles that react to players, see `tile_script`
+    #[serde(default)]
+    pub scripted_tiles: Vec<ScriptedTile>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MapTile {
+    pub position: TilePos,
+    pub tile: StaticTile,
+}
+
+/// Top-left tile of a team's mech at match start
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MechSpawnPoint {
+    pub team: TeamId,
+    pub position: TilePos,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ResourceSpawner {
+    pub position: TilePos,
+    /// None picks a random resource type when the match starts
+    pub resource_type: Option<ResourceType>,
+}
+
+/// Tiles around a team's spawn, `min` to `max` inclusive, where that
+/// team's enemies' shots fizzle and enemies standing in it are slowed and
+/// hurt
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SafeZone {
+    pub team: TeamId,
+    pub min: TilePos,
+    pub max: TilePos,
+}
+
+impl SafeZone {
+    pub fn contains(&self, tile: TilePos) -> bool {
+        (self.min.x..=self.max.x).contains(&tile.x) && (self.min.y..=self.max.y).contains(&tile.y)
+    }
+
+    /// Whether `team` is an enemy standing in it at `pos`
+    pub fn hostile_to(&self, team: TeamId, pos: WorldPos) -> bool {
+        team != self.team && self.contains(pos.to_tile())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum HazardKind {
+    /// Damages any mech standing over it
+    Radiation,
+    /// Detonates once under the first mech that crosses it
+    Mine,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MapHazard {
+    pub position: TilePos,
+    pub kind: HazardKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScriptedTile {
+    pub position: TilePos,
+    pub script: TileScript,
+}
+
+impl Default for ArenaMap {
+    /// The built-in arena: open grass with the standard spawn points
+    fn default() -> Self {
+        Self {
+            name: "Grasslands".to_string(),
+            width: ARENA_WIDTH_TILES,
+            height: ARENA_HEIGHT_TILES,
+            tiles: Vec::new(),
+            mech_spawns: vec![
+                MechSpawnPoint {
+                    team: TeamId::Red,
+                    position: TilePos::new(RED_MECH_SPAWN.0, RED_MECH_SPAWN.1),
+                },
+                MechSpawnPoint {
+                    team: TeamId::Blue,
+                    position: TilePos::new(BLUE_MECH_SPAWN.0, BLUE_MECH_SPAWN.1),
+                },
+            ],
+            resource_spawners: Vec::new(),
+            hazards: Vec::new(),
+            scripted_tiles: Vec::new(),
+        }
+    }
+}
+
+impl ArenaMap {
+    pub fn from_json(json: &str) -> GameResult<Self> {
+        let map: Self = serde_json::from_str(json)
+            .map_err(|e| GameError::invalid_input(format!("invalid arena map: {e}")))?;
+        map.validate()?;
+        Ok(map)
+    }
+
+    /// The built-in arena shrunk to `width` x `height` tiles, with the spawn
+    /// points moved in proportionally
+    pub fn sized(width: i32, height: i32) -> Self {
+        let mut map = Self::default();
+        for spawn in &mut map.mech_spawns {
+            spawn.position = TilePos::new(
+                spawn.position.x * width / ARENA_WIDTH_TILES,
+                spawn.position.y * height / ARENA_HEIGHT_TILES,
+            );
+        }
+        map.width = width;
+        map.height = height;
+        map
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("arena maps always serialize")
+    }
+
+    /// Maps can be smaller than the server's world but not bigger, and must
+    /// keep every marker on the grid
+    pub fn validate(&self) -> GameResult<()> {
+        if !(ARENA_MIN_TILES..=ARENA_WIDTH_TILES).contains(&self.width)
+            || !(ARENA_MIN_TILES..=ARENA_HEIGHT_TILES).contains(&self.height)
+        {
+            return Err(GameError::invalid_input(format!(
+                "arena map is {}x{}, expected between {ARENA_MIN_TILES}x{ARENA_MIN_TILES} \
+                 and {ARENA_WIDTH_TILES}x{ARENA_HEIGHT_TILES}",
+                self.width, self.height
+            )));
+        }
+        for team in [TeamId::Red, TeamId::Blue] {
+            if self.mech_spawn(team).is_none() {
+                return Err(GameError::invalid_input(format!(
+                    "arena map has no mech spawn for {team:?}"
+                )));
+            }
+        }
+
+        let positions = self
+            .tiles
+            .iter()
+            .map(|t| t.position)
+            .chain(self.mech_spawns.iter().map(|s| s.position))
+            .chain(self.resource_spawners.iter().map(|s| s.position))
+            .chain(self.hazards.iter().map(|h| h.position))
+            .chain(self.scripted_tiles.iter().map(|t| t.position))
+            .chain(self.teleport_targets());
+        for pos in positions {
+            if !self.in_bounds(pos) {
+                return Err(GameError::invalid_input(format!(
+                    "arena map marker at ({}, {}) is out of bounds",
+                    pos.x, pos.y
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Everywhere the map's scripts can send a player
+    fn teleport_targets(&self) -> impl Iterator<Item = TilePos> + '_ {
+        self.scripted_tiles
+            .iter()
+            .flat_map(|t| &t.script.rules)
+            .flat_map(|rule| &rule.actions)
+            .filter_map(|action| match action {
+                ScriptAction::Teleport(target) => Some(*target),
+                _ => None,
+            })
+    }
+
+    /// Width and height of the playable world in pixels
+    pub fn world_size(&self) -> (f32, f32) {
+        (
+            self.width as f32 * TILE_SIZE,
+            self.height as f32 * TILE_SIZE,
+        )
+    }
+
+    pub fn in_bounds(&self, pos: TilePos) -> bool {
+        pos.x >= 0 && pos.y >= 0 && pos.x < self.width && pos.y < self.height
+    }
+
+    pub fn tile_at(&self, pos: TilePos) -> StaticTile {
+        self.tiles
+            .iter()
+            .find(|t| t.position == pos)
+            .map_or(StaticTile::Grass, |t| t.tile)
+    }
+
+    pub fn set_tile(&mut self, pos: TilePos, tile: StaticTile) {
+        self.tiles.retain(|t| t.position != pos);
+        if tile != StaticTile::Grass {
+            self.tiles.push(MapTile {
+                position: pos,
+                tile,
+            });
+        }
+    }
+
+    pub fn mech_spawn(&self, team: TeamId) -> Option<TilePos> {
+        self.mech_spawns
+            .iter()
+            .find(|s| s.team == team)
+            .map(|s| s.position)
+    }
+
+    /// Where a team's players appear: just beside their mech's spawn, kept
+    /// on the map
+    pub fn player_spawn(&self, team: TeamId) -> WorldPos {
+        let mech = self.mech_spawn(team).unwrap_or_else(|| match team {
+            TeamId::Red => TilePos::new(RED_MECH_SPAWN.0, RED_MECH_SPAWN.1),
+            TeamId::Blue => TilePos::new(BLUE_MECH_SPAWN.0, BLUE_MECH_SPAWN.1),
+        });
+        let (world_width, world_height) = self.world_size();
+        WorldPos::new(
+            ((mech.x as f32 + PLAYER_SPAWN_OFFSET.0) * TILE_SIZE).clamp(0.0, world_width),
+            ((mech.y as f32 + PLAYER_SPAWN_OFFSET.1) * TILE_SIZE).clamp(0.0, world_height),
+        )
+    }
+
+    /// The square of tiles around a team's player spawn its enemies can't
+    /// shoot into or linger in
+    pub fn safe_zone(&self, team: TeamId) -> SafeZone {
+        let center = self.player_spawn(team).to_tile();
+        SafeZone {
+            team,
+            min: center.offset(-SAFE_ZONE_RADIUS_TILES, -SAFE_ZONE_RADIUS_TILES),
+            max: center.offset(SAFE_ZONE_RADIUS_TILES, SAFE_ZONE_RADIUS_TILES),
+        }
+    }
+
+    pub fn safe_zones(&self) -> [SafeZone; 2] {
+        [self.safe_zone(TeamId::Red), self.safe_zone(TeamId::Blue)]
+    }
+
+    /// Each team has exactly one mech spawn; placing a new one moves it
+    pub fn set_mech_spawn(&mut self, team: TeamId, pos: TilePos) {
+        self.mech_spawns.retain(|s| s.team != team);
+        self.mech_spawns.push(MechSpawnPoint {
+            team,
+            position: pos,
+        });
+    }
+
+    pub fn set_resource_spawner(&mut self, pos: TilePos, resource_type: Option<ResourceType>) {
+        self.resource_spawners.retain(|s| s.position != pos);
+        self.resource_spawners.push(ResourceSpawner {
+            position: pos,
+            resource_type,
+        });
+    }
+
+    pub fn set_hazard(&mut self, pos: TilePos, kind: HazardKind) {
+        self.hazards.retain(|h| h.position != pos);
+        self.hazards.push(MapHazard {
+            position: pos,
+            kind,
+        });
+    }
+
+    pub fn set_script(&mut self, pos: TilePos, script: TileScript) {
+        self.scripted_tiles.retain(|t| t.position != pos);
+        self.scripted_tiles.push(ScriptedTile {
+            position: pos,
+            script,
+        });
+    }
+
+    /// Remove spawners, hazards and scripts on a tile and reset it to grass
+    pub fn clear(&mut self, pos: TilePos) {
+        self.set_tile(pos, StaticTile::Grass);
+        self.resource_spawners.retain(|s| s.position != pos);
+        self.hazards.retain(|h| h.position != pos);
+        self.scripted_tiles.retain(|t| t.position != pos);
+    }
+}
+
+/// Flat color used by the editor and client to draw painted world tiles
+pub fn map_tile_color(tile: StaticTile) -> (f32, f32, f32) {
+    match tile {
+        StaticTile::Grass => (0.2, 0.6, 0.2),
+        StaticTile::Rock => (0.45, 0.42, 0.38),
+        StaticTile::MetalFloor => (0.55, 0.55, 0.6),
+        StaticTile::MetalWall => (0.3, 0.3, 0.35),
+        StaticTile::ReinforcedWall => (0.2, 0.2, 0.25),
+        _ => (0.5, 0.5, 0.5),
+    }
+}
+
+pub fn hazard_color(kind: HazardKind) -> (f32, f32, f32) {
+    match kind {
+        HazardKind::Radiation => (0.7, 0.9, 0.1),
+        HazardKind::Mine => (0.9, 0.3, 0.1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map_round_trips_through_json() {
+        let mut map = ArenaMap::default();
+        map.set_tile(TilePos::new(3, 4), StaticTile::Rock);
+        map.set_mech_spawn(TeamId::Red, TilePos::new(10, 12));
+        map.set_resource_spawner(TilePos::new(30, 30), Some(ResourceType::Wiring));
+        map.set_hazard(TilePos::new(50, 40), HazardKind::Mine);
+        map.set_script(
+            TilePos::new(20, 20),
+            "enter: teleport 60 60".parse().unwrap(),
+        );
+
+        let loaded = ArenaMap::from_json(&map.to_json()).unwrap();
+        assert_eq!(loaded, map);
+        assert_eq!(loaded.tile_at(TilePos::new(3, 4)), StaticTile::Rock);
+        assert_eq!(loaded.tile_at(TilePos::new(4, 4)), StaticTile::Grass);
+        assert_eq!(loaded.mech_spawn(TeamId::Red), Some(TilePos::new(10, 12)));
+    }
+
+    #[test]
+    fn test_painting_grass_and_clearing_remove_entries() {
+        let mut map = ArenaMap::default();
+        let pos = TilePos::new(5, 5);
+        map.set_tile(pos, StaticTile::MetalWall);
+        map.set_hazard(pos, HazardKind::Radiation);
+        map.set_tile(pos, StaticTile::Grass);
+        assert!(map.tiles.is_empty());
+
+        map.clear(pos);
+        assert!(map.hazards.is_empty());
+    }
+
+    #[test]
+    fn test_validation_rejects_bad_maps() {
+        let mut map = ArenaMap::default();
+        map.set_hazard(TilePos::new(-1, 5), HazardKind::Mine);
+        assert!(map.validate().is_err());
+
+        let mut map = ArenaMap::default();
+        map.mech_spawns.retain(|s| s.team != TeamId::Blue);
+        assert!(map.validate().is_err());
+
+        // Scripts can't send anyone off the map, and must parse at all
+        let mut map = ArenaMap::default();
+        map.set_script(TilePos::new(5, 5), "enter: teleport 5 500".parse().unwrap());
+        assert!(map.validate().is_err());
+        let broken = ArenaMap::default().to_json().replace(
+            "\"scripted_tiles\": []",
+            r#""scripted_tiles": [{"position": {"x": 1, "y": 1}, "script": "enter: explode"}]"#,
+        );
+        assert!(ArenaMap::from_json(&broken).is_err());
+
+        assert!(ArenaMap::default().validate().is_ok());
+    }
+
+    #[test]
+    fn test_sized_maps_keep_spawns_inside() {
+        let map = ArenaMap::sized(50, 60);
+        assert!(map.validate().is_ok());
+        assert_eq!(map.mech_spawn(TeamId::Red), Some(TilePos::new(10, 12)));
+        assert_eq!(map.mech_spawn(TeamId::Blue), Some(TilePos::new(40, 48)));
+
+        assert!(ArenaMap::sized(ARENA_MIN_TILES - 1, 60).validate().is_err());
+        assert!(ArenaMap::sized(ARENA_WIDTH_TILES + 1, 60)
+            .validate()
+            .is_err());
+    }
+
+    #[test]
+    fn test_safe_zones_surround_each_spawn() {
+        let map = ArenaMap::default();
+        let red = map.safe_zone(TeamId::Red);
+        let spawn = map.player_spawn(TeamId::Red);
+        assert!(red.contains(spawn.to_tile()));
+        assert!(red.hostile_to(TeamId::Blue, spawn));
+        assert!(!red.hostile_to(TeamId::Red, spawn));
+
+        let edge = red.max.offset(1, 0);
+        assert!(!red.contains(edge));
+        assert!(!map.safe_zone(TeamId::Blue).contains(spawn.to_tile()));
+    }
+}
@@ -0,0 +1,121 @@
+//! How much of a hit on a mech gets through. Mechs are armored thickest
+//! across the front and thinnest at the back, and shots lose bite the
+//! farther they travel, by how much depending on the weapon.
+
+use crate::balance::{
+    ARMOR_ARC_HALF_WIDTH, BEAM_FALLOFF, FRONT_ARMOR_MULTIPLIER, LASER_FALLOFF, MISSILE_FALLOFF,
+    PROJECTILE_FALLOFF, REAR_ARMOR_MULTIPLIER,
+};
+use crate::stations::WeaponType;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::{PI, TAU};
+
+/// The side of a mech a hit lands on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ArmorArc {
+    Front,
+    Side,
+    Rear,
+}
+
+impl ArmorArc {
+    /// The arc a shot coming in along `direction` strikes on a mech facing
+    /// `heading`. Shots flying against the heading hit the front.
+    pub fn struck(heading: f32, direction: (f32, f32)) -> Self {
+        let from = direction.1.atan2(direction.0) + PI;
+        let off_nose = ((from - heading + PI).rem_euclid(TAU) - PI).abs();
+        if off_nose <= ARMOR_ARC_HALF_WIDTH {
+            ArmorArc::Front
+        } else if off_nose >= PI - ARMOR_ARC_HALF_WIDTH {
+            ArmorArc::Rear
+        } else {
+            ArmorArc::Side
+        }
+    }
+
+    /// Share of a hit on this arc that gets through
+    pub fn multiplier(self) -> f32 {
+        match self {
+            ArmorArc::Front => FRONT_ARMOR_MULTIPLIER,
+            ArmorArc::Side => 1.0,
+            ArmorArc::Rear => REAR_ARMOR_MULTIPLIER,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ArmorArc::Front => "front",
+            ArmorArc::Side => "side",
+            ArmorArc::Rear => "rear",
+        }
+    }
+}
+
+/// Which way a hit on a mech came from, for its crew's hit indicator
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct HitDirection {
+    pub arc: ArmorArc,
+    /// Radians from the mech toward where the shot came from, 0 facing +x
+    pub from: f32,
+}
+
+impl HitDirection {
+    /// A shot coming in along `direction` on a mech facing `heading`
+    pub fn new(heading: f32, direction: (f32, f32)) -> Self {
+        Self {
+            arc: ArmorArc::struck(heading, direction),
+            from: (-direction.1).atan2(-direction.0),
+        }
+    }
+}
+
+/// Share of a weapon's damage left after `distance` tiles. Full up close,
+/// dropping off in a straight line to the weapon's floor. EMPs and
+/// interceptors don't do hull damage, so they never fall off.
+pub fn damage_falloff(weapon: WeaponType, distance: f32) -> f32 {
+    let (start, end, floor) = match weapon {
+        WeaponType::Laser => LASER_FALLOFF,
+        WeaponType::Projectile => PROJECTILE_FALLOFF,
+        WeaponType::Missile => MISSILE_FALLOFF,
+        WeaponType::Beam => BEAM_FALLOFF,
+        WeaponType::Emp | WeaponType::Interceptor => return 1.0,
+    };
+    let t = ((distance - start) / (end - start)).clamp(0.0, 1.0);
+    1.0 - t * (1.0 - floor)
+}
+
+/// A hit's damage after falloff over `distance` tiles and the armor on the
+/// arc it strikes
+pub fn hit_damage(damage: u32, weapon: WeaponType, distance: f32, arc: ArmorArc) -> u32 {
+    (damage as f32 * damage_falloff(weapon, distance) * arc.multiplier()).round() as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arcs_follow_the_heading() {
+        // Facing +x: a shot flying -x meets the nose, one flying +x the tail
+        assert_eq!(ArmorArc::struck(0.0, (-1.0, 0.0)), ArmorArc::Front);
+        assert_eq!(ArmorArc::struck(0.0, (1.0, 0.0)), ArmorArc::Rear);
+        assert_eq!(ArmorArc::struck(0.0, (0.0, 1.0)), ArmorArc::Side);
+        // Turned to face +y, the same shots come in from the side
+        assert_eq!(ArmorArc::struck(PI / 2.0, (-1.0, 0.0)), ArmorArc::Side);
+        assert_eq!(ArmorArc::struck(PI / 2.0, (0.0, -1.0)), ArmorArc::Front);
+    }
+
+    #[test]
+    fn test_falloff_drops_to_the_weapons_floor() {
+        let (start, end, floor) = PROJECTILE_FALLOFF;
+        assert_eq!(damage_falloff(WeaponType::Projectile, 0.0), 1.0);
+        assert_eq!(damage_falloff(WeaponType::Projectile, start), 1.0);
+        let halfway = damage_falloff(WeaponType::Projectile, (start + end) / 2.0);
+        assert!((halfway - (1.0 + floor) / 2.0).abs() < 1e-5);
+        assert_eq!(damage_falloff(WeaponType::Projectile, end * 2.0), floor);
+        assert_eq!(damage_falloff(WeaponType::Emp, 1000.0), 1.0);
+
+        assert_eq!(hit_damage(100, WeaponType::Laser, 0.0, ArmorArc::Rear), 125);
+        assert_eq!(hit_damage(100, WeaponType::Laser, 0.0, ArmorArc::Front), 75);
+    }
+}
@@ -3,8 +3,7 @@
 // ===== Spawning and Initial Setup =====
 pub const RED_MECH_SPAWN: (i32, i32) = (20, 20);
 pub const BLUE_MECH_SPAWN: (i32, i32) = (80, 80);
-pub const RED_PLAYER_SPAWN: (f32, f32) = (15.0, 20.0);
-pub const BLUE_PLAYER_SPAWN: (f32, f32) = (75.0, 80.0);
+pub const PLAYER_SPAWN_OFFSET: (f32, f32) = (-5.0, 0.0); // tiles from the team's mech spawn
 
 // ===== Mech Stats =====
 pub const MECH_INITIAL_HEALTH: u32 = 100;
@@ -19,6 +18,9 @@ pub const MECH_DOOR_ENTRY_DISTANCE: f32 = 0.8; // tiles
 pub const LADDER_INTERACTION_DISTANCE: f32 = 0.3; // tiles
 pub const MECH_COLLISION_DISTANCE: f32 = 5.0; // tiles for resource deposit
 
+// ===== Inventory =====
+pub const INVENTORY_SLOTS: usize = 3; // items a crew member can carry at once
+
 // ===== Combat =====
 pub const LASER_BASE_DAMAGE: u32 = 10;
 pub const LASER_DAMAGE_PER_LEVEL: u32 = 10;
@@ -27,14 +29,97 @@ pub const PROJECTILE_DAMAGE_PER_LEVEL: u32 = 15;
 pub const PROJECTILE_BASE_SPEED: f32 = 300.0; // pixels per second
 pub const PROJECTILE_LIFETIME: f32 = 5.0; // seconds
 pub const SHIELD_BOOST_AMOUNT: u32 = 10;
+pub const SHIELD_BUBBLE_RADIUS_TILES: f32 = 7.5; // from the mech center, just clears the hull corners
+pub const SHIELD_LASER_ABSORPTION: f32 = 0.5; // fraction of a laser hit a raised shield soaks up
+pub const EMP_PROJECTILE_SPEED: f32 = 250.0; // pixels per second
+pub const EMP_DISABLE_DURATION: f32 = 5.0; // seconds an EMP hit shuts down the target's stations
+pub const EMP_COOLDOWN: f32 = 8.0; // seconds
 pub const SHIELD_PER_LEVEL: u32 = 25;
 
+// ===== Armor and Falloff =====
+pub const FRONT_ARMOR_MULTIPLIER: f32 = 0.75; // damage taken from hits on the front arc
+pub const REAR_ARMOR_MULTIPLIER: f32 = 1.25; // damage taken from hits on the rear arc
+pub const ARMOR_ARC_HALF_WIDTH: f32 = 0.785; // radians either side of dead ahead (or astern) an arc spans
+
+// Per weapon: tiles of travel before damage starts dropping, tiles where it
+// bottoms out, and the share of damage left past that
+pub const LASER_FALLOFF: (f32, f32, f32) = (10.0, 40.0, 0.6);
+pub const PROJECTILE_FALLOFF: (f32, f32, f32) = (15.0, 45.0, 0.5);
+pub const MISSILE_FALLOFF: (f32, f32, f32) = (30.0, 60.0, 0.8);
+pub const BEAM_FALLOFF: (f32, f32, f32) = (8.0, 30.0, 0.4);
+
+// ===== Turret =====
+pub const TURRET_DAMAGE: u32 = 10;
+pub const TURRET_PROJECTILE_SPEED: f32 = 400.0; // pixels per second
+pub const TURRET_RANGE: f32 = 25.0; // tiles
+pub const TURRET_MANUAL_COOLDOWN: f32 = 0.5; // seconds between shots fired by an operator
+pub const TURRET_AUTO_COOLDOWN: f32 = 1.5; // seconds between shots with nobody at the station
+pub const TURRET_MANUAL_SPREAD: f32 = 0.02; // radians either side of where the operator aims
+pub const TURRET_AUTO_SPREAD: f32 = 0.2; // radians either side of the auto tracker's aim
+pub const TURRET_VISION_CONE_WIDTH: f32 = 40.0; // degrees the operator sees through the turret window
+
+// ===== Point Defense =====
+pub const POINT_DEFENSE_RANGE: f32 = 8.0; // tiles from the mech center it will engage shots at
+pub const POINT_DEFENSE_MAGAZINE: u32 = 12; // interceptor rounds in a full magazine
+pub const POINT_DEFENSE_COOLDOWN: f32 = 0.4; // seconds between interceptors
+pub const INTERCEPTOR_SPEED: f32 = 700.0; // pixels per second
+pub const INTERCEPTOR_HIT_RADIUS: f32 = 0.5; // tiles an interceptor must pass within to kill a shot
+
+// ===== Radar =====
+pub const RADAR_SWEEP_COOLDOWN: f32 = 15.0; // seconds
+pub const RADAR_SWEEP_BATTERY_COST: u32 = 1;
+pub const RADAR_RANGE: f32 = 60.0; // tiles
+pub const RADAR_BLIP_DURATION: f32 = 5.0; // seconds a sweep's contacts stay on the team's scopes
+pub const RADAR_ECM_RANGE_REDUCTION: f32 = 8.0; // tiles closer a sweep must be per ECM level
+pub const RADAR_ECM_BLIP_ERROR: f32 = 3.0; // tiles a jammed blip may be off by per ECM level
+
+// ===== Resource Scanner =====
+pub const SCANNER_COOLDOWN: f32 = 20.0; // seconds
+pub const SCANNER_RANGE: f32 = 12.0; // tiles within which a ping shows where a resource is
+pub const SCANNER_HINT_RANGE: f32 = 30.0; // tiles within which a ping still gives its bearing
+pub const SCANNER_PING_DURATION: f32 = 4.0; // seconds a ping stays on the scanning player's screen
+
+// ===== Stomp =====
+pub const STOMP_WINDUP: f32 = 0.8; // seconds between the pilot pressing stomp and the impact
+pub const STOMP_COOLDOWN: f32 = 8.0; // seconds, counted from the press
+pub const STOMP_RADIUS: f32 = 9.0; // tiles from the mech's center, 4 past the hull
+pub const STOMP_PLAYER_DAMAGE: u32 = 30;
+pub const STOMP_KNOCKBACK: f32 = 4.0; // tiles a player caught in the shockwave is thrown
+pub const STOMP_MECH_DAMAGE: u32 = 8;
+pub const STOMP_MECH_REACH: f32 = 2.0; // tiles of gap between hulls an enemy mech still gets hit across
+
 // ===== Engine and Speed =====
 pub const MECH_BASE_SPEED: f32 = 2.0; // tiles per second
 pub const MECH_SPEED_PER_LEVEL: f32 = 0.5; // additional tiles per second
 pub const MECH_DEBUG_SPEED: f32 = 1.0; // tiles per second (slow debug speed)
 pub const CONTINUOUS_MOVEMENT_DELTA: f32 = 0.016; // ~60fps frame time
 pub const PLAYER_MOVE_SPEED: f32 = 4.5; // tiles per second
+pub const DASH_DISTANCE: f32 = 3.0; // tiles a dash covers when nothing is in the way
+pub const DASH_COOLDOWN: f32 = 4.0; // seconds between dashes
+pub const MECH_ACCELERATION: f32 = 1.5; // tiles per second squared
+pub const MECH_BRAKE_DECELERATION: f32 = 4.0; // tiles per second squared
+pub const MECH_THROTTLE_RATE: f32 = 0.8; // throttle travel per second of held input
+pub const MECH_REVERSE_THROTTLE: f32 = 0.5; // reverse tops out at this fraction of max speed
+pub const MECH_TURNING_RADIUS: f32 = 4.0; // tiles
+pub const MECH_PIVOT_TURN_RATE: f32 = 0.6; // radians per second when (nearly) stationary
+pub const MECH_MAX_TURN_RATE: f32 = 1.5; // radians per second
+
+// ===== Mech Mass =====
+// Station masses are in stations.toml
+pub const MECH_HULL_MASS: f32 = 40.0; // tonnes of an empty mech
+pub const MECH_MASS_PER_UPGRADE: f32 = 2.0; // tonnes per mech upgrade or station tier past the first
+pub const MECH_MASS_PER_CARGO: f32 = 0.25; // tonnes per resource in the hold
+pub const MECH_MASS_PER_CREW: f32 = 0.5; // tonnes per player aboard
+pub const MECH_REFERENCE_MASS: f32 = 80.0; // tonnes at which a mech moves at its engine's rating
+pub const MECH_MIN_MASS_FACTOR: f32 = 0.5; // the heaviest mech still gets this share of its engine's pull
+pub const MECH_MAX_MASS_FACTOR: f32 = 1.5; // a stripped mech gets at most this much extra pull
+pub const MECH_MASS_SPEED_EXPONENT: f32 = 0.5; // top speed feels mass less than acceleration does
+
+// ===== Fuel =====
+pub const MECH_FUEL_CAPACITY: f32 = 100.0; // a full tank
+pub const MECH_FUEL_BURN_RATE: f32 = 0.5; // per second at full throttle, either way
+pub const FUEL_PER_BATTERY: f32 = 25.0; // poured into the tank per battery delivered
+pub const MECH_DRY_SPEED_FACTOR: f32 = 0.2; // share of max speed left with an empty tank
 
 // ===== Collision Radii =====
 pub const PLAYER_COLLISION_RADIUS: f32 = 0.4; // tiles
@@ -49,32 +134,224 @@ pub const MECH_SEPARATION_FORCE: f32 = 2.0; // force applied to separate overlap
 pub const PLAYER_PUSH_DISTANCE: f32 = 0.5; // tiles - how far to push players away from mechs
 pub const COLLISION_EPSILON: f32 = 0.001; // small value to prevent floating point issues
 
+// ===== Ramming and Bracing =====
+pub const RAM_MIN_CLOSING_SPEED: f32 = 1.0; // tiles per second two mechs must close at for a collision to hurt
+pub const RAM_DAMAGE_PER_SPEED: f32 = 8.0; // hull damage per tile per second of closing speed, before armor
+pub const RAM_CONTACT_MARGIN: f32 = 0.25; // tiles of gap between hulls that still counts as touching
+pub const RAM_COOLDOWN: f32 = 1.5; // seconds before the same two mechs can hurt each other again
+pub const RAM_BOUNCE_SPEED: f32 = 1.0; // tiles per second each mech is thrown back off the other
+pub const BRACE_DURATION: f32 = 2.0; // seconds a brace holds
+pub const BRACE_COOLDOWN: f32 = 8.0; // seconds, counted from the press
+pub const BRACE_RAM_MULTIPLIER: f32 = 0.5; // share of ram damage a braced mech takes
+
+// ===== Status Effects =====
+pub const PROJECTILE_SLOW_DURATION: f32 = 1.5; // seconds
+pub const PROJECTILE_SLOW_AMOUNT: f32 = 0.15; // fraction of speed lost per stack
+pub const PROJECTILE_KNOCKBACK_SPEED: f32 = 1.5; // tiles per second added to target mech
+pub const LASER_BURN_DURATION: f32 = 3.0; // seconds
+pub const LASER_BURN_DPS: f32 = 2.0; // damage per second per stack
+pub const SHIELD_BREAK_EMP_DURATION: f32 = 4.0; // seconds of EMP when a hit breaks the shield
+pub const EXPLOSION_STUN_RADIUS: f32 = 3.0; // tiles - players outside caught in a blast
+pub const EXPLOSION_STUN_DURATION: f32 = 1.0; // seconds
+pub const EXPLOSION_KNOCKBACK_DISTANCE: f32 = 1.0; // tiles players are thrown from a blast
+pub const OVERCHARGE_DAMAGE_MULTIPLIER: f32 = 1.25; // weapon damage while Boost Systems runs
+
 // ===== Repairs and Upgrades =====
 pub const REPAIR_HP_PER_SCRAP: u32 = 20;
 
+// ===== Interior Fires and Hull Breaches =====
+pub const FIRE_CHANCE_PER_DAMAGE: f32 = 0.02; // chance per point of hull damage to start a fire
+pub const HULL_BREACH_CHANCE_PER_DAMAGE: f32 = 0.01; // chance per point of hull damage to breach a wall
+pub const FIRE_IGNITION_INTENSITY: f32 = 0.3;
+pub const FIRE_GROWTH_RATE: f32 = 0.1; // intensity per second
+pub const FIRE_SPREAD_INTERVAL: f32 = 4.0; // seconds between spread attempts
+pub const FIRE_SPREAD_MIN_INTENSITY: f32 = 0.6; // fires weaker than this don't spread
+pub const FIRE_SPREAD_CHANCE: f32 = 0.5;
+pub const MAX_FIRES_PER_MECH: usize = 12;
+pub const FIRE_CREW_BURN_DURATION: f32 = 1.0; // seconds of Burning refreshed while standing in fire
+pub const FIRE_CREW_KNOCKOUT_TIME: f32 = 4.0; // seconds in fire before a crew member is knocked out
+pub const EXTINGUISHER_POWER: f32 = 0.5; // intensity removed per use
+pub const HULL_REPAIR_CHANNEL_TIME: f32 = 3.0; // seconds to patch one breached wall tile
+pub const HULL_BREACH_MAX_HEALTH_PENALTY: u32 = 10; // max health lost per breached wall tile
+pub const MIN_DAMAGED_MAX_HEALTH: u32 = 30; // breaches never reduce max health below this
+
+// ===== Interior Impacts =====
+pub const INTERIOR_IMPACT_DAMAGE: u32 = 30; // hull damage from one hit that rocks everyone inside
+pub const INTERIOR_IMPACT_FULL_DAMAGE: u32 = 60; // hull damage at which an impact is as violent as it gets
+pub const INTERIOR_IMPACT_STAGGER_DURATION: f32 = 0.6; // seconds crew inside lose control
+pub const INTERIOR_IMPACT_KNOCKOFF_CHANCE: f32 = 0.5; // chance an operator is thrown off their station, at full violence
+pub const INTERIOR_IMPACT_SPILL_CHANCE: f32 = 0.5; // chance loose cargo is shaken out of the hull, at full violence
+pub const INTERIOR_IMPACT_SPILL_MAX: u32 = 2; // most cargo one impact shakes loose
+pub const INTERIOR_IMPACT_SPILL_RADIUS: i32 = 3; // tiles past the hull spilled cargo lands
+
+// ===== Boarding =====
+pub const BOARDING_DOOR_BREAK_FRACTION: f32 = 0.5; // doors let enemies in at or below this share of max health
+pub const PLAYER_MAX_HEALTH: u32 = 100;
+pub const MELEE_DAMAGE: u32 = 25;
+pub const MELEE_RANGE: f32 = 1.5; // tiles
+pub const MELEE_COOLDOWN: f32 = 0.6; // seconds between swings
+pub const SABOTAGE_CHANNEL_TIME: f32 = 4.0; // seconds a boarder works a station to disable it
+pub const SABOTAGE_DISABLE_DURATION: f32 = 20.0; // seconds a sabotaged station stays offline
+pub const SABOTAGE_ALARM_DURATION: f32 = 8.0; // seconds an alarm sounds after the last boarder stops working a station
+
+// ===== Barricades =====
+pub const BARRICADE_SCRAP_COST: u32 = 2; // scrap taken from the mech's stock per barricade
+pub const BARRICADE_MAX_HEALTH: u32 = 100; // four melee swings
+pub const MAX_BARRICADES_PER_MECH: usize = 8;
+
+// ===== Achievements =====
+pub const ACHIEVEMENT_COURIER_DELIVERIES: u32 = 10; // resources delivered for the Courier achievement
+
+// ===== Arena Map Hazards =====
+pub const HAZARD_RADIATION_DPS: f32 = 1.0; // damage per second per irradiated tile under a mech
+pub const HAZARD_MINE_DAMAGE: u32 = 20;
+
+// ===== Scripted Tiles =====
+pub const SCRIPT_EFFECT_DURATION: f32 = 1.0; // seconds an effect lasts when the script doesn't say
+pub const SCRIPT_MIN_INTERVAL: f32 = 0.25; // shortest 'every' a script may ask for, in seconds
+pub const SCRIPT_MAX_RANGE: f32 = 10.0; // farthest 'near' a script may reach, in tiles
+
+// ===== Team Bases =====
+pub const REPAIR_BAY_MARGIN: i32 = 1; // tiles of bay floor around a parked mech
+pub const REPAIR_BAY_HEAL_RATE: f32 = 2.0; // hull points per second
+pub const REPAIR_BAY_MAX_PARKED_SPEED: f32 = 0.2; // tiles per second; faster is driving through
+pub const RESUPPLY_DEPOT_OFFSET: (i32, i32) = (-5, 3); // tiles from the team's mech spawn
+pub const RESUPPLY_DEPOT_INTERVAL: f32 = 30.0; // seconds between resources
+
+// ===== Spawn Protection =====
+pub const SAFE_ZONE_RADIUS_TILES: i32 = 4; // tiles each way from a team's player spawn
+pub const SPAWN_PROTECTION_DURATION: f32 = 5.0; // seconds a respawned player can't be hurt
+pub const SAFE_ZONE_AURA_DPS: f32 = 10.0; // damage per second to enemies standing in a safe zone
+pub const SAFE_ZONE_SLOW_AMOUNT: f32 = 0.4; // fraction of speed enemies lose in a safe zone
+pub const SAFE_ZONE_SLOW_DURATION: f32 = 0.5; // seconds the slow lingers after leaving
+
+// ===== Vision =====
+pub const PLAYER_VISION_RANGE: f32 = 8.0; // tiles of line of sight for crew outside
+pub const PLAYER_VISION_CONE_WIDTH: f32 = 120.0; // degrees of full-range sight ahead of crew outside
+pub const PLAYER_VISION_REAR_RANGE: f32 = 0.3; // share of full range crew outside see off the cone
+pub const MECH_SENSOR_RANGE: i32 = 12; // tiles swept by a mech's turret sensors
+pub const TEAM_VISION_UPDATE_TICKS: u64 = 15; // ticks between shared vision updates
+pub const VISION_RECALC_DISTANCE: i32 = 1; // tiles a viewer moves before their sight is recast
+pub const VISION_RECALCS_PER_TICK: usize = 4; // stale viewers recast per tick, at most
+pub const VISION_RECALC_BUDGET_MS: f32 = 1.0; // milliseconds per tick spent recasting stale viewers
+pub const VISION_CACHE_CAPACITY: usize = 512; // cached viewpoints before the cache starts over
+
+// ===== Day, Night and Weather =====
+pub const DAY_LENGTH: f32 = 480.0; // seconds for a full day and night
+pub const NIGHT_VISION_SCALE: f32 = 0.6; // share of sight range left at midnight
+pub const FOG_VISION_SCALE: f32 = 0.5; // share of sight range left in fog
+pub const RAIN_MOVE_SCALE: f32 = 0.85; // crew outside walk this much slower in the rain
+pub const WEATHER_CLEAR_SECONDS: (f32, f32) = (120.0, 240.0); // clear spells last between these
+pub const WEATHER_EVENT_SECONDS: (f32, f32) = (45.0, 90.0); // fog and rain last between these
+
+// ===== Match Director =====
+pub const DIRECTOR_EVALUATE_INTERVAL: f32 = 5.0; // seconds between pacing decisions
+pub const DIRECTOR_INTENSITY_WINDOW: f32 = 30.0; // seconds of history the intensity average covers
+pub const DIRECTOR_DEATH_WEIGHT: f32 = 25.0; // a crew death counts as this much damage
+pub const DIRECTOR_LOW_INTENSITY: f32 = 1.0; // damage per second below which the match is dragging
+pub const DIRECTOR_STALEMATE_TIME: f32 = 60.0; // seconds without any damage before forcing a fight
+pub const DIRECTOR_EVENT_COOLDOWN: f32 = 45.0; // minimum seconds between pacing events
+pub const RESOURCE_SURGE_COUNT: usize = 6;
+pub const RESOURCE_SURGE_RADIUS: f32 = 6.0; // tiles
+pub const RESOURCE_SURGE_DURATION: f32 = 20.0; // seconds the surge marker stays on screen
+pub const DAMAGE_BOOST_MULTIPLIER: f32 = 1.5;
+pub const DAMAGE_BOOST_RADIUS: f32 = 10.0; // tiles
+pub const DAMAGE_BOOST_DURATION: f32 = 45.0; // seconds
+pub const PLAY_AREA_SHRINK_START: f32 = 600.0; // seconds into the match
+pub const PLAY_AREA_MIN_RADIUS: f32 = 20.0; // tiles
+pub const PLAY_AREA_SHRINK_SPEED: f32 = 0.25; // tiles per second
+pub const PLAY_AREA_OUTSIDE_DPS: f32 = 2.0; // damage per second to mechs outside the play area
+
+// ===== Game Modes =====
+pub const RESOURCE_RACE_TARGET: u32 = 15; // resources a team delivers to win the race
+pub const ESCORT_GOAL_RADIUS: f32 = 8.0; // tiles from the goal the payload mech's center must reach
+pub const ESCORT_TIME_LIMIT: f32 = 600.0; // seconds the defenders have to hold out
+
+// ===== Hangar =====
+pub const HANGAR_DURATION: f32 = 60.0; // seconds the hangar waits for everyone to ready up
+pub const AI_ASSIGNED_HAT_BONUS: f32 = 0.3; // pull of the role the crew left an AI towards that hat
+
+// ===== Camera Director =====
+pub const CAMERA_DIRECTOR_INTERVAL: f32 = 0.5; // seconds between looks for something better to watch
+pub const CAMERA_DIRECTOR_MIN_HOLD: f32 = 4.0; // seconds the camera stays on a mech before cutting away
+pub const CAMERA_DIRECTOR_SWITCH_MARGIN: f32 = 1.5; // how many times more interesting a mech must be to cut to it
+pub const CAMERA_DIRECTOR_HEAT_WINDOW: f32 = 5.0; // seconds damage keeps a mech interesting
+pub const CAMERA_DIRECTOR_DAMAGE_WEIGHT: f32 = 1.0; // score per point of recent damage
+pub const CAMERA_DIRECTOR_PROXIMITY_RANGE: f32 = 25.0; // tiles within which an enemy mech counts as closing
+pub const CAMERA_DIRECTOR_PROXIMITY_WEIGHT: f32 = 20.0; // score for an enemy mech right alongside
+pub const CAMERA_DIRECTOR_LOW_HEALTH: f32 = 0.4; // share of max health below which a mech is hanging on
+pub const CAMERA_DIRECTOR_LOW_HEALTH_WEIGHT: f32 = 30.0; // score for a mech on its last point of health
+
 // ===== Game Balance =====
 pub const MAX_TEAM_SIZE_DIFFERENCE: usize = 1;
 pub const MAX_UPGRADE_LEVEL: u8 = 5;
+pub const MAX_STATION_LEVEL: u8 = 3; // tiers a single station can be upgraded through
+pub const STATION_DAMAGE_PER_LEVEL: u32 = 10; // weapon damage each station level past the first adds
+pub const STATION_BOOST_PER_LEVEL: u32 = 5; // shield, repair and charge each station level past the first adds
+pub const STATION_COOLDOWN_CUT_PER_LEVEL: f32 = 0.15; // share of a button's cooldown each station level past the first takes off
+pub const TOP_TIER_CORE_COST: u32 = 1; // power cores the last tier of a station or mech upgrade costs on top
+pub const AI_AUTOFILL_DIFFICULTY: f32 = 0.5; // difficulty of AI crew spawned by autofill
+pub const AI_PERCEPTION_RADIUS: f32 = 40.0; // tiles an AI refreshes resources and projectiles within
+pub const AI_MEMORY_TICKS: u64 = 300; // ticks an AI remembers a resource it can no longer see
+pub const AI_ORDER_MAX_AGE_TICKS: u64 = 15; // ticks an AI's orders stay good for before they're dropped as stale
+pub const AI_FRAME_BUDGET_SHARE: f32 = 0.25; // share of a game frame one AI update may take before it's flagged
+pub const AI_BUDGET_WARN_INTERVAL: f32 = 10.0; // seconds between warnings about AI updates over budget
+pub const AI_COMM_HISTORY: usize = 1000; // AI messages kept for the debug stream to catch up on
+pub const WASM_BOT_FUEL: u64 = 10_000_000; // fuel (roughly instructions) a WASM bot may burn per decision
+pub const WASM_BOT_TIME_LIMIT_MS: u64 = 20; // wall time a WASM bot's decision may take before it's cut off
+pub const WASM_BOT_MAX_MEMORY: usize = 16 << 20; // bytes of linear memory a WASM bot may grow to
+pub const WASM_BOT_MAX_OUTPUT: usize = 64 << 10; // bytes of JSON a WASM bot may answer a decision with
+
+// ===== Matchmaking =====
+pub const MATCH_SIZE: usize = 4; // players the queue gathers before opening a room, split evenly
+pub const RATING_START: f32 = 1000.0; // rating of a player with no record
+pub const RATING_PER_RESULT: f32 = 25.0; // rating each win adds and each loss takes off
+
+// ===== Idle Players =====
+pub const AFK_TIMEOUT_SECONDS: f32 = 90.0; // seconds without input before a player counts as away
+
+// ===== AI Formations =====
+pub const AI_FORMATION_SPACING: f32 = 2.0; // tiles between squad slots, and the closest a bot stands to anyone
+pub const AI_FORMATION_JOIN_RADIUS: f32 = 8.0; // tiles from a leader an AI falls in with its squad
+pub const AI_FORMATION_MAX_SIZE: usize = 4; // leader included
+pub const AI_ESCORT_RANGE: f32 = 15.0; // tiles a combat AI will travel to guard a carrier
+pub const AI_ESCORT_DISTANCE: f32 = 2.0; // tiles an escort keeps from its carrier, on the enemy side
+pub const AI_ESCORT_CALL_RANGE: f32 = 30.0; // tiles a combat AI will travel to answer a carrier's call
 
-// ===== Resource Costs =====
-pub mod upgrade_costs {
-    use crate::types::ResourceType;
+// ===== AI Threat Map =====
+pub const AI_THREAT_CELL_TILES: i32 = 2; // tiles per side of a threat map cell
+pub const AI_THREAT_FIRE_HALF_LIFE: f32 = 3.0; // seconds for the danger a shot leaves behind to halve
+pub const AI_THREAT_PROJECTILE_LOOKAHEAD: f32 = 1.5; // seconds of flight path marked ahead of each shot
+pub const AI_THREAT_PROJECTILE: f32 = 1.0;
+pub const AI_THREAT_TURRET: f32 = 0.6;
+pub const AI_THREAT_TURRET_MARGIN: f32 = 0.15; // radians added either side of a turret's spread
+pub const AI_THREAT_MECH: f32 = 0.8; // at the mech's center, fading to nothing at the radius
+pub const AI_THREAT_MECH_RADIUS: f32 = 8.0; // tiles
+pub const AI_THREAT_LOOKAHEAD: f32 = 4.0; // tiles ahead a bot checks each heading for danger
+pub const AI_THREAT_DETOUR_COST: f32 = 0.3; // danger a bot accepts rather than turning fully aside
+pub const AI_THREAT_IGNORE: f32 = 0.05; // danger ahead below which bots walk straight
 
-    pub const LASER_UPGRADE: &[(ResourceType, usize)] = &[
-        (ResourceType::ScrapMetal, 2),
-        (ResourceType::ComputerComponents, 1),
-    ];
+// ===== AI Carriers =====
+pub const AI_CARRIER_CALL_RADIUS: f32 = 12.0; // tiles within which an enemy makes a loaded carrier call for an escort
+pub const AI_CARRIER_DETOUR: f32 = 6.0; // tiles aside of the straight line a carrier considers swinging wide
+pub const AI_CARRIER_DETOUR_COST: f32 = 0.1; // danger-tiles a carrier walks through rather than add a tile of detour
 
-    pub const PROJECTILE_UPGRADE: &[(ResourceType, usize)] = &[(ResourceType::ScrapMetal, 3)];
+// ===== AI Self-Preservation =====
+pub const AI_RETREAT_MECH_HEALTH: f32 = 0.35; // fraction of hull below which crews head home to repair
+pub const AI_OUTNUMBERED_RADIUS: f32 = 10.0; // tiles within which bots count friends and foes on foot
+pub const AI_DODGE_LOOKAHEAD: f32 = 1.5; // seconds ahead a bot watches for mechs bearing down on it
+pub const AI_DODGE_MARGIN: f32 = 1.0; // tiles of clearance kept from a mech's path
+pub const AI_MECH_MOVING_SPEED: f32 = 0.1; // tiles/sec below which a mech counts as parked
+pub const AI_DASH_DODGE_TIME: f32 = 0.6; // seconds from contact at which a bot dashes clear instead of walking
 
-    pub const SHIELD_UPGRADE: &[(ResourceType, usize)] =
-        &[(ResourceType::Batteries, 2), (ResourceType::Wiring, 1)];
+// ===== AI Blackboard =====
+pub const AI_CLAIM_TICKS: u64 = 60; // ticks a bot's claim on a resource or station holds unless renewed
+pub const AI_SIGHTING_TICKS: u64 = 150; // ticks an enemy sighting stays on the board
+pub const AI_REPAIR_NEED_HEALTH: f32 = 0.6; // fraction of hull below which a mech asks for repairs
 
-    pub const ENGINE_UPGRADE: &[(ResourceType, usize)] = &[
-        (ResourceType::ComputerComponents, 2),
-        (ResourceType::Wiring, 2),
-    ];
-}
+// ===== Resource Economy =====
+// Upgrade costs are the resource_cost of the Upgrade station's buttons in StationRegistry
+pub const DELIVERY_SCORE_PER_RESOURCE: u32 = 10; // team score for each resource dropped off
 
 // ===== Initial Resource Spawns =====
 pub const INITIAL_RESOURCE_SPAWNS: &[(i32, i32)] =
@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+
+/// What one kind of server message is costing, for finding the messages
+/// worth slimming down before touching the protocol
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MessageBandwidth {
+    /// `ServerMessage::type_name` of the messages counted
+    pub message_type: String,
+    /// Over the last full second, across every client
+    pub messages_per_second: u64,
+    pub bytes_per_second: u64,
+    /// Since the server started
+    pub total_messages: u64,
+    pub total_bytes: u64,
+}
@@ -1,5 +1,9 @@
-use crate::{WorldPos, TILE_SIZE};
+use crate::{
+    MechPositioning, TilePos, WorldPos, COLLISION_EPSILON, SHIELD_BUBBLE_RADIUS_TILES,
+    SHIELD_LASER_ABSORPTION, TILE_SIZE,
+};
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
 /// Axis-Aligned Bounding Box for collision detection
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
@@ -334,6 +338,108 @@ impl CollisionUtils {
     }
 }
 
+/// A raised shield as a sphere around its mech. Projectiles stop at its
+/// surface and lasers lose part of their energy crossing it.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ShieldBubble {
+    pub center: WorldPos,
+    pub radius: f32,
+}
+
+impl ShieldBubble {
+    /// The bubble around a mech at the given tile position
+    pub fn around(mech_position: TilePos) -> Self {
+        Self {
+            center: MechPositioning::mech_center(mech_position),
+            radius: SHIELD_BUBBLE_RADIUS_TILES * TILE_SIZE,
+        }
+    }
+
+    pub fn contains(&self, point: WorldPos) -> bool {
+        point.distance_to(self.center) <= self.radius
+    }
+
+    /// Where a path from `start` to `end` first crosses the bubble surface.
+    /// None if it never reaches the bubble or already started inside it.
+    pub fn entry_point(&self, start: WorldPos, end: WorldPos) -> Option<WorldPos> {
+        if self.contains(start) {
+            return None;
+        }
+
+        // Solve |start + t * d - center| = radius for the first t in 0..=1
+        let d = (end.x - start.x, end.y - start.y);
+        let f = (start.x - self.center.x, start.y - self.center.y);
+        let a = d.0 * d.0 + d.1 * d.1;
+        if a < COLLISION_EPSILON {
+            return None;
+        }
+        let b = 2.0 * (f.0 * d.0 + f.1 * d.1);
+        let c = f.0 * f.0 + f.1 * f.1 - self.radius * self.radius;
+        let discriminant = b * b - 4.0 * a * c;
+        if discriminant < 0.0 {
+            return None;
+        }
+        let t = (-b - discriminant.sqrt()) / (2.0 * a);
+        (0.0..=1.0)
+            .contains(&t)
+            .then(|| WorldPos::new(start.x + d.0 * t, start.y + d.1 * t))
+    }
+
+    /// Split a laser hit into what the shield soaks and what reaches the hull
+    pub fn attenuate_laser(damage: u32, shield: u32) -> (u32, u32) {
+        let absorbed = ((damage as f32 * SHIELD_LASER_ABSORPTION).round() as u32).min(shield);
+        (absorbed, damage - absorbed)
+    }
+}
+
+/// The shapes the server tests collisions against, sent to clients that ask
+/// for verbose state so a debug overlay can draw them. Anything aboard a mech
+/// is placed where its floor is drawn.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CollisionShapes {
+    pub tick: u64,
+    pub players: Vec<PlayerHitbox>,
+    pub mechs: Vec<MechHitbox>,
+    pub projectiles: Vec<ProjectilePath>,
+    pub stations: Vec<StationReach>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerHitbox {
+    pub player_id: Uuid,
+    pub center: WorldPos,
+    pub radius: f32,
+    /// Mech and floor, for players aboard one
+    pub aboard: Option<(Uuid, u8)>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MechHitbox {
+    pub mech_id: Uuid,
+    pub hull: AABB,
+    /// Only while the shield is up
+    pub shield: Option<ShieldBubble>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectilePath {
+    pub id: Uuid,
+    pub position: WorldPos,
+    pub velocity: (f32, f32),
+    pub radius: f32,
+    /// Where it has been lately, oldest first
+    pub trail: Vec<WorldPos>,
+}
+
+/// The tiles a station can be taken over from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StationReach {
+    pub station_id: Uuid,
+    pub mech_id: Uuid,
+    pub floor: u8,
+    pub area: AABB,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -391,4 +497,35 @@ mod tests {
             1.0
         ));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_shield_bubble_entry_point() {
+        let bubble = ShieldBubble {
+            center: WorldPos::new(0.0, 0.0),
+            radius: 10.0,
+        };
+
+        let contact = bubble
+            .entry_point(WorldPos::new(-20.0, 0.0), WorldPos::new(0.0, 0.0))
+            .unwrap();
+        assert!((contact.x + 10.0).abs() < 1e-3 && contact.y.abs() < 1e-3);
+
+        // Falls short, passes wide, or started inside
+        assert!(bubble
+            .entry_point(WorldPos::new(-20.0, 0.0), WorldPos::new(-15.0, 0.0))
+            .is_none());
+        assert!(bubble
+            .entry_point(WorldPos::new(-20.0, 12.0), WorldPos::new(20.0, 12.0))
+            .is_none());
+        assert!(bubble
+            .entry_point(WorldPos::new(-5.0, 0.0), WorldPos::new(20.0, 0.0))
+            .is_none());
+    }
+
+    #[test]
+    fn test_laser_attenuation_is_limited_by_shield() {
+        assert_eq!(ShieldBubble::attenuate_laser(20, 50), (10, 10));
+        assert_eq!(ShieldBubble::attenuate_laser(20, 4), (4, 16));
+        assert_eq!(ShieldBubble::attenuate_laser(20, 0), (0, 20));
+    }
+}
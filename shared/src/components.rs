@@ -1,3 +1,9 @@
+use crate::balance::{
+    FIRE_GROWTH_RATE, FIRE_IGNITION_INTENSITY, FIRE_SPREAD_INTERVAL, FIRE_SPREAD_MIN_INTENSITY,
+    REPAIR_BAY_MARGIN, RESUPPLY_DEPOT_OFFSET,
+};
+use crate::constants::MECH_SIZE_TILES;
+use crate::tile_script::TileScript;
 use crate::{ResourceType, StationType, TeamId, TilePos, WorldPos, MechId, EntityId, PlayerId};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -48,6 +54,325 @@ pub enum TargetMode {
     Manual,
 }
 
+// =============================================================================
+// Status Effect Components
+// =============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum StatusEffectKind {
+    /// Cannot move or operate stations
+    Stunned,
+    /// Movement speed reduced by `magnitude` per stack
+    Slowed,
+    /// Takes `magnitude` damage per second per stack
+    Burning,
+    /// Electronics disabled: stations and engines stop responding
+    Emp,
+    /// A station wrecked by enemy boarders; nobody can operate it
+    Sabotaged,
+    /// Just respawned: enemies can't hurt them until it runs out or they
+    /// attack
+    SpawnProtected,
+    /// A mech set for a collision: rams do `magnitude` of their damage
+    Braced,
+    /// A mech running its systems hot: weapons do `magnitude` times their
+    /// damage
+    Overcharged,
+}
+
+/// How re-applying an effect that is already active combines with it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StackingRule {
+    /// Keep one instance, reset duration to the longer of the two
+    Refresh,
+    /// Keep one instance, add the new duration on top (capped)
+    Extend { max_duration: f32 },
+    /// Add a stack (capped) and refresh duration
+    Stack { max_stacks: u8 },
+}
+
+impl StatusEffectKind {
+    pub fn stacking_rule(&self) -> StackingRule {
+        match self {
+            StatusEffectKind::Stunned => StackingRule::Refresh,
+            StatusEffectKind::Slowed => StackingRule::Stack { max_stacks: 3 },
+            StatusEffectKind::Burning => StackingRule::Stack { max_stacks: 5 },
+            StatusEffectKind::Emp => StackingRule::Extend { max_duration: 10.0 },
+            StatusEffectKind::Sabotaged => StackingRule::Refresh,
+            StatusEffectKind::SpawnProtected => StackingRule::Refresh,
+            StatusEffectKind::Braced => StackingRule::Refresh,
+            StatusEffectKind::Overcharged => StackingRule::Refresh,
+        }
+    }
+
+    /// Short label used for HUD badges
+    pub fn badge(&self) -> &'static str {
+        match self {
+            StatusEffectKind::Stunned => "STN",
+            StatusEffectKind::Slowed => "SLW",
+            StatusEffectKind::Burning => "BRN",
+            StatusEffectKind::Emp => "EMP",
+            StatusEffectKind::Sabotaged => "SAB",
+            StatusEffectKind::SpawnProtected => "SPN",
+            StatusEffectKind::Braced => "BRC",
+            StatusEffectKind::Overcharged => "OVR",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusEffect {
+    pub kind: StatusEffectKind,
+    pub remaining: f32, // seconds
+    pub stacks: u8,
+    pub magnitude: f32,
+}
+
+/// All status effects currently active on a player or mech
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StatusEffects {
+    pub effects: Vec<StatusEffect>,
+}
+
+impl StatusEffects {
+    /// Apply an effect following its stacking rule
+    pub fn apply(&mut self, kind: StatusEffectKind, duration: f32, magnitude: f32) {
+        let Some(existing) = self.effects.iter_mut().find(|e| e.kind == kind) else {
+            self.effects.push(StatusEffect {
+                kind,
+                remaining: duration,
+                stacks: 1,
+                magnitude,
+            });
+            return;
+        };
+
+        match kind.stacking_rule() {
+            StackingRule::Refresh => {
+                existing.remaining = existing.remaining.max(duration);
+                existing.magnitude = existing.magnitude.max(magnitude);
+            }
+            StackingRule::Extend { max_duration } => {
+                existing.remaining = (existing.remaining + duration).min(max_duration);
+            }
+            StackingRule::Stack { max_stacks } => {
+                existing.stacks = (existing.stacks + 1).min(max_stacks);
+                existing.remaining = existing.remaining.max(duration);
+                existing.magnitude = existing.magnitude.max(magnitude);
+            }
+        }
+    }
+
+    /// Advance durations, returning the damage dealt by burning this step
+    /// and whether any effect expired
+    pub fn tick(&mut self, delta_time: f32) -> (f32, bool) {
+        let damage = self
+            .effects
+            .iter()
+            .filter(|e| e.kind == StatusEffectKind::Burning)
+            .map(|e| e.magnitude * e.stacks as f32 * delta_time.min(e.remaining))
+            .sum();
+
+        let before = self.effects.len();
+        for effect in &mut self.effects {
+            effect.remaining -= delta_time;
+        }
+        self.effects.retain(|e| e.remaining > 0.0);
+
+        (damage, self.effects.len() != before)
+    }
+
+    pub fn has(&self, kind: StatusEffectKind) -> bool {
+        self.effects.iter().any(|e| e.kind == kind)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.effects.is_empty()
+    }
+
+    /// Multiplier applied to movement speed (0.0 when stunned)
+    pub fn movement_multiplier(&self) -> f32 {
+        if self.has(StatusEffectKind::Stunned) {
+            return 0.0;
+        }
+
+        self.effects
+            .iter()
+            .filter(|e| e.kind == StatusEffectKind::Slowed)
+            .map(|e| (1.0 - e.magnitude * e.stacks as f32).max(0.2))
+            .product()
+    }
+
+    /// Multiplier applied to weapon damage (1.0 unless overcharged)
+    pub fn damage_multiplier(&self) -> f32 {
+        self.effects
+            .iter()
+            .filter(|e| e.kind == StatusEffectKind::Overcharged)
+            .map(|e| e.magnitude)
+            .product()
+    }
+
+    /// The effect preventing station use, if any
+    pub fn blocks_station_use(&self) -> Option<StatusEffectKind> {
+        [
+            StatusEffectKind::Stunned,
+            StatusEffectKind::Emp,
+            StatusEffectKind::Sabotaged,
+        ]
+            .into_iter()
+            .find(|kind| self.has(*kind))
+    }
+}
+
+// =============================================================================
+// Fire and Repair Components
+// =============================================================================
+
+/// A fire burning on an interior floor tile
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Fire {
+    /// 0.0 (out) to 1.0 (fully ablaze)
+    pub intensity: f32,
+    /// Seconds until this fire next tries to spread
+    pub spread_timer: f32,
+}
+
+impl Fire {
+    pub fn ignite() -> Self {
+        Self {
+            intensity: FIRE_IGNITION_INTENSITY,
+            spread_timer: FIRE_SPREAD_INTERVAL,
+        }
+    }
+
+    /// Grow the fire; returns true when it is strong enough to spread this tick
+    pub fn burn(&mut self, dt: f32) -> bool {
+        self.intensity = (self.intensity + FIRE_GROWTH_RATE * dt).min(1.0);
+        self.spread_timer -= dt;
+        if self.spread_timer > 0.0 {
+            return false;
+        }
+        self.spread_timer += FIRE_SPREAD_INTERVAL;
+        self.intensity >= FIRE_SPREAD_MIN_INTENSITY
+    }
+
+    /// Knock the fire down; returns true once it is out
+    pub fn douse(&mut self, amount: f32) -> bool {
+        self.intensity -= amount;
+        self.intensity <= 0.0
+    }
+}
+
+/// A hull repair being channelled by a crew member holding a repair tool
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RepairChannel {
+    pub mech_id: MechId,
+    pub floor: u8,
+    /// Breached wall tile being patched
+    pub target: TilePos,
+    /// 0.0 to 1.0
+    pub progress: f32,
+}
+
+/// A boarder wrecking an enemy station from an adjacent tile
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SabotageChannel {
+    pub mech_id: MechId,
+    pub station_id: EntityId,
+    /// 0.0 to 1.0
+    pub progress: f32,
+}
+
+/// A mech warning its crew that boarders are at one of its stations
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SabotageAlarm {
+    pub station_id: EntityId,
+    /// The boarder who set it off
+    pub intruder: PlayerId,
+    /// Seconds until it goes quiet, unless it's set off again
+    pub remaining: f32,
+}
+
+// =============================================================================
+// Team Base Components
+// =============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StructureKind {
+    /// Slowly repairs a friendly mech parked inside it
+    RepairBay,
+    /// Puts out a basic resource every so often
+    ResupplyDepot,
+}
+
+/// A static building at a team's base
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TeamStructure {
+    pub kind: StructureKind,
+    pub team: TeamId,
+    /// Top-left tile of the footprint
+    pub position: TilePos,
+    pub width: i32,  // tiles
+    pub height: i32, // tiles
+}
+
+impl TeamStructure {
+    /// The structures a team starts with, placed around its mech spawn and
+    /// kept inside an arena of the given size
+    pub fn base_for(
+        team: TeamId,
+        mech_spawn: TilePos,
+        arena_width: i32,
+        arena_height: i32,
+    ) -> [Self; 2] {
+        let bay_size = MECH_SIZE_TILES + REPAIR_BAY_MARGIN * 2;
+        let clamp = |x: i32, y: i32, size: i32| {
+            TilePos::new(
+                x.clamp(0, (arena_width - size).max(0)),
+                y.clamp(0, (arena_height - size).max(0)),
+            )
+        };
+        [
+            Self {
+                kind: StructureKind::RepairBay,
+                team,
+                position: clamp(
+                    mech_spawn.x - REPAIR_BAY_MARGIN,
+                    mech_spawn.y - REPAIR_BAY_MARGIN,
+                    bay_size,
+                ),
+                width: bay_size,
+                height: bay_size,
+            },
+            Self {
+                kind: StructureKind::ResupplyDepot,
+                team,
+                position: clamp(
+                    mech_spawn.x + RESUPPLY_DEPOT_OFFSET.0,
+                    mech_spawn.y + RESUPPLY_DEPOT_OFFSET.1,
+                    1,
+                ),
+                width: 1,
+                height: 1,
+            },
+        ]
+    }
+
+    pub fn contains(&self, tile: TilePos) -> bool {
+        (self.position.x..self.position.x + self.width).contains(&tile.x)
+            && (self.position.y..self.position.y + self.height).contains(&tile.y)
+    }
+
+    /// Whether a mech with its top-left tile at `mech_pos` fits entirely inside
+    pub fn holds_mech(&self, mech_pos: TilePos) -> bool {
+        self.contains(mech_pos)
+            && self.contains(TilePos::new(
+                mech_pos.x + MECH_SIZE_TILES - 1,
+                mech_pos.y + MECH_SIZE_TILES - 1,
+            ))
+    }
+}
+
 // =============================================================================
 // Infrastructure Components
 // =============================================================================
@@ -275,6 +600,7 @@ pub struct EntityComponents {
     pub resource_pickup: Option<ResourcePickup>,
     pub mech_entrance: Option<MechEntrance>,
     pub auto_interact: Option<AutoInteract>,
+    pub tile_script: Option<TileScript>,
 }
 
 // =============================================================================
@@ -325,4 +651,74 @@ mod tests {
         assert!(template.components.turret.is_some());
         assert!(template.components.power_consumer.is_some());
     }
+
+    #[test]
+    fn test_status_effect_stacking() {
+        let mut effects = StatusEffects::default();
+
+        effects.apply(StatusEffectKind::Slowed, 2.0, 0.25);
+        effects.apply(StatusEffectKind::Slowed, 1.0, 0.25);
+        assert_eq!(effects.effects.len(), 1);
+        assert_eq!(effects.effects[0].stacks, 2);
+        assert_eq!(effects.effects[0].remaining, 2.0);
+        assert!((effects.movement_multiplier() - 0.5).abs() < 0.001);
+
+        effects.apply(StatusEffectKind::Emp, 6.0, 1.0);
+        effects.apply(StatusEffectKind::Emp, 6.0, 1.0);
+        assert_eq!(effects.effects[1].remaining, 10.0);
+        assert_eq!(effects.blocks_station_use(), Some(StatusEffectKind::Emp));
+
+        effects.apply(StatusEffectKind::Stunned, 1.0, 1.0);
+        assert_eq!(effects.movement_multiplier(), 0.0);
+    }
+
+    #[test]
+    fn test_status_effect_tick_and_expiry() {
+        let mut effects = StatusEffects::default();
+        effects.apply(StatusEffectKind::Burning, 1.0, 4.0);
+        effects.apply(StatusEffectKind::Burning, 1.0, 4.0);
+
+        let (damage, expired) = effects.tick(0.5);
+        assert!((damage - 4.0).abs() < 0.001);
+        assert!(!expired);
+
+        let (_, expired) = effects.tick(0.6);
+        assert!(expired);
+        assert!(effects.is_empty());
+    }
+
+    #[test]
+    fn test_fire_growth_and_dousing() {
+        let mut fire = Fire::ignite();
+
+        // Doesn't try to spread before the interval elapses
+        assert!(!fire.burn(1.0));
+
+        // Grows past the spread threshold by the time the interval runs out
+        assert!(fire.burn(FIRE_SPREAD_INTERVAL - 1.0));
+        assert!(fire.intensity >= FIRE_SPREAD_MIN_INTENSITY);
+
+        assert!(!fire.douse(0.1));
+        assert!(fire.douse(1.0));
+    }
+
+    #[test]
+    fn test_base_structures_surround_the_spawn_and_stay_in_bounds() {
+        let spawn = TilePos::new(20, 20);
+        let [bay, depot] = TeamStructure::base_for(TeamId::Red, spawn, 100, 100);
+        assert_eq!(bay.kind, StructureKind::RepairBay);
+        assert!(bay.holds_mech(spawn));
+        assert!(!bay.holds_mech(TilePos::new(spawn.x + REPAIR_BAY_MARGIN + 1, spawn.y)));
+        assert_eq!(
+            depot.position,
+            TilePos::new(
+                spawn.x + RESUPPLY_DEPOT_OFFSET.0,
+                spawn.y + RESUPPLY_DEPOT_OFFSET.1
+            )
+        );
+
+        let [bay, depot] = TeamStructure::base_for(TeamId::Blue, TilePos::new(2, 95), 100, 100);
+        assert_eq!(bay.position, TilePos::new(1, 100 - bay.height));
+        assert!(depot.position.x >= 0);
+    }
 }
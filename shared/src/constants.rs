@@ -2,6 +2,7 @@
 pub const TILE_SIZE: f32 = 32.0;
 pub const ARENA_WIDTH_TILES: i32 = 100;
 pub const ARENA_HEIGHT_TILES: i32 = 100;
+pub const ARENA_MIN_TILES: i32 = 40; // smallest side a custom arena may have
 pub const MECH_SIZE_TILES: i32 = 10;
 
 // Player settings
@@ -641,6 +641,12 @@ impl MechDoorPositions {
         tile_pos == self.left_door || tile_pos == self.right_door
     }
 
+    /// Doors give way to enemy boarders once the mech is badly damaged
+    pub fn breached(health: u32, max_health: u32) -> bool {
+        max_health > 0
+            && (health as f32) <= max_health as f32 * crate::balance::BOARDING_DOOR_BREAK_FRACTION
+    }
+
     /// Get both door positions as an array for iteration
     pub fn door_tiles(&self) -> [TilePos; 2] {
         [self.left_door, self.right_door]
@@ -0,0 +1,122 @@
+//! Marks combat leaves on the ground: scorches from explosions, burns from
+//! laser hits, and debris where resources were blown apart. The server sends
+//! each one once when it's made and the whole layer to anyone joining; both
+//! ends evict the same way, so they never disagree about what's on the map.
+
+use crate::types::TilePos;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Most decals the map keeps; making a new one past this clears the stalest
+pub const MAX_DECALS: usize = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DecalKind {
+    Scorch,
+    Burn,
+    Debris,
+    /// What's left of a player a mech ran over
+    Gore,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Decal {
+    pub position: TilePos,
+    pub kind: DecalKind,
+}
+
+/// One decal per tile, least recently marked first. Marking a tile again
+/// replaces what was there and makes it the newest.
+#[derive(Debug, Clone, Default)]
+pub struct DecalLayer {
+    decals: VecDeque<Decal>,
+}
+
+impl DecalLayer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rebuild a layer from decals listed oldest first
+    pub fn from_decals(decals: impl IntoIterator<Item = Decal>) -> Self {
+        let mut layer = Self::new();
+        for decal in decals {
+            layer.add(decal);
+        }
+        layer
+    }
+
+    pub fn add(&mut self, decal: Decal) {
+        self.decals.retain(|d| d.position != decal.position);
+        self.decals.push_back(decal);
+        while self.decals.len() > MAX_DECALS {
+            self.decals.pop_front();
+        }
+    }
+
+    pub fn get(&self, position: TilePos) -> Option<DecalKind> {
+        self.decals
+            .iter()
+            .find(|d| d.position == position)
+            .map(|d| d.kind)
+    }
+
+    /// Every decal, oldest first
+    pub fn iter(&self) -> impl Iterator<Item = &Decal> {
+        self.decals.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.decals.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.decals.is_empty()
+    }
+
+    pub fn to_vec(&self) -> Vec<Decal> {
+        self.decals.iter().copied().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decal(x: i32, kind: DecalKind) -> Decal {
+        Decal {
+            position: TilePos::new(x, 0),
+            kind,
+        }
+    }
+
+    #[test]
+    fn test_remarking_a_tile_replaces_it_and_saves_it_from_eviction() {
+        let mut layer = DecalLayer::new();
+        for x in 0..MAX_DECALS as i32 {
+            layer.add(decal(x, DecalKind::Scorch));
+        }
+        layer.add(decal(0, DecalKind::Burn));
+        assert_eq!(layer.len(), MAX_DECALS);
+        assert_eq!(layer.get(TilePos::new(0, 0)), Some(DecalKind::Burn));
+
+        // Tile 1 is now the stalest, so it goes first
+        layer.add(decal(MAX_DECALS as i32, DecalKind::Debris));
+        assert_eq!(layer.len(), MAX_DECALS);
+        assert_eq!(layer.get(TilePos::new(1, 0)), None);
+        assert_eq!(layer.get(TilePos::new(0, 0)), Some(DecalKind::Burn));
+    }
+
+    #[test]
+    fn test_rebuilding_from_a_snapshot_keeps_eviction_order() {
+        let mut layer = DecalLayer::new();
+        for x in 0..10 {
+            layer.add(decal(x, DecalKind::Scorch));
+        }
+        layer.add(decal(3, DecalKind::Debris));
+
+        let copy = DecalLayer::from_decals(layer.to_vec());
+        assert_eq!(copy.to_vec(), layer.to_vec());
+        assert_eq!(copy.iter().last(), Some(&decal(3, DecalKind::Debris)));
+    }
+}
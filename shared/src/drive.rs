@@ -0,0 +1,259 @@
+use crate::balance::*;
+use crate::messages::MechUpgrades;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::{PI, TAU};
+
+/// Throttle and steering state of a mech. Pilots move the throttle lever and
+/// turn; the mech accelerates toward the throttle setting and can only turn
+/// as tightly as its turning radius allows at the current speed.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct MechDrive {
+    /// Lever position, from -MECH_REVERSE_THROTTLE (full reverse) to 1.0
+    pub throttle: f32,
+    /// Radians, 0 faces +x and positive turns clockwise on screen
+    pub heading: f32,
+    /// Tiles per second along the heading, negative when reversing
+    pub speed: f32,
+}
+
+impl MechDrive {
+    /// The engine's rated top speed, for a mech of `MECH_REFERENCE_MASS`
+    pub fn max_speed(engine_level: u8) -> f32 {
+        MECH_BASE_SPEED + (engine_level as f32 - 1.0) * MECH_SPEED_PER_LEVEL
+    }
+
+    /// Fuel the engine burns over `delta_time` at the current throttle
+    pub fn fuel_burn(&self, delta_time: f32) -> f32 {
+        self.throttle.abs() * MECH_FUEL_BURN_RATE * delta_time
+    }
+
+    /// Radians per second; slow mechs pivot, fast ones are held to their turning radius
+    pub fn turn_rate(&self) -> f32 {
+        (self.speed.abs() / MECH_TURNING_RADIUS).clamp(MECH_PIVOT_TURN_RATE, MECH_MAX_TURN_RATE)
+    }
+
+    /// Apply one frame of pilot input. Forward (negative y, like W) opens the
+    /// throttle, backward closes it and then reverses; x turns.
+    pub fn apply_input(&mut self, movement: (f32, f32), delta_time: f32) {
+        let (turn, push) = (movement.0.clamp(-1.0, 1.0), -movement.1.clamp(-1.0, 1.0));
+        self.throttle = (self.throttle + push * MECH_THROTTLE_RATE * delta_time)
+            .clamp(-MECH_REVERSE_THROTTLE, 1.0);
+        self.heading = (self.heading + turn * self.turn_rate() * delta_time).rem_euclid(TAU);
+    }
+
+    /// Input that steers toward a world direction, for drivers that think in
+    /// directions rather than levers. A zero direction pulls the throttle back.
+    pub fn input_toward(&self, direction: (f32, f32)) -> (f32, f32) {
+        if direction.0 == 0.0 && direction.1 == 0.0 {
+            return (0.0, self.throttle.signum());
+        }
+        let target = direction.1.atan2(direction.0);
+        let error = (target - self.heading + PI).rem_euclid(TAU) - PI;
+        let turn = (error / (self.turn_rate() * CONTINUOUS_MOVEMENT_DELTA)).clamp(-1.0, 1.0);
+        // Only open up once roughly facing the target
+        let push = if error.abs() < PI / 4.0 { -1.0 } else { 0.0 };
+        (turn, push)
+    }
+
+    /// Accelerate toward the throttle setting, braking harder than the engine
+    /// pulls whenever the mech is slowing down or changing direction
+    pub fn step(&mut self, performance: &MechPerformance, delta_time: f32) {
+        let target = self.throttle * performance.max_speed;
+        let braking = target.abs() < self.speed.abs() || target * self.speed < 0.0;
+        let rate = if braking {
+            performance.braking
+        } else {
+            performance.acceleration
+        } * delta_time;
+        self.speed += (target - self.speed).clamp(-rate, rate);
+    }
+
+    /// Tiles per second
+    pub fn velocity(&self) -> (f32, f32) {
+        (
+            self.heading.cos() * self.speed,
+            self.heading.sin() * self.speed,
+        )
+    }
+}
+
+/// What a mech weighs, in tonnes, by where the weight comes from. The hull
+/// adds `MECH_HULL_MASS` on top.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct MechMass {
+    pub stations: f32,
+    pub upgrades: f32,
+    pub cargo: f32,
+    pub crew: f32,
+}
+
+impl MechMass {
+    /// `stations` gives each installed station's mass and tier
+    pub fn new(
+        stations: impl IntoIterator<Item = (f32, u8)>,
+        upgrades: &MechUpgrades,
+        cargo: u32,
+        crew: usize,
+    ) -> Self {
+        let mut station_mass = 0.0;
+        let mut tiers = 0;
+        for (mass, tier) in stations {
+            station_mass += mass;
+            tiers += tier.saturating_sub(1) as u32;
+        }
+        let levels = [
+            upgrades.laser_level,
+            upgrades.projectile_level,
+            upgrades.engine_level,
+            upgrades.shield_level,
+        ]
+        .iter()
+        .map(|level| level.saturating_sub(1) as u32)
+        .sum::<u32>()
+            + upgrades.ecm_level as u32;
+        Self {
+            stations: station_mass,
+            upgrades: (levels + tiers) as f32 * MECH_MASS_PER_UPGRADE,
+            cargo: cargo as f32 * MECH_MASS_PER_CARGO,
+            crew: crew as f32 * MECH_MASS_PER_CREW,
+        }
+    }
+
+    pub fn total(&self) -> f32 {
+        MECH_HULL_MASS + self.stations + self.upgrades + self.cargo + self.crew
+    }
+}
+
+/// How a mech's engine copes with what it's hauling. Heavier mechs pick up
+/// speed and brake more slowly, and top out lower, though less so.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MechPerformance {
+    /// Tiles per second
+    pub max_speed: f32,
+    /// Tiles per second squared
+    pub acceleration: f32,
+    pub braking: f32,
+}
+
+impl MechPerformance {
+    pub fn new(engine_level: u8, mass: f32) -> Self {
+        let pull = (MECH_REFERENCE_MASS / mass.max(f32::EPSILON))
+            .clamp(MECH_MIN_MASS_FACTOR, MECH_MAX_MASS_FACTOR);
+        Self {
+            max_speed: MechDrive::max_speed(engine_level) * pull.powf(MECH_MASS_SPEED_EXPONENT),
+            acceleration: MECH_ACCELERATION * pull,
+            braking: MECH_BRAKE_DECELERATION * pull,
+        }
+    }
+
+    /// The same with what's left in the tank; running dry leaves a crawl
+    pub fn fueled(self, fuel: f32) -> Self {
+        if fuel > 0.0 {
+            self
+        } else {
+            Self {
+                max_speed: self.max_speed * MECH_DRY_SPEED_FACTOR,
+                ..self
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rated(engine_level: u8) -> MechPerformance {
+        MechPerformance::new(engine_level, MECH_REFERENCE_MASS)
+    }
+
+    #[test]
+    fn test_mech_accelerates_to_engine_max_speed() {
+        let mut drive = MechDrive {
+            throttle: 1.0,
+            ..Default::default()
+        };
+        drive.step(&rated(1), 0.1);
+        assert!(drive.speed > 0.0 && drive.speed < MechDrive::max_speed(1));
+
+        for _ in 0..200 {
+            drive.step(&rated(3), 0.1);
+        }
+        assert!((drive.speed - MechDrive::max_speed(3)).abs() < 1e-4);
+        assert!(MechDrive::max_speed(3) > MechDrive::max_speed(1));
+    }
+
+    #[test]
+    fn test_braking_is_faster_than_accelerating() {
+        let max = MechDrive::max_speed(1);
+        let mut drive = MechDrive {
+            speed: max,
+            ..Default::default()
+        };
+        drive.step(&rated(1), 0.1);
+        let braked = max - drive.speed;
+
+        let mut drive = MechDrive {
+            throttle: 1.0,
+            ..Default::default()
+        };
+        drive.step(&rated(1), 0.1);
+        assert!(braked > drive.speed);
+    }
+
+    #[test]
+    fn test_turning_is_limited_by_speed() {
+        let mut parked = MechDrive::default();
+        parked.apply_input((1.0, 0.0), 1.0);
+        assert!((parked.heading - MECH_PIVOT_TURN_RATE).abs() < 1e-4);
+        assert_eq!(parked.throttle, 0.0);
+
+        let mut moving = MechDrive {
+            speed: MECH_TURNING_RADIUS * MECH_MAX_TURN_RATE * 2.0,
+            ..Default::default()
+        };
+        moving.apply_input((1.0, -1.0), 1.0);
+        assert!((moving.heading - MECH_MAX_TURN_RATE).abs() < 1e-4);
+        assert!(moving.throttle > 0.0);
+    }
+
+    #[test]
+    fn test_input_toward_turns_before_accelerating() {
+        let drive = MechDrive::default();
+        let (turn, push) = drive.input_toward((0.0, 1.0));
+        assert!(turn > 0.0);
+        assert_eq!(push, 0.0);
+
+        let (turn, push) = drive.input_toward((1.0, 0.0));
+        assert_eq!(turn, 0.0);
+        assert_eq!(push, -1.0);
+    }
+
+    #[test]
+    fn test_heavier_mechs_are_slower_off_the_line_and_at_the_top() {
+        let upgrades = MechUpgrades {
+            laser_level: 2,
+            projectile_level: 1,
+            engine_level: 1,
+            shield_level: 1,
+            ecm_level: 1,
+        };
+        let light = MechMass::new([(4.0, 1), (2.0, 1)], &upgrades, 0, 1);
+        assert_eq!(light.stations, 6.0);
+        assert_eq!(light.upgrades, 2.0 * MECH_MASS_PER_UPGRADE);
+        let heavy = MechMass::new([(4.0, 3), (2.0, 1)], &upgrades, 40, 4);
+        assert!(heavy.total() > light.total());
+
+        let light = MechPerformance::new(1, light.total());
+        let heavy = MechPerformance::new(1, heavy.total());
+        assert!(heavy.acceleration < light.acceleration);
+        assert!(heavy.max_speed < light.max_speed);
+        // Acceleration feels the extra weight more than top speed
+        assert!(heavy.acceleration / light.acceleration < heavy.max_speed / light.max_speed);
+
+        // However light, the engine only gives so much
+        let stripped = MechPerformance::new(1, 1.0);
+        assert_eq!(stripped.acceleration, MECH_ACCELERATION * MECH_MAX_MASS_FACTOR);
+        assert!(stripped.fueled(0.0).max_speed < stripped.max_speed);
+    }
+}
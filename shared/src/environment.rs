@@ -0,0 +1,121 @@
+//! The match's day and night and its weather. The server keeps the clock and
+//! decides the weather; clients get both with every full state and run the
+//! clock themselves in between, so the light changes smoothly.
+
+use crate::balance::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Weather {
+    #[default]
+    Clear,
+    /// Fog banks roll in and cut how far anyone outside can see
+    Fog,
+    /// Rain slows crew on foot
+    Rain,
+}
+
+impl Weather {
+    pub fn name(self) -> &'static str {
+        match self {
+            Weather::Clear => "Clear",
+            Weather::Fog => "Fog",
+            Weather::Rain => "Rain",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Environment {
+    /// Seconds into the current day, starting at noon
+    pub time_of_day: f32,
+    pub weather: Weather,
+    /// Seconds until the weather changes
+    pub weather_remaining: f32,
+}
+
+impl Default for Environment {
+    fn default() -> Self {
+        Self {
+            time_of_day: 0.0,
+            weather: Weather::Clear,
+            weather_remaining: WEATHER_CLEAR_SECONDS.0,
+        }
+    }
+}
+
+impl Environment {
+    /// Run the clock. Returns true once the current weather has run out.
+    pub fn advance(&mut self, delta_time: f32) -> bool {
+        self.time_of_day = (self.time_of_day + delta_time).rem_euclid(DAY_LENGTH);
+        self.weather_remaining = (self.weather_remaining - delta_time).max(0.0);
+        self.weather_remaining <= 0.0
+    }
+
+    /// How bright the sun is: 1 at noon, 0 through the night
+    pub fn daylight(&self) -> f32 {
+        let phase = self.time_of_day / DAY_LENGTH;
+        (phase * std::f32::consts::TAU).cos().max(0.0)
+    }
+
+    /// Whole tiles of line of sight crew get right now, shortened by the
+    /// dark and by fog
+    pub fn vision_range(&self) -> f32 {
+        let night = NIGHT_VISION_SCALE + (1.0 - NIGHT_VISION_SCALE) * self.daylight();
+        let fog = if self.weather == Weather::Fog {
+            FOG_VISION_SCALE
+        } else {
+            1.0
+        };
+        (PLAYER_VISION_RANGE * night * fog).round().max(1.0)
+    }
+
+    /// Walking speed for crew outside, compared to a clear day
+    pub fn movement_scale(&self) -> f32 {
+        if self.weather == Weather::Rain {
+            RAIN_MOVE_SCALE
+        } else {
+            1.0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(time_of_day: f32, weather: Weather) -> Environment {
+        Environment {
+            time_of_day,
+            weather,
+            weather_remaining: 60.0,
+        }
+    }
+
+    #[test]
+    fn test_daylight_peaks_at_noon_and_is_dark_at_night() {
+        assert_eq!(at(0.0, Weather::Clear).daylight(), 1.0);
+        assert_eq!(at(DAY_LENGTH / 2.0, Weather::Clear).daylight(), 0.0);
+        assert!(at(DAY_LENGTH / 8.0, Weather::Clear).daylight() > 0.5);
+    }
+
+    #[test]
+    fn test_night_and_fog_shorten_sight() {
+        let noon = at(0.0, Weather::Clear).vision_range();
+        let midnight = at(DAY_LENGTH / 2.0, Weather::Clear).vision_range();
+        let foggy_night = at(DAY_LENGTH / 2.0, Weather::Fog).vision_range();
+        assert_eq!(noon, PLAYER_VISION_RANGE);
+        assert!(midnight < noon);
+        assert!(foggy_night < midnight);
+        assert!(foggy_night >= 1.0);
+    }
+
+    #[test]
+    fn test_clock_wraps_and_reports_when_the_weather_runs_out() {
+        let mut environment = at(DAY_LENGTH - 1.0, Weather::Rain);
+        assert!(!environment.advance(2.0));
+        assert!((environment.time_of_day - 1.0).abs() < 0.001);
+        assert!(environment.advance(60.0));
+        assert_eq!(environment.weather_remaining, 0.0);
+    }
+}
@@ -1,7 +1,57 @@
+use crate::messages::ServerMessage;
 use crate::types::{ResourceType, StationType, TeamId};
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use uuid::Uuid;
 
+/// What kind of failure an error is, sent along with it so clients and HTTP
+/// callers can react without parsing the message
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ErrorCode {
+    /// The request itself was malformed or out of range
+    InvalidInput,
+    /// Something it named doesn't exist (any more)
+    NotFound,
+    /// Not allowed for this player right now: wrong team, wrong password,
+    /// disabled by a status effect
+    NotAllowed,
+    /// Clashes with the current state: occupied, already carrying, full
+    Conflict,
+    /// Try again once the cooldown runs out
+    Cooldown,
+    /// The mech can't pay for it
+    InsufficientResources,
+    /// The client speaks a protocol this server doesn't
+    Incompatible,
+    /// The server isn't taking this now, e.g. while shutting down
+    Unavailable,
+    /// Something went wrong on the server's side
+    #[default]
+    Internal,
+}
+
+impl ErrorCode {
+    /// Errors a player can do something about, worth putting in front of them
+    pub fn is_actionable(self) -> bool {
+        !matches!(self, ErrorCode::NotFound | ErrorCode::Internal)
+    }
+
+    /// Short heading for showing the error to a player
+    pub fn title(self) -> &'static str {
+        match self {
+            ErrorCode::InvalidInput => "Invalid request",
+            ErrorCode::NotFound => "Not found",
+            ErrorCode::NotAllowed => "Not allowed",
+            ErrorCode::Conflict => "Can't do that now",
+            ErrorCode::Cooldown => "Cooling down",
+            ErrorCode::InsufficientResources => "Not enough resources",
+            ErrorCode::Incompatible => "Incompatible client",
+            ErrorCode::Unavailable => "Server unavailable",
+            ErrorCode::Internal => "Server error",
+        }
+    }
+}
+
 /// Main error type for the game
 #[derive(Error, Debug)]
 pub enum GameError {
@@ -18,9 +68,15 @@ pub enum GameError {
     #[error("Player {id} is not carrying any resource")]
     NotCarryingResource { id: Uuid },
 
+    #[error("Player {id} has nothing in inventory slot {slot}")]
+    EmptyInventorySlot { id: Uuid, slot: usize },
+
     #[error("Player {id} cannot perform action while operating station")]
     OperatingStation { id: Uuid },
 
+    #[error("A {item} can't be used")]
+    ItemNotUsable { item: &'static str },
+
     // Mech-related errors
     #[error("Mech {id} not found")]
     MechNotFound { id: Uuid },
@@ -41,6 +97,15 @@ pub enum GameError {
         required: u32,
     },
 
+    #[error("Cannot build a barricade at ({x}, {y}): the tile is not clear")]
+    TileNotClear { x: i32, y: i32 },
+
+    #[error("Mech {id} already has its {max} barricades")]
+    TooManyBarricades { id: Uuid, max: usize },
+
+    #[error("No barricade at ({x}, {y})")]
+    BarricadeNotFound { x: i32, y: i32 },
+
     // Station-related errors
     #[error("Station {id} not found")]
     StationNotFound { id: Uuid },
@@ -54,6 +119,12 @@ pub enum GameError {
     #[error("Player is not operating any station")]
     NotOperatingStation,
 
+    #[error("Entity {id} cannot operate stations while {effect:?}")]
+    BlockedByStatusEffect {
+        id: Uuid,
+        effect: crate::components::StatusEffectKind,
+    },
+
     // Resource-related errors
     #[error("Resource {id} not found")]
     ResourceNotFound { id: Uuid },
@@ -88,6 +159,12 @@ pub enum GameError {
     #[error("Weapon on cooldown: {remaining_seconds:.1} seconds remaining")]
     WeaponOnCooldown { remaining_seconds: f32 },
 
+    #[error("{ability} is recharging: {remaining_seconds:.1} seconds remaining")]
+    AbilityOnCooldown {
+        ability: &'static str,
+        remaining_seconds: f32,
+    },
+
     #[error("Invalid projectile {id}")]
     InvalidProjectile { id: Uuid },
 
@@ -98,6 +175,24 @@ pub enum GameError {
     #[error("Invalid team specified")]
     InvalidTeam,
 
+    #[error("Server is shutting down and not accepting new players")]
+    ServerShuttingDown,
+
+    #[error("Client protocol {client} is incompatible with server protocol {server}")]
+    IncompatibleProtocol { client: u32, server: u32 },
+
+    #[error("Wrong or missing room password")]
+    WrongPassword,
+
+    #[error("Room is full ({max} players)")]
+    RoomFull { max: usize },
+
+    #[error("No matchmaking ticket {id}")]
+    TicketNotFound { id: Uuid },
+
+    #[error("The matchmaking queue is full")]
+    QueueFull,
+
     // Validation errors
     #[error("Invalid input: {message}")]
     InvalidInput { message: String },
@@ -146,6 +241,12 @@ pub enum ValidationError {
     #[error("Button index {index} is out of range (max: {max})")]
     InvalidButtonIndex { index: u8, max: u8 },
 
+    #[error("Invalid inventory slot: {slot} (max: {max})")]
+    InvalidInventorySlot { slot: u8, max: u8 },
+
+    #[error("Turret aim angle {angle} is out of range")]
+    InvalidAimAngle { angle: f32 },
+
     #[error("Player name is invalid: {reason}")]
     InvalidPlayerName { reason: String },
 
@@ -154,12 +255,63 @@ pub enum ValidationError {
 
     #[error("Floor number is invalid (must be 0-2)")]
     InvalidFloorNumber,
+
+    #[error("Dev command is invalid: {reason}")]
+    InvalidDevCommand { reason: String },
+
+    #[error("Too many protocol features: {count} (max: {max})")]
+    TooManyProtocolFeatures { count: usize, max: usize },
+
+    #[error("Too many state channels: {count} (max: {max})")]
+    TooManyStateChannels { count: usize, max: usize },
+
+    #[error("Annotation is invalid: {reason}")]
+    InvalidAnnotation { reason: String },
+
+    #[error("Room password too long: {length} characters (max: {max})")]
+    PasswordTooLong { length: usize, max: usize },
+
+    #[error("Server listing is invalid: {reason}")]
+    InvalidServerListing { reason: String },
+}
+
+/// Anything that can go wrong handling a client's request, whether it was
+/// rejected up front, failed against the game or couldn't be encoded
+#[derive(Error, Debug)]
+pub enum ServerError {
+    #[error(transparent)]
+    Game(#[from] GameError),
+
+    #[error(transparent)]
+    Validation(#[from] ValidationError),
+
+    #[error(transparent)]
+    Network(#[from] NetworkError),
+}
+
+impl ServerError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            ServerError::Game(e) => e.code(),
+            ServerError::Validation(_) => ErrorCode::InvalidInput,
+            ServerError::Network(e) => e.code(),
+        }
+    }
+
+    /// The error as sent to the client that caused it
+    pub fn to_message(&self) -> ServerMessage {
+        ServerMessage::Error {
+            code: self.code(),
+            message: self.to_string(),
+        }
+    }
 }
 
 /// Result type aliases for convenience
 pub type GameResult<T> = Result<T, GameError>;
 pub type NetworkResult<T> = Result<T, NetworkError>;
 pub type ValidationResult<T> = Result<T, ValidationError>;
+pub type ServerResult<T> = Result<T, ServerError>;
 
 /// Extension trait for adding context to errors
 pub trait ErrorContext<T> {
@@ -199,4 +351,94 @@ impl GameError {
             message: message.into(),
         }
     }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            GameError::PlayerNotFound { .. }
+            | GameError::MechNotFound { .. }
+            | GameError::StationNotFound { .. }
+            | GameError::ResourceNotFound { .. }
+            | GameError::InvalidProjectile { .. }
+            | GameError::NoResourceAtLocation
+            | GameError::BarricadeNotFound { .. }
+            | GameError::TicketNotFound { .. }
+            | GameError::NoValidTarget => ErrorCode::NotFound,
+            GameError::WrongTeamMech { .. }
+            | GameError::BlockedByStatusEffect { .. }
+            | GameError::OperatingStation { .. }
+            | GameError::NotOperatingStation
+            | GameError::InvalidPlayerLocation { .. }
+            | GameError::InvalidStationOperation { .. }
+            | GameError::TeamsUnbalanced { .. }
+            | GameError::WrongPassword => ErrorCode::NotAllowed,
+            GameError::AlreadyCarryingResource { .. }
+            | GameError::NotCarryingResource { .. }
+            | GameError::EmptyInventorySlot { .. }
+            | GameError::ItemNotUsable { .. }
+            | GameError::MechFull { .. }
+            | GameError::InsufficientMechHealth { .. }
+            | GameError::TileNotClear { .. }
+            | GameError::TooManyBarricades { .. }
+            | GameError::StationOccupied { .. }
+            | GameError::CollisionDetected { .. }
+            | GameError::NoLadderAtPosition
+            | GameError::RoomFull { .. } => ErrorCode::Conflict,
+            GameError::WeaponOnCooldown { .. } | GameError::AbilityOnCooldown { .. } => {
+                ErrorCode::Cooldown
+            }
+            GameError::InsufficientResources { .. } => ErrorCode::InsufficientResources,
+            GameError::OutOfBounds { .. }
+            | GameError::InvalidFloor { .. }
+            | GameError::InvalidTeam
+            | GameError::InvalidInput { .. } => ErrorCode::InvalidInput,
+            GameError::IncompatibleProtocol { .. } => ErrorCode::Incompatible,
+            GameError::ServerShuttingDown | GameError::QueueFull => ErrorCode::Unavailable,
+            GameError::InvalidGameState { .. } => ErrorCode::Conflict,
+        }
+    }
+}
+
+impl NetworkError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            NetworkError::MessagePackDecodeError(_) | NetworkError::InvalidMessage(_) => {
+                ErrorCode::InvalidInput
+            }
+            NetworkError::Unauthorized { .. } => ErrorCode::NotAllowed,
+            NetworkError::JsonSerializationError(_)
+            | NetworkError::MessagePackEncodeError(_)
+            | NetworkError::ConnectionClosed
+            | NetworkError::ReceiveError(_)
+            | NetworkError::WebSocketError(_) => ErrorCode::Internal,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_server_errors_carry_their_code_to_the_client() {
+        let error = ServerError::from(GameError::WeaponOnCooldown {
+            remaining_seconds: 1.5,
+        });
+        let ServerMessage::Error { code, message } = error.to_message() else {
+            panic!("expected an error message");
+        };
+        assert_eq!(code, ErrorCode::Cooldown);
+        assert!(code.is_actionable());
+        assert_eq!(message, error.to_string());
+
+        // Older servers sent errors without a code
+        let old: ServerMessage =
+            serde_json::from_str(r#"{"type":"Error","message":"oops"}"#).unwrap();
+        assert!(matches!(
+            old,
+            ServerMessage::Error {
+                code: ErrorCode::Internal,
+                ..
+            }
+        ));
+    }
 }
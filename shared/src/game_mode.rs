@@ -0,0 +1,101 @@
+use crate::errors::{GameError, GameResult};
+use crate::types::{TeamId, WorldPos};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// The rules a match is won by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GameModeKind {
+    /// Last team with a mech standing wins
+    Deathmatch,
+    /// First team to deliver `RESOURCE_RACE_TARGET` resources wins
+    ResourceRace,
+    /// Red drives its mech into Blue's spawn; Blue stops it or runs out the clock
+    Escort,
+}
+
+impl GameModeKind {
+    pub const ALL: [GameModeKind; 3] = [
+        GameModeKind::Deathmatch,
+        GameModeKind::ResourceRace,
+        GameModeKind::Escort,
+    ];
+
+    /// Name used on the command line and in room specs
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameModeKind::Deathmatch => "deathmatch",
+            GameModeKind::ResourceRace => "race",
+            GameModeKind::Escort => "escort",
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            GameModeKind::Deathmatch => "Mech Deathmatch",
+            GameModeKind::ResourceRace => "Resource Race",
+            GameModeKind::Escort => "Payload Escort",
+        }
+    }
+}
+
+impl FromStr for GameModeKind {
+    type Err = GameError;
+
+    fn from_str(name: &str) -> GameResult<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.name() == name)
+            .ok_or_else(|| {
+                let names: Vec<&str> = Self::ALL.iter().map(|kind| kind.name()).collect();
+                GameError::invalid_input(format!(
+                    "unknown game mode '{name}', expected one of {}",
+                    names.join(", ")
+                ))
+            })
+    }
+}
+
+/// One team's progress toward winning, drawn as a bar
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TeamObjective {
+    pub team: TeamId,
+    pub label: String,
+    pub value: u32,
+    pub target: u32,
+}
+
+/// How a match ended
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MatchResult {
+    /// None for a draw
+    pub winner: Option<TeamId>,
+    pub reason: String,
+}
+
+/// Everything a client needs to show the match's objective
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameModeHud {
+    pub mode: GameModeKind,
+    /// One line telling the player what to do
+    pub objective: String,
+    pub teams: Vec<TeamObjective>,
+    pub time_left: Option<u32>, // whole seconds
+    /// A place the mode is about, marked in the world
+    pub goal: Option<WorldPos>,
+    pub result: Option<MatchResult>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mode_names_round_trip() {
+        for kind in GameModeKind::ALL {
+            assert_eq!(kind.name().parse::<GameModeKind>().unwrap(), kind);
+        }
+        let error = "ctf".parse::<GameModeKind>().unwrap_err().to_string();
+        assert!(error.contains("deathmatch, race, escort"), "{error}");
+    }
+}
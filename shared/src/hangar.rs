@@ -0,0 +1,44 @@
+use crate::types::TeamId;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Job a player wants once the match starts, picked in the hangar
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CrewRole {
+    /// Drives the mech
+    Pilot,
+    /// Works the turret
+    Gunner,
+    /// Keeps stations running and the hull patched
+    Engineer,
+    /// Brings resources in from outside
+    Runner,
+}
+
+impl CrewRole {
+    pub const ALL: [CrewRole; 4] = [
+        CrewRole::Pilot,
+        CrewRole::Gunner,
+        CrewRole::Engineer,
+        CrewRole::Runner,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CrewRole::Pilot => "Pilot",
+            CrewRole::Gunner => "Gunner",
+            CrewRole::Engineer => "Engineer",
+            CrewRole::Runner => "Runner",
+        }
+    }
+}
+
+/// One player's pick in the hangar
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HangarCrew {
+    pub player_id: Uuid,
+    pub name: String,
+    pub team: TeamId,
+    pub role: Option<CrewRole>,
+    pub ready: bool,
+}
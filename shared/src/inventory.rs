@@ -0,0 +1,207 @@
+use crate::balance::INVENTORY_SLOTS;
+use crate::types::ResourceType;
+use serde::{Deserialize, Serialize};
+
+/// Anything a crew member can carry in an inventory slot
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ItemType {
+    Resource(ResourceType),
+    Keycard,
+    Grenade,
+    /// Pings resources nearby through fog; issued at an Electrical station
+    Scanner,
+}
+
+impl ItemType {
+    pub fn as_resource(self) -> Option<ResourceType> {
+        match self {
+            ItemType::Resource(resource_type) => Some(resource_type),
+            _ => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ItemType::Resource(ResourceType::ScrapMetal) => "Scrap Metal",
+            ItemType::Resource(ResourceType::ComputerComponents) => "Components",
+            ItemType::Resource(ResourceType::Wiring) => "Wiring",
+            ItemType::Resource(ResourceType::Batteries) => "Batteries",
+            ItemType::Resource(ResourceType::PowerCore) => "Power Core",
+            ItemType::Keycard => "Keycard",
+            ItemType::Grenade => "Grenade",
+            ItemType::Scanner => "Scanner",
+        }
+    }
+
+    /// Higher goes first when several things are in reach at once
+    fn pickup_priority(self) -> u8 {
+        match self {
+            ItemType::Keycard | ItemType::Scanner => 3,
+            ItemType::Grenade => 2,
+            ItemType::Resource(_) => 1,
+        }
+    }
+}
+
+/// A handful of carrying slots, one of them active for drop and use
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Inventory {
+    slots: [Option<ItemType>; INVENTORY_SLOTS],
+    active: usize,
+}
+
+impl Inventory {
+    pub fn slots(&self) -> &[Option<ItemType>] {
+        &self.slots
+    }
+
+    pub fn active_slot(&self) -> usize {
+        self.active
+    }
+
+    pub fn active_item(&self) -> Option<ItemType> {
+        self.slots[self.active]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.iter().all(Option::is_none)
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.slots.iter().all(Option::is_some)
+    }
+
+    pub fn contains(&self, item: ItemType) -> bool {
+        self.slots.contains(&Some(item))
+    }
+
+    pub fn has_resource(&self) -> bool {
+        self.resources().next().is_some()
+    }
+
+    pub fn resources(&self) -> impl Iterator<Item = ResourceType> + '_ {
+        self.slots
+            .iter()
+            .flatten()
+            .filter_map(|item| item.as_resource())
+    }
+
+    /// Put an item in the active slot if it's free, otherwise the first free
+    /// one. Returns the slot used, or None when every slot is taken.
+    pub fn add(&mut self, item: ItemType) -> Option<usize> {
+        let slot = if self.slots[self.active].is_none() {
+            self.active
+        } else {
+            self.slots.iter().position(Option::is_none)?
+        };
+        self.slots[slot] = Some(item);
+        Some(slot)
+    }
+
+    pub fn take_active(&mut self) -> Option<ItemType> {
+        self.slots[self.active].take()
+    }
+
+    /// Empty the first slot holding this item
+    pub fn remove(&mut self, item: ItemType) -> bool {
+        match self.slots.iter().position(|slot| *slot == Some(item)) {
+            Some(slot) => {
+                self.slots[slot] = None;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Empty every slot holding a resource, for delivery to a mech
+    pub fn take_resources(&mut self) -> Vec<ResourceType> {
+        let mut taken = Vec::new();
+        for slot in &mut self.slots {
+            if let Some(resource_type) = slot.and_then(ItemType::as_resource) {
+                taken.push(resource_type);
+                *slot = None;
+            }
+        }
+        taken
+    }
+
+    pub fn clear(&mut self) {
+        self.slots = Default::default();
+    }
+
+    pub fn select(&mut self, slot: usize) -> bool {
+        if slot < INVENTORY_SLOTS {
+            self.active = slot;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Which of the items in reach to pick up, given nearest first: the
+    /// highest priority wins, then one we aren't already carrying, then
+    /// the nearest. Nothing is picked up into a full inventory.
+    pub fn choose_pickup<T>(
+        &self,
+        in_reach: impl IntoIterator<Item = (T, ItemType)>,
+    ) -> Option<(T, ItemType)> {
+        if self.is_full() {
+            return None;
+        }
+        let score = |item: ItemType| item.pickup_priority() * 2 + u8::from(!self.contains(item));
+        in_reach
+            .into_iter()
+            .fold(None, |best, candidate| match best {
+                Some((_, best_item)) if score(best_item) >= score(candidate.1) => best,
+                _ => Some(candidate),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCRAP: ItemType = ItemType::Resource(ResourceType::ScrapMetal);
+    const WIRING: ItemType = ItemType::Resource(ResourceType::Wiring);
+
+    #[test]
+    fn test_add_fills_active_slot_first() {
+        let mut inventory = Inventory::default();
+        inventory.select(1);
+        assert_eq!(inventory.add(SCRAP), Some(1));
+        assert_eq!(inventory.add(WIRING), Some(0));
+        assert_eq!(inventory.add(ItemType::Keycard), Some(2));
+        assert!(inventory.is_full());
+        assert_eq!(inventory.add(ItemType::Grenade), None);
+
+        assert_eq!(inventory.take_active(), Some(SCRAP));
+        assert_eq!(inventory.take_resources(), vec![ResourceType::Wiring]);
+        assert_eq!(inventory.slots(), &[None, None, Some(ItemType::Keycard)]);
+    }
+
+    #[test]
+    fn test_pickup_priority() {
+        let mut inventory = Inventory::default();
+        inventory.add(SCRAP);
+
+        // Items beat resources, new resources beat ones we already carry,
+        // and the nearest breaks ties
+        assert_eq!(
+            inventory.choose_pickup([(0, SCRAP), (1, WIRING), (2, ItemType::Grenade)]),
+            Some((2, ItemType::Grenade))
+        );
+        assert_eq!(
+            inventory.choose_pickup([(0, SCRAP), (1, WIRING)]),
+            Some((1, WIRING))
+        );
+        assert_eq!(
+            inventory.choose_pickup([(0, WIRING), (1, WIRING)]),
+            Some((0, WIRING))
+        );
+
+        inventory.add(WIRING);
+        inventory.add(WIRING);
+        assert_eq!(inventory.choose_pickup([(0, ItemType::Keycard)]), None);
+    }
+}
@@ -1,47 +1,99 @@
+pub mod achievements;
+pub mod annotations;
+pub mod arena_map;
+pub mod armor;
 pub mod balance;
+pub mod bandwidth;
 pub mod collision;
 pub mod components;
 pub mod constants;
 pub mod coordinates;
+pub mod decals;
+pub mod drive;
+pub mod environment;
 pub mod errors;
+pub mod game_mode;
+pub mod hangar;
+pub mod inventory;
+pub mod matchmaking;
 pub mod mech_coordinates;
 pub mod mech_layout;
 pub mod messages;
+pub mod net_conditioner;
 pub mod network;
 pub mod network_constants;
+pub mod pacing;
+pub mod point_defense;
+pub mod protocol;
 pub mod render_constants;
+pub mod replay;
+pub mod server_list;
 pub mod spatial;
+pub mod spawn_tables;
+pub mod state_hash;
 pub mod stations;
+pub mod subscription;
 pub mod tile_entity;
 pub mod tile_math;
+pub mod tile_script;
+pub mod turret;
 pub mod types;
 pub mod uuid_gen;
 pub mod validation;
 pub mod vision;
+pub mod voice;
 
 // Object pool is only needed server-side (uses Uuid::new_v4)
 #[cfg(not(target_arch = "wasm32"))]
 pub mod object_pool;
 
+#[cfg(test)]
+mod protocol_conformance;
+
+pub use achievements::*;
+pub use annotations::*;
+pub use arena_map::*;
+pub use armor::*;
 pub use balance::*;
+pub use bandwidth::*;
 pub use collision::*;
 pub use constants::*;
 pub use coordinates::*;
+pub use decals::*;
+pub use drive::*;
+pub use environment::*;
 pub use errors::*;
+pub use game_mode::*;
+pub use hangar::*;
+pub use inventory::*;
+pub use matchmaking::*;
 pub use mech_coordinates::*;
 pub use mech_layout::*;
 pub use messages::*;
+pub use net_conditioner::*;
 pub use network::*;
 pub use network_constants::*;
+pub use pacing::*;
+pub use point_defense::*;
+pub use protocol::*;
 pub use render_constants::*;
+pub use replay::*;
+pub use server_list::*;
 pub use spatial::*;
+pub use spawn_tables::*;
+pub use state_hash::*;
+pub use subscription::*;
 pub use tile_math::*;
+pub use tile_script::*;
+pub use turret::*;
 pub use types::*;
 pub use validation::*;
+pub use voice::*;
 // Export stations module types selectively to avoid conflicts
 pub use stations::{
-    ButtonDefinition, MechUpgradeType, StationAction, StationActionContext, StationActionResult,
-    StationDefinition, StationEffect, StationInstance, StationRegistry, WeaponType,
+    with_top_tier_cost, ButtonDefinition, MechUpgradeType, StationAction, StationActionContext,
+    StationActionResult, StationDefinition, StationEffect, StationInstance, StationRegistry,
+    UpgradeTarget, WeaponType,
 };
 // Station component is exported from components module
 
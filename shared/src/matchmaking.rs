@@ -0,0 +1,101 @@
+//! Pick-up games without arranging them. A player POSTs a `QueueRequest`
+//! to `MATCHMAKING_PATH` and gets a `QueueTicket` back, then polls
+//! `MATCHMAKING_PATH/<ticket>` for their `QueueStatus`. Once `MATCH_SIZE`
+//! players are waiting the server opens a room for them, splits them into
+//! teams of about equal rating, and each ticket's status names the room to
+//! join and the team to ask for. DELETE on the ticket leaves the queue.
+
+use crate::types::TeamId;
+use crate::validation::validate_player_name;
+use crate::{Validate, ValidationResult};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Path of the queue on a server
+pub const MATCHMAKING_PATH: &str = "/matchmaking";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueueRequest {
+    /// Records, and so ratings, are kept by name
+    pub player_name: String,
+}
+
+impl Validate for QueueRequest {
+    fn validate(&self) -> ValidationResult<()> {
+        validate_player_name(&self.player_name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct QueueTicket {
+    pub ticket: Uuid,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status")]
+pub enum QueueStatus {
+    Waiting {
+        /// 1 for the next player to be matched
+        position: usize,
+        queued: usize,
+    },
+    Matched {
+        room: String,
+        team: TeamId,
+    },
+}
+
+/// The queue of the server behind a game websocket URL, if it's one we can
+/// reach over plain HTTP
+pub fn matchmaking_url(ws_url: &str) -> Option<String> {
+    let rest = ws_url.strip_prefix("ws://")?;
+    let authority = rest.split(['/', '?']).next().filter(|a| !a.is_empty())?;
+    Some(format!("http://{authority}{MATCHMAKING_PATH}"))
+}
+
+/// `ws_url` pointed at another room on the same server
+pub fn room_ws_url(ws_url: &str, room: &str) -> String {
+    let (base, query) = ws_url.split_once('?').unwrap_or((ws_url, ""));
+    let mut params: Vec<&str> = query
+        .split('&')
+        .filter(|param| !param.is_empty() && !param.starts_with("room="))
+        .collect();
+    let room = format!("room={room}");
+    params.push(&room);
+    format!("{base}?{}", params.join("&"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::COMPRESSION_QUERY;
+
+    #[test]
+    fn test_queue_and_room_urls_come_from_the_game_url() {
+        let url = format!("ws://10.0.0.5:14191/ws?{COMPRESSION_QUERY}&room=default");
+        assert_eq!(
+            matchmaking_url(&url).as_deref(),
+            Some("http://10.0.0.5:14191/matchmaking")
+        );
+        assert_eq!(matchmaking_url("wss://example.com/ws"), None);
+        assert_eq!(
+            room_ws_url(&url, "match-3"),
+            format!("ws://10.0.0.5:14191/ws?{COMPRESSION_QUERY}&room=match-3")
+        );
+        assert_eq!(
+            room_ws_url("ws://localhost:14191/ws", "match-3"),
+            "ws://localhost:14191/ws?room=match-3"
+        );
+
+        assert!(QueueRequest {
+            player_name: "Pilot 7".to_string()
+        }
+        .validate()
+        .is_ok());
+        assert!(QueueRequest {
+            player_name: String::new()
+        }
+        .validate()
+        .is_err());
+    }
+}
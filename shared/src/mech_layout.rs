@@ -1,6 +1,6 @@
 use crate::tile_entity::{StaticTile, FloorMap, TransitionType};
 use crate::{
-    uuid_gen::new_uuid, StationType, TilePos, FLOOR_HEIGHT_TILES, FLOOR_WIDTH_TILES,
+    uuid_gen::new_uuid, Direction, StationType, TilePos, ToolType, FLOOR_HEIGHT_TILES, FLOOR_WIDTH_TILES,
 };
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -64,6 +64,12 @@ impl MechLayoutGenerator {
 
             // Add stations based on floor
             Self::add_stations_to_floor(&mut floors[floor_idx], stations, floor_idx as u8);
+
+            // Add damage control lockers
+            Self::add_tool_lockers_to_floor(&mut floors[floor_idx], floor_idx as u8);
+
+            // Cut windows into the hull
+            Self::add_windows_to_floor(&mut floors[floor_idx], floor_idx as u8);
         }
 
         MechInterior { 
@@ -213,6 +219,37 @@ impl MechLayoutGenerator {
         }
     }
 
+    /// Add extinguisher and repair tool lockers against the walls
+    fn add_tool_lockers_to_floor(floor: &mut FloorMap, floor_idx: u8) {
+        let lockers = match floor_idx {
+            0 => vec![
+                (TilePos::new(1, 7), ToolType::Extinguisher),
+                (TilePos::new(1, 8), ToolType::RepairTool),
+            ],
+            2 => vec![
+                (TilePos::new(8, 1), ToolType::Extinguisher),
+                (TilePos::new(1, 8), ToolType::RepairTool),
+            ],
+            _ => vec![],
+        };
+
+        for (pos, tool) in lockers {
+            floor.static_tiles.insert(pos, StaticTile::ToolLocker { tool });
+        }
+    }
+
+    /// Add windows to the outer wall, like the one the turret operator looks out of
+    fn add_windows_to_floor(floor: &mut FloorMap, floor_idx: u8) {
+        let windows = match floor_idx {
+            2 => vec![(TilePos::new(FLOOR_WIDTH_TILES - 1, 4), Direction::Right)],
+            _ => vec![],
+        };
+
+        for (pos, facing) in windows {
+            floor.static_tiles.insert(pos, StaticTile::Window { facing });
+        }
+    }
+
     /// Get the list of stations for a specific floor with multi-tile sizes
     fn get_stations_for_floor(floor_idx: u8) -> Vec<(TilePos, StationType, StationSize)> {
         match floor_idx {
@@ -225,13 +262,17 @@ impl MechLayoutGenerator {
                 (TilePos::new(4, 2), StationType::Pilot, StationSize::WIDE),
                 (TilePos::new(8, 4), StationType::Shield, StationSize::SINGLE),
                 (TilePos::new(2, 6), StationType::Electrical, StationSize::SINGLE),
+                (TilePos::new(6, 6), StationType::Radar, StationSize::SINGLE),
+                (TilePos::new(2, 4), StationType::PointDefense, StationSize::SINGLE),
             ],
             2 => vec![
                 // Floor 2 (Weapons/Operations)
                 (TilePos::new(2, 2), StationType::WeaponLaser, StationSize::SINGLE),
                 (TilePos::new(6, 2), StationType::WeaponProjectile, StationSize::SINGLE),
+                (TilePos::new(4, 2), StationType::WeaponEmp, StationSize::SINGLE),
                 (TilePos::new(4, 6), StationType::Repair, StationSize::WIDE),
                 (TilePos::new(8, 8), StationType::Upgrade, StationSize::SINGLE),
+                (TilePos::new(8, 4), StationType::TurretControl, StationSize::SINGLE),
             ],
             _ => vec![],
         }
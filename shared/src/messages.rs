@@ -1,6 +1,29 @@
-use crate::tile_entity::TileVisual;
+use crate::achievements::Achievement;
+use crate::annotations::{Annotation, AnnotationAudience, AnnotationColor, AnnotationShape};
+use crate::arena_map::{ArenaMap, MapHazard};
+use crate::armor::HitDirection;
+use crate::components::{SabotageAlarm, TeamStructure};
+use crate::decals::{Decal, DecalKind};
+use crate::drive::{MechDrive, MechMass};
+use crate::environment::Environment;
+use crate::errors::ErrorCode;
+use crate::game_mode::GameModeHud;
+use crate::hangar::{CrewRole, HangarCrew};
+use crate::inventory::{Inventory, ItemType};
+use crate::tile_entity::{Barricade, TileVisual};
+use crate::turret::MechTurret;
 use crate::mech_layout::{MechInterior, MechStation};
+use crate::network_constants::{
+    LOAD_OVERLOADED_AI_SLOWDOWN, LOAD_OVERLOADED_BROADCAST_STRIDE, LOAD_STRAINED_AI_SLOWDOWN,
+};
+use crate::pacing::{DirectorFocus, PacingZone};
+use crate::point_defense::PointDefense;
+use crate::protocol::ProtocolFeature;
+use crate::state_hash::StateHashes;
+use crate::stations::WeaponType;
+use crate::subscription::StateChannel;
 use crate::types::*;
+use crate::vision::SightShape;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -11,6 +34,14 @@ pub enum ClientMessage {
     JoinGame {
         player_name: String,
         preferred_team: Option<TeamId>,
+        /// Clients from before versioning leave this out, which reads as 0
+        #[serde(default)]
+        protocol_version: u32,
+        #[serde(default)]
+        features: Vec<ProtocolFeature>,
+        /// Needed for password-protected rooms
+        #[serde(default)]
+        password: Option<String>,
     },
     PlayerInput {
         movement: (f32, f32), // normalized x, y velocity
@@ -20,7 +51,7 @@ pub enum ClientMessage {
         button_index: u8,
     },
     EngineControl {
-        movement: (f32, f32), // normalized x, y velocity for mech movement
+        movement: (f32, f32), // x turns, y moves the throttle (negative is forward)
     },
     ExitMech,
     ExitStation,
@@ -32,6 +63,84 @@ pub enum ClientMessage {
     ChatMessage {
         message: String,
     },
+    /// Draw a coaching mark on the map for `audience` to see for `ttl` seconds
+    Annotate {
+        shape: AnnotationShape,
+        color: AnnotationColor,
+        audience: AnnotationAudience,
+        ttl: f32,
+    },
+    DevCommand {
+        command: DevCommand,
+    },
+    SelectInventorySlot {
+        slot: u8,
+    },
+    /// Drop the active item, or swap it for one on the ground when full
+    DropItem,
+    /// Point the turret we're crewing; radians, 0 faces +x
+    TurretAim {
+        angle: f32,
+    },
+    /// Swing at the closest enemy within reach
+    MeleeAttack,
+    /// Use the active item; only the scanner does anything so far
+    UseItem,
+    /// Burst a few tiles the given way, on a cooldown
+    Dash {
+        direction: Facing,
+    },
+    /// Build a barricade out of our mech's scrap on a floor tile within reach
+    PlaceBarricade {
+        position: TilePos,
+    },
+    /// Take down one of our barricades within reach, getting back the scrap
+    /// it has left
+    RemoveBarricade {
+        position: TilePos,
+    },
+    /// Turn to face where the mouse points, without moving
+    Look {
+        facing: Facing,
+    },
+    /// Watch the match without a player, following the camera director
+    Spectate {
+        #[serde(default)]
+        protocol_version: u32,
+        #[serde(default)]
+        features: Vec<ProtocolFeature>,
+    },
+    /// Start or stop getting the server's collision shapes alongside the
+    /// state, for the debug overlay
+    VerboseState {
+        enabled: bool,
+    },
+    /// Pick the job we want in the hangar, or clear our pick
+    PickRole {
+        role: Option<CrewRole>,
+    },
+    /// Say we're ready for the match to start, or take it back
+    SetReady {
+        ready: bool,
+    },
+    /// Only send us these channels of updates from now on; a client that
+    /// never asks gets them all
+    Subscribe {
+        channels: Vec<StateChannel>,
+    },
+}
+
+/// Admin commands from the client's developer console, only honoured when
+/// the server runs with `--dev-mode`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum DevCommand {
+    /// Move our player out onto a world tile
+    Teleport { x: i32, y: i32 },
+    /// Add resources to our team's mech
+    GiveResource {
+        resource_type: ResourceType,
+        amount: u32,
+    },
 }
 
 // Server -> Client Messages
@@ -43,10 +152,52 @@ pub enum ServerMessage {
         player_id: PlayerId,
         team: TeamId,
         spawn_position: TilePos,
+        /// Features both sides support; frames after this one use them
+        #[serde(default)]
+        features: Vec<ProtocolFeature>,
+        /// How far crew outside see around them in this room
+        #[serde(default)]
+        sight: SightShape,
+    },
+    /// The client's protocol version can't talk to this server
+    JoinRejected {
+        reason: String,
+        server_version: u32,
+    },
+    /// The room is locked and the join didn't carry its password; the client
+    /// may send `JoinGame` again with one
+    PasswordRequired {
+        /// A password was given but it didn't match
+        wrong: bool,
+    },
+    /// Key for opening `/voice` after a join that agreed on `Voice`; good for
+    /// one connection
+    VoiceChannel {
+        key: uuid::Uuid,
+    },
+    /// Token that gets this player back if the connection drops: reconnect
+    /// with `resume=<token>` in the websocket URL and join again
+    SessionResume {
+        token: uuid::Uuid,
     },
     PlayerDisconnected {
         player_id: PlayerId,
     },
+    /// A player stopped sending input long enough to count as away, or came back
+    PlayerAfk {
+        player_id: PlayerId,
+        afk: bool,
+    },
+    /// A spectator was let in; like `JoinedGame`, frames after this use `features`
+    SpectatingGame {
+        #[serde(default)]
+        features: Vec<ProtocolFeature>,
+    },
+    /// Where the camera director thinks spectators should be looking
+    CameraSuggestion {
+        target: MechId,
+        reason: DirectorFocus,
+    },
 
     // Game State Updates
     GameState {
@@ -54,6 +205,13 @@ pub enum ServerMessage {
         mechs: HashMap<MechId, MechState>,
         resources: Vec<ResourceState>,
         projectiles: Vec<ProjectileState>,
+        pacing_zones: Vec<PacingZone>,
+        ground_items: Vec<GroundItemState>,
+        structures: Vec<TeamStructure>,
+        #[serde(default)]
+        game_mode: Option<GameModeHud>,
+        #[serde(default)]
+        environment: Environment,
     },
 
     // Mech Floor Data - Complete floor layouts for clients
@@ -89,6 +247,12 @@ pub enum ServerMessage {
         player_id: PlayerId,
         location: PlayerLocation,
     },
+    /// Only sent when a player turns, so standing still or walking
+    /// straight costs nothing
+    PlayerFacing {
+        player_id: PlayerId,
+        facing: Facing,
+    },
     PlayerPickedUpResource {
         player_id: PlayerId,
         resource_type: ResourceType,
@@ -99,6 +263,20 @@ pub enum ServerMessage {
         resource_type: ResourceType,
         position: TilePos,
     },
+    InventoryUpdated {
+        player_id: PlayerId,
+        inventory: Inventory,
+    },
+    ItemDropped {
+        player_id: PlayerId,
+        item_id: ItemId,
+        item: ItemType,
+        position: WorldPos,
+    },
+    ItemPickedUp {
+        player_id: PlayerId,
+        item_id: ItemId,
+    },
     PlayerEnteredStation {
         player_id: PlayerId,
         station_id: StationId,
@@ -107,22 +285,71 @@ pub enum ServerMessage {
         player_id: PlayerId,
         station_id: StationId,
     },
+    // Sent only to the operator so the UI can show that station's buttons
+    StationLayout {
+        station_id: StationId,
+        name: String,
+        description: String,
+        buttons: Vec<StationButtonInfo>,
+    },
 
     // Mech Updates
     MechMoved {
         mech_id: MechId,
         position: TilePos,
         world_position: WorldPos,
+        drive: MechDrive,
     },
     MechDamaged {
         mech_id: MechId,
         damage: u32,
         health_remaining: u32,
+        /// Where the damage landed, for hit feedback
+        position: WorldPos,
+        /// Which way a weapon hit came from; none for hazards and the like
+        #[serde(default)]
+        hit: Option<HitDirection>,
+    },
+    /// A hit hard enough to throw around everyone inside the mech
+    InteriorImpact {
+        mech_id: MechId,
+        /// 0 for the lightest hit that counts, 1 for the most violent
+        severity: f32,
+        hit: Option<HitDirection>,
+    },
+    /// Two mechs crashed into each other hard enough to hurt; the damage
+    /// each took follows as MechDamaged
+    MechsCollided {
+        mech_ids: (MechId, MechId),
+        /// Where the hulls met
+        position: WorldPos,
+        /// Tiles per second they came together at
+        closing_speed: f32,
+    },
+    /// A mech ran a player down; the kill follows as PlayerKilled
+    PlayerRunOver {
+        player_id: PlayerId,
+        mech_id: MechId,
+        position: WorldPos,
     },
     MechShieldChanged {
         mech_id: MechId,
         shield: u32,
     },
+    /// A mech's tank, sent as it burns down a whole unit or is refilled
+    MechFuelChanged {
+        mech_id: MechId,
+        fuel: f32,
+    },
+    /// Something struck a mech's shield bubble at `position` on its surface
+    ShieldImpact {
+        mech_id: MechId,
+        position: WorldPos,
+        absorbed: u32,
+        shield_remaining: u32,
+        /// The hit took the last of the shield and the bubble collapsed
+        collapsed: bool,
+    },
     MechUpgraded {
         mech_id: MechId,
         upgrade_type: UpgradeType,
@@ -133,6 +360,35 @@ pub enum ServerMessage {
         health_restored: u32,
         new_health: u32,
     },
+    /// An operator turned the turret, or took over from or handed back to
+    /// the auto tracker
+    TurretAimed {
+        mech_id: MechId,
+        turret: MechTurret,
+    },
+    /// A point-defense battery fired, was restocked or switched mode
+    PointDefenseChanged {
+        mech_id: MechId,
+        point_defense: PointDefense,
+    },
+    /// A radar sweep found these enemy mechs. The sweeping team shows the
+    /// blips for `duration`; the swept team gets a warning.
+    RadarSweep {
+        mech_id: MechId,
+        team: TeamId,
+        blips: Vec<WorldPos>, // mech centers, offset when jammed by ECM
+        duration: f32,
+    },
+    /// What a player's scanner picked up, for them alone: resources close
+    /// enough to place and the bearings of ones further out. Shown for
+    /// `duration` whether or not they're in sight.
+    ScannerPing {
+        player_id: PlayerId,
+        origin: WorldPos,
+        contacts: Vec<(WorldPos, ResourceType)>,
+        bearings: Vec<f32>, // radians from the origin, 0 faces +x
+        duration: f32,
+    },
 
     // Combat
     WeaponFired {
@@ -141,14 +397,33 @@ pub enum ServerMessage {
         target_position: TilePos,
         projectile_id: Option<ProjectileId>,
     },
+    /// A pilot started a stomp; it lands after `windup` seconds, giving
+    /// anyone nearby a moment to get clear
+    MechStompWindup {
+        mech_id: MechId,
+        windup: f32,
+    },
+    /// A stomp landed, sending a shockwave out to `radius` world units
+    MechStomped {
+        mech_id: MechId,
+        position: WorldPos,
+        radius: f32,
+    },
     ProjectileHit {
         projectile_id: ProjectileId,
         hit_mech_id: Option<MechId>,
         damage_dealt: u32,
+        position: WorldPos,
     },
     ProjectileExpired {
         projectile_id: ProjectileId,
     },
+    /// A point-defense interceptor caught a shot; both are gone
+    ProjectileIntercepted {
+        projectile_id: ProjectileId,
+        interceptor_id: ProjectileId,
+        position: WorldPos,
+    },
     EffectCreated {
         effect_id: WeaponEffectId,
         effect_type: String,
@@ -169,6 +444,19 @@ pub enum ServerMessage {
         resource_id: ResourceId,
         player_id: PlayerId,
     },
+    ResourceDestroyed {
+        resource_id: ResourceId,
+    },
+    ResourceDelivered {
+        player_id: PlayerId,
+        mech_id: MechId,
+        resource_type: ResourceType,
+        delivery_score: u32,
+    },
+    MechInventoryChanged {
+        mech_id: MechId,
+        inventory: HashMap<ResourceType, u32>,
+    },
 
     // Chat
     ChatMessage {
@@ -177,6 +465,10 @@ pub enum ServerMessage {
         message: String,
         team_only: bool,
     },
+    /// Someone drew a coaching mark we're meant to see
+    AnnotationDrawn {
+        annotation: Annotation,
+    },
 
     // Player death
     PlayerKilled {
@@ -197,9 +489,168 @@ pub enum ServerMessage {
         visible_tiles: Vec<(TilePos, TileVisual)>,
         player_position: WorldPos,
     },
+    // Tiles seen by the player's team (crew line of sight and mech sensors)
+    TeamVisionUpdate {
+        visible_tiles: Vec<TilePos>,
+    },
+    // What the server tests collisions against, for clients that asked
+    CollisionShapes {
+        shapes: crate::collision::CollisionShapes,
+    },
+
+    // Status effects (full set for the entity, empty when all expired)
+    StatusEffectsChanged {
+        entity_id: EntityId,
+        effects: Vec<crate::components::StatusEffect>,
+    },
+
+    // Interior fires and hull repairs
+    MechFiresUpdated {
+        mech_id: MechId,
+        fires: Vec<FireState>, // full set for the mech, empty when all are out
+    },
+    HullBreachUpdated {
+        mech_id: MechId,
+        floor: u8,
+        position: TilePos,
+        breached: bool,
+        max_health: u32,
+    },
+    /// A barricade went up, took a hit or came down; None once it's gone
+    BarricadeUpdated {
+        mech_id: MechId,
+        floor: u8,
+        position: TilePos,
+        barricade: Option<Barricade>,
+    },
+    PlayerToolChanged {
+        player_id: PlayerId,
+        tool: Option<ToolType>,
+    },
+    RepairProgress {
+        player_id: PlayerId,
+        mech_id: MechId,
+        progress: Option<f32>, // None when the channel completes or is interrupted
+    },
+    /// Boarders are at one of a mech's stations, or None once it's quiet
+    MechAlarm {
+        mech_id: MechId,
+        alarm: Option<SabotageAlarm>,
+    },
+
+    // Boarding combat
+    PlayerAttacked {
+        attacker: PlayerId,
+        target: PlayerId,
+        damage: u32,
+        health: u32, // target's health after the hit
+    },
+    PlayerDashed {
+        player_id: PlayerId,
+        from: PlayerLocation,
+        to: PlayerLocation,
+    },
+    SabotageProgress {
+        player_id: PlayerId,
+        station_id: StationId,
+        progress: Option<f32>, // None when the channel completes or is interrupted
+    },
+
+    // Achievements
+    AchievementUnlocked {
+        player_id: PlayerId,
+        achievement: Achievement,
+    },
+
+    // Tutorials
+    TutorialInstruction {
+        player_id: PlayerId,
+        text: String,
+        step: u32,  // 1-based, out of `total`
+        total: u32,
+    },
+    TutorialComplete {
+        player_id: PlayerId,
+    },
+
+    // Arena layout, sent on join
+    ArenaMapData {
+        map: ArenaMap,
+    },
+    HazardsUpdated {
+        hazards: Vec<MapHazard>, // full set, sent when a mine detonates
+    },
+    // Every decal on the ground, oldest first, sent on join
+    DecalLayer {
+        decals: Vec<Decal>,
+    },
+    DecalAdded {
+        position: TilePos,
+        kind: DecalKind,
+    },
+
+    // Everyone's picks before the match, sent when they change and to
+    // players joining while the hangar is open
+    HangarUpdated {
+        crew: Vec<HangarCrew>,
+        /// Seconds until the match starts whether everyone's ready or not
+        time_left: f32,
+    },
+    // The hangar closed and the match is on
+    MatchStarted,
+
+    // Game mode objective, sent when anything on it changes
+    GameModeUpdated {
+        hud: GameModeHud,
+    },
+
+    // Match director pacing events
+    PacingEventStarted {
+        announcement: String,
+        zone: PacingZone,
+    },
+    PacingZonesUpdated {
+        zones: Vec<PacingZone>, // full set, sent when zones expire
+    },
+    /// The weather turned; the clock also rides along with every full state
+    WeatherChanged {
+        environment: Environment,
+    },
+
+    /// The channels this connection gets from now on, answering Subscribe
+    Subscribed {
+        channels: Vec<StateChannel>,
+    },
+
+    // The server can't keep up and is shedding work; sent when that changes
+    // and with full states while it lasts
+    ServerLoad {
+        level: ServerLoad,
+    },
+
+    // The server is going down in `seconds`; no new players are let in
+    ServerShutdown {
+        reason: String,
+        seconds: u32,
+    },
+
+    // Hashes of the full state sent just before, for clients to check their
+    // mirror against; only from servers run with state checksums on
+    StateChecksum {
+        tick: u64,
+        hashes: StateHashes,
+    },
+
+    // Reply to a console DevCommand, sent only to the player who ran it
+    DevCommandResult {
+        success: bool,
+        message: String,
+    },
 
     // Errors
     Error {
+        #[serde(default)]
+        code: ErrorCode,
         message: String,
     },
 }
@@ -208,34 +659,92 @@ impl ServerMessage {
     pub fn type_name(&self) -> &'static str {
         match self {
             ServerMessage::JoinedGame { .. } => "JoinedGame",
+            ServerMessage::JoinRejected { .. } => "JoinRejected",
+            ServerMessage::PasswordRequired { .. } => "PasswordRequired",
+            ServerMessage::VoiceChannel { .. } => "VoiceChannel",
+            ServerMessage::SessionResume { .. } => "SessionResume",
             ServerMessage::PlayerDisconnected { .. } => "PlayerDisconnected",
+            ServerMessage::PlayerAfk { .. } => "PlayerAfk",
+            ServerMessage::SpectatingGame { .. } => "SpectatingGame",
+            ServerMessage::CameraSuggestion { .. } => "CameraSuggestion",
             ServerMessage::GameState { .. } => "GameState",
             ServerMessage::MechFloorData { .. } => "MechFloorData",
             ServerMessage::FloorTransitionComplete { .. } => "FloorTransitionComplete",
             ServerMessage::FloorTransitionFailed { .. } => "FloorTransitionFailed",
             ServerMessage::MechInteriorUpdate { .. } => "MechInteriorUpdate",
             ServerMessage::PlayerMoved { .. } => "PlayerMoved",
+            ServerMessage::PlayerFacing { .. } => "PlayerFacing",
             ServerMessage::PlayerPickedUpResource { .. } => "PlayerPickedUpResource",
             ServerMessage::PlayerDroppedResource { .. } => "PlayerDroppedResource",
+            ServerMessage::InventoryUpdated { .. } => "InventoryUpdated",
+            ServerMessage::ItemDropped { .. } => "ItemDropped",
+            ServerMessage::ItemPickedUp { .. } => "ItemPickedUp",
             ServerMessage::PlayerEnteredStation { .. } => "PlayerEnteredStation",
             ServerMessage::PlayerExitedStation { .. } => "PlayerExitedStation",
+            ServerMessage::StationLayout { .. } => "StationLayout",
             ServerMessage::MechMoved { .. } => "MechMoved",
             ServerMessage::MechDamaged { .. } => "MechDamaged",
+            ServerMessage::InteriorImpact { .. } => "InteriorImpact",
+            ServerMessage::MechsCollided { .. } => "MechsCollided",
+            ServerMessage::PlayerRunOver { .. } => "PlayerRunOver",
             ServerMessage::MechShieldChanged { .. } => "MechShieldChanged",
+            ServerMessage::MechFuelChanged { .. } => "MechFuelChanged",
+            ServerMessage::ShieldImpact { .. } => "ShieldImpact",
             ServerMessage::MechUpgraded { .. } => "MechUpgraded",
             ServerMessage::MechRepaired { .. } => "MechRepaired",
+            ServerMessage::TurretAimed { .. } => "TurretAimed",
+            ServerMessage::PointDefenseChanged { .. } => "PointDefenseChanged",
+            ServerMessage::RadarSweep { .. } => "RadarSweep",
+            ServerMessage::ScannerPing { .. } => "ScannerPing",
                 ServerMessage::WeaponFired { .. } => "WeaponFired",
+            ServerMessage::MechStompWindup { .. } => "MechStompWindup",
+            ServerMessage::MechStomped { .. } => "MechStomped",
             ServerMessage::ProjectileHit { .. } => "ProjectileHit",
             ServerMessage::ProjectileExpired { .. } => "ProjectileExpired",
+            ServerMessage::ProjectileIntercepted { .. } => "ProjectileIntercepted",
             ServerMessage::EffectCreated { .. } => "EffectCreated",
             ServerMessage::EffectExpired { .. } => "EffectExpired",
             ServerMessage::ResourceSpawned { .. } => "ResourceSpawned",
             ServerMessage::ResourceCollected { .. } => "ResourceCollected",
+            ServerMessage::ResourceDestroyed { .. } => "ResourceDestroyed",
+            ServerMessage::ResourceDelivered { .. } => "ResourceDelivered",
+            ServerMessage::MechInventoryChanged { .. } => "MechInventoryChanged",
             ServerMessage::ChatMessage { .. } => "ChatMessage",
+            ServerMessage::AnnotationDrawn { .. } => "AnnotationDrawn",
             ServerMessage::PlayerKilled { .. } => "PlayerKilled",
             ServerMessage::TileUpdate { .. } => "TileUpdate",
             ServerMessage::TileBatch { .. } => "TileBatch",
             ServerMessage::VisibilityUpdate { .. } => "VisibilityUpdate",
+            ServerMessage::TeamVisionUpdate { .. } => "TeamVisionUpdate",
+            ServerMessage::CollisionShapes { .. } => "CollisionShapes",
+            ServerMessage::StatusEffectsChanged { .. } => "StatusEffectsChanged",
+            ServerMessage::MechFiresUpdated { .. } => "MechFiresUpdated",
+            ServerMessage::HullBreachUpdated { .. } => "HullBreachUpdated",
+            ServerMessage::BarricadeUpdated { .. } => "BarricadeUpdated",
+            ServerMessage::PlayerToolChanged { .. } => "PlayerToolChanged",
+            ServerMessage::RepairProgress { .. } => "RepairProgress",
+            ServerMessage::PlayerAttacked { .. } => "PlayerAttacked",
+            ServerMessage::PlayerDashed { .. } => "PlayerDashed",
+            ServerMessage::SabotageProgress { .. } => "SabotageProgress",
+            ServerMessage::MechAlarm { .. } => "MechAlarm",
+            ServerMessage::AchievementUnlocked { .. } => "AchievementUnlocked",
+            ServerMessage::TutorialInstruction { .. } => "TutorialInstruction",
+            ServerMessage::TutorialComplete { .. } => "TutorialComplete",
+            ServerMessage::ArenaMapData { .. } => "ArenaMapData",
+            ServerMessage::HazardsUpdated { .. } => "HazardsUpdated",
+            ServerMessage::DecalLayer { .. } => "DecalLayer",
+            ServerMessage::DecalAdded { .. } => "DecalAdded",
+            ServerMessage::HangarUpdated { .. } => "HangarUpdated",
+            ServerMessage::MatchStarted => "MatchStarted",
+            ServerMessage::GameModeUpdated { .. } => "GameModeUpdated",
+            ServerMessage::PacingEventStarted { .. } => "PacingEventStarted",
+            ServerMessage::PacingZonesUpdated { .. } => "PacingZonesUpdated",
+            ServerMessage::WeatherChanged { .. } => "WeatherChanged",
+            ServerMessage::Subscribed { .. } => "Subscribed",
+            ServerMessage::ServerLoad { .. } => "ServerLoad",
+            ServerMessage::ServerShutdown { .. } => "ServerShutdown",
+            ServerMessage::StateChecksum { .. } => "StateChecksum",
+            ServerMessage::DevCommandResult { .. } => "DevCommandResult",
             ServerMessage::Error { .. } => "Error",
         }
     }
@@ -248,8 +757,15 @@ pub struct PlayerState {
     pub name: String,
     pub team: TeamId,
     pub location: PlayerLocation,
-    pub carrying_resource: Option<ResourceType>,
+    pub inventory: Inventory,
     pub operating_station: Option<StationId>,
+    pub held_tool: Option<ToolType>,
+    pub health: u32,
+    #[serde(default)]
+    pub facing: Facing,
+    /// Hasn't sent input in a while
+    #[serde(default)]
+    pub afk: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -259,10 +775,25 @@ pub struct MechState {
     pub position: TilePos,
     pub world_position: WorldPos,
     pub health: u32,
+    pub max_health: u32,
     pub shield: u32,
     pub upgrades: MechUpgrades,
+    pub drive: MechDrive,
+    /// What it's carrying, which sets how it accelerates and its top speed
+    #[serde(default)]
+    pub mass: MechMass,
+    /// Left in the tank, out of `MECH_FUEL_CAPACITY`
+    pub fuel: f32,
+    pub turret: MechTurret,
+    pub point_defense: PointDefense,
     pub stations: Vec<StationState>,
     pub resource_inventory: HashMap<ResourceType, u32>,
+    /// Score earned by the crew for resources dropped off at this mech
+    pub delivery_score: u32,
+    pub fires: Vec<FireState>,
+    /// Sounding while boarders work one of its stations
+    #[serde(default)]
+    pub alarm: Option<SabotageAlarm>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -273,6 +804,28 @@ pub struct StationState {
     pub position: TilePos,
     pub size: crate::mech_layout::StationSize, // Add multi-tile station support
     pub operated_by: Option<PlayerId>,
+    #[serde(default)]
+    pub health: u32,
+    #[serde(default)]
+    pub max_health: u32,
+    #[serde(default)]
+    pub upgrade_level: u8,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StationButtonInfo {
+    pub index: u8,
+    pub label: String,
+    pub description: String,
+    pub cooldown_seconds: f32,
+    pub resource_cost: HashMap<ResourceType, u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FireState {
+    pub floor: u8,
+    pub position: TilePos,
+    pub intensity: f32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -282,6 +835,14 @@ pub struct ResourceState {
     pub resource_type: ResourceType,
 }
 
+/// Something a player dropped, waiting on the ground to be picked up
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroundItemState {
+    pub id: ItemId,
+    pub item: ItemType,
+    pub position: WorldPos,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProjectileState {
     pub id: ProjectileId,
@@ -289,6 +850,7 @@ pub struct ProjectileState {
     pub velocity: (f32, f32),
     pub damage: u32,
     pub owner_mech_id: MechId,
+    pub weapon_type: WeaponType,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
@@ -297,6 +859,29 @@ pub struct MechUpgrades {
     pub projectile_level: u8,
     pub engine_level: u8,
     pub shield_level: u8,
+    pub ecm_level: u8, // 0 until the first ECM suite is installed
+}
+
+impl MechUpgrades {
+    pub fn level(&self, upgrade_type: UpgradeType) -> u8 {
+        match upgrade_type {
+            UpgradeType::Laser => self.laser_level,
+            UpgradeType::Projectile => self.projectile_level,
+            UpgradeType::Engine => self.engine_level,
+            UpgradeType::Shield => self.shield_level,
+            UpgradeType::Ecm => self.ecm_level,
+        }
+    }
+
+    pub fn level_mut(&mut self, upgrade_type: UpgradeType) -> &mut u8 {
+        match upgrade_type {
+            UpgradeType::Laser => &mut self.laser_level,
+            UpgradeType::Projectile => &mut self.projectile_level,
+            UpgradeType::Engine => &mut self.engine_level,
+            UpgradeType::Shield => &mut self.shield_level,
+            UpgradeType::Ecm => &mut self.ecm_level,
+        }
+    }
 }
 
 // Station update for real-time interior changes (Future scope)
@@ -307,3 +892,46 @@ pub enum StationUpdate {
     Upgraded { station_id: StationId, new_level: u8 },
     StatusChanged { station_id: StationId, new_status: String },
 }
+
+/// How far behind the server is falling. Each level gives up more than the
+/// one before, cosmetics first and gameplay never.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum ServerLoad {
+    #[default]
+    Normal,
+    /// Effects and the spectator camera are paused and the AIs think less often
+    Strained,
+    /// On top of that, updates go out less often
+    Overloaded,
+}
+
+impl ServerLoad {
+    pub fn description(self) -> &'static str {
+        match self {
+            ServerLoad::Normal => "Normal",
+            ServerLoad::Strained => "Server under load: effects paused",
+            ServerLoad::Overloaded => "Server overloaded: updates slowed",
+        }
+    }
+
+    pub fn sheds_cosmetics(self) -> bool {
+        self >= ServerLoad::Strained
+    }
+
+    /// How many times longer the AIs wait between decisions
+    pub fn ai_slowdown(self) -> f32 {
+        match self {
+            ServerLoad::Normal => 1.0,
+            ServerLoad::Strained => LOAD_STRAINED_AI_SLOWDOWN,
+            ServerLoad::Overloaded => LOAD_OVERLOADED_AI_SLOWDOWN,
+        }
+    }
+
+    /// Broadcasts skipped between the ones that go out, plus one
+    pub fn broadcast_stride(self) -> u64 {
+        match self {
+            ServerLoad::Overloaded => LOAD_OVERLOADED_BROADCAST_STRIDE,
+            _ => 1,
+        }
+    }
+}
@@ -0,0 +1,190 @@
+//! Simulated bad networks for development. The client and server can run
+//! their traffic through a `NetConditioner` that delays, jitters, drops and
+//! reorders messages, so prediction and interpolation can be checked against
+//! a poor connection without external tools.
+
+use crate::network_constants::NET_REORDER_HOLD_MS;
+use serde::{Deserialize, Serialize};
+
+/// How bad to make the connection. All zero means untouched.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NetConditions {
+    pub latency_ms: u32, // one-way delay added to every message
+    pub jitter_ms: u32,  // each delay varies by up to this much either way
+    pub drop_percent: f32,
+    pub reorder_percent: f32, // chance a message is held back behind later ones
+}
+
+impl NetConditions {
+    pub fn is_active(&self) -> bool {
+        self.latency_ms > 0
+            || self.jitter_ms > 0
+            || self.drop_percent > 0.0
+            || self.reorder_percent > 0.0
+    }
+
+    /// Conditions from `MECH_NET_LATENCY_MS`, `MECH_NET_JITTER_MS`,
+    /// `MECH_NET_DROP_PERCENT` and `MECH_NET_REORDER_PERCENT`, or None if
+    /// none of them are set
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_env() -> Option<Self> {
+        fn var<T: std::str::FromStr>(name: &str) -> Option<T> {
+            std::env::var(name).ok()?.trim().parse().ok()
+        }
+
+        let latency_ms = var("MECH_NET_LATENCY_MS");
+        let jitter_ms = var("MECH_NET_JITTER_MS");
+        let drop_percent = var("MECH_NET_DROP_PERCENT");
+        let reorder_percent = var("MECH_NET_REORDER_PERCENT");
+        if latency_ms.is_none()
+            && jitter_ms.is_none()
+            && drop_percent.is_none()
+            && reorder_percent.is_none()
+        {
+            return None;
+        }
+        Some(Self {
+            latency_ms: latency_ms.unwrap_or(0),
+            jitter_ms: jitter_ms.unwrap_or(0),
+            drop_percent: drop_percent.unwrap_or(0.0),
+            reorder_percent: reorder_percent.unwrap_or(0.0),
+        })
+    }
+}
+
+impl std::fmt::Display for NetConditions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}ms ±{}ms, {}% drop, {}% reorder",
+            self.latency_ms, self.jitter_ms, self.drop_percent, self.reorder_percent
+        )
+    }
+}
+
+/// Holds messages until the simulated network would have delivered them.
+/// Times are seconds on whatever clock the caller uses, as long as it's the
+/// same one for every call.
+pub struct NetConditioner<T> {
+    conditions: NetConditions,
+    /// Waiting messages, ordered by release time
+    queue: Vec<(f64, T)>,
+    /// Release time of the newest in-order message; later ones never
+    /// overtake it unless picked for reordering
+    last_release: f64,
+    rng: u64,
+}
+
+impl<T> NetConditioner<T> {
+    pub fn new(conditions: NetConditions) -> Self {
+        Self {
+            conditions,
+            queue: Vec::new(),
+            last_release: 0.0,
+            rng: 0x9E37_79B9_7F4A_7C15,
+        }
+    }
+
+    pub fn conditions(&self) -> NetConditions {
+        self.conditions
+    }
+
+    /// Change conditions. Messages already waiting keep their release times.
+    pub fn set_conditions(&mut self, conditions: NetConditions) {
+        self.conditions = conditions;
+    }
+
+    /// Hand a message to the simulated network. It may be dropped.
+    pub fn push(&mut self, now: f64, item: T) {
+        if self.roll() * 100.0 < self.conditions.drop_percent as f64 {
+            return;
+        }
+
+        let jitter = self.conditions.jitter_ms as f64 * (self.roll() * 2.0 - 1.0);
+        let delay = (self.conditions.latency_ms as f64 + jitter).max(0.0) / 1000.0;
+        let release = if self.roll() * 100.0 < self.conditions.reorder_percent as f64 {
+            // Held back so whatever is sent next arrives first
+            now + delay + NET_REORDER_HOLD_MS as f64 / 1000.0
+        } else {
+            // Jitter alone doesn't reorder a stream connection
+            let release = (now + delay).max(self.last_release);
+            self.last_release = release;
+            release
+        };
+
+        let index = self.queue.partition_point(|(queued, _)| *queued <= release);
+        self.queue.insert(index, (release, item));
+    }
+
+    /// Everything the network has delivered by `now`, in arrival order
+    pub fn pop_ready(&mut self, now: f64) -> Vec<T> {
+        let ready = self.queue.partition_point(|(release, _)| *release <= now);
+        self.queue.drain(..ready).map(|(_, item)| item).collect()
+    }
+
+    /// When the next waiting message arrives
+    pub fn next_release(&self) -> Option<f64> {
+        self.queue.first().map(|(release, _)| *release)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Uniform in [0, 1), from a xorshift so it works the same on every
+    /// target without pulling in an RNG
+    fn roll(&mut self) -> f64 {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        (self.rng >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_untouched_when_inactive() {
+        let mut conditioner = NetConditioner::new(NetConditions::default());
+        conditioner.push(1.0, "a");
+        conditioner.push(1.0, "b");
+        assert_eq!(conditioner.pop_ready(1.0), vec!["a", "b"]);
+        assert!(conditioner.is_empty());
+    }
+
+    #[test]
+    fn test_latency_and_jitter_keep_order() {
+        let mut conditioner = NetConditioner::new(NetConditions {
+            latency_ms: 100,
+            jitter_ms: 80,
+            ..Default::default()
+        });
+        for i in 0..50 {
+            conditioner.push(i as f64 * 0.001, i);
+        }
+
+        assert!(conditioner.pop_ready(0.015).is_empty());
+        assert!(conditioner.next_release().unwrap() >= 0.02);
+        assert_eq!(conditioner.pop_ready(1.0), (0..50).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_drop_and_reorder() {
+        let mut conditioner = NetConditioner::new(NetConditions {
+            latency_ms: 10,
+            drop_percent: 20.0,
+            reorder_percent: 20.0,
+            ..Default::default()
+        });
+        for i in 0..500 {
+            conditioner.push(i as f64 * 0.001, i);
+        }
+
+        let delivered = conditioner.pop_ready(10.0);
+        assert!((300..490).contains(&delivered.len()));
+        assert!(delivered.windows(2).any(|pair| pair[0] > pair[1]));
+    }
+}
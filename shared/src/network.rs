@@ -1,4 +1,9 @@
-use crate::{ClientMessage, GameResult, NetworkError, NetworkResult, ServerMessage};
+use crate::network_constants::{
+    COMPRESSION_LEVEL, COMPRESSION_QUERY, COMPRESSION_THRESHOLD_BYTES, MAX_DECOMPRESSED_FRAME_SIZE,
+};
+use crate::{
+    ClientMessage, GameResult, NetworkError, NetworkResult, ProtocolFeature, ServerMessage,
+};
 use std::sync::{Arc, Mutex};
 
 /// Trait for network operations across platforms
@@ -139,11 +144,93 @@ pub fn serialize_client_message(msg: &ClientMessage) -> NetworkResult<Vec<u8>> {
     rmp_serde::to_vec(msg).map_err(NetworkError::from)
 }
 
+/// Deserialize a client message from MessagePack
+pub fn deserialize_client_message(bytes: &[u8]) -> NetworkResult<ClientMessage> {
+    rmp_serde::from_slice(bytes).map_err(NetworkError::from)
+}
+
 /// Deserialize a server message from MessagePack
 pub fn deserialize_server_message(bytes: &[u8]) -> NetworkResult<ServerMessage> {
     rmp_serde::from_slice(bytes).map_err(NetworkError::from)
 }
 
+/// Header byte of a server frame on a connection that negotiated compression
+const FRAME_RAW: u8 = 0;
+const FRAME_DEFLATE: u8 = 1;
+
+/// Whether a websocket URL asks the server for compressed frames
+pub fn url_requests_compression(url: &str) -> bool {
+    url.split_once('?')
+        .is_some_and(|(_, query)| query.split('&').any(|param| param == COMPRESSION_QUERY))
+}
+
+/// `url` asking to take back the player a `SessionResume` token was issued
+/// for, in place of any token it already carries
+pub fn resume_ws_url(url: &str, token: uuid::Uuid) -> String {
+    let (base, query) = url.split_once('?').unwrap_or((url, ""));
+    let mut params: Vec<&str> = query
+        .split('&')
+        .filter(|param| !param.is_empty() && !param.starts_with("resume="))
+        .collect();
+    let resume = format!("resume={token}");
+    params.push(&resume);
+    format!("{base}?{}", params.join("&"))
+}
+
+/// Serialize a server message for the wire. Without compression this is plain
+/// MessagePack, as older clients expect. With compression every frame starts
+/// with a header byte, and payloads over the threshold are deflated.
+pub fn encode_server_frame(msg: &ServerMessage, compression: bool) -> NetworkResult<Vec<u8>> {
+    let payload = rmp_serde::to_vec(msg)?;
+    if !compression {
+        return Ok(payload);
+    }
+
+    let mut frame = Vec::with_capacity(payload.len() + 1);
+    if payload.len() > COMPRESSION_THRESHOLD_BYTES {
+        frame.push(FRAME_DEFLATE);
+        frame.extend(miniz_oxide::deflate::compress_to_vec(&payload, COMPRESSION_LEVEL));
+    } else {
+        frame.push(FRAME_RAW);
+        frame.extend(payload);
+    }
+    Ok(frame)
+}
+
+/// Decode a frame produced by `encode_server_frame` with the same setting
+pub fn decode_server_frame(bytes: &[u8], compression: bool) -> NetworkResult<ServerMessage> {
+    if !compression {
+        return deserialize_server_message(bytes);
+    }
+
+    match bytes.split_first() {
+        Some((&FRAME_RAW, payload)) => deserialize_server_message(payload),
+        Some((&FRAME_DEFLATE, payload)) => {
+            let inflated = miniz_oxide::inflate::decompress_to_vec_with_limit(
+                payload,
+                MAX_DECOMPRESSED_FRAME_SIZE,
+            )
+            .map_err(|e| NetworkError::InvalidMessage(format!("bad compressed frame: {e}")))?;
+            deserialize_server_message(&inflated)
+        }
+        Some((header, _)) => Err(NetworkError::InvalidMessage(format!(
+            "unknown frame header {header}"
+        ))),
+        None => Err(NetworkError::InvalidMessage("empty frame".to_string())),
+    }
+}
+
+/// Whether frames after `msg` are compressed, if it changes that. The server
+/// switches over to the negotiated features right after sending `JoinedGame`.
+pub fn compression_after(msg: &ServerMessage) -> Option<bool> {
+    match msg {
+        ServerMessage::JoinedGame { features, .. } => {
+            Some(features.contains(&ProtocolFeature::Compression))
+        }
+        _ => None,
+    }
+}
+
 /// Auto-reconnect logic that can be shared across platforms
 pub struct ReconnectManager {
     max_attempts: u32,
@@ -207,6 +294,66 @@ impl ReconnectManager {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_compressed_frames_round_trip() {
+        let small = ServerMessage::PlayerDisconnected {
+            player_id: uuid::Uuid::nil(),
+        };
+        let large = ServerMessage::ChatMessage {
+            player_id: uuid::Uuid::nil(),
+            player_name: "Test".to_string(),
+            message: "mech ".repeat(COMPRESSION_THRESHOLD_BYTES),
+            team_only: false,
+        };
+
+        let small_frame = encode_server_frame(&small, true).unwrap();
+        assert_eq!(small_frame[0], FRAME_RAW);
+        assert!(matches!(
+            decode_server_frame(&small_frame, true).unwrap(),
+            ServerMessage::PlayerDisconnected { .. }
+        ));
+
+        let large_frame = encode_server_frame(&large, true).unwrap();
+        let uncompressed = encode_server_frame(&large, false).unwrap();
+        assert_eq!(large_frame[0], FRAME_DEFLATE);
+        assert!(large_frame.len() < uncompressed.len() / 4);
+        match decode_server_frame(&large_frame, true).unwrap() {
+            ServerMessage::ChatMessage { message, .. } => {
+                assert_eq!(message.len(), 5 * COMPRESSION_THRESHOLD_BYTES)
+            }
+            other => panic!("Expected ChatMessage, got {other:?}"),
+        }
+
+        // Connections without compression keep plain MessagePack
+        assert!(decode_server_frame(&uncompressed, false).is_ok());
+        assert!(decode_server_frame(&[7, 1, 2], true).is_err());
+    }
+
+    #[test]
+    fn test_url_requests_compression() {
+        assert!(url_requests_compression(
+            "ws://127.0.0.1:14191/ws?compression=deflate"
+        ));
+        assert!(!url_requests_compression("ws://127.0.0.1:14191/ws"));
+        assert!(!url_requests_compression("ws://host/compression=deflate"));
+    }
+
+    #[test]
+    fn test_resume_url_replaces_the_old_token() {
+        let url = format!("ws://127.0.0.1:14191/ws?{COMPRESSION_QUERY}&room=duel");
+        let first = resume_ws_url(&url, uuid::Uuid::nil());
+        let second = resume_ws_url(&first, uuid::Uuid::max());
+        assert_eq!(
+            second,
+            format!("{url}&resume={}", uuid::Uuid::max())
+        );
+        assert!(url_requests_compression(&second));
+        assert_eq!(
+            resume_ws_url("ws://host/ws", uuid::Uuid::nil()),
+            format!("ws://host/ws?resume={}", uuid::Uuid::nil())
+        );
+    }
+
     #[test]
     fn test_reconnect_manager() {
         let mut manager = ReconnectManager::new(3, 100, 1000);
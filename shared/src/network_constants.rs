@@ -3,16 +3,92 @@
 // ===== Network Configuration =====
 pub const BROADCAST_CHANNEL_SIZE: usize = 1000;
 pub const MESSAGE_BUFFER_SIZE: usize = 65536; // 64KB
+pub const CLIENT_SEND_QUEUE_CAPACITY: usize = 256; // non-critical messages buffered per client
 pub const SERVER_ADDRESS: [u8; 4] = [127, 0, 0, 1];
+pub const DEBUG_COMM_POLL_INTERVAL_MS: u64 = 250; // how often /debug observers get new AI messages
+pub const DEBUG_AI_METRICS_INTERVAL_MS: u64 = 1000; // how often /debug observers get AI timing totals
+pub const DEBUG_BANDWIDTH_INTERVAL_MS: u64 = 1000; // how often /debug observers get per-message bandwidth
+pub const EVENT_LOG_CAPACITY: usize = 5000; // gameplay events /debug/events can look back over
+pub const COLLISION_SHAPES_UPDATE_TICKS: u64 = 3; // ticks between collision shapes for clients that asked for verbose state
+pub const PROJECTILE_TRAIL_SAMPLES: usize = 15; // recent positions kept per projectile for the collision overlay
+
+// ===== Protocol =====
+pub const PROTOCOL_VERSION: u32 = 1; // bump when messages change incompatibly
+pub const MIN_PROTOCOL_VERSION: u32 = 1; // oldest client version still let in
+pub const MAX_PROTOCOL_FEATURES: usize = 16; // most features a JoinGame may list
+
+// ===== Voice =====
+pub const VOICE_SAMPLE_RATE: u32 = 48000; // mono, what opus is run at on every client
+pub const VOICE_FRAME_SAMPLES: usize = 960; // 20ms of audio per encoded frame
+pub const VOICE_BITRATE: i32 = 24000; // bits per second the encoder aims for
+pub const VOICE_MAX_FRAME_BYTES: usize = 400; // largest opus payload the relay passes on
+pub const VOICE_MAX_FRAMES_PER_SECOND: u32 = 60; // per speaker; 50 is a steady stream of 20ms frames
+pub const VOICE_RELAY_BUFFER_FRAMES: usize = 32; // frames queued per listener before new ones are dropped
+pub const VOICE_SPEAKING_HOLD_SECONDS: f64 = 0.3; // how long a speaker indicator lingers after the last frame
+
+// ===== Compression =====
+pub const COMPRESSION_QUERY: &str = "compression=deflate"; // websocket URL query that opts in
+pub const COMPRESSION_THRESHOLD_BYTES: usize = 512; // smaller messages aren't worth deflating
+pub const COMPRESSION_LEVEL: u8 = 6;
+pub const MAX_DECOMPRESSED_FRAME_SIZE: usize = 16 * 1024 * 1024; // guards against inflate bombs
+
+// ===== Rooms =====
+pub const DEFAULT_ROOM: &str = "default"; // room for clients that don't ask for one
+pub const MAX_ROOMS: usize = 16;
+pub const MAX_ROOM_NAME_LENGTH: usize = 24;
+pub const MAX_ROOM_TITLE_LENGTH: usize = 48;
+pub const MAX_ROOM_PASSWORD_LENGTH: usize = 64;
+pub const ROOM_IDLE_TIMEOUT_SECS: u64 = 300; // empty rooms close after this long
+pub const ROOM_IDLE_CHECK_SECS: u64 = 30;
+
+// ===== Matchmaking =====
+pub const MATCHMAKING_CHECK_SECS: u64 = 1; // how often the server tries to form matches
+pub const MATCHMAKING_POLL_MS: u64 = 1000; // how often a queued client asks after its ticket
+pub const MATCHMAKING_TICKET_TTL_SECS: u64 = 30; // tickets not asked after in this long are dropped
+pub const MAX_MATCHMAKING_QUEUE: usize = 256;
+
+// ===== Server Browser =====
+pub const DEFAULT_SERVER_NAME: &str = "Mech Battle Arena";
+pub const MAX_SERVER_NAME_LENGTH: usize = 48;
+pub const MASTER_HEARTBEAT_SECS: u64 = 30; // how often a server re-announces itself
+pub const MASTER_LISTING_TTL_SECS: u64 = 90; // listings not renewed in this long are dropped
+pub const MASTER_HTTP_TIMEOUT_MS: u64 = 3000;
+pub const MAX_MASTER_LISTINGS: usize = 256;
+pub const LAN_DISCOVERY_PORT: u16 = 14192; // UDP
+pub const LAN_DISCOVERY_WAIT_MS: u64 = 500; // how long a client listens for LAN replies
+pub const SERVER_PING_TIMEOUT_MS: u64 = 1000;
+
+// ===== Stream Overlay =====
+pub const OVERLAY_REFRESH_MS: u64 = 1000; // how often /overlay polls the score unless told otherwise
+pub const OVERLAY_MIN_REFRESH_MS: u64 = 250;
+pub const OVERLAY_MAX_REFRESH_MS: u64 = 60_000;
 
 // ===== Connection Settings =====
 pub const MAX_CONNECTION_ATTEMPTS: u32 = 60; // frames to wait
 pub const CONNECTION_RETRY_DELAY_MS: u64 = 100;
+pub const CONNECTION_TIMEOUT_SECS: f64 = 6.0; // silence from the server before the connection counts as lost
+pub const RECONNECT_DELAY_SECS: f64 = 3.0; // countdown before the first automatic reconnect
+pub const RECONNECT_MAX_DELAY_SECS: f64 = 30.0; // the countdown doubles with each failed attempt up to this
+pub const SESSION_RESUME_GRACE_SECS: f32 = 60.0; // how long a dropped player is kept for them to come back
+pub const HEADLESS_STATUS_INTERVAL: f32 = 1.0; // seconds between a headless client's status lines
 
 // ===== Game Loop Timing =====
 pub const FRAME_DURATION_MS: u64 = 33; // ~30 FPS
 pub const FRAME_DELTA_SECONDS: f32 = 0.033;
 pub const STATE_UPDATE_INTERVAL: u64 = 30; // Send full state every second at 30 FPS
+pub const MAX_CATCH_UP_STEPS: u32 = 5; // fixed steps one late frame may replay before dropping the backlog
+pub const BROADCAST_INTERVAL_MS: u64 = 33; // how often queued simulation messages go out
+
+// ===== Load Shedding =====
+pub const LOAD_SMOOTHING: f32 = 0.1; // weight of each frame's work in the smoothed load
+pub const LOAD_STRAINED: f32 = 0.7; // share of the frame budget spent working that sheds cosmetics
+pub const LOAD_OVERLOADED: f32 = 0.95; // share of the frame budget that sheds broadcasts too
+pub const LOAD_RECOVERY_MARGIN: f32 = 0.15; // how far under a level's threshold load must fall to leave it
+pub const LOAD_MIN_HOLD_FRAMES: u32 = 90; // frames a level is kept before easing off it
+pub const LOAD_STRAINED_AI_SLOWDOWN: f32 = 2.0; // AI decisions come this many times less often
+pub const LOAD_OVERLOADED_AI_SLOWDOWN: f32 = 4.0;
+pub const LOAD_AI_MAX_INTERVAL: f32 = 0.25; // seconds; slowest shedding makes the AIs, well inside their orders' lifetime
+pub const LOAD_OVERLOADED_BROADCAST_STRIDE: u64 = 2; // flushes and full states go out once every this many
 
 // ===== Player Configuration =====
 pub const PLAYER_NAME_MIN_ID: u32 = 1000;
@@ -26,3 +102,26 @@ pub const MAX_PLAYER_NAME_LENGTH: usize = 32;
 pub const MAX_CHAT_MESSAGE_LENGTH: usize = 256;
 pub const MAX_MOVEMENT_MAGNITUDE: f32 = 2.0;
 pub const MAX_STATION_BUTTONS: u8 = 8;
+pub const MAX_DEV_RESOURCE_GRANT: u32 = 99; // most resources one console `give` can add
+
+// ===== Input Throttling =====
+pub const TURRET_AIM_RESEND_ANGLE: f32 = 0.01; // radians the mouse aim drifts before it's sent again
+pub const IDLE_INPUT_NOTE_MS: u64 = 1000; // how often a client's input restarts its idle timer
+
+// ===== Lag Compensation =====
+pub const LAG_COMP_PING_INTERVAL_MS: u64 = 1000; // how often the server measures each client's round trip
+pub const LAG_COMP_RTT_SMOOTHING: f32 = 0.2; // weight of each new round-trip sample
+pub const LAG_COMP_MAX_REWIND: f32 = 0.25; // seconds; furthest back a laser hit check may look
+pub const LAG_COMP_HISTORY_SECONDS: f32 = 0.3; // position history kept per mech and player
+
+// ===== Shutdown =====
+pub const SHUTDOWN_GRACE_SECONDS: u32 = 5; // warning players get before a Ctrl-C shutdown
+pub const SHUTDOWN_FLUSH_TIMEOUT_MS: u64 = 2000; // longest we wait for send queues to empty
+pub const SHUTDOWN_FLUSH_POLL_MS: u64 = 50;
+
+// ===== Profiling =====
+pub const PROFILE_DEFAULT_SECONDS: u64 = 5; // capture length when /debug/profile isn't given one
+pub const PROFILE_MAX_SECONDS: u64 = 60;
+
+// ===== Network Conditioner =====
+pub const NET_REORDER_HOLD_MS: u32 = 60; // extra delay on messages picked to arrive out of order
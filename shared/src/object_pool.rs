@@ -1,4 +1,4 @@
-use crate::WorldPos;
+use crate::{WeaponType, WorldPos};
 use std::collections::VecDeque;
 use uuid::Uuid;
 
@@ -72,6 +72,7 @@ pub struct PooledProjectile {
     pub lifetime: f32,
     pub max_lifetime: f32,
     pub active: bool,
+    pub weapon_type: WeaponType,
 }
 
 impl Default for PooledProjectile {
@@ -92,6 +93,7 @@ impl PooledProjectile {
             lifetime: 0.0,
             max_lifetime: 5.0,
             active: false,
+            weapon_type: WeaponType::Projectile,
         }
     }
 
@@ -136,6 +138,7 @@ impl PooledProjectile {
         self.velocity = (0.0, 0.0);
         self.position = WorldPos::new(0.0, 0.0);
         self.owner_mech_id = Uuid::nil();
+        self.weapon_type = WeaponType::Projectile;
     }
 
     /// Check if projectile is still active and valid
@@ -0,0 +1,146 @@
+use crate::types::WorldPos;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// What a pacing zone does to the match
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum PacingZoneKind {
+    /// Extra resources were dropped inside the zone
+    ResourceSurge,
+    /// Weapons fired by mechs inside the zone deal extra damage
+    DamageBoost { multiplier: f32 },
+    /// Mechs outside the zone take damage; it shrinks toward `target_radius`
+    PlayArea {
+        target_radius: f32, // pixels
+        shrink_speed: f32,  // pixels per second
+    },
+}
+
+/// A circular area injected by the match director
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PacingZone {
+    pub id: Uuid,
+    pub kind: PacingZoneKind,
+    pub center: WorldPos,
+    pub radius: f32,            // pixels
+    pub remaining: Option<f32>, // seconds, None for zones that last the rest of the match
+}
+
+impl PacingZone {
+    pub fn contains(&self, pos: WorldPos) -> bool {
+        self.center.distance_to(pos) <= self.radius
+    }
+
+    /// Advance timers and shrinking. Returns true once the zone has expired.
+    /// Clients run this too so zones animate smoothly between server updates.
+    pub fn advance(&mut self, delta_time: f32) -> bool {
+        if let PacingZoneKind::PlayArea {
+            target_radius,
+            shrink_speed,
+        } = self.kind
+        {
+            self.radius = (self.radius - shrink_speed * delta_time).max(target_radius);
+        }
+
+        match self.remaining.as_mut() {
+            Some(remaining) => {
+                *remaining -= delta_time;
+                *remaining <= 0.0
+            }
+            None => false,
+        }
+    }
+}
+
+/// Combined damage multiplier from every boost zone covering a position
+pub fn damage_multiplier_at(zones: &[PacingZone], pos: WorldPos) -> f32 {
+    zones
+        .iter()
+        .filter(|zone| zone.contains(pos))
+        .map(|zone| match zone.kind {
+            PacingZoneKind::DamageBoost { multiplier } => multiplier,
+            _ => 1.0,
+        })
+        .product()
+}
+
+/// Why the camera director picked a mech for spectators to watch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DirectorFocus {
+    /// It's been taking hits
+    UnderFire,
+    /// An enemy mech is closing in on it
+    Closing,
+    /// It's close to going down
+    LowHealth,
+    /// Nothing's happening anywhere; it's just worth a look
+    Idle,
+}
+
+impl DirectorFocus {
+    pub fn describe(self) -> &'static str {
+        match self {
+            DirectorFocus::UnderFire => "Under fire",
+            DirectorFocus::Closing => "Mechs closing in",
+            DirectorFocus::LowHealth => "Hanging on",
+            DirectorFocus::Idle => "Quiet",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn zone(kind: PacingZoneKind, radius: f32, remaining: Option<f32>) -> PacingZone {
+        PacingZone {
+            id: Uuid::nil(),
+            kind,
+            center: WorldPos::new(100.0, 100.0),
+            radius,
+            remaining,
+        }
+    }
+
+    #[test]
+    fn test_play_area_shrinks_to_target() {
+        let mut area = zone(
+            PacingZoneKind::PlayArea {
+                target_radius: 50.0,
+                shrink_speed: 10.0,
+            },
+            100.0,
+            None,
+        );
+
+        assert!(!area.advance(2.0));
+        assert_eq!(area.radius, 80.0);
+        assert!(!area.advance(100.0));
+        assert_eq!(area.radius, 50.0);
+    }
+
+    #[test]
+    fn test_damage_boost_only_applies_inside() {
+        let zones = [
+            zone(
+                PacingZoneKind::DamageBoost { multiplier: 1.5 },
+                20.0,
+                Some(5.0),
+            ),
+            zone(PacingZoneKind::ResourceSurge, 200.0, Some(5.0)),
+        ];
+
+        assert_eq!(
+            damage_multiplier_at(&zones, WorldPos::new(110.0, 100.0)),
+            1.5
+        );
+        assert_eq!(
+            damage_multiplier_at(&zones, WorldPos::new(150.0, 100.0)),
+            1.0
+        );
+
+        let mut boost = zones[0];
+        assert!(!boost.advance(4.0));
+        assert!(boost.advance(1.0));
+    }
+}
@@ -0,0 +1,155 @@
+use crate::balance::*;
+use crate::types::WorldPos;
+use serde::{Deserialize, Serialize};
+
+/// Magazine and mode of a mech's point-defense battery. On auto it shoots
+/// down enemy shots closing on the mech by itself; either way the operator
+/// can fire an interceptor at the nearest one.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PointDefense {
+    /// Interceptors left; restocked a full magazine at a time
+    pub ammo: u32,
+    /// Engage incoming shots without anyone pressing Intercept
+    pub auto: bool,
+    /// Seconds until it can fire again
+    pub cooldown: f32,
+}
+
+impl Default for PointDefense {
+    fn default() -> Self {
+        Self {
+            ammo: POINT_DEFENSE_MAGAZINE,
+            auto: true,
+            cooldown: 0.0,
+        }
+    }
+}
+
+impl PointDefense {
+    pub fn ready(&self) -> bool {
+        self.ammo > 0 && self.cooldown <= 0.0
+    }
+
+    pub fn reload(&mut self, delta_time: f32) {
+        self.cooldown = (self.cooldown - delta_time).max(0.0);
+    }
+
+    /// Spend a round and start the cooldown; false if it couldn't fire
+    pub fn fire(&mut self) -> bool {
+        if !self.ready() {
+            return false;
+        }
+        self.ammo -= 1;
+        self.cooldown = POINT_DEFENSE_COOLDOWN;
+        true
+    }
+
+    pub fn restock(&mut self) {
+        self.ammo = POINT_DEFENSE_MAGAZINE;
+    }
+}
+
+/// Where a shot fired from `origin` at `speed` meets a target at `target`
+/// moving with `velocity`, or `None` if the shot can't catch it
+pub fn intercept_point(
+    origin: WorldPos,
+    target: WorldPos,
+    velocity: (f32, f32),
+    speed: f32,
+) -> Option<WorldPos> {
+    // Solve |target + velocity * t - origin| = speed * t for the first t > 0
+    let (dx, dy) = (target.x - origin.x, target.y - origin.y);
+    let a = velocity.0 * velocity.0 + velocity.1 * velocity.1 - speed * speed;
+    let b = 2.0 * (dx * velocity.0 + dy * velocity.1);
+    let c = dx * dx + dy * dy;
+    let time = if a.abs() < f32::EPSILON {
+        (b < 0.0).then(|| -c / b)?
+    } else {
+        let discriminant = b * b - 4.0 * a * c;
+        if discriminant < 0.0 {
+            return None;
+        }
+        let root = discriminant.sqrt();
+        [(-b - root) / (2.0 * a), (-b + root) / (2.0 * a)]
+            .into_iter()
+            .filter(|t| *t > 0.0)
+            .reduce(f32::min)?
+    };
+    Some(WorldPos::new(
+        target.x + velocity.0 * time,
+        target.y + velocity.1 * time,
+    ))
+}
+
+/// Closest two projectiles came to each other over the last `delta_time`,
+/// given where they are now and how fast they were going. Checking the whole
+/// step keeps fast shots from passing through each other between ticks.
+pub fn closest_approach(
+    a: WorldPos,
+    a_velocity: (f32, f32),
+    b: WorldPos,
+    b_velocity: (f32, f32),
+    delta_time: f32,
+) -> f32 {
+    let relative = (a.x - b.x, a.y - b.y);
+    let closing = (a_velocity.0 - b_velocity.0, a_velocity.1 - b_velocity.1);
+    let speed_sq = closing.0 * closing.0 + closing.1 * closing.1;
+    // Seconds back into the step where they were closest
+    let back = if speed_sq > 0.0 {
+        ((relative.0 * closing.0 + relative.1 * closing.1) / speed_sq).clamp(0.0, delta_time)
+    } else {
+        0.0
+    };
+    let (x, y) = (relative.0 - closing.0 * back, relative.1 - closing.1 * back);
+    (x * x + y * y).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_magazine_runs_dry_and_restocks() {
+        let mut battery = PointDefense::default();
+        for _ in 0..POINT_DEFENSE_MAGAZINE {
+            assert!(battery.fire());
+            assert!(!battery.fire(), "still cooling down");
+            battery.reload(POINT_DEFENSE_COOLDOWN);
+        }
+        assert_eq!(battery.ammo, 0);
+        assert!(!battery.fire());
+
+        battery.restock();
+        assert!(battery.fire());
+    }
+
+    #[test]
+    fn test_intercept_leads_a_crossing_shot() {
+        let origin = WorldPos::new(0.0, 0.0);
+        let target = WorldPos::new(300.0, -100.0);
+        let velocity = (0.0, 100.0);
+        let meet = intercept_point(origin, target, velocity, INTERCEPTOR_SPEED).unwrap();
+
+        // The interceptor and the shot arrive at the same moment
+        let shot_time = (meet.y - target.y) / velocity.1;
+        let interceptor_time = origin.distance_to(meet) / INTERCEPTOR_SPEED;
+        assert!(shot_time > 0.0);
+        assert!((shot_time - interceptor_time).abs() < 1e-3);
+
+        // Something running away faster than the interceptor can't be caught
+        assert!(intercept_point(origin, target, (1000.0, 0.0), 500.0).is_none());
+    }
+
+    #[test]
+    fn test_closest_approach_catches_shots_that_crossed_mid_step() {
+        // Head-on shots that passed through each other during the step
+        let a = WorldPos::new(10.0, 0.0);
+        let b = WorldPos::new(-10.0, 0.0);
+        let distance = closest_approach(a, (600.0, 0.0), b, (-600.0, 0.0), 0.1);
+        assert!(distance < 1e-3);
+
+        // Parallel shots never get closer than they are
+        let distance = closest_approach(a, (100.0, 0.0), b, (100.0, 0.0), 0.1);
+        assert!((distance - 20.0).abs() < 1e-3);
+    }
+}
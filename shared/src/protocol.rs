@@ -1,2 +1,93 @@
-// This file is no longer needed with the WebSocket approach
-// Protocol is now defined through JSON message enums in messages.rs
\ No newline at end of file
+//! Protocol version and optional features, agreed on when a client joins.
+//! The client says what it speaks in `JoinGame`; the server answers with the
+//! features both sides share in `JoinedGame`, or with `JoinRejected` when the
+//! two versions can't talk to each other.
+
+use crate::network_constants::{MIN_PROTOCOL_VERSION, PROTOCOL_VERSION};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ProtocolFeature {
+    /// MessagePack frames; without it the server sends JSON text
+    BinaryEncoding,
+    /// Frames with a header byte, deflated when large
+    Compression,
+    /// Push-to-talk audio on a second socket, keyed by `VoiceChannel`
+    Voice,
+    /// Something a newer build supports that this one doesn't know about
+    #[serde(other)]
+    Unknown,
+}
+
+/// Everything this build supports, on either end of the connection
+pub const SUPPORTED_FEATURES: &[ProtocolFeature] = &[
+    ProtocolFeature::BinaryEncoding,
+    ProtocolFeature::Compression,
+    ProtocolFeature::Voice,
+];
+
+/// Why a client speaking `version` can't join this server, if it can't
+pub fn version_mismatch(version: u32) -> Option<String> {
+    if version < MIN_PROTOCOL_VERSION {
+        Some(format!(
+            "Your client is out of date (protocol {version}, this server needs \
+             {MIN_PROTOCOL_VERSION}-{PROTOCOL_VERSION}). Please update it to play here."
+        ))
+    } else if version > PROTOCOL_VERSION {
+        Some(format!(
+            "This server is older than your client (protocol {PROTOCOL_VERSION}, yours is \
+             {version}). Try another server or an older client."
+        ))
+    } else {
+        None
+    }
+}
+
+/// The requested features this build also supports
+pub fn negotiate_features(requested: &[ProtocolFeature]) -> Vec<ProtocolFeature> {
+    SUPPORTED_FEATURES
+        .iter()
+        .copied()
+        .filter(|feature| requested.contains(feature))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_versions_outside_the_supported_range_are_refused() {
+        assert!(version_mismatch(PROTOCOL_VERSION).is_none());
+        assert!(version_mismatch(MIN_PROTOCOL_VERSION).is_none());
+        // Clients from before versioning send nothing, which reads as 0
+        assert!(version_mismatch(0).is_some());
+        assert!(version_mismatch(PROTOCOL_VERSION + 1).is_some());
+    }
+
+    #[test]
+    fn test_unknown_features_are_tolerated_and_dropped() {
+        #[derive(Serialize)]
+        struct NewerClient {
+            features: Vec<&'static str>,
+        }
+        #[derive(Deserialize)]
+        struct Decoded {
+            features: Vec<ProtocolFeature>,
+        }
+
+        let bytes = rmp_serde::to_vec_named(&NewerClient {
+            features: vec!["Compression", "TimeTravel"],
+        })
+        .unwrap();
+        let decoded: Decoded = rmp_serde::from_slice(&bytes).unwrap();
+        assert_eq!(
+            decoded.features,
+            vec![ProtocolFeature::Compression, ProtocolFeature::Unknown]
+        );
+        assert_eq!(
+            negotiate_features(&decoded.features),
+            vec![ProtocolFeature::Compression]
+        );
+    }
+}
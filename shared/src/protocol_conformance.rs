@@ -0,0 +1,970 @@
+//! Protocol conformance: every `ClientMessage` and `ServerMessage` variant
+//! has to come back unchanged through JSON, which older and text-only
+//! clients still speak, and through the MessagePack frames the native and
+//! WASM clients send and receive. A variant added without a sample here
+//! fails `test_every_variant_has_a_sample`.
+
+use crate::*;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+use uuid::Uuid;
+
+fn id(n: u128) -> Uuid {
+    Uuid::from_u128(n)
+}
+
+fn tile(x: i32, y: i32) -> TilePos {
+    TilePos::new(x, y)
+}
+
+fn world(x: f32, y: f32) -> WorldPos {
+    WorldPos::new(x, y)
+}
+
+fn inside(mech: u128) -> PlayerLocation {
+    PlayerLocation::InsideMech {
+        mech_id: id(mech),
+        pos: MechInteriorPos::new(2, tile(3, 4)),
+    }
+}
+
+fn stock() -> HashMap<ResourceType, u32> {
+    HashMap::from([(ResourceType::ScrapMetal, 4), (ResourceType::Wiring, 1)])
+}
+
+fn inventory() -> Inventory {
+    let mut inventory = Inventory::default();
+    inventory.add(ItemType::Resource(ResourceType::Batteries));
+    inventory.add(ItemType::Scanner);
+    inventory
+}
+
+fn player_state(n: u128) -> PlayerState {
+    PlayerState {
+        id: id(n),
+        name: format!("Player {n}"),
+        team: TeamId::Blue,
+        location: inside(900),
+        inventory: inventory(),
+        operating_station: Some(id(901)),
+        held_tool: Some(ToolType::Extinguisher),
+        health: 80,
+        facing: Facing::West,
+        afk: true,
+    }
+}
+
+fn upgrades() -> MechUpgrades {
+    MechUpgrades {
+        laser_level: 2,
+        projectile_level: 1,
+        engine_level: 3,
+        shield_level: 1,
+        ecm_level: 1,
+    }
+}
+
+fn mech_state() -> MechState {
+    MechState {
+        id: id(900),
+        team: TeamId::Red,
+        position: tile(10, 12),
+        world_position: world(160.0, 192.0),
+        health: 70,
+        max_health: 100,
+        shield: 25,
+        upgrades: upgrades(),
+        drive: MechDrive::default(),
+        mass: MechMass::new([(4.0, 2), (2.0, 1)], &upgrades(), 5, 2),
+        fuel: 42.5,
+        turret: MechTurret::default(),
+        point_defense: PointDefense::default(),
+        stations: vec![StationState {
+            id: id(901),
+            station_type: StationType::WeaponLaser,
+            floor: 1,
+            position: tile(2, 2),
+            size: StationSize {
+                width: 2,
+                height: 1,
+            },
+            operated_by: Some(id(1)),
+            health: 30,
+            max_health: 40,
+            upgrade_level: 2,
+        }],
+        resource_inventory: stock(),
+        delivery_score: 9,
+        fires: vec![fire()],
+        alarm: Some(alarm()),
+    }
+}
+
+fn alarm() -> SabotageAlarm {
+    SabotageAlarm {
+        station_id: id(901),
+        intruder: id(2),
+        remaining: 6.5,
+    }
+}
+
+fn fire() -> FireState {
+    FireState {
+        floor: 0,
+        position: tile(5, 5),
+        intensity: 0.75,
+    }
+}
+
+fn pacing_zone() -> PacingZone {
+    PacingZone {
+        id: id(700),
+        kind: PacingZoneKind::PlayArea {
+            target_radius: 300.0,
+            shrink_speed: 2.5,
+        },
+        center: world(500.0, 400.0),
+        radius: 800.0,
+        remaining: Some(30.0),
+    }
+}
+
+fn game_mode_hud() -> GameModeHud {
+    GameModeHud {
+        mode: GameModeKind::ResourceRace,
+        objective: "Deliver resources".to_string(),
+        teams: vec![TeamObjective {
+            team: TeamId::Red,
+            label: "Delivered".to_string(),
+            value: 3,
+            target: 20,
+        }],
+        time_left: Some(120),
+        goal: Some(world(10.0, 20.0)),
+        result: Some(MatchResult {
+            winner: None,
+            reason: "Time ran out".to_string(),
+        }),
+    }
+}
+
+fn game_state(players: impl IntoIterator<Item = u128>) -> ServerMessage {
+    let mech = mech_state();
+    ServerMessage::GameState {
+        players: players
+            .into_iter()
+            .map(|n| (id(n), player_state(n)))
+            .collect(),
+        mechs: HashMap::from([(mech.id, mech)]),
+        resources: vec![ResourceState {
+            id: id(600),
+            position: tile(7, 8),
+            resource_type: ResourceType::ComputerComponents,
+        }],
+        projectiles: vec![ProjectileState {
+            id: id(500),
+            position: world(1.5, 2.5),
+            velocity: (-3.0, 4.0),
+            damage: 15,
+            owner_mech_id: id(900),
+            weapon_type: WeaponType::Missile,
+        }],
+        pacing_zones: vec![pacing_zone()],
+        ground_items: vec![GroundItemState {
+            id: id(400),
+            item: ItemType::Grenade,
+            position: world(33.0, 44.0),
+        }],
+        structures: vec![TeamStructure {
+            kind: StructureKind::RepairBay,
+            team: TeamId::Blue,
+            position: tile(40, 40),
+            width: 6,
+            height: 6,
+        }],
+        game_mode: Some(game_mode_hud()),
+        environment: Environment::default(),
+    }
+}
+
+fn client_samples() -> Vec<ClientMessage> {
+    vec![
+        ClientMessage::JoinGame {
+            player_name: "Pilot \u{1F916} \"quoted\"".to_string(),
+            preferred_team: Some(TeamId::Blue),
+            protocol_version: 7,
+            features: vec![ProtocolFeature::BinaryEncoding, ProtocolFeature::Voice],
+            password: Some(String::new()),
+        },
+        ClientMessage::PlayerInput {
+            movement: (-1.0, 0.5),
+            action_key_pressed: true,
+        },
+        ClientMessage::StationInput {
+            button_index: u8::MAX,
+        },
+        ClientMessage::EngineControl {
+            movement: (0.0, -1.0),
+        },
+        ClientMessage::ExitMech,
+        ClientMessage::ExitStation,
+        ClientMessage::FloorTransition {
+            current_position: tile(-3, 4),
+            target_floor: 2,
+            stairway_position: tile(i32::MIN, i32::MAX),
+        },
+        ClientMessage::ChatMessage {
+            message: "gg\nwp".to_string(),
+        },
+        ClientMessage::Annotate {
+            shape: AnnotationShape::Arrow {
+                from: world(0.0, 0.0),
+                to: world(128.5, -64.25),
+            },
+            color: AnnotationColor::Cyan,
+            audience: AnnotationAudience::Team(TeamId::Blue),
+            ttl: 8.0,
+        },
+        ClientMessage::DevCommand {
+            command: DevCommand::GiveResource {
+                resource_type: ResourceType::Batteries,
+                amount: u32::MAX,
+            },
+        },
+        ClientMessage::SelectInventorySlot { slot: 3 },
+        ClientMessage::DropItem,
+        ClientMessage::TurretAim { angle: -2.5 },
+        ClientMessage::MeleeAttack,
+        ClientMessage::UseItem,
+        ClientMessage::Dash {
+            direction: Facing::North,
+        },
+        ClientMessage::PlaceBarricade {
+            position: tile(1, 2),
+        },
+        ClientMessage::RemoveBarricade {
+            position: tile(2, 1),
+        },
+        ClientMessage::Look {
+            facing: Facing::NorthEast,
+        },
+        ClientMessage::Spectate {
+            protocol_version: 0,
+            features: Vec::new(),
+        },
+        ClientMessage::VerboseState { enabled: true },
+        ClientMessage::Subscribe {
+            channels: vec![StateChannel::Mechs, StateChannel::Snapshots],
+        },
+        ClientMessage::PickRole {
+            role: Some(CrewRole::Gunner),
+        },
+        ClientMessage::PickRole { role: None },
+        ClientMessage::SetReady { ready: true },
+    ]
+}
+
+fn server_samples() -> Vec<ServerMessage> {
+    vec![
+        ServerMessage::JoinedGame {
+            player_id: id(1),
+            team: TeamId::Red,
+            spawn_position: tile(5, 6),
+            features: vec![ProtocolFeature::Compression],
+            sight: SightShape::default(),
+        },
+        ServerMessage::JoinRejected {
+            reason: "Too old".to_string(),
+            server_version: 3,
+        },
+        ServerMessage::PasswordRequired { wrong: true },
+        ServerMessage::VoiceChannel { key: Uuid::max() },
+        ServerMessage::SessionResume { token: id(7) },
+        ServerMessage::PlayerDisconnected { player_id: id(1) },
+        ServerMessage::PlayerAfk {
+            player_id: id(1),
+            afk: false,
+        },
+        ServerMessage::SpectatingGame {
+            features: vec![ProtocolFeature::BinaryEncoding],
+        },
+        ServerMessage::CameraSuggestion {
+            target: id(900),
+            reason: DirectorFocus::UnderFire,
+        },
+        game_state([1, 2]),
+        ServerMessage::MechFloorData {
+            mech_id: id(900),
+            interior: MechInterior::default(),
+            stations: HashMap::from([(
+                id(901),
+                MechStation {
+                    id: id(901),
+                    station_type: StationType::Radar,
+                    floor: 2,
+                    position: tile(3, 3),
+                    size: StationSize {
+                        width: 1,
+                        height: 1,
+                    },
+                    operated_by: None,
+                },
+            )]),
+        },
+        ServerMessage::FloorTransitionComplete {
+            player_id: id(1),
+            mech_id: id(900),
+            old_floor: 0,
+            new_floor: 1,
+            new_position: tile(4, 4),
+        },
+        ServerMessage::FloorTransitionFailed {
+            player_id: id(1),
+            reason: "Blocked".to_string(),
+        },
+        ServerMessage::MechInteriorUpdate {
+            mech_id: id(900),
+            floor: 1,
+            tile_updates: vec![
+                (
+                    tile(1, 1),
+                    TileVisual::Floor {
+                        material: Material::Reinforced,
+                        wear: 3,
+                    },
+                ),
+                (
+                    tile(2, 1),
+                    TileVisual::Window {
+                        broken: true,
+                        facing: Direction::Left,
+                    },
+                ),
+            ],
+            station_changes: vec![
+                StationUpdate::Damaged {
+                    station_id: id(901),
+                    damage_amount: 5,
+                },
+                StationUpdate::StatusChanged {
+                    station_id: id(901),
+                    new_status: "Offline".to_string(),
+                },
+            ],
+        },
+        ServerMessage::PlayerMoved {
+            player_id: id(1),
+            location: PlayerLocation::OutsideWorld(world(-16.0, 1e6)),
+        },
+        ServerMessage::PlayerFacing {
+            player_id: id(1),
+            facing: Facing::SouthWest,
+        },
+        ServerMessage::PlayerPickedUpResource {
+            player_id: id(1),
+            resource_type: ResourceType::Wiring,
+            resource_id: id(600),
+        },
+        ServerMessage::PlayerDroppedResource {
+            player_id: id(1),
+            resource_type: ResourceType::ScrapMetal,
+            position: tile(9, 9),
+        },
+        ServerMessage::InventoryUpdated {
+            player_id: id(1),
+            inventory: inventory(),
+        },
+        ServerMessage::ItemDropped {
+            player_id: id(1),
+            item_id: id(400),
+            item: ItemType::Resource(ResourceType::Wiring),
+            position: world(8.0, 9.0),
+        },
+        ServerMessage::ItemPickedUp {
+            player_id: id(1),
+            item_id: id(400),
+        },
+        ServerMessage::PlayerEnteredStation {
+            player_id: id(1),
+            station_id: id(901),
+        },
+        ServerMessage::PlayerExitedStation {
+            player_id: id(1),
+            station_id: id(901),
+        },
+        ServerMessage::StationLayout {
+            station_id: id(901),
+            name: "Laser".to_string(),
+            description: "Fires a laser".to_string(),
+            buttons: vec![StationButtonInfo {
+                index: 0,
+                label: "Fire".to_string(),
+                description: "Fire the laser".to_string(),
+                cooldown_seconds: 1.5,
+                resource_cost: stock(),
+            }],
+        },
+        ServerMessage::MechMoved {
+            mech_id: id(900),
+            position: tile(11, 12),
+            world_position: world(176.0, 192.0),
+            drive: MechDrive::default(),
+        },
+        ServerMessage::MechDamaged {
+            mech_id: id(900),
+            damage: 12,
+            health_remaining: 58,
+            position: world(170.0, 200.0),
+            hit: Some(HitDirection {
+                arc: ArmorArc::Rear,
+                from: 1.5,
+            }),
+        },
+        ServerMessage::InteriorImpact {
+            mech_id: id(900),
+            severity: 0.5,
+            hit: Some(HitDirection {
+                arc: ArmorArc::Front,
+                from: 0.0,
+            }),
+        },
+        ServerMessage::MechsCollided {
+            mech_ids: (id(900), id(901)),
+            position: world(320.0, 200.0),
+            closing_speed: 2.5,
+        },
+        ServerMessage::PlayerRunOver {
+            player_id: id(1),
+            mech_id: id(900),
+            position: world(12.0, 40.0),
+        },
+        ServerMessage::MechShieldChanged {
+            mech_id: id(900),
+            shield: 0,
+        },
+        ServerMessage::MechFuelChanged {
+            mech_id: id(900),
+            fuel: 0.0,
+        },
+        ServerMessage::ShieldImpact {
+            mech_id: id(900),
+            position: world(150.0, 180.0),
+            absorbed: 10,
+            shield_remaining: 0,
+            collapsed: true,
+        },
+        ServerMessage::MechUpgraded {
+            mech_id: id(900),
+            upgrade_type: UpgradeType::Ecm,
+            new_level: 1,
+        },
+        ServerMessage::MechRepaired {
+            mech_id: id(900),
+            health_restored: 10,
+            new_health: 68,
+        },
+        ServerMessage::TurretAimed {
+            mech_id: id(900),
+            turret: MechTurret::default(),
+        },
+        ServerMessage::PointDefenseChanged {
+            mech_id: id(900),
+            point_defense: PointDefense::default(),
+        },
+        ServerMessage::RadarSweep {
+            mech_id: id(900),
+            team: TeamId::Red,
+            blips: vec![world(1.0, 2.0), world(3.0, 4.0)],
+            duration: 5.0,
+        },
+        ServerMessage::ScannerPing {
+            player_id: id(1),
+            origin: world(10.0, 10.0),
+            contacts: vec![(world(20.0, 10.0), ResourceType::Batteries)],
+            bearings: vec![0.0, -3.1],
+            duration: 4.0,
+        },
+        ServerMessage::WeaponFired {
+            mech_id: id(900),
+            weapon_type: StationType::WeaponProjectile,
+            target_position: tile(30, 30),
+            projectile_id: Some(id(500)),
+        },
+        ServerMessage::MechStompWindup {
+            mech_id: id(900),
+            windup: 0.5,
+        },
+        ServerMessage::MechStomped {
+            mech_id: id(900),
+            position: world(5.0, 5.0),
+            radius: 96.0,
+        },
+        ServerMessage::ProjectileHit {
+            projectile_id: id(500),
+            hit_mech_id: None,
+            damage_dealt: 0,
+            position: world(6.0, 7.0),
+        },
+        ServerMessage::ProjectileExpired {
+            projectile_id: id(500),
+        },
+        ServerMessage::ProjectileIntercepted {
+            projectile_id: id(500),
+            interceptor_id: id(501),
+            position: world(7.0, 8.0),
+        },
+        ServerMessage::EffectCreated {
+            effect_id: id(300),
+            effect_type: "emp_burst".to_string(),
+            position: world(1.0, 1.0),
+            duration: 0.25,
+        },
+        ServerMessage::EffectExpired { effect_id: id(300) },
+        ServerMessage::ResourceSpawned {
+            resource_id: id(600),
+            position: tile(7, 8),
+            resource_type: ResourceType::ScrapMetal,
+        },
+        ServerMessage::ResourceCollected {
+            resource_id: id(600),
+            player_id: id(1),
+        },
+        ServerMessage::ResourceDestroyed {
+            resource_id: id(600),
+        },
+        ServerMessage::ResourceDelivered {
+            player_id: id(1),
+            mech_id: id(900),
+            resource_type: ResourceType::Batteries,
+            delivery_score: 10,
+        },
+        ServerMessage::MechInventoryChanged {
+            mech_id: id(900),
+            inventory: stock(),
+        },
+        ServerMessage::ChatMessage {
+            player_id: id(1),
+            player_name: "Player 1".to_string(),
+            message: String::new(),
+            team_only: true,
+        },
+        ServerMessage::AnnotationDrawn {
+            annotation: Annotation {
+                id: id(1),
+                author: id(2),
+                shape: AnnotationShape::Circle {
+                    center: world(16.0, 32.0),
+                    radius: 48.0,
+                },
+                color: AnnotationColor::Yellow,
+                ttl: ANNOTATION_MAX_TTL,
+            },
+        },
+        ServerMessage::PlayerKilled {
+            player_id: id(2),
+            killer: Some(id(1)),
+            respawn_position: world(0.0, 0.0),
+        },
+        ServerMessage::TileUpdate {
+            position: tile(1, 2),
+            visual: TileVisual::Station {
+                station_type: StationType::Shield,
+                active: true,
+                level: 3,
+            },
+        },
+        ServerMessage::TileBatch {
+            tiles: vec![
+                (
+                    tile(0, 0),
+                    TileVisual::Turret {
+                        facing: Direction::Up,
+                        firing: false,
+                    },
+                ),
+                (
+                    tile(0, 1),
+                    TileVisual::Locker {
+                        tool: ToolType::RepairTool,
+                    },
+                ),
+            ],
+        },
+        ServerMessage::VisibilityUpdate {
+            visible_tiles: vec![(
+                tile(4, 4),
+                TileVisual::Wall {
+                    material: Material::Damaged,
+                },
+            )],
+            player_position: world(64.0, 64.0),
+        },
+        ServerMessage::TeamVisionUpdate {
+            visible_tiles: vec![tile(1, 1), tile(-1, -1)],
+        },
+        ServerMessage::CollisionShapes {
+            shapes: CollisionShapes {
+                tick: 7,
+                players: vec![PlayerHitbox {
+                    player_id: id(1),
+                    center: world(2.0, 3.0),
+                    radius: 6.0,
+                    aboard: Some((id(900), 1)),
+                }],
+                ..Default::default()
+            },
+        },
+        ServerMessage::StatusEffectsChanged {
+            entity_id: id(1),
+            effects: vec![StatusEffect {
+                kind: StatusEffectKind::Slowed,
+                remaining: 2.0,
+                stacks: 2,
+                magnitude: 0.25,
+            }],
+        },
+        ServerMessage::MechFiresUpdated {
+            mech_id: id(900),
+            fires: vec![fire()],
+        },
+        ServerMessage::HullBreachUpdated {
+            mech_id: id(900),
+            floor: 0,
+            position: tile(0, 5),
+            breached: true,
+            max_health: 90,
+        },
+        ServerMessage::BarricadeUpdated {
+            mech_id: id(900),
+            floor: 1,
+            position: tile(3, 3),
+            barricade: Some(Barricade {
+                team: TeamId::Red,
+                health: 40,
+            }),
+        },
+        ServerMessage::PlayerToolChanged {
+            player_id: id(1),
+            tool: None,
+        },
+        ServerMessage::RepairProgress {
+            player_id: id(1),
+            mech_id: id(900),
+            progress: Some(0.5),
+        },
+        ServerMessage::PlayerAttacked {
+            attacker: id(1),
+            target: id(2),
+            damage: 20,
+            health: 0,
+        },
+        ServerMessage::PlayerDashed {
+            player_id: id(1),
+            from: PlayerLocation::OutsideWorld(world(0.0, 0.0)),
+            to: inside(900),
+        },
+        ServerMessage::SabotageProgress {
+            player_id: id(2),
+            station_id: id(901),
+            progress: None,
+        },
+        ServerMessage::MechAlarm {
+            mech_id: id(900),
+            alarm: Some(alarm()),
+        },
+        ServerMessage::AchievementUnlocked {
+            player_id: id(1),
+            achievement: Achievement::FirstBlood,
+        },
+        ServerMessage::TutorialInstruction {
+            player_id: id(1),
+            text: "Press E".to_string(),
+            step: 1,
+            total: 5,
+        },
+        ServerMessage::TutorialComplete { player_id: id(1) },
+        ServerMessage::ArenaMapData {
+            map: ArenaMap::default(),
+        },
+        ServerMessage::HazardsUpdated {
+            hazards: vec![MapHazard {
+                position: tile(20, 20),
+                kind: HazardKind::Mine,
+            }],
+        },
+        ServerMessage::DecalLayer {
+            decals: vec![Decal {
+                position: tile(6, 6),
+                kind: DecalKind::Scorch,
+            }],
+        },
+        ServerMessage::DecalAdded {
+            position: tile(7, 7),
+            kind: DecalKind::Debris,
+        },
+        ServerMessage::HangarUpdated {
+            crew: vec![
+                HangarCrew {
+                    player_id: id(1),
+                    name: "Pilot".to_string(),
+                    team: TeamId::Red,
+                    role: Some(CrewRole::Pilot),
+                    ready: true,
+                },
+                HangarCrew {
+                    player_id: id(2),
+                    name: String::new(),
+                    team: TeamId::Blue,
+                    role: None,
+                    ready: false,
+                },
+            ],
+            time_left: 42.5,
+        },
+        ServerMessage::MatchStarted,
+        ServerMessage::GameModeUpdated {
+            hud: game_mode_hud(),
+        },
+        ServerMessage::PacingEventStarted {
+            announcement: "Supply drop!".to_string(),
+            zone: PacingZone {
+                kind: PacingZoneKind::DamageBoost { multiplier: 1.5 },
+                remaining: None,
+                ..pacing_zone()
+            },
+        },
+        ServerMessage::PacingZonesUpdated {
+            zones: vec![PacingZone {
+                kind: PacingZoneKind::ResourceSurge,
+                ..pacing_zone()
+            }],
+        },
+        ServerMessage::WeatherChanged {
+            environment: Environment::default(),
+        },
+        ServerMessage::Subscribed {
+            channels: vec![StateChannel::Snapshots, StateChannel::Match],
+        },
+        ServerMessage::ServerLoad {
+            level: ServerLoad::Overloaded,
+        },
+        ServerMessage::ServerShutdown {
+            reason: "Restarting".to_string(),
+            seconds: 30,
+        },
+        ServerMessage::StateChecksum {
+            tick: 9000,
+            hashes: StateHashes {
+                players: u64::MAX,
+                mechs: 0,
+                resources: 0xcbf2_9ce4_8422_2325,
+            },
+        },
+        ServerMessage::DevCommandResult {
+            success: false,
+            message: "Unknown command".to_string(),
+        },
+        ServerMessage::Error {
+            code: ErrorCode::default(),
+            message: "Something broke".to_string(),
+        },
+    ]
+}
+
+/// Every variant name serde knows for `T`, read from the error it gives
+/// for a tag it doesn't
+fn variant_names<T: DeserializeOwned>() -> BTreeSet<String> {
+    let error = serde_json::from_str::<T>(r#"{"type":"NoSuchVariant"}"#)
+        .err()
+        .expect("a made-up tag shouldn't decode")
+        .to_string();
+    let listed = error
+        .split_once("expected one of ")
+        .map(|(_, listed)| listed)
+        .expect("serde lists the variants it expects");
+    listed
+        .split(", ")
+        .filter_map(|name| name.split('`').nth(1))
+        .map(str::to_string)
+        .collect()
+}
+
+fn tag(message: &impl Serialize) -> String {
+    serde_json::to_value(message).unwrap()["type"]
+        .as_str()
+        .expect("messages are tagged with their type")
+        .to_string()
+}
+
+/// What a message holds, in a form that compares equal whatever order its
+/// maps iterate in
+fn contents(message: &impl Serialize) -> Value {
+    serde_json::to_value(message).unwrap()
+}
+
+fn assert_client_round_trips(message: &ClientMessage) {
+    let name = tag(message);
+    let expected = contents(message);
+
+    let text = serde_json::to_string(message).unwrap();
+    let from_json: ClientMessage = serde_json::from_str(&text)
+        .unwrap_or_else(|e| panic!("{name} doesn't come back from JSON: {e}"));
+    assert_eq!(contents(&from_json), expected, "{name} changed in JSON");
+
+    let bytes = serialize_client_message(message).unwrap();
+    let from_binary = deserialize_client_message(&bytes)
+        .unwrap_or_else(|e| panic!("{name} doesn't come back from MessagePack: {e}"));
+    assert_eq!(
+        contents(&from_binary),
+        expected,
+        "{name} changed in MessagePack"
+    );
+}
+
+fn assert_server_round_trips(message: &ServerMessage) {
+    let name = message.type_name();
+    let expected = contents(message);
+
+    let text = serde_json::to_string(message).unwrap();
+    let from_json: ServerMessage = serde_json::from_str(&text)
+        .unwrap_or_else(|e| panic!("{name} doesn't come back from JSON: {e}"));
+    assert_eq!(contents(&from_json), expected, "{name} changed in JSON");
+
+    for compression in [false, true] {
+        let frame = encode_server_frame(message, compression).unwrap();
+        let from_binary = decode_server_frame(&frame, compression).unwrap_or_else(|e| {
+            panic!("{name} doesn't come back from MessagePack (compression {compression}): {e}")
+        });
+        assert_eq!(
+            contents(&from_binary),
+            expected,
+            "{name} changed in MessagePack (compression {compression})"
+        );
+    }
+}
+
+#[test]
+fn test_every_variant_has_a_sample() {
+    let names = variant_names::<ClientMessage>();
+    assert!(names.contains("JoinGame") && names.contains("VerboseState"));
+    let sampled: BTreeSet<String> = client_samples().iter().map(tag).collect();
+    let missing: Vec<_> = names.difference(&sampled).cloned().collect();
+    assert!(
+        missing.is_empty(),
+        "ClientMessage samples missing {missing:?}"
+    );
+
+    let sampled: BTreeSet<String> = server_samples()
+        .iter()
+        .map(|message| message.type_name().to_string())
+        .collect();
+    let names = variant_names::<ServerMessage>();
+    assert!(names.contains("JoinedGame") && names.contains("Error"));
+    let missing: Vec<_> = names.difference(&sampled).cloned().collect();
+    assert!(
+        missing.is_empty(),
+        "ServerMessage samples missing {missing:?}"
+    );
+}
+
+#[test]
+fn test_every_client_message_round_trips() {
+    for message in client_samples() {
+        assert_client_round_trips(&message);
+    }
+}
+
+#[test]
+fn test_every_server_message_round_trips() {
+    for message in server_samples() {
+        assert_server_round_trips(&message);
+    }
+}
+
+#[test]
+fn test_huge_uuid_maps_round_trip() {
+    // Thousands of players with ids from the top of the range, deflated well
+    // past the compression threshold
+    let message = game_state((0..5_000).map(|n| u128::MAX - n));
+    assert_server_round_trips(&message);
+
+    let mut inventory = HashMap::new();
+    for resource_type in ResourceType::ALL {
+        inventory.insert(resource_type, u32::MAX);
+    }
+    assert_server_round_trips(&ServerMessage::MechInventoryChanged {
+        mech_id: Uuid::max(),
+        inventory,
+    });
+}
+
+#[test]
+fn test_non_finite_floats_survive_only_messagepack() {
+    let odd = [
+        f32::NAN,
+        f32::INFINITY,
+        f32::NEG_INFINITY,
+        -0.0,
+        f32::MIN_POSITIVE,
+    ];
+    let message = ServerMessage::ScannerPing {
+        player_id: id(1),
+        origin: world(f32::NAN, f32::INFINITY),
+        contacts: Vec::new(),
+        bearings: odd.to_vec(),
+        duration: f32::MAX,
+    };
+
+    // MessagePack keeps every bit
+    for compression in [false, true] {
+        let frame = encode_server_frame(&message, compression).unwrap();
+        let ServerMessage::ScannerPing {
+            origin,
+            bearings,
+            duration,
+            ..
+        } = decode_server_frame(&frame, compression).unwrap()
+        else {
+            panic!("Expected ScannerPing");
+        };
+        assert!(origin.x.is_nan() && origin.y == f32::INFINITY);
+        let bits = |values: &[f32]| values.iter().map(|v| v.to_bits()).collect::<Vec<_>>();
+        assert_eq!(bits(&bearings), bits(&odd));
+        assert_eq!(duration, f32::MAX);
+    }
+    let input = ClientMessage::TurretAim { angle: f32::NAN };
+    let bytes = serialize_client_message(&input).unwrap();
+    assert!(matches!(
+        deserialize_client_message(&bytes).unwrap(),
+        ClientMessage::TurretAim { angle } if angle.is_nan()
+    ));
+
+    // JSON has no NaN or infinity; they go out as null and the message is
+    // refused rather than read back as something else
+    let text = serde_json::to_string(&message).unwrap();
+    assert!(serde_json::from_str::<ServerMessage>(&text).is_err());
+    let text = serde_json::to_string(&input).unwrap();
+    assert!(serde_json::from_str::<ClientMessage>(&text).is_err());
+}
+
+#[test]
+fn test_truncated_and_mistagged_frames_are_refused() {
+    for message in server_samples() {
+        let frame = encode_server_frame(&message, false).unwrap();
+        for cut in [1, frame.len() / 2, frame.len() - 1] {
+            assert!(
+                decode_server_frame(&frame[..cut], false).is_err(),
+                "{} decoded from {cut} of {} bytes",
+                message.type_name(),
+                frame.len()
+            );
+        }
+    }
+
+    let unknown = serde_json::json!({ "type": "TimeTravel", "year": 1985 });
+    assert!(serde_json::from_value::<ClientMessage>(unknown.clone()).is_err());
+    assert!(serde_json::from_value::<ServerMessage>(unknown).is_err());
+    assert!(deserialize_client_message(&[]).is_err());
+}
@@ -8,7 +8,39 @@ pub const OXYGEN_TETHER_WIDTH: f32 = 2.0;
 pub const OXYGEN_TETHER_OPACITY: f32 = 0.6;
 pub const OXYGEN_DANGER_DISTANCE: f32 = 10.0; // tiles
 pub const WEAPON_EFFECT_DURATION: f32 = 1.0; // seconds
+pub const PREDICTION_CONFIRM_TIMEOUT: f32 = 0.5; // seconds a predicted shot waits for the server
+pub const PREDICTION_FADE_DURATION: f32 = 0.3; // seconds an unconfirmed shot takes to fade out
+pub const DAMAGE_NUMBER_DURATION: f32 = 1.0; // seconds
+pub const DAMAGE_NUMBER_RISE_SPEED: f32 = 40.0; // pixels per second
+pub const DAMAGE_NUMBER_FONT_SIZE: f32 = 24.0;
+pub const HIT_FLASH_DURATION: f32 = 0.15; // seconds
+pub const HIT_FLASH_RADIUS: f32 = 14.0; // pixels
+pub const BIG_HIT_DAMAGE: u32 = 20; // hits on our own mech at least this big shake the screen
+pub const HIT_SHAKE_INTENSITY: f32 = 6.0; // pixels
+pub const HIT_SHAKE_DURATION: f32 = 0.3; // seconds
+pub const IMPACT_SHAKE_INTENSITY: f32 = 14.0; // pixels, for the most violent impact felt from inside
+pub const IMPACT_SHAKE_DURATION: f32 = 0.5; // seconds
+pub const HIT_INDICATOR_DURATION: f32 = 1.5; // seconds the arrow toward a hit on our mech lingers
+pub const HIT_INDICATOR_SIZE: f32 = 18.0; // pixels from the arrow's tip to its base
+pub const SHIELD_BUBBLE_ALPHA: f32 = 0.12; // fill opacity of a fully charged shield bubble
+pub const SHIELD_RIPPLE_DURATION: f32 = 0.4; // seconds
+pub const SHIELD_RIPPLE_RADIUS: f32 = 24.0; // pixels an impact ripple spreads over the bubble
+pub const SHIELD_COLLAPSE_DURATION: f32 = 0.6; // seconds for a broken bubble to fold in
+pub const EMP_SPARKS_PER_STATION: usize = 3;
+pub const EMP_SPARK_LENGTH: f32 = 6.0; // pixels per spark segment
+pub const STOMP_WARNING_PULSE: f32 = 10.0; // radians per second the windup warning ring throbs at
+pub const STOMP_SHOCKWAVE_DURATION: f32 = 0.5; // seconds for a landed stomp's ring to spread out
+pub const STOMP_SHAKE_INTENSITY: f32 = 10.0; // pixels
+pub const STOMP_SHAKE_DURATION: f32 = 0.4; // seconds
+pub const RAM_SHAKE_INTENSITY: f32 = 8.0; // pixels
+pub const RAM_SHAKE_DURATION: f32 = 0.35; // seconds
 pub const GRASS_VARIATION: f32 = 0.02;
+pub const PACING_ANNOUNCEMENT_DURATION: f32 = 5.0; // seconds
+pub const DELIVERY_HIGHLIGHT_DURATION: f32 = 2.0; // seconds a fresh drop-off glows in the cargo panel
+pub const PACING_ZONE_OUTLINE_WIDTH: f32 = 3.0;
+pub const SAFE_ZONE_OUTLINE_WIDTH: f32 = 2.0;
+pub const SAFE_ZONE_FILL_ALPHA: f32 = 0.08;
+pub const TEAM_VISION_VISIBILITY: f32 = 0.6; // fog strength lifted by teammates' vision
 
 // ===== Colors (RGB values) =====
 pub const OXYGEN_DANGER_COLOR: (f32, f32, f32) = (0.8, 0.2, 0.2);
@@ -36,9 +68,14 @@ pub const DOOR_OVERLAP_RATIO: f32 = 0.2;
 pub const DOOR_INTERIOR_PADDING: f32 = 4.0;
 pub const TEAM_COLOR_STRIP_HEIGHT: f32 = 8.0;
 pub const DOOR_THIRD_DIVISOR: f32 = 3.0;
+pub const BOARDING_PROMPT_RANGE: f32 = 4.0; // tiles from a breached enemy door
 
 // ===== Entity Rendering =====
 pub const RESOURCE_CIRCLE_RADIUS_DIVISOR: f32 = 3.0; // TILE_SIZE / 3.0
+pub const RESOURCE_GLOW_UNCOMMON_RADIUS: f32 = 0.5; // tiles the halo round an uncommon resource reaches
+pub const RESOURCE_GLOW_RARE_RADIUS: f32 = 0.8; // tiles the halo round a rare resource reaches
+pub const RESOURCE_GLOW_ALPHA: f32 = 0.35;
+pub const RESOURCE_GLOW_PULSE_SPEED: f32 = 3.0; // radians per second a rare resource's halo breathes at
 pub const PROJECTILE_RADIUS: f32 = 5.0;
 pub const LADDER_CIRCLE_RADIUS_DIVISOR: f32 = 4.0; // TILE_SIZE / 4.0
 pub const PLAYER_CIRCLE_RADIUS_DIVISOR: f32 = 5.0; // TILE_SIZE / 5.0
@@ -87,11 +124,23 @@ pub const SHIELD_BAR_Y_OFFSET: f32 = 130.0;
 
 // ===== Resource Panel =====
 pub const RESOURCE_PANEL_WIDTH: f32 = 200.0;
-pub const RESOURCE_PANEL_HEIGHT: f32 = 120.0;
+pub const RESOURCE_PANEL_HEIGHT: f32 = 160.0;
 pub const RESOURCE_PANEL_OFFSET_X: f32 = 10.0;
 pub const RESOURCE_PANEL_OFFSET_Y: f32 = 100.0;
 pub const RESOURCE_TEXT_SPACING: f32 = 20.0;
 
+// ===== Inventory Strip =====
+pub const INVENTORY_SLOT_SIZE: f32 = 40.0;
+pub const INVENTORY_SLOT_GAP: f32 = 6.0;
+pub const INVENTORY_STRIP_BOTTOM_OFFSET: f32 = 70.0; // clears the control hints below it
+
+// ===== Turret Overlay =====
+pub const TURRET_CONE_ALPHA: f32 = 0.12;
+pub const TURRET_CONE_SEGMENTS: usize = 12; // triangles approximating the sight cone's arc
+pub const TURRET_LOCK_MARKER_RADIUS: f32 = 60.0; // pixels around an enemy mech in view
+pub const TURRET_CROSSHAIR_SIZE: f32 = 10.0;
+pub const TURRET_HINT_BOTTOM_OFFSET: f32 = 130.0; // sits above the inventory strip
+
 // ===== Radar Display =====
 pub const RADAR_SIZE: f32 = 150.0;
 pub const RADAR_OFFSET_X: f32 = 50.0;
@@ -99,10 +148,59 @@ pub const RADAR_OFFSET_Y: f32 = 30.0;
 pub const RADAR_GRID_SIZE: i32 = 5;
 pub const RADAR_RANGE_TILES: f32 = 50.0;
 pub const RADAR_SWEEP_SPEED: f32 = 2.0;
+pub const RADAR_SCOPE_BLIP_RADIUS: f32 = 4.0; // pixels on the scope
+pub const RADAR_WORLD_BLIP_RADIUS: f32 = 50.0; // pixels around a contact out in the world
+pub const RADAR_BLIP_FADE_TIME: f32 = 1.0; // seconds of fade before a blip goes
+pub const SCANNER_SWEEP_TIME: f32 = 0.8; // seconds the scanner's ring takes to reach its range
+pub const SCANNER_CONTACT_SIZE: f32 = 10.0; // pixels from a pinged resource's center to its corners
+pub const SCANNER_BEARING_LENGTH: f32 = 24.0; // pixels of the arrow pointing past the ring at a far resource
+pub const SCANNER_COLOR: (f32, f32, f32) = (0.3, 0.9, 1.0);
+
+// ===== Coaching Annotations =====
+pub const ANNOTATION_LINE_WIDTH: f32 = 3.0;
+pub const ANNOTATION_ARROW_HEAD: f32 = 14.0; // pixels from an arrow's tip back along its head
+pub const ANNOTATION_FADE_TIME: f32 = 1.0; // seconds a mark fades over before it goes
+pub const ANNOTATION_PREVIEW_ALPHA: f32 = 0.5; // opacity of a mark still being dragged out
+pub const ANNOTATION_MIN_DRAG: f32 = 8.0; // pixels a drag has to cover to draw anything
+
+// ===== Camera =====
+pub const CAMERA_SMOOTHING: f32 = 0.15; // share of the way to the target covered per 60th of a second
+pub const CAMERA_MIN_SMOOTHING: f32 = 0.02;
+pub const CAMERA_ZOOM_SMOOTHING: f32 = 0.2; // same, for easing toward the wheel's zoom
+pub const CAMERA_MIN_ZOOM: f32 = 0.5;
+pub const CAMERA_MAX_ZOOM: f32 = 2.5;
+pub const CAMERA_ZOOM_STEP: f32 = 1.15; // zoom factor per mouse wheel notch
+pub const EXPLOSION_SHAKE_INTENSITY: f32 = 4.0; // pixels, for an explosion at the center of the view
+pub const EXPLOSION_SHAKE_DURATION: f32 = 0.25; // seconds
+pub const EXPLOSION_SHAKE_RANGE: f32 = 15.0; // tiles from the view center before explosions stop shaking it
+
+// ===== Interior Lighting =====
+pub const LIGHT_AMBIENT: f32 = 0.2; // how lit a mech interior is with every lamp off
+pub const LIGHT_STATION_RADIUS: f32 = 3.0; // tiles
+pub const LIGHT_WINDOW_RADIUS: f32 = 2.5; // tiles, at noon
+pub const LIGHT_EMERGENCY_RADIUS: f32 = 4.0; // tiles
+pub const LIGHT_EMERGENCY_PULSE: f32 = 1.5; // pulses per second
+pub const LIGHT_ALARM_FLASH: f32 = 2.0; // flashes per second while a mech's boarding alarm sounds
+pub const LIGHT_LOW_POWER_HEALTH: f32 = 0.25; // hull fraction below which the mech runs on emergency power
+pub const LIGHT_LOW_POWER_DIM: f32 = 0.3; // station lights left on emergency power
+pub const LIGHT_FLICKER_DURATION: f32 = 0.6; // seconds the lights stutter after a hit
+pub const LIGHT_TEXTURE_SIZE: u16 = 64; // pixels across the radial light sprite
+pub const LIGHT_DAYLIGHT_COLOR: (f32, f32, f32) = (1.0, 0.95, 0.8);
+pub const LIGHT_EMERGENCY_COLOR: (f32, f32, f32) = (0.9, 0.15, 0.1);
+
+// ===== Weather Overlays =====
+pub const NIGHT_TINT_COLOR: (f32, f32, f32) = (0.02, 0.03, 0.12);
+pub const NIGHT_TINT_ALPHA: f32 = 0.45; // darkness over the world at midnight
+pub const FOG_TINT_COLOR: (f32, f32, f32) = (0.7, 0.72, 0.75);
+pub const FOG_TINT_ALPHA: f32 = 0.3;
+pub const RAIN_STREAKS: usize = 160; // streaks on screen at once
+pub const RAIN_FALL_SPEED: f32 = 900.0; // screen pixels per second
+pub const RAIN_STREAK_LENGTH: f32 = 14.0; // screen pixels
 
 // ===== UI Positioning =====
 pub const CONNECTION_MESSAGE_X: f32 = 10.0;
 pub const CONNECTION_MESSAGE_Y: f32 = 30.0;
+pub const CONNECTION_LOST_DIM_ALPHA: f32 = 0.5; // how far the frozen game darkens behind the reconnect overlay
 pub const SCREEN_WIDTH_DIVISOR: f32 = 3.0;
 pub const WINDOW_CENTER_DIVISOR: f32 = 2.0;
 
@@ -124,3 +222,90 @@ pub const RADAR_CENTER_DIVISOR: f32 = 2.0;
 pub const REPAIR_STATION_Y_OFFSET: f32 = 100.0;
 pub const WEAPON_BUTTON_Y_OFFSET: f32 = 200.0;
 pub const DOOR_HANDLE_OFFSET: f32 = 5.0;
+
+// ===== Toast Notifications =====
+pub const TOAST_DURATION: f32 = 4.0; // seconds each toast stays up
+pub const TOAST_FADE_TIME: f32 = 0.5; // seconds of fade at the end
+pub const TOAST_MAX_VISIBLE: usize = 4; // older toasts are dropped past this
+pub const TOAST_WIDTH: f32 = 280.0;
+pub const TOAST_HEIGHT: f32 = 44.0;
+pub const TOAST_GAP: f32 = 6.0;
+
+// ===== Developer Console =====
+pub const CONSOLE_HEIGHT_RATIO: f32 = 0.4;
+pub const CONSOLE_FONT_SIZE: f32 = 16.0;
+pub const CONSOLE_LINE_SPACING: f32 = 18.0;
+pub const CONSOLE_PADDING: f32 = 8.0;
+pub const CONSOLE_MAX_OUTPUT_LINES: usize = 200;
+pub const CONSOLE_MAX_HISTORY: usize = 50;
+pub const UI_SCALE_MIN: f32 = 0.5;
+pub const UI_SCALE_MAX: f32 = 3.0;
+
+// ===== Screen Capture =====
+pub const CAPTURE_DIR: &str = "captures"; // relative to the working directory on native builds
+pub const CAPTURE_CLIP_SECONDS: f32 = 5.0; // how much a held record key keeps
+pub const CAPTURE_CLIP_MAX_SECONDS: f32 = 10.0;
+pub const CAPTURE_CLIP_FPS: u32 = 15;
+pub const CAPTURE_CLIP_SCALE: f32 = 0.5; // clip frames are downscaled to keep memory in check
+pub const CAPTURE_GIF_SPEED: i32 = 10; // 1 (best colours) to 30 (fastest) quantization
+pub const CAPTURE_NOTICE_DURATION: f32 = 3.0; // seconds
+
+// ===== Render Scale =====
+pub const RENDER_SCALE_MIN: f32 = 0.5; // share of the window's resolution the world can drop to
+pub const RENDER_SCALE_MAX: f32 = 1.0;
+pub const RENDER_SCALE_STEP: f32 = 0.05; // how far auto mode moves the scale at a time
+pub const RENDER_SCALE_ADJUST_INTERVAL: f32 = 0.5; // seconds auto mode waits between steps
+pub const RENDER_SCALE_HEADROOM: f32 = 1.15; // frame rate over the target auto mode wants before scaling back up
+pub const RENDER_TARGET_FPS: u32 = 60;
+
+// ===== Player Animation =====
+pub const PLAYER_SPRITE_SHEET: &str = "assets/player.png"; // drawn as shapes when missing
+pub const PLAYER_SPRITE_FRAME_SIZE: f32 = 32.0; // pixels per square frame in the sheet
+pub const PLAYER_ANIMATION_FPS: f32 = 8.0;
+pub const PLAYER_STOP_DELAY: f32 = 0.15; // seconds without moving before walking turns to idle
+pub const PLAYER_TELEPORT_DISTANCE: f32 = 64.0; // pixels a single update can jump without walking
+
+// ===== Mech Schematic =====
+pub const SCHEMATIC_WIDTH: f32 = 200.0; // matches the cargo panel it sits under
+pub const SCHEMATIC_TILE_SIZE: f32 = 6.0; // pixels per interior tile in the cutaway
+pub const SCHEMATIC_FLOOR_GAP: f32 = 6.0;
+pub const SCHEMATIC_HEADER_HEIGHT: f32 = 66.0;
+pub const SCHEMATIC_LINE_HEIGHT: f32 = 16.0;
+pub const SCHEMATIC_MAX_NEEDS: usize = 4; // repair and manning callouts listed under the cutaway
+pub const FUEL_LOW_SHARE: f32 = 0.25; // tank share below which fuel gauges turn orange
+
+// ===== Particles =====
+pub const PARTICLE_BUDGET_LOW: usize = 150; // most particles alive at once per effects quality
+pub const PARTICLE_BUDGET_MEDIUM: usize = 400;
+pub const PARTICLE_BUDGET_HIGH: usize = 1000;
+pub const MUZZLE_FLASH_PARTICLES: usize = 10;
+pub const IMPACT_SPARK_PARTICLES: usize = 16;
+pub const DASH_DUST_PARTICLES: usize = 14; // puffs kicked up along a dash
+pub const GORE_PARTICLES: usize = 20; // spatter from a player run over by a mech
+pub const PROJECTILE_TRAIL_RATE: f32 = 60.0; // particles per second behind each projectile
+pub const INTERCEPTOR_TRACER_LENGTH: f32 = 0.04; // seconds of flight an interceptor's tracer streak covers
+pub const DECAL_OPACITY: f32 = 0.6; // how dark scorches, burns and debris draw over the ground
+pub const MECH_SMOKE_HEALTH: f32 = 0.5; // fraction of hull below which a mech smokes
+pub const MECH_SMOKE_RATE: f32 = 20.0; // particles per second from a nearly wrecked mech
+pub const MECH_SMOKE_SPREAD: f32 = 40.0; // pixels from the mech's center smoke starts at
+
+// ===== Friend or Foe =====
+pub const IFF_RING_GAP: f32 = 3.0; // pixels between a player and their ring
+pub const IFF_FRIENDLY_ALPHA: f32 = 0.5; // how strongly teammates' rings and mechs' outlines show
+pub const IFF_ENEMY_COLOR: (f32, f32, f32) = (1.0, 0.15, 0.15);
+pub const IFF_ENEMY_TICK_LENGTH: f32 = 6.0; // pixels of the sight ticks around an enemy's ring
+pub const IFF_MECH_BRACKET_LENGTH: f32 = 0.2; // share of a mech's side its enemy corner brackets cover
+pub const IFF_ARROW_SIZE: f32 = 14.0; // pixels from an offscreen enemy arrow's tip to its base
+pub const IFF_ARROW_MARGIN: f32 = 30.0; // pixels an offscreen enemy arrow keeps from the screen's edge
+
+// ===== Accessibility =====
+pub const REDUCED_FLASH_INTENSITY: f32 = 0.35; // brightness kept by flashes and beams in reduced-flash mode
+pub const COLORBLIND_TEAM_RED_COLOR: (f32, f32, f32) = (0.9, 0.6, 0.0); // orange
+pub const COLORBLIND_TEAM_BLUE_COLOR: (f32, f32, f32) = (0.0, 0.45, 0.7); // blue
+pub const COLORBLIND_PLAYER_RED_COLOR: (f32, f32, f32) = (1.0, 0.75, 0.25);
+pub const COLORBLIND_PLAYER_BLUE_COLOR: (f32, f32, f32) = (0.35, 0.7, 0.9); // sky blue
+pub const COLORBLIND_COMPONENTS_COLOR: (f32, f32, f32) = (0.0, 0.62, 0.45); // bluish green
+pub const COLORBLIND_WIRING_COLOR: (f32, f32, f32) = (0.94, 0.89, 0.26); // yellow
+pub const COLORBLIND_BATTERIES_COLOR: (f32, f32, f32) = (0.8, 0.47, 0.65); // reddish purple
+pub const COLORBLIND_POWER_CORE_COLOR: (f32, f32, f32) = (0.95, 0.95, 0.95); // white
+pub const COLORBLIND_IFF_ENEMY_COLOR: (f32, f32, f32) = (0.84, 0.37, 0.0); // vermillion
@@ -0,0 +1,127 @@
+//! Recorded matches. A replay is a `ReplayHeader` followed by a run of
+//! `ReplayFrame`s, each MessagePack like on the wire and preceded by its
+//! length as a little-endian u32: first what a spectator joining when
+//! recording began would be sent, then every public broadcast in the order
+//! it went out, stamped with the tick.
+
+use crate::{NetworkError, NetworkResult, ServerMessage};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+const LENGTH_BYTES: usize = 4;
+
+/// What's needed to play the match again rather than just watch it
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ReplayHeader {
+    /// The seed the server's simulation rolled from
+    pub seed: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayFrame {
+    pub tick: u64,
+    pub message: ServerMessage,
+}
+
+/// A decoded replay file
+#[derive(Debug, Clone)]
+pub struct Replay {
+    pub header: ReplayHeader,
+    pub frames: Vec<ReplayFrame>,
+}
+
+fn encode_record(record: &impl Serialize) -> NetworkResult<Vec<u8>> {
+    let payload = rmp_serde::to_vec(record)?;
+    let mut bytes = Vec::with_capacity(LENGTH_BYTES + payload.len());
+    bytes.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    bytes.extend(payload);
+    Ok(bytes)
+}
+
+pub fn encode_replay_header(header: &ReplayHeader) -> NetworkResult<Vec<u8>> {
+    encode_record(header)
+}
+
+pub fn encode_replay_frame(frame: &ReplayFrame) -> NetworkResult<Vec<u8>> {
+    encode_record(frame)
+}
+
+/// The next whole record and what follows it; None once the bytes run out
+/// or the last record was cut short
+fn next_record<T: DeserializeOwned>(
+    bytes: &[u8],
+    what: impl FnOnce() -> String,
+) -> NetworkResult<Option<(T, &[u8])>> {
+    if bytes.len() < LENGTH_BYTES {
+        return Ok(None);
+    }
+    let (length, rest) = bytes.split_at(LENGTH_BYTES);
+    let length = u32::from_le_bytes(length.try_into().unwrap()) as usize;
+    if rest.len() < length {
+        return Ok(None);
+    }
+    let (payload, rest) = rest.split_at(length);
+    let record = rmp_serde::from_slice(payload)
+        .map_err(|e| NetworkError::InvalidMessage(format!("{}: {e}", what())))?;
+    Ok(Some((record, rest)))
+}
+
+/// The header and every frame in a replay, oldest first. A frame cut short
+/// by the recorder stopping mid-write ends the replay rather than failing it.
+pub fn decode_replay(bytes: &[u8]) -> NetworkResult<Replay> {
+    let (header, mut bytes) = next_record(bytes, || "replay header".to_string())?
+        .ok_or_else(|| NetworkError::InvalidMessage("replay has no header".to_string()))?;
+    let mut frames = Vec::new();
+    while let Some((frame, rest)) =
+        next_record(bytes, || format!("replay frame {}", frames.len() + 1))?
+    {
+        frames.push(frame);
+        bytes = rest;
+    }
+    Ok(Replay { header, frames })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replays_round_trip_and_survive_a_torn_last_frame() {
+        let frames = [
+            ReplayFrame {
+                tick: 3,
+                message: ServerMessage::ResourceDestroyed {
+                    resource_id: uuid::Uuid::nil(),
+                },
+            },
+            ReplayFrame {
+                tick: 4,
+                message: ServerMessage::DevCommandResult {
+                    success: true,
+                    message: "ok".to_string(),
+                },
+            },
+        ];
+        let header = ReplayHeader { seed: 42 };
+        let mut bytes = encode_replay_header(&header).unwrap();
+        let header_length = bytes.len();
+        bytes.extend(
+            frames
+                .iter()
+                .flat_map(|frame| encode_replay_frame(frame).unwrap()),
+        );
+
+        let decoded = decode_replay(&bytes).unwrap();
+        assert_eq!(decoded.header, header);
+        assert_eq!(decoded.frames.len(), 2);
+        assert_eq!(decoded.frames[1].tick, 4);
+
+        let torn = &bytes[..bytes.len() - 3];
+        assert_eq!(decode_replay(torn).unwrap().frames.len(), 1);
+        assert!(decode_replay(&bytes[..2]).is_err());
+
+        let mut corrupt = bytes.clone();
+        corrupt[header_length + LENGTH_BYTES] = 0xc1; // never used in MessagePack
+        assert!(decode_replay(&corrupt).is_err());
+    }
+}
@@ -0,0 +1,212 @@
+//! Finding servers to play on.
+//!
+//! Servers can announce themselves to a master list, any server started
+//! with its own endpoints serving as one, by POSTing a `ServerListing` to
+//! `/master/servers` every `MASTER_HEARTBEAT_SECS`. Clients GET the same
+//! path for everything heard from recently. On a LAN, clients can also
+//! broadcast `LAN_DISCOVERY_QUERY` over UDP and every server in earshot
+//! answers with its listing.
+
+use crate::network_constants::{COMPRESSION_QUERY, MAX_SERVER_NAME_LENGTH};
+use crate::{Validate, ValidationError, ValidationResult};
+use serde::{Deserialize, Serialize};
+
+/// Path of the master list on a server, for both registering and listing
+pub const MASTER_LIST_PATH: &str = "/master/servers";
+
+/// What a client broadcasts to find servers on its LAN
+pub const LAN_DISCOVERY_QUERY: &[u8] = b"MECH_DISCOVER";
+
+/// One server as the browser shows it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ServerListing {
+    pub name: String,
+    /// Left empty by a server that doesn't know its public address; the
+    /// master or the LAN client fills in where the announcement came from
+    #[serde(default)]
+    pub host: String,
+    pub port: u16,
+    /// People playing, across every room
+    pub players: usize,
+    pub ai_players: usize,
+    pub rooms: usize,
+    /// False once no room will take another player
+    pub accepting_players: bool,
+    pub protocol_version: u32,
+}
+
+impl ServerListing {
+    /// Where to point the game websocket
+    pub fn ws_url(&self) -> String {
+        format!("ws://{}:{}/ws?{COMPRESSION_QUERY}", self.host, self.port)
+    }
+
+    /// Key that tells two listings of the same server apart from others
+    pub fn address(&self) -> String {
+        format!("{}:{}", self.host, self.port)
+    }
+
+    /// Whether a browser search matches this server's name or address
+    pub fn matches(&self, search: &str) -> bool {
+        let search = search.trim().to_lowercase();
+        search.is_empty()
+            || self.name.to_lowercase().contains(&search)
+            || self.address().contains(&search)
+    }
+}
+
+impl Validate for ServerListing {
+    fn validate(&self) -> ValidationResult<()> {
+        let reason = if self.name.trim().is_empty() {
+            "name is empty".to_string()
+        } else if self.name.chars().count() > MAX_SERVER_NAME_LENGTH {
+            format!("name is longer than {MAX_SERVER_NAME_LENGTH} characters")
+        } else if self.name.chars().any(char::is_control) {
+            "name contains control characters".to_string()
+        } else if self.port == 0 {
+            "port is 0".to_string()
+        } else {
+            return Ok(());
+        };
+        Err(ValidationError::InvalidServerListing { reason })
+    }
+}
+
+/// Host, port and path of a plain `http://` URL
+pub fn parse_http_url(url: &str) -> Option<(String, u16, String)> {
+    let rest = url.strip_prefix("http://")?;
+    let (authority, path) = match rest.find('/') {
+        Some(slash) => (&rest[..slash], &rest[slash..]),
+        None => (rest, "/"),
+    };
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((host, port)) => (host, port.parse().ok()?),
+        None => (authority, 80),
+    };
+    if host.is_empty() {
+        return None;
+    }
+    Some((host.to_string(), port, path.to_string()))
+}
+
+/// A blocking HTTP/1.1 request to a plain `http://` URL, returning the body
+/// of a 2xx response. Enough for talking to a master list without pulling
+/// in an HTTP client; there's no TLS, so masters run behind plain HTTP.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn http_request(method: &str, url: &str, json_body: Option<&str>) -> Result<String, String> {
+    use crate::network_constants::MASTER_HTTP_TIMEOUT_MS;
+    use std::io::{Read, Write};
+    use std::net::{TcpStream, ToSocketAddrs};
+    use std::time::Duration;
+
+    let (host, port, path) =
+        parse_http_url(url).ok_or_else(|| format!("'{url}' is not an http:// URL"))?;
+    let timeout = Duration::from_millis(MASTER_HTTP_TIMEOUT_MS);
+    let addr = (host.as_str(), port)
+        .to_socket_addrs()
+        .map_err(|e| format!("Can't resolve {host}: {e}"))?
+        .next()
+        .ok_or_else(|| format!("Can't resolve {host}"))?;
+    let mut stream =
+        TcpStream::connect_timeout(&addr, timeout).map_err(|e| format!("{host}:{port}: {e}"))?;
+    stream.set_read_timeout(Some(timeout)).ok();
+    stream.set_write_timeout(Some(timeout)).ok();
+
+    let body = json_body.unwrap_or("");
+    let mut request = format!(
+        "{method} {path} HTTP/1.1\r\nHost: {host}:{port}\r\nConnection: close\r\nContent-Length: {}\r\n",
+        body.len()
+    );
+    if json_body.is_some() {
+        request.push_str("Content-Type: application/json\r\n");
+    }
+    request.push_str("\r\n");
+    request.push_str(body);
+    stream
+        .write_all(request.as_bytes())
+        .map_err(|e| e.to_string())?;
+
+    let mut response = String::new();
+    stream
+        .read_to_string(&mut response)
+        .map_err(|e| e.to_string())?;
+    parse_http_response(&response)
+}
+
+/// The body of a 2xx response, which must not be chunked
+#[cfg(not(target_arch = "wasm32"))]
+fn parse_http_response(response: &str) -> Result<String, String> {
+    let (head, body) = response
+        .split_once("\r\n\r\n")
+        .ok_or("Malformed HTTP response")?;
+    let status = head
+        .lines()
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .and_then(|code| code.parse::<u16>().ok())
+        .ok_or("Malformed HTTP status line")?;
+    if !(200..300).contains(&status) {
+        return Err(format!("HTTP {status}: {}", body.trim()));
+    }
+    Ok(body.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn listing(name: &str) -> ServerListing {
+        ServerListing {
+            name: name.to_string(),
+            host: "10.0.0.5".to_string(),
+            port: 14191,
+            players: 3,
+            ai_players: 1,
+            rooms: 1,
+            accepting_players: true,
+            protocol_version: crate::PROTOCOL_VERSION,
+        }
+    }
+
+    #[test]
+    fn test_listings_are_searched_by_name_and_address() {
+        let server = listing("Friday Night Mechs");
+        assert!(server.matches(""));
+        assert!(server.matches("friday"));
+        assert!(server.matches("10.0.0"));
+        assert!(!server.matches("saturday"));
+        assert_eq!(
+            server.ws_url(),
+            format!("ws://10.0.0.5:14191/ws?{COMPRESSION_QUERY}")
+        );
+
+        assert!(server.validate().is_ok());
+        assert!(listing(" ").validate().is_err());
+        assert!(listing(&"x".repeat(MAX_SERVER_NAME_LENGTH + 1))
+            .validate()
+            .is_err());
+    }
+
+    #[test]
+    fn test_http_urls_and_responses_are_parsed() {
+        assert_eq!(
+            parse_http_url("http://master.example:8080/master/servers"),
+            Some((
+                "master.example".to_string(),
+                8080,
+                "/master/servers".to_string()
+            ))
+        );
+        assert_eq!(
+            parse_http_url("http://master.example"),
+            Some(("master.example".to_string(), 80, "/".to_string()))
+        );
+        assert_eq!(parse_http_url("https://master.example"), None);
+
+        assert_eq!(
+            parse_http_response("HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\n[]"),
+            Ok("[]".to_string())
+        );
+        assert!(parse_http_response("HTTP/1.1 400 Bad Request\r\n\r\nname is empty").is_err());
+    }
+}
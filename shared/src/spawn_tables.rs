@@ -0,0 +1,228 @@
+//! How rare each resource is and which ones turn up where, read from
+//! `data/resources.toml`. The arena is split into rings around its center,
+//! each with its own weighted table, so rare resources can be kept to the
+//! contested middle while the outskirts mostly give scrap.
+
+use crate::{GameError, GameResult, ResourceType, TilePos};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// The spawn tables the game ships with
+pub const DEFAULT_SPAWN_TABLES: &str = include_str!("../data/resources.toml");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Rarity {
+    Common,
+    Uncommon,
+    Rare,
+}
+
+/// One ring of the arena and the odds of each resource spawning in it
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpawnRegion {
+    pub name: String,
+    /// Outer edge of the ring, from 0 at the arena's center to 1 at its
+    /// corners
+    pub within: f32,
+    /// Relative odds, in `ResourceType::ALL` order so a seeded match rolls
+    /// the same resources every time
+    pub weights: Vec<(ResourceType, u32)>,
+}
+
+impl SpawnRegion {
+    fn total_weight(&self) -> u32 {
+        self.weights.iter().map(|(_, weight)| weight).sum()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SpawnTables {
+    rarities: HashMap<ResourceType, Rarity>,
+    /// Inside out
+    regions: Vec<SpawnRegion>,
+}
+
+impl Default for SpawnTables {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SpawnTables {
+    /// The built-in tables
+    pub fn new() -> Self {
+        Self::from_toml(DEFAULT_SPAWN_TABLES, "built-in resources.toml")
+            .expect("built-in spawn tables are valid")
+    }
+
+    /// Read spawn tables from a TOML document. The error lists every
+    /// problem found, with `source` naming the document.
+    pub fn from_toml(text: &str, source: &str) -> GameResult<Self> {
+        #[derive(Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct RegionEntry {
+            name: String,
+            within: f32,
+            weights: HashMap<ResourceType, u32>,
+        }
+
+        #[derive(Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct SpawnFile {
+            rarity: HashMap<ResourceType, Rarity>,
+            region: Vec<RegionEntry>,
+        }
+
+        let file: SpawnFile = toml::from_str(text).map_err(|e| {
+            GameError::invalid_input(format!("{source} is not valid spawn table data: {e}"))
+        })?;
+
+        let mut problems = Vec::new();
+        for resource_type in ResourceType::ALL {
+            if !file.rarity.contains_key(&resource_type) {
+                problems.push(format!("{resource_type:?} has no rarity"));
+            }
+        }
+        let regions: Vec<SpawnRegion> = file
+            .region
+            .into_iter()
+            .map(|entry| SpawnRegion {
+                weights: ResourceType::ALL
+                    .into_iter()
+                    .filter_map(|resource_type| {
+                        let weight = *entry.weights.get(&resource_type)?;
+                        (weight > 0).then_some((resource_type, weight))
+                    })
+                    .collect(),
+                name: entry.name,
+                within: entry.within,
+            })
+            .collect();
+        let mut reached = 0.0;
+        for region in &regions {
+            if region.within <= reached {
+                problems.push(format!(
+                    "region '{}' doesn't reach past the one before it",
+                    region.name
+                ));
+            }
+            reached = region.within.max(reached);
+            if region.total_weight() == 0 {
+                problems.push(format!("region '{}' has nothing to spawn", region.name));
+            }
+        }
+        if reached < 1.0 {
+            problems.push("no region reaches the arena's corners (within = 1.0)".to_string());
+        }
+
+        if problems.is_empty() {
+            Ok(Self {
+                rarities: file.rarity,
+                regions,
+            })
+        } else {
+            Err(GameError::invalid_input(format!(
+                "{source} has {} problem(s) in its spawn tables:\n  - {}",
+                problems.len(),
+                problems.join("\n  - ")
+            )))
+        }
+    }
+
+    /// The built-in tables, or the ones in the file at `path`
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load(path: Option<&std::path::Path>) -> GameResult<Self> {
+        let Some(path) = path else {
+            return Ok(Self::new());
+        };
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| GameError::invalid_input(format!("can't read {}: {e}", path.display())))?;
+        Self::from_toml(&text, &path.display().to_string())
+    }
+
+    pub fn rarity(&self, resource_type: ResourceType) -> Rarity {
+        self.rarities
+            .get(&resource_type)
+            .copied()
+            .unwrap_or(Rarity::Common)
+    }
+
+    /// The ring `pos` falls in, on an arena `width` by `height` tiles
+    pub fn region_at(&self, pos: TilePos, width: i32, height: i32) -> &SpawnRegion {
+        let half_width = (width as f32 / 2.0).max(1.0);
+        let half_height = (height as f32 / 2.0).max(1.0);
+        let dx = (pos.x as f32 + 0.5 - half_width) / half_width;
+        let dy = (pos.y as f32 + 0.5 - half_height) / half_height;
+        let distance = (dx * dx + dy * dy).sqrt() / std::f32::consts::SQRT_2;
+        self.regions
+            .iter()
+            .find(|region| distance <= region.within)
+            .or(self.regions.last())
+            .expect("spawn tables have at least one region")
+    }
+
+    /// What spawns at `pos`, given a `roll` from 0 up to 1
+    pub fn sample(&self, pos: TilePos, width: i32, height: i32, roll: f32) -> ResourceType {
+        let region = self.region_at(pos, width, height);
+        let mut pick = (roll.clamp(0.0, 1.0) * region.total_weight() as f32) as u32;
+        for (resource_type, weight) in &region.weights {
+            if pick < *weight {
+                return *resource_type;
+            }
+            pick -= weight;
+        }
+        region
+            .weights
+            .last()
+            .map_or(ResourceType::ScrapMetal, |(resource_type, _)| {
+                *resource_type
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rare_cores_spawn_in_the_middle_only() {
+        let tables = SpawnTables::new();
+        assert_eq!(tables.rarity(ResourceType::ScrapMetal), Rarity::Common);
+        assert_eq!(tables.rarity(ResourceType::PowerCore), Rarity::Rare);
+
+        let center = TilePos::new(50, 50);
+        let corner = TilePos::new(1, 1);
+        assert_eq!(tables.region_at(center, 100, 100).name, "Core");
+        assert_eq!(tables.region_at(corner, 100, 100).name, "Outskirts");
+
+        let rolls = (0..100).map(|i| i as f32 / 100.0);
+        let spawned = |pos| {
+            rolls
+                .clone()
+                .map(|roll| tables.sample(pos, 100, 100, roll))
+                .collect::<Vec<_>>()
+        };
+        assert!(spawned(center).contains(&ResourceType::PowerCore));
+        assert!(!spawned(corner).contains(&ResourceType::PowerCore));
+        assert!(spawned(corner).contains(&ResourceType::ScrapMetal));
+    }
+
+    #[test]
+    fn test_broken_tables_list_every_problem() {
+        let text = r#"
+            [rarity]
+            ScrapMetal = "Common"
+
+            [[region]]
+            name = "Everywhere"
+            within = 0.5
+            weights = { ScrapMetal = 0 }
+        "#;
+        let error = SpawnTables::from_toml(text, "test.toml")
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("PowerCore has no rarity"));
+        assert!(error.contains("'Everywhere' has nothing to spawn"));
+        assert!(error.contains("no region reaches"));
+    }
+}
@@ -0,0 +1,259 @@
+//! Stable hashes of the state clients mirror, for hunting desyncs.
+//!
+//! The server and the client each hash their own copy of every player,
+//! mech and resource the same way, so comparing the per-subsystem totals
+//! says which part of the mirror drifted and the per-entity hashes say
+//! which entity. Only what both sides hold goes in, and positions count
+//! by tile so float noise doesn't show up as a desync. FNV-1a keeps the
+//! hashes identical across builds and platforms, unlike std's hasher.
+
+use crate::coordinates::TilePos;
+use crate::types::{PlayerLocation, ResourceType, TeamId};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// FNV-1a over whatever's written into it
+struct StableHasher(u64);
+
+impl StableHasher {
+    fn new() -> Self {
+        Self(FNV_OFFSET)
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) -> &mut Self {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+        self
+    }
+
+    fn u64(&mut self, value: u64) -> &mut Self {
+        self.bytes(&value.to_le_bytes())
+    }
+
+    fn uuid(&mut self, id: Uuid) -> &mut Self {
+        self.bytes(id.as_bytes())
+    }
+
+    fn tile(&mut self, tile: TilePos) -> &mut Self {
+        self.u64(tile.x as u64).u64(tile.y as u64)
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// One hash per subsystem
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct StateHashes {
+    pub players: u64,
+    pub mechs: u64,
+    pub resources: u64,
+}
+
+impl StateHashes {
+    pub const SUBSYSTEMS: [&'static str; 3] = ["players", "mechs", "resources"];
+
+    pub fn subsystem(&self, subsystem: &str) -> u64 {
+        match subsystem {
+            "players" => self.players,
+            "mechs" => self.mechs,
+            _ => self.resources,
+        }
+    }
+
+    /// All three folded into one
+    pub fn combined(&self) -> u64 {
+        StableHasher::new()
+            .u64(self.players)
+            .u64(self.mechs)
+            .u64(self.resources)
+            .finish()
+    }
+
+    /// Subsystems whose hashes differ from `other`'s
+    pub fn mismatches(&self, other: &StateHashes) -> Vec<&'static str> {
+        Self::SUBSYSTEMS
+            .into_iter()
+            .filter(|subsystem| self.subsystem(subsystem) != other.subsystem(subsystem))
+            .collect()
+    }
+}
+
+/// Every entity's hash, by subsystem and id
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StateDump {
+    pub players: BTreeMap<Uuid, u64>,
+    pub mechs: BTreeMap<Uuid, u64>,
+    pub resources: BTreeMap<Uuid, u64>,
+}
+
+impl StateDump {
+    pub fn add_player(&mut self, id: Uuid, team: TeamId, location: PlayerLocation, health: u32) {
+        let mut hasher = StableHasher::new();
+        hasher.uuid(id).u64(team as u64).u64(health as u64);
+        match location {
+            PlayerLocation::OutsideWorld(pos) => hasher.u64(0).tile(pos.to_tile()),
+            PlayerLocation::InsideMech { mech_id, pos } => hasher
+                .u64(1)
+                .uuid(mech_id)
+                .u64(pos.floor as u64)
+                .tile(pos.tile_pos),
+        };
+        self.players.insert(id, hasher.finish());
+    }
+
+    pub fn add_mech(
+        &mut self,
+        id: Uuid,
+        team: TeamId,
+        position: TilePos,
+        health: u32,
+        shield: u32,
+        cargo: &HashMap<ResourceType, u32>,
+    ) {
+        let mut hasher = StableHasher::new();
+        hasher
+            .uuid(id)
+            .u64(team as u64)
+            .tile(position)
+            .u64(health as u64)
+            .u64(shield as u64);
+        let mut cargo: Vec<(u64, u32)> = cargo
+            .iter()
+            .filter(|(_, amount)| **amount > 0)
+            .map(|(resource_type, amount)| (*resource_type as u64, *amount))
+            .collect();
+        cargo.sort_unstable();
+        for (resource_type, amount) in cargo {
+            hasher.u64(resource_type).u64(amount as u64);
+        }
+        self.mechs.insert(id, hasher.finish());
+    }
+
+    pub fn add_resource(&mut self, id: Uuid, resource_type: ResourceType, position: TilePos) {
+        let hash = StableHasher::new()
+            .uuid(id)
+            .u64(resource_type as u64)
+            .tile(position)
+            .finish();
+        self.resources.insert(id, hash);
+    }
+
+    pub fn hashes(&self) -> StateHashes {
+        fn fold(entities: &BTreeMap<Uuid, u64>) -> u64 {
+            let mut hasher = StableHasher::new();
+            for (id, hash) in entities {
+                hasher.uuid(*id).u64(*hash);
+            }
+            hasher.finish()
+        }
+        StateHashes {
+            players: fold(&self.players),
+            mechs: fold(&self.mechs),
+            resources: fold(&self.resources),
+        }
+    }
+
+    /// Entities that differ from `other` or are missing on either side, as
+    /// `subsystem id` lines
+    pub fn diff(&self, other: &StateDump) -> Vec<String> {
+        let pairs = [
+            ("players", &self.players, &other.players),
+            ("mechs", &self.mechs, &other.mechs),
+            ("resources", &self.resources, &other.resources),
+        ];
+        let mut lines = Vec::new();
+        for (subsystem, ours, theirs) in pairs {
+            for (id, hash) in ours {
+                match theirs.get(id) {
+                    Some(other_hash) if other_hash == hash => {}
+                    Some(_) => lines.push(format!("{subsystem} {id} differs")),
+                    None => lines.push(format!("{subsystem} {id} only here")),
+                }
+            }
+            for id in theirs.keys().filter(|id| !ours.contains_key(id)) {
+                lines.push(format!("{subsystem} {id} missing"));
+            }
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coordinates::WorldPos;
+
+    fn dump() -> StateDump {
+        let mut dump = StateDump::default();
+        let position = WorldPos::new(40.0, 40.0);
+        dump.add_player(
+            Uuid::from_u128(1),
+            TeamId::Red,
+            PlayerLocation::OutsideWorld(position),
+            100,
+        );
+        let cargo = HashMap::from([(ResourceType::Wiring, 2), (ResourceType::Batteries, 1)]);
+        dump.add_mech(
+            Uuid::from_u128(2),
+            TeamId::Blue,
+            TilePos::new(10, 10),
+            200,
+            50,
+            &cargo,
+        );
+        dump.add_resource(
+            Uuid::from_u128(3),
+            ResourceType::ScrapMetal,
+            TilePos::new(3, 4),
+        );
+        dump
+    }
+
+    #[test]
+    fn hashes_are_stable_and_ignore_sub_tile_movement() {
+        let a = dump();
+        assert_eq!(a.hashes(), dump().hashes());
+
+        let mut b = dump();
+        b.add_player(
+            Uuid::from_u128(1),
+            TeamId::Red,
+            PlayerLocation::OutsideWorld(WorldPos::new(41.5, 40.5)),
+            100,
+        );
+        assert_eq!(a.hashes(), b.hashes());
+        assert!(a.diff(&b).is_empty());
+    }
+
+    #[test]
+    fn mismatches_point_at_the_subsystem_and_entity() {
+        let a = dump();
+        let mut b = dump();
+        b.add_resource(
+            Uuid::from_u128(3),
+            ResourceType::ScrapMetal,
+            TilePos::new(5, 4),
+        );
+        b.add_resource(Uuid::from_u128(4), ResourceType::Wiring, TilePos::new(1, 1));
+
+        assert_eq!(a.hashes().mismatches(&b.hashes()), vec!["resources"]);
+        assert_ne!(a.hashes().combined(), b.hashes().combined());
+        let id = Uuid::from_u128(3);
+        let missing = Uuid::from_u128(4);
+        assert_eq!(
+            a.diff(&b),
+            vec![
+                format!("resources {id} differs"),
+                format!("resources {missing} missing"),
+            ]
+        );
+    }
+}
@@ -1,41 +1,81 @@
-use crate::{uuid_gen::new_uuid, GameError, GameResult, ResourceType, StationType, TilePos};
+use crate::{
+    uuid_gen::new_uuid, GameError, GameResult, ResourceType, StationButtonInfo, StationType,
+    TilePos, UpgradeType, MAX_STATION_LEVEL, MECH_FLOORS, STATION_BOOST_PER_LEVEL,
+    STATION_COOLDOWN_CUT_PER_LEVEL, STATION_DAMAGE_PER_LEVEL, TOP_TIER_CORE_COST,
+};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use uuid::Uuid;
 
+/// The station definitions the game ships with
+pub const DEFAULT_STATION_DEFINITIONS: &str = include_str!("../data/stations.toml");
+
+/// Most buttons a station can have; the number keys 1-9 press them
+const MAX_STATION_BUTTONS: usize = 9;
+
+/// `cost` for reaching `new_level`, plus `TOP_TIER_CORE_COST` power cores
+/// when that's `top_level`, so the best upgrades need rare resources
+pub fn with_top_tier_cost(
+    mut cost: HashMap<ResourceType, u32>,
+    new_level: u8,
+    top_level: u8,
+) -> HashMap<ResourceType, u32> {
+    if new_level == top_level {
+        *cost.entry(ResourceType::PowerCore).or_insert(0) += TOP_TIER_CORE_COST;
+    }
+    cost
+}
+
 /// Registry pattern for managing station types and their behaviors
+#[derive(Debug, Clone)]
 pub struct StationRegistry {
     station_definitions: HashMap<StationType, StationDefinition>,
 }
 
-/// Definition of a station type with its properties and behaviors
-#[derive(Debug, Clone)]
+/// Definition of a station type with its properties and behaviors, as
+/// read from a `[[station]]` entry in the definitions file
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct StationDefinition {
+    #[serde(rename = "type")]
     pub station_type: StationType,
     pub name: String,
+    /// Label drawn on the station tile
+    pub short_name: String,
     pub description: String,
+    #[serde(skip)] // counted from the buttons listed
     pub button_count: u8,
+    #[serde(rename = "buttons", default)]
     pub button_definitions: Vec<ButtonDefinition>,
     pub cooldown_seconds: f32,
+    #[serde(default)]
     pub resource_requirements: HashMap<ResourceType, u32>,
+    #[serde(default)]
     pub upgrade_requirements: HashMap<ResourceType, u32>,
     pub allowed_floors: Vec<u8>,
     pub max_per_mech: u8,
     pub size: (u8, u8), // width, height in tiles
+    /// Tonnes the station adds to its mech
+    pub mass: f32,
 }
 
 /// Definition of a button on a station
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct ButtonDefinition {
+    #[serde(skip)] // position in the station's button list
     pub index: u8,
     pub label: String,
     pub description: String,
     pub action: StationAction,
     pub cooldown_seconds: f32,
+    #[serde(rename = "cost", default)]
     pub resource_cost: HashMap<ResourceType, u32>,
 }
 
 /// Actions that stations can perform
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind")]
 pub enum StationAction {
     /// Fire a weapon at the nearest enemy
     FireWeapon {
@@ -50,6 +90,8 @@ pub enum StationAction {
     RepairMech { hp_per_resource: u32 },
     /// Upgrade a mech system
     UpgradeMech { upgrade_type: MechUpgradeType },
+    /// Upgrade the station picked at the Upgrade terminal to its next tier
+    UpgradeStation,
     /// Charge energy reserves
     ChargeEnergy { energy_per_tick: u32 },
     /// Trigger a temporary effect
@@ -62,22 +104,41 @@ pub enum StationAction {
 }
 
 /// Types of weapons
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum WeaponType {
     Laser,
     Projectile,
     Missile,
     Beam,
+    /// Disables the target's stations instead of damaging it
+    Emp,
+    /// Point-defense round that only hits other projectiles
+    Interceptor,
 }
 
 /// Types of mech upgrades
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
 pub enum MechUpgradeType {
     Laser,
     Projectile,
     Shield,
     Engine,
     Armor,
+    Ecm,
+}
+
+impl MechUpgradeType {
+    /// The level mechs keep for this upgrade, if they keep one
+    pub fn upgrade_type(self) -> Option<UpgradeType> {
+        match self {
+            MechUpgradeType::Laser => Some(UpgradeType::Laser),
+            MechUpgradeType::Projectile => Some(UpgradeType::Projectile),
+            MechUpgradeType::Shield => Some(UpgradeType::Shield),
+            MechUpgradeType::Engine => Some(UpgradeType::Engine),
+            MechUpgradeType::Ecm => Some(UpgradeType::Ecm),
+            MechUpgradeType::Armor => None,
+        }
+    }
 }
 
 /// Station instance in a mech
@@ -88,7 +149,7 @@ pub struct StationInstance {
     pub floor: u8,
     pub position: TilePos,
     pub operated_by: Option<Uuid>,
-    pub last_used: f32, // Time since last use for cooldown
+    pub last_used: f32, // Match time of the last use, for cooldowns
     pub health: u32,
     pub max_health: u32,
     pub upgrade_level: u8,
@@ -106,8 +167,12 @@ pub struct StationActionResult {
 /// Effects that station actions can produce
 #[derive(Debug, Clone)]
 pub enum StationEffect {
-    /// Damage dealt to a target
-    Damage { target_id: Uuid, amount: u32 },
+    /// Damage dealt to a target by an instant weapon
+    Damage {
+        target_id: Uuid,
+        amount: u32,
+        weapon_type: WeaponType,
+    },
     /// Healing applied to a target
     Heal { target_id: Uuid, amount: u32 },
     /// Shield boost applied
@@ -123,12 +188,15 @@ pub enum StationEffect {
         upgrade_type: MechUpgradeType,
         new_level: u8,
     },
+    /// A station moved up a tier
+    StationUpgraded { station_id: Uuid, new_level: u8 },
     /// Projectile created
     ProjectileCreated {
         projectile_id: Uuid,
         position: crate::WorldPos,
         velocity: (f32, f32),
         damage: u32,
+        weapon_type: WeaponType,
     },
     /// Visual effect triggered
     VisualEffect {
@@ -147,14 +215,71 @@ pub enum StationEffect {
 }
 
 impl StationRegistry {
-    /// Create a new station registry with default definitions
+    /// Create a new station registry with the built-in definitions
     pub fn new() -> Self {
+        Self::from_toml(DEFAULT_STATION_DEFINITIONS, "built-in stations.toml")
+            .expect("built-in station definitions are valid")
+    }
+
+    /// Read station definitions from a TOML document. Every station type must
+    /// be defined exactly once; the error lists every problem found, with
+    /// `source` naming the document.
+    pub fn from_toml(text: &str, source: &str) -> GameResult<Self> {
+        #[derive(Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct StationFile {
+            station: Vec<StationDefinition>,
+        }
+
+        let file: StationFile = toml::from_str(text).map_err(|e| {
+            GameError::invalid_input(format!("{source} is not valid station data: {e}"))
+        })?;
+
         let mut registry = Self {
             station_definitions: HashMap::new(),
         };
+        let mut problems = Vec::new();
+        for mut definition in file.station {
+            definition.button_count =
+                definition.button_definitions.len().min(u8::MAX as usize) as u8;
+            for (index, button) in definition.button_definitions.iter_mut().enumerate() {
+                button.index = index as u8;
+            }
+            problems.extend(definition.problems());
+            if registry.get_definition(definition.station_type).is_some() {
+                problems.push(format!(
+                    "{:?} is defined more than once",
+                    definition.station_type
+                ));
+            }
+            registry.register_station(definition);
+        }
+        for station_type in StationType::ALL {
+            if registry.get_definition(station_type).is_none() {
+                problems.push(format!("{station_type:?} has no definition"));
+            }
+        }
 
-        registry.register_default_stations();
-        registry
+        if problems.is_empty() {
+            Ok(registry)
+        } else {
+            Err(GameError::invalid_input(format!(
+                "{source} has {} problem(s) in its station definitions:\n  - {}",
+                problems.len(),
+                problems.join("\n  - ")
+            )))
+        }
+    }
+
+    /// The built-in definitions, or the ones in the file at `path`
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load(path: Option<&std::path::Path>) -> GameResult<Self> {
+        let Some(path) = path else {
+            return Ok(Self::new());
+        };
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| GameError::invalid_input(format!("can't read {}: {e}", path.display())))?;
+        Self::from_toml(&text, &path.display().to_string())
     }
 
     /// Register a new station type
@@ -190,7 +315,8 @@ impl StationRegistry {
             floor,
             position,
             operated_by: None,
-            last_used: 0.0,
+            // Never used, so ready the moment the match starts
+            last_used: f32::NEG_INFINITY,
             health: 100, // Default health
             max_health: 100,
             upgrade_level: 1,
@@ -221,13 +347,14 @@ impl StationRegistry {
             .get(button_index as usize)
             .ok_or_else(|| GameError::invalid_input("Button definition not found".to_string()))?;
 
-        // Check cooldown
-        if station.last_used + button.cooldown_seconds > context.current_time {
+        // Check cooldown, which upgraded stations get through quicker
+        let cooldown = station.scaled_cooldown(button.cooldown_seconds);
+        if station.last_used + cooldown > context.current_time {
             return Ok(StationActionResult {
                 success: false,
                 message: format!(
                     "Button on cooldown for {:.1} seconds",
-                    (station.last_used + button.cooldown_seconds) - context.current_time
+                    (station.last_used + cooldown) - context.current_time
                 ),
                 effects: Vec::new(),
                 cooldown_applied: 0.0,
@@ -253,6 +380,7 @@ impl StationRegistry {
         let mut effects = Vec::new();
         let mut success = true;
         let mut message = "Action executed successfully".to_string();
+        let bonus_levels = station.bonus_levels();
 
         match &button.action {
             StationAction::FireWeapon {
@@ -262,7 +390,11 @@ impl StationRegistry {
                 speed,
             } => {
                 if let Some(target_id) = context.nearest_enemy {
-                    let actual_damage = damage + (station.upgrade_level as u32 - 1) * 10; // Damage scales with upgrade
+                    let actual_damage = if *weapon_type == WeaponType::Emp {
+                        0
+                    } else {
+                        damage + bonus_levels * STATION_DAMAGE_PER_LEVEL // Damage scales with upgrade
+                    };
 
                     match speed {
                         None => {
@@ -270,16 +402,19 @@ impl StationRegistry {
                             effects.push(StationEffect::Damage {
                                 target_id,
                                 amount: actual_damage,
+                                weapon_type: *weapon_type,
                             });
                         }
-                        Some(_projectile_speed) => {
+                        Some(projectile_speed) => {
                             // Projectile weapon
                             let projectile_id = new_uuid();
+                            let (dx, dy) = context.direction_to_target.unwrap_or((0.0, 0.0));
                             effects.push(StationEffect::ProjectileCreated {
                                 projectile_id,
                                 position: context.station_world_pos,
-                                velocity: context.direction_to_target.unwrap_or((0.0, 0.0)),
+                                velocity: (dx * projectile_speed, dy * projectile_speed),
                                 damage: actual_damage,
+                                weapon_type: *weapon_type,
                             });
                         }
                     }
@@ -293,7 +428,7 @@ impl StationRegistry {
 
             StationAction::BoostShield { amount } => {
                 if let Some(mech_id) = context.mech_id {
-                    let boost_amount = amount + (station.upgrade_level as u32 - 1) * 5;
+                    let boost_amount = amount + bonus_levels * STATION_BOOST_PER_LEVEL;
                     effects.push(StationEffect::ShieldBoost {
                         target_id: mech_id,
                         amount: boost_amount,
@@ -309,6 +444,8 @@ impl StationRegistry {
                         .get(&ResourceType::ScrapMetal)
                         .unwrap_or(&0);
                     if *scrap_available > 0 {
+                        let hp_per_resource =
+                            hp_per_resource + bonus_levels * STATION_BOOST_PER_LEVEL;
                         let repair_amount = hp_per_resource * *scrap_available;
                         effects.push(StationEffect::Heal {
                             target_id: mech_id,
@@ -349,13 +486,54 @@ impl StationRegistry {
                 }
             }
 
+            StationAction::UpgradeStation => {
+                let offer = context.upgrade_target.and_then(|target| {
+                    let cost = self
+                        .get_definition(target.station_type)?
+                        .upgrade_cost(target.level)?;
+                    Some((target, cost))
+                });
+                match offer {
+                    Some((target, cost)) => {
+                        let short = cost.iter().find(|(resource_type, amount)| {
+                            context.available_resources.get(resource_type).unwrap_or(&0) < amount
+                        });
+                        if let Some((resource_type, amount)) = short {
+                            success = false;
+                            message = format!("Need {amount} {resource_type:?} for the upgrade");
+                        } else {
+                            effects.push(StationEffect::StationUpgraded {
+                                station_id: target.station_id,
+                                new_level: target.level + 1,
+                            });
+                            effects.extend(cost.into_iter().map(|(resource_type, amount)| {
+                                StationEffect::ResourceConsumed {
+                                    resource_type,
+                                    amount,
+                                }
+                            }));
+                            message = format!(
+                                "Upgraded {:?} to level {}",
+                                target.station_type,
+                                target.level + 1
+                            );
+                        }
+                    }
+                    None => {
+                        success = false;
+                        message = "No station picked that can be upgraded further".to_string();
+                    }
+                }
+            }
+
             StationAction::ChargeEnergy { energy_per_tick } => {
                 if let Some(mech_id) = context.mech_id {
+                    let amount = energy_per_tick + bonus_levels * STATION_BOOST_PER_LEVEL;
                     effects.push(StationEffect::EnergyCharge {
                         target_id: mech_id,
-                        amount: *energy_per_tick,
+                        amount,
                     });
-                    message = format!("Charging energy: +{energy_per_tick}");
+                    message = format!("Charging energy: +{amount}");
                 }
             }
 
@@ -393,262 +571,9 @@ impl StationRegistry {
             success,
             message,
             effects,
-            cooldown_applied: button.cooldown_seconds,
+            cooldown_applied: cooldown,
         })
     }
-
-    /// Register default station types
-    fn register_default_stations(&mut self) {
-        // Laser weapon station
-        self.register_station(StationDefinition {
-            station_type: StationType::WeaponLaser,
-            name: "Laser Cannon".to_string(),
-            description: "High-precision energy weapon with instant damage".to_string(),
-            button_count: 1,
-            button_definitions: vec![ButtonDefinition {
-                index: 0,
-                label: "Fire".to_string(),
-                description: "Fire laser at nearest enemy".to_string(),
-                action: StationAction::FireWeapon {
-                    weapon_type: WeaponType::Laser,
-                    damage: 25,
-                    range: 50.0,
-                    speed: None, // Instant
-                },
-                cooldown_seconds: 2.0,
-                resource_cost: HashMap::new(),
-            }],
-            cooldown_seconds: 2.0,
-            resource_requirements: HashMap::new(),
-            upgrade_requirements: HashMap::from([
-                (ResourceType::ComputerComponents, 2),
-                (ResourceType::Wiring, 1),
-            ]),
-            allowed_floors: vec![1],
-            max_per_mech: 2,
-            size: (1, 1),
-        });
-
-        // Projectile weapon station
-        self.register_station(StationDefinition {
-            station_type: StationType::WeaponProjectile,
-            name: "Projectile Cannon".to_string(),
-            description: "Ballistic weapon that fires explosive projectiles".to_string(),
-            button_count: 1,
-            button_definitions: vec![ButtonDefinition {
-                index: 0,
-                label: "Fire".to_string(),
-                description: "Fire projectile at nearest enemy".to_string(),
-                action: StationAction::FireWeapon {
-                    weapon_type: WeaponType::Projectile,
-                    damage: 35,
-                    range: 60.0,
-                    speed: Some(300.0),
-                },
-                cooldown_seconds: 3.0,
-                resource_cost: HashMap::new(),
-            }],
-            cooldown_seconds: 3.0,
-            resource_requirements: HashMap::new(),
-            upgrade_requirements: HashMap::from([(ResourceType::ScrapMetal, 3)]),
-            allowed_floors: vec![1],
-            max_per_mech: 2,
-            size: (1, 1),
-        });
-
-        // Shield station
-        self.register_station(StationDefinition {
-            station_type: StationType::Shield,
-            name: "Shield Generator".to_string(),
-            description: "Defensive system that boosts mech shields".to_string(),
-            button_count: 1,
-            button_definitions: vec![ButtonDefinition {
-                index: 0,
-                label: "Boost".to_string(),
-                description: "Boost mech shield strength".to_string(),
-                action: StationAction::BoostShield { amount: 25 },
-                cooldown_seconds: 5.0,
-                resource_cost: HashMap::from([(ResourceType::Batteries, 1)]),
-            }],
-            cooldown_seconds: 5.0,
-            resource_requirements: HashMap::new(),
-            upgrade_requirements: HashMap::from([
-                (ResourceType::Batteries, 2),
-                (ResourceType::Wiring, 1),
-            ]),
-            allowed_floors: vec![1],
-            max_per_mech: 1,
-            size: (1, 1),
-        });
-
-        // Engine station
-        self.register_station(StationDefinition {
-            station_type: StationType::Engine,
-            name: "Engine Control".to_string(),
-            description: "Controls mech movement and propulsion".to_string(),
-            button_count: 0, // Uses WASD controls, not buttons
-            button_definitions: vec![],
-            cooldown_seconds: 0.0,
-            resource_requirements: HashMap::new(),
-            upgrade_requirements: HashMap::from([
-                (ResourceType::ComputerComponents, 2),
-                (ResourceType::Wiring, 2),
-            ]),
-            allowed_floors: vec![0],
-            max_per_mech: 1,
-            size: (1, 1),
-        });
-
-        // Repair station
-        self.register_station(StationDefinition {
-            station_type: StationType::Repair,
-            name: "Repair Bay".to_string(),
-            description: "Restores mech hull integrity using scrap metal".to_string(),
-            button_count: 1,
-            button_definitions: vec![ButtonDefinition {
-                index: 0,
-                label: "Repair".to_string(),
-                description: "Repair mech hull damage".to_string(),
-                action: StationAction::RepairMech {
-                    hp_per_resource: 20,
-                },
-                cooldown_seconds: 1.0,
-                resource_cost: HashMap::new(), // Uses available scrap metal
-            }],
-            cooldown_seconds: 1.0,
-            resource_requirements: HashMap::new(),
-            upgrade_requirements: HashMap::from([(ResourceType::ScrapMetal, 2)]),
-            allowed_floors: vec![2],
-            max_per_mech: 1,
-            size: (1, 1),
-        });
-
-        // Upgrade station
-        self.register_station(StationDefinition {
-            station_type: StationType::Upgrade,
-            name: "Upgrade Terminal".to_string(),
-            description: "Enhances mech systems and capabilities".to_string(),
-            button_count: 4,
-            button_definitions: vec![
-                ButtonDefinition {
-                    index: 0,
-                    label: "Upgrade Laser".to_string(),
-                    description: "Improve laser weapon damage and efficiency".to_string(),
-                    action: StationAction::UpgradeMech {
-                        upgrade_type: MechUpgradeType::Laser,
-                    },
-                    cooldown_seconds: 1.0,
-                    resource_cost: HashMap::from([
-                        (ResourceType::ScrapMetal, 2),
-                        (ResourceType::ComputerComponents, 1),
-                    ]),
-                },
-                ButtonDefinition {
-                    index: 1,
-                    label: "Upgrade Projectile".to_string(),
-                    description: "Improve projectile weapon damage and speed".to_string(),
-                    action: StationAction::UpgradeMech {
-                        upgrade_type: MechUpgradeType::Projectile,
-                    },
-                    cooldown_seconds: 1.0,
-                    resource_cost: HashMap::from([(ResourceType::ScrapMetal, 3)]),
-                },
-                ButtonDefinition {
-                    index: 2,
-                    label: "Upgrade Shield".to_string(),
-                    description: "Improve shield capacity and recharge rate".to_string(),
-                    action: StationAction::UpgradeMech {
-                        upgrade_type: MechUpgradeType::Shield,
-                    },
-                    cooldown_seconds: 1.0,
-                    resource_cost: HashMap::from([
-                        (ResourceType::Batteries, 2),
-                        (ResourceType::Wiring, 1),
-                    ]),
-                },
-                ButtonDefinition {
-                    index: 3,
-                    label: "Upgrade Engine".to_string(),
-                    description: "Improve mech speed and maneuverability".to_string(),
-                    action: StationAction::UpgradeMech {
-                        upgrade_type: MechUpgradeType::Engine,
-                    },
-                    cooldown_seconds: 1.0,
-                    resource_cost: HashMap::from([
-                        (ResourceType::ComputerComponents, 2),
-                        (ResourceType::Wiring, 2),
-                    ]),
-                },
-            ],
-            cooldown_seconds: 1.0,
-            resource_requirements: HashMap::new(),
-            upgrade_requirements: HashMap::new(),
-            allowed_floors: vec![0],
-            max_per_mech: 1,
-            size: (1, 1),
-        });
-
-        // Electrical station
-        self.register_station(StationDefinition {
-            station_type: StationType::Electrical,
-            name: "Power Management".to_string(),
-            description: "Manages mech power systems and energy distribution".to_string(),
-            button_count: 2,
-            button_definitions: vec![
-                ButtonDefinition {
-                    index: 0,
-                    label: "Recharge".to_string(),
-                    description: "Recharge energy reserves".to_string(),
-                    action: StationAction::ChargeEnergy {
-                        energy_per_tick: 10,
-                    },
-                    cooldown_seconds: 0.5,
-                    resource_cost: HashMap::from([(ResourceType::Batteries, 1)]),
-                },
-                ButtonDefinition {
-                    index: 1,
-                    label: "Boost Systems".to_string(),
-                    description: "Temporarily boost all systems".to_string(),
-                    action: StationAction::TriggerEffect {
-                        effect: "EnergyBoost".to_string(),
-                        duration: 10.0,
-                    },
-                    cooldown_seconds: 30.0,
-                    resource_cost: HashMap::from([
-                        (ResourceType::Batteries, 2),
-                        (ResourceType::Wiring, 1),
-                    ]),
-                },
-            ],
-            cooldown_seconds: 0.5,
-            resource_requirements: HashMap::new(),
-            upgrade_requirements: HashMap::from([
-                (ResourceType::Wiring, 2),
-                (ResourceType::Batteries, 1),
-            ]),
-            allowed_floors: vec![1, 2],
-            max_per_mech: 2,
-            size: (1, 1),
-        });
-
-        // Pilot station
-        self.register_station(StationDefinition {
-            station_type: StationType::Pilot,
-            name: "Pilot Control".to_string(),
-            description: "Command center for controlling mech movement and systems".to_string(),
-            button_count: 0, // Uses WASD controls and special UI
-            button_definitions: vec![],
-            cooldown_seconds: 0.0,
-            resource_requirements: HashMap::new(),
-            upgrade_requirements: HashMap::from([
-                (ResourceType::ComputerComponents, 3),
-                (ResourceType::Wiring, 2),
-            ]),
-            allowed_floors: vec![2], // Top floor only
-            max_per_mech: 1,
-            size: (1, 1),
-        });
-    }
 }
 
 /// Context information needed for station actions
@@ -659,8 +584,153 @@ pub struct StationActionContext {
     pub station_world_pos: crate::WorldPos,
     pub available_resources: HashMap<ResourceType, u32>,
     pub nearest_enemy: Option<Uuid>,
+    /// Unit vector from the station towards `nearest_enemy`
     pub direction_to_target: Option<(f32, f32)>,
     pub current_upgrade_levels: HashMap<MechUpgradeType, u8>,
+    /// Station picked at the Upgrade terminal, if any
+    pub upgrade_target: Option<UpgradeTarget>,
+}
+
+/// A station the Upgrade terminal is set to upgrade next
+#[derive(Debug, Clone, Copy)]
+pub struct UpgradeTarget {
+    pub station_id: Uuid,
+    pub station_type: StationType,
+    pub level: u8,
+}
+
+impl StationDefinition {
+    /// Everything wrong with this definition that the file format can't
+    /// rule out by itself
+    fn problems(&self) -> Vec<String> {
+        let station = format!("{:?}", self.station_type);
+        let mut problems = Vec::new();
+        let mut problem = |message: String| problems.push(format!("{station}: {message}"));
+
+        if self.name.trim().is_empty() || self.short_name.trim().is_empty() {
+            problem("name and short_name can't be empty".to_string());
+        }
+        if !valid_cooldown(self.cooldown_seconds) {
+            problem(format!("cooldown_seconds is {}", self.cooldown_seconds));
+        }
+        if self.allowed_floors.is_empty() {
+            problem("allowed_floors is empty".to_string());
+        }
+        for floor in self
+            .allowed_floors
+            .iter()
+            .filter(|f| **f as usize >= MECH_FLOORS)
+        {
+            problem(format!(
+                "floor {floor} doesn't exist, mechs have {MECH_FLOORS}"
+            ));
+        }
+        if self.max_per_mech == 0 {
+            problem("max_per_mech is 0".to_string());
+        }
+        if self.size.0 == 0 || self.size.1 == 0 {
+            problem(format!("size {:?} has no area", self.size));
+        }
+        if !self.mass.is_finite() || self.mass < 0.0 {
+            problem(format!("mass is {}", self.mass));
+        }
+        for (what, costs) in [
+            ("resource_requirements", &self.resource_requirements),
+            ("upgrade_requirements", &self.upgrade_requirements),
+        ] {
+            for (resource, _) in costs.iter().filter(|(_, amount)| **amount == 0) {
+                problem(format!("{what} lists 0 {resource:?}"));
+            }
+        }
+        if self.button_definitions.len() > MAX_STATION_BUTTONS {
+            problem(format!(
+                "has {} buttons, at most {MAX_STATION_BUTTONS} can be pressed",
+                self.button_definitions.len()
+            ));
+        }
+
+        for button in &self.button_definitions {
+            let mut problem = |message: String| {
+                problems.push(format!("{station} button '{}': {message}", button.label))
+            };
+            if button.label.trim().is_empty() {
+                problem("label can't be empty".to_string());
+            }
+            if !valid_cooldown(button.cooldown_seconds) {
+                problem(format!("cooldown_seconds is {}", button.cooldown_seconds));
+            }
+            for (resource, _) in button
+                .resource_cost
+                .iter()
+                .filter(|(_, amount)| **amount == 0)
+            {
+                problem(format!("cost lists 0 {resource:?}"));
+            }
+            match &button.action {
+                StationAction::FireWeapon { range, speed, .. } => {
+                    if !positive(*range) {
+                        problem(format!("range is {range}"));
+                    }
+                    if let Some(speed) = speed.filter(|speed| !positive(*speed)) {
+                        problem(format!("speed is {speed}"));
+                    }
+                }
+                StationAction::TriggerEffect { duration, .. } if !positive(*duration) => {
+                    problem(format!("duration is {duration}"));
+                }
+                StationAction::UpgradeMech { upgrade_type }
+                    if upgrade_type.upgrade_type().is_none() =>
+                {
+                    problem(format!("mechs have no {upgrade_type:?} upgrade"));
+                }
+                _ => {}
+            }
+        }
+        problems
+    }
+
+    /// Whether stations of this type have tiers to buy at all
+    pub fn upgradeable(&self) -> bool {
+        !self.upgrade_requirements.is_empty()
+    }
+
+    /// What taking a station of this type from `level` to the next tier
+    /// costs: its upgrade requirements once for every level it already has.
+    /// `None` when it can't be upgraded or is already at the top tier.
+    pub fn upgrade_cost(&self, level: u8) -> Option<HashMap<ResourceType, u32>> {
+        if !self.upgradeable() || level >= MAX_STATION_LEVEL {
+            return None;
+        }
+        let cost = self
+            .upgrade_requirements
+            .iter()
+            .map(|(resource_type, amount)| (*resource_type, amount * level.max(1) as u32))
+            .collect();
+        Some(with_top_tier_cost(cost, level + 1, MAX_STATION_LEVEL))
+    }
+
+    /// Button metadata sent to clients so they can render this station's controls
+    pub fn button_info(&self) -> Vec<StationButtonInfo> {
+        self.button_definitions
+            .iter()
+            .take(self.button_count as usize)
+            .map(|button| StationButtonInfo {
+                index: button.index,
+                label: button.label.clone(),
+                description: button.description.clone(),
+                cooldown_seconds: button.cooldown_seconds,
+                resource_cost: button.resource_cost.clone(),
+            })
+            .collect()
+    }
+}
+
+fn valid_cooldown(seconds: f32) -> bool {
+    seconds.is_finite() && seconds >= 0.0
+}
+
+fn positive(value: f32) -> bool {
+    value.is_finite() && value > 0.0
 }
 
 impl Default for StationRegistry {
@@ -685,6 +755,16 @@ impl StationInstance {
         let remaining = (self.last_used + cooldown_duration) - current_time;
         remaining.max(0.0)
     }
+
+    /// Tiers bought past the first, each strengthening what the station does
+    pub fn bonus_levels(&self) -> u32 {
+        self.upgrade_level.saturating_sub(1) as u32
+    }
+
+    /// A button's cooldown once this station's tier has shortened it
+    pub fn scaled_cooldown(&self, seconds: f32) -> f32 {
+        seconds * (1.0 - STATION_COOLDOWN_CUT_PER_LEVEL * self.bonus_levels() as f32).max(0.0)
+    }
 }
 
 #[cfg(test)]
@@ -699,6 +779,81 @@ mod tests {
         assert!(registry.get_definition(StationType::Engine).is_some());
     }
 
+    #[test]
+    fn test_button_info_matches_definitions() {
+        let registry = StationRegistry::new();
+
+        let upgrade = registry.get_definition(StationType::Upgrade).unwrap();
+        let buttons = upgrade.button_info();
+        assert_eq!(buttons.len(), 7);
+        assert_eq!(buttons[2].label, "Upgrade Shield");
+        assert_eq!(buttons[2].resource_cost.get(&ResourceType::Batteries), Some(&2));
+
+        let engine = registry.get_definition(StationType::Engine).unwrap();
+        assert!(engine.button_info().is_empty());
+    }
+
+    #[test]
+    fn test_built_in_definitions_match_balance() {
+        let registry = StationRegistry::new();
+        let first_button = |station_type| {
+            registry
+                .get_definition(station_type)
+                .unwrap()
+                .button_definitions[0]
+                .clone()
+        };
+
+        let turret = first_button(StationType::TurretControl);
+        assert_eq!(turret.cooldown_seconds, crate::TURRET_MANUAL_COOLDOWN);
+        assert!(matches!(
+            turret.action,
+            StationAction::FireWeapon { damage, range, speed: Some(speed), .. }
+                if damage == crate::TURRET_DAMAGE
+                    && range == crate::TURRET_RANGE
+                    && speed == crate::TURRET_PROJECTILE_SPEED
+        ));
+
+        let emp = first_button(StationType::WeaponEmp);
+        assert_eq!(emp.cooldown_seconds, crate::EMP_COOLDOWN);
+        assert!(matches!(
+            emp.action,
+            StationAction::FireWeapon { speed: Some(speed), .. } if speed == crate::EMP_PROJECTILE_SPEED
+        ));
+
+        let sweep = first_button(StationType::Radar);
+        assert_eq!(sweep.cooldown_seconds, crate::RADAR_SWEEP_COOLDOWN);
+        assert_eq!(
+            sweep.resource_cost.get(&ResourceType::Batteries),
+            Some(&crate::RADAR_SWEEP_BATTERY_COST)
+        );
+    }
+
+    #[test]
+    fn test_malformed_definitions_report_every_problem() {
+        let text = DEFAULT_STATION_DEFINITIONS
+            .replacen("allowed_floors = [1]", "allowed_floors = [7]", 1)
+            .replacen("cooldown_seconds = 3.0", "cooldown_seconds = -3.0", 1)
+            .replacen("type = \"Radar\"", "type = \"Shield\"", 1)
+            .replacen("upgrade_type = \"Laser\"", "upgrade_type = \"Armor\"", 1);
+        let message = StationRegistry::from_toml(&text, "test.toml")
+            .unwrap_err()
+            .to_string();
+        assert!(message.contains("test.toml has 5 problem(s)"), "{message}");
+        assert!(message.contains("WeaponLaser: floor 7 doesn't exist"));
+        assert!(message.contains("WeaponProjectile: cooldown_seconds is -3"));
+        assert!(message.contains("Shield is defined more than once"));
+        assert!(message.contains("Radar has no definition"));
+        assert!(message.contains("'Upgrade Laser': mechs have no Armor upgrade"));
+
+        // Typos are caught by the format itself
+        let text = DEFAULT_STATION_DEFINITIONS.replacen("max_per_mech", "max_per_mehc", 1);
+        let message = StationRegistry::from_toml(&text, "test.toml")
+            .unwrap_err()
+            .to_string();
+        assert!(message.contains("max_per_mehc"), "{message}");
+    }
+
     #[test]
     fn test_station_creation() {
         let registry = StationRegistry::new();
@@ -727,6 +882,7 @@ mod tests {
             nearest_enemy: None,
             direction_to_target: None,
             current_upgrade_levels: HashMap::new(),
+            upgrade_target: None,
         };
 
         let result = registry
@@ -735,4 +891,85 @@ mod tests {
         assert!(result.success);
         assert_eq!(result.effects.len(), 2); // ShieldBoost + ResourceConsumed
     }
+
+    #[test]
+    fn test_station_tiers_cost_more_and_hit_harder() {
+        let registry = StationRegistry::new();
+        let laser = registry.get_definition(StationType::WeaponLaser).unwrap();
+        let base = laser.upgrade_requirements.clone();
+        let second = laser.upgrade_cost(2).unwrap();
+        for (resource_type, amount) in &base {
+            assert_eq!(second[resource_type], amount * 2);
+        }
+        // Only the top tier takes rare cores
+        assert_eq!(second[&ResourceType::PowerCore], TOP_TIER_CORE_COST);
+        assert!(!laser
+            .upgrade_cost(1)
+            .unwrap()
+            .contains_key(&ResourceType::PowerCore));
+        assert_eq!(laser.upgrade_cost(MAX_STATION_LEVEL), None);
+        let terminal = registry.get_definition(StationType::Upgrade).unwrap();
+        assert_eq!(terminal.upgrade_cost(1), None);
+
+        let mut shield = registry
+            .create_station(StationType::Shield, 1, TilePos::new(5, 5))
+            .unwrap();
+        let mut context = StationActionContext {
+            current_time: 100.0,
+            mech_id: Some(new_uuid()),
+            station_world_pos: crate::WorldPos::new(160.0, 160.0),
+            available_resources: HashMap::from([(ResourceType::Batteries, 5)]),
+            nearest_enemy: None,
+            direction_to_target: None,
+            current_upgrade_levels: HashMap::new(),
+            upgrade_target: None,
+        };
+        let boost = |result: StationActionResult| {
+            result.effects.into_iter().find_map(|effect| match effect {
+                StationEffect::ShieldBoost { amount, .. } => Some(amount),
+                _ => None,
+            })
+        };
+        let base_result = registry
+            .execute_button_action(&mut shield, 0, &context)
+            .unwrap();
+        let base_cooldown = base_result.cooldown_applied;
+        let base_boost = boost(base_result).unwrap();
+
+        shield.upgrade_level = 3;
+        context.current_time = 200.0;
+        let result = registry
+            .execute_button_action(&mut shield, 0, &context)
+            .unwrap();
+        assert!(result.cooldown_applied < base_cooldown);
+        assert_eq!(
+            boost(result),
+            Some(base_boost + 2 * STATION_BOOST_PER_LEVEL)
+        );
+
+        // The terminal buys the picked station's next tier at the scaled price
+        let mut terminal_station = registry
+            .create_station(StationType::Upgrade, 0, TilePos::new(2, 2))
+            .unwrap();
+        let upgrade_button = terminal
+            .button_definitions
+            .iter()
+            .find(|button| matches!(button.action, StationAction::UpgradeStation))
+            .unwrap()
+            .index;
+        context.available_resources = second.clone();
+        context.upgrade_target = Some(UpgradeTarget {
+            station_id: shield.id,
+            station_type: StationType::WeaponLaser,
+            level: 2,
+        });
+        let result = registry
+            .execute_button_action(&mut terminal_station, upgrade_button, &context)
+            .unwrap();
+        assert!(result.success, "{}", result.message);
+        assert!(result
+            .effects
+            .iter()
+            .any(|effect| matches!(effect, StationEffect::StationUpgraded { new_level: 3, .. })));
+    }
 }
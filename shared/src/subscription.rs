@@ -0,0 +1,221 @@
+//! State channels: the groups of server traffic a client can ask for. A
+//! player's client wants all of them, but a lightweight client like a score
+//! overlay or the AI debug client only needs a few, and the server skips
+//! encoding and sending the rest.
+
+use crate::messages::ServerMessage;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum StateChannel {
+    /// Full state snapshots and their checksums
+    Snapshots,
+    /// What players do: moving, carrying, crewing stations, fighting hand
+    /// to hand, and what they can see
+    Players,
+    /// Mech movement, health, systems and interiors
+    Mechs,
+    /// Shots, hits, explosions and other short-lived effects
+    Combat,
+    /// The arena: tiles, resources on the ground, hazards and weather
+    World,
+    /// Chat and coaching marks drawn on the map
+    Chat,
+    /// The hangar, the match, kills and scores
+    Match,
+}
+
+impl StateChannel {
+    pub const ALL: &'static [StateChannel] = &[
+        StateChannel::Snapshots,
+        StateChannel::Players,
+        StateChannel::Mechs,
+        StateChannel::Combat,
+        StateChannel::World,
+        StateChannel::Chat,
+        StateChannel::Match,
+    ];
+}
+
+impl ServerMessage {
+    /// The channel a message goes out on, or None for messages about the
+    /// connection itself, which every client gets
+    pub fn channel(&self) -> Option<StateChannel> {
+        match self {
+            ServerMessage::JoinedGame { .. }
+            | ServerMessage::JoinRejected { .. }
+            | ServerMessage::PasswordRequired { .. }
+            | ServerMessage::VoiceChannel { .. }
+            | ServerMessage::SessionResume { .. }
+            | ServerMessage::SpectatingGame { .. }
+            | ServerMessage::Subscribed { .. }
+            | ServerMessage::ServerLoad { .. }
+            | ServerMessage::ServerShutdown { .. }
+            | ServerMessage::DevCommandResult { .. }
+            | ServerMessage::Error { .. } => None,
+
+            ServerMessage::GameState { .. } | ServerMessage::StateChecksum { .. } => {
+                Some(StateChannel::Snapshots)
+            }
+
+            ServerMessage::PlayerDisconnected { .. }
+            | ServerMessage::PlayerAfk { .. }
+            | ServerMessage::FloorTransitionComplete { .. }
+            | ServerMessage::FloorTransitionFailed { .. }
+            | ServerMessage::PlayerMoved { .. }
+            | ServerMessage::PlayerFacing { .. }
+            | ServerMessage::PlayerPickedUpResource { .. }
+            | ServerMessage::PlayerDroppedResource { .. }
+            | ServerMessage::InventoryUpdated { .. }
+            | ServerMessage::ItemDropped { .. }
+            | ServerMessage::ItemPickedUp { .. }
+            | ServerMessage::PlayerEnteredStation { .. }
+            | ServerMessage::PlayerExitedStation { .. }
+            | ServerMessage::StationLayout { .. }
+            | ServerMessage::VisibilityUpdate { .. }
+            | ServerMessage::TeamVisionUpdate { .. }
+            | ServerMessage::StatusEffectsChanged { .. }
+            | ServerMessage::PlayerToolChanged { .. }
+            | ServerMessage::RepairProgress { .. }
+            | ServerMessage::PlayerAttacked { .. }
+            | ServerMessage::PlayerDashed { .. }
+            | ServerMessage::SabotageProgress { .. }
+            | ServerMessage::TutorialInstruction { .. }
+            | ServerMessage::TutorialComplete { .. } => Some(StateChannel::Players),
+
+            ServerMessage::MechFloorData { .. }
+            | ServerMessage::MechInteriorUpdate { .. }
+            | ServerMessage::MechMoved { .. }
+            | ServerMessage::MechDamaged { .. }
+            | ServerMessage::InteriorImpact { .. }
+            | ServerMessage::MechShieldChanged { .. }
+            | ServerMessage::MechFuelChanged { .. }
+            | ServerMessage::MechUpgraded { .. }
+            | ServerMessage::MechRepaired { .. }
+            | ServerMessage::TurretAimed { .. }
+            | ServerMessage::PointDefenseChanged { .. }
+            | ServerMessage::RadarSweep { .. }
+            | ServerMessage::ScannerPing { .. }
+            | ServerMessage::MechInventoryChanged { .. }
+            | ServerMessage::MechFiresUpdated { .. }
+            | ServerMessage::HullBreachUpdated { .. }
+            | ServerMessage::BarricadeUpdated { .. }
+            | ServerMessage::MechAlarm { .. } => Some(StateChannel::Mechs),
+
+            ServerMessage::ShieldImpact { .. }
+            | ServerMessage::MechsCollided { .. }
+            | ServerMessage::PlayerRunOver { .. }
+            | ServerMessage::WeaponFired { .. }
+            | ServerMessage::MechStompWindup { .. }
+            | ServerMessage::MechStomped { .. }
+            | ServerMessage::ProjectileHit { .. }
+            | ServerMessage::ProjectileExpired { .. }
+            | ServerMessage::ProjectileIntercepted { .. }
+            | ServerMessage::EffectCreated { .. }
+            | ServerMessage::EffectExpired { .. } => Some(StateChannel::Combat),
+
+            ServerMessage::CameraSuggestion { .. }
+            | ServerMessage::ResourceSpawned { .. }
+            | ServerMessage::ResourceCollected { .. }
+            | ServerMessage::ResourceDestroyed { .. }
+            | ServerMessage::TileUpdate { .. }
+            | ServerMessage::TileBatch { .. }
+            | ServerMessage::CollisionShapes { .. }
+            | ServerMessage::ArenaMapData { .. }
+            | ServerMessage::HazardsUpdated { .. }
+            | ServerMessage::DecalLayer { .. }
+            | ServerMessage::DecalAdded { .. }
+            | ServerMessage::PacingEventStarted { .. }
+            | ServerMessage::PacingZonesUpdated { .. }
+            | ServerMessage::WeatherChanged { .. } => Some(StateChannel::World),
+
+            ServerMessage::ChatMessage { .. } | ServerMessage::AnnotationDrawn { .. } => {
+                Some(StateChannel::Chat)
+            }
+
+            ServerMessage::ResourceDelivered { .. }
+            | ServerMessage::PlayerKilled { .. }
+            | ServerMessage::AchievementUnlocked { .. }
+            | ServerMessage::HangarUpdated { .. }
+            | ServerMessage::MatchStarted
+            | ServerMessage::GameModeUpdated { .. } => Some(StateChannel::Match),
+        }
+    }
+}
+
+/// The channels one connection is subscribed to; everything until it asks
+/// for less
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Subscription {
+    channels: Vec<StateChannel>,
+}
+
+impl Subscription {
+    pub fn all() -> Self {
+        Self::only(StateChannel::ALL)
+    }
+
+    /// Just the given channels, however many times each is listed
+    pub fn only(channels: &[StateChannel]) -> Self {
+        let mut channels = channels.to_vec();
+        channels.sort_unstable();
+        channels.dedup();
+        Self { channels }
+    }
+
+    pub fn channels(&self) -> &[StateChannel] {
+        &self.channels
+    }
+
+    /// Whether a message should go out to this connection
+    pub fn wants(&self, msg: &ServerMessage) -> bool {
+        msg.channel()
+            .is_none_or(|channel| self.channels.contains(&channel))
+    }
+}
+
+impl Default for Subscription {
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ErrorCode;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_subscription_filters_by_channel_but_never_the_connection() {
+        let overlay = Subscription::only(&[StateChannel::Match, StateChannel::Match]);
+        assert_eq!(overlay.channels(), &[StateChannel::Match]);
+
+        assert!(overlay.wants(&ServerMessage::MatchStarted));
+        assert!(!overlay.wants(&ServerMessage::ChatMessage {
+            player_id: Uuid::nil(),
+            player_name: "Pilot".to_string(),
+            message: "hello".to_string(),
+            team_only: false,
+        }));
+        assert!(!overlay.wants(&ServerMessage::ProjectileExpired {
+            projectile_id: Uuid::nil(),
+        }));
+        // Errors and the handshake's answers get through whatever was asked for
+        assert!(overlay.wants(&ServerMessage::Error {
+            code: ErrorCode::InvalidInput,
+            message: "no".to_string(),
+        }));
+        assert!(Subscription::only(&[]).wants(&ServerMessage::Subscribed {
+            channels: Vec::new(),
+        }));
+
+        let everything = Subscription::default();
+        assert!(everything.wants(&ServerMessage::ProjectileExpired {
+            projectile_id: Uuid::nil(),
+        }));
+        assert!(everything.wants(&ServerMessage::PlayerDisconnected {
+            player_id: Uuid::nil(),
+        }));
+    }
+}
@@ -1,6 +1,8 @@
-use crate::{Direction, StationType, TilePos, WorldPos};
+use crate::balance::BARRICADE_MAX_HEALTH;
+use crate::tile_math::TileNavigation;
+use crate::{Direction, StationType, TeamId, TilePos, ToolType, WorldPos};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 
 // =============================================================================
@@ -30,6 +32,7 @@ pub enum StaticTile {
     // Walls
     MetalWall,
     ReinforcedWall,
+    DamagedWall, // Hull breach, patched with a repair tool
 
     // Windows
     Window {
@@ -49,6 +52,9 @@ pub enum StaticTile {
     // Basic infrastructure
     PowerConduit,
     DataCable,
+    ToolLocker {
+        tool: ToolType,
+    },
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
@@ -88,6 +94,9 @@ pub struct TileMap {
 
     // Mech-relative tiles
     pub mech_tiles: HashMap<Uuid, MechTileMap>,
+
+    // World tiles changed since the last take_changed_tiles, for vision
+    changed_tiles: HashSet<TilePos>,
 }
 
 pub struct MechTileMap {
@@ -101,6 +110,25 @@ pub struct FloorMap {
     pub static_tiles: HashMap<TilePos, StaticTile>,
     pub entity_tiles: HashMap<TilePos, Uuid>,
     pub multi_tile_stations: HashMap<TilePos, Uuid>, // All tiles that belong to multi-tile stations
+    #[serde(default)]
+    pub barricades: HashMap<TilePos, Barricade>,
+}
+
+/// Scrap barricade a crew built on one of their mech's floor tiles. Keeps
+/// enemy boarders out and blocks everyone's sight until it's knocked down.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Barricade {
+    pub team: TeamId,
+    pub health: u32,
+}
+
+impl Barricade {
+    pub fn new(team: TeamId) -> Self {
+        Self {
+            team,
+            health: BARRICADE_MAX_HEALTH,
+        }
+    }
 }
 
 impl Default for FloorMap {
@@ -115,6 +143,7 @@ impl FloorMap {
             static_tiles: HashMap::new(),
             entity_tiles: HashMap::new(),
             multi_tile_stations: HashMap::new(),
+            barricades: HashMap::new(),
         }
     }
 
@@ -183,18 +212,35 @@ impl StaticTile {
     }
 
     pub fn blocks_vision(&self) -> bool {
-        matches!(self, StaticTile::Rock | StaticTile::MetalWall | StaticTile::ReinforcedWall)
+        matches!(
+            self,
+            StaticTile::Rock
+                | StaticTile::MetalWall
+                | StaticTile::ReinforcedWall
+                | StaticTile::DamagedWall
+        )
     }
 
     pub fn vision_attenuation(&self) -> f32 {
         match self {
             StaticTile::Window { .. } => 0.2,
             StaticTile::ReinforcedWindow { .. } => 0.3,
-            StaticTile::MetalWall | StaticTile::ReinforcedWall => 1.0,
+            StaticTile::MetalWall | StaticTile::ReinforcedWall | StaticTile::DamagedWall => 1.0,
             _ => 0.0,
         }
     }
 
+    /// Interior floor tiles that fires can start on or spread to
+    pub fn is_flammable(&self) -> bool {
+        matches!(
+            self,
+            StaticTile::MetalFloor
+                | StaticTile::CargoFloor { .. }
+                | StaticTile::PowerConduit
+                | StaticTile::DataCable
+        )
+    }
+
     pub fn on_enter(&self, actor: Uuid) -> Option<TileEvent> {
         match self {
             StaticTile::TransitionZone {
@@ -229,6 +275,9 @@ impl StaticTile {
             StaticTile::ReinforcedWall => TileVisual::Wall { 
                 material: Material::Reinforced 
             },
+            StaticTile::DamagedWall => TileVisual::Wall { 
+                material: Material::Damaged 
+            },
             StaticTile::CargoFloor { wear } => TileVisual::Floor { 
                 material: Material::Metal, 
                 wear: *wear 
@@ -254,6 +303,9 @@ impl StaticTile {
                 material: Material::Metal, 
                 wear: 0 
             }, // TODO: Add proper cable visual
+            StaticTile::ToolLocker { tool } => TileVisual::Locker { 
+                tool: *tool 
+            },
         }
     }
 }
@@ -320,6 +372,13 @@ pub enum TileEvent {
         target_floor: u8,
         stairway_pos: TilePos,
     },
+    /// One of a scripted tile's rules went off; `actor` is None for a timed
+    /// rule with nobody standing on the tile
+    ScriptTriggered {
+        entity: Uuid,
+        rule: usize,
+        actor: Option<Uuid>,
+    },
 }
 
 // =============================================================================
@@ -351,11 +410,17 @@ pub enum TileVisual {
     Station {
         station_type: StationType,
         active: bool,
+        /// Upgrade tier, drawn as a badge; 0 from servers that don't say
+        #[serde(default)]
+        level: u8,
     },
     Turret {
         facing: Direction,
         firing: bool,
     },
+    Locker {
+        tool: ToolType,
+    },
 
     // Effects
     TransitionFade {
@@ -387,10 +452,17 @@ impl TileMap {
             entity_tiles: HashMap::new(),
             spatial_index: SpatialIndex { _data: vec![] },
             mech_tiles: HashMap::new(),
+            changed_tiles: HashSet::new(),
         }
     }
 
+    /// World tiles set or removed since the last call
+    pub fn take_changed_tiles(&mut self) -> HashSet<TilePos> {
+        std::mem::take(&mut self.changed_tiles)
+    }
+
     pub fn set_world_tile(&mut self, pos: TilePos, content: TileContent) {
+        self.changed_tiles.insert(pos);
         match content {
             TileContent::Empty => {
                 self.static_tiles.remove(&pos);
@@ -471,15 +543,18 @@ impl TileMap {
 
     pub fn set_static_tile(&mut self, pos: TilePos, tile: StaticTile) {
         self.static_tiles.insert(pos, tile);
+        self.changed_tiles.insert(pos);
         // TODO: Update spatial index
     }
 
     pub fn set_entity_tile(&mut self, pos: TilePos, entity_id: Uuid) {
         self.entity_tiles.insert(pos, entity_id);
+        self.changed_tiles.insert(pos);
         // TODO: Update spatial index
     }
 
     pub fn remove_tile(&mut self, pos: TilePos) {
+        self.changed_tiles.insert(pos);
         self.static_tiles.remove(&pos);
         self.entity_tiles.remove(&pos);
         // TODO: Update spatial index
@@ -575,6 +650,29 @@ impl FloorMap {
             TileContent::Empty
         }
     }
+
+    /// Whether a barricade on `pos` keeps players of `team` out; crews walk
+    /// through their own
+    pub fn barricade_blocks(&self, pos: TilePos, team: TeamId) -> bool {
+        self.barricades
+            .get(&pos)
+            .is_some_and(|barricade| barricade.team != team)
+    }
+
+    /// Whether nothing between two tiles on this floor blocks the view:
+    /// walls and barricades do, the end tiles themselves don't count
+    pub fn has_line_of_sight(&self, from: TilePos, to: TilePos) -> bool {
+        TileNavigation::line_of_tiles(from, to)
+            .into_iter()
+            .filter(|tile| *tile != from && *tile != to)
+            .all(|tile| {
+                !self.barricades.contains_key(&tile)
+                    && !self
+                        .static_tiles
+                        .get(&tile)
+                        .is_some_and(|static_tile| static_tile.blocks_vision())
+            })
+    }
 }
 
 #[cfg(test)]
@@ -599,6 +697,25 @@ mod tests {
         assert_eq!(window.vision_attenuation(), 0.2);
     }
 
+    #[test]
+    fn test_barricades_block_enemies_and_sight() {
+        let mut floor = FloorMap::new();
+        for x in 0..5 {
+            floor.set_static_tile(TilePos::new(x, 0), StaticTile::MetalFloor);
+        }
+        let (left, right) = (TilePos::new(0, 0), TilePos::new(4, 0));
+        assert!(floor.has_line_of_sight(left, right));
+
+        floor
+            .barricades
+            .insert(TilePos::new(2, 0), Barricade::new(TeamId::Red));
+        assert!(!floor.has_line_of_sight(left, right));
+        // Standing right up against it still sees it
+        assert!(floor.has_line_of_sight(left, TilePos::new(2, 0)));
+        assert!(floor.barricade_blocks(TilePos::new(2, 0), TeamId::Blue));
+        assert!(!floor.barricade_blocks(TilePos::new(2, 0), TeamId::Red));
+    }
+
     #[test]
     fn test_tile_map_basic_operations() {
         let mut tile_map = TileMap::new();
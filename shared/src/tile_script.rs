@@ -0,0 +1,316 @@
+//! Scripted tiles for arena maps.
+//!
+//! A map can attach a short script to any world tile so it reacts to
+//! players without new code per tile. A script is one or more rules
+//! separated by `;`, each a trigger and the actions it runs:
+//!
+//! ```text
+//! enter: damage 10, effect sparks
+//! near 3: give wiring 2
+//! every 5: damage 2; enter: teleport 40 12
+//! ```
+//!
+//! Triggers are `enter` (a player steps onto the tile), `near <tiles>` (a
+//! player comes within range) and `every <seconds>` (repeatedly, on whoever
+//! is standing on the tile). Actions are `damage <amount>`,
+//! `teleport <x> <y>`, `effect <name> [seconds]` and
+//! `give <resource> [amount]`. Scripts are stored in the map file as text
+//! and parsed when it loads, so a typo fails the load with a reason.
+
+use crate::balance::{SCRIPT_EFFECT_DURATION, SCRIPT_MAX_RANGE, SCRIPT_MIN_INTERVAL};
+use crate::types::{ResourceType, TilePos};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Effects a script can spawn; the client knows how to draw each
+pub const SCRIPT_EFFECTS: [&str; 3] = ["sparks", "smoke", "glow"];
+
+/// Resource names as scripts spell them
+pub const RESOURCE_NAMES: [(&str, ResourceType); 4] = [
+    ("scrap_metal", ResourceType::ScrapMetal),
+    ("computer_components", ResourceType::ComputerComponents),
+    ("wiring", ResourceType::Wiring),
+    ("batteries", ResourceType::Batteries),
+];
+
+/// When a rule runs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScriptTrigger {
+    /// A player steps onto the tile
+    OnEnter,
+    /// A player comes within `range` tiles of it
+    OnProximity { range: f32 },
+    /// Every `interval` seconds, on whoever is standing on the tile
+    OnTick { interval: f32 },
+}
+
+/// What a rule does to the player who set it off
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScriptAction {
+    Damage(u32),
+    Teleport(TilePos),
+    /// One of `SCRIPT_EFFECTS` on the tile, for `duration` seconds
+    SpawnEffect {
+        name: String,
+        duration: f32,
+    },
+    GrantResource {
+        resource_type: ResourceType,
+        amount: u32,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScriptRule {
+    pub trigger: ScriptTrigger,
+    pub actions: Vec<ScriptAction>,
+}
+
+/// A tile's parsed script, kept as its text in map files
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct TileScript {
+    pub rules: Vec<ScriptRule>,
+}
+
+impl FromStr for TileScript {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, String> {
+        let rules = text
+            .split(';')
+            .map(str::trim)
+            .filter(|rule| !rule.is_empty())
+            .map(parse_rule)
+            .collect::<Result<Vec<_>, _>>()?;
+        if rules.is_empty() {
+            return Err("script has no rules".to_string());
+        }
+        Ok(Self { rules })
+    }
+}
+
+fn parse_rule(rule: &str) -> Result<ScriptRule, String> {
+    let (trigger, actions) = rule
+        .split_once(':')
+        .ok_or_else(|| format!("'{rule}' needs a ':' between the trigger and its actions"))?;
+    let actions = actions
+        .split(',')
+        .map(str::trim)
+        .filter(|action| !action.is_empty())
+        .map(parse_action)
+        .collect::<Result<Vec<_>, _>>()?;
+    if actions.is_empty() {
+        return Err(format!("'{rule}' has no actions"));
+    }
+    Ok(ScriptRule {
+        trigger: parse_trigger(trigger.trim())?,
+        actions,
+    })
+}
+
+fn parse_trigger(trigger: &str) -> Result<ScriptTrigger, String> {
+    let words: Vec<&str> = trigger.split_whitespace().collect();
+    match words.as_slice() {
+        ["enter"] => Ok(ScriptTrigger::OnEnter),
+        ["near", range] => {
+            let range = number(range)?;
+            if !(0.0..=SCRIPT_MAX_RANGE).contains(&range) {
+                return Err(format!(
+                    "'near' range must be 0 to {SCRIPT_MAX_RANGE} tiles"
+                ));
+            }
+            Ok(ScriptTrigger::OnProximity { range })
+        }
+        ["every", interval] => {
+            let interval = number(interval)?;
+            if interval < SCRIPT_MIN_INTERVAL {
+                return Err(format!(
+                    "'every' must be at least {SCRIPT_MIN_INTERVAL} seconds"
+                ));
+            }
+            Ok(ScriptTrigger::OnTick { interval })
+        }
+        _ => Err(format!(
+            "unknown trigger '{trigger}', expected 'enter', 'near <tiles>' or 'every <seconds>'"
+        )),
+    }
+}
+
+fn parse_action(action: &str) -> Result<ScriptAction, String> {
+    let words: Vec<&str> = action.split_whitespace().collect();
+    match words.as_slice() {
+        ["damage", amount] => Ok(ScriptAction::Damage(whole(amount)?)),
+        ["teleport", x, y] => Ok(ScriptAction::Teleport(TilePos::new(
+            coordinate(x)?,
+            coordinate(y)?,
+        ))),
+        ["effect", name, rest @ ..] if rest.len() <= 1 => {
+            if !SCRIPT_EFFECTS.contains(name) {
+                return Err(format!(
+                    "unknown effect '{name}', try one of: {}",
+                    SCRIPT_EFFECTS.join(", ")
+                ));
+            }
+            let duration = match rest {
+                [seconds] => number(seconds)?,
+                _ => SCRIPT_EFFECT_DURATION,
+            };
+            Ok(ScriptAction::SpawnEffect {
+                name: name.to_string(),
+                duration,
+            })
+        }
+        ["give", resource, rest @ ..] if rest.len() <= 1 => {
+            let Some((_, resource_type)) = RESOURCE_NAMES.iter().find(|(n, _)| n == resource)
+            else {
+                return Err(format!("unknown resource '{resource}'"));
+            };
+            let amount = match rest {
+                [amount] => whole(amount)?,
+                _ => 1,
+            };
+            Ok(ScriptAction::GrantResource {
+                resource_type: *resource_type,
+                amount,
+            })
+        }
+        _ => Err(format!(
+            "unknown action '{action}', expected damage, teleport, effect or give"
+        )),
+    }
+}
+
+fn number(word: &str) -> Result<f32, String> {
+    word.parse::<f32>()
+        .ok()
+        .filter(|n| n.is_finite())
+        .ok_or_else(|| format!("'{word}' is not a number"))
+}
+
+fn whole(word: &str) -> Result<u32, String> {
+    word.parse()
+        .map_err(|_| format!("'{word}' is not a whole number"))
+}
+
+fn coordinate(word: &str) -> Result<i32, String> {
+    word.parse()
+        .map_err(|_| format!("'{word}' is not a tile coordinate"))
+}
+
+impl fmt::Display for ScriptTrigger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScriptTrigger::OnEnter => write!(f, "enter"),
+            ScriptTrigger::OnProximity { range } => write!(f, "near {range}"),
+            ScriptTrigger::OnTick { interval } => write!(f, "every {interval}"),
+        }
+    }
+}
+
+impl fmt::Display for ScriptAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScriptAction::Damage(amount) => write!(f, "damage {amount}"),
+            ScriptAction::Teleport(pos) => write!(f, "teleport {} {}", pos.x, pos.y),
+            ScriptAction::SpawnEffect { name, duration } => write!(f, "effect {name} {duration}"),
+            ScriptAction::GrantResource {
+                resource_type,
+                amount,
+            } => {
+                let name = RESOURCE_NAMES
+                    .iter()
+                    .find(|(_, r)| r == resource_type)
+                    .map_or("scrap_metal", |(n, _)| n);
+                write!(f, "give {name} {amount}")
+            }
+        }
+    }
+}
+
+impl fmt::Display for TileScript {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, rule) in self.rules.iter().enumerate() {
+            if i > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{}:", rule.trigger)?;
+            for (j, action) in rule.actions.iter().enumerate() {
+                write!(f, "{}{action}", if j == 0 { " " } else { ", " })?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl TryFrom<String> for TileScript {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, String> {
+        text.parse()
+    }
+}
+
+impl From<TileScript> for String {
+    fn from(script: TileScript) -> Self {
+        script.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scripts_parse_and_print_back() {
+        let script: TileScript =
+            "enter: damage 10, effect sparks; near 3: give wiring 2; every 5: teleport 40 12"
+                .parse()
+                .unwrap();
+        assert_eq!(script.rules.len(), 3);
+        assert_eq!(script.rules[0].trigger, ScriptTrigger::OnEnter);
+        assert_eq!(
+            script.rules[0].actions,
+            vec![
+                ScriptAction::Damage(10),
+                ScriptAction::SpawnEffect {
+                    name: "sparks".to_string(),
+                    duration: SCRIPT_EFFECT_DURATION,
+                },
+            ]
+        );
+        assert_eq!(
+            script.rules[1].actions[0],
+            ScriptAction::GrantResource {
+                resource_type: ResourceType::Wiring,
+                amount: 2,
+            }
+        );
+        assert_eq!(
+            script.rules[2].trigger,
+            ScriptTrigger::OnTick { interval: 5.0 }
+        );
+
+        let reparsed: TileScript = script.to_string().parse().unwrap();
+        assert_eq!(reparsed, script);
+    }
+
+    #[test]
+    fn test_bad_scripts_say_what_is_wrong() {
+        for (script, complaint) in [
+            ("", "no rules"),
+            ("enter damage 10", "':'"),
+            ("jump: damage 10", "unknown trigger"),
+            ("enter: explode", "unknown action"),
+            ("enter: effect fireworks", "unknown effect"),
+            ("enter: give gold", "unknown resource"),
+            ("every 0: damage 1", "at least"),
+            ("near 99: damage 1", "range"),
+            ("enter: damage lots", "not a whole number"),
+        ] {
+            let error = script.parse::<TileScript>().unwrap_err();
+            assert!(error.contains(complaint), "'{script}' gave '{error}'");
+        }
+    }
+}
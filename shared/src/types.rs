@@ -9,6 +9,7 @@ pub type PlayerId = Uuid;
 pub type MechId = Uuid;
 pub type StationId = Uuid;
 pub type ResourceId = Uuid;
+pub type ItemId = Uuid;
 pub type ProjectileId = Uuid;
 pub type WeaponEffectId = Uuid;
 pub type EntityId = Uuid;
@@ -127,12 +128,101 @@ impl Direction {
     }
 }
 
+/// Which of eight ways a player faces, clockwise from east in screen space.
+/// Sent as a single number since it rides along with movement.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(into = "u8", try_from = "u8")]
+pub enum Facing {
+    East,
+    SouthEast,
+    #[default]
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+    North,
+    NorthEast,
+}
+
+impl Facing {
+    pub const ALL: [Facing; 8] = [
+        Facing::East,
+        Facing::SouthEast,
+        Facing::South,
+        Facing::SouthWest,
+        Facing::West,
+        Facing::NorthWest,
+        Facing::North,
+        Facing::NorthEast,
+    ];
+
+    /// The way a movement input points, if it points anywhere
+    pub fn from_movement(movement: (f32, f32)) -> Option<Self> {
+        let (x, y) = movement;
+        if x.abs() < f32::EPSILON && y.abs() < f32::EPSILON {
+            return None;
+        }
+        let octant = (y.atan2(x) / std::f32::consts::FRAC_PI_4).round() as i32;
+        Some(Self::ALL[octant.rem_euclid(8) as usize])
+    }
+
+    /// Angle in radians, with y pointing down the screen
+    pub fn angle(self) -> f32 {
+        self as u8 as f32 * std::f32::consts::FRAC_PI_4
+    }
+
+    /// The nearest of the four straight directions; diagonals lean sideways
+    pub fn to_four(self) -> Self {
+        match self {
+            Facing::NorthEast | Facing::SouthEast => Facing::East,
+            Facing::NorthWest | Facing::SouthWest => Facing::West,
+            straight => straight,
+        }
+    }
+}
+
+impl From<Facing> for u8 {
+    fn from(facing: Facing) -> Self {
+        facing as u8
+    }
+}
+
+impl TryFrom<u8> for Facing {
+    type Error = String;
+
+    fn try_from(value: u8) -> Result<Self, String> {
+        Self::ALL
+            .get(value as usize)
+            .copied()
+            .ok_or_else(|| format!("{value} is not a facing"))
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ResourceType {
     ScrapMetal,
     ComputerComponents,
     Wiring,
     Batteries,
+    /// Rare; the top tier of any upgrade takes some
+    PowerCore,
+}
+
+impl ResourceType {
+    pub const ALL: [ResourceType; 5] = [
+        ResourceType::ScrapMetal,
+        ResourceType::ComputerComponents,
+        ResourceType::Wiring,
+        ResourceType::Batteries,
+        ResourceType::PowerCore,
+    ];
+}
+
+/// Hand tools crew take from lockers inside a mech
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ToolType {
+    Extinguisher,
+    RepairTool,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -141,18 +231,41 @@ pub enum UpgradeType {
     Projectile,
     Shield,
     Engine,
+    /// Jams enemy radar sweeps
+    Ecm,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum StationType {
     WeaponLaser,
     WeaponProjectile,
+    WeaponEmp,
     Engine,
     Shield,
     Repair,
     Electrical,
     Upgrade,
     Pilot,
+    TurretControl,
+    Radar,
+    PointDefense,
+}
+
+impl StationType {
+    pub const ALL: [StationType; 12] = [
+        StationType::WeaponLaser,
+        StationType::WeaponProjectile,
+        StationType::WeaponEmp,
+        StationType::Engine,
+        StationType::Shield,
+        StationType::Repair,
+        StationType::Electrical,
+        StationType::Upgrade,
+        StationType::Pilot,
+        StationType::TurretControl,
+        StationType::Radar,
+        StationType::PointDefense,
+    ];
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
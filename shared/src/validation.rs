@@ -1,5 +1,8 @@
+use crate::annotations::{valid_annotation_ttl, ANNOTATION_MAX_TTL};
+use crate::balance::INVENTORY_SLOTS;
+use crate::constants::{ARENA_HEIGHT_TILES, ARENA_WIDTH_TILES};
 use crate::network_constants::*;
-use crate::{ClientMessage, TeamId, ValidationError, ValidationResult};
+use crate::{ClientMessage, DevCommand, StateChannel, TeamId, ValidationError, ValidationResult};
 
 /// Trait for validating messages
 pub trait Validate {
@@ -12,11 +15,28 @@ impl Validate for ClientMessage {
             ClientMessage::JoinGame {
                 player_name,
                 preferred_team,
+                features,
+                password,
+                ..
             } => {
                 validate_player_name(player_name)?;
                 if let Some(team) = preferred_team {
                     validate_team_id(team)?;
                 }
+                if features.len() > MAX_PROTOCOL_FEATURES {
+                    return Err(ValidationError::TooManyProtocolFeatures {
+                        count: features.len(),
+                        max: MAX_PROTOCOL_FEATURES,
+                    });
+                }
+                if let Some(password) = password {
+                    if password.len() > MAX_ROOM_PASSWORD_LENGTH {
+                        return Err(ValidationError::PasswordTooLong {
+                            length: password.len(),
+                            max: MAX_ROOM_PASSWORD_LENGTH,
+                        });
+                    }
+                }
                 Ok(())
             }
 
@@ -50,12 +70,79 @@ impl Validate for ClientMessage {
                 validate_chat_message(message)?;
                 Ok(())
             }
+
+            ClientMessage::Annotate { shape, ttl, .. } => {
+                if let Some(reason) = shape.problem() {
+                    return Err(ValidationError::InvalidAnnotation {
+                        reason: reason.to_string(),
+                    });
+                }
+                if !valid_annotation_ttl(*ttl) {
+                    return Err(ValidationError::InvalidAnnotation {
+                        reason: format!(
+                            "Time to live must be above 0 and at most {ANNOTATION_MAX_TTL} seconds"
+                        ),
+                    });
+                }
+                Ok(())
+            }
+
+            ClientMessage::DevCommand { command } => {
+                validate_dev_command(command)?;
+                Ok(())
+            }
+
+            ClientMessage::SelectInventorySlot { slot } => {
+                validate_inventory_slot(*slot)?;
+                Ok(())
+            }
+
+            ClientMessage::DropItem => Ok(()),
+
+            ClientMessage::TurretAim { angle } => {
+                validate_aim_angle(*angle)?;
+                Ok(())
+            }
+
+            ClientMessage::MeleeAttack => Ok(()),
+
+            ClientMessage::UseItem => Ok(()),
+            ClientMessage::Dash { .. } => Ok(()),
+
+            // Reach is checked against the player's position on the server
+            ClientMessage::PlaceBarricade { .. } | ClientMessage::RemoveBarricade { .. } => Ok(()),
+
+            ClientMessage::Look { .. } => Ok(()),
+
+            ClientMessage::Spectate { features, .. } => {
+                if features.len() > MAX_PROTOCOL_FEATURES {
+                    return Err(ValidationError::TooManyProtocolFeatures {
+                        count: features.len(),
+                        max: MAX_PROTOCOL_FEATURES,
+                    });
+                }
+                Ok(())
+            }
+
+            ClientMessage::VerboseState { .. } => Ok(()),
+
+            ClientMessage::PickRole { .. } | ClientMessage::SetReady { .. } => Ok(()),
+
+            ClientMessage::Subscribe { channels } => {
+                if channels.len() > StateChannel::ALL.len() {
+                    return Err(ValidationError::TooManyStateChannels {
+                        count: channels.len(),
+                        max: StateChannel::ALL.len(),
+                    });
+                }
+                Ok(())
+            }
         }
     }
 }
 
 /// Validate player name
-fn validate_player_name(name: &str) -> ValidationResult<()> {
+pub(crate) fn validate_player_name(name: &str) -> ValidationResult<()> {
     if name.is_empty() {
         return Err(ValidationError::InvalidPlayerName {
             reason: "Name cannot be empty".to_string(),
@@ -123,6 +210,25 @@ fn validate_button_index(index: u8) -> ValidationResult<()> {
     Ok(())
 }
 
+/// Validate an inventory slot selection
+fn validate_inventory_slot(slot: u8) -> ValidationResult<()> {
+    if slot as usize >= INVENTORY_SLOTS {
+        return Err(ValidationError::InvalidInventorySlot {
+            slot,
+            max: INVENTORY_SLOTS as u8 - 1,
+        });
+    }
+    Ok(())
+}
+
+/// Validate a turret aim angle, which should be at most one turn either way
+fn validate_aim_angle(angle: f32) -> ValidationResult<()> {
+    if !angle.is_finite() || angle.abs() > std::f32::consts::TAU {
+        return Err(ValidationError::InvalidAimAngle { angle });
+    }
+    Ok(())
+}
+
 /// Validate chat message
 fn validate_chat_message(message: &str) -> ValidationResult<()> {
     if message.len() > MAX_CHAT_MESSAGE_LENGTH {
@@ -134,6 +240,27 @@ fn validate_chat_message(message: &str) -> ValidationResult<()> {
     Ok(())
 }
 
+/// Validate a developer console command
+fn validate_dev_command(command: &DevCommand) -> ValidationResult<()> {
+    match command {
+        DevCommand::Teleport { x, y } => {
+            if !(0..ARENA_WIDTH_TILES).contains(x) || !(0..ARENA_HEIGHT_TILES).contains(y) {
+                return Err(ValidationError::InvalidDevCommand {
+                    reason: format!("({x}, {y}) is outside the arena"),
+                });
+            }
+        }
+        DevCommand::GiveResource { amount, .. } => {
+            if !(1..=MAX_DEV_RESOURCE_GRANT).contains(amount) {
+                return Err(ValidationError::InvalidDevCommand {
+                    reason: format!("amount must be 1-{MAX_DEV_RESOURCE_GRANT}"),
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
 /// Helper function to sanitize player names
 pub fn sanitize_player_name(name: &str) -> String {
     // Trim whitespace
@@ -191,6 +318,39 @@ mod tests {
         assert!(validate_button_index(MAX_STATION_BUTTONS).is_err());
     }
 
+    #[test]
+    fn test_validate_inventory_slot() {
+        assert!(validate_inventory_slot(0).is_ok());
+        assert!(validate_inventory_slot(INVENTORY_SLOTS as u8 - 1).is_ok());
+        assert!(validate_inventory_slot(INVENTORY_SLOTS as u8).is_err());
+    }
+
+    #[test]
+    fn test_validate_aim_angle() {
+        assert!(validate_aim_angle(0.0).is_ok());
+        assert!(validate_aim_angle(-3.0).is_ok());
+        assert!(validate_aim_angle(f32::NAN).is_err());
+        assert!(validate_aim_angle(100.0).is_err());
+    }
+
+    #[test]
+    fn test_validate_dev_command() {
+        assert!(validate_dev_command(&DevCommand::Teleport { x: 10, y: 10 }).is_ok());
+        assert!(validate_dev_command(&DevCommand::Teleport {
+            x: ARENA_WIDTH_TILES,
+            y: 0
+        })
+        .is_err());
+
+        let give = |amount| DevCommand::GiveResource {
+            resource_type: crate::ResourceType::Wiring,
+            amount,
+        };
+        assert!(validate_dev_command(&give(1)).is_ok());
+        assert!(validate_dev_command(&give(0)).is_err());
+        assert!(validate_dev_command(&give(MAX_DEV_RESOURCE_GRANT + 1)).is_err());
+    }
+
     #[test]
     fn test_sanitize_player_name() {
         assert_eq!(sanitize_player_name("  Valid Name  "), "Valid Name");
@@ -1,5 +1,9 @@
+use crate::balance::{PLAYER_VISION_CONE_WIDTH, PLAYER_VISION_REAR_RANGE};
+use crate::errors::{GameError, GameResult};
 use crate::{components::*, tile_entity::*, Direction, TilePos, WorldPos};
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
 
 // =============================================================================
 // Vision System
@@ -80,15 +84,48 @@ impl VisionSystem {
             return self.visibility_cache.get(&viewer_id).unwrap();
         }
 
+        let visibility_data = Self::cast(viewer_pos, max_range, tile_map, component_storage);
+        self.visibility_cache.insert(viewer_id, visibility_data);
+        self.visibility_cache.get(&viewer_id).unwrap()
+    }
+
+    /// Raycast what can be seen from a position, without touching the cache
+    pub fn cast<S: ComponentStorage>(
+        viewer_pos: WorldPos,
+        max_range: f32,
+        tile_map: &TileMap,
+        component_storage: &S,
+    ) -> VisibilityData {
+        Self::cast_facing(
+            viewer_pos,
+            0.0,
+            SightShape::Radial,
+            max_range,
+            tile_map,
+            component_storage,
+        )
+    }
+
+    /// Raycast what a viewer facing `facing` degrees can see, each ray
+    /// reaching as far as `shape` allows in its direction
+    pub fn cast_facing<S: ComponentStorage>(
+        viewer_pos: WorldPos,
+        facing: f32,
+        shape: SightShape,
+        max_range: f32,
+        tile_map: &TileMap,
+        component_storage: &S,
+    ) -> VisibilityData {
         let mut visible = HashSet::new();
         let mut light_levels = HashMap::new();
 
         // Cast rays in all directions
         for angle in 0..360 {
             let mut ray = Ray::new(viewer_pos, angle as f32);
+            let range = max_range * shape.reach(angle as f32, facing);
             let mut attenuation = 0.0;
 
-            while ray.length < max_range && attenuation < 1.0 {
+            while ray.length < range && attenuation < 1.0 {
                 let check_pos = ray.current_pos();
                 let tile_pos = check_pos.to_tile();
 
@@ -123,15 +160,11 @@ impl VisionSystem {
             }
         }
 
-        // Cache and return
-        let visibility_data = VisibilityData {
+        VisibilityData {
             visible_tiles: visible,
             light_levels,
             last_update_pos: viewer_pos,
-        };
-
-        self.visibility_cache.insert(viewer_id, visibility_data);
-        self.visibility_cache.get(&viewer_id).unwrap()
+        }
     }
 
     pub fn get_visibility(&self, viewer_id: uuid::Uuid) -> Option<&VisibilityData> {
@@ -147,6 +180,77 @@ impl VisionSystem {
     }
 }
 
+// =============================================================================
+// Sight Shape
+// =============================================================================
+
+/// How far a viewer sees in each direction
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum SightShape {
+    /// The same range all the way round
+    Radial,
+    /// Full range within `width` degrees of the way the viewer faces and
+    /// `rear_range` of it everywhere else, so flanks and backs are blind
+    Cone { width: f32, rear_range: f32 },
+}
+
+impl Default for SightShape {
+    fn default() -> Self {
+        SightShape::Cone {
+            width: PLAYER_VISION_CONE_WIDTH,
+            rear_range: PLAYER_VISION_REAR_RANGE,
+        }
+    }
+}
+
+impl SightShape {
+    /// Share of full range seen looking `angle` degrees for a viewer facing
+    /// `facing` degrees
+    pub fn reach(&self, angle: f32, facing: f32) -> f32 {
+        match *self {
+            SightShape::Radial => 1.0,
+            SightShape::Cone { width, rear_range } => {
+                if off_axis(angle, facing).abs() <= width / 2.0 {
+                    1.0
+                } else {
+                    rear_range
+                }
+            }
+        }
+    }
+
+    pub fn is_directional(&self) -> bool {
+        matches!(self, SightShape::Cone { .. })
+    }
+}
+
+impl FromStr for SightShape {
+    type Err = GameError;
+
+    /// `radial`, `cone`, or `cone:<width degrees>:<rear range share>`
+    fn from_str(spec: &str) -> GameResult<Self> {
+        let invalid = || {
+            GameError::invalid_input(format!(
+                "bad sight '{spec}', expected radial, cone or cone:<width>:<rear range>"
+            ))
+        };
+        let mut parts = spec.split(':');
+        match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some("radial"), None, None, None) => Ok(SightShape::Radial),
+            (Some("cone"), None, None, None) => Ok(SightShape::default()),
+            (Some("cone"), Some(width), Some(rear_range), None) => {
+                let width: f32 = width.parse().map_err(|_| invalid())?;
+                let rear_range: f32 = rear_range.parse().map_err(|_| invalid())?;
+                if !(0.0..=360.0).contains(&width) || !(0.0..=1.0).contains(&rear_range) {
+                    return Err(invalid());
+                }
+                Ok(SightShape::Cone { width, rear_range })
+            }
+            _ => Err(invalid()),
+        }
+    }
+}
+
 // =============================================================================
 // Enhanced Vision for Windows
 // =============================================================================
@@ -266,6 +370,16 @@ pub struct VisionCone {
     pub range: f32,     // How far the cone extends
 }
 
+impl VisionCone {
+    pub fn contains(&self, point: WorldPos) -> bool {
+        let (dx, dy) = (point.x - self.origin.x, point.y - self.origin.y);
+        if dx * dx + dy * dy > self.range * self.range {
+            return false;
+        }
+        off_axis(dy.atan2(dx).to_degrees(), self.direction).abs() <= self.width / 2.0
+    }
+}
+
 // =============================================================================
 // Helper Functions
 // =============================================================================
@@ -275,6 +389,11 @@ fn angle_to_direction(angle: f32) -> (f32, f32) {
     (radians.cos(), radians.sin())
 }
 
+/// Degrees from `direction` to `angle`, between -180 and 180
+fn off_axis(angle: f32, direction: f32) -> f32 {
+    (angle - direction + 180.0).rem_euclid(360.0) - 180.0
+}
+
 fn direction_to_angle(direction: Direction) -> f32 {
     match direction {
         Direction::Up => 270.0,
@@ -377,4 +496,16 @@ mod tests {
         assert!(dx.abs() < 0.001);
         assert!((dy - 1.0).abs() < 0.001);
     }
+
+    #[test]
+    fn test_cone_sight_is_short_behind() {
+        let cone: SightShape = "cone:90:0.25".parse().unwrap();
+        assert_eq!(cone.reach(30.0, 350.0), 1.0);
+        assert_eq!(cone.reach(180.0, 0.0), 0.25);
+        assert_eq!(SightShape::Radial.reach(180.0, 0.0), 1.0);
+        assert_eq!("cone".parse::<SightShape>().unwrap(), SightShape::default());
+        assert_eq!("radial".parse::<SightShape>().unwrap(), SightShape::Radial);
+        assert!("cone:90".parse::<SightShape>().is_err());
+        assert!("cone:90:2".parse::<SightShape>().is_err());
+    }
 }
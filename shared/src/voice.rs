@@ -0,0 +1,108 @@
+//! Voice frames, carried on their own binary websocket beside the game one so
+//! audio never waits behind state updates. A client sends
+//! `[sequence u16 LE][opus payload]`; the server passes it on to the
+//! speaker's teammates as `[speaker uuid][sequence u16 LE][opus payload]`.
+
+use crate::network_constants::VOICE_MAX_FRAME_BYTES;
+use crate::{NetworkError, NetworkResult};
+use uuid::Uuid;
+
+const SEQUENCE_BYTES: usize = 2;
+const SPEAKER_BYTES: usize = 16;
+
+/// One frame of someone's voice, as a listener receives it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VoiceFrame {
+    pub speaker: Uuid,
+    pub sequence: u16,
+    pub payload: Vec<u8>,
+}
+
+/// A frame from the microphone, ready for the voice socket
+pub fn encode_voice_frame(sequence: u16, payload: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(SEQUENCE_BYTES + payload.len());
+    bytes.extend_from_slice(&sequence.to_le_bytes());
+    bytes.extend_from_slice(payload);
+    bytes
+}
+
+/// The sequence and payload of a frame a speaker sent
+pub fn decode_voice_frame(bytes: &[u8]) -> NetworkResult<(u16, &[u8])> {
+    let (sequence, payload) = split_sequence(bytes)?;
+    Ok((sequence, payload))
+}
+
+/// The frame as relayed to a listener, naming who spoke
+pub fn encode_relayed_voice_frame(speaker: Uuid, sequence: u16, payload: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(SPEAKER_BYTES + SEQUENCE_BYTES + payload.len());
+    bytes.extend_from_slice(speaker.as_bytes());
+    bytes.extend_from_slice(&sequence.to_le_bytes());
+    bytes.extend_from_slice(payload);
+    bytes
+}
+
+pub fn decode_relayed_voice_frame(bytes: &[u8]) -> NetworkResult<VoiceFrame> {
+    if bytes.len() < SPEAKER_BYTES {
+        return Err(NetworkError::InvalidMessage(
+            "voice frame is missing its speaker".to_string(),
+        ));
+    }
+    let (speaker, rest) = bytes.split_at(SPEAKER_BYTES);
+    let speaker = Uuid::from_slice(speaker)
+        .map_err(|e| NetworkError::InvalidMessage(format!("voice frame speaker: {e}")))?;
+    let (sequence, payload) = split_sequence(rest)?;
+    Ok(VoiceFrame {
+        speaker,
+        sequence,
+        payload: payload.to_vec(),
+    })
+}
+
+fn split_sequence(bytes: &[u8]) -> NetworkResult<(u16, &[u8])> {
+    if bytes.len() <= SEQUENCE_BYTES {
+        return Err(NetworkError::InvalidMessage(
+            "voice frame has no audio".to_string(),
+        ));
+    }
+    let (sequence, payload) = bytes.split_at(SEQUENCE_BYTES);
+    if payload.len() > VOICE_MAX_FRAME_BYTES {
+        return Err(NetworkError::InvalidMessage(format!(
+            "voice frame of {} bytes is over the {VOICE_MAX_FRAME_BYTES} byte limit",
+            payload.len()
+        )));
+    }
+    Ok((u16::from_le_bytes([sequence[0], sequence[1]]), payload))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frames_round_trip_through_the_relay_format() {
+        let speaker = Uuid::from_u128(7);
+        let sent = encode_voice_frame(513, &[1, 2, 3]);
+        let (sequence, payload) = decode_voice_frame(&sent).unwrap();
+        assert_eq!((sequence, payload), (513, &[1u8, 2, 3][..]));
+
+        let relayed = encode_relayed_voice_frame(speaker, sequence, payload);
+        assert_eq!(
+            decode_relayed_voice_frame(&relayed).unwrap(),
+            VoiceFrame {
+                speaker,
+                sequence: 513,
+                payload: vec![1, 2, 3],
+            }
+        );
+    }
+
+    #[test]
+    fn test_empty_and_oversized_frames_are_refused() {
+        assert!(decode_voice_frame(&[0, 0]).is_err());
+        assert!(decode_voice_frame(&encode_voice_frame(0, &[0; VOICE_MAX_FRAME_BYTES])).is_ok());
+        assert!(
+            decode_voice_frame(&encode_voice_frame(0, &[0; VOICE_MAX_FRAME_BYTES + 1])).is_err()
+        );
+        assert!(decode_relayed_voice_frame(&[0; 10]).is_err());
+    }
+}